@@ -1,4 +1,5 @@
 use std::{
+    net::IpAddr,
     ops::Bound,
     sync::Arc,
     time::Duration,
@@ -13,6 +14,7 @@ use common::{
         ExportPath,
         PublicFunctionPath,
     },
+    execution_context::RequestContext,
     http::ResolvedHostname,
     runtime::Runtime,
     types::{
@@ -21,6 +23,7 @@ use common::{
         FunctionCaller,
         RepeatableTimestamp,
     },
+    version::ClientVersion,
     RequestId,
 };
 use database::{
@@ -33,6 +36,7 @@ use database::{
 use file_storage::{
     FileRangeStream,
     FileStream,
+    StoredFile,
 };
 use futures::{
     future::BoxFuture,
@@ -43,9 +47,17 @@ use headers::{
     ContentLength,
     ContentType,
 };
-use keybroker::Identity;
+use keybroker::{
+    Identity,
+    ServiceToken,
+    SignedFileUrl,
+};
 use model::{
-    file_storage::FileStorageId,
+    file_storage::{
+        types::FileEncryptionMetadata,
+        FileStorageId,
+    },
+    ip_access_policy::types::IpAccessSurface,
     session_requests::types::SessionRequestIdentifier,
 };
 use serde_json::Value as JsonValue;
@@ -64,6 +76,7 @@ use value::{
 };
 
 use crate::{
+    builtin_auth::BuiltinAuthTokens,
     Application,
     FunctionError,
     FunctionReturn,
@@ -83,6 +96,9 @@ pub enum ExecuteQueryTimestamp {
     Latest,
     // Execute the query at a given timestamp.
     At(Timestamp),
+    // Execute the query at the latest timestamp, which must be no earlier
+    // than the given one. Used to give HTTP clients causal consistency.
+    AtLeast(Timestamp),
 }
 
 // A trait that abstracts the backend API. It all state and validation logic
@@ -98,6 +114,116 @@ pub trait ApplicationApi: Send + Sync {
         auth_token: AuthenticationToken,
     ) -> anyhow::Result<Identity>;
 
+    /// Like `authenticate`, but also counts invalid admin keys against the
+    /// client's address, so repeated guesses from it get locked out.
+    async fn authenticate_from(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        auth_token: AuthenticationToken,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<Identity>;
+
+    /// Issue an ID token for the anonymous identity bound to `device_id`.
+    async fn issue_anonymous_identity_token(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        device_id: String,
+    ) -> anyhow::Result<String>;
+
+    /// Create a built-in auth user with an email and password. Succeeds
+    /// whether or not a user with the email already exists.
+    async fn builtin_auth_sign_up(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        email: String,
+        password: String,
+    ) -> anyhow::Result<()>;
+
+    /// Sign a built-in auth user in with their email and password. Failed
+    /// attempts are counted against the email and the client's address.
+    async fn builtin_auth_sign_in(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        email: String,
+        password: String,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<BuiltinAuthTokens>;
+
+    /// Send a built-in auth magic link to `email`. Links sent are counted
+    /// against the email and the client's address.
+    async fn builtin_auth_send_magic_link(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        email: String,
+        client_version: ClientVersion,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<()>;
+
+    /// Sign a built-in auth user in with the token from a magic link.
+    async fn builtin_auth_verify_magic_link(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        token: String,
+    ) -> anyhow::Result<BuiltinAuthTokens>;
+
+    /// Exchange a built-in auth refresh token for a fresh ID token.
+    async fn builtin_auth_refresh(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        refresh_token: String,
+    ) -> anyhow::Result<BuiltinAuthTokens>;
+
+    /// End the built-in auth session with `refresh_token`.
+    async fn builtin_auth_sign_out(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        refresh_token: String,
+    ) -> anyhow::Result<()>;
+
+    /// Start registering a passkey for a built-in auth user.
+    async fn builtin_auth_passkey_registration_options(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        identity: Identity,
+    ) -> anyhow::Result<JsonValue>;
+
+    /// Finish registering a passkey for a built-in auth user.
+    async fn builtin_auth_register_passkey(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        identity: Identity,
+        client_data_json: String,
+        attestation_object: String,
+    ) -> anyhow::Result<String>;
+
+    /// Start signing in to built-in auth with a passkey.
+    async fn builtin_auth_passkey_sign_in_options(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+    ) -> anyhow::Result<JsonValue>;
+
+    /// Sign a built-in auth user in with a passkey.
+    async fn builtin_auth_passkey_sign_in(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        credential_id: String,
+        client_data_json: String,
+        authenticator_data: String,
+        signature: String,
+    ) -> anyhow::Result<BuiltinAuthTokens>;
+
     /// Execute a public query on the root app. This method is used by the sync
     /// worker and HTTP API for the majority of traffic as the main entry point
     /// for queries.
@@ -139,6 +265,7 @@ pub trait ApplicationApi: Send + Sync {
         caller: FunctionCaller,
         // Identifier used to make this mutation idempotent.
         mutation_identifier: Option<SessionRequestIdentifier>,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>>;
 
     /// Execute an admin mutation for a particular component for the dashboard.
@@ -151,6 +278,7 @@ pub trait ApplicationApi: Send + Sync {
         args: Vec<JsonValue>,
         caller: FunctionCaller,
         mutation_identifier: Option<SessionRequestIdentifier>,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>>;
 
     /// Execute a public action on the root app.
@@ -162,6 +290,7 @@ pub trait ApplicationApi: Send + Sync {
         path: ExportPath,
         args: Vec<JsonValue>,
         caller: FunctionCaller,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedActionReturn, RedactedActionError>>;
 
     /// Execute an admin action for a particular component for the dashboard.
@@ -173,6 +302,7 @@ pub trait ApplicationApi: Send + Sync {
         path: CanonicalizedComponentFunctionPath,
         args: Vec<JsonValue>,
         caller: FunctionCaller,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedActionReturn, RedactedActionError>>;
 
     /// Execute an HTTP action on the root app.
@@ -213,6 +343,31 @@ pub trait ApplicationApi: Send + Sync {
         validity: Duration,
     ) -> anyhow::Result<ComponentId>;
 
+    async fn check_service_token(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        token: &str,
+    ) -> anyhow::Result<ServiceToken>;
+
+    async fn check_signed_file_url(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        token: &str,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<SignedFileUrl>;
+
+    /// Fails if the deployment's IP access policy doesn't allow requests to
+    /// `surface` from `client_ip`.
+    async fn check_ip_access(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        surface: IpAccessSurface,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<()>;
+
     async fn store_file(
         &self,
         host: &ResolvedHostname,
@@ -222,9 +377,19 @@ pub trait ApplicationApi: Send + Sync {
         content_length: Option<ContentLength>,
         content_type: Option<ContentType>,
         expected_sha256: Option<Sha256Digest>,
+        encryption: Option<FileEncryptionMetadata>,
         body: BoxStream<'_, anyhow::Result<Bytes>>,
     ) -> anyhow::Result<DeveloperDocumentId>;
 
+    /// Looks up a file's metadata without fetching its contents.
+    async fn get_stored_file(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        component: ComponentId,
+        file_storage_id: FileStorageId,
+    ) -> anyhow::Result<StoredFile>;
+
     async fn get_file_range(
         &self,
         host: &ResolvedHostname,
@@ -244,6 +409,13 @@ pub trait ApplicationApi: Send + Sync {
         file_storage_id: FileStorageId,
     ) -> anyhow::Result<FileStream>;
 
+    async fn get_transformed_image(
+        &self,
+        host: &ResolvedHostname,
+        request_id: RequestId,
+        token: &str,
+    ) -> anyhow::Result<FileStream>;
+
     // Returns a fallible subscription client. The implementation is not required to
     // recover from transient errors with the underlying connection or stream. The
     // client is responsible to Drop the client and create a new one on any system
@@ -270,6 +442,135 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         self.authenticate(auth_token, validate_time).await
     }
 
+    async fn authenticate_from(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        auth_token: AuthenticationToken,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<Identity> {
+        let validate_time = self.runtime().system_time();
+        self.authenticate_from(auth_token, validate_time, client_ip)
+            .await
+    }
+
+    async fn issue_anonymous_identity_token(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        device_id: String,
+    ) -> anyhow::Result<String> {
+        self.issue_anonymous_identity_token(device_id).await
+    }
+
+    async fn builtin_auth_sign_up(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        email: String,
+        password: String,
+    ) -> anyhow::Result<()> {
+        self.builtin_auth_sign_up(email, password).await
+    }
+
+    async fn builtin_auth_sign_in(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        email: String,
+        password: String,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<BuiltinAuthTokens> {
+        self.builtin_auth_sign_in(email, password, client_ip).await
+    }
+
+    async fn builtin_auth_send_magic_link(
+        &self,
+        _host: &ResolvedHostname,
+        request_id: RequestId,
+        email: String,
+        client_version: ClientVersion,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<()> {
+        self.builtin_auth_send_magic_link(request_id, email, client_version, client_ip)
+            .await
+    }
+
+    async fn builtin_auth_verify_magic_link(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        token: String,
+    ) -> anyhow::Result<BuiltinAuthTokens> {
+        self.builtin_auth_verify_magic_link(token).await
+    }
+
+    async fn builtin_auth_refresh(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        refresh_token: String,
+    ) -> anyhow::Result<BuiltinAuthTokens> {
+        self.builtin_auth_refresh(refresh_token).await
+    }
+
+    async fn builtin_auth_sign_out(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        refresh_token: String,
+    ) -> anyhow::Result<()> {
+        self.builtin_auth_sign_out(refresh_token).await
+    }
+
+    async fn builtin_auth_passkey_registration_options(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        identity: Identity,
+    ) -> anyhow::Result<JsonValue> {
+        self.builtin_auth_passkey_registration_options(identity)
+            .await
+    }
+
+    async fn builtin_auth_register_passkey(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        identity: Identity,
+        client_data_json: String,
+        attestation_object: String,
+    ) -> anyhow::Result<String> {
+        self.builtin_auth_register_passkey(identity, client_data_json, attestation_object)
+            .await
+    }
+
+    async fn builtin_auth_passkey_sign_in_options(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+    ) -> anyhow::Result<JsonValue> {
+        self.builtin_auth_passkey_sign_in_options().await
+    }
+
+    async fn builtin_auth_passkey_sign_in(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        credential_id: String,
+        client_data_json: String,
+        authenticator_data: String,
+        signature: String,
+    ) -> anyhow::Result<BuiltinAuthTokens> {
+        self.builtin_auth_passkey_sign_in(
+            credential_id,
+            client_data_json,
+            authenticator_data,
+            signature,
+        )
+        .await
+    }
+
     async fn execute_public_query(
         &self,
         _host: &ResolvedHostname,
@@ -288,6 +589,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         let ts = match ts {
             ExecuteQueryTimestamp::Latest => *self.now_ts_for_reads(),
             ExecuteQueryTimestamp::At(ts) => ts,
+            ExecuteQueryTimestamp::AtLeast(min_ts) => *self.now_ts_for_reads_at_least(min_ts)?,
         };
         self.read_only_udf_at_ts(
             request_id,
@@ -319,6 +621,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         let ts = match ts {
             ExecuteQueryTimestamp::Latest => *self.now_ts_for_reads(),
             ExecuteQueryTimestamp::At(ts) => ts,
+            ExecuteQueryTimestamp::AtLeast(min_ts) => *self.now_ts_for_reads_at_least(min_ts)?,
         };
         self.read_only_udf_at_ts(
             request_id,
@@ -342,6 +645,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         caller: FunctionCaller,
         // Identifier used to make this mutation idempotent.
         mutation_identifier: Option<SessionRequestIdentifier>,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
         anyhow::ensure!(
             caller.allowed_visibility() == AllowedVisibility::PublicOnly,
@@ -354,6 +658,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
             identity,
             mutation_identifier,
             caller,
+            request_context,
         )
         .await
     }
@@ -367,6 +672,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         args: Vec<JsonValue>,
         caller: FunctionCaller,
         mutation_identifier: Option<SessionRequestIdentifier>,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
         anyhow::ensure!(
             path.component.is_root() || identity.is_admin() || identity.is_system(),
@@ -379,6 +685,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
             identity,
             mutation_identifier,
             caller,
+            request_context,
         )
         .await
     }
@@ -391,6 +698,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         path: ExportPath,
         args: Vec<JsonValue>,
         caller: FunctionCaller,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedActionReturn, RedactedActionError>> {
         anyhow::ensure!(
             caller.allowed_visibility() == AllowedVisibility::PublicOnly,
@@ -402,6 +710,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
             args,
             identity,
             caller,
+            request_context,
         )
         .await
     }
@@ -414,6 +723,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         path: CanonicalizedComponentFunctionPath,
         args: Vec<JsonValue>,
         caller: FunctionCaller,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedActionReturn, RedactedActionError>> {
         anyhow::ensure!(
             path.component.is_root() || identity.is_admin() || identity.is_system(),
@@ -425,6 +735,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
             args,
             identity,
             caller,
+            request_context,
         )
         .await
     }
@@ -483,6 +794,36 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
             .check_store_file_authorization(&self.runtime, token, validity)
     }
 
+    async fn check_service_token(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        token: &str,
+    ) -> anyhow::Result<ServiceToken> {
+        self.key_broker()
+            .check_service_token(token, self.runtime.unix_timestamp())
+    }
+
+    async fn check_signed_file_url(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        token: &str,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<SignedFileUrl> {
+        self.check_signed_file_url(token, client_ip).await
+    }
+
+    async fn check_ip_access(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        surface: IpAccessSurface,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<()> {
+        self.check_ip_access(surface, client_ip).await
+    }
+
     async fn store_file(
         &self,
         _host: &ResolvedHostname,
@@ -492,6 +833,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         content_length: Option<ContentLength>,
         content_type: Option<ContentType>,
         expected_sha256: Option<Sha256Digest>,
+        encryption: Option<FileEncryptionMetadata>,
         body: BoxStream<'_, anyhow::Result<Bytes>>,
     ) -> anyhow::Result<DeveloperDocumentId> {
         self.store_file(
@@ -499,11 +841,22 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
             content_length,
             content_type,
             expected_sha256,
+            encryption,
             body,
         )
         .await
     }
 
+    async fn get_stored_file(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        component: ComponentId,
+        file_storage_id: FileStorageId,
+    ) -> anyhow::Result<StoredFile> {
+        self.get_stored_file(component, file_storage_id).await
+    }
+
     async fn get_file_range(
         &self,
         _host: &ResolvedHostname,
@@ -527,6 +880,15 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         self.get_file(component, file_storage_id).await
     }
 
+    async fn get_transformed_image(
+        &self,
+        _host: &ResolvedHostname,
+        _request_id: RequestId,
+        token: &str,
+    ) -> anyhow::Result<FileStream> {
+        self.get_transformed_image(token).await
+    }
+
     async fn subscription_client(
         &self,
         _host: &ResolvedHostname,
@@ -1,13 +1,19 @@
 use anyhow::Context;
+use bytes::BytesMut;
 use common::{
     components::{
         CanonicalizedComponentFunctionPath,
+        ComponentId,
         ComponentPath,
         Reference,
     },
     errors::JsError,
     execution_context::ExecutionContext,
-    http::RoutedHttpPath,
+    http::{
+        request_context_from_headers,
+        RoutedHttpPath,
+    },
+    knobs::FUNCTION_MAX_ARGS_SIZE,
     log_lines::{
         run_function_and_collect_log_lines,
         LogLevel,
@@ -30,16 +36,45 @@ use database::{
 use errors::ErrorMetadataAnyhowExt;
 use function_runner::server::HttpActionMetadata;
 use futures::{
-    stream::FusedStream,
+    stream::{
+        self,
+        FusedStream,
+    },
     FutureExt,
     StreamExt,
+    TryStreamExt,
+};
+use http::{
+    header::{
+        ACCEPT,
+        CACHE_CONTROL,
+        CONTENT_LENGTH,
+        CONTENT_TYPE,
+        ETAG,
+        IF_NONE_MATCH,
+    },
+    HeaderMap,
+    Method,
+    StatusCode,
 };
-use http::StatusCode;
 use keybroker::Identity;
-use model::modules::{
-    ModuleModel,
-    HTTP_MODULE_PATH,
+use model::{
+    modules::{
+        function_validators::{
+            HttpBodyValidationError,
+            HttpBodyValidator,
+        },
+        module_versions::AnalyzedHttpRoute,
+        ModuleModel,
+        HTTP_MODULE_PATH,
+    },
+    static_assets::{
+        types::StaticAsset,
+        StaticAssetsModel,
+    },
+    virtual_system_mapping,
 };
+use serde_json::json;
 use sync_types::{
     CanonicalizedUdfPath,
     FunctionName,
@@ -51,6 +86,7 @@ use udf::{
     HttpActionOutcome,
     HttpActionRequest,
     HttpActionRequestHead,
+    HttpActionResponseHead,
     HttpActionResponsePart,
     HttpActionResponseStreamer,
     HttpActionResult,
@@ -65,7 +101,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
     pub async fn run_http_action(
         &self,
         request_id: RequestId,
-        http_request: HttpActionRequest,
+        mut http_request: HttpActionRequest,
         mut response_streamer: HttpActionResponseStreamer,
         identity: Identity,
         caller: FunctionCaller,
@@ -78,21 +114,53 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             .begin_with_usage(identity.clone(), usage_tracker.clone())
             .await?;
 
-        let (component_path, routed_path) =
-            match self.route_http_action(&mut tx, &http_request.head).await? {
-                Some(r) => r,
-                None => {
-                    drop(tx);
-                    let response_parts = udf::HttpActionResponsePart::from_text(
-                        StatusCode::NOT_FOUND,
-                        "This Convex deployment does not have HTTP actions enabled.".to_string(),
-                    );
-                    for part in response_parts {
-                        response_streamer.send_part(part)?;
-                    }
-                    return Ok(udf::HttpActionResult::Streamed);
-                },
-            };
+        // Static assets take precedence over HTTP action routes.
+        let is_get_or_head = matches!(http_request.head.method, Method::GET | Method::HEAD);
+        if is_get_or_head {
+            let request_path = urlencoding::decode(http_request.head.url.path())?;
+            if let Some(asset) = StaticAssetsModel::new(&mut tx)
+                .resolve(&request_path)
+                .await?
+            {
+                drop(tx);
+                self.serve_static_asset(&http_request.head, asset, &mut response_streamer)
+                    .await?;
+                return Ok(udf::HttpActionResult::Streamed);
+            }
+        }
+        let route = self.route_http_action(&mut tx, &http_request.head).await?;
+        // Navigation requests that no route handles get the SPA fallback so
+        // the app can route them on the client.
+        if is_get_or_head
+            && route.as_ref().map_or(true, |route| route.is_fallthrough)
+            && accepts_html(&http_request.head.headers)
+        {
+            if let Some(asset) = StaticAssetsModel::new(&mut tx).spa_fallback().await? {
+                drop(tx);
+                self.serve_static_asset(&http_request.head, asset, &mut response_streamer)
+                    .await?;
+                return Ok(udf::HttpActionResult::Streamed);
+            }
+        }
+        let MatchedHttpRoute {
+            component_path,
+            routed_path,
+            body_validator,
+            ..
+        } = match route {
+            Some(r) => r,
+            None => {
+                drop(tx);
+                let response_parts = udf::HttpActionResponsePart::from_text(
+                    StatusCode::NOT_FOUND,
+                    "This Convex deployment does not have HTTP actions enabled.".to_string(),
+                );
+                for part in response_parts {
+                    response_streamer.send_part(part)?;
+                }
+                return Ok(udf::HttpActionResult::Streamed);
+            },
+        };
         let path = CanonicalizedComponentFunctionPath {
             component: component_path,
             udf_path: CanonicalizedUdfPath::new(
@@ -104,8 +172,52 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             Ok(validated_path) => validated_path,
             Err(e) => return Ok(udf::HttpActionResult::Error(e)),
         };
+        if let Err(e) = validate_request_body(
+            &mut tx,
+            validated_path.path().component,
+            &body_validator,
+            &mut http_request,
+        )
+        .await?
+        {
+            drop(tx);
+            let status = match e {
+                HttpBodyValidationError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            let body = json!({
+                "code": e.code(),
+                "message": e.message(),
+                "path": e.path(),
+            });
+            for part in udf::HttpActionResponsePart::from_json(status, body) {
+                response_streamer.send_part(part)?;
+            }
+            return Ok(udf::HttpActionResult::Streamed);
+        }
+        let cache_key = self
+            .http_action_cache
+            .key(validated_path.path().component, &http_request.head);
+        if let Some(key) = &cache_key {
+            if let Some(parts) = self
+                .http_action_cache
+                .get(key, self.runtime.monotonic_now())
+            {
+                drop(tx);
+                for part in parts {
+                    response_streamer.send_part(part)?;
+                }
+                return Ok(udf::HttpActionResult::Streamed);
+            }
+        }
+        let mut captured_response = cache_key.map(|key| self.http_action_cache.capture(key));
         let unix_timestamp = self.runtime.unix_timestamp();
-        let context = ExecutionContext::new(request_id, &caller);
+        let request_context = request_context_from_headers(&http_request.head.headers)?;
+        let context =
+            ExecutionContext::new(request_id, &caller).with_request_context(request_context);
+
+        let execution_id = context.execution_id.clone();
+        let _pending_reads = self.surrogate_keys.start_http_action(&execution_id);
 
         let request_head = http_request.head.clone();
         let route = http_request.head.route_for_failure();
@@ -151,7 +263,14 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
 
         let (outcome_result, mut log_lines): (anyhow::Result<HttpActionOutcome>, LogLines) = loop {
             tokio::select! {
-                Some(result) = response_stream.next(), if !response_stream.is_terminated() => {
+                Some(mut result) = response_stream.next(), if !response_stream.is_terminated() => {
+                    if let HttpActionResponsePart::Head(h) = &mut result {
+                        self.surrogate_keys
+                            .tag_http_action_response(&execution_id, &mut h.headers)?;
+                    }
+                    if let Some(captured_response) = &mut captured_response {
+                        captured_response.record(&result);
+                    }
                     match result {
                         HttpActionResponsePart::Head(h) => {
                             result_for_logging = Some(Ok(HttpActionStatusCode(h.status)));
@@ -168,7 +287,14 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             }
         };
 
-        while let Some(part) = response_stream.next().await {
+        while let Some(mut part) = response_stream.next().await {
+            if let HttpActionResponsePart::Head(h) = &mut part {
+                self.surrogate_keys
+                    .tag_http_action_response(&execution_id, &mut h.headers)?;
+            }
+            if let Some(captured_response) = &mut captured_response {
+                captured_response.record(&part);
+            }
             match part {
                 HttpActionResponsePart::Head(h) => {
                     result_for_logging = Some(Ok(HttpActionStatusCode(h.status)));
@@ -181,6 +307,12 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         }
 
         let response_sha256 = response_streamer.complete();
+        if let (Some(captured_response), Ok(outcome)) = (captured_response, &outcome_result) {
+            if matches!(outcome.result, HttpActionResult::Streamed) {
+                self.http_action_cache
+                    .insert(captured_response, self.runtime.monotonic_now());
+            }
+        }
 
         match outcome_result {
             Ok(outcome) => {
@@ -279,11 +411,56 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         }
     }
 
+    /// Streams a static asset, or just its headers for `HEAD` requests and
+    /// requests whose `If-None-Match` already has the current version.
+    async fn serve_static_asset(
+        &self,
+        head: &HttpActionRequestHead,
+        asset: StaticAsset,
+        response_streamer: &mut HttpActionResponseStreamer,
+    ) -> anyhow::Result<()> {
+        let etag = asset.etag();
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, etag.parse()?);
+        headers.insert(CACHE_CONTROL, asset.cache_control.parse()?);
+        let not_modified = head
+            .headers
+            .get(IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                value.split(',').any(|tag| {
+                    let tag = tag.trim();
+                    tag == "*" || tag.trim_start_matches("W/") == etag
+                })
+            });
+        if not_modified {
+            response_streamer.send_part(HttpActionResponsePart::Head(HttpActionResponseHead {
+                status: StatusCode::NOT_MODIFIED,
+                headers,
+            }))?;
+            return Ok(());
+        }
+        headers.insert(CONTENT_TYPE, asset.content_type.parse()?);
+        headers.insert(CONTENT_LENGTH, asset.size.into());
+        response_streamer.send_part(HttpActionResponsePart::Head(HttpActionResponseHead {
+            status: StatusCode::OK,
+            headers,
+        }))?;
+        if head.method == Method::HEAD {
+            return Ok(());
+        }
+        let mut stream = self.file_storage.get_static_asset(&asset).await?.stream;
+        while let Some(chunk) = stream.try_next().await? {
+            response_streamer.send_part(HttpActionResponsePart::BodyChunk(chunk))?;
+        }
+        Ok(())
+    }
+
     async fn route_http_action(
         &self,
         tx: &mut Transaction<RT>,
         head: &HttpActionRequestHead,
-    ) -> anyhow::Result<Option<(ComponentPath, RoutedHttpPath)>> {
+    ) -> anyhow::Result<Option<MatchedHttpRoute>> {
         let mut model = BootstrapComponentsModel::new(tx);
         let mut current_component_path = ComponentPath::root();
         let mut routed_path = RoutedHttpPath(head.url.path().to_string());
@@ -311,22 +488,28 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             // First, try matching an exact path from `http.js`, which will always
             // be the most specific match.
             if let Some(ref http_routes) = http_routes {
-                if http_routes.route_exact(&routed_path[..], method) {
-                    return Ok(Some((current_component_path, routed_path)));
+                if let Some(route) = http_routes.route_exact(&routed_path[..], method) {
+                    return Ok(Some(MatchedHttpRoute {
+                        component_path: current_component_path,
+                        routed_path,
+                        body_validator: route.body()?,
+                        is_fallthrough: false,
+                    }));
                 }
             }
 
             // Next, try finding the most specific prefix match from both `http.js`
             // and the component-level mounts.
             enum CurrentMatch<'a> {
-                CurrentHttpJs,
+                CurrentHttpJs(&'a AnalyzedHttpRoute),
                 MountedComponent(&'a Reference),
             }
             let mut longest_match = None;
 
             if let Some(ref http_routes) = http_routes {
-                if let Some(match_suffix) = http_routes.route_prefix(&routed_path, method) {
-                    longest_match = Some((match_suffix, CurrentMatch::CurrentHttpJs));
+                if let Some((match_suffix, route)) = http_routes.route_prefix(&routed_path, method)
+                {
+                    longest_match = Some((match_suffix, CurrentMatch::CurrentHttpJs(route)));
                 }
             }
             for (mount_path, reference) in &definition.http_mounts {
@@ -349,19 +532,23 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     // component's `http.js` if present. This lets the JS layer uniformly handle
                     // 404s when defined.
                     if http_routes.is_some() {
-                        return Ok(Some((
-                            current_component_path,
-                            RoutedHttpPath(routed_path.to_string()),
-                        )));
+                        return Ok(Some(MatchedHttpRoute {
+                            component_path: current_component_path,
+                            routed_path: RoutedHttpPath(routed_path.to_string()),
+                            body_validator: HttpBodyValidator::Unvalidated,
+                            is_fallthrough: true,
+                        }));
                     } else {
                         return Ok(None);
                     }
                 },
-                Some((_, CurrentMatch::CurrentHttpJs)) => {
-                    return Ok(Some((
-                        current_component_path,
-                        RoutedHttpPath(routed_path.to_string()),
-                    )));
+                Some((_, CurrentMatch::CurrentHttpJs(route))) => {
+                    return Ok(Some(MatchedHttpRoute {
+                        component_path: current_component_path,
+                        routed_path: RoutedHttpPath(routed_path.to_string()),
+                        body_validator: route.body()?,
+                        is_fallthrough: false,
+                    }));
                 },
                 Some((match_suffix, CurrentMatch::MountedComponent(reference))) => {
                     let Reference::ChildComponent {
@@ -381,3 +568,51 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         }
     }
 }
+
+struct MatchedHttpRoute {
+    component_path: ComponentPath,
+    routed_path: RoutedHttpPath,
+    body_validator: HttpBodyValidator,
+    /// No route matched, so the request goes to `http.js` to handle the 404.
+    is_fallthrough: bool,
+}
+
+/// Whether the client will take an HTML response, which is how browsers
+/// mark navigation requests.
+fn accepts_html(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.contains("text/html"))
+}
+
+/// Check the request's body against the matched route's validator before the
+/// action runs. Validating needs the whole body, so it's buffered and handed
+/// to the action as a single chunk.
+async fn validate_request_body<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    component: ComponentId,
+    body_validator: &HttpBodyValidator,
+    http_request: &mut HttpActionRequest,
+) -> anyhow::Result<Result<(), HttpBodyValidationError>> {
+    if *body_validator == HttpBodyValidator::Unvalidated {
+        return Ok(Ok(()));
+    }
+    let mut body = BytesMut::new();
+    if let Some(mut chunks) = http_request.body.take() {
+        while let Some(chunk) = chunks.next().await {
+            body.extend_from_slice(&chunk?);
+            if body.len() > *FUNCTION_MAX_ARGS_SIZE {
+                return Ok(Err(HttpBodyValidationError::TooLarge {
+                    limit: *FUNCTION_MAX_ARGS_SIZE,
+                }));
+            }
+        }
+    }
+    let body = body.freeze();
+    let table_mapping = tx.table_mapping().namespace(component.into());
+    let result = body_validator.check_body(&body, &table_mapping, &virtual_system_mapping());
+    http_request.body = Some(stream::once(async move { Ok(body) }).boxed());
+    Ok(result)
+}
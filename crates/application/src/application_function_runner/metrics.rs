@@ -14,6 +14,7 @@ use metrics::{
     StatusTimer,
     STATUS_LABEL,
 };
+use model::traffic_splits::types::TrafficSplitVariant;
 
 pub enum UdfExecutorResult {
     Success,
@@ -50,6 +51,23 @@ pub fn log_mutation_already_committed() {
     log_counter(&APPLICATION_MUTATION_ALREADY_COMMITTED_TOTAL, 1);
 }
 
+register_convex_counter!(
+    APPLICATION_TRAFFIC_SPLIT_EXECUTIONS_TOTAL,
+    "Number of queries and mutations executed under a traffic split",
+    &["variant", "result"]
+);
+pub fn log_traffic_split_execution(variant: TrafficSplitVariant, is_error: bool) {
+    let result = if is_error { "error" } else { "success" };
+    log_counter_with_labels(
+        &APPLICATION_TRAFFIC_SPLIT_EXECUTIONS_TOTAL,
+        1,
+        vec![
+            StaticMetricLabel::new("variant", variant.as_str()),
+            StaticMetricLabel::new("result", result),
+        ],
+    );
+}
+
 register_convex_histogram!(OCC_RETRIES_TOTAL, "Number of OCC retries for a commit");
 pub fn log_occ_retries(count: usize) {
     log_distribution(&OCC_RETRIES_TOTAL, count as f64);
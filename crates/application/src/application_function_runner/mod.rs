@@ -33,7 +33,10 @@ use common::{
         Resource,
     },
     errors::JsError,
-    execution_context::ExecutionContext,
+    execution_context::{
+        ExecutionContext,
+        RequestContext,
+    },
     fastrace_helpers::EncodedSpan,
     knobs::{
         APPLICATION_FUNCTION_RUNNER_SEMAPHORE_TIMEOUT,
@@ -43,6 +46,7 @@ use common::{
         APPLICATION_MAX_CONCURRENT_QUERIES,
         APPLICATION_MAX_CONCURRENT_V8_ACTIONS,
         BACKEND_ISOLATE_ACTIVE_THREADS_PERCENT,
+        HTTP_ACTION_CACHE_MAX_SIZE,
         ISOLATE_MAX_USER_HEAP_SIZE,
         UDF_EXECUTOR_OCC_INITIAL_BACKOFF,
         UDF_EXECUTOR_OCC_MAX_BACKOFF,
@@ -53,12 +57,17 @@ use common::{
         LogLine,
         LogLines,
     },
+    query::{
+        CursorPosition,
+        Query,
+    },
     query_journal::QueryJournal,
     runtime::{
         Runtime,
         UnixTimestamp,
     },
     schemas::DatabaseSchema,
+    secrets::SecretsResolver,
     tokio::sync::{
         Semaphore,
         SemaphorePermit,
@@ -76,6 +85,8 @@ use common::{
 };
 use database::{
     unauthorized_error,
+    BulkWriteOperation,
+    BulkWriteProgress,
     Database,
     Token,
     Transaction,
@@ -84,7 +95,11 @@ use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
-use file_storage::TransactionalFileStorage;
+use file_storage::{
+    ImageTransform,
+    SignedUrlOptions,
+    TransactionalFileStorage,
+};
 use function_runner::{
     server::{
         FunctionMetadata,
@@ -98,7 +113,10 @@ use futures::{
     select_biased,
     FutureExt,
 };
-use isolate::ActionCallbacks;
+use isolate::{
+    ActionCallbacks,
+    QueryStreamBatch,
+};
 use keybroker::{
     Identity,
     KeyBroker,
@@ -110,6 +128,10 @@ use model::{
         module_loader::ModuleLoader,
         types::ModuleConfig,
     },
+    deployment_audit_log::{
+        types::DeploymentAuditLogEvent,
+        DeploymentAuditLogModel,
+    },
     environment_variables::{
         types::{
             EnvVarName,
@@ -133,7 +155,14 @@ use model::{
         },
         ModuleModel,
     },
-    scheduled_jobs::VirtualSchedulerModel,
+    outbound_network_policy::{
+        types::NetworkPolicyViolation,
+        OutboundNetworkPolicyModel,
+    },
+    scheduled_jobs::{
+        types::ScheduleOptions,
+        VirtualSchedulerModel,
+    },
     session_requests::{
         types::{
             SessionRequestIdentifier,
@@ -159,7 +188,10 @@ use storage::Storage;
 use sync_types::CanonicalizedModulePath;
 use tokio::sync::mpsc;
 use udf::{
-    helpers::parse_udf_args,
+    helpers::{
+        max_udf_args_size,
+        parse_udf_args,
+    },
     validation::{
         validate_schedule_args,
         ValidatedActionOutcome,
@@ -181,6 +213,7 @@ use usage_tracking::{
 use value::{
     id_v6::DeveloperDocumentId,
     identifier::Identifier,
+    ConvexValue,
     JsonPackedValue,
     TableNamespace,
 };
@@ -204,6 +237,7 @@ use crate::{
         function_total_timer,
         log_function_wait_timeout,
         log_mutation_already_committed,
+        log_traffic_split_execution,
     },
     cache::{
         CacheManager,
@@ -213,6 +247,13 @@ use crate::{
         ActionCompletion,
         FunctionExecutionLog,
     },
+    http_action_cache::HttpActionCache,
+    surrogate_keys::{
+        CdnPurger,
+        HttpCdnPurger,
+        SurrogateKeys,
+    },
+    traffic_split_stats::TrafficSplitStats,
     ActionError,
     ActionReturn,
     MutationError,
@@ -222,6 +263,7 @@ use crate::{
 
 mod http_routing;
 mod metrics;
+mod triggers;
 
 static BUILD_DEPS_TIMEOUT: LazyLock<Duration> = LazyLock::new(|| Duration::from_secs(1200));
 
@@ -238,6 +280,7 @@ pub struct FunctionRouter<RT: Runtime> {
     rt: RT,
     database: Database<RT>,
     system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+    pub(crate) traffic_split_stats: TrafficSplitStats,
 }
 
 impl<RT: Runtime> FunctionRouter<RT> {
@@ -252,6 +295,7 @@ impl<RT: Runtime> FunctionRouter<RT> {
             rt,
             database,
             system_env_vars,
+            traffic_split_stats: TrafficSplitStats::default(),
             query_limiter: Arc::new(Limiter::new(
                 ModuleEnvironment::Isolate,
                 UdfType::Query,
@@ -289,6 +333,10 @@ impl<RT: Runtime> FunctionRouter<RT> {
         anyhow::ensure!(udf_type == UdfType::Query || udf_type == UdfType::Mutation);
         // All queries and mutations are run in the isolate environment.
         let timer = function_total_timer(ModuleEnvironment::Isolate, udf_type);
+        let traffic_split = path_and_args
+            .traffic_split()
+            .map(|assignment| (path_and_args.path().clone(), assignment.variant));
+        let start = self.rt.monotonic_now();
         let (tx, outcome) = self
             .function_runner_execute(
                 tx,
@@ -304,6 +352,22 @@ impl<RT: Runtime> FunctionRouter<RT> {
             .await?;
         let tx = tx.with_context(|| format!("Missing transaction in response for {udf_type}"))?;
         timer.finish();
+        if let Some((path, variant)) = traffic_split {
+            let is_error = match &outcome {
+                FunctionOutcome::Query(outcome) | FunctionOutcome::Mutation(outcome) => {
+                    outcome.result.is_err()
+                },
+                FunctionOutcome::Action(_) | FunctionOutcome::HttpAction(_) => false,
+            };
+            log_traffic_split_execution(variant, is_error);
+            self.traffic_split_stats.record(
+                path.component,
+                path.udf_path,
+                variant,
+                is_error,
+                self.rt.monotonic_now() - start,
+            );
+        }
         Ok((tx, outcome))
     }
 
@@ -579,7 +643,10 @@ pub struct ApplicationFunctionRunner<RT: Runtime> {
 
     cache_manager: CacheManager<RT>,
     system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+    secrets: SecretsResolver,
     node_action_limiter: Limiter,
+    http_action_cache: HttpActionCache,
+    pub(crate) surrogate_keys: SurrogateKeys<RT>,
 }
 
 impl<RT: Runtime> ApplicationFunctionRunner<RT> {
@@ -594,6 +661,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         module_cache: Arc<dyn ModuleLoader<RT>>,
         function_log: FunctionExecutionLog<RT>,
         system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        secrets: SecretsResolver,
         cache: QueryCache,
     ) -> Self {
         // We limit the isolates to only consume fraction of the available
@@ -614,6 +682,11 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             database.clone(),
             system_env_vars.clone(),
         );
+        let surrogate_keys = SurrogateKeys::new(
+            runtime.clone(),
+            database.clone(),
+            HttpCdnPurger::from_env().map(|purger| Arc::new(purger) as Arc<dyn CdnPurger>),
+        );
         let cache_manager = CacheManager::new(
             runtime.clone(),
             database.clone(),
@@ -634,11 +707,14 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             function_log,
             cache_manager,
             system_env_vars,
+            secrets,
             node_action_limiter: Limiter::new(
                 ModuleEnvironment::Node,
                 UdfType::Action,
                 *APPLICATION_MAX_CONCURRENT_NODE_ACTIONS,
             ),
+            http_action_cache: HttpActionCache::new(*HTTP_ACTION_CACHE_MAX_SIZE),
+            surrogate_keys,
         }
     }
 
@@ -647,6 +723,10 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         Ok(())
     }
 
+    pub(crate) fn traffic_split_stats(&self) -> &TrafficSplitStats {
+        &self.isolate_functions.traffic_split_stats
+    }
+
     // Only used for running queries from REPLs.
     pub async fn run_query_without_caching(
         &self,
@@ -726,6 +806,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         identity: Identity,
         mutation_identifier: Option<SessionRequestIdentifier>,
         caller: FunctionCaller,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<MutationReturn, MutationError>> {
         let timer = mutation_timer();
         let result = self
@@ -736,6 +817,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 identity,
                 mutation_identifier,
                 caller,
+                request_context,
             )
             .await;
         match &result {
@@ -755,6 +837,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         identity: Identity,
         mutation_identifier: Option<SessionRequestIdentifier>,
         caller: FunctionCaller,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<MutationReturn, MutationError>> {
         if path.is_system() && !(identity.is_admin() || identity.is_system()) {
             anyhow::bail!(unauthorized_error("mutation"));
@@ -780,7 +863,8 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
 
             // Note that we use different context for every mutation attempt.
             // This so every JS function run gets a different executionId.
-            let context = ExecutionContext::new(request_id.clone(), &caller);
+            let context = ExecutionContext::new(request_id.clone(), &caller)
+                .with_request_context(request_context.clone());
 
             let start = self.runtime.monotonic_now();
             let mut tx = self
@@ -986,9 +1070,39 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         result
     }
 
-    /// Runs the mutation once without any logging.
+    /// Runs the mutation once without any logging, followed by the table
+    /// triggers for the documents it wrote. Then enqueues the tables' commit
+    /// hooks, records file attachments and queues file text extraction in the
+    /// same transaction.
     #[fastrace::trace]
     async fn run_mutation_inner(
+        &self,
+        tx: Transaction<RT>,
+        path: PublicFunctionPath,
+        arguments: ConvexArray,
+        allowed_visibility: AllowedVisibility,
+        context: ExecutionContext,
+    ) -> anyhow::Result<(Transaction<RT>, ValidatedUdfOutcome)> {
+        let snapshot = tx.writes().as_flat()?.clone();
+        let (tx, outcome) = self
+            .run_mutation_without_triggers(tx, path, arguments, allowed_visibility, context.clone())
+            .await?;
+        if outcome.result.is_err() {
+            return Ok((tx, outcome));
+        }
+        let (mut tx, outcome) = self
+            .run_triggers(tx, snapshot.clone(), outcome, &context)
+            .await?;
+        if outcome.result.is_err() {
+            return Ok((tx, outcome));
+        }
+        triggers::enqueue_commit_hooks(&mut tx, &snapshot, self.runtime.unix_timestamp(), &context)
+            .await?;
+        triggers::record_file_writes(&mut tx, &snapshot).await?;
+        Ok((tx, outcome))
+    }
+
+    async fn run_mutation_without_triggers(
         &self,
         mut tx: Transaction<RT>,
         path: PublicFunctionPath,
@@ -1006,6 +1120,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             path.clone(),
             arguments.clone(),
             UdfType::Mutation,
+            max_udf_args_size(&context),
         )
         .await?;
 
@@ -1056,6 +1171,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         arguments: Vec<JsonValue>,
         identity: Identity,
         caller: FunctionCaller,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<ActionReturn, ActionError>> {
         if path.is_system() && !(identity.is_admin() || identity.is_system()) {
             anyhow::bail!(unauthorized_error("action"));
@@ -1069,7 +1185,8 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 }))
             },
         };
-        let context = ExecutionContext::new(request_id.clone(), &caller);
+        let context = ExecutionContext::new(request_id.clone(), &caller)
+            .with_request_context(request_context);
         let usage_tracking = FunctionUsageTracker::new();
         let start = self.runtime.monotonic_now();
         let completion_result = self
@@ -1172,6 +1289,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             path.clone(),
             arguments.clone(),
             UdfType::Action,
+            max_udf_args_size(&context),
         )
         .await?;
 
@@ -1299,10 +1417,14 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     .get(source_package_id)
                     .await?
                     .into_value();
-                let mut environment_variables =
+                let environment_variables =
                     EnvironmentVariablesModel::new(&mut tx).get_all().await?;
+                let mut environment_variables =
+                    self.secrets.resolve_env_vars(environment_variables).await?;
                 // Insert special environment variables if not already provided by user
                 environment_variables.extend(self.system_env_vars.clone());
+                let outbound_network_policy =
+                    OutboundNetworkPolicyModel::new(&mut tx).get().await?;
 
                 // Fetch source and external_deps presigned URI first
                 let source_uri_future = self
@@ -1348,6 +1470,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     user_identity: tx.user_identity(),
                     auth_header: token_to_authorization_header(tx.authentication_token())?,
                     environment_variables,
+                    outbound_network_policy,
                     callback_token: self.key_broker.issue_action_token(path.component),
                     context: context.clone(),
                     encoded_parent_trace: EncodedSpan::from_parent().0,
@@ -1853,7 +1976,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         context: ExecutionContext,
     ) -> anyhow::Result<FunctionResult> {
         let ts = self.database.now_ts_for_reads();
-        let result = self
+        let query_return = self
             .run_query_at_ts(
                 context.request_id,
                 PublicFunctionPath::Component(path),
@@ -1865,9 +1988,14 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
                     parent_scheduled_job: context.parent_scheduled_job,
                 },
             )
-            .await?
-            .result;
-        Ok(FunctionResult { result })
+            .await?;
+        // Queries run by HTTP actions decide which CDN keys their response
+        // is tagged with.
+        self.surrogate_keys
+            .record_query(&context.execution_id, query_return.token);
+        Ok(FunctionResult {
+            result: query_return.result,
+        })
     }
 
     #[fastrace::trace]
@@ -1888,6 +2016,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
                 FunctionCaller::Action {
                     parent_scheduled_job: context.parent_scheduled_job,
                 },
+                context.request_context,
             )
             .await
             .map(|r| match r {
@@ -1915,6 +2044,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
                 FunctionCaller::Action {
                     parent_scheduled_job: context.parent_scheduled_job,
                 },
+                context.request_context,
             )
             .await
             .map(|r| match r {
@@ -1937,6 +2067,60 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
             .await
     }
 
+    async fn storage_get_signed_url(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        options: SignedUrlOptions,
+    ) -> anyhow::Result<Option<String>> {
+        let mut tx = self.database.begin(identity).await?;
+        self.bail_if_backend_not_running(&mut tx).await?;
+        self.file_storage
+            .get_signed_url(&mut tx, &self.key_broker, component, storage_id, options)
+            .await
+    }
+
+    async fn storage_revoke_signed_url(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        url: String,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(identity.clone()).await?;
+        self.bail_if_backend_not_running(&mut tx).await?;
+        self.database
+            .execute_with_occ_retries(
+                identity,
+                FunctionUsageTracker::new(),
+                "app_funrun_storage_revoke_signed_url",
+                |tx| {
+                    async {
+                        self.file_storage
+                            .revoke_signed_url(tx, &self.key_broker, component, &url)
+                            .await
+                    }
+                    .into()
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn storage_get_image_url(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        transform: ImageTransform,
+    ) -> anyhow::Result<Option<String>> {
+        let mut tx = self.database.begin(identity).await?;
+        self.bail_if_backend_not_running(&mut tx).await?;
+        self.file_storage
+            .get_image_url(&mut tx, &self.key_broker, component, storage_id, transform)
+            .await
+    }
+
     async fn storage_get_file_entry(
         &self,
         identity: Identity,
@@ -2022,6 +2206,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         scheduled_path: CanonicalizedComponentFunctionPath,
         udf_args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let (_ts, virtual_id, _stats) = self
@@ -2045,9 +2230,13 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
                             tx,
                         )
                         .await?;
+                        let udf_args = self
+                            .file_storage
+                            .offload_scheduled_job_args(udf_args)
+                            .await?;
                         let virtual_id =
                             VirtualSchedulerModel::new(tx, scheduling_component.into())
-                                .schedule(path, udf_args, scheduled_ts, context)
+                                .schedule(path, udf_args, scheduled_ts, options, context)
                                 .await?;
                         Ok(virtual_id)
                     }
@@ -2093,6 +2282,59 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         self.database.vector_search(identity, query).await
     }
 
+    async fn stream_query_batch(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        query: Query,
+        cursor: Option<String>,
+        batch_size: usize,
+    ) -> anyhow::Result<QueryStreamBatch> {
+        let persistence_version = self.database.persistence_version();
+        let start_cursor = cursor
+            .map(|c| self.key_broker.decrypt_cursor(c, persistence_version))
+            .transpose()?;
+        let (page, cursor, usage_stats) = self
+            .database
+            .query_stream_batch(identity, component.into(), query, start_cursor, batch_size)
+            .await?;
+        Ok(QueryStreamBatch {
+            page: page
+                .into_iter()
+                .map(|doc| ConvexValue::from(doc.into_value().0))
+                .collect(),
+            is_done: matches!(cursor.position, CursorPosition::End),
+            continue_cursor: self.key_broker.encrypt_cursor(&cursor, persistence_version),
+            usage_stats,
+        })
+    }
+
+    async fn bulk_write(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        operations: Vec<BulkWriteOperation>,
+    ) -> anyhow::Result<(BulkWriteProgress, FunctionUsageStats)> {
+        let usage = FunctionUsageTracker::new();
+        let progress = self
+            .database
+            .bulk_write(
+                identity,
+                component.into(),
+                operations,
+                usage.clone(),
+                |progress| {
+                    tracing::debug!(
+                        "Bulk write committed {} operations in {} chunks",
+                        progress.operations_committed,
+                        progress.chunks_committed
+                    )
+                },
+            )
+            .await?;
+        Ok((progress, usage.gather_user_stats()))
+    }
+
     async fn lookup_function_handle(
         &self,
         identity: Identity,
@@ -2112,4 +2354,39 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
             .get_with_component_path(path)
             .await
     }
+
+    async fn record_network_policy_violation(
+        &self,
+        _identity: Identity,
+        violation: NetworkPolicyViolation,
+        environment: ModuleEnvironment,
+    ) -> anyhow::Result<()> {
+        // Record the violation as the system, since the action's identity
+        // can't write to the audit log.
+        let mut tx = self.database.begin_system().await?;
+        DeploymentAuditLogModel::new(&mut tx)
+            .insert(vec![
+                DeploymentAuditLogEvent::OutboundNetworkPolicyViolation {
+                    host: violation.host,
+                    reason: violation.reason,
+                    environment,
+                },
+            ])
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "app_funrun_record_network_policy_violation")
+            .await?;
+        Ok(())
+    }
+
+    async fn invalidate_http_action_cache(
+        &self,
+        _identity: Identity,
+        component: ComponentId,
+        path_prefix: Option<String>,
+    ) -> anyhow::Result<usize> {
+        Ok(self
+            .http_action_cache
+            .invalidate(component, path_prefix.as_deref()))
+    }
 }
@@ -0,0 +1,306 @@
+//! Table triggers and commit hooks. Triggers are mutations registered on a
+//! table in the schema that run in the writing transaction whenever a document
+//! in the table is inserted, updated or deleted. Commit hooks are actions that
+//! the writing transaction schedules for the same changes. The same changes
+//! also update the file attachments of tables with `v.id("_storage")` fields
+//! and queue the text extraction of tables that configure it.
+
+use std::collections::{
+    BTreeMap,
+    VecDeque,
+};
+
+use common::{
+    bootstrap_model::schema::SchemaState,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentId,
+        PublicFunctionPath,
+    },
+    document::{
+        DocumentUpdate,
+        ResolvedDocument,
+    },
+    errors::JsError,
+    execution_context::ExecutionContext,
+    knobs::TRIGGER_MAX_DEPTH,
+    log_lines::LogLine,
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    schemas::{
+        DatabaseSchema,
+        TableDefinition,
+    },
+    types::AllowedVisibility,
+};
+use database::{
+    SchemaModel,
+    Transaction,
+    Writes,
+};
+use model::{
+    file_attachments::{
+        has_attachment_fields,
+        FileAttachmentsModel,
+    },
+    file_text::FileTextModel,
+    scheduled_jobs::{
+        types::{
+            ScheduleOptions,
+            ScheduledJobArgs,
+        },
+        SchedulerModel,
+    },
+};
+use serde_json::Value as JsonValue;
+use udf::validation::{
+    validate_schedule_args,
+    ValidatedUdfOutcome,
+};
+use value::{
+    ConvexArray,
+    ConvexObject,
+    ConvexValue,
+    FieldName,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+    TabletId,
+};
+
+use super::ApplicationFunctionRunner;
+
+struct PendingTrigger {
+    path: CanonicalizedComponentFunctionPath,
+    args: ConvexObject,
+    /// The triggers and documents that led to this run, outermost first.
+    chain: Vec<(CanonicalizedComponentFunctionPath, ResolvedDocumentId)>,
+}
+
+impl<RT: Runtime> ApplicationFunctionRunner<RT> {
+    /// Runs the triggers for documents written since `snapshot`, then the
+    /// triggers for the documents those triggers wrote, and so on. A trigger
+    /// that throws, loops or nests too deeply fails the mutation.
+    pub(super) async fn run_triggers(
+        &self,
+        mut tx: Transaction<RT>,
+        snapshot: Writes,
+        mut outcome: ValidatedUdfOutcome,
+        context: &ExecutionContext,
+    ) -> anyhow::Result<(Transaction<RT>, ValidatedUdfOutcome)> {
+        let mut schemas = BTreeMap::new();
+        let updates = tx.writes().as_flat()?.updates_since(&snapshot);
+        let mut pending = match pending_triggers(&mut tx, &mut schemas, updates, &[]).await? {
+            Ok(pending) => VecDeque::from(pending),
+            Err(e) => {
+                outcome.result = Err(e);
+                return Ok((tx, outcome));
+            },
+        };
+        while let Some(trigger) = pending.pop_front() {
+            if trigger.chain.len() > *TRIGGER_MAX_DEPTH {
+                outcome.result = Err(JsError::from_message(format!(
+                    "Trigger {} is nested more than {} triggers deep",
+                    trigger.path.udf_path, *TRIGGER_MAX_DEPTH
+                )));
+                return Ok((tx, outcome));
+            }
+            let snapshot = tx.writes().as_flat()?.clone();
+            let (trigger_tx, trigger_outcome) = self
+                .run_mutation_without_triggers(
+                    tx,
+                    PublicFunctionPath::Component(trigger.path.clone()),
+                    ConvexArray::try_from(vec![ConvexValue::Object(trigger.args)])?,
+                    AllowedVisibility::All,
+                    context.clone(),
+                )
+                .await?;
+            tx = trigger_tx;
+            outcome.log_lines.push(LogLine::SubFunction {
+                path: trigger.path.clone(),
+                log_lines: trigger_outcome.log_lines,
+            });
+            if let Err(e) = trigger_outcome.result {
+                outcome.result = Err(JsError::from_message(format!(
+                    "Trigger {} failed: {e}",
+                    trigger.path.udf_path
+                )));
+                return Ok((tx, outcome));
+            }
+            let updates = tx.writes().as_flat()?.updates_since(&snapshot);
+            match pending_triggers(&mut tx, &mut schemas, updates, &trigger.chain).await? {
+                Ok(next) => pending.extend(next),
+                Err(e) => {
+                    outcome.result = Err(e);
+                    return Ok((tx, outcome));
+                },
+            }
+        }
+        Ok((tx, outcome))
+    }
+}
+
+/// Schedules the commit hooks for the documents written since `snapshot`.
+/// They're enqueued in the writing transaction, so they run if and only if it
+/// commits.
+pub(super) async fn enqueue_commit_hooks<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    snapshot: &Writes,
+    now: UnixTimestamp,
+    context: &ExecutionContext,
+) -> anyhow::Result<()> {
+    let mut schemas = BTreeMap::new();
+    let updates = tx.writes().as_flat()?.updates_since(snapshot);
+    for update in updates {
+        let Some((namespace, table)) = table_definition(tx, &mut schemas, update.id.tablet_id)
+            .await?
+            .filter(|(_, table)| !table.on_commit.is_empty())
+        else {
+            continue;
+        };
+        let component = tx.must_component_path(ComponentId::from(namespace))?;
+        let args = trigger_args(&table.table_name, &update)?;
+        for udf_path in table.on_commit {
+            let path = CanonicalizedComponentFunctionPath {
+                component: component.clone(),
+                udf_path,
+            };
+            let (path, args) =
+                validate_schedule_args(path, vec![JsonValue::from(args.clone())], now, now, tx)
+                    .await?;
+            SchedulerModel::new(tx, namespace)
+                .schedule(
+                    path,
+                    ScheduledJobArgs::Inline(args),
+                    now,
+                    ScheduleOptions::default(),
+                    context.clone(),
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Records the file attachments of the documents written since `snapshot`,
+/// including the writes of triggers, and queues the text extraction of the
+/// files they newly hold.
+pub(super) async fn record_file_writes<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    snapshot: &Writes,
+) -> anyhow::Result<()> {
+    let mut schemas = BTreeMap::new();
+    let updates = tx.writes().as_flat()?.updates_since(snapshot);
+    for update in updates {
+        let Some((namespace, table)) = table_definition(tx, &mut schemas, update.id.tablet_id)
+            .await?
+            .filter(|(_, table)| has_attachment_fields(table) || table.file_text.is_some())
+        else {
+            continue;
+        };
+        if has_attachment_fields(&table) {
+            FileAttachmentsModel::new(tx, namespace)
+                .record_write(&table, &update)
+                .await?;
+        }
+        FileTextModel::new(tx, namespace)
+            .record_write(&table, &update)
+            .await?;
+    }
+    Ok(())
+}
+
+/// The schema definition of a user table, if its component has an active
+/// schema that defines it.
+async fn table_definition<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    schemas: &mut BTreeMap<TableNamespace, Option<DatabaseSchema>>,
+    tablet_id: TabletId,
+) -> anyhow::Result<Option<(TableNamespace, TableDefinition)>> {
+    if tx.table_mapping().is_system_tablet(tablet_id) {
+        return Ok(None);
+    }
+    let namespace = tx.table_mapping().tablet_namespace(tablet_id)?;
+    let table_name = tx.table_mapping().tablet_name(tablet_id)?;
+    if !schemas.contains_key(&namespace) {
+        let schema = SchemaModel::new(tx, namespace)
+            .get_by_state(SchemaState::Active)
+            .await?
+            .map(|(_, schema)| schema);
+        schemas.insert(namespace, schema);
+    }
+    let table = schemas[&namespace]
+        .as_ref()
+        .and_then(|schema| schema.tables.get(&table_name))
+        .cloned();
+    Ok(table.map(|table| (namespace, table)))
+}
+
+/// The triggers registered on the tables of `updates`, or an error if one of
+/// them already ran on the same document earlier in `chain`.
+async fn pending_triggers<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    schemas: &mut BTreeMap<TableNamespace, Option<DatabaseSchema>>,
+    updates: Vec<DocumentUpdate>,
+    chain: &[(CanonicalizedComponentFunctionPath, ResolvedDocumentId)],
+) -> anyhow::Result<Result<Vec<PendingTrigger>, JsError>> {
+    let mut pending = vec![];
+    for update in updates {
+        let Some((namespace, table)) = table_definition(tx, schemas, update.id.tablet_id)
+            .await?
+            .filter(|(_, table)| !table.triggers.is_empty())
+        else {
+            continue;
+        };
+        let component = tx.must_component_path(ComponentId::from(namespace))?;
+        let args = trigger_args(&table.table_name, &update)?;
+        for udf_path in table.triggers {
+            let path = CanonicalizedComponentFunctionPath {
+                component: component.clone(),
+                udf_path,
+            };
+            if chain.contains(&(path.clone(), update.id)) {
+                return Ok(Err(JsError::from_message(format!(
+                    "Trigger {} fired again for document {} while handling its own write",
+                    path.udf_path, update.id.developer_id
+                ))));
+            }
+            let mut trigger_chain = chain.to_vec();
+            trigger_chain.push((path.clone(), update.id));
+            pending.push(PendingTrigger {
+                path,
+                args: args.clone(),
+                chain: trigger_chain,
+            });
+        }
+    }
+    Ok(Ok(pending))
+}
+
+fn trigger_args(table_name: &TableName, update: &DocumentUpdate) -> anyhow::Result<ConvexObject> {
+    let operation = match (&update.old_document, &update.new_document) {
+        (None, Some(_)) => "insert",
+        (Some(_), Some(_)) => "update",
+        (Some(_), None) => "delete",
+        (None, None) => anyhow::bail!("Trigger for document {} that never existed", update.id),
+    };
+    let document = |document: &Option<ResolvedDocument>| match document {
+        Some(document) => ConvexValue::Object(document.value().0.clone()),
+        None => ConvexValue::Null,
+    };
+    let mut fields: BTreeMap<FieldName, ConvexValue> = BTreeMap::new();
+    fields.insert(
+        "table".parse()?,
+        ConvexValue::try_from(String::from(table_name.clone()))?,
+    );
+    fields.insert(
+        "operation".parse()?,
+        ConvexValue::try_from(operation.to_string())?,
+    );
+    fields.insert("id".parse()?, update.id.developer_id.into());
+    fields.insert("oldDoc".parse()?, document(&update.old_document));
+    fields.insert("newDoc".parse()?, document(&update.new_document));
+    ConvexObject::try_from(fields)
+}
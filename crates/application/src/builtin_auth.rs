@@ -0,0 +1,668 @@
+//! Built-in auth: email/password, magic link and passkey sign-in for
+//! deployments that don't want a third-party auth provider.
+//!
+//! It's enabled by adding an auth provider with the built-in issuer and
+//! audience to `auth.config.js`. Signing in starts a session and returns a
+//! short-lived ID token along with a refresh token for the session. The ID
+//! tokens are validated like any other provider's, so functions see the user
+//! through `ctx.auth`.
+
+use std::{
+    net::IpAddr,
+    sync::LazyLock,
+};
+
+use common::{
+    auth::AuthInfo,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    execution_context::ExecutionContext,
+    knobs::{
+        BUILTIN_AUTH_MAGIC_LINK_TTL,
+        BUILTIN_AUTH_PASSKEY_CHALLENGE_TTL,
+        BUILTIN_AUTH_SESSION_TTL,
+        BUILTIN_AUTH_TOKEN_TTL,
+    },
+    runtime::{
+        block_in_place,
+        Runtime,
+    },
+    types::FunctionCaller,
+    version::ClientVersion,
+    RequestId,
+};
+use database::Transaction;
+use errors::ErrorMetadata;
+use keybroker::{
+    client_data_challenge,
+    hash_password,
+    verify_passkey_assertion,
+    verify_passkey_registration,
+    verify_password,
+    Identity,
+    RelyingParty,
+    BUILTIN_AUTH_AUDIENCE,
+    BUILTIN_AUTH_ISSUER,
+    COSE_ALG_ES256,
+};
+use model::{
+    auth::AuthInfoModel,
+    builtin_auth::{
+        normalize_email,
+        types::{
+            AuthCredentialOwner,
+            AuthPasskey,
+            AuthPasskeyChallenge,
+            AuthUser,
+        },
+        BuiltinAuthModel,
+    },
+    scheduled_jobs::{
+        types::ScheduledJobArgs,
+        ScheduleOptions,
+        SchedulerModel,
+    },
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use sync_types::CanonicalizedUdfPath;
+use tokio::time::Instant;
+use udf::helpers::parse_udf_args;
+use url::Url;
+use value::{
+    base64::{
+        decode_urlsafe,
+        encode_urlsafe,
+    },
+    TableNamespace,
+};
+
+use crate::Application;
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+const MAX_PASSWORD_LENGTH: usize = 256;
+const MAX_EMAIL_LENGTH: usize = 256;
+
+/// What a successful sign-in returns to the client.
+pub struct BuiltinAuthTokens {
+    /// ID token for the user, which clients pass to `setAuth`.
+    pub token: String,
+    /// Exchanged for a fresh ID token once the current one expires.
+    pub refresh_token: String,
+}
+
+/// Checked against when there's no password to check, so sign-ins take as
+/// long whether or not the user exists.
+static DUMMY_PASSWORD_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password("dummy password").expect("Failed to hash dummy password"));
+
+/// What failed sign-ins are counted against: the account, so its password
+/// can't be guessed from many addresses, and the address, so it can't guess
+/// many accounts' passwords.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SignInSource {
+    Email(String),
+    Ip(IpAddr),
+}
+
+fn sign_in_sources(email: &str, client_ip: Option<IpAddr>) -> Vec<SignInSource> {
+    let mut sources = vec![SignInSource::Email(normalize_email(email))];
+    sources.extend(client_ip.map(|ip| SignInSource::Ip(ip.to_canonical())));
+    sources
+}
+
+fn invalid_credentials() -> ErrorMetadata {
+    ErrorMetadata::unauthenticated("InvalidCredentials", "Invalid email or password")
+}
+
+fn validate_email(email: &str) -> anyhow::Result<()> {
+    let email = email.trim();
+    let valid = email.len() <= MAX_EMAIL_LENGTH
+        && email
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty());
+    if !valid {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidEmail",
+            format!("{email:?} isn't a valid email address")
+        ));
+    }
+    Ok(())
+}
+
+fn validate_password(password: &str) -> anyhow::Result<()> {
+    if password.len() < MIN_PASSWORD_LENGTH || password.len() > MAX_PASSWORD_LENGTH {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidPassword",
+            format!(
+                "Passwords must be between {MIN_PASSWORD_LENGTH} and {MAX_PASSWORD_LENGTH} \
+                 characters"
+            )
+        ));
+    }
+    Ok(())
+}
+
+/// The site passkeys are scoped to, from the built-in auth provider's
+/// `passkeyOrigin`.
+struct PasskeyRelyingParty {
+    id: String,
+    origin: String,
+}
+
+impl PasskeyRelyingParty {
+    fn as_ref(&self) -> RelyingParty<'_> {
+        RelyingParty {
+            id: &self.id,
+            origin: &self.origin,
+        }
+    }
+}
+
+/// The user signed in with built-in auth, which is required to manage their
+/// passkeys.
+fn builtin_auth_user_id(identity: &Identity) -> anyhow::Result<&str> {
+    match identity {
+        Identity::User(user) if user.issuer == BUILTIN_AUTH_ISSUER => Ok(&user.subject),
+        _ => Err(ErrorMetadata::unauthenticated(
+            "BuiltinAuthRequired",
+            "Sign in with built-in auth to register a passkey",
+        )
+        .into()),
+    }
+}
+
+fn decode_passkey_response(field: &str, value: &str) -> anyhow::Result<Vec<u8>> {
+    decode_urlsafe(value).map_err(|e| {
+        ErrorMetadata::bad_request(
+            "InvalidPasskeyResponse",
+            format!("{field} isn't valid base64url: {e}"),
+        )
+        .into()
+    })
+}
+
+impl<RT: Runtime> Application<RT> {
+    /// The built-in auth provider from the deployment's auth config, or an
+    /// error if built-in auth isn't enabled.
+    async fn builtin_auth_info(&self, tx: &mut Transaction<RT>) -> anyhow::Result<AuthInfo> {
+        AuthInfoModel::new(tx)
+            .get()
+            .await?
+            .into_iter()
+            .map(|auth_info| auth_info.into_value())
+            .find(|auth_info| {
+                auth_info.domain.trim_end_matches('/') == BUILTIN_AUTH_ISSUER
+                    && auth_info.application_id == BUILTIN_AUTH_AUDIENCE
+            })
+            .ok_or_else(|| {
+                ErrorMetadata::bad_request(
+                    "BuiltinAuthDisabled",
+                    format!(
+                        "Add an auth provider with domain \"{BUILTIN_AUTH_ISSUER}\" and \
+                         applicationID \"{BUILTIN_AUTH_AUDIENCE}\" to auth.config.js to enable \
+                         built-in auth"
+                    ),
+                )
+                .into()
+            })
+    }
+
+    /// Start a session for `user` in `tx` and mint its first ID token.
+    async fn start_builtin_auth_session(
+        &self,
+        tx: &mut Transaction<RT>,
+        user: &AuthUser,
+    ) -> anyhow::Result<BuiltinAuthTokens> {
+        let expires = self.runtime.unix_timestamp() + *BUILTIN_AUTH_SESSION_TTL;
+        let refresh_token = BuiltinAuthModel::new(tx)
+            .create_session(&user.user_id, expires)
+            .await?;
+        let token = self.issue_builtin_auth_token(user)?;
+        Ok(BuiltinAuthTokens {
+            token,
+            refresh_token,
+        })
+    }
+
+    fn issue_builtin_auth_token(&self, user: &AuthUser) -> anyhow::Result<String> {
+        let now = self.runtime.system_time();
+        self.key_broker().issue_builtin_auth_token(
+            user.user_id.clone(),
+            user.email.clone(),
+            user.email_verified,
+            now,
+            now + *BUILTIN_AUTH_TOKEN_TTL,
+        )
+    }
+
+    /// Create a user with an email and password, who can then sign in.
+    /// Succeeds without changing anything if a user with the email already
+    /// exists, so sign ups can't be used to find out which emails have
+    /// accounts.
+    pub async fn builtin_auth_sign_up(
+        &self,
+        email: String,
+        password: String,
+    ) -> anyhow::Result<()> {
+        validate_email(&email)?;
+        validate_password(&password)?;
+        let password_hash = block_in_place(|| hash_password(&password))?;
+        let mut tx = self.begin(Identity::system()).await?;
+        self.builtin_auth_info(&mut tx).await?;
+        let mut model = BuiltinAuthModel::new(&mut tx);
+        if model.get_user_by_email(&email).await?.is_some() {
+            return Ok(());
+        }
+        let user = model.create_user(&email, false).await?;
+        model
+            .set_password_hash(&user.user_id, password_hash)
+            .await?;
+        self.commit(tx, "builtin_auth_sign_up").await?;
+        Ok(())
+    }
+
+    /// Fails if the email or the client's address is locked out after too
+    /// many failed sign-ins or magic links.
+    fn check_builtin_auth_lockout(
+        &self,
+        sources: &[SignInSource],
+        now: Instant,
+    ) -> anyhow::Result<()> {
+        for source in sources {
+            if let Err(remaining) = self.builtin_auth_lockout.check(source, now) {
+                anyhow::bail!(ErrorMetadata::rate_limited(
+                    "TooManySignInAttempts",
+                    format!(
+                        "Too many failed sign-in attempts. Try again in {} seconds.",
+                        remaining.as_secs().max(1)
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sign in with an email and password. Too many failed attempts for the
+    /// email or from the client's address lock out further ones for a while.
+    pub async fn builtin_auth_sign_in(
+        &self,
+        email: String,
+        password: String,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<BuiltinAuthTokens> {
+        let sources = sign_in_sources(&email, client_ip);
+        let now = self.runtime.monotonic_now();
+        self.check_builtin_auth_lockout(&sources, now)?;
+        let mut tx = self.begin(Identity::system()).await?;
+        self.builtin_auth_info(&mut tx).await?;
+        let mut model = BuiltinAuthModel::new(&mut tx);
+        let user = model
+            .get_user_by_email(&email)
+            .await?
+            .map(|u| u.into_value());
+        let password_hash = match &user {
+            Some(user) => model.get_password_hash(&user.user_id).await?,
+            None => None,
+        };
+        let verified = block_in_place(|| match &password_hash {
+            Some(password_hash) => verify_password(password_hash, &password),
+            None => {
+                verify_password(&DUMMY_PASSWORD_HASH, &password);
+                false
+            },
+        });
+        let Some(user) = user.filter(|_| verified) else {
+            for source in sources {
+                self.builtin_auth_lockout.record_failure(source, now);
+            }
+            anyhow::bail!(invalid_credentials());
+        };
+        self.builtin_auth_lockout
+            .forget(&SignInSource::Email(normalize_email(&email)));
+        let tokens = self.start_builtin_auth_session(&mut tx, &user).await?;
+        self.commit(tx, "builtin_auth_sign_in").await?;
+        Ok(tokens)
+    }
+
+    /// Send a magic link to `email` by scheduling the configured magic link
+    /// action. Users who don't exist yet are created when they redeem the
+    /// link, so magic links double as sign up. Each link sent counts against
+    /// the email and the client's address like a failed sign-in until one is
+    /// redeemed, so they can't be used to spam arbitrary inboxes.
+    pub async fn builtin_auth_send_magic_link(
+        &self,
+        request_id: RequestId,
+        email: String,
+        client_version: ClientVersion,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<()> {
+        validate_email(&email)?;
+        let sources = sign_in_sources(&email, client_ip);
+        let now = self.runtime.monotonic_now();
+        self.check_builtin_auth_lockout(&sources, now)?;
+        let mut tx = self.begin(Identity::system()).await?;
+        let auth_info = self.builtin_auth_info(&mut tx).await?;
+        let Some(magic_link_action) = auth_info.magic_link_action else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "MagicLinksDisabled",
+                "Set magicLinkAction on the built-in auth provider in auth.config.js to send \
+                 magic links"
+            ));
+        };
+        let udf_path: CanonicalizedUdfPath = magic_link_action.parse().map_err(|e| {
+            ErrorMetadata::bad_request(
+                "InvalidMagicLinkAction",
+                format!("Invalid magicLinkAction {magic_link_action:?}: {e}"),
+            )
+        })?;
+        for source in sources {
+            self.builtin_auth_lockout.record_failure(source, now);
+        }
+        let email = normalize_email(&email);
+        let now = self.runtime.unix_timestamp();
+        let token = BuiltinAuthModel::new(&mut tx)
+            .create_magic_link(&email, now + *BUILTIN_AUTH_MAGIC_LINK_TTL)
+            .await?;
+        let args = parse_udf_args(&udf_path, vec![json!({ "email": email, "token": token })])?;
+        SchedulerModel::new(&mut tx, TableNamespace::root_component())
+            .schedule(
+                CanonicalizedComponentFunctionPath {
+                    component: ComponentPath::root(),
+                    udf_path,
+                },
+                ScheduledJobArgs::Inline(args),
+                now,
+                ScheduleOptions::default(),
+                ExecutionContext::new(request_id, &FunctionCaller::HttpApi(client_version)),
+            )
+            .await?;
+        self.commit(tx, "builtin_auth_send_magic_link").await?;
+        Ok(())
+    }
+
+    /// Sign in with the token from a magic link, which also verifies the
+    /// user's email, creating the user if they don't exist yet.
+    pub async fn builtin_auth_verify_magic_link(
+        &self,
+        token: String,
+    ) -> anyhow::Result<BuiltinAuthTokens> {
+        let mut tx = self.begin(Identity::system()).await?;
+        self.builtin_auth_info(&mut tx).await?;
+        let now = self.runtime.unix_timestamp();
+        let mut model = BuiltinAuthModel::new(&mut tx);
+        let invalid_link = || {
+            ErrorMetadata::unauthenticated(
+                "InvalidMagicLink",
+                "Magic link is invalid or has expired",
+            )
+        };
+        let Some(owner) = model.consume_magic_link(&token, now).await? else {
+            // Commit anyway so expired links are used up.
+            self.commit(tx, "builtin_auth_verify_magic_link").await?;
+            anyhow::bail!(invalid_link());
+        };
+        let user = match owner {
+            AuthCredentialOwner::User(user_id) => {
+                let Some(user) = model.get_user(&user_id).await? else {
+                    anyhow::bail!(invalid_link());
+                };
+                model.mark_email_verified(user).await?
+            },
+            AuthCredentialOwner::Email(email) => match model.get_user_by_email(&email).await? {
+                Some(user) => model.mark_email_verified(user).await?,
+                None => model.create_user(&email, true).await?,
+            },
+        };
+        self.builtin_auth_lockout
+            .forget(&SignInSource::Email(user.email.clone()));
+        let tokens = self.start_builtin_auth_session(&mut tx, &user).await?;
+        self.commit(tx, "builtin_auth_verify_magic_link").await?;
+        Ok(tokens)
+    }
+
+    /// Exchange a refresh token for a fresh ID token. Refresh tokens are
+    /// single use, so this also returns the session's next refresh token.
+    pub async fn builtin_auth_refresh(
+        &self,
+        refresh_token: String,
+    ) -> anyhow::Result<BuiltinAuthTokens> {
+        let mut tx = self.begin(Identity::system()).await?;
+        self.builtin_auth_info(&mut tx).await?;
+        let now = self.runtime.unix_timestamp();
+        let mut model = BuiltinAuthModel::new(&mut tx);
+        let expired_session = || {
+            ErrorMetadata::unauthenticated(
+                "BuiltinAuthSessionExpired",
+                "Session has expired or was signed out",
+            )
+        };
+        let Some((user_id, refresh_token)) = model
+            .refresh_session(&refresh_token, now, now + *BUILTIN_AUTH_SESSION_TTL)
+            .await?
+        else {
+            anyhow::bail!(expired_session());
+        };
+        let Some(user) = model.get_user(&user_id).await? else {
+            anyhow::bail!(expired_session());
+        };
+        let token = self.issue_builtin_auth_token(&user.into_value())?;
+        self.commit(tx, "builtin_auth_refresh").await?;
+        Ok(BuiltinAuthTokens {
+            token,
+            refresh_token,
+        })
+    }
+
+    /// End the session with `refresh_token`. ID tokens that were already
+    /// issued for it stay valid until they expire.
+    pub async fn builtin_auth_sign_out(&self, refresh_token: String) -> anyhow::Result<()> {
+        let mut tx = self.begin(Identity::system()).await?;
+        BuiltinAuthModel::new(&mut tx)
+            .delete_session(&refresh_token)
+            .await?;
+        self.commit(tx, "builtin_auth_sign_out").await?;
+        Ok(())
+    }
+
+    async fn passkey_relying_party(
+        &self,
+        tx: &mut Transaction<RT>,
+    ) -> anyhow::Result<PasskeyRelyingParty> {
+        let auth_info = self.builtin_auth_info(tx).await?;
+        let Some(origin) = auth_info.passkey_origin else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "PasskeysDisabled",
+                "Set passkeyOrigin on the built-in auth provider in auth.config.js to offer \
+                 passkey sign-in"
+            ));
+        };
+        let origin = origin.trim_end_matches('/').to_string();
+        let Some(id) = Url::parse(&origin)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+        else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidPasskeyOrigin",
+                format!("passkeyOrigin {origin:?} isn't a valid origin")
+            ));
+        };
+        Ok(PasskeyRelyingParty { id, origin })
+    }
+
+    /// Start registering a passkey for the signed-in user. Returns options
+    /// for `PublicKeyCredential.parseCreationOptionsFromJSON()`.
+    pub async fn builtin_auth_passkey_registration_options(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<JsonValue> {
+        let user_id = builtin_auth_user_id(&identity)?;
+        let mut tx = self.begin(Identity::system()).await?;
+        let rp = self.passkey_relying_party(&mut tx).await?;
+        let mut model = BuiltinAuthModel::new(&mut tx);
+        let Some(user) = model.get_user(user_id).await? else {
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "BuiltinAuthRequired",
+                "Signed-in user no longer exists"
+            ));
+        };
+        let exclude_credentials: Vec<_> = model
+            .list_passkeys(user_id)
+            .await?
+            .into_iter()
+            .map(|passkey| json!({ "type": "public-key", "id": passkey.credential_id }))
+            .collect();
+        let expires = self.runtime.unix_timestamp() + *BUILTIN_AUTH_PASSKEY_CHALLENGE_TTL;
+        let challenge = model
+            .create_passkey_challenge(Some(user_id), expires)
+            .await?;
+        self.commit(tx, "builtin_auth_passkey_registration_options")
+            .await?;
+        Ok(json!({
+            "challenge": encode_urlsafe(&challenge),
+            "rp": { "id": rp.id, "name": rp.id },
+            "user": {
+                "id": encode_urlsafe(user_id.as_bytes()),
+                "name": user.email,
+                "displayName": user.email,
+            },
+            "pubKeyCredParams": [{ "type": "public-key", "alg": COSE_ALG_ES256 }],
+            "timeout": BUILTIN_AUTH_PASSKEY_CHALLENGE_TTL.as_millis() as u64,
+            "attestation": "none",
+            "authenticatorSelection": {
+                "residentKey": "required",
+                "userVerification": "required",
+            },
+            "excludeCredentials": exclude_credentials,
+        }))
+    }
+
+    /// Use up `challenge` in its own transaction, so it can't be answered
+    /// again even if verifying the response to it fails.
+    async fn consume_passkey_challenge(
+        &self,
+        challenge: &[u8],
+    ) -> anyhow::Result<Option<AuthPasskeyChallenge>> {
+        let mut tx = self.begin(Identity::system()).await?;
+        let now = self.runtime.unix_timestamp();
+        let pending = BuiltinAuthModel::new(&mut tx)
+            .consume_passkey_challenge(challenge, now)
+            .await?;
+        self.commit(tx, "builtin_auth_consume_passkey_challenge")
+            .await?;
+        Ok(pending)
+    }
+
+    /// Finish registering a passkey with the client's response to the
+    /// registration options. Returns the passkey's credential ID.
+    pub async fn builtin_auth_register_passkey(
+        &self,
+        identity: Identity,
+        client_data_json: String,
+        attestation_object: String,
+    ) -> anyhow::Result<String> {
+        let user_id = builtin_auth_user_id(&identity)?;
+        let client_data_json = decode_passkey_response("clientDataJSON", &client_data_json)?;
+        let attestation_object = decode_passkey_response("attestationObject", &attestation_object)?;
+        let challenge = client_data_challenge(&client_data_json)?;
+        let pending = self.consume_passkey_challenge(&challenge).await?;
+        let mut tx = self.begin(Identity::system()).await?;
+        let rp = self.passkey_relying_party(&mut tx).await?;
+        let mut model = BuiltinAuthModel::new(&mut tx);
+        if pending.and_then(|pending| pending.user_id).as_deref() != Some(user_id) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidPasskeyChallenge",
+                "Passkey challenge is invalid or has expired"
+            ));
+        }
+        let passkey = verify_passkey_registration(
+            &rp.as_ref(),
+            &challenge,
+            &client_data_json,
+            &attestation_object,
+        )?;
+        let credential_id = encode_urlsafe(&passkey.credential_id);
+        model
+            .insert_passkey(AuthPasskey {
+                user_id: user_id.to_string(),
+                credential_id: credential_id.clone(),
+                public_key: encode_urlsafe(&passkey.public_key),
+                sign_count: passkey.sign_count,
+            })
+            .await?;
+        self.commit(tx, "builtin_auth_register_passkey").await?;
+        Ok(credential_id)
+    }
+
+    /// Start signing in with a passkey. Returns options for
+    /// `PublicKeyCredential.parseRequestOptionsFromJSON()`. Passkeys are
+    /// discoverable, so the user picks theirs without entering an email.
+    pub async fn builtin_auth_passkey_sign_in_options(&self) -> anyhow::Result<JsonValue> {
+        let mut tx = self.begin(Identity::system()).await?;
+        let rp = self.passkey_relying_party(&mut tx).await?;
+        let expires = self.runtime.unix_timestamp() + *BUILTIN_AUTH_PASSKEY_CHALLENGE_TTL;
+        let challenge = BuiltinAuthModel::new(&mut tx)
+            .create_passkey_challenge(None, expires)
+            .await?;
+        self.commit(tx, "builtin_auth_passkey_sign_in_options")
+            .await?;
+        Ok(json!({
+            "challenge": encode_urlsafe(&challenge),
+            "rpId": rp.id,
+            "timeout": BUILTIN_AUTH_PASSKEY_CHALLENGE_TTL.as_millis() as u64,
+            "userVerification": "required",
+        }))
+    }
+
+    /// Sign in with the client's response to the sign-in options.
+    pub async fn builtin_auth_passkey_sign_in(
+        &self,
+        credential_id: String,
+        client_data_json: String,
+        authenticator_data: String,
+        signature: String,
+    ) -> anyhow::Result<BuiltinAuthTokens> {
+        let client_data_json = decode_passkey_response("clientDataJSON", &client_data_json)?;
+        let authenticator_data = decode_passkey_response("authenticatorData", &authenticator_data)?;
+        let signature = decode_passkey_response("signature", &signature)?;
+        let challenge = client_data_challenge(&client_data_json)?;
+        let pending = self.consume_passkey_challenge(&challenge).await?;
+        let mut tx = self.begin(Identity::system()).await?;
+        let rp = self.passkey_relying_party(&mut tx).await?;
+        let mut model = BuiltinAuthModel::new(&mut tx);
+        if !pending.is_some_and(|pending| pending.user_id.is_none()) {
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "InvalidPasskeyChallenge",
+                "Passkey challenge is invalid or has expired"
+            ));
+        }
+        let unknown_passkey = || {
+            ErrorMetadata::unauthenticated("InvalidPasskeyAssertion", "Passkey isn't registered")
+        };
+        let Some(passkey) = model.get_passkey(&credential_id).await? else {
+            anyhow::bail!(unknown_passkey());
+        };
+        let sign_count = verify_passkey_assertion(
+            &rp.as_ref(),
+            &challenge,
+            &client_data_json,
+            &authenticator_data,
+            &signature,
+            &decode_urlsafe(&passkey.public_key)?,
+            passkey.sign_count,
+        )?;
+        let Some(user) = model.get_user(&passkey.user_id).await? else {
+            anyhow::bail!(unknown_passkey());
+        };
+        model.update_passkey_sign_count(passkey, sign_count).await?;
+        let tokens = self
+            .start_builtin_auth_session(&mut tx, &user.into_value())
+            .await?;
+        self.commit(tx, "builtin_auth_passkey_sign_in").await?;
+        Ok(tokens)
+    }
+}
@@ -2,6 +2,7 @@ use std::{
     cmp,
     collections::BTreeMap,
     mem,
+    num::NonZeroUsize,
     sync::{
         atomic::{
             AtomicU32,
@@ -28,6 +29,8 @@ use common::{
     knobs::{
         DATABASE_UDF_SYSTEM_TIMEOUT,
         DATABASE_UDF_USER_TIMEOUT,
+        FUNCTION_MAX_ARGS_SIZE,
+        UDF_CACHE_MAX_QUERY_CACHE_KEYS,
     },
     query_journal::QueryJournal,
     runtime::Runtime,
@@ -74,7 +77,12 @@ use metrics::{
     succeed_get_timer,
     GoReason,
 };
+use model::modules::{
+    module_versions::QueryCacheKey,
+    ModuleModel,
+};
 use parking_lot::Mutex;
+use serde_json::Value as JsonValue;
 use udf::{
     validation::ValidatedPathAndArgs,
     FunctionOutcome,
@@ -133,6 +141,9 @@ pub struct RequestedCacheKey {
     path: PublicFunctionPath,
     args: ConvexArray,
     identity: IdentityCacheKey,
+    // Set if the query declared a cache key and the identity is one we can
+    // share results between.
+    identity_claims: Option<IdentityClaimsKey>,
     journal: QueryJournal,
     allowed_visibility: AllowedVisibility,
 }
@@ -140,18 +151,31 @@ pub struct RequestedCacheKey {
 impl RequestedCacheKey {
     // In order from most specific to least specific.
     fn _possible_cache_keys(&self) -> Vec<StoredCacheKey> {
-        vec![
-            self.precise_cache_key(),
-            StoredCacheKey {
+        let mut keys = vec![self.precise_cache_key()];
+        if let Some(identity_claims) = &self.identity_claims {
+            // Include queries that read `ctx.auth` but declared the claims they
+            // depend on.
+            keys.push(StoredCacheKey {
                 instance: self.instance,
                 path: self.path.clone(),
                 args: self.args.clone(),
-                // Include queries that did not read `ctx.auth`.
                 identity: None,
+                identity_claims: Some(identity_claims.clone()),
                 journal: self.journal.clone(),
                 allowed_visibility: self.allowed_visibility,
-            },
-        ]
+            });
+        }
+        keys.push(StoredCacheKey {
+            instance: self.instance,
+            path: self.path.clone(),
+            args: self.args.clone(),
+            // Include queries that did not read `ctx.auth`.
+            identity: None,
+            identity_claims: None,
+            journal: self.journal.clone(),
+            allowed_visibility: self.allowed_visibility,
+        });
+        keys
     }
 
     fn precise_cache_key(&self) -> StoredCacheKey {
@@ -160,6 +184,7 @@ impl RequestedCacheKey {
             path: self.path.clone(),
             args: self.args.clone(),
             identity: Some(self.identity.clone()),
+            identity_claims: None,
             journal: self.journal.clone(),
             allowed_visibility: self.allowed_visibility,
         }
@@ -184,16 +209,17 @@ impl RequestedCacheKey {
     }
 
     fn cache_key_after_execution(&self, outcome: &UdfOutcome) -> StoredCacheKey {
-        let identity = if outcome.observed_identity {
-            Some(self.identity.clone())
-        } else {
-            None
+        let (identity, identity_claims) = match &self.identity_claims {
+            _ if !outcome.observed_identity => (None, None),
+            Some(identity_claims) => (None, Some(identity_claims.clone())),
+            None => (Some(self.identity.clone()), None),
         };
         StoredCacheKey {
             instance: self.instance,
             path: self.path.clone(),
             args: self.args.clone(),
             identity,
+            identity_claims,
             journal: outcome.journal.clone(),
             allowed_visibility: self.allowed_visibility,
         }
@@ -206,8 +232,10 @@ pub struct StoredCacheKey {
     instance: InstanceId,
     path: PublicFunctionPath,
     args: ConvexArray,
-    // None means that the query did not read `ctx.auth`.
+    // None means that the query did not read `ctx.auth`, or that it declared
+    // the claims it depends on in `identity_claims`.
     identity: Option<IdentityCacheKey>,
+    identity_claims: Option<IdentityClaimsKey>,
     journal: QueryJournal,
     allowed_visibility: AllowedVisibility,
 }
@@ -220,10 +248,55 @@ impl StoredCacheKey {
             + self.path.heap_size()
             + self.args.heap_size()
             + self.identity.heap_size()
+            + self
+                .identity_claims
+                .as_ref()
+                .map_or(0, |identity_claims| identity_claims.heap_size())
             + self.journal.heap_size()
     }
 }
 
+/// The values of the identity claims a query declared in its `QueryCacheKey`,
+/// serialized as JSON. `None` for unauthenticated callers, so they don't share
+/// results with users that lack the claims.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct IdentityClaimsKey(Option<BTreeMap<String, String>>);
+
+impl IdentityClaimsKey {
+    /// Returns `None` for admin and system identities, which always get their
+    /// own results.
+    fn new(identity: &Identity, cache_key: &QueryCacheKey) -> anyhow::Result<Option<Self>> {
+        let attributes = match identity {
+            Identity::User(user) => user.attributes.clone(),
+            Identity::Unknown => return Ok(Some(Self(None))),
+            Identity::InstanceAdmin(_) | Identity::System(_) | Identity::ActingUser(..) => {
+                return Ok(None)
+            },
+        };
+        let JsonValue::Object(attributes) = JsonValue::try_from(attributes)? else {
+            anyhow::bail!("User identity attributes aren't an object");
+        };
+        let claims = cache_key
+            .identity_claims
+            .iter()
+            .map(|claim| {
+                let value = attributes.get(claim).unwrap_or(&JsonValue::Null);
+                anyhow::Ok((claim.clone(), serde_json::to_string(value)?))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Some(Self(Some(claims))))
+    }
+
+    fn heap_size(&self) -> usize {
+        self.0.as_ref().map_or(0, |claims| {
+            claims
+                .iter()
+                .map(|(claim, value)| claim.heap_size() + value.heap_size())
+                .sum()
+        })
+    }
+}
+
 enum CacheEntry {
     Ready(CacheResult),
     Waiting {
@@ -342,11 +415,16 @@ impl<RT: Runtime> CacheManager<RT> {
     ) -> anyhow::Result<(QueryReturn, bool)> {
         let start = self.rt.monotonic_now();
         let identity_cache_key = identity.cache_key();
+        let identity_claims = match self.cache.get_query_cache_key(self.instance_id, &path) {
+            Some(cache_key) => IdentityClaimsKey::new(&identity, &cache_key)?,
+            None => None,
+        };
         let requested_key = RequestedCacheKey {
             instance: self.instance_id,
             path,
             args,
             identity: identity_cache_key,
+            identity_claims,
             journal: journal.unwrap_or_else(QueryJournal::new),
             allowed_visibility: caller.allowed_visibility(),
         };
@@ -555,6 +633,7 @@ impl<RT: Runtime> CacheManager<RT> {
                     path.clone(),
                     args.clone(),
                     UdfType::Query,
+                    *FUNCTION_MAX_ARGS_SIZE,
                 )
                 .await?;
 
@@ -571,6 +650,22 @@ impl<RT: Runtime> CacheManager<RT> {
                         (tx, query_outcome)
                     },
                     Ok((path_and_args, returns_validator)) => {
+                        // Remember whether the query declared a cache key, so
+                        // later requests can look for results shared on it.
+                        let cache_key = if path_and_args.path().udf_path.is_system() {
+                            None
+                        } else {
+                            ModuleModel::new(&mut tx)
+                                .get_analyzed_function_at_version(
+                                    path_and_args.path(),
+                                    path_and_args.module_version(),
+                                )
+                                .await?
+                                .ok()
+                                .and_then(|function| function.cache_key)
+                        };
+                        self.cache
+                            .set_query_cache_key(self.instance_id, path.clone(), cache_key);
                         let component = path_and_args.path().component;
                         let (mut tx, outcome) = self
                             .function_router
@@ -721,6 +816,9 @@ struct Inner {
     cache: LruCache<StoredCacheKey, CacheEntry>,
     size: usize,
     size_limit: usize,
+    // Cache keys declared by the queries we've run, as of their last execution.
+    // Bounded so deleted functions' keys are eventually evicted.
+    query_cache_keys: LruCache<(InstanceId, PublicFunctionPath), QueryCacheKey>,
 
     next_waiting_id: u64,
 }
@@ -735,6 +833,10 @@ impl QueryCache {
         let inner = Inner {
             cache: LruCache::unbounded(),
             size: 0,
+            query_cache_keys: LruCache::new(
+                NonZeroUsize::new(*UDF_CACHE_MAX_QUERY_CACHE_KEYS)
+                    .expect("UDF_CACHE_MAX_QUERY_CACHE_KEYS must be positive"),
+            ),
             next_waiting_id: 0,
             size_limit,
         };
@@ -849,6 +951,35 @@ impl QueryCache {
     fn put_ready(&self, key: StoredCacheKey, result: CacheResult) {
         self.inner.lock().put_ready(key, result)
     }
+
+    fn get_query_cache_key(
+        &self,
+        instance: InstanceId,
+        path: &PublicFunctionPath,
+    ) -> Option<QueryCacheKey> {
+        self.inner
+            .lock()
+            .query_cache_keys
+            .get(&(instance, path.clone()))
+            .cloned()
+    }
+
+    fn set_query_cache_key(
+        &self,
+        instance: InstanceId,
+        path: PublicFunctionPath,
+        cache_key: Option<QueryCacheKey>,
+    ) {
+        let mut inner = self.inner.lock();
+        match cache_key {
+            Some(cache_key) => {
+                inner.query_cache_keys.put((instance, path), cache_key);
+            },
+            None => {
+                inner.query_cache_keys.pop(&(instance, path));
+            },
+        }
+    }
 }
 
 impl Inner {
@@ -1010,6 +1141,12 @@ mod tests {
         types::AllowedVisibility,
     };
     use database::Token;
+    use keybroker::{
+        testing::TestUserIdentity,
+        Identity,
+        UserIdentity,
+    };
+    use model::modules::module_versions::QueryCacheKey;
     use proptest::{
         prelude::{
             Arbitrary,
@@ -1032,6 +1169,7 @@ mod tests {
 
     use super::{
         CacheResult,
+        IdentityClaimsKey,
         InstanceId,
         QueryCache,
         StoredCacheKey,
@@ -1064,13 +1202,16 @@ mod tests {
             identity: Some(IdentityCacheKey::InstanceAdmin(with_extra_capacity!(
                 "admin".to_string()
             ))),
+            identity_claims: None,
             journal: QueryJournal {
                 end_cursor: Some(Cursor {
                     position: CursorPosition::After(IndexKeyBytes(with_extra_capacity!(
                         b"key".to_vec()
                     ))),
                     query_fingerprint: with_extra_capacity!(b"fingerprint".to_vec()),
+                    index_fingerprint: Some(with_extra_capacity!(b"index".to_vec())),
                 }),
+                page_split: None,
             },
             allowed_visibility: AllowedVisibility::All,
         }
@@ -1093,6 +1234,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_identity_claims_key() -> anyhow::Result<()> {
+        let cache_key = QueryCacheKey {
+            identity_claims: vec!["subject".to_string()],
+        };
+        let alice = UserIdentity::test();
+        let mut alice_renamed = UserIdentity::test();
+        alice_renamed.attributes.name = Some("Alice Renamed".to_string());
+        let alice_key = IdentityClaimsKey::new(&Identity::user(alice.clone()), &cache_key)?;
+        assert!(alice_key.is_some());
+        assert_eq!(
+            alice_key,
+            IdentityClaimsKey::new(&Identity::user(alice_renamed.clone()), &cache_key)?
+        );
+
+        let name_cache_key = QueryCacheKey {
+            identity_claims: vec!["name".to_string()],
+        };
+        assert_ne!(
+            IdentityClaimsKey::new(&Identity::user(alice), &name_cache_key)?,
+            IdentityClaimsKey::new(&Identity::user(alice_renamed), &name_cache_key)?,
+        );
+
+        assert_ne!(
+            IdentityClaimsKey::new(&Identity::Unknown, &cache_key)?,
+            alice_key
+        );
+        assert_eq!(
+            IdentityClaimsKey::new(&Identity::system(), &cache_key)?,
+            None
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_put_waiting_excess_capacity() {
         let cache = QueryCache::new(usize::MAX);
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use common::{
+    bootstrap_model::index::DeveloperIndexMetadata,
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
+    runtime::Runtime,
+    types::{
+        EnvVarName,
+        TableName,
+    },
+};
+use database::{
+    IndexModel,
+    Transaction,
+};
+use model::{
+    cron_jobs::{
+        types::CronIdentifier,
+        CronModel,
+    },
+    environment_variables::EnvironmentVariablesModel,
+    file_storage::FileStorageModel,
+    scheduled_jobs::SCHEDULED_JOBS_TABLE,
+};
+use value::TableNamespace;
+
+/// Everything a component owns in the deployment. Components can only read
+/// and write resources in their own namespace, so this is the full set of
+/// data a component can touch.
+#[derive(Debug)]
+pub struct ComponentAudit {
+    pub path: ComponentPath,
+    /// User tables and the number of documents in each.
+    pub tables: BTreeMap<TableName, u64>,
+    /// Database, text and vector indexes on the component's tables.
+    pub indexes: Vec<DeveloperIndexMetadata>,
+    pub num_stored_files: u64,
+    /// Scheduled functions in the component's queue, in any state.
+    pub num_scheduled_jobs: u64,
+    pub crons: Vec<CronIdentifier>,
+    /// Names of environment variables the component can read. Only the app
+    /// can read the deployment's environment variables.
+    pub environment_variables: Vec<EnvVarName>,
+}
+
+pub async fn audit_component<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    component: ComponentId,
+) -> anyhow::Result<ComponentAudit> {
+    let path = tx.must_component_path(component)?;
+    let namespace = TableNamespace::from(component);
+    let table_names: Vec<TableName> = tx
+        .table_mapping()
+        .namespace(namespace)
+        .iter_active_user_tables()
+        .map(|(_, _, name)| name.clone())
+        .collect();
+    let mut tables = BTreeMap::new();
+    for table_name in table_names {
+        let count = tx.must_count(namespace, &table_name).await?;
+        tables.insert(table_name, count);
+    }
+    let indexes = IndexModel::new(tx)
+        .get_application_indexes(namespace)
+        .await?
+        .into_iter()
+        .map(|index| index.into_value())
+        .collect();
+    let num_stored_files = FileStorageModel::new(tx, namespace)
+        .get_total_storage_count()
+        .await?;
+    let num_scheduled_jobs = tx.must_count(namespace, &SCHEDULED_JOBS_TABLE).await?;
+    let crons = CronModel::new(tx, component)
+        .list()
+        .await?
+        .into_keys()
+        .collect();
+    let environment_variables = if component.is_root() {
+        EnvironmentVariablesModel::new(tx)
+            .get_all()
+            .await?
+            .into_keys()
+            .collect()
+    } else {
+        vec![]
+    };
+    Ok(ComponentAudit {
+        path,
+        tables,
+        indexes,
+        num_stored_files,
+        num_scheduled_jobs,
+        crons,
+        environment_variables,
+    })
+}
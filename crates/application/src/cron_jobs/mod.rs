@@ -252,6 +252,23 @@ impl<RT: Runtime> CronJobExecutor<RT> {
         Ok(None)
     }
 
+    /// Whether any job is due to run or still running, so tests can wait for
+    /// the executor to catch up after advancing time. Running actions are
+    /// `InProgress` and keep the `next_ts` they were due at, so they sort
+    /// before any job that isn't due yet.
+    #[cfg(any(test, feature = "testing"))]
+    pub async fn has_due_or_running_jobs(
+        &self,
+        tx: &mut Transaction<RT>,
+        now: Timestamp,
+    ) -> anyhow::Result<bool> {
+        let mut job_stream = self.stream_jobs_to_run(tx);
+        Ok(job_stream
+            .try_next()
+            .await?
+            .is_some_and(|job| job.state == CronJobState::InProgress || job.next_ts <= now))
+    }
+
     #[try_stream(boxed, ok = ParsedDocument<CronJob>, error = anyhow::Error)]
     async fn stream_jobs_to_run<'a>(&'a self, tx: &'a mut Transaction<RT>) {
         let namespaces: Vec<_> = tx
@@ -0,0 +1,242 @@
+use std::{
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    components::PublicFunctionPath,
+    document::ParsedDocument,
+    errors::report_error,
+    execution_context::ExecutionContext,
+    knobs::DATA_MIGRATION_BATCH_SIZE,
+    runtime::Runtime,
+    types::FunctionCaller,
+    RequestId,
+};
+use database::Database;
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use model::data_migrations::{
+    types::{
+        DataMigration,
+        DataMigrationState,
+    },
+    DataMigrationsModel,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use usage_tracking::FunctionUsageTracker;
+use value::{
+    ConvexArray,
+    ConvexValue,
+    ResolvedDocumentId,
+};
+
+use crate::{
+    application_function_runner::ApplicationFunctionRunner,
+    function_log::FunctionExecutionLog,
+    metrics::log_worker_starting,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Argument passed to a data migration mutation for each batch.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DataMigrationBatchArgs {
+    cursor: Option<String>,
+    batch_size: usize,
+    dry_run: bool,
+}
+
+/// Value a data migration mutation must return for each batch.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataMigrationBatchResult {
+    cursor: Option<String>,
+    is_done: bool,
+    // JavaScript numbers are floats.
+    num_processed: f64,
+}
+
+/// Runs registered data migrations one batch at a time, in version order.
+///
+/// Each batch runs the migration's mutation with the last checkpointed cursor
+/// and commits the new checkpoint in the same transaction as the mutation's
+/// writes, so a batch is applied exactly once even if the worker restarts.
+/// Dry runs execute the same batches but never commit the mutation's writes;
+/// only the checkpoint is saved. A finished dry run blocks later migrations
+/// until an admin starts its real run.
+pub struct DataMigrationWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    runner: Arc<ApplicationFunctionRunner<RT>>,
+    function_log: FunctionExecutionLog<RT>,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> DataMigrationWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        runner: Arc<ApplicationFunctionRunner<RT>>,
+        function_log: FunctionExecutionLog<RT>,
+    ) -> impl Future<Output = ()> + Send {
+        let mut worker = Self {
+            runtime,
+            database,
+            runner,
+            function_log,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        };
+        async move {
+            loop {
+                if let Err(e) = worker.run_once().await {
+                    report_error(&mut e.context("DataMigrationWorker died")).await;
+                    let delay = worker.backoff.fail(&mut worker.runtime.rng());
+                    worker.runtime.wait(delay).await;
+                } else {
+                    worker.backoff.reset();
+                }
+            }
+        }
+    }
+
+    /// Run the next batch of the next runnable migration, or wait for the
+    /// _data_migrations table to change if there's nothing to run.
+    async fn run_once(&mut self) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let next = DataMigrationsModel::new(&mut tx).next_runnable().await?;
+        match next {
+            Some(migration) => {
+                let status = log_worker_starting("DataMigration");
+                self.run_batch(migration).await?;
+                drop(status);
+            },
+            None => {
+                let token = tx.into_token()?;
+                let subscription = self.database.subscribe(token).await?;
+                subscription.wait_for_invalidation().await;
+            },
+        }
+        Ok(())
+    }
+
+    async fn run_batch(&self, migration: ParsedDocument<DataMigration>) -> anyhow::Result<()> {
+        let start = self.runtime.monotonic_now();
+        let (id, migration) = migration.into_id_and_value();
+        let usage_tracker = FunctionUsageTracker::new();
+        let tx = self
+            .database
+            .begin_with_usage(Identity::Unknown, usage_tracker.clone())
+            .await?;
+        let caller = FunctionCaller::DataMigration;
+        let context = ExecutionContext::new(RequestId::new(), &caller);
+        let args = DataMigrationBatchArgs {
+            cursor: migration.state.cursor().map(String::from),
+            batch_size: *DATA_MIGRATION_BATCH_SIZE,
+            dry_run: migration.dry_run,
+        };
+        let args =
+            ConvexArray::try_from(vec![ConvexValue::try_from(serde_json::to_value(args)?)?])?;
+        let (mut tx, outcome) = self
+            .runner
+            .run_mutation_no_udf_log(
+                tx,
+                PublicFunctionPath::Component(migration.path.clone()),
+                args,
+                caller.allowed_visibility(),
+                context.clone(),
+            )
+            .await?;
+        let stats = tx.take_stats();
+        let result = match &outcome.result {
+            Ok(value) => parse_batch_result(value.json_value()),
+            Err(e) => Err(e.to_string()),
+        };
+        let num_processed = migration.state.num_processed();
+        let state = match result {
+            Ok(batch) if batch.is_done && migration.dry_run => {
+                DataMigrationState::DryRunCompleted {
+                    num_processed: num_processed + batch.num_processed as u64,
+                    completed_ts: *tx.begin_timestamp(),
+                }
+            },
+            Ok(batch) if batch.is_done => DataMigrationState::Completed {
+                num_processed: num_processed + batch.num_processed as u64,
+                completed_ts: *tx.begin_timestamp(),
+            },
+            Ok(batch) => DataMigrationState::InProgress {
+                cursor: batch.cursor,
+                num_processed: num_processed + batch.num_processed as u64,
+            },
+            Err(error) => DataMigrationState::Failed {
+                cursor: migration.state.cursor().map(String::from),
+                num_processed,
+                error,
+            },
+        };
+        if migration.dry_run || matches!(state, DataMigrationState::Failed { .. }) {
+            // Discard the mutation's writes and only save the checkpoint.
+            self.save_state(id, state).await?;
+        } else {
+            DataMigrationsModel::new(&mut tx)
+                .update_state(id, state)
+                .await?;
+            if let Err(e) = self
+                .database
+                .commit_with_write_source(tx, "data_migration_batch")
+                .await
+            {
+                if !e.is_deterministic_user_error() {
+                    return Err(e);
+                }
+                let state = DataMigrationState::Failed {
+                    cursor: migration.state.cursor().map(String::from),
+                    num_processed,
+                    error: e.user_facing_message(),
+                };
+                self.save_state(id, state).await?;
+            }
+        }
+        self.function_log.log_mutation(
+            outcome,
+            stats,
+            start.elapsed(),
+            caller,
+            usage_tracker,
+            context,
+        );
+        Ok(())
+    }
+
+    async fn save_state(
+        &self,
+        id: ResolvedDocumentId,
+        state: DataMigrationState,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        DataMigrationsModel::new(&mut tx)
+            .update_state(id, state)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "data_migration_save_state")
+            .await?;
+        Ok(())
+    }
+}
+
+fn parse_batch_result(value: JsonValue) -> Result<DataMigrationBatchResult, String> {
+    serde_json::from_value(value).map_err(|e| {
+        format!(
+            "Data migration must return {{ cursor: string | null, isDone: boolean, numProcessed: \
+             number }}: {e}"
+        )
+    })
+}
@@ -11,20 +11,28 @@ use async_trait::async_trait;
 use common::{
     auth::AuthInfo,
     bootstrap_model::{
-        components::definition::ComponentDefinitionMetadata,
+        components::{
+            definition::ComponentDefinitionMetadata,
+            ComponentState,
+        },
         schema::{
             SchemaMetadata,
             SchemaState,
         },
     },
     components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentDefinitionId,
         ComponentDefinitionPath,
         ComponentId,
         ComponentName,
         ComponentPath,
+        PublicFunctionPath,
         Resource,
     },
     errors::JsError,
+    execution_context::ExecutionContext,
+    knobs::STATIC_ASSETS_MAX_TOTAL_SIZE,
     runtime::{
         Runtime,
         UnixTimestamp,
@@ -32,14 +40,17 @@ use common::{
     types::{
         EnvVarName,
         EnvVarValue,
+        FunctionCaller,
         ModuleEnvironment,
         NodeDependency,
     },
+    RequestId,
 };
 use database::{
     BootstrapComponentsModel,
     IndexModel,
     Token,
+    Transaction,
     WriteSource,
     SCHEMAS_TABLE,
 };
@@ -48,6 +59,10 @@ use fastrace::{
     future::FutureExt as _,
     Span,
 };
+use isolate::environment::unindexed_queries::{
+    find_unindexed_queries,
+    UnindexedQuery,
+};
 use keybroker::Identity;
 use maplit::btreeset;
 use model::{
@@ -61,10 +76,12 @@ use model::{
             ComponentDefinitionConfigModel,
             ComponentDefinitionDiff,
             ComponentDiff,
+            ComponentDiffType,
             SchemaChange,
         },
         file_based_routing::file_based_exports,
         type_checking::{
+            validate_component_definitions,
             CheckedComponent,
             InitializerEvaluator,
             TypecheckContext,
@@ -97,6 +114,16 @@ use model::{
         types::SourcePackage,
         upload_download::download_package,
     },
+    static_assets::{
+        types::{
+            validate_static_asset_path,
+            StaticAsset,
+            StaticAssetFile,
+            StaticAssetsConfig,
+        },
+        upload_static_asset,
+        StaticAssetsModel,
+    },
     udf_config::types::UdfConfig,
 };
 use rand::Rng;
@@ -112,6 +139,10 @@ use udf::EvaluateAppDefinitionsResult;
 use usage_tracking::FunctionUsageTracker;
 use value::{
     identifier::Identifier,
+    sha256::Sha256,
+    ConvexArray,
+    ConvexObject,
+    ConvexValue,
     DeveloperDocumentId,
     ResolvedDocumentId,
     TableNamespace,
@@ -179,6 +210,7 @@ impl<RT: Runtime> Application<RT> {
                 environment_variables.clone(),
             )
             .await?;
+        validate_component_definitions(&evaluated_components)?;
         // Build and typecheck the component tree. We don't strictly need to do this
         // before `/finish_push`, but it's better to fail fast here on errors before
         // waiting for schema backfills to complete.
@@ -196,6 +228,11 @@ impl<RT: Runtime> Application<RT> {
         let schema_change = self
             ._handle_schema_change_in_start_push(&app, &evaluated_components, dry_run)
             .await?;
+        let unindexed_queries = Self::find_unindexed_queries(config, &evaluated_components);
+        let static_assets = match &config.static_assets {
+            Some(static_assets) => Some(self.upload_static_assets(static_assets).await?),
+            None => None,
+        };
         self.database
             .load_indexes_into_memory(btreeset! { SCHEMAS_TABLE.clone() })
             .await?;
@@ -220,10 +257,106 @@ impl<RT: Runtime> Application<RT> {
             analysis: evaluated_components,
             app,
             schema_change,
+            unindexed_queries,
+            static_assets,
         };
         Ok(resp)
     }
 
+    /// Queries in each component definition's functions that look like they
+    /// filter without an index, to warn about before they hit scan limits.
+    fn find_unindexed_queries(
+        config: &ProjectConfig,
+        evaluated_components: &BTreeMap<ComponentDefinitionPath, EvaluatedComponentDefinition>,
+    ) -> BTreeMap<ComponentDefinitionPath, Vec<UnindexedQuery>> {
+        let app_functions = (
+            ComponentDefinitionPath::root(),
+            &config.app_definition.functions,
+        );
+        let component_functions = config
+            .component_definitions
+            .iter()
+            .map(|definition| (definition.definition_path.clone(), &definition.functions));
+        std::iter::once(app_functions)
+            .chain(component_functions)
+            .filter_map(|(definition_path, functions)| {
+                let schema = evaluated_components
+                    .get(&definition_path)
+                    .and_then(|definition| definition.schema.as_ref());
+                // Node actions can't query the database directly.
+                let isolate_functions = functions
+                    .iter()
+                    .filter(|module| module.environment == ModuleEnvironment::Isolate);
+                let queries = find_unindexed_queries(isolate_functions, schema);
+                (!queries.is_empty()).then_some((definition_path, queries))
+            })
+            .collect()
+    }
+
+    /// Check the push's static assets and upload them to file storage,
+    /// reusing the stored objects of files that haven't changed.
+    async fn upload_static_assets(
+        &self,
+        config: &StaticAssetsConfig,
+    ) -> anyhow::Result<Vec<StaticAsset>> {
+        let total_size: usize = config.files.iter().map(|file| file.content.len()).sum();
+        anyhow::ensure!(
+            total_size <= *STATIC_ASSETS_MAX_TOTAL_SIZE,
+            ErrorMetadata::bad_request(
+                "StaticAssetsTooLarge",
+                format!(
+                    "Static assets are too large (actual: {total_size} bytes, limit: {} bytes)",
+                    *STATIC_ASSETS_MAX_TOTAL_SIZE
+                ),
+            )
+        );
+        if let Some(spa_fallback) = &config.spa_fallback {
+            anyhow::ensure!(
+                config.files.iter().any(|file| file.path == *spa_fallback),
+                ErrorMetadata::bad_request(
+                    "InvalidSpaFallback",
+                    format!("SPA fallback {spa_fallback} isn't one of the static assets"),
+                )
+            );
+        }
+        let existing: BTreeMap<_, _> = {
+            let mut tx = self.begin(Identity::system()).await?;
+            StaticAssetsModel::new(&mut tx)
+                .list()
+                .await?
+                .into_iter()
+                .map(|document| (document.path.clone(), document.into_value()))
+                .collect()
+        };
+        let mut paths = BTreeSet::new();
+        let mut assets = vec![];
+        for file in &config.files {
+            validate_static_asset_path(&file.path)?;
+            anyhow::ensure!(
+                paths.insert(&file.path),
+                ErrorMetadata::bad_request(
+                    "DuplicateStaticAsset",
+                    format!("Static asset {} is included more than once", file.path),
+                )
+            );
+            let sha256 = Sha256::hash(&file.content);
+            let storage_key = match existing.get(&file.path) {
+                Some(asset) if asset.sha256 == sha256 => asset.storage_key.clone(),
+                _ => upload_static_asset(&self.files_storage, file.content.clone()).await?,
+            };
+            assets.push(StaticAsset {
+                path: file.path.clone(),
+                storage_key,
+                sha256,
+                size: file.content.len().try_into()?,
+                content_type: file.content_type(),
+                cache_control: file.cache_control(),
+                is_spa_fallback: config.spa_fallback.as_ref() == Some(&file.path),
+            });
+        }
+        Ok(assets)
+    }
+
     async fn _handle_schema_change_in_start_push(
         &self,
         app: &CheckedComponent,
@@ -565,6 +698,11 @@ impl<RT: Runtime> Application<RT> {
                         ));
                     }
 
+                    // Run onUnmount hooks while the components' functions still exist.
+                    for path in Self::unmount_hooks(tx, &start_push.app).await? {
+                        self.run_lifecycle_hook(tx, "onUnmount", path).await?;
+                    }
+
                     // Update app state: auth info and UDF server version.
                     let auth_diff = AuthInfoModel::new(tx)
                         .put(start_push.app_auth.clone())
@@ -590,6 +728,17 @@ impl<RT: Runtime> Application<RT> {
                         )
                         .await?;
 
+                    if let Some(static_assets) = &start_push.static_assets {
+                        StaticAssetsModel::new(tx)
+                            .replace_all(static_assets.clone())
+                            .await?;
+                    }
+
+                    // Run onMount hooks for newly created components, parents first.
+                    for path in Self::mount_hooks(start_push, &component_diffs)? {
+                        self.run_lifecycle_hook(tx, "onMount", path).await?;
+                    }
+
                     let diffs = PushComponentDiffs {
                         auth_diff: auth_diff.clone(),
                         component_diffs: component_diffs.clone(),
@@ -610,6 +759,131 @@ impl<RT: Runtime> Application<RT> {
 
         Ok(diff)
     }
+
+    /// The onUnmount hooks of active components that the new app no longer
+    /// mounts.
+    async fn unmount_hooks(
+        tx: &mut Transaction<RT>,
+        app: &CheckedComponent,
+    ) -> anyhow::Result<Vec<CanonicalizedComponentFunctionPath>> {
+        let mounted = checked_component_definitions(app);
+        let mut model = BootstrapComponentsModel::new(tx);
+        let component_paths = model.all_component_paths();
+        let mut hooks = vec![];
+        for component in model.load_all_components().await? {
+            if component.state != ComponentState::Active || component.parent_and_name().is_none() {
+                continue;
+            }
+            let path = component_paths
+                .get(&ComponentId::Child(component.id().into()))
+                .context("Missing path for component")?;
+            if mounted.contains_key(path) {
+                continue;
+            }
+            let definition = model
+                .load_definition_metadata(ComponentDefinitionId::Child(component.definition_id))
+                .await?;
+            if let Some(udf_path) = definition.lifecycle.on_unmount {
+                hooks.push(CanonicalizedComponentFunctionPath {
+                    component: path.clone(),
+                    udf_path,
+                });
+            }
+        }
+        Ok(hooks)
+    }
+
+    /// The onMount hooks of components created by this push, ordered so
+    /// parents run before their children.
+    fn mount_hooks(
+        start_push: &StartPushResponse,
+        component_diffs: &BTreeMap<ComponentPath, ComponentDiff>,
+    ) -> anyhow::Result<Vec<CanonicalizedComponentFunctionPath>> {
+        let definitions = checked_component_definitions(&start_push.app);
+        let mut hooks = vec![];
+        for (path, diff) in component_diffs {
+            if !matches!(diff.diff_type, ComponentDiffType::Create) {
+                continue;
+            }
+            let definition_path = definitions
+                .get(path)
+                .context("Missing definition for created component")?;
+            let evaluated = start_push
+                .analysis
+                .get(*definition_path)
+                .context("Missing analysis for component definition")?;
+            if let Some(ref udf_path) = evaluated.definition.lifecycle.on_mount {
+                hooks.push(CanonicalizedComponentFunctionPath {
+                    component: path.clone(),
+                    udf_path: udf_path.clone(),
+                });
+            }
+        }
+        Ok(hooks)
+    }
+
+    /// Run a lifecycle hook as a mutation inside the push transaction, so its
+    /// writes commit atomically with the push. If the hook throws, the push
+    /// fails.
+    async fn run_lifecycle_hook(
+        &self,
+        tx: &mut Transaction<RT>,
+        hook: &'static str,
+        path: CanonicalizedComponentFunctionPath,
+    ) -> anyhow::Result<()> {
+        let start = self.runtime.monotonic_now();
+        let caller = FunctionCaller::LifecycleHook;
+        let context = ExecutionContext::new(RequestId::new(), &caller);
+        let args = ConvexArray::try_from(vec![ConvexValue::Object(ConvexObject::empty())])?;
+        // The function runner takes ownership of the transaction, so swap in a
+        // placeholder until it's handed back. On error the push transaction is
+        // discarded anyway.
+        let placeholder = self.database.begin(Identity::system()).await?;
+        let push_tx = std::mem::replace(tx, placeholder);
+        let (push_tx, outcome) = self
+            .runner
+            .run_mutation_no_udf_log(
+                push_tx,
+                PublicFunctionPath::Component(path.clone()),
+                args,
+                caller.allowed_visibility(),
+                context.clone(),
+            )
+            .await?;
+        *tx = push_tx;
+        let error = outcome.result.as_ref().err().map(|e| e.to_string());
+        self.function_log.log_mutation(
+            outcome,
+            tx.take_stats(),
+            start.elapsed(),
+            caller,
+            FunctionUsageTracker::new(),
+            context,
+        );
+        if let Some(error) = error {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ComponentLifecycleHookFailed",
+                format!(
+                    "The {hook} hook for component {:?} failed: {error}",
+                    path.component
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The definition of every component in the checked app, by component path.
+fn checked_component_definitions(
+    app: &CheckedComponent,
+) -> BTreeMap<ComponentPath, &ComponentDefinitionPath> {
+    let mut definitions = BTreeMap::new();
+    let mut stack = vec![app];
+    while let Some(node) = stack.pop() {
+        definitions.insert(node.component_path.clone(), &node.definition_path);
+        stack.extend(node.child_components.values());
+    }
+    definitions
 }
 
 struct ApplicationInitializerEvaluator<'a, RT: Runtime> {
@@ -679,6 +953,9 @@ pub struct StartPushRequest {
     pub component_definitions: Vec<ComponentDefinitionConfigJson>,
 
     pub node_dependencies: Vec<NodeDependencyJson>,
+
+    #[serde(default)]
+    pub static_assets: Option<StaticAssetsJson>,
 }
 
 impl StartPushRequest {
@@ -699,6 +976,7 @@ impl StartPushRequest {
                 .into_iter()
                 .map(NodeDependency::from)
                 .collect(),
+            static_assets: self.static_assets.map(TryInto::try_into).transpose()?,
         })
     }
 }
@@ -718,6 +996,12 @@ pub struct StartPushResponse {
     pub app: CheckedComponent,
 
     pub schema_change: SchemaChange,
+
+    // Warnings for the CLI to show. These don't affect `/finish_push`.
+    pub unindexed_queries: BTreeMap<ComponentDefinitionPath, Vec<UnindexedQuery>>,
+
+    // Uploaded static assets, if the push included any.
+    pub static_assets: Option<Vec<StaticAsset>>,
 }
 
 impl From<NodeDependencyJson> for NodeDependency {
@@ -729,6 +1013,47 @@ impl From<NodeDependencyJson> for NodeDependency {
     }
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticAssetsJson {
+    pub files: Vec<StaticAssetFileJson>,
+    pub spa_fallback: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticAssetFileJson {
+    pub path: String,
+    // Base64 encoded.
+    pub content: String,
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+}
+
+impl TryFrom<StaticAssetsJson> for StaticAssetsConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(value: StaticAssetsJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            files: value
+                .files
+                .into_iter()
+                .map(|file| {
+                    Ok(StaticAssetFile {
+                        content: base64::decode(&file.content)
+                            .with_context(|| format!("Invalid contents for {}", file.path))?
+                            .into(),
+                        path: file.path,
+                        content_type: file.content_type,
+                        cache_control: file.cache_control,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+            spa_fallback: value.spa_fallback,
+        })
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AppDefinitionConfigJson {
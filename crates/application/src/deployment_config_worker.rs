@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    runtime::Runtime,
+};
+use database::Database;
+use futures::Future;
+use keybroker::Identity;
+use model::deployment_config::DeploymentConfigModel;
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Keeps the document behind the `_deployment_config` virtual table up to
+/// date, rewriting it whenever the configuration it was gathered from
+/// changes.
+pub struct DeploymentConfigWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> DeploymentConfigWorker<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime: runtime.clone(),
+            database,
+        };
+        async move {
+            tracing::info!("Starting DeploymentConfigWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("DeploymentConfigWorker died")).await;
+                    tracing::error!("Deployment config worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let status = log_worker_starting("DeploymentConfigWorker");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let config = DeploymentConfigModel::new(&mut tx).collect().await?;
+        // Subscribe to what the config was gathered from, and not to the
+        // document it's written to, so writing it doesn't wake us up again.
+        let token = tx.into_token()?;
+
+        let mut tx = self.database.begin(Identity::system()).await?;
+        if DeploymentConfigModel::new(&mut tx).set(config).await? {
+            self.database
+                .commit_with_write_source(tx, "deployment_config_worker")
+                .await?;
+        }
+
+        drop(status);
+        let subscription = self.database.subscribe(token).await?;
+        subscription.wait_for_invalidation().await;
+        Ok(())
+    }
+}
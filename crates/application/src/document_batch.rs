@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use common::{
+    document::DeveloperDocument,
+    knobs::DOCUMENT_BATCH_MAX_OPERATIONS,
+    runtime::Runtime,
+    types::{
+        TableName,
+        Timestamp,
+    },
+};
+use database::{
+    query::TableFilter,
+    Database,
+    PatchValue,
+    Transaction,
+    UserFacingModel,
+};
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
+use keybroker::Identity;
+use model::http_table_access::{
+    HttpTableAccessModel,
+    HttpTableOperation,
+};
+use serde_json::Value as JsonValue;
+use usage_tracking::FunctionUsageTracker;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    DeveloperDocumentId,
+    TableNamespace,
+};
+
+/// Values refer to a document inserted earlier in the batch with an object
+/// whose only field is `$tempId`. Field names can't start with `$`, so this
+/// never collides with user data.
+const TEMP_ID_FIELD: &str = "$tempId";
+
+/// The document an operation applies to.
+#[derive(Clone, Debug)]
+pub enum DocumentBatchTarget {
+    Id(DeveloperDocumentId),
+    /// The temp id given to an insert earlier in the batch.
+    TempId(String),
+}
+
+#[derive(Clone, Debug)]
+pub enum DocumentBatchOperation {
+    Insert {
+        table_name: TableName,
+        temp_id: Option<String>,
+        value: JsonValue,
+    },
+    Patch {
+        target: DocumentBatchTarget,
+        value: JsonValue,
+        expected_version: Option<Timestamp>,
+    },
+    Replace {
+        target: DocumentBatchTarget,
+        value: JsonValue,
+        expected_version: Option<Timestamp>,
+    },
+    Delete {
+        target: DocumentBatchTarget,
+    },
+}
+
+#[derive(Debug)]
+pub enum DocumentBatchOperationResult {
+    Inserted { id: DeveloperDocumentId },
+    Updated { document: DeveloperDocument },
+    Deleted { id: DeveloperDocumentId },
+}
+
+#[derive(Debug)]
+pub struct DocumentBatchResult {
+    pub ts: Timestamp,
+    pub results: Vec<DocumentBatchOperationResult>,
+    /// The ids of the documents inserted with a temp id.
+    pub temp_ids: BTreeMap<String, DeveloperDocumentId>,
+}
+
+/// Apply `operations` in order in a single transaction, so either all of them
+/// commit or none do.
+pub async fn execute_document_batch<RT: Runtime>(
+    database: &Database<RT>,
+    identity: Identity,
+    namespace: TableNamespace,
+    operations: Vec<DocumentBatchOperation>,
+) -> anyhow::Result<DocumentBatchResult> {
+    anyhow::ensure!(
+        operations.len() <= *DOCUMENT_BATCH_MAX_OPERATIONS,
+        ErrorMetadata::bad_request(
+            "TooManyOperations",
+            format!(
+                "Batch has {} operations, but at most {} are allowed",
+                operations.len(),
+                *DOCUMENT_BATCH_MAX_OPERATIONS
+            ),
+        )
+    );
+    let (ts, (results, temp_ids), _) = database
+        .execute_with_overloaded_retries(
+            identity,
+            FunctionUsageTracker::new(),
+            "document_batch",
+            |tx| async { execute_operations(tx, namespace, &operations).await }.into(),
+        )
+        .await?;
+    Ok(DocumentBatchResult {
+        ts,
+        results,
+        temp_ids,
+    })
+}
+
+async fn execute_operations<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    operations: &[DocumentBatchOperation],
+) -> anyhow::Result<(
+    Vec<DocumentBatchOperationResult>,
+    BTreeMap<String, DeveloperDocumentId>,
+)> {
+    let mut results = Vec::with_capacity(operations.len());
+    let mut temp_ids = BTreeMap::new();
+    for (i, operation) in operations.iter().enumerate() {
+        let result = execute_operation(tx, namespace, operation, &mut temp_ids)
+            .await
+            .map_err(|e| e.wrap_error_message(|msg| format!("Operation {i} failed: {msg}")))?;
+        results.push(result);
+    }
+    Ok((results, temp_ids))
+}
+
+async fn execute_operation<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    operation: &DocumentBatchOperation,
+    temp_ids: &mut BTreeMap<String, DeveloperDocumentId>,
+) -> anyhow::Result<DocumentBatchOperationResult> {
+    let result = match operation {
+        DocumentBatchOperation::Insert {
+            table_name,
+            temp_id,
+            value,
+        } => {
+            if let Some(temp_id) = temp_id {
+                anyhow::ensure!(
+                    !temp_ids.contains_key(temp_id),
+                    ErrorMetadata::bad_request(
+                        "DuplicateTempId",
+                        format!("Temp id {temp_id:?} is used by more than one insert"),
+                    )
+                );
+            }
+            HttpTableAccessModel::new(tx)
+                .check(namespace, table_name, HttpTableOperation::Write)
+                .await?;
+            let value = resolve_object(value, temp_ids)?;
+            let id = UserFacingModel::new(tx, namespace)
+                .insert(table_name.clone(), value)
+                .await?;
+            if let Some(temp_id) = temp_id {
+                temp_ids.insert(temp_id.clone(), id);
+            }
+            DocumentBatchOperationResult::Inserted { id }
+        },
+        DocumentBatchOperation::Patch {
+            target,
+            value,
+            expected_version,
+        } => {
+            let id = resolve_target(tx, namespace, target, temp_ids).await?;
+            let value = PatchValue::try_from(resolve_temp_ids(value, temp_ids)?)?;
+            let mut model = UserFacingModel::new(tx, namespace);
+            if let Some(expected_version) = expected_version {
+                model.check_document_version(id, *expected_version).await?;
+            }
+            let document = model.patch(id, value).await?;
+            DocumentBatchOperationResult::Updated { document }
+        },
+        DocumentBatchOperation::Replace {
+            target,
+            value,
+            expected_version,
+        } => {
+            let id = resolve_target(tx, namespace, target, temp_ids).await?;
+            let value = resolve_object(value, temp_ids)?;
+            let mut model = UserFacingModel::new(tx, namespace);
+            if let Some(expected_version) = expected_version {
+                model.check_document_version(id, *expected_version).await?;
+            }
+            let document = model.replace(id, value).await?;
+            DocumentBatchOperationResult::Updated { document }
+        },
+        DocumentBatchOperation::Delete { target } => {
+            let id = resolve_target(tx, namespace, target, temp_ids).await?;
+            UserFacingModel::new(tx, namespace).delete(id).await?;
+            DocumentBatchOperationResult::Deleted { id }
+        },
+    };
+    Ok(result)
+}
+
+/// Resolve the document an update applies to, refusing system tables since
+/// they can only be changed through their own APIs, and tables the identity
+/// can't write to.
+async fn resolve_target<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    target: &DocumentBatchTarget,
+    temp_ids: &BTreeMap<String, DeveloperDocumentId>,
+) -> anyhow::Result<DeveloperDocumentId> {
+    let id = match target {
+        DocumentBatchTarget::Id(id) => *id,
+        DocumentBatchTarget::TempId(temp_id) => lookup_temp_id(temp_id, temp_ids)?,
+    };
+    let table_name = tx
+        .resolve_idv6(id, namespace, TableFilter::ExcludePrivateSystemTables)
+        .context(ErrorMetadata::bad_request(
+            "InvalidId",
+            format!("Table for ID {id} not found"),
+        ))?;
+    anyhow::ensure!(
+        !table_name.is_system(),
+        ErrorMetadata::bad_request(
+            "InvalidTableName",
+            format!("Documents in system table {table_name} can't be modified"),
+        )
+    );
+    HttpTableAccessModel::new(tx)
+        .check(namespace, &table_name, HttpTableOperation::Write)
+        .await?;
+    Ok(id)
+}
+
+fn lookup_temp_id(
+    temp_id: &str,
+    temp_ids: &BTreeMap<String, DeveloperDocumentId>,
+) -> anyhow::Result<DeveloperDocumentId> {
+    temp_ids.get(temp_id).copied().with_context(|| {
+        ErrorMetadata::bad_request(
+            "UnknownTempId",
+            format!("Temp id {temp_id:?} isn't given to an insert earlier in the batch"),
+        )
+    })
+}
+
+fn resolve_object(
+    value: &JsonValue,
+    temp_ids: &BTreeMap<String, DeveloperDocumentId>,
+) -> anyhow::Result<ConvexObject> {
+    ConvexValue::try_from(resolve_temp_ids(value, temp_ids)?)?.try_into()
+}
+
+/// Replace every `{"$tempId": ...}` object in `value` with the id of the
+/// document inserted with that temp id.
+fn resolve_temp_ids(
+    value: &JsonValue,
+    temp_ids: &BTreeMap<String, DeveloperDocumentId>,
+) -> anyhow::Result<JsonValue> {
+    let resolved = match value {
+        JsonValue::Object(fields) => {
+            if let Some(temp_id) = fields.get(TEMP_ID_FIELD) {
+                let JsonValue::String(temp_id) = temp_id else {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "InvalidTempId",
+                        format!("{TEMP_ID_FIELD} must be a string, not {temp_id}"),
+                    ));
+                };
+                anyhow::ensure!(
+                    fields.len() == 1,
+                    ErrorMetadata::bad_request(
+                        "InvalidTempId",
+                        format!("Objects with a {TEMP_ID_FIELD} field can't have other fields"),
+                    )
+                );
+                JsonValue::String(lookup_temp_id(temp_id, temp_ids)?.encode())
+            } else {
+                JsonValue::Object(
+                    fields
+                        .iter()
+                        .map(|(k, v)| Ok((k.clone(), resolve_temp_ids(v, temp_ids)?)))
+                        .collect::<anyhow::Result<_>>()?,
+                )
+            }
+        },
+        JsonValue::Array(items) => JsonValue::Array(
+            items
+                .iter()
+                .map(|v| resolve_temp_ids(v, temp_ids))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        v => v.clone(),
+    };
+    Ok(resolved)
+}
@@ -0,0 +1,324 @@
+use std::collections::BTreeSet;
+
+use common::{
+    bootstrap_model::index::{
+        database_index::{
+            DeveloperDatabaseIndexConfig,
+            IndexedFields,
+        },
+        IndexConfig,
+    },
+    document::DeveloperDocument,
+    knobs::DOCUMENT_QUERY_MAX_LIMIT,
+    paths::FieldPath,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexDescriptor,
+        IndexName,
+        TableName,
+    },
+};
+use database::{
+    query::TableFilter,
+    DeveloperQuery,
+    IndexModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use itertools::Itertools;
+use model::http_table_access::{
+    HttpTableAccessModel,
+    HttpTableOperation,
+};
+use value::{
+    ConvexValue,
+    MaybeValue,
+    TableNamespace,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentFilterOp {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+/// A comparison of a document field against a value.
+#[derive(Clone, Debug)]
+pub struct DocumentFilter {
+    pub field: FieldPath,
+    pub op: DocumentFilterOp,
+    pub value: ConvexValue,
+}
+
+/// A read of a table described without a UDF. Filters are answered with an
+/// index range, so they're limited to what an index can serve: equality on
+/// a prefix of the index's fields followed by comparisons on the next field.
+#[derive(Clone, Debug)]
+pub struct DocumentQuery {
+    pub table_name: TableName,
+    /// The index to read from. If unset, the first index that can serve the
+    /// filters is used.
+    pub index: Option<IndexDescriptor>,
+    pub filters: Vec<DocumentFilter>,
+    pub order: Order,
+    pub limit: usize,
+}
+
+#[derive(Debug)]
+pub struct DocumentQueryResult {
+    pub index_name: IndexName,
+    pub documents: Vec<DeveloperDocument>,
+}
+
+pub async fn query_documents<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    query: DocumentQuery,
+) -> anyhow::Result<DocumentQueryResult> {
+    let DocumentQuery {
+        table_name,
+        index,
+        filters,
+        order,
+        limit,
+    } = query;
+    anyhow::ensure!(
+        !table_name.is_system(),
+        ErrorMetadata::bad_request(
+            "InvalidTableName",
+            format!("System table {table_name} can't be queried"),
+        )
+    );
+    HttpTableAccessModel::new(tx)
+        .check(namespace, &table_name, HttpTableOperation::Read)
+        .await?;
+    anyhow::ensure!(
+        limit <= *DOCUMENT_QUERY_MAX_LIMIT,
+        ErrorMetadata::bad_request(
+            "InvalidLimit",
+            format!(
+                "Limit {limit} is greater than the maximum of {}",
+                *DOCUMENT_QUERY_MAX_LIMIT
+            ),
+        )
+    );
+    if !tx
+        .table_mapping()
+        .namespace(namespace)
+        .name_exists(&table_name)
+    {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "TableNotFound",
+            format!("Table {table_name} does not exist"),
+        ));
+    }
+
+    let candidates = database_indexes(tx, namespace, &table_name).await?;
+    let (index_name, _) = match index {
+        Some(descriptor) => {
+            let Some(candidate) = candidates
+                .iter()
+                .find(|(name, _)| *name.descriptor() == descriptor)
+            else {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "IndexNotFound",
+                    format!(
+                        "Index {table_name}.{descriptor} not found. Available indexes: {}",
+                        describe_indexes(&candidates)
+                    ),
+                ));
+            };
+            if !index_serves_filters(&candidate.1, &filters)? {
+                anyhow::bail!(non_indexable_filter_error(
+                    &table_name,
+                    &filters,
+                    &candidates
+                ));
+            }
+            candidate.clone()
+        },
+        None => {
+            let mut chosen = None;
+            for candidate in &candidates {
+                if index_serves_filters(&candidate.1, &filters)? {
+                    chosen = Some(candidate.clone());
+                    break;
+                }
+            }
+            chosen.ok_or_else(|| non_indexable_filter_error(&table_name, &filters, &candidates))?
+        },
+    };
+
+    let range = filters
+        .into_iter()
+        .map(|DocumentFilter { field, op, value }| match op {
+            DocumentFilterOp::Eq => IndexRangeExpression::Eq(field, MaybeValue(Some(value))),
+            DocumentFilterOp::Gt => IndexRangeExpression::Gt(field, value),
+            DocumentFilterOp::Gte => IndexRangeExpression::Gte(field, value),
+            DocumentFilterOp::Lt => IndexRangeExpression::Lt(field, value),
+            DocumentFilterOp::Lte => IndexRangeExpression::Lte(field, value),
+        })
+        .collect();
+    let query = Query::index_range(IndexRange {
+        index_name: index_name.clone(),
+        range,
+        order,
+    })
+    .limit(limit);
+    let mut query_stream = DeveloperQuery::new(
+        tx,
+        namespace,
+        query,
+        TableFilter::ExcludePrivateSystemTables,
+    )?;
+    let mut documents = vec![];
+    while let Some(document) = query_stream.next(tx, Some(limit)).await? {
+        documents.push(document);
+    }
+    Ok(DocumentQueryResult {
+        index_name,
+        documents,
+    })
+}
+
+/// The enabled database indexes on the table, starting with the indexes every
+/// table has so unfiltered queries read in creation order.
+async fn database_indexes<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    table_name: &TableName,
+) -> anyhow::Result<Vec<(IndexName, IndexedFields)>> {
+    let mut indexes = vec![
+        (
+            IndexName::by_creation_time(table_name.clone()),
+            IndexedFields::creation_time(),
+        ),
+        (IndexName::by_id(table_name.clone()), IndexedFields::by_id()),
+    ];
+    for index in IndexModel::new(tx)
+        .get_application_indexes(namespace)
+        .await?
+    {
+        if index.name.table() != table_name
+            || index.name.is_by_id_or_creation_time()
+            || !index.config.is_enabled()
+        {
+            continue;
+        }
+        if let IndexConfig::Database {
+            developer_config: DeveloperDatabaseIndexConfig { fields },
+            ..
+        } = &index.config
+        {
+            indexes.push((index.name.clone(), fields.clone()));
+        }
+    }
+    Ok(indexes)
+}
+
+/// An index range can only restrict a prefix of the index's fields with
+/// equalities and then the next field with comparisons.
+fn index_serves_filters(
+    indexed_fields: &IndexedFields,
+    filters: &[DocumentFilter],
+) -> anyhow::Result<bool> {
+    let (equality_fields, inequality_field) = split_filter_fields(filters)?;
+    let mut fields = indexed_fields.iter_with_id();
+    let prefix: BTreeSet<_> = fields.by_ref().take(equality_fields.len()).collect();
+    if prefix != equality_fields.iter().collect() {
+        return Ok(false);
+    }
+    Ok(match inequality_field {
+        Some(field) => fields.next() == Some(field),
+        None => true,
+    })
+}
+
+fn split_filter_fields(
+    filters: &[DocumentFilter],
+) -> anyhow::Result<(BTreeSet<FieldPath>, Option<&FieldPath>)> {
+    let mut equality_fields = BTreeSet::new();
+    let mut inequality_field = None;
+    for filter in filters {
+        if filter.op == DocumentFilterOp::Eq {
+            anyhow::ensure!(
+                equality_fields.insert(filter.field.clone()),
+                ErrorMetadata::bad_request(
+                    "InvalidFilter",
+                    format!(
+                        "Field {} is compared for equality more than once",
+                        filter.field
+                    ),
+                )
+            );
+            continue;
+        }
+        match inequality_field {
+            Some(field) if field != &filter.field => {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidFilter",
+                    format!(
+                        "Filters compare both {field} and {} with gt/gte/lt/lte, but an index can \
+                         only serve comparisons on one field",
+                        filter.field
+                    ),
+                ));
+            },
+            _ => inequality_field = Some(&filter.field),
+        }
+    }
+    if let Some(field) = inequality_field {
+        anyhow::ensure!(
+            !equality_fields.contains(field),
+            ErrorMetadata::bad_request(
+                "InvalidFilter",
+                format!("Field {field} can't be compared with both eq and gt/gte/lt/lte"),
+            )
+        );
+    }
+    Ok((equality_fields, inequality_field))
+}
+
+fn describe_indexes(indexes: &[(IndexName, IndexedFields)]) -> String {
+    indexes
+        .iter()
+        .map(|(name, fields)| format!("{} {fields}", name.descriptor()))
+        .join(", ")
+}
+
+fn non_indexable_filter_error(
+    table_name: &TableName,
+    filters: &[DocumentFilter],
+    indexes: &[(IndexName, IndexedFields)],
+) -> anyhow::Error {
+    let (equality_fields, inequality_field) = match split_filter_fields(filters) {
+        Ok(fields) => fields,
+        Err(e) => return e,
+    };
+    let suggested_fields = equality_fields
+        .iter()
+        .chain(inequality_field)
+        .map(|field| format!("\"{field}\""))
+        .join(", ");
+    ErrorMetadata::bad_request(
+        "NonIndexableFilter",
+        format!(
+            "No index on {table_name} can serve this filter. Filters need an index whose fields \
+             start with the fields compared with eq, in any order, followed by the field \
+             compared with gt/gte/lt/lte. Available indexes: {}. To serve this filter, add \
+             `.index(\"by_...\", [{suggested_fields}])` to {table_name} in your schema. For \
+             more information see https://docs.convex.dev/using/indexes.",
+            describe_indexes(indexes)
+        ),
+    )
+    .into()
+}
@@ -21,7 +21,10 @@ use mime2ext::mime2ext;
 use model::{
     exports::types::ExportRequestor,
     file_storage::{
-        types::FileStorageEntry,
+        types::{
+            FileEncryptionMetadata,
+            FileStorageEntry,
+        },
         FILE_STORAGE_TABLE,
         FILE_STORAGE_VIRTUAL_TABLE,
     },
@@ -91,6 +94,7 @@ pub async fn write_storage_table<'a, 'b: 'a, RT: Runtime>(
                 size: Some(file_storage_entry.size),
                 content_type: file_storage_entry.content_type.clone(),
                 internal_id: Some(file_storage_entry.storage_id.to_string()),
+                encryption: file_storage_entry.encryption.clone(),
             }))
             .await?;
     }
@@ -162,4 +166,6 @@ pub struct FileStorageZipMetadata {
     pub size: Option<i64>,
     pub content_type: Option<String>,
     pub internal_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<FileEncryptionMetadata>,
 }
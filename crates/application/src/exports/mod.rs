@@ -38,9 +38,12 @@ use futures::{
 use itertools::Itertools;
 use keybroker::Identity;
 use maplit::btreemap;
-use model::exports::types::{
-    ExportFormat,
-    ExportRequestor,
+use model::{
+    exports::types::{
+        ExportFormat,
+        ExportRequestor,
+    },
+    preview_deployments::PreviewDeploymentsModel,
 };
 use serde_json::json;
 use shape_inference::export_context::{
@@ -64,12 +67,14 @@ use value::{
 
 use crate::exports::{
     export_storage::write_storage_table,
+    parquet::construct_parquet_snapshot,
     worker::ExportWorker,
     zip_uploader::ZipSnapshotUpload,
 };
 
 mod export_storage;
 mod metrics;
+mod parquet;
 #[cfg(test)]
 mod tests;
 pub mod worker;
@@ -92,11 +97,14 @@ where
     let (ts, tables, component_ids_to_paths, by_id_indexes, system_tables) = {
         let mut tx = worker.database.begin(Identity::system()).await?;
         let by_id_indexes = IndexModel::new(&mut tx).by_id_indexes().await?;
+        // Preview deployments' tables aren't part of the deployment's data.
+        let preview_namespaces = PreviewDeploymentsModel::new(&mut tx).namespaces().await?;
         let snapshot = worker.database.snapshot(tx.begin_timestamp())?;
         let table_summaries = snapshot.must_table_summaries()?;
         let tables: BTreeMap<_, _> = snapshot
             .table_registry
             .iter_active_user_tables()
+            .filter(|(_, table_namespace, ..)| !preview_namespaces.contains(table_namespace))
             .map(|(tablet_id, table_namespace, table_number, table_name)| {
                 (
                     tablet_id,
@@ -150,6 +158,28 @@ where
             let zip_object_key = upload.complete().await?;
             Ok((*ts, zip_object_key, usage))
         },
+        ExportFormat::Parquet => {
+            let mut upload = storage.start_upload().await?;
+            let (sender, receiver) = mpsc::channel::<Bytes>(1);
+            let uploader =
+                upload.try_write_parallel_and_hash(ReceiverStream::new(receiver).map(Ok));
+            let writer = ChannelWriter::new(sender, 5 * (1 << 20));
+            let usage = FunctionUsageTracker::new();
+
+            let zipper = construct_parquet_snapshot(
+                worker,
+                writer,
+                tables,
+                component_ids_to_paths,
+                ts,
+                by_id_indexes,
+                usage.clone(),
+                update_progress,
+            );
+            let (_, ()) = try_join!(uploader, zipper)?;
+            let zip_object_key = upload.complete().await?;
+            Ok((*ts, zip_object_key, usage))
+        },
     }
 }
 
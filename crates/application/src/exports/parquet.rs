@@ -0,0 +1,711 @@
+//! Parquet snapshot exports, for loading tables into data warehouses without
+//! the type loss of JSONL.
+//!
+//! Each table is written to `<table>/part-NNNNN.parquet` files of at most about
+//! [`PARQUET_EXPORT_FILE_MAX_BYTES`] of documents each, and `manifest.json`
+//! lists every table's columns and files. Columns come from the top-level
+//! fields of the table's inferred shape and map from Convex types like so:
+//!
+//! | Convex type                        | Arrow type                     |
+//! |------------------------------------|--------------------------------|
+//! | `v.int64()`                        | `Int64`                        |
+//! | `v.float64()`                      | `Float64`                      |
+//! | `v.boolean()`                      | `Boolean`                      |
+//! | `v.string()`, `v.id(...)`          | `Utf8`                         |
+//! | `v.bytes()`                        | `Binary`                       |
+//! | arrays, objects, mixed types       | `Utf8` holding the clean JSON export |
+//!
+//! Fields that are optional or can be null are nullable columns. Tables whose
+//! documents don't have a consistent set of fields are written with `_id`,
+//! `_creationTime` and a `document` column holding each whole document as JSON.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use arrow_array::{
+    builder::{
+        BinaryBuilder,
+        BooleanBuilder,
+        Float64Builder,
+        Int64Builder,
+        StringBuilder,
+    },
+    ArrayRef,
+    RecordBatch,
+};
+use arrow_schema::{
+    DataType,
+    Field,
+    Schema,
+    SchemaRef,
+};
+use common::{
+    async_compat::TokioAsyncWriteCompatExt,
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
+    fastrace_helpers::get_sampled_span,
+    knobs::PARQUET_EXPORT_FILE_MAX_BYTES,
+    persistence::LatestDocument,
+    runtime::Runtime,
+    types::{
+        IndexId,
+        RepeatableTimestamp,
+        TableName,
+    },
+};
+use database::TableSummary;
+use fastrace::future::FutureExt;
+use futures::{
+    pin_mut,
+    AsyncWriteExt,
+    Future,
+    TryStreamExt,
+};
+use maplit::btreemap;
+use parquet::{
+    arrow::ArrowWriter,
+    basic::Compression,
+    file::properties::WriterProperties,
+    format::KeyValue,
+};
+use serde::Serialize;
+use shape_inference::{
+    Shape,
+    ShapeConfig,
+    ShapeCounter,
+    ShapeEnum,
+};
+use storage::ChannelWriter;
+use usage_tracking::FunctionUsageTracker;
+use value::{
+    export::ValueFormat,
+    ConvexObject,
+    ConvexValue,
+    InternalId,
+    Size,
+    TableNamespace,
+    TableNumber,
+    TabletId,
+};
+
+use crate::exports::{
+    get_export_path_prefix,
+    worker::ExportWorker,
+    zip_uploader::ZipSnapshotUpload,
+};
+
+/// Column holding whole documents for tables without a consistent shape.
+const DOCUMENT_COLUMN: &str = "document";
+
+static PARQUET_README_MD_CONTENTS: &str = r#"# Welcome to your Convex Parquet export!
+
+This ZIP file contains a snapshot of the tables in your Convex deployment as
+Parquet files, for loading into a data warehouse.
+
+Documents for each table are in <table_name>/part-NNNNN.parquet files, and
+manifest.json lists each table's columns, their types and its files.
+
+Arrays, objects and fields with mixed types are stored as JSON strings. This
+export can't be imported back into Convex; use a ZIP export for backups.
+"#;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColumnType {
+    Int64,
+    Float64,
+    Boolean,
+    Utf8,
+    Binary,
+    /// Values of any type, as the JSON of their clean export.
+    Json,
+}
+
+impl ColumnType {
+    fn data_type(&self) -> DataType {
+        match self {
+            ColumnType::Int64 => DataType::Int64,
+            ColumnType::Float64 => DataType::Float64,
+            ColumnType::Boolean => DataType::Boolean,
+            ColumnType::Utf8 | ColumnType::Json => DataType::Utf8,
+            ColumnType::Binary => DataType::Binary,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ColumnType::Int64 => "int64",
+            ColumnType::Float64 => "float64",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Utf8 => "utf8",
+            ColumnType::Binary => "binary",
+            ColumnType::Json => "json",
+        }
+    }
+
+    fn builder(&self) -> ColumnBuilder {
+        match self {
+            ColumnType::Int64 => ColumnBuilder::Int64(Int64Builder::new()),
+            ColumnType::Float64 => ColumnBuilder::Float64(Float64Builder::new()),
+            ColumnType::Boolean => ColumnBuilder::Boolean(BooleanBuilder::new()),
+            ColumnType::Utf8 => ColumnBuilder::Utf8(StringBuilder::new()),
+            ColumnType::Binary => ColumnBuilder::Binary(BinaryBuilder::new()),
+            ColumnType::Json => ColumnBuilder::Json(StringBuilder::new()),
+        }
+    }
+
+    /// The column type for values of both types, where `None` means the
+    /// values are all null.
+    fn merge(a: Option<ColumnType>, b: Option<ColumnType>) -> Option<ColumnType> {
+        match (a, b) {
+            (None, t) | (t, None) => t,
+            (Some(a), Some(b)) if a == b => Some(a),
+            _ => Some(ColumnType::Json),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParquetColumn {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+}
+
+/// The column type for a shape, or `None` if it has no non-null values, and
+/// whether it includes null.
+fn shape_column_type<C: ShapeConfig, S: ShapeCounter>(
+    shape: &Shape<C, S>,
+) -> (Option<ColumnType>, bool) {
+    match shape.variant() {
+        ShapeEnum::Never => (None, false),
+        ShapeEnum::Null => (None, true),
+        ShapeEnum::Int64 => (Some(ColumnType::Int64), false),
+        ShapeEnum::NegativeInf
+        | ShapeEnum::PositiveInf
+        | ShapeEnum::NegativeZero
+        | ShapeEnum::NaN
+        | ShapeEnum::NormalFloat64
+        | ShapeEnum::Float64 => (Some(ColumnType::Float64), false),
+        ShapeEnum::Boolean => (Some(ColumnType::Boolean), false),
+        ShapeEnum::StringLiteral(_)
+        | ShapeEnum::Id(_)
+        | ShapeEnum::FieldName
+        | ShapeEnum::String => (Some(ColumnType::Utf8), false),
+        ShapeEnum::Bytes => (Some(ColumnType::Binary), false),
+        ShapeEnum::Array(_)
+        | ShapeEnum::Set(_)
+        | ShapeEnum::Map(_)
+        | ShapeEnum::Object(_)
+        | ShapeEnum::Record(_) => (Some(ColumnType::Json), false),
+        ShapeEnum::Unknown => (Some(ColumnType::Json), true),
+        ShapeEnum::Union(union) => {
+            union
+                .iter()
+                .fold((None, false), |(column_type, nullable), variant| {
+                    let (variant_type, variant_nullable) = shape_column_type(variant);
+                    (
+                        ColumnType::merge(column_type, variant_type),
+                        nullable || variant_nullable,
+                    )
+                })
+        },
+    }
+}
+
+fn system_columns() -> Vec<ParquetColumn> {
+    vec![
+        ParquetColumn {
+            name: "_id".to_string(),
+            column_type: ColumnType::Utf8,
+            nullable: false,
+        },
+        ParquetColumn {
+            name: "_creationTime".to_string(),
+            column_type: ColumnType::Float64,
+            nullable: false,
+        },
+    ]
+}
+
+/// The columns for a table with documents of the given shape, starting with
+/// `_id` and `_creationTime`. Returns `None` if the documents don't have a
+/// consistent set of fields.
+pub fn columns_for_shape<C: ShapeConfig, S: ShapeCounter>(
+    shape: &Shape<C, S>,
+) -> Option<Vec<ParquetColumn>> {
+    let objects: Vec<_> = match shape.variant() {
+        ShapeEnum::Never => vec![],
+        ShapeEnum::Object(object) => vec![object.fields()],
+        ShapeEnum::Union(union) => union
+            .iter()
+            .map(|variant| match variant.variant() {
+                ShapeEnum::Object(object) => Some(object.fields()),
+                _ => None,
+            })
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    let mut fields: BTreeMap<String, (Option<ColumnType>, bool)> = BTreeMap::new();
+    for object in &objects {
+        for (name, field) in object.iter() {
+            let (field_type, field_nullable) = shape_column_type(&field.value_shape);
+            let (column_type, nullable) = fields.entry(name.to_string()).or_default();
+            *column_type = ColumnType::merge(*column_type, field_type);
+            *nullable |= field_nullable || field.optional;
+        }
+    }
+    // Fields missing from some documents are null in those rows.
+    for (name, (_, nullable)) in fields.iter_mut() {
+        if objects
+            .iter()
+            .any(|object| !object.keys().any(|field| **field == **name))
+        {
+            *nullable = true;
+        }
+    }
+    let user_columns = fields
+        .into_iter()
+        .filter(|(name, _)| !name.starts_with('_'))
+        .map(|(name, (column_type, nullable))| {
+            let column_type = column_type.unwrap_or(ColumnType::Utf8);
+            ParquetColumn {
+                name,
+                column_type,
+                nullable: nullable || column_type == ColumnType::Json,
+            }
+        });
+    Some(system_columns().into_iter().chain(user_columns).collect())
+}
+
+enum ColumnBuilder {
+    Int64(Int64Builder),
+    Float64(Float64Builder),
+    Boolean(BooleanBuilder),
+    Utf8(StringBuilder),
+    Binary(BinaryBuilder),
+    Json(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn append(&mut self, value: Option<&ConvexValue>) -> anyhow::Result<()> {
+        let Some(value) = value.filter(|value| !matches!(value, ConvexValue::Null)) else {
+            match self {
+                ColumnBuilder::Int64(builder) => builder.append_null(),
+                ColumnBuilder::Float64(builder) => builder.append_null(),
+                ColumnBuilder::Boolean(builder) => builder.append_null(),
+                ColumnBuilder::Utf8(builder) | ColumnBuilder::Json(builder) => {
+                    builder.append_null()
+                },
+                ColumnBuilder::Binary(builder) => builder.append_null(),
+            }
+            return Ok(());
+        };
+        match (self, value) {
+            (ColumnBuilder::Int64(builder), ConvexValue::Int64(i)) => builder.append_value(*i),
+            (ColumnBuilder::Float64(builder), ConvexValue::Float64(f)) => builder.append_value(*f),
+            (ColumnBuilder::Boolean(builder), ConvexValue::Boolean(b)) => builder.append_value(*b),
+            (ColumnBuilder::Utf8(builder), ConvexValue::String(s)) => builder.append_value(&**s),
+            (ColumnBuilder::Binary(builder), ConvexValue::Bytes(b)) => builder.append_value(&**b),
+            (ColumnBuilder::Json(builder), value) => builder.append_value(
+                value
+                    .clone()
+                    .export(ValueFormat::ConvexCleanJSON)
+                    .to_string(),
+            ),
+            (_, value) => anyhow::bail!(
+                "{} value doesn't match the inferred column type",
+                value.type_name()
+            ),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Int64(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Float64(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Boolean(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Utf8(builder) | ColumnBuilder::Json(builder) => {
+                Arc::new(builder.finish())
+            },
+            ColumnBuilder::Binary(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Buffers a table's documents as Arrow columns and writes them out as
+/// Parquet files.
+pub struct ParquetTableWriter {
+    columns: Vec<ParquetColumn>,
+    /// Whether the table has no consistent shape, so documents are written
+    /// whole to [`DOCUMENT_COLUMN`].
+    whole_documents: bool,
+    schema: SchemaRef,
+    builders: Vec<ColumnBuilder>,
+    num_buffered_rows: usize,
+    buffered_bytes: usize,
+}
+
+impl ParquetTableWriter {
+    pub fn new<C: ShapeConfig, S: ShapeCounter>(shape: &Shape<C, S>) -> Self {
+        let (columns, whole_documents) = match columns_for_shape(shape) {
+            Some(columns) => (columns, false),
+            None => {
+                let mut columns = system_columns();
+                columns.push(ParquetColumn {
+                    name: DOCUMENT_COLUMN.to_string(),
+                    column_type: ColumnType::Json,
+                    nullable: false,
+                });
+                (columns, true)
+            },
+        };
+        let schema = Arc::new(Schema::new(
+            columns
+                .iter()
+                .map(|column| {
+                    Field::new(
+                        column.name.clone(),
+                        column.column_type.data_type(),
+                        column.nullable,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ));
+        let builders = columns
+            .iter()
+            .map(|column| column.column_type.builder())
+            .collect();
+        Self {
+            columns,
+            whole_documents,
+            schema,
+            builders,
+            num_buffered_rows: 0,
+            buffered_bytes: 0,
+        }
+    }
+
+    pub fn columns(&self) -> &[ParquetColumn] {
+        &self.columns
+    }
+
+    pub fn num_buffered_rows(&self) -> usize {
+        self.num_buffered_rows
+    }
+
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes
+    }
+
+    pub fn append(&mut self, document: ConvexObject) -> anyhow::Result<()> {
+        self.buffered_bytes += document.size();
+        self.num_buffered_rows += 1;
+        let whole_document = self
+            .whole_documents
+            .then(|| ConvexValue::Object(document.clone()));
+        for (column, builder) in self.columns.iter().zip(self.builders.iter_mut()) {
+            let value = match &whole_document {
+                Some(whole_document) if column.name == DOCUMENT_COLUMN => Some(whole_document),
+                _ => document.get(column.name.as_str()),
+            };
+            builder
+                .append(value)
+                .with_context(|| format!("Failed to export field {}", column.name))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered rows to a Parquet file and returns its contents.
+    pub fn finish_file(&mut self, metadata: Vec<KeyValue>) -> anyhow::Result<Vec<u8>> {
+        let arrays = self
+            .builders
+            .iter_mut()
+            .map(ColumnBuilder::finish)
+            .collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_key_value_metadata(Some(metadata))
+            .build();
+        let mut buf = vec![];
+        let mut writer = ArrowWriter::try_new(&mut buf, self.schema.clone(), Some(properties))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        self.num_buffered_rows = 0;
+        self.buffered_bytes = 0;
+        Ok(buf)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ParquetManifest {
+    snapshot_ts: String,
+    tables: Vec<ParquetTableManifest>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ParquetTableManifest {
+    component_path: String,
+    table_name: String,
+    num_rows: u64,
+    /// True if the table's documents are in a single `document` JSON column.
+    whole_documents: bool,
+    columns: Vec<ParquetColumnManifest>,
+    files: Vec<ParquetFileManifest>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ParquetColumnManifest {
+    name: String,
+    /// One of "int64", "float64", "boolean", "utf8", "binary" or "json", where
+    /// "json" columns are Arrow `Utf8` holding JSON.
+    #[serde(rename = "type")]
+    column_type: &'static str,
+    nullable: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ParquetFileManifest {
+    path: String,
+    num_rows: u64,
+    size_bytes: u64,
+}
+
+async fn write_parquet_file(
+    zip_snapshot_upload: &mut ZipSnapshotUpload<'_>,
+    table_writer: &mut ParquetTableWriter,
+    path: String,
+    metadata: Vec<KeyValue>,
+) -> anyhow::Result<ParquetFileManifest> {
+    let num_rows = table_writer.num_buffered_rows() as u64;
+    let contents = table_writer.finish_file(metadata)?;
+    let size_bytes = contents.len() as u64;
+    zip_snapshot_upload
+        .stream_full_file(path.clone(), &contents[..])
+        .await?;
+    Ok(ParquetFileManifest {
+        path,
+        num_rows,
+        size_bytes,
+    })
+}
+
+async fn write_parquet_table<RT: Runtime>(
+    worker: &ExportWorker<RT>,
+    path_prefix: &str,
+    zip_snapshot_upload: &mut ZipSnapshotUpload<'_>,
+    snapshot_ts: RepeatableTimestamp,
+    component_path: &ComponentPath,
+    tablet_id: &TabletId,
+    table_name: TableName,
+    table_summary: &TableSummary,
+    by_id: &InternalId,
+    usage: &FunctionUsageTracker,
+) -> anyhow::Result<ParquetTableManifest> {
+    let mut table_writer = ParquetTableWriter::new(table_summary.inferred_type());
+    let metadata = vec![
+        KeyValue::new("convex.tableName".to_string(), table_name.to_string()),
+        KeyValue::new(
+            "convex.componentPath".to_string(),
+            String::from(component_path.clone()),
+        ),
+        KeyValue::new(
+            "convex.snapshotTs".to_string(),
+            u64::from(*snapshot_ts).to_string(),
+        ),
+    ];
+    let file_path = |files: &Vec<ParquetFileManifest>| {
+        format!("{path_prefix}{table_name}/part-{:05}.parquet", files.len())
+    };
+    let mut files = vec![];
+    let mut num_rows = 0;
+
+    let table_iterator = worker.database.table_iterator(snapshot_ts, 1000);
+    let stream = table_iterator.stream_documents_in_table(*tablet_id, *by_id, None);
+    pin_mut!(stream);
+    while let Some(LatestDocument { value: doc, .. }) = stream.try_next().await? {
+        usage.track_database_egress_size(
+            component_path.clone(),
+            table_name.to_string(),
+            doc.size() as u64,
+            false,
+        );
+        table_writer.append(doc.into_value().0)?;
+        num_rows += 1;
+        if table_writer.buffered_bytes() >= *PARQUET_EXPORT_FILE_MAX_BYTES {
+            let path = file_path(&files);
+            let file = write_parquet_file(
+                zip_snapshot_upload,
+                &mut table_writer,
+                path,
+                metadata.clone(),
+            )
+            .await?;
+            files.push(file);
+        }
+    }
+    // Empty tables still get a file, so their schema is in the export.
+    if table_writer.num_buffered_rows() > 0 || files.is_empty() {
+        let path = file_path(&files);
+        let file =
+            write_parquet_file(zip_snapshot_upload, &mut table_writer, path, metadata).await?;
+        files.push(file);
+    }
+    Ok(ParquetTableManifest {
+        component_path: String::from(component_path.clone()),
+        table_name: table_name.to_string(),
+        num_rows,
+        whole_documents: table_writer.whole_documents,
+        columns: table_writer
+            .columns()
+            .iter()
+            .map(|column| ParquetColumnManifest {
+                name: column.name.clone(),
+                column_type: column.column_type.name(),
+                nullable: column.nullable,
+            })
+            .collect(),
+        files,
+    })
+}
+
+pub(super) async fn construct_parquet_snapshot<F, Fut, RT: Runtime>(
+    worker: &ExportWorker<RT>,
+    mut writer: ChannelWriter,
+    tables: BTreeMap<TabletId, (TableNamespace, TableNumber, TableName, TableSummary)>,
+    component_ids_to_paths: BTreeMap<ComponentId, ComponentPath>,
+    snapshot_ts: RepeatableTimestamp,
+    by_id_indexes: BTreeMap<TabletId, IndexId>,
+    usage: FunctionUsageTracker,
+    update_progress: F,
+) -> anyhow::Result<()>
+where
+    F: Fn(String) -> Fut + Send + Copy,
+    Fut: Future<Output = anyhow::Result<()>> + Send,
+{
+    let mut zip_snapshot_upload =
+        ZipSnapshotUpload::new_with_readme(&mut writer, PARQUET_README_MD_CONTENTS).await?;
+
+    // Write small tables first, as for zip snapshots.
+    let mut sorted_tables: Vec<_> = tables.iter().collect();
+    sorted_tables.sort_by_key(|(_, (_, _, _, table_summary))| table_summary.total_size());
+    let mut table_manifests = vec![];
+    for (tablet_id, (namespace, _, table_name, table_summary)) in sorted_tables {
+        let component_id: ComponentId = (*namespace).into();
+        let component_path = component_ids_to_paths
+            .get(&component_id)
+            .context("Component missing")?;
+        let in_component_str = component_path.in_component_str();
+        let path_prefix = get_export_path_prefix(component_path);
+        let by_id = by_id_indexes
+            .get(tablet_id)
+            .ok_or_else(|| anyhow::anyhow!("no by_id index for {} found", tablet_id))?;
+
+        let root = get_sampled_span(
+            &worker.instance_name,
+            "export_worker/write_parquet_table",
+            &mut worker.runtime.rng(),
+            btreemap! {
+                "dev.convex.component_path".to_string() => component_path.to_string(),
+                "dev.convex.table_name".to_string() => table_name.to_string(),
+            },
+        );
+
+        update_progress(format!("Backing up {table_name}{in_component_str}")).await?;
+
+        let table_manifest = write_parquet_table(
+            worker,
+            &path_prefix,
+            &mut zip_snapshot_upload,
+            snapshot_ts,
+            component_path,
+            tablet_id,
+            table_name.clone(),
+            table_summary,
+            by_id,
+            &usage,
+        )
+        .in_span(root)
+        .await?;
+        table_manifests.push(table_manifest);
+    }
+
+    let manifest = ParquetManifest {
+        snapshot_ts: u64::from(*snapshot_ts).to_string(),
+        tables: table_manifests,
+    };
+    zip_snapshot_upload
+        .stream_full_file(
+            "manifest.json".to_string(),
+            &serde_json::to_vec_pretty(&manifest)?[..],
+        )
+        .await?;
+
+    // Complete upload.
+    zip_snapshot_upload.complete().await?;
+    writer.compat_write().close().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use shape_inference::{
+        testing::TestConfig,
+        CountedShape,
+    };
+    use value::assert_obj;
+
+    use super::{
+        columns_for_shape,
+        ColumnType,
+        ParquetColumn,
+        ParquetTableWriter,
+    };
+
+    #[test]
+    fn test_columns_for_shape() {
+        let objects = [
+            assert_obj!("_id" => "a", "_creationTime" => 1.0, "n" => 1i64, "tags" => ["x"]),
+            assert_obj!("_id" => "b", "_creationTime" => 2.0, "n" => 2i64, "note" => "hi"),
+        ];
+        let shape = objects
+            .iter()
+            .fold(CountedShape::<TestConfig>::empty(), |shape, object| {
+                shape.insert_value(&object.clone().into())
+            });
+        let columns = columns_for_shape(&shape).unwrap();
+        let column = |name: &str, column_type, nullable| ParquetColumn {
+            name: name.to_string(),
+            column_type,
+            nullable,
+        };
+        assert_eq!(
+            columns,
+            vec![
+                column("_id", ColumnType::Utf8, false),
+                column("_creationTime", ColumnType::Float64, false),
+                column("n", ColumnType::Int64, false),
+                column("note", ColumnType::Utf8, true),
+                column("tags", ColumnType::Json, true),
+            ]
+        );
+
+        let mut writer = ParquetTableWriter::new(&shape);
+        for object in objects {
+            writer.append(object).unwrap();
+        }
+        let contents = writer.finish_file(vec![]).unwrap();
+        assert_eq!(&contents[..4], b"PAR1");
+        assert_eq!(writer.num_buffered_rows(), 0);
+    }
+}
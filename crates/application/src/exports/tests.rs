@@ -400,6 +400,7 @@ async fn test_export_storage(rt: TestRuntime) -> anyhow::Result<()> {
             Some(ContentType::jpeg()),
             futures::stream::iter(vec![Ok(Bytes::from_static(b"abc"))]),
             None,
+            None,
             &usage_tracker,
         )
         .await?;
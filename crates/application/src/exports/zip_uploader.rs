@@ -93,10 +93,17 @@ pub struct ZipSnapshotUpload<'a> {
 
 impl<'a> ZipSnapshotUpload<'a> {
     pub async fn new(out: &'a mut ChannelWriter) -> anyhow::Result<Self> {
+        Self::new_with_readme(out, README_MD_CONTENTS).await
+    }
+
+    pub async fn new_with_readme(
+        out: &'a mut ChannelWriter,
+        readme: &'static str,
+    ) -> anyhow::Result<Self> {
         let writer = ZipFileWriter::new(out);
         let mut zip_snapshot_upload = Self { writer };
         zip_snapshot_upload
-            .stream_full_file("README.md".to_owned(), README_MD_CONTENTS.as_bytes())
+            .stream_full_file("README.md".to_owned(), readme.as_bytes())
             .await?;
         Ok(zip_snapshot_upload)
     }
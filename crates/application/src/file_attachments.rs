@@ -0,0 +1,156 @@
+//! Cascading file deletes. Deleting a document from a table whose schema sets
+//! `cascadeDeleteFiles` leaves its file attachments pending, and this worker
+//! deletes each of those files from `_storage` once no other document attaches
+//! it. Deleting a component can also delete its files, which no other
+//! component can reference.
+
+use std::{
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    document::ParsedDocument,
+    errors::report_error,
+    knobs::{
+        FILE_ATTACHMENT_CASCADE_BATCH_SIZE,
+        FILE_ATTACHMENT_CASCADE_INTERVAL,
+    },
+    runtime::Runtime,
+    types::RepeatableTimestamp,
+};
+use database::Database;
+use futures::{
+    pin_mut,
+    TryStreamExt,
+};
+use keybroker::Identity;
+use model::{
+    file_attachments::{
+        FileAttachmentsModel,
+        FILE_ATTACHMENTS_TABLE,
+    },
+    file_storage::types::FileStorageEntry,
+};
+use storage::Storage;
+use value::{
+    TableNamespace,
+    TabletId,
+};
+
+use crate::metrics::{
+    log_file_attachment_cascades,
+    log_worker_starting,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+pub struct FileAttachmentCascadeWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> FileAttachmentCascadeWorker<RT> {
+    pub fn new(runtime: RT, database: Database<RT>) -> Self {
+        Self {
+            runtime,
+            database,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        }
+    }
+
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let mut worker = Self::new(runtime, database);
+        async move {
+            tracing::info!("Starting FileAttachmentCascadeWorker");
+            loop {
+                match worker.run_once().await {
+                    Ok(_) => {
+                        worker.backoff.reset();
+                        worker.runtime.wait(*FILE_ATTACHMENT_CASCADE_INTERVAL).await;
+                    },
+                    Err(e) => {
+                        report_error(&mut e.context("FileAttachmentCascadeWorker died")).await;
+                        let delay = worker.backoff.fail(&mut worker.runtime.rng());
+                        worker.runtime.wait(delay).await;
+                    },
+                }
+            }
+        }
+    }
+
+    /// Works through every component's pending cascades, returning the number
+    /// of files deleted.
+    pub async fn run_once(&self) -> anyhow::Result<usize> {
+        let _status = log_worker_starting("FileAttachmentCascadeWorker");
+        let namespaces: Vec<TableNamespace> = {
+            let tx = self.database.begin(Identity::system()).await?;
+            tx.table_mapping()
+                .iter()
+                .filter(|(_, _, _, table_name)| **table_name == *FILE_ATTACHMENTS_TABLE)
+                .map(|(_, namespace, ..)| namespace)
+                .collect()
+        };
+        let mut deleted = 0;
+        for namespace in namespaces {
+            loop {
+                let mut tx = self.database.begin(Identity::system()).await?;
+                let pending = FileAttachmentsModel::new(&mut tx, namespace)
+                    .pending_cascades(*FILE_ATTACHMENT_CASCADE_BATCH_SIZE)
+                    .await?;
+                if pending.is_empty() {
+                    break;
+                }
+                let mut deleted_in_batch = 0;
+                for attachment in pending {
+                    if FileAttachmentsModel::new(&mut tx, namespace)
+                        .complete_cascade(attachment)
+                        .await?
+                    {
+                        deleted_in_batch += 1;
+                    }
+                }
+                self.database
+                    .commit_with_write_source(tx, "file_attachment_cascade")
+                    .await?;
+                deleted += deleted_in_batch;
+            }
+        }
+        if deleted > 0 {
+            tracing::info!("Deleted {deleted} files attached to deleted documents");
+        }
+        log_file_attachment_cascades(deleted);
+        Ok(deleted)
+    }
+}
+
+/// Deletes the objects of the files in `file_storage_tablets`, the `_storage`
+/// tables of deleted components, as of `snapshot_ts` before they were deleted.
+/// Returns the number of objects deleted.
+pub async fn delete_component_files<RT: Runtime>(
+    database: Database<RT>,
+    files_storage: Arc<dyn Storage>,
+    snapshot_ts: RepeatableTimestamp,
+    file_storage_tablets: Vec<TabletId>,
+) -> anyhow::Result<usize> {
+    let snapshot = database.snapshot(snapshot_ts)?;
+    let mut deleted = 0;
+    for tablet_id in file_storage_tablets {
+        let by_id = snapshot.index_registry.must_get_by_id(tablet_id)?.id();
+        let stream = database
+            .table_iterator(snapshot_ts, 1000)
+            .stream_documents_in_table(tablet_id, by_id, None);
+        pin_mut!(stream);
+        while let Some(document) = stream.try_next().await? {
+            let entry = ParsedDocument::<FileStorageEntry>::try_from(document.value)?;
+            files_storage.delete_object(&entry.storage_key).await?;
+            deleted += 1;
+        }
+    }
+    log_file_attachment_cascades(deleted);
+    Ok(deleted)
+}
@@ -0,0 +1,289 @@
+//! Extracts the text of files for tables whose schema configures
+//! `extractFileText`. Mutations queue each document that gets a new file, and
+//! this worker reads plain text files directly and passes every other file to
+//! the table's extractor action, then writes the text into the document so the
+//! table's search index covers it.
+
+use std::{
+    future::Future,
+    ops::Bound,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentId,
+        PublicFunctionPath,
+    },
+    document::ParsedDocument,
+    errors::report_error,
+    execution_context::RequestContext,
+    knobs::FILE_TEXT_MAX_BYTES,
+    runtime::Runtime,
+    types::FunctionCaller,
+    RequestId,
+};
+use database::Database;
+use errors::ErrorMetadataAnyhowExt;
+use futures::{
+    select_biased,
+    FutureExt,
+    TryStreamExt,
+};
+use keybroker::Identity;
+use model::{
+    file_storage::{
+        types::FileStorageEntry,
+        FileStorageId,
+        FileStorageModel,
+    },
+    file_text::{
+        types::FileTextExtraction,
+        FileTextModel,
+        FILE_TEXT_EXTRACTIONS_TABLE,
+    },
+};
+use serde_json::json;
+use storage::{
+    Storage,
+    StorageExt,
+};
+use value::{
+    ConvexValue,
+    DeveloperDocumentId,
+    TableNamespace,
+};
+
+use crate::{
+    application_function_runner::ApplicationFunctionRunner,
+    metrics::{
+        log_file_text_extraction,
+        log_worker_starting,
+    },
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Components created while the worker is idle aren't covered by its
+/// subscription, so it also wakes up periodically.
+const MAX_IDLE_WAIT: Duration = Duration::from_secs(60);
+
+pub struct FileTextWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    runner: Arc<ApplicationFunctionRunner<RT>>,
+    files_storage: Arc<dyn Storage>,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> FileTextWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        runner: Arc<ApplicationFunctionRunner<RT>>,
+        files_storage: Arc<dyn Storage>,
+    ) -> impl Future<Output = ()> + Send {
+        let mut worker = Self {
+            runtime,
+            database,
+            runner,
+            files_storage,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        };
+        async move {
+            tracing::info!("Starting FileTextWorker");
+            loop {
+                if let Err(e) = worker.run_once().await {
+                    report_error(&mut e.context("FileTextWorker died")).await;
+                    let delay = worker.backoff.fail(&mut worker.runtime.rng());
+                    worker.runtime.wait(delay).await;
+                } else {
+                    worker.backoff.reset();
+                }
+            }
+        }
+    }
+
+    /// Extract the next queued file's text, or wait for something to be
+    /// queued.
+    async fn run_once(&self) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let namespaces: Vec<TableNamespace> = tx
+            .table_mapping()
+            .iter()
+            .filter(|(_, _, _, table_name)| **table_name == *FILE_TEXT_EXTRACTIONS_TABLE)
+            .map(|(_, namespace, ..)| namespace)
+            .collect();
+        for namespace in namespaces {
+            if let Some(extraction) = FileTextModel::new(&mut tx, namespace)
+                .next_pending()
+                .await?
+            {
+                let _status = log_worker_starting("FileTextWorker");
+                return self.extract(namespace, extraction).await;
+            }
+        }
+        let token = tx.into_token()?;
+        let subscription = self.database.subscribe(token).await?;
+        select_biased! {
+            _ = subscription.wait_for_invalidation().fuse() => {},
+            _ = self.runtime.wait(MAX_IDLE_WAIT).fuse() => {},
+        }
+        Ok(())
+    }
+
+    async fn extract(
+        &self,
+        namespace: TableNamespace,
+        extraction: ParsedDocument<FileTextExtraction>,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let file_text = FileTextModel::new(&mut tx, namespace)
+            .file_text_schema(extraction.document_id)
+            .await?;
+        let entry = FileStorageModel::new(&mut tx, namespace)
+            .get_file(FileStorageId::DocumentId(extraction.storage_id))
+            .await?;
+        let component = tx.must_component_path(ComponentId::from(namespace))?;
+        drop(tx);
+        // Quarantined files are never served, so their text isn't either.
+        let text = match (file_text, entry) {
+            (Some(file_text), Some(entry)) if entry.quarantine_reason.is_none() => {
+                let path = file_text
+                    .extractor
+                    .map(|udf_path| CanonicalizedComponentFunctionPath {
+                        component,
+                        udf_path,
+                    });
+                self.extract_text(extraction.storage_id, &entry.into_value(), path)
+                    .await?
+            },
+            _ => None,
+        };
+        // Extractors can run for a while, so write the text in a new
+        // transaction, which rechecks that the document still holds the file.
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let has_text = text.is_some();
+        let updated = FileTextModel::new(&mut tx, namespace)
+            .complete(extraction.clone(), text)
+            .await?;
+        if let Err(e) = self
+            .database
+            .commit_with_write_source(tx, "file_text_extraction")
+            .await
+        {
+            if !e.is_deterministic_user_error() {
+                return Err(e);
+            }
+            // The text doesn't fit the document's schema, which retrying won't
+            // fix.
+            tracing::warn!(
+                "Couldn't write the text of {}: {}",
+                extraction.document_id,
+                e.user_facing_message()
+            );
+            let mut tx = self.database.begin(Identity::system()).await?;
+            FileTextModel::new(&mut tx, namespace)
+                .dequeue(&extraction)
+                .await?;
+            self.database
+                .commit_with_write_source(tx, "file_text_extraction_failed")
+                .await?;
+            log_file_text_extraction(false);
+            return Ok(());
+        }
+        log_file_text_extraction(updated && has_text);
+        Ok(())
+    }
+
+    /// The file's text, truncated to `FILE_TEXT_MAX_BYTES`. Plain text files
+    /// are read directly. Other files need an extractor; a failing extractor
+    /// leaves the file without text rather than retrying it forever.
+    async fn extract_text(
+        &self,
+        storage_id: DeveloperDocumentId,
+        entry: &FileStorageEntry,
+        extractor: Option<CanonicalizedComponentFunctionPath>,
+    ) -> anyhow::Result<Option<String>> {
+        let is_plain_text = entry
+            .content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.starts_with("text/"));
+        let text = if is_plain_text {
+            let Some(stream) = self
+                .files_storage
+                .get_range(
+                    &entry.storage_key,
+                    (
+                        Bound::Unbounded,
+                        Bound::Excluded(*FILE_TEXT_MAX_BYTES as u64),
+                    ),
+                )
+                .await?
+            else {
+                return Ok(None);
+            };
+            let mut bytes = vec![];
+            let mut stream = stream.stream;
+            while let Some(chunk) = stream.try_next().await? {
+                bytes.extend_from_slice(&chunk);
+            }
+            String::from_utf8_lossy(&bytes).into_owned()
+        } else if let Some(extractor) = extractor {
+            let args = json!({
+                "storageId": storage_id.to_string(),
+                "contentType": entry.content_type,
+            });
+            let result = self
+                .runner
+                .run_action(
+                    RequestId::new(),
+                    PublicFunctionPath::Component(extractor.clone()),
+                    vec![args],
+                    Identity::system(),
+                    FunctionCaller::FileTextExtraction,
+                    RequestContext::default(),
+                )
+                .await?;
+            match result {
+                Ok(action_return) => match action_return.value {
+                    ConvexValue::String(text) => String::from(text),
+                    ConvexValue::Null => return Ok(None),
+                    value => {
+                        tracing::warn!(
+                            "File text extractor {} returned {} instead of a string",
+                            extractor.udf_path,
+                            value.type_name()
+                        );
+                        return Ok(None);
+                    },
+                },
+                Err(e) => {
+                    tracing::warn!(
+                        "File text extractor {} failed: {}",
+                        extractor.udf_path,
+                        e.error
+                    );
+                    return Ok(None);
+                },
+            }
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(truncate_text(text, *FILE_TEXT_MAX_BYTES)))
+    }
+}
+
+fn truncate_text(mut text: String, max_bytes: usize) -> String {
+    if text.len() > max_bytes {
+        let mut end = max_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
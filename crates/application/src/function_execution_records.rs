@@ -0,0 +1,108 @@
+use std::{
+    future::Future,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    knobs::{
+        FUNCTION_EXECUTION_RECORD_MAX_LOG_LINES,
+        FUNCTION_EXECUTION_RECORD_RETENTION,
+    },
+    runtime::Runtime,
+    types::CursorMs,
+};
+use database::Database;
+use keybroker::Identity;
+use model::function_execution_records::{
+    types::FunctionExecutionRecord,
+    FunctionExecutionRecordModel,
+};
+
+use crate::{
+    function_log::FunctionExecutionLog,
+    metrics::log_worker_starting,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Number of records inserted per transaction.
+const RECORDS_PER_TRANSACTION: usize = 128;
+
+/// Copies completed function executions from the in-memory function log into
+/// `_function_execution_records`. Executions of system functions aren't
+/// persisted. The system table cleanup worker deletes records once they're
+/// older than the retention period.
+pub struct FunctionExecutionRecordWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    function_log: FunctionExecutionLog<RT>,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> FunctionExecutionRecordWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        function_log: FunctionExecutionLog<RT>,
+    ) -> impl Future<Output = ()> + Send {
+        let mut worker = Self {
+            runtime,
+            database,
+            function_log,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        };
+        async move {
+            if FUNCTION_EXECUTION_RECORD_RETENTION.is_none() {
+                tracing::info!("Function execution records are disabled");
+                return;
+            }
+            tracing::info!("Starting FunctionExecutionRecordWorker");
+            // Executions logged before startup were persisted by the previous
+            // process, if at all.
+            let mut cursor = worker.function_log.latest_cursor();
+            loop {
+                match worker.run_once(cursor).await {
+                    Ok(new_cursor) => {
+                        cursor = new_cursor;
+                        worker.backoff.reset();
+                    },
+                    Err(e) => {
+                        report_error(&mut e.context("FunctionExecutionRecordWorker died")).await;
+                        let delay = worker.backoff.fail(&mut worker.runtime.rng());
+                        worker.runtime.wait(delay).await;
+                    },
+                }
+            }
+        }
+    }
+
+    /// Wait for executions logged after `cursor` and persist them, returning
+    /// the cursor to continue from.
+    async fn run_once(&self, cursor: CursorMs) -> anyhow::Result<CursorMs> {
+        let (executions, new_cursor) = self.function_log.stream(cursor).await;
+        let _status = log_worker_starting("FunctionExecutionRecordWorker");
+        let records = executions
+            .iter()
+            .filter(|execution| !execution.is_system())
+            .map(|execution| execution.execution_record(*FUNCTION_EXECUTION_RECORD_MAX_LOG_LINES))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for chunk in records.chunks(RECORDS_PER_TRANSACTION) {
+            self.insert_records(chunk.to_vec()).await?;
+        }
+        Ok(new_cursor)
+    }
+
+    async fn insert_records(&self, records: Vec<FunctionExecutionRecord>) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        FunctionExecutionRecordModel::new(&mut tx)
+            .insert(records)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "function_execution_record_worker")
+            .await?;
+        Ok(())
+    }
+}
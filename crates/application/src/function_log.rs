@@ -57,6 +57,10 @@ use http::{
     StatusCode,
 };
 use itertools::Itertools;
+use model::function_execution_records::types::{
+    FunctionExecutionRecord,
+    FunctionExecutionStatus,
+};
 use parking_lot::Mutex;
 use serde_json::{
     json,
@@ -101,6 +105,8 @@ use value::{
     ConvexArray,
 };
 
+use crate::function_metrics::FunctionMetrics;
+
 /// A function's execution is summarized by this structure and stored in the
 /// UdfExecutionLog
 #[derive(Debug, Clone)]
@@ -252,6 +258,8 @@ impl FunctionExecution {
                     vector_index_read_bytes: self.usage_stats.vector_index_read_bytes,
                     vector_index_write_bytes: self.usage_stats.vector_index_write_bytes,
                     action_memory_used_mb: self.action_memory_used_mb,
+                    peak_heap_used_bytes: self.peak_heap_used_bytes(),
+                    cpu_time_micros: self.usage_stats.cpu_time_micros,
                 },
             },
         }];
@@ -272,6 +280,79 @@ impl FunctionExecution {
     }
 }
 
+impl FunctionExecution {
+    /// The heap the function used, for functions that ran in the
+    /// transactional isolate environment rather than being served from the
+    /// cache.
+    fn peak_heap_used_bytes(&self) -> Option<u64> {
+        let ran_in_udf_environment = matches!(self.udf_type, UdfType::Query | UdfType::Mutation);
+        (ran_in_udf_environment && !self.cached_result)
+            .then_some(self.usage_stats.peak_heap_used_bytes)
+    }
+
+    /// Whether this is an execution of one of the system functions, e.g. the
+    /// dashboard's queries.
+    pub fn is_system(&self) -> bool {
+        match &self.params {
+            UdfParams::Function { identifier, .. } => identifier.udf_path.is_system(),
+            UdfParams::Http { .. } => false,
+        }
+    }
+
+    /// Summarize the execution for `_function_execution_records`, keeping at
+    /// most `max_log_lines` log lines.
+    pub fn execution_record(
+        &self,
+        max_log_lines: usize,
+    ) -> anyhow::Result<FunctionExecutionRecord> {
+        let component_path = match &self.params {
+            UdfParams::Function { identifier, .. } => identifier.component.clone(),
+            // TODO(ENG-7612): Support HTTP actions in components.
+            UdfParams::Http { .. } => ComponentPath::root(),
+        };
+        let mut log_lines = self
+            .log_lines
+            .clone()
+            .into_iter()
+            .flat_map(LogLine::to_pretty_strings);
+        let kept_log_lines = log_lines
+            .by_ref()
+            .take(max_log_lines)
+            .map(|mut line| {
+                line.truncate(line.floor_char_boundary(MAX_RECORD_LOG_LINE_LENGTH));
+                line
+            })
+            .collect();
+        let log_lines_truncated = log_lines.next().is_some();
+        Ok(FunctionExecutionRecord {
+            component_path,
+            function_path: self.params.identifier_str(),
+            udf_type: self.udf_type,
+            status: if self.params.is_err() {
+                FunctionExecutionStatus::Failure
+            } else {
+                FunctionExecutionStatus::Success
+            },
+            error: self.params.err().map(|e| e.to_string()),
+            request_id: self.context.request_id.to_string(),
+            execution_id: self.context.execution_id.to_string(),
+            unix_timestamp_ms: self.unix_timestamp.as_ms_since_epoch()?,
+            execution_time_ms: Duration::from_secs_f64(self.execution_time).as_millis() as u64,
+            cached_result: self.cached_result,
+            rows_read: self.tables_touched.values().map(|t| t.rows_read).sum(),
+            rows_written: self.tables_touched.values().map(|t| t.rows_written).sum(),
+            database_read_bytes: self.usage_stats.database_read_bytes,
+            database_write_bytes: self.usage_stats.database_write_bytes,
+            log_lines: kept_log_lines,
+            log_lines_truncated,
+        })
+    }
+}
+
+/// Log lines in execution records are truncated to this many bytes so a
+/// record stays well under the maximum document size.
+const MAX_RECORD_LOG_LINE_LENGTH: usize = 1000;
+
 #[derive(Debug, Clone)]
 pub struct FunctionExecutionProgress {
     /// Log lines that the UDF emitted via the `Console` API.
@@ -495,6 +576,7 @@ impl FromStr for TableRate {
 pub struct FunctionExecutionLog<RT: Runtime> {
     inner: Arc<Mutex<Inner<RT>>>,
     usage_tracking: UsageCounter,
+    function_metrics: Arc<FunctionMetrics>,
     rt: RT,
 }
 
@@ -522,6 +604,9 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             inner: Arc::new(Mutex::new(inner)),
             rt,
             usage_tracking,
+            function_metrics: Arc::new(
+                FunctionMetrics::new().expect("Failed to initialize function metrics registry"),
+            ),
         }
     }
 
@@ -594,6 +679,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             TrackUsage::Track(usage_tracker) => {
                 let usage_stats = usage_tracker.gather_user_stats();
                 let aggregated = usage_stats.aggregate();
+                self.function_metrics.record_table_usage(&usage_stats);
                 self.usage_tracking.track_call(
                     UdfIdentifier::Function(outcome.path.clone()),
                     context.execution_id.clone(),
@@ -703,6 +789,10 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         context: ExecutionContext,
         occ_info: OccInfo,
     ) {
+        if !outcome.path.udf_path.is_system() {
+            self.function_metrics
+                .record_occ_retry(UdfIdentifier::Function(outcome.path.clone()), &occ_info);
+        }
         self._log_mutation(
             outcome,
             tables_touched,
@@ -728,6 +818,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             TrackUsage::Track(usage_tracker) => {
                 let usage_stats = usage_tracker.gather_user_stats();
                 let aggregated = usage_stats.aggregate();
+                self.function_metrics.record_table_usage(&usage_stats);
                 self.usage_tracking.track_call(
                     UdfIdentifier::Function(outcome.path.clone()),
                     context.execution_id.clone(),
@@ -814,6 +905,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             TrackUsage::Track(usage_tracker) => {
                 let usage_stats = usage_tracker.gather_user_stats();
                 let aggregated = usage_stats.aggregate();
+                self.function_metrics.record_table_usage(&usage_stats);
                 self.usage_tracking.track_call(
                     UdfIdentifier::Function(outcome.path.clone()),
                     completion.context.execution_id.clone(),
@@ -954,6 +1046,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             TrackUsage::Track(usage_tracker) => {
                 let usage_stats = usage_tracker.gather_user_stats();
                 let aggregated = usage_stats.aggregate();
+                self.function_metrics.record_table_usage(&usage_stats);
                 self.usage_tracking.track_call(
                     UdfIdentifier::Http(outcome.route.clone()),
                     context.execution_id.clone(),
@@ -1016,6 +1109,16 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
     }
 
     fn log_execution(&self, execution: FunctionExecution, send_console_events: bool) {
+        let is_err = match &execution.params {
+            UdfParams::Function { error, .. } => error.is_some(),
+            UdfParams::Http { result, .. } => result.is_err(),
+        };
+        self.function_metrics.record_execution(
+            execution.identifier(),
+            execution.udf_type,
+            is_err,
+            execution.execution_time,
+        );
         if let Err(mut e) = self
             .inner
             .lock()
@@ -1052,6 +1155,11 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         }
     }
 
+    /// Per-function metrics in the Prometheus text exposition format.
+    pub fn prometheus_metrics(&self) -> anyhow::Result<String> {
+        self.function_metrics.encode()
+    }
+
     pub fn udf_rate(
         &self,
         identifier: UdfIdentifier,
@@ -0,0 +1,193 @@
+//! Per-function metrics in the Prometheus exposition format.
+//!
+//! These are kept in their own registry rather than `CONVEX_METRICS_REGISTRY`
+//! since they're labeled with function and table names from the deployment,
+//! which would be unbounded labels for the backend's own metrics.
+use common::{
+    components::ComponentPath,
+    types::{
+        UdfIdentifier,
+        UdfType,
+    },
+};
+use prometheus::{
+    HistogramOpts,
+    HistogramVec,
+    IntCounterVec,
+    Opts,
+    Registry,
+    TextEncoder,
+};
+use usage_tracking::{
+    FunctionUsageStats,
+    OccInfo,
+};
+use value::TableName;
+
+const EXECUTION_TIME_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10., 30., 60., 300., 600.,
+];
+
+pub struct FunctionMetrics {
+    registry: Registry,
+    executions: IntCounterVec,
+    execution_seconds: HistogramVec,
+    occ_retries: IntCounterVec,
+    table_read_bytes: IntCounterVec,
+    table_write_bytes: IntCounterVec,
+}
+
+impl FunctionMetrics {
+    pub fn new() -> anyhow::Result<Self> {
+        let registry = Registry::new_custom(Some("convex".to_string()), None)?;
+        let executions = IntCounterVec::new(
+            Opts::new(
+                "function_executions_total",
+                "Number of completed function executions",
+            ),
+            &["component", "function", "udf_type", "status"],
+        )?;
+        let execution_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "function_execution_seconds",
+                "Execution time of completed functions",
+            )
+            .buckets(EXECUTION_TIME_BUCKETS.to_vec()),
+            &["component", "function", "udf_type"],
+        )?;
+        let occ_retries = IntCounterVec::new(
+            Opts::new(
+                "function_occ_retries_total",
+                "Number of mutation attempts that failed with an OCC conflict",
+            ),
+            &["component", "function", "table"],
+        )?;
+        let table_read_bytes = IntCounterVec::new(
+            Opts::new(
+                "table_read_bytes",
+                "Number of document bytes read from a table by functions",
+            ),
+            &["component", "table"],
+        )?;
+        let table_write_bytes = IntCounterVec::new(
+            Opts::new(
+                "table_write_bytes",
+                "Number of document bytes written to a table by functions",
+            ),
+            &["component", "table"],
+        )?;
+        registry.register(Box::new(executions.clone()))?;
+        registry.register(Box::new(execution_seconds.clone()))?;
+        registry.register(Box::new(occ_retries.clone()))?;
+        registry.register(Box::new(table_read_bytes.clone()))?;
+        registry.register(Box::new(table_write_bytes.clone()))?;
+        Ok(Self {
+            registry,
+            executions,
+            execution_seconds,
+            occ_retries,
+            table_read_bytes,
+            table_write_bytes,
+        })
+    }
+
+    pub fn record_execution(
+        &self,
+        identifier: UdfIdentifier,
+        udf_type: UdfType,
+        is_err: bool,
+        execution_time: f64,
+    ) {
+        let (component, function) = identifier.into_component_and_udf_path();
+        let component = component.unwrap_or_default();
+        let udf_type = udf_type.to_lowercase_string();
+        let status = if is_err { "failure" } else { "success" };
+        self.executions
+            .with_label_values(&[&component, &function, udf_type, status])
+            .inc();
+        self.execution_seconds
+            .with_label_values(&[&component, &function, udf_type])
+            .observe(execution_time);
+    }
+
+    pub fn record_occ_retry(&self, identifier: UdfIdentifier, occ_info: &OccInfo) {
+        let (component, function) = identifier.into_component_and_udf_path();
+        let table = occ_info.table_name.as_deref().unwrap_or("unknown");
+        self.occ_retries
+            .with_label_values(&[&component.unwrap_or_default(), &function, table])
+            .inc();
+    }
+
+    pub fn record_table_usage(&self, usage_stats: &FunctionUsageStats) {
+        let labels = |component_path: &ComponentPath, table_name: &TableName| {
+            [
+                component_path.clone().serialize().unwrap_or_default(),
+                table_name.to_string(),
+            ]
+        };
+        for ((component_path, table_name), bytes) in &usage_stats.database_egress_size {
+            let [component, table] = labels(component_path, table_name);
+            self.table_read_bytes
+                .with_label_values(&[&component, &table])
+                .inc_by(*bytes);
+        }
+        for ((component_path, table_name), bytes) in &usage_stats.database_ingress_size {
+            let [component, table] = labels(component_path, table_name);
+            self.table_write_bytes
+                .with_label_values(&[&component, &table])
+                .inc_by(*bytes);
+        }
+    }
+
+    pub fn encode(&self) -> anyhow::Result<String> {
+        Ok(TextEncoder::new().encode_to_string(&self.registry.gather())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        components::{
+            CanonicalizedComponentFunctionPath,
+            ComponentPath,
+        },
+        types::{
+            UdfIdentifier,
+            UdfType,
+        },
+    };
+    use usage_tracking::OccInfo;
+
+    use super::FunctionMetrics;
+
+    #[test]
+    fn test_encode_function_metrics() -> anyhow::Result<()> {
+        let metrics = FunctionMetrics::new()?;
+        let identifier = UdfIdentifier::Function(CanonicalizedComponentFunctionPath {
+            component: ComponentPath::root(),
+            udf_path: "messages.js:send".parse()?,
+        });
+        metrics.record_execution(identifier.clone(), UdfType::Mutation, false, 0.02);
+        metrics.record_execution(identifier.clone(), UdfType::Mutation, true, 0.3);
+        metrics.record_occ_retry(
+            identifier,
+            &OccInfo {
+                table_name: Some("messages".to_string()),
+                document_id: None,
+                write_source: None,
+                retry_count: 1,
+            },
+        );
+        let output = metrics.encode()?;
+        assert!(output.contains(
+            "convex_function_executions_total{component=\"\",function=\"messages.js:send\",\
+             status=\"failure\",udf_type=\"mutation\"} 1"
+        ));
+        assert!(output.contains(
+            "convex_function_occ_retries_total{component=\"\",function=\"messages.js:send\",\
+             table=\"messages\"} 1"
+        ));
+        assert!(output.contains("convex_function_execution_seconds_count"));
+        Ok(())
+    }
+}
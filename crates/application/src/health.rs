@@ -0,0 +1,206 @@
+//! Liveness and readiness checks for orchestrators.
+//!
+//! Liveness only looks at state in this process, so it's cheap enough to poll
+//! often. Readiness additionally checks that the backend can reach everything
+//! it needs to serve requests.
+
+use std::{
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    knobs::{
+        ENABLE_INDEX_BACKFILL,
+        FUNCTION_EXECUTION_RECORD_RETENTION,
+        HEALTH_CHECK_TIMEOUT,
+        MAX_SESSION_CLEANUP_DURATION,
+        SLOW_QUERY_LOG_RETENTION,
+        USAGE_EXPORT_INTERVAL,
+    },
+    runtime::{
+        Runtime,
+        SpawnHandle,
+        WithTimeout,
+    },
+    types::ObjectKey,
+};
+use parking_lot::Mutex;
+use storage::Storage;
+
+use crate::Application;
+
+/// Storage key used to probe storage backends. Nothing is ever written to it.
+const STORAGE_PROBE_KEY: &str = "health-check-probe";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    /// The dependency is reachable but not ready yet, e.g. indexes are still
+    /// loading.
+    Starting,
+    Unavailable,
+}
+
+impl CheckStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Starting => "starting",
+            CheckStatus::Unavailable => "unavailable",
+        }
+    }
+}
+
+pub struct DependencyCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// How long the check took. Zero for checks that don't do any IO.
+    pub latency: Duration,
+    pub error: Option<anyhow::Error>,
+}
+
+impl DependencyCheck {
+    fn from_status(name: &'static str, status: CheckStatus) -> Self {
+        Self {
+            name,
+            status,
+            latency: Duration::ZERO,
+            error: None,
+        }
+    }
+}
+
+pub struct HealthReport {
+    pub checks: Vec<DependencyCheck>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status == CheckStatus::Ok)
+    }
+}
+
+impl<RT: Runtime> Application<RT> {
+    /// Check that the background workers that should always be running are.
+    pub fn check_liveness(&self) -> HealthReport {
+        HealthReport {
+            checks: self.check_workers(),
+        }
+    }
+
+    /// Check persistence, storage, search and the background workers.
+    pub async fn check_readiness(&self) -> HealthReport {
+        let mut checks = vec![
+            self.check_dependency("persistence", self.database.check_persistence())
+                .await,
+        ];
+        for (name, storage) in [
+            ("modules_storage", &self.modules_storage),
+            ("files_storage", &self.files_storage),
+            ("search_storage", &self.search_storage),
+            ("exports_storage", &self.exports_storage),
+            ("snapshot_imports_storage", &self.snapshot_imports_storage),
+        ] {
+            checks.push(self.check_storage(name, storage.clone()).await);
+        }
+        checks.push(DependencyCheck::from_status(
+            "search",
+            if self.database.has_search_indexes_bootstrapped() {
+                CheckStatus::Ok
+            } else {
+                CheckStatus::Starting
+            },
+        ));
+        checks.extend(self.check_workers());
+        HealthReport { checks }
+    }
+
+    async fn check_storage(
+        &self,
+        name: &'static str,
+        storage: Arc<dyn Storage>,
+    ) -> DependencyCheck {
+        self.check_dependency(name, async move {
+            let key = ObjectKey::try_from(STORAGE_PROBE_KEY)?;
+            storage.get_object_attributes(&key).await?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn check_dependency(
+        &self,
+        name: &'static str,
+        check: impl Future<Output = anyhow::Result<()>> + Send,
+    ) -> DependencyCheck {
+        let start = self.runtime.monotonic_now();
+        let result = self
+            .runtime
+            .with_timeout(name, *HEALTH_CHECK_TIMEOUT, check)
+            .await;
+        let latency = self.runtime.monotonic_now() - start;
+        match result {
+            Ok(()) => DependencyCheck {
+                name,
+                status: CheckStatus::Ok,
+                latency,
+                error: None,
+            },
+            Err(e) => DependencyCheck {
+                name,
+                status: CheckStatus::Unavailable,
+                latency,
+                error: Some(e),
+            },
+        }
+    }
+
+    fn check_workers(&self) -> Vec<DependencyCheck> {
+        // Some workers exit immediately when they're disabled, so only check
+        // the ones that should be running.
+        let workers: [(&'static str, &Arc<Mutex<Box<dyn SpawnHandle>>>, bool); 10] = [
+            ("index_worker", &self.index_worker, *ENABLE_INDEX_BACKFILL),
+            ("fast_forward_worker", &self.fast_forward_worker, true),
+            ("schema_worker", &self.schema_worker, true),
+            (
+                "system_table_cleanup_worker",
+                &self.system_table_cleanup_worker,
+                MAX_SESSION_CLEANUP_DURATION.is_some(),
+            ),
+            ("cron_job_executor", &self.cron_job_executor, true),
+            ("export_worker", &self.export_worker, true),
+            ("snapshot_import_worker", &self.snapshot_import_worker, true),
+            (
+                "function_execution_record_worker",
+                &self.function_execution_record_worker,
+                FUNCTION_EXECUTION_RECORD_RETENTION.is_some(),
+            ),
+            (
+                "usage_export_worker",
+                &self.usage_export_worker,
+                USAGE_EXPORT_INTERVAL.is_some(),
+            ),
+            (
+                "slow_query_worker",
+                &self.slow_query_worker,
+                SLOW_QUERY_LOG_RETENTION.is_some(),
+            ),
+        ];
+        workers
+            .into_iter()
+            .filter(|(_, _, enabled)| *enabled)
+            .map(|(name, handle, _)| {
+                let status = if handle.lock().is_finished() {
+                    CheckStatus::Unavailable
+                } else {
+                    CheckStatus::Ok
+                };
+                DependencyCheck::from_status(name, status)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,448 @@
+//! Cache of responses to GET HTTP actions, so an expensive endpoint doesn't
+//! rerun for every request in a traffic spike. Only responses that opt in with
+//! a positive `s-maxage` or `max-age` are stored. Entries are keyed by the
+//! serving component, the request's path and query and the headers that
+//! identify the caller, so one user's response is never served to another.
+//! Actions drop stale entries with the `invalidateHttpActionCache` syscall.
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use common::components::ComponentId;
+use headers::{
+    CacheControl,
+    HeaderMapExt,
+    HeaderName,
+    HeaderValue,
+};
+use http::{
+    header::{
+        AGE,
+        AUTHORIZATION,
+        COOKIE,
+        SET_COOKIE,
+        VARY,
+    },
+    Method,
+    StatusCode,
+};
+use lru::LruCache;
+use parking_lot::Mutex;
+use tokio::time::Instant;
+use udf::{
+    HttpActionRequestHead,
+    HttpActionResponseHead,
+    HttpActionResponsePart,
+};
+use value::sha256::Sha256;
+
+use crate::metrics::log_http_action_cache_lookup;
+
+/// Request headers that are part of the cache key. A response may only `Vary`
+/// on these.
+const KEYED_HEADERS: [HeaderName; 2] = [AUTHORIZATION, COOKIE];
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct HttpActionCacheKey {
+    component: ComponentId,
+    path: String,
+    query: Option<String>,
+    /// Hash of the `KEYED_HEADERS`, so credentials aren't kept in memory.
+    credentials: [u8; 32],
+}
+
+impl HttpActionCacheKey {
+    fn new(component: ComponentId, head: &HttpActionRequestHead) -> Self {
+        let mut hasher = Sha256::new();
+        for name in &KEYED_HEADERS {
+            for value in head.headers.get_all(name) {
+                hasher.update(name.as_str().as_bytes());
+                hasher.update(b":");
+                hasher.update(value.as_bytes());
+                hasher.update(b"\n");
+            }
+        }
+        Self {
+            component,
+            path: head.url.path().to_string(),
+            query: head.url.query().map(|q| q.to_string()),
+            credentials: *hasher.finalize(),
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.path.len() + self.query.as_ref().map_or(0, |q| q.len()) + 32
+    }
+}
+
+struct CachedResponse {
+    head: HttpActionResponseHead,
+    body: Vec<Bytes>,
+    size: usize,
+    stored_at: Instant,
+    expires_at: Instant,
+}
+
+/// A response being streamed to the client, kept so it can be cached once
+/// the action finishes.
+pub struct CapturedHttpResponse {
+    key: HttpActionCacheKey,
+    head: Option<HttpActionResponseHead>,
+    body: Vec<Bytes>,
+    size: usize,
+    max_size: usize,
+}
+
+impl CapturedHttpResponse {
+    pub fn record(&mut self, part: &HttpActionResponsePart) {
+        if self.size > self.max_size {
+            return;
+        }
+        match part {
+            HttpActionResponsePart::Head(head) => {
+                self.size += head
+                    .headers
+                    .iter()
+                    .map(|(name, value)| name.as_str().len() + value.len())
+                    .sum::<usize>();
+                self.head = Some(head.clone());
+            },
+            HttpActionResponsePart::BodyChunk(chunk) => {
+                self.size += chunk.len();
+                self.body.push(chunk.clone());
+            },
+        }
+        if self.size > self.max_size {
+            // Too big to cache, so stop holding on to it.
+            self.head = None;
+            self.body = vec![];
+        }
+    }
+}
+
+struct Inner {
+    cache: LruCache<HttpActionCacheKey, CachedResponse>,
+    size: usize,
+    size_limit: usize,
+}
+
+pub struct HttpActionCache {
+    inner: Mutex<Inner>,
+}
+
+impl HttpActionCache {
+    pub fn new(size_limit: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                cache: LruCache::unbounded(),
+                size: 0,
+                size_limit,
+            }),
+        }
+    }
+
+    /// Responses bigger than this would evict too much of the cache.
+    fn max_entry_size(&self) -> usize {
+        self.inner.lock().size_limit / 16
+    }
+
+    /// The key for a request the component serves, or `None` if the cache
+    /// is disabled or the request can't be served from it.
+    pub fn key(
+        &self,
+        component: ComponentId,
+        head: &HttpActionRequestHead,
+    ) -> Option<HttpActionCacheKey> {
+        if self.max_entry_size() == 0 || head.method != Method::GET {
+            return None;
+        }
+        Some(HttpActionCacheKey::new(component, head))
+    }
+
+    /// The cached response for `key`, with an `Age` header for how long it's
+    /// been cached.
+    pub fn get(
+        &self,
+        key: &HttpActionCacheKey,
+        now: Instant,
+    ) -> Option<Vec<HttpActionResponsePart>> {
+        let mut inner = self.inner.lock();
+        let parts = match inner.cache.get(key) {
+            Some(entry) if now < entry.expires_at => {
+                let mut head = entry.head.clone();
+                let age = now.saturating_duration_since(entry.stored_at).as_secs();
+                head.headers.insert(AGE, HeaderValue::from(age));
+                Some(
+                    std::iter::once(HttpActionResponsePart::Head(head))
+                        .chain(
+                            entry
+                                .body
+                                .iter()
+                                .cloned()
+                                .map(HttpActionResponsePart::BodyChunk),
+                        )
+                        .collect(),
+                )
+            },
+            Some(_) => {
+                if let Some(entry) = inner.cache.pop(key) {
+                    inner.size -= entry.size;
+                }
+                None
+            },
+            None => None,
+        };
+        log_http_action_cache_lookup(parts.is_some());
+        parts
+    }
+
+    pub fn capture(&self, key: HttpActionCacheKey) -> CapturedHttpResponse {
+        CapturedHttpResponse {
+            size: key.size(),
+            key,
+            head: None,
+            body: vec![],
+            max_size: self.max_entry_size(),
+        }
+    }
+
+    /// Store a completed response if its headers allow it.
+    pub fn insert(&self, captured: CapturedHttpResponse, now: Instant) {
+        let CapturedHttpResponse {
+            key,
+            head,
+            body,
+            size,
+            max_size,
+        } = captured;
+        let Some(head) = head else {
+            return;
+        };
+        if size > max_size {
+            return;
+        }
+        let Some(ttl) = cache_ttl(&head) else {
+            return;
+        };
+        let mut inner = self.inner.lock();
+        let entry = CachedResponse {
+            head,
+            body,
+            size,
+            stored_at: now,
+            expires_at: now + ttl,
+        };
+        inner.size += size;
+        if let Some(previous) = inner.cache.put(key, entry) {
+            inner.size -= previous.size;
+        }
+        while inner.size > inner.size_limit {
+            let Some((_, evicted)) = inner.cache.pop_lru() else {
+                break;
+            };
+            inner.size -= evicted.size;
+        }
+    }
+
+    /// Drop the component's cached responses whose path starts with
+    /// `path_prefix`, or all of them if there's no prefix. Returns how many
+    /// were dropped.
+    pub fn invalidate(&self, component: ComponentId, path_prefix: Option<&str>) -> usize {
+        let mut inner = self.inner.lock();
+        let keys: Vec<_> = inner
+            .cache
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| {
+                key.component == component
+                    && path_prefix.is_none_or(|prefix| key.path.starts_with(prefix))
+            })
+            .cloned()
+            .collect();
+        for key in &keys {
+            if let Some(entry) = inner.cache.pop(key) {
+                inner.size -= entry.size;
+            }
+        }
+        keys.len()
+    }
+}
+
+/// How long a response may be cached for, going by its headers. Responses
+/// that set cookies, are private to the browser or vary on headers that
+/// aren't part of the key aren't cached at all.
+fn cache_ttl(head: &HttpActionResponseHead) -> Option<Duration> {
+    if head.status != StatusCode::OK || head.headers.contains_key(SET_COOKIE) {
+        return None;
+    }
+    for vary in head.headers.get_all(VARY) {
+        for name in vary.to_str().ok()?.split(',').map(str::trim) {
+            let keyed = name.is_empty()
+                || KEYED_HEADERS
+                    .iter()
+                    .any(|keyed| name.eq_ignore_ascii_case(keyed.as_str()));
+            if !keyed {
+                return None;
+            }
+        }
+    }
+    let cache_control: CacheControl = head.headers.typed_get()?;
+    if cache_control.no_store() || cache_control.no_cache() || cache_control.private() {
+        return None;
+    }
+    cache_control
+        .s_max_age()
+        .or(cache_control.max_age())
+        .filter(|ttl| !ttl.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use common::components::ComponentId;
+    use headers::{
+        HeaderMap,
+        HeaderValue,
+    };
+    use http::{
+        header::{
+            AUTHORIZATION,
+            CACHE_CONTROL,
+            SET_COOKIE,
+            VARY,
+        },
+        Method,
+        StatusCode,
+    };
+    use tokio::time::Instant;
+    use udf::{
+        HttpActionRequestHead,
+        HttpActionResponseHead,
+        HttpActionResponsePart,
+    };
+
+    use super::{
+        cache_ttl,
+        HttpActionCache,
+    };
+
+    fn request(path: &str, authorization: Option<&str>) -> HttpActionRequestHead {
+        let mut headers = HeaderMap::new();
+        if let Some(authorization) = authorization {
+            headers.insert(AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        }
+        HttpActionRequestHead {
+            headers,
+            url: format!("https://example.convex.site{path}")
+                .parse()
+                .unwrap(),
+            method: Method::GET,
+        }
+    }
+
+    fn response(headers: &[(http::HeaderName, &'static str)]) -> HttpActionResponseHead {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(name, HeaderValue::from_static(value));
+        }
+        HttpActionResponseHead {
+            status: StatusCode::OK,
+            headers: map,
+        }
+    }
+
+    #[test]
+    fn test_cache_ttl() {
+        assert_eq!(
+            cache_ttl(&response(&[(CACHE_CONTROL, "public, max-age=60")])),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            cache_ttl(&response(&[(CACHE_CONTROL, "max-age=60, s-maxage=10")])),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            cache_ttl(&response(&[
+                (CACHE_CONTROL, "max-age=60"),
+                (VARY, "Authorization")
+            ])),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(cache_ttl(&response(&[])), None);
+        assert_eq!(cache_ttl(&response(&[(CACHE_CONTROL, "max-age=0")])), None);
+        assert_eq!(
+            cache_ttl(&response(&[(CACHE_CONTROL, "private, max-age=60")])),
+            None
+        );
+        assert_eq!(
+            cache_ttl(&response(&[(CACHE_CONTROL, "no-store, max-age=60")])),
+            None
+        );
+        assert_eq!(
+            cache_ttl(&response(&[
+                (CACHE_CONTROL, "max-age=60"),
+                (SET_COOKIE, "session=1")
+            ])),
+            None
+        );
+        assert_eq!(
+            cache_ttl(&response(&[
+                (CACHE_CONTROL, "max-age=60"),
+                (VARY, "Accept-Language")
+            ])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_http_action_cache() {
+        let cache = HttpActionCache::new(1 << 20);
+        let component = ComponentId::test_user();
+        let now = Instant::now();
+        let store = |path: &str, authorization: Option<&str>| {
+            let key = cache.key(component, &request(path, authorization)).unwrap();
+            let mut captured = cache.capture(key);
+            captured.record(&HttpActionResponsePart::Head(response(&[(
+                CACHE_CONTROL,
+                "max-age=60",
+            )])));
+            captured.record(&HttpActionResponsePart::BodyChunk(Bytes::from("hello")));
+            cache.insert(captured, now);
+        };
+        let lookup = |path: &str, authorization: Option<&str>, now: Instant| {
+            let key = cache.key(component, &request(path, authorization)).unwrap();
+            cache.get(&key, now)
+        };
+
+        store("/stats?day=1", Some("Bearer a"));
+        store("/other", None);
+        let parts = lookup("/stats?day=1", Some("Bearer a"), now).unwrap();
+        assert_eq!(parts.len(), 2);
+        // Different credentials or query strings are different entries.
+        assert!(lookup("/stats?day=1", Some("Bearer b"), now).is_none());
+        assert!(lookup("/stats?day=2", Some("Bearer a"), now).is_none());
+        // Entries expire after their max-age.
+        assert!(lookup(
+            "/stats?day=1",
+            Some("Bearer a"),
+            now + Duration::from_secs(61)
+        )
+        .is_none());
+
+        store("/stats?day=1", Some("Bearer a"));
+        assert_eq!(cache.invalidate(component, Some("/stats")), 1);
+        assert!(lookup("/stats?day=1", Some("Bearer a"), now).is_none());
+        assert!(lookup("/other", None, now).is_some());
+        assert_eq!(cache.invalidate(component, None), 1);
+
+        // POSTs and disabled caches don't have keys.
+        let mut post = request("/stats", None);
+        post.method = Method::POST;
+        assert!(cache.key(component, &post).is_none());
+        assert!(HttpActionCache::new(0)
+            .key(component, &request("/stats", None))
+            .is_none());
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    net::IpAddr,
+    num::NonZeroUsize,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    runtime::Runtime,
+    types::Timestamp,
+};
+use database::Database;
+use futures::Future;
+use keybroker::Identity;
+use lru::LruCache;
+use model::ip_access_policy::{
+    types::IpAccessPolicy,
+    IpAccessPolicyModel,
+};
+use parking_lot::{
+    Mutex,
+    RwLock,
+};
+use tokio::time::Instant;
+
+use crate::metrics::log_worker_starting;
+
+/// Only record a denied request in the audit log if the same address hasn't
+/// been denied for the same reason within this long, so a client retrying in
+/// a loop can't flood the log.
+const DENIAL_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+const MAX_TRACKED_DENIALS: NonZeroUsize = NonZeroUsize::new(1000).unwrap();
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// When each recently denied address was last recorded in the audit log, by
+/// the surface or identity it was denied for.
+pub struct IpAccessDenials {
+    last_recorded: Mutex<LruCache<(IpAddr, String), Instant>>,
+}
+
+impl Default for IpAccessDenials {
+    fn default() -> Self {
+        Self {
+            last_recorded: Mutex::new(LruCache::new(MAX_TRACKED_DENIALS)),
+        }
+    }
+}
+
+impl IpAccessDenials {
+    /// Whether a denial at `now` should be recorded, marking it recorded if
+    /// so.
+    pub fn should_record(&self, ip: IpAddr, denied_for: String, now: Instant) -> bool {
+        let mut last_recorded = self.last_recorded.lock();
+        let key = (ip, denied_for);
+        if let Some(last) = last_recorded.get(&key)
+            && now.saturating_duration_since(*last) < DENIAL_LOG_INTERVAL
+        {
+            return false;
+        }
+        last_recorded.put(key, now);
+        true
+    }
+}
+
+/// The IP access policy as of the latest timestamp it was read at, so
+/// requests can be checked against it without starting a transaction.
+#[derive(Default)]
+pub struct IpAccessPolicyCache {
+    policy: RwLock<Option<(Timestamp, Arc<IpAccessPolicy>)>>,
+}
+
+impl IpAccessPolicyCache {
+    /// The cached policy, or `None` if it hasn't been read yet.
+    pub fn get(&self) -> Option<Arc<IpAccessPolicy>> {
+        self.policy
+            .read()
+            .as_ref()
+            .map(|(_, policy)| policy.clone())
+    }
+
+    /// Caches the policy as of `ts`, unless a later one is already cached.
+    pub fn update(&self, ts: Timestamp, policy: IpAccessPolicy) {
+        let mut cached = self.policy.write();
+        if cached.as_ref().is_none_or(|(cached_ts, _)| *cached_ts < ts) {
+            *cached = Some((ts, Arc::new(policy)));
+        }
+    }
+}
+
+/// Keeps the `IpAccessPolicyCache` up to date, rereading the policy whenever
+/// it changes.
+pub struct IpAccessPolicyWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    cache: Arc<IpAccessPolicyCache>,
+}
+
+impl<RT: Runtime> IpAccessPolicyWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        cache: Arc<IpAccessPolicyCache>,
+    ) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime,
+            database,
+            cache,
+        };
+        async move {
+            tracing::info!("Starting IpAccessPolicyWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("IpAccessPolicyWorker died")).await;
+                    tracing::error!("IP access policy worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let status = log_worker_starting("IpAccessPolicyWorker");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let policy = IpAccessPolicyModel::new(&mut tx).get().await?;
+        self.cache.update(*tx.begin_timestamp(), policy);
+        let token = tx.into_token()?;
+
+        drop(status);
+        let subscription = self.database.subscribe(token).await?;
+        subscription.wait_for_invalidation().await;
+        Ok(())
+    }
+}
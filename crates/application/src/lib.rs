@@ -10,6 +10,8 @@ use std::{
         BTreeMap,
         HashSet,
     },
+    net::IpAddr,
+    num::NonZeroUsize,
     ops::Bound,
     sync::Arc,
     time::{
@@ -22,6 +24,7 @@ use std::{
 use anyhow::Context;
 use authentication::{
     application_auth::ApplicationAuth,
+    provider_metadata_cache::ProviderMetadataCache,
     validate_id_token,
     Auth0IdToken,
 };
@@ -41,6 +44,7 @@ use common::{
         schema::{
             invalid_schema_id,
             parse_schema_id,
+            SchemaState,
         },
     },
     components::{
@@ -54,30 +58,45 @@ use common::{
         Resource,
     },
     document::{
+        CreationTime,
         DocumentUpdate,
+        ParsedDocument,
         CREATION_TIME_FIELD_PATH,
     },
     errors::{
         report_error,
         JsError,
     },
+    execution_context::RequestContext,
     knobs::{
+        ANONYMOUS_IDENTITY_TOKEN_TTL,
         APPLICATION_MAX_CONCURRENT_UPLOADS,
         MAX_JOBS_CANCEL_BATCH,
+        PREVIEW_DEPLOYMENT_DEFAULT_TTL,
+        SHUTDOWN_DRAIN_TIMEOUT,
         SNAPSHOT_LIST_LIMIT,
     },
     log_lines::LogLines,
     log_streaming::LogSender,
     paths::FieldPath,
     persistence::Persistence,
+    pool_stats::{
+        ConnectionPoolSnapshot,
+        PoolWorkload,
+    },
     query_journal::QueryJournal,
     runtime::{
         shutdown_and_join,
         Runtime,
         SpawnHandle,
         UnixTimestamp,
+        WithTimeout,
+    },
+    schemas::{
+        codegen::SchemaTypeDefinitions,
+        DatabaseSchema,
     },
-    schemas::DatabaseSchema,
+    secrets::SecretsResolver,
     types::{
         env_var_limit_met,
         env_var_name_not_unique,
@@ -103,15 +122,21 @@ use common::{
     RequestId,
 };
 use cron_jobs::CronJobExecutor;
+use data_migrations::DataMigrationWorker;
 use database::{
+    document_history::DocumentHistory,
+    retention_backlog::RetentionBacklog,
     unauthorized_error,
     BootstrapComponentsModel,
+    BulkWriteOperation,
+    BulkWriteProgress,
     Database,
     DocumentDeltas,
     FastForwardIndexWorker,
     IndexModel,
     IndexWorker,
     OccRetryStats,
+    SchemaModel,
     SearchIndexWorkers,
     Snapshot,
     SnapshotPage,
@@ -120,8 +145,10 @@ use database::{
     TableModel,
     Token,
     Transaction,
+    TransactionResourceUsage,
     WriteSource,
 };
+use deployment_config_worker::DeploymentConfigWorker;
 use either::Either;
 use errors::{
     ErrorMetadata,
@@ -133,11 +160,15 @@ use fastrace::{
     future::FutureExt,
     Span,
 };
+use file_attachments::FileAttachmentCascadeWorker;
 use file_storage::{
     FileRangeStream,
     FileStorage,
     FileStream,
+    StoredFile,
 };
+use file_text::FileTextWorker;
+use function_execution_records::FunctionExecutionRecordWorker;
 use function_log::{
     FunctionExecution,
     FunctionExecutionPart,
@@ -152,14 +183,41 @@ use http_client::{
     cached_http_client_for,
     ClientPurpose,
 };
+use ip_access::{
+    IpAccessDenials,
+    IpAccessPolicyCache,
+    IpAccessPolicyWorker,
+};
 use keybroker::{
     Identity,
     KeyBroker,
+    SignedFileUrl,
+    ANONYMOUS_IDENTITY_AUDIENCE,
+    ANONYMOUS_IDENTITY_ISSUER,
+};
+use lockout::{
+    admin_key_failure_delay,
+    admin_key_fingerprint,
+    admin_key_sources,
+    AdminKeySource,
+    FailureLockout,
+    LockoutPolicy,
 };
+use maintenance_window_worker::MaintenanceWindowWorker;
 use maplit::btreemap;
 use model::{
+    anonymous_identities::AnonymousIdentitiesModel,
     auth::AuthInfoModel,
     backend_state::BackendStateModel,
+    component_limits::{
+        types::ComponentLimits,
+        ComponentLimitsModel,
+    },
+    component_versions::{
+        size_report::BundleSizeReport,
+        types::ComponentVersion,
+        ComponentVersionsModel,
+    },
     components::{
         config::ComponentConfigModel,
         handles::FunctionHandlesModel,
@@ -176,8 +234,15 @@ use model::{
         },
         ConfigModel,
     },
+    data_migrations::{
+        types::DataMigration,
+        DataMigrationsModel,
+    },
     deployment_audit_log::{
-        types::DeploymentAuditLogEvent,
+        types::{
+            DeploymentAuditLogEvent,
+            PushComponentDiffs,
+        },
         DeploymentAuditLogModel,
     },
     environment_variables::{
@@ -200,8 +265,41 @@ use model::{
         ExternalPackagesModel,
     },
     file_storage::{
-        types::FileStorageEntry,
+        types::{
+            FileEncryptionMetadata,
+            FileStorageEntry,
+        },
         FileStorageId,
+        FILE_STORAGE_TABLE,
+    },
+    file_url_revocations::FileUrlRevocationsModel,
+    function_execution_records::{
+        FunctionExecutionRecordFilter,
+        FunctionExecutionRecordModel,
+        FunctionExecutionRecordPage,
+    },
+    function_kill_switches::{
+        types::{
+            FunctionKillSwitch,
+            KillSwitchTarget,
+        },
+        FunctionKillSwitchesModel,
+    },
+    http_table_access::{
+        types::HttpTableAccess,
+        HttpTableAccessModel,
+    },
+    ip_access_policy::{
+        types::{
+            IpAccessIdentity,
+            IpAccessPolicy,
+            IpAccessSurface,
+        },
+        IpAccessPolicyModel,
+    },
+    maintenance_windows::{
+        types::MaintenanceWindow,
+        MaintenanceWindowsModel,
     },
     migrations::MigrationWorker,
     modules::{
@@ -211,8 +309,28 @@ use model::{
         },
         ModuleModel,
     },
-    scheduled_jobs::SchedulerModel,
+    outbound_network_policy::{
+        types::OutboundNetworkPolicy,
+        OutboundNetworkPolicyModel,
+    },
+    preview_deployments::{
+        types::PreviewDeployment,
+        PreviewDeploymentsModel,
+    },
+    scheduled_jobs::{
+        SchedulerBacklog,
+        SchedulerModel,
+    },
+    schema_validation_reports::{
+        types::SchemaValidationReport,
+        SchemaValidationReportModel,
+    },
     session_requests::types::SessionRequestIdentifier,
+    slow_queries::{
+        types::IndexRecommendation,
+        SlowQueryModel,
+        SlowQueryPage,
+    },
     snapshot_imports::types::{
         ImportFormat,
         ImportMode,
@@ -226,16 +344,34 @@ use model::{
         upload_download::upload_package,
         SourcePackageModel,
     },
+    traffic_splits::{
+        types::{
+            TrafficSplit,
+            TrafficSplitVariant,
+        },
+        TrafficSplitModel,
+    },
     udf_config::{
         types::UdfConfig,
         UdfConfigModel,
     },
+    usage_exports::{
+        UsageExportModel,
+        UsageExportPage,
+    },
 };
 use node_executor::Actions;
 use parking_lot::Mutex;
+use preview_deployment_worker::PreviewDeploymentWorker;
 use rand::Rng;
-use scheduled_jobs::ScheduledJobRunner;
-use schema_worker::SchemaWorker;
+use scheduled_jobs::{
+    scheduler_backlog,
+    ScheduledJobRunner,
+};
+use schema_worker::{
+    SchemaWorker,
+    ValidationReportWorker,
+};
 use search::{
     query::RevisionWithKeys,
     searcher::{
@@ -246,6 +382,7 @@ use search::{
 use semver::Version;
 use serde_json::Value as JsonValue;
 use short_future::ShortBoxFuture;
+use slow_queries::SlowQueryWorker;
 use snapshot_import::{
     clear_tables,
     start_stored_import,
@@ -259,15 +396,24 @@ use storage::{
     StorageGetStream,
     Upload,
 };
+use storage_gc::StorageGcWorker;
 use sync_types::{
     AuthenticationToken,
     CanonicalizedModulePath,
     CanonicalizedUdfPath,
     FunctionName,
     ModulePath,
+    PageSplitMessage,
+    PageStatus,
     SerializedQueryJournal,
+    UserIdentityAttributes,
 };
 use system_table_cleanup::SystemTableCleanupWorker;
+use table_stats::{
+    LatestTableStats,
+    TableStatsSnapshot,
+    TableStatsWorker,
+};
 use table_summary_worker::{
     TableSummaryClient,
     TableSummaryWorker,
@@ -294,15 +440,22 @@ use udf_metrics::{
     Percentile,
     Timeseries,
 };
+use usage_export::{
+    types::UsageReport,
+    UsageAggregator,
+    UsageExportWorker,
+};
 use usage_tracking::{
     FunctionUsageStats,
     FunctionUsageTracker,
     UsageCounter,
 };
 use value::{
+    export::ValueFormat,
     id_v6::DeveloperDocumentId,
     sha256::Sha256Digest,
     ConvexValue,
+    FieldName,
     Namespace,
     ResolvedDocumentId,
     TableNamespace,
@@ -315,6 +468,19 @@ use vector::{
 
 use crate::{
     application_function_runner::ApplicationFunctionRunner,
+    builtin_auth::SignInSource,
+    component_audit::{
+        audit_component,
+        ComponentAudit,
+    },
+    document_batch::{
+        DocumentBatchOperation,
+        DocumentBatchResult,
+    },
+    document_query::{
+        DocumentQuery,
+        DocumentQueryResult,
+    },
     exports::worker::ExportWorker,
     function_log::{
         FunctionExecutionLog,
@@ -328,25 +494,56 @@ use crate::{
         RedactedJsError,
         RedactedLogLines,
     },
+    seed::{
+        SeedFixture,
+        SeedResult,
+    },
     snapshot_import::SnapshotImportWorker,
+    traffic_split_stats::ComponentTrafficSplitStats,
 };
 
 pub mod api;
 pub mod application_function_runner;
+pub mod builtin_auth;
 mod cache;
+pub mod component_audit;
 pub mod cron_jobs;
+mod data_migrations;
 pub mod deploy_config;
+mod deployment_config_worker;
+pub mod document_batch;
+pub mod document_query;
 mod exports;
+mod file_attachments;
+mod file_text;
+mod function_execution_records;
 pub mod function_log;
+mod function_metrics;
+pub mod health;
+pub mod http_action_cache;
+mod ip_access;
+mod lockout;
+pub mod log_sinks;
 pub mod log_visibility;
+mod maintenance_window_worker;
 mod metrics;
 mod module_cache;
+mod preview_deployment_worker;
 pub mod redaction;
 pub mod scheduled_jobs;
+mod schema_defaults;
 mod schema_worker;
+pub mod seed;
+mod slow_queries;
 pub mod snapshot_import;
+mod storage_gc;
+pub mod surrogate_keys;
 mod system_table_cleanup;
+mod table_clone;
+pub mod table_stats;
 mod table_summary_worker;
+pub mod traffic_split_stats;
+pub mod usage_export;
 pub mod valid_identifier;
 
 #[cfg(any(test, feature = "testing"))]
@@ -356,6 +553,9 @@ mod tests;
 
 pub use crate::cache::QueryCache;
 use crate::metrics::{
+    log_admin_key_auth_failure,
+    log_admin_key_locked_out_request,
+    log_admin_key_lockout,
     log_external_deps_package,
     log_source_package_size_bytes_total,
 };
@@ -363,6 +563,9 @@ use crate::metrics::{
 // The maximum number of user defined modules
 pub const MAX_USER_MODULES: usize = 10000;
 
+// The maximum length of the device IDs anonymous identities are bound to
+const MAX_DEVICE_ID_LENGTH: usize = 256;
+
 pub struct ConfigMetadataAndSchema {
     pub config_metadata: ConfigMetadata,
     pub schema: Option<DatabaseSchema>,
@@ -393,6 +596,7 @@ pub struct RedactedQueryReturn {
     pub log_lines: RedactedLogLines,
     pub token: Token,
     pub journal: SerializedQueryJournal,
+    pub page_split: Option<PageSplitMessage>,
 }
 
 #[derive(Debug)]
@@ -485,12 +689,28 @@ pub struct Application<RT: Runtime> {
     instance_name: String,
     scheduled_job_runner: ScheduledJobRunner,
     cron_job_executor: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    data_migration_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    function_execution_record_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    usage_aggregator: Arc<UsageAggregator>,
+    usage_export_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    slow_query_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    table_stats_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    storage_gc_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    file_attachment_cascade_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    file_text_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    provider_metadata_refresher: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    table_stats: LatestTableStats,
     index_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
     fast_forward_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
     search_worker: Arc<Mutex<SearchIndexWorkers>>,
     search_and_vector_bootstrap_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
     table_summary_worker: TableSummaryClient,
     schema_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    validation_report_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    deployment_config_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    maintenance_window_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    preview_deployment_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    ip_access_policy_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
     snapshot_import_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
     export_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
     system_table_cleanup_worker: Arc<Mutex<Box<dyn SpawnHandle>>>,
@@ -500,6 +720,11 @@ pub struct Application<RT: Runtime> {
     module_cache: ModuleCache<RT>,
     system_env_var_names: HashSet<EnvVarName>,
     app_auth: Arc<ApplicationAuth>,
+    provider_metadata_cache: Arc<ProviderMetadataCache>,
+    ip_access_policy_cache: Arc<IpAccessPolicyCache>,
+    ip_access_denials: Arc<IpAccessDenials>,
+    admin_key_lockout: Arc<FailureLockout<AdminKeySource>>,
+    builtin_auth_lockout: Arc<FailureLockout<SignInSource>>,
 }
 
 impl<RT: Runtime> Clone for Application<RT> {
@@ -520,12 +745,28 @@ impl<RT: Runtime> Clone for Application<RT> {
             instance_name: self.instance_name.clone(),
             scheduled_job_runner: self.scheduled_job_runner.clone(),
             cron_job_executor: self.cron_job_executor.clone(),
+            data_migration_worker: self.data_migration_worker.clone(),
+            function_execution_record_worker: self.function_execution_record_worker.clone(),
+            usage_aggregator: self.usage_aggregator.clone(),
+            usage_export_worker: self.usage_export_worker.clone(),
+            slow_query_worker: self.slow_query_worker.clone(),
+            table_stats_worker: self.table_stats_worker.clone(),
+            storage_gc_worker: self.storage_gc_worker.clone(),
+            file_attachment_cascade_worker: self.file_attachment_cascade_worker.clone(),
+            file_text_worker: self.file_text_worker.clone(),
+            provider_metadata_refresher: self.provider_metadata_refresher.clone(),
+            table_stats: self.table_stats.clone(),
             index_worker: self.index_worker.clone(),
             fast_forward_worker: self.fast_forward_worker.clone(),
             search_worker: self.search_worker.clone(),
             search_and_vector_bootstrap_worker: self.search_and_vector_bootstrap_worker.clone(),
             table_summary_worker: self.table_summary_worker.clone(),
             schema_worker: self.schema_worker.clone(),
+            validation_report_worker: self.validation_report_worker.clone(),
+            deployment_config_worker: self.deployment_config_worker.clone(),
+            maintenance_window_worker: self.maintenance_window_worker.clone(),
+            preview_deployment_worker: self.preview_deployment_worker.clone(),
+            ip_access_policy_worker: self.ip_access_policy_worker.clone(),
             snapshot_import_worker: self.snapshot_import_worker.clone(),
             export_worker: self.export_worker.clone(),
             system_table_cleanup_worker: self.system_table_cleanup_worker.clone(),
@@ -535,6 +776,11 @@ impl<RT: Runtime> Clone for Application<RT> {
             module_cache: self.module_cache.clone(),
             system_env_var_names: self.system_env_var_names.clone(),
             app_auth: self.app_auth.clone(),
+            provider_metadata_cache: self.provider_metadata_cache.clone(),
+            ip_access_policy_cache: self.ip_access_policy_cache.clone(),
+            ip_access_denials: self.ip_access_denials.clone(),
+            admin_key_lockout: self.admin_key_lockout.clone(),
+            builtin_auth_lockout: self.builtin_auth_lockout.clone(),
         }
     }
 }
@@ -550,6 +796,7 @@ impl<RT: Runtime> Application<RT> {
         exports_storage: Arc<dyn Storage>,
         snapshot_imports_storage: Arc<dyn Storage>,
         usage_tracking: UsageCounter,
+        usage_aggregator: Arc<UsageAggregator>,
         key_broker: KeyBroker,
         instance_name: String,
         function_runner: Arc<dyn FunctionRunner<RT>>,
@@ -559,6 +806,7 @@ impl<RT: Runtime> Application<RT> {
         segment_term_metadata_fetcher: Arc<dyn SegmentTermMetadataFetcher>,
         persistence: Arc<dyn Persistence>,
         node_actions: Actions<RT>,
+        secrets: SecretsResolver,
         log_sender: Arc<dyn LogSender>,
         log_visibility: Arc<dyn LogVisibility<RT>>,
         app_auth: Arc<ApplicationAuth>,
@@ -601,6 +849,31 @@ impl<RT: Runtime> Application<RT> {
             "schema_worker",
             SchemaWorker::start(runtime.clone(), database.clone()),
         )));
+        let validation_report_worker = Arc::new(Mutex::new(runtime.spawn(
+            "validation_report_worker",
+            ValidationReportWorker::start(runtime.clone(), database.clone()),
+        )));
+        let deployment_config_worker = Arc::new(Mutex::new(runtime.spawn(
+            "deployment_config_worker",
+            DeploymentConfigWorker::start(runtime.clone(), database.clone()),
+        )));
+        let maintenance_window_worker = Arc::new(Mutex::new(runtime.spawn(
+            "maintenance_window_worker",
+            MaintenanceWindowWorker::start(runtime.clone(), database.clone()),
+        )));
+        let preview_deployment_worker = Arc::new(Mutex::new(runtime.spawn(
+            "preview_deployment_worker",
+            PreviewDeploymentWorker::start(runtime.clone(), database.clone()),
+        )));
+        let ip_access_policy_cache = Arc::new(IpAccessPolicyCache::default());
+        let ip_access_policy_worker = Arc::new(Mutex::new(runtime.spawn(
+            "ip_access_policy_worker",
+            IpAccessPolicyWorker::start(
+                runtime.clone(),
+                database.clone(),
+                ip_access_policy_cache.clone(),
+            ),
+        )));
 
         let system_table_cleanup_worker = SystemTableCleanupWorker::new(
             runtime.clone(),
@@ -627,6 +900,7 @@ impl<RT: Runtime> Application<RT> {
             module_loader,
             function_log.clone(),
             system_env_vars.clone(),
+            secrets,
             cache,
         ));
         function_runner.set_action_callbacks(runner.clone());
@@ -637,6 +911,7 @@ impl<RT: Runtime> Application<RT> {
             database.clone(),
             runner.clone(),
             function_log.clone(),
+            files_storage.clone(),
         );
 
         let cron_job_executor_fut = CronJobExecutor::start(
@@ -650,6 +925,80 @@ impl<RT: Runtime> Application<RT> {
             runtime.spawn("cron_job_executor", cron_job_executor_fut),
         ));
 
+        let data_migration_worker = DataMigrationWorker::start(
+            runtime.clone(),
+            database.clone(),
+            runner.clone(),
+            function_log.clone(),
+        );
+        let data_migration_worker = Arc::new(Mutex::new(
+            runtime.spawn("data_migration_worker", data_migration_worker),
+        ));
+
+        let function_execution_record_worker = FunctionExecutionRecordWorker::start(
+            runtime.clone(),
+            database.clone(),
+            function_log.clone(),
+        );
+        let function_execution_record_worker = Arc::new(Mutex::new(runtime.spawn(
+            "function_execution_record_worker",
+            function_execution_record_worker,
+        )));
+
+        let usage_export_worker = UsageExportWorker::start(
+            runtime.clone(),
+            database.clone(),
+            exports_storage.clone(),
+            usage_aggregator.clone(),
+        );
+        let usage_export_worker = Arc::new(Mutex::new(
+            runtime.spawn("usage_export_worker", usage_export_worker),
+        ));
+
+        let slow_query_worker = SlowQueryWorker::start(runtime.clone(), database.clone());
+        let slow_query_worker = Arc::new(Mutex::new(
+            runtime.spawn("slow_query_worker", slow_query_worker),
+        ));
+
+        let table_stats = LatestTableStats::default();
+        let table_stats_worker =
+            TableStatsWorker::start(runtime.clone(), database.clone(), table_stats.clone());
+        let table_stats_worker = Arc::new(Mutex::new(
+            runtime.spawn("table_stats_worker", table_stats_worker),
+        ));
+
+        let storage_gc_worker =
+            StorageGcWorker::start(runtime.clone(), database.clone(), files_storage.clone());
+        let storage_gc_worker = Arc::new(Mutex::new(
+            runtime.spawn("storage_gc_worker", storage_gc_worker),
+        ));
+
+        let file_attachment_cascade_worker =
+            FileAttachmentCascadeWorker::start(runtime.clone(), database.clone());
+        let file_attachment_cascade_worker = Arc::new(Mutex::new(runtime.spawn(
+            "file_attachment_cascade_worker",
+            file_attachment_cascade_worker,
+        )));
+
+        let file_text_worker = FileTextWorker::start(
+            runtime.clone(),
+            database.clone(),
+            runner.clone(),
+            files_storage.clone(),
+        );
+        let file_text_worker = Arc::new(Mutex::new(
+            runtime.spawn("file_text_worker", file_text_worker),
+        ));
+
+        let provider_metadata_cache = Arc::new(ProviderMetadataCache::default());
+        let provider_metadata_refresher = provider_metadata_cache.clone().start_refresher(
+            runtime.clone(),
+            cached_http_client_for(ClientPurpose::ProviderMetadata),
+        );
+        let provider_metadata_refresher = Arc::new(Mutex::new(
+            runtime.spawn("provider_metadata_refresher", provider_metadata_refresher),
+        ));
+
         let export_worker = ExportWorker::new(
             runtime.clone(),
             database.clone(),
@@ -696,6 +1045,17 @@ impl<RT: Runtime> Application<RT> {
             key_broker,
             scheduled_job_runner,
             cron_job_executor,
+            data_migration_worker,
+            function_execution_record_worker,
+            usage_aggregator,
+            usage_export_worker,
+            slow_query_worker,
+            table_stats_worker,
+            storage_gc_worker,
+            file_attachment_cascade_worker,
+            file_text_worker,
+            provider_metadata_refresher,
+            table_stats,
             instance_name,
             index_worker,
             fast_forward_worker,
@@ -703,6 +1063,11 @@ impl<RT: Runtime> Application<RT> {
             search_and_vector_bootstrap_worker,
             table_summary_worker,
             schema_worker,
+            validation_report_worker,
+            deployment_config_worker,
+            maintenance_window_worker,
+            preview_deployment_worker,
+            ip_access_policy_worker,
             export_worker,
             snapshot_import_worker,
             system_table_cleanup_worker,
@@ -712,6 +1077,13 @@ impl<RT: Runtime> Application<RT> {
             module_cache,
             system_env_var_names: system_env_vars.into_keys().collect(),
             app_auth,
+            provider_metadata_cache,
+            ip_access_policy_cache,
+            ip_access_denials: Arc::new(IpAccessDenials::default()),
+            admin_key_lockout: Arc::new(FailureLockout::new(LockoutPolicy::admin_key())),
+            builtin_auth_lockout: Arc::new(FailureLockout::new(
+                LockoutPolicy::builtin_auth_sign_in(),
+            )),
         })
     }
 
@@ -743,6 +1115,27 @@ impl<RT: Runtime> Application<RT> {
         self.database.now_ts_for_reads()
     }
 
+    /// Returns the latest timestamp for reads, failing if it's behind
+    /// `min_ts`. Clients pass `min_ts` from a session token to make sure they
+    /// observe their own writes.
+    pub fn now_ts_for_reads_at_least(
+        &self,
+        min_ts: Timestamp,
+    ) -> anyhow::Result<RepeatableTimestamp> {
+        let ts = self.now_ts_for_reads();
+        anyhow::ensure!(
+            *ts >= min_ts,
+            ErrorMetadata::bad_request(
+                "InvalidSessionToken",
+                format!(
+                    "Session token timestamp {min_ts} is ahead of the latest timestamp {}",
+                    *ts
+                ),
+            )
+        );
+        Ok(ts)
+    }
+
     pub fn instance_name(&self) -> String {
         self.instance_name.clone()
     }
@@ -768,6 +1161,15 @@ impl<RT: Runtime> Application<RT> {
             .await
     }
 
+    /// How much of its read, write and scheduling budget `transaction` has
+    /// used so far, so callers can stop before hitting the transaction limits.
+    pub fn transaction_resource_usage(
+        &self,
+        transaction: &Transaction<RT>,
+    ) -> TransactionResourceUsage {
+        transaction.execution_size().resource_usage()
+    }
+
     #[fastrace::trace]
     pub async fn subscribe(&self, token: Token) -> anyhow::Result<Subscription> {
         self.database.subscribe(token).await
@@ -868,6 +1270,26 @@ impl<RT: Runtime> Application<RT> {
         self.database.vector_search(identity, query).await
     }
 
+    /// Apply a stream of writes in size-bounded chunks, each committed
+    /// atomically in its own transaction. See `Database::bulk_write`.
+    pub async fn bulk_write(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        operations: Vec<BulkWriteOperation>,
+        on_progress: impl FnMut(&BulkWriteProgress),
+    ) -> anyhow::Result<BulkWriteProgress> {
+        self.database
+            .bulk_write(
+                identity,
+                component.into(),
+                operations,
+                FunctionUsageTracker::new(),
+                on_progress,
+            )
+            .await
+    }
+
     pub async fn get_source_code(
         &self,
         identity: Identity,
@@ -947,6 +1369,7 @@ impl<RT: Runtime> Application<RT> {
                 caller.allowed_visibility(),
             )
             .await?;
+        let show_error_context = identity.is_admin() || identity.is_system();
 
         let query_return: anyhow::Result<_> = try {
             let journal = journal
@@ -972,20 +1395,35 @@ impl<RT: Runtime> Application<RT> {
             Ok(query_return) => RedactedQueryReturn {
                 result: match query_return.result {
                     Ok(r) => Ok(r),
-                    Err(e) => Err(RedactedJsError::from_js_error(e, block_logging, request_id)),
+                    Err(e) => Err(RedactedJsError::from_js_error(e, block_logging, request_id)
+                        .show_context(show_error_context)),
                 },
                 log_lines: RedactedLogLines::from_log_lines(query_return.log_lines, block_logging),
                 token: query_return.token,
                 journal: self
                     .key_broker
                     .encrypt_query_journal(&query_return.journal, persistence_version),
+                page_split: query_return
+                    .journal
+                    .page_split
+                    .map(|page_split| PageSplitMessage {
+                        split_cursor: self
+                            .key_broker
+                            .encrypt_cursor(&page_split.split_cursor, persistence_version),
+                        page_status: if page_split.required {
+                            PageStatus::SplitRequired
+                        } else {
+                            PageStatus::SplitRecommended
+                        },
+                    }),
             },
             Err(e) if e.is_deterministic_user_error() => RedactedQueryReturn {
                 result: Err(RedactedJsError::from_js_error(
                     JsError::from_error(e),
                     block_logging,
                     request_id,
-                )),
+                )
+                .show_context(show_error_context)),
                 log_lines: RedactedLogLines::empty(),
                 // Create a token for an empty read set because we haven't
                 // done any reads yet.
@@ -993,6 +1431,7 @@ impl<RT: Runtime> Application<RT> {
                 journal: self
                     .key_broker
                     .encrypt_query_journal(&QueryJournal::new(), persistence_version),
+                page_split: None,
             },
             Err(e) => anyhow::bail!(e),
         };
@@ -1009,6 +1448,7 @@ impl<RT: Runtime> Application<RT> {
         // Identifier used to make this mutation idempotent.
         mutation_identifier: Option<SessionRequestIdentifier>,
         caller: FunctionCaller,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
         identity.ensure_can_run_function(UdfType::Mutation)?;
         let block_logging = self
@@ -1019,6 +1459,7 @@ impl<RT: Runtime> Application<RT> {
                 caller.allowed_visibility(),
             )
             .await?;
+        let show_error_context = identity.is_admin() || identity.is_system();
         let result = match self
             .runner
             .retry_mutation(
@@ -1028,6 +1469,7 @@ impl<RT: Runtime> Application<RT> {
                 identity,
                 mutation_identifier,
                 caller,
+                request_context,
             )
             .await
         {
@@ -1044,7 +1486,8 @@ impl<RT: Runtime> Application<RT> {
                     mutation_error.error,
                     block_logging,
                     request_id,
-                ),
+                )
+                .show_context(show_error_context),
                 log_lines: RedactedLogLines::from_log_lines(
                     mutation_error.log_lines,
                     block_logging,
@@ -1055,7 +1498,8 @@ impl<RT: Runtime> Application<RT> {
                     JsError::from_error(e),
                     block_logging,
                     request_id,
-                ),
+                )
+                .show_context(show_error_context),
                 log_lines: RedactedLogLines::empty(),
             }),
             Err(e) => anyhow::bail!(e),
@@ -1071,6 +1515,7 @@ impl<RT: Runtime> Application<RT> {
         args: Vec<JsonValue>,
         identity: Identity,
         caller: FunctionCaller,
+        request_context: RequestContext,
     ) -> anyhow::Result<Result<RedactedActionReturn, RedactedActionError>> {
         identity.ensure_can_run_function(UdfType::Action)?;
 
@@ -1082,6 +1527,7 @@ impl<RT: Runtime> Application<RT> {
                 caller.allowed_visibility(),
             )
             .await?;
+        let show_error_context = identity.is_admin() || identity.is_system();
 
         let should_spawn = caller.run_until_completion_if_cancelled();
         let runner: Arc<ApplicationFunctionRunner<RT>> = self.runner.clone();
@@ -1091,7 +1537,7 @@ impl<RT: Runtime> Application<RT> {
             .unwrap_or(Span::noop());
         let run_action = async move {
             runner
-                .run_action(request_id_, name, args, identity, caller)
+                .run_action(request_id_, name, args, identity, caller, request_context)
                 .in_span(span)
                 .await
         };
@@ -1121,7 +1567,8 @@ impl<RT: Runtime> Application<RT> {
                     action_error.error,
                     block_logging,
                     request_id,
-                ),
+                )
+                .show_context(show_error_context),
                 log_lines: RedactedLogLines::from_log_lines(action_error.log_lines, block_logging),
             }),
             Err(e) => anyhow::bail!(e),
@@ -1147,6 +1594,7 @@ impl<RT: Runtime> Application<RT> {
                 caller.allowed_visibility(),
             )
             .await?;
+        let show_error_context = identity.is_admin() || identity.is_system();
 
         // Spawn running the action in a separate future. This way, even if we
         // get cancelled, it will continue to run to completion.
@@ -1177,6 +1625,7 @@ impl<RT: Runtime> Application<RT> {
             Ok(HttpActionResult::Error(error)) => {
                 let response_parts =
                     RedactedJsError::from_js_error(error, block_logging, RequestId::new())
+                        .show_context(show_error_context)
                         .to_http_response_parts();
                 for part in response_parts {
                     response_streamer.send_part(part)?;
@@ -1269,6 +1718,7 @@ impl<RT: Runtime> Application<RT> {
                     identity,
                     None,
                     caller,
+                    RequestContext::default(),
                 )
                 .await
                 .map(|res| {
@@ -1290,6 +1740,7 @@ impl<RT: Runtime> Application<RT> {
                     args,
                     identity,
                     caller,
+                    RequestContext::default(),
                 )
                 .await
                 .map(|res| {
@@ -1313,6 +1764,62 @@ impl<RT: Runtime> Application<RT> {
         }
     }
 
+    /// Run a query or mutation as a user identity synthesized from explicit
+    /// claims, so admins can reproduce permission bugs without minting a real
+    /// auth token. The impersonation is recorded in the deployment audit log
+    /// before the function runs.
+    pub async fn run_as_user(
+        &self,
+        request_id: RequestId,
+        path: CanonicalizedComponentFunctionPath,
+        args: Vec<JsonValue>,
+        identity: Identity,
+        acting_as: UserIdentityAttributes,
+        caller: FunctionCaller,
+    ) -> anyhow::Result<Result<FunctionReturn, FunctionError>> {
+        let Identity::InstanceAdmin(admin_identity) = identity.clone() else {
+            anyhow::bail!(unauthorized_error("run_as_user"));
+        };
+        let mut tx = self.begin(identity).await?;
+        let udf_type = ModuleModel::new(&mut tx)
+            .get_analyzed_function(&path)
+            .await?
+            .ok()
+            .map(|analyzed_function| analyzed_function.udf_type);
+        // If the function doesn't exist, `any_udf` below reports it the same
+        // way it would for the real user.
+        if let Some(udf_type) = udf_type {
+            if !matches!(udf_type, UdfType::Query | UdfType::Mutation) {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidImpersonatedFunction",
+                    format!(
+                        "Only queries and mutations can be run as a user, but '{}'{} is {}.",
+                        String::from(path.udf_path.clone().strip()),
+                        path.component.in_component_str(),
+                        udf_type.to_lowercase_string(),
+                    ),
+                ));
+            }
+            let event = DeploymentAuditLogEvent::ImpersonateUser {
+                component: path.component.clone(),
+                udf_path: path.udf_path.to_string(),
+                udf_type,
+                subject: acting_as.subject.clone(),
+                issuer: acting_as.issuer.clone(),
+            };
+            self.commit_with_audit_log_events(tx, vec![event], "run_as_user")
+                .await?;
+        }
+        self.any_udf(
+            request_id,
+            path,
+            args,
+            Identity::ActingUser(admin_identity, acting_as),
+            caller,
+        )
+        .await
+    }
+
     pub async fn request_export(
         &self,
         identity: Identity,
@@ -2012,6 +2519,7 @@ impl<RT: Runtime> Application<RT> {
         identity: Identity,
         format: ImportFormat,
         mode: ImportMode,
+        upsert_key: Option<FieldName>,
         component_path: ComponentPath,
         upload_token: ClientDrivenUploadToken,
         part_tokens: Vec<ClientDrivenUploadPartToken>,
@@ -2034,6 +2542,7 @@ impl<RT: Runtime> Application<RT> {
             component_path,
             object_key,
             ImportRequestor::SnapshotImport,
+            upsert_key,
         )
         .await
     }
@@ -2347,118 +2856,1006 @@ impl<RT: Runtime> Application<RT> {
         Ok(count)
     }
 
-    pub async fn delete_component(
+    /// Rescans tables and rebuilds their summaries, which back the dashboard's
+    /// shapes. Recomputes every table in the namespace if `table_names` is
+    /// `None`.
+    pub async fn recompute_table_summaries(
         &self,
         identity: &Identity,
-        component_id: ComponentId,
+        table_namespace: TableNamespace,
+        table_names: Option<Vec<TableName>>,
     ) -> anyhow::Result<()> {
-        let mut tx = self.begin(identity.clone()).await?;
-        ComponentConfigModel::new(&mut tx)
-            .delete_component(component_id)
-            .await?;
-        self.commit(tx, "delete_component").await?;
-        Ok(())
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("recompute_table_summaries"));
+        }
+        let snapshot = self.latest_snapshot()?;
+        let table_mapping = snapshot.table_mapping().namespace(table_namespace);
+        let tablet_ids = match table_names {
+            Some(table_names) => table_names
+                .into_iter()
+                .map(|table_name| {
+                    table_mapping.id_if_exists(&table_name).with_context(|| {
+                        ErrorMetadata::not_found(
+                            "TableNotFound",
+                            format!("Table {table_name} not found"),
+                        )
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+            None => table_mapping
+                .iter()
+                .map(|(tablet_id, ..)| tablet_id)
+                .collect(),
+        };
+        self.table_summary_worker.recompute(Some(tablet_ids)).await
     }
 
-    /// Add system indexes if they do not already exist and update
-    /// existing indexes if needed.
-    pub async fn _add_system_indexes(
+    /// Clones a user table, including its indexes, into a new table.
+    /// Returns the number of documents copied.
+    pub async fn clone_table(
         &self,
         identity: &Identity,
-        indexes: BTreeMap<IndexName, IndexedFields>,
-    ) -> anyhow::Result<()> {
-        let mut tx = self.begin(identity.clone()).await?;
-        let namespace = TableNamespace::by_component_TODO();
-        for (index_name, index_fields) in indexes.into_iter() {
-            let index_fields = self._validate_user_defined_index_fields(index_fields)?;
-            let index_metadata =
-                IndexMetadata::new_backfilling(*tx.begin_timestamp(), index_name, index_fields);
-            let mut model = IndexModel::new(&mut tx);
-            if let Some(existing_index_metadata) = model
-                .pending_index_metadata(namespace, &index_metadata.name)?
-                .or(model.enabled_index_metadata(namespace, &index_metadata.name)?)
-            {
-                if !index_metadata
-                    .config
-                    .same_config(&existing_index_metadata.config)
-                {
-                    IndexModel::new(&mut tx)
-                        .drop_index(existing_index_metadata.id())
-                        .await?;
-                    IndexModel::new(&mut tx)
-                        .add_system_index(namespace, index_metadata)
-                        .await?;
-                }
-            } else {
-                IndexModel::new(&mut tx)
-                    .add_system_index(namespace, index_metadata)
-                    .await?;
-            }
-        }
-        self.commit(tx, "add_system_indexes").await?;
-        Ok(())
+        table_namespace: TableNamespace,
+        source: TableName,
+        target: TableName,
+    ) -> anyhow::Result<u64> {
+        table_clone::clone_table(&self.database, identity, table_namespace, &source, &target).await
     }
 
-    async fn bail_if_not_running(&self) -> anyhow::Result<()> {
-        let backend_state = BackendStateModel::new(&mut self.begin(Identity::Unknown).await?)
-            .get_backend_state()
-            .await?;
-        if backend_state.is_stopped() {
-            anyhow::bail!(ErrorMetadata::bad_request(
-                "BackendIsNotRunning",
-                "Cannot perform this operation when the backend is not running"
-            ));
-        }
-        Ok(())
+    /// Load a fixture of documents into user tables, resolving references
+    /// between them to the IDs they were inserted with.
+    pub async fn seed(
+        &self,
+        identity: &Identity,
+        table_namespace: TableNamespace,
+        fixture: SeedFixture,
+    ) -> anyhow::Result<SeedResult> {
+        seed::seed(&self.database, identity, table_namespace, fixture).await
     }
 
-    pub async fn store_file(
+    /// Fill in the active schema's defaults on existing documents that are
+    /// missing them, returning the number of documents updated.
+    pub async fn backfill_schema_defaults(
         &self,
-        component: ComponentId,
-        content_length: Option<ContentLength>,
-        content_type: Option<ContentType>,
-        expected_sha256: Option<Sha256Digest>,
-        body: BoxStream<'_, anyhow::Result<Bytes>>,
-    ) -> anyhow::Result<DeveloperDocumentId> {
-        self.bail_if_not_running().await?;
-        let storage_id = self
-            .file_storage
-            .store_file(
-                component.into(),
-                content_length,
-                content_type,
-                body,
-                expected_sha256,
-                &self.usage_tracking,
-            )
-            .await?;
-        Ok(storage_id)
+        identity: &Identity,
+        table_namespace: TableNamespace,
+        table_name: TableName,
+    ) -> anyhow::Result<u64> {
+        schema_defaults::backfill_schema_defaults(
+            &self.database,
+            identity,
+            table_namespace,
+            &table_name,
+        )
+        .await
     }
 
-    pub async fn store_file_entry(
+    /// Apply an ordered list of inserts, patches, replaces and deletes in one
+    /// transaction.
+    pub async fn execute_document_batch(
         &self,
-        component: ComponentId,
-        entry: FileStorageEntry,
-    ) -> anyhow::Result<DeveloperDocumentId> {
-        let storage_id = self
-            .file_storage
-            .store_entry(component.into(), entry, &self.usage_tracking)
-            .await?;
-        Ok(storage_id)
+        identity: Identity,
+        table_namespace: TableNamespace,
+        operations: Vec<DocumentBatchOperation>,
+    ) -> anyhow::Result<DocumentBatchResult> {
+        document_batch::execute_document_batch(
+            &self.database,
+            identity,
+            table_namespace,
+            operations,
+        )
+        .await
     }
 
-    pub async fn get_file_entry(
+    /// Read documents matching a filter, served by an index range.
+    pub async fn query_documents(
         &self,
-        component: ComponentId,
-        storage_id: FileStorageId,
-    ) -> anyhow::Result<FileStorageEntry> {
-        let mut file_storage_tx = self.begin(Identity::system()).await?;
+        identity: Identity,
+        table_namespace: TableNamespace,
+        query: DocumentQuery,
+    ) -> anyhow::Result<DocumentQueryResult> {
+        let mut tx = self.begin(identity).await?;
+        document_query::query_documents(&mut tx, table_namespace, query).await
+    }
 
-        let Some(file_entry) = self
-            .file_storage
-            .transactional_file_storage
-            // The transaction is not part of UDF so use the global usage counters.
-            .get_file_entry(&mut file_storage_tx, component.into(), storage_id.clone())
+    /// The component's tables that the HTTP document API exposes to
+    /// non-admin identities.
+    pub async fn list_http_table_access(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<Vec<HttpTableAccess>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("list_http_table_access"));
+        }
+        let mut tx = self.begin(identity).await?;
+        HttpTableAccessModel::new(&mut tx).list(component_id).await
+    }
+
+    /// Set who may read and write a table through the HTTP document API.
+    pub async fn set_http_table_access(
+        &self,
+        identity: Identity,
+        access: HttpTableAccess,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        HttpTableAccessModel::new(&mut tx).set(access).await?;
+        self.commit(tx, "set_http_table_access").await?;
+        Ok(())
+    }
+
+    pub async fn list_function_kill_switches(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<Vec<FunctionKillSwitch>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("list_function_kill_switches"));
+        }
+        let mut tx = self.begin(identity).await?;
+        Ok(FunctionKillSwitchesModel::new(&mut tx)
+            .list(component_id)
+            .await?
+            .into_iter()
+            .map(ParsedDocument::into_value)
+            .collect())
+    }
+
+    /// Stop a function, or every function under a path prefix, from running
+    /// until it's enabled again.
+    pub async fn disable_function(
+        &self,
+        identity: Identity,
+        kill_switch: FunctionKillSwitch,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        FunctionKillSwitchesModel::new(&mut tx)
+            .disable(kill_switch)
+            .await?;
+        self.commit(tx, "disable_function").await?;
+        Ok(())
+    }
+
+    /// Let a disabled function run again, returning whether it was disabled.
+    pub async fn enable_function(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+        target: KillSwitchTarget,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.begin(identity).await?;
+        let enabled = FunctionKillSwitchesModel::new(&mut tx)
+            .enable(component_id, &target)
+            .await?;
+        self.commit(tx, "enable_function").await?;
+        Ok(enabled)
+    }
+
+    #[fastrace::trace]
+    pub async fn document_history(
+        &self,
+        identity: Identity,
+        table_namespace: TableNamespace,
+        id: DeveloperDocumentId,
+        before: Option<Timestamp>,
+        limit: usize,
+    ) -> anyhow::Result<DocumentHistory> {
+        self.database
+            .document_history(identity, table_namespace, id, before, limit)
+            .await
+    }
+
+    pub async fn retention_backlog(&self, identity: Identity) -> anyhow::Result<RetentionBacklog> {
+        self.database.retention_backlog(identity).await
+    }
+
+    pub fn prioritize_retention(
+        &self,
+        identity: Identity,
+        table_namespace: TableNamespace,
+        table_names: Vec<TableName>,
+    ) -> anyhow::Result<()> {
+        self.database
+            .prioritize_retention(identity, table_namespace, table_names)
+    }
+
+    /// Scheduled jobs that are due but haven't run yet, summed over every
+    /// component, and how many jobs finished recently.
+    pub async fn scheduler_backlog(&self, identity: &Identity) -> anyhow::Result<SchedulerBacklog> {
+        anyhow::ensure!(
+            identity.is_admin() || identity.is_system(),
+            unauthorized_error("scheduler_backlog")
+        );
+        scheduler_backlog(&self.runtime, &self.database).await
+    }
+
+    /// The latest per-table storage statistics, or `None` if the table stats
+    /// worker hasn't finished computing them yet.
+    pub fn table_stats(&self, identity: &Identity) -> anyhow::Result<Option<TableStatsSnapshot>> {
+        anyhow::ensure!(
+            identity.is_admin() || identity.is_system(),
+            unauthorized_error("table_stats")
+        );
+        Ok(self.table_stats.lock().clone())
+    }
+
+    /// Registers a data migration. The migration worker picks it up once all
+    /// migrations with a lower version have completed.
+    pub async fn register_data_migration(
+        &self,
+        identity: &Identity,
+        migration: DataMigration,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        DataMigrationsModel::new(&mut tx)
+            .register(migration)
+            .await?;
+        self.commit(tx, "register_data_migration").await?;
+        Ok(())
+    }
+
+    pub async fn list_data_migrations(
+        &self,
+        identity: &Identity,
+    ) -> anyhow::Result<Vec<DataMigration>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let migrations = DataMigrationsModel::new(&mut tx).list().await?;
+        Ok(migrations
+            .into_iter()
+            .map(|migration| migration.into_value())
+            .collect())
+    }
+
+    /// Resumes a failed data migration from its last checkpoint.
+    pub async fn retry_data_migration(
+        &self,
+        identity: &Identity,
+        name: &str,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        DataMigrationsModel::new(&mut tx).retry(name).await?;
+        self.commit(tx, "retry_data_migration").await?;
+        Ok(())
+    }
+
+    /// Runs a data migration for real after its dry run completed.
+    pub async fn start_data_migration_real_run(
+        &self,
+        identity: &Identity,
+        name: &str,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        DataMigrationsModel::new(&mut tx)
+            .start_real_run(name)
+            .await?;
+        self.commit(tx, "start_data_migration_real_run").await?;
+        Ok(())
+    }
+
+    /// Ask the schema worker to check the component's documents against
+    /// `schema` without enforcing it. Replaces any previous report.
+    pub async fn request_schema_validation_report(
+        &self,
+        identity: &Identity,
+        component_id: ComponentId,
+        schema: DatabaseSchema,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        SchemaValidationReportModel::new(&mut tx, component_id.into())
+            .request(schema)
+            .await?;
+        self.commit(tx, "request_schema_validation_report").await?;
+        Ok(())
+    }
+
+    pub async fn get_schema_validation_report(
+        &self,
+        identity: &Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<Option<SchemaValidationReport>> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let report = SchemaValidationReportModel::new(&mut tx, component_id.into())
+            .get()
+            .await?;
+        Ok(report.map(|report| report.into_value()))
+    }
+
+    /// Deletes an unmounted component and its descendants. With
+    /// `cascade_delete_files`, their files are deleted from storage in the
+    /// background; otherwise they're left for storage GC.
+    pub async fn delete_component(
+        &self,
+        identity: &Identity,
+        component_id: ComponentId,
+        cascade_delete_files: bool,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        // Find the `_storage` tables before they're deleted with the components.
+        let file_storage_tablets: BTreeMap<_, _> = tx
+            .table_mapping()
+            .iter()
+            .filter(|(_, _, _, table_name)| **table_name == *FILE_STORAGE_TABLE)
+            .map(|(tablet_id, namespace, ..)| (namespace, tablet_id))
+            .collect();
+        let snapshot_ts = tx.begin_timestamp();
+        let deleted = ComponentConfigModel::new(&mut tx)
+            .delete_component(component_id)
+            .await?;
+        self.commit(tx, "delete_component").await?;
+        if cascade_delete_files {
+            let tablets = deleted
+                .into_iter()
+                .filter_map(|component_id| {
+                    file_storage_tablets
+                        .get(&TableNamespace::from(component_id))
+                        .copied()
+                })
+                .collect();
+            let delete_files = file_attachments::delete_component_files(
+                self.database.clone(),
+                self.files_storage.clone(),
+                snapshot_ts,
+                tablets,
+            );
+            // The component is already gone, so files left behind by a failure
+            // here are collected as orphans by storage GC.
+            drop(self.runtime.spawn("delete_component_files", async move {
+                if let Err(mut e) = delete_files.await {
+                    report_error(&mut e).await;
+                }
+            }));
+        }
+        Ok(())
+    }
+
+    /// Versions of the component's modules and config that it can be rolled
+    /// back to, newest first.
+    pub async fn list_component_versions(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<Vec<ParsedDocument<ComponentVersion>>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("list_component_versions"));
+        }
+        let mut tx = self.begin(identity).await?;
+        ComponentVersionsModel::new(&mut tx)
+            .list(component_id)
+            .await
+    }
+
+    /// Per-module bundle sizes and external dependencies of a version of the
+    /// component (its latest if `version` is `None`), with deltas from the
+    /// version pushed before it.
+    pub async fn bundle_size_report(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+        version: Option<u64>,
+    ) -> anyhow::Result<Option<BundleSizeReport>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("bundle_size_report"));
+        }
+        let mut tx = self.begin(identity).await?;
+        ComponentVersionsModel::new(&mut tx)
+            .bundle_size_report(component_id, version)
+            .await
+    }
+
+    /// Restore the modules and config the component had at `version`, without
+    /// pushing its source again. Returns the version created by the rollback.
+    pub async fn rollback_component(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+        version: u64,
+    ) -> anyhow::Result<u64> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("rollback_component"));
+        }
+        self.execute_with_audit_log_events_and_occ_retries(identity, "rollback_component", |tx| {
+            async move {
+                let (new_version, diff) = ComponentConfigModel::new(tx)
+                    .rollback_component(component_id, version)
+                    .await?;
+                let component_path = BootstrapComponentsModel::new(tx)
+                    .get_component_path(component_id)
+                    .unwrap_or_else(ComponentPath::root);
+                let diffs = PushComponentDiffs {
+                    auth_diff: Default::default(),
+                    component_diffs: btreemap! { component_path => diff },
+                };
+                Ok((
+                    new_version,
+                    vec![DeploymentAuditLogEvent::PushConfigWithComponents { diffs }],
+                ))
+            }
+            .into()
+        })
+        .await
+    }
+
+    pub async fn get_component_limits(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<Option<ComponentLimits>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("get_component_limits"));
+        }
+        let mut tx = self.begin(identity).await?;
+        ComponentLimitsModel::new(&mut tx)
+            .get_limits(component_id)
+            .await
+    }
+
+    /// Render the component's active schema as TypeScript types and JSON
+    /// Schema. Returns `None` if the component has no active schema.
+    pub async fn schema_type_definitions(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+        value_format: ValueFormat,
+    ) -> anyhow::Result<Option<SchemaTypeDefinitions>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("schema_type_definitions"));
+        }
+        let mut tx = self.begin(identity).await?;
+        let schema = SchemaModel::new(&mut tx, component_id.into())
+            .get_by_state(SchemaState::Active)
+            .await?;
+        Ok(schema.map(|(_, schema)| SchemaTypeDefinitions::new(&schema, value_format)))
+    }
+
+    /// Report the tables, indexes, files, scheduled functions, crons and
+    /// environment variables a component owns.
+    pub async fn audit_component(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<ComponentAudit> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("audit_component"));
+        }
+        let mut tx = self.begin(identity).await?;
+        audit_component(&mut tx, component_id).await
+    }
+
+    /// Override the deployment-wide execution limits for a single component.
+    /// Limits with no fields set remove the component's overrides.
+    pub async fn set_component_limits(
+        &self,
+        identity: Identity,
+        limits: ComponentLimits,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        ComponentLimitsModel::new(&mut tx).set(limits).await?;
+        self.commit(tx, "set_component_limits").await?;
+        Ok(())
+    }
+
+    pub async fn get_outbound_network_policy(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<OutboundNetworkPolicy> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("get_outbound_network_policy"));
+        }
+        let mut tx = self.begin(identity).await?;
+        OutboundNetworkPolicyModel::new(&mut tx).get().await
+    }
+
+    /// Replace the hosts that actions may send requests to. Takes effect for
+    /// actions that start after the change commits.
+    pub async fn set_outbound_network_policy(
+        &self,
+        identity: Identity,
+        policy: OutboundNetworkPolicy,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        OutboundNetworkPolicyModel::new(&mut tx)
+            .set(policy.clone())
+            .await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::UpdateOutboundNetworkPolicy { policy }],
+            "set_outbound_network_policy",
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_ip_access_policy(&self, identity: Identity) -> anyhow::Result<IpAccessPolicy> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("get_ip_access_policy"));
+        }
+        let mut tx = self.begin(identity).await?;
+        IpAccessPolicyModel::new(&mut tx).get().await
+    }
+
+    /// Replace the rules for which client addresses may reach the deployment.
+    /// Rejects a policy that would deny `client_ip` on the admin APIs or for
+    /// the admin setting it, so admins can't lock themselves out.
+    pub async fn set_ip_access_policy(
+        &self,
+        identity: Identity,
+        policy: IpAccessPolicy,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<()> {
+        if let Some(ip) = client_ip
+            && (policy.check(IpAccessSurface::Admin, ip).is_err()
+                || IpAccessIdentity::from_identity(&identity)
+                    .is_some_and(|identity| policy.check_identity(&identity, ip).is_err()))
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "IpAccessPolicyLockout",
+                format!(
+                    "This policy would deny admin requests from your address, {ip}. Allow it \
+                     before restricting admin access."
+                ),
+            ));
+        }
+        let mut tx = self.begin(identity).await?;
+        IpAccessPolicyModel::new(&mut tx)
+            .set(policy.clone())
+            .await?;
+        let ts = self
+            .commit_with_audit_log_events(
+                tx,
+                vec![DeploymentAuditLogEvent::UpdateIpAccessPolicy {
+                    policy: policy.clone(),
+                }],
+                "set_ip_access_policy",
+            )
+            .await?;
+        // Apply the policy right away instead of waiting for the worker to
+        // notice it changed.
+        self.ip_access_policy_cache.update(ts, policy);
+        Ok(())
+    }
+
+    /// The IP access policy in effect, read from the database only until
+    /// `IpAccessPolicyWorker` first caches it.
+    async fn cached_ip_access_policy(&self) -> anyhow::Result<Arc<IpAccessPolicy>> {
+        if let Some(policy) = self.ip_access_policy_cache.get() {
+            return Ok(policy);
+        }
+        let mut tx = self.begin(Identity::system()).await?;
+        let policy = IpAccessPolicyModel::new(&mut tx).get().await?;
+        self.ip_access_policy_cache
+            .update(*tx.begin_timestamp(), policy.clone());
+        Ok(Arc::new(policy))
+    }
+
+    /// Rejects a request to `surface` if the IP access policy doesn't allow
+    /// the client's address, recording the attempt in the audit log. Requests
+    /// without a client address, like those made within the process, are
+    /// always allowed.
+    pub async fn check_ip_access(
+        &self,
+        surface: IpAccessSurface,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<()> {
+        let Some(ip) = client_ip else {
+            return Ok(());
+        };
+        let policy = self.cached_ip_access_policy().await?;
+        let Err(reason) = policy.check(surface, ip) else {
+            return Ok(());
+        };
+        self.record_ip_access_denial(
+            ip,
+            surface.to_string(),
+            DeploymentAuditLogEvent::IpAccessDenied {
+                ip,
+                surface,
+                reason,
+            },
+        )
+        .await;
+        anyhow::bail!(ErrorMetadata::forbidden(
+            "IpAddressNotAllowed",
+            format!("Requests from {ip} aren't allowed by this deployment's IP access policy"),
+        ))
+    }
+
+    /// Rejects `identity` authenticating from `ip` if the IP access policy
+    /// has rules for the identity that don't allow the address.
+    async fn check_identity_ip_access(
+        &self,
+        identity: &Identity,
+        ip: IpAddr,
+    ) -> anyhow::Result<()> {
+        let Some(identity) = IpAccessIdentity::from_identity(identity) else {
+            return Ok(());
+        };
+        let policy = self.cached_ip_access_policy().await?;
+        let Err(reason) = policy.check_identity(&identity, ip) else {
+            return Ok(());
+        };
+        let message = format!(
+            "{identity} can't authenticate from {ip} under this deployment's IP access policy"
+        );
+        self.record_ip_access_denial(
+            ip,
+            identity.to_string(),
+            DeploymentAuditLogEvent::IdentityIpAccessDenied {
+                ip,
+                identity,
+                reason,
+            },
+        )
+        .await;
+        anyhow::bail!(ErrorMetadata::forbidden("IpAddressNotAllowed", message))
+    }
+
+    /// Records a denied request in the audit log, unless the same denial was
+    /// recorded recently.
+    async fn record_ip_access_denial(
+        &self,
+        ip: IpAddr,
+        denied_for: String,
+        event: DeploymentAuditLogEvent,
+    ) {
+        if !self
+            .ip_access_denials
+            .should_record(ip, denied_for, self.runtime.monotonic_now())
+        {
+            return;
+        }
+        let result = async {
+            let tx = self.begin(Identity::system()).await?;
+            self.commit_with_audit_log_events(tx, vec![event], "record_ip_access_denied")
+                .await
+        }
+        .await;
+        // The request is still rejected if the audit log write fails.
+        if let Err(mut e) = result {
+            report_error(&mut e).await;
+        }
+    }
+
+    pub fn connection_pool_stats(
+        &self,
+        identity: &Identity,
+    ) -> anyhow::Result<Option<ConnectionPoolSnapshot>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("connection_pool_stats"));
+        }
+        Ok(self.database.connection_pool_stats())
+    }
+
+    /// Cap how many persistence pool connections `workload` may hold at
+    /// once, or uncap it if `limit` is `None`. The change lasts until the
+    /// backend restarts, after which the knobs' limits apply again.
+    pub async fn set_connection_pool_limit(
+        &self,
+        identity: Identity,
+        workload: PoolWorkload,
+        limit: Option<NonZeroUsize>,
+    ) -> anyhow::Result<()> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("set_connection_pool_limit"));
+        }
+        self.database.set_connection_pool_limit(workload, limit)?;
+        let tx = self.begin(identity).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::SetConnectionPoolLimit {
+                workload,
+                limit: limit.map(|limit| limit.get() as u64),
+            }],
+            "set_connection_pool_limit",
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_traffic_split(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<Option<TrafficSplit>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("get_traffic_split"));
+        }
+        let mut tx = self.begin(identity).await?;
+        Ok(TrafficSplitModel::new(&mut tx)
+            .get(component_id)
+            .await?
+            .map(ParsedDocument::into_value))
+    }
+
+    /// Start routing a percentage of the component's query and mutation
+    /// traffic to `split.candidate_version`, resetting its comparison stats.
+    pub async fn set_traffic_split(
+        &self,
+        identity: Identity,
+        split: TrafficSplit,
+    ) -> anyhow::Result<()> {
+        let component_id = split.component;
+        let mut tx = self.begin(identity).await?;
+        TrafficSplitModel::new(&mut tx).set(split).await?;
+        self.commit(tx, "set_traffic_split").await?;
+        self.runner.traffic_split_stats().reset(component_id);
+        Ok(())
+    }
+
+    /// Execution stats for each variant of the component's split functions
+    /// since the split was last set.
+    pub fn traffic_split_stats(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<ComponentTrafficSplitStats> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("traffic_split_stats"));
+        }
+        Ok(self.runner.traffic_split_stats().get(component_id))
+    }
+
+    /// Send all traffic to the split's candidate version and remove the
+    /// split. Returns the component version created by the promotion.
+    pub async fn promote_traffic_split(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<u64> {
+        self.finish_traffic_split(identity, component_id, TrafficSplitVariant::Candidate)
+            .await
+    }
+
+    /// Send all traffic back to the split's stable version and remove the
+    /// split. Returns the component version created by the rollback.
+    pub async fn rollback_traffic_split(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+    ) -> anyhow::Result<u64> {
+        self.finish_traffic_split(identity, component_id, TrafficSplitVariant::Stable)
+            .await
+    }
+
+    async fn finish_traffic_split(
+        &self,
+        identity: Identity,
+        component_id: ComponentId,
+        keep: TrafficSplitVariant,
+    ) -> anyhow::Result<u64> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("finish_traffic_split"));
+        }
+        let new_version = self
+            .execute_with_audit_log_events_and_occ_retries(identity, "finish_traffic_split", |tx| {
+                async move {
+                    let Some(split) = TrafficSplitModel::new(tx).clear(component_id).await? else {
+                        anyhow::bail!(ErrorMetadata::bad_request(
+                            "NoTrafficSplit",
+                            "This component doesn't have a traffic split"
+                        ));
+                    };
+                    let version = match keep {
+                        TrafficSplitVariant::Stable => split.stable_version,
+                        TrafficSplitVariant::Candidate => split.candidate_version,
+                    };
+                    let (new_version, diff) = ComponentConfigModel::new(tx)
+                        .rollback_component(component_id, version)
+                        .await?;
+                    let component_path = BootstrapComponentsModel::new(tx)
+                        .get_component_path(component_id)
+                        .unwrap_or_else(ComponentPath::root);
+                    let diffs = PushComponentDiffs {
+                        auth_diff: Default::default(),
+                        component_diffs: btreemap! { component_path => diff },
+                    };
+                    Ok((
+                        new_version,
+                        vec![DeploymentAuditLogEvent::PushConfigWithComponents { diffs }],
+                    ))
+                }
+                .into()
+            })
+            .await?;
+        self.runner.traffic_split_stats().reset(component_id);
+        Ok(new_version)
+    }
+
+    /// Add system indexes if they do not already exist and update
+    /// existing indexes if needed.
+    pub async fn _add_system_indexes(
+        &self,
+        identity: &Identity,
+        indexes: BTreeMap<IndexName, IndexedFields>,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity.clone()).await?;
+        let namespace = TableNamespace::by_component_TODO();
+        for (index_name, index_fields) in indexes.into_iter() {
+            let index_fields = self._validate_user_defined_index_fields(index_fields)?;
+            let index_metadata =
+                IndexMetadata::new_backfilling(*tx.begin_timestamp(), index_name, index_fields);
+            let mut model = IndexModel::new(&mut tx);
+            if let Some(existing_index_metadata) = model
+                .pending_index_metadata(namespace, &index_metadata.name)?
+                .or(model.enabled_index_metadata(namespace, &index_metadata.name)?)
+            {
+                if !index_metadata
+                    .config
+                    .same_config(&existing_index_metadata.config)
+                {
+                    IndexModel::new(&mut tx)
+                        .drop_index(existing_index_metadata.id())
+                        .await?;
+                    IndexModel::new(&mut tx)
+                        .add_system_index(namespace, index_metadata)
+                        .await?;
+                }
+            } else {
+                IndexModel::new(&mut tx)
+                    .add_system_index(namespace, index_metadata)
+                    .await?;
+            }
+        }
+        self.commit(tx, "add_system_indexes").await?;
+        Ok(())
+    }
+
+    /// Schedule the deployment to be paused between `start` and `end`, showing
+    /// clients `message` instead of the default paused message.
+    pub async fn schedule_maintenance_window(
+        &self,
+        identity: Identity,
+        start: UnixTimestamp,
+        end: Option<UnixTimestamp>,
+        message: Option<String>,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        let mut tx = self.begin(identity).await?;
+        let id = MaintenanceWindowsModel::new(&mut tx)
+            .schedule(start, end, message)
+            .await?;
+        self.commit(tx, "schedule_maintenance_window").await?;
+        Ok(id)
+    }
+
+    pub async fn list_maintenance_windows(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<Vec<ParsedDocument<MaintenanceWindow>>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("list_maintenance_windows"));
+        }
+        let mut tx = self.begin(identity).await?;
+        MaintenanceWindowsModel::new(&mut tx).list().await
+    }
+
+    /// Cancel a maintenance window, resuming the deployment if the window
+    /// already paused it.
+    pub async fn cancel_maintenance_window(
+        &self,
+        identity: Identity,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        MaintenanceWindowsModel::new(&mut tx).cancel(id).await?;
+        self.commit(tx, "cancel_maintenance_window").await?;
+        Ok(())
+    }
+
+    /// Create a preview deployment that expires after `ttl`, or renew an
+    /// existing one, keeping its tables. Returns when it expires.
+    pub async fn create_preview_deployment(
+        &self,
+        identity: Identity,
+        name: String,
+        ttl: Option<Duration>,
+    ) -> anyhow::Result<UnixTimestamp> {
+        let expires_at =
+            self.runtime.unix_timestamp() + ttl.unwrap_or(*PREVIEW_DEPLOYMENT_DEFAULT_TTL);
+        let mut tx = self.begin(identity).await?;
+        PreviewDeploymentsModel::new(&mut tx)
+            .create_or_renew(name, expires_at)
+            .await?;
+        self.commit(tx, "create_preview_deployment").await?;
+        Ok(expires_at)
+    }
+
+    pub async fn list_preview_deployments(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<Vec<ParsedDocument<PreviewDeployment>>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("list_preview_deployments"));
+        }
+        let mut tx = self.begin(identity).await?;
+        PreviewDeploymentsModel::new(&mut tx).list().await
+    }
+
+    /// The namespace holding a preview deployment's tables.
+    pub async fn preview_deployment_namespace(
+        &self,
+        identity: Identity,
+        name: &str,
+    ) -> anyhow::Result<TableNamespace> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("preview_deployment_namespace"));
+        }
+        let mut tx = self.begin(identity).await?;
+        PreviewDeploymentsModel::new(&mut tx).namespace(name).await
+    }
+
+    /// Delete a preview deployment and its tables before it expires.
+    pub async fn delete_preview_deployment(
+        &self,
+        identity: Identity,
+        name: &str,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.begin(identity).await?;
+        PreviewDeploymentsModel::new(&mut tx).delete(name).await?;
+        self.commit(tx, "delete_preview_deployment").await?;
+        Ok(())
+    }
+
+    async fn bail_if_not_running(&self) -> anyhow::Result<()> {
+        let backend_state = BackendStateModel::new(&mut self.begin(Identity::Unknown).await?)
+            .get_backend_state()
+            .await?;
+        if backend_state.is_stopped() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "BackendIsNotRunning",
+                "Cannot perform this operation when the backend is not running"
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn store_file(
+        &self,
+        component: ComponentId,
+        content_length: Option<ContentLength>,
+        content_type: Option<ContentType>,
+        expected_sha256: Option<Sha256Digest>,
+        encryption: Option<FileEncryptionMetadata>,
+        body: BoxStream<'_, anyhow::Result<Bytes>>,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        self.bail_if_not_running().await?;
+        let storage_id = self
+            .file_storage
+            .store_file(
+                component.into(),
+                content_length,
+                content_type,
+                body,
+                expected_sha256,
+                encryption,
+                &self.usage_tracking,
+            )
+            .await?;
+        Ok(storage_id)
+    }
+
+    pub async fn store_file_entry(
+        &self,
+        component: ComponentId,
+        entry: FileStorageEntry,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        let storage_id = self
+            .file_storage
+            .store_entry(component.into(), entry, &self.usage_tracking)
+            .await?;
+        Ok(storage_id)
+    }
+
+    pub async fn get_file_entry(
+        &self,
+        component: ComponentId,
+        storage_id: FileStorageId,
+    ) -> anyhow::Result<FileStorageEntry> {
+        let mut file_storage_tx = self.begin(Identity::system()).await?;
+
+        let Some(file_entry) = self
+            .file_storage
+            .transactional_file_storage
+            // The transaction is not part of UDF so use the global usage counters.
+            .get_file_entry(&mut file_storage_tx, component.into(), storage_id.clone())
             .await?
         else {
             return Err(ErrorMetadata::not_found(
@@ -2470,6 +3867,75 @@ impl<RT: Runtime> Application<RT> {
         Ok(file_entry)
     }
 
+    pub async fn get_stored_file(
+        &self,
+        component: ComponentId,
+        storage_id: FileStorageId,
+    ) -> anyhow::Result<StoredFile> {
+        self.bail_if_not_running().await?;
+        let mut tx = self.begin(Identity::system()).await?;
+        let Some(file) = self
+            .file_storage
+            .transactional_file_storage
+            .get_stored_file(&mut tx, component.into(), storage_id.clone())
+            .await?
+        else {
+            return Err(ErrorMetadata::not_found(
+                "FileNotFound",
+                format!("File {storage_id} not found"),
+            )
+            .into());
+        };
+        self.runner
+            .surrogate_keys
+            .watch_file(file.entry.storage_id.to_string(), tx.into_token()?);
+        Ok(file)
+    }
+
+    /// Purge responses tagged with any of `keys` from the CDN.
+    pub async fn purge_surrogate_keys(
+        &self,
+        identity: Identity,
+        keys: Vec<String>,
+    ) -> anyhow::Result<()> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("purge_surrogate_keys"));
+        }
+        self.runner.surrogate_keys.purge(keys).await
+    }
+
+    /// Checks the token from a signed file URL. Besides the checks done by the
+    /// key broker, the URL mustn't have been revoked and, if it's bound to an
+    /// IP address, the client must be connecting from that address.
+    pub async fn check_signed_file_url(
+        &self,
+        token: &str,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<SignedFileUrl> {
+        let signed_url = self
+            .key_broker()
+            .check_signed_file_url(token, self.runtime.unix_timestamp())?;
+        if let Some(ip) = signed_url.ip
+            && client_ip != Some(ip)
+        {
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "SignedUrlIpMismatch",
+                "This signed URL can't be used from this IP address",
+            ));
+        }
+        let mut tx = self.begin(Identity::system()).await?;
+        if FileUrlRevocationsModel::new(&mut tx)
+            .is_revoked(&signed_url.nonce)
+            .await?
+        {
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "SignedUrlRevoked",
+                "This signed URL has been revoked",
+            ));
+        }
+        Ok(signed_url)
+    }
+
     pub async fn get_file(
         &self,
         component: ComponentId,
@@ -2505,6 +3971,16 @@ impl<RT: Runtime> Application<RT> {
             .await
     }
 
+    /// Serves a URL from `storage.getImageUrl()`, transforming the image if it
+    /// isn't cached yet.
+    pub async fn get_transformed_image(&self, token: &str) -> anyhow::Result<FileStream> {
+        self.bail_if_not_running().await?;
+        let url = self.key_broker().check_image_transform_url(token)?;
+        self.file_storage
+            .get_transformed_image(url, self.usage_tracking.clone())
+            .await
+    }
+
     pub async fn get_file_range(
         &self,
         component: ComponentId,
@@ -2548,17 +4024,70 @@ impl<RT: Runtime> Application<RT> {
             .await
     }
 
+    /// Issue an ID token for the anonymous identity bound to `device_id`,
+    /// creating the identity if the device doesn't have one yet. Anonymous
+    /// identities must be enabled in the deployment's auth config.
+    pub async fn issue_anonymous_identity_token(
+        &self,
+        device_id: String,
+    ) -> anyhow::Result<String> {
+        if device_id.is_empty() || device_id.len() > MAX_DEVICE_ID_LENGTH {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidDeviceId",
+                format!("Device IDs must be between 1 and {MAX_DEVICE_ID_LENGTH} characters")
+            ));
+        }
+        let mut tx = self.begin(Identity::system()).await?;
+        let enabled = AuthInfoModel::new(&mut tx)
+            .get()
+            .await?
+            .iter()
+            .any(|auth_info| {
+                auth_info.domain.trim_end_matches('/') == ANONYMOUS_IDENTITY_ISSUER
+                    && auth_info.application_id == ANONYMOUS_IDENTITY_AUDIENCE
+            });
+        if !enabled {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "AnonymousIdentitiesDisabled",
+                format!(
+                    "Add an auth provider with domain \"{ANONYMOUS_IDENTITY_ISSUER}\" and \
+                     applicationID \"{ANONYMOUS_IDENTITY_AUDIENCE}\" to auth.config.js to enable \
+                     anonymous identities"
+                )
+            ));
+        }
+        let anonymous_id = AnonymousIdentitiesModel::new(&mut tx)
+            .get_or_create(&device_id)
+            .await?;
+        self.commit(tx, "issue_anonymous_identity_token").await?;
+        let now = self.runtime.system_time();
+        self.key_broker().issue_anonymous_identity_token(
+            anonymous_id,
+            now,
+            now + *ANONYMOUS_IDENTITY_TOKEN_TTL,
+        )
+    }
+
     pub async fn authenticate(
         &self,
         token: AuthenticationToken,
         system_time: SystemTime,
+    ) -> anyhow::Result<Identity> {
+        self.authenticate_from(token, system_time, None).await
+    }
+
+    /// Like `authenticate`, but also counts invalid admin keys against the
+    /// client's address, so repeated guesses from it get locked out, and
+    /// rejects identities whose IP access rules don't allow the address.
+    pub async fn authenticate_from(
+        &self,
+        token: AuthenticationToken,
+        system_time: SystemTime,
+        client_ip: Option<IpAddr>,
     ) -> anyhow::Result<Identity> {
         let identity = match token {
             AuthenticationToken::Admin(token, acting_as) => {
-                let admin_identity = self
-                    .app_auth()
-                    .check_key(token.to_string(), self.instance_name())
-                    .await?;
+                let admin_identity = self.check_admin_key(token, client_ip).await?;
 
                 match acting_as {
                     Some(acting_user) => {
@@ -2585,15 +4114,97 @@ impl<RT: Runtime> Application<RT> {
                         .map(|auth_info| auth_info.into_value())
                         .collect(),
                     system_time,
+                    &self.provider_metadata_cache,
+                    self.key_broker(),
                 )
                 .await?;
+                if identity.issuer == ANONYMOUS_IDENTITY_ISSUER
+                    && AnonymousIdentitiesModel::new(&mut tx)
+                        .is_promoted(&identity.subject)
+                        .await?
+                {
+                    anyhow::bail!(ErrorMetadata::unauthenticated(
+                        "AnonymousIdentityPromoted",
+                        "Anonymous identity was promoted to a user and can't sign in anymore"
+                    ));
+                }
                 Identity::user(identity)
             },
             AuthenticationToken::None => Identity::Unknown,
         };
+        if let Some(ip) = client_ip {
+            self.check_identity_ip_access(&identity, ip).await?;
+        }
         Ok(identity)
     }
 
+    /// Checks an admin key, unless the key or the client's address is locked
+    /// out after too many invalid attempts. Locked out requests are rejected
+    /// before the key is checked, so a guess made during a lockout fails even
+    /// if it is right. Failed checks are held for a growing delay before
+    /// they're answered.
+    async fn check_admin_key(
+        &self,
+        key: String,
+        client_ip: Option<IpAddr>,
+    ) -> anyhow::Result<Identity> {
+        let fingerprint = admin_key_fingerprint(&key);
+        let sources = admin_key_sources(&fingerprint, client_ip);
+        let now = self.runtime.monotonic_now();
+        for source in &sources {
+            if let Err(remaining) = self.admin_key_lockout.check(source, now) {
+                log_admin_key_locked_out_request();
+                anyhow::bail!(ErrorMetadata::rate_limited(
+                    "AdminKeyLockedOut",
+                    format!(
+                        "Too many requests with an invalid admin key. Try again in {} seconds.",
+                        remaining.as_secs().max(1)
+                    ),
+                ));
+            }
+        }
+        let err = match self.app_auth().check_key(key, self.instance_name()).await {
+            Ok(identity) => return Ok(identity),
+            // Don't count errors that don't say anything about the key, like
+            // failing to reach the access token service.
+            Err(e) if !(e.is_unauthenticated() || e.is_forbidden()) => return Err(e),
+            Err(e) => e,
+        };
+        log_admin_key_auth_failure();
+        let mut delay = Duration::ZERO;
+        let mut lockout: Option<(u32, Duration)> = None;
+        for source in sources {
+            let failure = self.admin_key_lockout.record_failure(source, now);
+            match failure.lockout {
+                Some(duration) => {
+                    lockout = lockout.max(Some((failure.failed_attempts, duration)));
+                },
+                None => delay = delay.max(admin_key_failure_delay(failure.failed_attempts)),
+            }
+        }
+        if let Some((failed_attempts, duration)) = lockout {
+            log_admin_key_lockout();
+            let event = DeploymentAuditLogEvent::AdminKeyLockout {
+                ip: client_ip.map(|ip| ip.to_canonical()),
+                key_fingerprint: fingerprint,
+                failed_attempts: failed_attempts as u64,
+                duration_secs: duration.as_secs(),
+            };
+            let result = async {
+                let tx = self.begin(Identity::system()).await?;
+                self.commit_with_audit_log_events(tx, vec![event], "admin_key_lockout")
+                    .await
+            }
+            .await;
+            if let Err(mut e) = result {
+                report_error(&mut e).await;
+            }
+        } else {
+            self.runtime.wait(delay).await;
+        }
+        Err(err)
+    }
+
     pub async fn validate_component_id(
         &self,
         identity: Identity,
@@ -2607,6 +4218,83 @@ impl<RT: Runtime> Application<RT> {
         Ok(())
     }
 
+    /// Persisted function executions matching `filter`, newest first.
+    pub async fn list_function_execution_records(
+        &self,
+        identity: Identity,
+        filter: FunctionExecutionRecordFilter,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<FunctionExecutionRecordPage> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("list_function_execution_records"));
+        }
+        let mut tx = self.begin(identity).await?;
+        FunctionExecutionRecordModel::new(&mut tx)
+            .list(filter, cursor, limit)
+            .await
+    }
+
+    /// Usage since the last usage export, or since the backend started if
+    /// there hasn't been one.
+    pub async fn current_usage_report(&self, identity: Identity) -> anyhow::Result<UsageReport> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("current_usage_report"));
+        }
+        let snapshot = self.latest_snapshot()?;
+        self.usage_aggregator
+            .current_report(self.runtime.unix_timestamp(), &snapshot)
+    }
+
+    pub async fn list_usage_exports(
+        &self,
+        identity: Identity,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<UsageExportPage> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("list_usage_exports"));
+        }
+        let mut tx = self.begin(identity).await?;
+        UsageExportModel::new(&mut tx).list(cursor, limit).await
+    }
+
+    /// Queries that exceeded the slow query thresholds, newest first.
+    pub async fn list_slow_queries(
+        &self,
+        identity: Identity,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<SlowQueryPage> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("list_slow_queries"));
+        }
+        let mut tx = self.begin(identity).await?;
+        SlowQueryModel::new(&mut tx).list(cursor, limit).await
+    }
+
+    /// Indexes suggested by the most recent `max_queries` slow queries.
+    pub async fn index_recommendations(
+        &self,
+        identity: Identity,
+        max_queries: usize,
+    ) -> anyhow::Result<Vec<IndexRecommendation>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("index_recommendations"));
+        }
+        let mut tx = self.begin(identity).await?;
+        SlowQueryModel::new(&mut tx)
+            .index_recommendations(max_queries)
+            .await
+    }
+
+    pub async fn prometheus_metrics(&self, identity: Identity) -> anyhow::Result<String> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("prometheus_metrics"));
+        }
+        self.function_log.prometheus_metrics()
+    }
+
     pub async fn udf_rate(
         &self,
         identity: Identity,
@@ -2960,11 +4648,44 @@ impl<RT: Runtime> Application<RT> {
         self.files_storage.clone()
     }
 
+    /// Persist state that background workers only buffer in memory, so it
+    /// isn't lost when they're stopped. Failures are reported rather than
+    /// returned so they don't block shutdown.
+    async fn checkpoint_workers(&self) {
+        let slow_queries = self.runtime.with_timeout(
+            "checkpoint_slow_query_worker",
+            *SHUTDOWN_DRAIN_TIMEOUT,
+            SlowQueryWorker::checkpoint(self.runtime.clone(), self.database.clone()),
+        );
+        if let Err(e) = slow_queries.await {
+            report_error(&mut e.context("Failed to checkpoint SlowQueryWorker")).await;
+        }
+        let usage = self.runtime.with_timeout(
+            "checkpoint_usage_export_worker",
+            *SHUTDOWN_DRAIN_TIMEOUT,
+            UsageExportWorker::checkpoint(
+                self.runtime.clone(),
+                self.database.clone(),
+                self.exports_storage.clone(),
+                self.usage_aggregator.clone(),
+            ),
+        );
+        if let Err(e) = usage.await {
+            report_error(&mut e.context("Failed to checkpoint UsageExportWorker")).await;
+        }
+    }
+
     pub async fn shutdown(&self) -> anyhow::Result<()> {
+        self.checkpoint_workers().await;
         self.log_sender.shutdown()?;
         self.table_summary_worker.shutdown().await?;
         self.system_table_cleanup_worker.lock().shutdown();
         self.schema_worker.lock().shutdown();
+        self.validation_report_worker.lock().shutdown();
+        self.deployment_config_worker.lock().shutdown();
+        self.maintenance_window_worker.lock().shutdown();
+        self.preview_deployment_worker.lock().shutdown();
+        self.ip_access_policy_worker.lock().shutdown();
         self.index_worker.lock().shutdown();
         self.search_worker.lock().shutdown();
         self.search_and_vector_bootstrap_worker.lock().shutdown();
@@ -2974,6 +4695,15 @@ impl<RT: Runtime> Application<RT> {
         self.runner.shutdown().await?;
         self.scheduled_job_runner.shutdown();
         self.cron_job_executor.lock().shutdown();
+        self.data_migration_worker.lock().shutdown();
+        self.function_execution_record_worker.lock().shutdown();
+        self.usage_export_worker.lock().shutdown();
+        self.slow_query_worker.lock().shutdown();
+        self.table_stats_worker.lock().shutdown();
+        self.storage_gc_worker.lock().shutdown();
+        self.file_attachment_cascade_worker.lock().shutdown();
+        self.file_text_worker.lock().shutdown();
+        self.provider_metadata_refresher.lock().shutdown();
         self.database.shutdown().await?;
         let migration_worker = self.migration_worker.lock().take();
         if let Some(migration_worker) = migration_worker {
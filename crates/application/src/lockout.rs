@@ -0,0 +1,217 @@
+//! Tracks failed credential checks per source, like a client address, and
+//! locks the source out of further attempts once there have been too many, so
+//! credentials can't be brute-forced against deployments exposed to the
+//! internet.
+
+use std::{
+    hash::Hash,
+    net::IpAddr,
+    num::NonZeroUsize,
+    time::Duration,
+};
+
+use common::knobs::{
+    ADMIN_KEY_FAILURE_DELAY,
+    ADMIN_KEY_LOCKOUT_BASE,
+    ADMIN_KEY_LOCKOUT_THRESHOLD,
+    ADMIN_KEY_MAX_FAILURE_DELAY,
+    ADMIN_KEY_MAX_LOCKOUT,
+    BUILTIN_AUTH_SIGN_IN_LOCKOUT_BASE,
+    BUILTIN_AUTH_SIGN_IN_LOCKOUT_THRESHOLD,
+    BUILTIN_AUTH_SIGN_IN_MAX_LOCKOUT,
+};
+use lru::LruCache;
+use parking_lot::Mutex;
+use tokio::time::Instant;
+use value::sha256::Sha256;
+
+const MAX_TRACKED_SOURCES: NonZeroUsize = NonZeroUsize::new(10_000).unwrap();
+
+/// Identifies an admin key in logs without revealing it.
+pub fn admin_key_fingerprint(key: &str) -> String {
+    Sha256::hash(key.as_bytes()).as_hex()[..16].to_string()
+}
+
+/// What failed admin key checks are counted against.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AdminKeySource {
+    Ip(IpAddr),
+    KeyFingerprint(String),
+}
+
+pub fn admin_key_sources(fingerprint: &str, client_ip: Option<IpAddr>) -> Vec<AdminKeySource> {
+    let mut sources = vec![AdminKeySource::KeyFingerprint(fingerprint.to_string())];
+    sources.extend(client_ip.map(|ip| AdminKeySource::Ip(ip.to_canonical())));
+    sources
+}
+
+/// How long to hold the response to a failed admin key check that didn't
+/// start a lockout. Doubles with each failure from the same source, so
+/// guessing slows down well before the lockout starts.
+pub fn admin_key_failure_delay(failed_attempts: u32) -> Duration {
+    ADMIN_KEY_FAILURE_DELAY
+        .saturating_mul(2u32.saturating_pow(failed_attempts.saturating_sub(1)))
+        .min(*ADMIN_KEY_MAX_FAILURE_DELAY)
+}
+
+/// How many failures start a lockout and how long lockouts last.
+#[derive(Clone, Copy, Debug)]
+pub struct LockoutPolicy {
+    pub threshold: u32,
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl LockoutPolicy {
+    pub fn admin_key() -> Self {
+        Self {
+            threshold: *ADMIN_KEY_LOCKOUT_THRESHOLD,
+            base: *ADMIN_KEY_LOCKOUT_BASE,
+            max: *ADMIN_KEY_MAX_LOCKOUT,
+        }
+    }
+
+    pub fn builtin_auth_sign_in() -> Self {
+        Self {
+            threshold: *BUILTIN_AUTH_SIGN_IN_LOCKOUT_THRESHOLD,
+            base: *BUILTIN_AUTH_SIGN_IN_LOCKOUT_BASE,
+            max: *BUILTIN_AUTH_SIGN_IN_MAX_LOCKOUT,
+        }
+    }
+}
+
+struct Failures {
+    count: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// A failed check, and the lockout it started, if any.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Failure {
+    pub failed_attempts: u32,
+    pub lockout: Option<Duration>,
+}
+
+pub struct FailureLockout<K: Hash + Eq> {
+    policy: LockoutPolicy,
+    failures: Mutex<LruCache<K, Failures>>,
+}
+
+impl<K: Hash + Eq> FailureLockout<K> {
+    pub fn new(policy: LockoutPolicy) -> Self {
+        Self {
+            policy,
+            failures: Mutex::new(LruCache::new(MAX_TRACKED_SOURCES)),
+        }
+    }
+
+    /// Fails with how long is left if the source is locked out.
+    pub fn check(&self, source: &K, now: Instant) -> Result<(), Duration> {
+        let remaining = self
+            .failures
+            .lock()
+            .get(source)
+            .and_then(|failures| failures.locked_until)
+            .map(|locked_until| locked_until.saturating_duration_since(now))
+            .unwrap_or_default();
+        if remaining.is_zero() {
+            Ok(())
+        } else {
+            Err(remaining)
+        }
+    }
+
+    /// Counts a failed check against the source.
+    pub fn record_failure(&self, source: K, now: Instant) -> Failure {
+        let mut failures = self.failures.lock();
+        let entry = failures.get_or_insert_mut(source, || Failures {
+            count: 0,
+            last_failure: now,
+            locked_until: None,
+        });
+        // Forget failures once the source has been idle for as long as the
+        // longest lockout.
+        let idle_since = entry.locked_until.unwrap_or(entry.last_failure);
+        if now.saturating_duration_since(idle_since) > self.policy.max {
+            entry.count = 0;
+            entry.locked_until = None;
+        }
+        entry.count += 1;
+        entry.last_failure = now;
+        let lockout = entry
+            .count
+            .checked_sub(self.policy.threshold)
+            .map(|excess| {
+                // Each failure past the threshold doubles the lockout.
+                self.policy
+                    .base
+                    .saturating_mul(2u32.saturating_pow(excess))
+                    .min(self.policy.max)
+            });
+        if let Some(duration) = lockout {
+            entry.locked_until = Some(now + duration);
+        }
+        Failure {
+            failed_attempts: entry.count,
+            lockout,
+        }
+    }
+
+    /// Forgets the failures for the source.
+    pub fn forget(&self, source: &K) {
+        self.failures.lock().pop(source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::IpAddr,
+        time::Duration,
+    };
+
+    use tokio::time::Instant;
+
+    use super::{
+        FailureLockout,
+        LockoutPolicy,
+    };
+
+    #[test]
+    fn test_lockout_backs_off_exponentially() {
+        let policy = LockoutPolicy {
+            threshold: 3,
+            base: Duration::from_secs(30),
+            max: Duration::from_secs(60 * 60),
+        };
+        let lockout = FailureLockout::new(policy);
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let mut now = Instant::now();
+        for _ in 1..policy.threshold {
+            assert_eq!(lockout.record_failure(ip, now).lockout, None);
+            assert!(lockout.check(&ip, now).is_ok());
+        }
+        let failure = lockout.record_failure(ip, now);
+        assert_eq!(failure.failed_attempts, policy.threshold);
+        assert_eq!(failure.lockout, Some(policy.base));
+        assert_eq!(lockout.check(&ip, now), Err(policy.base));
+        // Other sources aren't affected.
+        let other_ip: IpAddr = "198.51.100.1".parse().unwrap();
+        assert!(lockout.check(&other_ip, now).is_ok());
+
+        now += policy.base;
+        assert!(lockout.check(&ip, now).is_ok());
+        let next = lockout.record_failure(ip, now).lockout.unwrap();
+        assert_eq!(next, policy.base * 2);
+
+        // Failures are forgotten once the source has been idle long enough.
+        now += next + policy.max + Duration::from_secs(1);
+        assert_eq!(lockout.record_failure(ip, now).failed_attempts, 1);
+        assert!(lockout.check(&ip, now).is_ok());
+
+        lockout.record_failure(ip, now);
+        lockout.forget(&ip);
+        assert_eq!(lockout.record_failure(ip, now).failed_attempts, 1);
+    }
+}
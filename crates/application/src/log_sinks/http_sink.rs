@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use common::{
+    http::{
+        HttpRequest,
+        APPLICATION_JSON_CONTENT_TYPE,
+    },
+    log_streaming::{
+        LogEvent,
+        LogEventFormatVersion,
+    },
+};
+use errors::ErrorMetadata;
+use http::{
+    header::CONTENT_TYPE,
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    Method,
+};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use url::Url;
+
+use super::LogSinkClient;
+
+/// Sends logs to an arbitrary HTTP endpoint.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpSinkConfig {
+    pub url: Url,
+    /// Headers sent with every request. Values are templates that can refer to
+    /// `{{instanceName}}`, `{{batchSize}}` and environment variables as
+    /// `{{env.NAME}}`, so secrets don't need to be in the config itself.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub format: HttpSinkFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HttpSinkFormat {
+    /// A JSON array of events.
+    #[default]
+    Json,
+    /// One JSON object per line.
+    Jsonl,
+}
+
+#[derive(Debug, PartialEq)]
+enum TemplatePart {
+    Literal(String),
+    InstanceName,
+    BatchSize,
+}
+
+/// A header value with placeholders. Environment variables are substituted
+/// when the sink starts, the other placeholders for every request.
+#[derive(Debug, PartialEq)]
+struct HeaderTemplate(Vec<TemplatePart>);
+
+impl HeaderTemplate {
+    fn parse(template: &str, lookup_env: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let mut parts = vec![];
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(TemplatePart::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..].find("}}").with_context(|| {
+                invalid_template_error(format!("Unclosed `{{{{` in header template {template:?}"))
+            })?;
+            let placeholder = rest[start + 2..start + end].trim();
+            let part = match placeholder {
+                "instanceName" => TemplatePart::InstanceName,
+                "batchSize" => TemplatePart::BatchSize,
+                _ => match placeholder.strip_prefix("env.") {
+                    Some(name) => TemplatePart::Literal(lookup_env(name).with_context(|| {
+                        invalid_template_error(format!(
+                            "Environment variable {name} used in a header template isn't set"
+                        ))
+                    })?),
+                    None => anyhow::bail!(invalid_template_error(format!(
+                        "Unknown placeholder {{{{{placeholder}}}}} in header template. Supported \
+                         placeholders are {{{{instanceName}}}}, {{{{batchSize}}}} and \
+                         {{{{env.NAME}}}}"
+                    ))),
+                },
+            };
+            parts.push(part);
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Literal(rest.to_string()));
+        }
+        Ok(Self(parts))
+    }
+
+    fn render(&self, instance_name: &str, batch_size: usize) -> String {
+        self.0
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Literal(s) => s.clone(),
+                TemplatePart::InstanceName => instance_name.to_string(),
+                TemplatePart::BatchSize => batch_size.to_string(),
+            })
+            .collect()
+    }
+}
+
+fn invalid_template_error(msg: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidLogSinkConfig", msg)
+}
+
+pub struct HttpSink {
+    url: Url,
+    headers: Vec<(HeaderName, HeaderTemplate)>,
+    format: HttpSinkFormat,
+    instance_name: String,
+}
+
+impl HttpSink {
+    pub fn new(config: HttpSinkConfig, instance_name: String) -> anyhow::Result<Self> {
+        let headers = config
+            .headers
+            .iter()
+            .map(|(name, template)| {
+                let name = HeaderName::try_from(name.as_str()).with_context(|| {
+                    invalid_template_error(format!("Invalid header name {name:?}"))
+                })?;
+                let template = HeaderTemplate::parse(template, |var| std::env::var(var).ok())?;
+                Ok((name, template))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            url: config.url,
+            headers,
+            format: config.format,
+            instance_name,
+        })
+    }
+}
+
+impl LogSinkClient for HttpSink {
+    fn build_request(&self, events: Vec<LogEvent>) -> anyhow::Result<HttpRequest> {
+        let batch_size = events.len();
+        let entries = events
+            .into_iter()
+            .map(|event| {
+                Ok(JsonValue::Object(
+                    event.to_json_map(LogEventFormatVersion::V2)?,
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let body = match self.format {
+            HttpSinkFormat::Json => serde_json::to_vec(&entries)?,
+            HttpSinkFormat::Jsonl => {
+                let mut body = vec![];
+                for entry in &entries {
+                    serde_json::to_writer(&mut body, entry)?;
+                    body.push(b'\n');
+                }
+                body
+            },
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, APPLICATION_JSON_CONTENT_TYPE);
+        for (name, template) in &self.headers {
+            let value = template.render(&self.instance_name, batch_size);
+            let mut value = HeaderValue::from_str(&value)
+                .with_context(|| format!("Header {name} has an invalid value"))?;
+            value.set_sensitive(true);
+            headers.insert(name.clone(), value);
+        }
+        Ok(HttpRequest {
+            headers,
+            url: self.url.clone(),
+            method: Method::POST,
+            body: Some(body),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        HeaderTemplate,
+        TemplatePart,
+    };
+
+    #[test]
+    fn test_header_template() -> anyhow::Result<()> {
+        let lookup_env = |name: &str| (name == "LOG_TOKEN").then(|| "s3cret".to_string());
+        let template = HeaderTemplate::parse(
+            "Bearer {{env.LOG_TOKEN}} for {{ instanceName }} ({{batchSize}})",
+            lookup_env,
+        )?;
+        assert_eq!(
+            template,
+            HeaderTemplate(vec![
+                TemplatePart::Literal("Bearer ".to_string()),
+                TemplatePart::Literal("s3cret".to_string()),
+                TemplatePart::Literal(" for ".to_string()),
+                TemplatePart::InstanceName,
+                TemplatePart::Literal(" (".to_string()),
+                TemplatePart::BatchSize,
+                TemplatePart::Literal(")".to_string()),
+            ])
+        );
+        assert_eq!(
+            template.render("carnitas", 3),
+            "Bearer s3cret for carnitas (3)"
+        );
+
+        assert!(HeaderTemplate::parse("{{env.MISSING}}", lookup_env).is_err());
+        assert!(HeaderTemplate::parse("{{unknown}}", lookup_env).is_err());
+        assert!(HeaderTemplate::parse("{{instanceName", lookup_env).is_err());
+        Ok(())
+    }
+}
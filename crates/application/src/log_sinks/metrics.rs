@@ -0,0 +1,78 @@
+use metrics::{
+    log_counter_with_labels,
+    log_distribution_with_labels,
+    register_convex_counter,
+    register_convex_histogram,
+    StaticMetricLabel,
+    StatusTimer,
+    STATUS_LABEL,
+};
+
+fn sink_label(sink_name: &str) -> StaticMetricLabel {
+    StaticMetricLabel::new("sink", sink_name.to_owned())
+}
+
+register_convex_counter!(
+    LOG_SINK_EVENTS_DELIVERED_TOTAL,
+    "Number of log events delivered to a log sink",
+    &["sink"]
+);
+pub fn log_sink_events_delivered(sink_name: &str, num_events: usize) {
+    log_counter_with_labels(
+        &LOG_SINK_EVENTS_DELIVERED_TOTAL,
+        num_events as u64,
+        vec![sink_label(sink_name)],
+    );
+}
+
+register_convex_counter!(
+    LOG_SINK_EVENTS_DROPPED_TOTAL,
+    "Number of log events a log sink gave up on",
+    &["sink", "reason"]
+);
+/// `reason` is `buffer_full` if the sink's worker fell behind, `format` if
+/// the events couldn't be formatted for the sink, and `delivery` if the
+/// destination didn't accept them.
+pub fn log_sink_events_dropped(sink_name: &str, reason: &'static str, num_events: usize) {
+    log_counter_with_labels(
+        &LOG_SINK_EVENTS_DROPPED_TOTAL,
+        num_events as u64,
+        vec![
+            sink_label(sink_name),
+            StaticMetricLabel::new("reason", reason),
+        ],
+    );
+}
+
+register_convex_counter!(
+    LOG_SINK_RETRIES_TOTAL,
+    "Number of times a batch of log events was resent to a log sink",
+    &["sink"]
+);
+pub fn log_sink_retry(sink_name: &str) {
+    log_counter_with_labels(&LOG_SINK_RETRIES_TOTAL, 1, vec![sink_label(sink_name)]);
+}
+
+register_convex_histogram!(
+    LOG_SINK_REQUEST_SECONDS,
+    "Duration of a request delivering a batch of log events to a log sink",
+    &[STATUS_LABEL[0], "sink"]
+);
+pub fn log_sink_request_timer(sink_name: &str) -> StatusTimer {
+    let mut timer = StatusTimer::new(&LOG_SINK_REQUEST_SECONDS);
+    timer.add_label(sink_label(sink_name));
+    timer
+}
+
+register_convex_histogram!(
+    LOG_SINK_BATCH_SIZE,
+    "Number of log events in a batch sent to a log sink",
+    &["sink"]
+);
+pub fn log_sink_batch_size(sink_name: &str, num_events: usize) {
+    log_distribution_with_labels(
+        &LOG_SINK_BATCH_SIZE,
+        num_events as f64,
+        vec![sink_label(sink_name)],
+    );
+}
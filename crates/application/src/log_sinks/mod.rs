@@ -0,0 +1,442 @@
+//! Log streaming to external destinations. Each configured sink runs its own
+//! worker that batches log events, formats them for the destination's
+//! ingestion API, and delivers them with retries. A sink that falls behind
+//! drops events instead of slowing down function execution.
+
+use std::{
+    collections::BTreeSet,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    http::{
+        fetch::{
+            FetchClient,
+            InternalFetchPurpose,
+        },
+        HttpRequest,
+    },
+    knobs::{
+        LOG_MANAGER_AGGREGATION_INTERVAL_MILLIS,
+        LOG_MANAGER_EVENT_RECV_BUFFER_SIZE,
+        LOG_SINK_MAX_BATCH_SIZE,
+        LOG_SINK_MAX_RETRIES,
+    },
+    log_streaming::{
+        LogEvent,
+        LogSender,
+        StructuredLogEvent,
+    },
+    runtime::{
+        Runtime,
+        SpawnHandle,
+    },
+};
+use errors::ErrorMetadata;
+use futures::{
+    select_biased,
+    FutureExt,
+};
+use http::StatusCode;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use self::{
+    datadog::DatadogSink,
+    http_sink::HttpSink,
+    metrics::{
+        log_sink_batch_size,
+        log_sink_events_delivered,
+        log_sink_events_dropped,
+        log_sink_request_timer,
+        log_sink_retry,
+    },
+    splunk::SplunkSink,
+};
+pub use self::{
+    datadog::DatadogSinkConfig,
+    http_sink::{
+        HttpSinkConfig,
+        HttpSinkFormat,
+    },
+    splunk::SplunkSinkConfig,
+};
+
+mod datadog;
+mod http_sink;
+mod metrics;
+mod splunk;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How much of an error response's body to include in the error we log.
+const MAX_ERROR_BODY_LENGTH: usize = 512;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSinkConfig {
+    /// Identifies the sink in logs and metrics.
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: LogSinkKind,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LogSinkKind {
+    Datadog(DatadogSinkConfig),
+    Splunk(SplunkSinkConfig),
+    Http(HttpSinkConfig),
+}
+
+/// Parse a JSON array of sink configurations.
+pub fn parse_log_sinks_config(config: &str) -> anyhow::Result<Vec<LogSinkConfig>> {
+    let configs: Vec<LogSinkConfig> = serde_json::from_str(config).context(
+        ErrorMetadata::bad_request("InvalidLogSinkConfig", "Invalid log sink config"),
+    )?;
+    let mut names = BTreeSet::new();
+    for config in &configs {
+        anyhow::ensure!(
+            !config.name.is_empty(),
+            ErrorMetadata::bad_request("InvalidLogSinkConfig", "Log sink names can't be empty")
+        );
+        anyhow::ensure!(
+            names.insert(config.name.as_str()),
+            ErrorMetadata::bad_request(
+                "InvalidLogSinkConfig",
+                format!("Log sink name {:?} is used more than once", config.name),
+            )
+        );
+    }
+    Ok(configs)
+}
+
+/// Formats a batch of log events as a request to a destination's ingestion
+/// API.
+trait LogSinkClient: Send + Sync + 'static {
+    fn build_request(&self, events: Vec<LogEvent>) -> anyhow::Result<HttpRequest>;
+}
+
+/// Sends log events to every configured sink.
+pub struct LogManager {
+    sinks: Mutex<Vec<LogSinkHandle>>,
+}
+
+struct LogSinkHandle {
+    name: String,
+    tx: mpsc::Sender<LogEvent>,
+    worker: Box<dyn SpawnHandle>,
+}
+
+impl LogManager {
+    pub fn start<RT: Runtime>(
+        runtime: RT,
+        fetch_client: Arc<dyn FetchClient>,
+        configs: Vec<LogSinkConfig>,
+        instance_name: String,
+    ) -> anyhow::Result<Self> {
+        let mut sinks = Vec::with_capacity(configs.len());
+        for LogSinkConfig { name, kind } in configs {
+            let client: Arc<dyn LogSinkClient> = match kind {
+                LogSinkKind::Datadog(config) => {
+                    Arc::new(DatadogSink::new(config, instance_name.clone()))
+                },
+                LogSinkKind::Splunk(config) => {
+                    Arc::new(SplunkSink::new(config, instance_name.clone())?)
+                },
+                LogSinkKind::Http(config) => {
+                    Arc::new(HttpSink::new(config, instance_name.clone())?)
+                },
+            };
+            let (tx, rx) = mpsc::channel(*LOG_MANAGER_EVENT_RECV_BUFFER_SIZE);
+            // Check that the sink is reachable and accepts our credentials.
+            tx.try_send(LogEvent::default_for_verification(&runtime)?)?;
+            let worker = LogSinkWorker {
+                runtime: runtime.clone(),
+                fetch_client: fetch_client.clone(),
+                name: name.clone(),
+                client,
+            };
+            let worker = runtime.spawn("log_sink_worker", worker.go(rx));
+            tracing::info!("Started log sink {name}");
+            sinks.push(LogSinkHandle { name, tx, worker });
+        }
+        Ok(Self {
+            sinks: Mutex::new(sinks),
+        })
+    }
+}
+
+impl LogSender for LogManager {
+    fn send_logs(&self, logs: Vec<LogEvent>) {
+        let sinks = self.sinks.lock();
+        for sink in sinks.iter() {
+            let mut num_dropped = 0;
+            for event in &logs {
+                // Exceptions are only reported to error trackers, which we
+                // don't support as sinks.
+                if matches!(event.event, StructuredLogEvent::Exception { .. }) {
+                    continue;
+                }
+                if sink.tx.try_send(event.clone()).is_err() {
+                    num_dropped += 1;
+                }
+            }
+            if num_dropped > 0 {
+                tracing::warn!(
+                    "Log sink {} is behind, dropped {num_dropped} events",
+                    sink.name
+                );
+                log_sink_events_dropped(&sink.name, "buffer_full", num_dropped);
+            }
+        }
+    }
+
+    fn shutdown(&self) -> anyhow::Result<()> {
+        for mut sink in self.sinks.lock().drain(..) {
+            sink.worker.shutdown();
+        }
+        Ok(())
+    }
+}
+
+struct LogSinkWorker<RT: Runtime> {
+    runtime: RT,
+    fetch_client: Arc<dyn FetchClient>,
+    name: String,
+    client: Arc<dyn LogSinkClient>,
+}
+
+/// Why a batch wasn't delivered.
+enum DeliveryError {
+    /// The request may succeed if sent again, e.g. network errors, rate
+    /// limiting and server errors.
+    Retryable(anyhow::Error),
+    Permanent(anyhow::Error),
+}
+
+impl<RT: Runtime> LogSinkWorker<RT> {
+    async fn go(self, mut rx: mpsc::Receiver<LogEvent>) {
+        while let Some(batch) = self.next_batch(&mut rx).await {
+            self.deliver(batch).await;
+        }
+    }
+
+    /// Wait for an event, then collect events until the batch is full or the
+    /// aggregation interval has passed. Returns `None` once the sender is
+    /// dropped and every event has been delivered.
+    async fn next_batch(&self, rx: &mut mpsc::Receiver<LogEvent>) -> Option<Vec<LogEvent>> {
+        let first = rx.recv().await?;
+        let mut batch = vec![first];
+        let mut deadline = self.runtime.wait(Duration::from_millis(
+            *LOG_MANAGER_AGGREGATION_INTERVAL_MILLIS,
+        ));
+        while batch.len() < *LOG_SINK_MAX_BATCH_SIZE {
+            select_biased! {
+                event = rx.recv().fuse() => match event {
+                    Some(event) => batch.push(event),
+                    None => break,
+                },
+                _ = &mut deadline => break,
+            }
+        }
+        Some(batch)
+    }
+
+    /// Send a batch, retrying with backoff. Returns whether the batch was
+    /// delivered.
+    async fn deliver(&self, events: Vec<LogEvent>) -> bool {
+        let num_events = events.len();
+        log_sink_batch_size(&self.name, num_events);
+        let request = match self.client.build_request(events) {
+            Ok(request) => request,
+            Err(e) => {
+                report_error(&mut e.context(format!("Failed to format logs for {}", self.name)))
+                    .await;
+                log_sink_events_dropped(&self.name, "format", num_events);
+                return false;
+            },
+        };
+        let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+        let mut attempt = 0;
+        let error = loop {
+            let timer = log_sink_request_timer(&self.name);
+            match self.send(request.clone()).await {
+                Ok(()) => {
+                    timer.finish();
+                    log_sink_events_delivered(&self.name, num_events);
+                    return true;
+                },
+                Err(DeliveryError::Retryable(e)) if attempt < *LOG_SINK_MAX_RETRIES => {
+                    attempt += 1;
+                    let delay = backoff.fail(&mut self.runtime.rng());
+                    tracing::warn!(
+                        "Failed to send logs to {}, retrying in {delay:?}: {e:#}",
+                        self.name
+                    );
+                    log_sink_retry(&self.name);
+                    self.runtime.wait(delay).await;
+                },
+                Err(DeliveryError::Retryable(e) | DeliveryError::Permanent(e)) => break e,
+            }
+        };
+        tracing::error!(
+            "Dropping {num_events} log events for {} after {} attempts: {error:#}",
+            self.name,
+            attempt + 1
+        );
+        log_sink_events_dropped(&self.name, "delivery", num_events);
+        false
+    }
+
+    async fn send(&self, request: HttpRequest) -> Result<(), DeliveryError> {
+        let response = self
+            .fetch_client
+            .internal_fetch(request.into(), InternalFetchPurpose::LogStreaming)
+            .await
+            .map_err(DeliveryError::Retryable)?;
+        let status = response.status;
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = match response.into_http_response().await {
+            Ok(response) => response
+                .body
+                .map(|body| {
+                    String::from_utf8_lossy(&body)
+                        .chars()
+                        .take(MAX_ERROR_BODY_LENGTH)
+                        .collect::<String>()
+                })
+                .unwrap_or_default(),
+            Err(e) => format!("<failed to read body: {e}>"),
+        };
+        let error = anyhow::anyhow!("{} responded with {status}: {body}", self.name);
+        if status.is_server_error()
+            || status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::REQUEST_TIMEOUT
+        {
+            Err(DeliveryError::Retryable(error))
+        } else {
+            Err(DeliveryError::Permanent(error))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+        Arc,
+    };
+
+    use common::{
+        http::{
+            fetch::StaticFetchClient,
+            HttpResponse,
+            HttpResponseStream,
+        },
+        log_streaming::LogEvent,
+        runtime::testing::TestRuntime,
+    };
+    use futures::FutureExt;
+    use http::{
+        HeaderMap,
+        Method,
+        StatusCode,
+    };
+
+    use super::{
+        http_sink::HttpSink,
+        parse_log_sinks_config,
+        LogSinkKind,
+        LogSinkWorker,
+    };
+
+    fn http_sink_worker(
+        rt: &TestRuntime,
+        statuses: Vec<StatusCode>,
+    ) -> anyhow::Result<(LogSinkWorker<TestRuntime>, Arc<AtomicUsize>)> {
+        let mut configs = parse_log_sinks_config(
+            r#"[{"name": "test", "type": "http", "url": "https://logs.example.com/ingest"}]"#,
+        )?;
+        assert_eq!(configs.len(), 1);
+        let LogSinkKind::Http(config) = configs.remove(0).kind else {
+            anyhow::bail!("Expected an HTTP sink");
+        };
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut fetch_client = StaticFetchClient::new();
+        let calls_ = calls.clone();
+        fetch_client.register_http_route(
+            "https://logs.example.com/ingest".parse()?,
+            Method::POST,
+            move |_request| {
+                let call = calls_.fetch_add(1, Ordering::SeqCst);
+                let status = statuses[call.min(statuses.len() - 1)];
+                async move {
+                    Ok(HttpResponseStream::from(HttpResponse::new(
+                        status,
+                        HeaderMap::new(),
+                        None,
+                        None,
+                    )))
+                }
+                .boxed()
+            },
+        );
+        let worker = LogSinkWorker {
+            runtime: rt.clone(),
+            fetch_client: Arc::new(fetch_client),
+            name: "test".to_string(),
+            client: Arc::new(HttpSink::new(config, "carnitas".to_string())?),
+        };
+        Ok((worker, calls))
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_log_sink_retries_server_errors(rt: TestRuntime) -> anyhow::Result<()> {
+        let (worker, calls) = http_sink_worker(
+            &rt,
+            vec![
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::OK,
+            ],
+        )?;
+        let delivered = worker
+            .deliver(vec![LogEvent::default_for_verification(&rt)?])
+            .await;
+        assert!(delivered);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_log_sink_does_not_retry_client_errors(rt: TestRuntime) -> anyhow::Result<()> {
+        let (worker, calls) = http_sink_worker(&rt, vec![StatusCode::UNAUTHORIZED])?;
+        let delivered = worker
+            .deliver(vec![LogEvent::default_for_verification(&rt)?])
+            .await;
+        assert!(!delivered);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_sink_names_rejected() {
+        let config = r#"[
+            {"name": "logs", "type": "http", "url": "https://a.example.com"},
+            {"name": "logs", "type": "http", "url": "https://b.example.com"}
+        ]"#;
+        assert!(parse_log_sinks_config(config).is_err());
+    }
+}
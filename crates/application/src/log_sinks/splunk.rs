@@ -0,0 +1,94 @@
+use common::{
+    http::{
+        HttpRequest,
+        APPLICATION_JSON_CONTENT_TYPE,
+    },
+    log_streaming::{
+        LogEvent,
+        LogEventFormatVersion,
+    },
+};
+use http::{
+    header::{
+        AUTHORIZATION,
+        CONTENT_TYPE,
+    },
+    HeaderMap,
+    HeaderValue,
+    Method,
+};
+use serde::Deserialize;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use url::Url;
+
+use super::LogSinkClient;
+
+/// Sends logs to a Splunk HTTP Event Collector.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplunkSinkConfig {
+    /// The collector's base URL, e.g. `https://splunk.example.com:8088`.
+    pub url: Url,
+    pub token: String,
+    /// The index to write to. Uses the token's default index if unset.
+    pub index: Option<String>,
+    pub source_type: Option<String>,
+}
+
+pub struct SplunkSink {
+    endpoint: Url,
+    authorization: HeaderValue,
+    index: Option<String>,
+    source_type: String,
+    instance_name: String,
+}
+
+impl SplunkSink {
+    pub fn new(config: SplunkSinkConfig, instance_name: String) -> anyhow::Result<Self> {
+        let mut authorization = HeaderValue::from_str(&format!("Splunk {}", config.token))?;
+        authorization.set_sensitive(true);
+        Ok(Self {
+            endpoint: config.url.join("services/collector/event")?,
+            authorization,
+            index: config.index,
+            source_type: config.source_type.unwrap_or_else(|| "_json".to_string()),
+            instance_name,
+        })
+    }
+}
+
+impl LogSinkClient for SplunkSink {
+    fn build_request(&self, events: Vec<LogEvent>) -> anyhow::Result<HttpRequest> {
+        // HEC accepts several events in one request as concatenated JSON
+        // objects.
+        let mut body = vec![];
+        for event in events {
+            let time = event.timestamp.as_secs_f64();
+            let fields = event.to_json_map(LogEventFormatVersion::V2)?;
+            let mut entry = json!({
+                "time": time,
+                "host": self.instance_name,
+                "source": "convex",
+                "sourcetype": self.source_type,
+                "event": JsonValue::Object(fields),
+            });
+            if let Some(index) = &self.index {
+                entry["index"] = json!(index);
+            }
+            serde_json::to_writer(&mut body, &entry)?;
+            body.push(b'\n');
+        }
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, APPLICATION_JSON_CONTENT_TYPE);
+        headers.insert(AUTHORIZATION, self.authorization.clone());
+        Ok(HttpRequest {
+            headers,
+            url: self.endpoint.clone(),
+            method: Method::POST,
+            body: Some(body),
+        })
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    runtime::Runtime,
+};
+use database::Database;
+use futures::{
+    future,
+    select_biased,
+    Future,
+    FutureExt,
+};
+use keybroker::Identity;
+use model::maintenance_windows::MaintenanceWindowsModel;
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Pauses the deployment when a scheduled maintenance window starts and
+/// resumes it when the window ends.
+pub struct MaintenanceWindowWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> MaintenanceWindowWorker<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime: runtime.clone(),
+            database,
+        };
+        async move {
+            tracing::info!("Starting MaintenanceWindowWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("MaintenanceWindowWorker died")).await;
+                    tracing::error!("Maintenance window worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let status = log_worker_starting("MaintenanceWindowWorker");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let now = self.runtime.unix_timestamp();
+        let next_due = MaintenanceWindowsModel::new(&mut tx).advance(now).await?;
+        if !tx.is_readonly() {
+            self.database
+                .commit_with_write_source(tx, "maintenance_window_worker")
+                .await?;
+            return Ok(());
+        }
+        let token = tx.into_token()?;
+
+        drop(status);
+        let subscription = self.database.subscribe(token).await?;
+        let wait_until_due = match next_due.and_then(|due| due.checked_sub(now)) {
+            Some(delay) => self.runtime.wait(delay).left_future(),
+            None => future::pending::<()>().right_future(),
+        };
+        select_biased! {
+            _ = subscription.wait_for_invalidation().fuse() => {},
+            _ = wait_until_due.fuse() => {},
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use metrics::{
+    log_counter,
     log_counter_with_labels,
     log_distribution_with_labels,
+    log_gauge,
     log_gauge_with_labels,
     register_convex_counter,
     register_convex_gauge,
@@ -99,3 +101,89 @@ register_convex_histogram!(
 pub fn table_summary_bootstrap_timer() -> StatusTimer {
     StatusTimer::new(&TABLE_SUMMARY_BOOTSTRAP_SECONDS)
 }
+
+register_convex_histogram!(
+    TABLE_SUMMARY_RECOMPUTE_SECONDS,
+    "Time to rescan tables and recompute their summaries",
+    &STATUS_LABEL
+);
+pub fn table_summary_recompute_timer() -> StatusTimer {
+    StatusTimer::new(&TABLE_SUMMARY_RECOMPUTE_SECONDS)
+}
+
+register_convex_gauge!(
+    STORAGE_GC_ORPHANED_OBJECTS_TOTAL,
+    "Number of orphaned objects found by the last storage GC run"
+);
+register_convex_gauge!(
+    STORAGE_GC_ORPHANED_BYTES_TOTAL,
+    "Size of the orphaned objects found by the last storage GC run"
+);
+register_convex_counter!(
+    STORAGE_GC_DELETED_OBJECTS_TOTAL,
+    "Number of orphaned objects deleted by storage GC"
+);
+pub fn log_storage_gc_orphans(objects: usize, bytes: u64, deleted: usize) {
+    log_gauge(&STORAGE_GC_ORPHANED_OBJECTS_TOTAL, objects as f64);
+    log_gauge(&STORAGE_GC_ORPHANED_BYTES_TOTAL, bytes as f64);
+    log_counter(&STORAGE_GC_DELETED_OBJECTS_TOTAL, deleted as u64);
+}
+
+register_convex_counter!(
+    FILE_ATTACHMENT_CASCADE_DELETED_TOTAL,
+    "Number of files deleted because the documents or components attaching them were deleted"
+);
+pub fn log_file_attachment_cascades(deleted: usize) {
+    log_counter(&FILE_ATTACHMENT_CASCADE_DELETED_TOTAL, deleted as u64);
+}
+
+register_convex_counter!(
+    FILE_TEXT_EXTRACTIONS_TOTAL,
+    "Number of queued file text extractions processed",
+    &["has_text"],
+);
+pub fn log_file_text_extraction(has_text: bool) {
+    log_counter_with_labels(
+        &FILE_TEXT_EXTRACTIONS_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("has_text", has_text.to_string())],
+    );
+}
+
+register_convex_counter!(
+    HTTP_ACTION_CACHE_LOOKUPS_TOTAL,
+    "Number of GET HTTP action requests looked up in the response cache",
+    &["cache_status"],
+);
+pub fn log_http_action_cache_lookup(is_cache_hit: bool) {
+    let cache_label = if is_cache_hit { "hit" } else { "miss" };
+    log_counter_with_labels(
+        &HTTP_ACTION_CACHE_LOOKUPS_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("cache_status", cache_label)],
+    );
+}
+
+register_convex_counter!(
+    ADMIN_KEY_AUTH_FAILURES_TOTAL,
+    "Number of requests with an invalid admin key"
+);
+pub fn log_admin_key_auth_failure() {
+    log_counter(&ADMIN_KEY_AUTH_FAILURES_TOTAL, 1);
+}
+
+register_convex_counter!(
+    ADMIN_KEY_LOCKOUTS_TOTAL,
+    "Number of times too many invalid admin keys locked out an address or key"
+);
+pub fn log_admin_key_lockout() {
+    log_counter(&ADMIN_KEY_LOCKOUTS_TOTAL, 1);
+}
+
+register_convex_counter!(
+    ADMIN_KEY_LOCKED_OUT_REQUESTS_TOTAL,
+    "Number of admin key requests rejected because of a lockout"
+);
+pub fn log_admin_key_locked_out_request() {
+    log_counter(&ADMIN_KEY_LOCKED_OUT_REQUESTS_TOTAL, 1);
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    runtime::Runtime,
+};
+use database::Database;
+use futures::{
+    future,
+    select_biased,
+    Future,
+    FutureExt,
+};
+use keybroker::Identity;
+use model::preview_deployments::PreviewDeploymentsModel;
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Deletes preview deployments and their tables once they expire.
+pub struct PreviewDeploymentWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> PreviewDeploymentWorker<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime: runtime.clone(),
+            database,
+        };
+        async move {
+            tracing::info!("Starting PreviewDeploymentWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("PreviewDeploymentWorker died")).await;
+                    tracing::error!("Preview deployment worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let status = log_worker_starting("PreviewDeploymentWorker");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let now = self.runtime.unix_timestamp();
+        let next_expiry = PreviewDeploymentsModel::new(&mut tx)
+            .delete_expired(now)
+            .await?;
+        if !tx.is_readonly() {
+            self.database
+                .commit_with_write_source(tx, "preview_deployment_worker")
+                .await?;
+            return Ok(());
+        }
+        let token = tx.into_token()?;
+
+        drop(status);
+        let subscription = self.database.subscribe(token).await?;
+        let wait_until_expiry = match next_expiry.and_then(|expiry| expiry.checked_sub(now)) {
+            Some(delay) => self.runtime.wait(delay).left_future(),
+            None => future::pending::<()>().right_future(),
+        };
+        select_biased! {
+            _ = subscription.wait_for_invalidation().fuse() => {},
+            _ = wait_until_expiry.fuse() => {},
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use std::fmt;
+use std::{
+    collections::BTreeMap,
+    fmt,
+};
 
 use anyhow::Context;
 use common::{
@@ -97,6 +100,9 @@ pub struct RedactedJsError {
     error: JsError,
     block_logging: bool,
     request_id: RequestId,
+    /// Whether to include the error's system-provided context, which is only
+    /// shown to admins.
+    show_context: bool,
 }
 
 impl RedactedJsError {
@@ -105,9 +111,20 @@ impl RedactedJsError {
             error,
             block_logging,
             request_id,
+            show_context: false,
         }
     }
 
+    pub fn show_context(mut self, show_context: bool) -> Self {
+        self.show_context = show_context;
+        self
+    }
+
+    fn visible_context(&self) -> Option<&BTreeMap<String, String>> {
+        (self.show_context && !self.block_logging && !self.error.context.is_empty())
+            .then_some(&self.error.context)
+    }
+
     pub fn custom_data_if_any(self) -> Option<ConvexValue> {
         self.error.custom_data
     }
@@ -157,6 +174,9 @@ impl RedactedJsError {
         if !self.block_logging {
             body["trace"] = self.error.to_string().into();
         }
+        if let Some(context) = self.visible_context() {
+            body["context"] = json!(context);
+        }
         if let Some(custom_data) = self.custom_data_if_any() {
             body["data"] = custom_data.into();
         }
@@ -170,6 +190,12 @@ impl fmt::Display for RedactedJsError {
         if !self.block_logging {
             write!(f, "\n{}", self.error)?;
         }
+        if let Some(context) = self.visible_context() {
+            write!(f, "\nContext:")?;
+            for (key, value) in context {
+                write!(f, "\n  {key}: {value}")?;
+            }
+        }
         Ok(())
     }
 }
@@ -182,6 +208,7 @@ impl TryFrom<RedactedJsError> for RedactedJsErrorProto {
             error: Some(value.error.try_into()?),
             block_logging: Some(value.block_logging),
             request_id: Some(value.request_id.into()),
+            show_context: Some(value.show_context),
         })
     }
 }
@@ -200,6 +227,7 @@ impl TryFrom<RedactedJsErrorProto> for RedactedJsError {
             error,
             block_logging,
             request_id,
+            show_context: msg.show_context.unwrap_or_default(),
         })
     }
 }
@@ -283,6 +311,25 @@ pub mod tests {
             );
         }
 
+        #[test]
+        fn format_shows_context_only_when_requested(
+            js_error in any::<JsError>(), request_id in any::<RequestId>()
+        ) {
+            let js_error = js_error.with_context("syscall", "1.0/get".to_string());
+            let hidden =
+                RedactedJsError::from_js_error(js_error.clone(), false, request_id.clone());
+            assert!(!format!("{hidden}").contains("Context:"));
+            let shown = RedactedJsError::from_js_error(js_error.clone(), false, request_id.clone())
+                .show_context(true);
+            assert!(format!("{shown}").contains("\n  syscall: 1.0/get"));
+            let blocked = RedactedJsError::from_js_error(js_error, true, request_id.clone())
+                .show_context(true);
+            assert_eq!(
+                format!("{blocked}"),
+                format!("[Request ID: {}] Server Error", request_id)
+            );
+        }
+
         #[test]
         fn test_redacted_js_error_roundtrips(left in any::<RedactedJsError>()) {
             assert_roundtrips::<RedactedJsError, RedactedJsErrorProto>(left);
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use common::knobs::SCHEDULER_BACKLOG_THROUGHPUT_WINDOW;
 use errors::ErrorMetadataAnyhowExt;
 use metrics::{
     log_counter_with_labels,
@@ -11,6 +12,8 @@ use metrics::{
     StaticMetricLabel,
     STATUS_LABEL,
 };
+use model::scheduled_jobs::SchedulerBacklog;
+use sync_types::Timestamp;
 
 register_convex_counter!(
     SCHEDULED_JOB_RESULT_TOTAL,
@@ -63,3 +66,27 @@ register_convex_gauge!(
 pub fn log_num_running_jobs(num_running: usize) {
     log_gauge(&SCHEDULED_JOB_NUM_RUNNING_TOTAL, num_running as f64);
 }
+
+register_convex_gauge!(
+    SCHEDULED_JOB_DUE_TOTAL,
+    "Number of scheduled jobs that are due but haven't started"
+);
+register_convex_gauge!(
+    SCHEDULED_JOB_OLDEST_DUE_AGE_SECONDS,
+    "How long the longest-waiting due scheduled job has been waiting"
+);
+register_convex_gauge!(
+    SCHEDULED_JOB_COMPLETED_PER_SECOND,
+    "Scheduled jobs finished per second over the throughput window"
+);
+pub fn log_scheduler_backlog(backlog: &SchedulerBacklog, now: Timestamp) {
+    log_gauge(&SCHEDULED_JOB_DUE_TOTAL, backlog.due_jobs as f64);
+    let oldest_due_age = backlog
+        .oldest_due_ts
+        .map_or(0.0, |ts| now.secs_since_f64(ts).max(0.0));
+    log_gauge(&SCHEDULED_JOB_OLDEST_DUE_AGE_SECONDS, oldest_due_age);
+    log_gauge(
+        &SCHEDULED_JOB_COMPLETED_PER_SECOND,
+        backlog.completed_jobs as f64 / SCHEDULER_BACKLOG_THROUGHPUT_WINDOW.as_secs_f64(),
+    );
+}
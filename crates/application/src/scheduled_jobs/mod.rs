@@ -2,7 +2,7 @@ use std::{
     cmp,
     collections::{
         BTreeMap,
-        HashSet,
+        HashMap,
     },
     ops::Deref,
     sync::Arc,
@@ -14,7 +14,10 @@ use std::{
 
 use common::{
     backoff::Backoff,
-    components::PublicFunctionPath,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        PublicFunctionPath,
+    },
     document::ParsedDocument,
     errors::{
         report_error,
@@ -30,7 +33,11 @@ use common::{
         SCHEDULED_JOB_GARBAGE_COLLECTION_MAX_BACKOFF,
         SCHEDULED_JOB_INITIAL_BACKOFF,
         SCHEDULED_JOB_MAX_BACKOFF,
+        SCHEDULED_JOB_MAX_SKIPPED_PER_PRIORITY,
         SCHEDULED_JOB_RETENTION,
+        SCHEDULER_BACKLOG_MAX_SCANNED_JOBS,
+        SCHEDULER_BACKLOG_METRICS_INTERVAL,
+        SCHEDULER_BACKLOG_THROUGHPUT_WINDOW,
         UDF_EXECUTOR_OCC_MAX_RETRIES,
     },
     pause::Fault,
@@ -72,21 +79,25 @@ use futures_async_stream::try_stream;
 use keybroker::Identity;
 use model::{
     backend_state::BackendStateModel,
+    function_kill_switches::FunctionKillSwitchesModel,
     modules::ModuleModel,
     scheduled_jobs::{
+        args::load_args,
         types::{
+            SchedulePriority,
             ScheduledJob,
             ScheduledJobState,
         },
+        unfinished_jobs_query,
+        SchedulerBacklog,
         SchedulerModel,
         COMPLETED_TS_FIELD,
-        NEXT_TS_FIELD,
-        SCHEDULED_JOBS_INDEX,
         SCHEDULED_JOBS_INDEX_BY_COMPLETED_TS,
         SCHEDULED_JOBS_TABLE,
     },
 };
 use parking_lot::Mutex;
+use storage::Storage;
 use sync_types::Timestamp;
 use tokio::sync::mpsc;
 use usage_tracking::FunctionUsageTracker;
@@ -106,6 +117,7 @@ pub(crate) const SCHEDULED_JOB_COMMITTING: &str = "scheduled_job_committing";
 pub struct ScheduledJobRunner {
     executor: Arc<Mutex<Box<dyn SpawnHandle>>>,
     garbage_collector: Arc<Mutex<Box<dyn SpawnHandle>>>,
+    backlog_reporter: Arc<Mutex<Box<dyn SpawnHandle>>>,
 }
 
 impl ScheduledJobRunner {
@@ -115,6 +127,7 @@ impl ScheduledJobRunner {
         database: Database<RT>,
         runner: Arc<ApplicationFunctionRunner<RT>>,
         function_log: FunctionExecutionLog<RT>,
+        files_storage: Arc<dyn Storage>,
     ) -> Self {
         let executor_fut = ScheduledJobExecutor::start(
             rt.clone(),
@@ -122,22 +135,77 @@ impl ScheduledJobRunner {
             database.clone(),
             runner,
             function_log,
+            files_storage.clone(),
         );
         let executor = Arc::new(Mutex::new(rt.spawn("scheduled_job_executor", executor_fut)));
 
-        let garbage_collector_fut = ScheduledJobGarbageCollector::start(rt.clone(), database);
+        let garbage_collector_fut =
+            ScheduledJobGarbageCollector::start(rt.clone(), database.clone(), files_storage);
         let garbage_collector = Arc::new(Mutex::new(
             rt.spawn("scheduled_job_garbage_collector", garbage_collector_fut),
         ));
+
+        let backlog_reporter_fut = report_scheduler_backlog(rt.clone(), database);
+        let backlog_reporter = Arc::new(Mutex::new(
+            rt.spawn("scheduled_job_backlog_reporter", backlog_reporter_fut),
+        ));
         Self {
             executor,
             garbage_collector,
+            backlog_reporter,
         }
     }
 
     pub fn shutdown(&self) {
         self.executor.lock().shutdown();
         self.garbage_collector.lock().shutdown();
+        self.backlog_reporter.lock().shutdown();
+    }
+}
+
+/// The scheduler backlog summed over every component, with throughput
+/// measured over the last `SCHEDULER_BACKLOG_THROUGHPUT_WINDOW`.
+pub async fn scheduler_backlog<RT: Runtime>(
+    rt: &RT,
+    database: &Database<RT>,
+) -> anyhow::Result<SchedulerBacklog> {
+    let now = rt.generate_timestamp()?;
+    let completed_since = now.sub(*SCHEDULER_BACKLOG_THROUGHPUT_WINDOW)?;
+    let namespaces = database
+        .begin(Identity::system())
+        .await?
+        .table_mapping()
+        .namespaces_for_name(&SCHEDULED_JOBS_TABLE);
+    let mut backlog = SchedulerBacklog {
+        complete: true,
+        ..Default::default()
+    };
+    // Each component gets its own transaction so large backlogs don't run into
+    // the transaction read limits.
+    for namespace in namespaces {
+        let mut tx = database.begin(Identity::system()).await?;
+        let component_backlog = SchedulerModel::new(&mut tx, namespace)
+            .backlog(now, completed_since, *SCHEDULER_BACKLOG_MAX_SCANNED_JOBS)
+            .await?;
+        backlog.merge(component_backlog);
+    }
+    Ok(backlog)
+}
+
+/// Periodically logs the scheduler backlog to metrics, so we can alert when
+/// jobs start running late.
+async fn report_scheduler_backlog<RT: Runtime>(rt: RT, database: Database<RT>) {
+    loop {
+        match scheduler_backlog(&rt, &database).await {
+            Ok(backlog) => match rt.generate_timestamp() {
+                Ok(now) => metrics::log_scheduler_backlog(&backlog, now),
+                Err(mut e) => report_error(&mut e).await,
+            },
+            Err(e) => {
+                report_error(&mut e.context("Failed to measure the scheduler backlog")).await;
+            },
+        }
+        rt.wait(*SCHEDULER_BACKLOG_METRICS_INTERVAL).await;
     }
 }
 
@@ -160,6 +228,7 @@ pub struct ScheduledJobContext<RT: Runtime> {
     database: Database<RT>,
     runner: Arc<ApplicationFunctionRunner<RT>>,
     function_log: FunctionExecutionLog<RT>,
+    files_storage: Arc<dyn Storage>,
 }
 
 /// This roughly matches tokio's permits that it uses as part of cooperative
@@ -175,6 +244,7 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
         database: Database<RT>,
         runner: Arc<ApplicationFunctionRunner<RT>>,
         function_log: FunctionExecutionLog<RT>,
+        files_storage: Arc<dyn Storage>,
     ) -> impl Future<Output = ()> + Send {
         let mut executor = Self {
             context: ScheduledJobContext {
@@ -183,6 +253,7 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 database,
                 runner,
                 function_log,
+                files_storage,
             },
         };
         async move {
@@ -204,6 +275,7 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
         database: Database<RT>,
         runner: Arc<ApplicationFunctionRunner<RT>>,
         function_log: FunctionExecutionLog<RT>,
+        files_storage: Arc<dyn Storage>,
     ) -> Self {
         Self {
             context: ScheduledJobContext {
@@ -212,18 +284,19 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 database,
                 runner,
                 function_log,
+                files_storage,
             },
         }
     }
 
     async fn drain_finished_jobs(
-        running_job_ids: &mut HashSet<ResolvedDocumentId>,
+        running_jobs: &mut HashMap<ResolvedDocumentId, CanonicalizedComponentFunctionPath>,
         rx: &mut mpsc::Receiver<ResolvedDocumentId>,
     ) {
         let mut total_drained = 0;
         while let Ok(job_id) = rx.try_recv() {
             total_drained += 1;
-            running_job_ids.remove(&job_id);
+            running_jobs.remove(&job_id);
             if total_drained % CHECKS_BETWEEN_YIELDS == 0 {
                 yield_now().await;
             }
@@ -236,11 +309,11 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
         let pause_client = self.context.rt.pause_client();
         let (job_finished_tx, mut job_finished_rx) =
             mpsc::channel(*SCHEDULED_JOB_EXECUTION_PARALLELISM);
-        let mut running_job_ids = HashSet::new();
+        let mut running_jobs = HashMap::new();
         // Some if there's at least one pending job. May be in the past!
         let mut next_job_ready_time = None;
         loop {
-            Self::drain_finished_jobs(&mut running_job_ids, &mut job_finished_rx).await;
+            Self::drain_finished_jobs(&mut running_jobs, &mut job_finished_rx).await;
 
             let mut tx = self.database.begin(Identity::Unknown).await?;
             let backend_state = BackendStateModel::new(&mut tx).get_backend_state().await?;
@@ -250,18 +323,18 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 // If the backend is stopped we shouldn't poll. Our subscription will notify us
                 // when the backend is started again.
                 None
-            } else if running_job_ids.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
+            } else if running_jobs.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
                 // A scheduled job may have been added, but we can't do anything because we're
                 // still running jobs at our concurrency limit.
                 next_job_ready_time
             } else {
                 // Great! we have enough remaining concurrency and our backend is running, start
                 // new job(s) if we can and update our next ready time.
-                self.query_and_start_jobs(&mut tx, &mut running_job_ids, &job_finished_tx)
+                self.query_and_start_jobs(&mut tx, &mut running_jobs, &job_finished_tx)
                     .await?
             };
 
-            metrics::log_num_running_jobs(running_job_ids.len());
+            metrics::log_num_running_jobs(running_jobs.len());
             let now = self.rt.system_time();
             let next_job_ready_time = next_job_ready_time.map(SystemTime::from);
             self.log_scheduled_job_execution_lag(next_job_ready_time, now);
@@ -283,7 +356,7 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 job_id = job_finished_rx.recv().fuse() => {
                     if let Some(job_id) = job_id {
                         pause_client.wait(SCHEDULED_JOB_EXECUTED).await;
-                        running_job_ids.remove(&job_id);
+                        running_jobs.remove(&job_id);
                     } else {
                         anyhow::bail!("Job results channel closed, this is unexpected!");
                     }
@@ -297,9 +370,9 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
         }
     }
 
-    /// Reads through scheduled jobs in timestamp ascending order and starts any
-    /// that are allowed by our concurrency limit and the jobs' scheduled
-    /// time.
+    /// Reads through scheduled jobs, highest priority first and in timestamp
+    /// ascending order within a priority, and starts any that are allowed by
+    /// our concurrency limits and the jobs' scheduled time.
     ///
     /// Returns the time at which the next job in the queue will be ready to
     /// run. If the scheduler is behind, the returned time may be in the
@@ -307,72 +380,127 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
     async fn query_and_start_jobs(
         &self,
         tx: &mut Transaction<RT>,
-        running_job_ids: &mut HashSet<ResolvedDocumentId>,
+        running_jobs: &mut HashMap<ResolvedDocumentId, CanonicalizedComponentFunctionPath>,
         job_finished_tx: &mpsc::Sender<ResolvedDocumentId>,
     ) -> anyhow::Result<Option<Timestamp>> {
         let now = self.rt.generate_timestamp()?;
-        let mut job_stream = self.stream_jobs_to_run(tx);
-        while let Some(job) = job_stream.try_next().await? {
-            let (job_id, job) = job.clone().into_id_and_value();
-            if running_job_ids.contains(&job_id) {
-                continue;
-            }
-            let next_ts = job
-                .next_ts
-                .ok_or_else(|| anyhow::anyhow!("Could not get next_ts to run scheduled job at"))?;
-            // If we can't execute the job return the job's target timestamp. If we're
-            // caught up, we can sleep until the timestamp. If we're behind and
-            // at our concurrency limit, we can use the timestamp to log how far
-            // behind we get.
-            if next_ts > now || running_job_ids.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
-                return Ok(Some(next_ts));
-            }
+        let disabled_functions = FunctionKillSwitchesModel::new(tx)
+            .disabled_functions()
+            .await?;
+        let mut next_job_ts: Option<Timestamp> = None;
+        for priority in SchedulePriority::ALL {
+            let mut num_skipped = 0;
+            let mut job_stream = self.stream_jobs_to_run(tx, priority);
+            while let Some(job) = job_stream.try_next().await? {
+                let (job_id, job) = job.clone().into_id_and_value();
+                if running_jobs.contains_key(&job_id) {
+                    continue;
+                }
+                let next_ts = job.next_ts.ok_or_else(|| {
+                    anyhow::anyhow!("Could not get next_ts to run scheduled job at")
+                })?;
+                // If we can't execute the job keep the job's target timestamp. If we're
+                // caught up, we can sleep until the timestamp. If we're behind and
+                // at our concurrency limit, we can use the timestamp to log how far
+                // behind we get.
+                if next_ts > now || running_jobs.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
+                    next_job_ts = Some(next_job_ts.map_or(next_ts, |ts| ts.min(next_ts)));
+                    break;
+                }
+                if disabled_functions.get(&job.path).is_some() {
+                    // Leave the job pending until its function is enabled
+                    // again. That writes to the kill switches we read, so our
+                    // subscription wakes us up to run it.
+                    num_skipped += 1;
+                    if num_skipped == *SCHEDULED_JOB_MAX_SKIPPED_PER_PRIORITY {
+                        next_job_ts = Some(next_job_ts.map_or(next_ts, |ts| ts.min(next_ts)));
+                        break;
+                    }
+                    continue;
+                }
+                if let Some(max_concurrency) = job.options.max_concurrency {
+                    let num_running = running_jobs
+                        .values()
+                        .filter(|path| **path == job.path)
+                        .count();
+                    if num_running >= max_concurrency as usize {
+                        // Jobs for other functions further back in the queue can still
+                        // start, so keep looking, but only so far.
+                        next_job_ts = Some(next_job_ts.map_or(next_ts, |ts| ts.min(next_ts)));
+                        num_skipped += 1;
+                        if num_skipped == *SCHEDULED_JOB_MAX_SKIPPED_PER_PRIORITY {
+                            break;
+                        }
+                        continue;
+                    }
+                }
+
+                let context = self.context.clone();
+                let tx = job_finished_tx.clone();
+                let path = job.path.clone();
 
-            let context = self.context.clone();
-            let tx = job_finished_tx.clone();
+                let root = get_sampled_span(
+                    &self.instance_name,
+                    "scheduler/execute_job",
+                    &mut self.rt.rng(),
+                    BTreeMap::new(),
+                );
+                self.rt.spawn(
+                    "spawn_scheduled_job",
+                    async move {
+                        context.execute_job(job, job_id).await;
+                        let _ = tx.send(job_id).await;
+                    }
+                    .in_span(root),
+                );
 
-            let root = get_sampled_span(
-                &self.instance_name,
-                "scheduler/execute_job",
-                &mut self.rt.rng(),
-                BTreeMap::new(),
-            );
-            self.rt.spawn(
-                "spawn_scheduled_job",
-                async move {
-                    context.execute_job(job, job_id).await;
-                    let _ = tx.send(job_id).await;
-                }
-                .in_span(root),
-            );
+                running_jobs.insert(job_id, path);
 
-            running_job_ids.insert(job_id);
+                // We might have hit the concurrency limit by adding the new
+                // job, so we could check and break immediately if we have.
+                // However we want to know the time the next job in the queue
+                // (if any) is due, so instead we continue the loop one more
+                // time.
+            }
+        }
+        Ok(next_job_ts)
+    }
 
-            // We might have hit the concurrency limit by adding the new job, so
-            // we could check and break immediately if we have.
-            // However we want to know the time the next job in the
-            // queue (if any) is due, so instead we continue the loop one more
-            // time.
+    /// Whether any job is due to run or still running, so tests can wait for
+    /// the executor to catch up after advancing time. Running actions are
+    /// `InProgress` and keep the `next_ts` they were due at, so they sort
+    /// before any job that isn't due yet.
+    #[cfg(any(test, feature = "testing"))]
+    pub async fn has_due_or_running_jobs(
+        &self,
+        tx: &mut Transaction<RT>,
+        now: Timestamp,
+    ) -> anyhow::Result<bool> {
+        for priority in SchedulePriority::ALL {
+            let mut job_stream = self.stream_jobs_to_run(tx, priority);
+            if let Some(job) = job_stream.try_next().await?
+                && (job.state == ScheduledJobState::InProgress
+                    || job.next_ts.is_none_or(|next_ts| next_ts <= now))
+            {
+                return Ok(true);
+            }
         }
-        Ok(None)
+        Ok(false)
     }
 
     #[try_stream(boxed, ok = ParsedDocument<ScheduledJob>, error = anyhow::Error)]
-    async fn stream_jobs_to_run<'a>(&'a self, tx: &'a mut Transaction<RT>) {
+    async fn stream_jobs_to_run<'a>(
+        &'a self,
+        tx: &'a mut Transaction<RT>,
+        priority: SchedulePriority,
+    ) {
         let namespaces: Vec<_> = tx
             .table_mapping()
             .iter()
             .filter(|(_, _, _, name)| **name == *SCHEDULED_JOBS_TABLE)
             .map(|(_, namespace, ..)| namespace)
             .collect();
-        let index_query = Query::index_range(IndexRange {
-            index_name: SCHEDULED_JOBS_INDEX.clone(),
-            range: vec![IndexRangeExpression::Gt(
-                NEXT_TS_FIELD.clone(),
-                value::ConvexValue::Null,
-            )],
-            order: Order::Asc,
-        });
+        let index_query = unfinished_jobs_query(priority)?;
         // Key is (next_ts, namespace), where next_ts is for sorting and namespace
         // is for deduping.
         // Value is (job, query) where job is the job to run and query will get
@@ -497,6 +625,16 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
             // Continue without running function since the job state has changed
             return Ok(());
         }
+        if FunctionKillSwitchesModel::new(&mut tx)
+            .disabled_functions()
+            .await?
+            .get(&job.path)
+            .is_some()
+        {
+            // The function was disabled after the job was picked up. Leave it
+            // pending so it runs once the function is enabled again.
+            return Ok(());
+        }
 
         tracing::info!(
             "Executing '{}'{}!",
@@ -529,7 +667,8 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                     .await?;
                 // NOTE: We didn't actually run anything, so we are creating a request context
                 // just report the error.
-                let context = ExecutionContext::new(request_id, &caller);
+                let context = ExecutionContext::new(request_id, &caller)
+                    .with_request_context(job.request_context.clone());
                 // We don't know what the UdfType is since this is an invalid module.
                 // Log as mutation for now.
                 self.function_log.log_mutation_system_error(
@@ -574,7 +713,8 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                     .await?;
                 // NOTE: We didn't actually run anything, so we are creating a request context
                 // just report the error.
-                let context = ExecutionContext::new(request_id, &caller);
+                let context = ExecutionContext::new(request_id, &caller)
+                    .with_request_context(job.request_context.clone());
                 match udf_type {
                     UdfType::Query => {
                         self.function_log.log_query_system_error(
@@ -630,13 +770,14 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         usage_tracker: FunctionUsageTracker,
     ) -> anyhow::Result<()> {
         let start = self.rt.monotonic_now();
-        let context = ExecutionContext::new(request_id, &caller);
+        let context = ExecutionContext::new(request_id, &caller)
+            .with_request_context(job.request_context.clone());
         let identity = tx.inert_identity();
         let namespace = tx.table_mapping().tablet_namespace(job_id.tablet_id)?;
         let path = job.path.clone();
         let pause_client = self.rt.pause_client();
 
-        let udf_args = job.udf_args()?;
+        let udf_args = load_args(&self.files_storage, &job).await?;
         let result = self
             .runner
             .run_mutation_no_udf_log(
@@ -729,6 +870,10 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         let namespace = tx.table_mapping().tablet_namespace(job_id.tablet_id)?;
         match job.state {
             ScheduledJobState::Pending => {
+                // Read offloaded arguments before marking the job in progress,
+                // so failing to read them is retried like other system
+                // errors.
+                let udf_args = load_args(&self.files_storage, &job).await?;
                 // Set state to in progress
                 let mut updated_job = job.clone();
                 updated_job.state = ScheduledJobState::InProgress;
@@ -740,13 +885,14 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                     .await?;
 
                 // Execute the action
-                let context = ExecutionContext::new(request_id, &caller);
+                let context = ExecutionContext::new(request_id, &caller)
+                    .with_request_context(job.request_context.clone());
                 let path = job.path.clone();
                 let completion = self
                     .runner
                     .run_action_no_udf_log(
                         PublicFunctionPath::Component(path),
-                        job.udf_args()?,
+                        udf_args,
                         identity,
                         caller,
                         usage_tracker.clone(),
@@ -790,7 +936,8 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                 // started with. We generate a new executionId and use it to log the failures. I
                 // guess the correct behavior here is to store the executionId in the state so
                 // we can log correctly here.
-                let context = ExecutionContext::new(request_id, &caller);
+                let context = ExecutionContext::new(request_id, &caller)
+                    .with_request_context(job.request_context.clone());
                 let path = job.path.clone();
                 self.function_log.log_action_system_error(
                     &JsError::from_message(message).into(),
@@ -866,11 +1013,20 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
 pub struct ScheduledJobGarbageCollector<RT: Runtime> {
     rt: RT,
     database: Database<RT>,
+    files_storage: Arc<dyn Storage>,
 }
 
 impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
-    pub fn start(rt: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
-        let garbage_collector = Self { rt, database };
+    pub fn start(
+        rt: RT,
+        database: Database<RT>,
+        files_storage: Arc<dyn Storage>,
+    ) -> impl Future<Output = ()> + Send {
+        let garbage_collector = Self {
+            rt,
+            database,
+            files_storage,
+        };
         async move {
             let mut backoff = Backoff::new(
                 *SCHEDULED_JOB_GARBAGE_COLLECTION_INITIAL_BACKOFF,
@@ -895,6 +1051,7 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
                 .table_mapping()
                 .namespaces_for_name(&SCHEDULED_JOBS_TABLE);
             let mut deleted_jobs = false;
+            let mut deleted_args = vec![];
             let mut next_job_wait = None;
             for namespace in namespaces {
                 let now = self.rt.generate_timestamp()?;
@@ -936,6 +1093,7 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
                         break;
                     }
                     jobs_to_delete.push(job.id());
+                    deleted_args.extend(job.into_value().args_storage_key);
                 }
                 if !jobs_to_delete.is_empty() {
                     tracing::debug!(
@@ -953,6 +1111,12 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
                 self.database
                     .commit_with_write_source(tx, "scheduled_job_gc")
                     .await?;
+                // Storage GC cleans up any objects we fail to delete.
+                for storage_key in deleted_args {
+                    if let Err(e) = self.files_storage.delete_object(&storage_key).await {
+                        tracing::warn!("Failed to delete scheduled job arguments: {e:#}");
+                    }
+                }
                 self.rt.wait(*SCHEDULED_JOB_GARBAGE_COLLECTION_DELAY).await;
             } else {
                 let next_job_future = if let Some(next_job_wait) = next_job_wait {
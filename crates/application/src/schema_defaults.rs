@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::schema::SchemaState,
+    knobs::{
+        TRANSACTION_MAX_NUM_USER_WRITES,
+        TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+    },
+    persistence::LatestDocument,
+    runtime::Runtime,
+    types::TableName,
+};
+use database::{
+    Database,
+    IndexModel,
+    PatchValue,
+    SchemaModel,
+    UserFacingModel,
+};
+use errors::ErrorMetadata;
+use futures::{
+    pin_mut,
+    TryStreamExt,
+};
+use keybroker::Identity;
+use usage_tracking::FunctionUsageTracker;
+use value::{
+    ResolvedDocumentId,
+    Size,
+    TableNamespace,
+};
+
+/// Fill in the active schema's defaults on the documents of `table_name` that
+/// are missing them, returning the number of documents updated.
+///
+/// Documents are found at a single snapshot and then patched in batches with
+/// an empty patch. Patching applies the defaults to the latest version of
+/// each document, so writes made since the snapshot aren't overwritten.
+pub async fn backfill_schema_defaults<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    namespace: TableNamespace,
+    table_name: &TableName,
+) -> anyhow::Result<u64> {
+    let mut tx = database.begin(identity.clone()).await?;
+    let snapshot_ts = tx.begin_timestamp();
+    let (_, active_schema) = SchemaModel::new(&mut tx, namespace)
+        .get_by_state(SchemaState::Active)
+        .await?
+        .context(ErrorMetadata::bad_request(
+            "NoActiveSchema",
+            "Defaults can only be backfilled once a schema is active",
+        ))?;
+    let tablet_id = tx
+        .table_mapping()
+        .namespace(namespace)
+        .id_if_exists(table_name)
+        .context(ErrorMetadata::bad_request(
+            "TableNotFound",
+            format!("Table {table_name} does not exist"),
+        ))?;
+    let by_id = *IndexModel::new(&mut tx)
+        .by_id_indexes()
+        .await?
+        .get(&tablet_id)
+        .context("Missing by_id index for table")?;
+    drop(tx);
+
+    let usage = FunctionUsageTracker::new();
+    let table_iterator = database.table_iterator(snapshot_ts, 1000);
+    let stream = table_iterator.stream_documents_in_table(tablet_id, by_id, None);
+    pin_mut!(stream);
+    let mut num_documents = 0;
+    let mut batch = vec![];
+    let mut batch_size = 0;
+    while let Some(LatestDocument { value: doc, .. }) = stream.try_next().await? {
+        let object = doc.value().0.clone();
+        let with_defaults = active_schema.apply_defaults(table_name, object.clone())?;
+        if with_defaults == object {
+            continue;
+        }
+        batch_size += with_defaults.size();
+        batch.push(doc.id());
+        if batch_size > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES / 2
+            || batch.len() > *TRANSACTION_MAX_NUM_USER_WRITES / 2
+        {
+            num_documents += patch_defaults_batch(
+                database,
+                identity,
+                namespace,
+                std::mem::take(&mut batch),
+                usage.clone(),
+            )
+            .await?;
+            batch_size = 0;
+        }
+    }
+    num_documents += patch_defaults_batch(database, identity, namespace, batch, usage).await?;
+    Ok(num_documents)
+}
+
+async fn patch_defaults_batch<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    namespace: TableNamespace,
+    ids: Vec<ResolvedDocumentId>,
+    usage: FunctionUsageTracker,
+) -> anyhow::Result<u64> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let (_, num_patched, _) = database
+        .execute_with_overloaded_retries(
+            identity.clone(),
+            usage,
+            "backfill_schema_defaults",
+            |tx| {
+                async {
+                    let mut num_patched = 0;
+                    for id in ids.clone() {
+                        // Skip documents deleted since the snapshot.
+                        if tx.get(id).await?.is_none() {
+                            continue;
+                        }
+                        UserFacingModel::new(tx, namespace)
+                            .patch(id.into(), PatchValue::from(BTreeMap::new()))
+                            .await?;
+                        num_patched += 1;
+                    }
+                    Ok(num_patched)
+                }
+                .into()
+            },
+        )
+        .await?;
+    Ok(num_patched)
+}
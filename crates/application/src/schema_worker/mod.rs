@@ -46,6 +46,9 @@ use value::{
 use crate::metrics::log_worker_starting;
 
 mod metrics;
+mod validation_report;
+
+pub use validation_report::ValidationReportWorker;
 
 const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
 const MAX_BACKOFF: Duration = Duration::from_secs(5);
@@ -288,6 +291,10 @@ mod tests {
             search_indexes: btreemap! {},
             vector_indexes: btreemap! {},
             document_type: Some(DocumentSchema::Any),
+            triggers: vec![],
+            on_commit: vec![],
+            cascade_delete_files: false,
+            file_text: None,
         };
         let db_schema = DatabaseSchema {
             tables: btreemap! { table_name.clone() => table_definition },
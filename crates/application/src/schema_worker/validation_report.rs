@@ -0,0 +1,246 @@
+use std::{
+    collections::BTreeMap,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    knobs::SCHEMA_VALIDATION_REPORT_MAX_VIOLATIONS,
+    persistence::LatestDocument,
+    runtime::Runtime,
+    schemas::{
+        DatabaseSchema,
+        SchemaValidationError,
+    },
+    types::{
+        IndexId,
+        RepeatableTimestamp,
+    },
+    virtual_system_mapping::VirtualSystemMapping,
+};
+use database::{
+    Database,
+    IndexModel,
+    Transaction,
+};
+use futures::{
+    pin_mut,
+    Future,
+    TryStreamExt,
+};
+use keybroker::Identity;
+use model::schema_validation_reports::{
+    types::{
+        SchemaValidationReportState,
+        SchemaViolation,
+    },
+    SchemaValidationReportModel,
+    SCHEMA_VALIDATION_REPORTS_TABLE,
+};
+use value::{
+    NamespacedTableMapping,
+    ResolvedDocumentId,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Violation reasons include the mismatching value, so cap their length to
+/// keep reports well under the maximum document size.
+const MAX_REASON_LENGTH: usize = 1000;
+
+pub struct PendingValidationReport {
+    namespace: TableNamespace,
+    id: ResolvedDocumentId,
+    table_mapping: NamespacedTableMapping,
+    virtual_system_mapping: VirtualSystemMapping,
+    db_schema: DatabaseSchema,
+    ts: RepeatableTimestamp,
+    by_id_indexes: BTreeMap<TabletId, IndexId>,
+}
+
+/// Produces schema validation reports. Reports scan whole tables, so they run
+/// separately from the `SchemaWorker` to avoid holding up schema pushes.
+pub struct ValidationReportWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> ValidationReportWorker<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime: runtime.clone(),
+            database,
+        };
+        async move {
+            tracing::info!("Starting ValidationReportWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            loop {
+                if let Err(e) = worker.run().await {
+                    let delay = backoff.fail(&mut worker.runtime.rng());
+                    report_error(&mut e.context("ValidationReportWorker died")).await;
+                    tracing::error!("Validation report worker failed, sleeping {delay:?}");
+                    worker.runtime.wait(delay).await;
+                } else {
+                    backoff.reset();
+                }
+            }
+        }
+    }
+
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let status = log_worker_starting("ValidationReportWorker");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let pending_reports = Self::pending_validation_reports(&mut tx).await?;
+        let token = tx.into_token()?;
+        for report in pending_reports {
+            self.produce_validation_report(report).await?;
+        }
+        drop(status);
+        tracing::debug!("ValidationReportWorker waiting...");
+        let subscription = self.database.subscribe(token).await?;
+        subscription.wait_for_invalidation().await;
+        Ok(())
+    }
+
+    pub(crate) async fn pending_validation_reports(
+        tx: &mut Transaction<RT>,
+    ) -> anyhow::Result<Vec<PendingValidationReport>> {
+        let mut pending_reports = Vec::new();
+        let namespaces: Vec<_> = tx
+            .table_mapping()
+            .namespaces_for_name(&SCHEMA_VALIDATION_REPORTS_TABLE);
+        for namespace in namespaces {
+            if let Some((id, db_schema)) = SchemaValidationReportModel::new(tx, namespace)
+                .pending()
+                .await?
+            {
+                pending_reports.push(PendingValidationReport {
+                    namespace,
+                    id,
+                    table_mapping: tx.table_mapping().namespace(namespace),
+                    virtual_system_mapping: tx.virtual_system_mapping().clone(),
+                    db_schema,
+                    ts: tx.begin_timestamp(),
+                    by_id_indexes: IndexModel::new(tx).by_id_indexes().await?,
+                });
+            }
+        }
+        Ok(pending_reports)
+    }
+
+    /// Validate every document in the schema's tables without enforcing the
+    /// schema, and save the violations found into the report.
+    pub(crate) async fn produce_validation_report(
+        &self,
+        PendingValidationReport {
+            namespace,
+            id,
+            table_mapping,
+            virtual_system_mapping,
+            mut db_schema,
+            ts,
+            by_id_indexes,
+        }: PendingValidationReport,
+    ) -> anyhow::Result<()> {
+        tracing::info!("ValidationReportWorker producing a schema validation report");
+        // The report describes what would fail once validation is enforced.
+        db_schema.schema_validation = true;
+        let scan = self
+            .find_violations(
+                &db_schema,
+                &table_mapping,
+                &virtual_system_mapping,
+                ts,
+                &by_id_indexes,
+            )
+            .await;
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let state = match scan {
+            Ok((num_documents_checked, violations, truncated)) => {
+                SchemaValidationReportState::Completed {
+                    completed_ts: *tx.begin_timestamp(),
+                    num_documents_checked,
+                    violations,
+                    truncated,
+                }
+            },
+            Err(mut e) => {
+                report_error(&mut e).await;
+                SchemaValidationReportState::Failed {
+                    error: e.to_string(),
+                }
+            },
+        };
+        SchemaValidationReportModel::new(&mut tx, namespace)
+            .finish(id, state)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "schema_worker_validation_report")
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the number of documents checked, the violations found and
+    /// whether there were more violations than we keep.
+    async fn find_violations(
+        &self,
+        db_schema: &DatabaseSchema,
+        table_mapping: &NamespacedTableMapping,
+        virtual_system_mapping: &VirtualSystemMapping,
+        ts: RepeatableTimestamp,
+        by_id_indexes: &BTreeMap<TabletId, IndexId>,
+    ) -> anyhow::Result<(u64, Vec<SchemaViolation>, bool)> {
+        let mut num_documents_checked = 0;
+        let mut violations = vec![];
+        let mut truncated = false;
+        for table_name in db_schema.tables.keys() {
+            let Some(tablet_id) = table_mapping.id_if_exists(table_name) else {
+                continue;
+            };
+            let by_id = *by_id_indexes.get(&tablet_id).ok_or_else(|| {
+                anyhow::anyhow!("Failed to find id index for table id {tablet_id}")
+            })?;
+            let table_iterator = self.database.table_iterator(ts, 1000);
+            let stream = table_iterator.stream_documents_in_table(tablet_id, by_id, None);
+            pin_mut!(stream);
+            while let Some(LatestDocument { value: doc, .. }) = stream.try_next().await? {
+                num_documents_checked += 1;
+                let Err(error) = db_schema.check_existing_document(
+                    &doc,
+                    table_name.clone(),
+                    table_mapping,
+                    virtual_system_mapping,
+                ) else {
+                    continue;
+                };
+                let SchemaValidationError::ExistingDocument {
+                    validation_error,
+                    id: document_id,
+                    ..
+                } = error
+                else {
+                    anyhow::bail!("Unexpected schema validation error: {error}");
+                };
+                if violations.len() >= *SCHEMA_VALIDATION_REPORT_MAX_VIOLATIONS {
+                    truncated = true;
+                    continue;
+                }
+                let mut reason = validation_error.to_string();
+                reason.truncate(reason.floor_char_boundary(MAX_REASON_LENGTH));
+                violations.push(SchemaViolation {
+                    table_name: table_name.clone(),
+                    document_id,
+                    field_path: validation_error.field_path(),
+                    reason,
+                });
+            }
+        }
+        Ok((num_documents_checked, violations, truncated))
+    }
+}
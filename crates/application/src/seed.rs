@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use common::{
+    document::ID_FIELD,
+    knobs::{
+        TRANSACTION_MAX_NUM_USER_WRITES,
+        TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+    },
+    runtime::Runtime,
+    types::TableName,
+};
+use database::{
+    Database,
+    ImportFacingModel,
+    SchemaModel,
+    TableModel,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use serde_json::Value as JsonValue;
+use usage_tracking::FunctionUsageTracker;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldName,
+    Size,
+    TableNamespace,
+    TabletIdAndTableNumber,
+};
+
+/// Field naming a seed document, so other documents can refer to it.
+const NAME_FIELD: &str = "$name";
+/// Key of an object that stands for the ID of the seed document it names.
+const REF_FIELD: &str = "$ref";
+
+/// Documents to load with [`crate::Application::seed`], by table.
+///
+/// Documents are objects in Convex's JSON format. A document can be given a
+/// `$name`, and any value in the fixture can refer to that document's ID as
+/// `{"$ref": "<name>"}`, whichever table or order the documents are in.
+#[derive(Debug, Clone, Default)]
+pub struct SeedFixture {
+    pub tables: BTreeMap<TableName, Vec<JsonValue>>,
+}
+
+impl TryFrom<JsonValue> for SeedFixture {
+    type Error = anyhow::Error;
+
+    /// Parses `{"<table>": [<document>, ...], ...}`.
+    fn try_from(value: JsonValue) -> anyhow::Result<Self> {
+        let JsonValue::Object(tables) = value else {
+            anyhow::bail!(invalid_fixture(
+                "Seed fixture must map table names to documents"
+            ));
+        };
+        let tables = tables
+            .into_iter()
+            .map(|(table_name, documents)| {
+                let table_name: TableName = table_name.parse().map_err(|e| {
+                    invalid_fixture(format!("Invalid table name {table_name}: {e}"))
+                })?;
+                let JsonValue::Array(documents) = documents else {
+                    anyhow::bail!(invalid_fixture(format!(
+                        "Documents for table {table_name} must be an array"
+                    )));
+                };
+                Ok((table_name, documents))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { tables })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedResult {
+    pub num_documents: u64,
+    /// IDs of the documents that were given a `$name`.
+    pub ids: BTreeMap<String, DeveloperDocumentId>,
+}
+
+/// Loads a fixture into user tables of `namespace`, creating the tables if
+/// needed. Documents are inserted in bounded transactions, so a large fixture
+/// can be partially loaded if a later transaction fails.
+pub async fn seed<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    namespace: TableNamespace,
+    fixture: SeedFixture,
+) -> anyhow::Result<SeedResult> {
+    anyhow::ensure!(
+        identity.is_admin() || identity.is_system(),
+        ErrorMetadata::forbidden("Unauthorized", "Seeding requires admin auth")
+    );
+    for table_name in fixture.tables.keys() {
+        anyhow::ensure!(
+            !table_name.is_system(),
+            invalid_fixture(format!("Cannot seed system table {table_name}"))
+        );
+    }
+
+    // Pick the ID of every document up front, so documents can refer to ones
+    // inserted in later transactions.
+    let (_, table_ids, _) = database
+        .execute_with_overloaded_retries(
+            identity.clone(),
+            FunctionUsageTracker::new(),
+            "seed_prepare_tables",
+            |tx| {
+                async {
+                    let mut table_ids = BTreeMap::new();
+                    for (table_name, documents) in &fixture.tables {
+                        TableModel::new(tx)
+                            .insert_table_metadata(namespace, table_name)
+                            .await?;
+                        let table_id = tx.table_mapping().namespace(namespace).id(table_name)?;
+                        let mut ids = Vec::with_capacity(documents.len());
+                        for _ in documents {
+                            ids.push(tx.generate_document_id(table_id.table_number));
+                        }
+                        table_ids.insert(table_name.clone(), (table_id, ids));
+                    }
+                    Ok(table_ids)
+                }
+                .into()
+            },
+        )
+        .await?;
+
+    let mut named_ids = BTreeMap::new();
+    for (table_name, documents) in &fixture.tables {
+        let (_, ids) = &table_ids[table_name];
+        for (document, id) in documents.iter().zip(ids) {
+            let Some(name) = document.get(NAME_FIELD) else {
+                continue;
+            };
+            let name = name
+                .as_str()
+                .context(invalid_fixture("`$name` must be a string"))?;
+            anyhow::ensure!(
+                named_ids.insert(name.to_string(), *id).is_none(),
+                invalid_fixture(format!("More than one seed document is named {name}"))
+            );
+        }
+    }
+
+    let mut num_documents = 0;
+    let mut batch = vec![];
+    let mut batch_size = 0;
+    for (table_name, documents) in fixture.tables {
+        let (table_id, ids) = &table_ids[&table_name];
+        for (document, id) in documents.into_iter().zip(ids) {
+            let object = seed_document_to_object(document, *id, &named_ids)?;
+            batch_size += object.size();
+            batch.push((*table_id, table_name.clone(), object));
+            if batch_size > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES / 2
+                || batch.len() > *TRANSACTION_MAX_NUM_USER_WRITES / 2
+            {
+                num_documents += batch.len() as u64;
+                insert_seed_batch(database, identity, namespace, std::mem::take(&mut batch))
+                    .await?;
+                batch_size = 0;
+            }
+        }
+    }
+    num_documents += batch.len() as u64;
+    insert_seed_batch(database, identity, namespace, batch).await?;
+    Ok(SeedResult {
+        num_documents,
+        ids: named_ids,
+    })
+}
+
+fn seed_document_to_object(
+    document: JsonValue,
+    id: DeveloperDocumentId,
+    named_ids: &BTreeMap<String, DeveloperDocumentId>,
+) -> anyhow::Result<ConvexObject> {
+    let JsonValue::Object(mut fields) = document else {
+        anyhow::bail!(invalid_fixture("Seed documents must be objects"));
+    };
+    fields.remove(NAME_FIELD);
+    anyhow::ensure!(
+        !fields.contains_key("_id"),
+        invalid_fixture("Seed documents can't have `_id` fields, give them a `$name` instead")
+    );
+    let mut value = JsonValue::Object(fields);
+    resolve_refs(&mut value, named_ids)?;
+    let object = ConvexObject::try_from(value)
+        .map_err(|e| invalid_fixture(format!("Invalid seed document: {e}")))?;
+    object.shallow_merge(ConvexObject::for_value(
+        FieldName::from(ID_FIELD.clone()),
+        ConvexValue::String(id.encode().try_into()?),
+    )?)
+}
+
+/// Replaces every `{"$ref": name}` in `value` with the named document's ID.
+fn resolve_refs(
+    value: &mut JsonValue,
+    named_ids: &BTreeMap<String, DeveloperDocumentId>,
+) -> anyhow::Result<()> {
+    match value {
+        JsonValue::Object(fields) => {
+            if fields.len() == 1
+                && let Some(reference) = fields.get(REF_FIELD)
+            {
+                let name = reference
+                    .as_str()
+                    .context(invalid_fixture("`$ref` must be a string"))?;
+                let id = named_ids.get(name).context(ErrorMetadata::bad_request(
+                    "UnknownSeedReference",
+                    format!("No seed document is named {name}"),
+                ))?;
+                *value = JsonValue::String(id.encode());
+                return Ok(());
+            }
+            for field in fields.values_mut() {
+                resolve_refs(field, named_ids)?;
+            }
+        },
+        JsonValue::Array(items) => {
+            for item in items {
+                resolve_refs(item, named_ids)?;
+            }
+        },
+        _ => {},
+    }
+    Ok(())
+}
+
+async fn insert_seed_batch<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    namespace: TableNamespace,
+    batch: Vec<(TabletIdAndTableNumber, TableName, ConvexObject)>,
+) -> anyhow::Result<()> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+    database
+        .execute_with_overloaded_retries(
+            identity.clone(),
+            FunctionUsageTracker::new(),
+            "seed_insert_documents",
+            |tx| {
+                async {
+                    let table_mapping = tx.table_mapping().clone();
+                    for (table_id, table_name, object) in batch.clone() {
+                        let object = SchemaModel::new(tx, namespace)
+                            .apply_defaults(&table_name, object)
+                            .await?;
+                        ImportFacingModel::new(tx)
+                            .insert(table_id, &table_name, object, &table_mapping)
+                            .await?;
+                    }
+                    Ok(())
+                }
+                .into()
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+fn invalid_fixture(msg: impl Into<String>) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidSeedFixture", msg.into())
+}
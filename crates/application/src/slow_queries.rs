@@ -0,0 +1,127 @@
+use std::{
+    future::Future,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    components::ComponentId,
+    errors::report_error,
+    knobs::SLOW_QUERY_LOG_RETENTION,
+    runtime::Runtime,
+};
+use database::{
+    slow_query_log::{
+        SlowQuery,
+        SLOW_QUERY_LOG,
+    },
+    Database,
+};
+use keybroker::Identity;
+use model::slow_queries::{
+    types::SlowQueryRecord,
+    SlowQueryModel,
+};
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How often buffered slow queries are persisted.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of records inserted per transaction.
+const RECORDS_PER_TRANSACTION: usize = 128;
+
+/// Copies slow queries from the in-memory slow query log into
+/// `_slow_queries`. The system table cleanup worker deletes them once they're
+/// older than the retention period.
+pub struct SlowQueryWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> SlowQueryWorker<RT> {
+    fn new(runtime: RT, database: Database<RT>) -> Self {
+        Self {
+            runtime,
+            database,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        }
+    }
+
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let mut worker = Self::new(runtime, database);
+        async move {
+            if SLOW_QUERY_LOG_RETENTION.is_none() {
+                tracing::info!("Slow query log is disabled");
+                return;
+            }
+            tracing::info!("Starting SlowQueryWorker");
+            loop {
+                worker.runtime.wait(FLUSH_INTERVAL).await;
+                match worker.run_once().await {
+                    Ok(()) => worker.backoff.reset(),
+                    Err(e) => {
+                        report_error(&mut e.context("SlowQueryWorker died")).await;
+                        let delay = worker.backoff.fail(&mut worker.runtime.rng());
+                        worker.runtime.wait(delay).await;
+                    },
+                }
+            }
+        }
+    }
+
+    /// Persist the queries still buffered in the slow query log, so queries
+    /// recorded since the last flush survive a shutdown.
+    pub async fn checkpoint(runtime: RT, database: Database<RT>) -> anyhow::Result<()> {
+        if SLOW_QUERY_LOG_RETENTION.is_none() {
+            return Ok(());
+        }
+        Self::new(runtime, database).run_once().await
+    }
+
+    async fn run_once(&self) -> anyhow::Result<()> {
+        let queries = SLOW_QUERY_LOG.drain();
+        if queries.is_empty() {
+            return Ok(());
+        }
+        let _status = log_worker_starting("SlowQueryWorker");
+        for chunk in queries.chunks(RECORDS_PER_TRANSACTION) {
+            self.insert_records(chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert_records(&self, queries: &[SlowQuery]) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let mut records = Vec::with_capacity(queries.len());
+        for query in queries {
+            // Skip queries from components that have since been deleted.
+            let Some(component_path) = tx.get_component_path(ComponentId::from(query.namespace))
+            else {
+                continue;
+            };
+            records.push(SlowQueryRecord {
+                component_path,
+                table_name: query.shape.index_name.table().to_string(),
+                index_name: query.shape.index_name.descriptor().as_str().to_string(),
+                range: query.shape.range.clone(),
+                filter: query.shape.filter.clone(),
+                order: query.shape.order,
+                documents_scanned: query.stats.documents_scanned as u64,
+                bytes_scanned: query.stats.bytes_scanned as u64,
+                documents_returned: query.stats.documents_returned as u64,
+                execution_time_ms: query.stats.execution_time.as_millis() as u64,
+                suggested_index: query.shape.suggested_index.clone(),
+            });
+        }
+        SlowQueryModel::new(&mut tx).insert(records).await?;
+        self.database
+            .commit_with_write_source(tx, "slow_query_worker")
+            .await?;
+        Ok(())
+    }
+}
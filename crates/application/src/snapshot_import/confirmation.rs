@@ -15,8 +15,10 @@ use common::{
     types::TableName,
 };
 use database::TransactionReadSet;
+use errors::ErrorMetadata;
 use futures::TryStreamExt;
 use itertools::Itertools;
+use keybroker::Identity;
 use model::{
     file_storage::{
         FILE_STORAGE_TABLE,
@@ -28,8 +30,10 @@ use model::{
         SnapshotImport,
     },
 };
+use value::TableNumber;
 
 use crate::snapshot_import::{
+    id_preservation::table_number_conflicts_for_clone,
     import_error::ImportError,
     parse::ImportUnit,
     table_change::{
@@ -73,6 +77,8 @@ async fn messages_to_confirm_replace<RT: Runtime>(
     // Find all tables being written to.
     let mut count_by_table: BTreeMap<(ComponentPath, TableName), u64> = BTreeMap::new();
     let mut tables_missing_id_field: BTreeSet<(ComponentPath, TableName)> = BTreeSet::new();
+    let mut source_table_numbers: BTreeMap<ComponentPath, Vec<(TableName, TableNumber)>> =
+        BTreeMap::new();
     let mut current_table = None;
     let mut lineno = 0;
     while let Some(object) = objects.try_next().await? {
@@ -103,9 +109,19 @@ async fn messages_to_confirm_replace<RT: Runtime>(
                                 anyhow::anyhow!("table requires name"),
                             )
                         })?;
-                    let table_name = table_name
+                    let table_name: TableName = table_name
                         .parse()
                         .map_err(|e| ImportError::InvalidName(table_name.to_string(), e))?;
+                    if let Some(table_number) = exported_object
+                        .get("id")
+                        .and_then(|id| id.as_f64())
+                        .and_then(|id| TableNumber::try_from(id as u32).ok())
+                    {
+                        source_table_numbers
+                            .entry(current_component.clone())
+                            .or_default()
+                            .push((table_name.clone(), table_number));
+                    }
                     count_by_table
                         .entry((current_component.clone(), table_name))
                         .or_default();
@@ -119,16 +135,29 @@ async fn messages_to_confirm_replace<RT: Runtime>(
                     tables_missing_id_field.insert(current_component_table.clone());
                 }
             },
+            ImportUnit::TypedObject(_) => {
+                let Some(current_component_table) = &current_table else {
+                    continue;
+                };
+                if let Some(count) = count_by_table.get_mut(current_component_table) {
+                    *count += 1;
+                }
+                tables_missing_id_field.insert(current_component_table.clone());
+            },
             // Ignore storage file chunks and generated schemas.
             ImportUnit::StorageFileChunk(..) | ImportUnit::GeneratedSchema(..) => {},
         }
     }
 
+    if mode == ImportMode::Clone {
+        check_ids_preserved(executor, &source_table_numbers, &tables_missing_id_field).await?;
+    }
+
     let db_snapshot = executor.database.latest_snapshot()?;
 
     // Add to count_by_table all tables that are being replaced that don't appear in
     // the import.
-    if mode == ImportMode::ReplaceAll {
+    if matches!(mode, ImportMode::ReplaceAll | ImportMode::Clone) {
         let component_paths = db_snapshot.component_ids_to_paths();
         let table_mapping = db_snapshot.table_mapping();
         for (tablet_id, namespace, _, table_name) in table_mapping.iter() {
@@ -164,11 +193,11 @@ async fn messages_to_confirm_replace<RT: Runtime>(
             .unwrap_or(0);
         if !table_name.is_system() {
             let to_delete = match mode {
-                ImportMode::Replace | ImportMode::ReplaceAll => {
+                ImportMode::Replace | ImportMode::ReplaceAll | ImportMode::Clone => {
                     // Overwriting nonempty user table.
                     existing_num_values
                 },
-                ImportMode::Append => 0,
+                ImportMode::Append | ImportMode::Upsert => 0,
                 ImportMode::RequireEmpty if existing_num_values > 0 => {
                     anyhow::bail!(ImportError::TableExists(table_name.clone()))
                 },
@@ -187,11 +216,11 @@ async fn messages_to_confirm_replace<RT: Runtime>(
         }
         if table_name == &*FILE_STORAGE_VIRTUAL_TABLE {
             let to_delete = match mode {
-                ImportMode::Replace | ImportMode::ReplaceAll => {
+                ImportMode::Replace | ImportMode::ReplaceAll | ImportMode::Clone => {
                     // Overwriting nonempty file storage.
                     existing_num_values
                 },
-                ImportMode::Append => 0,
+                ImportMode::Append | ImportMode::Upsert => 0,
                 ImportMode::RequireEmpty if existing_num_values > 0 => {
                     anyhow::bail!(ImportError::TableExists(table_name.clone()))
                 },
@@ -236,6 +265,7 @@ async fn messages_to_confirm_replace<RT: Runtime>(
             existing_rows_to_delete: *deleted as i64,
             existing_rows_in_table: *existing as i64,
             is_missing_id_field: *is_missing_id_field,
+            upsert_stats: None,
         });
     }
     let mut message_lines = Vec::new();
@@ -250,3 +280,46 @@ async fn messages_to_confirm_replace<RT: Runtime>(
     }
     Ok((message_lines, require_manual_confirmation, new_checkpoints))
 }
+
+/// Clone imports fail before writing anything if some documents couldn't keep
+/// their IDs, either because they don't have any or because their table
+/// numbers are taken in this deployment.
+async fn check_ids_preserved<RT: Runtime>(
+    executor: &SnapshotImportExecutor<RT>,
+    source_table_numbers: &BTreeMap<ComponentPath, Vec<(TableName, TableNumber)>>,
+    tables_missing_id_field: &BTreeSet<(ComponentPath, TableName)>,
+) -> anyhow::Result<()> {
+    if let Some((component_path, table_name)) =
+        tables_missing_id_field.iter().find(|(_, table_name)| {
+            !table_name.is_system() || table_name == &*FILE_STORAGE_VIRTUAL_TABLE
+        })
+    {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "CloneMissingIds",
+            format!(
+                "Cannot clone \"{table_name}\"{} because its documents don't have `_id` fields",
+                component_path.in_component_str()
+            )
+        ));
+    }
+    let mut tx = executor.database.begin(Identity::system()).await?;
+    let conflicts = table_number_conflicts_for_clone(&mut tx, source_table_numbers).await?;
+    if !conflicts.is_empty() {
+        let conflicts = conflicts
+            .iter()
+            .map(|conflict| {
+                format!(
+                    "\"{}\"{} conflicts with \"{}\"",
+                    conflict.table_name,
+                    conflict.component_path.in_component_str(),
+                    conflict.existing_table
+                )
+            })
+            .join(", ");
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "CloneTableConflict",
+            format!("Cannot clone with the same document IDs: {conflicts}")
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,1052 @@
+//! Importers for documents exported from other databases: Firestore (bundles
+//! or one REST API `Document` per line) and MongoDB (`mongoexport` extended
+//! JSON or `mongodump` BSON).
+//!
+//! Documents are first parsed into [`SourceDocument`]s, which keep the types
+//! the source database distinguishes, and then converted into Convex objects
+//! following an [`ImportFieldMapping`]. Without a rule for a field:
+//! - timestamps become float64 milliseconds since the Unix epoch,
+//! - Firestore references become their document path,
+//! - MongoDB ObjectIds become their hex string,
+//! - decimals become float64,
+//! - geo points become `{ latitude, longitude }` objects,
+//! - everything else maps onto the Convex type of the same name.
+
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use chrono::{
+    DateTime,
+    SecondsFormat,
+    Utc,
+};
+use common::knobs::TRANSACTION_MAX_USER_WRITE_SIZE_BYTES;
+use futures::{
+    io::BufReader,
+    pin_mut,
+    AsyncBufReadExt,
+    AsyncRead,
+    AsyncReadExt,
+    Stream,
+    TryStreamExt,
+};
+use futures_async_stream::try_stream;
+use model::snapshot_imports::types::{
+    FieldConversion,
+    ImportFieldMapping,
+};
+use serde_json::{
+    Map as JsonMap,
+    Value as JsonValue,
+};
+use value::{
+    ConvexObject,
+    ConvexValue,
+    FieldName,
+};
+
+use crate::snapshot_import::import_error::ImportError;
+
+/// Most errors listed in an [`ImportValidationReport`].
+const MAX_REPORTED_ERRORS: usize = 100;
+
+/// A value in a document exported from another database.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SourceValue {
+    Null,
+    Boolean(bool),
+    Int64(i64),
+    Float64(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    /// Milliseconds since the Unix epoch.
+    Timestamp(f64),
+    /// A Firestore document reference, as the referenced document's path.
+    Reference(String),
+    /// A decimal number in its source string form, e.g. "1.50" or "1E+3".
+    Decimal(String),
+    /// A MongoDB ObjectId, as hex.
+    ObjectId(String),
+    GeoPoint {
+        latitude: f64,
+        longitude: f64,
+    },
+    Array(Vec<SourceValue>),
+    Object(Vec<(String, SourceValue)>),
+}
+
+impl SourceValue {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            SourceValue::Null => "null",
+            SourceValue::Boolean(_) => "boolean",
+            SourceValue::Int64(_) => "int64",
+            SourceValue::Float64(_) => "float64",
+            SourceValue::String(_) => "string",
+            SourceValue::Bytes(_) => "bytes",
+            SourceValue::Timestamp(_) => "timestamp",
+            SourceValue::Reference(_) => "reference",
+            SourceValue::Decimal(_) => "decimal",
+            SourceValue::ObjectId(_) => "objectId",
+            SourceValue::GeoPoint { .. } => "geoPoint",
+            SourceValue::Array(_) => "array",
+            SourceValue::Object(_) => "object",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceDocument {
+    /// The Firestore document id or the MongoDB `_id`.
+    pub id: Option<String>,
+    pub fields: Vec<(String, SourceValue)>,
+}
+
+/// What a dry run of an import found, without writing anything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportValidationReport {
+    pub num_documents: u64,
+    /// Documents that parsed but couldn't be converted into Convex objects.
+    pub num_invalid_documents: u64,
+    /// How many documents had each source type in each top-level field.
+    pub field_types: BTreeMap<String, BTreeMap<&'static str, u64>>,
+    /// The first errors found.
+    pub errors: Vec<String>,
+    /// False if the dry run stopped at a document that couldn't be parsed.
+    pub complete: bool,
+}
+
+/// Converts a source document into the object to import, following `mapping`.
+pub fn convert_document(
+    document: SourceDocument,
+    mapping: &ImportFieldMapping,
+) -> anyhow::Result<ConvexObject> {
+    let mut fields = BTreeMap::new();
+    if let Some(id_field) = &mapping.id_field
+        && let Some(id) = document.id
+    {
+        fields.insert(id_field.clone(), ConvexValue::try_from(id)?);
+    }
+    for (name, value) in document.fields {
+        let rule = mapping.fields.get(&name);
+        let converted = match rule.and_then(|rule| rule.convert) {
+            Some(FieldConversion::Skip) => continue,
+            Some(conversion) => convert_value_as(value, conversion),
+            None => convert_value(value),
+        }
+        .with_context(|| format!("Failed to convert field {name:?}"))?;
+        let field_name = match rule.and_then(|rule| rule.rename.clone()) {
+            Some(rename) => rename,
+            None => parse_field_name(&name)?,
+        };
+        anyhow::ensure!(
+            !fields.contains_key(&field_name),
+            "More than one field maps to {field_name:?}"
+        );
+        fields.insert(field_name, converted);
+    }
+    fields.try_into()
+}
+
+fn parse_field_name(name: &str) -> anyhow::Result<FieldName> {
+    name.parse()
+        .with_context(|| format!("{name:?} isn't a valid field name"))
+}
+
+fn convert_value(value: SourceValue) -> anyhow::Result<ConvexValue> {
+    let converted: ConvexValue = match value {
+        SourceValue::Null => ConvexValue::Null,
+        SourceValue::Boolean(b) => b.into(),
+        SourceValue::Int64(i) => i.into(),
+        SourceValue::Float64(f) | SourceValue::Timestamp(f) => f.into(),
+        SourceValue::String(s) | SourceValue::Reference(s) | SourceValue::ObjectId(s) => {
+            s.try_into()?
+        },
+        SourceValue::Bytes(bytes) => bytes.try_into()?,
+        SourceValue::Decimal(s) => parse_float(&s)?.into(),
+        SourceValue::GeoPoint {
+            latitude,
+            longitude,
+        } => {
+            let fields = BTreeMap::from([
+                (parse_field_name("latitude")?, ConvexValue::from(latitude)),
+                (parse_field_name("longitude")?, ConvexValue::from(longitude)),
+            ]);
+            ConvexObject::try_from(fields)?.into()
+        },
+        SourceValue::Array(values) => values
+            .into_iter()
+            .map(convert_value)
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .try_into()?,
+        SourceValue::Object(entries) => {
+            let mut fields = BTreeMap::new();
+            for (name, value) in entries {
+                fields.insert(parse_field_name(&name)?, convert_value(value)?);
+            }
+            ConvexObject::try_from(fields)?.into()
+        },
+    };
+    Ok(converted)
+}
+
+fn convert_value_as(
+    value: SourceValue,
+    conversion: FieldConversion,
+) -> anyhow::Result<ConvexValue> {
+    if value == SourceValue::Null {
+        return Ok(ConvexValue::Null);
+    }
+    let type_name = value.type_name();
+    let converted: ConvexValue = match (conversion, value) {
+        (FieldConversion::Skip, _) => anyhow::bail!("Skipped fields have no value"),
+        (FieldConversion::String, SourceValue::Boolean(b)) => b.to_string().try_into()?,
+        (FieldConversion::String, SourceValue::Int64(i)) => i.to_string().try_into()?,
+        (FieldConversion::String, SourceValue::Float64(f)) => f.to_string().try_into()?,
+        (FieldConversion::String, SourceValue::Timestamp(ms)) => {
+            let timestamp = DateTime::<Utc>::from_timestamp_millis(ms as i64)
+                .with_context(|| format!("Timestamp {ms} is out of range"))?;
+            timestamp
+                .to_rfc3339_opts(SecondsFormat::Millis, true)
+                .try_into()?
+        },
+        (
+            FieldConversion::String,
+            SourceValue::String(s)
+            | SourceValue::Reference(s)
+            | SourceValue::Decimal(s)
+            | SourceValue::ObjectId(s),
+        ) => s.try_into()?,
+        (FieldConversion::Float64, SourceValue::Int64(i)) => (i as f64).into(),
+        (FieldConversion::Float64, SourceValue::Float64(f) | SourceValue::Timestamp(f)) => f.into(),
+        (FieldConversion::Float64, SourceValue::String(s) | SourceValue::Decimal(s)) => {
+            parse_float(&s)?.into()
+        },
+        (FieldConversion::Int64, SourceValue::Int64(i)) => i.into(),
+        (FieldConversion::Int64, SourceValue::Float64(f) | SourceValue::Timestamp(f)) => {
+            anyhow::ensure!(
+                f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64,
+                "{f} isn't an int64"
+            );
+            (f as i64).into()
+        },
+        (FieldConversion::Int64, SourceValue::String(s) | SourceValue::Decimal(s)) => s
+            .parse::<i64>()
+            .with_context(|| format!("{s:?} isn't an int64"))?
+            .into(),
+        (conversion, _) => anyhow::bail!("Can't convert {type_name} to {conversion}"),
+    };
+    Ok(converted)
+}
+
+fn parse_float(s: &str) -> anyhow::Result<f64> {
+    s.parse().with_context(|| format!("{s:?} isn't a number"))
+}
+
+/// Converts parsed source documents into objects to import, numbering
+/// documents from 1 in errors.
+#[try_stream(ok = ConvexObject, error = anyhow::Error)]
+pub async fn convert_documents<S>(documents: S, mapping: ImportFieldMapping)
+where
+    S: Stream<Item = anyhow::Result<SourceDocument>>,
+{
+    pin_mut!(documents);
+    let mut document_number = 0;
+    while let Some(document) = documents.try_next().await? {
+        document_number += 1;
+        yield convert_document(document, &mapping)
+            .map_err(|e| ImportError::InvalidConvexValue(document_number, e))?;
+    }
+}
+
+/// Parses and converts every document, collecting what was found and what
+/// would fail to import.
+pub async fn validate_documents<S>(
+    documents: S,
+    mapping: &ImportFieldMapping,
+) -> anyhow::Result<ImportValidationReport>
+where
+    S: Stream<Item = anyhow::Result<SourceDocument>>,
+{
+    pin_mut!(documents);
+    let mut report = ImportValidationReport {
+        complete: true,
+        ..Default::default()
+    };
+    loop {
+        let document = match documents.try_next().await {
+            Ok(Some(document)) => document,
+            Ok(None) => break,
+            // Problems with the file are part of the report, but anything else
+            // (e.g. failing to read the upload) fails the dry run.
+            Err(e) if e.downcast_ref::<ImportError>().is_some() => {
+                report.errors.push(e.to_string());
+                report.complete = false;
+                break;
+            },
+            Err(e) => return Err(e),
+        };
+        report.num_documents += 1;
+        for (name, value) in &document.fields {
+            *report
+                .field_types
+                .entry(name.clone())
+                .or_default()
+                .entry(value.type_name())
+                .or_default() += 1;
+        }
+        if let Err(e) = convert_document(document, mapping) {
+            report.num_invalid_documents += 1;
+            if report.errors.len() < MAX_REPORTED_ERRORS {
+                let row_number = report.num_documents as usize;
+                report
+                    .errors
+                    .push(ImportError::InvalidConvexValue(row_number, e).to_string());
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Parses Firestore documents, either from a bundle (length-prefixed JSON
+/// elements) or with one REST API `Document` JSON object per line. Documents
+/// from every collection in the file are returned.
+#[try_stream(ok = SourceDocument, error = anyhow::Error)]
+pub async fn parse_firestore_documents<R: AsyncRead + Unpin>(reader: R) {
+    let mut reader = BufReader::new(reader);
+    let is_bundle = reader
+        .fill_buf()
+        .await?
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| b.is_ascii_digit());
+    let mut document_number = 0;
+    loop {
+        let element = if is_bundle {
+            match read_bundle_element(&mut reader, document_number + 1).await? {
+                Some(element) => element,
+                None => break,
+            }
+        } else {
+            let mut line = String::new();
+            if reader
+                .read_line(&mut line)
+                .await
+                .map_err(ImportError::NotUtf8)?
+                == 0
+            {
+                break;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            serde_json::from_str(&line)
+                .map_err(|e| ImportError::JsonInvalidRow(document_number + 1, e))?
+        };
+        // Bundles also contain metadata and queries, which aren't imported.
+        let document = match element {
+            JsonValue::Object(mut element) if element.contains_key("document") => {
+                element.remove("document").unwrap_or_default()
+            },
+            element if !is_bundle => element,
+            _ => continue,
+        };
+        document_number += 1;
+        yield parse_firestore_document(document)
+            .map_err(|e| ImportError::InvalidSourceDocument(document_number, e))?;
+    }
+}
+
+async fn read_bundle_element<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    document_number: usize,
+) -> anyhow::Result<Option<JsonValue>> {
+    // Each element is its length in bytes followed by that many bytes of JSON.
+    let mut prefix = Vec::new();
+    reader.read_until(b'{', &mut prefix).await?;
+    if prefix.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(None);
+    }
+    let invalid_length = || {
+        ImportError::InvalidSourceDocument(
+            document_number,
+            anyhow::anyhow!("Invalid bundle element length"),
+        )
+    };
+    let length: usize = prefix
+        .strip_suffix(b"{")
+        .and_then(|length| std::str::from_utf8(length).ok())
+        .and_then(|length| length.trim().parse().ok())
+        .filter(|length| *length > 0)
+        .ok_or_else(invalid_length)?;
+    if length > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES {
+        anyhow::bail!(ImportError::SourceDocumentTooLarge(document_number, length));
+    }
+    let mut element = vec![0; length];
+    element[0] = b'{';
+    reader.read_exact(&mut element[1..]).await?;
+    let element = serde_json::from_slice(&element)
+        .map_err(|e| ImportError::JsonInvalidRow(document_number, e))?;
+    Ok(Some(element))
+}
+
+fn parse_firestore_document(document: JsonValue) -> anyhow::Result<SourceDocument> {
+    let JsonValue::Object(mut document) = document else {
+        anyhow::bail!("Firestore documents must be JSON objects");
+    };
+    // Names look like "projects/p/databases/(default)/documents/users/alice".
+    let id = match document.remove("name") {
+        Some(JsonValue::String(name)) => name.rsplit('/').next().map(str::to_string),
+        _ => None,
+    };
+    let fields = match document.remove("fields") {
+        Some(JsonValue::Object(fields)) => parse_firestore_fields(fields)?,
+        Some(_) => anyhow::bail!("Firestore document fields must be an object"),
+        None => vec![],
+    };
+    Ok(SourceDocument { id, fields })
+}
+
+fn parse_firestore_fields(
+    fields: JsonMap<String, JsonValue>,
+) -> anyhow::Result<Vec<(String, SourceValue)>> {
+    fields
+        .into_iter()
+        .map(|(name, value)| {
+            let value = parse_firestore_value(value)
+                .with_context(|| format!("Invalid value for field {name:?}"))?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+fn parse_firestore_value(value: JsonValue) -> anyhow::Result<SourceValue> {
+    let JsonValue::Object(value) = value else {
+        anyhow::bail!("Firestore values must be JSON objects");
+    };
+    anyhow::ensure!(
+        value.len() == 1,
+        "Firestore values must have exactly one type"
+    );
+    let (kind, inner) = value.into_iter().next().context("Missing value")?;
+    let parsed = match (kind.as_str(), inner) {
+        ("nullValue", _) => SourceValue::Null,
+        ("booleanValue", JsonValue::Bool(b)) => SourceValue::Boolean(b),
+        // int64s are strings so they survive JSON parsers that only have doubles.
+        ("integerValue", JsonValue::String(s)) => {
+            SourceValue::Int64(s.parse().with_context(|| format!("{s:?} isn't an int64"))?)
+        },
+        ("integerValue", JsonValue::Number(n)) => {
+            SourceValue::Int64(n.as_i64().with_context(|| format!("{n} isn't an int64"))?)
+        },
+        ("doubleValue", JsonValue::Number(n)) => {
+            SourceValue::Float64(n.as_f64().context("Invalid double")?)
+        },
+        // NaN and infinities.
+        ("doubleValue", JsonValue::String(s)) => SourceValue::Float64(parse_float(&s)?),
+        ("timestampValue", JsonValue::String(s)) => SourceValue::Timestamp(parse_rfc3339(&s)?),
+        ("timestampValue", JsonValue::Object(timestamp)) => {
+            let seconds = json_i64(timestamp.get("seconds"))?;
+            let nanos = json_i64(timestamp.get("nanos"))?;
+            SourceValue::Timestamp(seconds as f64 * 1000. + nanos as f64 / 1_000_000.)
+        },
+        ("stringValue", JsonValue::String(s)) => SourceValue::String(s),
+        ("bytesValue", JsonValue::String(s)) => {
+            SourceValue::Bytes(base64::decode(&s).context("Invalid base64 in bytesValue")?)
+        },
+        ("referenceValue", JsonValue::String(s)) => SourceValue::Reference(s),
+        ("geoPointValue", JsonValue::Object(point)) => SourceValue::GeoPoint {
+            latitude: point
+                .get("latitude")
+                .and_then(JsonValue::as_f64)
+                .unwrap_or(0.),
+            longitude: point
+                .get("longitude")
+                .and_then(JsonValue::as_f64)
+                .unwrap_or(0.),
+        },
+        ("arrayValue", JsonValue::Object(mut array)) => match array.remove("values") {
+            Some(JsonValue::Array(values)) => SourceValue::Array(
+                values
+                    .into_iter()
+                    .map(parse_firestore_value)
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            Some(_) => anyhow::bail!("arrayValue values must be an array"),
+            None => SourceValue::Array(vec![]),
+        },
+        ("mapValue", JsonValue::Object(mut map)) => match map.remove("fields") {
+            Some(JsonValue::Object(fields)) => SourceValue::Object(parse_firestore_fields(fields)?),
+            Some(_) => anyhow::bail!("mapValue fields must be an object"),
+            None => SourceValue::Object(vec![]),
+        },
+        (kind, inner) => anyhow::bail!("Unsupported Firestore value {kind}: {inner}"),
+    };
+    Ok(parsed)
+}
+
+fn json_i64(value: Option<&JsonValue>) -> anyhow::Result<i64> {
+    match value {
+        None => Ok(0),
+        Some(JsonValue::Number(n)) => n.as_i64().with_context(|| format!("{n} isn't an integer")),
+        Some(JsonValue::String(s)) => s.parse().with_context(|| format!("{s:?} isn't an integer")),
+        Some(value) => anyhow::bail!("{value} isn't an integer"),
+    }
+}
+
+fn parse_rfc3339(s: &str) -> anyhow::Result<f64> {
+    let timestamp =
+        DateTime::parse_from_rfc3339(s).with_context(|| format!("{s:?} isn't a timestamp"))?;
+    Ok(timestamp.timestamp_micros() as f64 / 1000.)
+}
+
+/// Parses MongoDB extended JSON, canonical or relaxed, as written by
+/// `mongoexport`: either one document per line or, with `--jsonArray`, a
+/// single array of documents.
+#[try_stream(ok = SourceDocument, error = anyhow::Error)]
+pub async fn parse_mongo_json_documents<R: AsyncRead + Unpin>(reader: R) {
+    let mut reader = BufReader::new(reader);
+    let is_array = reader
+        .fill_buf()
+        .await?
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        == Some(&b'[');
+    if is_array {
+        let mut buf = Vec::new();
+        let mut truncated_reader = reader.take((*TRANSACTION_MAX_USER_WRITE_SIZE_BYTES as u64) + 1);
+        truncated_reader.read_to_end(&mut buf).await?;
+        if buf.len() > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES {
+            anyhow::bail!(ImportError::JsonArrayTooLarge(buf.len()));
+        }
+        let v: JsonValue = serde_json::from_slice(&buf).map_err(ImportError::NotJson)?;
+        let JsonValue::Array(documents) = v else {
+            anyhow::bail!(ImportError::NotJsonArray);
+        };
+        for (i, document) in documents.into_iter().enumerate() {
+            yield parse_mongo_json_document(document)
+                .map_err(|e| ImportError::InvalidSourceDocument(i + 1, e))?;
+        }
+    } else {
+        let mut line = String::new();
+        let mut document_number = 0;
+        while reader
+            .read_line(&mut line)
+            .await
+            .map_err(ImportError::NotUtf8)?
+            > 0
+        {
+            if !line.trim().is_empty() {
+                document_number += 1;
+                let document = serde_json::from_str(&line)
+                    .map_err(|e| ImportError::JsonInvalidRow(document_number, e))?;
+                yield parse_mongo_json_document(document)
+                    .map_err(|e| ImportError::InvalidSourceDocument(document_number, e))?;
+            }
+            line.clear();
+        }
+    }
+}
+
+fn parse_mongo_json_document(document: JsonValue) -> anyhow::Result<SourceDocument> {
+    let SourceValue::Object(fields) = parse_mongo_json_value(document)? else {
+        anyhow::bail!("MongoDB documents must be objects");
+    };
+    mongo_document(fields)
+}
+
+/// Splits `_id` out of a MongoDB document's fields.
+fn mongo_document(mut fields: Vec<(String, SourceValue)>) -> anyhow::Result<SourceDocument> {
+    let id = match fields.iter().position(|(name, _)| name == "_id") {
+        Some(i) => Some(match fields.remove(i).1 {
+            SourceValue::ObjectId(s) | SourceValue::String(s) => s,
+            SourceValue::Int64(i) => i.to_string(),
+            SourceValue::Float64(f) => f.to_string(),
+            id => anyhow::bail!("Unsupported _id type {}", id.type_name()),
+        }),
+        None => None,
+    };
+    Ok(SourceDocument { id, fields })
+}
+
+fn parse_mongo_json_value(value: JsonValue) -> anyhow::Result<SourceValue> {
+    let parsed = match value {
+        JsonValue::Null => SourceValue::Null,
+        JsonValue::Bool(b) => SourceValue::Boolean(b),
+        // Relaxed extended JSON writes int32s and doubles as plain numbers.
+        JsonValue::Number(n) => SourceValue::Float64(n.as_f64().context("Invalid number")?),
+        JsonValue::String(s) => SourceValue::String(s),
+        JsonValue::Array(values) => SourceValue::Array(
+            values
+                .into_iter()
+                .map(parse_mongo_json_value)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        JsonValue::Object(object) => {
+            let is_wrapper = object.len() <= 2
+                && object
+                    .keys()
+                    .next()
+                    .is_some_and(|key| MONGO_JSON_WRAPPERS.contains(&key.as_str()));
+            if is_wrapper {
+                parse_mongo_json_wrapper(object)?
+            } else {
+                SourceValue::Object(
+                    object
+                        .into_iter()
+                        .map(|(name, value)| Ok((name, parse_mongo_json_value(value)?)))
+                        .collect::<anyhow::Result<_>>()?,
+                )
+            }
+        },
+    };
+    Ok(parsed)
+}
+
+/// Keys of the `{"$type": ...}` objects extended JSON uses for types JSON
+/// doesn't have.
+const MONGO_JSON_WRAPPERS: &[&str] = &[
+    "$oid",
+    "$numberLong",
+    "$numberInt",
+    "$numberDouble",
+    "$numberDecimal",
+    "$date",
+    "$timestamp",
+    "$binary",
+    "$uuid",
+    "$symbol",
+    "$code",
+    "$regularExpression",
+    "$undefined",
+    "$minKey",
+    "$maxKey",
+    "$dbPointer",
+];
+
+fn parse_mongo_json_wrapper(object: JsonMap<String, JsonValue>) -> anyhow::Result<SourceValue> {
+    let Some((key, value)) = object.iter().next() else {
+        anyhow::bail!("Empty extended JSON wrapper");
+    };
+    let string_value = || {
+        value
+            .as_str()
+            .map(str::to_string)
+            .with_context(|| format!("{key} must be a string"))
+    };
+    let parsed = match key.as_str() {
+        "$oid" => SourceValue::ObjectId(string_value()?),
+        "$numberLong" => {
+            let s = string_value()?;
+            SourceValue::Int64(s.parse().with_context(|| format!("{s:?} isn't an int64"))?)
+        },
+        "$numberInt" | "$numberDouble" => SourceValue::Float64(parse_float(&string_value()?)?),
+        "$numberDecimal" => SourceValue::Decimal(string_value()?),
+        "$date" => SourceValue::Timestamp(match value {
+            JsonValue::String(s) => parse_rfc3339(s)?,
+            JsonValue::Number(n) => n.as_f64().context("Invalid $date")?,
+            JsonValue::Object(date) => {
+                let ms = date.get("$numberLong").and_then(JsonValue::as_str);
+                let ms = ms.context("$date must be a string, number or $numberLong")?;
+                ms.parse::<i64>()
+                    .with_context(|| format!("{ms:?} isn't an int64"))? as f64
+            },
+            _ => anyhow::bail!("Invalid $date {value}"),
+        }),
+        "$timestamp" => {
+            let seconds = value.get("t").and_then(JsonValue::as_u64);
+            SourceValue::Timestamp(seconds.context("$timestamp must have t")? as f64 * 1000.)
+        },
+        "$binary" => {
+            // Canonical: {"$binary": {"base64": ..., "subType": ...}}.
+            // Legacy: {"$binary": ..., "$type": ...}.
+            let encoded = match value {
+                JsonValue::Object(binary) => binary.get("base64").and_then(JsonValue::as_str),
+                JsonValue::String(s) => Some(s.as_str()),
+                _ => None,
+            };
+            let encoded = encoded.context("Invalid $binary")?;
+            SourceValue::Bytes(base64::decode(encoded).context("Invalid base64 in $binary")?)
+        },
+        "$uuid" | "$symbol" | "$code" => SourceValue::String(string_value()?),
+        "$regularExpression" => {
+            let pattern = value.get("pattern").and_then(JsonValue::as_str);
+            let options = value.get("options").and_then(JsonValue::as_str);
+            let pattern = pattern.context("$regularExpression must have a pattern")?;
+            SourceValue::String(format!("/{pattern}/{}", options.unwrap_or_default()))
+        },
+        "$undefined" => SourceValue::Null,
+        _ => anyhow::bail!("Unsupported extended JSON type {key}"),
+    };
+    Ok(parsed)
+}
+
+/// Parses a `mongodump` BSON file, which is BSON documents back to back.
+#[try_stream(ok = SourceDocument, error = anyhow::Error)]
+pub async fn parse_bson_documents<R: AsyncRead + Unpin>(reader: R) {
+    let mut reader = BufReader::new(reader);
+    let mut document_number = 0;
+    loop {
+        if reader.fill_buf().await?.is_empty() {
+            break;
+        }
+        document_number += 1;
+        let mut length = [0; 4];
+        reader.read_exact(&mut length).await?;
+        let length = i32::from_le_bytes(length);
+        if length < 5 {
+            anyhow::bail!(ImportError::InvalidSourceDocument(
+                document_number,
+                anyhow::anyhow!("Invalid BSON document length {length}")
+            ));
+        }
+        if length as usize > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES {
+            anyhow::bail!(ImportError::SourceDocumentTooLarge(
+                document_number,
+                length as usize
+            ));
+        }
+        let mut document = vec![0; length as usize];
+        document[..4].copy_from_slice(&length.to_le_bytes());
+        reader.read_exact(&mut document[4..]).await?;
+        let fields = BsonReader::new(&document)
+            .read_document()
+            .and_then(mongo_document)
+            .map_err(|e| ImportError::InvalidSourceDocument(document_number, e))?;
+        yield fields;
+    }
+}
+
+struct BsonReader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> BsonReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn take(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        anyhow::ensure!(n <= self.buf.len(), "BSON document is truncated");
+        let (taken, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Ok(taken)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn read_i32(&mut self) -> anyhow::Result<i32> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_i64(&mut self) -> anyhow::Result<i64> {
+        Ok(i64::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_length(&mut self) -> anyhow::Result<usize> {
+        let length = self.read_i32()?;
+        usize::try_from(length).with_context(|| format!("Invalid BSON length {length}"))
+    }
+
+    fn read_cstring(&mut self) -> anyhow::Result<String> {
+        let end = self
+            .buf
+            .iter()
+            .position(|b| *b == 0)
+            .context("BSON cstring is missing its terminator")?;
+        let s = std::str::from_utf8(self.take(end)?)?.to_string();
+        self.take(1)?;
+        Ok(s)
+    }
+
+    fn read_string(&mut self) -> anyhow::Result<String> {
+        let length = self.read_length()?;
+        anyhow::ensure!(length > 0, "Invalid BSON string length");
+        let bytes = self.take(length)?;
+        Ok(std::str::from_utf8(&bytes[..length - 1])?.to_string())
+    }
+
+    fn read_document(&mut self) -> anyhow::Result<Vec<(String, SourceValue)>> {
+        let length = self.read_length()?;
+        anyhow::ensure!(length >= 5, "Invalid BSON document length {length}");
+        let mut document = BsonReader::new(self.take(length - 4)?);
+        let mut fields = vec![];
+        loop {
+            let element_type = document.read_array::<1>()?[0];
+            if element_type == 0 {
+                break;
+            }
+            let name = document.read_cstring()?;
+            let value = document
+                .read_value(element_type)
+                .with_context(|| format!("Invalid value for field {name:?}"))?;
+            fields.push((name, value));
+        }
+        anyhow::ensure!(document.buf.is_empty(), "BSON document has trailing bytes");
+        Ok(fields)
+    }
+
+    fn read_value(&mut self, element_type: u8) -> anyhow::Result<SourceValue> {
+        let value = match element_type {
+            0x01 => SourceValue::Float64(f64::from_le_bytes(self.read_array()?)),
+            0x02 | 0x0D | 0x0E => SourceValue::String(self.read_string()?),
+            0x03 => SourceValue::Object(self.read_document()?),
+            // Arrays are documents with keys "0", "1", ...
+            0x04 => SourceValue::Array(
+                self.read_document()?
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .collect(),
+            ),
+            0x05 => {
+                let length = self.read_length()?;
+                let subtype = self.read_array::<1>()?[0];
+                let mut bytes = self.take(length)?;
+                // The old binary subtype repeats the length inside the data.
+                if subtype == 0x02 && bytes.len() >= 4 {
+                    bytes = &bytes[4..];
+                }
+                SourceValue::Bytes(bytes.to_vec())
+            },
+            0x06 | 0x0A => SourceValue::Null,
+            0x07 => SourceValue::ObjectId(hex::encode(self.take(12)?)),
+            0x08 => SourceValue::Boolean(self.read_array::<1>()?[0] != 0),
+            0x09 => SourceValue::Timestamp(self.read_i64()? as f64),
+            0x0B => {
+                let pattern = self.read_cstring()?;
+                let options = self.read_cstring()?;
+                SourceValue::String(format!("/{pattern}/{options}"))
+            },
+            0x10 => SourceValue::Float64(self.read_i32()? as f64),
+            // Internal replication timestamps: seconds in the high 32 bits.
+            0x11 => SourceValue::Timestamp((self.read_i64()? as u64 >> 32) as f64 * 1000.),
+            0x12 => SourceValue::Int64(self.read_i64()?),
+            0x13 => SourceValue::Decimal(decimal128_to_string(u128::from_le_bytes(
+                self.read_array()?,
+            ))),
+            element_type => anyhow::bail!("Unsupported BSON type {element_type:#04x}"),
+        };
+        Ok(value)
+    }
+}
+
+/// Formats an IEEE 754 decimal128 in its binary integer decimal encoding.
+fn decimal128_to_string(bits: u128) -> String {
+    let sign = if bits >> 127 == 1 { "-" } else { "" };
+    match (bits >> 122) & 0x1f {
+        0x1f => return "NaN".to_string(),
+        0x1e => return format!("{sign}Infinity"),
+        _ => {},
+    }
+    let (biased_exponent, coefficient) = if (bits >> 125) & 0b11 == 0b11 {
+        // This form has a coefficient of at least 2^113, more than the 34
+        // digits a decimal128 holds, so it's a non-canonical zero.
+        ((bits >> 111) & 0x3fff, 0)
+    } else {
+        ((bits >> 113) & 0x3fff, bits & ((1 << 113) - 1))
+    };
+    let exponent = biased_exponent as i64 - 6176;
+    let digits = coefficient.to_string();
+    if exponent == 0 {
+        format!("{sign}{digits}")
+    } else if exponent < 0 && -exponent <= 34 {
+        let scale = (-exponent) as usize;
+        let digits = format!("{digits:0>width$}", width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        format!("{sign}{integer}.{fraction}")
+    } else {
+        format!("{sign}{digits}E{exponent:+}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use futures::TryStreamExt;
+    use model::snapshot_imports::types::{
+        FieldConversion,
+        FieldMappingRule,
+        ImportFieldMapping,
+    };
+    use serde_json::json;
+    use value::assert_obj;
+
+    use super::{
+        convert_document,
+        decimal128_to_string,
+        parse_bson_documents,
+        parse_firestore_documents,
+        parse_mongo_json_documents,
+        validate_documents,
+        SourceDocument,
+        SourceValue,
+    };
+
+    #[tokio::test]
+    async fn test_parse_firestore_bundle() -> anyhow::Result<()> {
+        let metadata = json!({"metadata": {"id": "bundle"}}).to_string();
+        let document = json!({"document": {
+            "name": "projects/p/databases/(default)/documents/users/alice",
+            "fields": {
+                "age": {"integerValue": "36"},
+                "joined": {"timestampValue": "2024-01-01T00:00:00.5Z"},
+                "manager": {"referenceValue": "projects/p/databases/(default)/documents/users/bob"},
+                "tags": {"arrayValue": {"values": [{"stringValue": "admin"}]}},
+            },
+        }})
+        .to_string();
+        let bundle = format!("{}{metadata}{}{document}", metadata.len(), document.len());
+        let documents: Vec<_> = parse_firestore_documents(bundle.as_bytes())
+            .try_collect()
+            .await?;
+        assert_eq!(
+            documents,
+            vec![SourceDocument {
+                id: Some("alice".to_string()),
+                fields: vec![
+                    ("age".to_string(), SourceValue::Int64(36)),
+                    ("joined".to_string(), SourceValue::Timestamp(1704067200500.)),
+                    (
+                        "manager".to_string(),
+                        SourceValue::Reference(
+                            "projects/p/databases/(default)/documents/users/bob".to_string()
+                        )
+                    ),
+                    (
+                        "tags".to_string(),
+                        SourceValue::Array(vec![SourceValue::String("admin".to_string())])
+                    ),
+                ],
+            }]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_convert_mongo_json_with_mapping() -> anyhow::Result<()> {
+        let lines = [
+            json!({
+                "_id": {"$oid": "65a1b2c3d4e5f60718293a4b"},
+                "count": {"$numberLong": "9007199254740993"},
+                "price": {"$numberDecimal": "19.99"},
+                "createdAt": {"$date": "2024-01-01T00:00:00Z"},
+                "legacy": true,
+            }),
+            json!({"_id": "second", "count": {"$numberLong": "2"}}),
+        ]
+        .map(|line| line.to_string())
+        .join("\n");
+        let mapping = ImportFieldMapping {
+            id_field: Some("mongoId".parse()?),
+            fields: [
+                (
+                    "price".to_string(),
+                    FieldMappingRule {
+                        rename: None,
+                        convert: Some(FieldConversion::String),
+                    },
+                ),
+                (
+                    "legacy".to_string(),
+                    FieldMappingRule {
+                        rename: None,
+                        convert: Some(FieldConversion::Skip),
+                    },
+                ),
+            ]
+            .into(),
+        };
+        let documents: Vec<_> = parse_mongo_json_documents(lines.as_bytes())
+            .try_collect()
+            .await?;
+        let objects = documents
+            .into_iter()
+            .map(|document| convert_document(document, &mapping))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(
+            objects,
+            vec![
+                assert_obj!(
+                    "mongoId" => "65a1b2c3d4e5f60718293a4b",
+                    "count" => 9007199254740993i64,
+                    "price" => "19.99",
+                    "createdAt" => 1704067200000.,
+                ),
+                assert_obj!("mongoId" => "second", "count" => 2i64),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_bson() -> anyhow::Result<()> {
+        // {"_id": ObjectId, "n": int64(7), "ok": true}
+        let mut body = vec![];
+        body.push(0x07);
+        body.extend_from_slice(b"_id\0");
+        body.extend_from_slice(&[0xab; 12]);
+        body.push(0x12);
+        body.extend_from_slice(b"n\0");
+        body.extend_from_slice(&7i64.to_le_bytes());
+        body.push(0x08);
+        body.extend_from_slice(b"ok\0");
+        body.push(1);
+        body.push(0);
+        let mut document = ((body.len() + 4) as i32).to_le_bytes().to_vec();
+        document.extend(body);
+        let dump = [document.clone(), document].concat();
+        let documents: Vec<_> = parse_bson_documents(&dump[..]).try_collect().await?;
+        assert_eq!(documents.len(), 2);
+        assert_eq!(
+            documents[0],
+            SourceDocument {
+                id: Some("ab".repeat(12)),
+                fields: vec![
+                    ("n".to_string(), SourceValue::Int64(7)),
+                    ("ok".to_string(), SourceValue::Boolean(true)),
+                ],
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decimal128_to_string() {
+        let decimal = |negative: bool, exponent: i64, coefficient: u128| {
+            ((negative as u128) << 127) | (((exponent + 6176) as u128) << 113) | coefficient
+        };
+        assert_eq!(decimal128_to_string(decimal(false, -2, 1999)), "19.99");
+        assert_eq!(decimal128_to_string(decimal(true, -3, 5)), "-0.005");
+        assert_eq!(decimal128_to_string(decimal(false, 0, 42)), "42");
+        assert_eq!(decimal128_to_string(decimal(false, 3, 1)), "1E+3");
+        assert_eq!(decimal128_to_string(0x7c << 120), "NaN");
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_invalid_documents() -> anyhow::Result<()> {
+        let lines = [
+            json!({"a": 1, "b": {"$binary": {"base64": "AQI=", "subType": "00"}}}),
+            json!({"a": {"$numberLong": "1"}, "b": 1, "$bad": 1}),
+            json!({"a": 2}),
+        ]
+        .map(|line| line.to_string())
+        .join("\n");
+        let report = validate_documents(
+            parse_mongo_json_documents(lines.as_bytes()),
+            &ImportFieldMapping::default(),
+        )
+        .await?;
+        assert_eq!(report.num_documents, 3);
+        assert_eq!(report.num_invalid_documents, 1);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.complete);
+        assert_eq!(
+            report.field_types["a"],
+            BTreeMap::from([("float64", 2), ("int64", 1)])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+use common::{
+    components::ComponentPath,
+    runtime::Runtime,
+    types::TableName,
+};
+use database::{
+    BootstrapComponentsModel,
+    TableModel,
+    Transaction,
+};
+use value::{
+    TableNamespace,
+    TableNumber,
+};
+
+/// A table from another deployment whose documents can't keep their IDs here,
+/// because a table that a clone wouldn't replace already has its number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableNumberConflict {
+    pub component_path: ComponentPath,
+    pub table_name: TableName,
+    pub table_number: TableNumber,
+    pub existing_table: TableName,
+}
+
+/// Checks whether a snapshot with the given tables, as listed in its `_tables`
+/// entries, can be cloned into this deployment with all document IDs intact.
+/// A clone replaces every user table, so only system tables can conflict.
+pub async fn table_number_conflicts_for_clone<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    source_tables: &BTreeMap<ComponentPath, Vec<(TableName, TableNumber)>>,
+) -> anyhow::Result<Vec<TableNumberConflict>> {
+    let mut tables_affected: BTreeSet<(TableNamespace, TableName)> = tx
+        .table_mapping()
+        .iter_active_user_tables()
+        .map(|(_, namespace, _, table_name)| (namespace, table_name.clone()))
+        .collect();
+    let mut source_namespaces = Vec::new();
+    for (component_path, tables) in source_tables {
+        // Components that don't exist yet have no tables to conflict with.
+        let Some((_, component_id)) =
+            BootstrapComponentsModel::new(tx).component_path_to_ids(component_path)?
+        else {
+            continue;
+        };
+        let namespace = TableNamespace::from(component_id);
+        tables_affected.extend(
+            tables
+                .iter()
+                .map(|(table_name, _)| (namespace, table_name.clone())),
+        );
+        source_namespaces.push((component_path, namespace, tables));
+    }
+
+    let mut conflicts = Vec::new();
+    for (component_path, namespace, tables) in source_namespaces {
+        for (table_name, table_number) in tables {
+            if let Some(existing_table) = TableModel::new(tx).table_number_conflict(
+                namespace,
+                table_name,
+                *table_number,
+                &tables_affected,
+            ) {
+                conflicts.push(TableNumberConflict {
+                    component_path: component_path.clone(),
+                    table_name: table_name.clone(),
+                    table_number: *table_number,
+                    existing_table,
+                });
+            }
+        }
+    }
+    Ok(conflicts)
+}
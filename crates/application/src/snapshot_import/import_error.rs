@@ -60,8 +60,20 @@ pub enum ImportError {
 
     #[error("Not valid JSON: {0}")]
     NotJson(serde_json::Error),
+
+    #[error("Document {0} couldn't be parsed: {1:#}")]
+    InvalidSourceDocument(usize, anyhow::Error),
+
+    #[error("Document {0} is too large ({1} bytes > maximum {})", *IMPORT_SIZE_LIMIT)]
+    SourceDocumentTooLarge(usize, usize),
 }
 
+/// Returned when the worker notices an in-progress import was paused or
+/// canceled, so it stops without failing the import.
+#[derive(Debug, thiserror::Error)]
+#[error("Import was paused or canceled")]
+pub struct ImportInterrupted;
+
 impl ImportError {
     pub fn error_metadata(&self) -> ErrorMetadata {
         match self {
@@ -134,6 +134,11 @@ pub async fn import_storage_table<RT: Runtime>(
             .map(CreationTime::try_from)
             .transpose()
             .map_err(|e| ImportError::InvalidConvexValue(lineno, e))?;
+        if let Some(encryption) = &metadata.encryption {
+            encryption
+                .validate()
+                .map_err(|e| ImportError::InvalidConvexValue(lineno, e))?;
+        }
 
         storage_metadata.insert(
             id,
@@ -143,6 +148,7 @@ pub async fn import_storage_table<RT: Runtime>(
                 sha256,
                 storage_id,
                 creation_time,
+                metadata.encryption,
             ),
         );
     }
@@ -153,7 +159,7 @@ pub async fn import_storage_table<RT: Runtime>(
         // The or_default means a storage file with a valid id will be imported
         // even if it has been explicitly removed from _storage/documents.jsonl,
         // to be robust to manual modifications.
-        let (content_length, content_type, expected_sha256, storage_id, creation_time) =
+        let (content_length, content_type, expected_sha256, storage_id, creation_time, encryption) =
             storage_metadata.remove(&id).unwrap_or_default();
         let file_chunks = objects
             .as_mut()
@@ -175,6 +181,7 @@ pub async fn import_storage_table<RT: Runtime>(
         if let Some(storage_id) = storage_id {
             entry.storage_id = storage_id;
         }
+        entry.encryption = encryption;
         if num_files < num_to_skip {
             num_files += 1;
             continue;
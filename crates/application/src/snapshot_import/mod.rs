@@ -30,10 +30,15 @@ use common::{
     ext::TryPeekableExt,
     knobs::{
         MAX_IMPORT_AGE,
+        SNAPSHOT_IMPORT_ROWS_PER_SECOND,
         TRANSACTION_MAX_NUM_USER_WRITES,
         TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
     },
-    runtime::Runtime,
+    runtime::{
+        new_rate_limiter,
+        RateLimiter,
+        Runtime,
+    },
     types::{
         FullyQualifiedObjectKey,
         MemberId,
@@ -67,6 +72,7 @@ use futures::{
     StreamExt,
     TryStreamExt,
 };
+use governor::Quota;
 use keybroker::Identity;
 use model::{
     deployment_audit_log::{
@@ -77,12 +83,14 @@ use model::{
         FILE_STORAGE_TABLE,
         FILE_STORAGE_VIRTUAL_TABLE,
     },
+    preview_deployments::PreviewDeploymentsModel,
     snapshot_imports::{
         types::{
             ImportFormat,
             ImportMode,
             ImportRequestor,
             ImportState,
+            ImportUpsertStats,
             SnapshotImport,
         },
         SnapshotImportModel,
@@ -112,7 +120,9 @@ use value::{
     id_v6::DeveloperDocumentId,
     ConvexObject,
     ConvexValue,
+    FieldName,
     IdentifierFieldName,
+    Namespace,
     ResolvedDocumentId,
     Size,
     TableMapping,
@@ -126,9 +136,17 @@ use crate::{
     snapshot_import::{
         audit_log::make_audit_log_event,
         confirmation::info_message_for_import,
+        external_formats::{
+            parse_bson_documents,
+            parse_firestore_documents,
+            parse_mongo_json_documents,
+            validate_documents,
+        },
+        id_preservation::table_number_conflicts_for_clone,
         import_error::{
             wrap_import_err,
             ImportError,
+            ImportInterrupted,
         },
         import_file_storage::import_storage_table,
         metrics::log_snapshot_import_age,
@@ -140,18 +158,25 @@ use crate::{
         progress::{
             add_checkpoint_message,
             best_effort_update_progress_message,
+            check_import_interrupted,
         },
         schema_constraints::{
             schemas_for_import,
             ImportSchemaConstraints,
             SchemasForImport,
         },
+        upsert::{
+            upsert_import_objects,
+            upsert_index_for_table,
+        },
     },
     Application,
 };
 
 mod audit_log;
 mod confirmation;
+mod external_formats;
+mod id_preservation;
 mod import_error;
 mod import_file_storage;
 mod metrics;
@@ -162,8 +187,11 @@ mod schema_constraints;
 mod table_change;
 #[cfg(test)]
 mod tests;
+mod upsert;
 mod worker;
 
+pub use external_formats::ImportValidationReport;
+pub use id_preservation::TableNumberConflict;
 pub use worker::SnapshotImportWorker;
 
 struct SnapshotImportExecutor<RT: Runtime> {
@@ -267,6 +295,11 @@ impl<RT: Runtime> SnapshotImportExecutor<RT> {
                     )
                     .await?;
             },
+            Err(e) if e.is::<ImportInterrupted>() => {
+                // Paused imports are picked up again once they are resumed, and
+                // canceled imports are already marked as failed.
+                tracing::info!("Snapshot import {import_id} paused or canceled");
+            },
             Err(e) => {
                 let mut e = wrap_import_err(e);
                 if e.is_bad_request() {
@@ -335,6 +368,7 @@ impl<RT: Runtime> SnapshotImportExecutor<RT> {
             &self.file_storage,
             Identity::system(),
             snapshot_import.mode,
+            snapshot_import.upsert_key.as_ref(),
             objects,
             usage.clone(),
             Some(snapshot_import.id()),
@@ -371,6 +405,7 @@ impl<RT: Runtime> SnapshotImportExecutor<RT> {
             usage,
             audit_log_event,
             snapshot_import.requestor.clone(),
+            Some(snapshot_import.id()),
         )
         .await?;
 
@@ -433,10 +468,12 @@ pub async fn start_stored_import<RT: Runtime>(
     component_path: ComponentPath,
     object_key: ObjectKey,
     requestor: ImportRequestor,
+    upsert_key: Option<FieldName>,
 ) -> anyhow::Result<DeveloperDocumentId> {
     if !(identity.is_admin() || identity.is_system()) {
         anyhow::bail!(ImportError::Unauthorized);
     }
+    validate_import_mode(&format, mode, upsert_key.as_ref())?;
     let (_, id, _) = application
         .database
         .execute_with_overloaded_retries(
@@ -453,6 +490,7 @@ pub async fn start_stored_import<RT: Runtime>(
                             component_path.clone(),
                             object_key.clone(),
                             requestor.clone(),
+                            upsert_key.clone(),
                         )
                         .await
                 }
@@ -463,6 +501,48 @@ pub async fn start_stored_import<RT: Runtime>(
     Ok(id.into())
 }
 
+fn validate_import_mode(
+    format: &ImportFormat,
+    mode: ImportMode,
+    upsert_key: Option<&FieldName>,
+) -> anyhow::Result<()> {
+    if mode == ImportMode::Clone && *format != ImportFormat::Zip {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidImportFormat",
+            "Clone imports must be from a ZIP snapshot export"
+        ));
+    }
+    match (mode, upsert_key) {
+        (ImportMode::Upsert, Some(upsert_key)) => {
+            if upsert_key.is_system() {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidUpsertKey",
+                    format!("Upsert key {upsert_key} must not be a system field")
+                ));
+            }
+            if *format == ImportFormat::Zip {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidImportFormat",
+                    "Upsert imports must be into a single table, not from a ZIP file"
+                ));
+            }
+        },
+        (ImportMode::Upsert, None) => anyhow::bail!(ErrorMetadata::bad_request(
+            "MissingUpsertKey",
+            "Upsert imports require an upsert key"
+        )),
+        (_, Some(_)) => anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidUpsertKey",
+            format!(
+                "An upsert key can only be used in {} mode",
+                ImportMode::Upsert
+            )
+        )),
+        (_, None) => {},
+    }
+    Ok(())
+}
+
 pub async fn perform_import<RT: Runtime>(
     application: &Application<RT>,
     identity: Identity,
@@ -495,6 +575,70 @@ pub async fn perform_import<RT: Runtime>(
     Ok(())
 }
 
+pub async fn pause_import<RT: Runtime>(
+    application: &Application<RT>,
+    identity: Identity,
+    import_id: DeveloperDocumentId,
+) -> anyhow::Result<()> {
+    if !identity.is_admin() {
+        anyhow::bail!(ImportError::Unauthorized);
+    }
+    application
+        .database
+        .execute_with_overloaded_retries(
+            identity,
+            FunctionUsageTracker::new(),
+            "snapshot_import_pause",
+            |tx| {
+                async {
+                    let import_id = import_id.to_resolved(
+                        tx.table_mapping()
+                            .namespace(TableNamespace::Global)
+                            .number_to_tablet(),
+                    )?;
+                    let mut import_model = SnapshotImportModel::new(tx);
+                    import_model.pause_import(import_id).await?;
+                    Ok(())
+                }
+                .into()
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn resume_import<RT: Runtime>(
+    application: &Application<RT>,
+    identity: Identity,
+    import_id: DeveloperDocumentId,
+) -> anyhow::Result<()> {
+    if !identity.is_admin() {
+        anyhow::bail!(ImportError::Unauthorized);
+    }
+    application
+        .database
+        .execute_with_overloaded_retries(
+            identity,
+            FunctionUsageTracker::new(),
+            "snapshot_import_resume",
+            |tx| {
+                async {
+                    let import_id = import_id.to_resolved(
+                        tx.table_mapping()
+                            .namespace(TableNamespace::Global)
+                            .number_to_tablet(),
+                    )?;
+                    let mut import_model = SnapshotImportModel::new(tx);
+                    import_model.resume_import(import_id).await?;
+                    Ok(())
+                }
+                .into()
+            },
+        )
+        .await?;
+    Ok(())
+}
+
 pub async fn cancel_import<RT: Runtime>(
     application: &Application<RT>,
     identity: Identity,
@@ -549,7 +693,7 @@ async fn wait_for_import_worker<RT: Runtime>(
                     format!("import {import_id} not found"),
                 ))?;
         match &snapshot_import.state {
-            ImportState::Uploaded | ImportState::InProgress { .. } => {
+            ImportState::Uploaded | ImportState::InProgress { .. } | ImportState::Paused { .. } => {
                 let token = tx.into_token()?;
                 application.subscribe(token).await?;
             },
@@ -568,6 +712,7 @@ pub async fn do_import<RT: Runtime>(
     identity: Identity,
     format: ImportFormat,
     mode: ImportMode,
+    upsert_key: Option<FieldName>,
     component_path: ComponentPath,
     body_stream: BoxStream<'_, anyhow::Result<Bytes>>,
 ) -> anyhow::Result<u64> {
@@ -577,12 +722,61 @@ pub async fn do_import<RT: Runtime>(
         identity,
         format,
         mode,
+        upsert_key,
         component_path,
         object_key,
     )
     .await
 }
 
+/// Dry run of an import from another database's export: parses and converts
+/// every document without writing anything, so a field mapping can be checked
+/// before importing.
+pub async fn validate_import(
+    identity: Identity,
+    format: ImportFormat,
+    body_stream: BoxStream<'_, anyhow::Result<Bytes>>,
+) -> anyhow::Result<ImportValidationReport> {
+    if !(identity.is_admin() || identity.is_system()) {
+        anyhow::bail!(ImportError::Unauthorized);
+    }
+    let reader = body_stream.map_err(std::io::Error::other).into_async_read();
+    let report = match format {
+        ImportFormat::Firestore(_, mapping) => {
+            validate_documents(parse_firestore_documents(reader), &mapping).await
+        },
+        ImportFormat::MongoExtendedJson(_, mapping) => {
+            validate_documents(parse_mongo_json_documents(reader), &mapping).await
+        },
+        ImportFormat::MongoBson(_, mapping) => {
+            validate_documents(parse_bson_documents(reader), &mapping).await
+        },
+        ImportFormat::Csv(_)
+        | ImportFormat::JsonLines(_)
+        | ImportFormat::JsonArray(_)
+        | ImportFormat::Zip => anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidImportFormat",
+            "Only Firestore and MongoDB imports can be validated without importing",
+        )),
+    };
+    report.map_err(wrap_import_err)
+}
+
+/// Lists the tables of another deployment, by their `_tables` entries, that
+/// couldn't keep their document IDs if that deployment were cloned into this
+/// one, so a clone can be checked before exporting anything.
+pub async fn check_clone_table_numbers<RT: Runtime>(
+    application: &Application<RT>,
+    identity: Identity,
+    source_tables: BTreeMap<ComponentPath, Vec<(TableName, TableNumber)>>,
+) -> anyhow::Result<Vec<TableNumberConflict>> {
+    if !identity.is_admin() {
+        anyhow::bail!(ImportError::Unauthorized);
+    }
+    let mut tx = application.begin(identity).await?;
+    table_number_conflicts_for_clone(&mut tx, &source_tables).await
+}
+
 pub async fn do_import_from_fully_qualified_export<RT: Runtime>(
     application: &Application<RT>,
     identity: Identity,
@@ -600,6 +794,7 @@ pub async fn do_import_from_fully_qualified_export<RT: Runtime>(
         identity,
         format,
         mode,
+        None,
         component_path,
         import_object_key,
     )
@@ -611,6 +806,7 @@ async fn do_import_from_object_key<RT: Runtime>(
     identity: Identity,
     format: ImportFormat,
     mode: ImportMode,
+    upsert_key: Option<FieldName>,
     component_path: ComponentPath,
     object_key: ObjectKey,
 ) -> anyhow::Result<u64> {
@@ -622,12 +818,16 @@ async fn do_import_from_object_key<RT: Runtime>(
         component_path,
         object_key,
         ImportRequestor::SnapshotImport,
+        upsert_key,
     )
     .await?;
 
     let snapshot_import = wait_for_import_worker(application, identity.clone(), import_id).await?;
     match &snapshot_import.state {
-        ImportState::Uploaded | ImportState::InProgress { .. } | ImportState::Completed { .. } => {
+        ImportState::Uploaded
+        | ImportState::InProgress { .. }
+        | ImportState::Paused { .. }
+        | ImportState::Completed { .. } => {
             anyhow::bail!("should be WaitingForConfirmation, is {snapshot_import:?}")
         },
         ImportState::WaitingForConfirmation { .. } => {},
@@ -642,7 +842,8 @@ async fn do_import_from_object_key<RT: Runtime>(
     match &snapshot_import.state {
         ImportState::Uploaded
         | ImportState::WaitingForConfirmation { .. }
-        | ImportState::InProgress { .. } => {
+        | ImportState::InProgress { .. }
+        | ImportState::Paused { .. } => {
             anyhow::bail!("should be done, is {snapshot_import:?}")
         },
         ImportState::Completed {
@@ -681,6 +882,7 @@ pub async fn clear_tables<RT: Runtime>(
         &application.file_storage,
         identity.clone(),
         ImportMode::Replace,
+        None,
         objects,
         usage.clone(),
         None,
@@ -698,6 +900,7 @@ pub async fn clear_tables<RT: Runtime>(
         usage,
         DeploymentAuditLogEvent::ClearTables,
         ImportRequestor::SnapshotImport,
+        None,
     )
     .await?;
     Ok(documents_deleted)
@@ -708,6 +911,7 @@ async fn import_objects<RT: Runtime>(
     file_storage: &FileStorage<RT>,
     identity: Identity,
     mode: ImportMode,
+    upsert_key: Option<&FieldName>,
     objects: Peekable<BoxStream<'_, anyhow::Result<ImportUnit>>>,
     usage: FunctionUsageTracker,
     import_id: Option<ResolvedDocumentId>,
@@ -717,36 +921,49 @@ async fn import_objects<RT: Runtime>(
     let mut generated_schemas = BTreeMap::new();
     let mut total_num_documents = 0;
 
-    // In ReplaceAll mode, we want to delete all unaffected user tables
+    // In ReplaceAll and Clone modes, we want to delete all unaffected user tables
     // If there's a schema, then we want to clear it instead.
     let mut tx = database.begin(identity.clone()).await?;
     let to_delete = match mode {
-        ImportMode::Append | ImportMode::Replace | ImportMode::RequireEmpty => BTreeMap::new(),
-        ImportMode::ReplaceAll => tx
-            .table_mapping()
-            .iter_active_user_tables()
-            .map(|(tablet_id, namespace, table_number, table_name)| {
-                (tablet_id, (namespace, table_number, table_name.clone()))
-            })
-            .collect(),
+        ImportMode::Append
+        | ImportMode::Replace
+        | ImportMode::RequireEmpty
+        | ImportMode::Upsert => BTreeMap::new(),
+        ImportMode::ReplaceAll | ImportMode::Clone => {
+            // Preview deployments' tables aren't part of the deployment's data.
+            let preview_namespaces = PreviewDeploymentsModel::new(&mut tx).namespaces().await?;
+            tx.table_mapping()
+                .iter_active_user_tables()
+                .filter(|(_, namespace, ..)| !preview_namespaces.contains(namespace))
+                .map(|(tablet_id, namespace, table_number, table_name)| {
+                    (tablet_id, (namespace, table_number, table_name.clone()))
+                })
+                .collect()
+        },
     };
 
     let mut table_mapping_for_import = TableMappingForImport {
         table_mapping_in_import: TableMapping::new(),
         to_delete,
     };
+    let rate_limiter = new_rate_limiter(
+        database.runtime().clone(),
+        Quota::per_second(*SNAPSHOT_IMPORT_ROWS_PER_SECOND),
+    );
 
     while let Some(num_documents) = import_single_table(
         database,
         file_storage,
         &identity,
         mode,
+        upsert_key,
         objects.as_mut(),
         &mut generated_schemas,
         &mut table_mapping_for_import,
         usage.clone(),
         import_id,
         requestor.clone(),
+        &rate_limiter,
     )
     .await?
     {
@@ -852,6 +1069,7 @@ async fn finalize_import<RT: Runtime>(
     usage: FunctionUsageTracker,
     audit_log_event: DeploymentAuditLogEvent,
     requestor: ImportRequestor,
+    import_id: Option<ResolvedDocumentId>,
 ) -> anyhow::Result<(Timestamp, u64)> {
     let tables_affected = table_mapping_for_import.tables_affected();
 
@@ -871,6 +1089,14 @@ async fn finalize_import<RT: Runtime>(
             "snapshot_import_finalize",
             |tx| {
                 async {
+                    if let Some(import_id) = import_id {
+                        // Check in the same transaction that activates the tables, so
+                        // an import paused after its last batch isn't finalized.
+                        let state = SnapshotImportModel::new(tx)
+                            .must_get_state(import_id)
+                            .await?;
+                        anyhow::ensure!(state.is_in_progress(), ImportInterrupted);
+                    }
                     let mut documents_deleted = 0;
                     for tablet_id in table_mapping_for_import.to_delete.keys() {
                         let namespace = tx.table_mapping().tablet_namespace(*tablet_id)?;
@@ -1013,6 +1239,7 @@ async fn import_single_table<RT: Runtime>(
     file_storage: &FileStorage<RT>,
     identity: &Identity,
     mode: ImportMode,
+    upsert_key: Option<&FieldName>,
     mut objects: Pin<&mut Peekable<BoxStream<'_, anyhow::Result<ImportUnit>>>>,
     generated_schemas: &mut BTreeMap<
         (ComponentPath, TableName),
@@ -1022,6 +1249,7 @@ async fn import_single_table<RT: Runtime>(
     usage: FunctionUsageTracker,
     import_id: Option<ResolvedDocumentId>,
     requestor: ImportRequestor,
+    rate_limiter: &RateLimiter<RT>,
 ) -> anyhow::Result<Option<u64>> {
     while let Some(ImportUnit::GeneratedSchema(component_path, table_name, generated_schema)) =
         objects
@@ -1133,6 +1361,26 @@ async fn import_single_table<RT: Runtime>(
         return Ok(Some(0));
     }
 
+    let upsert = if mode == ImportMode::Upsert {
+        let upsert_key = upsert_key.context("Upsert imports require an upsert key")?;
+        let index_name =
+            upsert_index_for_table(database, identity, table_id, table_name, upsert_key).await?;
+        Some((upsert_key, index_name))
+    } else {
+        None
+    };
+    let mut upsert_stats = match import_id {
+        Some(import_id) if upsert.is_some() => {
+            let mut tx = database.begin(identity.clone()).await?;
+            SnapshotImportModel::new(&mut tx)
+                .get_table_checkpoint(import_id, component_path, table_name)
+                .await?
+                .and_then(|checkpoint| checkpoint.upsert_stats)
+                .unwrap_or_default()
+        },
+        _ => ImportUpsertStats::default(),
+    };
+
     let mut num_objects = 0;
 
     let mut tx = database.begin(identity.clone()).await?;
@@ -1141,9 +1389,9 @@ async fn import_single_table<RT: Runtime>(
     let mut objects_to_insert = vec![];
     let mut objects_to_insert_size = 0;
     // Peek so we don't pop ImportUnit::NewTable items.
-    while let Some(ImportUnit::Object(exported_value)) = objects
+    while let Some(unit) = objects
         .as_mut()
-        .try_next_if(|line| matches!(line, ImportUnit::Object(_)))
+        .try_next_if(|line| matches!(line, ImportUnit::Object(_) | ImportUnit::TypedObject(_)))
         .await?
     {
         if num_objects < num_to_skip {
@@ -1151,13 +1399,20 @@ async fn import_single_table<RT: Runtime>(
             continue;
         }
         let row_number = (num_objects + 1) as usize;
-        let convex_value = GeneratedSchema::<ProdConfigWithOptionalFields>::apply(
-            &mut generated_schema,
-            exported_value,
-        )
-        .map_err(|e| ImportError::InvalidConvexValue(row_number, e))?;
-        let ConvexValue::Object(convex_object) = convex_value else {
-            anyhow::bail!(ImportError::NotAnObject(row_number));
+        let convex_object = match unit {
+            ImportUnit::Object(exported_value) => {
+                let convex_value = GeneratedSchema::<ProdConfigWithOptionalFields>::apply(
+                    &mut generated_schema,
+                    exported_value,
+                )
+                .map_err(|e| ImportError::InvalidConvexValue(row_number, e))?;
+                let ConvexValue::Object(convex_object) = convex_value else {
+                    anyhow::bail!(ImportError::NotAnObject(row_number));
+                };
+                convex_object
+            },
+            ImportUnit::TypedObject(convex_object) => convex_object,
+            _ => anyhow::bail!("only objects should be imported into {table_name}"),
         };
         objects_to_insert_size += convex_object.size();
         objects_to_insert.push(convex_object);
@@ -1165,19 +1420,41 @@ async fn import_single_table<RT: Runtime>(
         if objects_to_insert_size > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES / 2
             || objects_to_insert.len() > *TRANSACTION_MAX_NUM_USER_WRITES / 2
         {
-            insert_import_objects(
-                database,
-                identity,
-                objects_to_insert,
-                table_name,
-                table_id,
-                &table_mapping_for_schema,
-                usage.clone(),
-            )
-            .await?;
+            if let Some((upsert_key, index_name)) = &upsert {
+                upsert_stats += upsert_import_objects(
+                    database,
+                    identity,
+                    objects_to_insert,
+                    component_path,
+                    table_name,
+                    table_id,
+                    index_name,
+                    upsert_key,
+                    &table_mapping_for_schema,
+                    usage.clone(),
+                    import_id,
+                    rate_limiter,
+                )
+                .await?;
+            } else {
+                insert_import_objects(
+                    database,
+                    identity,
+                    objects_to_insert,
+                    table_name,
+                    table_id,
+                    &table_mapping_for_schema,
+                    usage.clone(),
+                    rate_limiter,
+                )
+                .await?;
+            }
             objects_to_insert = Vec::new();
             objects_to_insert_size = 0;
             if let Some(import_id) = import_id {
+                // Everything written so far is checkpointed by the rows in the hidden
+                // table, so the import continues from here when resumed.
+                check_import_interrupted(database, identity, import_id).await?;
                 best_effort_update_progress_message(
                     database,
                     identity,
@@ -1196,16 +1473,44 @@ async fn import_single_table<RT: Runtime>(
         num_objects += 1;
     }
 
-    insert_import_objects(
-        database,
-        identity,
-        objects_to_insert,
-        table_name,
-        table_id,
-        &table_mapping_for_schema,
-        usage,
-    )
-    .await?;
+    let num_documents_message = if let Some((upsert_key, index_name)) = &upsert {
+        upsert_stats += upsert_import_objects(
+            database,
+            identity,
+            objects_to_insert,
+            component_path,
+            table_name,
+            table_id,
+            index_name,
+            upsert_key,
+            &table_mapping_for_schema,
+            usage,
+            import_id,
+            rate_limiter,
+        )
+        .await?;
+        format!(
+            "{} documents: {} inserted, {} updated, {} unchanged, {} conflicts",
+            num_objects.separate_with_commas(),
+            upsert_stats.num_inserted.separate_with_commas(),
+            upsert_stats.num_updated.separate_with_commas(),
+            upsert_stats.num_unchanged.separate_with_commas(),
+            upsert_stats.num_conflicts.separate_with_commas(),
+        )
+    } else {
+        insert_import_objects(
+            database,
+            identity,
+            objects_to_insert,
+            table_name,
+            table_id,
+            &table_mapping_for_schema,
+            usage,
+            rate_limiter,
+        )
+        .await?;
+        format!("{} documents", num_objects.separate_with_commas())
+    };
 
     if let Some(import_id) = import_id {
         add_checkpoint_message(
@@ -1213,9 +1518,8 @@ async fn import_single_table<RT: Runtime>(
             identity,
             import_id,
             format!(
-                "Imported \"{table_name}\"{} ({} documents)",
+                "Imported \"{table_name}\"{} ({num_documents_message})",
                 component_path.in_component_str(),
-                num_objects.separate_with_commas()
             ),
             component_path,
             table_name,
@@ -1235,10 +1539,19 @@ async fn insert_import_objects<RT: Runtime>(
     table_id: TabletIdAndTableNumber,
     table_mapping_for_schema: &TableMapping,
     usage: FunctionUsageTracker,
+    rate_limiter: &RateLimiter<RT>,
 ) -> anyhow::Result<()> {
     if objects_to_insert.is_empty() {
         return Ok(());
     }
+    for _ in 0..objects_to_insert.len() {
+        // Rate limit between transactions rather than within them, which would just
+        // increase contention.
+        while let Err(not_until) = rate_limiter.check() {
+            let delay = not_until.wait_time_from(database.runtime().monotonic_now().into());
+            database.runtime().wait(delay).await;
+        }
+    }
     let object_ids: Vec<_> = objects_to_insert
         .iter()
         .filter_map(|object| object.get(&**ID_FIELD))
@@ -1344,9 +1657,32 @@ async fn prepare_table_for_import<RT: Runtime>(
                     }
                     None
                 },
-                ImportMode::Replace | ImportMode::ReplaceAll => None,
+                ImportMode::Replace | ImportMode::ReplaceAll | ImportMode::Clone => None,
+                ImportMode::Upsert => {
+                    if table_name == &*FILE_STORAGE_TABLE {
+                        anyhow::bail!(ErrorMetadata::bad_request(
+                            "InvalidImportMode",
+                            format!("Files cannot be imported in {mode} mode")
+                        ));
+                    }
+                    let Some(table_id) = existing_active_table_id else {
+                        anyhow::bail!(ErrorMetadata::bad_request(
+                            "TableNotFound",
+                            format!(
+                                "Table \"{table_name}\" must exist to be imported in {mode} mode"
+                            )
+                        ));
+                    };
+                    Some(table_id)
+                },
+            };
+            // Upserted rows are checkpointed in the transactions that write
+            // them, so skip those written by a previous attempt.
+            let num_to_skip = match (mode, &existing_checkpoint) {
+                (ImportMode::Upsert, Some(checkpoint)) => checkpoint.num_rows_written as u64,
+                _ => 0,
             };
-            (tablet_id, 0)
+            (tablet_id, num_to_skip)
         },
     };
     drop(tx);
@@ -1403,7 +1739,7 @@ async fn prepare_table_for_import<RT: Runtime>(
 
 /// Waits for all indexes on a table to be backfilled, which may take a while
 /// for large tables. After the indexes are backfilled, enable them.
-async fn backfill_and_enable_indexes_on_table<RT: Runtime>(
+pub(crate) async fn backfill_and_enable_indexes_on_table<RT: Runtime>(
     database: &Database<RT>,
     identity: &Identity,
     tablet_id: TabletId,
@@ -1463,6 +1799,8 @@ async fn table_number_for_import(
             let id_v6 = DeveloperDocumentId::decode(id).ok()?;
             Some(id_v6.table())
         },
+        // Converted objects never carry Convex ids.
+        ImportUnit::TypedObject(..) => None,
         ImportUnit::NewTable(..) => None,
         ImportUnit::GeneratedSchema(..) => None,
         ImportUnit::StorageFileChunk(..) => None,
@@ -1495,6 +1833,7 @@ async fn remap_empty_string_by_schema<'a, RT: Runtime>(
         Ok(objects
             .map_ok(move |object| match object {
                 unit @ ImportUnit::NewTable(..)
+                | unit @ ImportUnit::TypedObject(..)
                 | unit @ ImportUnit::GeneratedSchema(..)
                 | unit @ ImportUnit::StorageFileChunk(..) => unit,
                 ImportUnit::Object(mut object) => ImportUnit::Object({
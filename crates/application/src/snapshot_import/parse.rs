@@ -60,14 +60,26 @@ use shape_inference::{
 use storage::StorageObjectReader;
 use value::{
     id_v6::DeveloperDocumentId,
+    ConvexObject,
     TableName,
 };
 
-use crate::snapshot_import::import_error::ImportError;
+use crate::snapshot_import::{
+    external_formats::{
+        convert_documents,
+        parse_bson_documents,
+        parse_firestore_documents,
+        parse_mongo_json_documents,
+    },
+    import_error::ImportError,
+};
 
 #[derive(Debug)]
 pub enum ImportUnit {
     Object(JsonValue),
+    /// An object that was already converted from another database's export
+    /// format, so it doesn't need a GeneratedSchema to decode.
+    TypedObject(ConvexObject),
     NewTable(ComponentPath, TableName),
     GeneratedSchema(
         ComponentPath,
@@ -125,8 +137,8 @@ fn map_csv_error(e: csv_async::Error) -> anyhow::Error {
 /// must be read out of order, e.g. because the _tables table must be imported
 /// first.
 /// Objects are yielded with the following guarantees:
-/// 1. When an Object is yielded, it is in the table corresponding to the most
-///    recently yielded NewTable.
+/// 1. When an Object or TypedObject is yielded, it is in the table
+///    corresponding to the most recently yielded NewTable.
 /// 2. When a StorageFileChunk is yielded, it is in the _storage table
 ///    corresponding to the most recently yielded NewTable.
 /// 3. All StorageFileChunks for a single file are yielded contiguously, in
@@ -215,6 +227,33 @@ pub async fn parse_objects<'a, Fut>(
                 yield ImportUnit::Object(value.clone());
             }
         },
+        ImportFormat::Firestore(table_name, mapping) => {
+            let reader = stream_body().await?;
+            yield ImportUnit::NewTable(component_path, table_name);
+            let objects = convert_documents(parse_firestore_documents(reader), mapping);
+            pin_mut!(objects);
+            while let Some(object) = objects.try_next().await? {
+                yield ImportUnit::TypedObject(object);
+            }
+        },
+        ImportFormat::MongoExtendedJson(table_name, mapping) => {
+            let reader = stream_body().await?;
+            yield ImportUnit::NewTable(component_path, table_name);
+            let objects = convert_documents(parse_mongo_json_documents(reader), mapping);
+            pin_mut!(objects);
+            while let Some(object) = objects.try_next().await? {
+                yield ImportUnit::TypedObject(object);
+            }
+        },
+        ImportFormat::MongoBson(table_name, mapping) => {
+            let reader = stream_body().await?;
+            yield ImportUnit::NewTable(component_path, table_name);
+            let objects = convert_documents(parse_bson_documents(reader), mapping);
+            pin_mut!(objects);
+            while let Some(object) = objects.try_next().await? {
+                yield ImportUnit::TypedObject(object);
+            }
+        },
         ImportFormat::Zip => {
             let base_component_path = component_path;
             let mut reader = stream_body().await?.compat();
@@ -5,6 +5,7 @@ use common::{
         definition::{
             ComponentDefinitionMetadata,
             ComponentDefinitionType,
+            ComponentLifecycle,
         },
         ComponentMetadata,
         ComponentState,
@@ -97,6 +98,7 @@ async fn create_unmounted_component<RT: Runtime>(
         child_components: vec![],
         http_mounts: btreemap! {},
         exports: btreemap! {},
+        lifecycle: ComponentLifecycle::default(),
     };
     let (definition_id, _diff) = ComponentDefinitionConfigModel::new(&mut tx)
         .create_component_definition(definition)
@@ -112,6 +114,7 @@ async fn create_unmounted_component<RT: Runtime>(
                 .developer_id(),
             name: component_name,
             args: btreemap! {},
+            grants: btreemap! {},
         },
         state: ComponentState::Unmounted,
     };
@@ -139,6 +142,7 @@ async fn create_root_component<RT: Runtime>(tx: &mut Transaction<RT>) -> anyhow:
         child_components: vec![],
         http_mounts: btreemap! {},
         exports: btreemap! {},
+        lifecycle: ComponentLifecycle::default(),
     };
 
     let (definition_id, _diff) = ComponentDefinitionConfigModel::new(tx)
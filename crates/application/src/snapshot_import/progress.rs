@@ -9,6 +9,8 @@ use model::snapshot_imports::SnapshotImportModel;
 use usage_tracking::FunctionUsageTracker;
 use value::ResolvedDocumentId;
 
+use crate::snapshot_import::import_error::ImportInterrupted;
+
 pub async fn best_effort_update_progress_message<RT: Runtime>(
     database: &Database<RT>,
     identity: &Identity,
@@ -70,3 +72,18 @@ pub async fn add_checkpoint_message<RT: Runtime>(
         .await?;
     Ok(())
 }
+
+/// Fails with [`ImportInterrupted`] if the import was paused or canceled
+/// since the worker started it.
+pub async fn check_import_interrupted<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    import_id: ResolvedDocumentId,
+) -> anyhow::Result<()> {
+    let mut tx = database.begin(identity.clone()).await?;
+    let state = SnapshotImportModel::new(&mut tx)
+        .must_get_state(import_id)
+        .await?;
+    anyhow::ensure!(state.is_in_progress(), ImportInterrupted);
+    Ok(())
+}
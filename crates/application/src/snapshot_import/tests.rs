@@ -66,9 +66,12 @@ use keybroker::{
     Identity,
 };
 use maplit::btreemap;
-use model::snapshot_imports::types::{
-    ImportRequestor,
-    ImportState,
+use model::snapshot_imports::{
+    types::{
+        ImportRequestor,
+        ImportState,
+    },
+    SnapshotImportModel,
 };
 use must_let::must_let;
 use runtime::testing::TestRuntime;
@@ -97,6 +100,7 @@ use value::{
 
 use crate::{
     snapshot_import::{
+        check_clone_table_numbers,
         do_import,
         do_import_from_object_key,
         import_objects,
@@ -104,10 +108,13 @@ use crate::{
             parse_objects,
             ImportUnit,
         },
+        pause_import,
+        resume_import,
         start_stored_import,
         wait_for_import_worker,
         ImportFormat,
         ImportMode,
+        TableNumberConflict,
     },
     test_helpers::ApplicationTestExt,
     Application,
@@ -153,6 +160,7 @@ async fn run_parse_objects<RT: Runtime>(
         .filter_map(|line| async move {
             match line {
                 Ok(super::ImportUnit::Object(object)) => Some(Ok(object)),
+                Ok(super::ImportUnit::TypedObject(..)) => None,
                 Ok(super::ImportUnit::NewTable(..)) => None,
                 Ok(super::ImportUnit::GeneratedSchema(..)) => None,
                 Ok(super::ImportUnit::StorageFileChunk(..)) => None,
@@ -440,6 +448,7 @@ a
         ComponentPath::root(),
         object_key,
         ImportRequestor::SnapshotImport,
+        None,
     )
     .await?;
 
@@ -525,6 +534,51 @@ a
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn import_paused_and_resumed(
+    rt: TestRuntime,
+    pause_controller: PauseController,
+) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let table_name = "table1";
+    let test_csv = r#"
+a
+"string"
+"#;
+
+    let hold_guard = pause_controller.hold("before_finalize_import");
+
+    let mut import_fut = run_csv_import(&app, table_name, test_csv).boxed();
+
+    select! {
+        r = import_fut.as_mut().fuse() => {
+            anyhow::bail!("import finished before pausing: {r:?}");
+        },
+        pause_guard = hold_guard.wait_for_blocked().fuse() => {
+            let pause_guard = pause_guard.unwrap();
+            let import_id = {
+                let mut tx = app.begin(new_admin_id()).await?;
+                let imports = SnapshotImportModel::new(&mut tx).list().await?;
+                imports[0].id()
+            };
+            pause_import(&app, new_admin_id(), import_id.into()).await?;
+            pause_guard.unpause();
+            resume_import(&app, new_admin_id(), import_id.into()).await?;
+        },
+    }
+    // The import waits while paused and finishes once resumed.
+    import_fut.await?;
+
+    let mut tx = app.begin(new_admin_id()).await?;
+    assert_eq!(
+        TableModel::new(&mut tx)
+            .must_count(TableNamespace::Global, &table_name.parse()?)
+            .await?,
+        1
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn import_would_break_foreign_key(rt: TestRuntime) -> anyhow::Result<()> {
     let app = Application::new_for_tests(&rt).await?;
@@ -674,6 +728,7 @@ _id,a
         new_admin_id(),
         ImportFormat::Csv(table_name2.clone()),
         ImportMode::ReplaceAll,
+        None,
         ComponentPath::root(),
         stream_from_str(&test_csv),
     )
@@ -750,6 +805,7 @@ _id,a
                 new_admin_id(),
                 ImportFormat::Csv(table_name2.clone()),
                 mode,
+                None,
                 ComponentPath::root(),
                 stream_from_str(&test_csv),
             )
@@ -1093,6 +1149,142 @@ a
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn import_upserts_by_field(rt: TestRuntime) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let table_name: TableName = "table1".parse()?;
+    let index_name = IndexName::new(table_name.clone(), IndexDescriptor::new("by_key")?)?;
+    {
+        let mut tx = app.begin(new_admin_id()).await?;
+        IndexModel::new(&mut tx)
+            .add_application_index(
+                TableNamespace::test_user(),
+                IndexMetadata::new_enabled(index_name, vec!["key".parse()?].try_into()?),
+            )
+            .await?;
+        app.commit_test(tx).await?;
+    }
+    do_import(
+        &app,
+        new_admin_id(),
+        ImportFormat::JsonLines(table_name.clone()),
+        ImportMode::Replace,
+        None,
+        ComponentPath::root(),
+        stream_from_str(
+            r#"{"key": "a", "n": 1}
+{"key": "b", "n": 2}"#,
+        ),
+    )
+    .await?;
+
+    let num_rows_written = do_import(
+        &app,
+        new_admin_id(),
+        ImportFormat::JsonLines(table_name.clone()),
+        ImportMode::Upsert,
+        Some("key".parse()?),
+        ComponentPath::root(),
+        stream_from_str(
+            r#"{"key": "a", "n": 1}
+{"key": "b", "n": 3}
+{"key": "c", "n": 4}"#,
+        ),
+    )
+    .await?;
+    assert_eq!(num_rows_written, 3);
+    let objects = load_fields_as_maps(&app, "table1", vec!["key", "n"]).await?;
+    assert_eq!(
+        objects,
+        vec![
+            btreemap!("key" => assert_val!("a"), "n" => assert_val!(1.)),
+            btreemap!("key" => assert_val!("b"), "n" => assert_val!(3.)),
+            btreemap!("key" => assert_val!("c"), "n" => assert_val!(4.)),
+        ]
+    );
+
+    // Upserting requires an index on the key.
+    let err = do_import(
+        &app,
+        new_admin_id(),
+        ImportFormat::JsonLines(table_name.clone()),
+        ImportMode::Upsert,
+        Some("n".parse()?),
+        ComponentPath::root(),
+        stream_from_str(r#"{"key": "d", "n": 5}"#),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.is_bad_request());
+    assert!(err.to_string().contains("requires an index"), "{err}");
+
+    // And an upsert key.
+    let err = do_import(
+        &app,
+        new_admin_id(),
+        ImportFormat::JsonLines(table_name.clone()),
+        ImportMode::Upsert,
+        None,
+        ComponentPath::root(),
+        stream_from_str(r#"{"key": "d", "n": 5}"#),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.is_bad_request());
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn clone_table_numbers_only_conflict_with_system_tables(
+    rt: TestRuntime,
+) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let table_name: TableName = "table1".parse()?;
+    let mut tx = app.begin(new_admin_id()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!())
+        .await?;
+    app.commit_test(tx).await?;
+
+    let mut tx = app.begin(new_admin_id()).await?;
+    let user_table_number = tx
+        .table_mapping()
+        .namespace(TableNamespace::test_user())
+        .id(&table_name)?
+        .table_number;
+    let (system_table_number, system_table_name) = tx
+        .table_mapping()
+        .iter()
+        .find(|(_, namespace, _, name)| {
+            *namespace == TableNamespace::test_user() && name.is_system()
+        })
+        .map(|(_, _, table_number, name)| (table_number, name.clone()))
+        .context("no system tables")?;
+
+    // A clone replaces every user table, so it can take over their numbers.
+    let conflicts = check_clone_table_numbers(
+        &app,
+        new_admin_id(),
+        btreemap! {
+            ComponentPath::root() => vec![
+                ("other".parse()?, user_table_number),
+                ("another".parse()?, system_table_number),
+            ],
+        },
+    )
+    .await?;
+    assert_eq!(
+        conflicts,
+        vec![TableNumberConflict {
+            component_path: ComponentPath::root(),
+            table_name: "another".parse()?,
+            table_number: system_table_number,
+            existing_table: system_table_name,
+        }]
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_import_counts_bandwidth(rt: TestRuntime) -> anyhow::Result<()> {
     let app = Application::new_for_tests(&rt).await?;
@@ -1130,6 +1322,7 @@ async fn test_import_counts_bandwidth(rt: TestRuntime) -> anyhow::Result<()> {
         &app.file_storage,
         identity,
         ImportMode::Replace,
+        None,
         objects,
         usage.clone(),
         None,
@@ -1158,6 +1351,7 @@ a,b
         new_admin_id(),
         ImportFormat::Csv(table_name.clone()),
         ImportMode::Replace,
+        None,
         component_path.clone(),
         stream_from_str(test_csv),
     )
@@ -1185,6 +1379,7 @@ a,b
         new_admin_id(),
         ImportFormat::Csv(table_name.clone()),
         ImportMode::Replace,
+        None,
         component_path.clone(),
         stream_from_str(test_csv),
     )
@@ -1272,6 +1467,7 @@ async fn run_csv_import(
         new_admin_id(),
         ImportFormat::Csv(table_name.parse()?),
         ImportMode::Replace,
+        None,
         ComponentPath::root(),
         stream_from_str(input),
     )
@@ -0,0 +1,144 @@
+use common::{
+    bootstrap_model::index::IndexConfig,
+    components::ComponentPath,
+    runtime::{
+        RateLimiter,
+        Runtime,
+    },
+    types::{
+        IndexName,
+        TableName,
+    },
+};
+use database::{
+    Database,
+    ImportFacingModel,
+    IndexModel,
+    UpsertByFieldOutcome,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use model::snapshot_imports::{
+    types::ImportUpsertStats,
+    SnapshotImportModel,
+};
+use usage_tracking::FunctionUsageTracker;
+use value::{
+    ConvexObject,
+    FieldName,
+    FieldPath,
+    IdentifierFieldName,
+    Namespace,
+    ResolvedDocumentId,
+    TableMapping,
+    TabletIdAndTableNumber,
+};
+
+/// Finds an enabled index on the table whose first field is the upsert key,
+/// so each imported document can be matched without scanning the table.
+pub async fn upsert_index_for_table<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    table_id: TabletIdAndTableNumber,
+    table_name: &TableName,
+    upsert_key: &FieldName,
+) -> anyhow::Result<IndexName> {
+    let invalid_key = || {
+        ErrorMetadata::bad_request(
+            "InvalidUpsertKey",
+            format!("Upsert key {upsert_key} must be a top-level, non-system field"),
+        )
+    };
+    anyhow::ensure!(!upsert_key.is_system(), invalid_key());
+    let key_path = FieldPath::new(vec![
+        IdentifierFieldName::try_from(upsert_key.clone()).map_err(|_| invalid_key())?
+    ])?;
+    let mut tx = database.begin(identity.clone()).await?;
+    let indexes = IndexModel::new(&mut tx)
+        .all_indexes_on_table(table_id.tablet_id)
+        .await?;
+    let index = indexes.into_iter().find(|index| {
+        let IndexConfig::Database {
+            developer_config, ..
+        } = &index.config
+        else {
+            return false;
+        };
+        index.config.is_enabled() && developer_config.fields.first() == Some(&key_path)
+    });
+    let Some(index) = index else {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "MissingUpsertIndex",
+            format!(
+                "Upserting into \"{table_name}\" requires an index on \"{table_name}\" whose \
+                 first field is {upsert_key}"
+            )
+        ));
+    };
+    IndexName::new(table_name.clone(), index.name.descriptor().clone())
+}
+
+/// Upserts a batch of objects in one transaction, adding the batch to the
+/// import's checkpoint in the same transaction.
+pub async fn upsert_import_objects<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    objects_to_upsert: Vec<ConvexObject>,
+    component_path: &ComponentPath,
+    table_name: &TableName,
+    table_id: TabletIdAndTableNumber,
+    index_name: &IndexName,
+    upsert_key: &FieldName,
+    table_mapping_for_schema: &TableMapping,
+    usage: FunctionUsageTracker,
+    import_id: Option<ResolvedDocumentId>,
+    rate_limiter: &RateLimiter<RT>,
+) -> anyhow::Result<ImportUpsertStats> {
+    if objects_to_upsert.is_empty() {
+        return Ok(ImportUpsertStats::default());
+    }
+    for _ in 0..objects_to_upsert.len() {
+        while let Err(not_until) = rate_limiter.check() {
+            let delay = not_until.wait_time_from(database.runtime().monotonic_now().into());
+            database.runtime().wait(delay).await;
+        }
+    }
+    let (_, stats, _) = database
+        .execute_with_overloaded_retries(
+            identity.clone(),
+            usage,
+            "snapshot_import_upsert_objects",
+            |tx| {
+                async {
+                    let mut stats = ImportUpsertStats::default();
+                    for object_to_upsert in objects_to_upsert.clone() {
+                        let outcome = ImportFacingModel::new(tx)
+                            .upsert_by_field(
+                                table_id,
+                                table_name,
+                                index_name.clone(),
+                                upsert_key,
+                                object_to_upsert,
+                                table_mapping_for_schema,
+                            )
+                            .await?;
+                        match outcome {
+                            UpsertByFieldOutcome::Inserted => stats.num_inserted += 1,
+                            UpsertByFieldOutcome::Updated => stats.num_updated += 1,
+                            UpsertByFieldOutcome::Unchanged => stats.num_unchanged += 1,
+                            UpsertByFieldOutcome::Conflict => stats.num_conflicts += 1,
+                        }
+                    }
+                    if let Some(import_id) = import_id {
+                        SnapshotImportModel::new(tx)
+                            .checkpoint_upsert_batch(import_id, component_path, table_name, stats)
+                            .await?;
+                    }
+                    Ok(stats)
+                }
+                .into()
+            },
+        )
+        .await?;
+    Ok(stats)
+}
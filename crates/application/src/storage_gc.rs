@@ -0,0 +1,246 @@
+//! Garbage collection for file storage. Objects can be left behind in storage
+//! without a `_storage` document referencing them, for example when the
+//! backend crashes between uploading a file and recording it, or when a file
+//! is deleted from `_storage`. A background worker periodically compares the
+//! objects in storage with the documents that reference them, reports the
+//! orphans it finds, and deletes them if `STORAGE_GC_DELETE_ORPHANS` is set.
+//!
+//! Objects written within `STORAGE_GC_HOLD_OFF` are never collected, since
+//! they may belong to an upload that hasn't been recorded yet.
+
+use std::{
+    collections::BTreeSet,
+    future::Future,
+    sync::Arc,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use common::{
+    backoff::Backoff,
+    document::ParsedDocument,
+    errors::report_error,
+    knobs::{
+        STORAGE_GC_DELETE_ORPHANS,
+        STORAGE_GC_HOLD_OFF,
+        STORAGE_GC_INTERVAL,
+    },
+    runtime::Runtime,
+    types::ObjectKey,
+};
+use database::Database;
+use futures::{
+    pin_mut,
+    TryStreamExt,
+};
+use keybroker::Identity;
+use model::{
+    exports::ExportsModel,
+    file_storage::{
+        types::FileStorageEntry,
+        FILE_STORAGE_TABLE,
+    },
+    image_transform_cache::{
+        types::ImageTransformCacheEntry,
+        IMAGE_TRANSFORM_CACHE_TABLE,
+    },
+    scheduled_jobs::{
+        types::ScheduledJob,
+        SCHEDULED_JOBS_TABLE,
+    },
+    snapshot_imports::{
+        types::ImportState,
+        SnapshotImportModel,
+    },
+    static_assets::{
+        types::StaticAsset,
+        STATIC_ASSETS_TABLE,
+    },
+};
+use storage::Storage;
+
+use crate::metrics::{
+    log_storage_gc_orphans,
+    log_worker_starting,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageGcReport {
+    /// Objects older than the hold-off window with no document referencing
+    /// them.
+    pub orphaned_objects: Vec<ObjectKey>,
+    pub orphaned_bytes: u64,
+    /// How many of the orphaned objects were deleted.
+    pub deleted_objects: usize,
+}
+
+pub struct StorageGcWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    files_storage: Arc<dyn Storage>,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> StorageGcWorker<RT> {
+    pub fn new(runtime: RT, database: Database<RT>, files_storage: Arc<dyn Storage>) -> Self {
+        Self {
+            runtime,
+            database,
+            files_storage,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        }
+    }
+
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        files_storage: Arc<dyn Storage>,
+    ) -> impl Future<Output = ()> + Send {
+        let mut worker = Self::new(runtime, database, files_storage);
+        async move {
+            if STORAGE_GC_INTERVAL.is_zero() {
+                tracing::info!("Storage GC worker is disabled");
+                return;
+            }
+            tracing::info!("Starting StorageGcWorker");
+            loop {
+                // Wait first so restarts don't immediately rescan storage.
+                worker.runtime.wait(*STORAGE_GC_INTERVAL).await;
+                let cutoff = worker.runtime.system_time() - *STORAGE_GC_HOLD_OFF;
+                match worker.run_once(cutoff, *STORAGE_GC_DELETE_ORPHANS).await {
+                    Ok(_) => worker.backoff.reset(),
+                    Err(e) => {
+                        report_error(&mut e.context("StorageGcWorker died")).await;
+                        let delay = worker.backoff.fail(&mut worker.runtime.rng());
+                        worker.runtime.wait(delay).await;
+                    },
+                }
+            }
+        }
+    }
+
+    /// Find objects last written before `cutoff` that no document references,
+    /// deleting them if `delete` is set. Returns `None` without looking at
+    /// storage while an import or export is running, since imports upload
+    /// files before their documents are visible and exports read files that
+    /// may have just been deleted from `_storage`.
+    pub async fn run_once(
+        &self,
+        cutoff: SystemTime,
+        delete: bool,
+    ) -> anyhow::Result<Option<StorageGcReport>> {
+        let _status = log_worker_starting("StorageGcWorker");
+        if self.import_or_export_running().await? {
+            tracing::info!("Skipping storage GC while an import or export is running");
+            return Ok(None);
+        }
+
+        // List storage before reading the references, so any object that's
+        // recorded by the time we read them is seen as referenced.
+        let candidates: Vec<_> = self
+            .files_storage
+            .list_objects()
+            .try_filter(|object| futures::future::ready(object.last_modified <= cutoff))
+            .try_collect()
+            .await?;
+        let referenced = self.referenced_keys().await?;
+
+        let mut report = StorageGcReport::default();
+        for object in candidates {
+            if referenced.contains(&object.key) {
+                continue;
+            }
+            report.orphaned_bytes += object.size;
+            if delete {
+                self.files_storage.delete_object(&object.key).await?;
+                report.deleted_objects += 1;
+            }
+            report.orphaned_objects.push(object.key);
+        }
+        if !report.orphaned_objects.is_empty() {
+            tracing::warn!(
+                "Found {} orphaned storage objects ({} bytes), deleted {}",
+                report.orphaned_objects.len(),
+                report.orphaned_bytes,
+                report.deleted_objects,
+            );
+        }
+        log_storage_gc_orphans(
+            report.orphaned_objects.len(),
+            report.orphaned_bytes,
+            report.deleted_objects,
+        );
+        Ok(Some(report))
+    }
+
+    async fn import_or_export_running(&self) -> anyhow::Result<bool> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let import_running = SnapshotImportModel::new(&mut tx)
+            .list()
+            .await?
+            .iter()
+            .any(|import| {
+                matches!(
+                    import.state,
+                    ImportState::InProgress { .. } | ImportState::Paused { .. }
+                )
+            });
+        let export_running = ExportsModel::new(&mut tx)
+            .latest_in_progress()
+            .await?
+            .is_some();
+        Ok(import_running || export_running)
+    }
+
+    /// Every object key referenced by `_storage` in any component, including
+    /// tables that are still hidden, by cached image transforms, by static
+    /// assets and by the offloaded arguments of scheduled jobs.
+    async fn referenced_keys(&self) -> anyhow::Result<BTreeSet<ObjectKey>> {
+        let snapshot_ts = self.database.now_ts_for_reads();
+        let snapshot = self.database.snapshot(snapshot_ts)?;
+        let mut referenced = BTreeSet::new();
+        for (tablet_id, _, _, table_name) in snapshot.table_mapping().iter() {
+            let is_file_storage = *table_name == *FILE_STORAGE_TABLE;
+            let is_scheduled_jobs = *table_name == *SCHEDULED_JOBS_TABLE;
+            let is_static_assets = *table_name == *STATIC_ASSETS_TABLE;
+            let is_image_transforms = *table_name == *IMAGE_TRANSFORM_CACHE_TABLE;
+            if !(is_file_storage || is_scheduled_jobs || is_static_assets || is_image_transforms) {
+                continue;
+            }
+            let by_id = snapshot.index_registry.must_get_by_id(tablet_id)?.id();
+            let stream = self
+                .database
+                .table_iterator(snapshot_ts, 1000)
+                .stream_documents_in_table(tablet_id, by_id, None);
+            pin_mut!(stream);
+            while let Some(document) = stream.try_next().await? {
+                let storage_key = if is_file_storage {
+                    ParsedDocument::<FileStorageEntry>::try_from(document.value)?
+                        .into_value()
+                        .storage_key
+                } else if is_scheduled_jobs {
+                    let job = ParsedDocument::<ScheduledJob>::try_from(document.value)?;
+                    let Some(storage_key) = job.into_value().args_storage_key else {
+                        continue;
+                    };
+                    storage_key
+                } else if is_static_assets {
+                    ParsedDocument::<StaticAsset>::try_from(document.value)?
+                        .into_value()
+                        .storage_key
+                } else {
+                    ParsedDocument::<ImageTransformCacheEntry>::try_from(document.value)?
+                        .into_value()
+                        .storage_key
+                };
+                referenced.insert(storage_key);
+            }
+        }
+        Ok(referenced)
+    }
+}
@@ -0,0 +1,326 @@
+//! Surrogate keys tag the responses a CDN in front of the deployment caches,
+//! so it can purge them once the data behind them changes. File responses are
+//! tagged with the file, and HTTP action responses with the tables their
+//! queries read plus a key of their own.
+//!
+//! When a [`CdnPurger`] is configured, the queries behind each HTTP action
+//! response are subscribed to the same way a client's queries are, and the
+//! response's key is purged as soon as one of them is invalidated. Served
+//! files are watched the same way, so deleting a file purges it.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    sync::{
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use common::{
+    execution_context::ExecutionId,
+    knobs::{
+        CDN_PURGE_MAX_WATCHES,
+        CDN_PURGE_WATCH_TTL,
+    },
+    runtime::Runtime,
+};
+use database::{
+    Database,
+    Token,
+};
+use errors::ErrorMetadata;
+use futures::{
+    future,
+    select_biased,
+    FutureExt,
+};
+use http::{
+    HeaderMap,
+    HeaderName,
+};
+use itertools::Itertools;
+use parking_lot::Mutex;
+use serde_json::json;
+use value::TabletId;
+
+#[allow(clippy::declare_interior_mutable_const)]
+pub const SURROGATE_KEY: HeaderName = HeaderName::from_static("surrogate-key");
+
+pub fn file_key(storage_id: &str) -> String {
+    format!("file:{storage_id}")
+}
+
+fn table_key(tablet_id: TabletId) -> String {
+    format!("table:{tablet_id}")
+}
+
+fn response_key(execution_id: &ExecutionId) -> String {
+    format!("response:{execution_id}")
+}
+
+/// Add keys to a response's `Surrogate-Key` header, keeping any keys the
+/// response already set.
+pub fn add_surrogate_keys(
+    headers: &mut HeaderMap,
+    keys: impl IntoIterator<Item = String>,
+) -> anyhow::Result<()> {
+    let mut all_keys: BTreeSet<String> = headers
+        .get_all(SURROGATE_KEY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split_whitespace().map(str::to_string))
+        .collect();
+    all_keys.extend(keys);
+    if !all_keys.is_empty() {
+        headers.insert(SURROGATE_KEY, all_keys.into_iter().join(" ").parse()?);
+    }
+    Ok(())
+}
+
+#[async_trait]
+pub trait CdnPurger: Send + Sync {
+    async fn purge(&self, keys: Vec<String>) -> anyhow::Result<()>;
+}
+
+/// POSTs `{"surrogateKeys": [...]}` to a purge endpoint, which can be a CDN's
+/// purge API or a service that forwards to one. The keys are also sent
+/// space-separated in a `Surrogate-Key` header, which is how Fastly's API
+/// takes them.
+pub struct HttpCdnPurger {
+    client: reqwest::Client,
+    url: String,
+    auth_token: Option<String>,
+}
+
+impl HttpCdnPurger {
+    /// Configured from `CDN_PURGE_URL` and optionally `CDN_PURGE_AUTH_TOKEN`,
+    /// which is sent as a bearer token.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            client: reqwest::Client::new(),
+            url: std::env::var("CDN_PURGE_URL").ok()?,
+            auth_token: std::env::var("CDN_PURGE_AUTH_TOKEN").ok(),
+        })
+    }
+}
+
+#[async_trait]
+impl CdnPurger for HttpCdnPurger {
+    async fn purge(&self, keys: Vec<String>) -> anyhow::Result<()> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(SURROGATE_KEY, keys.join(" "))
+            .json(&json!({ "surrogateKeys": keys }));
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("CDN purge endpoint returned {status}: {text}");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct SurrogateKeys<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    purger: Option<Arc<dyn CdnPurger>>,
+    // Tokens of the queries run by HTTP actions that haven't sent their
+    // response head yet, by execution.
+    pending_reads: Arc<Mutex<BTreeMap<String, Vec<Token>>>>,
+    // Files whose `_storage` document is being watched.
+    watched_files: Arc<Mutex<BTreeSet<String>>>,
+    num_watches: Arc<AtomicUsize>,
+}
+
+impl<RT: Runtime> SurrogateKeys<RT> {
+    pub fn new(runtime: RT, database: Database<RT>, purger: Option<Arc<dyn CdnPurger>>) -> Self {
+        Self {
+            runtime,
+            database,
+            purger,
+            pending_reads: Arc::new(Mutex::new(BTreeMap::new())),
+            watched_files: Arc::new(Mutex::new(BTreeSet::new())),
+            num_watches: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Start collecting the reads of the queries an HTTP action runs, until
+    /// the returned guard is dropped.
+    pub fn start_http_action(&self, execution_id: &ExecutionId) -> PendingReadsGuard {
+        let key = execution_id.to_string();
+        if self.purger.is_some() {
+            self.pending_reads.lock().insert(key.clone(), vec![]);
+        }
+        PendingReadsGuard {
+            pending_reads: self.pending_reads.clone(),
+            key,
+        }
+    }
+
+    pub fn record_query(&self, execution_id: &ExecutionId, token: Token) {
+        if let Some(tokens) = self.pending_reads.lock().get_mut(&execution_id.to_string()) {
+            tokens.push(token);
+        }
+    }
+
+    /// Tag an HTTP action's response with the tables its queries have read so
+    /// far and a key that's purged once any of those reads change.
+    pub fn tag_http_action_response(
+        &self,
+        execution_id: &ExecutionId,
+        headers: &mut HeaderMap,
+    ) -> anyhow::Result<()> {
+        let Some(tokens) = self.pending_reads.lock().remove(&execution_id.to_string()) else {
+            return Ok(());
+        };
+        if tokens.is_empty() {
+            return Ok(());
+        }
+        let mut keys: BTreeSet<_> = tokens
+            .iter()
+            .flat_map(|token| {
+                let reads = token.reads();
+                reads
+                    .iter_indexed()
+                    .map(|(index_name, _)| *index_name.table())
+                    .chain(
+                        reads
+                            .iter_search()
+                            .map(|(index_name, _)| *index_name.table()),
+                    )
+                    .collect::<Vec<_>>()
+            })
+            .map(table_key)
+            .collect();
+        let key = response_key(execution_id);
+        if self.watch(key.clone(), tokens, || {}) {
+            keys.insert(key);
+        }
+        add_surrogate_keys(headers, keys)
+    }
+
+    /// Purge a served file once the `_storage` document `token` read changes,
+    /// which only happens when the file is deleted.
+    pub fn watch_file(&self, storage_id: String, token: Token) {
+        if self.purger.is_none() || !self.watched_files.lock().insert(storage_id.clone()) {
+            return;
+        }
+        let watched_files = self.watched_files.clone();
+        let storage_id_ = storage_id.clone();
+        let on_done = move || {
+            watched_files.lock().remove(&storage_id_);
+        };
+        if !self.watch(file_key(&storage_id), vec![token], on_done) {
+            self.watched_files.lock().remove(&storage_id);
+        }
+    }
+
+    /// Subscribe to `tokens` and purge `key` when any of them is invalidated.
+    /// Returns false if purging isn't configured or too many keys are being
+    /// watched already.
+    fn watch(
+        &self,
+        key: String,
+        tokens: Vec<Token>,
+        on_done: impl FnOnce() + Send + 'static,
+    ) -> bool {
+        let Some(purger) = self.purger.clone() else {
+            return false;
+        };
+        if self.num_watches.fetch_add(1, Ordering::SeqCst) >= *CDN_PURGE_MAX_WATCHES {
+            self.num_watches.fetch_sub(1, Ordering::SeqCst);
+            return false;
+        }
+        let runtime = self.runtime.clone();
+        let database = self.database.clone();
+        let num_watches = self.num_watches.clone();
+        self.runtime.spawn("cdn_purge_watch", async move {
+            let result = async {
+                let mut subscriptions = vec![];
+                for token in tokens {
+                    subscriptions.push(database.subscribe(token).await?);
+                }
+                let invalidated = future::select_all(
+                    subscriptions
+                        .iter()
+                        .map(|subscription| subscription.wait_for_invalidation().boxed()),
+                );
+                select_biased! {
+                    _ = invalidated.fuse() => purger.purge(vec![key.clone()]).await?,
+                    _ = runtime.wait(*CDN_PURGE_WATCH_TTL) => {},
+                }
+                anyhow::Ok(())
+            }
+            .await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to purge {key} from the CDN: {e:#}");
+            }
+            num_watches.fetch_sub(1, Ordering::SeqCst);
+            on_done();
+        });
+        true
+    }
+
+    /// Purge keys from the CDN right away.
+    pub async fn purge(&self, keys: Vec<String>) -> anyhow::Result<()> {
+        let Some(purger) = &self.purger else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "CdnPurgeNotConfigured",
+                "Set CDN_PURGE_URL to purge surrogate keys from a CDN",
+            ));
+        };
+        if keys.is_empty() {
+            return Ok(());
+        }
+        purger.purge(keys).await
+    }
+}
+
+/// Stops collecting an HTTP action's reads, including when the action fails
+/// before sending a response.
+pub struct PendingReadsGuard {
+    pending_reads: Arc<Mutex<BTreeMap<String, Vec<Token>>>>,
+    key: String,
+}
+
+impl Drop for PendingReadsGuard {
+    fn drop(&mut self) {
+        self.pending_reads.lock().remove(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderMap;
+
+    use super::{
+        add_surrogate_keys,
+        file_key,
+        SURROGATE_KEY,
+    };
+
+    #[test]
+    fn test_add_surrogate_keys() -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        add_surrogate_keys(&mut headers, vec![])?;
+        assert!(headers.get(SURROGATE_KEY).is_none());
+
+        headers.insert(SURROGATE_KEY, "posts  user:1".parse()?);
+        add_surrogate_keys(&mut headers, vec![file_key("abc"), "posts".to_string()])?;
+        assert_eq!(headers[SURROGATE_KEY], "file:abc posts user:1");
+        Ok(())
+    }
+}
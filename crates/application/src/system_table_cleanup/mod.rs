@@ -18,9 +18,13 @@ use common::{
     },
     errors::report_error,
     knobs::{
+        BUILTIN_AUTH_PASSKEY_CHALLENGE_TTL,
+        FUNCTION_EXECUTION_RECORD_RETENTION,
         MAX_EXPIRED_SNAPSHOT_AGE,
         MAX_IMPORT_AGE,
         MAX_SESSION_CLEANUP_DURATION,
+        SIGNED_FILE_URL_MAX_TTL,
+        SLOW_QUERY_LOG_RETENTION,
         SYSTEM_TABLE_CLEANUP_CHUNK_SIZE,
         SYSTEM_TABLE_CLEANUP_FREQUENCY,
         SYSTEM_TABLE_ROWS_PER_SECOND,
@@ -58,8 +62,12 @@ use metrics::{
     system_table_cleanup_timer,
 };
 use model::{
+    builtin_auth::BUILTIN_AUTH_PASSKEY_CHALLENGES_TABLE,
     exports::ExportsModel,
+    file_url_revocations::FILE_URL_REVOCATIONS_TABLE,
+    function_execution_records::FUNCTION_EXECUTION_RECORDS_TABLE,
     session_requests::SESSION_REQUESTS_TABLE,
+    slow_queries::SLOW_QUERIES_TABLE,
 };
 use rand::Rng;
 use storage::Storage;
@@ -137,6 +145,70 @@ impl<RT: Runtime> SystemTableCleanupWorker<RT> {
                 &rate_limiter,
             )
             .await?;
+
+            // _function_execution_records are kept for the configured retention
+            // period.
+            let function_execution_records_cutoff = match *FUNCTION_EXECUTION_RECORD_RETENTION {
+                Some(duration) => {
+                    Some((*self.database.now_ts_for_reads().sub(duration)?).try_into()?)
+                },
+                None => None,
+            };
+            self.cleanup_system_table(
+                TableNamespace::Global,
+                &FUNCTION_EXECUTION_RECORDS_TABLE,
+                function_execution_records_cutoff
+                    .map_or(CreationTimeInterval::None, CreationTimeInterval::Before),
+                &rate_limiter,
+            )
+            .await?;
+
+            // _slow_queries are kept for the configured retention period.
+            let slow_queries_cutoff = match *SLOW_QUERY_LOG_RETENTION {
+                Some(duration) => {
+                    Some((*self.database.now_ts_for_reads().sub(duration)?).try_into()?)
+                },
+                None => None,
+            };
+            self.cleanup_system_table(
+                TableNamespace::Global,
+                &SLOW_QUERIES_TABLE,
+                slow_queries_cutoff
+                    .map_or(CreationTimeInterval::None, CreationTimeInterval::Before),
+                &rate_limiter,
+            )
+            .await?;
+
+            // _builtin_auth_passkey_challenges can't be answered once they
+            // expire, so anything older than that was abandoned.
+            let passkey_challenges_cutoff = (*self
+                .database
+                .now_ts_for_reads()
+                .sub(*BUILTIN_AUTH_PASSKEY_CHALLENGE_TTL)?)
+            .try_into()?;
+            self.cleanup_system_table(
+                TableNamespace::Global,
+                &BUILTIN_AUTH_PASSKEY_CHALLENGES_TABLE,
+                CreationTimeInterval::Before(passkey_challenges_cutoff),
+                &rate_limiter,
+            )
+            .await?;
+
+            // _file_url_revocations only matter until the revoked URL expires.
+            // URLs live at most `SIGNED_FILE_URL_MAX_TTL` and are revoked after
+            // they're issued, so revocations older than that have expired.
+            let file_url_revocations_cutoff = (*self
+                .database
+                .now_ts_for_reads()
+                .sub(*SIGNED_FILE_URL_MAX_TTL)?)
+            .try_into()?;
+            self.cleanup_system_table(
+                TableNamespace::Global,
+                &FILE_URL_REVOCATIONS_TABLE,
+                CreationTimeInterval::Before(file_url_revocations_cutoff),
+                &rate_limiter,
+            )
+            .await?;
         }
     }
 
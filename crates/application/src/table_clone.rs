@@ -0,0 +1,251 @@
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use common::{
+    document::ID_FIELD,
+    knobs::{
+        TRANSACTION_MAX_NUM_USER_WRITES,
+        TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+    },
+    persistence::LatestDocument,
+    runtime::Runtime,
+    types::{
+        IndexId,
+        RepeatableTimestamp,
+        TableName,
+    },
+};
+use database::{
+    Database,
+    ImportFacingModel,
+    IndexModel,
+    TableModel,
+};
+use errors::ErrorMetadata;
+use futures::{
+    pin_mut,
+    TryStreamExt,
+};
+use keybroker::Identity;
+use usage_tracking::FunctionUsageTracker;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexObject,
+    ConvexValue,
+    Size,
+    TableNamespace,
+    TabletIdAndTableNumber,
+};
+
+use crate::snapshot_import::backfill_and_enable_indexes_on_table;
+
+/// Copy the documents and index definitions of `source` into a new table
+/// `target` in the same namespace, returning the number of documents copied.
+///
+/// Documents are read at a single snapshot and written into a hidden table
+/// that only becomes visible once the copy is complete. Cloned documents
+/// keep the internal part of their IDs, so a source ID maps to its clone by
+/// swapping the table number. References between documents are copied
+/// verbatim and still point at the source table.
+pub async fn clone_table<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    namespace: TableNamespace,
+    source: &TableName,
+    target: &TableName,
+) -> anyhow::Result<u64> {
+    for table_name in [source, target] {
+        anyhow::ensure!(
+            !table_name.is_system(),
+            ErrorMetadata::bad_request(
+                "InvalidTableName",
+                format!("Cannot clone system table {table_name}")
+            )
+        );
+    }
+    let mut tx = database.begin(identity.clone()).await?;
+    let snapshot_ts = tx.begin_timestamp();
+    let table_mapping = tx.table_mapping().namespace(namespace);
+    let source_id =
+        table_mapping
+            .id_and_number_if_exists(source)
+            .context(ErrorMetadata::bad_request(
+                "TableNotFound",
+                format!("Table {source} does not exist"),
+            ))?;
+    anyhow::ensure!(
+        !table_mapping.name_exists(target),
+        ErrorMetadata::bad_request(
+            "TableAlreadyExists",
+            format!("Table {target} already exists"),
+        )
+    );
+    let by_id = *IndexModel::new(&mut tx)
+        .by_id_indexes()
+        .await?
+        .get(&source_id.tablet_id)
+        .context("Missing by_id index for source table")?;
+    drop(tx);
+
+    let tables_affected = BTreeSet::from([(namespace, target.clone())]);
+    let (_, target_id, _) = database
+        .execute_with_overloaded_retries(
+            identity.clone(),
+            FunctionUsageTracker::new(),
+            "clone_table_prepare",
+            |tx| {
+                async {
+                    // Create the clone in state Hidden, so it's invisible until it's filled.
+                    let target_id = TableModel::new(tx)
+                        .insert_table_for_import(namespace, target, None, &tables_affected)
+                        .await?;
+                    IndexModel::new(tx)
+                        .copy_indexes_to_table(namespace, source, target_id.tablet_id)
+                        .await?;
+                    Ok(target_id)
+                }
+                .into()
+            },
+        )
+        .await?;
+    match fill_clone(
+        database,
+        identity,
+        snapshot_ts,
+        source_id,
+        by_id,
+        target,
+        target_id,
+        &tables_affected,
+    )
+    .await
+    {
+        Ok(num_documents) => Ok(num_documents),
+        Err(e) => {
+            // Don't leave a partially filled hidden table behind, which would
+            // otherwise only be cleaned up by the system table cleanup worker.
+            let cleanup = database
+                .execute_with_overloaded_retries(
+                    identity.clone(),
+                    FunctionUsageTracker::new(),
+                    "clone_table_cleanup",
+                    |tx| {
+                        async {
+                            TableModel::new(tx)
+                                .delete_hidden_table(target_id.tablet_id)
+                                .await
+                        }
+                        .into()
+                    },
+                )
+                .await;
+            if let Err(cleanup_err) = cleanup {
+                tracing::error!(
+                    "Failed to delete partially cloned table {target}: {cleanup_err:#}"
+                );
+            }
+            Err(e)
+        },
+    }
+}
+
+/// Backfill the hidden `target` table's indexes, copy the documents into it
+/// and then make it visible.
+async fn fill_clone<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    snapshot_ts: RepeatableTimestamp,
+    source_id: TabletIdAndTableNumber,
+    by_id: IndexId,
+    target: &TableName,
+    target_id: TabletIdAndTableNumber,
+    tables_affected: &BTreeSet<(TableNamespace, TableName)>,
+) -> anyhow::Result<u64> {
+    backfill_and_enable_indexes_on_table(database, identity, target_id.tablet_id).await?;
+
+    let usage = FunctionUsageTracker::new();
+    let table_iterator = database.table_iterator(snapshot_ts, 1000);
+    let stream = table_iterator.stream_documents_in_table(source_id.tablet_id, by_id, None);
+    pin_mut!(stream);
+    let mut num_documents = 0;
+    let mut batch = vec![];
+    let mut batch_size = 0;
+    while let Some(LatestDocument { value: doc, .. }) = stream.try_next().await? {
+        let clone_id =
+            DeveloperDocumentId::new(target_id.table_number, doc.developer_id().internal_id());
+        let object = doc.into_value().0.shallow_merge(ConvexObject::for_value(
+            ID_FIELD.clone().into(),
+            ConvexValue::String(clone_id.encode().try_into()?),
+        )?)?;
+        batch_size += object.size();
+        batch.push(object);
+        if batch_size >= *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES / 2
+            || batch.len() >= *TRANSACTION_MAX_NUM_USER_WRITES / 2
+        {
+            num_documents += batch.len() as u64;
+            insert_clone_batch(
+                database,
+                identity,
+                std::mem::take(&mut batch),
+                target,
+                target_id,
+                usage.clone(),
+            )
+            .await?;
+            batch_size = 0;
+        }
+    }
+    num_documents += batch.len() as u64;
+    insert_clone_batch(database, identity, batch, target, target_id, usage.clone()).await?;
+
+    database
+        .execute_with_overloaded_retries(identity.clone(), usage, "clone_table_activate", |tx| {
+            async {
+                TableModel::new(tx)
+                    .activate_table(
+                        target_id.tablet_id,
+                        target,
+                        target_id.table_number,
+                        tables_affected,
+                    )
+                    .await?;
+                Ok(())
+            }
+            .into()
+        })
+        .await?;
+    Ok(num_documents)
+}
+
+async fn insert_clone_batch<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    objects: Vec<ConvexObject>,
+    table_name: &TableName,
+    table_id: TabletIdAndTableNumber,
+    usage: FunctionUsageTracker,
+) -> anyhow::Result<()> {
+    if objects.is_empty() {
+        return Ok(());
+    }
+    database
+        .execute_with_overloaded_retries(
+            identity.clone(),
+            usage,
+            "clone_table_insert_documents",
+            |tx| {
+                async {
+                    let table_mapping = tx.table_mapping().clone();
+                    for object in objects.clone() {
+                        ImportFacingModel::new(tx)
+                            .insert(table_id, table_name, object, &table_mapping)
+                            .await?;
+                    }
+                    Ok(())
+                }
+                .into()
+            },
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,248 @@
+//! Per-table storage statistics for capacity planning. Computing them reads
+//! the document log, so a background worker refreshes them periodically and
+//! the API serves the latest result along with when it was computed.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    bootstrap_model::index::{
+        text_index::{
+            TextIndexSnapshotData,
+            TextIndexState,
+        },
+        IndexConfig,
+    },
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
+    errors::report_error,
+    knobs::{
+        TABLE_STATS_LOG_ROWS_PER_SECOND,
+        TABLE_STATS_REFRESH_INTERVAL,
+    },
+    persistence::{
+        RetentionValidator,
+        TimestampRange,
+    },
+    query::Order,
+    runtime::{
+        new_rate_limiter,
+        RateLimiter,
+        Runtime,
+        UnixTimestamp,
+    },
+    types::{
+        IndexDescriptor,
+        TableName,
+        Timestamp,
+    },
+};
+use database::Database;
+use errors::ErrorMetadataAnyhowExt;
+use futures::TryStreamExt;
+use governor::Quota;
+use parking_lot::Mutex;
+use value::TabletId;
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableStats {
+    pub component_path: ComponentPath,
+    pub table_name: TableName,
+    pub document_count: u64,
+    /// Total size of the current documents.
+    pub document_bytes: u64,
+    /// Estimated size of each enabled database and vector index. Database
+    /// indexes are estimated as the table's document size, as for usage.
+    pub index_bytes: BTreeMap<IndexDescriptor, u64>,
+    /// Total size of the segments of the table's search indexes.
+    pub search_segment_bytes: u64,
+    /// Size of revisions that are no longer current, written within the
+    /// retention window.
+    pub historical_revision_bytes: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableStatsSnapshot {
+    pub tables: Vec<TableStats>,
+    /// The database timestamp the stats reflect.
+    pub snapshot_ts: Timestamp,
+    /// When the worker finished computing the stats.
+    pub computed_at: UnixTimestamp,
+}
+
+/// The most recent stats, `None` until the worker has computed them once.
+pub type LatestTableStats = Arc<Mutex<Option<TableStatsSnapshot>>>;
+
+pub struct TableStatsWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    latest: LatestTableStats,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> TableStatsWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        latest: LatestTableStats,
+    ) -> impl Future<Output = ()> + Send {
+        let mut worker = Self {
+            runtime,
+            database,
+            latest,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        };
+        async move {
+            if TABLE_STATS_REFRESH_INTERVAL.is_zero() {
+                tracing::info!("Table stats worker is disabled");
+                return;
+            }
+            tracing::info!("Starting TableStatsWorker");
+            loop {
+                match worker.run_once().await {
+                    Ok(()) => {
+                        worker.backoff.reset();
+                        worker.runtime.wait(*TABLE_STATS_REFRESH_INTERVAL).await;
+                    },
+                    Err(e) => {
+                        report_error(&mut e.context("TableStatsWorker died")).await;
+                        let delay = worker.backoff.fail(&mut worker.runtime.rng());
+                        worker.runtime.wait(delay).await;
+                    },
+                }
+            }
+        }
+    }
+
+    async fn run_once(&self) -> anyhow::Result<()> {
+        let _status = log_worker_starting("TableStatsWorker");
+        let snapshot_ts = self.database.now_ts_for_reads();
+        let snapshot = self.database.snapshot(snapshot_ts)?;
+        let table_mapping = snapshot.table_mapping().clone();
+        let table_summaries = snapshot.must_table_summaries()?;
+        let component_paths = snapshot.component_ids_to_paths();
+
+        let mut tables = BTreeMap::new();
+        for (tablet_id, namespace, _, table_name) in table_mapping.iter() {
+            if !table_mapping.is_active(tablet_id) {
+                continue;
+            }
+            // Skip tables in components that are being deleted.
+            let Some(component_path) = component_paths.get(&ComponentId::from(namespace)) else {
+                continue;
+            };
+            let summary = table_summaries.tablet_summary(&tablet_id);
+            tables.insert(
+                tablet_id,
+                TableStats {
+                    component_path: component_path.clone(),
+                    table_name: table_name.clone(),
+                    document_count: summary.num_values(),
+                    document_bytes: summary.total_size(),
+                    index_bytes: BTreeMap::new(),
+                    search_segment_bytes: 0,
+                    historical_revision_bytes: 0,
+                },
+            );
+        }
+
+        for index in snapshot.index_registry.all_enabled_indexes() {
+            let Some(stats) = tables.get_mut(index.name.table()) else {
+                continue;
+            };
+            let descriptor = index.name.descriptor().clone();
+            match &index.config {
+                IndexConfig::Database { .. } => {
+                    stats.index_bytes.insert(descriptor, stats.document_bytes);
+                },
+                IndexConfig::Text { on_disk_state, .. } => {
+                    if let TextIndexState::Backfilled(text_snapshot)
+                    | TextIndexState::SnapshottedAt(text_snapshot) = on_disk_state
+                        && let TextIndexSnapshotData::MultiSegment(segments) = &text_snapshot.data
+                    {
+                        stats.search_segment_bytes += segments
+                            .iter()
+                            .map(|segment| segment.size_bytes_total)
+                            .sum::<u64>();
+                    }
+                },
+                IndexConfig::Vector { .. } => {
+                    let size = index.config.estimate_pricing_size_bytes()?;
+                    stats.index_bytes.insert(descriptor, size);
+                },
+            }
+        }
+
+        let min_ts = self
+            .database
+            .retention_validator()
+            .min_document_snapshot_ts()
+            .await?;
+        let rate_limiter = new_rate_limiter(
+            self.runtime.clone(),
+            Quota::per_second(*TABLE_STATS_LOG_ROWS_PER_SECOND),
+        );
+        for (tablet_id, stats) in tables.iter_mut() {
+            stats.historical_revision_bytes = self
+                .historical_revision_bytes(
+                    *tablet_id,
+                    TimestampRange::new(*min_ts..=*snapshot_ts)?,
+                    &rate_limiter,
+                )
+                .await?;
+        }
+
+        *self.latest.lock() = Some(TableStatsSnapshot {
+            tables: tables.into_values().collect(),
+            snapshot_ts: *snapshot_ts,
+            computed_at: self.runtime.unix_timestamp(),
+        });
+        Ok(())
+    }
+
+    /// Reads the table's log newest first. The first entry for each document
+    /// is its current revision and every later one is historical.
+    async fn historical_revision_bytes(
+        &self,
+        tablet_id: TabletId,
+        range: TimestampRange,
+        rate_limiter: &RateLimiter<RT>,
+    ) -> anyhow::Result<u64> {
+        let mut stream =
+            self.database
+                .load_documents_in_table(tablet_id, range, Order::Desc, rate_limiter);
+        let mut seen = BTreeSet::new();
+        let mut bytes = 0;
+        loop {
+            let entry = match stream.try_next().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                // Retention advanced past the scan, so the remaining revisions
+                // are gone.
+                Err(e) if e.is_out_of_retention() => break,
+                Err(e) => return Err(e),
+            };
+            if !seen.insert(entry.id)
+                && let Some(document) = entry.value
+            {
+                bytes += document.size() as u64;
+            }
+        }
+        Ok(bytes)
+    }
+}
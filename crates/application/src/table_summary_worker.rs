@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeSet,
     sync::Arc,
     time::Duration,
 };
@@ -23,6 +24,7 @@ use database::{
     Database,
     TableSummaryWriter,
 };
+use errors::ErrorMetadata;
 use futures::{
     pin_mut,
     select_biased,
@@ -30,14 +32,23 @@ use futures::{
 };
 use parking_lot::Mutex;
 use rand::Rng;
-use tokio::sync::oneshot;
+use tokio::sync::{
+    mpsc,
+    oneshot,
+};
+use value::TabletId;
 
 use crate::metrics::{
     log_table_summary_checkpoint,
     log_worker_starting,
     table_summary_bootstrap_timer,
+    table_summary_recompute_timer,
 };
 
+/// How many recompute requests can wait for the worker before new ones are
+/// turned away.
+const MAX_PENDING_RECOMPUTES: usize = 4;
+
 pub struct TableSummaryWorker<RT: Runtime> {
     runtime: RT,
     database: Database<RT>,
@@ -49,9 +60,15 @@ struct Inner {
     cancel_sender: oneshot::Sender<()>,
 }
 
+struct RecomputeRequest {
+    tablet_ids: Option<BTreeSet<TabletId>>,
+    result: oneshot::Sender<anyhow::Result<()>>,
+}
+
 #[derive(Clone)]
 pub struct TableSummaryClient {
     inner: Arc<Mutex<Option<Inner>>>,
+    recompute_sender: mpsc::Sender<RecomputeRequest>,
 }
 
 struct LastWriteInfo {
@@ -71,9 +88,10 @@ impl<RT: Runtime> TableSummaryWorker<RT> {
             persistence,
         };
         let (cancel_sender, cancel_receiver) = oneshot::channel();
+        let (recompute_sender, recompute_receiver) = mpsc::channel(MAX_PENDING_RECOMPUTES);
         let handle = runtime.spawn(
             "table_summary_worker",
-            table_summary_worker.go(cancel_receiver),
+            table_summary_worker.go(cancel_receiver, recompute_receiver),
         );
         let inner = Inner {
             handle,
@@ -81,6 +99,7 @@ impl<RT: Runtime> TableSummaryWorker<RT> {
         };
         TableSummaryClient {
             inner: Arc::new(Mutex::new(Some(inner))),
+            recompute_sender,
         }
     }
 
@@ -127,7 +146,33 @@ impl<RT: Runtime> TableSummaryWorker<RT> {
         Ok(())
     }
 
-    async fn go(self, cancel_receiver: oneshot::Receiver<()>) {
+    /// Rebuild the summaries of some tables by rescanning them, rather than
+    /// carrying them forward from the last checkpoint, and checkpoint the
+    /// result.
+    async fn recompute_table_summaries(
+        &self,
+        writer: &TableSummaryWriter<RT>,
+        tablet_ids: Option<BTreeSet<TabletId>>,
+        has_bootstrapped: bool,
+    ) -> anyhow::Result<()> {
+        let timer = table_summary_recompute_timer();
+        let snapshot = writer.compute_with_rescanned_tables(tablet_ids).await?;
+        tracing::info!("Writing recomputed table summaries at ts {}", snapshot.ts);
+        write_snapshot(self.persistence.as_ref(), &snapshot).await?;
+        // Before the bootstrap finishes, the next checkpoint picks up the new
+        // snapshot and loads it into `Database` when it's ready.
+        if has_bootstrapped {
+            self.database.finish_table_summary_bootstrap().await?;
+        }
+        timer.finish();
+        Ok(())
+    }
+
+    async fn go(
+        self,
+        cancel_receiver: oneshot::Receiver<()>,
+        mut recompute_receiver: mpsc::Receiver<RecomputeRequest>,
+    ) {
         tracing::info!("Starting background table summary worker");
         let mut timer = Some(table_summary_bootstrap_timer());
         let cancel_fut = cancel_receiver.fuse();
@@ -174,6 +219,17 @@ impl<RT: Runtime> TableSummaryWorker<RT> {
                     tracing::info!("Shutting down table summary worker...");
                     break;
                 }
+                request = recompute_receiver.recv().fuse() => match request {
+                    Some(RecomputeRequest { tablet_ids, result }) => {
+                        let response = self
+                            .recompute_table_summaries(&writer, tablet_ids, has_bootstrapped)
+                            .await;
+                        let _ = result.send(response);
+                    },
+                    // Every client is gone, so there's nothing left to wait on
+                    // besides the next checkpoint.
+                    None => wait_fut.await,
+                },
                 _ = wait_fut => {},
             }
         }
@@ -181,6 +237,27 @@ impl<RT: Runtime> TableSummaryWorker<RT> {
 }
 
 impl TableSummaryClient {
+    /// Rescan the given tables, or every table if `None`, and replace their
+    /// summaries once the rescan finishes.
+    pub async fn recompute(&self, tablet_ids: Option<BTreeSet<TabletId>>) -> anyhow::Result<()> {
+        let (result, receiver) = oneshot::channel();
+        let request = RecomputeRequest { tablet_ids, result };
+        self.recompute_sender
+            .try_send(request)
+            .map_err(|e| match e {
+                mpsc::error::TrySendError::Full(..) => anyhow::anyhow!(ErrorMetadata::overloaded(
+                    "TooManyTableSummaryRecomputes",
+                    "Too many table summary recomputes are already queued. Try again later.",
+                )),
+                mpsc::error::TrySendError::Closed(..) => {
+                    anyhow::anyhow!("Table summary worker has shut down")
+                },
+            })?;
+        receiver
+            .await
+            .map_err(|_| anyhow::anyhow!("Table summary worker has shut down"))?
+    }
+
     pub async fn shutdown(&self) -> anyhow::Result<()> {
         let inner = { self.inner.lock().take() };
         if let Some(mut inner) = inner {
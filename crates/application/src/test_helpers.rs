@@ -29,8 +29,13 @@ use common::{
     log_streaming::NoopLogSender,
     persistence::Persistence,
     runtime::Runtime,
+    secrets::SecretsResolver,
     shutdown::ShutdownSignal,
-    testing::TestPersistence,
+    testing::{
+        FaultyPersistence,
+        PersistenceFaults,
+        TestPersistence,
+    },
     types::{
         ConvexOrigin,
         FullyQualifiedObjectKey,
@@ -89,12 +94,14 @@ use node_executor::{
     local::LocalNodeExecutor,
     Actions,
 };
+use serde_json::Value as JsonValue;
 use storage::{
     LocalDirStorage,
     Storage,
     StorageUseCase,
 };
 use value::{
+    DeveloperDocumentId,
     ResolvedDocumentId,
     TableName,
     TableNamespace,
@@ -110,6 +117,8 @@ use crate::{
     },
     log_visibility::AllowLogging,
     scheduled_jobs::ScheduledJobExecutor,
+    seed::SeedFixture,
+    usage_export::UsageAggregator,
     Application,
 };
 
@@ -120,6 +129,8 @@ pub static OBJECTS_TABLE_COMPONENT: ComponentId = ComponentId::test_user();
 pub struct ApplicationFixtureArgs {
     pub tp: Option<TestPersistence>,
     pub event_logger: Option<Arc<dyn UsageEventLogger>>,
+    /// Faults to inject into writes to the test persistence.
+    pub persistence_faults: Option<PersistenceFaults>,
 }
 
 impl ApplicationFixtureArgs {
@@ -155,6 +166,10 @@ pub trait ApplicationTestExt<RT: Runtime> {
         job: CronJob,
         job_id: ResolvedDocumentId,
     ) -> anyhow::Result<()>;
+    /// Wait until the scheduler and cron executors have run every job that is
+    /// due, including actions still in progress. Pair with
+    /// `TestRuntime::advance_time` to make jobs come due.
+    async fn wait_for_due_jobs(&self) -> anyhow::Result<()>;
     fn validate_user_defined_index_fields(
         &self,
         fields: IndexedFields,
@@ -163,6 +178,13 @@ pub trait ApplicationTestExt<RT: Runtime> {
     fn snapshot_imports_storage(&self) -> Arc<dyn Storage>;
     fn exports_storage(&self) -> Arc<dyn Storage>;
     async fn export_and_wait(&self) -> anyhow::Result<FullyQualifiedObjectKey>;
+    /// Seed the test user namespace from a fixture like
+    /// `{"users": [{"$name": "alice", "name": "Alice"}]}`, returning the IDs
+    /// of the named documents.
+    async fn seed_for_test(
+        &self,
+        fixture: JsonValue,
+    ) -> anyhow::Result<BTreeMap<String, DeveloperDocumentId>>;
 }
 
 #[async_trait]
@@ -182,14 +204,23 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
         let convex_site = "http://127.0.0.1:8001".into();
         let searcher = Arc::new(search::searcher::SearcherStub {});
         let segment_term_metadata_fetcher = Arc::new(search::searcher::SearcherStub {});
-        let persistence = args.tp.unwrap_or_else(TestPersistence::new);
+        let test_persistence = args.tp.unwrap_or_else(TestPersistence::new);
+        let persistence: Arc<dyn Persistence> = match args.persistence_faults {
+            Some(faults) => Arc::new(FaultyPersistence::new(Arc::new(test_persistence), faults)),
+            None => Arc::new(test_persistence),
+        };
+        let usage_aggregator = Arc::new(UsageAggregator::new(
+            DEV_INSTANCE_NAME.into(),
+            rt.unix_timestamp(),
+            args.event_logger.unwrap_or(Arc::new(NoOpUsageEventLogger)),
+        ));
         let database = Database::load(
-            Arc::new(persistence.clone()),
+            persistence.clone(),
             rt.clone(),
             searcher.clone(),
             ShutdownSignal::panic(),
             virtual_system_mapping(),
-            args.event_logger.unwrap_or(Arc::new(NoOpUsageEventLogger)),
+            usage_aggregator.clone(),
         )
         .await?;
         initialize_application_system_tables(&database).await?;
@@ -233,6 +264,7 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
                 },
                 database.clone(),
                 fetch_client,
+                SecretsResolver::default(),
             )
             .await?,
         );
@@ -265,6 +297,7 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
             exports_storage.clone(),
             snapshot_imports_storage.clone(),
             database.usage_counter(),
+            usage_aggregator,
             kb.clone(),
             DEV_INSTANCE_NAME.into(),
             function_runner,
@@ -272,8 +305,9 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
             convex_site,
             searcher,
             segment_term_metadata_fetcher,
-            Arc::new(persistence.clone()),
+            persistence.clone(),
             actions,
+            SecretsResolver::default(),
             Arc::new(NoopLogSender),
             Arc::new(AllowLogging),
             Arc::new(ApplicationAuth::new(
@@ -306,6 +340,7 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
             self.database.clone(),
             self.runner.clone(),
             self.function_log.clone(),
+            self.files_storage.clone(),
         );
         test_executor.execute_job(job, job_id).await;
         Ok(())
@@ -327,6 +362,39 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
         Ok(())
     }
 
+    async fn wait_for_due_jobs(&self) -> anyhow::Result<()> {
+        let scheduled_job_executor = ScheduledJobExecutor::new(
+            self.runtime.clone(),
+            DEV_INSTANCE_NAME.into(),
+            self.database.clone(),
+            self.runner.clone(),
+            self.function_log.clone(),
+            self.files_storage.clone(),
+        );
+        let cron_job_executor = CronJobExecutor::new(
+            self.runtime.clone(),
+            DEV_INSTANCE_NAME.into(),
+            self.database.clone(),
+            self.runner.clone(),
+            self.function_log.clone(),
+        );
+        loop {
+            let now = self.runtime.generate_timestamp()?;
+            let mut tx = self.begin(Identity::system()).await?;
+            if !scheduled_job_executor
+                .has_due_or_running_jobs(&mut tx, now)
+                .await?
+                && !cron_job_executor
+                    .has_due_or_running_jobs(&mut tx, now)
+                    .await?
+            {
+                return Ok(());
+            }
+            let subscription = self.database.subscribe(tx.into_token()?).await?;
+            subscription.wait_for_invalidation().await;
+        }
+    }
+
     async fn load_udf_tests_modules(&self) -> anyhow::Result<()> {
         self.load_udf_tests_modules_inner(false).await
     }
@@ -400,6 +468,20 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
         };
         Ok(self.cloud_export_key(export_object_key))
     }
+
+    async fn seed_for_test(
+        &self,
+        fixture: JsonValue,
+    ) -> anyhow::Result<BTreeMap<String, DeveloperDocumentId>> {
+        let result = self
+            .seed(
+                &Identity::system(),
+                TableNamespace::test_user(),
+                SeedFixture::try_from(fixture)?,
+            )
+            .await?;
+        Ok(result.ids)
+    }
 }
 
 impl<RT: Runtime> Application<RT> {
@@ -0,0 +1,76 @@
+use std::net::IpAddr;
+
+use common::{
+    knobs::{
+        ADMIN_KEY_LOCKOUT_BASE,
+        ADMIN_KEY_LOCKOUT_THRESHOLD,
+    },
+    runtime::Runtime,
+    types::MemberId,
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use sync_types::AuthenticationToken;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_admin_key_lockout(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let locked_ip: IpAddr = "203.0.113.7".parse()?;
+    let invalid_key = |i: u32| {
+        AuthenticationToken::Admin(format!("{}|invalid{i}", application.instance_name()), None)
+    };
+    let admin_key = application.key_broker().issue_admin_key(MemberId(1));
+    let valid_key = || AuthenticationToken::Admin(admin_key.as_string(), None);
+
+    for i in 0..*ADMIN_KEY_LOCKOUT_THRESHOLD {
+        let err = application
+            .authenticate_from(invalid_key(i), rt.system_time(), Some(locked_ip))
+            .await
+            .unwrap_err();
+        assert!(err.is_unauthenticated() || err.is_forbidden(), "{err:?}");
+    }
+    // The locked out address can't even use a valid key.
+    let err = application
+        .authenticate_from(valid_key(), rt.system_time(), Some(locked_ip))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "AdminKeyLockedOut");
+
+    // Other addresses can.
+    let identity = application
+        .authenticate_from(valid_key(), rt.system_time(), Some("198.51.100.1".parse()?))
+        .await?;
+    assert!(matches!(identity, Identity::InstanceAdmin(_)));
+
+    rt.advance_time(*ADMIN_KEY_LOCKOUT_BASE).await;
+    let identity = application
+        .authenticate_from(valid_key(), rt.system_time(), Some(locked_ip))
+        .await?;
+    assert!(matches!(identity, Identity::InstanceAdmin(_)));
+
+    // Retrying one key locks out the key, even without a client address.
+    let retried = u32::MAX;
+    for _ in 0..*ADMIN_KEY_LOCKOUT_THRESHOLD {
+        let err = application
+            .authenticate_from(invalid_key(retried), rt.system_time(), None)
+            .await
+            .unwrap_err();
+        assert!(err.is_unauthenticated() || err.is_forbidden(), "{err:?}");
+    }
+    let err = application
+        .authenticate_from(
+            invalid_key(retried),
+            rt.system_time(),
+            Some("192.0.2.1".parse()?),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "AdminKeyLockedOut");
+    Ok(())
+}
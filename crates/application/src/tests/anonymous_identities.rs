@@ -0,0 +1,126 @@
+use common::{
+    auth::AuthInfo,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+        PublicFunctionPath,
+    },
+    execution_context::RequestContext,
+    runtime::Runtime,
+    types::FunctionCaller,
+    RequestId,
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::{
+    testing::TestUserIdentity,
+    Identity,
+    UserIdentity,
+    ANONYMOUS_IDENTITY_AUDIENCE,
+    ANONYMOUS_IDENTITY_ISSUER,
+};
+use model::auth::AuthInfoModel;
+use openidconnect::IssuerUrl;
+use runtime::testing::TestRuntime;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use sync_types::AuthenticationToken;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+async fn anonymous_subject(
+    rt: &TestRuntime,
+    application: &Application<TestRuntime>,
+    token: &str,
+) -> anyhow::Result<String> {
+    let identity = application
+        .authenticate(
+            AuthenticationToken::User(token.to_string()),
+            rt.system_time(),
+        )
+        .await?;
+    let Identity::User(user) = identity else {
+        anyhow::bail!("Expected a user identity");
+    };
+    assert_eq!(user.issuer, ANONYMOUS_IDENTITY_ISSUER);
+    Ok(user.subject)
+}
+
+async fn promote(
+    application: &Application<TestRuntime>,
+    token: &str,
+    device_id: &str,
+) -> anyhow::Result<Option<JsonValue>> {
+    let result = application
+        .mutation_udf(
+            RequestId::new(),
+            PublicFunctionPath::Component(CanonicalizedComponentFunctionPath {
+                component: ComponentPath::test_user(),
+                udf_path: "auth:promoteAnonymous".parse()?,
+            }),
+            vec![json!({"token": token, "deviceId": device_id})],
+            Identity::user(UserIdentity::test()),
+            None,
+            FunctionCaller::Test,
+            RequestContext::default(),
+        )
+        .await?;
+    Ok(result.ok().map(|result| JsonValue::from(result.value)))
+}
+
+#[convex_macro::test_runtime]
+async fn test_anonymous_identities(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let err = application
+        .issue_anonymous_identity_token("device1".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "AnonymousIdentitiesDisabled");
+
+    let mut tx = application.begin(Identity::system()).await?;
+    AuthInfoModel::new(&mut tx)
+        .put(vec![AuthInfo {
+            application_id: ANONYMOUS_IDENTITY_AUDIENCE.to_string(),
+            domain: IssuerUrl::new(ANONYMOUS_IDENTITY_ISSUER.to_string())?,
+            jwks_cache_ttl_seconds: None,
+            magic_link_action: None,
+            passkey_origin: None,
+        }])
+        .await?;
+    application.commit_test(tx).await?;
+
+    // The same device keeps its anonymous identity across tokens.
+    let token = application
+        .issue_anonymous_identity_token("device1".to_string())
+        .await?;
+    let subject = anonymous_subject(&rt, &application, &token).await?;
+    let token = application
+        .issue_anonymous_identity_token("device1".to_string())
+        .await?;
+    assert_eq!(anonymous_subject(&rt, &application, &token).await?, subject);
+
+    // Promotion requires the device the identity was issued to.
+    assert_eq!(promote(&application, &token, "device2").await?, None);
+    assert_eq!(
+        promote(&application, &token, "device1").await?,
+        Some(json!(format!("{ANONYMOUS_IDENTITY_ISSUER}|{subject}")))
+    );
+    assert_eq!(promote(&application, &token, "device1").await?, None);
+
+    // Promoted identities can't sign in, and the device gets a new one.
+    let err = anonymous_subject(&rt, &application, &token)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "AnonymousIdentityPromoted");
+    let token = application
+        .issue_anonymous_identity_token("device1".to_string())
+        .await?;
+    assert_ne!(anonymous_subject(&rt, &application, &token).await?, subject);
+    Ok(())
+}
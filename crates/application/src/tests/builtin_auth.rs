@@ -0,0 +1,425 @@
+use std::{
+    net::IpAddr,
+    time::Duration,
+};
+
+use common::{
+    auth::AuthInfo,
+    knobs::{
+        BUILTIN_AUTH_SIGN_IN_LOCKOUT_BASE,
+        BUILTIN_AUTH_SIGN_IN_LOCKOUT_THRESHOLD,
+    },
+    runtime::Runtime,
+    testing::TestPersistence,
+    version::ClientVersion,
+    RequestId,
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::{
+    Identity,
+    BUILTIN_AUTH_AUDIENCE,
+    BUILTIN_AUTH_ISSUER,
+};
+use model::{
+    auth::AuthInfoModel,
+    builtin_auth::BuiltinAuthModel,
+};
+use openidconnect::IssuerUrl;
+use runtime::testing::TestRuntime;
+use serde_json::json;
+use sync_types::AuthenticationToken;
+use value::base64::encode_urlsafe;
+
+use crate::{
+    test_helpers::{
+        ApplicationFixtureArgs,
+        ApplicationTestExt,
+    },
+    Application,
+};
+
+async fn signed_in_subject(
+    rt: &TestRuntime,
+    application: &Application<TestRuntime>,
+    token: &str,
+) -> anyhow::Result<String> {
+    let identity = application
+        .authenticate(
+            AuthenticationToken::User(token.to_string()),
+            rt.system_time(),
+        )
+        .await?;
+    let Identity::User(user) = identity else {
+        anyhow::bail!("Expected a user identity");
+    };
+    assert_eq!(user.issuer, BUILTIN_AUTH_ISSUER);
+    Ok(user.subject)
+}
+
+async fn enable_builtin_auth(
+    application: &Application<TestRuntime>,
+    passkey_origin: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut tx = application.begin(Identity::system()).await?;
+    AuthInfoModel::new(&mut tx)
+        .put(vec![AuthInfo {
+            application_id: BUILTIN_AUTH_AUDIENCE.to_string(),
+            domain: IssuerUrl::new(BUILTIN_AUTH_ISSUER.to_string())?,
+            jwks_cache_ttl_seconds: None,
+            magic_link_action: None,
+            passkey_origin: passkey_origin.map(String::from),
+        }])
+        .await?;
+    application.commit_test(tx).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_builtin_auth_password(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let email = "Ada@example.com".to_string();
+    let password = "correct horse".to_string();
+
+    let err = application
+        .builtin_auth_sign_up(email.clone(), password.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "BuiltinAuthDisabled");
+    enable_builtin_auth(&application, None).await?;
+
+    let err = application
+        .builtin_auth_sign_up(email.clone(), "short".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidPassword");
+    application
+        .builtin_auth_sign_up(email.clone(), password.clone())
+        .await?;
+    let tokens = application
+        .builtin_auth_sign_in(email.clone(), password.clone(), None)
+        .await?;
+    let subject = signed_in_subject(&rt, &application, &tokens.token).await?;
+    // Signing up with an existing email looks like it succeeded, but doesn't
+    // change the user's password.
+    application
+        .builtin_auth_sign_up("ada@example.com".to_string(), "new password".to_string())
+        .await?;
+    let err = application
+        .builtin_auth_sign_in(email.clone(), "new password".to_string(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidCredentials");
+
+    // Emails are case insensitive, passwords aren't.
+    let err = application
+        .builtin_auth_sign_in(email.clone(), "Correct horse".to_string(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidCredentials");
+    let err = application
+        .builtin_auth_sign_in("bob@example.com".to_string(), password.clone(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidCredentials");
+    let tokens = application
+        .builtin_auth_sign_in("ADA@example.com".to_string(), password, None)
+        .await?;
+    assert_eq!(
+        signed_in_subject(&rt, &application, &tokens.token).await?,
+        subject
+    );
+
+    // Refresh tokens are single use.
+    let refreshed = application
+        .builtin_auth_refresh(tokens.refresh_token.clone())
+        .await?;
+    assert_eq!(
+        signed_in_subject(&rt, &application, &refreshed.token).await?,
+        subject
+    );
+    let err = application
+        .builtin_auth_refresh(tokens.refresh_token)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "BuiltinAuthSessionExpired");
+
+    application
+        .builtin_auth_sign_out(refreshed.refresh_token.clone())
+        .await?;
+    let err = application
+        .builtin_auth_refresh(refreshed.refresh_token)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "BuiltinAuthSessionExpired");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_builtin_auth_sign_in_lockout(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    enable_builtin_auth(&application, None).await?;
+    let email = "ada@example.com".to_string();
+    let password = "correct horse".to_string();
+    application
+        .builtin_auth_sign_up(email.clone(), password.clone())
+        .await?;
+
+    // Guessing one account's password from many addresses locks out the
+    // account.
+    for i in 0..*BUILTIN_AUTH_SIGN_IN_LOCKOUT_THRESHOLD {
+        let ip = IpAddr::from([203, 0, 113, i as u8]);
+        let err = application
+            .builtin_auth_sign_in(email.clone(), format!("guess {i}"), Some(ip))
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "InvalidCredentials");
+    }
+    let err = application
+        .builtin_auth_sign_in(email.clone(), password.clone(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TooManySignInAttempts");
+    rt.advance_time(*BUILTIN_AUTH_SIGN_IN_LOCKOUT_BASE).await;
+    application
+        .builtin_auth_sign_in(email.clone(), password.clone(), None)
+        .await?;
+
+    // Guessing many accounts' passwords from one address locks out the
+    // address, whether or not the accounts exist.
+    let ip: IpAddr = "198.51.100.1".parse()?;
+    for i in 0..*BUILTIN_AUTH_SIGN_IN_LOCKOUT_THRESHOLD {
+        let err = application
+            .builtin_auth_sign_in(format!("user{i}@example.com"), password.clone(), Some(ip))
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "InvalidCredentials");
+    }
+    let err = application
+        .builtin_auth_sign_in(email.clone(), password.clone(), Some(ip))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TooManySignInAttempts");
+    application
+        .builtin_auth_sign_in(email, password, None)
+        .await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_builtin_auth_survives_restart(rt: TestRuntime) -> anyhow::Result<()> {
+    let tp = TestPersistence::new();
+    let args = || ApplicationFixtureArgs {
+        tp: Some(tp.clone()),
+        ..Default::default()
+    };
+    let application = Application::new_for_tests_with_args(&rt, args()).await?;
+    enable_builtin_auth(&application, None).await?;
+    let email = "ada@example.com".to_string();
+    let password = "correct horse".to_string();
+    application
+        .builtin_auth_sign_up(email.clone(), password.clone())
+        .await?;
+    application.shutdown().await?;
+
+    // Restarting initializes the now existing built-in auth tables again.
+    let application = Application::new_for_tests_with_args(&rt, args()).await?;
+    let tokens = application
+        .builtin_auth_sign_in(email, password, None)
+        .await?;
+    signed_in_subject(&rt, &application, &tokens.token).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_builtin_auth_magic_link(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    enable_builtin_auth(&application, None).await?;
+
+    let err = application
+        .builtin_auth_send_magic_link(
+            RequestId::new(),
+            "ada@example.com".to_string(),
+            ClientVersion::unknown(),
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "MagicLinksDisabled");
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = BuiltinAuthModel::new(&mut tx);
+    let user = model.create_user("ada@example.com", false).await?;
+    let token = model
+        .create_magic_link(&user.email, rt.unix_timestamp() + Duration::from_secs(60))
+        .await?;
+    application.commit_test(tx).await?;
+
+    // Magic links can only be used once, and they verify the user's email.
+    let tokens = application
+        .builtin_auth_verify_magic_link(token.clone())
+        .await?;
+    assert_eq!(
+        signed_in_subject(&rt, &application, &tokens.token).await?,
+        user.user_id
+    );
+    let err = application
+        .builtin_auth_verify_magic_link(token)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidMagicLink");
+    let mut tx = application.begin(Identity::system()).await?;
+    let user = BuiltinAuthModel::new(&mut tx)
+        .get_user(&user.user_id)
+        .await?
+        .unwrap();
+    assert!(user.email_verified);
+
+    // Users are only created once a link sent to a new email is redeemed.
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = BuiltinAuthModel::new(&mut tx);
+    let token = model
+        .create_magic_link(
+            "Bob@example.com",
+            rt.unix_timestamp() + Duration::from_secs(60),
+        )
+        .await?;
+    assert!(model.get_user_by_email("bob@example.com").await?.is_none());
+    application.commit_test(tx).await?;
+    let tokens = application.builtin_auth_verify_magic_link(token).await?;
+    let subject = signed_in_subject(&rt, &application, &tokens.token).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let user = BuiltinAuthModel::new(&mut tx)
+        .get_user_by_email("bob@example.com")
+        .await?
+        .unwrap()
+        .into_value();
+    assert_eq!(user.user_id, subject);
+    assert!(user.email_verified);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_builtin_auth_magic_link_rate_limit(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    AuthInfoModel::new(&mut tx)
+        .put(vec![AuthInfo {
+            application_id: BUILTIN_AUTH_AUDIENCE.to_string(),
+            domain: IssuerUrl::new(BUILTIN_AUTH_ISSUER.to_string())?,
+            jwks_cache_ttl_seconds: None,
+            magic_link_action: Some("auth:sendMagicLink".to_string()),
+            passkey_origin: None,
+        }])
+        .await?;
+    application.commit_test(tx).await?;
+    let send = |email: &str, ip: IpAddr| {
+        application.builtin_auth_send_magic_link(
+            RequestId::new(),
+            email.to_string(),
+            ClientVersion::unknown(),
+            Some(ip),
+        )
+    };
+
+    // Sending links to one email from many addresses locks out the email.
+    for i in 0..*BUILTIN_AUTH_SIGN_IN_LOCKOUT_THRESHOLD {
+        send("bob@example.com", IpAddr::from([203, 0, 113, i as u8])).await?;
+    }
+    let err = send("bob@example.com", "198.51.100.1".parse()?)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TooManySignInAttempts");
+
+    // Sending links to many emails from one address locks out the address.
+    let ip: IpAddr = "198.51.100.2".parse()?;
+    for i in 0..*BUILTIN_AUTH_SIGN_IN_LOCKOUT_THRESHOLD {
+        send(&format!("user{i}@example.com"), ip).await?;
+    }
+    let err = send("carol@example.com", ip).await.unwrap_err();
+    assert_eq!(err.short_msg(), "TooManySignInAttempts");
+
+    // None of the links created a user.
+    let mut tx = application.begin(Identity::system()).await?;
+    assert!(BuiltinAuthModel::new(&mut tx)
+        .get_user_by_email("bob@example.com")
+        .await?
+        .is_none());
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_builtin_auth_passkey_options(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    enable_builtin_auth(&application, None).await?;
+    application
+        .builtin_auth_sign_up("ada@example.com".to_string(), "correct horse".to_string())
+        .await?;
+    let tokens = application
+        .builtin_auth_sign_in(
+            "ada@example.com".to_string(),
+            "correct horse".to_string(),
+            None,
+        )
+        .await?;
+    let identity = application
+        .authenticate(AuthenticationToken::User(tokens.token), rt.system_time())
+        .await?;
+
+    let err = application
+        .builtin_auth_passkey_sign_in_options()
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "PasskeysDisabled");
+    enable_builtin_auth(&application, Some("https://example.com/")).await?;
+
+    // Only built-in auth users can register passkeys.
+    let err = application
+        .builtin_auth_passkey_registration_options(Identity::system())
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "BuiltinAuthRequired");
+    let options = application
+        .builtin_auth_passkey_registration_options(identity.clone())
+        .await?;
+    assert_eq!(options["rp"]["id"], "example.com");
+    assert_eq!(options["user"]["name"], "ada@example.com");
+
+    // Sign-in challenges can't be used to register a passkey.
+    let options = application.builtin_auth_passkey_sign_in_options().await?;
+    assert_eq!(options["rpId"], "example.com");
+    let client_data_json = json!({
+        "type": "webauthn.create",
+        "challenge": options["challenge"],
+        "origin": "https://example.com",
+    });
+    let err = application
+        .builtin_auth_register_passkey(
+            identity,
+            encode_urlsafe(&serde_json::to_vec(&client_data_json)?),
+            encode_urlsafe(b"attestation"),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidPasskeyChallenge");
+
+    // Failed sign-ins still use up their challenge.
+    let options = application.builtin_auth_passkey_sign_in_options().await?;
+    let client_data_json = encode_urlsafe(&serde_json::to_vec(&json!({
+        "type": "webauthn.get",
+        "challenge": options["challenge"],
+        "origin": "https://example.com",
+    }))?);
+    let sign_in = || {
+        application.builtin_auth_passkey_sign_in(
+            encode_urlsafe(b"credential"),
+            client_data_json.clone(),
+            encode_urlsafe(b"authenticator data"),
+            encode_urlsafe(b"signature"),
+        )
+    };
+    let err = sign_in().await.unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidPasskeyAssertion");
+    let err = sign_in().await.unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidPasskeyChallenge");
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use common::{
     bootstrap_model::components::ComponentState,
     components::{
@@ -5,23 +7,38 @@ use common::{
         ComponentId,
         ComponentPath,
     },
+    query::{
+        Order,
+        Query,
+    },
     runtime::Runtime,
     testing::assert_contains,
     types::{
         EnvironmentVariable,
         FunctionCaller,
     },
+    version::Version,
     RequestId,
 };
 use database::{
+    query::TableFilter,
     BootstrapComponentsModel,
+    DeveloperQuery,
     TableModel,
     UserFacingModel,
+    COMPONENTS_VIRTUAL_TABLE,
 };
 use errors::ErrorMetadataAnyhowExt;
 use futures::FutureExt;
 use itertools::Itertools;
 use keybroker::Identity;
+use model::{
+    component_limits::types::ComponentLimits,
+    traffic_splits::types::{
+        TrafficSplit,
+        TrafficSplitVariant,
+    },
+};
 use must_let::must_let;
 use runtime::testing::TestRuntime;
 use serde_json::{
@@ -29,6 +46,7 @@ use serde_json::{
     Value as JsonValue,
 };
 use sync_types::CanonicalizedUdfPath;
+use udf::validation::validate_schedule_args;
 use value::{
     assert_obj,
     ConvexObject,
@@ -373,6 +391,37 @@ async fn test_writes_to_unmounted_tables_fails(rt: TestRuntime) -> anyhow::Resul
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_schedule_into_unmounted_component_fails(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    unmount_component(&application).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let now = rt.unix_timestamp();
+    let args = vec![JsonValue::from(example_message())];
+    let path = CanonicalizedComponentFunctionPath {
+        component: component_path(),
+        udf_path: "messages:insertMessage".parse()?,
+    };
+    let err = validate_schedule_args(path, args.clone(), now, now, &mut tx)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "UnmountedComponent");
+
+    let path = CanonicalizedComponentFunctionPath {
+        component: ComponentPath::deserialize(Some("missing"))?,
+        udf_path: "messages:insertMessage".parse()?,
+    };
+    let err = validate_schedule_args(path, args, now, now, &mut tx)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidScheduledFunction");
+    assert_contains(
+        &err.user_facing_message(),
+        "nonexistent component 'missing'",
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_data_exists_in_unmounted_components(rt: TestRuntime) -> anyhow::Result<()> {
     let application = Application::new_for_tests(&rt).await?;
@@ -415,7 +464,7 @@ async fn test_delete_unmounted_component_deletes_component(rt: TestRuntime) -> a
         .unwrap();
     assert!(matches!(component.state, ComponentState::Unmounted));
     application
-        .delete_component(&system_identity, component_id)
+        .delete_component(&system_identity, component_id, false)
         .await?;
     let mut tx = application.begin(system_identity).await?;
     let component = BootstrapComponentsModel::new(&mut tx)
@@ -437,7 +486,7 @@ async fn test_delete_unmounted_component_deletes_component_tables(
     assert!(TableModel::new(&mut tx).table_exists(table_namespace, &table_name()));
 
     application
-        .delete_component(&Identity::system(), component_id)
+        .delete_component(&Identity::system(), component_id, false)
         .await?;
 
     let mut tx = application.begin(Identity::system()).await?;
@@ -457,7 +506,7 @@ async fn test_delete_unmounted_component_deletes_component_system_tables(
     assert!(TableModel::new(&mut tx).table_exists(table_namespace, &system_table_name()));
 
     application
-        .delete_component(&Identity::system(), component_id)
+        .delete_component(&Identity::system(), component_id, false)
         .await?;
 
     let mut tx = application.begin(Identity::system()).await?;
@@ -473,7 +522,7 @@ async fn test_mounted_component_delete_component_errors_out(rt: TestRuntime) ->
     let (_, component_id) =
         BootstrapComponentsModel::new(&mut tx).must_component_path_to_ids(&component_path())?;
     assert!(application
-        .delete_component(&Identity::system(), component_id)
+        .delete_component(&Identity::system(), component_id, false)
         .await
         .is_err());
     Ok(())
@@ -489,3 +538,304 @@ async fn test_infinite_loop_in_component(rt: TestRuntime) -> anyhow::Result<()>
     assert_contains(&err.error, "Cross component call depth limit exceeded");
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_rollback_component(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let component_id = unmount_component(&application).await?;
+    let err = application
+        .rollback_component(Identity::system(), component_id, 1)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "UnmountedComponent");
+
+    // Remounting records a second version, which we roll back to the first.
+    application.load_component_tests_modules("mounted").await?;
+    let versions = application
+        .list_component_versions(Identity::system(), component_id)
+        .await?;
+    assert_eq!(versions.iter().map(|v| v.version).collect_vec(), vec![2, 1]);
+    let new_version = application
+        .rollback_component(Identity::system(), component_id, 1)
+        .await?;
+    assert_eq!(new_version, 3);
+    let versions = application
+        .list_component_versions(Identity::system(), component_id)
+        .await?;
+    assert_eq!(versions[0].restored_from, Some(1));
+    assert_eq!(versions[0].modules, versions[2].modules);
+
+    run_component_function(
+        &application,
+        "messages:insertMessage".parse()?,
+        vec![example_message().into()],
+        component_path(),
+    )
+    .await??;
+
+    let err = application
+        .rollback_component(Identity::system(), component_id, 10)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "ComponentVersionNotFound");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_bundle_size_report(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let component_id = unmount_component(&application).await?;
+    application.load_component_tests_modules("mounted").await?;
+    let report = application
+        .bundle_size_report(Identity::system(), component_id, None)
+        .await?
+        .expect("component should have a version");
+    assert_eq!(report.version, 2);
+    assert_eq!(report.previous_version, Some(1));
+    assert!(report.total_source_size > 0);
+    // The same modules were pushed again, so nothing changed.
+    assert_eq!(report.total_source_size_delta, Some(0));
+    assert!(report.removed_modules.is_empty());
+    assert!(report.added_dependencies.is_empty());
+
+    let report = application
+        .bundle_size_report(Identity::system(), component_id, Some(1))
+        .await?
+        .expect("version 1 should exist");
+    assert_eq!(report.previous_version, None);
+    assert_eq!(report.total_source_size_delta, None);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_traffic_split(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_component_tests_modules("mounted").await?;
+    application.load_component_tests_modules("mounted").await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let (_, component_id) =
+        BootstrapComponentsModel::new(&mut tx).must_component_path_to_ids(&component_path())?;
+
+    let split = TrafficSplit {
+        component: component_id,
+        stable_version: 1,
+        candidate_version: 2,
+        default_percent: 101,
+        function_percents: BTreeMap::new(),
+    };
+    let err = application
+        .set_traffic_split(Identity::system(), split.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidTrafficSplit");
+    let err = application
+        .set_traffic_split(
+            Identity::system(),
+            TrafficSplit {
+                candidate_version: 10,
+                ..split.clone()
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "ComponentVersionNotFound");
+
+    // Route every call to the candidate.
+    application
+        .set_traffic_split(
+            Identity::system(),
+            TrafficSplit {
+                default_percent: 100,
+                ..split
+            },
+        )
+        .await?;
+    run_component_function(
+        &application,
+        "messages:insertMessage".parse()?,
+        vec![example_message().into()],
+        component_path(),
+    )
+    .await??;
+    let stats = application.traffic_split_stats(Identity::system(), component_id)?;
+    let function_stats = &stats[&"messages:insertMessage".parse::<CanonicalizedUdfPath>()?];
+    assert_eq!(
+        function_stats[&TrafficSplitVariant::Candidate].executions,
+        1
+    );
+    assert!(!function_stats.contains_key(&TrafficSplitVariant::Stable));
+
+    let new_version = application
+        .promote_traffic_split(Identity::system(), component_id)
+        .await?;
+    assert_eq!(new_version, 3);
+    assert!(application
+        .get_traffic_split(Identity::system(), component_id)
+        .await?
+        .is_none());
+    assert!(application
+        .traffic_split_stats(Identity::system(), component_id)?
+        .is_empty());
+    let err = application
+        .rollback_traffic_split(Identity::system(), component_id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "NoTrafficSplit");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_component_limits(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_component_tests_modules("mounted").await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let (_, component_id) =
+        BootstrapComponentsModel::new(&mut tx).must_component_path_to_ids(&component_path())?;
+    for _ in 0..2 {
+        run_component_function(
+            &application,
+            "messages:insertMessage".parse()?,
+            vec![example_message().into()],
+            component_path(),
+        )
+        .await??;
+    }
+
+    let limits = ComponentLimits {
+        component: component_id,
+        max_documents_read: Some(0),
+        function_timeout: None,
+        max_scheduled_functions: None,
+        max_heap_size: None,
+        max_storage_bytes: None,
+        max_storage_files: None,
+    };
+    let err = application
+        .set_component_limits(Identity::system(), limits.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidComponentLimits");
+
+    application
+        .set_component_limits(
+            Identity::system(),
+            ComponentLimits {
+                max_documents_read: Some(1),
+                ..limits.clone()
+            },
+        )
+        .await?;
+    assert_eq!(
+        application
+            .get_component_limits(Identity::system(), component_id)
+            .await?
+            .and_then(|limits| limits.max_documents_read),
+        Some(1)
+    );
+    let err = run_component_function(
+        &application,
+        "messages:listMessages".parse()?,
+        vec![],
+        component_path(),
+    )
+    .await?
+    .unwrap_err();
+    assert_contains(&err.error, "Too many documents read");
+    // The root component keeps the deployment-wide limits.
+    assert!(application
+        .get_component_limits(Identity::system(), ComponentId::Root)
+        .await?
+        .is_none());
+
+    // Setting no limits removes the overrides.
+    application
+        .set_component_limits(
+            Identity::system(),
+            ComponentLimits {
+                max_documents_read: None,
+                ..limits
+            },
+        )
+        .await?;
+    assert!(application
+        .get_component_limits(Identity::system(), component_id)
+        .await?
+        .is_none());
+    run_component_function(
+        &application,
+        "messages:listMessages".parse()?,
+        vec![],
+        component_path(),
+    )
+    .await??;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_audit_component(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_component_tests_modules("mounted").await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let (_, component_id) =
+        BootstrapComponentsModel::new(&mut tx).must_component_path_to_ids(&component_path())?;
+    for _ in 0..2 {
+        run_component_function(
+            &application,
+            "messages:insertMessage".parse()?,
+            vec![example_message().into()],
+            component_path(),
+        )
+        .await??;
+    }
+
+    let audit = application
+        .audit_component(Identity::system(), component_id)
+        .await?;
+    assert_eq!(audit.path, component_path());
+    assert_eq!(
+        audit.tables.get(&"messages".parse::<TableName>()?),
+        Some(&2)
+    );
+    assert!(audit.environment_variables.is_empty());
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_component_tree_virtual_table(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_component_tests_modules("mounted").await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut query = DeveloperQuery::new_with_version(
+        &mut tx,
+        TableNamespace::root_component(),
+        Query::full_table_scan(COMPONENTS_VIRTUAL_TABLE.clone(), Order::Asc),
+        Some(Version::new(1, 17, 0)),
+        TableFilter::ExcludePrivateSystemTables,
+    )?;
+    let mut paths = BTreeMap::new();
+    while let Some(doc) = query.next(&mut tx, None).await? {
+        must_let!(let Some(ConvexValue::String(path)) = doc.value().get("path"));
+        must_let!(let Some(ConvexValue::String(state)) = doc.value().get("state"));
+        paths.insert(String::from(path.clone()), String::from(state.clone()));
+    }
+    assert_eq!(paths.get(""), Some(&"active".to_string()));
+    assert_eq!(
+        paths.get(&String::from(component_path())),
+        Some(&"active".to_string())
+    );
+
+    // Child components can't see where they are mounted.
+    let (_, component_id) =
+        BootstrapComponentsModel::new(&mut tx).must_component_path_to_ids(&component_path())?;
+    let err = DeveloperQuery::new_with_version(
+        &mut tx,
+        component_id.into(),
+        Query::full_table_scan(COMPONENTS_VIRTUAL_TABLE.clone(), Order::Asc),
+        Some(Version::new(1, 17, 0)),
+        TableFilter::ExcludePrivateSystemTables,
+    )
+    .err()
+    .unwrap();
+    assert!(err.is_forbidden());
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    ResolvedQuery,
+    UserFacingModel,
+};
+use keybroker::Identity;
+use model::data_migrations::{
+    types::{
+        DataMigration,
+        DataMigrationState,
+    },
+    DataMigrationsModel,
+};
+use runtime::testing::TestRuntime;
+use value::{
+    assert_obj,
+    ConvexValue,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+async fn wait_for_migration<RT: Runtime>(
+    application: &Application<RT>,
+    name: &str,
+    done: impl Fn(&DataMigrationState) -> bool,
+) -> anyhow::Result<DataMigration> {
+    loop {
+        let mut tx = application.begin(Identity::system()).await?;
+        let migration = DataMigrationsModel::new(&mut tx)
+            .get_by_name(name)
+            .await?
+            .unwrap()
+            .into_value();
+        if done(&migration.state) {
+            return Ok(migration);
+        }
+        let subscription = application.database().subscribe(tx.into_token()?).await?;
+        subscription.wait_for_invalidation().await;
+    }
+}
+
+async fn num_migrated<RT: Runtime>(application: &Application<RT>) -> anyhow::Result<usize> {
+    let mut tx = application.begin(Identity::system()).await?;
+    let table: TableName = "objects".parse()?;
+    let query = Query::full_table_scan(table, Order::Asc);
+    let mut query_stream = ResolvedQuery::new(&mut tx, TableNamespace::test_user(), query)?;
+    let mut num_migrated = 0;
+    while let Some(doc) = query_stream.next(&mut tx, None).await? {
+        if doc.value().get("migrated") == Some(&ConvexValue::Boolean(true)) {
+            num_migrated += 1;
+        }
+    }
+    Ok(num_migrated)
+}
+
+#[convex_macro::test_runtime]
+async fn test_real_run_after_dry_run(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    for i in 0..5 {
+        UserFacingModel::new(&mut tx, TableNamespace::test_user())
+            .insert("objects".parse()?, assert_obj!("i" => i))
+            .await?;
+    }
+    application.commit_test(tx).await?;
+
+    let path = CanonicalizedComponentFunctionPath {
+        component: ComponentPath::root(),
+        udf_path: "basic.js:migrateObjects".parse()?,
+    };
+    application
+        .register_data_migration(
+            &Identity::system(),
+            DataMigration::new("mark_migrated".to_string(), 1, path, true),
+        )
+        .await?;
+    let migration = wait_for_migration(&application, "mark_migrated", |state| {
+        matches!(state, DataMigrationState::DryRunCompleted { .. })
+    })
+    .await?;
+    assert_eq!(migration.state.num_processed(), 5);
+    assert_eq!(num_migrated(&application).await?, 0);
+
+    application
+        .start_data_migration_real_run(&Identity::system(), "mark_migrated")
+        .await?;
+    let migration = wait_for_migration(&application, "mark_migrated", |state| {
+        matches!(state, DataMigrationState::Completed { .. })
+    })
+    .await?;
+    assert!(!migration.dry_run);
+    assert_eq!(migration.state.num_processed(), 5);
+    assert_eq!(num_migrated(&application).await?, 5);
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use common::{
+    query::{
+        Order,
+        Query,
+    },
+    version::Version,
+};
+use database::{
+    query::TableFilter,
+    DeveloperQuery,
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use model::{
+    deployment_config::DEPLOYMENT_CONFIG_VIRTUAL_TABLE,
+    environment_variables::types::EnvironmentVariable,
+};
+use runtime::testing::TestRuntime;
+use value::{
+    ConvexValue,
+    TableNamespace,
+};
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+fn deployment_config_query() -> Query {
+    Query::full_table_scan(DEPLOYMENT_CONFIG_VIRTUAL_TABLE.clone(), Order::Asc)
+}
+
+#[convex_macro::test_runtime]
+async fn test_deployment_config_virtual_table(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    application
+        .create_environment_variables(
+            &mut tx,
+            vec![EnvironmentVariable::new(
+                "API_KEY".parse()?,
+                "hunter2".parse()?,
+            )],
+        )
+        .await?;
+    application.commit_test(tx).await?;
+
+    // Wait for the worker to pick up the new variable.
+    let config = loop {
+        let mut tx = application.begin(Identity::system()).await?;
+        let mut query = DeveloperQuery::new_with_version(
+            &mut tx,
+            TableNamespace::root_component(),
+            deployment_config_query(),
+            Some(Version::new(1, 17, 0)),
+            TableFilter::ExcludePrivateSystemTables,
+        )?;
+        let doc = query.next(&mut tx, None).await?;
+        if let Some(doc) = doc {
+            let names = doc.value().get("environmentVariableNames").cloned();
+            let expected = vec![ConvexValue::try_from("API_KEY".to_string())?];
+            if names == Some(ConvexValue::Array(expected.try_into()?)) {
+                break doc.into_value().0;
+            }
+        }
+        let subscription = application.subscribe(tx.into_token()?).await?;
+        subscription.wait_for_invalidation().await;
+    };
+    assert_eq!(
+        config.get("backendState"),
+        Some(&ConvexValue::try_from("running".to_string())?)
+    );
+    assert!(!format!("{config:?}").contains("hunter2"));
+
+    let mut tx = application.begin(Identity::Unknown).await?;
+    let err = DeveloperQuery::new_with_version(
+        &mut tx,
+        TableNamespace::root_component(),
+        deployment_config_query(),
+        Some(Version::new(1, 17, 0)),
+        TableFilter::ExcludePrivateSystemTables,
+    )
+    .err()
+    .unwrap();
+    assert!(err.is_forbidden());
+    Ok(())
+}
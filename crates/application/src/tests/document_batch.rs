@@ -0,0 +1,141 @@
+use database::UserFacingModel;
+use errors::ErrorMetadataAnyhowExt;
+use http::StatusCode;
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use serde_json::json;
+use value::{
+    ConvexValue,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    document_batch::{
+        DocumentBatchOperation,
+        DocumentBatchOperationResult,
+        DocumentBatchTarget,
+    },
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_document_batch_resolves_temp_ids(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let namespace = TableNamespace::test_user();
+    let users: TableName = "users".parse()?;
+    let messages: TableName = "messages".parse()?;
+
+    let result = application
+        .execute_document_batch(
+            Identity::system(),
+            namespace,
+            vec![
+                DocumentBatchOperation::Insert {
+                    table_name: users.clone(),
+                    temp_id: Some("alice".to_string()),
+                    value: json!({ "name": "alice" }),
+                },
+                DocumentBatchOperation::Insert {
+                    table_name: messages.clone(),
+                    temp_id: Some("hello".to_string()),
+                    value: json!({ "author": { "$tempId": "alice" }, "body": "hi" }),
+                },
+                DocumentBatchOperation::Patch {
+                    target: DocumentBatchTarget::TempId("alice".to_string()),
+                    value: json!({ "lastMessage": { "$tempId": "hello" } }),
+                    expected_version: None,
+                },
+            ],
+        )
+        .await?;
+    assert_eq!(result.results.len(), 3);
+    let alice = result.temp_ids["alice"];
+    let hello = result.temp_ids["hello"];
+    let DocumentBatchOperationResult::Updated { ref document } = result.results[2] else {
+        panic!("Expected patch result, got {:?}", result.results[2]);
+    };
+    assert_eq!(document.id(), alice);
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let message = UserFacingModel::new(&mut tx, namespace)
+        .get(hello, None)
+        .await?
+        .unwrap();
+    assert_eq!(
+        message.into_value().0.get("author"),
+        Some(&ConvexValue::try_from(alice.encode())?)
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_document_batch_is_atomic(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let namespace = TableNamespace::test_user();
+    let users: TableName = "users".parse()?;
+
+    let err = application
+        .execute_document_batch(
+            Identity::system(),
+            namespace,
+            vec![
+                DocumentBatchOperation::Insert {
+                    table_name: users.clone(),
+                    temp_id: None,
+                    value: json!({ "name": "alice" }),
+                },
+                DocumentBatchOperation::Delete {
+                    target: DocumentBatchTarget::TempId("missing".to_string()),
+                },
+            ],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "UnknownTempId");
+    assert!(err.msg().starts_with("Operation 1 failed"));
+
+    // The insert before the failing operation didn't commit.
+    let mut tx = application.begin(Identity::system()).await?;
+    assert!(!tx.table_mapping().namespace(namespace).name_exists(&users));
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_document_batch_version_conflict(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let namespace = TableNamespace::test_user();
+    let users: TableName = "users".parse()?;
+
+    let inserted = application
+        .execute_document_batch(
+            Identity::system(),
+            namespace,
+            vec![DocumentBatchOperation::Insert {
+                table_name: users.clone(),
+                temp_id: Some("alice".to_string()),
+                value: json!({ "name": "alice" }),
+            }],
+        )
+        .await?;
+    let alice = inserted.temp_ids["alice"];
+    let patch = |name: &str| DocumentBatchOperation::Patch {
+        target: DocumentBatchTarget::Id(alice),
+        value: json!({ "name": name }),
+        expected_version: Some(inserted.ts),
+    };
+
+    application
+        .execute_document_batch(Identity::system(), namespace, vec![patch("bob")])
+        .await?;
+    // The first patch changed the version, so a second write expecting the
+    // insert's version is rejected with a conflict rather than a bad request.
+    let err = application
+        .execute_document_batch(Identity::system(), namespace, vec![patch("carol")])
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "DocumentVersionConflict");
+    assert_eq!(err.http_status(), StatusCode::CONFLICT);
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use common::{
+    components::ComponentId,
+    query::Order,
+};
+use database::UserFacingModel;
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::{
+    testing::TestUserIdentity,
+    Identity,
+    UserIdentity,
+};
+use model::http_table_access::types::{
+    HttpTableAccess,
+    HttpTableAccessLevel,
+};
+use runtime::testing::TestRuntime;
+use value::{
+    assert_obj,
+    ConvexValue,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    document_query::{
+        DocumentFilter,
+        DocumentFilterOp,
+        DocumentQuery,
+    },
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_query_documents(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let namespace = TableNamespace::test_user();
+    let table_name: TableName = "messages".parse()?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut ids = vec![];
+    for i in 0..3 {
+        ids.push(
+            UserFacingModel::new(&mut tx, namespace)
+                .insert(table_name.clone(), assert_obj!("i" => i))
+                .await?,
+        );
+    }
+    application.commit_test(tx).await?;
+
+    // Unfiltered queries read in creation order.
+    let result = application
+        .query_documents(
+            Identity::system(),
+            namespace,
+            DocumentQuery {
+                table_name: table_name.clone(),
+                index: None,
+                filters: vec![],
+                order: Order::Desc,
+                limit: 2,
+            },
+        )
+        .await?;
+    assert!(result.index_name.is_creation_time());
+    let returned: Vec<_> = result.documents.iter().map(|d| d.id()).collect();
+    assert_eq!(returned, vec![ids[2], ids[1]]);
+
+    // Filters on `_id` are served by the `by_id` index.
+    let result = application
+        .query_documents(
+            Identity::system(),
+            namespace,
+            DocumentQuery {
+                table_name: table_name.clone(),
+                index: None,
+                filters: vec![DocumentFilter {
+                    field: "_id".parse()?,
+                    op: DocumentFilterOp::Eq,
+                    value: ConvexValue::try_from(ids[1].encode())?,
+                }],
+                order: Order::Asc,
+                limit: 10,
+            },
+        )
+        .await?;
+    assert!(result.index_name.is_by_id());
+    assert_eq!(result.documents.len(), 1);
+    assert_eq!(result.documents[0].id(), ids[1]);
+
+    // Filters no index can serve are rejected instead of scanning the table.
+    let err = application
+        .query_documents(
+            Identity::system(),
+            namespace,
+            DocumentQuery {
+                table_name,
+                index: None,
+                filters: vec![DocumentFilter {
+                    field: "i".parse()?,
+                    op: DocumentFilterOp::Eq,
+                    value: ConvexValue::from(1i64),
+                }],
+                order: Order::Asc,
+                limit: 10,
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "NonIndexableFilter");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_documents_table_access(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let namespace = TableNamespace::test_user();
+    let table_name: TableName = "messages".parse()?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    UserFacingModel::new(&mut tx, namespace)
+        .insert(table_name.clone(), assert_obj!("i" => 0))
+        .await?;
+    application.commit_test(tx).await?;
+
+    let query = || DocumentQuery {
+        table_name: table_name.clone(),
+        index: None,
+        filters: vec![],
+        order: Order::Asc,
+        limit: 10,
+    };
+    let user = || Identity::user(UserIdentity::test());
+
+    // Tables are admin-only until their access is opened up.
+    let err = application
+        .query_documents(user(), namespace, query())
+        .await
+        .unwrap_err();
+    assert!(err.is_forbidden());
+    assert_eq!(err.short_msg(), "TableAccessDenied");
+
+    application
+        .set_http_table_access(
+            Identity::system(),
+            HttpTableAccess {
+                component: ComponentId::test_user(),
+                table_name: table_name.clone(),
+                read: HttpTableAccessLevel::Authenticated,
+                write: HttpTableAccessLevel::Admin,
+            },
+        )
+        .await?;
+    let result = application
+        .query_documents(user(), namespace, query())
+        .await?;
+    assert_eq!(result.documents.len(), 1);
+    let err = application
+        .query_documents(Identity::Unknown, namespace, query())
+        .await
+        .unwrap_err();
+    assert!(err.is_unauthenticated());
+
+    let access = application
+        .list_http_table_access(Identity::system(), ComponentId::test_user())
+        .await?;
+    assert_eq!(access.len(), 1);
+    assert_eq!(access[0].read, HttpTableAccessLevel::Authenticated);
+
+    // Only admins can change access.
+    let err = application
+        .set_http_table_access(
+            user(),
+            HttpTableAccess::admin_only(ComponentId::test_user(), table_name.clone()),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.is_forbidden());
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    types::{
+        FunctionCaller,
+        MemberId,
+        UdfType,
+    },
+    RequestId,
+};
+use futures::TryStreamExt;
+use keybroker::{
+    testing::TestUserIdentity,
+    AdminIdentity,
+    Identity,
+};
+use model::deployment_audit_log::{
+    types::DeploymentAuditLogEvent,
+    DeploymentAuditLogModel,
+};
+use runtime::testing::TestRuntime;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use sync_types::UserIdentityAttributes;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+fn get_name_path() -> anyhow::Result<CanonicalizedComponentFunctionPath> {
+    Ok(CanonicalizedComponentFunctionPath {
+        component: ComponentPath::test_user(),
+        udf_path: "auth:getName".parse()?,
+    })
+}
+
+#[convex_macro::test_runtime]
+async fn test_run_as_user(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let admin = Identity::InstanceAdmin(AdminIdentity::new_for_test_only(
+        "test".to_string(),
+        MemberId(1),
+    ));
+    let result = application
+        .run_as_user(
+            RequestId::new(),
+            get_name_path()?,
+            vec![json!({})],
+            admin,
+            UserIdentityAttributes::test(),
+            FunctionCaller::Test,
+        )
+        .await??;
+    assert_eq!(JsonValue::from(result.value), json!("bozo"));
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let events: Vec<_> = DeploymentAuditLogModel::new(&mut tx)
+        .list()
+        .try_collect()
+        .await?;
+    let impersonations: Vec<_> = events
+        .into_iter()
+        .map(|event| event.into_value())
+        .filter(|event| matches!(event, DeploymentAuditLogEvent::ImpersonateUser { .. }))
+        .collect();
+    assert_eq!(
+        impersonations,
+        vec![DeploymentAuditLogEvent::ImpersonateUser {
+            component: ComponentPath::test_user(),
+            udf_path: "auth.js:getName".to_string(),
+            udf_type: UdfType::Query,
+            subject: Some("fake_user".to_string()),
+            issuer: Some("convex".to_string()),
+        }]
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_run_as_user_requires_admin(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let result = application
+        .run_as_user(
+            RequestId::new(),
+            get_name_path()?,
+            vec![json!({})],
+            Identity::system(),
+            UserIdentityAttributes::test(),
+            FunctionCaller::Test,
+        )
+        .await;
+    assert!(result.is_err());
+    Ok(())
+}
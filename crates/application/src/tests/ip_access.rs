@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use common::{
+    runtime::Runtime,
+    types::MemberId,
+};
+use errors::ErrorMetadataAnyhowExt;
+use futures::TryStreamExt;
+use keybroker::{
+    AdminIdentity,
+    Identity,
+};
+use maplit::btreemap;
+use model::{
+    deployment_audit_log::{
+        types::DeploymentAuditLogEvent,
+        DeploymentAuditLogModel,
+    },
+    ip_access_policy::{
+        types::{
+            IpAccessDenialReason,
+            IpAccessIdentity,
+            IpAccessPolicy,
+            IpAccessRules,
+            IpAccessSurface,
+        },
+        IpAccessPolicyModel,
+    },
+};
+use runtime::testing::TestRuntime;
+use sync_types::AuthenticationToken;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_ip_access_denied_and_audited(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let admin = Identity::InstanceAdmin(AdminIdentity::new_for_test_only(
+        "test".to_string(),
+        MemberId(1),
+    ));
+    let admin_ip = "10.0.0.1".parse()?;
+    let blocked_ip = "203.0.113.7".parse()?;
+
+    // Admins can't deny their own address on the admin APIs.
+    let lockout = IpAccessPolicy {
+        admin: IpAccessRules {
+            allowed: vec!["192.168.0.0/16".parse()?],
+            denied: vec![],
+        },
+        ..Default::default()
+    };
+    let err = application
+        .set_ip_access_policy(admin.clone(), lockout, Some(admin_ip))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "IpAccessPolicyLockout");
+
+    let policy = IpAccessPolicy {
+        admin: IpAccessRules {
+            allowed: vec!["10.0.0.0/8".parse()?],
+            denied: vec![],
+        },
+        client: IpAccessRules {
+            allowed: vec![],
+            denied: vec!["203.0.113.7".parse()?],
+        },
+        ..Default::default()
+    };
+    application
+        .set_ip_access_policy(admin.clone(), policy.clone(), Some(admin_ip))
+        .await?;
+    assert_eq!(application.get_ip_access_policy(admin).await?, policy);
+
+    application
+        .check_ip_access(IpAccessSurface::Admin, Some(admin_ip))
+        .await?;
+    application
+        .check_ip_access(IpAccessSurface::HttpActions, Some(blocked_ip))
+        .await?;
+    application
+        .check_ip_access(IpAccessSurface::Client, None)
+        .await?;
+    for _ in 0..2 {
+        let err = application
+            .check_ip_access(IpAccessSurface::Client, Some(blocked_ip))
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "IpAddressNotAllowed");
+    }
+
+    // Repeated denials within a minute are only recorded once.
+    let mut tx = application.begin(Identity::system()).await?;
+    let denials: Vec<_> = DeploymentAuditLogModel::new(&mut tx)
+        .list()
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .map(|event| event.into_value())
+        .filter(|event| matches!(event, DeploymentAuditLogEvent::IpAccessDenied { .. }))
+        .collect();
+    assert_eq!(
+        denials,
+        vec![DeploymentAuditLogEvent::IpAccessDenied {
+            ip: blocked_ip,
+            surface: IpAccessSurface::Client,
+            reason: IpAccessDenialReason::Denied,
+        }]
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_identity_ip_access_rules(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let admin = Identity::InstanceAdmin(AdminIdentity::new_for_test_only(
+        "test".to_string(),
+        MemberId(1),
+    ));
+    let admin_ip = "10.0.0.1".parse()?;
+    let other_ip = "203.0.113.7".parse()?;
+    let office_only = IpAccessRules {
+        allowed: vec!["10.0.0.0/8".parse()?],
+        denied: vec![],
+    };
+
+    // Admins can't restrict themselves away from their own address.
+    let lockout = IpAccessPolicy {
+        identities: btreemap! {
+            IpAccessIdentity::Member(MemberId(1)) => IpAccessRules {
+                allowed: vec!["192.168.0.0/16".parse()?],
+                denied: vec![],
+            },
+        },
+        ..Default::default()
+    };
+    let err = application
+        .set_ip_access_policy(admin.clone(), lockout, Some(admin_ip))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "IpAccessPolicyLockout");
+
+    let policy = IpAccessPolicy {
+        identities: btreemap! {
+            IpAccessIdentity::Member(MemberId(2)) => office_only,
+        },
+        ..Default::default()
+    };
+    application
+        .set_ip_access_policy(admin, policy, Some(admin_ip))
+        .await?;
+    let admin_key = application.key_broker().issue_admin_key(MemberId(2));
+    let token = || AuthenticationToken::Admin(admin_key.as_string(), None);
+    application
+        .authenticate_from(token(), rt.system_time(), Some(admin_ip))
+        .await?;
+    application
+        .authenticate_from(token(), rt.system_time(), None)
+        .await?;
+    let err = application
+        .authenticate_from(token(), rt.system_time(), Some(other_ip))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "IpAddressNotAllowed");
+    // Other addresses are only restricted for that identity.
+    application
+        .check_ip_access(IpAccessSurface::Admin, Some(other_ip))
+        .await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let denials: Vec<_> = DeploymentAuditLogModel::new(&mut tx)
+        .list()
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .map(|event| event.into_value())
+        .filter(|event| {
+            matches!(
+                event,
+                DeploymentAuditLogEvent::IdentityIpAccessDenied { .. }
+            )
+        })
+        .collect();
+    assert_eq!(
+        denials,
+        vec![DeploymentAuditLogEvent::IdentityIpAccessDenied {
+            ip: other_ip,
+            identity: IpAccessIdentity::Member(MemberId(2)),
+            reason: IpAccessDenialReason::NotAllowed,
+        }]
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_ip_access_policy_cache_follows_writes(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let blocked_ip = "203.0.113.7".parse()?;
+    application
+        .check_ip_access(IpAccessSurface::Client, Some(blocked_ip))
+        .await?;
+
+    // Policies written without `set_ip_access_policy`, like by a snapshot
+    // import, apply once the worker rereads the policy.
+    let mut tx = application.begin(Identity::system()).await?;
+    IpAccessPolicyModel::new(&mut tx)
+        .set(IpAccessPolicy {
+            global: IpAccessRules {
+                allowed: vec![],
+                denied: vec!["203.0.113.0/24".parse()?],
+            },
+            ..Default::default()
+        })
+        .await?;
+    application.commit_test(tx).await?;
+    for _ in 0..100 {
+        match application
+            .check_ip_access(IpAccessSurface::Client, Some(blocked_ip))
+            .await
+        {
+            Ok(()) => rt.wait(Duration::from_millis(10)).await,
+            Err(e) => {
+                assert_eq!(e.short_msg(), "IpAddressNotAllowed");
+                return Ok(());
+            },
+        }
+    }
+    panic!("IP access policy cache never picked up the new policy");
+}
@@ -1,16 +1,28 @@
+mod admin_key_lockout;
 mod analyze;
+mod anonymous_identities;
 mod auth_config;
+mod builtin_auth;
 pub mod components;
 mod cron_jobs;
+mod data_migrations;
+mod deployment_config;
+mod document_batch;
+mod document_query;
 mod environment_variables;
+mod impersonation;
+mod ip_access;
 mod mutation;
 mod occ_retries;
+mod preview_deployments;
 mod query_cache;
 mod returns_validation;
 mod scheduled_jobs;
 mod schema;
+mod seed;
 mod source_package;
 mod storage;
+mod table_clone;
 
 const NODE_SOURCE: &str = r#"
 var nodeFunction = () => {};
@@ -7,6 +7,7 @@ use common::{
         ComponentPath,
         PublicFunctionPath,
     },
+    execution_context::RequestContext,
     knobs::UDF_EXECUTOR_OCC_MAX_RETRIES,
     pause::PauseController,
     types::FunctionCaller,
@@ -50,6 +51,7 @@ async fn insert_object(application: &Application<TestRuntime>) -> anyhow::Result
             FunctionCaller::Action {
                 parent_scheduled_job: None,
             },
+            RequestContext::default(),
         )
         .await??;
     Ok(JsonValue::from(result.value))
@@ -70,6 +72,7 @@ async fn insert_and_count(application: &Application<TestRuntime>) -> anyhow::Res
             FunctionCaller::Action {
                 parent_scheduled_job: None,
             },
+            RequestContext::default(),
         )
         .await??;
     Ok(JsonValue::from(result.value)
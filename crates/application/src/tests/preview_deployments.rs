@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use common::query::Order;
+use database::TableModel;
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use serde_json::json;
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    document_batch::DocumentBatchOperation,
+    document_query::DocumentQuery,
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_preview_deployment_data_is_separate(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let messages: TableName = "messages".parse()?;
+
+    application
+        .create_preview_deployment(
+            Identity::system(),
+            "pr-1".to_string(),
+            Some(Duration::from_secs(3600)),
+        )
+        .await?;
+    let namespace = application
+        .preview_deployment_namespace(Identity::system(), "pr-1")
+        .await?;
+    application
+        .execute_document_batch(
+            Identity::system(),
+            namespace,
+            vec![DocumentBatchOperation::Insert {
+                table_name: messages.clone(),
+                temp_id: None,
+                value: json!({ "body": "preview" }),
+            }],
+        )
+        .await?;
+    let result = application
+        .query_documents(
+            Identity::system(),
+            namespace,
+            DocumentQuery {
+                table_name: messages.clone(),
+                index: None,
+                filters: vec![],
+                order: Order::Asc,
+                limit: 10,
+            },
+        )
+        .await?;
+    assert_eq!(result.documents.len(), 1);
+
+    let mut tx = application.begin(Identity::system()).await?;
+    assert!(!TableModel::new(&mut tx).table_exists(TableNamespace::Global, &messages));
+    assert!(TableModel::new(&mut tx).table_exists(namespace, &messages));
+
+    application
+        .delete_preview_deployment(Identity::system(), "pr-1")
+        .await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    assert!(!TableModel::new(&mut tx).table_exists(namespace, &messages));
+    let err = application
+        .preview_deployment_namespace(Identity::system(), "pr-1")
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+    Ok(())
+}
@@ -6,6 +6,7 @@ use common::{
         ComponentPath,
         PublicFunctionPath,
     },
+    execution_context::RequestContext,
     pause::PauseController,
     types::FunctionCaller,
     RequestId,
@@ -72,6 +73,7 @@ async fn insert_object(application: &Application<TestRuntime>) -> anyhow::Result
             FunctionCaller::Action {
                 parent_scheduled_job: None,
             },
+            RequestContext::default(),
         )
         .await??;
     Ok(result.value)
@@ -4,6 +4,7 @@ use common::{
         ComponentPath,
         PublicFunctionPath,
     },
+    execution_context::RequestContext,
     types::FunctionCaller,
     RequestId,
 };
@@ -41,6 +42,7 @@ async fn run_zero_arg_mutation(
             Identity::user(UserIdentity::test()),
             None,
             FunctionCaller::HttpEndpoint,
+            RequestContext::default(),
         )
         .await
 }
@@ -79,6 +81,7 @@ async fn run_zero_arg_action(
             vec![obj],
             Identity::user(UserIdentity::test()),
             FunctionCaller::HttpEndpoint,
+            RequestContext::default(),
         )
         .await
 }
@@ -6,37 +6,60 @@ use std::{
 use common::{
     components::{
         CanonicalizedComponentFunctionPath,
+        ComponentId,
         ComponentPath,
         PublicFunctionPath,
     },
-    execution_context::ExecutionContext,
+    document::ParsedDocument,
+    execution_context::{
+        ExecutionContext,
+        RequestContext,
+    },
     pause::{
         HoldGuard,
         PauseController,
     },
     runtime::Runtime,
+    testing::TestPersistence,
     types::FunctionCaller,
     RequestId,
 };
 use database::{
     BootstrapComponentsModel,
+    ResolvedQuery,
     TableModel,
     Transaction,
 };
-use errors::ErrorMetadata;
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
 use keybroker::Identity;
+use maplit::btreemap;
 use model::{
     backend_state::{
         types::BackendState,
         BackendStateModel,
     },
+    function_kill_switches::types::{
+        FunctionKillSwitch,
+        KillSwitchTarget,
+    },
     scheduled_jobs::{
-        types::ScheduledJobState,
+        types::{
+            ScheduleOptions,
+            SchedulePriority,
+            ScheduledJob,
+            ScheduledJobArgs,
+            ScheduledJobState,
+        },
+        unfinished_jobs_query,
         SchedulerModel,
     },
 };
 use runtime::testing::TestRuntime;
 use serde_json::Value as JsonValue;
+use storage::Upload;
 use sync_types::CanonicalizedUdfPath;
 use udf::helpers::parse_udf_args;
 use value::{
@@ -50,6 +73,7 @@ use crate::{
         SCHEDULED_JOB_EXECUTED,
     },
     test_helpers::{
+        ApplicationFixtureArgs,
         ApplicationTestExt,
         OBJECTS_TABLE,
         OBJECTS_TABLE_COMPONENT,
@@ -80,8 +104,12 @@ async fn create_scheduled_job<'a>(
     let job_id = model
         .schedule(
             path.clone(),
-            parse_udf_args(&path.udf_path, vec![JsonValue::Object(map)])?,
+            ScheduledJobArgs::Inline(parse_udf_args(
+                &path.udf_path,
+                vec![JsonValue::Object(map)],
+            )?),
             rt.unix_timestamp(),
+            ScheduleOptions::default(),
             ExecutionContext::new_for_test(),
         )
         .await?;
@@ -130,6 +158,159 @@ async fn test_scheduled_jobs_success(
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_scheduled_job_offloaded_args(
+    rt: TestRuntime,
+    pause_controller: PauseController,
+) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let hold_guard = pause_controller.hold(SCHEDULED_JOB_EXECUTED);
+
+    // Arguments this small aren't offloaded, so upload them directly.
+    let files_storage = application.files_storage();
+    let mut upload = files_storage.start_upload().await?;
+    upload
+        .write(serde_json::to_vec(&serde_json::json!([{ "key": "value" }]))?.into())
+        .await?;
+    let storage_key = upload.complete().await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let job_id = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .schedule(
+            insert_object_path(),
+            ScheduledJobArgs::Offloaded(storage_key.clone()),
+            rt.unix_timestamp(),
+            ScheduleOptions::default(),
+            ExecutionContext::new_for_test(),
+        )
+        .await?;
+    let job: ParsedDocument<ScheduledJob> = tx.get(job_id).await?.unwrap().try_into()?;
+    assert_eq!(job.args_storage_key, Some(storage_key));
+    application.commit_test(tx).await?;
+
+    // `insertObject` fails without its argument, so the job only succeeds if
+    // the executor read the arguments back from storage.
+    wait_for_scheduled_job_execution(hold_guard).await;
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = SchedulerModel::new(&mut tx, TableNamespace::test_user());
+    assert_eq!(
+        model.check_status(job_id).await?.unwrap(),
+        ScheduledJobState::Success
+    );
+    assert!(
+        !TableModel::new(&mut tx)
+            .table_is_empty(OBJECTS_TABLE_COMPONENT.into(), &OBJECTS_TABLE)
+            .await?
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_scheduled_job_request_context(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let request_context =
+        RequestContext::new(btreemap! { "traceId".to_string() => "abc123".to_string() })?;
+    let context = ExecutionContext::new_for_test().with_request_context(request_context.clone());
+    let path = insert_object_path();
+    let mut tx = application.begin(Identity::system()).await?;
+    let job_id = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .schedule(
+            path.clone(),
+            ScheduledJobArgs::Inline(parse_udf_args(
+                &path.udf_path,
+                vec![serde_json::json!({ "key": "value" })],
+            )?),
+            rt.unix_timestamp(),
+            ScheduleOptions::default(),
+            context,
+        )
+        .await?;
+    let job: ParsedDocument<ScheduledJob> = tx.get(job_id).await?.unwrap().try_into()?;
+    assert_eq!(job.request_context, request_context);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_scheduled_jobs_run_when_time_advances(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let path = insert_object_path();
+    let mut tx = application.begin(Identity::system()).await?;
+    let job_id = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .schedule(
+            path.clone(),
+            ScheduledJobArgs::Inline(parse_udf_args(
+                &path.udf_path,
+                vec![serde_json::json!({ "key": "value" })],
+            )?),
+            rt.unix_timestamp() + Duration::from_secs(60),
+            ScheduleOptions::default(),
+            ExecutionContext::new_for_test(),
+        )
+        .await?;
+    application.commit_test(tx).await?;
+
+    // The job isn't due yet, so there's nothing to wait for.
+    application.wait_for_due_jobs().await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = SchedulerModel::new(&mut tx, TableNamespace::test_user());
+    assert_eq!(
+        model.check_status(job_id).await?.unwrap(),
+        ScheduledJobState::Pending
+    );
+
+    rt.advance_time(Duration::from_secs(60)).await;
+    application.wait_for_due_jobs().await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = SchedulerModel::new(&mut tx, TableNamespace::test_user());
+    assert_eq!(
+        model.check_status(job_id).await?.unwrap(),
+        ScheduledJobState::Success
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_wait_for_due_jobs_waits_for_running_action(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let path = CanonicalizedComponentFunctionPath {
+        component: ComponentPath::test_user(),
+        udf_path: CanonicalizedUdfPath::from_str("action:sleep")?,
+    };
+    let mut tx = application.begin(Identity::system()).await?;
+    let job_id = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .schedule(
+            path.clone(),
+            ScheduledJobArgs::Inline(parse_udf_args(
+                &path.udf_path,
+                vec![serde_json::json!({ "ms": 1000 })],
+            )?),
+            rt.unix_timestamp(),
+            ScheduleOptions::default(),
+            ExecutionContext::new_for_test(),
+        )
+        .await?;
+    application.commit_test(tx).await?;
+
+    // The action is `InProgress` while it sleeps, which still counts as
+    // running.
+    application.wait_for_due_jobs().await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = SchedulerModel::new(&mut tx, TableNamespace::test_user());
+    assert_eq!(
+        model.check_status(job_id).await?.unwrap(),
+        ScheduledJobState::Success
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_scheduled_jobs_canceled(rt: TestRuntime) -> anyhow::Result<()> {
     let application = Application::new_for_tests(&rt).await?;
@@ -324,6 +505,7 @@ async fn test_cancel_recursively_scheduled_job(rt: TestRuntime) -> anyhow::Resul
             FunctionCaller::Action {
                 parent_scheduled_job,
             },
+            RequestContext::default(),
         )
         .await??;
 
@@ -340,6 +522,7 @@ async fn test_cancel_recursively_scheduled_job(rt: TestRuntime) -> anyhow::Resul
             FunctionCaller::Action {
                 parent_scheduled_job,
             },
+            RequestContext::default(),
         )
         .await??;
 
@@ -396,3 +579,209 @@ async fn test_scheduled_job_retry(
     assert_eq!(state, ScheduledJobState::Success);
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_scheduler_backlog(
+    rt: TestRuntime,
+    pause_controller: PauseController,
+) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let scheduled_job_executed_hold_guard = pause_controller.hold(SCHEDULED_JOB_EXECUTED);
+
+    // Pausing the backend leaves the job due but unexecuted.
+    let mut tx = application.begin(Identity::system()).await?;
+    BackendStateModel::new(&mut tx)
+        .toggle_backend_state(BackendState::Paused)
+        .await?;
+    let (_job_id, _model) = create_scheduled_job(&rt, &mut tx, insert_object_path()).await?;
+    application.commit_test(tx).await?;
+    rt.advance_time(Duration::from_secs(10)).await;
+
+    let backlog = application.scheduler_backlog(&Identity::system()).await?;
+    assert_eq!(backlog.due_jobs, 1);
+    assert_eq!(
+        backlog.due_jobs_by_function.get(&insert_object_path()),
+        Some(&1)
+    );
+    assert!(backlog.oldest_due_ts.is_some());
+    assert_eq!(backlog.completed_jobs, 0);
+    assert!(backlog.complete);
+
+    let mut tx = application.begin(Identity::system()).await?;
+    BackendStateModel::new(&mut tx)
+        .toggle_backend_state(BackendState::Running)
+        .await?;
+    application.commit_test(tx).await?;
+    wait_for_scheduled_job_execution(scheduled_job_executed_hold_guard).await;
+
+    let backlog = application.scheduler_backlog(&Identity::system()).await?;
+    assert_eq!(backlog.due_jobs, 0);
+    assert!(backlog.due_jobs_by_function.is_empty());
+    assert_eq!(backlog.oldest_due_ts, None);
+    assert_eq!(backlog.completed_jobs, 1);
+
+    let err = application
+        .scheduler_backlog(&Identity::Unknown)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "Unauthorized");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_scheduled_job_parked_while_function_disabled(
+    rt: TestRuntime,
+    pause_controller: PauseController,
+) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let hold_guard = pause_controller.hold(SCHEDULED_JOB_EXECUTED);
+    let target = KillSwitchTarget::Prefix("basic.js:".to_string());
+    application
+        .disable_function(
+            Identity::system(),
+            FunctionKillSwitch {
+                component: ComponentId::test_user(),
+                target: target.clone(),
+                reason: None,
+            },
+        )
+        .await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let (job_id, _model) = create_scheduled_job(&rt, &mut tx, insert_object_path()).await?;
+    application.commit_test(tx).await?;
+    rt.advance_time(Duration::from_secs(10)).await;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = SchedulerModel::new(&mut tx, TableNamespace::test_user());
+    assert_eq!(
+        model.check_status(job_id).await?,
+        Some(ScheduledJobState::Pending)
+    );
+
+    assert!(
+        application
+            .enable_function(Identity::system(), ComponentId::test_user(), target)
+            .await?
+    );
+    wait_for_scheduled_job_execution(hold_guard).await;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = SchedulerModel::new(&mut tx, TableNamespace::test_user());
+    assert_eq!(
+        model.check_status(job_id).await?,
+        Some(ScheduledJobState::Success)
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_scheduled_job_priority(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let path = insert_object_path();
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut job_ids = vec![];
+    for priority in SchedulePriority::ALL {
+        let options = ScheduleOptions {
+            priority,
+            max_concurrency: Some(1),
+        };
+        let job_id = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+            .schedule(
+                path.clone(),
+                ScheduledJobArgs::Inline(parse_udf_args(
+                    &path.udf_path,
+                    vec![serde_json::json!({ "key": "value" })],
+                )?),
+                rt.unix_timestamp() + Duration::from_secs(60),
+                options,
+                ExecutionContext::new_for_test(),
+            )
+            .await?;
+        job_ids.push((priority, job_id));
+    }
+
+    // Each priority's range of the index holds only its own jobs.
+    for (priority, job_id) in job_ids {
+        let query = unfinished_jobs_query(priority)?;
+        let mut query_stream = ResolvedQuery::new(&mut tx, TableNamespace::test_user(), query)?;
+        let job: ParsedDocument<ScheduledJob> = query_stream
+            .next(&mut tx, None)
+            .await?
+            .expect("missing scheduled job")
+            .try_into()?;
+        assert_eq!(job.id(), job_id);
+        assert_eq!(job.options.priority, priority);
+        assert_eq!(job.options.max_concurrency, Some(1));
+        assert!(query_stream.next(&mut tx, None).await?.is_none());
+    }
+
+    let err = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .schedule(
+            path.clone(),
+            ScheduledJobArgs::Inline(parse_udf_args(
+                &path.udf_path,
+                vec![serde_json::json!({ "key": "value" })],
+            )?),
+            rt.unix_timestamp(),
+            ScheduleOptions {
+                priority: SchedulePriority::Normal,
+                max_concurrency: Some(0),
+            },
+            ExecutionContext::new_for_test(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidScheduleOptions");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_scheduled_jobs_survive_restart(rt: TestRuntime) -> anyhow::Result<()> {
+    let tp = TestPersistence::new();
+    let args = || ApplicationFixtureArgs {
+        tp: Some(tp.clone()),
+        ..Default::default()
+    };
+    let application = Application::new_for_tests_with_args(&rt, args()).await?;
+    application.load_udf_tests_modules().await?;
+    let path = insert_object_path();
+    let mut tx = application.begin(Identity::system()).await?;
+    let job_id = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .schedule(
+            path.clone(),
+            ScheduledJobArgs::Inline(parse_udf_args(
+                &path.udf_path,
+                vec![serde_json::json!({ "key": "value" })],
+            )?),
+            rt.unix_timestamp() + Duration::from_secs(60),
+            ScheduleOptions {
+                priority: SchedulePriority::High,
+                max_concurrency: None,
+            },
+            ExecutionContext::new_for_test(),
+        )
+        .await?;
+    application.commit_test(tx).await?;
+    application.shutdown().await?;
+
+    // Restarting initializes the existing system tables again, including the
+    // scheduled jobs indexes.
+    let application = Application::new_for_tests_with_args(&rt, args()).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let query = unfinished_jobs_query(SchedulePriority::High)?;
+    let mut query_stream = ResolvedQuery::new(&mut tx, TableNamespace::test_user(), query)?;
+    let job: ParsedDocument<ScheduledJob> = query_stream
+        .next(&mut tx, None)
+        .await?
+        .expect("missing scheduled job")
+        .try_into()?;
+    assert_eq!(job.id(), job_id);
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use database::{
+    TableModel,
+    UserFacingModel,
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use serde_json::json;
+use value::{
+    ConvexValue,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    seed::SeedFixture,
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_seed_resolves_references(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let namespace = TableNamespace::test_user();
+
+    // Messages refer to users, which sort after them, and to each other.
+    let ids = application
+        .seed_for_test(json!({
+            "messages": [
+                { "$name": "hello", "author": { "$ref": "alice" }, "body": "hi" },
+                { "author": { "$ref": "bob" }, "replyTo": [{ "$ref": "hello" }] },
+            ],
+            "users": [
+                { "$name": "alice", "name": "Alice" },
+                { "$name": "bob", "name": "Bob" },
+            ],
+        }))
+        .await?;
+    assert_eq!(ids.len(), 3);
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let messages: TableName = "messages".parse()?;
+    assert_eq!(
+        TableModel::new(&mut tx)
+            .must_count(namespace, &messages)
+            .await?,
+        2
+    );
+    let hello = UserFacingModel::new(&mut tx, namespace)
+        .get(ids["hello"], None)
+        .await?
+        .unwrap();
+    assert_eq!(
+        hello.into_value().0.get("author"),
+        Some(&ConvexValue::try_from(ids["alice"].encode())?)
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_seed_rejects_bad_references(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+
+    let err = application
+        .seed_for_test(json!({
+            "messages": [{ "author": { "$ref": "nobody" } }],
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "UnknownSeedReference");
+
+    let err = application
+        .seed_for_test(json!({
+            "users": [{ "$name": "alice" }, { "$name": "alice" }],
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidSeedFixture");
+
+    let err = application
+        .seed(
+            &Identity::Unknown,
+            TableNamespace::test_user(),
+            SeedFixture::try_from(json!({ "users": [{}] }))?,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "Unauthorized");
+    Ok(())
+}
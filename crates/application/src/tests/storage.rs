@@ -1,14 +1,33 @@
+use std::{
+    net::IpAddr,
+    time::{
+        Duration,
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
 use common::{
     components::ComponentId,
     types::BackendState,
 };
 use errors::ErrorMetadataAnyhowExt;
+use file_storage::SignedUrlOptions;
 use futures::stream;
 use keybroker::Identity;
-use model::backend_state::BackendStateModel;
+use model::{
+    backend_state::BackendStateModel,
+    file_storage::FileStorageId,
+};
 use runtime::testing::TestRuntime;
+use storage::{
+    StorageExt,
+    Upload,
+};
+use value::DeveloperDocumentId;
 
 use crate::{
+    storage_gc::StorageGcWorker,
     test_helpers::ApplicationTestExt,
     Application,
 };
@@ -23,7 +42,7 @@ pub(crate) async fn test_backend_not_running_cannot_store_file(
         Ok(bytes::Bytes::from(vec![55; 1024 + 1]))
     }));
     let ok_result = app
-        .store_file(ComponentId::Root, None, None, None, file_body)
+        .store_file(ComponentId::Root, None, None, None, None, file_body)
         .await;
     assert!(ok_result.is_ok());
 
@@ -36,7 +55,7 @@ pub(crate) async fn test_backend_not_running_cannot_store_file(
         Ok(bytes::Bytes::from(vec![55; 1024 + 1]))
     }));
     let result = app
-        .store_file(ComponentId::Root, None, None, None, file_body)
+        .store_file(ComponentId::Root, None, None, None, None, file_body)
         .await;
     assert!(result.is_err());
     let error = result.unwrap_err();
@@ -44,3 +63,135 @@ pub(crate) async fn test_backend_not_running_cannot_store_file(
     assert_eq!(error.short_msg(), "BackendIsNotRunning");
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_storage_gc(rt: TestRuntime) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let file_body = Box::pin(stream::once(async {
+        Ok(bytes::Bytes::from(vec![55; 1024]))
+    }));
+    let storage_id = app
+        .store_file(ComponentId::Root, None, None, None, None, file_body)
+        .await?;
+    // An upload that was never recorded in `_storage`.
+    let mut upload = app.files_storage.start_upload().await?;
+    upload.write(vec![1, 2, 3].into()).await?;
+    let orphan = upload.complete().await?;
+
+    let worker = StorageGcWorker::new(rt.clone(), app.database.clone(), app.files_storage.clone());
+    // Nothing is collected within the hold-off window.
+    let report = worker.run_once(UNIX_EPOCH, true).await?.unwrap();
+    assert!(report.orphaned_objects.is_empty());
+
+    let report = worker.run_once(SystemTime::now(), false).await?.unwrap();
+    assert_eq!(report.orphaned_objects, vec![orphan.clone()]);
+    assert_eq!(report.orphaned_bytes, 3);
+    assert_eq!(report.deleted_objects, 0);
+    assert!(app.files_storage.get(&orphan).await?.is_some());
+
+    let report = worker.run_once(SystemTime::now(), true).await?.unwrap();
+    assert_eq!(report.deleted_objects, 1);
+    assert!(app.files_storage.get(&orphan).await?.is_none());
+    let entry = app
+        .get_file_entry(ComponentId::Root, FileStorageId::DocumentId(storage_id))
+        .await?;
+    assert!(app.files_storage.get(&entry.storage_key).await?.is_some());
+    Ok(())
+}
+
+async fn store_test_file(app: &Application<TestRuntime>) -> anyhow::Result<DeveloperDocumentId> {
+    let file_body = Box::pin(stream::once(async {
+        Ok(bytes::Bytes::from(vec![55; 1024]))
+    }));
+    app.store_file(ComponentId::Root, None, None, None, None, file_body)
+        .await
+}
+
+/// Returns the signed URL and the token the storage route passes to
+/// `check_signed_file_url`.
+async fn signed_url(
+    app: &Application<TestRuntime>,
+    storage_id: DeveloperDocumentId,
+    options: SignedUrlOptions,
+) -> anyhow::Result<(String, String)> {
+    let mut tx = app.begin(Identity::system()).await?;
+    let url = app
+        .file_storage
+        .transactional_file_storage
+        .get_signed_url(
+            &mut tx,
+            app.key_broker(),
+            ComponentId::Root,
+            FileStorageId::DocumentId(storage_id),
+            options,
+        )
+        .await?
+        .expect("File not found");
+    let token = url.rsplit('/').next().unwrap().to_string();
+    Ok((url, token))
+}
+
+#[convex_macro::test_runtime]
+async fn test_revoked_signed_url(rt: TestRuntime) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let storage_id = store_test_file(&app).await?;
+    let options = SignedUrlOptions {
+        expires_in: Duration::from_secs(60),
+        filename: None,
+        ip: None,
+    };
+    let (url, token) = signed_url(&app, storage_id, options).await?;
+    app.check_signed_file_url(&token, None).await?;
+
+    let mut tx = app.begin(Identity::system()).await?;
+    app.file_storage
+        .transactional_file_storage
+        .revoke_signed_url(&mut tx, app.key_broker(), ComponentId::Root, &url)
+        .await?;
+    app.commit_test(tx).await?;
+    let err = app.check_signed_file_url(&token, None).await.unwrap_err();
+    assert_eq!(err.short_msg(), "SignedUrlRevoked");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_signed_url_ip_mismatch(rt: TestRuntime) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let storage_id = store_test_file(&app).await?;
+    let ip: IpAddr = "203.0.113.7".parse()?;
+    let options = SignedUrlOptions {
+        expires_in: Duration::from_secs(60),
+        filename: None,
+        ip: Some(ip),
+    };
+    let (_, token) = signed_url(&app, storage_id, options).await?;
+    app.check_signed_file_url(&token, Some(ip)).await?;
+
+    let other_ip: IpAddr = "198.51.100.1".parse()?;
+    let err = app
+        .check_signed_file_url(&token, Some(other_ip))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "SignedUrlIpMismatch");
+    let err = app.check_signed_file_url(&token, None).await.unwrap_err();
+    assert_eq!(err.short_msg(), "SignedUrlIpMismatch");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_expired_signed_url(rt: TestRuntime) -> anyhow::Result<()> {
+    let app = Application::new_for_tests(&rt).await?;
+    let storage_id = store_test_file(&app).await?;
+    let options = SignedUrlOptions {
+        expires_in: Duration::from_secs(60),
+        filename: None,
+        ip: None,
+    };
+    let (_, token) = signed_url(&app, storage_id, options).await?;
+    app.check_signed_file_url(&token, None).await?;
+
+    rt.advance_time(Duration::from_secs(61)).await;
+    let err = app.check_signed_file_url(&token, None).await.unwrap_err();
+    assert_eq!(err.short_msg(), "SignedUrlExpired");
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use database::{
+    TableModel,
+    UserFacingModel,
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use value::{
+    assert_obj,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_clone_table(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let namespace = TableNamespace::test_user();
+    let source: TableName = "messages".parse()?;
+    let target: TableName = "messages_clone".parse()?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    for i in 0..3 {
+        UserFacingModel::new(&mut tx, namespace)
+            .insert(source.clone(), assert_obj!("i" => i))
+            .await?;
+    }
+    application.commit_test(tx).await?;
+
+    let copied = application
+        .clone_table(
+            &Identity::system(),
+            namespace,
+            source.clone(),
+            target.clone(),
+        )
+        .await?;
+    assert_eq!(copied, 3);
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut table_model = TableModel::new(&mut tx);
+    assert_eq!(table_model.must_count(namespace, &source).await?, 3);
+    assert_eq!(table_model.must_count(namespace, &target).await?, 3);
+
+    // Cloning onto an existing table fails.
+    let err = application
+        .clone_table(&Identity::system(), namespace, source, target)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TableAlreadyExists");
+    Ok(())
+}
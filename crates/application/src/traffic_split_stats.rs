@@ -0,0 +1,81 @@
+//! In-memory execution stats for functions under a traffic split, used to
+//! compare a candidate version against the stable one before promoting it.
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::components::ComponentId;
+use model::traffic_splits::types::TrafficSplitVariant;
+use parking_lot::Mutex;
+use sync_types::CanonicalizedUdfPath;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrafficSplitVariantStats {
+    pub executions: u64,
+    pub errors: u64,
+    pub total_duration: Duration,
+}
+
+impl TrafficSplitVariantStats {
+    pub fn error_rate(&self) -> f64 {
+        if self.executions == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.executions as f64
+    }
+
+    pub fn mean_duration(&self) -> Duration {
+        if self.executions == 0 {
+            return Duration::ZERO;
+        }
+        self.total_duration.div_f64(self.executions as f64)
+    }
+}
+
+pub type FunctionTrafficSplitStats = BTreeMap<TrafficSplitVariant, TrafficSplitVariantStats>;
+pub type ComponentTrafficSplitStats = BTreeMap<CanonicalizedUdfPath, FunctionTrafficSplitStats>;
+
+#[derive(Clone, Default)]
+pub struct TrafficSplitStats {
+    inner: Arc<Mutex<BTreeMap<ComponentId, ComponentTrafficSplitStats>>>,
+}
+
+impl TrafficSplitStats {
+    pub fn record(
+        &self,
+        component: ComponentId,
+        udf_path: CanonicalizedUdfPath,
+        variant: TrafficSplitVariant,
+        is_error: bool,
+        duration: Duration,
+    ) {
+        let mut inner = self.inner.lock();
+        let stats = inner
+            .entry(component)
+            .or_default()
+            .entry(udf_path)
+            .or_default()
+            .entry(variant)
+            .or_default();
+        stats.executions += 1;
+        if is_error {
+            stats.errors += 1;
+        }
+        stats.total_duration += duration;
+    }
+
+    pub fn get(&self, component: ComponentId) -> ComponentTrafficSplitStats {
+        self.inner
+            .lock()
+            .get(&component)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Forget the component's stats, e.g. when its split changes.
+    pub fn reset(&self, component: ComponentId) {
+        self.inner.lock().remove(&component);
+    }
+}
@@ -0,0 +1,507 @@
+//! Per-component, per-function and per-table usage for chargeback.
+//!
+//! `UsageAggregator` sits in front of the deployment's `UsageEventLogger` and
+//! sums up usage events as they're recorded. The current period's usage is
+//! served by the usage API, and if `USAGE_EXPORT_INTERVAL` is set,
+//! `UsageExportWorker` periodically ends the period, uploads its report to the
+//! exports storage bucket and records the upload in `_usage_exports`.
+//!
+//! Usage is only held in memory until it's exported, so usage recorded since
+//! the last export is lost when the backend restarts.
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use common::{
+    backoff::Backoff,
+    components::ComponentId,
+    errors::report_error,
+    knobs::USAGE_EXPORT_INTERVAL,
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+};
+use database::{
+    Database,
+    Snapshot,
+};
+use events::usage::{
+    UsageEvent,
+    UsageEventLogger,
+};
+use keybroker::Identity;
+use model::usage_exports::{
+    types::UsageExport,
+    UsageExportModel,
+};
+use parking_lot::Mutex;
+use storage::{
+    Storage,
+    Upload,
+};
+use value::TableNamespace;
+
+use self::types::{
+    FunctionUsage,
+    TableUsage,
+    UsageReport,
+    USAGE_REPORT_FORMAT_VERSION,
+};
+use crate::metrics::log_worker_starting;
+
+pub mod types;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+type UsageKey = (Option<String>, String);
+
+#[derive(Debug)]
+struct UsageAggregates {
+    period_start: UnixTimestamp,
+    functions: BTreeMap<UsageKey, FunctionUsage>,
+    tables: BTreeMap<UsageKey, TableUsage>,
+}
+
+impl UsageAggregates {
+    fn new(period_start: UnixTimestamp) -> Self {
+        Self {
+            period_start,
+            functions: BTreeMap::new(),
+            tables: BTreeMap::new(),
+        }
+    }
+
+    fn function(&mut self, component_path: &Option<String>, udf_id: &str) -> &mut FunctionUsage {
+        self.functions
+            .entry((component_path.clone(), udf_id.to_string()))
+            .or_insert_with(|| FunctionUsage {
+                component_path: component_path.clone(),
+                function: udf_id.to_string(),
+                ..Default::default()
+            })
+    }
+
+    fn table(&mut self, component_path: &Option<String>, table_name: &str) -> &mut TableUsage {
+        self.tables
+            .entry((component_path.clone(), table_name.to_string()))
+            .or_insert_with(|| TableUsage {
+                component_path: component_path.clone(),
+                table: table_name.to_string(),
+                ..Default::default()
+            })
+    }
+
+    fn record(&mut self, event: &UsageEvent) {
+        match event {
+            UsageEvent::FunctionCall { fields } => {
+                // Calls that aren't tracked are from system functions and the
+                // CLI, which deployments aren't charged for.
+                if !fields.is_tracked {
+                    return;
+                }
+                let usage = self.function(&fields.component_path, &fields.udf_id);
+                usage.calls += 1;
+                if fields.status != "success" {
+                    usage.failures += 1;
+                }
+                usage.execution_time_ms += fields.duration_millis;
+                usage.action_compute_mb_ms += fields.memory_megabytes * fields.duration_millis;
+                if let Some(heap_used) = fields.peak_heap_used_bytes {
+                    usage.peak_heap_used_bytes = usage.peak_heap_used_bytes.max(heap_used);
+                }
+                usage.cpu_time_micros += fields.cpu_time_micros;
+            },
+            UsageEvent::FunctionStorageBandwidth {
+                component_path,
+                udf_id,
+                ingress,
+                egress,
+                ..
+            } => {
+                let usage = self.function(component_path, udf_id);
+                usage.file_write_bytes += ingress;
+                usage.file_read_bytes += egress;
+            },
+            UsageEvent::DatabaseBandwidth {
+                component_path,
+                udf_id,
+                table_name,
+                ingress,
+                egress,
+                ..
+            } => {
+                let usage = self.function(component_path, udf_id);
+                usage.database_write_bytes += ingress;
+                usage.database_read_bytes += egress;
+                let usage = self.table(component_path, table_name);
+                usage.database_write_bytes += ingress;
+                usage.database_read_bytes += egress;
+            },
+            UsageEvent::VectorBandwidth {
+                component_path,
+                udf_id,
+                table_name,
+                ingress,
+                egress,
+                ..
+            } => {
+                let usage = self.function(component_path, udf_id);
+                usage.vector_write_bytes += ingress;
+                usage.vector_read_bytes += egress;
+                let usage = self.table(component_path, table_name);
+                usage.vector_write_bytes += ingress;
+                usage.vector_read_bytes += egress;
+            },
+            UsageEvent::FunctionStorageCalls { .. }
+            | UsageEvent::StorageCall { .. }
+            | UsageEvent::StorageBandwidth { .. }
+            | UsageEvent::CurrentVectorStorage { .. }
+            | UsageEvent::CurrentDatabaseStorage { .. }
+            | UsageEvent::CurrentFileStorage { .. }
+            | UsageEvent::CurrentDocumentCounts { .. } => {},
+        }
+    }
+
+    /// Sets each user table's storage to its current size in `snapshot`.
+    fn add_table_storage(&mut self, snapshot: &Snapshot) -> anyhow::Result<()> {
+        let component_paths = snapshot.component_ids_to_paths();
+        let component_path = |namespace: TableNamespace| {
+            component_paths
+                .get(&ComponentId::from(namespace))
+                .map(|path| path.clone().serialize())
+        };
+        for ((namespace, table_name), summary) in snapshot.iter_table_summaries()? {
+            if table_name.is_system() {
+                continue;
+            }
+            let Some(component_path) = component_path(namespace) else {
+                continue;
+            };
+            self.table(&component_path, &table_name).num_documents = summary.num_values();
+        }
+        let storage = snapshot.get_document_and_index_storage()?;
+        for ((namespace, table_name), table_storage) in storage.0 {
+            if table_name.is_system() {
+                continue;
+            }
+            let Some(component_path) = component_path(namespace) else {
+                continue;
+            };
+            let usage = self.table(&component_path, &table_name);
+            usage.document_storage_bytes = table_storage.document_size;
+            usage.index_storage_bytes = table_storage.index_size;
+        }
+        Ok(())
+    }
+
+    fn into_report(
+        self,
+        instance_name: String,
+        period_end: UnixTimestamp,
+    ) -> anyhow::Result<UsageReport> {
+        Ok(UsageReport {
+            format_version: USAGE_REPORT_FORMAT_VERSION,
+            instance_name,
+            period_start_ms: self.period_start.as_ms_since_epoch()?,
+            period_end_ms: period_end.as_ms_since_epoch()?,
+            functions: self.functions.into_values().collect(),
+            tables: self.tables.into_values().collect(),
+        })
+    }
+}
+
+/// Sums up usage events before passing them on to `inner`.
+#[derive(Debug)]
+pub struct UsageAggregator {
+    instance_name: String,
+    inner: Arc<dyn UsageEventLogger>,
+    aggregates: Mutex<UsageAggregates>,
+}
+
+impl UsageAggregator {
+    pub fn new(
+        instance_name: String,
+        period_start: UnixTimestamp,
+        inner: Arc<dyn UsageEventLogger>,
+    ) -> Self {
+        Self {
+            instance_name,
+            inner,
+            aggregates: Mutex::new(UsageAggregates::new(period_start)),
+        }
+    }
+
+    /// Usage since the start of the current period, with storage as of
+    /// `snapshot`.
+    pub fn current_report(
+        &self,
+        now: UnixTimestamp,
+        snapshot: &Snapshot,
+    ) -> anyhow::Result<UsageReport> {
+        let mut aggregates = {
+            let aggregates = self.aggregates.lock();
+            UsageAggregates {
+                period_start: aggregates.period_start,
+                functions: aggregates.functions.clone(),
+                tables: aggregates.tables.clone(),
+            }
+        };
+        aggregates.add_table_storage(snapshot)?;
+        aggregates.into_report(self.instance_name.clone(), now)
+    }
+
+    /// Ends the current period at `now` and returns its usage, with storage
+    /// as of `snapshot`.
+    fn end_period(&self, now: UnixTimestamp, snapshot: &Snapshot) -> anyhow::Result<UsageReport> {
+        let mut aggregates =
+            std::mem::replace(&mut *self.aggregates.lock(), UsageAggregates::new(now));
+        aggregates.add_table_storage(snapshot)?;
+        aggregates.into_report(self.instance_name.clone(), now)
+    }
+
+    fn aggregate(&self, events: &[UsageEvent]) {
+        let mut aggregates = self.aggregates.lock();
+        for event in events {
+            aggregates.record(event);
+        }
+    }
+}
+
+#[async_trait]
+impl UsageEventLogger for UsageAggregator {
+    fn record(&self, events: Vec<UsageEvent>) {
+        self.aggregate(&events);
+        self.inner.record(events);
+    }
+
+    async fn record_async(&self, events: Vec<UsageEvent>) {
+        self.aggregate(&events);
+        self.inner.record_async(events).await;
+    }
+
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        self.inner.shutdown().await
+    }
+}
+
+/// Uploads a usage report to the exports storage bucket every
+/// `USAGE_EXPORT_INTERVAL`.
+pub struct UsageExportWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    exports_storage: Arc<dyn Storage>,
+    usage_aggregator: Arc<UsageAggregator>,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> UsageExportWorker<RT> {
+    fn new(
+        runtime: RT,
+        database: Database<RT>,
+        exports_storage: Arc<dyn Storage>,
+        usage_aggregator: Arc<UsageAggregator>,
+    ) -> Self {
+        Self {
+            runtime,
+            database,
+            exports_storage,
+            usage_aggregator,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        }
+    }
+
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        exports_storage: Arc<dyn Storage>,
+        usage_aggregator: Arc<UsageAggregator>,
+    ) -> impl Future<Output = ()> + Send {
+        let mut worker = Self::new(runtime, database, exports_storage, usage_aggregator);
+        async move {
+            let Some(interval) = *USAGE_EXPORT_INTERVAL else {
+                tracing::info!("Usage exports are disabled");
+                return;
+            };
+            tracing::info!("Starting UsageExportWorker");
+            // A report that failed to upload, which is retried before ending
+            // the next period so no usage is lost.
+            let mut pending = None;
+            loop {
+                if pending.is_none() {
+                    worker.runtime.wait(interval).await;
+                }
+                match worker.run_once(&mut pending).await {
+                    Ok(()) => worker.backoff.reset(),
+                    Err(e) => {
+                        report_error(&mut e.context("UsageExportWorker died")).await;
+                        let delay = worker.backoff.fail(&mut worker.runtime.rng());
+                        worker.runtime.wait(delay).await;
+                    },
+                }
+            }
+        }
+    }
+
+    /// End the current period early and upload its report, so usage since the
+    /// last export survives a shutdown.
+    pub async fn checkpoint(
+        runtime: RT,
+        database: Database<RT>,
+        exports_storage: Arc<dyn Storage>,
+        usage_aggregator: Arc<UsageAggregator>,
+    ) -> anyhow::Result<()> {
+        if USAGE_EXPORT_INTERVAL.is_none() {
+            return Ok(());
+        }
+        Self::new(runtime, database, exports_storage, usage_aggregator)
+            .run_once(&mut None)
+            .await
+    }
+
+    async fn run_once(&self, pending: &mut Option<UsageReport>) -> anyhow::Result<()> {
+        let _status = log_worker_starting("UsageExportWorker");
+        let report = match pending.take() {
+            Some(report) => report,
+            None => {
+                let snapshot = self.database.latest_snapshot()?;
+                self.usage_aggregator
+                    .end_period(self.runtime.unix_timestamp(), &snapshot)?
+            },
+        };
+        if let Err(e) = self.upload(&report).await {
+            *pending = Some(report);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn upload(&self, report: &UsageReport) -> anyhow::Result<()> {
+        let body = serde_json::to_vec(report)?;
+        let size_bytes = body.len() as u64;
+        let mut upload = self.exports_storage.start_upload().await?;
+        upload.write(Bytes::from(body)).await?;
+        let object_key = upload.complete().await?;
+
+        let mut tx = self.database.begin(Identity::system()).await?;
+        UsageExportModel::new(&mut tx)
+            .insert(UsageExport {
+                period_start_ms: report.period_start_ms,
+                period_end_ms: report.period_end_ms,
+                object_key,
+                size_bytes,
+            })
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "usage_export_worker")
+            .await?;
+        tracing::info!(
+            "Exported usage from {} to {}",
+            report.period_start_ms,
+            report.period_end_ms
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common::runtime::UnixTimestamp;
+    use events::usage::{
+        FunctionCallUsageFields,
+        NoOpUsageEventLogger,
+        UsageEvent,
+        UsageEventLogger,
+    };
+
+    use super::UsageAggregator;
+    use crate::usage_export::types::{
+        FunctionUsage,
+        TableUsage,
+    };
+
+    fn function_call(udf_id: &str, status: &str, is_tracked: bool) -> UsageEvent {
+        UsageEvent::FunctionCall {
+            fields: FunctionCallUsageFields {
+                id: "execution".to_string(),
+                request_id: "request".to_string(),
+                status: status.to_string(),
+                component_path: None,
+                udf_id: udf_id.to_string(),
+                udf_id_type: "function".to_string(),
+                tag: "action".to_string(),
+                memory_megabytes: 512,
+                duration_millis: 100,
+                peak_heap_used_bytes: None,
+                cpu_time_micros: 0,
+                environment: "isolate".to_string(),
+                is_tracked,
+                response_sha256: None,
+                is_occ: false,
+                occ_table_name: None,
+                occ_document_id: None,
+                occ_write_source: None,
+                occ_retry_count: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_aggregate_usage_events() -> anyhow::Result<()> {
+        let aggregator = UsageAggregator::new(
+            "carnitas".to_string(),
+            UnixTimestamp::from_millis(1000),
+            Arc::new(NoOpUsageEventLogger),
+        );
+        aggregator.record(vec![
+            function_call("messages.js:send", "success", true),
+            function_call("messages.js:send", "failure", true),
+            function_call("_system/cli/tables", "success", false),
+            UsageEvent::DatabaseBandwidth {
+                id: "execution".to_string(),
+                request_id: "request".to_string(),
+                component_path: None,
+                udf_id: "messages.js:send".to_string(),
+                table_name: "messages".to_string(),
+                ingress: 10,
+                egress: 20,
+                egress_rows: 1,
+            },
+        ]);
+        let aggregates = aggregator.aggregates.lock();
+        assert_eq!(
+            aggregates.functions.values().cloned().collect::<Vec<_>>(),
+            vec![FunctionUsage {
+                component_path: None,
+                function: "messages.js:send".to_string(),
+                calls: 2,
+                failures: 1,
+                execution_time_ms: 200,
+                action_compute_mb_ms: 2 * 512 * 100,
+                database_read_bytes: 20,
+                database_write_bytes: 10,
+                ..Default::default()
+            }]
+        );
+        assert_eq!(
+            aggregates.tables.values().cloned().collect::<Vec<_>>(),
+            vec![TableUsage {
+                component_path: None,
+                table: "messages".to_string(),
+                database_read_bytes: 20,
+                database_write_bytes: 10,
+                ..Default::default()
+            }]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! The format of usage reports. Reports are served by the usage API and
+//! uploaded to the exports storage bucket as JSON, so changes here must stay
+//! backwards compatible or bump `USAGE_REPORT_FORMAT_VERSION`.
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+pub const USAGE_REPORT_FORMAT_VERSION: u32 = 1;
+
+/// Usage between `period_start_ms` (inclusive) and `period_end_ms`
+/// (exclusive), both in milliseconds since the epoch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub format_version: u32,
+    pub instance_name: String,
+    pub period_start_ms: u64,
+    pub period_end_ms: u64,
+    pub functions: Vec<FunctionUsage>,
+    pub tables: Vec<TableUsage>,
+}
+
+/// Usage attributed to a function, identified by its component and path (or
+/// route, for HTTP actions). Calls from the CLI and the dashboard aren't
+/// counted, but the bandwidth they use is.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionUsage {
+    /// `None` for the root component.
+    pub component_path: Option<String>,
+    pub function: String,
+    pub calls: u64,
+    pub failures: u64,
+    pub execution_time_ms: u64,
+    /// Memory used by actions multiplied by their duration, in
+    /// megabyte-milliseconds.
+    pub action_compute_mb_ms: u64,
+    /// The most JavaScript heap a single query or mutation call used at once,
+    /// in bytes.
+    #[serde(default)]
+    pub peak_heap_used_bytes: u64,
+    /// CPU time spent running JavaScript, in microseconds. Unlike
+    /// `execution_time_ms`, this excludes time spent waiting on syscalls.
+    #[serde(default)]
+    pub cpu_time_micros: u64,
+    pub database_read_bytes: u64,
+    pub database_write_bytes: u64,
+    pub file_read_bytes: u64,
+    pub file_write_bytes: u64,
+    pub vector_read_bytes: u64,
+    pub vector_write_bytes: u64,
+}
+
+/// Usage attributed to a table. Bandwidth is summed over the period, while
+/// storage is measured at the end of it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableUsage {
+    /// `None` for the root component.
+    pub component_path: Option<String>,
+    pub table: String,
+    pub database_read_bytes: u64,
+    pub database_write_bytes: u64,
+    pub vector_read_bytes: u64,
+    pub vector_write_bytes: u64,
+    pub num_documents: u64,
+    pub document_storage_bytes: u64,
+    pub index_storage_bytes: u64,
+}
@@ -15,10 +15,17 @@ use biscuit::{
     JWT,
 };
 use chrono::TimeZone;
-use common::auth::AuthInfo;
+use common::{
+    auth::AuthInfo,
+    knobs::AUTH_PROVIDER_METADATA_CACHE_TTL,
+};
 use errors::ErrorMetadata;
 use futures::Future;
-use keybroker::UserIdentity;
+use keybroker::{
+    KeyBroker,
+    SelfIssuedTokenKind,
+    UserIdentity,
+};
 use oauth2::{
     HttpRequest,
     HttpResponse,
@@ -37,7 +44,6 @@ use openidconnect::{
     },
     ClaimsVerificationError,
     ClientId,
-    DiscoveryError,
 };
 use serde::{
     Deserialize,
@@ -46,9 +52,15 @@ use serde::{
 use sync_types::AuthenticationToken;
 use url::Url;
 
+use crate::{
+    metrics::id_token_validation_timer,
+    provider_metadata_cache::ProviderMetadataCache,
+};
+
 pub mod access_token_auth;
 pub mod application_auth;
 pub mod metrics;
+pub mod provider_metadata_cache;
 
 /// Issuer for API access tokens
 pub static CONVEX_AUTH_URL: LazyLock<Url> =
@@ -103,6 +115,34 @@ pub async fn validate_id_token<F, E>(
     http_client: impl Fn(HttpRequest) -> F + 'static,
     auth_infos: Vec<AuthInfo>,
     system_time: SystemTime,
+    cache: &ProviderMetadataCache,
+    key_broker: &KeyBroker,
+) -> anyhow::Result<UserIdentity>
+where
+    F: Future<Output = Result<HttpResponse, E>>,
+    E: std::error::Error + 'static + Send + Sync,
+{
+    let timer = id_token_validation_timer();
+    let identity = validate_id_token_inner(
+        token_str,
+        http_client,
+        auth_infos,
+        system_time,
+        cache,
+        key_broker,
+    )
+    .await?;
+    timer.finish();
+    Ok(identity)
+}
+
+async fn validate_id_token_inner<F, E>(
+    token_str: Auth0IdToken,
+    http_client: impl Fn(HttpRequest) -> F + 'static,
+    auth_infos: Vec<AuthInfo>,
+    system_time: SystemTime,
+    cache: &ProviderMetadataCache,
+    key_broker: &KeyBroker,
 ) -> anyhow::Result<UserIdentity>
 where
     F: Future<Output = Result<HttpResponse, E>>,
@@ -147,39 +187,17 @@ where
             "NoAuthProvider",
             "No auth provider found matching the given token",
         ))?;
+    // Anonymous identity and built-in auth tokens are signed by this deployment
+    // rather than by an external provider, so there's nothing to discover.
+    if let Some(kind) = SelfIssuedTokenKind::from_issuer(issuer.as_str()) {
+        return key_broker.check_self_issued_token(kind, &token_str.0, system_time);
+    }
     // Use the OpenID Connect Discovery protocol to get the public keys for this
-    // provider.
-    // TODO(CX-606): Add an caching layer that respects the HTTP cache headers
-    // in the response.
-    let metadata = CoreProviderMetadata::discover_async(issuer.clone(), http_client)
-        .await
-        .map_err(|e| {
-            let short = "AuthProviderDiscoveryFailed";
-            let long = format!("Auth provider discovery of {} failed", issuer.as_str());
-            match e {
-                DiscoveryError::Response(code, body, _) => {
-                    let long = format!("{long}: {} {}", code, String::from_utf8_lossy(&body));
-                    let Ok(code) = http::StatusCode::from_u16(code.as_u16()) else {
-                        return ErrorMetadata::bad_request(short, long);
-                    };
-                    if let Some(em) =
-                        ErrorMetadata::from_http_status_code(code, short, long.clone())
-                    {
-                        em
-                    } else {
-                        ErrorMetadata::bad_request(short, long)
-                    }
-                },
-                e => {
-                    tracing::error!(
-                        "Error discovering auth provider: {}, {}",
-                        issuer.as_str(),
-                        e
-                    );
-                    ErrorMetadata::bad_request(short, long)
-                },
-            }
-        })?;
+    // provider, unless they're already cached.
+    let ttl = auth_info
+        .jwks_cache_ttl()
+        .unwrap_or(*AUTH_PROVIDER_METADATA_CACHE_TTL);
+    let metadata = cache.get(issuer, ttl, http_client, system_time).await?;
     // Create a verifier for the provider using this metadata. Set the verifier
     // to enforce that the issuer and audience match.
     // Note for posterity: this verifier will reject tokens containing multiple
@@ -390,8 +408,10 @@ where
 #[cfg(test)]
 mod tests {
     use std::{
+        cell::Cell,
         convert::Infallible,
         pin::Pin,
+        rc::Rc,
         time::SystemTime,
     };
 
@@ -404,7 +424,12 @@ mod tests {
         Future,
         FutureExt,
     };
-    use keybroker::testing::TEST_SIGNING_KEY;
+    use keybroker::{
+        testing::TEST_SIGNING_KEY,
+        KeyBroker,
+        ANONYMOUS_IDENTITY_AUDIENCE,
+        ANONYMOUS_IDENTITY_ISSUER,
+    };
     use openidconnect::{
         core::{
             CoreClaimName,
@@ -445,6 +470,7 @@ mod tests {
     };
 
     use crate::{
+        provider_metadata_cache::ProviderMetadataCache,
         validate_access_token,
         validate_id_token,
         Auth0AccessToken,
@@ -482,6 +508,19 @@ mod tests {
         }
     }
 
+    fn counting_http_client(
+        metadata: String,
+        jwks: String,
+        requests: Rc<Cell<usize>>,
+    ) -> impl Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<HttpResponse, Infallible>>>>
+    {
+        let http_client = fake_http_client(metadata, jwks);
+        move |request: HttpRequest| {
+            requests.set(requests.get() + 1);
+            http_client(request)
+        }
+    }
+
     #[tokio::test]
     async fn test_id_token_auth() -> anyhow::Result<()> {
         let issuer_url = IssuerUrl::new("https://dev-1sfr-rpl.us.auth0.com".to_string()).unwrap();
@@ -549,16 +588,98 @@ mod tests {
         .unwrap()
         .to_string();
         validate_id_token(
-            Auth0IdToken(id_token),
-            fake_http_client(provider_metadata, jwks),
+            Auth0IdToken(id_token.clone()),
+            fake_http_client(provider_metadata.clone(), jwks.clone()),
             vec![AuthInfo {
                 application_id: (*audience).clone(),
-                domain: issuer_url,
+                domain: issuer_url.clone(),
+                jwks_cache_ttl_seconds: None,
+                magic_link_action: None,
+                passkey_origin: None,
             }],
             SystemTime::now(),
+            &ProviderMetadataCache::default(),
+            &KeyBroker::dev(),
         )
         .await
         .unwrap();
+
+        // Discovery (one request for the metadata, one for the JWKS) only
+        // happens again once the provider's cache TTL has passed.
+        let auth_infos = vec![AuthInfo {
+            application_id: (*audience).clone(),
+            domain: issuer_url,
+            jwks_cache_ttl_seconds: Some(60),
+            magic_link_action: None,
+            passkey_origin: None,
+        }];
+        let cache = ProviderMetadataCache::default();
+        let requests = Rc::new(Cell::new(0));
+        let now = SystemTime::now();
+        for (system_time, expected_requests) in [
+            (now, 2),
+            (now + std::time::Duration::from_secs(30), 2),
+            (now + std::time::Duration::from_secs(90), 4),
+        ] {
+            validate_id_token(
+                Auth0IdToken(id_token.clone()),
+                counting_http_client(provider_metadata.clone(), jwks.clone(), requests.clone()),
+                auth_infos.clone(),
+                system_time,
+                &cache,
+                &KeyBroker::dev(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(requests.get(), expected_requests);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_id_token_auth() -> anyhow::Result<()> {
+        let key_broker = KeyBroker::dev();
+        let now = SystemTime::now();
+        let id_token = key_broker.issue_anonymous_identity_token(
+            "anon1".to_string(),
+            now,
+            now + std::time::Duration::from_secs(60),
+        )?;
+        let requests = Rc::new(Cell::new(0));
+        let http_client = || counting_http_client(String::new(), String::new(), requests.clone());
+
+        // Anonymous identities must be enabled in the auth config.
+        let err = validate_id_token(
+            Auth0IdToken(id_token.clone()),
+            http_client(),
+            vec![],
+            now,
+            &ProviderMetadataCache::default(),
+            &key_broker,
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{err}").contains("No auth provider found"));
+
+        let auth_infos = vec![AuthInfo {
+            application_id: ANONYMOUS_IDENTITY_AUDIENCE.to_string(),
+            domain: IssuerUrl::new(ANONYMOUS_IDENTITY_ISSUER.to_string())?,
+            jwks_cache_ttl_seconds: None,
+            magic_link_action: None,
+            passkey_origin: None,
+        }];
+        let identity = validate_id_token(
+            Auth0IdToken(id_token),
+            http_client(),
+            auth_infos,
+            now,
+            &ProviderMetadataCache::default(),
+            &key_broker,
+        )
+        .await?;
+        assert_eq!(identity.subject, "anon1");
+        // The token is checked with the deployment's own key, without discovery.
+        assert_eq!(requests.get(), 0);
         Ok(())
     }
 
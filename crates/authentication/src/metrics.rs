@@ -1,7 +1,10 @@
 use metrics::{
     log_counter_with_labels,
     register_convex_counter,
+    register_convex_histogram,
     StaticMetricLabel,
+    StatusTimer,
+    STATUS_LABEL,
 };
 
 register_convex_counter!(pub DEPLOY_KEY_USE_TOTAL, "Count of deploy key uses", &["key_type"]);
@@ -22,3 +25,50 @@ pub fn log_deploy_key_use(key_type: DeployKeyType) {
         vec![StaticMetricLabel::new("key_type", key_type_label)],
     );
 }
+
+register_convex_histogram!(
+    AUTH_ID_TOKEN_VALIDATION_SECONDS,
+    "Time to validate an ID token, including provider discovery",
+    &STATUS_LABEL
+);
+pub fn id_token_validation_timer() -> StatusTimer {
+    StatusTimer::new(&AUTH_ID_TOKEN_VALIDATION_SECONDS)
+}
+
+register_convex_counter!(
+    AUTH_PROVIDER_METADATA_CACHE_TOTAL,
+    "Count of auth provider metadata cache lookups",
+    &["result"]
+);
+
+pub enum ProviderMetadataCacheResult {
+    Hit,
+    NegativeHit,
+    Miss,
+}
+
+pub fn log_provider_metadata_cache_lookup(result: ProviderMetadataCacheResult) {
+    let result_label = match result {
+        ProviderMetadataCacheResult::Hit => "hit",
+        ProviderMetadataCacheResult::NegativeHit => "negative_hit",
+        ProviderMetadataCacheResult::Miss => "miss",
+    };
+    log_counter_with_labels(
+        &AUTH_PROVIDER_METADATA_CACHE_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("result", result_label)],
+    );
+}
+
+register_convex_counter!(
+    AUTH_PROVIDER_METADATA_REFRESH_TOTAL,
+    "Count of background auth provider metadata refreshes",
+    &STATUS_LABEL
+);
+pub fn log_provider_metadata_refresh(is_ok: bool) {
+    log_counter_with_labels(
+        &AUTH_PROVIDER_METADATA_REFRESH_TOTAL,
+        1,
+        vec![StaticMetricLabel::status(is_ok)],
+    );
+}
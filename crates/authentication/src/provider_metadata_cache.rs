@@ -0,0 +1,235 @@
+//! Cache of OpenID Connect provider metadata, including each provider's JWKS,
+//! for validating ID tokens. Entries that were used since they were fetched
+//! are refetched in the background before they expire, so token validation
+//! doesn't stall on discovery when an entry runs out. Failed discoveries are
+//! cached for `AUTH_PROVIDER_NEGATIVE_CACHE_TTL` so an unreachable provider
+//! doesn't get a discovery request for every token we see.
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::Arc,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use common::{
+    knobs::{
+        AUTH_PROVIDER_METADATA_REFRESH_INTERVAL,
+        AUTH_PROVIDER_NEGATIVE_CACHE_TTL,
+    },
+    runtime::Runtime,
+};
+use errors::ErrorMetadata;
+use oauth2::{
+    HttpRequest,
+    HttpResponse,
+};
+use openidconnect::{
+    core::CoreProviderMetadata,
+    DiscoveryError,
+    IssuerUrl,
+};
+use parking_lot::Mutex;
+
+use crate::metrics::{
+    log_provider_metadata_cache_lookup,
+    log_provider_metadata_refresh,
+    ProviderMetadataCacheResult,
+};
+
+struct CachedProviderMetadata {
+    issuer: IssuerUrl,
+    result: Result<CoreProviderMetadata, ErrorMetadata>,
+    fetched_at: SystemTime,
+    ttl: Duration,
+    /// Whether a token was validated with this entry since it was fetched.
+    /// Unused entries aren't refreshed, so providers that are no longer
+    /// configured fall out of the cache once they expire.
+    used: bool,
+}
+
+impl CachedProviderMetadata {
+    fn expires_at(&self) -> SystemTime {
+        let ttl = match self.result {
+            Ok(_) => self.ttl,
+            Err(_) => self.ttl.min(*AUTH_PROVIDER_NEGATIVE_CACHE_TTL),
+        };
+        self.fetched_at + ttl
+    }
+
+    /// Refresh once three quarters of the TTL have passed, leaving a few
+    /// refresh intervals for retries before the entry expires.
+    fn refresh_at(&self) -> SystemTime {
+        self.fetched_at + self.ttl * 3 / 4
+    }
+}
+
+#[derive(Default)]
+pub struct ProviderMetadataCache {
+    entries: Mutex<BTreeMap<String, CachedProviderMetadata>>,
+}
+
+impl ProviderMetadataCache {
+    /// Get the metadata for `issuer`, running discovery if there's no
+    /// unexpired entry for it. Discovery results are cached for `ttl`.
+    pub async fn get<F, E>(
+        &self,
+        issuer: &IssuerUrl,
+        ttl: Duration,
+        http_client: impl Fn(HttpRequest) -> F + 'static,
+        now: SystemTime,
+    ) -> Result<CoreProviderMetadata, ErrorMetadata>
+    where
+        F: Future<Output = Result<HttpResponse, E>>,
+        E: std::error::Error + 'static + Send + Sync,
+    {
+        if let Some(result) = self.lookup(issuer, now) {
+            return result;
+        }
+        let result = discover_provider_metadata(issuer, http_client).await;
+        self.insert(issuer.clone(), result.clone(), ttl, now);
+        result
+    }
+
+    fn lookup(
+        &self,
+        issuer: &IssuerUrl,
+        now: SystemTime,
+    ) -> Option<Result<CoreProviderMetadata, ErrorMetadata>> {
+        let mut entries = self.entries.lock();
+        let Some(entry) = entries
+            .get_mut(issuer.as_str())
+            .filter(|entry| now < entry.expires_at())
+        else {
+            log_provider_metadata_cache_lookup(ProviderMetadataCacheResult::Miss);
+            return None;
+        };
+        entry.used = true;
+        log_provider_metadata_cache_lookup(match entry.result {
+            Ok(_) => ProviderMetadataCacheResult::Hit,
+            Err(_) => ProviderMetadataCacheResult::NegativeHit,
+        });
+        Some(entry.result.clone())
+    }
+
+    fn insert(
+        &self,
+        issuer: IssuerUrl,
+        result: Result<CoreProviderMetadata, ErrorMetadata>,
+        ttl: Duration,
+        now: SystemTime,
+    ) {
+        self.entries.lock().insert(
+            issuer.as_str().to_owned(),
+            CachedProviderMetadata {
+                issuer,
+                result,
+                fetched_at: now,
+                ttl,
+                used: false,
+            },
+        );
+    }
+
+    /// Drop expired entries and refetch the ones that are due for a refresh.
+    /// A failed refresh keeps the previous metadata until it expires.
+    pub async fn refresh<F, E>(
+        &self,
+        http_client: impl Fn(HttpRequest) -> F + Clone + 'static,
+        now: SystemTime,
+    ) where
+        F: Future<Output = Result<HttpResponse, E>>,
+        E: std::error::Error + 'static + Send + Sync,
+    {
+        let due: Vec<_> = {
+            let mut entries = self.entries.lock();
+            entries.retain(|_, entry| now < entry.expires_at());
+            entries
+                .values()
+                .filter(|entry| entry.result.is_ok() && entry.used && now >= entry.refresh_at())
+                .map(|entry| (entry.issuer.clone(), entry.ttl))
+                .collect()
+        };
+        for (issuer, ttl) in due {
+            match discover_provider_metadata(&issuer, http_client.clone()).await {
+                Ok(metadata) => {
+                    log_provider_metadata_refresh(true);
+                    self.insert(issuer, Ok(metadata), ttl, now);
+                },
+                Err(e) => {
+                    log_provider_metadata_refresh(false);
+                    tracing::warn!(
+                        "Failed to refresh metadata for auth provider {}: {e}",
+                        issuer.as_str()
+                    );
+                },
+            }
+        }
+    }
+
+    pub fn start_refresher<RT: Runtime, F, E>(
+        self: Arc<Self>,
+        runtime: RT,
+        http_client: impl Fn(HttpRequest) -> F + Clone + Send + Sync + 'static,
+    ) -> impl Future<Output = ()> + Send
+    where
+        F: Future<Output = Result<HttpResponse, E>> + Send,
+        E: std::error::Error + 'static + Send + Sync,
+    {
+        async move {
+            if AUTH_PROVIDER_METADATA_REFRESH_INTERVAL.is_zero() {
+                tracing::info!("Auth provider metadata refresher is disabled");
+                return;
+            }
+            loop {
+                runtime.wait(*AUTH_PROVIDER_METADATA_REFRESH_INTERVAL).await;
+                self.refresh(http_client.clone(), runtime.system_time())
+                    .await;
+            }
+        }
+    }
+}
+
+/// Use the OpenID Connect Discovery protocol to get the metadata, including
+/// the public keys, for a provider.
+async fn discover_provider_metadata<F, E>(
+    issuer: &IssuerUrl,
+    http_client: impl Fn(HttpRequest) -> F + 'static,
+) -> Result<CoreProviderMetadata, ErrorMetadata>
+where
+    F: Future<Output = Result<HttpResponse, E>>,
+    E: std::error::Error + 'static + Send + Sync,
+{
+    CoreProviderMetadata::discover_async(issuer.clone(), http_client)
+        .await
+        .map_err(|e| {
+            let short = "AuthProviderDiscoveryFailed";
+            let long = format!("Auth provider discovery of {} failed", issuer.as_str());
+            match e {
+                DiscoveryError::Response(code, body, _) => {
+                    let long = format!("{long}: {} {}", code, String::from_utf8_lossy(&body));
+                    let Ok(code) = http::StatusCode::from_u16(code.as_u16()) else {
+                        return ErrorMetadata::bad_request(short, long);
+                    };
+                    if let Some(em) =
+                        ErrorMetadata::from_http_status_code(code, short, long.clone())
+                    {
+                        em
+                    } else {
+                        ErrorMetadata::bad_request(short, long)
+                    }
+                },
+                e => {
+                    tracing::error!(
+                        "Error discovering auth provider: {}, {}",
+                        issuer.as_str(),
+                        e
+                    );
+                    ErrorMetadata::bad_request(short, long)
+                },
+            }
+        })
+}
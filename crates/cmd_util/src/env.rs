@@ -1,10 +1,14 @@
 use std::{
+    collections::BTreeMap,
     env,
     fmt::Debug,
     fs::File,
     io,
     str::FromStr,
-    sync::LazyLock,
+    sync::{
+        LazyLock,
+        Mutex,
+    },
 };
 
 use sentry_tracing::EventFilter;
@@ -20,6 +24,18 @@ use tracing_subscriber::{
     Layer,
 };
 
+static ENV_CONFIG_OVERRIDES: LazyLock<Mutex<BTreeMap<String, String>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// The values read by [`env_config`] so far that differ from their defaults,
+/// by name. Knobs are read lazily, so ones nothing has used yet are missing.
+pub fn env_config_overrides() -> BTreeMap<String, String> {
+    ENV_CONFIG_OVERRIDES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
 pub fn env_config<T>(name: &str, default: T) -> T
 where
     T: Debug + FromStr + PartialEq,
@@ -37,6 +53,10 @@ where
         Ok(v) => {
             if v != default {
                 tracing::info!("Overriding {name} to {v:?} from environment");
+                ENV_CONFIG_OVERRIDES
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(name.to_string(), format!("{v:?}"));
             }
             v
         },
@@ -1,4 +1,7 @@
-use std::sync::LazyLock;
+use std::{
+    sync::LazyLock,
+    time::Duration,
+};
 
 use openidconnect::IssuerUrl;
 use regex::Regex;
@@ -15,6 +18,20 @@ pub struct AuthInfo {
     pub application_id: String,
     #[serde(deserialize_with = "deserialize_issuer_url")]
     pub domain: IssuerUrl,
+    /// How long to cache this provider's metadata and JWKS. Defaults to
+    /// `AUTH_PROVIDER_METADATA_CACHE_TTL`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwks_cache_ttl_seconds: Option<u64>,
+    /// For the built-in auth provider, the function that emails magic links,
+    /// e.g. `"auth:sendMagicLink"`. It's scheduled with the `email` and
+    /// `token` to send.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub magic_link_action: Option<String>,
+    /// For the built-in auth provider, the origin of the site that offers
+    /// passkey sign-in, e.g. `"https://example.com"`. Passkeys are scoped to
+    /// its host.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passkey_origin: Option<String>,
 }
 
 static PROTOCOL_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\w+://").unwrap());
@@ -81,8 +98,15 @@ impl AuthInfo {
         Self {
             application_id: "12345".to_string(),
             domain: IssuerUrl::new("https://convex.dev".to_string()).unwrap(),
+            jwks_cache_ttl_seconds: None,
+            magic_link_action: None,
+            passkey_origin: None,
         }
     }
+
+    pub fn jwks_cache_ttl(&self) -> Option<Duration> {
+        self.jwks_cache_ttl_seconds.map(Duration::from_secs)
+    }
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -93,13 +117,23 @@ impl proptest::arbitrary::Arbitrary for AuthInfo {
 
     fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
         use proptest::prelude::*;
-        any::<(String, proptest_http::ArbitraryUri)>().prop_filter_map(
+        any::<(
+            String,
+            proptest_http::ArbitraryUri,
+            Option<u32>,
+            Option<String>,
+            Option<String>,
+        )>()
+        .prop_filter_map(
             "String and URI weren't valid AuthInfo",
-            |(s, uri)| {
+            |(s, uri, ttl, magic_link_action, passkey_origin)| {
                 IssuerUrl::new(format!("{}", uri.0))
                     .map(|domain| Self {
                         application_id: s,
                         domain,
+                        jwks_cache_ttl_seconds: ttl.map(u64::from),
+                        magic_link_action,
+                        passkey_origin,
                     })
                     .ok()
             },
@@ -119,6 +153,8 @@ pub struct AuthConfig {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use crate::auth::AuthInfo;
 
     #[test]
@@ -160,6 +196,18 @@ mod tests {
         .unwrap_err();
     }
 
+    #[test]
+    fn test_auth_info_jwks_cache_ttl() {
+        let info: AuthInfo =
+            serde_json::from_str(r#"{"applicationID": "123", "domain": "example.com"}"#).unwrap();
+        assert_eq!(info.jwks_cache_ttl(), None);
+        let info: AuthInfo = serde_json::from_str(
+            r#"{"applicationID": "123", "domain": "example.com", "jwksCacheTtlSeconds": 300}"#,
+        )
+        .unwrap();
+        assert_eq!(info.jwks_cache_ttl(), Some(Duration::from_secs(300)));
+    }
+
     #[test]
     fn test_auth_info_rejects_bogus_domain() {
         serde_json::from_str::<AuthInfo>(r#"{"applicationID": "123", "domain": "foobar123"}"#)
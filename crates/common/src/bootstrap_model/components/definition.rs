@@ -1,5 +1,6 @@
 use std::{
     collections::BTreeMap,
+    fmt,
     ops::Deref,
     str::FromStr,
 };
@@ -9,12 +10,16 @@ use serde::{
     Serialize,
 };
 use serde_json::Value as JsonValue;
-use sync_types::path::PathComponent;
+use sync_types::{
+    path::PathComponent,
+    CanonicalizedUdfPath,
+};
 use value::{
     codegen_convex_serialization,
     heap_size::HeapSize,
     identifier::Identifier,
     ConvexValue,
+    TableName,
 };
 
 use crate::{
@@ -57,6 +62,8 @@ pub struct ComponentDefinitionMetadata {
         )
     )]
     pub exports: BTreeMap<PathComponent, ComponentExport>,
+
+    pub lifecycle: ComponentLifecycle,
 }
 
 impl ComponentDefinitionMetadata {
@@ -67,6 +74,7 @@ impl ComponentDefinitionMetadata {
             child_components: Vec::new(),
             http_mounts: BTreeMap::new(),
             exports: BTreeMap::new(),
+            lifecycle: ComponentLifecycle::default(),
         }
     }
 
@@ -75,6 +83,23 @@ impl ComponentDefinitionMetadata {
     }
 }
 
+/// Mutations a component runs in the same transaction as the push that mounts
+/// or unmounts it. If a hook fails, the whole push fails.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ComponentLifecycle {
+    /// Runs after a component is first instantiated, e.g. to seed its tables.
+    pub on_mount: Option<CanonicalizedUdfPath>,
+    /// Runs before a component is unmounted, while its functions still exist.
+    pub on_unmount: Option<CanonicalizedUdfPath>,
+}
+
+impl ComponentLifecycle {
+    pub fn is_empty(&self) -> bool {
+        self.on_mount.is_none() && self.on_unmount.is_none()
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct HttpMountPath(String);
 
@@ -138,6 +163,49 @@ pub struct ComponentInstantiation {
     pub name: ComponentName,
     pub path: ComponentDefinitionPath,
     pub args: Option<BTreeMap<Identifier, ComponentArgument>>,
+    /// Tables of the instantiating component that the child may access.
+    pub grants: BTreeMap<TableName, TableAccess>,
+}
+
+/// Access a parent component grants a child to one of its tables. Write
+/// access includes read access.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum TableAccess {
+    Read,
+    Write,
+}
+
+impl TableAccess {
+    /// Whether this grant permits `access`.
+    pub fn allows(&self, access: TableAccess) -> bool {
+        access <= *self
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TableAccess::Read => "read",
+            TableAccess::Write => "write",
+        }
+    }
+}
+
+impl FromStr for TableAccess {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "read" => Ok(TableAccess::Read),
+            "write" => Ok(TableAccess::Write),
+            _ => anyhow::bail!("Invalid table access {s:?}, expected \"read\" or \"write\""),
+        }
+    }
+}
+
+impl fmt::Display for TableAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -149,7 +217,12 @@ pub enum ComponentExport {
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub enum ComponentArgumentValidator {
-    Value(Validator),
+    /// `optional` arguments may be omitted when the component is
+    /// instantiated; all others are required.
+    Value {
+        validator: Validator,
+        optional: bool,
+    },
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -166,6 +239,14 @@ pub struct SerializedComponentDefinitionMetadata {
     child_components: Vec<SerializedComponentInstantiation>,
     http_mounts: Option<BTreeMap<String, String>>,
     exports: SerializedComponentExport,
+    lifecycle: Option<SerializedComponentLifecycle>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedComponentLifecycle {
+    on_mount: Option<String>,
+    on_unmount: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -181,7 +262,12 @@ pub enum SerializedComponentDefinitionType {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum SerializedComponentArgumentValidator {
-    Value { value: String },
+    Value {
+        value: String,
+        /// Missing for definitions from older clients, whose arguments were
+        /// all treated as optional.
+        optional: Option<bool>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -196,6 +282,7 @@ struct SerializedComponentInstantiation {
     name: String,
     path: String,
     args: Option<Vec<(String, SerializedComponentArgument)>>,
+    grants: Option<Vec<(String, String)>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -228,6 +315,10 @@ impl TryFrom<ComponentDefinitionMetadata> for SerializedComponentDefinitionMetad
                     .collect(),
             ),
             exports: ComponentExport::Branch(m.exports).try_into()?,
+            lifecycle: (!m.lifecycle.is_empty()).then(|| SerializedComponentLifecycle {
+                on_mount: m.lifecycle.on_mount.map(|path| path.to_string()),
+                on_unmount: m.lifecycle.on_unmount.map(|path| path.to_string()),
+            }),
         })
     }
 }
@@ -254,6 +345,13 @@ impl TryFrom<SerializedComponentDefinitionMetadata> for ComponentDefinitionMetad
                 .map(|(k, v)| anyhow::Ok((k.parse()?, v.parse()?)))
                 .try_collect()?,
             exports,
+            lifecycle: match m.lifecycle {
+                Some(lifecycle) => ComponentLifecycle {
+                    on_mount: lifecycle.on_mount.map(|p| p.parse()).transpose()?,
+                    on_unmount: lifecycle.on_unmount.map(|p| p.parse()).transpose()?,
+                },
+                None => ComponentLifecycle::default(),
+            },
         })
     }
 }
@@ -309,6 +407,12 @@ impl TryFrom<ComponentInstantiation> for SerializedComponentInstantiation {
                         .try_collect()
                 })
                 .transpose()?,
+            grants: Some(
+                i.grants
+                    .into_iter()
+                    .map(|(table, access)| (table.to_string(), access.to_string()))
+                    .collect(),
+            ),
         })
     }
 }
@@ -328,6 +432,12 @@ impl TryFrom<SerializedComponentInstantiation> for ComponentInstantiation {
                         .try_collect()
                 })
                 .transpose()?,
+            grants: i
+                .grants
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(table, access)| anyhow::Ok((table.parse()?, access.parse()?)))
+                .try_collect()?,
         })
     }
 }
@@ -337,8 +447,12 @@ impl TryFrom<ComponentArgumentValidator> for SerializedComponentArgumentValidato
 
     fn try_from(r: ComponentArgumentValidator) -> anyhow::Result<Self> {
         Ok(match r {
-            ComponentArgumentValidator::Value(v) => SerializedComponentArgumentValidator::Value {
-                value: serde_json::to_string(&JsonValue::try_from(v)?)?,
+            ComponentArgumentValidator::Value {
+                validator,
+                optional,
+            } => SerializedComponentArgumentValidator::Value {
+                value: serde_json::to_string(&JsonValue::try_from(validator)?)?,
+                optional: Some(optional),
             },
         })
     }
@@ -349,10 +463,11 @@ impl TryFrom<SerializedComponentArgumentValidator> for ComponentArgumentValidato
 
     fn try_from(r: SerializedComponentArgumentValidator) -> anyhow::Result<Self> {
         Ok(match r {
-            SerializedComponentArgumentValidator::Value { value: v } => {
-                ComponentArgumentValidator::Value(Validator::try_from(serde_json::from_str::<
-                    JsonValue,
-                >(&v)?)?)
+            SerializedComponentArgumentValidator::Value { value: v, optional } => {
+                ComponentArgumentValidator::Value {
+                    validator: Validator::try_from(serde_json::from_str::<JsonValue>(&v)?)?,
+                    optional: optional.unwrap_or(true),
+                }
             },
         })
     }
@@ -453,7 +568,13 @@ impl proptest::arbitrary::Arbitrary for ComponentInstantiation {
                 any::<ComponentArgument>(),
                 0..4,
             )),
+            prop::collection::btree_map(any::<TableName>(), any::<TableAccess>(), 0..4),
         )
-            .prop_map(|(name, path, args)| ComponentInstantiation { name, path, args })
+            .prop_map(|(name, path, args, grants)| ComponentInstantiation {
+                name,
+                path,
+                args,
+                grants,
+            })
     }
 }
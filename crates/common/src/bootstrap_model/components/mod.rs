@@ -11,8 +11,10 @@ use value::{
     codegen_convex_serialization,
     identifier::Identifier,
     DeveloperDocumentId,
+    TableName,
 };
 
+use self::definition::TableAccess;
 use crate::components::{
     ComponentName,
     Resource,
@@ -54,6 +56,8 @@ pub enum ComponentType {
         parent: DeveloperDocumentId,
         name: ComponentName,
         args: BTreeMap<Identifier, Resource>,
+        /// Tables of the parent component this component may access.
+        grants: BTreeMap<TableName, TableAccess>,
     },
 }
 
@@ -70,6 +74,7 @@ struct SerializedComponentMetadata {
     pub parent: Option<String>,
     pub name: Option<String>,
     pub args: Option<Vec<(String, SerializedResource)>>,
+    pub grants: Option<Vec<(String, String)>>,
     pub state: Option<String>,
 }
 
@@ -77,9 +82,14 @@ impl TryFrom<ComponentMetadata> for SerializedComponentMetadata {
     type Error = anyhow::Error;
 
     fn try_from(m: ComponentMetadata) -> anyhow::Result<Self> {
-        let (parent, name, args) = match m.component_type {
-            ComponentType::App => (None, None, None),
-            ComponentType::ChildComponent { parent, name, args } => (
+        let (parent, name, args, grants) = match m.component_type {
+            ComponentType::App => (None, None, None, None),
+            ComponentType::ChildComponent {
+                parent,
+                name,
+                args,
+                grants,
+            } => (
                 Some(parent.to_string()),
                 Some(name.to_string()),
                 Some(
@@ -87,6 +97,12 @@ impl TryFrom<ComponentMetadata> for SerializedComponentMetadata {
                         .map(|(k, v)| anyhow::Ok((k.to_string(), v.try_into()?)))
                         .try_collect()?,
                 ),
+                Some(
+                    grants
+                        .into_iter()
+                        .map(|(table, access)| (table.to_string(), access.to_string()))
+                        .collect(),
+                ),
             ),
         };
         let state = match m.state {
@@ -98,6 +114,7 @@ impl TryFrom<ComponentMetadata> for SerializedComponentMetadata {
             parent,
             name,
             args,
+            grants,
             state: Some(state.to_string()),
         })
     }
@@ -116,6 +133,12 @@ impl TryFrom<SerializedComponentMetadata> for ComponentMetadata {
                     .into_iter()
                     .map(|(k, v)| Ok((k.parse()?, v.try_into()?)))
                     .collect::<anyhow::Result<_>>()?,
+                grants: m
+                    .grants
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(table, access)| Ok((table.parse()?, access.parse()?)))
+                    .collect::<anyhow::Result<_>>()?,
             },
             _ => anyhow::bail!("Invalid component type"),
         };
@@ -60,6 +60,7 @@ use value::{
 use crate::value::FieldType;
 use crate::{
     floating_point::MAX_EXACT_F64_INT,
+    geospatial,
     index::IndexKey,
     pii::PII,
     types::{
@@ -502,7 +503,13 @@ impl ResolvedDocument {
     ) -> IndexKey {
         let mut values = vec![];
         for field in fields.iter() {
-            if let Some(v) = self.value.get_path(field) {
+            if let Some(location) = geospatial::location_field(field) {
+                values.push(
+                    self.value
+                        .get_path(&location)
+                        .and_then(geospatial::geohash_key),
+                );
+            } else if let Some(v) = self.value.get_path(field) {
                 values.push(Some(v.clone()));
             } else {
                 values.push(None);
@@ -826,7 +833,13 @@ impl PackedDocument {
     ) -> IndexKey {
         let mut values = vec![];
         for field in fields.iter() {
-            if let Some(v) = self.0.get_path(field) {
+            if let Some(location) = geospatial::location_field(field) {
+                values.push(
+                    self.0
+                        .get_path(&location)
+                        .and_then(|v| geospatial::geohash_key(&v)),
+                );
+            } else if let Some(v) = self.0.get_path(field) {
                 values.push(Some(v));
             } else {
                 values.push(None);
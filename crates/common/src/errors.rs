@@ -20,6 +20,7 @@ use metrics::log_counter;
 use pb::common::{
     FrameData as FrameDataProto,
     JsError as JsErrorProto,
+    JsErrorContext as JsErrorContextProto,
     JsFrames as JsFramesProto,
 };
 use rand::Rng;
@@ -36,7 +37,10 @@ use value::{
     ConvexValue,
 };
 
-use crate::metrics::log_errors_reported_total;
+use crate::{
+    knobs::FUNCTION_ERROR_SOURCE_MAPS,
+    metrics::log_errors_reported_total,
+};
 
 // Regex to match emails from https://emailregex.com/
 pub static EMAIL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
@@ -372,6 +376,11 @@ impl fmt::Display for FrameData {
     }
 }
 
+/// Keys for the system-provided [`JsError::context`].
+pub const JS_ERROR_CONTEXT_COMPONENT_PATH: &str = "componentPath";
+pub const JS_ERROR_CONTEXT_SYSCALL: &str = "syscall";
+pub const JS_ERROR_CONTEXT_DOCUMENT_ID: &str = "documentId";
+
 /// An Error emitted from a Convex Function execution.
 #[derive(Clone)]
 #[cfg_attr(
@@ -382,6 +391,10 @@ pub struct JsError {
     pub message: String,
     pub custom_data: Option<ConvexValue>,
     pub frames: Option<JsFrames>,
+    /// Where the error came from (e.g. the syscall that failed), added by the
+    /// system rather than the function. Not part of the formatted error, since
+    /// it's only shown to admins.
+    pub context: BTreeMap<String, String>,
 }
 
 impl From<JsError> for anyhow::Error {
@@ -399,6 +412,7 @@ impl TryFrom<JsError> for JsErrorProto {
             message,
             custom_data,
             frames,
+            context,
         }: JsError,
     ) -> anyhow::Result<Self> {
         Ok(Self {
@@ -410,6 +424,13 @@ impl TryFrom<JsError> for JsErrorProto {
                 })
                 .transpose()?,
             frames: frames.map(JsFramesProto::from),
+            context: context
+                .into_iter()
+                .map(|(key, value)| JsErrorContextProto {
+                    key: Some(key),
+                    value: Some(value),
+                })
+                .collect(),
         })
     }
 }
@@ -422,6 +443,7 @@ impl TryFrom<JsErrorProto> for JsError {
             message,
             custom_data,
             frames,
+            context,
         }: JsErrorProto,
     ) -> anyhow::Result<Self> {
         Ok(Self {
@@ -434,13 +456,30 @@ impl TryFrom<JsErrorProto> for JsError {
                 })
                 .transpose()?,
             frames: frames.map(JsFrames::try_from).transpose()?,
+            context: context
+                .into_iter()
+                .map(|entry| {
+                    anyhow::Ok((
+                        entry.key.ok_or_else(|| anyhow::anyhow!("Missing key"))?,
+                        entry
+                            .value
+                            .ok_or_else(|| anyhow::anyhow!("Missing value"))?,
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
         })
     }
 }
 
 impl HeapSize for JsError {
     fn heap_size(&self) -> usize {
-        self.message.heap_size() + self.frames.heap_size()
+        self.message.heap_size()
+            + self.frames.heap_size()
+            + self
+                .context
+                .iter()
+                .map(|(key, value)| key.heap_size() + value.heap_size())
+                .sum::<usize>()
     }
 }
 
@@ -495,6 +534,7 @@ impl JsError {
             message,
             custom_data: None,
             frames: None,
+            context: BTreeMap::new(),
         }
     }
 
@@ -503,6 +543,7 @@ impl JsError {
             message,
             custom_data: Some(data),
             frames: None,
+            context: BTreeMap::new(),
         }
     }
 
@@ -510,17 +551,36 @@ impl JsError {
         message: String,
         frame_data: Vec<FrameData>,
         custom_data: Option<ConvexValue>,
+        lookup_source_map: impl FnMut(&Url) -> anyhow::Result<Option<SourceMap>>,
+    ) -> Self {
+        Self::from_frames_inner(
+            message,
+            frame_data,
+            custom_data,
+            *FUNCTION_ERROR_SOURCE_MAPS,
+            lookup_source_map,
+        )
+    }
+
+    /// When `resolve_source_maps` is false, frames keep their positions in the
+    /// bundled modules.
+    fn from_frames_inner(
+        message: String,
+        frame_data: Vec<FrameData>,
+        custom_data: Option<ConvexValue>,
+        resolve_source_maps: bool,
         mut lookup_source_map: impl FnMut(&Url) -> anyhow::Result<Option<SourceMap>>,
     ) -> Self {
         let mut source_maps = BTreeMap::new();
         let mut mapped_frames = Vec::with_capacity(frame_data.len());
         for mut frame in frame_data {
-            if let FrameData {
-                file_name: Some(ref f),
-                line_number: Some(l),
-                column_number: Some(c),
-                ..
-            } = frame
+            if resolve_source_maps
+                && let FrameData {
+                    file_name: Some(ref f),
+                    line_number: Some(l),
+                    column_number: Some(c),
+                    ..
+                } = frame
             {
                 let Ok(specifier) = Url::parse(f) else {
                     // We expect the file_name to be fully qualified URL but seems
@@ -579,9 +639,15 @@ impl JsError {
             message,
             custom_data,
             frames: Some(JsFrames(mapped_frames.into())),
+            context: BTreeMap::new(),
         }
     }
 
+    pub fn with_context(mut self, key: &str, value: String) -> Self {
+        self.context.insert(key.to_string(), value);
+        self
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub fn from_frames_for_test(message: &str, frames: Vec<&str>) -> Self {
         let frame_data = frames
@@ -809,6 +875,34 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_source_maps_disabled_leaves_frames_unmapped() -> anyhow::Result<()> {
+        let frames = vec![FrameData {
+            file_name: Some("convex:/user/messages.js".to_string()),
+            line_number: Some(10),
+            column_number: Some(5),
+            ..Default::default()
+        }];
+        let mut lookups = 0;
+        let js_error =
+            JsError::from_frames_inner("Big Error".into(), frames.clone(), None, false, |_| {
+                lookups += 1;
+                Ok(None)
+            });
+        assert_eq!(lookups, 0);
+        assert_eq!(js_error.frames.unwrap().0.to_vec(), frames);
+
+        let js_error =
+            JsError::from_frames_inner("Big Error".into(), frames.clone(), None, true, |_| {
+                lookups += 1;
+                Ok(None)
+            });
+        assert_eq!(lookups, 1);
+        // Frames whose source map can't be found are dropped.
+        assert!(js_error.frames.unwrap().0.is_empty());
+        Ok(())
+    }
+
     proptest! {
         #![proptest_config(
             ProptestConfig { cases: 256 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, ..ProptestConfig::default() }
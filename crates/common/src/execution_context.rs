@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     fmt::{
         Display,
         Formatter,
@@ -7,6 +8,7 @@ use std::{
 };
 
 use anyhow::Context;
+use errors::ErrorMetadata;
 use rand::Rng;
 use serde_json::{
     json,
@@ -20,7 +22,10 @@ use value::{
     sha256,
 };
 
-use crate::types::FunctionCaller;
+use crate::{
+    knobs::REQUEST_CONTEXT_MAX_SIZE,
+    types::FunctionCaller,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
@@ -37,6 +42,9 @@ pub struct ExecutionContext {
     /// version of this would be something like parent_execution_id:
     /// Option<ExecutionId>
     is_root: bool,
+    /// Opaque context attached by the client to the request that started this
+    /// chain of functions, inherited by any functions it calls or schedules.
+    pub request_context: RequestContext,
 }
 
 impl ExecutionContext {
@@ -46,6 +54,7 @@ impl ExecutionContext {
             execution_id: ExecutionId::new(),
             parent_scheduled_job: caller.parent_scheduled_job(),
             is_root: caller.is_root(),
+            request_context: RequestContext::default(),
         }
     }
 
@@ -54,15 +63,22 @@ impl ExecutionContext {
         execution_id: ExecutionId,
         parent_scheduled_job: Option<DeveloperDocumentId>,
         is_root: bool,
+        request_context: RequestContext,
     ) -> Self {
         Self {
             request_id,
             execution_id,
             parent_scheduled_job,
             is_root,
+            request_context,
         }
     }
 
+    pub fn with_request_context(mut self, request_context: RequestContext) -> Self {
+        self.request_context = request_context;
+        self
+    }
+
     pub fn is_root(&self) -> bool {
         self.is_root
     }
@@ -74,6 +90,7 @@ impl ExecutionContext {
             execution_id: ExecutionId::new(),
             parent_scheduled_job: None,
             is_root: true,
+            request_context: RequestContext::default(),
         }
     }
 }
@@ -84,6 +101,96 @@ impl HeapSize for ExecutionContext {
             + self.execution_id.heap_size()
             + self.parent_scheduled_job.heap_size()
             + self.is_root.heap_size()
+            + self.request_context.heap_size()
+    }
+}
+
+/// Opaque key-value pairs a client attaches to a request (e.g. trace ids,
+/// experiment flags, or a locale). Functions can read them, and they're
+/// recorded in the function logs for correlation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct RequestContext(
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::btree_map(\"[a-z-]{1,8}\", \"[a-z0-9]{0,16}\", 0..4)"
+        )
+    )]
+    BTreeMap<String, String>,
+);
+
+impl RequestContext {
+    pub fn new(entries: BTreeMap<String, String>) -> anyhow::Result<Self> {
+        let size: usize = entries.iter().map(|(k, v)| k.len() + v.len()).sum();
+        anyhow::ensure!(
+            size <= *REQUEST_CONTEXT_MAX_SIZE,
+            ErrorMetadata::bad_request(
+                "RequestContextTooLarge",
+                format!(
+                    "Request context is too large (actual: {size} bytes, limit: {} bytes)",
+                    *REQUEST_CONTEXT_MAX_SIZE
+                ),
+            )
+        );
+        Ok(Self(entries))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|v| &v[..])
+    }
+}
+
+impl From<RequestContext> for BTreeMap<String, String> {
+    fn from(value: RequestContext) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<JsonValue> for RequestContext {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonValue) -> anyhow::Result<Self> {
+        let entries = serde_json::from_value(value).map_err(|e| {
+            ErrorMetadata::bad_request(
+                "InvalidRequestContext",
+                format!("Request context must be an object with string values: {e}"),
+            )
+        })?;
+        Self::new(entries)
+    }
+}
+
+impl FromStr for RequestContext {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value: JsonValue = serde_json::from_str(s).map_err(|e| {
+            ErrorMetadata::bad_request(
+                "InvalidRequestContext",
+                format!("Request context must be valid JSON: {e}"),
+            )
+        })?;
+        value.try_into()
+    }
+}
+
+impl From<RequestContext> for JsonValue {
+    fn from(value: RequestContext) -> Self {
+        json!(value.0)
+    }
+}
+
+impl HeapSize for RequestContext {
+    fn heap_size(&self) -> usize {
+        self.0
+            .iter()
+            .map(|(k, v)| k.heap_size() + v.heap_size())
+            .sum()
     }
 }
 
@@ -210,6 +317,7 @@ impl From<ExecutionContext> for pb::common::ExecutionContext {
             execution_id: Some(value.execution_id.to_string()),
             parent_scheduled_job: value.parent_scheduled_job.map(|id| id.into()),
             is_root: Some(value.is_root),
+            request_context: value.request_context.0.into_iter().collect(),
         }
     }
 }
@@ -226,6 +334,7 @@ impl TryFrom<pb::common::ExecutionContext> for ExecutionContext {
             },
             parent_scheduled_job: value.parent_scheduled_job.map(|s| s.parse()).transpose()?,
             is_root: value.is_root.unwrap_or_default(),
+            request_context: RequestContext(value.request_context.into_iter().collect()),
         })
     }
 }
@@ -237,6 +346,7 @@ impl From<ExecutionContext> for JsonValue {
             "executionId": value.execution_id.to_string(),
             "isRoot": value.is_root,
             "parentScheduledJob": value.parent_scheduled_job.map(|id| id.to_string()),
+            "requestContext": JsonValue::from(value.request_context),
         })
     }
 }
@@ -1,29 +1,99 @@
 use std::{
+    borrow::Cow,
     collections::BTreeMap,
     hash::{
         Hash,
         Hasher,
     },
     str::FromStr,
-    sync::LazyLock,
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+        LazyLock,
+    },
 };
 
 use anyhow::Context;
 use fastrace::{
-    collector::SpanContext,
+    collector::{
+        Config,
+        SpanContext,
+    },
     Span,
 };
+use fastrace_opentelemetry::OpenTelemetryReporter;
 use fnv::FnvHasher;
+use opentelemetry::{
+    trace::SpanKind,
+    InstrumentationLibrary,
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
 use parking_lot::Mutex;
 use rand::Rng;
 use regex::Regex;
 use serde::Deserialize;
 
-use crate::knobs::REQUEST_TRACE_SAMPLE_CONFIG;
+use crate::{
+    knobs::{
+        OTEL_EXPORTER_OTLP_ENDPOINT,
+        OTEL_SERVICE_NAME,
+        REQUEST_TRACE_SAMPLE_CONFIG,
+    },
+    version::SERVER_VERSION_STR,
+};
 
 static SAMPLING_CONFIG_FROM_LOADER: LazyLock<Mutex<Option<SamplingConfig>>> =
     LazyLock::new(|| Mutex::new(None));
 
+static TRACE_EXPORT_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Flushes spans that haven't been exported yet when dropped.
+pub struct TraceExportGuard;
+
+impl Drop for TraceExportGuard {
+    fn drop(&mut self) {
+        fastrace::flush();
+    }
+}
+
+/// Export spans to the OTLP collector at `OTEL_EXPORTER_OTLP_ENDPOINT`, if
+/// it's set. Call this before starting the Tokio runtime, since the exporter
+/// sends spans with a blocking HTTP client from fastrace's reporter thread.
+pub fn config_trace_export() -> anyhow::Result<Option<TraceExportGuard>> {
+    let Some(endpoint) = &*OTEL_EXPORTER_OTLP_ENDPOINT else {
+        return Ok(None);
+    };
+    let endpoint = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+    let exporter = opentelemetry_otlp::new_exporter()
+        .http()
+        .with_endpoint(endpoint.clone())
+        .build_span_exporter()
+        .context("Failed to build OTLP span exporter")?;
+    let reporter = OpenTelemetryReporter::new(
+        exporter,
+        SpanKind::Server,
+        Cow::Owned(Resource::new([
+            KeyValue::new("service.name", OTEL_SERVICE_NAME.clone()),
+            KeyValue::new("service.version", SERVER_VERSION_STR.to_string()),
+        ])),
+        InstrumentationLibrary::builder("convex").build(),
+    );
+    fastrace::set_reporter(reporter, Config::default());
+    TRACE_EXPORT_ENABLED.store(true, Ordering::Relaxed);
+    tracing::info!("Exporting traces to {endpoint}");
+    Ok(Some(TraceExportGuard))
+}
+
+/// Whether spans are exported. Without an exporter, sampling requests
+/// locally would only add overhead.
+pub fn is_trace_export_enabled() -> bool {
+    TRACE_EXPORT_ENABLED.load(Ordering::Relaxed)
+}
+
 #[derive(Clone, Debug)]
 pub struct EncodedSpan(pub Option<String>);
 
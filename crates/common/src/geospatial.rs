@@ -0,0 +1,350 @@
+//! Geohash cells for geospatial indexes.
+//!
+//! A geospatial index is a database index over a derived `_geohash` field
+//! nested under a point field: the index key for `location._geohash` is the
+//! geohash of the document's `location`. Nearby points share geohash
+//! prefixes, so a radius or bounding box search scans a handful of prefix
+//! ranges of the index and then filters by the exact distance or bounds.
+
+use std::{
+    collections::BTreeSet,
+    sync::LazyLock,
+};
+
+use errors::ErrorMetadata;
+use value::{
+    ConvexValue,
+    FieldPath,
+    IdentifierFieldName,
+};
+
+/// The derived field that a geospatial index orders documents by.
+pub static GEOHASH_FIELD: LazyLock<IdentifierFieldName> = LazyLock::new(|| {
+    "_geohash"
+        .parse()
+        .expect("_geohash is not a valid field name")
+});
+
+/// Number of base32 characters in an index key, about 4cm x 2cm at the
+/// equator.
+pub const GEOHASH_PRECISION: usize = 12;
+
+/// Searches use the finest precision whose cells cover the area in at most
+/// this many prefix ranges.
+const MAX_COVERING_CELLS: u64 = 16;
+
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Mean radius of the earth.
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// A point on the earth, stored in documents as
+/// `{ latitude: number, longitude: number }`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Point {
+    pub fn new(latitude: f64, longitude: f64) -> anyhow::Result<Self> {
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidGeospatialPoint",
+                format!(
+                    "({latitude}, {longitude}) is not a valid point: latitude must be between -90 \
+                     and 90 and longitude between -180 and 180"
+                ),
+            ));
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+        })
+    }
+
+    /// Reads a point from a document field, or `None` if the value isn't a
+    /// valid point. Documents without a valid point are left out of
+    /// geospatial searches.
+    pub fn from_value(value: &ConvexValue) -> Option<Self> {
+        let ConvexValue::Object(object) = value else {
+            return None;
+        };
+        let coordinate = |name: &str| match object.get(name)? {
+            ConvexValue::Float64(f) => Some(*f),
+            _ => None,
+        };
+        Self::new(coordinate("latitude")?, coordinate("longitude")?).ok()
+    }
+
+    /// Great-circle distance using the haversine formula.
+    pub fn distance_meters(&self, other: &Point) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlng = (other.longitude - self.longitude).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlng / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// If `field` is a geospatial index field, i.e. ends in `_geohash`, returns
+/// the path of the point it's derived from.
+pub fn location_field(field: &FieldPath) -> Option<FieldPath> {
+    let (last, parent) = field.fields().split_last()?;
+    if *last != *GEOHASH_FIELD || parent.is_empty() {
+        return None;
+    }
+    FieldPath::new(parent.to_vec()).ok()
+}
+
+/// The index field for a geospatial index over `location`.
+pub fn geospatial_index_field(location: &FieldPath) -> FieldPath {
+    let mut fields: Vec<_> = location.fields().to_vec();
+    fields.push(GEOHASH_FIELD.clone());
+    FieldPath::new(fields).expect("Location field path is non-empty")
+}
+
+/// The index key value for a point field, or `None` if it isn't a point.
+pub fn geohash_key(value: &ConvexValue) -> Option<ConvexValue> {
+    let point = Point::from_value(value)?;
+    ConvexValue::try_from(geohash(point, GEOHASH_PRECISION)).ok()
+}
+
+/// Encodes `point` as a geohash with `precision` base32 characters.
+pub fn geohash(point: Point, precision: usize) -> String {
+    let (mut lat_range, mut lng_range) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut hash = String::with_capacity(precision);
+    let mut even_bit = true;
+    let mut bits = 0;
+    let mut index = 0;
+    while hash.len() < precision {
+        let (range, value) = if even_bit {
+            (&mut lng_range, point.longitude)
+        } else {
+            (&mut lat_range, point.latitude)
+        };
+        let mid = (range.0 + range.1) / 2.0;
+        index <<= 1;
+        if value >= mid {
+            index |= 1;
+            range.0 = mid;
+        } else {
+            range.1 = mid;
+        }
+        even_bit = !even_bit;
+        bits += 1;
+        if bits == 5 {
+            hash.push(BASE32[index] as char);
+            bits = 0;
+            index = 0;
+        }
+    }
+    hash
+}
+
+/// Width and height in degrees of a geohash cell with `precision` characters.
+fn cell_size(precision: usize) -> (f64, f64) {
+    let bits = 5 * precision as i32;
+    let lng_bits = (bits + 1) / 2;
+    let lat_bits = bits / 2;
+    (360.0 / 2f64.powi(lng_bits), 180.0 / 2f64.powi(lat_bits))
+}
+
+/// The area a geospatial search returns documents from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeoArea {
+    Radius { center: Point, meters: f64 },
+    BoundingBox { southwest: Point, northeast: Point },
+}
+
+impl GeoArea {
+    pub fn contains(&self, point: Point) -> bool {
+        match self {
+            GeoArea::Radius { center, meters } => center.distance_meters(&point) <= *meters,
+            GeoArea::BoundingBox {
+                southwest,
+                northeast,
+            } => {
+                let in_latitude =
+                    southwest.latitude <= point.latitude && point.latitude <= northeast.latitude;
+                let in_longitude = if southwest.longitude <= northeast.longitude {
+                    southwest.longitude <= point.longitude && point.longitude <= northeast.longitude
+                } else {
+                    // The box crosses the antimeridian.
+                    southwest.longitude <= point.longitude || point.longitude <= northeast.longitude
+                };
+                in_latitude && in_longitude
+            },
+        }
+    }
+
+    /// Bounding boxes as (min latitude, min longitude, max latitude, max
+    /// longitude), split in two if the area crosses the antimeridian.
+    fn bounds(&self) -> Vec<(f64, f64, f64, f64)> {
+        let (min_lat, min_lng, max_lat, max_lng) = match self {
+            GeoArea::Radius { center, meters } => {
+                let angle = meters / EARTH_RADIUS_METERS;
+                let dlat = angle.to_degrees();
+                let min_lat = center.latitude - dlat;
+                let max_lat = center.latitude + dlat;
+                if min_lat <= -90.0 || max_lat >= 90.0 {
+                    // The circle includes a pole, so it spans every longitude.
+                    return vec![(min_lat.max(-90.0), -180.0, max_lat.min(90.0), 180.0)];
+                }
+                let ratio = angle.sin() / center.latitude.to_radians().cos();
+                if ratio >= 1.0 {
+                    return vec![(min_lat, -180.0, max_lat, 180.0)];
+                }
+                let dlng = ratio.asin().to_degrees();
+                (
+                    min_lat,
+                    center.longitude - dlng,
+                    max_lat,
+                    center.longitude + dlng,
+                )
+            },
+            GeoArea::BoundingBox {
+                southwest,
+                northeast,
+            } => (
+                southwest.latitude,
+                southwest.longitude,
+                northeast.latitude,
+                northeast.longitude
+                    + if southwest.longitude > northeast.longitude {
+                        360.0
+                    } else {
+                        0.0
+                    },
+            ),
+        };
+        if min_lng < -180.0 {
+            vec![
+                (min_lat, min_lng + 360.0, max_lat, 180.0),
+                (min_lat, -180.0, max_lat, max_lng),
+            ]
+        } else if max_lng > 180.0 {
+            vec![
+                (min_lat, min_lng, max_lat, 180.0),
+                (min_lat, -180.0, max_lat, max_lng - 360.0),
+            ]
+        } else {
+            vec![(min_lat, min_lng, max_lat, max_lng)]
+        }
+    }
+
+    /// Geohash prefixes whose cells together cover the area. An empty prefix
+    /// covers the whole earth.
+    pub fn covering_cells(&self) -> BTreeSet<String> {
+        let bounds = self.bounds();
+        for precision in (1..=GEOHASH_PRECISION).rev() {
+            let (width, height) = cell_size(precision);
+            let grids: Vec<_> = bounds
+                .iter()
+                .map(|&(min_lat, min_lng, max_lat, max_lng)| {
+                    (
+                        cell_indexes(min_lng + 180.0, max_lng + 180.0, width, 360.0),
+                        cell_indexes(min_lat + 90.0, max_lat + 90.0, height, 180.0),
+                    )
+                })
+                .collect();
+            let num_cells: u64 = grids
+                .iter()
+                .map(|((first_column, last_column), (first_row, last_row))| {
+                    (last_column - first_column + 1) * (last_row - first_row + 1)
+                })
+                .sum();
+            if num_cells > MAX_COVERING_CELLS {
+                continue;
+            }
+            let mut cells = BTreeSet::new();
+            for ((first_column, last_column), (first_row, last_row)) in grids {
+                for column in first_column..=last_column {
+                    for row in first_row..=last_row {
+                        let center = Point {
+                            latitude: -90.0 + (row as f64 + 0.5) * height,
+                            longitude: -180.0 + (column as f64 + 0.5) * width,
+                        };
+                        cells.insert(geohash(center, precision));
+                    }
+                }
+            }
+            return cells;
+        }
+        BTreeSet::from([String::new()])
+    }
+}
+
+/// The first and last cells of `size` degrees that overlap `min..=max`, where
+/// both are offsets into a dimension that's `extent` degrees long.
+fn cell_indexes(min: f64, max: f64, size: f64, extent: f64) -> (u64, u64) {
+    let last = (extent / size) as u64 - 1;
+    let index = |offset: f64| ((offset / size).floor().max(0.0) as u64).min(last);
+    (index(min), index(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        geohash,
+        GeoArea,
+        Point,
+    };
+
+    #[test]
+    fn test_geohash() -> anyhow::Result<()> {
+        let point = Point::new(57.64911, 10.40744)?;
+        assert_eq!(geohash(point, 11), "u4pruydqqvj");
+        Ok(())
+    }
+
+    #[test]
+    fn test_distance() -> anyhow::Result<()> {
+        let london = Point::new(51.5074, -0.1278)?;
+        let paris = Point::new(48.8566, 2.3522)?;
+        let meters = london.distance_meters(&paris);
+        assert!((343_000.0..345_000.0).contains(&meters), "{meters}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_covering_cells_contain_points_in_area() -> anyhow::Result<()> {
+        let center = Point::new(37.7749, -122.4194)?;
+        let area = GeoArea::Radius {
+            center,
+            meters: 2_000.0,
+        };
+        let cells = area.covering_cells();
+        assert!(!cells.contains(""));
+        for point in [
+            center,
+            Point::new(37.7849, -122.4194)?,
+            Point::new(37.7749, -122.4394)?,
+        ] {
+            assert!(area.contains(point));
+            let hash = geohash(point, 12);
+            assert!(cells.iter().any(|cell| hash.starts_with(cell)), "{hash}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounding_box_across_antimeridian() -> anyhow::Result<()> {
+        let area = GeoArea::BoundingBox {
+            southwest: Point::new(-20.0, 170.0)?,
+            northeast: Point::new(-10.0, -170.0)?,
+        };
+        let fiji = Point::new(-17.7, 178.0)?;
+        let samoa = Point::new(-13.8, -172.0)?;
+        for point in [fiji, samoa] {
+            assert!(area.contains(point));
+            let hash = geohash(point, 12);
+            assert!(area
+                .covering_cells()
+                .iter()
+                .any(|cell| hash.starts_with(cell)));
+        }
+        assert!(!area.contains(Point::new(-15.0, 0.0)?));
+        Ok(())
+    }
+}
@@ -10,6 +10,7 @@ use std::{
 use futures::Future;
 use pb::error_metadata::ErrorMetadataStatusExt;
 use sentry::integrations::tower as sentry_tower;
+use tokio::net::TcpListener;
 use tokio_metrics::Instrumented;
 use tonic::{
     server::NamedService,
@@ -29,8 +30,11 @@ use tower::{
     ServiceBuilder,
 };
 
+use self::tls::GrpcTls;
 use crate::runtime::TaskManager;
 
+pub mod tls;
+
 pub struct ConvexGrpcService {
     routes: Routes,
     health_reporter: HealthReporter,
@@ -77,20 +81,29 @@ impl ConvexGrpcService {
             .layer(sentry_tower::NewSentryLayer::new_from_top())
             .layer(sentry_tower::SentryHttpLayer::with_transaction());
 
+        let tls = GrpcTls::from_knobs()?;
         tracing::info!(
-            "gRPC services {} listening on ipv4://{addr}",
-            self.service_names.join(",")
+            "gRPC services {} listening on ipv4://{addr}{}",
+            self.service_names.join(","),
+            if tls.is_some() { " with mTLS" } else { "" }
         );
         for service_name in self.service_names {
             self.health_reporter
                 .set_service_status(service_name, ServingStatus::Serving)
                 .await;
         }
-        tonic::transport::Server::builder()
+        let router = tonic::transport::Server::builder()
             .layer(convex_layers)
-            .add_routes(self.routes)
-            .serve_with_shutdown(addr, shutdown)
-            .await?;
+            .add_routes(self.routes);
+        match tls {
+            Some(tls) => {
+                let listener = TcpListener::bind(addr).await?;
+                router
+                    .serve_with_incoming_shutdown(tls.incoming(listener), shutdown)
+                    .await?;
+            },
+            None => router.serve_with_shutdown(addr, shutdown).await?,
+        }
         tracing::info!("GRPC server shutdown complete");
         Ok(())
     }
@@ -0,0 +1,352 @@
+//! Mutual TLS between backend components. When `GRPC_TLS_CERT_PATH`,
+//! `GRPC_TLS_KEY_PATH` and `GRPC_TLS_CA_PATH` are set, gRPC servers only
+//! accept clients presenting a certificate signed by the CA and clients only
+//! connect to servers that do the same. The files are checked for changes every
+//! `GRPC_TLS_RELOAD_INTERVAL`, so certificates can be rotated without a
+//! restart: established connections keep their certificates and new ones use
+//! the reloaded files.
+
+use std::{
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+    pin::Pin,
+    sync::Arc,
+    task::{
+        Context,
+        Poll,
+    },
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
+};
+
+use anyhow::Context as _;
+use futures::{
+    stream,
+    Stream,
+    StreamExt,
+};
+use http::Uri;
+use hyper_util::rt::TokioIo;
+use parking_lot::Mutex;
+use tokio::{
+    io::{
+        AsyncRead,
+        AsyncWrite,
+        ReadBuf,
+    },
+    net::{
+        TcpListener,
+        TcpStream,
+    },
+};
+use tokio_rustls::{
+    client,
+    rustls::{
+        crypto::ring::default_provider,
+        pki_types::{
+            CertificateDer,
+            PrivateKeyDer,
+            ServerName,
+        },
+        server::WebPkiClientVerifier,
+        ClientConfig,
+        RootCertStore,
+        ServerConfig,
+    },
+    server,
+    TlsAcceptor,
+    TlsConnector,
+};
+use tonic::transport::{
+    server::{
+        Connected,
+        TcpConnectInfo,
+    },
+    Channel,
+    Endpoint,
+};
+
+use crate::knobs::{
+    GRPC_TLS_CA_PATH,
+    GRPC_TLS_CERT_PATH,
+    GRPC_TLS_KEY_PATH,
+    GRPC_TLS_RELOAD_INTERVAL,
+};
+
+/// Handshakes still running after this long are dropped so a client that
+/// stalls can't hold a slot forever.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MAX_CONCURRENT_HANDSHAKES: usize = 128;
+
+/// How long to wait before accepting again after `accept` fails, e.g. when the
+/// process is out of file descriptors.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug)]
+pub struct GrpcTlsPaths {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    pub ca: PathBuf,
+}
+
+impl GrpcTlsPaths {
+    /// The paths set by knobs, or `None` if mTLS isn't configured.
+    pub fn from_knobs() -> anyhow::Result<Option<Self>> {
+        match (
+            GRPC_TLS_CERT_PATH.clone(),
+            GRPC_TLS_KEY_PATH.clone(),
+            GRPC_TLS_CA_PATH.clone(),
+        ) {
+            (Some(cert), Some(key), Some(ca)) => Ok(Some(Self { cert, key, ca })),
+            (None, None, None) => Ok(None),
+            _ => anyhow::bail!(
+                "GRPC_TLS_CERT_PATH, GRPC_TLS_KEY_PATH and GRPC_TLS_CA_PATH must be set together"
+            ),
+        }
+    }
+
+    fn modified(&self) -> io::Result<[SystemTime; 3]> {
+        Ok([
+            fs::metadata(&self.cert)?.modified()?,
+            fs::metadata(&self.key)?.modified()?,
+            fs::metadata(&self.ca)?.modified()?,
+        ])
+    }
+}
+
+struct LoadedConfig {
+    server: Arc<ServerConfig>,
+    client: Arc<ClientConfig>,
+    modified: [SystemTime; 3],
+    checked_at: Instant,
+}
+
+impl LoadedConfig {
+    fn read(paths: &GrpcTlsPaths) -> anyhow::Result<Self> {
+        // Check modification times before reading, so a file replaced while
+        // we're reading is picked up by the next check.
+        let modified = paths.modified()?;
+        let certs = read_certs(&paths.cert)?;
+        let key = read_key(&paths.key)?;
+        let mut roots = RootCertStore::empty();
+        for ca in read_certs(&paths.ca)? {
+            roots.add(ca)?;
+        }
+        let roots = Arc::new(roots);
+        let provider = Arc::new(default_provider());
+
+        let verifier =
+            WebPkiClientVerifier::builder_with_provider(roots.clone(), provider.clone()).build()?;
+        let mut server = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(certs.clone(), key.clone_key())?;
+        server.alpn_protocols = vec![b"h2".to_vec()];
+
+        let mut client = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots)
+            .with_client_auth_cert(certs, key)?;
+        client.alpn_protocols = vec![b"h2".to_vec()];
+
+        Ok(Self {
+            server: Arc::new(server),
+            client: Arc::new(client),
+            modified,
+            checked_at: Instant::now(),
+        })
+    }
+}
+
+fn read_certs(path: &Path) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let pem = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let certs: Vec<_> = rustls_pemfile::certs(&mut &pem[..]).try_collect()?;
+    anyhow::ensure!(!certs.is_empty(), "No certificates in {}", path.display());
+    Ok(certs)
+}
+
+fn read_key(path: &Path) -> anyhow::Result<PrivateKeyDer<'static>> {
+    let pem = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    rustls_pemfile::private_key(&mut &pem[..])?
+        .with_context(|| format!("No private key in {}", path.display()))
+}
+
+/// TLS configuration for gRPC servers and clients, reloaded from disk when
+/// the certificate files change.
+pub struct GrpcTls {
+    paths: GrpcTlsPaths,
+    loaded: Mutex<LoadedConfig>,
+}
+
+impl GrpcTls {
+    pub fn load(paths: GrpcTlsPaths) -> anyhow::Result<Self> {
+        let loaded = LoadedConfig::read(&paths)?;
+        Ok(Self {
+            paths,
+            loaded: Mutex::new(loaded),
+        })
+    }
+
+    /// Loads the certificates set by knobs, or returns `None` if mTLS isn't
+    /// configured.
+    pub fn from_knobs() -> anyhow::Result<Option<Arc<Self>>> {
+        GrpcTlsPaths::from_knobs()?
+            .map(|paths| Self::load(paths).map(Arc::new))
+            .transpose()
+    }
+
+    /// The current configuration, reloading it first if it's been
+    /// `GRPC_TLS_RELOAD_INTERVAL` since the files were last checked and they
+    /// have changed. A reload that fails keeps the previous certificates.
+    fn current(&self) -> (Arc<ServerConfig>, Arc<ClientConfig>) {
+        let mut loaded = self.loaded.lock();
+        if loaded.checked_at.elapsed() >= *GRPC_TLS_RELOAD_INTERVAL {
+            loaded.checked_at = Instant::now();
+            match self.paths.modified() {
+                Ok(modified) if modified == loaded.modified => {},
+                Ok(_) => match LoadedConfig::read(&self.paths) {
+                    Ok(reloaded) => {
+                        tracing::info!("Reloaded gRPC TLS certificates");
+                        *loaded = reloaded;
+                    },
+                    Err(e) => tracing::error!(
+                        "Failed to reload gRPC TLS certificates, keeping the previous ones: {e:#}"
+                    ),
+                },
+                Err(e) => tracing::error!("Failed to check gRPC TLS certificates: {e}"),
+            }
+        }
+        (loaded.server.clone(), loaded.client.clone())
+    }
+
+    /// Accepts connections on `listener` and completes their TLS handshakes,
+    /// dropping connections that fail to handshake.
+    pub fn incoming(
+        self: Arc<Self>,
+        listener: TcpListener,
+    ) -> impl Stream<Item = io::Result<GrpcTlsStream>> {
+        stream::unfold(listener, |listener| async move {
+            loop {
+                match listener.accept().await {
+                    Ok((tcp, _)) => return Some((tcp, listener)),
+                    Err(e) => {
+                        tracing::warn!("Failed to accept gRPC connection: {e}");
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    },
+                }
+            }
+        })
+        .map(move |tcp| {
+            let acceptor = TlsAcceptor::from(self.current().0);
+            tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(tcp))
+        })
+        .buffer_unordered(MAX_CONCURRENT_HANDSHAKES)
+        .filter_map(|result| async move {
+            match result {
+                Ok(Ok(stream)) => Some(Ok(GrpcTlsStream(stream))),
+                Ok(Err(e)) => {
+                    tracing::warn!("gRPC TLS handshake failed: {e}");
+                    None
+                },
+                Err(_) => {
+                    tracing::warn!("gRPC TLS handshake timed out");
+                    None
+                },
+            }
+        })
+    }
+
+    /// A channel to `endpoint` that connects over TLS with the certificates
+    /// current at the time of each connection. The endpoint's scheme is
+    /// ignored.
+    pub fn channel(self: Arc<Self>, endpoint: Endpoint) -> Channel {
+        endpoint.connect_with_connector_lazy(tower::service_fn(move |uri: Uri| {
+            let tls = self.clone();
+            async move { tls.connect(uri).await }
+        }))
+    }
+
+    async fn connect(&self, uri: Uri) -> io::Result<TokioIo<client::TlsStream<TcpStream>>> {
+        let host = uri
+            .host()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("{uri} has no host"))
+            })?
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let port = uri.port_u16().unwrap_or(443);
+        let server_name = ServerName::try_from(host.to_string())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let tcp = TcpStream::connect((host, port)).await?;
+        let connector = TlsConnector::from(self.current().1);
+        Ok(TokioIo::new(connector.connect(server_name, tcp).await?))
+    }
+}
+
+/// A channel to another backend component's gRPC server, over mTLS if `tls`
+/// is set and plaintext otherwise.
+pub fn channel(tls: Option<Arc<GrpcTls>>, endpoint: Endpoint) -> Channel {
+    match tls {
+        Some(tls) => tls.channel(endpoint),
+        None => endpoint.connect_lazy(),
+    }
+}
+
+/// A server-side connection that has completed its TLS handshake.
+pub struct GrpcTlsStream(server::TlsStream<TcpStream>);
+
+impl Connected for GrpcTlsStream {
+    type ConnectInfo = TcpConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.0.get_ref().0.connect_info()
+    }
+}
+
+impl AsyncRead for GrpcTlsStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for GrpcTlsStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.0.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
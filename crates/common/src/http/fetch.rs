@@ -3,32 +3,66 @@ use std::{
         BTreeMap,
         HashMap,
     },
+    net::SocketAddr,
     sync::{
         atomic::{
             AtomicU64,
             Ordering,
         },
+        Arc,
         LazyLock,
     },
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 use async_trait::async_trait;
 use futures::{
-    future::BoxFuture,
+    future::{
+        self,
+        BoxFuture,
+    },
     StreamExt,
     TryStreamExt,
 };
 use http::StatusCode;
+use parking_lot::Mutex;
 use reqwest::{
+    dns::{
+        Addrs,
+        Name,
+        Resolve,
+        Resolving,
+    },
     redirect,
     Body,
     Proxy,
     Url,
 };
+use tokio::sync::{
+    OwnedSemaphorePermit,
+    Semaphore,
+};
 
-use crate::http::{
-    HttpRequestStream,
-    HttpResponseStream,
+use crate::{
+    http::{
+        HttpRequestStream,
+        HttpResponseStream,
+    },
+    knobs::{
+        FETCH_DNS_CACHE_TTL,
+        FETCH_MAX_CONCURRENT_REQUESTS,
+        FETCH_MAX_CONCURRENT_REQUESTS_PER_DESTINATION,
+        FETCH_POOL_IDLE_TIMEOUT,
+        FETCH_POOL_MAX_IDLE_PER_HOST,
+    },
+    metrics::{
+        log_fetch_dns_lookup,
+        log_fetch_in_flight,
+        log_fetch_permit_wait,
+    },
 };
 
 /// Http client used for fetch syscall.
@@ -52,17 +86,27 @@ pub trait FetchClient: Send + Sync {
 
 pub static INTERNAL_HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
 
+/// Fetch client for actions. A single client is shared by all actions in the
+/// backend, so its connection pool and concurrency limits apply across them.
 pub struct ProxiedFetchClient {
     http_client:
         LazyLock<reqwest::Client, Box<dyn FnOnce() -> reqwest::Client + Send + Sync + 'static>>,
     internal_http_client: reqwest::Client,
+    limiter: FetchLimiter,
 }
 
 impl ProxiedFetchClient {
     pub fn new(proxy_url: Option<Url>, client_id: String) -> Self {
         Self {
             http_client: LazyLock::new(Box::new(move || {
-                let mut builder = reqwest::Client::builder().redirect(redirect::Policy::none());
+                let mut builder = reqwest::Client::builder()
+                    .redirect(redirect::Policy::none())
+                    .pool_max_idle_per_host(*FETCH_POOL_MAX_IDLE_PER_HOST)
+                    .pool_idle_timeout(*FETCH_POOL_IDLE_TIMEOUT);
+                if !FETCH_DNS_CACHE_TTL.is_zero() {
+                    builder =
+                        builder.dns_resolver(Arc::new(CachingResolver::new(*FETCH_DNS_CACHE_TTL)));
+                }
                 // It's okay to panic on these errors, as they indicate a serious programming
                 // error -- building the reqwest client is expected to be infallible.
                 if let Some(proxy_url) = proxy_url {
@@ -79,8 +123,134 @@ impl ProxiedFetchClient {
                 builder.build().expect("Failed to build reqwest client")
             })),
             internal_http_client: INTERNAL_HTTP_CLIENT.clone(),
+            limiter: FetchLimiter::new(
+                *FETCH_MAX_CONCURRENT_REQUESTS,
+                *FETCH_MAX_CONCURRENT_REQUESTS_PER_DESTINATION,
+            ),
+        }
+    }
+}
+
+/// How many destinations `FetchLimiter` tracks before it drops the ones
+/// without requests in flight.
+const MAX_IDLE_DESTINATIONS: usize = 1024;
+
+/// Limits how many fetches can be in flight at once, both in total and to
+/// each destination host and port. Fetches over the limit wait for a slot.
+pub struct FetchLimiter {
+    max_total: usize,
+    max_per_destination: usize,
+    total: Arc<Semaphore>,
+    per_destination: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl FetchLimiter {
+    pub fn new(max_total: usize, max_per_destination: usize) -> Self {
+        Self {
+            max_total,
+            max_per_destination,
+            total: Arc::new(Semaphore::new(max_total)),
+            per_destination: Mutex::new(HashMap::new()),
         }
     }
+
+    pub async fn acquire(&self, url: &Url) -> anyhow::Result<FetchPermit> {
+        let destination = format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        );
+        let destination_semaphore = {
+            let mut per_destination = self.per_destination.lock();
+            if per_destination.len() >= MAX_IDLE_DESTINATIONS {
+                // Permits hold a reference to their semaphore, so a semaphore
+                // with no other references has nothing in flight.
+                per_destination.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+            }
+            per_destination
+                .entry(destination)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.max_per_destination)))
+                .clone()
+        };
+        let start = Instant::now();
+        // Wait on the destination first so fetches queued behind one busy
+        // destination don't hold slots that other destinations could use.
+        let destination_permit = destination_semaphore.acquire_owned().await?;
+        let total_permit = self.total.clone().acquire_owned().await?;
+        log_fetch_permit_wait(start.elapsed());
+        let permit = FetchPermit {
+            max_total: self.max_total,
+            total: self.total.clone(),
+            permits: Some((destination_permit, total_permit)),
+        };
+        log_fetch_in_flight(permit.in_flight());
+        Ok(permit)
+    }
+}
+
+/// A slot for one fetch, held until its response body has been read or
+/// dropped.
+pub struct FetchPermit {
+    max_total: usize,
+    total: Arc<Semaphore>,
+    permits: Option<(OwnedSemaphorePermit, OwnedSemaphorePermit)>,
+}
+
+impl FetchPermit {
+    fn in_flight(&self) -> usize {
+        self.max_total - self.total.available_permits()
+    }
+}
+
+impl Drop for FetchPermit {
+    fn drop(&mut self) {
+        self.permits.take();
+        log_fetch_in_flight(self.in_flight());
+    }
+}
+
+/// Resolves hostnames for the fetch client, caching the results for a TTL.
+struct CachingResolver {
+    ttl: Duration,
+    cache: Arc<Mutex<HashMap<String, (Instant, Arc<[SocketAddr]>)>>>,
+}
+
+impl CachingResolver {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_owned();
+        if let Some((resolved_at, addrs)) = self.cache.lock().get(&host)
+            && resolved_at.elapsed() < self.ttl
+        {
+            log_fetch_dns_lookup(true);
+            let addrs: Addrs = Box::new(addrs.to_vec().into_iter());
+            return Box::pin(future::ready(Ok(addrs)));
+        }
+        log_fetch_dns_lookup(false);
+        let ttl = self.ttl;
+        let cache = self.cache.clone();
+        Box::pin(async move {
+            let resolved: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            {
+                let mut cache = cache.lock();
+                if cache.len() >= MAX_IDLE_DESTINATIONS {
+                    cache.retain(|_, (resolved_at, _)| resolved_at.elapsed() < ttl);
+                }
+                cache.insert(host, (Instant::now(), resolved.clone().into()));
+            }
+            let addrs: Addrs = Box::new(resolved.into_iter());
+            Ok(addrs)
+        })
+    }
 }
 
 #[async_trait]
@@ -95,6 +265,7 @@ impl FetchClient for ProxiedFetchClient {
             request_builder = request_builder.header(name.as_str(), value.as_bytes());
         }
         let raw_request = request_builder.build()?;
+        let permit = self.limiter.acquire(&request.url).await?;
         let raw_response = self.http_client.execute(raw_request).await?;
         if raw_response.status() == StatusCode::PROXY_AUTHENTICATION_REQUIRED {
             // SSRF mitigated -- our proxy blocked this request because it was
@@ -104,11 +275,19 @@ impl FetchClient for ProxiedFetchClient {
         }
         let status = raw_response.status();
         let headers = raw_response.headers().to_owned();
+        let body = raw_response
+            .bytes_stream()
+            .map(move |chunk| {
+                // Hold the permit until the body is consumed or dropped.
+                let _permit = &permit;
+                chunk.map_err(|e| e.into())
+            })
+            .boxed();
         let response = HttpResponseStream {
             status,
             headers,
             url: Some(request.url),
-            body: Some(raw_response.bytes_stream().map_err(|e| e.into()).boxed()),
+            body: Some(body),
         };
         Ok(response)
     }
@@ -207,6 +386,7 @@ impl FetchClient for StaticFetchClient {
 
 pub enum InternalFetchPurpose {
     AccessTokenAuth,
+    LogStreaming,
 }
 
 #[cfg(test)]
@@ -219,7 +399,10 @@ mod tests {
         StatusCode,
     };
 
-    use super::ProxiedFetchClient;
+    use super::{
+        FetchLimiter,
+        ProxiedFetchClient,
+    };
     use crate::http::{
         categorize_http_response_stream,
         fetch::{
@@ -254,6 +437,30 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_fetch_limiter_per_destination() -> anyhow::Result<()> {
+        let limiter = FetchLimiter::new(3, 1);
+        let first = limiter.acquire(&"https://a.example.com/x".parse()?).await?;
+        // The same host and port shares a limit, even on a different path.
+        assert!(limiter
+            .acquire(&"https://a.example.com/y".parse()?)
+            .now_or_never()
+            .is_none());
+        // Other destinations aren't blocked.
+        let _other = limiter.acquire(&"https://b.example.com".parse()?).await?;
+        let _other_port = limiter
+            .acquire(&"https://a.example.com:8443".parse()?)
+            .await?;
+        // But the total limit applies across destinations.
+        assert!(limiter
+            .acquire(&"https://c.example.com".parse()?)
+            .now_or_never()
+            .is_none());
+        drop(first);
+        limiter.acquire(&"https://a.example.com/y".parse()?).await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_static_fetch_client() {
         let handler = |request: HttpRequestStream| {
@@ -1,5 +1,6 @@
 use std::{
     borrow::Cow,
+    collections::BTreeMap,
     convert::Infallible,
     fmt,
     future::Future,
@@ -104,6 +105,11 @@ use utoipa::ToSchema;
 use self::metrics::log_http_request;
 use crate::{
     errors::report_error_sync,
+    execution_context::RequestContext,
+    fastrace_helpers::{
+        get_sampled_span,
+        is_trace_export_enabled,
+    },
     knobs::HTTP_SERVER_TCP_BACKLOG,
     metrics::log_client_version_unsupported,
     runtime::TaskManager,
@@ -169,7 +175,7 @@ mod metrics {
 #[allow(clippy::declare_interior_mutable_const)]
 pub const APPLICATION_JSON_CONTENT_TYPE: HeaderValue = HeaderValue::from_static("application/json");
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct HttpRequest {
     pub headers: HeaderMap,
     pub url: Url,
@@ -822,11 +828,24 @@ pub async fn stats_middleware<RM: RouteMapper>(
         .map(|r| r.as_str().to_owned())
         .unwrap_or("unknown".to_owned());
 
-    // Sampling isn't done here, and should be done upstream
+    // Requests are usually sampled upstream, which sets `traceparent`. If we're
+    // exporting traces ourselves, sample the rest here.
     let root = match traceparent {
         Some(span_ctx) => Span::root(route.to_owned(), span_ctx),
+        None if is_trace_export_enabled() => get_sampled_span(
+            &resolved_host.instance_name,
+            &route,
+            &mut rand::thread_rng(),
+            BTreeMap::new(),
+        ),
         None => Span::noop(),
-    };
+    }
+    .with_properties(|| {
+        [
+            ("http.request.method", method.to_string()),
+            ("http.route", route.clone()),
+        ]
+    });
     let resp = next.run(req).in_span(root).await;
 
     let client_version_s = client_version.to_string();
@@ -1041,6 +1060,42 @@ where
     }
 }
 
+/// Header containing a JSON object of string values that's attached to the
+/// request as its `RequestContext`.
+#[allow(clippy::declare_interior_mutable_const)]
+pub const CONVEX_REQUEST_CONTEXT_HEADER: HeaderName =
+    HeaderName::from_static("convex-request-context");
+
+pub fn request_context_from_headers(headers: &HeaderMap) -> anyhow::Result<RequestContext> {
+    let Some(header) = headers.get(CONVEX_REQUEST_CONTEXT_HEADER) else {
+        return Ok(RequestContext::default());
+    };
+    let header = header.to_str().map_err(|_| {
+        ErrorMetadata::bad_request(
+            "InvalidRequestContext",
+            "Request context header must be valid ASCII",
+        )
+    })?;
+    header.parse()
+}
+
+pub struct ExtractRequestContext(pub RequestContext);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ExtractRequestContext
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpResponseError;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self(request_context_from_headers(&parts.headers)?))
+    }
+}
+
 pub const TRACEPARENT_HEADER: &str = "traceparent";
 
 pub struct ExtractTraceparent(pub Option<SpanContext>);
@@ -14,6 +14,7 @@
 
 use std::{
     num::NonZeroU32,
+    path::PathBuf,
     sync::LazyLock,
     time::Duration,
 };
@@ -61,6 +62,11 @@ pub static RUNTIME_DISABLE_LIFO_SLOT: LazyLock<bool> =
 pub static UDF_CACHE_MAX_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("UDF_CACHE_MAX_SIZE", 104857600));
 
+/// Maximum number of queries whose declared cache keys the UDF cache
+/// remembers. Least recently used entries are evicted past this.
+pub static UDF_CACHE_MAX_QUERY_CACHE_KEYS: LazyLock<usize> =
+    LazyLock::new(|| env_config("UDF_CACHE_MAX_QUERY_CACHE_KEYS", 10_000));
+
 /// Maximum size of the shared UDF cache in Conductor. Default 500MiB.
 pub static SHARED_UDF_CACHE_MAX_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("SHARED_UDF_CACHE_MAX_SIZE", 5 * 104857600));
@@ -113,6 +119,147 @@ pub static HEAP_WORKER_REPORT_INTERVAL_SECONDS: LazyLock<Duration> =
 pub static ACTION_USER_TIMEOUT: LazyLock<Duration> =
     LazyLock::new(|| Duration::from_secs(env_config("ACTIONS_USER_TIMEOUT_SECS", 600)));
 
+/// Timeout on the CPU time a V8 action spends running JavaScript, separate from
+/// `ACTION_USER_TIMEOUT`, which counts wall clock time including awaited
+/// fetches. Only enforced when lower than the wall clock timeout.
+pub static ACTION_CPU_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("ACTIONS_CPU_TIMEOUT_SECS", 600)));
+
+/// Maximum number of outbound `fetch` requests from actions that can be in
+/// flight at once across the whole backend. Further requests wait for a slot.
+pub static FETCH_MAX_CONCURRENT_REQUESTS: LazyLock<usize> =
+    LazyLock::new(|| env_config("FETCH_MAX_CONCURRENT_REQUESTS", 1024));
+
+/// Maximum number of outbound `fetch` requests from actions that can be in
+/// flight at once to a single host and port.
+pub static FETCH_MAX_CONCURRENT_REQUESTS_PER_DESTINATION: LazyLock<usize> =
+    LazyLock::new(|| env_config("FETCH_MAX_CONCURRENT_REQUESTS_PER_DESTINATION", 64));
+
+/// Maximum number of idle connections the `fetch` client keeps open to a single
+/// host for reuse.
+pub static FETCH_POOL_MAX_IDLE_PER_HOST: LazyLock<usize> =
+    LazyLock::new(|| env_config("FETCH_POOL_MAX_IDLE_PER_HOST", 32));
+
+/// How long the `fetch` client keeps an idle connection open for reuse.
+pub static FETCH_POOL_IDLE_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("FETCH_POOL_IDLE_TIMEOUT_SECS", 90)));
+
+/// How long the `fetch` client caches DNS lookups. Set to 0 to disable the
+/// cache.
+pub static FETCH_DNS_CACHE_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("FETCH_DNS_CACHE_TTL_SECS", 60)));
+
+/// How long a secret fetched for an environment variable secret reference is
+/// cached before it's fetched again. Rotated secrets take up to this long to be
+/// picked up.
+pub static SECRETS_CACHE_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SECRETS_CACHE_TTL_SECS", 300)));
+
+/// How long a cached secret can still be used when refetching it from its
+/// secrets manager fails.
+pub static SECRETS_MAX_STALENESS: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SECRETS_MAX_STALENESS_SECS", 3600)));
+
+/// Longest lifetime a function can give a service token it mints with
+/// `ctx.auth.mintServiceToken`.
+pub static SERVICE_TOKEN_MAX_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SERVICE_TOKEN_MAX_TTL_SECS", 24 * 60 * 60)));
+
+/// Longest lifetime a function can give a URL it creates with
+/// `storage.getSignedUrl`.
+pub static SIGNED_FILE_URL_MAX_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config("SIGNED_FILE_URL_MAX_TTL_SECS", 7 * 24 * 60 * 60))
+});
+
+/// Whether image URLs from `storage.getImageUrl` can be created and served.
+pub static IMAGE_TRANSFORMS_ENABLED: LazyLock<bool> =
+    LazyLock::new(|| env_config("IMAGE_TRANSFORMS_ENABLED", true));
+
+/// Largest width or height an image transform can produce.
+pub static IMAGE_TRANSFORM_MAX_OUTPUT_DIMENSION: LazyLock<u32> =
+    LazyLock::new(|| env_config("IMAGE_TRANSFORM_MAX_OUTPUT_DIMENSION", 4096));
+
+/// Largest width or height of a source image we're willing to decode. Protects
+/// against small files that decompress to huge images.
+pub static IMAGE_TRANSFORM_MAX_SOURCE_DIMENSION: LazyLock<u32> =
+    LazyLock::new(|| env_config("IMAGE_TRANSFORM_MAX_SOURCE_DIMENSION", 16384));
+
+/// Largest source file we'll load into memory to transform.
+pub static IMAGE_TRANSFORM_MAX_SOURCE_SIZE_BYTES: LazyLock<u64> = LazyLock::new(|| {
+    env_config("IMAGE_TRANSFORM_MAX_SOURCE_SIZE_BYTES", 1 << 25) // 32 MiB
+});
+
+/// How long to wait for the configured malware scanner to scan an uploaded
+/// file before failing the upload.
+pub static FILE_SCAN_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("FILE_SCAN_TIMEOUT_SECS", 60)));
+
+/// How often the storage garbage collector compares file storage with the
+/// `_storage` table to find orphaned objects. Zero disables it.
+pub static STORAGE_GC_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("STORAGE_GC_INTERVAL_SECS", 24 * 60 * 60)));
+
+/// Objects younger than this are never considered orphaned, so uploads that
+/// haven't been recorded in `_storage` yet aren't collected.
+pub static STORAGE_GC_HOLD_OFF: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("STORAGE_GC_HOLD_OFF_SECS", 7 * 24 * 60 * 60)));
+
+/// Whether the storage garbage collector deletes the orphaned objects it
+/// finds. When disabled it only reports them.
+pub static STORAGE_GC_DELETE_ORPHANS: LazyLock<bool> =
+    LazyLock::new(|| env_config("STORAGE_GC_DELETE_ORPHANS", false));
+
+/// How often the file attachments worker deletes the files of documents
+/// deleted from tables that cascade file deletes.
+pub static FILE_ATTACHMENT_CASCADE_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("FILE_ATTACHMENT_CASCADE_INTERVAL_SECS", 30)));
+
+/// Max number of cascaded file deletes per transaction.
+pub static FILE_ATTACHMENT_CASCADE_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("FILE_ATTACHMENT_CASCADE_BATCH_SIZE", 100));
+
+/// Max size of the text extracted from a file. Longer text is truncated
+/// before it's written to the document, which has to stay under the document
+/// size limit.
+pub static FILE_TEXT_MAX_BYTES: LazyLock<usize> =
+    LazyLock::new(|| env_config("FILE_TEXT_MAX_BYTES", 1 << 18));
+
+/// Max number of unfinished scheduled jobs per component read to report the
+/// scheduler backlog. Past this, the reported backlog is incomplete.
+pub static SCHEDULER_BACKLOG_MAX_SCANNED_JOBS: LazyLock<usize> =
+    LazyLock::new(|| env_config("SCHEDULER_BACKLOG_MAX_SCANNED_JOBS", 4096));
+
+/// Window over which the scheduler backlog measures execution throughput.
+pub static SCHEDULER_BACKLOG_THROUGHPUT_WINDOW: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "SCHEDULER_BACKLOG_THROUGHPUT_WINDOW_SECS",
+        5 * 60,
+    ))
+});
+
+/// How often the scheduler backlog is logged to metrics.
+pub static SCHEDULER_BACKLOG_METRICS_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config("SCHEDULER_BACKLOG_METRICS_INTERVAL_SECS", 30))
+});
+
+/// Max number of due jobs per priority the scheduled job executor skips,
+/// because their function is running as many jobs as they allow or has been
+/// disabled, before it stops looking for jobs it can start. Bounds the reads of
+/// each pass through the queue when a large backlog is capped.
+pub static SCHEDULED_JOB_MAX_SKIPPED_PER_PRIORITY: LazyLock<usize> =
+    LazyLock::new(|| env_config("SCHEDULED_JOB_MAX_SKIPPED_PER_PRIORITY", 256));
+
+/// Maximum size in bytes of the arguments of a scheduled function. Arguments
+/// larger than `FUNCTION_MAX_ARGS_SIZE` are kept in file storage until the job
+/// runs instead of in the job itself.
+pub static SCHEDULED_JOB_MAX_ARGS_SIZE: LazyLock<usize> = LazyLock::new(|| {
+    env_config("SCHEDULED_JOB_MAX_ARGS_SIZE", 1 << 24) // 16 MiB
+});
+
+/// Maximum size in bytes of the request context attached to a function call.
+pub static REQUEST_CONTEXT_MAX_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("REQUEST_CONTEXT_MAX_SIZE", 1 << 12)); // 4 KiB
+
 /// Max number of rows we will read when calculating document deltas.
 pub static DOCUMENT_DELTAS_LIMIT: LazyLock<usize> =
     LazyLock::new(|| env_config("DOCUMENT_DELTAS_LIMIT", 128));
@@ -136,10 +283,24 @@ pub static LOG_MANAGER_EVENT_RECV_BUFFER_SIZE: LazyLock<usize> =
 pub static LOG_MANAGER_AGGREGATION_INTERVAL_MILLIS: LazyLock<u64> =
     LazyLock::new(|| env_config("LOG_MANAGER_AGGREGATION_INTERVAL", 5000));
 
+/// Maximum number of log events sent to a log sink in one request.
+pub static LOG_SINK_MAX_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("LOG_SINK_MAX_BATCH_SIZE", 500));
+
+/// Number of times a log sink resends a batch that failed with a retryable
+/// error before dropping it.
+pub static LOG_SINK_MAX_RETRIES: LazyLock<usize> =
+    LazyLock::new(|| env_config("LOG_SINK_MAX_RETRIES", 5));
+
 /// Max number of times a mutation can retry due to OCC conflicts.
 pub static UDF_EXECUTOR_OCC_MAX_RETRIES: LazyLock<usize> =
     LazyLock::new(|| env_config("UDF_EXECUTOR_OCC_MAX_RETRIES", 4));
 
+/// Max nesting of table triggers, where a trigger's writes fire further
+/// triggers.
+pub static TRIGGER_MAX_DEPTH: LazyLock<usize> =
+    LazyLock::new(|| env_config("TRIGGER_MAX_DEPTH", 8));
+
 /// Initial backoff when we encounter an OCC conflict.
 pub static UDF_EXECUTOR_OCC_INITIAL_BACKOFF: LazyLock<Duration> =
     LazyLock::new(|| Duration::from_millis(env_config("UDF_EXECUTOR_OCC_INITIAL_BACKOFF_MS", 10)));
@@ -208,6 +369,44 @@ pub static HTTP_SERVER_TCP_BACKLOG: LazyLock<u32> =
 pub static HTTP_SERVER_MAX_CONCURRENT_REQUESTS: LazyLock<usize> =
     LazyLock::new(|| env_config("HTTP_SERVER_MAX_CONCURRENT_REQUESTS", 1024));
 
+/// PEM certificate chain that gRPC servers present to clients and clients
+/// present to servers. Traffic between backend components is plaintext unless
+/// this, `GRPC_TLS_KEY_PATH` and `GRPC_TLS_CA_PATH` are all set.
+pub static GRPC_TLS_CERT_PATH: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    let path: String = env_config("GRPC_TLS_CERT_PATH", String::new());
+    if !path.is_empty() {
+        Some(path.into())
+    } else {
+        None
+    }
+});
+
+/// PEM private key for `GRPC_TLS_CERT_PATH`.
+pub static GRPC_TLS_KEY_PATH: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    let path: String = env_config("GRPC_TLS_KEY_PATH", String::new());
+    if !path.is_empty() {
+        Some(path.into())
+    } else {
+        None
+    }
+});
+
+/// PEM CA certificates that gRPC peers' certificates must chain to, in both
+/// directions.
+pub static GRPC_TLS_CA_PATH: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    let path: String = env_config("GRPC_TLS_CA_PATH", String::new());
+    if !path.is_empty() {
+        Some(path.into())
+    } else {
+        None
+    }
+});
+
+/// How often gRPC servers and clients check the TLS files for rotated
+/// certificates. New connections use the reloaded certificates.
+pub static GRPC_TLS_RELOAD_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("GRPC_TLS_RELOAD_INTERVAL_SECS", 60)));
+
 /// Max number of user writes in a transaction
 pub static TRANSACTION_MAX_NUM_USER_WRITES: LazyLock<usize> =
     LazyLock::new(|| env_config("TRANSACTION_MAX_NUM_USER_WRITES", 8192));
@@ -257,6 +456,12 @@ pub static TRANSACTION_MAX_SYSTEM_WRITE_SIZE_BYTES: LazyLock<usize> = LazyLock::
 pub static TRANSACTION_MAX_NUM_SCHEDULED: LazyLock<usize> =
     LazyLock::new(|| env_config("TRANSACTION_MAX_NUM_SCHEDULED", 1000));
 
+/// Number of shards a sharded counter spreads its increments over. Concurrent
+/// increments only conflict when they land on the same shard, while reads sum
+/// every shard.
+pub static SHARDED_COUNTER_NUM_SHARDS: LazyLock<u32> =
+    LazyLock::new(|| env_config("SHARDED_COUNTER_NUM_SHARDS", 16));
+
 /// Maximum number of scheduled jobs to cancel in a single transaction.
 pub static MAX_JOBS_CANCEL_BATCH: LazyLock<usize> =
     LazyLock::new(|| env_config("MAX_JOBS_CANCEL_BATCH", 1000));
@@ -497,6 +702,11 @@ pub static DOCUMENT_RETENTION_BATCH_INTERVAL_SECONDS: LazyLock<Duration> = LazyL
 pub static DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS: LazyLock<usize> =
     LazyLock::new(|| env_config("DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS", 10000));
 
+/// Maximum number of document log entries read to report the document
+/// retention backlog. Past this, the reported backlog is incomplete.
+pub static RETENTION_BACKLOG_MAX_SCANNED_DOCUMENTS: LazyLock<usize> =
+    LazyLock::new(|| env_config("RETENTION_BACKLOG_MAX_SCANNED_DOCUMENTS", 100000));
+
 /// Size at which a search index will be queued for snapshotting.
 pub static SEARCH_INDEX_SIZE_SOFT_LIMIT: LazyLock<usize> =
     LazyLock::new(|| env_config("SEARCH_INDEX_SIZE_SOFT_LIMIT", 10 * (1 << 20))); // 10 MiB
@@ -600,6 +810,13 @@ pub static MAX_EXPIRED_SNAPSHOT_AGE: LazyLock<Duration> = LazyLock::new(|| {
     Duration::from_days(days)
 });
 
+/// How long a preview deployment lives when it's created or renewed without
+/// a TTL, after which its tables are deleted.
+pub static PREVIEW_DEPLOYMENT_DEFAULT_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    let days = env_config("PREVIEW_DEPLOYMENT_DEFAULT_TTL_DAYS", 7);
+    Duration::from_days(days)
+});
+
 /// Number of chunks processed per second when calculating table summaries.
 pub static TABLE_SUMMARY_CHUNKS_PER_SECOND: LazyLock<NonZeroU32> = LazyLock::new(|| {
     env_config(
@@ -654,6 +871,11 @@ pub static VECTOR_INDEX_WORKER_PAGE_SIZE: LazyLock<usize> =
 pub static DATABASE_UDF_USER_TIMEOUT: LazyLock<Duration> =
     LazyLock::new(|| Duration::from_secs(env_config("DATABASE_UDF_USER_TIMEOUT_SECONDS", 1)));
 
+/// Timeout on the CPU time a UDF spends running JavaScript. Only enforced when
+/// lower than the user timeout.
+pub static DATABASE_UDF_CPU_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("DATABASE_UDF_CPU_TIMEOUT_MS", 1000)));
+
 /// Timeout on the "system time" during a UDF -- i.e. syscalls.
 // The user limits are not very tight, which requires us to have a high
 // syscall timeout. When the database is healthy, we should never have UDF
@@ -669,6 +891,13 @@ pub static DATABASE_UDF_SYSTEM_TIMEOUT: LazyLock<Duration> =
 pub static ISOLATE_ANALYZE_USER_TIMEOUT: LazyLock<Duration> =
     LazyLock::new(|| Duration::from_secs(env_config("ISOLATE_ANALYZE_USER_TIMEOUT_SECONDS", 2)));
 
+/// Map stack frames in function errors and logs back to the original source
+/// using the source maps pushed with the modules. Disable this for deployments
+/// where the original file names and line numbers shouldn't be revealed to
+/// callers or in logs; frames then point into the bundled modules.
+pub static FUNCTION_ERROR_SOURCE_MAPS: LazyLock<bool> =
+    LazyLock::new(|| env_config("FUNCTION_ERROR_SOURCE_MAPS", true));
+
 /// Increasing the size of the queue helps us deal with bursty requests. This is
 /// a CoDel queue [https://queue.acm.org/detail.cfm?id=2209336], which will
 /// switch from FIFO to LIFO queue when overloaded, in order to process as much
@@ -781,6 +1010,27 @@ pub static APPLICATION_MAX_CONCURRENT_HTTP_ACTIONS: LazyLock<usize> = LazyLock::
     )
 });
 
+/// Maximum size in bytes of the cache of GET HTTP action responses. Only
+/// responses whose Cache-Control allows shared caching are stored. Disabled
+/// (0) by default.
+pub static HTTP_ACTION_CACHE_MAX_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("HTTP_ACTION_CACHE_MAX_SIZE", 0));
+
+/// Maximum total size in bytes of the static assets included in a push.
+pub static STATIC_ASSETS_MAX_TOTAL_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("STATIC_ASSETS_MAX_TOTAL_SIZE", 100 << 20));
+
+/// Maximum number of cached responses whose reads are watched so they can be
+/// purged from the CDN when they change. Responses past the limit are only
+/// tagged with the tables they read.
+pub static CDN_PURGE_MAX_WATCHES: LazyLock<usize> =
+    LazyLock::new(|| env_config("CDN_PURGE_MAX_WATCHES", 10000));
+
+/// How long to watch a response's reads for changes. This should be at least
+/// as long as the CDN caches responses for.
+pub static CDN_PURGE_WATCH_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("CDN_PURGE_WATCH_TTL_SECS", 24 * 60 * 60)));
+
 /// The maximum number of concurrent package uploads during
 /// `/api/deploy2/start_push`.
 pub static APPLICATION_MAX_CONCURRENT_UPLOADS: LazyLock<usize> =
@@ -813,6 +1063,17 @@ pub static MYSQL_TIMEOUT: LazyLock<u64> = LazyLock::new(|| env_config("MYSQL_TIM
 pub static MYSQL_MAX_CONNECTIONS: LazyLock<usize> =
     LazyLock::new(|| env_config("MYSQL_MAX_CONNECTIONS", 128));
 
+/// Maximum number of persistence pool connections that reads serving queries
+/// may hold at once. 0 means reads are only limited by the pool size.
+pub static PERSISTENCE_POOL_READ_MAX_CONNECTIONS: LazyLock<usize> =
+    LazyLock::new(|| env_config("PERSISTENCE_POOL_READ_MAX_CONNECTIONS", 0));
+
+/// Maximum number of persistence pool connections that retention may hold at
+/// once, so it can't starve the committer. Keep this well below the pool size.
+/// 0 means retention is only limited by the pool size.
+pub static PERSISTENCE_POOL_BACKGROUND_MAX_CONNECTIONS: LazyLock<usize> =
+    LazyLock::new(|| env_config("PERSISTENCE_POOL_BACKGROUND_MAX_CONNECTIONS", 4));
+
 /// Minimum number of rows to read from MySQL in a single query.
 pub static MYSQL_MIN_QUERY_BATCH_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("MYSQL_MIN_QUERY_BATCH_SIZE", 1));
@@ -854,6 +1115,132 @@ pub static MYSQL_CHUNK_SIZE: LazyLock<usize> =
 pub static MAX_CONCURRENT_ACTION_OPS: LazyLock<usize> =
     LazyLock::new(|| env_config("MAX_CONCURRENT_ACTION_OPS", 8));
 
+/// Maximum number of documents returned by a single batch of a streaming
+/// query from an action. Each batch is read in its own transaction, so this
+/// must stay well below `TRANSACTION_MAX_READ_SIZE_ROWS`.
+pub static ACTION_QUERY_STREAM_MAX_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("ACTION_QUERY_STREAM_MAX_BATCH_SIZE", 1024));
+
+/// Maximum number of operations in a single bulk write from an action. The
+/// operations are committed in chunks, but they're all held in memory while
+/// the write runs.
+pub static BULK_WRITE_MAX_OPERATIONS: LazyLock<usize> =
+    LazyLock::new(|| env_config("BULK_WRITE_MAX_OPERATIONS", 10_000));
+
+/// Batch size passed to data migration mutations. Each batch runs in its own
+/// transaction along with the migration's checkpoint.
+pub static DATA_MIGRATION_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("DATA_MIGRATION_BATCH_SIZE", 100));
+
+/// Maximum number of violating documents kept in a schema validation report.
+/// Scanning continues past the limit so the document count stays accurate.
+pub static SCHEMA_VALIDATION_REPORT_MAX_VIOLATIONS: LazyLock<usize> =
+    LazyLock::new(|| env_config("SCHEMA_VALIDATION_REPORT_MAX_VIOLATIONS", 100));
+
+/// Maximum number of operations in a single atomic document batch. Batches
+/// are also bounded by the transaction write limits.
+pub static DOCUMENT_BATCH_MAX_OPERATIONS: LazyLock<usize> =
+    LazyLock::new(|| env_config("DOCUMENT_BATCH_MAX_OPERATIONS", 1000));
+
+/// Maximum number of documents returned by a single document query.
+pub static DOCUMENT_QUERY_MAX_LIMIT: LazyLock<usize> =
+    LazyLock::new(|| env_config("DOCUMENT_QUERY_MAX_LIMIT", 1000));
+
+/// How long persisted function execution records are kept before the system
+/// table cleanup worker deletes them. Zero disables persisting records.
+pub static FUNCTION_EXECUTION_RECORD_RETENTION: LazyLock<Option<Duration>> = LazyLock::new(|| {
+    let hours = env_config("FUNCTION_EXECUTION_RECORD_RETENTION_HOURS", 7 * 24);
+    if hours > 0 {
+        Some(Duration::from_secs(60 * 60 * hours))
+    } else {
+        None
+    }
+});
+
+/// How often a usage report is uploaded to the exports storage bucket. Zero
+/// disables usage exports, though the current period's usage is still
+/// available from the usage API.
+pub static USAGE_EXPORT_INTERVAL: LazyLock<Option<Duration>> = LazyLock::new(|| {
+    let secs = env_config("USAGE_EXPORT_INTERVAL_SECONDS", 0);
+    if secs > 0 {
+        Some(Duration::from_secs(secs))
+    } else {
+        None
+    }
+});
+
+/// Parquet snapshot exports split each table into files holding about this
+/// many bytes of documents, since each file is built in memory.
+pub static PARQUET_EXPORT_FILE_MAX_BYTES: LazyLock<usize> = LazyLock::new(|| {
+    env_config("PARQUET_EXPORT_FILE_MAX_BYTES", 1 << 26) // 64 MiB
+});
+
+/// Maximum number of log lines kept in a persisted function execution record.
+pub static FUNCTION_EXECUTION_RECORD_MAX_LOG_LINES: LazyLock<usize> =
+    LazyLock::new(|| env_config("FUNCTION_EXECUTION_RECORD_MAX_LOG_LINES", 32));
+
+/// How long slow queries are kept in `_slow_queries` before the system table
+/// cleanup worker deletes them. Zero disables the slow query log.
+pub static SLOW_QUERY_LOG_RETENTION: LazyLock<Option<Duration>> = LazyLock::new(|| {
+    let hours = env_config("SLOW_QUERY_LOG_RETENTION_HOURS", 7 * 24);
+    if hours > 0 {
+        Some(Duration::from_secs(60 * 60 * hours))
+    } else {
+        None
+    }
+});
+
+/// Queries on user tables that scan at least this many documents are recorded
+/// in the slow query log.
+pub static SLOW_QUERY_DOCUMENTS_SCANNED_THRESHOLD: LazyLock<usize> =
+    LazyLock::new(|| env_config("SLOW_QUERY_DOCUMENTS_SCANNED_THRESHOLD", 1000));
+
+/// Queries on user tables that scan at least this many bytes are recorded in
+/// the slow query log.
+pub static SLOW_QUERY_BYTES_SCANNED_THRESHOLD: LazyLock<usize> =
+    LazyLock::new(|| env_config("SLOW_QUERY_BYTES_SCANNED_THRESHOLD", 1 << 20));
+
+/// Queries on user tables that spend at least this long fetching documents are
+/// recorded in the slow query log. Time spent in the function between reads
+/// doesn't count.
+pub static SLOW_QUERY_LATENCY_THRESHOLD: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("SLOW_QUERY_LATENCY_THRESHOLD_MS", 500)));
+
+/// Maximum number of slow queries buffered in memory before they're persisted.
+/// Beyond this, the oldest are dropped.
+pub static SLOW_QUERY_LOG_BUFFER_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("SLOW_QUERY_LOG_BUFFER_SIZE", 1024));
+
+/// How often the table stats worker recomputes per-table storage statistics.
+/// Zero disables the worker.
+pub static TABLE_STATS_REFRESH_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("TABLE_STATS_REFRESH_INTERVAL_SECS", 3600)));
+
+/// Maximum number of document log entries the table stats worker reads per
+/// second while measuring historical revisions.
+pub static TABLE_STATS_LOG_ROWS_PER_SECOND: LazyLock<NonZeroU32> = LazyLock::new(|| {
+    env_config(
+        "TABLE_STATS_LOG_ROWS_PER_SECOND",
+        NonZeroU32::new(1000).unwrap(),
+    )
+});
+
+/// How long each dependency check of the readiness endpoint may take before the
+/// dependency is reported as unavailable.
+pub static HEALTH_CHECK_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("HEALTH_CHECK_TIMEOUT_SECS", 5)));
+
+/// How long graceful shutdown waits for in-flight requests, and for mutations
+/// and actions on websocket sessions, to finish before closing them anyway.
+/// Also bounds how long background workers get to checkpoint their state.
+pub static SHUTDOWN_DRAIN_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SHUTDOWN_DRAIN_TIMEOUT_SECS", 30)));
+
+/// How many versions of each component's modules and config are kept in
+/// `_component_versions` for rollback. Older versions are deleted on push.
+pub static COMPONENT_VERSIONS_TO_KEEP: LazyLock<usize> =
+    LazyLock::new(|| env_config("COMPONENT_VERSIONS_TO_KEEP", 10));
+
 /// Maximum count of transitions within the web socket server message buffer.
 /// When this limit is reached, the web socket worker will temporary stop
 /// computing and sending transition messages to the client.
@@ -894,6 +1281,13 @@ pub static MAX_SEGMENT_DELETED_PERCENTAGE: LazyLock<f64> =
 /// (true) or InProcessFunctionRunner (false).
 pub static UDF_USE_FUNRUN: LazyLock<bool> = LazyLock::new(|| env_config("UDF_USE_FUNRUN", true));
 
+/// Run every query and mutation a second time against the same snapshot, with
+/// different seeds for `Math.random` and `Date.now`, and warn in the function
+/// log if the two executions differ. Doubles execution cost, so only enable it
+/// in staging.
+pub static UDF_DETERMINISM_CHECK: LazyLock<bool> =
+    LazyLock::new(|| env_config("UDF_DETERMINISM_CHECK", false));
+
 /// The amount of time to wait for the primary request to finish before starting
 /// a second backup request when running a vector search.
 pub static VECTOR_BACKUP_REQUEST_DELAY_MILLIS: LazyLock<Duration> =
@@ -969,6 +1363,20 @@ pub static FUNRUN_SCHEDULER_MAX_PERCENT_PER_CLIENT: LazyLock<usize> =
 pub static FUNRUN_CLUSTER_NAME: LazyLock<String> =
     LazyLock::new(|| env_config("FUNRUN_CLUSTER_NAME", String::from("funrun-default")));
 
+/// How long a function runner worker stays in the backend's worker pool
+/// without sending a heartbeat. Workers should heartbeat well within this.
+pub static FUNCTION_RUNNER_WORKER_HEARTBEAT_TIMEOUT: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "FUNCTION_RUNNER_WORKER_HEARTBEAT_TIMEOUT_SECS",
+        30,
+    ))
+});
+
+/// Number of consecutive failed requests after which a function runner worker
+/// is removed from the worker pool. It rejoins when it next registers.
+pub static FUNCTION_RUNNER_WORKER_MAX_FAILURES: LazyLock<u32> =
+    LazyLock::new(|| env_config("FUNCTION_RUNNER_WORKER_MAX_FAILURES", 3));
+
 /// Name of the service to discover for when connecting to Searchlight. (e.g.
 /// searchlight-default, searchlight-staging, etc.)
 // cluster is created.
@@ -979,6 +1387,23 @@ pub static SEARCHLIGHT_CLUSTER_NAME: LazyLock<String> = LazyLock::new(|| {
     )
 });
 
+/// Number of consecutive failed requests after which a `FailoverSearcher`
+/// stops sending requests to a searcher and fails over to the next one.
+pub static SEARCHER_CIRCUIT_BREAKER_FAILURES: LazyLock<u32> =
+    LazyLock::new(|| env_config("SEARCHER_CIRCUIT_BREAKER_FAILURES", 3));
+
+/// How long a `FailoverSearcher` leaves a failing searcher alone before
+/// letting a request through to check whether it has recovered.
+pub static SEARCHER_CIRCUIT_BREAKER_COOLDOWN: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_millis(env_config("SEARCHER_CIRCUIT_BREAKER_COOLDOWN_MS", 10_000))
+});
+
+/// How long a `FailoverSearcher` waits for a search query before also sending
+/// it to the next searcher and taking whichever answers first. Compactions
+/// are never hedged. Zero disables hedging.
+pub static SEARCHER_HEDGE_DELAY: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("SEARCHER_HEDGE_DELAY_MS", 0)));
+
 /// The maximum number of CPU cores that can be used simultaneously by the
 /// isolates. Zero means no limit.
 pub static FUNRUN_ISOLATE_ACTIVE_THREADS: LazyLock<usize> =
@@ -1182,6 +1607,23 @@ pub static REQUEST_TRACE_SAMPLE_CONFIG: LazyLock<SamplingConfig> = LazyLock::new
     )
 });
 
+/// OTLP/HTTP collector to export traces to, e.g. `http://localhost:4318`.
+/// Traces aren't exported if unset. Requests with a `traceparent` header are
+/// always traced, and other requests are sampled with
+/// `REQUEST_TRACE_SAMPLE_CONFIG`.
+pub static OTEL_EXPORTER_OTLP_ENDPOINT: LazyLock<Option<String>> = LazyLock::new(|| {
+    let endpoint: String = env_config("OTEL_EXPORTER_OTLP_ENDPOINT", String::new());
+    if !endpoint.is_empty() {
+        Some(endpoint)
+    } else {
+        None
+    }
+});
+
+/// The `service.name` exported traces are tagged with.
+pub static OTEL_SERVICE_NAME: LazyLock<String> =
+    LazyLock::new(|| env_config("OTEL_SERVICE_NAME", "convex-backend".to_string()));
+
 /// If true, the backend will check the rate limiter service for capacity under
 /// the "backend_startup" domain keyed by db cluster name.
 pub static STARTUP_RATE_LIMIT_ENABLED: LazyLock<bool> =
@@ -1194,6 +1636,111 @@ pub static AUTH_CACHE_SIZE: LazyLock<usize> = LazyLock::new(|| env_config("AUTH_
 pub static AUTH_CACHE_TTL_SECONDS: LazyLock<u64> =
     LazyLock::new(|| env_config("AUTH_CACHE_TTL_SECONDS", 60));
 
+/// How long OpenID Connect provider metadata, including the provider's JWKS,
+/// is cached for providers that don't set `jwksCacheTtlSeconds` in
+/// `auth.config.js`.
+pub static AUTH_PROVIDER_METADATA_CACHE_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config("AUTH_PROVIDER_METADATA_CACHE_TTL_SECS", 60 * 60))
+});
+
+/// How long a failed provider discovery is cached, so an unreachable
+/// provider doesn't get a discovery request for every token we validate.
+pub static AUTH_PROVIDER_NEGATIVE_CACHE_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("AUTH_PROVIDER_NEGATIVE_CACHE_TTL_SECS", 10)));
+
+/// How often cached provider metadata is checked for entries close to
+/// expiring, which are refetched in the background. Zero disables the
+/// background refresh.
+pub static AUTH_PROVIDER_METADATA_REFRESH_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "AUTH_PROVIDER_METADATA_REFRESH_INTERVAL_SECS",
+        30,
+    ))
+});
+
+/// How long ID tokens for anonymous identities are valid. Clients get a fresh
+/// token for the same identity by presenting their device ID again.
+pub static ANONYMOUS_IDENTITY_TOKEN_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("ANONYMOUS_IDENTITY_TOKEN_TTL_SECS", 60 * 60)));
+
+/// How long ID tokens issued by the built-in auth subsystem are valid. Clients
+/// get a fresh token by exchanging their refresh token.
+pub static BUILTIN_AUTH_TOKEN_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("BUILTIN_AUTH_TOKEN_TTL_SECS", 60 * 60)));
+
+/// How long a built-in auth session lasts without being refreshed.
+pub static BUILTIN_AUTH_SESSION_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "BUILTIN_AUTH_SESSION_TTL_SECS",
+        30 * 24 * 60 * 60,
+    ))
+});
+
+/// How long magic links sent by the built-in auth subsystem can be used.
+pub static BUILTIN_AUTH_MAGIC_LINK_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("BUILTIN_AUTH_MAGIC_LINK_TTL_SECS", 15 * 60)));
+
+/// How long clients have to answer a passkey registration or sign-in
+/// challenge. Unanswered challenges are deleted by the system table cleanup
+/// worker once they're this old.
+pub static BUILTIN_AUTH_PASSKEY_CHALLENGE_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "BUILTIN_AUTH_PASSKEY_CHALLENGE_TTL_SECS",
+        5 * 60,
+    ))
+});
+
+/// Comma-separated addresses or CIDR ranges of the reverse proxies in front of
+/// the backend, like `10.0.0.0/8,192.168.1.5`. Requests from them are
+/// attributed to the client address they report in `X-Forwarded-For`, which
+/// IP access rules and lockouts then apply to. When empty, the header is
+/// ignored and requests are attributed to the connecting address.
+pub static TRUSTED_PROXIES: LazyLock<String> =
+    LazyLock::new(|| env_config("TRUSTED_PROXIES", String::new()));
+
+/// How many failed admin key checks from one address, or with one key, lock
+/// out further attempts from the address or with the key, valid or not.
+pub static ADMIN_KEY_LOCKOUT_THRESHOLD: LazyLock<u32> =
+    LazyLock::new(|| env_config("ADMIN_KEY_LOCKOUT_THRESHOLD", 10));
+
+/// How long the first admin key lockout lasts. Each failed check after a
+/// lockout ends doubles the next one, up to `ADMIN_KEY_MAX_LOCKOUT`.
+pub static ADMIN_KEY_LOCKOUT_BASE: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("ADMIN_KEY_LOCKOUT_BASE_SECS", 30)));
+
+/// The longest an admin key lockout lasts. Failed checks are forgotten once
+/// an address or key goes this long without one after its last lockout.
+pub static ADMIN_KEY_MAX_LOCKOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("ADMIN_KEY_MAX_LOCKOUT_SECS", 60 * 60)));
+
+/// How long the response to the first failed admin key check from an address
+/// is held. Each further failure before a lockout doubles the delay, up to
+/// `ADMIN_KEY_MAX_FAILURE_DELAY`.
+pub static ADMIN_KEY_FAILURE_DELAY: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("ADMIN_KEY_FAILURE_DELAY_MS", 100)));
+
+/// The longest the response to a failed admin key check is held.
+pub static ADMIN_KEY_MAX_FAILURE_DELAY: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("ADMIN_KEY_MAX_FAILURE_DELAY_MS", 5000)));
+
+/// How many failed built-in auth sign-ins or magic links sent for one email,
+/// or from one address, lock out further attempts.
+pub static BUILTIN_AUTH_SIGN_IN_LOCKOUT_THRESHOLD: LazyLock<u32> =
+    LazyLock::new(|| env_config("BUILTIN_AUTH_SIGN_IN_LOCKOUT_THRESHOLD", 10));
+
+/// How long the first built-in auth sign-in lockout lasts. Each failed sign-in
+/// after a lockout ends doubles the next one, up to
+/// `BUILTIN_AUTH_SIGN_IN_MAX_LOCKOUT`.
+pub static BUILTIN_AUTH_SIGN_IN_LOCKOUT_BASE: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("BUILTIN_AUTH_SIGN_IN_LOCKOUT_BASE_SECS", 30)));
+
+/// The longest a built-in auth sign-in lockout lasts. Failed sign-ins are
+/// forgotten once an email or address goes this long without one after its
+/// last lockout.
+pub static BUILTIN_AUTH_SIGN_IN_MAX_LOCKOUT: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config("BUILTIN_AUTH_SIGN_IN_MAX_LOCKOUT_SECS", 15 * 60))
+});
+
 /// Request body limit for airbyte streaming import requests
 pub static AIRBYTE_STREAMING_IMPORT_REQUEST_SIZE_LIMIT: LazyLock<usize> = LazyLock::new(|| {
     env_config(
@@ -1221,6 +1768,16 @@ pub static MIGRATION_REWRITE_BATCH_SIZE: LazyLock<usize> =
 pub static MAX_IMPORT_AGE: LazyLock<Duration> =
     LazyLock::new(|| Duration::from_secs(env_config("MAX_IMPORT_AGE_SECONDS", 7 * 24 * 60 * 60)));
 
+/// Maximum number of documents per second the snapshot import worker writes,
+/// so large imports don't slow down live traffic. The limit applies between
+/// transactions, so individual batches are still written at once.
+pub static SNAPSHOT_IMPORT_ROWS_PER_SECOND: LazyLock<NonZeroU32> = LazyLock::new(|| {
+    env_config(
+        "SNAPSHOT_IMPORT_ROWS_PER_SECOND",
+        NonZeroU32::new(10_000).unwrap(),
+    )
+});
+
 /// Max staleness in seconds of a partition loader result before we allow
 /// refreshing. If a request tries to update the partition loader and this
 /// duration has not passed since the last refresh, a stale value will be used.
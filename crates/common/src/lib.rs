@@ -36,6 +36,7 @@ pub mod execution_context;
 pub mod ext;
 pub mod fastrace_helpers;
 pub mod floating_point;
+pub mod geospatial;
 pub mod grpc;
 pub mod heap_size;
 pub mod http;
@@ -63,6 +64,7 @@ pub mod query_journal;
 pub mod retriable_stream;
 pub mod runtime;
 pub mod schemas;
+pub mod secrets;
 pub mod sha256;
 pub mod shapes;
 pub mod shutdown;
@@ -68,6 +68,8 @@ pub struct AggregatedFunctionUsageStats {
     pub vector_index_read_bytes: u64,
     pub vector_index_write_bytes: u64,
     pub action_memory_used_mb: Option<u64>,
+    pub peak_heap_used_bytes: Option<u64>,
+    pub cpu_time_micros: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -306,7 +308,9 @@ impl LogEvent {
                             "file_storage_write_bytes": usage_stats.storage_write_bytes,
                             "vector_storage_read_bytes": usage_stats.vector_index_read_bytes,
                             "vector_storage_write_bytes": usage_stats.vector_index_write_bytes,
-                            "action_memory_used_mb": usage_stats.action_memory_used_mb
+                            "action_memory_used_mb": usage_stats.action_memory_used_mb,
+                            "peak_heap_used_bytes": usage_stats.peak_heap_used_bytes,
+                            "cpu_time_micros": usage_stats.cpu_time_micros
                         }
                     })
                 },
@@ -447,6 +451,12 @@ impl FunctionEventSource {
                 JsonValue::String(component_path_str),
             );
         }
+        if !self.context.request_context.is_empty() {
+            fields.insert(
+                "request_context".to_string(),
+                JsonValue::from(self.context.request_context),
+            );
+        }
         fields
     }
 }
@@ -469,7 +479,10 @@ mod tests {
 
     use crate::{
         components::ComponentPath,
-        execution_context::ExecutionContext,
+        execution_context::{
+            ExecutionContext,
+            RequestContext,
+        },
         log_lines::{
             LogLevel,
             LogLineStructured,
@@ -534,4 +547,17 @@ mod tests {
         );
         Ok(())
     }
+    #[test]
+    fn test_function_source_includes_request_context() -> anyhow::Result<()> {
+        let request_context =
+            RequestContext::new([("locale".to_string(), "fr-CA".to_string())].into())?;
+        let mut source = FunctionEventSource::new_for_test();
+        source.context = source.context.with_request_context(request_context);
+        let fields = source.to_json_map();
+        assert_eq!(fields["request_context"], json!({ "locale": "fr-CA" }));
+
+        let fields = FunctionEventSource::new_for_test().to_json_map();
+        assert!(!fields.contains_key("request_context"));
+        Ok(())
+    }
 }
@@ -15,7 +15,9 @@ use metrics::{
     log_counter,
     log_counter_with_labels,
     log_distribution,
+    log_distribution_with_labels,
     log_gauge,
+    log_gauge_with_labels,
     register_convex_counter,
     register_convex_gauge,
     register_convex_histogram,
@@ -30,9 +32,13 @@ use prometheus::{
 };
 use sync_types::backoff::Backoff;
 
-use crate::runtime::{
-    Runtime,
-    SpawnHandle,
+use crate::{
+    pool_stats::PoolWorkload,
+    runtime::{
+        Runtime,
+        SpawnHandle,
+    },
+    secrets::SecretsProviderKind,
 };
 
 register_convex_counter!(
@@ -118,6 +124,115 @@ pub fn static_repeatable_ts_timer(is_recent: bool) -> Timer<VMHistogramVec> {
     timer
 }
 
+register_convex_histogram!(
+    FETCH_PERMIT_WAIT_SECONDS,
+    "Time an outbound fetch waited for a concurrency slot"
+);
+pub fn log_fetch_permit_wait(duration: Duration) {
+    log_distribution(&FETCH_PERMIT_WAIT_SECONDS, duration.as_secs_f64());
+}
+
+register_convex_gauge!(
+    FETCH_IN_FLIGHT_TOTAL,
+    "Number of outbound fetches currently in flight"
+);
+pub fn log_fetch_in_flight(in_flight: usize) {
+    log_gauge(&FETCH_IN_FLIGHT_TOTAL, in_flight as f64);
+}
+
+register_convex_counter!(
+    FETCH_DNS_LOOKUPS_TOTAL,
+    "Count of DNS lookups by the fetch client",
+    &["cached"]
+);
+pub fn log_fetch_dns_lookup(cached: bool) {
+    log_counter_with_labels(
+        &FETCH_DNS_LOOKUPS_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("cached", cached.as_label())],
+    );
+}
+
+register_convex_counter!(
+    SECRET_LOOKUPS_TOTAL,
+    "Count of secret lookups by environment variable secret references",
+    &["provider", "cached", "status"]
+);
+pub fn log_secret_lookup(provider: SecretsProviderKind, cached: bool, is_ok: bool) {
+    log_counter_with_labels(
+        &SECRET_LOOKUPS_TOTAL,
+        1,
+        vec![
+            StaticMetricLabel::new("provider", provider.to_string()),
+            StaticMetricLabel::new("cached", cached.as_label()),
+            StaticMetricLabel::status(is_ok),
+        ],
+    );
+}
+
+register_convex_gauge!(
+    PERSISTENCE_POOL_IN_USE_CONNECTIONS,
+    "Number of persistence pool connections held or being acquired",
+    &["workload"]
+);
+pub fn log_persistence_pool_in_use(workload: PoolWorkload, in_use: usize) {
+    log_gauge_with_labels(
+        &PERSISTENCE_POOL_IN_USE_CONNECTIONS,
+        in_use as f64,
+        vec![StaticMetricLabel::new("workload", workload.to_string())],
+    );
+}
+
+register_convex_gauge!(
+    PERSISTENCE_POOL_WAITING_TOTAL,
+    "Number of requests waiting for a persistence pool connection",
+    &["workload"]
+);
+pub fn log_persistence_pool_waiting(workload: PoolWorkload, waiting: usize) {
+    log_gauge_with_labels(
+        &PERSISTENCE_POOL_WAITING_TOTAL,
+        waiting as f64,
+        vec![StaticMetricLabel::new("workload", workload.to_string())],
+    );
+}
+
+register_convex_gauge!(
+    PERSISTENCE_POOL_IDLE_CONNECTIONS,
+    "Number of open persistence pool connections not in use"
+);
+pub fn log_persistence_pool_idle(idle: usize) {
+    log_gauge(&PERSISTENCE_POOL_IDLE_CONNECTIONS, idle as f64);
+}
+
+register_convex_histogram!(
+    PERSISTENCE_POOL_WAIT_SECONDS,
+    "Time spent waiting for a persistence pool connection",
+    &["workload", "status"]
+);
+pub fn log_persistence_pool_wait(workload: PoolWorkload, wait: Duration, is_ok: bool) {
+    log_distribution_with_labels(
+        &PERSISTENCE_POOL_WAIT_SECONDS,
+        wait.as_secs_f64(),
+        vec![
+            StaticMetricLabel::new("workload", workload.to_string()),
+            StaticMetricLabel::status(is_ok),
+        ],
+    );
+}
+
+register_convex_counter!(
+    PERSISTENCE_POOL_TIMEOUTS_TOTAL,
+    "Count of requests that timed out waiting for a persistence pool connection",
+    &["workload"]
+);
+pub fn log_persistence_pool_timeout(workload: PoolWorkload) {
+    log_counter_with_labels(
+        &PERSISTENCE_POOL_TIMEOUTS_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("workload", workload.to_string())],
+    );
+}
+
 register_convex_counter!(ERRORS_REPORTED_TOTAL, "Count of errors reported", &["type"]);
 pub fn log_errors_reported_total(tag: StaticMetricLabel) {
     log_counter_with_labels(&ERRORS_REPORTED_TOTAL, 1, vec![tag]);
@@ -4,6 +4,7 @@ use std::{
         BTreeMap,
         BTreeSet,
     },
+    num::NonZeroUsize,
     ops::{
         Bound,
         RangeBounds,
@@ -14,6 +15,7 @@ use std::{
 
 use async_trait::async_trait;
 use enum_iterator::Sequence;
+use errors::ErrorMetadata;
 use futures::{
     future,
     stream::BoxStream,
@@ -38,6 +40,10 @@ use crate::{
     knobs::DEFAULT_DOCUMENTS_PAGE_SIZE,
     metrics::static_repeatable_ts_timer,
     persistence_helpers::RevisionPair,
+    pool_stats::{
+        ConnectionPoolSnapshot,
+        PoolWorkload,
+    },
     query::Order,
     runtime::Runtime,
     types::{
@@ -446,6 +452,25 @@ pub trait PersistenceReader: Send + Sync + 'static {
         Ok(vec![])
     }
 
+    /// Usage of the connection pool shared by this persistence's reads and
+    /// writes, or `None` if it doesn't pool connections.
+    fn connection_pool_stats(&self) -> Option<ConnectionPoolSnapshot> {
+        None
+    }
+
+    /// Caps how many pooled connections `workload` may hold at once, or
+    /// uncaps it if `limit` is `None`.
+    fn set_connection_pool_limit(
+        &self,
+        _workload: PoolWorkload,
+        _limit: Option<NonZeroUsize>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "ConnectionPoolNotSupported",
+            "This deployment's persistence doesn't pool connections"
+        ))
+    }
+
     /// Returns all timestamps and documents in ascending (ts, tablet_id, id)
     /// order. Only should be used for testing
     #[cfg(any(test, feature = "testing"))]
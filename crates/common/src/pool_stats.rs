@@ -1,20 +1,47 @@
-use std::sync::{
-    atomic::{
-        AtomicU64,
-        Ordering,
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
     },
-    Arc,
+    time::Duration,
 };
 
+use enum_iterator::Sequence;
+use errors::ErrorMetadata;
+use futures::pin_mut;
 use metrics::{
     log_distribution_with_labels,
     log_gauge_with_labels,
     StaticMetricLabel,
 };
+use parking_lot::Mutex;
 use prometheus::{
     GaugeVec,
     VMHistogramVec,
 };
+use tokio::{
+    sync::Notify,
+    time::Instant,
+};
+
+use crate::{
+    knobs::{
+        PERSISTENCE_POOL_BACKGROUND_MAX_CONNECTIONS,
+        PERSISTENCE_POOL_READ_MAX_CONNECTIONS,
+    },
+    metrics::{
+        log_persistence_pool_in_use,
+        log_persistence_pool_timeout,
+        log_persistence_pool_wait,
+        log_persistence_pool_waiting,
+    },
+};
 
 /// Stats for a pool of connections.
 #[derive(Clone)]
@@ -88,3 +115,335 @@ impl Drop for ConnectionTracker {
         );
     }
 }
+
+/// The kinds of work sharing a persistence connection pool. Reads and
+/// background work can each be capped to a share of the pool so that, e.g.,
+/// retention can't hold every connection while the committer waits for one.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Sequence, strum::EnumString, strum::Display,
+)]
+#[strum(serialize_all = "snake_case")]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum PoolWorkload {
+    /// Commits and other writes made while holding the lease.
+    Commit,
+    /// Reads serving queries and loading snapshots.
+    Read,
+    /// Retention scanning for and deleting expired documents and index
+    /// entries.
+    Background,
+}
+
+impl PoolWorkload {
+    fn configured_limit(self) -> Option<NonZeroUsize> {
+        match self {
+            Self::Commit => None,
+            Self::Read => NonZeroUsize::new(*PERSISTENCE_POOL_READ_MAX_CONNECTIONS),
+            Self::Background => NonZeroUsize::new(*PERSISTENCE_POOL_BACKGROUND_MAX_CONNECTIONS),
+        }
+    }
+}
+
+struct Partition {
+    limit: Option<NonZeroUsize>,
+    in_use: usize,
+    waiting: usize,
+    acquired_total: u64,
+    timeouts_total: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+}
+
+/// Caps how many connections each workload can hold from a shared pool and
+/// tracks how long each waits for one.
+pub struct ConnectionPoolPartitions {
+    partitions: Mutex<BTreeMap<PoolWorkload, Partition>>,
+    released: Notify,
+}
+
+/// Usage of a persistence connection pool by one workload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkloadPoolStats {
+    pub workload: PoolWorkload,
+    /// The most connections the workload may hold at once, if capped.
+    pub limit: Option<NonZeroUsize>,
+    /// Connections the workload holds or is acquiring from the pool.
+    pub in_use: usize,
+    /// Requests waiting for a connection.
+    pub waiting: usize,
+    pub acquired_total: u64,
+    pub timeouts_total: u64,
+    pub mean_wait: Duration,
+    pub max_wait: Duration,
+}
+
+/// Usage of a persistence connection pool, for the admin API.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionPoolSnapshot {
+    /// The most connections the pool opens.
+    pub max_size: usize,
+    /// Open connections not in use, if the pool reports them.
+    pub idle: Option<usize>,
+    pub workloads: Vec<WorkloadPoolStats>,
+}
+
+impl ConnectionPoolPartitions {
+    pub fn new() -> Arc<Self> {
+        let partitions = enum_iterator::all::<PoolWorkload>()
+            .map(|workload| {
+                let partition = Partition {
+                    limit: workload.configured_limit(),
+                    in_use: 0,
+                    waiting: 0,
+                    acquired_total: 0,
+                    timeouts_total: 0,
+                    total_wait: Duration::ZERO,
+                    max_wait: Duration::ZERO,
+                };
+                (workload, partition)
+            })
+            .collect();
+        Arc::new(Self {
+            partitions: Mutex::new(partitions),
+            released: Notify::new(),
+        })
+    }
+
+    /// Waits until `workload` is under its cap and then gets a connection
+    /// from the pool with `get`, failing if both together take longer than
+    /// `timeout`. The permit must be held for as long as the connection is.
+    pub async fn acquire<T>(
+        self: &Arc<Self>,
+        workload: PoolWorkload,
+        timeout: Duration,
+        get: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<(T, PartitionPermit)> {
+        let start = Instant::now();
+        let waiting = WaitingGuard::new(self, workload);
+        let result = tokio::time::timeout(timeout, async {
+            let permit = self.reserve(workload).await;
+            Ok::<_, anyhow::Error>((get.await?, permit))
+        })
+        .await;
+        drop(waiting);
+
+        let wait = start.elapsed();
+        let mut partitions = self.partitions.lock();
+        let partition = partitions.get_mut(&workload).expect("missing partition");
+        match &result {
+            Ok(Ok(_)) => {
+                partition.acquired_total += 1;
+                partition.total_wait += wait;
+                partition.max_wait = partition.max_wait.max(wait);
+            },
+            Ok(Err(_)) => {},
+            Err(_) => {
+                partition.timeouts_total += 1;
+                log_persistence_pool_timeout(workload);
+            },
+        }
+        log_persistence_pool_wait(workload, wait, matches!(result, Ok(Ok(_))));
+        match result {
+            Ok(result) => result,
+            Err(_) => Err(anyhow::anyhow!(
+                "Timed out after {timeout:?} waiting for a {workload} connection"
+            )
+            .context(ErrorMetadata::operational_internal_server_error())),
+        }
+    }
+
+    async fn reserve(self: &Arc<Self>, workload: PoolWorkload) -> PartitionPermit {
+        loop {
+            // Register for wakeups before checking, so a release between the
+            // check and the wait isn't missed.
+            let released = self.released.notified();
+            pin_mut!(released);
+            released.as_mut().enable();
+            {
+                let mut partitions = self.partitions.lock();
+                let partition = partitions.get_mut(&workload).expect("missing partition");
+                if partition
+                    .limit
+                    .is_none_or(|limit| partition.in_use < limit.get())
+                {
+                    partition.in_use += 1;
+                    log_persistence_pool_in_use(workload, partition.in_use);
+                    return PartitionPermit {
+                        partitions: self.clone(),
+                        workload,
+                    };
+                }
+            }
+            released.await;
+        }
+    }
+
+    /// Caps how many connections `workload` may hold at once, or uncaps it
+    /// if `limit` is `None`. Takes effect for connections acquired after the
+    /// change; connections already held are not closed.
+    pub fn set_limit(
+        &self,
+        workload: PoolWorkload,
+        limit: Option<NonZeroUsize>,
+    ) -> anyhow::Result<()> {
+        if workload == PoolWorkload::Commit && limit.is_some() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidConnectionPoolLimit",
+                "Commits can't be capped, since they'd stall every write to the deployment"
+            ));
+        }
+        self.partitions
+            .lock()
+            .get_mut(&workload)
+            .expect("missing partition")
+            .limit = limit;
+        self.released.notify_waiters();
+        Ok(())
+    }
+
+    fn update_waiting(&self, workload: PoolWorkload, f: impl FnOnce(usize) -> usize) {
+        let mut partitions = self.partitions.lock();
+        let partition = partitions.get_mut(&workload).expect("missing partition");
+        partition.waiting = f(partition.waiting);
+        log_persistence_pool_waiting(workload, partition.waiting);
+    }
+
+    pub fn snapshot(&self, max_size: usize, idle: Option<usize>) -> ConnectionPoolSnapshot {
+        let partitions = self.partitions.lock();
+        let workloads = partitions
+            .iter()
+            .map(|(workload, partition)| WorkloadPoolStats {
+                workload: *workload,
+                limit: partition.limit,
+                in_use: partition.in_use,
+                waiting: partition.waiting,
+                acquired_total: partition.acquired_total,
+                timeouts_total: partition.timeouts_total,
+                mean_wait: if partition.acquired_total == 0 {
+                    Duration::ZERO
+                } else {
+                    partition
+                        .total_wait
+                        .div_f64(partition.acquired_total as f64)
+                },
+                max_wait: partition.max_wait,
+            })
+            .collect();
+        ConnectionPoolSnapshot {
+            max_size,
+            idle,
+            workloads,
+        }
+    }
+}
+
+/// Holds a workload's share of the pool until dropped.
+pub struct PartitionPermit {
+    partitions: Arc<ConnectionPoolPartitions>,
+    workload: PoolWorkload,
+}
+
+impl Drop for PartitionPermit {
+    fn drop(&mut self) {
+        {
+            let mut partitions = self.partitions.partitions.lock();
+            let partition = partitions
+                .get_mut(&self.workload)
+                .expect("missing partition");
+            partition.in_use -= 1;
+            log_persistence_pool_in_use(self.workload, partition.in_use);
+        }
+        self.partitions.released.notify_waiters();
+    }
+}
+
+/// Counts a request as waiting until dropped, including when the request is
+/// canceled.
+struct WaitingGuard<'a> {
+    partitions: &'a ConnectionPoolPartitions,
+    workload: PoolWorkload,
+}
+
+impl<'a> WaitingGuard<'a> {
+    fn new(partitions: &'a ConnectionPoolPartitions, workload: PoolWorkload) -> Self {
+        partitions.update_waiting(workload, |waiting| waiting + 1);
+        Self {
+            partitions,
+            workload,
+        }
+    }
+}
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.partitions
+            .update_waiting(self.workload, |waiting| waiting - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        num::NonZeroUsize,
+        time::Duration,
+    };
+
+    use errors::ErrorMetadataAnyhowExt;
+
+    use super::{
+        ConnectionPoolPartitions,
+        PoolWorkload,
+    };
+
+    #[tokio::test(start_paused = true)]
+    async fn test_partition_limits() -> anyhow::Result<()> {
+        let partitions = ConnectionPoolPartitions::new();
+        let timeout = Duration::from_secs(1);
+        partitions.set_limit(PoolWorkload::Background, NonZeroUsize::new(1))?;
+
+        let (_, held) = partitions
+            .acquire(PoolWorkload::Background, timeout, async { Ok(()) })
+            .await?;
+        // Background work is at its cap, but commits aren't held up by it.
+        let err = partitions
+            .acquire(PoolWorkload::Background, timeout, async { Ok(()) })
+            .await
+            .unwrap_err();
+        assert!(err.is_operational_internal_server_error());
+        partitions
+            .acquire(PoolWorkload::Commit, timeout, async { Ok(()) })
+            .await?;
+
+        // Releasing the held connection wakes up a waiter.
+        let waiter = tokio::spawn({
+            let partitions = partitions.clone();
+            async move {
+                partitions
+                    .acquire(PoolWorkload::Background, timeout, async { Ok(()) })
+                    .await
+                    .map(|_| ())
+            }
+        });
+        tokio::task::yield_now().await;
+        drop(held);
+        waiter.await??;
+
+        let stats = partitions.snapshot(10, Some(2));
+        let background = stats
+            .workloads
+            .iter()
+            .find(|w| w.workload == PoolWorkload::Background)
+            .unwrap();
+        assert_eq!(background.in_use, 0);
+        assert_eq!(background.waiting, 0);
+        assert_eq!(background.acquired_total, 2);
+        assert_eq!(background.timeouts_total, 1);
+
+        // Commits can't be capped.
+        let err = partitions
+            .set_limit(PoolWorkload::Commit, NonZeroUsize::new(1))
+            .unwrap_err();
+        assert!(err.is_bad_request());
+        Ok(())
+    }
+}
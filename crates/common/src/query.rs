@@ -61,6 +61,23 @@ pub type SerializedCursor = String;
 /// A hash of the query that's included in cursors.
 pub type QueryFingerprint = Vec<u8>;
 
+/// A short hash of the fields of the index a paginated query reads from.
+/// The query fingerprint already covers these fields, but keeping them
+/// separately lets us tell an index being redefined apart from the query
+/// itself changing.
+pub type IndexFingerprint = Vec<u8>;
+
+const INDEX_FINGERPRINT_LEN: usize = 8;
+
+pub fn index_fingerprint(indexed_fields: &IndexedFields) -> IndexFingerprint {
+    let mut hasher = Sha256::new();
+    for field in indexed_fields.iter() {
+        hasher.update(String::from(field.clone()).as_bytes());
+        hasher.update([0]);
+    }
+    hasher.finalize()[..INDEX_FINGERPRINT_LEN].to_vec()
+}
+
 /// A `CursorPosition` is a position within query results used to implement
 /// `paginate()`.
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
@@ -86,6 +103,10 @@ pub struct Cursor {
 
     /// Hashed representation of the query this cursor refers to.
     pub query_fingerprint: QueryFingerprint,
+
+    /// Hashed fields of the index the query read from. `None` for cursors
+    /// issued before we started tracking it.
+    pub index_fingerprint: Option<IndexFingerprint>,
 }
 
 impl From<Cursor> for pb::convex_cursor::Cursor {
@@ -93,6 +114,7 @@ impl From<Cursor> for pb::convex_cursor::Cursor {
         Cursor {
             position,
             query_fingerprint,
+            index_fingerprint,
         }: Cursor,
     ) -> Self {
         let position = match position {
@@ -104,6 +126,7 @@ impl From<Cursor> for pb::convex_cursor::Cursor {
         Self {
             position: Some(position),
             query_fingerprint: Some(query_fingerprint),
+            index_fingerprint,
         }
     }
 }
@@ -115,6 +138,7 @@ impl TryFrom<pb::convex_cursor::Cursor> for Cursor {
         pb::convex_cursor::Cursor {
             position,
             query_fingerprint,
+            index_fingerprint,
         }: pb::convex_cursor::Cursor,
     ) -> anyhow::Result<Self> {
         let position = position.ok_or_else(|| anyhow::anyhow!("Cursor is missing position"))?;
@@ -128,13 +152,16 @@ impl TryFrom<pb::convex_cursor::Cursor> for Cursor {
             position,
             query_fingerprint: query_fingerprint
                 .ok_or_else(|| anyhow::anyhow!("Missing query_fingerprint"))?,
+            index_fingerprint,
         })
     }
 }
 
 impl HeapSize for Cursor {
     fn heap_size(&self) -> usize {
-        self.position.heap_size() + self.query_fingerprint.heap_size()
+        self.position.heap_size()
+            + self.query_fingerprint.heap_size()
+            + self.index_fingerprint.heap_size()
     }
 }
 
@@ -25,27 +25,88 @@ pub struct QueryJournal {
     /// for the end of the query so we can continue to sync to the same point
     /// if this function is re-executed.
     pub end_cursor: Option<Cursor>,
+
+    /// If the paginated query's page grew large enough that it should be
+    /// split, where to split it. This isn't part of the serialized journal:
+    /// the sync worker reports it to the client alongside the query result.
+    pub page_split: Option<PageSplit>,
 }
 
 impl QueryJournal {
     pub fn new() -> QueryJournal {
-        QueryJournal { end_cursor: None }
+        QueryJournal {
+            end_cursor: None,
+            page_split: None,
+        }
     }
 }
 
 impl HeapSize for QueryJournal {
     fn heap_size(&self) -> usize {
-        match &self.end_cursor {
-            Some(cursor) => cursor.heap_size(),
-            None => 0,
+        self.end_cursor.heap_size() + self.page_split.heap_size()
+    }
+}
+
+/// A boundary at which an oversized page of a paginated query can be split
+/// into `(start, split_cursor]` and `(split_cursor, end_cursor]`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct PageSplit {
+    pub split_cursor: Cursor,
+
+    /// The page hit a read limit and is missing results, so the client must
+    /// split it rather than just being encouraged to.
+    pub required: bool,
+}
+
+impl HeapSize for PageSplit {
+    fn heap_size(&self) -> usize {
+        self.split_cursor.heap_size()
+    }
+}
+
+impl From<PageSplit> for pb::convex_query_journal::PageSplit {
+    fn from(
+        PageSplit {
+            split_cursor,
+            required,
+        }: PageSplit,
+    ) -> Self {
+        Self {
+            split_cursor: Some(split_cursor.into()),
+            required: Some(required),
         }
     }
 }
 
+impl TryFrom<pb::convex_query_journal::PageSplit> for PageSplit {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        pb::convex_query_journal::PageSplit {
+            split_cursor,
+            required,
+        }: pb::convex_query_journal::PageSplit,
+    ) -> anyhow::Result<Self> {
+        let split_cursor =
+            split_cursor.ok_or_else(|| anyhow::anyhow!("PageSplit is missing split_cursor"))?;
+        Ok(Self {
+            split_cursor: split_cursor.try_into()?,
+            required: required.unwrap_or_default(),
+        })
+    }
+}
+
 impl From<QueryJournal> for pb::convex_query_journal::QueryJournal {
-    fn from(QueryJournal { end_cursor }: QueryJournal) -> Self {
+    fn from(
+        QueryJournal {
+            end_cursor,
+            page_split,
+        }: QueryJournal,
+    ) -> Self {
         Self {
             cursor: end_cursor.map(pb::convex_cursor::Cursor::from),
+            page_split: page_split.map(pb::convex_query_journal::PageSplit::from),
         }
     }
 }
@@ -54,9 +115,16 @@ impl TryFrom<pb::convex_query_journal::QueryJournal> for QueryJournal {
     type Error = anyhow::Error;
 
     fn try_from(
-        pb::convex_query_journal::QueryJournal { cursor }: pb::convex_query_journal::QueryJournal,
+        pb::convex_query_journal::QueryJournal {
+            cursor,
+            page_split,
+        }: pb::convex_query_journal::QueryJournal,
     ) -> anyhow::Result<Self> {
         let end_cursor = cursor.map(Cursor::try_from).transpose()?;
-        Ok(QueryJournal { end_cursor })
+        let page_split = page_split.map(PageSplit::try_from).transpose()?;
+        Ok(QueryJournal {
+            end_cursor,
+            page_split,
+        })
     }
 }
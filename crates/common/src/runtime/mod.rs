@@ -98,6 +98,9 @@ impl From<tokio::task::JoinError> for JoinError {
 pub trait SpawnHandle: Send + Sync {
     fn shutdown(&mut self);
     fn join(&mut self) -> BoxFuture<'_, Result<(), JoinError>>;
+    /// Whether the spawned future has exited, either by completing, panicking
+    /// or being shut down. Also true once the handle has been joined.
+    fn is_finished(&self) -> bool;
 }
 
 /// Shutdown the associated future, preempting it at its next yield point, and
@@ -141,9 +141,28 @@ impl TestRuntime {
         f(&mut state)
     }
 
+    /// Moves both the monotonic clock and `system_time` forward, waking any
+    /// timers that come due. Functions see the new time in `Date.now()`.
     pub async fn advance_time(&self, duration: Duration) {
         tokio::time::advance(duration).await
     }
+
+    /// Advances time until `system_time` reaches `time`. Time can't go
+    /// backwards, so this panics if `time` has already passed.
+    pub async fn advance_time_to(&self, time: SystemTime) {
+        let duration = time
+            .duration_since(self.system_time())
+            .expect("TestRuntime time can't go backwards");
+        self.advance_time(duration).await
+    }
+
+    /// Restarts the runtime's random number generator from `seed`. Every
+    /// function seeds `Math.random()` and `crypto.getRandomValues()` from this
+    /// generator, so reseeding makes their output independent of how much
+    /// randomness earlier setup consumed.
+    pub fn reseed_rng(&self, seed: u64) {
+        self.with_state(|state| state.rng = ChaCha12Rng::seed_from_u64(seed))
+    }
 }
 
 impl Runtime for TestRuntime {
@@ -239,6 +258,12 @@ impl SpawnHandle for TestFutureHandle {
         };
         future.boxed()
     }
+
+    fn is_finished(&self) -> bool {
+        self.handle
+            .as_ref()
+            .is_none_or(|handle| handle.is_finished())
+    }
 }
 
 #[cfg(test)]
@@ -266,6 +291,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deterministic_time_and_rng() -> anyhow::Result<()> {
+        let td = TestDriver::new_with_seed(0);
+        let rt = td.rt();
+        td.run_until(async {
+            let target = rt.system_time() + Duration::from_secs(3600);
+            rt.advance_time_to(target).await;
+            assert_eq!(rt.system_time(), target);
+
+            rt.reseed_rng(7);
+            let first = rt.rng().next_u64();
+            rt.reseed_rng(7);
+            assert_eq!(rt.rng().next_u64(), first);
+        });
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_mutex_with_timeout() -> anyhow::Result<()> {
         let mutex = MutexWithTimeout::new(Duration::from_secs(1), ());
@@ -45,6 +45,12 @@ impl SpawnHandle for ThreadFutureHandle {
         };
         future.boxed()
     }
+
+    fn is_finished(&self) -> bool {
+        self.handle
+            .as_ref()
+            .is_none_or(|handle| handle.is_finished())
+    }
 }
 
 impl Drop for ThreadFutureHandle {
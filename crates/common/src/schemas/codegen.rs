@@ -0,0 +1,196 @@
+//! Renders a [`DatabaseSchema`] as TypeScript types and JSON Schema so
+//! toolchains other than the Convex CLI can generate bindings against a
+//! deployment.
+
+use std::fmt::Write;
+
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use value::{
+    export::ValueFormat,
+    TableName,
+};
+
+use super::{
+    validator::{
+        AddTopLevelFields,
+        LiteralValidator,
+        ObjectValidator,
+        Validator,
+    },
+    DatabaseSchema,
+    DocumentSchema,
+};
+use crate::{
+    document::{
+        CREATION_TIME_FIELD,
+        ID_FIELD,
+    },
+    json_schemas,
+};
+
+/// Type definitions for the documents of every table in a schema.
+pub struct SchemaTypeDefinitions {
+    pub typescript: String,
+    pub json_schema: JsonValue,
+}
+
+impl SchemaTypeDefinitions {
+    pub fn new(schema: &DatabaseSchema, value_format: ValueFormat) -> Self {
+        Self {
+            typescript: database_schema_to_typescript(schema),
+            json_schema: database_schema_to_json_schema(schema, value_format),
+        }
+    }
+}
+
+/// A TypeScript module with a `Doc<TableName>` type for every table in the
+/// schema, including the system fields.
+pub fn database_schema_to_typescript(schema: &DatabaseSchema) -> String {
+    let mut out = String::new();
+    out.push_str(
+        "export type Id<TableName extends string> = string & { __tableName: TableName };\n",
+    );
+    out.push_str("\nexport type DataModel = {\n");
+    for (table_name, table) in &schema.tables {
+        let document_type = match &table.document_type {
+            None | Some(DocumentSchema::Any) => format!(
+                "{{ {}; [field: string]: any }}",
+                system_fields_typescript(table_name)
+            ),
+            Some(DocumentSchema::Union(options)) => {
+                let options: Vec<_> = options
+                    .iter()
+                    .map(|object| object_typescript(object, Some(table_name)))
+                    .collect();
+                union_typescript(options)
+            },
+        };
+        writeln!(
+            out,
+            "  {}: {document_type};",
+            quote(&table_name.to_string())
+        )
+        .expect("Writing to a String can't fail");
+    }
+    out.push_str("};\n");
+    out.push_str("\nexport type TableNames = keyof DataModel;\n");
+    out.push_str("\nexport type Doc<TableName extends TableNames> = DataModel[TableName];\n");
+    out
+}
+
+/// A JSON Schema with a definition for the documents of every table in the
+/// schema, including the system fields.
+pub fn database_schema_to_json_schema(
+    schema: &DatabaseSchema,
+    value_format: ValueFormat,
+) -> JsonValue {
+    let definitions: serde_json::Map<_, _> = schema
+        .tables
+        .iter()
+        .map(|(table_name, table)| {
+            let json_schema = match &table.document_type {
+                None | Some(DocumentSchema::Any) => json_schemas::any(),
+                Some(DocumentSchema::Union(options)) => json_schemas::union(
+                    options
+                        .iter()
+                        .map(|object| {
+                            object.to_json_schema(
+                                AddTopLevelFields::True(table_name.clone()),
+                                value_format,
+                            )
+                        })
+                        .collect(),
+                ),
+            };
+            (table_name.to_string(), json_schema)
+        })
+        .collect();
+    json!({ "definitions": definitions })
+}
+
+fn validator_typescript(validator: &Validator) -> String {
+    match validator {
+        Validator::Id(table_name) => format!("Id<{}>", quote(&table_name.to_string())),
+        Validator::Null => "null".to_string(),
+        Validator::Float64 => "number".to_string(),
+        Validator::Int64 => "bigint".to_string(),
+        Validator::Boolean => "boolean".to_string(),
+        Validator::String => "string".to_string(),
+        Validator::Bytes => "ArrayBuffer".to_string(),
+        Validator::Literal(literal) => match literal {
+            LiteralValidator::Float64(float) => {
+                let float = f64::from(float.clone());
+                if float.is_finite() {
+                    float.to_string()
+                } else {
+                    "number".to_string()
+                }
+            },
+            LiteralValidator::Int64(int) => format!("{int}n"),
+            LiteralValidator::Boolean(bool) => bool.to_string(),
+            LiteralValidator::String(string) => quote(string),
+        },
+        Validator::Array(element) => format!("Array<{}>", validator_typescript(element)),
+        Validator::Set(element) => format!("Set<{}>", validator_typescript(element)),
+        Validator::Record(key, value) => format!(
+            "Record<{}, {}>",
+            validator_typescript(key),
+            validator_typescript(value)
+        ),
+        Validator::Map(key, value) => format!(
+            "Map<{}, {}>",
+            validator_typescript(key),
+            validator_typescript(value)
+        ),
+        Validator::Object(object) => object_typescript(object, None),
+        Validator::Union(options) => {
+            union_typescript(options.iter().map(validator_typescript).collect())
+        },
+        Validator::Any => "any".to_string(),
+    }
+}
+
+/// Adds the system fields when `table_name` is set, i.e. for the top-level
+/// object of a document.
+fn object_typescript(object: &ObjectValidator, table_name: Option<&TableName>) -> String {
+    let mut fields: Vec<_> = table_name
+        .map(system_fields_typescript)
+        .into_iter()
+        .collect();
+    for (field_name, field) in &object.0 {
+        let optional = if field.optional { "?" } else { "" };
+        fields.push(format!(
+            "{field_name}{optional}: {}",
+            validator_typescript(&field.validator)
+        ));
+    }
+    if fields.is_empty() {
+        return "{}".to_string();
+    }
+    format!("{{ {} }}", fields.join("; "))
+}
+
+fn system_fields_typescript(table_name: &TableName) -> String {
+    format!(
+        "{ID_FIELD}: Id<{}>; {CREATION_TIME_FIELD}: number",
+        quote(&table_name.to_string())
+    )
+}
+
+fn union_typescript(options: Vec<String>) -> String {
+    match options.len() {
+        0 => "never".to_string(),
+        1 => options
+            .into_iter()
+            .next()
+            .expect("Checked the length above"),
+        _ => format!("({})", options.join(" | ")),
+    }
+}
+
+fn quote(s: &str) -> String {
+    JsonValue::String(s.to_string()).to_string()
+}
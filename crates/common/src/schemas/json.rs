@@ -16,6 +16,7 @@ use serde::{
     Serialize,
 };
 use serde_json::Value as JsonValue;
+use sync_types::CanonicalizedUdfPath;
 use value::{
     ConvexValue,
     FieldPath,
@@ -45,9 +46,11 @@ use crate::{
         },
         vector_index::VectorDimensions,
     },
+    geospatial,
     json::invalid_json,
     schemas::{
         invalid_top_level_type_in_schema,
+        FileTextSchema,
         SearchIndexSchema,
         TableDefinition,
         MAX_INDEXES_PER_TABLE,
@@ -134,6 +137,156 @@ struct TableDefinitionJson {
     search_indexes: Option<Vec<JsonValue>>,
     vector_indexes: Option<Vec<JsonValue>>,
     document_type: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    geospatial_indexes: Option<Vec<GeospatialIndexJson>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    triggers: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    on_commit: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    cascade_delete_files: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_text: Option<FileTextJson>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileTextJson {
+    storage_field: String,
+    text_field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extractor: Option<String>,
+}
+
+impl FileTextJson {
+    fn into_schema(
+        self,
+        table_name: &TableName,
+        search_indexes: &BTreeMap<IndexDescriptor, SearchIndexSchema>,
+    ) -> anyhow::Result<FileTextSchema> {
+        let invalid = |message: String| {
+            anyhow::anyhow!(ErrorMetadata::bad_request(
+                "InvalidFileText",
+                format!("Invalid file text extraction on table {table_name}: {message}"),
+            ))
+        };
+        let field = |field: &str| {
+            field
+                .parse::<IdentifierFieldName>()
+                .map_err(|e| invalid(format!("invalid field {field:?}: {e}")))
+        };
+        let storage_field = field(&self.storage_field)?;
+        let text_field = field(&self.text_field)?;
+        if storage_field == text_field {
+            return Err(invalid(
+                "the text can't be written to the file's field".to_string(),
+            ));
+        }
+        let text_path = FieldPath::for_root_field(text_field.clone());
+        if !search_indexes
+            .values()
+            .any(|index| index.search_field == text_path)
+        {
+            return Err(invalid(format!(
+                "no search index searches the text field \"{text_field}\""
+            )));
+        }
+        let extractor = self
+            .extractor
+            .map(|extractor| {
+                extractor
+                    .parse()
+                    .map_err(|e| invalid(format!("invalid extractor {extractor:?}: {e}")))
+            })
+            .transpose()?;
+        Ok(FileTextSchema {
+            storage_field,
+            text_field,
+            extractor,
+        })
+    }
+}
+
+impl From<FileTextSchema> for FileTextJson {
+    fn from(
+        FileTextSchema {
+            storage_field,
+            text_field,
+            extractor,
+        }: FileTextSchema,
+    ) -> Self {
+        Self {
+            storage_field: storage_field.into(),
+            text_field: text_field.into(),
+            extractor: extractor.map(String::from),
+        }
+    }
+}
+
+/// A geospatial index is stored as a database index over the derived
+/// `_geohash` field of its point field.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeospatialIndexJson {
+    index_descriptor: String,
+    location_field: String,
+}
+
+impl GeospatialIndexJson {
+    fn into_index_json(self) -> anyhow::Result<JsonValue> {
+        let location: FieldPath = self.location_field.parse().with_context(|| {
+            ErrorMetadata::bad_request(
+                "InvalidGeospatialIndexField",
+                format!(
+                    "In geospatial index \"{}\": invalid location field {:?}",
+                    self.index_descriptor, self.location_field
+                ),
+            )
+        })?;
+        Ok(serde_json::to_value(IndexSchemaJson {
+            index_descriptor: self.index_descriptor,
+            fields: vec![String::from(geospatial::geospatial_index_field(&location))],
+        })?)
+    }
+
+    /// The geospatial index stored as `index`, if it is one.
+    fn from_index(index: &IndexSchema) -> Option<Self> {
+        let [field] = &index.fields[..] else {
+            return None;
+        };
+        let location = geospatial::location_field(field)?;
+        Some(Self {
+            index_descriptor: index.index_descriptor.to_string(),
+            location_field: String::from(location),
+        })
+    }
+}
+
+/// Parses the function paths a table registers as triggers or commit hooks.
+fn parse_table_hooks(
+    table_name: &TableName,
+    hooks: Option<Vec<String>>,
+    error_code: &'static str,
+) -> anyhow::Result<Vec<CanonicalizedUdfPath>> {
+    let hooks = hooks
+        .unwrap_or_default()
+        .into_iter()
+        .map(|hook| {
+            hook.parse().map_err(|e| {
+                anyhow::anyhow!(ErrorMetadata::bad_request(
+                    error_code,
+                    format!("Invalid function {hook:?} on table {table_name}: {e}"),
+                ))
+            })
+        })
+        .collect::<anyhow::Result<Vec<CanonicalizedUdfPath>>>()?;
+    if hooks.iter().duplicates().next().is_some() {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            error_code,
+            format!("Table {table_name} registers the same function more than once"),
+        ));
+    }
+    Ok(hooks)
 }
 
 // Collect the index names separately from the deduplicating map so that we can
@@ -180,7 +333,8 @@ impl TryFrom<JsonValue> for TableDefinition {
         let search_indexes = j.search_indexes.unwrap_or_default();
         let vector_indexes = j.vector_indexes.unwrap_or_default();
 
-        let document_type = j.document_type.map(|t| t.try_into()).transpose()?;
+        let document_type: Option<DocumentSchema> =
+            j.document_type.map(|t| t.try_into()).transpose()?;
 
         let table_name: TableName = j
             .table_name
@@ -190,8 +344,25 @@ impl TryFrom<JsonValue> for TableDefinition {
             !table_name.is_system(),
             index_validation_error::table_name_reserved(&table_name)
         );
+        if let Some(document_type) = &document_type {
+            document_type.check_top_level_options(&table_name)?;
+        }
+        let triggers = parse_table_hooks(&table_name, j.triggers, "InvalidTrigger")?;
+        let on_commit = parse_table_hooks(&table_name, j.on_commit, "InvalidCommitHook")?;
+
+        let geospatial_indexes = j.geospatial_indexes.unwrap_or_default();
+        let geospatial_descriptors: BTreeSet<_> = geospatial_indexes
+            .iter()
+            .map(|index| index.index_descriptor.clone())
+            .collect();
+        let mut database_indexes = j.indexes;
+        for index in geospatial_indexes {
+            database_indexes.push(index.into_index_json()?);
+        }
 
-        if j.indexes.len() + vector_indexes.len() + search_indexes.len() > MAX_INDEXES_PER_TABLE {
+        if database_indexes.len() + vector_indexes.len() + search_indexes.len()
+            > MAX_INDEXES_PER_TABLE
+        {
             anyhow::bail!(index_validation_error::too_many_indexes(
                 &table_name,
                 MAX_INDEXES_PER_TABLE
@@ -199,13 +370,29 @@ impl TryFrom<JsonValue> for TableDefinition {
         }
 
         let (index_names, indexes) =
-            parse_names_and_indexes(&table_name, j.indexes, |idx: &IndexSchema| {
+            parse_names_and_indexes(&table_name, database_indexes, |idx: &IndexSchema| {
                 &idx.index_descriptor
             })?;
         for schema in indexes.values() {
             if schema.fields.is_empty() {
                 anyhow::bail!(index_validation_error::empty_index(&table_name, schema));
             }
+            if !geospatial_descriptors.contains(&schema.index_descriptor.to_string())
+                && schema
+                    .fields
+                    .iter()
+                    .any(|field| geospatial::location_field(field).is_some())
+            {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "IndexFieldNameReserved",
+                    format!(
+                        "In index \"{}\" on table \"{table_name}\": \"{}\" is reserved for \
+                         geospatial indexes",
+                        schema.index_descriptor,
+                        *geospatial::GEOHASH_FIELD
+                    ),
+                ));
+            }
         }
         validate_unique_index_fields(
             &indexes,
@@ -222,6 +409,10 @@ impl TryFrom<JsonValue> for TableDefinition {
             |idx| idx.search_field.clone(),
             |index1, index2| search_field_not_unique(&table_name, index1, index2),
         )?;
+        let file_text = j
+            .file_text
+            .map(|file_text| file_text.into_schema(&table_name, &search_indexes))
+            .transpose()?;
 
         let (vector_index_names, vector_indexes): (Vec<_>, BTreeMap<_, _>) =
             parse_names_and_indexes(&table_name, vector_indexes, |idx: &VectorIndexSchema| {
@@ -258,6 +449,10 @@ impl TryFrom<JsonValue> for TableDefinition {
             search_indexes,
             vector_indexes,
             document_type,
+            triggers,
+            on_commit,
+            cascade_delete_files: j.cascade_delete_files,
+            file_text,
         })
     }
 }
@@ -272,11 +467,22 @@ impl TryFrom<TableDefinition> for JsonValue {
             search_indexes,
             vector_indexes,
             document_type,
+            triggers,
+            on_commit,
+            cascade_delete_files,
+            file_text,
         }: TableDefinition,
     ) -> anyhow::Result<Self> {
         let table_name = String::from(table_name);
-        let indexes = indexes
+        let (geospatial_indexes, indexes): (Vec<_>, Vec<_>) = indexes
             .into_values()
+            .partition(|index| GeospatialIndexJson::from_index(index).is_some());
+        let geospatial_indexes: Vec<_> = geospatial_indexes
+            .iter()
+            .filter_map(GeospatialIndexJson::from_index)
+            .collect();
+        let indexes = indexes
+            .into_iter()
             .map(JsonValue::try_from)
             .collect::<anyhow::Result<Vec<_>>>()?;
         let search_indexes = Some(
@@ -298,6 +504,13 @@ impl TryFrom<TableDefinition> for JsonValue {
             search_indexes,
             vector_indexes,
             document_type,
+            geospatial_indexes: (!geospatial_indexes.is_empty()).then_some(geospatial_indexes),
+            triggers: (!triggers.is_empty())
+                .then(|| triggers.into_iter().map(String::from).collect()),
+            on_commit: (!on_commit.is_empty())
+                .then(|| on_commit.into_iter().map(String::from).collect()),
+            cascade_delete_files,
+            file_text: file_text.map(FileTextJson::from),
         })?)
     }
 }
@@ -535,6 +748,10 @@ impl TryFrom<DocumentSchema> for JsonValue {
 struct FieldTypeJson {
     field_type: JsonValue,
     optional: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<JsonValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    immutable: Option<bool>,
 }
 
 impl TryFrom<JsonValue> for FieldValidator {
@@ -543,10 +760,22 @@ impl TryFrom<JsonValue> for FieldValidator {
     fn try_from(value: JsonValue) -> anyhow::Result<Self> {
         let field_type_json: FieldTypeJson =
             serde_json::from_value(value).context("Not a field validator")?;
-        Ok(FieldValidator {
+        let field_validator = FieldValidator {
             validator: field_type_json.field_type.try_into()?,
             optional: field_type_json.optional,
-        })
+            default: field_type_json
+                .default
+                .map(ConvexValue::try_from)
+                .transpose()?,
+            immutable: field_type_json.immutable.unwrap_or(false),
+        };
+        if let Err(e) = field_validator.check_default() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidFieldDefault",
+                format!("Default value doesn't match its field's validator: {e}"),
+            ));
+        }
+        Ok(field_validator)
     }
 }
 
@@ -557,6 +786,8 @@ impl TryFrom<FieldValidator> for JsonValue {
         let field_type_json = FieldTypeJson {
             field_type: JsonValue::try_from(f.validator)?,
             optional: f.optional,
+            default: f.default.map(JsonValue::from),
+            immutable: f.immutable.then_some(true),
         };
         Ok(serde_json::to_value(field_type_json)?)
     }
@@ -706,6 +937,8 @@ impl TryFrom<Validator> for JsonValue {
                 values: JsonValue::try_from(FieldValidator {
                     optional: false,
                     validator: *v,
+                    default: None,
+                    immutable: false,
                 })?,
             },
             Validator::Object(o) => ValidatorJson::Object {
@@ -1,4 +1,5 @@
 use std::{
+    borrow::Borrow,
     collections::{
         BTreeMap,
         BTreeSet,
@@ -21,12 +22,14 @@ use shape_inference::{
     ShapeConfig,
     ShapeCounter,
 };
+use sync_types::CanonicalizedUdfPath;
 #[cfg(any(test, feature = "testing"))]
 use value::TableType;
 use value::{
     id_v6::DeveloperDocumentId,
     ConvexObject,
     ConvexValue,
+    FieldName,
     IdentifierFieldName,
     Namespace,
     NamespacedTableMapping,
@@ -46,6 +49,7 @@ use crate::{
         MAX_VECTOR_INDEX_FILTER_FIELDS_SIZE,
     },
     document::ResolvedDocument,
+    geospatial,
     paths::FieldPath,
     types::{
         IndexDescriptor,
@@ -54,6 +58,7 @@ use crate::{
     virtual_system_mapping::VirtualSystemMapping,
 };
 
+pub mod codegen;
 pub mod json;
 #[cfg(test)]
 mod tests;
@@ -173,6 +178,10 @@ macro_rules! db_schema {
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
                         document_type: Some($document_schema),
+                        triggers: vec![],
+                        on_commit: vec![],
+                        cascade_delete_files: false,
+                        file_text: None,
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -204,6 +213,10 @@ macro_rules! db_schema_not_validated {
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
                         document_type: Some($document_schema),
+                        triggers: vec![],
+                        on_commit: vec![],
+                        cascade_delete_files: false,
+                        file_text: None,
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -257,6 +270,10 @@ macro_rules! db_schema_with_vector_indexes {
                         search_indexes: Default::default(),
                         vector_indexes,
                         document_type: Some($document_schema),
+                        triggers: vec![],
+                        on_commit: vec![],
+                        cascade_delete_files: false,
+                        file_text: None,
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -394,6 +411,51 @@ impl DatabaseSchema {
             })
     }
 
+    /// Fill in the schema's defaults for fields missing from a document
+    /// about to be written to `table_name`.
+    pub fn apply_defaults(
+        &self,
+        table_name: &TableName,
+        object: ConvexObject,
+    ) -> anyhow::Result<ConvexObject> {
+        match self
+            .tables
+            .get(table_name)
+            .and_then(|table| table.document_type.as_ref())
+        {
+            Some(document_type) => document_type.apply_defaults(object),
+            None => Ok(object),
+        }
+    }
+
+    /// Reject an update to a document in `table_name` that changes one of
+    /// its immutable fields.
+    pub fn check_immutable_fields(
+        &self,
+        table_name: &TableName,
+        old: &ConvexObject,
+        new: &ConvexObject,
+    ) -> anyhow::Result<()> {
+        let Some(document_type) = self
+            .tables
+            .get(table_name)
+            .and_then(|table| table.document_type.as_ref())
+        else {
+            return Ok(());
+        };
+        if let Some(field_name) = document_type.modified_immutable_field(old, new) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ImmutableFieldModified",
+                format!(
+                    "Failed to update a document in table \"{table_name}\" because it changes the \
+                     immutable field `{field_name}`. Immutable fields can't be changed once the \
+                     document is created."
+                ),
+            ));
+        }
+        Ok(())
+    }
+
     fn contains_table_as_reference(&self, table_name: &TableName) -> Option<TableName> {
         for table_schema in self.tables.values() {
             if let Some(document_schema) = &table_schema.document_type {
@@ -436,10 +498,13 @@ impl DatabaseSchema {
             if let Some((index_descriptor, field_path)) = table_definition
                 .fields_referenced_in_indexes()
                 .find(|(_, field_path)| {
+                    // Geospatial indexes reference their point field.
+                    let field_path = geospatial::location_field(field_path)
+                        .unwrap_or_else(|| (*field_path).clone());
                     table_definition
                         .document_type
                         .as_ref()
-                        .map(|document_schema| !document_schema.can_contain_field(field_path))
+                        .map(|document_schema| !document_schema.can_contain_field(&field_path))
                         .unwrap_or(false)
                 })
             {
@@ -536,6 +601,33 @@ pub struct TableDefinition {
     pub search_indexes: BTreeMap<IndexDescriptor, SearchIndexSchema>,
     pub vector_indexes: BTreeMap<IndexDescriptor, VectorIndexSchema>,
     pub document_type: Option<DocumentSchema>,
+    /// Mutations run in the writing transaction whenever a document in this
+    /// table is inserted, updated or deleted.
+    pub triggers: Vec<CanonicalizedUdfPath>,
+    /// Actions scheduled in the writing transaction whenever a document in
+    /// this table changes, so they're enqueued if and only if it commits.
+    pub on_commit: Vec<CanonicalizedUdfPath>,
+    /// Whether deleting a document also deletes the files its
+    /// `v.id("_storage")` fields reference, once no other document references
+    /// them.
+    pub cascade_delete_files: bool,
+    /// Extracts the text of the file each document references, so a search
+    /// index can make the files searchable.
+    pub file_text: Option<FileTextSchema>,
+}
+
+/// Where a table keeps the text of the files its documents reference.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileTextSchema {
+    /// The top-level `v.id("_storage")` field holding the file.
+    pub storage_field: IdentifierFieldName,
+    /// The top-level field the extracted text is written to, which one of the
+    /// table's search indexes searches.
+    pub text_field: IdentifierFieldName,
+    /// An action that extracts the text of files that aren't plain text, such
+    /// as PDFs. It's called with `{ storageId, contentType }` and returns the
+    /// text, or null if the file has none.
+    pub extractor: Option<CanonicalizedUdfPath>,
 }
 
 impl TableDefinition {
@@ -631,6 +723,10 @@ impl proptest::arbitrary::Arbitrary for TableDefinition {
                                 .map(|i| (i.index_descriptor.clone(), i))
                                 .collect(),
                             document_type,
+                            triggers: vec![],
+                            on_commit: vec![],
+                            cascade_delete_files: false,
+                            file_text: None,
                         })
                     } else {
                         None
@@ -847,6 +943,107 @@ impl DocumentSchema {
             },
         }
     }
+
+    /// The ids of documents in `table_name` that `value` references through
+    /// fields typed `v.id(table_name)`. Documents in tables without a schema
+    /// aren't considered to reference anything.
+    pub fn referenced_ids(
+        &self,
+        table_name: &TableName,
+        value: &ConvexObject,
+    ) -> BTreeSet<DeveloperDocumentId> {
+        let mut ids = BTreeSet::new();
+        if let Self::Union(options) = self {
+            for option in options {
+                option.referenced_ids(table_name, value, &mut ids);
+            }
+        }
+        ids
+    }
+
+    /// Defaults and immutability are only allowed on top-level fields.
+    /// Defaults also require a document type with a single object variant:
+    /// with several variants it's ambiguous which one a document missing a
+    /// field is meant to match.
+    pub fn check_top_level_options(&self, table_name: &TableName) -> anyhow::Result<()> {
+        let Self::Union(options) = self else {
+            return Ok(());
+        };
+        for option in options {
+            for (field_name, field_validator) in &option.0 {
+                anyhow::ensure!(
+                    !field_validator.validator.has_top_level_options(),
+                    ErrorMetadata::bad_request(
+                        "InvalidFieldOption",
+                        format!(
+                            "In table \"{table_name}\": defaults and immutability are only \
+                             supported on top-level fields, but field `{field_name}` contains a \
+                             nested default or immutable field"
+                        ),
+                    )
+                );
+                anyhow::ensure!(
+                    field_validator.default.is_none() || options.len() == 1,
+                    ErrorMetadata::bad_request(
+                        "InvalidFieldDefault",
+                        format!(
+                            "In table \"{table_name}\": field `{field_name}` has a default, but \
+                             defaults aren't supported on tables whose documents are a union of \
+                             objects"
+                        ),
+                    )
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The first immutable top-level field whose value differs between the
+    /// old and new versions of a document, including being added or removed.
+    pub fn modified_immutable_field(
+        &self,
+        old: &ConvexObject,
+        new: &ConvexObject,
+    ) -> Option<&IdentifierFieldName> {
+        let Self::Union(options) = self else {
+            return None;
+        };
+        options
+            .iter()
+            .flat_map(|option| option.0.iter())
+            .filter(|(_, field_validator)| field_validator.immutable)
+            .map(|(field_name, _)| field_name)
+            .find(|field_name| {
+                old.get::<str>((*field_name).borrow()) != new.get::<str>((*field_name).borrow())
+            })
+    }
+
+    /// Fill in the defaults for top-level fields missing from `object`.
+    /// Fields set to `null` are present and keep their value.
+    pub fn apply_defaults(&self, object: ConvexObject) -> anyhow::Result<ConvexObject> {
+        let Self::Union(options) = self else {
+            return Ok(object);
+        };
+        let [option] = &options[..] else {
+            return Ok(object);
+        };
+        let missing_defaults: Vec<_> = option
+            .0
+            .iter()
+            .filter_map(|(field_name, field_validator)| {
+                Some((field_name, field_validator.default.as_ref()?))
+            })
+            .filter(|(field_name, _)| object.get::<str>(field_name.borrow()).is_none())
+            .collect();
+        if missing_defaults.is_empty() {
+            return Ok(object);
+        }
+        let mut fields: BTreeMap<FieldName, ConvexValue> = object.into();
+        for (field_name, default) in missing_defaults {
+            fields.insert(field_name.clone().into(), default.clone());
+        }
+        fields.try_into()
+    }
 }
 
 const SEE_SCHEMA_DOCS: &str =
@@ -1,4 +1,5 @@
 use cmd_util::env::env_config;
+use maplit::btreeset;
 use proptest::prelude::*;
 use serde_json::{
     json,
@@ -6,17 +7,28 @@ use serde_json::{
 };
 use value::{
     assert_obj,
+    assert_val,
+    export::ValueFormat,
+    id_v6::DeveloperDocumentId,
     ConvexObject,
+    ConvexValue,
     FieldName,
+    FieldPath,
     NamespacedTableMapping,
     TableMapping,
+    TableName,
     TableNamespace,
+    TableNumber,
 };
 
 use crate::{
     db_schema_with_vector_indexes,
     object_validator,
     schemas::{
+        codegen::{
+            database_schema_to_json_schema,
+            database_schema_to_typescript,
+        },
         validator::{
             FieldValidator,
             ValidationContext,
@@ -26,7 +38,11 @@ use crate::{
         DocumentSchema,
         Validator,
     },
-    testing::assert_roundtrips,
+    testing::{
+        assert_roundtrips,
+        TestIdGenerator,
+    },
+    types::IndexDescriptor,
     virtual_system_mapping::VirtualSystemMapping,
 };
 
@@ -140,6 +156,118 @@ fn test_document_schema_extra_field() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_document_schema_null_is_not_missing() -> anyhow::Result<()> {
+    let object_validator = object_validator!("name" => FieldValidator::required_field_type(Validator::String), "age" => FieldValidator::optional_field_type(Validator::Int64));
+    let document_schema = DocumentSchema::Union(vec![object_validator]);
+    let object = assert_obj!("name" => "emma", "age" => null);
+    let err = document_schema
+        .check_value(
+            &object,
+            &empty_table_mapping(),
+            &VirtualSystemMapping::default(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ValidationError::NullForOptionalField {
+            field_name: "age".parse()?,
+            validator: Validator::Int64,
+            context: ValidationContext::new()
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_document_schema_apply_defaults() -> anyhow::Result<()> {
+    let object_validator = object_validator!(
+        "name" => FieldValidator::required_field_type(Validator::String),
+        "status" => FieldValidator {
+            validator: Validator::String,
+            optional: false,
+            default: Some(assert_val!("active")),
+            immutable: false,
+        },
+        "nickname" => FieldValidator {
+            validator: Validator::Union(vec![Validator::Null, Validator::String]),
+            optional: true,
+            default: Some(assert_val!("none")),
+            immutable: false,
+        }
+    );
+    let document_schema = DocumentSchema::Union(vec![object_validator]);
+
+    let object = document_schema.apply_defaults(assert_obj!("name" => "emma"))?;
+    assert_eq!(
+        object,
+        assert_obj!("name" => "emma", "nickname" => "none", "status" => "active")
+    );
+    // `null` is a value, so it isn't replaced by the default.
+    let object = document_schema
+        .apply_defaults(assert_obj!("name" => "emma", "nickname" => null, "status" => "done"))?;
+    assert_eq!(
+        object,
+        assert_obj!("name" => "emma", "nickname" => null, "status" => "done")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_invalid_field_defaults() {
+    let field_with_default = json!({
+        "fieldType": { "type": "string" },
+        "optional": false,
+        "default": 1,
+    });
+    let schema_json = json!({
+        "tables": [
+            {
+                "tableName": "testTable",
+                "documentType": {
+                    "type": "object",
+                    "value": { "myField": field_with_default },
+                },
+                "indexes": [],
+            },
+        ],
+        "schemaValidation": true
+    });
+    let error = DatabaseSchema::try_from(schema_json).expect_err("Default doesn't match");
+    assert!(error.to_string().contains("Default value doesn't match"));
+
+    let field_with_default = json!({
+        "fieldType": { "type": "string" },
+        "optional": false,
+        "default": "hello",
+    });
+    let schema_json = json!({
+        "tables": [
+            {
+                "tableName": "testTable",
+                "documentType": {
+                    "type": "object",
+                    "value": {
+                        "myField": {
+                            "fieldType": {
+                                "type": "object",
+                                "value": { "nested": field_with_default },
+                            },
+                            "optional": false
+                        },
+                    },
+                },
+                "indexes": [],
+            },
+        ],
+        "schemaValidation": true
+    });
+    let error = DatabaseSchema::try_from(schema_json).expect_err("Nested default");
+    assert!(error
+        .to_string()
+        .contains("only supported on top-level fields"));
+}
+
 #[test]
 fn test_nonexistent_table_name_reference() {
     // This schema has an ID that references "otherTable" but never defines
@@ -169,6 +297,244 @@ fn test_nonexistent_table_name_reference() {
     DatabaseSchema::try_from(schema_json).unwrap();
 }
 
+#[test]
+fn test_table_triggers() -> anyhow::Result<()> {
+    let table = |triggers: JsonValue| {
+        json!({
+            "tables": [
+                {
+                    "tableName": "messages",
+                    "indexes": [],
+                    "searchIndexes": [],
+                    "triggers": triggers,
+                },
+            ],
+            "schemaValidation": true
+        })
+    };
+    let schema = DatabaseSchema::try_from(table(json!(["audit:onWrite"])))?;
+    let triggers = &schema.tables[&"messages".parse::<TableName>()?].triggers;
+    assert_eq!(triggers.len(), 1);
+    assert_eq!(String::from(triggers[0].clone()), "audit.js:onWrite");
+
+    let error = DatabaseSchema::try_from(table(json!(["audit:onWrite", "audit.js:onWrite"])))
+        .expect_err("Duplicate trigger");
+    assert!(error.to_string().contains("same function more than once"));
+    Ok(())
+}
+
+#[test]
+fn test_table_commit_hooks() -> anyhow::Result<()> {
+    let schema_json = json!({
+        "tables": [
+            {
+                "tableName": "messages",
+                "indexes": [],
+                "searchIndexes": [],
+                "onCommit": ["emails:send"],
+            },
+        ],
+        "schemaValidation": true
+    });
+    let schema = DatabaseSchema::try_from(schema_json)?;
+    let table = &schema.tables[&"messages".parse::<TableName>()?];
+    assert!(table.triggers.is_empty());
+    assert_eq!(
+        table
+            .on_commit
+            .iter()
+            .cloned()
+            .map(String::from)
+            .collect::<Vec<_>>(),
+        vec!["emails.js:send".to_string()]
+    );
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+    Ok(())
+}
+
+#[test]
+fn test_file_attachments() -> anyhow::Result<()> {
+    let schema_json = json!({
+        "tables": [
+            {
+                "tableName": "messages",
+                "documentType": {
+                    "type": "object",
+                    "value": {
+                        "attachment": {
+                            "fieldType": { "type": "id", "tableName": "_storage" },
+                            "optional": true
+                        },
+                        "images": {
+                            "fieldType": {
+                                "type": "array",
+                                "value": { "type": "id", "tableName": "_storage" }
+                            },
+                            "optional": false
+                        },
+                        "author": {
+                            "fieldType": { "type": "id", "tableName": "users" },
+                            "optional": false
+                        },
+                    }
+                },
+                "indexes": [],
+                "searchIndexes": [],
+                "cascadeDeleteFiles": true,
+            },
+        ],
+        "schemaValidation": true
+    });
+    let schema = DatabaseSchema::try_from(schema_json)?;
+    let table = &schema.tables[&"messages".parse::<TableName>()?];
+    assert!(table.cascade_delete_files);
+    let document_type = table.document_type.clone().unwrap();
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+
+    let mut id_generator = TestIdGenerator::new();
+    let mut generate_id = || {
+        DeveloperDocumentId::new(
+            TableNumber::try_from(1000).unwrap(),
+            id_generator.generate_internal(),
+        )
+    };
+    let (file1, file2, user) = (generate_id(), generate_id(), generate_id());
+    let document = assert_obj!(
+        "attachment" => file1,
+        "images" => ConvexValue::Array(vec![file2.into()].try_into()?),
+        "author" => user,
+    );
+    assert_eq!(
+        document_type.referenced_ids(&"_storage".parse()?, &document),
+        btreeset! { file1, file2 }
+    );
+    let document = assert_obj!("images" => ConvexValue::Array(vec![].try_into()?));
+    let ids = document_type.referenced_ids(&"_storage".parse()?, &document);
+    assert!(ids.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_file_text() -> anyhow::Result<()> {
+    let table = |text_field: &str| {
+        json!({
+            "tables": [
+                {
+                    "tableName": "documents",
+                    "indexes": [],
+                    "searchIndexes": [
+                        {
+                            "indexDescriptor": "search_text",
+                            "searchField": "text",
+                            "filterFields": [],
+                        },
+                    ],
+                    "fileText": {
+                        "storageField": "file",
+                        "textField": text_field,
+                        "extractor": "files:extractText",
+                    },
+                },
+            ],
+            "schemaValidation": true
+        })
+    };
+    let schema = DatabaseSchema::try_from(table("text"))?;
+    let file_text = schema.tables[&"documents".parse::<TableName>()?]
+        .file_text
+        .clone()
+        .unwrap();
+    assert_eq!(&*file_text.storage_field, "file");
+    assert_eq!(&*file_text.text_field, "text");
+    assert_eq!(
+        file_text.extractor.map(String::from),
+        Some("files.js:extractText".to_string())
+    );
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+
+    let error = DatabaseSchema::try_from(table("summary")).expect_err("No search index");
+    assert!(error.to_string().contains("no search index searches"));
+    let error = DatabaseSchema::try_from(table("file")).expect_err("Same field");
+    assert!(error.to_string().contains("the file's field"));
+    Ok(())
+}
+
+#[test]
+fn test_geospatial_indexes() -> anyhow::Result<()> {
+    let schema_json = json!({
+        "tables": [
+            {
+                "tableName": "places",
+                "indexes": [],
+                "geospatialIndexes": [
+                    { "indexDescriptor": "by_location", "locationField": "location" },
+                ],
+            },
+        ],
+        "schemaValidation": true
+    });
+    let schema = DatabaseSchema::try_from(schema_json)?;
+    let table = &schema.tables[&"places".parse::<TableName>()?];
+    let index = &table.indexes[&IndexDescriptor::new("by_location")?];
+    assert_eq!(
+        Vec::<FieldPath>::from(index.fields.clone()),
+        vec!["location._geohash".parse()?]
+    );
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+
+    let reserved_json = json!({
+        "tables": [
+            {
+                "tableName": "places",
+                "indexes": [
+                    { "indexDescriptor": "by_hash", "fields": ["location._geohash"] },
+                ],
+            },
+        ],
+        "schemaValidation": true
+    });
+    let error = DatabaseSchema::try_from(reserved_json).expect_err("_geohash is reserved");
+    assert!(error
+        .to_string()
+        .contains("reserved for geospatial indexes"));
+    Ok(())
+}
+
+#[test]
+fn test_schema_typescript() -> anyhow::Result<()> {
+    let schema_json = json!({
+        "tables": [
+            {
+                "tableName": "messages",
+                "documentType": {
+                    "type": "object",
+                    "value": {
+                        "author": {
+                            "fieldType": { "type": "id", "tableName": "users" },
+                            "optional": false
+                        },
+                        "body": {
+                            "fieldType": { "type": "string" },
+                            "optional": true
+                        },
+                    }
+                },
+                "indexes": [],
+            },
+        ],
+        "schemaValidation": true
+    });
+    let schema = DatabaseSchema::try_from(schema_json)?;
+    let typescript = database_schema_to_typescript(&schema);
+    assert!(typescript.contains(
+        "\"messages\": { _id: Id<\"messages\">; _creationTime: number; author: Id<\"users\">; \
+         body?: string };"
+    ));
+    let json_schema = database_schema_to_json_schema(&schema, ValueFormat::ConvexCleanJSON);
+    assert!(json_schema["definitions"]["messages"].is_object());
+    Ok(())
+}
+
 #[test]
 fn test_nested_optional_float64_vector_index_field_succeeds() -> anyhow::Result<()> {
     let document_schema = DocumentSchema::Union(vec![object_validator!(
@@ -1,8 +1,9 @@
-#[cfg(any(test, feature = "testing"))]
-use std::collections::BTreeSet;
 use std::{
     borrow::Borrow,
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     fmt::{
         self,
         Display,
@@ -116,6 +117,8 @@ impl proptest::arbitrary::Arbitrary for Validator {
                         FieldValidator {
                             validator,
                             optional,
+                            default: None,
+                            immutable: false,
                         }
                     }),
                     0..8
@@ -268,11 +271,19 @@ impl Validator {
                 for (field_name, field_type) in &object_validator.0 {
                     let maybe_value = object.get::<str>(field_name.borrow());
                     if let Some(value) = maybe_value {
-                        field_type.validator.check_value_internal(
+                        let result = field_type.validator.check_value_internal(
                             value,
                             all_tables_number_to_name,
                             context.with(format!(".{field_name}")),
-                        )?
+                        );
+                        if result.is_err() && field_type.optional && value == &ConvexValue::Null {
+                            return Err(ValidationError::NullForOptionalField {
+                                field_name: field_name.clone(),
+                                validator: field_type.validator.clone(),
+                                context,
+                            });
+                        }
+                        result?
                     } else if !field_type.optional {
                         return Err(ValidationError::MissingRequiredField {
                             object: object.clone(),
@@ -394,6 +405,8 @@ impl Validator {
                                     virtual_system_mapping,
                                 ),
                                 optional: v.optional,
+                                default: None,
+                                immutable: false,
                             },
                         )
                     })
@@ -730,6 +743,56 @@ impl Validator {
         ))
     }
 
+    /// Collects the ids in `value` that this validator checks with
+    /// `v.id(table_name)`, i.e. the documents in `table_name` that `value`
+    /// references through typed fields.
+    pub fn referenced_ids(
+        &self,
+        table_name: &TableName,
+        value: &ConvexValue,
+        ids: &mut BTreeSet<DeveloperDocumentId>,
+    ) {
+        match (self, value) {
+            (Self::Id(validator_table), ConvexValue::String(s)) => {
+                if validator_table == table_name
+                    && let Ok(id) = DeveloperDocumentId::decode(s)
+                {
+                    ids.insert(id);
+                }
+            },
+            (Self::Object(object), ConvexValue::Object(value)) => {
+                object.referenced_ids(table_name, value, ids)
+            },
+            (Self::Array(item), ConvexValue::Array(values)) => {
+                for value in values {
+                    item.referenced_ids(table_name, value, ids);
+                }
+            },
+            (Self::Set(item), ConvexValue::Set(values)) => {
+                for value in values {
+                    item.referenced_ids(table_name, value, ids);
+                }
+            },
+            (Self::Record(_, item), ConvexValue::Object(values)) => {
+                for (_, value) in values.iter() {
+                    item.referenced_ids(table_name, value, ids);
+                }
+            },
+            (Self::Map(key_validator, value_validator), ConvexValue::Map(entries)) => {
+                for (key, value) in entries {
+                    key_validator.referenced_ids(table_name, key, ids);
+                    value_validator.referenced_ids(table_name, value, ids);
+                }
+            },
+            (Self::Union(options), _) => {
+                for option in options {
+                    option.referenced_ids(table_name, value, ids);
+                }
+            },
+            _ => {},
+        }
+    }
+
     pub fn has_map_or_set(&self) -> bool {
         match self {
             Self::Id(_)
@@ -749,6 +812,28 @@ impl Validator {
         }
     }
 
+    /// Whether any object field nested within this validator has a default or
+    /// is immutable.
+    pub fn has_top_level_options(&self) -> bool {
+        match self {
+            Self::Id(_)
+            | Self::Null
+            | Self::Float64
+            | Self::Int64
+            | Self::Boolean
+            | Self::String
+            | Self::Bytes
+            | Self::Literal(_)
+            | Self::Any => false,
+            Self::Array(a) | Self::Set(a) => a.has_top_level_options(),
+            Self::Map(k, v) | Self::Record(k, v) => {
+                k.has_top_level_options() || v.has_top_level_options()
+            },
+            Self::Object(o) => o.0.values().any(|f| f.has_top_level_options()),
+            Self::Union(u) => u.iter().any(|v| v.has_top_level_options()),
+        }
+    }
+
     // Filter out `_id` and `_creationTime` at the top level
     pub fn filter_top_level_system_fields(self) -> Self {
         match self {
@@ -985,6 +1070,19 @@ impl ObjectValidator {
             .values()
             .flat_map(|field| field.validator.foreign_keys())
     }
+
+    pub fn referenced_ids(
+        &self,
+        table_name: &TableName,
+        value: &ConvexObject,
+        ids: &mut BTreeSet<DeveloperDocumentId>,
+    ) {
+        for (field_name, field) in &self.0 {
+            if let Some(field_value) = value.get(&**field_name) {
+                field.validator.referenced_ids(table_name, field_value, ids);
+            }
+        }
+    }
 }
 
 /// Object fields can be optional.
@@ -1001,6 +1099,15 @@ pub struct FieldValidator {
     )]
     pub validator: Validator,
     pub optional: bool,
+    /// Value written when the field is missing from an inserted or patched
+    /// document. Only applied to the top-level fields of a table's document
+    /// type; a field set to `null` is not missing and keeps its value.
+    #[cfg_attr(any(test, feature = "testing"), proptest(value = "None"))]
+    pub default: Option<ConvexValue>,
+    /// Whether the field's value is fixed once the document is created. Only
+    /// supported on the top-level fields of a table's document type.
+    #[cfg_attr(any(test, feature = "testing"), proptest(value = "false"))]
+    pub immutable: bool,
 }
 
 impl FieldValidator {
@@ -1012,6 +1119,8 @@ impl FieldValidator {
         Self {
             validator,
             optional: false,
+            default: None,
+            immutable: false,
         }
     }
 
@@ -1019,9 +1128,31 @@ impl FieldValidator {
         Self {
             validator,
             optional: true,
+            default: None,
+            immutable: false,
         }
     }
 
+    /// Check that the default, if any, matches the field's validator. IDs
+    /// can't be checked without a table mapping, so `v.id` fields can't have
+    /// defaults.
+    pub fn check_default(&self) -> Result<(), ValidationError> {
+        let Some(default) = &self.default else {
+            return Ok(());
+        };
+        self.validator.check_value_internal(
+            default,
+            &|table_number| anyhow::bail!("Unknown table number {table_number}"),
+            ValidationContext::new(),
+        )
+    }
+
+    /// Whether this field or any field nested within it has a default or is
+    /// immutable, which are only supported on top-level fields.
+    pub fn has_top_level_options(&self) -> bool {
+        self.default.is_some() || self.immutable || self.validator.has_top_level_options()
+    }
+
     pub fn has_map_or_set(&self) -> bool {
         self.validator.has_map_or_set()
     }
@@ -1090,6 +1221,16 @@ Validator: {object_validator}"
         object_validator: ObjectValidator,
         context: ValidationContext,
     },
+    #[display(
+        fmt = "Field `{field_name}` is `null`, but `null` is not the same as a missing field. \
+               Omit the field, or use `v.optional(v.union(v.null(), {validator}))` to allow both.
+{context}"
+    )]
+    NullForOptionalField {
+        field_name: IdentifierFieldName,
+        validator: Validator,
+        context: ValidationContext,
+    },
     #[display(fmt = "Value does not match validator.
 {context}
 Value: {value}
@@ -1101,6 +1242,34 @@ Validator: {validator}")]
     },
 }
 
+impl ValidationError {
+    /// Path within the document of the value that failed validation, e.g.
+    /// `.author.tags[2]`, or `None` if the document itself doesn't match.
+    pub fn field_path(&self) -> Option<String> {
+        match self {
+            ValidationError::MissingRequiredField {
+                field_name,
+                context,
+                ..
+            } => context.with(format!(".{field_name}")).0,
+            ValidationError::ExtraField {
+                field_name,
+                context,
+                ..
+            } => context.with(format!(".{field_name}")).0,
+            ValidationError::NullForOptionalField {
+                field_name,
+                context,
+                ..
+            } => context.with(format!(".{field_name}")).0,
+            ValidationError::TableNamesDoNotMatch { context, .. }
+            | ValidationError::SystemTableReference { context, .. }
+            | ValidationError::LiteralValuesDoNotMatch { context, .. }
+            | ValidationError::NoMatch { context, .. } => context.0.clone(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -1817,6 +1986,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_error_field_path() -> anyhow::Result<()> {
+        let validator = Validator::Object(ObjectValidator(btreemap! {
+            "property".parse()? => FieldValidator::required_field_type(
+                Validator::Array(Box::new(Validator::String))
+            )
+        }));
+        let check = |object| {
+            validator
+                .check_value(
+                    &ConvexValue::Object(object),
+                    &empty_table_mapping(),
+                    &VirtualSystemMapping::default(),
+                )
+                .unwrap_err()
+                .field_path()
+        };
+        assert_eq!(
+            check(assert_obj!("property" => ConvexValue::Array(array!(123.into())?))),
+            Some(".property[0]".to_string())
+        );
+        assert_eq!(check(assert_obj!()), Some(".property".to_string()));
+        Ok(())
+    }
+
     #[test]
     fn test_ensure_supported_for_streaming_export() -> anyhow::Result<()> {
         let simple_object_validator = Validator::Object(ObjectValidator(btreemap! {
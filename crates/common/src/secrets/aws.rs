@@ -0,0 +1,130 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use ring::{
+    digest,
+    hmac,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::SecretsProvider;
+
+const SERVICE: &str = "secretsmanager";
+
+/// Reads secrets from AWS Secrets Manager. Requests are signed with SigV4
+/// using static credentials from the environment.
+pub struct AwsSecretsManagerProvider {
+    client: reqwest::Client,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GetSecretValueResponse {
+    secret_string: Option<String>,
+}
+
+impl AwsSecretsManagerProvider {
+    /// Configured from `AWS_REGION`, `AWS_ACCESS_KEY_ID`,
+    /// `AWS_SECRET_ACCESS_KEY` and optionally `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            client: reqwest::Client::new(),
+            region: std::env::var("AWS_REGION").ok()?,
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+
+    fn host(&self) -> String {
+        format!("{SERVICE}.{}.amazonaws.com", self.region)
+    }
+
+    /// Returns the signed headers for a `POST /` request with `body`.
+    fn sign(&self, body: &str, now: chrono::DateTime<chrono::Utc>) -> Vec<(&'static str, String)> {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", self.host()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        headers.push(("x-amz-target", "secretsmanager.GetSecretValue".to_string()));
+
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        let canonical_request = format!(
+            "POST\n/\n\n{canonical_headers}\n{signed_headers}\n{}",
+            sha256_hex(body.as_bytes())
+        );
+        let scope = format!("{date}/{}/{SERVICE}/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            sha256_hex(canonical_request.as_bytes())
+        );
+        let signing_key = [self.region.as_str(), SERVICE, "aws4_request"].iter().fold(
+            hmac_sha256(
+                format!("AWS4{}", self.secret_access_key).as_bytes(),
+                date.as_bytes(),
+            ),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+        headers.push((
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, \
+                 Signature={signature}",
+                self.access_key_id
+            ),
+        ));
+        headers
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for AwsSecretsManagerProvider {
+    async fn fetch(&self, name: &str) -> anyhow::Result<String> {
+        let body = json!({ "SecretId": name }).to_string();
+        let mut request = self.client.post(format!("https://{}/", self.host()));
+        for (header, value) in self.sign(&body, chrono::Utc::now()) {
+            // reqwest sets the host header from the URL.
+            if header != "host" {
+                request = request.header(header, value);
+            }
+        }
+        let response = request.body(body).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("AWS Secrets Manager returned {status}: {text}");
+        }
+        let response: GetSecretValueResponse = response.json().await?;
+        response
+            .secret_string
+            .context("Binary secrets aren't supported, store the secret as a string")
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(digest::digest(&digest::SHA256, data))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::sign(&key, data).as_ref().to_vec()
+}
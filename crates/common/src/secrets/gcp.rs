@@ -0,0 +1,103 @@
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::Deserialize;
+
+use super::SecretsProvider;
+
+/// Reads secrets from GCP Secret Manager, authenticating with the service
+/// account attached to the instance via the metadata server. Secret names are
+/// either a full resource name or a short name, which reads the latest version
+/// of that secret in this project.
+pub struct GcpSecretManagerProvider {
+    client: reqwest::Client,
+    project: String,
+    metadata_host: String,
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct AccessSecretVersionResponse {
+    payload: SecretPayload,
+}
+
+#[derive(Deserialize)]
+struct SecretPayload {
+    data: String,
+}
+
+impl GcpSecretManagerProvider {
+    /// Configured from `GOOGLE_CLOUD_PROJECT` and optionally
+    /// `GCE_METADATA_HOST`.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            client: reqwest::Client::new(),
+            project: std::env::var("GOOGLE_CLOUD_PROJECT").ok()?,
+            metadata_host: std::env::var("GCE_METADATA_HOST")
+                .unwrap_or_else(|_| "metadata.google.internal".to_string()),
+            token: Mutex::new(None),
+        })
+    }
+
+    async fn access_token(&self) -> anyhow::Result<String> {
+        if let Some((token, expires_at)) = &*self.token.lock()
+            && Instant::now() < *expires_at
+        {
+            return Ok(token.clone());
+        }
+        let response = self
+            .client
+            .get(format!(
+                "http://{}/computeMetadata/v1/instance/service-accounts/default/token",
+                self.metadata_host
+            ))
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await?
+            .error_for_status()?;
+        let response: AccessTokenResponse = response.json().await?;
+        // Refresh a little early so the token doesn't expire mid-request.
+        let expires_at =
+            Instant::now() + Duration::from_secs(response.expires_in.saturating_sub(60));
+        *self.token.lock() = Some((response.access_token.clone(), expires_at));
+        Ok(response.access_token)
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for GcpSecretManagerProvider {
+    async fn fetch(&self, name: &str) -> anyhow::Result<String> {
+        let resource = if name.starts_with("projects/") {
+            name.to_string()
+        } else {
+            format!("projects/{}/secrets/{name}/versions/latest", self.project)
+        };
+        let response = self
+            .client
+            .get(format!(
+                "https://secretmanager.googleapis.com/v1/{resource}:access"
+            ))
+            .bearer_auth(self.access_token().await?)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("GCP Secret Manager returned {status}: {text}");
+        }
+        let response: AccessSecretVersionResponse = response.json().await?;
+        let data = base64::decode(response.payload.data)?;
+        String::from_utf8(data).context("The secret isn't valid UTF-8")
+    }
+}
@@ -0,0 +1,335 @@
+//! Environment variables whose values live in an external secrets manager.
+//!
+//! An environment variable's value can be a reference like
+//! `secret://aws-secrets-manager/prod/stripe#apiKey` instead of the secret
+//! itself. The deployment only stores the reference, and actions resolve it
+//! through a [`SecretsProvider`] when they run. Resolved values are cached for
+//! `SECRETS_CACHE_TTL`, so rotated secrets are picked up once the cached value
+//! expires.
+
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    sync::Arc,
+    time::Instant,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use errors::ErrorMetadata;
+use parking_lot::Mutex;
+use serde_json::Value as JsonValue;
+
+use crate::{
+    knobs::{
+        SECRETS_CACHE_TTL,
+        SECRETS_MAX_STALENESS,
+    },
+    metrics::log_secret_lookup,
+    types::{
+        EnvVarName,
+        EnvVarValue,
+    },
+};
+
+mod aws;
+mod gcp;
+mod vault;
+
+pub use self::{
+    aws::AwsSecretsManagerProvider,
+    gcp::GcpSecretManagerProvider,
+    vault::VaultSecretsProvider,
+};
+
+pub const SECRET_REFERENCE_PREFIX: &str = "secret://";
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, strum::Display, strum::EnumString,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum SecretsProviderKind {
+    AwsSecretsManager,
+    Vault,
+    GcpSecretManager,
+}
+
+/// A reference to a secret, parsed from an environment variable value of the
+/// form `secret://<provider>/<name>[#<key>]`. If `key` is set, the secret is
+/// parsed as a JSON object and the value of that field is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecretReference {
+    pub provider: SecretsProviderKind,
+    pub name: String,
+    pub key: Option<String>,
+}
+
+impl SecretReference {
+    /// Returns `None` if the value is a plain value rather than a reference.
+    pub fn parse(value: &str) -> anyhow::Result<Option<Self>> {
+        let Some(rest) = value.strip_prefix(SECRET_REFERENCE_PREFIX) else {
+            return Ok(None);
+        };
+        let (provider, path) = rest
+            .split_once('/')
+            .context("Secret references must look like secret://<provider>/<name>")?;
+        let provider = provider.parse().map_err(|_| {
+            anyhow::anyhow!(
+                "Unknown secrets provider {provider:?}. Expected one of aws-secrets-manager, \
+                 vault or gcp-secret-manager"
+            )
+        })?;
+        let (name, key) = match path.split_once('#') {
+            Some((name, key)) => (name, Some(key.to_string())),
+            None => (path, None),
+        };
+        anyhow::ensure!(
+            !name.is_empty(),
+            "Secret reference is missing a secret name"
+        );
+        Ok(Some(Self {
+            provider,
+            name: name.to_string(),
+            key,
+        }))
+    }
+}
+
+#[async_trait]
+pub trait SecretsProvider: Send + Sync {
+    /// Fetch the current value of the secret called `name`.
+    async fn fetch(&self, name: &str) -> anyhow::Result<String>;
+}
+
+#[derive(Clone)]
+struct CachedSecret {
+    value: String,
+    fetched_at: Instant,
+}
+
+/// Resolves secret references in environment variables using the configured
+/// providers. Cheap to clone; clones share a cache.
+#[derive(Clone, Default)]
+pub struct SecretsResolver {
+    providers: Arc<BTreeMap<SecretsProviderKind, Arc<dyn SecretsProvider>>>,
+    cache: Arc<Mutex<HashMap<(SecretsProviderKind, String), CachedSecret>>>,
+}
+
+impl SecretsResolver {
+    pub fn new(providers: BTreeMap<SecretsProviderKind, Arc<dyn SecretsProvider>>) -> Self {
+        Self {
+            providers: Arc::new(providers),
+            cache: Default::default(),
+        }
+    }
+
+    /// Configure each provider whose settings are present in the process
+    /// environment.
+    pub fn from_env() -> Self {
+        let mut providers: BTreeMap<SecretsProviderKind, Arc<dyn SecretsProvider>> =
+            BTreeMap::new();
+        if let Some(provider) = AwsSecretsManagerProvider::from_env() {
+            providers.insert(SecretsProviderKind::AwsSecretsManager, Arc::new(provider));
+        }
+        if let Some(provider) = VaultSecretsProvider::from_env() {
+            providers.insert(SecretsProviderKind::Vault, Arc::new(provider));
+        }
+        if let Some(provider) = GcpSecretManagerProvider::from_env() {
+            providers.insert(SecretsProviderKind::GcpSecretManager, Arc::new(provider));
+        }
+        Self::new(providers)
+    }
+
+    /// Replace every secret reference in `env_vars` with the secret's value.
+    /// Plain values are returned unchanged.
+    pub async fn resolve_env_vars(
+        &self,
+        mut env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+    ) -> anyhow::Result<BTreeMap<EnvVarName, EnvVarValue>> {
+        for (name, value) in env_vars.iter_mut() {
+            let resolved = async {
+                let Some(reference) = SecretReference::parse(value.as_ref())? else {
+                    return Ok(None);
+                };
+                let secret = self.resolve(&reference).await?;
+                anyhow::Ok(Some(secret.parse::<EnvVarValue>()?))
+            }
+            .await
+            .map_err(|e| {
+                ErrorMetadata::bad_request(
+                    "SecretResolutionFailed",
+                    format!("Failed to resolve the secret for environment variable {name}: {e:#}"),
+                )
+            })?;
+            if let Some(resolved) = resolved {
+                *value = resolved;
+            }
+        }
+        Ok(env_vars)
+    }
+
+    async fn resolve(&self, reference: &SecretReference) -> anyhow::Result<String> {
+        let cache_key = (reference.provider, reference.name.clone());
+        let cached = self.cache.lock().get(&cache_key).cloned();
+        if let Some(cached) = &cached
+            && cached.fetched_at.elapsed() < *SECRETS_CACHE_TTL
+        {
+            log_secret_lookup(reference.provider, true, true);
+            return extract_key(&cached.value, reference.key.as_deref());
+        }
+        let provider = self.providers.get(&reference.provider).with_context(|| {
+            format!(
+                "The {} secrets provider isn't configured for this deployment",
+                reference.provider
+            )
+        })?;
+        let value = match provider.fetch(&reference.name).await {
+            Ok(value) => {
+                log_secret_lookup(reference.provider, false, true);
+                self.cache.lock().insert(
+                    cache_key,
+                    CachedSecret {
+                        value: value.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+                value
+            },
+            Err(e) => {
+                log_secret_lookup(reference.provider, false, false);
+                // Keep serving the last value we fetched if the provider is
+                // briefly unavailable.
+                match cached {
+                    Some(cached) if cached.fetched_at.elapsed() < *SECRETS_MAX_STALENESS => {
+                        tracing::warn!(
+                            "Failed to refresh secret from {}, using cached value: {e:#}",
+                            reference.provider
+                        );
+                        cached.value
+                    },
+                    _ => return Err(e),
+                }
+            },
+        };
+        extract_key(&value, reference.key.as_deref())
+    }
+}
+
+fn extract_key(secret: &str, key: Option<&str>) -> anyhow::Result<String> {
+    let Some(key) = key else {
+        return Ok(secret.to_string());
+    };
+    let JsonValue::Object(mut fields) = serde_json::from_str(secret)
+        .context("The secret isn't a JSON object, so it can't be indexed with #")?
+    else {
+        anyhow::bail!("The secret isn't a JSON object, so it can't be indexed with #");
+    };
+    match fields.remove(key) {
+        Some(JsonValue::String(s)) => Ok(s),
+        Some(value) => Ok(value.to_string()),
+        None => anyhow::bail!("The secret has no field {key:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{
+            atomic::{
+                AtomicUsize,
+                Ordering,
+            },
+            Arc,
+        },
+    };
+
+    use async_trait::async_trait;
+    use maplit::btreemap;
+
+    use super::{
+        SecretReference,
+        SecretsProvider,
+        SecretsProviderKind,
+        SecretsResolver,
+    };
+
+    struct FakeProvider {
+        fetches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SecretsProvider for FakeProvider {
+        async fn fetch(&self, name: &str) -> anyhow::Result<String> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            match name {
+                "stripe" => Ok(r#"{"apiKey": "sk_test_123", "accountId": 7}"#.to_string()),
+                "plain" => Ok("hunter2".to_string()),
+                _ => anyhow::bail!("Secret {name} not found"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_reference() -> anyhow::Result<()> {
+        assert_eq!(SecretReference::parse("not a reference")?, None);
+        assert_eq!(
+            SecretReference::parse("secret://aws-secrets-manager/prod/stripe#apiKey")?,
+            Some(SecretReference {
+                provider: SecretsProviderKind::AwsSecretsManager,
+                name: "prod/stripe".to_string(),
+                key: Some("apiKey".to_string()),
+            })
+        );
+        assert!(SecretReference::parse("secret://keychain/stripe").is_err());
+        assert!(SecretReference::parse("secret://vault/").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resolve_env_vars() -> anyhow::Result<()> {
+        let provider = Arc::new(FakeProvider {
+            fetches: AtomicUsize::new(0),
+        });
+        let resolver = SecretsResolver::new(BTreeMap::from([(
+            SecretsProviderKind::Vault,
+            provider.clone() as Arc<dyn SecretsProvider>,
+        )]));
+        let env_vars = btreemap! {
+            "API_KEY".parse()? => "secret://vault/stripe#apiKey".parse()?,
+            "ACCOUNT".parse()? => "secret://vault/stripe#accountId".parse()?,
+            "PASSWORD".parse()? => "secret://vault/plain".parse()?,
+            "PLAIN".parse()? => "value".parse()?,
+        };
+        let resolved = resolver.resolve_env_vars(env_vars).await?;
+        assert_eq!(
+            resolved,
+            btreemap! {
+                "API_KEY".parse()? => "sk_test_123".parse()?,
+                "ACCOUNT".parse()? => "7".parse()?,
+                "PASSWORD".parse()? => "hunter2".parse()?,
+                "PLAIN".parse()? => "value".parse()?,
+            }
+        );
+        // Both fields of the same secret share one cached fetch.
+        assert_eq!(provider.fetches.load(Ordering::SeqCst), 2);
+
+        let err = resolver
+            .resolve_env_vars(btreemap! {
+                "MISSING".parse()? => "secret://vault/missing".parse()?,
+            })
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("Secret missing not found"));
+        let err = resolver
+            .resolve_env_vars(btreemap! {
+                "UNCONFIGURED".parse()? => "secret://gcp-secret-manager/x".parse()?,
+            })
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("isn't configured"));
+        Ok(())
+    }
+}
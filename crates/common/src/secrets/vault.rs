@@ -0,0 +1,64 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+use super::SecretsProvider;
+
+/// Reads secrets from a HashiCorp Vault KV version 2 secrets engine. Secret
+/// names are `<mount>/<path>`, and the secret's data is returned as a JSON
+/// object so individual fields can be selected with `#<key>`.
+pub struct VaultSecretsProvider {
+    client: reqwest::Client,
+    address: String,
+    token: String,
+    namespace: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct KvV2Response {
+    data: KvV2Data,
+}
+
+#[derive(Deserialize)]
+struct KvV2Data {
+    data: JsonValue,
+}
+
+impl VaultSecretsProvider {
+    /// Configured from `VAULT_ADDR`, `VAULT_TOKEN` and optionally
+    /// `VAULT_NAMESPACE`.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            client: reqwest::Client::new(),
+            address: std::env::var("VAULT_ADDR").ok()?,
+            token: std::env::var("VAULT_TOKEN").ok()?,
+            namespace: std::env::var("VAULT_NAMESPACE").ok(),
+        })
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for VaultSecretsProvider {
+    async fn fetch(&self, name: &str) -> anyhow::Result<String> {
+        let (mount, path) = name
+            .split_once('/')
+            .context("Vault secret names must look like <mount>/<path>")?;
+        let url = format!(
+            "{}/v1/{mount}/data/{path}",
+            self.address.trim_end_matches('/')
+        );
+        let mut request = self.client.get(url).header("X-Vault-Token", &self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Vault returned {status}: {text}");
+        }
+        let response: KvV2Response = response.json().await?;
+        Ok(response.data.data.to_string())
+    }
+}
@@ -52,4 +52,11 @@ impl ShutdownSignal {
             instance_name: "".to_owned(),
         }
     }
+
+    // Creates a new ShutdownSignal along with a receiver for its messages.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn new_for_test() -> (Self, async_broadcast::Receiver<ShutdownMessage>) {
+        let (sender, receiver) = async_broadcast::broadcast(1);
+        (Self::new(sender, "test".to_owned()), receiver)
+    }
 }
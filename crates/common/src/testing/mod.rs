@@ -1,4 +1,5 @@
 //! Test helpers for types defined in this crate
+mod persistence_faults;
 #[cfg(test)]
 mod schema;
 mod test_id_generator;
@@ -7,6 +8,11 @@ mod test_persistence;
 use std::fmt::Display;
 
 pub use cmd_util::env::config_test as init_test_logging;
+pub use persistence_faults::{
+    FaultyPersistence,
+    PersistenceFaults,
+    WriteFault,
+};
 use proptest::{
     arbitrary::{
         any,
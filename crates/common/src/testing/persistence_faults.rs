@@ -0,0 +1,201 @@
+use std::{
+    collections::{
+        BTreeSet,
+        VecDeque,
+    },
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::Value as JsonValue;
+use value::InternalDocumentId;
+
+use crate::{
+    index::IndexEntry,
+    persistence::{
+        ConflictStrategy,
+        DocumentLogEntry,
+        Persistence,
+        PersistenceGlobalKey,
+        PersistenceReader,
+    },
+    types::{
+        DatabaseIndexUpdate,
+        Timestamp,
+    },
+};
+
+/// How an injected write failure behaves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteFault {
+    /// The write fails without reaching the underlying persistence.
+    BeforeCommit,
+    /// The write reaches the underlying persistence but the caller still sees
+    /// an error, as when a connection drops before the commit is acknowledged.
+    AfterCommit,
+}
+
+/// Faults for a [`FaultyPersistence`] to inject. Clones share state, so a test
+/// can keep a handle and change the faults while the database is running.
+#[derive(Clone, Default)]
+pub struct PersistenceFaults {
+    state: Arc<Mutex<FaultState>>,
+}
+
+#[derive(Default)]
+struct FaultState {
+    latency: Option<Duration>,
+    write_faults: VecDeque<WriteFault>,
+    partial_deletes: usize,
+    num_injected: usize,
+}
+
+impl PersistenceFaults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delays every write and delete by `latency`, until cleared with `None`.
+    pub fn set_latency(&self, latency: Option<Duration>) {
+        self.state.lock().latency = latency;
+    }
+
+    /// Fails the next `write` that isn't already claimed by an earlier fault.
+    pub fn fail_next_write(&self, fault: WriteFault) {
+        self.state.lock().write_faults.push_back(fault);
+    }
+
+    /// Makes the next `count` deletes of documents or index entries remove the
+    /// first half of their batch and then fail.
+    pub fn fail_next_deletes_partway(&self, count: usize) {
+        self.state.lock().partial_deletes += count;
+    }
+
+    /// The number of write and delete failures injected so far.
+    pub fn num_injected(&self) -> usize {
+        self.state.lock().num_injected
+    }
+
+    async fn delay(&self) {
+        let latency = self.state.lock().latency;
+        if let Some(latency) = latency {
+            tokio::time::sleep(latency).await;
+        }
+    }
+
+    fn take_write_fault(&self) -> Option<WriteFault> {
+        let mut state = self.state.lock();
+        let fault = state.write_faults.pop_front();
+        if fault.is_some() {
+            state.num_injected += 1;
+        }
+        fault
+    }
+
+    fn take_partial_delete(&self) -> bool {
+        let mut state = self.state.lock();
+        if state.partial_deletes == 0 {
+            return false;
+        }
+        state.partial_deletes -= 1;
+        state.num_injected += 1;
+        true
+    }
+}
+
+/// Wraps a persistence and injects the faults in a [`PersistenceFaults`] into
+/// its writes and deletes. Reads pass straight through.
+#[derive(Clone)]
+pub struct FaultyPersistence {
+    inner: Arc<dyn Persistence>,
+    faults: PersistenceFaults,
+}
+
+impl FaultyPersistence {
+    pub fn new(inner: Arc<dyn Persistence>, faults: PersistenceFaults) -> Self {
+        Self { inner, faults }
+    }
+}
+
+#[async_trait]
+impl Persistence for FaultyPersistence {
+    fn is_fresh(&self) -> bool {
+        self.inner.is_fresh()
+    }
+
+    fn reader(&self) -> Arc<dyn PersistenceReader> {
+        self.inner.reader()
+    }
+
+    async fn write(
+        &self,
+        documents: Vec<DocumentLogEntry>,
+        indexes: BTreeSet<(Timestamp, DatabaseIndexUpdate)>,
+        conflict_strategy: ConflictStrategy,
+    ) -> anyhow::Result<()> {
+        self.faults.delay().await;
+        let fault = self.faults.take_write_fault();
+        anyhow::ensure!(
+            fault != Some(WriteFault::BeforeCommit),
+            "Injected persistence fault: write failed before commit"
+        );
+        self.inner
+            .write(documents, indexes, conflict_strategy)
+            .await?;
+        anyhow::ensure!(
+            fault != Some(WriteFault::AfterCommit),
+            "Injected persistence fault: write failed after commit"
+        );
+        Ok(())
+    }
+
+    async fn set_read_only(&self, read_only: bool) -> anyhow::Result<()> {
+        self.inner.set_read_only(read_only).await
+    }
+
+    async fn write_persistence_global(
+        &self,
+        key: PersistenceGlobalKey,
+        value: JsonValue,
+    ) -> anyhow::Result<()> {
+        self.faults.delay().await;
+        self.inner.write_persistence_global(key, value).await
+    }
+
+    async fn load_index_chunk(
+        &self,
+        cursor: Option<IndexEntry>,
+        chunk_size: usize,
+    ) -> anyhow::Result<Vec<IndexEntry>> {
+        self.inner.load_index_chunk(cursor, chunk_size).await
+    }
+
+    async fn delete_index_entries(&self, mut entries: Vec<IndexEntry>) -> anyhow::Result<usize> {
+        self.faults.delay().await;
+        if self.faults.take_partial_delete() {
+            entries.truncate(entries.len() / 2);
+            self.inner.delete_index_entries(entries).await?;
+            anyhow::bail!("Injected persistence fault: index entry delete failed partway");
+        }
+        self.inner.delete_index_entries(entries).await
+    }
+
+    async fn delete(
+        &self,
+        mut documents: Vec<(Timestamp, InternalDocumentId)>,
+    ) -> anyhow::Result<usize> {
+        self.faults.delay().await;
+        if self.faults.take_partial_delete() {
+            documents.truncate(documents.len() / 2);
+            self.inner.delete(documents).await?;
+            anyhow::bail!("Injected persistence fault: document delete failed partway");
+        }
+        self.inner.delete(documents).await
+    }
+
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        self.inner.shutdown().await
+    }
+}
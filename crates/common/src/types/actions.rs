@@ -142,14 +142,14 @@ pub struct HttpActionRoute {
 impl HttpActionRoute {
     pub fn overlaps_with_mount(&self, mount_path: &HttpMountPath) -> bool {
         // Only prefix routes can overlap with mounts.
-        let Some(mut suffix) = mount_path.strip_suffix('*') else {
+        let Some(mut prefix) = self.path.strip_suffix('*') else {
             return false;
         };
         // For backwards compatibility, permit bare `*` paths as a synonym for `/*`.
-        if suffix.is_empty() {
-            suffix = "/";
+        if prefix.is_empty() {
+            prefix = "/";
         }
-        suffix == &mount_path[..]
+        prefix == &mount_path[..]
     }
 }
 
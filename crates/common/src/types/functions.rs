@@ -156,6 +156,12 @@ pub enum FunctionCaller {
     Action {
         parent_scheduled_job: Option<DeveloperDocumentId>,
     },
+    /// A batch of a user-defined data migration run by the migration worker.
+    DataMigration,
+    /// A component's onMount or onUnmount hook, run as part of a push.
+    LifecycleHook,
+    /// A table's file text extractor, run by the file text worker.
+    FileTextExtraction,
     #[cfg(any(test, feature = "testing"))]
     #[proptest(weight = 0)]
     Test,
@@ -170,7 +176,10 @@ impl FunctionCaller {
             FunctionCaller::HttpEndpoint
             | FunctionCaller::Cron
             | FunctionCaller::Scheduler { .. }
-            | FunctionCaller::Action { .. } => None,
+            | FunctionCaller::Action { .. }
+            | FunctionCaller::DataMigration
+            | FunctionCaller::LifecycleHook
+            | FunctionCaller::FileTextExtraction => None,
             #[cfg(any(test, feature = "testing"))]
             FunctionCaller::Test => None,
         }
@@ -183,7 +192,10 @@ impl FunctionCaller {
             | FunctionCaller::HttpApi(_)
             | FunctionCaller::Tester(_)
             | FunctionCaller::HttpEndpoint
-            | FunctionCaller::Cron => None,
+            | FunctionCaller::Cron
+            | FunctionCaller::DataMigration
+            | FunctionCaller::LifecycleHook
+            | FunctionCaller::FileTextExtraction => None,
             #[cfg(any(test, feature = "testing"))]
             FunctionCaller::Test => None,
             FunctionCaller::Scheduler { job_id } => Some(*job_id),
@@ -200,7 +212,10 @@ impl FunctionCaller {
             | FunctionCaller::Tester(_)
             | FunctionCaller::HttpEndpoint
             | FunctionCaller::Cron
-            | FunctionCaller::Scheduler { .. } => true,
+            | FunctionCaller::Scheduler { .. }
+            | FunctionCaller::DataMigration
+            | FunctionCaller::LifecycleHook
+            | FunctionCaller::FileTextExtraction => true,
             FunctionCaller::Action { .. } => false,
             #[cfg(any(test, feature = "testing"))]
             FunctionCaller::Test => true,
@@ -218,7 +233,10 @@ impl FunctionCaller {
             | FunctionCaller::Tester(_) => true,
             FunctionCaller::Cron
             | FunctionCaller::Scheduler { .. }
-            | FunctionCaller::Action { .. } => false,
+            | FunctionCaller::Action { .. }
+            | FunctionCaller::DataMigration
+            | FunctionCaller::LifecycleHook
+            | FunctionCaller::FileTextExtraction => false,
             #[cfg(any(test, feature = "testing"))]
             FunctionCaller::Test => true,
         }
@@ -236,7 +254,10 @@ impl FunctionCaller {
             FunctionCaller::Tester(_)
             | FunctionCaller::Cron
             | FunctionCaller::Scheduler { .. }
-            | FunctionCaller::Action { .. } => AllowedVisibility::All,
+            | FunctionCaller::Action { .. }
+            | FunctionCaller::DataMigration
+            | FunctionCaller::LifecycleHook
+            | FunctionCaller::FileTextExtraction => AllowedVisibility::All,
             #[cfg(any(test, feature = "testing"))]
             FunctionCaller::Test => AllowedVisibility::PublicOnly,
         }
@@ -253,6 +274,9 @@ impl fmt::Display for FunctionCaller {
             FunctionCaller::Cron => "Cron",
             FunctionCaller::Scheduler { .. } => "Scheduler",
             FunctionCaller::Action { .. } => "Action",
+            FunctionCaller::DataMigration => "DataMigration",
+            FunctionCaller::LifecycleHook => "LifecycleHook",
+            FunctionCaller::FileTextExtraction => "FileTextExtraction",
             #[cfg(any(test, feature = "testing"))]
             FunctionCaller::Test => "Test",
         };
@@ -288,6 +312,11 @@ impl From<FunctionCaller> for pb::common::FunctionCaller {
                 };
                 pb::common::function_caller::Caller::Action(caller)
             },
+            FunctionCaller::DataMigration => pb::common::function_caller::Caller::DataMigration(()),
+            FunctionCaller::LifecycleHook => pb::common::function_caller::Caller::LifecycleHook(()),
+            FunctionCaller::FileTextExtraction => {
+                pb::common::function_caller::Caller::FileTextExtraction(())
+            },
             #[cfg(any(test, feature = "testing"))]
             FunctionCaller::Test => panic!("Can't use test function caller"),
         };
@@ -315,6 +344,15 @@ impl TryFrom<pb::common::FunctionCaller> for FunctionCaller {
                 FunctionCaller::HttpEndpoint
             },
             Some(pb::common::function_caller::Caller::Cron(())) => FunctionCaller::Cron,
+            Some(pb::common::function_caller::Caller::DataMigration(())) => {
+                FunctionCaller::DataMigration
+            },
+            Some(pb::common::function_caller::Caller::LifecycleHook(())) => {
+                FunctionCaller::LifecycleHook
+            },
+            Some(pb::common::function_caller::Caller::FileTextExtraction(())) => {
+                FunctionCaller::FileTextExtraction
+            },
             Some(pb::common::function_caller::Caller::Scheduler(caller)) => {
                 let pb::common::SchedulerFunctionCaller { job_id } = caller;
                 let job_id = job_id.context("Missing `job_id` field")?.try_into()?;
@@ -5,7 +5,10 @@ use std::{
 
 use anyhow::Context;
 use errors::ErrorMetadata;
-use imbl::OrdMap;
+use imbl::{
+    OrdMap,
+    OrdSet,
+};
 use semver::Version;
 use value::{
     DeveloperDocumentId,
@@ -75,6 +78,8 @@ pub struct VirtualSystemMapping {
     virtual_to_system_indexes: OrdMap<IndexName, IndexName>,
     // system_table_name -> (Fn (SystemDoc) -> VirtualDoc)
     system_to_virtual_doc_mapper: OrdMap<TableName, Arc<dyn VirtualSystemDocMapper>>,
+    // Virtual tables only admins can read.
+    admin_only: OrdSet<TableName>,
 }
 
 impl std::fmt::Debug for VirtualSystemMapping {
@@ -108,6 +113,14 @@ impl VirtualSystemMapping {
             .insert(system.clone(), mapper);
     }
 
+    pub fn set_admin_only(&mut self, virt: &TableName) {
+        self.admin_only.insert(virt.clone());
+    }
+
+    pub fn is_admin_only(&self, virt: &TableName) -> bool {
+        self.admin_only.contains(virt)
+    }
+
     pub fn is_virtual_table(&self, table_name: &TableName) -> bool {
         self.virtual_to_system.contains_key(table_name)
     }
@@ -0,0 +1,147 @@
+//! A harness for writing Rust integration tests against Convex components.
+//!
+//! The harness runs an in-memory [`Application`] on the given runtime, usually
+//! a `TestRuntime`. Load an app that mounts your component with
+//! [`ComponentTestHarness::load_bundle`], call its functions, and check what
+//! they wrote to each component's tables. Bundles are the
+//! `start_push_request.json` files the CLI writes when pushing with a debug
+//! bundle path.
+use std::{
+    fs,
+    path::Path,
+};
+
+use anyhow::Context;
+use application::{
+    deploy_config::{
+        FinishPushDiff,
+        StartPushRequest,
+    },
+    test_helpers::ApplicationTestExt,
+    Application,
+    FunctionError,
+    FunctionReturn,
+};
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::FunctionCaller,
+    RequestId,
+};
+use database::{
+    query::TableFilter,
+    BootstrapComponentsModel,
+    DeveloperQuery,
+    TableModel,
+};
+use futures::FutureExt;
+use keybroker::Identity;
+pub use runtime::testing::{
+    TestDriver,
+    TestRuntime,
+};
+use serde_json::Value as JsonValue;
+use sync_types::CanonicalizedUdfPath;
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+pub struct ComponentTestHarness<RT: Runtime> {
+    application: Application<RT>,
+}
+
+impl<RT: Runtime> ComponentTestHarness<RT> {
+    /// Starts an empty deployment.
+    pub async fn new(rt: &RT) -> anyhow::Result<Self> {
+        Ok(Self {
+            application: Application::new_for_tests(rt).await?,
+        })
+    }
+
+    /// Pushes the app and components in a bundle, waiting for their schemas
+    /// to validate.
+    pub async fn load_bundle(&self, path: &Path) -> anyhow::Result<FinishPushDiff> {
+        let contents =
+            fs::read(path).with_context(|| format!("Failed to read bundle {}", path.display()))?;
+        let request: StartPushRequest = serde_json::from_slice(&contents)
+            .with_context(|| format!("Invalid bundle {}", path.display()))?;
+        self.application.run_test_push(request).await
+    }
+
+    /// Runs a query, mutation or action as the system identity, returning
+    /// its logs along with its result. `component` is the path the component
+    /// is mounted at, or "" for the app.
+    pub async fn run_function(
+        &self,
+        component: &str,
+        udf_path: &str,
+        args: JsonValue,
+    ) -> anyhow::Result<Result<FunctionReturn, FunctionError>> {
+        let path = CanonicalizedComponentFunctionPath {
+            component: component.parse()?,
+            udf_path: udf_path.parse::<CanonicalizedUdfPath>()?,
+        };
+        self.application
+            .any_udf(
+                RequestId::new(),
+                path,
+                vec![args],
+                Identity::system(),
+                FunctionCaller::Test,
+            )
+            .boxed()
+            .await
+    }
+
+    /// Runs a function and returns its result, failing if it threw.
+    pub async fn call(
+        &self,
+        component: &str,
+        udf_path: &str,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        let result = self.run_function(component, udf_path, args).await??;
+        Ok(result.value.into())
+    }
+
+    /// Reads every document in one of a component's tables, in creation order.
+    /// Returns an empty list if the component has no such table.
+    pub async fn documents(&self, component: &str, table: &str) -> anyhow::Result<Vec<JsonValue>> {
+        let component_path: ComponentPath = component.parse()?;
+        let table_name: TableName = table.parse()?;
+        let mut tx = self.application.begin(Identity::system()).await?;
+        let (_, component_id) =
+            BootstrapComponentsModel::new(&mut tx).must_component_path_to_ids(&component_path)?;
+        let namespace = TableNamespace::from(component_id);
+        if !TableModel::new(&mut tx).table_exists(namespace, &table_name) {
+            return Ok(vec![]);
+        }
+        let mut query = DeveloperQuery::new(
+            &mut tx,
+            namespace,
+            Query::full_table_scan(table_name, Order::Asc),
+            TableFilter::ExcludePrivateSystemTables,
+        )?;
+        let mut documents = vec![];
+        while let Some(document) = query.next(&mut tx, None).await? {
+            documents.push(document.into_value().0.into());
+        }
+        Ok(documents)
+    }
+
+    /// The underlying deployment, for anything the harness doesn't cover.
+    pub fn application(&self) -> &Application<RT> {
+        &self.application
+    }
+}
+
+#[cfg(test)]
+mod tests;
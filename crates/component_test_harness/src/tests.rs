@@ -0,0 +1,33 @@
+use std::path::Path;
+
+use isolate::bundled_js::OUT_DIR;
+use runtime::testing::TestRuntime;
+use serde_json::json;
+
+use crate::ComponentTestHarness;
+
+#[convex_macro::test_runtime]
+async fn test_component_tables_are_isolated(rt: TestRuntime) -> anyhow::Result<()> {
+    let harness = ComponentTestHarness::new(&rt).await?;
+    harness
+        .load_bundle(&Path::new(OUT_DIR).join("with-schema/start_push_request.json"))
+        .await?;
+
+    harness
+        .call(
+            "",
+            "componentEntry:insert",
+            json!({ "channel": "random", "text": "hello" }),
+        )
+        .await?;
+    let messages = harness.documents("component", "messages").await?;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["text"], json!("hello"));
+    assert!(harness.documents("", "messages").await?.is_empty());
+
+    let listed = harness
+        .call("component", "messages:listMessages", json!({}))
+        .await?;
+    assert_eq!(listed.as_array().map(Vec::len), Some(1));
+    Ok(())
+}
@@ -297,6 +297,7 @@ impl RemoteQuerySet {
                     value,
                     log_lines,
                     journal: _,
+                    page_split: _,
                 } => {
                     for log_line in log_lines.0 {
                         convex_logs!("{}", log_line);
@@ -515,6 +516,7 @@ impl BaseConvexClient {
             udf_path,
             args: vec![Value::Object(args).into()],
             component_path: None,
+            request_context: None,
         };
 
         let result_receiver = self.request_manager.track_request(
@@ -544,6 +546,7 @@ impl BaseConvexClient {
             udf_path,
             args: vec![Value::Object(args).into()],
             component_path: None,
+            request_context: None,
         };
 
         let result_receiver = self.request_manager.track_request(
@@ -563,6 +563,7 @@ pub mod tests {
                         value,
                         journal: None,
                         log_lines: LogLinesMessage(vec![]),
+                        page_split: None,
                     })
                     .collect(),
             },
@@ -586,6 +587,7 @@ pub mod tests {
                 udf_path: UdfPath::from_str("incrementCounter")?,
                 args: vec![json!({})],
                 component_path: None,
+                request_context: None,
             }]
         );
 
@@ -640,6 +642,7 @@ pub mod tests {
                 udf_path: UdfPath::from_str("runAction:hello")?,
                 args: vec![json!({})],
                 component_path: None,
+                request_context: None,
             }]
         );
 
@@ -23,6 +23,7 @@ use crate::{
     ClientMessage,
     IdentityVersion,
     LogLinesMessage,
+    PageSplitMessage,
     Query,
     QueryId,
     QuerySetModification,
@@ -187,6 +188,9 @@ enum ClientMessageJson {
         args: JsonValue,
         #[serde(skip_serializing_if = "Option::is_none")]
         component_path: Option<String>,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        request_context: Option<BTreeMap<String, String>>,
     },
     #[serde(rename_all = "camelCase")]
     Action {
@@ -195,6 +199,9 @@ enum ClientMessageJson {
         args: JsonValue,
         #[serde(skip_serializing_if = "Option::is_none")]
         component_path: Option<String>,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        request_context: Option<BTreeMap<String, String>>,
     },
     #[serde(rename_all = "camelCase")]
     Authenticate {
@@ -242,22 +249,26 @@ impl TryFrom<ClientMessage> for JsonValue {
                 udf_path,
                 args,
                 component_path,
+                request_context,
             } => ClientMessageJson::Mutation {
                 request_id,
                 udf_path: String::from(udf_path),
                 args: JsonValue::Array(args.into_iter().map(JsonValue::from).collect::<Vec<_>>()),
                 component_path,
+                request_context,
             },
             ClientMessage::Action {
                 request_id,
                 udf_path,
                 args,
                 component_path,
+                request_context,
             } => ClientMessageJson::Action {
                 request_id,
                 udf_path: String::from(udf_path),
                 args: JsonValue::Array(args.into_iter().map(JsonValue::from).collect::<Vec<_>>()),
                 component_path,
+                request_context,
             },
             ClientMessage::Authenticate {
                 base_version,
@@ -330,6 +341,7 @@ impl TryFrom<JsonValue> for ClientMessage {
                 udf_path,
                 args,
                 component_path,
+                request_context,
             } => {
                 let json_args: Vec<JsonValue> = serde_json::from_value(args)?;
                 ClientMessage::Mutation {
@@ -337,6 +349,7 @@ impl TryFrom<JsonValue> for ClientMessage {
                     udf_path: udf_path.parse()?,
                     args: json_args,
                     component_path,
+                    request_context,
                 }
             },
             ClientMessageJson::Action {
@@ -344,6 +357,7 @@ impl TryFrom<JsonValue> for ClientMessage {
                 udf_path,
                 args,
                 component_path,
+                request_context,
             } => {
                 let json_args: Vec<JsonValue> = serde_json::from_value(args)?;
                 ClientMessage::Action {
@@ -351,6 +365,7 @@ impl TryFrom<JsonValue> for ClientMessage {
                     udf_path: udf_path.parse()?,
                     args: json_args,
                     component_path,
+                    request_context,
                 }
             },
             ClientMessageJson::Authenticate {
@@ -415,15 +430,20 @@ impl<V: Into<JsonValue>> From<StateModification<V>> for JsonValue {
                 value,
                 log_lines,
                 journal,
+                page_split,
             } => {
                 let jv: JsonValue = value.into();
-                json!({
+                let mut response = json!({
                     "type": "QueryUpdated",
                     "queryId": query_id,
                     "value": jv,
                     "logLines": log_lines,
                     "journal": journal
-                })
+                });
+                if let Some(page_split) = page_split {
+                    response["pageSplit"] = json!(page_split);
+                }
+                response
             },
             StateModification::QueryFailed {
                 query_id,
@@ -467,6 +487,8 @@ impl<V: TryFrom<JsonValue, Error = anyhow::Error>> TryFrom<JsonValue> for StateM
                 value: JsonValue,
                 log_lines: LogLinesMessage,
                 journal: SerializedQueryJournal,
+                #[serde(default)]
+                page_split: Option<PageSplitMessage>,
             },
             #[serde(rename_all = "camelCase")]
             QueryFailed {
@@ -487,11 +509,13 @@ impl<V: TryFrom<JsonValue, Error = anyhow::Error>> TryFrom<JsonValue> for StateM
                 value,
                 log_lines,
                 journal,
+                page_split,
             } => StateModification::QueryUpdated {
                 query_id,
                 value: value.try_into()?,
                 log_lines,
                 journal,
+                page_split,
             },
             StateModificationJson::QueryFailed {
                 query_id,
@@ -24,6 +24,8 @@ pub use crate::{
         ErrorPayload,
         IdentityVersion,
         LogLinesMessage,
+        PageSplitMessage,
+        PageStatus,
         Query,
         QueryId,
         QuerySetModification,
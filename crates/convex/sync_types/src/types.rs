@@ -129,6 +129,9 @@ pub enum ClientMessage {
         /// For internal use by Convex dashboard. Only works with admin auth.
         /// Allows calling a mutation within a component directly.
         component_path: Option<String>,
+        /// Opaque context (e.g. trace ids or a locale) that's readable by the
+        /// function and any functions it calls or schedules.
+        request_context: Option<BTreeMap<String, String>>,
     },
     Action {
         request_id: SessionRequestSeqNumber,
@@ -141,6 +144,9 @@ pub enum ClientMessage {
         /// For internal use by Convex dashboard. Only works with admin auth.
         /// Allows calling an action within a component directly.
         component_path: Option<String>,
+        /// Opaque context (e.g. trace ids or a locale) that's readable by the
+        /// function and any functions it calls or schedules.
+        request_context: Option<BTreeMap<String, String>>,
     },
     Authenticate {
         base_version: IdentityVersion,
@@ -266,6 +272,9 @@ pub enum StateModification<V> {
         value: V,
         log_lines: LogLinesMessage,
         journal: SerializedQueryJournal,
+        /// Set when the query's paginated page grew too large and should be
+        /// split, so the client only needs to re-execute that page.
+        page_split: Option<PageSplitMessage>,
     },
     QueryFailed {
         query_id: QueryId,
@@ -356,6 +365,24 @@ impl<V: 'static> ErrorPayload<V> {
     }
 }
 
+/// Whether an oversized page of a paginated query should or must be split.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum PageStatus {
+    SplitRecommended,
+    SplitRequired,
+}
+
+/// A boundary at which the client can replace a page `(cursor, endCursor]`
+/// with `(cursor, splitCursor]` and `(splitCursor, endCursor]`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct PageSplitMessage {
+    pub split_cursor: String,
+    pub page_status: PageStatus,
+}
+
 /// List of log lines from a Convex function execution.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
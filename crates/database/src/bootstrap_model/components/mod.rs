@@ -1,16 +1,23 @@
 pub mod definition;
+pub mod virtual_table;
 
 use std::{
     collections::BTreeMap,
-    sync::LazyLock,
+    sync::{
+        Arc,
+        LazyLock,
+    },
 };
 
 use anyhow::Context;
+use async_trait::async_trait;
 use common::{
     bootstrap_model::components::{
         definition::{
             ComponentDefinitionMetadata,
             ComponentDefinitionType,
+            ComponentExport,
+            ComponentLifecycle,
         },
         ComponentMetadata,
         ComponentType,
@@ -23,6 +30,7 @@ use common::{
         ComponentId,
         ComponentName,
         ComponentPath,
+        Reference,
         Resource,
     },
     document::{
@@ -35,8 +43,11 @@ use common::{
     },
     runtime::Runtime,
     types::IndexName,
+    virtual_system_mapping::VirtualSystemDocMapper,
 };
 use errors::ErrorMetadata;
+use maplit::btreemap;
+use sync_types::path::PathComponent;
 use value::{
     identifier::Identifier,
     ConvexValue,
@@ -47,6 +58,14 @@ use value::{
     TableNamespace,
 };
 
+use self::virtual_table::{
+    ComponentsDocMapper,
+    COMPONENTS_INDEX_BY_CREATION_TIME,
+    COMPONENTS_INDEX_BY_ID,
+    COMPONENTS_VIRTUAL_INDEX_BY_CREATION_TIME,
+    COMPONENTS_VIRTUAL_INDEX_BY_ID,
+    COMPONENTS_VIRTUAL_TABLE,
+};
 use crate::{
     defaults::{
         system_index,
@@ -86,12 +105,43 @@ impl SystemTable for ComponentsTable {
         }]
     }
 
+    fn virtual_table(
+        &self,
+    ) -> Option<(
+        &'static TableName,
+        BTreeMap<IndexName, IndexName>,
+        Arc<dyn VirtualSystemDocMapper>,
+    )> {
+        Some((
+            &COMPONENTS_VIRTUAL_TABLE,
+            btreemap! {
+                COMPONENTS_VIRTUAL_INDEX_BY_CREATION_TIME.clone() =>
+                    COMPONENTS_INDEX_BY_CREATION_TIME.clone(),
+                COMPONENTS_VIRTUAL_INDEX_BY_ID.clone() =>
+                    COMPONENTS_INDEX_BY_ID.clone(),
+            },
+            Arc::new(ComponentsDocMapper),
+        ))
+    }
+
     fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
         ParsedDocument::<ComponentMetadata>::try_from(document)?;
         Ok(())
     }
 }
 
+/// Loads the exports of a component. Exports are derived from a component's
+/// modules, which the bootstrap model can't read, so callers of
+/// [`BootstrapComponentsModel::resolve_export`] supply them.
+#[async_trait]
+pub trait ComponentExportsLoader<RT: Runtime>: Send + Sync {
+    async fn load_exports(
+        &self,
+        tx: &mut Transaction<RT>,
+        component: ComponentId,
+    ) -> anyhow::Result<BTreeMap<PathComponent, ComponentExport>>;
+}
+
 pub struct BootstrapComponentsModel<'a, RT: Runtime> {
     pub tx: &'a mut Transaction<RT>,
 }
@@ -320,6 +370,7 @@ impl<'a, RT: Runtime> BootstrapComponentsModel<'a, RT> {
                         child_components: Vec::new(),
                         exports: BTreeMap::new(),
                         http_mounts: BTreeMap::new(),
+                        lifecycle: ComponentLifecycle::default(),
                     })
                 } else {
                     anyhow::bail!(ErrorMetadata::bad_request(
@@ -373,6 +424,148 @@ impl<'a, RT: Runtime> BootstrapComponentsModel<'a, RT> {
             .context("Component path not found")
     }
 
+    /// Resolve a path exported by a component (e.g. `["foo", "bar"]` for
+    /// `components.child.foo.bar`) to the function it refers to. Exports that
+    /// refer to a child component's exports or to a function passed in as a
+    /// component argument are followed until they reach a function.
+    pub async fn resolve_export(
+        &mut self,
+        loader: &dyn ComponentExportsLoader<RT>,
+        component_id: ComponentId,
+        attributes: &[PathComponent],
+    ) -> anyhow::Result<CanonicalizedComponentFunctionPath> {
+        let mut visited: Vec<(ComponentId, Vec<PathComponent>)> = vec![];
+        let mut current = (component_id, attributes.to_vec());
+        loop {
+            if visited.contains(&current) {
+                let component_path = self.must_component_path(component_id)?;
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "ExportCycle",
+                    format!(
+                        "Export {} of component {component_path:?} refers back to itself",
+                        Self::export_debug_str(attributes),
+                    ),
+                ));
+            }
+            visited.push(current.clone());
+            let (component_id, attributes) = &current;
+            let exports = loader.load_exports(self.tx, *component_id).await?;
+            let component_path = self.must_component_path(*component_id)?;
+            let reference = Self::lookup_export(&exports, attributes).ok_or_else(|| {
+                ErrorMetadata::bad_request(
+                    "ExportNotFound",
+                    format!(
+                        "Component {component_path:?} does not export a function at {}",
+                        Self::export_debug_str(attributes)
+                    ),
+                )
+            })?;
+            let next = match reference {
+                Reference::Function(udf_path) => {
+                    return Ok(CanonicalizedComponentFunctionPath {
+                        component: component_path,
+                        udf_path: udf_path.clone(),
+                    });
+                },
+                Reference::ChildComponent {
+                    component: name,
+                    attributes: child_attributes,
+                } => {
+                    let parent_id = match component_id {
+                        ComponentId::Root => self
+                            .root_component()?
+                            .context("Missing root component")?
+                            .id()
+                            .into(),
+                        ComponentId::Child(id) => *id,
+                    };
+                    let child = self
+                        .component_in_parent(Some((parent_id, name.clone())))?
+                        .ok_or_else(|| {
+                            ErrorMetadata::bad_request(
+                                "ExportNotFound",
+                                format!(
+                                    "Export {} of component {component_path:?} refers to missing \
+                                     child component {name:?}",
+                                    Self::export_debug_str(attributes)
+                                ),
+                            )
+                        })?;
+                    (
+                        ComponentId::Child(child.id().into()),
+                        child_attributes.clone(),
+                    )
+                },
+                Reference::ComponentArgument {
+                    attributes: arg_attributes,
+                } => {
+                    let ComponentType::ChildComponent { args, .. } =
+                        self.load_component_type(*component_id).await?
+                    else {
+                        anyhow::bail!(ErrorMetadata::bad_request(
+                            "InvalidExport",
+                            "The app can't export a component argument",
+                        ));
+                    };
+                    let resource = match &arg_attributes[..] {
+                        [name] => args.get(name),
+                        _ => None,
+                    };
+                    match resource {
+                        Some(Resource::Function(path)) => return Ok(path.clone()),
+                        _ => anyhow::bail!(ErrorMetadata::bad_request(
+                            "InvalidExport",
+                            format!(
+                                "Export {} of component {component_path:?} refers to {}, which \
+                                 isn't a function",
+                                Self::export_debug_str(attributes),
+                                reference.evaluation_time_debug_str(),
+                            ),
+                        )),
+                    }
+                },
+                Reference::CurrentSystemUdfInComponent { .. } => {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "InvalidExport",
+                        format!(
+                            "Export {} of component {component_path:?} can't refer to a system \
+                             function",
+                            Self::export_debug_str(attributes)
+                        ),
+                    ));
+                },
+            };
+            current = next;
+        }
+    }
+
+    fn lookup_export<'b>(
+        exports: &'b BTreeMap<PathComponent, ComponentExport>,
+        attributes: &[PathComponent],
+    ) -> Option<&'b Reference> {
+        let (last, prefix) = attributes.split_last()?;
+        let mut current = exports;
+        for attribute in prefix {
+            match current.get(attribute)? {
+                ComponentExport::Branch(branch) => current = branch,
+                ComponentExport::Leaf(_) => return None,
+            }
+        }
+        match current.get(last)? {
+            ComponentExport::Leaf(reference) => Some(reference),
+            ComponentExport::Branch(_) => None,
+        }
+    }
+
+    fn export_debug_str(attributes: &[PathComponent]) -> String {
+        let mut s = "api".to_string();
+        for attribute in attributes {
+            s.push('.');
+            s.push_str(&attribute[..]);
+        }
+        s
+    }
+
     pub fn function_path_to_module(
         &mut self,
         path: &CanonicalizedComponentFunctionPath,
@@ -389,34 +582,45 @@ impl<'a, RT: Runtime> BootstrapComponentsModel<'a, RT> {
 mod tests {
     use std::collections::BTreeMap;
 
+    use async_trait::async_trait;
     use common::{
         bootstrap_model::components::{
             definition::{
                 ComponentDefinitionMetadata,
                 ComponentDefinitionType,
+                ComponentExport,
                 ComponentInstantiation,
+                ComponentLifecycle,
             },
             ComponentMetadata,
             ComponentState,
             ComponentType,
         },
         components::{
+            CanonicalizedComponentFunctionPath,
             ComponentDefinitionPath,
             ComponentId,
             ComponentPath,
+            Reference,
+            Resource,
         },
     };
+    use errors::ErrorMetadataAnyhowExt;
     use keybroker::Identity;
     use runtime::testing::TestRuntime;
+    use sync_types::path::PathComponent;
+    use value::DeveloperDocumentId;
 
     use super::definition::COMPONENT_DEFINITIONS_TABLE;
     use crate::{
         bootstrap_model::components::{
             BootstrapComponentsModel,
+            ComponentExportsLoader,
             COMPONENTS_TABLE,
         },
         test_helpers::new_test_database,
         SystemMetadataModel,
+        Transaction,
     };
 
     #[convex_macro::test_runtime]
@@ -436,6 +640,7 @@ mod tests {
                     child_components: Vec::new(),
                     http_mounts: BTreeMap::new(),
                     exports: BTreeMap::new(),
+                    lifecycle: ComponentLifecycle::default(),
                 }
                 .try_into()?,
             )
@@ -450,9 +655,11 @@ mod tests {
                         name: "child_subcomponent".parse().unwrap(),
                         path: child_definition_path,
                         args: Some(BTreeMap::new()),
+                        grants: BTreeMap::new(),
                     }],
                     http_mounts: BTreeMap::new(),
                     exports: BTreeMap::new(),
+                    lifecycle: ComponentLifecycle::default(),
                 }
                 .try_into()?,
             )
@@ -477,6 +684,7 @@ mod tests {
                         parent: root_id.into(),
                         name: "subcomponent_child".parse()?,
                         args: Default::default(),
+                        grants: Default::default(),
                     },
                     state: ComponentState::Active,
                 }
@@ -494,4 +702,125 @@ mod tests {
         );
         Ok(())
     }
+
+    struct TestExportsLoader(BTreeMap<ComponentId, BTreeMap<PathComponent, ComponentExport>>);
+
+    #[async_trait]
+    impl ComponentExportsLoader<TestRuntime> for TestExportsLoader {
+        async fn load_exports(
+            &self,
+            _tx: &mut Transaction<TestRuntime>,
+            component: ComponentId,
+        ) -> anyhow::Result<BTreeMap<PathComponent, ComponentExport>> {
+            Ok(self.0.get(&component).cloned().unwrap_or_default())
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_resolve_export(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = new_test_database(rt.clone()).await;
+        let mut tx = db.begin(Identity::system()).await?;
+        let root_id = SystemMetadataModel::new_global(&mut tx)
+            .insert(
+                &COMPONENTS_TABLE,
+                ComponentMetadata {
+                    definition_id: DeveloperDocumentId::MIN,
+                    component_type: ComponentType::App,
+                    state: ComponentState::Active,
+                }
+                .try_into()?,
+            )
+            .await?;
+        let callback = CanonicalizedComponentFunctionPath {
+            component: ComponentPath::root(),
+            udf_path: "callbacks:onDone".parse()?,
+        };
+        let child_id = SystemMetadataModel::new_global(&mut tx)
+            .insert(
+                &COMPONENTS_TABLE,
+                ComponentMetadata {
+                    definition_id: DeveloperDocumentId::MIN,
+                    component_type: ComponentType::ChildComponent {
+                        parent: root_id.into(),
+                        name: "child".parse()?,
+                        args: BTreeMap::from([(
+                            "onDone".parse()?,
+                            Resource::Function(callback.clone()),
+                        )]),
+                        grants: BTreeMap::new(),
+                    },
+                    state: ComponentState::Active,
+                }
+                .try_into()?,
+            )
+            .await?;
+        let child_id = ComponentId::Child(child_id.into());
+        let loader = TestExportsLoader(BTreeMap::from([
+            (
+                ComponentId::Root,
+                BTreeMap::from([
+                    (
+                        "list".parse()?,
+                        ComponentExport::Leaf(Reference::ChildComponent {
+                            component: "child".parse()?,
+                            attributes: vec!["messages".parse()?, "list".parse()?],
+                        }),
+                    ),
+                    (
+                        "missing".parse()?,
+                        ComponentExport::Leaf(Reference::ChildComponent {
+                            component: "other".parse()?,
+                            attributes: vec!["list".parse()?],
+                        }),
+                    ),
+                ]),
+            ),
+            (
+                child_id,
+                BTreeMap::from([
+                    (
+                        "messages".parse()?,
+                        ComponentExport::Branch(BTreeMap::from([(
+                            "list".parse()?,
+                            ComponentExport::Leaf(Reference::Function("messages:list".parse()?)),
+                        )])),
+                    ),
+                    (
+                        "onDone".parse()?,
+                        ComponentExport::Leaf(Reference::ComponentArgument {
+                            attributes: vec!["onDone".parse()?],
+                        }),
+                    ),
+                ]),
+            ),
+        ]));
+
+        let mut model = BootstrapComponentsModel::new(&mut tx);
+        let path = model
+            .resolve_export(&loader, ComponentId::Root, &["list".parse()?])
+            .await?;
+        assert_eq!(
+            path,
+            CanonicalizedComponentFunctionPath {
+                component: ComponentPath::from(vec!["child".parse()?]),
+                udf_path: "messages:list".parse()?,
+            }
+        );
+        let path = model
+            .resolve_export(&loader, child_id, &["onDone".parse()?])
+            .await?;
+        assert_eq!(path, callback);
+
+        let err = model
+            .resolve_export(&loader, child_id, &["messages".parse()?])
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "ExportNotFound");
+        let err = model
+            .resolve_export(&loader, ComponentId::Root, &["missing".parse()?])
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "ExportNotFound");
+        Ok(())
+    }
 }
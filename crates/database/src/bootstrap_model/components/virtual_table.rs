@@ -0,0 +1,164 @@
+use std::{
+    collections::BTreeMap,
+    sync::LazyLock,
+};
+
+use common::{
+    bootstrap_model::components::{
+        ComponentMetadata,
+        ComponentState,
+        ComponentType,
+    },
+    components::{
+        ComponentDefinitionId,
+        ComponentId,
+    },
+    document::{
+        DeveloperDocument,
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD,
+        ID_FIELD,
+    },
+    runtime::Runtime,
+    types::{
+        GenericIndexName,
+        IndexName,
+    },
+    version::Version,
+    virtual_system_mapping::{
+        VirtualSystemDocMapper,
+        VirtualSystemMapping,
+    },
+};
+use errors::ErrorMetadata;
+use value::{
+    ConvexValue,
+    FieldName,
+    TableMapping,
+    TableName,
+    TableNamespace,
+};
+
+use super::{
+    BootstrapComponentsModel,
+    COMPONENTS_TABLE,
+};
+use crate::Transaction;
+
+/// Read-only view of the component tree. The `_components` name is taken by
+/// the system table itself, so the virtual table gets its own name.
+pub static COMPONENTS_VIRTUAL_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_component_tree"
+        .parse()
+        .expect("_component_tree is not a valid virtual table name")
+});
+
+pub(super) static COMPONENTS_INDEX_BY_ID: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_id(COMPONENTS_TABLE.clone()));
+pub(super) static COMPONENTS_INDEX_BY_CREATION_TIME: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_creation_time(COMPONENTS_TABLE.clone()));
+pub(super) static COMPONENTS_VIRTUAL_INDEX_BY_ID: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_id(COMPONENTS_VIRTUAL_TABLE.clone()));
+pub(super) static COMPONENTS_VIRTUAL_INDEX_BY_CREATION_TIME: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_creation_time(COMPONENTS_VIRTUAL_TABLE.clone()));
+
+/// Maps the fields stored on the `_components` document. The component path
+/// and definition path depend on other documents, so
+/// [`add_component_tree_paths`] fills them in afterwards.
+pub struct ComponentsDocMapper;
+
+impl VirtualSystemDocMapper for ComponentsDocMapper {
+    fn system_to_virtual_doc(
+        &self,
+        virtual_system_mapping: &VirtualSystemMapping,
+        doc: ResolvedDocument,
+        _table_mapping: &TableMapping,
+        _version: Version,
+    ) -> anyhow::Result<DeveloperDocument> {
+        let component: ParsedDocument<ComponentMetadata> = doc.clone().try_into()?;
+        let state = match component.state {
+            ComponentState::Active => "active",
+            ComponentState::Unmounted => "unmounted",
+        };
+        let virtual_developer_id =
+            virtual_system_mapping.system_resolved_id_to_virtual_developer_id(doc.id())?;
+
+        let mut fields: BTreeMap<FieldName, ConvexValue> = BTreeMap::new();
+        fields.insert(ID_FIELD.to_owned().into(), virtual_developer_id.into());
+        if let Some(t) = doc.creation_time() {
+            fields.insert(
+                CREATION_TIME_FIELD.to_owned().into(),
+                ConvexValue::from(f64::from(t)),
+            );
+        }
+        fields.insert("state".parse()?, ConvexValue::try_from(state.to_string())?);
+        Ok(DeveloperDocument::new(
+            virtual_developer_id,
+            doc.creation_time(),
+            fields.try_into()?,
+        ))
+    }
+}
+
+/// Only admins can list the component tree, and only from the app: child
+/// components shouldn't learn where they're mounted.
+pub fn check_component_tree_access<RT: Runtime>(
+    tx: &Transaction<RT>,
+    namespace: TableNamespace,
+) -> anyhow::Result<()> {
+    let identity = tx.identity();
+    if !(identity.is_admin() || identity.is_system()) {
+        anyhow::bail!(ErrorMetadata::forbidden(
+            "ComponentTreeAccessDenied",
+            format!(
+                "Only admins can read the {} table",
+                *COMPONENTS_VIRTUAL_TABLE
+            ),
+        ));
+    }
+    if namespace != TableNamespace::root_component() {
+        anyhow::bail!(ErrorMetadata::forbidden(
+            "ComponentTreeAccessDenied",
+            format!(
+                "The {} table can only be read from the app",
+                *COMPONENTS_VIRTUAL_TABLE
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Adds `path` and `definitionPath` to a `_component_tree` document produced
+/// by [`ComponentsDocMapper`].
+pub async fn add_component_tree_paths<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    system_doc: ResolvedDocument,
+    virtual_doc: DeveloperDocument,
+) -> anyhow::Result<DeveloperDocument> {
+    let component: ParsedDocument<ComponentMetadata> = system_doc.try_into()?;
+    let (component_id, definition_id) = match &component.component_type {
+        ComponentType::App => (ComponentId::Root, ComponentDefinitionId::Root),
+        ComponentType::ChildComponent { .. } => (
+            ComponentId::Child(component.developer_id()),
+            ComponentDefinitionId::Child(component.definition_id),
+        ),
+    };
+    let mut model = BootstrapComponentsModel::new(tx);
+    let path = model.must_component_path(component_id)?;
+    let definition = model.load_definition_metadata(definition_id).await?;
+
+    let id = virtual_doc.id();
+    let creation_time = virtual_doc.creation_time();
+    let mut fields: BTreeMap<_, _> = virtual_doc.into_value().0.into();
+    fields.insert("path".parse()?, ConvexValue::try_from(String::from(path))?);
+    fields.insert(
+        "definitionPath".parse()?,
+        ConvexValue::try_from(String::from(definition.path))?,
+    );
+    Ok(DeveloperDocument::new(
+        id,
+        creation_time,
+        fields.try_into()?,
+    ))
+}
@@ -6,7 +6,14 @@ use common::{
         CREATION_TIME_FIELD,
         ID_FIELD,
     },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
     runtime::Runtime,
+    types::IndexName,
 };
 use errors::ErrorMetadata;
 use value::{
@@ -15,6 +22,8 @@ use value::{
     ConvexValue,
     DeveloperDocumentId,
     FieldName,
+    FieldPath,
+    IdentifierFieldName,
     ResolvedDocumentId,
     Size,
     TableMapping,
@@ -24,10 +33,22 @@ use value::{
 
 use crate::{
     defaults::bootstrap_system_tables,
+    query::ResolvedQuery,
     SchemaModel,
     Transaction,
 };
 
+/// How [`ImportFacingModel::upsert_by_field`] applied a document.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpsertByFieldOutcome {
+    Inserted,
+    Updated,
+    /// The matching document already had the imported values.
+    Unchanged,
+    /// More than one document matched, so nothing was written.
+    Conflict,
+}
+
 /// `ImportFacingModel` is similar to `UserFacingModel` but with a few
 /// differences for insertions:
 /// - the table for insertion is chosen by table id, not table name or number.
@@ -206,6 +227,93 @@ impl<'a, RT: Runtime> ImportFacingModel<'a, RT> {
         Ok(id.into())
     }
 
+    /// Patches the document whose `key_field` equals `value`'s with the fields
+    /// of `value`, or inserts `value` if no document matches. `index_name`
+    /// must be an index on the table starting with `key_field`. System fields
+    /// of `value` are ignored.
+    #[convex_macro::instrument_future]
+    pub async fn upsert_by_field(
+        &mut self,
+        table_id: TabletIdAndTableNumber,
+        table_name: &TableName,
+        index_name: IndexName,
+        key_field: &FieldName,
+        value: ConvexObject,
+        table_mapping_for_schema: &TableMapping,
+    ) -> anyhow::Result<UpsertByFieldOutcome> {
+        if self
+            .tx
+            .virtual_system_mapping()
+            .is_virtual_table(table_name)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ReadOnlyTable",
+                format!("{table_name} is a read-only table"),
+            ));
+        }
+        anyhow::ensure!(
+            bootstrap_system_tables()
+                .iter()
+                .all(|t| t.table_name() != table_name),
+            "Cannot import into bootstrap system table {table_name}"
+        );
+        if !(self.tx.identity.is_admin() || self.tx.identity.is_system()) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "UnauthorizedImport",
+                "Import requires admin auth"
+            ));
+        }
+
+        let value = value.filter_system_fields();
+        let key_value = value
+            .get(key_field)
+            .cloned()
+            .context(ErrorMetadata::bad_request(
+                "MissingUpsertKey",
+                format!(
+                    "Document imported into {table_name} is missing upsert key field {key_field}"
+                ),
+            ))?;
+        let key_path = FieldPath::new(vec![IdentifierFieldName::try_from(key_field.clone())?])?;
+        let namespace = self
+            .tx
+            .table_mapping()
+            .tablet_namespace(table_id.tablet_id)?;
+        let query = Query::index_range(IndexRange {
+            index_name,
+            range: vec![IndexRangeExpression::Eq(key_path, key_value.into())],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, namespace, query)?;
+        let Some(existing_doc) = query_stream.next(self.tx, None).await? else {
+            self.insert(table_id, table_name, value, table_mapping_for_schema)
+                .await?;
+            return Ok(UpsertByFieldOutcome::Inserted);
+        };
+        if query_stream.next(self.tx, Some(1)).await?.is_some() {
+            return Ok(UpsertByFieldOutcome::Conflict);
+        }
+
+        let existing_value = existing_doc.value().0.clone();
+        let patched_value = existing_value.clone().shallow_merge(value)?;
+        if patched_value == existing_value {
+            return Ok(UpsertByFieldOutcome::Unchanged);
+        }
+        if !table_name.is_system() {
+            check_user_size(patched_value.size())?;
+        }
+        self.tx.retention_validator.fail_if_falling_behind()?;
+        let id = existing_doc.id();
+        let existing_doc_with_ts = self.tx.get_with_ts(id).await?;
+        let document = existing_doc.replace_value(patched_value)?;
+        SchemaModel::new(self.tx, namespace)
+            .enforce_with_table_mapping(&document, &table_mapping_for_schema.namespace(namespace))
+            .await?;
+        self.tx
+            .apply_validated_write(id, existing_doc_with_ts, Some(document))?;
+        Ok(UpsertByFieldOutcome::Updated)
+    }
+
     pub async fn delete(
         &mut self,
         table_id: TabletIdAndTableNumber,
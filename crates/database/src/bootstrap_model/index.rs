@@ -69,6 +69,10 @@ use value::{
 };
 
 use crate::{
+    bootstrap_model::components::virtual_table::{
+        check_component_tree_access,
+        COMPONENTS_VIRTUAL_TABLE,
+    },
     defaults::{
         SystemIndex,
         SystemTable,
@@ -78,6 +82,7 @@ use crate::{
     table_summary::table_summary_bootstrapping_error,
     transaction_index::TransactionIndex,
     unauthorized_error,
+    virtual_tables::check_virtual_table_access,
     ResolvedQuery,
     SystemMetadataModel,
     TableModel,
@@ -702,6 +707,10 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
             .virtual_system_mapping()
             .is_virtual_table(index_name.table())
         {
+            if *index_name.table() == *COMPONENTS_VIRTUAL_TABLE {
+                check_component_tree_access(self.tx, namespace)?;
+            }
+            check_virtual_table_access(self.tx, index_name.table())?;
             let physical_index_name = self
                 .tx
                 .virtual_system_mapping()
@@ -31,6 +31,7 @@ use common::{
 };
 use errors::ErrorMetadata;
 use value::{
+    ConvexObject,
     FieldPath,
     NamespacedTableMapping,
     ResolvedDocumentId,
@@ -139,6 +140,42 @@ impl<'a, RT: Runtime> SchemaModel<'a, RT> {
             .await
     }
 
+    /// Fill in the active schema's defaults for fields missing from a
+    /// document about to be written to `table_name`.
+    pub async fn apply_defaults(
+        &mut self,
+        table_name: &TableName,
+        object: ConvexObject,
+    ) -> anyhow::Result<ConvexObject> {
+        match self.get_by_state(SchemaState::Active).await? {
+            Some((_id, active_schema)) => active_schema.apply_defaults(table_name, object),
+            None => Ok(object),
+        }
+    }
+
+    /// Reject an update that changes a field the active schema marks as
+    /// immutable.
+    pub async fn enforce_immutable_fields(
+        &mut self,
+        old_document: &ResolvedDocument,
+        new_document: &ResolvedDocument,
+    ) -> anyhow::Result<()> {
+        let schema_table_mapping = self.tx.table_mapping().namespace(self.namespace);
+        let tablet_id = new_document.id().tablet_id;
+        if schema_table_mapping.is_system_tablet(tablet_id) {
+            return Ok(());
+        }
+        let table_name = schema_table_mapping.tablet_name(tablet_id)?;
+        if let Some((_id, active_schema)) = self.get_by_state(SchemaState::Active).await? {
+            active_schema.check_immutable_fields(
+                &table_name,
+                &old_document.value().0,
+                &new_document.value().0,
+            )?;
+        }
+        Ok(())
+    }
+
     pub async fn enforce_table_deletion(
         &mut self,
         active_table_to_delete: TableName,
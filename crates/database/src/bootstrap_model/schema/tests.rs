@@ -394,6 +394,55 @@ async fn test_schema_enforced_on_write(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_immutable_fields_enforced_on_update(rt: TestRuntime) -> anyhow::Result<()> {
+    let db = new_test_database(rt.clone()).await;
+    let mut tx = db.begin(Identity::system()).await?;
+    let mut model = SchemaModel::new_root_for_test(&mut tx);
+
+    let table = "table".parse::<TableName>()?;
+    let object_validator = object_validator!(
+        "owner" => FieldValidator {
+            validator: Validator::String,
+            optional: false,
+            default: None,
+            immutable: true,
+        },
+        "name" => FieldValidator::required_field_type(Validator::String)
+    );
+    let document_schema = DocumentSchema::Union(vec![object_validator]);
+    let db_schema = db_schema!(table.clone() => document_schema);
+    let (schema_id, _state) = model.submit_pending(db_schema.clone()).await?;
+    model.mark_validated(schema_id).await?;
+    model.mark_active(schema_id).await?;
+
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table, assert_obj!("owner" => "emma", "name" => "first"))
+        .await?;
+
+    // Mutable fields can change as long as the immutable ones don't.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(id, assert_obj!("owner" => "emma", "name" => "second"))
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .patch(id, assert_obj!("name" => "third").into())
+        .await?;
+
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .replace(id, assert_obj!("owner" => "lee", "name" => "third"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "ImmutableFieldModified");
+    assert!(err.msg().contains("`owner`"));
+
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .patch(id, assert_obj!("owner" => "lee").into())
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "ImmutableFieldModified");
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_schema_failed_after_bad_insert(rt: TestRuntime) -> anyhow::Result<()> {
     let db = new_test_database(rt.clone()).await;
@@ -334,30 +334,17 @@ impl<'a, RT: Runtime> TableModel<'a, RT> {
         let Some(table_number) = table_number else {
             return Ok(());
         };
-        let Some(existing_table_by_number) = self
-            .tx
-            .table_mapping()
-            .namespace(namespace)
-            .name_by_number_if_exists(table_number)
-            .cloned()
+        let Some(existing_table_by_number) =
+            self.table_number_conflict(namespace, table, table_number, tables_affected_in_import)
         else {
-            // No existing table with this number.
             return Ok(());
         };
-        if let Some(existing_virtual_table) = self
+        if self
             .tx
             .virtual_system_mapping()
             .system_to_virtual_table(&existing_table_by_number)
+            .is_some()
         {
-            let existing_system_table = self
-                .tx
-                .virtual_system_mapping()
-                .virtual_to_system_table(existing_virtual_table)?;
-            if existing_system_table == table {
-                // Setting physical table to have the same table number as its virtual table is
-                // allowed.
-                return Ok(());
-            }
             anyhow::bail!(ErrorMetadata::bad_request(
                 "TableConflict",
                 format!("New table {table} has IDs that conflict with existing system table")
@@ -370,16 +357,6 @@ impl<'a, RT: Runtime> TableModel<'a, RT> {
                 .all(|t| *t.table_name() != existing_table_by_number),
             "Conflict with bootstrap system table {existing_table_by_number}",
         );
-        if existing_table_by_number == *table {
-            // Overwriting in-place, same table name and number.
-            return Ok(());
-        }
-        if tables_affected_in_import.contains(&(namespace, existing_table_by_number.clone())) {
-            // Overwriting would create a table number conflict with an
-            // existing table, but that existing table is also being
-            // overwritten.
-            return Ok(());
-        }
         if existing_table_by_number.is_system() {
             anyhow::bail!(ErrorMetadata::bad_request(
                 "TableConflict",
@@ -399,6 +376,46 @@ impl<'a, RT: Runtime> TableModel<'a, RT> {
         ));
     }
 
+    /// Returns the existing table that would stop `table` from being created
+    /// with `table_number` by an import, if any. Numbers can be taken over
+    /// from tables that the import also overwrites or deletes.
+    pub fn table_number_conflict(
+        &mut self,
+        namespace: TableNamespace,
+        table: &TableName,
+        table_number: TableNumber,
+        tables_affected_in_import: &BTreeSet<(TableNamespace, TableName)>,
+    ) -> Option<TableName> {
+        let existing_table_by_number = self
+            .tx
+            .table_mapping()
+            .namespace(namespace)
+            .name_by_number_if_exists(table_number)
+            .cloned()?;
+        let virtual_system_mapping = self.tx.virtual_system_mapping();
+        if let Some(existing_virtual_table) =
+            virtual_system_mapping.system_to_virtual_table(&existing_table_by_number)
+        {
+            // Setting physical table to have the same table number as its virtual table is
+            // allowed.
+            return match virtual_system_mapping.virtual_to_system_table(existing_virtual_table) {
+                Ok(existing_system_table) if existing_system_table == table => None,
+                _ => Some(existing_table_by_number),
+            };
+        }
+        if existing_table_by_number == *table {
+            // Overwriting in-place, same table name and number.
+            return None;
+        }
+        if tables_affected_in_import.contains(&(namespace, existing_table_by_number.clone())) {
+            // Overwriting would create a table number conflict with an
+            // existing table, but that existing table is also being
+            // overwritten.
+            return None;
+        }
+        Some(existing_table_by_number)
+    }
+
     pub async fn activate_table(
         &mut self,
         tablet_id: TabletId,
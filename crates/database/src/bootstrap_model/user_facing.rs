@@ -5,7 +5,11 @@ use std::{
 
 use anyhow::Context;
 use common::{
-    bootstrap_model::components::ComponentState,
+    bootstrap_model::components::{
+        definition::TableAccess,
+        ComponentState,
+        ComponentType,
+    },
     components::ComponentId,
     document::{
         DeveloperDocument,
@@ -15,6 +19,7 @@ use common::{
     runtime::Runtime,
     types::{
         StableIndexName,
+        Timestamp,
         WriteTimestamp,
     },
     version::Version,
@@ -51,8 +56,10 @@ use crate::{
     virtual_tables::VirtualTable,
     BootstrapComponentsModel,
     PatchValue,
+    SchemaModel,
     TableModel,
     Transaction,
+    COMPONENTS_TABLE,
 };
 
 // Low-level model struct that represents a "user facing" data model
@@ -104,24 +111,19 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         id: DeveloperDocumentId,
         version: Option<Version>,
     ) -> anyhow::Result<Option<(DeveloperDocument, WriteTimestamp)>> {
-        if !self
-            .tx
-            .table_mapping()
-            .namespace(self.namespace)
-            .table_number_exists()(id.table())
-        {
+        let Some(namespace) = self.namespace_for_id(id, TableAccess::Read).await? else {
             return Ok(None);
-        }
+        };
         let id_ = id.to_resolved(
             self.tx
                 .table_mapping()
-                .namespace(self.namespace)
+                .namespace(namespace)
                 .number_to_tablet(),
         )?;
         let physical_table_name = self
             .tx
             .table_mapping()
-            .namespace(self.namespace)
+            .namespace(namespace)
             .tablet_name(id_.tablet_id)?;
         if let Some(table_name) = self
             .tx
@@ -130,13 +132,9 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
             .cloned()
         {
             log_virtual_table_get();
-            let result = VirtualTable::new(self.tx)
-                .get(self.namespace, id, version)
-                .await;
+            let result = VirtualTable::new(self.tx).get(namespace, id, version).await;
             if let Ok(Some((document, _))) = &result {
-                let component_path = self
-                    .tx
-                    .must_component_path(ComponentId::from(self.namespace))?;
+                let component_path = self.tx.must_component_path(ComponentId::from(namespace))?;
                 self.tx.reads.record_read_document(
                     component_path,
                     table_name,
@@ -153,12 +151,91 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         }
     }
 
+    /// The namespace holding the table of `id`. IDs for this component's
+    /// tables resolve in its own namespace. Otherwise, if the component's
+    /// parent granted it `access` to the ID's table at instantiation, the ID
+    /// resolves in the parent's namespace. Returns `None` if neither applies.
+    ///
+    /// Grants only apply to document IDs: a child still can't query its
+    /// parent's tables by name.
+    async fn namespace_for_id(
+        &mut self,
+        id: DeveloperDocumentId,
+        access: TableAccess,
+    ) -> anyhow::Result<Option<TableNamespace>> {
+        if self
+            .tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .table_number_exists()(id.table())
+        {
+            return Ok(Some(self.namespace));
+        }
+        let TableNamespace::ByComponent(component_id) = self.namespace else {
+            return Ok(None);
+        };
+        let mut model = BootstrapComponentsModel::new(self.tx);
+        let Some(component) = model
+            .load_component(ComponentId::Child(component_id))
+            .await?
+        else {
+            return Ok(None);
+        };
+        let ComponentType::ChildComponent { parent, grants, .. } =
+            component.into_value().component_type
+        else {
+            return Ok(None);
+        };
+        if grants.is_empty() {
+            return Ok(None);
+        }
+        let parent_is_root = model
+            .root_component()?
+            .is_some_and(|root| root.developer_id() == parent);
+        let parent_namespace = TableNamespace::from(if parent_is_root {
+            ComponentId::Root
+        } else {
+            ComponentId::Child(parent)
+        });
+        let Some(table_name) = self
+            .tx
+            .table_mapping()
+            .namespace(parent_namespace)
+            .name_by_number_if_exists(id.table())
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        match grants.get(&table_name) {
+            Some(grant) if grant.allows(access) => Ok(Some(parent_namespace)),
+            Some(grant) => anyhow::bail!(ErrorMetadata::forbidden(
+                "TableAccessNotGranted",
+                format!(
+                    "This component was only granted {grant} access to its parent's table \
+                     {table_name}"
+                ),
+            )),
+            None => Ok(None),
+        }
+    }
+
     /// Returns an error if the component associated with the current namespace
     /// is unmounted. Should be called in all methods that write to user tables.
     async fn require_active_component(&mut self) -> anyhow::Result<()> {
         match self.namespace {
             TableNamespace::Global => {},
             TableNamespace::ByComponent(developer_id) => {
+                let components_table = self
+                    .tx
+                    .table_mapping()
+                    .namespace(TableNamespace::Global)
+                    .id(&COMPONENTS_TABLE)?
+                    .table_number;
+                // Namespaces keyed by other documents, like a preview
+                // deployment's, don't belong to a component.
+                if developer_id.table() != components_table {
+                    return Ok(());
+                }
                 let component = BootstrapComponentsModel::new(self.tx)
                     .load_component(ComponentId::Child(developer_id))
                     .await?
@@ -179,6 +256,58 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         Ok(())
     }
 
+    /// The version of a document that clients can pass back as the expected
+    /// version of a patch or replace: the timestamp of its last committed
+    /// write. Returns `None` if the document doesn't exist.
+    pub async fn document_version(
+        &mut self,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<Timestamp>> {
+        match self.get_with_ts(id, None).await? {
+            None => Ok(None),
+            Some((_, WriteTimestamp::Committed(ts))) => Ok(Some(ts)),
+            Some((_, WriteTimestamp::Pending)) => anyhow::bail!(ErrorMetadata::bad_request(
+                "DocumentVersionPending",
+                format!(
+                    "Document {id} was written earlier in this transaction, so it doesn't have a \
+                     version yet"
+                ),
+            )),
+        }
+    }
+
+    /// Fail unless the document's last committed write was at
+    /// `expected_version`, so clients can compare-and-swap. Checking reads the
+    /// document, so a concurrent write to it makes the transaction conflict
+    /// and retry rather than overwrite the new version.
+    pub async fn check_document_version(
+        &mut self,
+        id: DeveloperDocumentId,
+        expected_version: Timestamp,
+    ) -> anyhow::Result<()> {
+        let Some((_, write_ts)) = self.get_with_ts(id, None).await? else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "NonexistentDocument",
+                format!("Update on nonexistent document ID {id}"),
+            ));
+        };
+        if write_ts != WriteTimestamp::Committed(expected_version) {
+            let current = match write_ts {
+                WriteTimestamp::Committed(ts) => format!("its current version is {ts}"),
+                WriteTimestamp::Pending => {
+                    "it was already written earlier in this transaction".to_string()
+                },
+            };
+            anyhow::bail!(ErrorMetadata::conflict(
+                "DocumentVersionConflict",
+                format!(
+                    "Document {id} has changed: expected version {expected_version}, but {current}"
+                ),
+            ));
+        }
+        Ok(())
+    }
+
     /// Creates a new document with given value in the specified table.
     #[fastrace::trace]
     #[convex_macro::instrument_future]
@@ -195,6 +324,9 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
             ));
         }
 
+        let value = SchemaModel::new(self.tx, self.namespace)
+            .apply_defaults(&table, value)
+            .await?;
         check_user_size(value.size())?;
         self.tx.retention_validator.fail_if_falling_behind()?;
         let internal_id = self.tx.id_generator.generate_internal();
@@ -242,7 +374,8 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
     }
 
     /// Merges the existing document with the given object. Will overwrite any
-    /// conflicting fields.
+    /// conflicting fields. Fields missing from the result are filled in with
+    /// their schema defaults.
     #[fastrace::trace]
     #[convex_macro::instrument_future]
     pub async fn patch(
@@ -250,7 +383,11 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         id: DeveloperDocumentId,
         value: PatchValue,
     ) -> anyhow::Result<DeveloperDocument> {
-        if self.tx.is_system(self.namespace, id.table())
+        let namespace = self
+            .namespace_for_id(id, TableAccess::Write)
+            .await?
+            .unwrap_or(self.namespace);
+        if self.tx.is_system(namespace, id.table())
             && !(self.tx.identity.is_admin() || self.tx.identity.is_system())
         {
             anyhow::bail!(unauthorized_error("patch"))
@@ -261,14 +398,14 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         let id_ = id.to_resolved(
             self.tx
                 .table_mapping()
-                .namespace(self.namespace)
+                .namespace(namespace)
                 .number_to_tablet(),
         )?;
 
         let new_document = self.tx.patch_inner(id_, value).await?;
 
         // Check the size of the patched document.
-        if !self.tx.is_system(self.namespace, id.table()) {
+        if !self.tx.is_system(namespace, id.table()) {
             check_user_size(new_document.size())?;
         }
 
@@ -284,20 +421,24 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         id: DeveloperDocumentId,
         value: ConvexObject,
     ) -> anyhow::Result<DeveloperDocument> {
-        if self.tx.is_system(self.namespace, id.table())
+        let namespace = self
+            .namespace_for_id(id, TableAccess::Write)
+            .await?
+            .unwrap_or(self.namespace);
+        if self.tx.is_system(namespace, id.table())
             && !(self.tx.identity.is_admin() || self.tx.identity.is_system())
         {
             anyhow::bail!(unauthorized_error("replace"))
         }
         self.require_active_component().await?;
-        if !self.tx.is_system(self.namespace, id.table()) {
+        if !self.tx.is_system(namespace, id.table()) {
             check_user_size(value.size())?;
         }
         self.tx.retention_validator.fail_if_falling_behind()?;
         let id_ = id.to_resolved(
             self.tx
                 .table_mapping()
-                .namespace(self.namespace)
+                .namespace(namespace)
                 .number_to_tablet(),
         )?;
 
@@ -311,7 +452,11 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
     #[fastrace::trace]
     #[convex_macro::instrument_future]
     pub async fn delete(&mut self, id: DeveloperDocumentId) -> anyhow::Result<DeveloperDocument> {
-        if self.tx.is_system(self.namespace, id.table())
+        let namespace = self
+            .namespace_for_id(id, TableAccess::Write)
+            .await?
+            .unwrap_or(self.namespace);
+        if self.tx.is_system(namespace, id.table())
             && !(self.tx.identity.is_admin() || self.tx.identity.is_system())
         {
             anyhow::bail!(unauthorized_error("delete"))
@@ -322,7 +467,7 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         let id_ = id.to_resolved(
             self.tx
                 .table_mapping()
-                .namespace(self.namespace)
+                .namespace(namespace)
                 .number_to_tablet(),
         )?;
         let document = self.tx.delete_inner(id_).await?;
@@ -338,9 +483,7 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
             .tx
             .virtual_system_mapping()
             .is_virtual_table(table_name);
-        let component_path = self
-            .tx
-            .must_component_path(ComponentId::from(self.namespace))?;
+        let component_path = self.tx.usage_component_path(self.namespace);
         self.tx.reads.record_read_document(
             component_path,
             table_name.clone(),
@@ -351,6 +494,17 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
     }
 }
 
+/// Parse a document version as returned by `document_version` and sent back
+/// by clients, which pass it as a string since it doesn't fit in a JavaScript
+/// number.
+pub fn parse_document_version(version: &str) -> anyhow::Result<Timestamp> {
+    let ts: u64 = version.parse().context(ErrorMetadata::bad_request(
+        "InvalidDocumentVersion",
+        format!("Invalid document version {version:?}"),
+    ))?;
+    Timestamp::try_from(ts)
+}
+
 fn start_index_range<RT: Runtime>(
     tx: &mut Transaction<RT>,
     request: IndexRangeRequest,
@@ -427,28 +581,41 @@ pub async fn index_range_batch<RT: Runtime>(
 
     for (batch_key, fetch_result) in fetch_results {
         let virtual_table_version = virtual_table_versions.get(&batch_key).cloned();
-        let result = fetch_result.and_then(|IndexRangeResponse { page, cursor }| {
-            let developer_results = match virtual_table_version {
-                Some(version) => page
-                    .into_iter()
-                    .map(|(key, doc, ts)| {
-                        let doc = VirtualTable::new(tx)
-                            .map_system_doc_to_virtual_doc(doc, version.clone())?;
-                        anyhow::Ok((key, doc, ts))
-                    })
-                    .try_collect()?,
-                None => page
-                    .into_iter()
-                    .map(|(key, doc, ts)| (key, doc.to_developer(), ts))
-                    .collect(),
-            };
-            anyhow::Ok(DeveloperIndexRangeResponse {
-                page: developer_results,
-                cursor,
-            })
-        });
+        let result = match fetch_result {
+            Ok(response) => {
+                developer_index_range_response(tx, response, virtual_table_version).await
+            },
+            Err(e) => Err(e),
+        };
         results.insert(batch_key, result);
     }
     assert_eq!(results.len(), batch_size);
     results
 }
+
+async fn developer_index_range_response<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    IndexRangeResponse { page, cursor }: IndexRangeResponse,
+    virtual_table_version: Option<Option<Version>>,
+) -> anyhow::Result<DeveloperIndexRangeResponse> {
+    let developer_results = match virtual_table_version {
+        Some(version) => {
+            let mut developer_results = Vec::with_capacity(page.len());
+            for (key, doc, ts) in page {
+                let doc = VirtualTable::new(tx)
+                    .map_system_doc_to_virtual_doc(doc, version.clone())
+                    .await?;
+                developer_results.push((key, doc, ts));
+            }
+            developer_results
+        },
+        None => page
+            .into_iter()
+            .map(|(key, doc, ts)| (key, doc.to_developer(), ts))
+            .collect(),
+    };
+    Ok(DeveloperIndexRangeResponse {
+        page: developer_results,
+        cursor,
+    })
+}
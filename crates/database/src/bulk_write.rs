@@ -0,0 +1,238 @@
+use std::fmt;
+
+use common::{
+    knobs::{
+        TRANSACTION_MAX_NUM_USER_WRITES,
+        TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+    },
+    runtime::Runtime,
+    types::TableName,
+    value::ConvexObject,
+};
+use errors::ErrorMetadata;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+    Size,
+    TableNamespace,
+};
+
+use crate::{
+    query::TableFilter,
+    PatchValue,
+    Transaction,
+    UserFacingModel,
+};
+
+/// A single write in a bulk write request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BulkWriteOperation {
+    Insert {
+        table: TableName,
+        value: ConvexObject,
+    },
+    Patch {
+        id: DeveloperDocumentId,
+        value: PatchValue,
+    },
+    Replace {
+        id: DeveloperDocumentId,
+        value: ConvexObject,
+    },
+    Delete {
+        id: DeveloperDocumentId,
+    },
+}
+
+impl BulkWriteOperation {
+    /// Approximate number of bytes this operation contributes to a
+    /// transaction's write size.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Insert { value, .. } | Self::Replace { value, .. } => value.size(),
+            Self::Patch { value, .. } => value.size(),
+            Self::Delete { id } => id.size(),
+        }
+    }
+
+    /// Apply the operation, returning the ID of the inserted document for
+    /// inserts.
+    pub async fn apply<RT: Runtime>(
+        self,
+        tx: &mut Transaction<RT>,
+        namespace: TableNamespace,
+    ) -> anyhow::Result<Option<DeveloperDocumentId>> {
+        let id = match &self {
+            Self::Insert { .. } => None,
+            Self::Patch { id, .. } | Self::Replace { id, .. } | Self::Delete { id } => Some(*id),
+        };
+        if let Some(id) = id {
+            let table_name =
+                tx.resolve_idv6(id, namespace, TableFilter::ExcludePrivateSystemTables)?;
+            anyhow::ensure!(
+                !table_name.is_system(),
+                ErrorMetadata::bad_request(
+                    "BulkWriteSystemTable",
+                    format!("Bulk writes can't modify system table {table_name}"),
+                )
+            );
+        }
+        let mut model = UserFacingModel::new(tx, namespace);
+        match self {
+            Self::Insert { table, value } => Ok(Some(model.insert(table, value).await?)),
+            Self::Patch { id, value } => {
+                model.patch(id, value).await?;
+                Ok(None)
+            },
+            Self::Replace { id, value } => {
+                model.replace(id, value).await?;
+                Ok(None)
+            },
+            Self::Delete { id } => {
+                model.delete(id).await?;
+                Ok(None)
+            },
+        }
+    }
+}
+
+impl TryFrom<JsonValue> for BulkWriteOperation {
+    type Error = anyhow::Error;
+
+    fn try_from(json_value: JsonValue) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(tag = "op", rename_all = "camelCase")]
+        enum BulkWriteOperationJson {
+            Insert { table: String, value: JsonValue },
+            Patch { id: String, value: JsonValue },
+            Replace { id: String, value: JsonValue },
+            Delete { id: String },
+        }
+        let operation = match serde_json::from_value(json_value)? {
+            BulkWriteOperationJson::Insert { table, value } => Self::Insert {
+                table: table.parse()?,
+                value: ConvexValue::try_from(value)?.try_into()?,
+            },
+            BulkWriteOperationJson::Patch { id, value } => Self::Patch {
+                id: DeveloperDocumentId::decode(&id)?,
+                value: PatchValue::try_from(value)?,
+            },
+            BulkWriteOperationJson::Replace { id, value } => Self::Replace {
+                id: DeveloperDocumentId::decode(&id)?,
+                value: ConvexValue::try_from(value)?.try_into()?,
+            },
+            BulkWriteOperationJson::Delete { id } => Self::Delete {
+                id: DeveloperDocumentId::decode(&id)?,
+            },
+        };
+        Ok(operation)
+    }
+}
+
+/// Progress of a bulk write. Each chunk is committed atomically in its own
+/// transaction, so on failure all chunks before the failing one remain
+/// committed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BulkWriteProgress {
+    pub chunks_committed: usize,
+    pub operations_committed: usize,
+    /// One entry per committed operation: the new document's ID for inserts
+    /// and `None` otherwise.
+    pub inserted_ids: Vec<Option<DeveloperDocumentId>>,
+}
+
+/// Attached as context to the error from a bulk write that failed partway
+/// through, so callers can find out what was committed before the failure
+/// with `error.downcast_ref::<BulkWriteFailure>()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BulkWriteFailure {
+    pub progress: BulkWriteProgress,
+    pub num_operations: usize,
+}
+
+impl fmt::Display for BulkWriteFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Bulk write failed after committing {} of {} operations",
+            self.progress.operations_committed, self.num_operations
+        )
+    }
+}
+
+/// Split operations into chunks that each fit comfortably within a single
+/// transaction's user write limits. We only use half of each limit since
+/// writes to indexes and table metadata also count against the transaction.
+pub fn chunk_bulk_write_operations(
+    operations: Vec<BulkWriteOperation>,
+) -> Vec<Vec<BulkWriteOperation>> {
+    let max_operations = *TRANSACTION_MAX_NUM_USER_WRITES / 2;
+    let max_bytes = *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES / 2;
+    let mut chunks = vec![];
+    let mut chunk = vec![];
+    let mut chunk_size = 0;
+    for operation in operations {
+        let size = operation.size();
+        if !chunk.is_empty() && (chunk.len() >= max_operations || chunk_size + size > max_bytes) {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_size = 0;
+        }
+        chunk_size += size;
+        chunk.push(operation);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        assert_obj,
+        knobs::TRANSACTION_MAX_NUM_USER_WRITES,
+    };
+    use serde_json::json;
+
+    use super::{
+        chunk_bulk_write_operations,
+        BulkWriteOperation,
+    };
+
+    #[test]
+    fn test_chunk_bulk_write_operations() -> anyhow::Result<()> {
+        let max_operations = *TRANSACTION_MAX_NUM_USER_WRITES / 2;
+        let operations = (0..max_operations + 1)
+            .map(|_| BulkWriteOperation::Insert {
+                table: "messages".parse().unwrap(),
+                value: assert_obj!("body" => "hello"),
+            })
+            .collect();
+        let chunks = chunk_bulk_write_operations(operations);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), max_operations);
+        assert_eq!(chunks[1].len(), 1);
+        assert!(chunk_bulk_write_operations(vec![]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_bulk_write_operation() -> anyhow::Result<()> {
+        let operation = BulkWriteOperation::try_from(json!({
+            "op": "insert",
+            "table": "messages",
+            "value": { "body": "hello" },
+        }))?;
+        assert_eq!(
+            operation,
+            BulkWriteOperation::Insert {
+                table: "messages".parse()?,
+                value: assert_obj!("body" => "hello"),
+            }
+        );
+        assert!(BulkWriteOperation::try_from(json!({ "op": "upsert" })).is_err());
+        Ok(())
+    }
+}
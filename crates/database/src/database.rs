@@ -4,6 +4,7 @@ use std::{
         BTreeMap,
         BTreeSet,
     },
+    num::NonZeroUsize,
     ops::Bound,
     sync::{
         atomic::{
@@ -48,13 +49,18 @@ use common::{
     },
     document::{
         CreationTime,
+        DeveloperDocument,
         DocumentUpdate,
         InternalId,
         ParsedDocument,
         ResolvedDocument,
     },
     interval::Interval,
-    knobs::DEFAULT_DOCUMENTS_PAGE_SIZE,
+    knobs::{
+        BULK_WRITE_MAX_OPERATIONS,
+        DEFAULT_DOCUMENTS_PAGE_SIZE,
+        RETENTION_BACKLOG_MAX_SCANNED_DOCUMENTS,
+    },
     persistence::{
         new_idle_repeatable_ts,
         ConflictStrategy,
@@ -62,6 +68,7 @@ use common::{
         DocumentStream,
         LatestDocument,
         LatestDocumentStream,
+        NoopRetentionValidator,
         Persistence,
         PersistenceGlobalKey,
         PersistenceReader,
@@ -70,7 +77,15 @@ use common::{
         RetentionValidator,
         TimestampRange,
     },
-    query::Order,
+    pool_stats::{
+        ConnectionPoolSnapshot,
+        PoolWorkload,
+    },
+    query::{
+        Cursor,
+        Order,
+        Query,
+    },
     runtime::{
         RateLimiter,
         Runtime,
@@ -121,6 +136,7 @@ use indexing::{
 };
 use itertools::Itertools;
 use keybroker::Identity;
+use maplit::btreeset;
 use parking_lot::Mutex;
 use search::{
     query::RevisionWithKeys,
@@ -139,6 +155,7 @@ use usage_tracking::{
 };
 use value::{
     id_v6::DeveloperDocumentId,
+    InternalDocumentId,
     Size,
     TableNamespace,
     TableNumber,
@@ -154,6 +171,12 @@ use crate::{
         NUM_RESERVED_LEGACY_TABLE_NUMBERS,
         NUM_RESERVED_SYSTEM_TABLE_NUMBERS,
     },
+    bulk_write::{
+        chunk_bulk_write_operations,
+        BulkWriteFailure,
+        BulkWriteOperation,
+        BulkWriteProgress,
+    },
     committer::{
         Committer,
         CommitterClient,
@@ -163,13 +186,33 @@ use crate::{
         SystemIndex,
         DEFAULT_BOOTSTRAP_TABLE_NUMBERS,
     },
+    document_history::{
+        revisions_from_log,
+        DocumentHistory,
+    },
     metrics::{
         self,
         load_indexes_into_memory_timer,
+        log_bulk_write_chunk_committed,
+        log_bulk_write_failed,
+        log_document_retention_backlog,
         vector::vector_search_with_retries_timer,
         verify_invariants_timer,
     },
-    retention::LeaderRetentionManager,
+    query::{
+        DeveloperQuery,
+        PaginationOptions,
+        TableFilter,
+    },
+    retention::{
+        LeaderRetentionManager,
+        RetentionType,
+    },
+    retention_backlog::{
+        RetentionBacklog,
+        RetentionProgress,
+        TableRetentionBacklog,
+    },
     schema_registry::SchemaRegistry,
     search_index_bootstrap::SearchIndexBootstrapWorker,
     snapshot_manager::{
@@ -1288,6 +1331,18 @@ impl<RT: Runtime> Database<RT> {
         self.reader.version()
     }
 
+    pub fn connection_pool_stats(&self) -> Option<ConnectionPoolSnapshot> {
+        self.reader.connection_pool_stats()
+    }
+
+    pub fn set_connection_pool_limit(
+        &self,
+        workload: PoolWorkload,
+        limit: Option<NonZeroUsize>,
+    ) -> anyhow::Result<()> {
+        self.reader.set_connection_pool_limit(workload, limit)
+    }
+
     pub fn now_ts_for_reads(&self) -> RepeatableTimestamp {
         let snapshot_manager = self.snapshot_manager.lock();
         snapshot_manager.latest_ts()
@@ -1708,6 +1763,195 @@ impl<RT: Runtime> Database<RT> {
         })
     }
 
+    /// Up to `limit` revisions of the document, newest first, starting
+    /// before `before` if set. Walks the document log backwards one revision
+    /// at a time, stopping at the retention window.
+    #[fastrace::trace]
+    pub async fn document_history(
+        &self,
+        identity: Identity,
+        namespace: TableNamespace,
+        id: DeveloperDocumentId,
+        before: Option<Timestamp>,
+        limit: usize,
+    ) -> anyhow::Result<DocumentHistory> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("document_history")
+        );
+        let (upper_bound, table_mapping) = {
+            let tx = self.begin(identity).await?;
+            (tx.begin_timestamp(), tx.table_mapping().clone())
+        };
+        let id: InternalDocumentId = id
+            .to_resolved(table_mapping.namespace(namespace).number_to_tablet())
+            .context(ErrorMetadata::not_found(
+                "TableNotFound",
+                format!("No table found for document {id}"),
+            ))?
+            .into();
+        let repeatable_persistence = RepeatablePersistence::new(
+            self.reader.clone(),
+            upper_bound,
+            self.retention_validator(),
+        );
+        let mut entries = vec![];
+        let mut older = None;
+        let mut next_ts = match before {
+            Some(ts) => ts.min(upper_bound.succ()?),
+            None => upper_bound.succ()?,
+        };
+        // Load one revision past the limit to diff the oldest returned revision
+        // against and to know if there are more.
+        while entries.len() <= limit {
+            let entry = match repeatable_persistence
+                .previous_revisions(btreeset! { (id, next_ts) })
+                .await
+            {
+                Ok(mut revisions) => revisions.remove(&(id, next_ts)),
+                Err(e) if e.is_out_of_retention() => None,
+                Err(e) => return Err(e),
+            };
+            let Some(entry) = entry else {
+                break;
+            };
+            next_ts = entry.ts;
+            if entries.len() == limit {
+                older = Some(entry);
+                break;
+            }
+            entries.push(entry);
+        }
+        let cursor = older.as_ref().and(entries.last().map(|entry| entry.ts));
+        Ok(DocumentHistory {
+            revisions: revisions_from_log(entries, older),
+            cursor,
+        })
+    }
+
+    /// How far index and document retention are behind, with the document
+    /// log entries document retention hasn't processed yet counted per table.
+    pub async fn retention_backlog(&self, identity: Identity) -> anyhow::Result<RetentionBacklog> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("retention_backlog")
+        );
+        let (min_snapshot_ts, min_document_snapshot_ts) = self.retention_manager.snapshot_bounds();
+        let index_checkpoint = LeaderRetentionManager::<RT>::get_checkpoint_not_repeatable(
+            self.reader.as_ref(),
+            RetentionType::Index,
+        )
+        .await?;
+        let document_checkpoint = LeaderRetentionManager::<RT>::get_checkpoint_not_repeatable(
+            self.reader.as_ref(),
+            RetentionType::Document,
+        )
+        .await?;
+        let table_mapping = self.latest_snapshot()?.table_mapping().clone();
+        let prioritized_tables = self.retention_manager.prioritized_tables();
+
+        // (pending revisions, pending tombstones) per table.
+        let mut counts: BTreeMap<TabletId, (u64, u64)> = prioritized_tables
+            .iter()
+            .map(|tablet_id| (*tablet_id, (0, 0)))
+            .collect();
+        let mut complete = true;
+        if document_checkpoint < *min_document_snapshot_ts {
+            let range = TimestampRange::new((
+                Bound::Excluded(document_checkpoint),
+                Bound::Excluded(*min_document_snapshot_ts),
+            ))?;
+            // These revisions are already outside the retention window.
+            let mut stream = self.reader.load_documents(
+                range,
+                Order::Asc,
+                *DEFAULT_DOCUMENTS_PAGE_SIZE,
+                Arc::new(NoopRetentionValidator),
+            );
+            let mut scanned = 0;
+            while let Some(entry) = stream.try_next().await? {
+                if scanned >= *RETENTION_BACKLOG_MAX_SCANNED_DOCUMENTS {
+                    complete = false;
+                    break;
+                }
+                scanned += 1;
+                let (revisions, tombstones) = counts.entry(entry.id.table()).or_default();
+                *revisions += 1;
+                if entry.value.is_none() {
+                    *tombstones += 1;
+                }
+            }
+        }
+        log_document_retention_backlog(
+            counts.values().map(|(revisions, _)| revisions).sum(),
+            counts.values().map(|(_, tombstones)| tombstones).sum(),
+        );
+
+        let mut tables = vec![];
+        for (tablet_id, (pending_revisions, pending_tombstones)) in counts {
+            let (Ok(namespace), Ok(table_name)) = (
+                table_mapping.tablet_namespace(tablet_id),
+                table_mapping.tablet_name(tablet_id),
+            ) else {
+                continue;
+            };
+            tables.push(TableRetentionBacklog {
+                namespace,
+                table_name,
+                pending_revisions,
+                pending_tombstones,
+                prioritized: prioritized_tables.contains(&tablet_id),
+            });
+        }
+        tables.sort_by_key(|table| cmp::Reverse(table.pending_revisions));
+        Ok(RetentionBacklog {
+            index_retention: RetentionProgress {
+                min_snapshot_ts: *min_snapshot_ts,
+                checkpoint: index_checkpoint,
+                lag_secs: min_snapshot_ts.secs_since_f64(index_checkpoint).max(0.0),
+            },
+            document_retention: RetentionProgress {
+                min_snapshot_ts: *min_document_snapshot_ts,
+                checkpoint: document_checkpoint,
+                lag_secs: min_document_snapshot_ts
+                    .secs_since_f64(document_checkpoint)
+                    .max(0.0),
+            },
+            tables,
+            complete,
+        })
+    }
+
+    /// Have document retention delete the tables' expired revisions on its
+    /// next pass, ahead of the rest of its backlog.
+    pub fn prioritize_retention(
+        &self,
+        identity: Identity,
+        namespace: TableNamespace,
+        table_names: Vec<TableName>,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("prioritize_retention")
+        );
+        let table_mapping = self.latest_snapshot()?.table_mapping().namespace(namespace);
+        let tablet_ids = table_names
+            .iter()
+            .map(|table_name| {
+                let tablet_id = table_mapping
+                    .id(table_name)
+                    .context(ErrorMetadata::not_found(
+                        "TableNotFound",
+                        format!("Table {table_name} not found"),
+                    ))?
+                    .tablet_id;
+                anyhow::Ok(tablet_id)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.retention_manager.prioritize_tables(tablet_ids);
+        Ok(())
+    }
+
     #[fastrace::trace]
     pub async fn list_snapshot(
         &self,
@@ -1967,6 +2211,19 @@ impl<RT: Runtime> Database<RT> {
             .is_some()
     }
 
+    pub fn has_search_indexes_bootstrapped(&self) -> bool {
+        let snapshot = self.snapshot_manager.lock().latest_snapshot();
+        !snapshot.text_indexes.is_bootstrapping() && !snapshot.vector_indexes.is_bootstrapping()
+    }
+
+    /// Check that persistence is reachable with a single point read.
+    pub async fn check_persistence(&self) -> anyhow::Result<()> {
+        self.reader
+            .get_persistence_global(PersistenceGlobalKey::RetentionMinSnapshotTimestamp)
+            .await?;
+        Ok(())
+    }
+
     pub async fn get_document_and_index_storage(
         &self,
         identity: Identity,
@@ -2014,6 +2271,129 @@ impl<RT: Runtime> Database<RT> {
             .clone()
     }
 
+    /// Read the next batch of a streaming query in its own transaction at the
+    /// latest timestamp, returning the documents and the cursor to resume
+    /// from.
+    ///
+    /// Each batch is a consistent snapshot, but successive batches are read at
+    /// different timestamps. Documents may be observed at different versions
+    /// across batches, and writes to the range behind the cursor are not
+    /// revisited. A document that exists for the whole stream and whose index
+    /// key doesn't change is returned exactly once.
+    pub async fn query_stream_batch(
+        &self,
+        identity: Identity,
+        namespace: TableNamespace,
+        query: Query,
+        start_cursor: Option<Cursor>,
+        batch_size: usize,
+    ) -> anyhow::Result<(Vec<DeveloperDocument>, Cursor, FunctionUsageStats)> {
+        anyhow::ensure!(batch_size > 0, "Query stream batch size must be positive");
+        let usage = FunctionUsageTracker::new();
+        let mut tx = self.begin_with_usage(identity, usage.clone()).await?;
+        let mut query_stream = DeveloperQuery::new_bounded(
+            &mut tx,
+            namespace,
+            query,
+            PaginationOptions::ManualPagination {
+                start_cursor,
+                maximum_rows_read: None,
+                maximum_bytes_read: None,
+            },
+            None,
+            TableFilter::ExcludePrivateSystemTables,
+        )?;
+        let mut page = Vec::with_capacity(batch_size);
+        while page.len() < batch_size {
+            match query_stream
+                .next(&mut tx, Some(batch_size - page.len()))
+                .await
+            {
+                Ok(Some(document)) => page.push(document),
+                Ok(None) => break,
+                // A heavily filtered query can hit the transaction read limits before
+                // filling the batch. Return what we have and resume from the cursor in
+                // the next batch's transaction.
+                Err(e) if e.is_pagination_limit() && query_stream.cursor().is_some() => break,
+                Err(e) => return Err(e),
+            }
+        }
+        let cursor = query_stream
+            .cursor()
+            .context("Query stream finished a batch without a cursor")?;
+        Ok((page, cursor, usage.gather_user_stats()))
+    }
+
+    /// Apply `operations` in order, splitting them into chunks that each
+    /// commit atomically in their own transaction. `on_progress` is called
+    /// after every committed chunk. If a chunk fails, earlier chunks stay
+    /// committed and the error carries a `BulkWriteFailure` with how far the
+    /// write got.
+    pub async fn bulk_write(
+        &self,
+        identity: Identity,
+        namespace: TableNamespace,
+        operations: Vec<BulkWriteOperation>,
+        usage: FunctionUsageTracker,
+        mut on_progress: impl FnMut(&BulkWriteProgress),
+    ) -> anyhow::Result<BulkWriteProgress> {
+        let num_operations = operations.len();
+        anyhow::ensure!(
+            num_operations <= *BULK_WRITE_MAX_OPERATIONS,
+            ErrorMetadata::bad_request(
+                "TooManyBulkWriteOperations",
+                format!(
+                    "Bulk write has {num_operations} operations, but the limit is {}. Split it \
+                     into smaller bulk writes.",
+                    *BULK_WRITE_MAX_OPERATIONS
+                ),
+            )
+        );
+        let mut progress = BulkWriteProgress::default();
+        for chunk in chunk_bulk_write_operations(operations) {
+            let chunk_len = chunk.len();
+            let result = self
+                .execute_with_occ_retries(identity.clone(), usage.clone(), "bulk_write", |tx| {
+                    let chunk = chunk.clone();
+                    async move {
+                        let mut inserted_ids = Vec::with_capacity(chunk.len());
+                        for operation in chunk {
+                            inserted_ids.push(operation.apply(tx, namespace).await?);
+                        }
+                        Ok(inserted_ids)
+                    }
+                    .boxed()
+                    .into()
+                })
+                .await;
+            match result {
+                Ok((_, inserted_ids, _)) => {
+                    progress.chunks_committed += 1;
+                    progress.operations_committed += chunk_len;
+                    progress.inserted_ids.extend(inserted_ids);
+                    log_bulk_write_chunk_committed(chunk_len);
+                    on_progress(&progress);
+                },
+                Err(e) => {
+                    log_bulk_write_failed();
+                    let failure = BulkWriteFailure {
+                        progress,
+                        num_operations,
+                    };
+                    // The `ErrorMetadata` message is what the caller sees, so include the
+                    // progress there too.
+                    let e = if e.downcast_ref::<ErrorMetadata>().is_some() {
+                        e.wrap_error_message(|msg| format!("{failure}: {msg}"))
+                    } else {
+                        e
+                    };
+                    return Err(e.context(failure));
+                },
+            }
+        }
+        Ok(progress)
+    }
+
     pub async fn vector_search(
         &self,
         _identity: Identity,
@@ -0,0 +1,113 @@
+//! Revision history of a single document, read from the document log. Only
+//! revisions within the retention window are available.
+
+use common::{
+    document::ResolvedDocument,
+    persistence::DocumentLogEntry,
+    types::Timestamp,
+};
+use value::{
+    ConvexObject,
+    ConvexValue,
+    FieldName,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevisionOperation {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// A top-level field whose value differs from the previous revision. `None`
+/// means the field isn't set on that side.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: FieldName,
+    pub old_value: Option<ConvexValue>,
+    pub new_value: Option<ConvexValue>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentRevision {
+    pub ts: Timestamp,
+    pub operation: RevisionOperation,
+    /// The document after this revision, `None` for deletes.
+    pub document: Option<ResolvedDocument>,
+    /// Changes from the previous revision, or `None` if the previous revision
+    /// is out of retention.
+    pub changes: Option<Vec<FieldChange>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentHistory {
+    /// Revisions newest first.
+    pub revisions: Vec<DocumentRevision>,
+    /// Pass as `before` to continue with older revisions, or `None` if there
+    /// are no more revisions within retention.
+    pub cursor: Option<Timestamp>,
+}
+
+/// Builds revisions from log entries ordered newest first. `older` is the
+/// entry preceding the last of `entries`, if it was loaded.
+pub(crate) fn revisions_from_log(
+    entries: Vec<DocumentLogEntry>,
+    older: Option<DocumentLogEntry>,
+) -> Vec<DocumentRevision> {
+    let mut previous: Vec<Option<Option<ResolvedDocument>>> = entries
+        .iter()
+        .skip(1)
+        .map(|entry| Some(entry.value.clone()))
+        .collect();
+    previous.push(match older {
+        Some(older) => Some(older.value),
+        // The log has no earlier revision, so this one created the document.
+        None if entries.last().is_some_and(|entry| entry.prev_ts.is_none()) => Some(None),
+        None => None,
+    });
+    entries
+        .into_iter()
+        .zip(previous)
+        .map(|(entry, previous)| {
+            let operation = match (&entry.value, &previous) {
+                (None, _) => RevisionOperation::Delete,
+                (Some(_), Some(None)) => RevisionOperation::Insert,
+                (Some(_), _) => RevisionOperation::Update,
+            };
+            let changes = previous.map(|previous| {
+                diff_objects(
+                    previous.as_ref().map(|document| &document.value().0),
+                    entry.value.as_ref().map(|document| &document.value().0),
+                )
+            });
+            DocumentRevision {
+                ts: entry.ts,
+                operation,
+                document: entry.value,
+                changes,
+            }
+        })
+        .collect()
+}
+
+fn diff_objects(old: Option<&ConvexObject>, new: Option<&ConvexObject>) -> Vec<FieldChange> {
+    let mut fields: Vec<&FieldName> = old
+        .into_iter()
+        .chain(new)
+        .flat_map(|object| object.keys())
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let old_value = old.and_then(|object| object.get(field)).cloned();
+            let new_value = new.and_then(|object| object.get(field)).cloned();
+            (old_value != new_value).then(|| FieldChange {
+                field: field.clone(),
+                old_value,
+                new_value,
+            })
+        })
+        .collect()
+}
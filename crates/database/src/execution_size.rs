@@ -1,3 +1,13 @@
+use common::knobs::{
+    TRANSACTION_MAX_NUM_SCHEDULED,
+    TRANSACTION_MAX_NUM_USER_WRITES,
+    TRANSACTION_MAX_READ_SET_INTERVALS,
+    TRANSACTION_MAX_READ_SIZE_BYTES,
+    TRANSACTION_MAX_READ_SIZE_ROWS,
+    TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
+    TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+};
+
 use crate::{
     TransactionReadSize,
     TransactionWriteSize,
@@ -10,3 +20,115 @@ pub struct FunctionExecutionSize {
     pub write_size: TransactionWriteSize,
     pub scheduled_size: TransactionWriteSize,
 }
+
+impl FunctionExecutionSize {
+    /// Compare the current execution size against the user-facing transaction
+    /// limits. Exceeding any of these limits fails the function, so callers
+    /// can use the remaining budget to stop work early.
+    pub fn resource_usage(&self) -> TransactionResourceUsage {
+        TransactionResourceUsage {
+            documents_read: ResourceUsage::new(
+                self.read_size.total_document_count,
+                *TRANSACTION_MAX_READ_SIZE_ROWS,
+            ),
+            bytes_read: ResourceUsage::new(
+                self.read_size.total_document_size,
+                *TRANSACTION_MAX_READ_SIZE_BYTES,
+            ),
+            read_set_intervals: ResourceUsage::new(
+                self.num_intervals,
+                *TRANSACTION_MAX_READ_SET_INTERVALS,
+            ),
+            documents_written: ResourceUsage::new(
+                self.write_size.num_writes,
+                *TRANSACTION_MAX_NUM_USER_WRITES,
+            ),
+            bytes_written: ResourceUsage::new(
+                self.write_size.size,
+                *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+            ),
+            functions_scheduled: ResourceUsage::new(
+                self.scheduled_size.num_writes,
+                *TRANSACTION_MAX_NUM_SCHEDULED,
+            ),
+            scheduled_function_argument_bytes: ResourceUsage::new(
+                self.scheduled_size.size,
+                *TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
+            ),
+        }
+    }
+}
+
+/// Snapshot of a transaction's resource usage against its limits.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransactionResourceUsage {
+    pub documents_read: ResourceUsage,
+    pub bytes_read: ResourceUsage,
+    pub read_set_intervals: ResourceUsage,
+    pub documents_written: ResourceUsage,
+    pub bytes_written: ResourceUsage,
+    pub functions_scheduled: ResourceUsage,
+    pub scheduled_function_argument_bytes: ResourceUsage,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ResourceUsage {
+    pub used: usize,
+    pub limit: usize,
+}
+
+impl ResourceUsage {
+    pub fn new(used: usize, limit: usize) -> Self {
+        Self { used, limit }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.limit.saturating_sub(self.used)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::knobs::{
+        TRANSACTION_MAX_NUM_USER_WRITES,
+        TRANSACTION_MAX_READ_SIZE_ROWS,
+    };
+
+    use super::{
+        FunctionExecutionSize,
+        ResourceUsage,
+    };
+    use crate::{
+        TransactionReadSize,
+        TransactionWriteSize,
+    };
+
+    #[test]
+    fn test_resource_usage_remaining() {
+        let execution_size = FunctionExecutionSize {
+            num_intervals: 3,
+            read_size: TransactionReadSize {
+                total_document_size: 1024,
+                total_document_count: 10,
+            },
+            write_size: TransactionWriteSize {
+                num_writes: 2,
+                size: 512,
+            },
+            scheduled_size: TransactionWriteSize::default(),
+        };
+        let usage = execution_size.resource_usage();
+        assert_eq!(usage.documents_read.used, 10);
+        assert_eq!(
+            usage.documents_read.remaining(),
+            *TRANSACTION_MAX_READ_SIZE_ROWS - 10
+        );
+        assert_eq!(
+            usage.documents_written.remaining(),
+            *TRANSACTION_MAX_NUM_USER_WRITES - 2
+        );
+        assert_eq!(usage.functions_scheduled.used, 0);
+        // Usage that has already exceeded the limit reports no remaining budget.
+        assert_eq!(ResourceUsage::new(11, 10).remaining(), 0);
+    }
+}
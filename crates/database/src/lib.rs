@@ -13,8 +13,10 @@
 #![feature(try_find)]
 
 mod bootstrap_model;
+mod bulk_write;
 mod committer;
 mod database;
+pub mod document_history;
 mod execution_size;
 mod index_worker;
 mod index_workers;
@@ -25,7 +27,9 @@ mod preloaded;
 pub mod query;
 pub mod reads;
 mod retention;
+pub mod retention_backlog;
 mod search_index_bootstrap;
+pub mod slow_query_log;
 mod snapshot_manager;
 mod stack_traces;
 pub mod subscription;
@@ -50,8 +54,18 @@ pub mod test_helpers;
 #[cfg(test)]
 pub mod tests;
 pub mod text_index_worker;
+pub use bulk_write::{
+    chunk_bulk_write_operations,
+    BulkWriteFailure,
+    BulkWriteOperation,
+    BulkWriteProgress,
+};
 pub use component_registry::ComponentRegistry;
-pub use execution_size::FunctionExecutionSize;
+pub use execution_size::{
+    FunctionExecutionSize,
+    ResourceUsage,
+    TransactionResourceUsage,
+};
 pub use index_worker::IndexWorker;
 pub use index_workers::{
     fast_forward::FastForwardIndexWorker,
@@ -98,13 +112,18 @@ pub use self::{
                 ComponentDefinitionsTable,
                 COMPONENT_DEFINITIONS_TABLE,
             },
+            virtual_table::COMPONENTS_VIRTUAL_TABLE,
             BootstrapComponentsModel,
+            ComponentExportsLoader,
             ComponentsTable,
             COMPONENTS_BY_PARENT_INDEX,
             COMPONENTS_TABLE,
         },
         defaults,
-        import_facing::ImportFacingModel,
+        import_facing::{
+            ImportFacingModel,
+            UpsertByFieldOutcome,
+        },
         index::{
             IndexModel,
             IndexTable,
@@ -134,7 +153,10 @@ pub use self::{
             NUM_RESERVED_SYSTEM_TABLE_NUMBERS,
             TABLES_INDEX,
         },
-        user_facing::UserFacingModel,
+        user_facing::{
+            parse_document_version,
+            UserFacingModel,
+        },
     },
     database::{
         unauthorized_error,
@@ -451,6 +451,37 @@ pub fn log_document_retention_no_cursor() {
     log_gauge(&DOCUMENT_RETENTION_MISSING_CURSOR_INFO, 1.0)
 }
 
+register_convex_gauge!(
+    DOCUMENT_RETENTION_PENDING_REVISIONS_TOTAL,
+    "Document log entries that document retention hasn't processed yet, as of the last backlog \
+     report"
+);
+register_convex_gauge!(
+    DOCUMENT_RETENTION_PENDING_TOMBSTONES_TOTAL,
+    "Tombstones that document retention hasn't deleted yet, as of the last backlog report"
+);
+pub fn log_document_retention_backlog(pending_revisions: u64, pending_tombstones: u64) {
+    log_gauge(
+        &DOCUMENT_RETENTION_PENDING_REVISIONS_TOTAL,
+        pending_revisions as f64,
+    );
+    log_gauge(
+        &DOCUMENT_RETENTION_PENDING_TOMBSTONES_TOTAL,
+        pending_tombstones as f64,
+    );
+}
+
+register_convex_gauge!(
+    DOCUMENT_RETENTION_PRIORITIZED_TABLES_TOTAL,
+    "Tables waiting for a prioritized document retention pass"
+);
+pub fn log_document_retention_prioritized_tables(num_tables: usize) {
+    log_gauge(
+        &DOCUMENT_RETENTION_PRIORITIZED_TABLES_TOTAL,
+        num_tables as f64,
+    )
+}
+
 register_convex_counter!(
     RETENTION_SCANNED_DOCUMENT_TOTAL,
     "Count of documents scanned by retention",
@@ -980,6 +1011,30 @@ pub fn log_document_skipped() {
     log_counter(&SEARCH_AND_VECTOR_BOOTSTRAP_DOCUMENTS_SKIPPED_TOTAL, 1);
 }
 
+register_convex_counter!(
+    BULK_WRITE_OPERATIONS_COMMITTED_TOTAL,
+    "Number of bulk write operations committed"
+);
+register_convex_counter!(
+    BULK_WRITE_CHUNKS_COMMITTED_TOTAL,
+    "Number of bulk write chunks committed"
+);
+pub fn log_bulk_write_chunk_committed(num_operations: usize) {
+    log_counter(
+        &BULK_WRITE_OPERATIONS_COMMITTED_TOTAL,
+        num_operations as u64,
+    );
+    log_counter(&BULK_WRITE_CHUNKS_COMMITTED_TOTAL, 1);
+}
+
+register_convex_counter!(
+    BULK_WRITE_FAILED_TOTAL,
+    "Number of bulk writes that failed partway through"
+);
+pub fn log_bulk_write_failed() {
+    log_counter(&BULK_WRITE_FAILED_TOTAL, 1);
+}
+
 pub mod search {
 
     use metrics::{
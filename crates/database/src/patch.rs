@@ -5,6 +5,7 @@ use common::{
     value::{
         ConvexObject,
         FieldName,
+        Size,
     },
 };
 use serde_json::Value as JsonValue;
@@ -32,6 +33,15 @@ impl PatchValue {
         }
         original_fields.try_into()
     }
+
+    /// Size of the patch's field names and values. Removed fields only count
+    /// their name.
+    pub fn size(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|(field, value)| field.len() + value.0.as_ref().map_or(0, |v| v.size()))
+            .sum()
+    }
 }
 
 impl From<BTreeMap<FieldName, MaybeValue>> for PatchValue {
@@ -16,6 +16,7 @@ use super::{
     QueryNode,
     QueryStream,
     QueryStreamNext,
+    ScanStats,
 };
 use crate::Transaction;
 
@@ -82,4 +83,8 @@ impl QueryStream for Filter {
     fn printable_index_name(&self) -> &IndexName {
         self.inner.printable_index_name()
     }
+
+    fn scan_stats(&self) -> ScanStats {
+        self.inner.scan_stats()
+    }
 }
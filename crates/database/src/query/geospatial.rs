@@ -0,0 +1,139 @@
+//! Radius and bounding box searches over geospatial indexes.
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::index::{
+        database_index::DeveloperDatabaseIndexConfig,
+        IndexConfig,
+    },
+    document::DeveloperDocument,
+    geospatial::{
+        self,
+        GeoArea,
+        Point,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+        QuerySource,
+    },
+    runtime::Runtime,
+    types::IndexName,
+    version::Version,
+};
+use errors::ErrorMetadata;
+use indexing::index_registry::index_not_found_error;
+use value::{
+    ConvexValue,
+    TableNamespace,
+};
+
+use super::{
+    DeveloperQuery,
+    TableFilter,
+};
+use crate::{
+    IndexModel,
+    Transaction,
+};
+
+pub struct GeospatialSearchResult {
+    pub document: DeveloperDocument,
+    /// Distance from the center of a radius search.
+    pub distance_meters: Option<f64>,
+}
+
+/// Returns up to `limit` documents whose point lies in `area`, closest first
+/// for radius searches. Each covering geohash cell is read as an index range,
+/// so the search is reactive like any other indexed query.
+pub async fn geospatial_search<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    index_name: IndexName,
+    area: GeoArea,
+    limit: usize,
+    version: Option<Version>,
+    table_filter: TableFilter,
+) -> anyhow::Result<Vec<GeospatialSearchResult>> {
+    let metadata = IndexModel::new(tx)
+        .enabled_index_metadata(namespace, &index_name)?
+        .with_context(|| index_not_found_error(&index_name))?;
+    let index_field = match &metadata.config {
+        IndexConfig::Database {
+            developer_config: DeveloperDatabaseIndexConfig { fields },
+            ..
+        } => match &fields[..] {
+            [field] => Some(field.clone()),
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some((index_field, location)) = index_field
+        .and_then(|field| geospatial::location_field(&field).map(|location| (field, location)))
+    else {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "IndexNotAGeospatialIndex",
+            format!("Index {index_name} is not a geospatial index"),
+        ));
+    };
+
+    let mut results = vec![];
+    for cell in area.covering_cells() {
+        let range = if cell.is_empty() {
+            vec![]
+        } else {
+            // Geohashes only use lowercase letters and digits, which all sort
+            // before `~`.
+            vec![
+                IndexRangeExpression::Gte(
+                    index_field.clone(),
+                    ConvexValue::try_from(cell.clone())?,
+                ),
+                IndexRangeExpression::Lt(
+                    index_field.clone(),
+                    ConvexValue::try_from(format!("{cell}~"))?,
+                ),
+            ]
+        };
+        let query = Query {
+            source: QuerySource::IndexRange(IndexRange {
+                index_name: index_name.clone(),
+                range,
+                order: Order::Asc,
+            }),
+            operators: vec![],
+        };
+        let mut stream =
+            DeveloperQuery::new_with_version(tx, namespace, query, version.clone(), table_filter)?;
+        while let Some(document) = stream.next(tx, None).await? {
+            let Some(point) = document
+                .value()
+                .0
+                .get_path(&location)
+                .and_then(Point::from_value)
+            else {
+                continue;
+            };
+            if !area.contains(point) {
+                continue;
+            }
+            let distance_meters = match area {
+                GeoArea::Radius { center, .. } => Some(center.distance_meters(&point)),
+                GeoArea::BoundingBox { .. } => None,
+            };
+            results.push(GeospatialSearchResult {
+                document,
+                distance_meters,
+            });
+        }
+    }
+    results.sort_by(|a, b| {
+        a.distance_meters
+            .unwrap_or_default()
+            .total_cmp(&b.distance_meters.unwrap_or_default())
+    });
+    results.truncate(limit);
+    Ok(results)
+}
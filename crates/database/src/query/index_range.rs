@@ -6,7 +6,6 @@ use std::{
 use async_trait::async_trait;
 use common::{
     bootstrap_model::index::database_index::IndexedFields,
-    components::ComponentId,
     document::DeveloperDocument,
     index::IndexKeyBytes,
     interval::Interval,
@@ -36,6 +35,7 @@ use super::{
     DeveloperIndexRangeResponse,
     QueryStream,
     QueryStreamNext,
+    ScanStats,
     DEFAULT_QUERY_PREFETCH,
     MAX_QUERY_FETCH,
 };
@@ -214,7 +214,7 @@ impl IndexRange {
                 .record_read_document(&v, self.printable_index_name.table())?;
 
             // Database bandwidth for index reads
-            let component_path = tx.must_component_path(ComponentId::from(self.namespace))?;
+            let component_path = tx.usage_component_path(self.namespace);
             tx.usage_tracker.track_database_egress_size(
                 component_path,
                 self.printable_index_name.table().to_string(),
@@ -328,6 +328,13 @@ impl QueryStream for IndexRange {
     fn printable_index_name(&self) -> &IndexName {
         &self.printable_index_name
     }
+
+    fn scan_stats(&self) -> ScanStats {
+        ScanStats {
+            documents: self.rows_read,
+            bytes: self.returned_bytes,
+        }
+    }
 }
 
 impl Drop for IndexRange {
@@ -15,6 +15,7 @@ use super::{
     QueryNode,
     QueryStream,
     QueryStreamNext,
+    ScanStats,
     DEFAULT_QUERY_PREFETCH,
 };
 use crate::Transaction;
@@ -80,4 +81,8 @@ impl QueryStream for Limit {
     fn printable_index_name(&self) -> &IndexName {
         self.inner.printable_index_name()
     }
+
+    fn scan_stats(&self) -> ScanStats {
+        self.inner.scan_stats()
+    }
 }
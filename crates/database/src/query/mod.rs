@@ -2,6 +2,10 @@ use std::{
     collections::BTreeMap,
     marker::PhantomData,
     ops::Deref,
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 use anyhow::Context;
@@ -19,8 +23,10 @@ use common::{
     index::IndexKeyBytes,
     interval::Interval,
     query::{
+        index_fingerprint,
         Cursor,
         CursorPosition,
+        IndexFingerprint,
         Query,
         QueryFingerprint,
         QueryOperator,
@@ -57,16 +63,26 @@ use self::{
 };
 use crate::{
     bootstrap_model::user_facing::index_range_batch,
+    slow_query_log::{
+        QueryExecutionStats,
+        SlowQueryLog,
+        SLOW_QUERY_LOG,
+    },
     transaction::IndexRangeRequest,
     IndexModel,
     Transaction,
 };
 
 mod filter;
+mod geospatial;
 mod index_range;
 mod limit;
 mod search_query;
 
+pub use geospatial::{
+    geospatial_search,
+    GeospatialSearchResult,
+};
 pub use index_range::soft_data_limit;
 
 // Even in the presence of large prefetch hints, we should never fetch too much
@@ -124,6 +140,15 @@ trait QueryStream: Send {
 
     /// For logging. All queries have an index name.
     fn printable_index_name(&self) -> &IndexName;
+
+    /// How much the query has read from its index so far, before filtering.
+    fn scan_stats(&self) -> ScanStats;
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct ScanStats {
+    documents: usize,
+    bytes: usize,
 }
 
 pub struct DeveloperIndexRangeResponse {
@@ -145,7 +170,15 @@ pub enum QueryStreamNext {
 pub struct DeveloperQuery<RT: Runtime> {
     root: QueryNode,
     query_fingerprint: Option<QueryFingerprint>,
+    index_fingerprint: Option<IndexFingerprint>,
     end_cursor: Option<Cursor>,
+    namespace: TableNamespace,
+    /// The query as written, if it should be recorded in the slow query log
+    /// when it's slow.
+    slow_query_log_query: Option<Query>,
+    documents_returned: usize,
+    /// Time spent in `next`, excluding time between calls.
+    execution_time: Duration,
     _marker: PhantomData<RT>,
 }
 
@@ -319,6 +352,10 @@ impl<RT: Runtime> DeveloperQuery<RT> {
             QuerySource::IndexRange(ref index_range) => index_range.index_name.clone(),
             QuerySource::Search(ref search) => search.index_name.clone(),
         };
+        let slow_query_log_query = (SlowQueryLog::is_enabled()
+            && !index_name.table().is_system()
+            && !matches!(query.source, QuerySource::Search(_)))
+        .then(|| query.clone());
         let stable_index_name =
             IndexModel::new(tx).stable_index_name(namespace, &index_name, table_filter)?;
         let indexed_fields = match query.source {
@@ -365,13 +402,16 @@ impl<RT: Runtime> DeveloperQuery<RT> {
         // ```
         // If the user changes their email address, we don't want to continue
         // using the same cursors.
-        let fingerprint = match &pagination_options {
-            PaginationOptions::NoPagination => None,
+        let (fingerprint, index_fingerprint) = match &pagination_options {
+            PaginationOptions::NoPagination => (None, None),
             PaginationOptions::ManualPagination { .. }
             | PaginationOptions::ReactivePagination { .. } => {
                 // Calculating fingerprint is expensive, so only do it if we're
                 // paginating.
-                Some(query.fingerprint(&indexed_fields)?)
+                (
+                    Some(query.fingerprint(&indexed_fields)?),
+                    Some(index_fingerprint(&indexed_fields)),
+                )
             },
         };
         let end_cursor = match &pagination_options {
@@ -384,10 +424,11 @@ impl<RT: Runtime> DeveloperQuery<RT> {
                 end_cursor: Some(end_cursor),
                 ..
             } => {
-                anyhow::ensure!(
-                    Some(&end_cursor.query_fingerprint) == fingerprint.as_ref(),
-                    invalid_cursor()
-                );
+                check_cursor_fingerprints(
+                    end_cursor,
+                    fingerprint.as_ref(),
+                    index_fingerprint.as_ref(),
+                )?;
                 Some(end_cursor.clone())
             },
         };
@@ -400,10 +441,11 @@ impl<RT: Runtime> DeveloperQuery<RT> {
             | PaginationOptions::ReactivePagination { start_cursor, .. } => {
                 let start_cursor_position = match start_cursor {
                     Some(cursor) => {
-                        anyhow::ensure!(
-                            Some(cursor.query_fingerprint) == fingerprint,
-                            invalid_cursor()
-                        );
+                        check_cursor_fingerprints(
+                            &cursor,
+                            fingerprint.as_ref(),
+                            index_fingerprint.as_ref(),
+                        )?;
                         Some(cursor.position)
                     },
                     None => None,
@@ -469,7 +511,12 @@ impl<RT: Runtime> DeveloperQuery<RT> {
         Ok(Self {
             root: cur_node,
             query_fingerprint: fingerprint,
+            index_fingerprint,
             end_cursor,
+            namespace,
+            slow_query_log_query,
+            documents_returned: 0,
+            execution_time: Duration::ZERO,
             _marker: PhantomData,
         })
     }
@@ -489,6 +536,7 @@ impl<RT: Runtime> DeveloperQuery<RT> {
             Some(position) => Some(Cursor {
                 position,
                 query_fingerprint: self.query_fingerprint.clone()?,
+                index_fingerprint: self.index_fingerprint.clone(),
             }),
             None => None,
         }
@@ -499,6 +547,7 @@ impl<RT: Runtime> DeveloperQuery<RT> {
             Some(position) => Some(Cursor {
                 position,
                 query_fingerprint: self.query_fingerprint.clone()?,
+                index_fingerprint: self.index_fingerprint.clone(),
             }),
             None => None,
         }
@@ -534,6 +583,35 @@ impl<RT: Runtime> DeveloperQuery<RT> {
     pub fn printable_index_name(&self) -> &IndexName {
         self.root.printable_index_name()
     }
+
+    fn record_next(
+        &mut self,
+        start: Instant,
+        result: &anyhow::Result<Option<(DeveloperDocument, WriteTimestamp)>>,
+    ) {
+        self.execution_time += start.elapsed();
+        if let Ok(Some(_)) = result {
+            self.documents_returned += 1;
+        }
+    }
+}
+
+impl<RT: Runtime> Drop for DeveloperQuery<RT> {
+    fn drop(&mut self) {
+        if let Some(query) = &self.slow_query_log_query {
+            let scan_stats = self.root.scan_stats();
+            SLOW_QUERY_LOG.record(
+                self.namespace,
+                query,
+                QueryExecutionStats {
+                    documents_scanned: scan_stats.documents,
+                    bytes_scanned: scan_stats.bytes,
+                    documents_returned: self.documents_returned,
+                    execution_time: self.execution_time,
+                },
+            );
+        }
+    }
 }
 
 impl<RT: Runtime> ResolvedQuery<RT> {
@@ -588,6 +666,7 @@ pub async fn query_batch_next_<RT: Runtime>(
     tx: &mut Transaction<RT>,
 ) -> BTreeMap<BatchKey, anyhow::Result<Option<(DeveloperDocument, WriteTimestamp)>>> {
     let batch_size = batch.len();
+    let start = Instant::now();
     // Algorithm overview:
     // Call `next` on every query.
     // Accumulate fetch (IO) requests and perform them all in a batch.
@@ -600,7 +679,9 @@ pub async fn query_batch_next_<RT: Runtime>(
         for (batch_key, (query, prefetch_hint)) in batch {
             match query.root.next(tx, prefetch_hint).await {
                 Err(e) => {
-                    results.insert(batch_key, Err(e));
+                    let result = Err(e);
+                    query.record_next(start, &result);
+                    results.insert(batch_key, result);
                 },
                 Ok(QueryStreamNext::WaitingOn(request)) => {
                     requests.insert(batch_key, request);
@@ -618,7 +699,9 @@ pub async fn query_batch_next_<RT: Runtime>(
                     //     [(Cow::Borrowed("query.table"), Cow::Owned(table_name))]
                     // });
 
-                    results.insert(batch_key, Ok(result));
+                    let result = Ok(result);
+                    query.record_next(start, &result);
+                    results.insert(batch_key, result);
                 },
             }
         }
@@ -637,7 +720,9 @@ pub async fn query_batch_next_<RT: Runtime>(
             };
             match result {
                 Err(e) => {
-                    results.insert(batch_key, Err(e));
+                    let result = Err(e);
+                    query.record_next(start, &result);
+                    results.insert(batch_key, result);
                 },
                 Ok(_) => {
                     next_batch.insert(batch_key, (query, prefetch_hint));
@@ -771,6 +856,15 @@ impl QueryStream for QueryNode {
             QueryNode::Limit(r) => r.printable_index_name(),
         }
     }
+
+    fn scan_stats(&self) -> ScanStats {
+        match self {
+            QueryNode::IndexRange(r) => r.scan_stats(),
+            QueryNode::Search(r) => r.scan_stats(),
+            QueryNode::Filter(r) => r.scan_stats(),
+            QueryNode::Limit(r) => r.scan_stats(),
+        }
+    }
 }
 
 /// Return a system limit for reading too many documents in a query
@@ -789,6 +883,40 @@ fn query_scanned_too_much_data(num_bytes: usize) -> ErrorMetadata {
     )
 }
 
+/// Checks that a cursor was issued by the same query. Index positions don't
+/// depend on the index's id, so cursors stay valid across index rebuilds as
+/// long as the indexed fields are unchanged. If the fields changed, the
+/// cursor's position means nothing in the new index and the client needs to
+/// restart pagination.
+fn check_cursor_fingerprints(
+    cursor: &Cursor,
+    query_fingerprint: Option<&QueryFingerprint>,
+    index_fingerprint: Option<&IndexFingerprint>,
+) -> anyhow::Result<()> {
+    if Some(&cursor.query_fingerprint) == query_fingerprint {
+        return Ok(());
+    }
+    match &cursor.index_fingerprint {
+        Some(cursor_index_fingerprint) if Some(cursor_index_fingerprint) != index_fingerprint => {
+            Err(restart_pagination())
+        },
+        _ => Err(invalid_cursor()),
+    }
+}
+
+pub fn restart_pagination() -> anyhow::Error {
+    let data: anyhow::Result<_> =
+        try { val!({ "isConvexSystemError" => true, "paginationError" => "RestartPagination"}) };
+    let message = "RestartPagination: The index this paginated query reads from was redefined \
+                   after the cursor was created. Restart pagination from the first page.";
+    anyhow::anyhow!("RestartPagination")
+        .context(JsError::convex_error(
+            message.to_string(),
+            data.expect("RestartPagination data should be a valid Value"),
+        ))
+        .context(ErrorMetadata::bad_request("RestartPagination", message))
+}
+
 pub fn invalid_cursor() -> anyhow::Error {
     let data: anyhow::Result<_> =
         try { val!({ "isConvexSystemError" => true, "paginationError" => "InvalidCursor"}) };
@@ -41,6 +41,7 @@ use super::{
     DeveloperIndexRangeResponse,
     QueryStream,
     QueryStreamNext,
+    ScanStats,
 };
 use crate::{
     metrics,
@@ -188,6 +189,11 @@ impl QueryStream for SearchQuery {
     fn printable_index_name(&self) -> &IndexName {
         &self.query.index_name
     }
+
+    fn scan_stats(&self) -> ScanStats {
+        // Search queries aren't recorded in the slow query log.
+        ScanStats::default()
+    }
 }
 
 #[derive(Clone)]
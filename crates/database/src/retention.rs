@@ -6,6 +6,7 @@ use std::{
     collections::{
         hash_map::DefaultHasher,
         BTreeMap,
+        BTreeSet,
     },
     hash::{
         Hash,
@@ -101,7 +102,10 @@ use common::{
 };
 use errors::ErrorMetadata;
 use futures::{
-    future::try_join_all,
+    future::{
+        self,
+        try_join_all,
+    },
     pin_mut,
     StreamExt,
     TryStreamExt,
@@ -124,6 +128,7 @@ use crate::{
         log_document_retention_cursor_age,
         log_document_retention_cursor_lag,
         log_document_retention_no_cursor,
+        log_document_retention_prioritized_tables,
         log_document_retention_scanned_document,
         log_retention_cursor_age,
         log_retention_cursor_lag,
@@ -192,6 +197,9 @@ pub struct LeaderRetentionManager<RT: Runtime> {
     index_table_id: TabletId,
     checkpoint_reader: Reader<Checkpoint>,
     document_checkpoint_reader: Reader<Checkpoint>,
+    /// Tables whose expired document revisions are deleted on document
+    /// retention's next pass, ahead of the rest of the log.
+    prioritized_tables: Arc<Mutex<BTreeSet<TabletId>>>,
     handles: Arc<Mutex<Vec<Box<dyn SpawnHandle>>>>,
 }
 
@@ -203,6 +211,7 @@ impl<RT: Runtime> Clone for LeaderRetentionManager<RT> {
             index_table_id: self.index_table_id,
             checkpoint_reader: self.checkpoint_reader.clone(),
             document_checkpoint_reader: self.document_checkpoint_reader.clone(),
+            prioritized_tables: self.prioritized_tables.clone(),
             handles: self.handles.clone(),
         }
     }
@@ -342,6 +351,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 snapshot_reader.clone(),
             ),
         );
+        let prioritized_tables = Arc::new(Mutex::new(BTreeSet::new()));
         let document_deletion_handle = rt.spawn(
             "document_retention_delete",
             Self::go_delete_documents(
@@ -352,6 +362,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 receive_min_document_snapshot,
                 document_checkpoint_writer,
                 snapshot_reader.clone(),
+                prioritized_tables.clone(),
             ),
         );
         Ok(Self {
@@ -360,6 +371,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
             index_table_id,
             checkpoint_reader,
             document_checkpoint_reader,
+            prioritized_tables,
             handles: Arc::new(Mutex::new(vec![
                 // Order matters because we need to shutdown the threads that have
                 // receivers before the senders
@@ -378,6 +390,28 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         Ok(())
     }
 
+    /// The current (min_snapshot_ts, min_document_snapshot_ts).
+    pub fn snapshot_bounds(&self) -> (RepeatableTimestamp, RepeatableTimestamp) {
+        let bounds = self.bounds_reader.lock();
+        (
+            bounds.min_index_snapshot_ts,
+            bounds.min_document_snapshot_ts,
+        )
+    }
+
+    /// Delete the tables' expired document revisions on document retention's
+    /// next pass instead of waiting for its cursor to reach them.
+    pub fn prioritize_tables(&self, tablet_ids: impl IntoIterator<Item = TabletId>) {
+        let mut prioritized_tables = self.prioritized_tables.lock();
+        prioritized_tables.extend(tablet_ids);
+        log_document_retention_prioritized_tables(prioritized_tables.len());
+    }
+
+    /// Tables waiting for a prioritized document retention pass.
+    pub fn prioritized_tables(&self) -> BTreeSet<TabletId> {
+        self.prioritized_tables.lock().clone()
+    }
+
     /// Returns the timestamp which we would like to use as min_snapshot_ts.
     /// This timestamp is created relative to the `max_repeatable_ts`.
     async fn candidate_min_snapshot_ts(
@@ -753,13 +787,14 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
 
     /// Finds expired documents in the documents log and returns a tuple of the
     /// form (scanned_document_ts, (expired_document_ts,
-    /// internal_document_ts))
+    /// internal_document_ts)). Only scans documents in `tablet_id` if set.
     #[try_stream(ok = (Timestamp, Option<(Timestamp, InternalDocumentId)>), error = anyhow::Error)]
     async fn expired_documents(
         rt: &RT,
         reader: RepeatablePersistence,
         cursor: RepeatableTimestamp,
         min_document_snapshot_ts: RepeatableTimestamp,
+        tablet_id: Option<TabletId>,
     ) {
         tracing::trace!(
             "expired_documents: reading expired documents from {cursor:?} to {:?}",
@@ -772,6 +807,9 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 Order::Asc,
                 Arc::new(NoopRetentionValidator),
             )
+            .try_filter(move |entry| {
+                future::ready(tablet_id.is_none_or(|tablet_id| entry.id.table() == tablet_id))
+            })
             .try_chunks2(*RETENTION_READ_CHUNK)
             .map(move |chunk| async move {
                 let chunk = chunk?.to_vec();
@@ -891,7 +929,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         let reader = RepeatablePersistence::new(reader, snapshot_ts, retention_validator.clone());
 
         tracing::trace!("delete_documents: about to grab chunks");
-        let expired_chunks = Self::expired_documents(rt, reader, cursor, min_snapshot_ts)
+        let expired_chunks = Self::expired_documents(rt, reader, cursor, min_snapshot_ts, None)
             .try_chunks2(*DOCUMENT_RETENTION_DELETE_CHUNK);
         pin_mut!(expired_chunks);
         while let Some(scanned_chunk) = expired_chunks.try_next().await? {
@@ -949,6 +987,52 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
             .map(|timestamp| (timestamp, total_expired_entries))
     }
 
+    /// Deletes the expired revisions of documents in one table between
+    /// `cursor` and `min_snapshot_ts`, without moving the checkpoint. When
+    /// `delete_documents` later reaches them, it finds nothing left to delete.
+    /// Returns the number of rows deleted.
+    async fn delete_table_documents(
+        tablet_id: TabletId,
+        min_snapshot_ts: RepeatableTimestamp,
+        persistence: Arc<dyn Persistence>,
+        rt: &RT,
+        cursor: RepeatableTimestamp,
+        retention_validator: Arc<dyn RetentionValidator>,
+    ) -> anyhow::Result<usize> {
+        if !*RETENTION_DOCUMENT_DELETES_ENABLED || *min_snapshot_ts == Timestamp::MIN {
+            return Ok(0);
+        }
+        let reader =
+            RepeatablePersistence::new(persistence.reader(), min_snapshot_ts, retention_validator);
+        let expired_chunks =
+            Self::expired_documents(rt, reader, cursor, min_snapshot_ts, Some(tablet_id))
+                .try_chunks2(*DOCUMENT_RETENTION_DELETE_CHUNK);
+        pin_mut!(expired_chunks);
+        let mut total_deleted_rows = 0;
+        while let Some(scanned_chunk) = expired_chunks.try_next().await? {
+            let delete_chunk: Vec<_> = scanned_chunk
+                .into_iter()
+                .filter_map(|(ts, expired)| expired.map(|expired| (ts, expired)))
+                .collect();
+            let results = try_join_all(
+                Self::partition_document_chunk(delete_chunk)
+                    .into_iter()
+                    .map(|delete_chunk| {
+                        Self::delete_document_chunk(delete_chunk, persistence.clone(), *cursor)
+                    }),
+            )
+            .await?;
+            total_deleted_rows += results
+                .into_iter()
+                .map(|(_, deleted_rows)| deleted_rows)
+                .sum::<usize>();
+        }
+        tracing::info!(
+            "delete_table_documents: deleted {total_deleted_rows} rows from table {tablet_id}"
+        );
+        Ok(total_deleted_rows)
+    }
+
     /// Partitions IndexEntry into INDEX_RETENTION_DELETE_PARALLEL parts where
     /// each index key only exists in one part.
     fn partition_chunk(
@@ -1197,6 +1281,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         mut min_document_snapshot_rx: Receiver<RepeatableTimestamp>,
         checkpoint_writer: Writer<Checkpoint>,
         snapshot_reader: Reader<SnapshotManager>,
+        prioritized_tables: Arc<Mutex<BTreeSet<TabletId>>>,
     ) {
         // Wait with jitter on startup to avoid thundering herd
         Self::wait_with_jitter(&rt, *DOCUMENT_RETENTION_BATCH_INTERVAL_SECONDS).await;
@@ -1248,6 +1333,21 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 )
                 .await?;
                 tracing::trace!("go_delete_documents: loaded checkpoint: {cursor:?}");
+                let tablet_ids: Vec<_> = prioritized_tables.lock().iter().copied().collect();
+                for tablet_id in tablet_ids {
+                    Self::delete_table_documents(
+                        tablet_id,
+                        min_document_snapshot_ts,
+                        persistence.clone(),
+                        &rt,
+                        cursor,
+                        retention_validator.clone(),
+                    )
+                    .await?;
+                    let mut prioritized_tables = prioritized_tables.lock();
+                    prioritized_tables.remove(&tablet_id);
+                    log_document_retention_prioritized_tables(prioritized_tables.len());
+                }
                 let (new_cursor, scanned_documents) = Self::delete_documents(
                     min_document_snapshot_ts,
                     persistence.clone(),
@@ -1930,6 +2030,7 @@ mod tests {
             reader,
             RepeatableTimestamp::MIN,
             min_snapshot_ts,
+            None,
         );
         let scanned: Vec<_> = scanned_stream.try_collect().await?;
         let expired: Vec<_> = scanned
@@ -1969,6 +2070,52 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_expired_documents_in_table(rt: TestRuntime) -> anyhow::Result<()> {
+        let p = TestPersistence::new();
+        let mut id_generator = TestIdGenerator::new();
+        let table1: TableName = str::parse("table1")?;
+        let table2: TableName = str::parse("table2")?;
+        let id1 = id_generator.user_generate(&table1);
+        let id2 = id_generator.user_generate(&table2);
+
+        let documents = vec![
+            doc(id1, 1, Some(1))?,
+            doc(id2, 1, Some(1))?,
+            doc(id1, 2, None)?,
+            doc(id2, 2, Some(2))?,
+            // min_document_snapshot_ts: 4
+        ];
+        p.write(documents, BTreeSet::new(), ConflictStrategy::Error)
+            .await?;
+
+        let min_snapshot_ts = unchecked_repeatable_ts(Timestamp::must(4));
+        let repeatable_ts =
+            unchecked_repeatable_ts(min_snapshot_ts.add(*DOCUMENT_RETENTION_DELAY)?);
+        let reader =
+            RepeatablePersistence::new(p.reader(), repeatable_ts, Arc::new(NoopRetentionValidator));
+
+        // Only table1's revision and tombstone are expired.
+        let scanned: Vec<_> = LeaderRetentionManager::<TestRuntime>::expired_documents(
+            &rt,
+            reader,
+            RepeatableTimestamp::MIN,
+            min_snapshot_ts,
+            Some(id1.tablet_id),
+        )
+        .try_collect()
+        .await?;
+        let expired: Vec<_> = scanned.into_iter().filter_map(|doc| doc.1).collect();
+        assert_eq!(
+            expired,
+            vec![
+                (Timestamp::must(1), id1.into()),
+                (Timestamp::must(2), id1.into()),
+            ]
+        );
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     async fn test_delete_document_chunk(rt: TestRuntime) -> anyhow::Result<()> {
         env::set_var("DOCUMENT_RETENTION_DELETE_PARALLEL", "4");
@@ -2014,6 +2161,7 @@ mod tests {
             reader.clone(),
             RepeatableTimestamp::MIN,
             min_snapshot_ts,
+            None,
         );
         let scanned: Vec<_> = scanned_stream.try_collect().await?;
         let expired: Vec<_> = scanned
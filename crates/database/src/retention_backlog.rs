@@ -0,0 +1,43 @@
+//! How far retention is behind: how much of the document log it still has to
+//! process, per table, and how far its cursors lag the snapshots they chase.
+
+use common::types::{
+    TableName,
+    Timestamp,
+};
+use value::TableNamespace;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetentionProgress {
+    /// Retention may delete data needed only by snapshots before this.
+    pub min_snapshot_ts: Timestamp,
+    /// Everything at or before this timestamp has been deleted.
+    pub checkpoint: Timestamp,
+    /// Seconds between the checkpoint and `min_snapshot_ts`.
+    pub lag_secs: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableRetentionBacklog {
+    pub namespace: TableNamespace,
+    pub table_name: TableName,
+    /// Expired document log entries that document retention hasn't processed
+    /// yet. Processing one deletes the revision it replaced, if any.
+    pub pending_revisions: u64,
+    /// Expired tombstones among them, which are deleted too.
+    pub pending_tombstones: u64,
+    /// Whether the table is waiting for a prioritized retention pass.
+    pub prioritized: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetentionBacklog {
+    pub index_retention: RetentionProgress,
+    pub document_retention: RetentionProgress,
+    /// Tables with a backlog or waiting for a prioritized pass, largest
+    /// backlog first. Tables that have been deleted aren't listed.
+    pub tables: Vec<TableRetentionBacklog>,
+    /// False if the scan of the backlog stopped early, so the counts are
+    /// lower bounds.
+    pub complete: bool,
+}
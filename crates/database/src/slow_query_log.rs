@@ -0,0 +1,408 @@
+//! In-memory buffer of queries that exceeded the slow query thresholds.
+//!
+//! Queries are recorded wherever they run, including in the function runner,
+//! which builds its own transactions. So unlike most of the database, the
+//! buffer is process-wide rather than owned by a `Database`. The application
+//! periodically drains it into the `_slow_queries` system table.
+
+use std::{
+    collections::VecDeque,
+    sync::LazyLock,
+    time::Duration,
+};
+
+use common::{
+    document::CREATION_TIME_FIELD_PATH,
+    knobs::{
+        SLOW_QUERY_BYTES_SCANNED_THRESHOLD,
+        SLOW_QUERY_DOCUMENTS_SCANNED_THRESHOLD,
+        SLOW_QUERY_LATENCY_THRESHOLD,
+        SLOW_QUERY_LOG_BUFFER_SIZE,
+        SLOW_QUERY_LOG_RETENTION,
+    },
+    query::{
+        Expression,
+        IndexRangeExpression,
+        Order,
+        Query,
+        QueryOperator,
+        QuerySource,
+    },
+    types::IndexName,
+};
+use parking_lot::Mutex;
+use value::{
+    FieldPath,
+    TableNamespace,
+};
+
+pub static SLOW_QUERY_LOG: LazyLock<SlowQueryLog> = LazyLock::new(SlowQueryLog::new);
+
+/// What a query read and how long it spent reading it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct QueryExecutionStats {
+    pub documents_scanned: usize,
+    pub bytes_scanned: usize,
+    pub documents_returned: usize,
+    pub execution_time: Duration,
+}
+
+impl QueryExecutionStats {
+    pub fn exceeds_thresholds(&self) -> bool {
+        self.documents_scanned >= *SLOW_QUERY_DOCUMENTS_SCANNED_THRESHOLD
+            || self.bytes_scanned >= *SLOW_QUERY_BYTES_SCANNED_THRESHOLD
+            || self.execution_time >= *SLOW_QUERY_LATENCY_THRESHOLD
+    }
+}
+
+/// A query that scanned too many documents or bytes, or took too long.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlowQuery {
+    pub namespace: TableNamespace,
+    pub shape: QueryShape,
+    pub stats: QueryExecutionStats,
+}
+
+pub struct SlowQueryLog {
+    queries: Mutex<VecDeque<SlowQuery>>,
+}
+
+impl SlowQueryLog {
+    fn new() -> Self {
+        Self {
+            queries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Whether queries should hold on to what's needed to record them.
+    pub fn is_enabled() -> bool {
+        SLOW_QUERY_LOG_RETENTION.is_some()
+    }
+
+    /// Buffer `query` if its execution exceeded the thresholds. If nothing has
+    /// drained the buffer in a while, the oldest queries are dropped.
+    pub fn record(&self, namespace: TableNamespace, query: &Query, stats: QueryExecutionStats) {
+        if !stats.exceeds_thresholds() {
+            return;
+        }
+        let Some(shape) = QueryShape::new(query) else {
+            return;
+        };
+        let mut queries = self.queries.lock();
+        if queries.len() >= *SLOW_QUERY_LOG_BUFFER_SIZE {
+            queries.pop_front();
+        }
+        queries.push_back(SlowQuery {
+            namespace,
+            shape,
+            stats,
+        });
+    }
+
+    pub fn drain(&self) -> Vec<SlowQuery> {
+        self.queries.lock().drain(..).collect()
+    }
+}
+
+/// The structure of a query with all of its values removed, so executions of
+/// the same query with different arguments can be grouped together.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryShape {
+    pub index_name: IndexName,
+    /// The index range, e.g. `author == ? && _creationTime > ?`. Empty for a
+    /// full table scan.
+    pub range: String,
+    /// The filter applied to the index range, e.g. `channel == ?`.
+    pub filter: Option<String>,
+    pub order: Order,
+    /// Fields of an index which would let the query read only the documents it
+    /// returns, if it doesn't already.
+    pub suggested_index: Option<Vec<String>>,
+}
+
+impl QueryShape {
+    /// Returns `None` for search queries, which are served by search indexes
+    /// and aren't affected by database indexes.
+    pub fn new(query: &Query) -> Option<Self> {
+        let (index_name, range, order): (_, &[IndexRangeExpression], _) = match &query.source {
+            QuerySource::FullTableScan(scan) => (
+                IndexName::by_creation_time(scan.table_name.clone()),
+                &[],
+                scan.order,
+            ),
+            QuerySource::IndexRange(index_range) => (
+                index_range.index_name.clone(),
+                &index_range.range,
+                index_range.order,
+            ),
+            QuerySource::Search(_) => return None,
+        };
+        let filters: Vec<_> = query
+            .operators
+            .iter()
+            .filter_map(|operator| match operator {
+                QueryOperator::Filter(expr) => Some(expr),
+                QueryOperator::Limit(_) => None,
+            })
+            .collect();
+        let filter = match &filters[..] {
+            [] => None,
+            [expr] => Some(expression_shape(expr)),
+            exprs => Some(
+                exprs
+                    .iter()
+                    .map(|expr| format!("({})", expression_shape(expr)))
+                    .collect::<Vec<_>>()
+                    .join(" && "),
+            ),
+        };
+        Some(Self {
+            index_name,
+            range: range
+                .iter()
+                .map(range_expression_shape)
+                .collect::<Vec<_>>()
+                .join(" && "),
+            filter,
+            order,
+            suggested_index: suggest_index(range, &filters),
+        })
+    }
+}
+
+fn field(field_path: &FieldPath) -> String {
+    String::from(field_path.clone())
+}
+
+fn range_expression_shape(expr: &IndexRangeExpression) -> String {
+    let (field_path, op) = match expr {
+        IndexRangeExpression::Eq(field_path, _) => (field_path, "=="),
+        IndexRangeExpression::Gt(field_path, _) => (field_path, ">"),
+        IndexRangeExpression::Gte(field_path, _) => (field_path, ">="),
+        IndexRangeExpression::Lt(field_path, _) => (field_path, "<"),
+        IndexRangeExpression::Lte(field_path, _) => (field_path, "<="),
+    };
+    format!("{} {op} ?", field(field_path))
+}
+
+fn expression_shape(expr: &Expression) -> String {
+    let binary = |l: &Expression, op: &str, r: &Expression| {
+        format!("{} {op} {}", operand_shape(l), operand_shape(r))
+    };
+    match expr {
+        Expression::Eq(l, r) => binary(l, "==", r),
+        Expression::Neq(l, r) => binary(l, "!=", r),
+        Expression::Lt(l, r) => binary(l, "<", r),
+        Expression::Lte(l, r) => binary(l, "<=", r),
+        Expression::Gt(l, r) => binary(l, ">", r),
+        Expression::Gte(l, r) => binary(l, ">=", r),
+        Expression::Add(l, r) => binary(l, "+", r),
+        Expression::Sub(l, r) => binary(l, "-", r),
+        Expression::Mul(l, r) => binary(l, "*", r),
+        Expression::Div(l, r) => binary(l, "/", r),
+        Expression::Mod(l, r) => binary(l, "%", r),
+        Expression::Neg(x) => format!("-{}", operand_shape(x)),
+        Expression::And(exprs) => exprs
+            .iter()
+            .map(operand_shape)
+            .collect::<Vec<_>>()
+            .join(" && "),
+        Expression::Or(exprs) => exprs
+            .iter()
+            .map(operand_shape)
+            .collect::<Vec<_>>()
+            .join(" || "),
+        Expression::Not(x) => format!("!{}", operand_shape(x)),
+        Expression::Field(field_path) => field(field_path),
+        Expression::Literal(_) => "?".to_string(),
+    }
+}
+
+fn operand_shape(expr: &Expression) -> String {
+    match expr {
+        Expression::Field(_) | Expression::Literal(_) => expression_shape(expr),
+        _ => format!("({})", expression_shape(expr)),
+    }
+}
+
+/// A comparison of a field against a value, e.g. `q.eq(q.field("a"), 1)`.
+fn field_comparison(expr: &Expression) -> Option<(&FieldPath, bool)> {
+    let (l, r, is_eq) = match expr {
+        Expression::Eq(l, r) => (l, r, true),
+        Expression::Lt(l, r)
+        | Expression::Lte(l, r)
+        | Expression::Gt(l, r)
+        | Expression::Gte(l, r) => (l, r, false),
+        _ => return None,
+    };
+    match (&**l, &**r) {
+        (Expression::Field(field_path), Expression::Literal(_))
+        | (Expression::Literal(_), Expression::Field(field_path)) => Some((field_path, is_eq)),
+        _ => None,
+    }
+}
+
+/// Suggest an index for the fields the query compares by equality, followed
+/// by at most one field it compares by range, which is the most an index range
+/// can serve. Only the filter's top-level conjunction is considered, since
+/// disjunctions can't be served by a single index range.
+fn suggest_index(range: &[IndexRangeExpression], filters: &[&Expression]) -> Option<Vec<String>> {
+    let mut eq_fields: Vec<&FieldPath> = vec![];
+    let mut range_field: Option<&FieldPath> = None;
+    for expr in range {
+        match expr {
+            IndexRangeExpression::Eq(field_path, _) => eq_fields.push(field_path),
+            IndexRangeExpression::Gt(field_path, _)
+            | IndexRangeExpression::Gte(field_path, _)
+            | IndexRangeExpression::Lt(field_path, _)
+            | IndexRangeExpression::Lte(field_path, _) => {
+                range_field = range_field.or(Some(field_path));
+            },
+        }
+    }
+    let num_index_fields = eq_fields.len() + range_field.iter().count();
+    let conjuncts = filters.iter().flat_map(|expr| match expr {
+        Expression::And(exprs) => exprs.iter().collect(),
+        expr => vec![*expr],
+    });
+    for expr in conjuncts {
+        match field_comparison(expr) {
+            Some((field_path, true)) => {
+                if !eq_fields.contains(&field_path) {
+                    eq_fields.push(field_path);
+                }
+            },
+            Some((field_path, false)) => {
+                range_field = range_field.or(Some(field_path));
+            },
+            _ => {},
+        }
+    }
+    let mut fields = eq_fields;
+    if let Some(range_field) = range_field
+        && !fields.contains(&range_field)
+    {
+        fields.push(range_field);
+    }
+    // Every index implicitly ends with `_creationTime`.
+    fields.retain(|f| **f != *CREATION_TIME_FIELD_PATH);
+    if fields.is_empty() || fields.len() <= num_index_fields {
+        return None;
+    }
+    Some(fields.into_iter().map(field).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        query::{
+            Expression,
+            IndexRange,
+            IndexRangeExpression,
+            Order,
+            Query,
+            QueryOperator,
+        },
+        types::{
+            IndexDescriptor,
+            IndexName,
+        },
+    };
+    use value::{
+        val,
+        FieldPath,
+    };
+
+    use super::QueryShape;
+
+    fn field(name: &str) -> anyhow::Result<FieldPath> {
+        name.parse()
+    }
+
+    fn eq(name: &str) -> anyhow::Result<Expression> {
+        Ok(Expression::Eq(
+            Box::new(Expression::Field(field(name)?)),
+            Box::new(Expression::Literal(val!("value").into())),
+        ))
+    }
+
+    fn gt(name: &str) -> anyhow::Result<Expression> {
+        Ok(Expression::Gt(
+            Box::new(Expression::Field(field(name)?)),
+            Box::new(Expression::Literal(val!(1.).into())),
+        ))
+    }
+
+    #[test]
+    fn test_full_table_scan_with_filter() -> anyhow::Result<()> {
+        let mut query = Query::full_table_scan("messages".parse()?, Order::Desc);
+        query
+            .operators
+            .push(QueryOperator::Filter(Expression::And(vec![
+                eq("channel")?,
+                gt("likes")?,
+                eq("author")?,
+            ])));
+        let shape = QueryShape::new(&query).unwrap();
+        assert_eq!(shape.range, "");
+        assert_eq!(
+            shape.filter.as_deref(),
+            Some("(channel == ?) && (likes > ?) && (author == ?)")
+        );
+        assert_eq!(
+            shape.suggested_index,
+            Some(vec![
+                "channel".to_string(),
+                "author".to_string(),
+                "likes".to_string()
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_range_with_filter() -> anyhow::Result<()> {
+        let index_range = IndexRange {
+            index_name: IndexName::new("messages".parse()?, IndexDescriptor::new("by_channel")?)?,
+            range: vec![IndexRangeExpression::Eq(
+                field("channel")?,
+                val!("general").into(),
+            )],
+            order: Order::Asc,
+        };
+        let mut query = Query::index_range(index_range.clone());
+        query.operators.push(QueryOperator::Filter(eq("author")?));
+        query.operators.push(QueryOperator::Limit(10));
+        let shape = QueryShape::new(&query).unwrap();
+        assert_eq!(shape.range, "channel == ?");
+        assert_eq!(shape.filter.as_deref(), Some("author == ?"));
+        assert_eq!(
+            shape.suggested_index,
+            Some(vec!["channel".to_string(), "author".to_string()])
+        );
+
+        // Without a filter the index already serves the whole query.
+        let shape = QueryShape::new(&Query::index_range(index_range)).unwrap();
+        assert_eq!(shape.filter, None);
+        assert_eq!(shape.suggested_index, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_suggestion_for_disjunction() -> anyhow::Result<()> {
+        let mut query = Query::full_table_scan("messages".parse()?, Order::Asc);
+        query
+            .operators
+            .push(QueryOperator::Filter(Expression::Or(vec![
+                eq("author")?,
+                eq("channel")?,
+            ])));
+        let shape = QueryShape::new(&query).unwrap();
+        assert_eq!(
+            shape.filter.as_deref(),
+            Some("(author == ?) || (channel == ?)")
+        );
+        assert_eq!(shape.suggested_index, None);
+        Ok(())
+    }
+}
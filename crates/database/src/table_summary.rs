@@ -1,6 +1,9 @@
 use std::{
     cmp,
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     fmt,
     sync::Arc,
 };
@@ -370,6 +373,40 @@ impl<RT: Runtime> TableSummaryWriter<RT> {
         self.compute(BootstrapKind::FromScratch).await
     }
 
+    /// Compute a snapshot from the last checkpoint, but rebuild the summaries
+    /// of `tablet_ids` (or of every table if `None`) by rescanning their
+    /// documents instead of trusting what's been accumulated from the log.
+    pub async fn compute_with_rescanned_tables(
+        &self,
+        tablet_ids: Option<BTreeSet<TabletId>>,
+    ) -> anyhow::Result<TableSummarySnapshot> {
+        let ts = self.database.now_ts_for_reads();
+        let (mut snapshot, _) = bootstrap(
+            self.database.runtime().clone(),
+            self.persistence.reader(),
+            self.retention_validator.clone(),
+            ts,
+            BootstrapKind::FromCheckpoint,
+        )
+        .await?;
+        let by_id_indexes = self.database.snapshot(ts)?.index_registry.by_id_indexes();
+        let tablet_ids = tablet_ids.unwrap_or_else(|| by_id_indexes.keys().copied().collect());
+        for tablet_id in tablet_ids {
+            // Tables deleted since the caller looked them up have no summary
+            // left to rebuild.
+            let Some(by_id_index) = by_id_indexes.get(&tablet_id) else {
+                continue;
+            };
+            let revision_stream = self
+                .database
+                .table_iterator(ts, 1000)
+                .stream_documents_in_table(tablet_id, *by_id_index, None);
+            let summary = Self::collect_table_revisions(revision_stream).await?;
+            snapshot.tables.insert(tablet_id, summary);
+        }
+        Ok(snapshot)
+    }
+
     async fn compute(&self, bootstrap_kind: BootstrapKind) -> anyhow::Result<TableSummarySnapshot> {
         let reader = self.persistence.reader();
         let upper_bound = self.database.now_ts_for_reads();
@@ -567,7 +604,10 @@ fn add_revision(
 #[cfg(test)]
 mod tests {
     use std::{
-        collections::BTreeMap,
+        collections::{
+            BTreeMap,
+            BTreeSet,
+        },
         sync::Arc,
     };
 
@@ -737,6 +777,61 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_recompute_rescans_requested_tables(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures {
+            db: database,
+            tp: persistence,
+            ..
+        } = DbFixtures::new(&rt).await?;
+        let writer = TableSummaryWriter::new(
+            rt.clone(),
+            persistence.clone(),
+            database.clone(),
+            database.retention_validator(),
+        );
+
+        let mut tx = database.begin(Identity::system()).await?;
+        let mut tablet_ids = vec![];
+        let mut expected = vec![];
+        for table_name in ["t", "u"] {
+            let table_name: TableName = table_name.parse()?;
+            let inserted = TestFacingModel::new(&mut tx)
+                .insert_and_get(table_name.clone(), assert_obj!("f" => 1))
+                .await?;
+            expected.push(TableSummary::empty().insert(&inserted.value().0));
+            tablet_ids.push(
+                tx.table_mapping()
+                    .namespace(TableNamespace::test_user())
+                    .id(&table_name)?
+                    .tablet_id,
+            );
+        }
+        database.commit(tx).await?;
+
+        // Checkpoint summaries that have drifted from the tables' documents.
+        let mut snapshot = writer.compute_from_last_checkpoint().await?;
+        for tablet_id in &tablet_ids {
+            snapshot.tables.insert(*tablet_id, TableSummary::empty());
+        }
+        write_snapshot(persistence.as_ref(), &snapshot).await?;
+
+        // Only the requested table is rescanned.
+        let snapshot = writer
+            .compute_with_rescanned_tables(Some(BTreeSet::from([tablet_ids[0]])))
+            .await?;
+        assert_eq!(snapshot.tables.get(&tablet_ids[0]), Some(&expected[0]));
+        assert_eq!(
+            snapshot.tables.get(&tablet_ids[1]),
+            Some(&TableSummary::empty())
+        );
+
+        let snapshot = writer.compute_with_rescanned_tables(None).await?;
+        assert_eq!(snapshot.tables.get(&tablet_ids[0]), Some(&expected[0]));
+        assert_eq!(snapshot.tables.get(&tablet_ids[1]), Some(&expected[1]));
+        Ok(())
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig { cases: 32 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, .. ProptestConfig::default() })]
         #[test]
@@ -43,6 +43,9 @@ pub struct DbFixturesArgs {
     pub virtual_system_mapping: VirtualSystemMapping,
     pub bootstrap_search_and_vector_indexes: bool,
     pub bootstrap_table_summaries: bool,
+    /// Defaults to panicking on fatal errors. Pass a non-panicking signal to
+    /// test what happens after the committer hits one.
+    pub shutdown: Option<ShutdownSignal>,
 }
 
 impl Default for DbFixturesArgs {
@@ -54,6 +57,7 @@ impl Default for DbFixturesArgs {
             virtual_system_mapping: Default::default(),
             bootstrap_search_and_vector_indexes: true,
             bootstrap_table_summaries: true,
+            shutdown: None,
         }
     }
 }
@@ -72,6 +76,7 @@ impl<RT: Runtime> DbFixtures<RT> {
             virtual_system_mapping,
             bootstrap_search_and_vector_indexes,
             bootstrap_table_summaries,
+            shutdown,
         }: DbFixturesArgs,
     ) -> anyhow::Result<Self> {
         let tp = tp.unwrap_or_else(|| Arc::new(TestPersistence::new()));
@@ -85,7 +90,7 @@ impl<RT: Runtime> DbFixtures<RT> {
             tp.clone(),
             rt.clone(),
             searcher.clone(),
-            ShutdownSignal::panic(),
+            shutdown.unwrap_or_else(ShutdownSignal::panic),
             virtual_system_mapping,
             Arc::new(test_usage_logger.clone()),
         )
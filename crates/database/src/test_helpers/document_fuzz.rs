@@ -0,0 +1,116 @@
+//! Strategies and checks for fuzzing the document write paths with arbitrary
+//! user documents.
+use std::iter;
+
+use anyhow::Context;
+use common::runtime::Runtime;
+use keybroker::Identity;
+use proptest::prelude::*;
+use serde_json::Value as JsonValue;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    DeveloperDocumentId,
+    ExcludeSetsAndMaps,
+    FieldType,
+    JsonPackedValue,
+    Size,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    Database,
+    PatchValue,
+    UserFacingModel,
+};
+
+#[derive(Clone, Debug)]
+pub enum DocumentWrite {
+    Insert(ConvexObject),
+    Patch(ConvexObject),
+    Replace(ConvexObject),
+}
+
+/// Objects a user can write as a document: no system fields, sets or maps.
+pub fn user_object_strategy() -> impl Strategy<Value = ConvexObject> {
+    any_with::<ConvexObject>(((0..8).into(), FieldType::User, ExcludeSetsAndMaps(true)))
+}
+
+/// An insert followed by a run of patches and replaces of the same document.
+pub fn document_writes_strategy() -> impl Strategy<Value = Vec<DocumentWrite>> {
+    let update = prop_oneof![
+        user_object_strategy().prop_map(DocumentWrite::Patch),
+        user_object_strategy().prop_map(DocumentWrite::Replace),
+    ];
+    (user_object_strategy(), prop::collection::vec(update, 0..8)).prop_map(|(insert, updates)| {
+        iter::once(DocumentWrite::Insert(insert))
+            .chain(updates)
+            .collect()
+    })
+}
+
+/// Applies `writes` to a single document in `table`, one transaction per
+/// write, and checks after each that:
+/// - the transaction's user write size counts exactly the ID and new value,
+/// - the document reads back as expected before and after the commit,
+/// - its value survives the JSON encodings used for import/export and to send
+///   query results to clients.
+pub async fn check_document_writes<RT: Runtime>(
+    db: &Database<RT>,
+    table: &TableName,
+    writes: Vec<DocumentWrite>,
+) -> anyhow::Result<()> {
+    let namespace = TableNamespace::test_user();
+    let mut id: Option<DeveloperDocumentId> = None;
+    let mut expected = ConvexObject::empty();
+    for write in writes {
+        let mut tx = db.begin(Identity::system()).await?;
+        let size_before = tx.writes().as_flat()?.user_size().size;
+        let mut model = UserFacingModel::new(&mut tx, namespace);
+        let written_id = match (write, id) {
+            (DocumentWrite::Insert(object), None) => {
+                expected = object.clone();
+                model.insert(table.clone(), object).await?
+            },
+            (DocumentWrite::Patch(object), Some(id)) => {
+                expected = PatchValue::from(object.clone()).apply(expected)?;
+                model.patch(id, object.into()).await?;
+                id
+            },
+            (DocumentWrite::Replace(object), Some(id)) => {
+                expected = object.clone();
+                model.replace(id, object).await?;
+                id
+            },
+            (write, _) => anyhow::bail!("{write:?} must be the first write and only the first"),
+        };
+        id = Some(written_id);
+
+        let resolved_id =
+            written_id.to_resolved(tx.table_mapping().namespace(namespace).number_to_tablet())?;
+        let document = tx
+            .get(resolved_id)
+            .await?
+            .context("Written document is missing")?;
+        assert_eq!(
+            tx.writes().as_flat()?.user_size().size - size_before,
+            resolved_id.size() + document.value().size(),
+        );
+        assert_eq!(document.value().0.clone().filter_system_fields(), expected);
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let document = tx
+            .get(resolved_id)
+            .await?
+            .context("Committed document is missing")?;
+        let value = document.into_value().0;
+        assert_eq!(value.clone().filter_system_fields(), expected);
+        let json = JsonValue::from(value.clone());
+        assert_eq!(ConvexObject::try_from(json)?, value);
+        let packed = JsonPackedValue::pack(ConvexValue::Object(value.clone()));
+        assert_eq!(packed.unpack(), ConvexValue::Object(value));
+    }
+    Ok(())
+}
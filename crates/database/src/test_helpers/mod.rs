@@ -1,4 +1,5 @@
 pub mod db_fixtures;
+pub mod document_fuzz;
 pub mod index_utils;
 pub mod vector_utils;
 
@@ -0,0 +1,35 @@
+use cmd_util::env::env_config;
+use common::types::TableName;
+use proptest::prelude::*;
+use runtime::testing::TestDriver;
+
+use crate::test_helpers::{
+    document_fuzz::{
+        check_document_writes,
+        document_writes_strategy,
+        DocumentWrite,
+    },
+    DbFixtures,
+};
+
+fn document_writes_test(writes: Vec<DocumentWrite>) {
+    let td = TestDriver::new();
+    let rt = td.rt();
+    let test = async {
+        let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+        let table: TableName = "fuzz".parse()?;
+        check_document_writes(&db, &table, writes).await
+    };
+    td.run_until(test).unwrap();
+}
+
+proptest! {
+    #![proptest_config(
+        ProptestConfig { cases: 32 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, ..ProptestConfig::default() }
+    )]
+
+    #[test]
+    fn proptest_document_writes(writes in document_writes_strategy()) {
+        document_writes_test(writes);
+    }
+}
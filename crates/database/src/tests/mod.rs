@@ -27,6 +27,7 @@ use common::{
         PackedDocument,
         ResolvedDocument,
     },
+    knobs::BULK_WRITE_MAX_OPERATIONS,
     maybe_val,
     object_validator,
     persistence::{
@@ -34,6 +35,9 @@ use common::{
         Persistence,
     },
     query::{
+        index_fingerprint,
+        Cursor,
+        CursorPosition,
         Expression,
         FullTableScan,
         IndexRange,
@@ -55,6 +59,7 @@ use common::{
         TableDefinition,
         MAX_INDEXES_PER_TABLE,
     },
+    testing::assert_contains,
     types::{
         unchecked_repeatable_ts,
         IndexDescriptor,
@@ -99,6 +104,7 @@ use value::{
 };
 
 use crate::{
+    chunk_bulk_write_operations,
     index_worker::{
         IndexSelector,
         IndexWriter,
@@ -119,6 +125,8 @@ use crate::{
         DbFixturesArgs,
     },
     write_log::WriteSource,
+    BulkWriteFailure,
+    BulkWriteOperation,
     Database,
     DatabaseSnapshot,
     ImportFacingModel,
@@ -132,6 +140,8 @@ use crate::{
     UserFacingModel,
 };
 
+mod document_fuzz;
+mod persistence_faults;
 mod randomized_search_tests;
 mod streaming_export_tests;
 mod usage_tracking;
@@ -280,6 +290,10 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
             search_indexes: BTreeMap::new(),
             vector_indexes: BTreeMap::new(),
             document_type: None,
+            triggers: vec![],
+            on_commit: vec![],
+            cascade_delete_files: false,
+            file_text: None,
         },
     );
     let schema = DatabaseSchema {
@@ -336,6 +350,10 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
             search_indexes: BTreeMap::new(),
             vector_indexes: BTreeMap::new(),
             document_type: None,
+            triggers: vec![],
+            on_commit: vec![],
+            cascade_delete_files: false,
+            file_text: None,
         },
     );
     let schema = DatabaseSchema {
@@ -395,6 +413,37 @@ fn get_pending_index_fields(
     Ok(fields.clone())
 }
 
+#[convex_macro::test_runtime]
+async fn test_check_document_version(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let mut tx = database.begin(Identity::system()).await?;
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert("key".parse()?, assert_obj!("count" => 0))
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    let version = model.document_version(id).await?.unwrap();
+    model.check_document_version(id, version).await?;
+    model.patch(id, assert_obj!("count" => 1).into()).await?;
+    // The document has been written in this transaction, so its old version
+    // no longer matches.
+    let err = model.check_document_version(id, version).await.unwrap_err();
+    assert_eq!(err.short_msg(), "DocumentVersionConflict");
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    let err = model.check_document_version(id, version).await.unwrap_err();
+    assert_eq!(err.short_msg(), "DocumentVersionConflict");
+    assert!(err.is_conflict());
+    let new_version = model.document_version(id).await?.unwrap();
+    assert!(new_version > version);
+    model.check_document_version(id, new_version).await?;
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_delete_conflict(rt: TestRuntime) -> anyhow::Result<()> {
     let database = new_test_database(rt).await;
@@ -959,6 +1008,178 @@ async fn test_query_cursor_reuse(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_query_cursor_after_index_redefined(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let namespace = TableNamespace::test_user();
+    let mut tx = database.begin(Identity::system()).await?;
+    let query = Query::full_table_scan("table1".parse()?, Order::Asc);
+    let mut new_query = |start_cursor| {
+        ResolvedQuery::<TestRuntime>::new_bounded(
+            &mut tx,
+            namespace,
+            query.clone(),
+            PaginationOptions::ManualPagination {
+                start_cursor,
+                maximum_rows_read: None,
+                maximum_bytes_read: None,
+            },
+            None,
+            TableFilter::IncludePrivateSystemTables,
+        )
+    };
+
+    // A cursor issued while the index had different fields can't be resumed,
+    // but tells the client to restart pagination.
+    let old_fields = IndexedFields::try_from(vec!["a".parse::<FieldPath>()?])?;
+    let cursor = Cursor {
+        position: CursorPosition::End,
+        query_fingerprint: query.fingerprint(&old_fields)?,
+        index_fingerprint: Some(index_fingerprint(&old_fields)),
+    };
+    let err = new_query(Some(cursor.clone())).err().unwrap();
+    assert!(err.is_bad_request());
+    assert_eq!(err.short_msg(), "RestartPagination");
+
+    // Cursors from before index fingerprints were tracked are just invalid.
+    let legacy_cursor = Cursor {
+        index_fingerprint: None,
+        ..cursor
+    };
+    let err = new_query(Some(legacy_cursor)).err().unwrap();
+    assert_eq!(err.short_msg(), "InvalidCursor");
+
+    // Cursors on an unchanged index keep working.
+    let fields = IndexedFields::creation_time();
+    let cursor = Cursor {
+        position: CursorPosition::End,
+        query_fingerprint: query.fingerprint(&fields)?,
+        index_fingerprint: Some(index_fingerprint(&fields)),
+    };
+    new_query(Some(cursor))?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_stream_batches(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let table_name: TableName = "table".parse()?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    for i in 0..5 {
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), assert_obj!("i" => i))
+            .await?;
+    }
+    database.commit(tx).await?;
+
+    let query = Query::full_table_scan(table_name.clone(), Order::Asc);
+    let mut cursor = None;
+    let mut batch_lens = vec![];
+    loop {
+        let (page, next_cursor, _) = database
+            .query_stream_batch(
+                Identity::system(),
+                TableNamespace::test_user(),
+                query.clone(),
+                cursor,
+                2,
+            )
+            .await?;
+        batch_lens.push(page.len());
+        if matches!(next_cursor.position, CursorPosition::End) {
+            break;
+        }
+        cursor = Some(next_cursor);
+
+        // Each batch runs in a new transaction, so a document inserted ahead of
+        // the cursor between batches is picked up by a later batch.
+        let mut tx = database.begin(Identity::system()).await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), assert_obj!("i" => -1))
+            .await?;
+        database.commit(tx).await?;
+    }
+    assert_eq!(batch_lens.iter().sum::<usize>(), 5 + batch_lens.len() - 1);
+    assert!(batch_lens.iter().all(|len| *len <= 2));
+
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_bulk_write_partial_failure(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let table_name: TableName = "table".parse()?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let deleted_id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!())
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(deleted_id)
+        .await?;
+    database.commit(tx).await?;
+
+    // Each insert is just under 1MB, so they don't all fit in one chunk and the
+    // delete of a missing document fails the second chunk.
+    let big_string = "x".repeat(900_000);
+    let mut operations: Vec<_> = (0..5)
+        .map(|_| BulkWriteOperation::Insert {
+            table: table_name.clone(),
+            value: assert_obj!("data" => big_string.as_str()),
+        })
+        .collect();
+    operations.push(BulkWriteOperation::Delete { id: deleted_id });
+    let chunks = chunk_bulk_write_operations(operations.clone());
+    assert_eq!(chunks.len(), 2);
+    let first_chunk_len = chunks[0].len();
+
+    let mut progress_updates = vec![];
+    let err = database
+        .bulk_write(
+            Identity::system(),
+            TableNamespace::test_user(),
+            operations,
+            FunctionUsageTracker::new(),
+            |progress| progress_updates.push(progress.operations_committed),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.is_bad_request());
+    let failure = err.downcast_ref::<BulkWriteFailure>().unwrap();
+    assert_eq!(failure.num_operations, 6);
+    assert_eq!(failure.progress.chunks_committed, 1);
+    assert_eq!(failure.progress.operations_committed, first_chunk_len);
+    assert_eq!(progress_updates, vec![first_chunk_len]);
+    assert_contains(
+        &err,
+        &format!("Bulk write failed after committing {first_chunk_len} of 6 operations"),
+    );
+
+    // Only the first chunk's inserts were committed.
+    let query = Query::full_table_scan(table_name.clone(), Order::Asc);
+    let documents = run_query(database.clone(), TableNamespace::test_user(), query).await?;
+    assert_eq!(documents.len(), first_chunk_len);
+
+    // Bulk writes over the limit are rejected before anything is committed.
+    let too_many = (0..*BULK_WRITE_MAX_OPERATIONS + 1)
+        .map(|_| BulkWriteOperation::Delete { id: deleted_id })
+        .collect();
+    let err = database
+        .bulk_write(
+            Identity::system(),
+            TableNamespace::test_user(),
+            too_many,
+            FunctionUsageTracker::new(),
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+    assert!(err.is_bad_request());
+    assert!(err.downcast_ref::<BulkWriteFailure>().is_none());
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_too_large_values(rt: TestRuntime) -> anyhow::Result<()> {
     let huge_obj = assert_obj!("huge" => vec![0; 1 << 22]);
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use common::{
+    assert_obj,
+    persistence::Persistence,
+    shutdown::ShutdownSignal,
+    testing::{
+        FaultyPersistence,
+        PersistenceFaults,
+        TestPersistence,
+        WriteFault,
+    },
+    types::TableName,
+};
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use value::TableNamespace;
+
+use crate::{
+    test_helpers::{
+        DbFixtures,
+        DbFixturesArgs,
+    },
+    TestFacingModel,
+};
+
+async fn faulty_db_fixtures(
+    rt: &TestRuntime,
+    tp: Arc<dyn Persistence>,
+    faults: PersistenceFaults,
+) -> anyhow::Result<DbFixtures<TestRuntime>> {
+    DbFixtures::new_with_args(
+        rt,
+        DbFixturesArgs {
+            tp: Some(Arc::new(FaultyPersistence::new(tp, faults))),
+            shutdown: Some(ShutdownSignal::no_op()),
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+#[convex_macro::test_runtime]
+async fn test_write_failing_after_commit_is_durable(rt: TestRuntime) -> anyhow::Result<()> {
+    let tp: Arc<dyn Persistence> = Arc::new(TestPersistence::new());
+    let faults = PersistenceFaults::new();
+    let DbFixtures { db, .. } = faulty_db_fixtures(&rt, tp.clone(), faults.clone()).await?;
+    let table: TableName = "table".parse()?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    let id = TestFacingModel::new(&mut tx)
+        .insert(&table, assert_obj!("x" => 1))
+        .await?;
+    faults.fail_next_write(WriteFault::AfterCommit);
+    assert!(db.commit(tx).await.is_err());
+    assert_eq!(faults.num_injected(), 1);
+
+    // The caller saw an error, but the write reached persistence, so a
+    // database reloaded from it has the document.
+    let DbFixtures { db, .. } = DbFixtures::new_with_args(
+        &rt,
+        DbFixturesArgs {
+            tp: Some(tp),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    assert!(tx.get(id).await?.is_some());
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_write_failing_before_commit_is_lost(rt: TestRuntime) -> anyhow::Result<()> {
+    let tp: Arc<dyn Persistence> = Arc::new(TestPersistence::new());
+    let faults = PersistenceFaults::new();
+    let DbFixtures { db, .. } = faulty_db_fixtures(&rt, tp.clone(), faults.clone()).await?;
+    let table: TableName = "table".parse()?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    TestFacingModel::new(&mut tx)
+        .insert(&table, assert_obj!("x" => 1))
+        .await?;
+    faults.fail_next_write(WriteFault::BeforeCommit);
+    assert!(db.commit(tx).await.is_err());
+
+    let DbFixtures { db, .. } = DbFixtures::new_with_args(
+        &rt,
+        DbFixturesArgs {
+            tp: Some(tp),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    assert!(!tx
+        .table_mapping()
+        .namespace(TableNamespace::test_user())
+        .name_exists(&table));
+    Ok(())
+}
@@ -14,9 +14,11 @@ use value::{
 
 use crate::{
     database::StreamingExportTableFilter,
+    document_history::RevisionOperation,
     test_helpers::DbFixtures,
     DocumentDeltas,
     SnapshotPage,
+    SystemMetadataModel,
     TableModel,
     TestFacingModel,
     UserFacingModel,
@@ -410,3 +412,77 @@ async fn test_snapshot_list(rt: TestRuntime) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_document_history(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let id = TestFacingModel::new(&mut tx)
+        .insert(&"table1".parse()?, assert_obj!("a" => 1, "b" => "x"))
+        .await?;
+    let ts1 = db.commit(tx).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    TestFacingModel::new(&mut tx)
+        .replace(id, assert_obj!("a" => 2, "b" => "x", "c" => true))
+        .await?;
+    let ts2 = db.commit(tx).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    SystemMetadataModel::new_global(&mut tx).delete(id).await?;
+    let ts3 = db.commit(tx).await?;
+
+    let history = db
+        .document_history(
+            Identity::system(),
+            TableNamespace::test_user(),
+            id.into(),
+            None,
+            10,
+        )
+        .await?;
+    assert_eq!(history.cursor, None);
+    let operations: Vec<_> = history
+        .revisions
+        .iter()
+        .map(|revision| (revision.ts, revision.operation))
+        .collect();
+    assert_eq!(
+        operations,
+        vec![
+            (ts3, RevisionOperation::Delete),
+            (ts2, RevisionOperation::Update),
+            (ts1, RevisionOperation::Insert),
+        ]
+    );
+    let changed_fields: Vec<_> = history.revisions[1]
+        .changes
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|change| change.field.to_string())
+        .collect();
+    assert_eq!(changed_fields, vec!["a", "c"]);
+
+    // Page through the history one revision at a time.
+    let page = db
+        .document_history(
+            Identity::system(),
+            TableNamespace::test_user(),
+            id.into(),
+            None,
+            1,
+        )
+        .await?;
+    assert_eq!(page.revisions.len(), 1);
+    assert_eq!(page.cursor, Some(ts3));
+    let page = db
+        .document_history(
+            Identity::system(),
+            TableNamespace::test_user(),
+            id.into(),
+            page.cursor,
+            1,
+        )
+        .await?;
+    assert_eq!(page.revisions[0].ts, ts2);
+    Ok(())
+}
@@ -254,6 +254,12 @@ impl<RT: Runtime> Transaction<RT> {
         &self.virtual_system_mapping
     }
 
+    /// Generates an ID for a document to insert later, e.g. so that documents
+    /// being loaded together can refer to each other.
+    pub fn generate_document_id(&mut self, table_number: TableNumber) -> DeveloperDocumentId {
+        self.id_generator.generate(table_number)
+    }
+
     /// Checks both virtual tables and tables to get the table number to name
     /// mapping. If table is excluded by `table_filter`, returns error as if
     /// the table doesn't exist.
@@ -372,6 +378,18 @@ impl<RT: Runtime> Transaction<RT> {
         &self.writes
     }
 
+    pub fn reads(&self) -> &TransactionReadSet {
+        &self.reads
+    }
+
+    /// Makes this transaction generate the same document IDs and creation
+    /// times as `other` from here on, so two executions against the same
+    /// snapshot can be compared.
+    pub fn copy_generated_values_from(&mut self, other: &Self) {
+        self.id_generator = other.id_generator.clone();
+        self.next_creation_time = other.next_creation_time;
+    }
+
     pub fn into_reads_and_writes(self) -> (TransactionReadSet, NestedWrites<Writes>) {
         (self.reads, self.writes)
     }
@@ -533,6 +551,7 @@ impl<RT: Runtime> Transaction<RT> {
         self.get_inner(id, table_name).await
     }
 
+    #[fastrace::trace]
     #[convex_macro::instrument_future]
     pub(crate) async fn patch_inner(
         &mut self,
@@ -556,8 +575,14 @@ impl<RT: Runtime> Transaction<RT> {
             let patched_value = value
                 .clone()
                 .apply(old_document.value().clone().into_value())?;
+            let patched_value = SchemaModel::new(self, namespace)
+                .apply_defaults(&table_name, patched_value)
+                .await?;
             old_document.replace_value(patched_value)?
         };
+        SchemaModel::new(self, namespace)
+            .enforce_immutable_fields(&old_document, &new_document)
+            .await?;
         SchemaModel::new(self, namespace)
             .enforce(&new_document)
             .await?;
@@ -575,6 +600,7 @@ impl<RT: Runtime> Transaction<RT> {
         tablet_id.is_some_and(|id| self.table_mapping().is_system_tablet(id))
     }
 
+    #[fastrace::trace]
     #[convex_macro::instrument_future]
     pub(crate) async fn replace_inner(
         &mut self,
@@ -596,6 +622,9 @@ impl<RT: Runtime> Transaction<RT> {
         // Replace document.
         let new_document = old_document.replace_value(value)?;
 
+        SchemaModel::new(self, namespace)
+            .enforce_immutable_fields(&old_document, &new_document)
+            .await?;
         SchemaModel::new(self, namespace)
             .enforce(&new_document)
             .await?;
@@ -608,6 +637,7 @@ impl<RT: Runtime> Transaction<RT> {
         Ok(new_document)
     }
 
+    #[fastrace::trace]
     #[convex_macro::instrument_future]
     pub async fn delete_inner(
         &mut self,
@@ -738,6 +768,14 @@ impl<RT: Runtime> Transaction<RT> {
             .must_component_path(component_id, &mut self.reads)
     }
 
+    /// The component that reads from `namespace` are billed to. Tables that
+    /// aren't in a component, like a preview deployment's, are billed to the
+    /// root, like the committer does for writes.
+    pub fn usage_component_path(&mut self, namespace: TableNamespace) -> ComponentPath {
+        self.get_component_path(ComponentId::from(namespace))
+            .unwrap_or(ComponentPath::root())
+    }
+
     /// Get the component path for a document ID. This might be None when table
     /// namespaces for new components are created in `start_push`,  but
     /// components have not yet been created.
@@ -1021,6 +1059,7 @@ impl<RT: Runtime> Transaction<RT> {
         Ok(())
     }
 
+    #[fastrace::trace]
     pub(crate) async fn insert_document(
         &mut self,
         document: ResolvedDocument,
@@ -1034,6 +1073,7 @@ impl<RT: Runtime> Transaction<RT> {
         Ok(document_id)
     }
 
+    #[fastrace::trace]
     pub async fn search(
         &mut self,
         stable_index_name: &StableIndexName,
@@ -1080,6 +1120,7 @@ impl<RT: Runtime> Transaction<RT> {
     //   - It loads the entirety of the index into memory.
     //   - The preloaded snapshot only permits point queries on the index key.
     //
+    #[fastrace::trace]
     pub async fn preload_index_range(
         &mut self,
         namespace: TableNamespace,
@@ -23,6 +23,7 @@ use value::{
 /// 2 bytes.
 ///
 /// The time is pinned on construction, so only use for a single transaction!
+#[derive(Clone)]
 pub struct TransactionIdGenerator {
     rng: ChaCha12Rng,
     day_bytes: [u8; 2],
@@ -7,13 +7,41 @@ use common::{
     types::WriteTimestamp,
     version::Version,
 };
+use errors::ErrorMetadata;
 use value::{
     DeveloperDocumentId,
     ResolvedDocumentId,
+    TableName,
     TableNamespace,
 };
 
-use crate::Transaction;
+use crate::{
+    bootstrap_model::components::virtual_table::{
+        add_component_tree_paths,
+        check_component_tree_access,
+    },
+    Transaction,
+    COMPONENTS_TABLE,
+};
+
+/// Fail unless the transaction's identity may read the virtual table.
+pub fn check_virtual_table_access<RT: Runtime>(
+    tx: &Transaction<RT>,
+    virtual_table_name: &TableName,
+) -> anyhow::Result<()> {
+    let identity = tx.identity();
+    if tx
+        .virtual_system_mapping()
+        .is_admin_only(virtual_table_name)
+        && !(identity.is_admin() || identity.is_system())
+    {
+        anyhow::bail!(ErrorMetadata::forbidden(
+            "VirtualTableAccessDenied",
+            format!("Only admins can read the {virtual_table_name} table"),
+        ));
+    }
+    Ok(())
+}
 
 pub struct VirtualTable<'a, RT: Runtime> {
     tx: &'a mut Transaction<RT>,
@@ -42,33 +70,47 @@ impl<'a, RT: Runtime> VirtualTable<'a, RT> {
             .table_mapping()
             .namespace(namespace)
             .tablet_name(tablet_id)?;
+        if system_table_name == *COMPONENTS_TABLE {
+            check_component_tree_access(self.tx, namespace)?;
+        }
+        if let Some(virtual_table_name) = self
+            .tx
+            .virtual_system_mapping()
+            .system_to_virtual_table(&system_table_name)
+            .cloned()
+        {
+            check_virtual_table_access(self.tx, &virtual_table_name)?;
+        }
 
         // NOTE we intentionally pass `system_table_name` in, which means this
         // `get_inner` doesn't count as bandwidth. It's the caller's
         // responsibility to count bandwidth.
         let result = self.tx.get_inner(id_, system_table_name).await?;
-        let table_mapping = self.tx.table_mapping().clone();
         match result {
             Some((doc, ts)) => {
-                let doc = self.tx.virtual_system_mapping().system_to_virtual_doc(
-                    doc,
-                    &table_mapping,
-                    version,
-                )?;
+                let doc = self.map_system_doc_to_virtual_doc(doc, version).await?;
                 Ok(Some((doc, ts)))
             },
             None => Ok(None),
         }
     }
 
-    pub fn map_system_doc_to_virtual_doc(
+    pub async fn map_system_doc_to_virtual_doc(
         &mut self,
         doc: ResolvedDocument,
         version: Option<Version>,
     ) -> anyhow::Result<DeveloperDocument> {
         let table_mapping = self.tx.table_mapping().clone();
-        self.tx
-            .virtual_system_mapping()
-            .system_to_virtual_doc(doc, &table_mapping, version)
+        let virtual_doc = self.tx.virtual_system_mapping().system_to_virtual_doc(
+            doc.clone(),
+            &table_mapping,
+            version,
+        )?;
+        // Component paths are derived from other `_components` and
+        // `_component_definitions` documents, which the doc mapper can't read.
+        if table_mapping.tablet_name(doc.id().tablet_id)? == *COMPONENTS_TABLE {
+            return add_component_tree_paths(self.tx, doc, virtual_doc).await;
+        }
+        Ok(virtual_doc)
     }
 }
@@ -15,6 +15,7 @@ use common::{
         TABLE_ID_FIELD_PATH,
     },
     document::{
+        DocumentUpdate,
         DocumentUpdateWithPrevTs,
         ResolvedDocument,
     },
@@ -40,7 +41,10 @@ use common::{
     },
 };
 use errors::ErrorMetadata;
-use imbl::OrdMap;
+use imbl::{
+    ordmap::DiffItem,
+    OrdMap,
+};
 use value::{
     values_to_bytes,
     DeveloperDocumentId,
@@ -428,6 +432,36 @@ impl Writes {
             .map(|(id, _)| *id)
             .collect()
     }
+
+    /// Documents changed since `earlier`, an older copy of this write set,
+    /// with their contents at the time of `earlier` as the old document.
+    pub fn updates_since(&self, earlier: &Writes) -> Vec<DocumentUpdate> {
+        earlier
+            .updates
+            .diff(&self.updates)
+            .filter_map(|item| {
+                let (old_document, update) = match item {
+                    DiffItem::Add(_, update) => (
+                        update.old_document.as_ref().map(|(doc, _)| doc.clone()),
+                        update,
+                    ),
+                    DiffItem::Update {
+                        old: (_, earlier_update),
+                        new: (_, update),
+                    } => (earlier_update.new_document.clone(), update),
+                    DiffItem::Remove(..) => return None,
+                };
+                if old_document.is_none() && update.new_document.is_none() {
+                    return None;
+                }
+                Some(DocumentUpdate {
+                    id: update.id,
+                    old_document,
+                    new_document: update.new_document.clone(),
+                })
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
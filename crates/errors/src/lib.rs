@@ -45,6 +45,7 @@ pub enum ErrorCode {
     Unauthenticated,
     Forbidden,
     NotFound,
+    Conflict,
     ClientDisconnect,
     RateLimited,
 
@@ -91,6 +92,25 @@ impl ErrorMetadata {
         }
     }
 
+    /// The request conflicts with the current state of the resource, e.g. a
+    /// compare-and-set whose expected version is out of date. Maps to 409 in
+    /// HTTP. This is a deterministic user error, like `bad_request`, but lets
+    /// clients tell it apart from invalid input and retry with fresh state.
+    ///
+    /// The short_msg should be a CapitalCamelCased describing the error (eg
+    /// DocumentVersionConflict). The msg should be a descriptive message
+    /// targeted toward the developer.
+    pub fn conflict(
+        short_msg: impl Into<Cow<'static, str>>,
+        msg: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            code: ErrorCode::Conflict,
+            short_msg: short_msg.into(),
+            msg: msg.into(),
+        }
+    }
+
     /// Resource not found. Maps to 404 in HTTP. This is not considered
     /// a deterministic user error. It should typically be used when the
     /// resource can't be currently found, e.g. the backend is not currently
@@ -364,6 +384,10 @@ impl ErrorMetadata {
         self.code == ErrorCode::NotFound
     }
 
+    pub fn is_conflict(&self) -> bool {
+        self.code == ErrorCode::Conflict
+    }
+
     pub fn is_overloaded(&self) -> bool {
         self.code == ErrorCode::Overloaded
     }
@@ -390,6 +414,7 @@ impl ErrorMetadata {
     pub fn is_deterministic_user_error(&self) -> bool {
         match self.code {
             ErrorCode::BadRequest
+            | ErrorCode::Conflict
             | ErrorCode::PaginationLimit
             | ErrorCode::Unauthenticated
             | ErrorCode::Forbidden => true,
@@ -422,6 +447,7 @@ impl ErrorMetadata {
             ErrorCode::ClientDisconnect => None,
             ErrorCode::BadRequest
             | ErrorCode::NotFound
+            | ErrorCode::Conflict
             | ErrorCode::PaginationLimit
             | ErrorCode::Unauthenticated
             | ErrorCode::Forbidden
@@ -447,6 +473,7 @@ impl ErrorMetadata {
     fn metric_server_error_label_value(&self) -> Option<&'static str> {
         match self.code {
             ErrorCode::BadRequest
+            | ErrorCode::Conflict
             | ErrorCode::PaginationLimit
             | ErrorCode::Unauthenticated
             | ErrorCode::Forbidden
@@ -476,6 +503,7 @@ impl ErrorMetadata {
             ErrorCode::Forbidden => Some(&crate::metrics::FORBIDDEN_ERROR_TOTAL),
             ErrorCode::OCC { .. } => Some(&crate::metrics::COMMIT_RACE_TOTAL),
             ErrorCode::NotFound => None,
+            ErrorCode::Conflict => None,
             ErrorCode::PaginationLimit => None,
             ErrorCode::OutOfRetention => None,
             ErrorCode::Overloaded => None,
@@ -500,7 +528,7 @@ impl ErrorMetadata {
             ErrorCode::OperationalInternalServerError => Some(CloseCode::Error),
             // These ones are client errors - so no close code - the client
             // will handle and close the connection instead.
-            ErrorCode::BadRequest | ErrorCode::Unauthenticated => None,
+            ErrorCode::BadRequest | ErrorCode::Conflict | ErrorCode::Unauthenticated => None,
         }?;
         // According to the WebSocket protocol specification (RFC 6455), the reason
         // string (if present) is limited to 123 bytes. This is because the
@@ -525,6 +553,7 @@ impl ErrorCode {
             ErrorCode::Unauthenticated => StatusCode::UNAUTHORIZED,
             ErrorCode::Forbidden => StatusCode::FORBIDDEN,
             ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
             ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
             ErrorCode::OperationalInternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
             ErrorCode::OCC { .. }
@@ -542,6 +571,7 @@ impl ErrorCode {
             ErrorCode::Unauthenticated => tonic::Code::Unauthenticated,
             ErrorCode::Forbidden => tonic::Code::FailedPrecondition,
             ErrorCode::NotFound => tonic::Code::NotFound,
+            ErrorCode::Conflict => tonic::Code::Aborted,
             ErrorCode::ClientDisconnect => tonic::Code::Aborted,
             ErrorCode::Overloaded | ErrorCode::RejectedBeforeExecution | ErrorCode::RateLimited => {
                 tonic::Code::ResourceExhausted
@@ -559,6 +589,7 @@ impl ErrorCode {
             StatusCode::UNAUTHORIZED => Some(ErrorCode::Unauthenticated),
             StatusCode::FORBIDDEN => Some(ErrorCode::Forbidden),
             StatusCode::NOT_FOUND => Some(ErrorCode::NotFound),
+            StatusCode::CONFLICT => Some(ErrorCode::Conflict),
             StatusCode::TOO_MANY_REQUESTS => Some(ErrorCode::RateLimited),
             StatusCode::MISDIRECTED_REQUEST => Some(ErrorCode::MisdirectedRequest),
             // Tries to categorize in one of the above more specific 4xx codes first,
@@ -578,6 +609,7 @@ pub trait ErrorMetadataAnyhowExt {
     fn is_out_of_retention(&self) -> bool;
     fn is_bad_request(&self) -> bool;
     fn is_not_found(&self) -> bool;
+    fn is_conflict(&self) -> bool;
     fn is_overloaded(&self) -> bool;
     fn is_operational_internal_server_error(&self) -> bool;
     fn is_rejected_before_execution(&self) -> bool;
@@ -666,6 +698,14 @@ impl ErrorMetadataAnyhowExt for anyhow::Error {
         false
     }
 
+    /// Returns true if error is tagged as Conflict
+    fn is_conflict(&self) -> bool {
+        if let Some(e) = self.downcast_ref::<ErrorMetadata>() {
+            return e.is_conflict();
+        }
+        false
+    }
+
     /// Returns true if error is tagged as Overloaded
     fn is_overloaded(&self) -> bool {
         if let Some(e) = self.downcast_ref::<ErrorMetadata>() {
@@ -850,6 +890,7 @@ mod proptest {
             any::<ErrorCode>().prop_map(|ec| match ec {
                 ErrorCode::BadRequest => ErrorMetadata::bad_request("bad", "request"),
                 ErrorCode::NotFound => ErrorMetadata::not_found("not", "found"),
+                ErrorCode::Conflict => ErrorMetadata::conflict("con", "flict"),
                 ErrorCode::PaginationLimit => {
                     ErrorMetadata::pagination_limit("pagination", "limit")
                 },
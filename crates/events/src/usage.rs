@@ -36,6 +36,13 @@ pub struct FunctionCallUsageFields {
     /// The duration in milliseconds of the UDF, or 0 if we don't track
     /// execution time for this tag type.
     pub duration_millis: u64,
+    /// The most JavaScript heap the UDF used at once, in bytes. Only set for
+    /// uncached queries and mutations.
+    pub peak_heap_used_bytes: Option<u64>,
+    /// CPU time the UDF spent running JavaScript in microseconds, or 0 if it
+    /// wasn't measured. Unlike `duration_millis`, this excludes time spent
+    /// waiting on syscalls like fetches.
+    pub cpu_time_micros: u64,
     /// Whether this was run in V8 or Node, or "unknown".
     pub environment: String,
     /// True if we think it's a call we should track in usage. Right now
@@ -224,6 +231,8 @@ mod tests {
                 tag: "tag".to_string(),
                 memory_megabytes: 100,
                 duration_millis: 200,
+                peak_heap_used_bytes: None,
+                cpu_time_micros: 150,
                 environment: "Node".to_string(),
                 is_tracked: true,
                 response_sha256: Some("sha256".to_string()),
@@ -246,6 +255,8 @@ mod tests {
             "tag": "tag",
             "memory_megabytes": 100,
             "duration_millis": 200,
+            "peak_heap_used_bytes": null,
+            "cpu_time_micros": 150,
             "environment": "Node",
             "is_tracked": true,
             "response_sha256": "sha256",
@@ -273,6 +284,8 @@ mod tests {
                 tag: "tag".to_string(),
                 memory_megabytes: 100,
                 duration_millis: 200,
+                peak_heap_used_bytes: None,
+                cpu_time_micros: 150,
                 environment: "Node".to_string(),
                 is_tracked: true,
                 response_sha256: Some("sha256".to_string()),
@@ -295,6 +308,8 @@ mod tests {
             "tag": "tag",
             "memory_megabytes": 100,
             "duration_millis": 200,
+            "peak_heap_used_bytes": null,
+            "cpu_time_micros": 150,
             "environment": "Node",
             "is_tracked": true,
             "response_sha256": "sha256",
@@ -22,7 +22,10 @@ use common::{
     },
 };
 use database::Transaction;
-use errors::ErrorMetadata;
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
 use futures::{
     stream::{
         self,
@@ -39,17 +42,33 @@ use headers::{
 use keybroker::{
     Identity,
     KeyBroker,
+    SignedFileUrl,
 };
 use maplit::btreemap;
-use model::file_storage::{
-    types::FileStorageEntry,
-    BatchKey,
-    FileStorageId,
-    FileStorageModel,
+use model::{
+    file_storage::{
+        types::{
+            FileEncryptionMetadata,
+            FileStorageEntry,
+        },
+        BatchKey,
+        FileStorageId,
+        FileStorageModel,
+    },
+    file_url_revocations::FileUrlRevocationsModel,
+    scheduled_jobs::{
+        args::offload_args,
+        types::ScheduledJobArgs,
+    },
+    static_assets::{
+        download_static_asset,
+        types::StaticAsset,
+    },
 };
 use storage::{
     Storage,
     StorageExt,
+    StorageGetStream,
     Upload,
     UploadExt,
 };
@@ -59,6 +78,7 @@ use usage_tracking::{
 };
 use value::{
     id_v6::DeveloperDocumentId,
+    ConvexArray,
     TableNamespace,
 };
 
@@ -69,22 +89,40 @@ use crate::{
         GetFileType,
     },
     FileRangeStream,
+    FileScanner,
     FileStorage,
     FileStream,
+    ScanVerdict,
+    SignedUrlOptions,
+    StoredFile,
     TransactionalFileStorage,
 };
 
 const MAX_CHUNK_SIZE: usize = 32 * 1024;
 
+pub(crate) fn quarantined_error(storage_id: &StorageUuid, reason: &str) -> ErrorMetadata {
+    ErrorMetadata::forbidden(
+        "FileQuarantined",
+        format!("File {storage_id} was quarantined by a malware scan: {reason}"),
+    )
+}
+
 impl<RT: Runtime> TransactionalFileStorage<RT> {
     pub fn new(rt: RT, storage: Arc<dyn Storage>, convex_origin: ConvexOrigin) -> Self {
         Self {
             rt,
             storage,
             convex_origin,
+            scanner: None,
         }
     }
 
+    /// Scan every uploaded file with `scanner` before it's recorded.
+    pub fn with_scanner(mut self, scanner: Arc<dyn FileScanner>) -> Self {
+        self.scanner = Some(scanner);
+        self
+    }
+
     pub fn generate_upload_url(
         &self,
         key_broker: &KeyBroker,
@@ -141,6 +179,65 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
             .collect()
     }
 
+    /// Returns a URL for downloading the file that expires after
+    /// `options.expires_in`, or `None` if the file doesn't exist. Unlike the
+    /// URLs from `get_url`, it doesn't reveal the file's storage ID.
+    pub async fn get_signed_url(
+        &self,
+        tx: &mut Transaction<RT>,
+        key_broker: &KeyBroker,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        options: SignedUrlOptions,
+    ) -> anyhow::Result<Option<String>> {
+        let Some(entry) = self
+            .get_file_entry(tx, component.into(), storage_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let token = key_broker.issue_signed_file_url(SignedFileUrl {
+            component,
+            storage_id: entry.storage_id,
+            expires: self.rt.unix_timestamp() + options.expires_in,
+            filename: options.filename,
+            ip: options.ip,
+            nonce: self.rt.new_uuid_v4().to_string(),
+        });
+        let origin = &self.convex_origin;
+        Ok(Some(format!("{origin}/api/storage/signed/{token}")))
+    }
+
+    /// Revokes a URL returned by `get_signed_url` before it expires.
+    pub async fn revoke_signed_url(
+        &self,
+        tx: &mut Transaction<RT>,
+        key_broker: &KeyBroker,
+        component: ComponentId,
+        url: &str,
+    ) -> anyhow::Result<()> {
+        let token = url
+            .split('?')
+            .next()
+            .and_then(|url| url.rsplit('/').next())
+            .unwrap_or(url);
+        let signed_url = match key_broker.check_signed_file_url(token, self.rt.unix_timestamp()) {
+            Ok(signed_url) => signed_url,
+            // Expired URLs can't be used anyway.
+            Err(e) if e.short_msg() == "SignedUrlExpired" => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if signed_url.component != component {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSignedUrl",
+                "Can't revoke a signed URL for a file in another component",
+            ));
+        }
+        FileUrlRevocationsModel::new(tx)
+            .revoke(signed_url.nonce, signed_url.expires)
+            .await
+    }
+
     pub async fn delete(
         &self,
         tx: &mut Transaction<RT>,
@@ -169,6 +266,27 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
             .context("batch_key missing")?
     }
 
+    pub async fn get_stored_file(
+        &self,
+        tx: &mut Transaction<RT>,
+        namespace: TableNamespace,
+        storage_id: FileStorageId,
+    ) -> anyhow::Result<Option<StoredFile>> {
+        let Some(document) = FileStorageModel::new(tx, namespace)
+            .get_file(storage_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let creation_time = document
+            .creation_time()
+            .context("File storage entry missing creation time")?;
+        Ok(Some(StoredFile {
+            entry: document.into_value(),
+            creation_time,
+        }))
+    }
+
     pub async fn get_file_entry_batch(
         &self,
         tx: &mut Transaction<RT>,
@@ -205,6 +323,7 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
             sha256,
             content_length: result.content_length,
             content_type: result.content_type,
+            encryption: result.encryption,
             stream: result.stream,
         })
     }
@@ -240,7 +359,12 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
             sha256,
             size,
             content_type,
+            encryption,
+            quarantine_reason,
         } = file;
+        if let Some(reason) = quarantine_reason {
+            anyhow::bail!(quarantined_error(&storage_id, &reason));
+        }
 
         let content_type = content_type.as_ref().map(|ct| ct.parse()).transpose()?;
 
@@ -267,6 +391,7 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
             content_length,
             content_range,
             content_type,
+            encryption,
             stream: Self::track_stream_usage(component_path, stream, get_file_type, call_tracker),
         })
     }
@@ -367,17 +492,53 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
             "Wrote file {size} to {storage_key:?}. Total:{elapsed:?} ContentType:{content_type:?}",
         );
 
-        let entry = FileStorageEntry {
+        let mut entry = FileStorageEntry {
             storage_id,
             storage_key,
             sha256: actual_sha256,
             size: size.try_into()?,
             content_type: content_type.map(|ct| ct.to_string()),
+            encryption: None,
+            quarantine_reason: None,
         };
+        if let Some(scanner) = &self.scanner {
+            match self.scan(scanner.as_ref(), &entry).await {
+                Ok(ScanVerdict::Clean) => {},
+                Ok(ScanVerdict::Infected { reason }) => {
+                    tracing::warn!("Quarantining file {}: {reason}", entry.storage_id);
+                    entry.quarantine_reason = Some(reason);
+                },
+                Err(e) => {
+                    // Nothing references the object yet, so don't leave it
+                    // behind when the upload fails.
+                    if let Err(delete_err) = self.storage.delete_object(&entry.storage_key).await {
+                        tracing::warn!("Failed to delete unscanned file: {delete_err:#}");
+                    }
+                    return Err(e.context("Failed to scan uploaded file"));
+                },
+            }
+        }
 
         Ok(entry)
     }
 
+    async fn scan(
+        &self,
+        scanner: &dyn FileScanner,
+        entry: &FileStorageEntry,
+    ) -> anyhow::Result<ScanVerdict> {
+        let contents = self
+            .storage
+            .get(&entry.storage_key)
+            .await?
+            .with_context(|| format!("object {:?} not found", entry.storage_key))?;
+        let timer = metrics::scan_file_timer();
+        let verdict = scanner.scan(entry, contents).await?;
+        metrics::log_file_scan(matches!(verdict, ScanVerdict::Infected { .. }));
+        timer.finish();
+        Ok(verdict)
+    }
+
     /// Stores a file entry generated by upload_file(). The caller is
     /// responsible to track usage. If you are outside of the
     /// isolate environment, it is recommended to use FileStorage::store_file
@@ -397,6 +558,20 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
 
         Ok(virtual_id)
     }
+
+    /// Uploads the arguments of a function being scheduled if they're too
+    /// large to store in the scheduled job.
+    pub async fn offload_scheduled_job_args(
+        &self,
+        args: ConvexArray,
+    ) -> anyhow::Result<ScheduledJobArgs> {
+        offload_args(&self.storage, args).await
+    }
+
+    /// Streams the contents of a static asset for the HTTP actions host.
+    pub async fn get_static_asset(&self, asset: &StaticAsset) -> anyhow::Result<StorageGetStream> {
+        download_static_asset(&self.storage, asset).await
+    }
 }
 
 impl<RT: Runtime> FileStorage<RT> {
@@ -407,13 +582,41 @@ impl<RT: Runtime> FileStorage<RT> {
         content_type: Option<ContentType>,
         file: impl Stream<Item = anyhow::Result<impl Into<Bytes>>> + Send,
         expected_sha256: Option<Sha256Digest>,
+        encryption: Option<FileEncryptionMetadata>,
         usage_tracker: &dyn StorageUsageTracker,
     ) -> anyhow::Result<DeveloperDocumentId> {
-        let entry = self
+        // Reject files we already know won't fit in the component's storage
+        // quota before spending time uploading them.
+        if let Some(ContentLength(length)) = content_length {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            FileStorageModel::new(&mut tx, namespace)
+                .check_storage_quota(length)
+                .await?;
+        }
+        let mut entry = self
             .transactional_file_storage
             .upload_file(content_length, content_type, file, expected_sha256)
             .await?;
-        self.store_entry(namespace, entry, usage_tracker).await
+        entry.encryption = encryption;
+        let storage_key = entry.storage_key.clone();
+        match self.store_entry(namespace, entry, usage_tracker).await {
+            Err(e) if e.short_msg() == "StorageQuotaExceeded" => {
+                // The object was never recorded in `_storage`, so nothing
+                // else will reference it.
+                if let Err(delete_err) = self
+                    .transactional_file_storage
+                    .storage
+                    .delete_object(&storage_key)
+                    .await
+                {
+                    tracing::warn!(
+                        "Failed to delete file rejected by storage quota: {delete_err:#}"
+                    );
+                }
+                Err(e)
+            },
+            result => result,
+        }
     }
 
     /// Record the existence of a file that has already been uploaded to the
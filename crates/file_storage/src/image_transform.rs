@@ -0,0 +1,677 @@
+//! On-the-fly image transforms for files in storage.
+//!
+//! Functions create URLs for a transformed version of an image with
+//! `storage.getImageUrl`. The transform parameters are signed into the URL by
+//! the key broker, so clients can't request arbitrary transforms. The first
+//! request for a URL transforms the image and stores the result in object
+//! storage, and later requests are served from there.
+
+use std::{
+    fmt,
+    io::Cursor,
+    str::FromStr,
+};
+
+use anyhow::Context;
+use common::{
+    components::ComponentId,
+    knobs::{
+        IMAGE_TRANSFORMS_ENABLED,
+        IMAGE_TRANSFORM_MAX_OUTPUT_DIMENSION,
+        IMAGE_TRANSFORM_MAX_SOURCE_DIMENSION,
+        IMAGE_TRANSFORM_MAX_SOURCE_SIZE_BYTES,
+    },
+    runtime::Runtime,
+};
+use database::Transaction;
+use errors::ErrorMetadata;
+use futures::stream;
+use headers::ContentLength;
+use image::{
+    codecs::{
+        avif::AvifEncoder,
+        jpeg::JpegEncoder,
+        png::PngEncoder,
+        webp::WebPEncoder,
+    },
+    imageops::FilterType,
+    DynamicImage,
+    ImageFormat,
+    ImageReader,
+    Limits,
+};
+use keybroker::{
+    Identity,
+    ImageTransformUrl,
+    KeyBroker,
+};
+use model::{
+    file_storage::{
+        types::FileStorageEntry,
+        FileStorageId,
+    },
+    image_transform_cache::{
+        types::ImageTransformCacheEntry,
+        ImageTransformCacheModel,
+    },
+};
+use storage::StorageExt;
+use usage_tracking::StorageUsageTracker;
+
+use crate::{
+    core::quarantined_error,
+    metrics::{
+        image_transform_timer,
+        log_image_transform_cache,
+    },
+    FileStorage,
+    FileStream,
+    TransactionalFileStorage,
+};
+
+const DEFAULT_QUALITY: u8 = 80;
+/// AVIF encoding is slow at the lower speed settings, and images are encoded
+/// while the client waits.
+const AVIF_ENCODER_SPEED: u8 = 8;
+
+/// How to fit the image into the requested width and height when both are
+/// set and the aspect ratio doesn't match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageFit {
+    /// Scale the image to cover the requested size and crop the overflow.
+    #[default]
+    Cover,
+    /// Scale the image to fit within the requested size, preserving its
+    /// aspect ratio.
+    Contain,
+    /// Stretch the image to exactly the requested size.
+    Fill,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageOutputFormat {
+    Webp,
+    Avif,
+    Png,
+    Jpeg,
+}
+
+impl ImageOutputFormat {
+    fn content_type(&self) -> &'static str {
+        match self {
+            Self::Webp => "image/webp",
+            Self::Avif => "image/avif",
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+        }
+    }
+}
+
+/// A rectangle of the source image to keep, applied before resizing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImageTransform {
+    pub crop: Option<CropRect>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fit: ImageFit,
+    /// Defaults to the source image's format, or PNG if we can't encode it.
+    pub format: Option<ImageOutputFormat>,
+    /// Encoder quality from 1 to 100 for JPEG and AVIF output. WebP output is
+    /// always lossless.
+    pub quality: Option<u8>,
+}
+
+pub struct TransformedImage {
+    pub bytes: Vec<u8>,
+    pub content_type: &'static str,
+}
+
+fn invalid_transform(msg: impl Into<String>) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidImageTransform", msg.into())
+}
+
+fn ensure_image_transforms_enabled() -> anyhow::Result<()> {
+    if !*IMAGE_TRANSFORMS_ENABLED {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "ImageTransformsDisabled",
+            "Image transforms aren't enabled on this deployment",
+        ));
+    }
+    Ok(())
+}
+
+impl ImageTransform {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.crop.is_none()
+            && self.width.is_none()
+            && self.height.is_none()
+            && self.format.is_none()
+        {
+            anyhow::bail!(invalid_transform(
+                "An image transform must crop, resize or convert the image"
+            ));
+        }
+        let max = *IMAGE_TRANSFORM_MAX_OUTPUT_DIMENSION;
+        for (name, value) in [("width", self.width), ("height", self.height)] {
+            if let Some(value) = value
+                && !(1..=max).contains(&value)
+            {
+                anyhow::bail!(invalid_transform(format!(
+                    "The {name} must be between 1 and {max} pixels"
+                )));
+            }
+        }
+        if let Some(crop) = self.crop
+            && (crop.width == 0 || crop.height == 0)
+        {
+            anyhow::bail!(invalid_transform("The crop rectangle can't be empty"));
+        }
+        if let Some(quality) = self.quality
+            && !(1..=100).contains(&quality)
+        {
+            anyhow::bail!(invalid_transform("The quality must be between 1 and 100"));
+        }
+        Ok(())
+    }
+
+    /// Decode `source`, apply the transform and encode the result.
+    pub fn apply(&self, source: &[u8]) -> anyhow::Result<TransformedImage> {
+        let unsupported = || {
+            ErrorMetadata::bad_request(
+                "UnsupportedImage",
+                "The file isn't an image in a supported format",
+            )
+        };
+        let mut reader = ImageReader::new(Cursor::new(source)).with_guessed_format()?;
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(*IMAGE_TRANSFORM_MAX_SOURCE_DIMENSION);
+        limits.max_image_height = Some(*IMAGE_TRANSFORM_MAX_SOURCE_DIMENSION);
+        reader.limits(limits);
+        let source_format = reader.format().context(unsupported())?;
+        let mut image = reader.decode().context(unsupported())?;
+
+        if let Some(CropRect {
+            x,
+            y,
+            width,
+            height,
+        }) = self.crop
+        {
+            if x.saturating_add(width) > image.width() || y.saturating_add(height) > image.height()
+            {
+                anyhow::bail!(invalid_transform(format!(
+                    "The crop rectangle is outside the {}x{} image",
+                    image.width(),
+                    image.height()
+                )));
+            }
+            image = image.crop_imm(x, y, width, height);
+        }
+        image = self.resize(image);
+
+        let format = self.format.unwrap_or(match source_format {
+            ImageFormat::Jpeg => ImageOutputFormat::Jpeg,
+            ImageFormat::WebP => ImageOutputFormat::Webp,
+            ImageFormat::Avif => ImageOutputFormat::Avif,
+            _ => ImageOutputFormat::Png,
+        });
+        let quality = self.quality.unwrap_or(DEFAULT_QUALITY);
+        let mut bytes = vec![];
+        match format {
+            ImageOutputFormat::Webp => DynamicImage::ImageRgba8(image.to_rgba8())
+                .write_with_encoder(WebPEncoder::new_lossless(&mut bytes))?,
+            ImageOutputFormat::Avif => {
+                DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(
+                    AvifEncoder::new_with_speed_quality(&mut bytes, AVIF_ENCODER_SPEED, quality),
+                )?
+            },
+            ImageOutputFormat::Png => image.write_with_encoder(PngEncoder::new(&mut bytes))?,
+            // JPEG has no alpha channel.
+            ImageOutputFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, quality))?,
+        }
+        Ok(TransformedImage {
+            bytes,
+            content_type: format.content_type(),
+        })
+    }
+
+    fn resize(&self, image: DynamicImage) -> DynamicImage {
+        let (width, height) = match (self.width, self.height) {
+            (None, None) => return image,
+            (Some(width), Some(height)) => (width, height),
+            // Scale the other dimension to preserve the aspect ratio.
+            (Some(width), None) => (width, scale_dimension(image.height(), width, image.width())),
+            (None, Some(height)) => (
+                scale_dimension(image.width(), height, image.height()),
+                height,
+            ),
+        };
+        match self.fit {
+            ImageFit::Cover => image.resize_to_fill(width, height, FilterType::Lanczos3),
+            ImageFit::Contain => image.resize(width, height, FilterType::Lanczos3),
+            ImageFit::Fill => image.resize_exact(width, height, FilterType::Lanczos3),
+        }
+    }
+}
+
+fn scale_dimension(value: u32, numerator: u32, denominator: u32) -> u32 {
+    ((value as u64 * numerator as u64).div_ceil(denominator.max(1) as u64) as u32)
+        .clamp(1, *IMAGE_TRANSFORM_MAX_OUTPUT_DIMENSION)
+}
+
+/// The canonical form of the transform, which is signed into image URLs and
+/// used as the cache key for the transformed image.
+impl fmt::Display for ImageTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params = vec![];
+        if let Some(CropRect {
+            x,
+            y,
+            width,
+            height,
+        }) = self.crop
+        {
+            params.push(format!("crop={x},{y},{width},{height}"));
+        }
+        if let Some(width) = self.width {
+            params.push(format!("w={width}"));
+        }
+        if let Some(height) = self.height {
+            params.push(format!("h={height}"));
+        }
+        let fit = match self.fit {
+            ImageFit::Cover => "cover",
+            ImageFit::Contain => "contain",
+            ImageFit::Fill => "fill",
+        };
+        params.push(format!("fit={fit}"));
+        if let Some(format) = self.format {
+            let format = match format {
+                ImageOutputFormat::Webp => "webp",
+                ImageOutputFormat::Avif => "avif",
+                ImageOutputFormat::Png => "png",
+                ImageOutputFormat::Jpeg => "jpeg",
+            };
+            params.push(format!("format={format}"));
+        }
+        if let Some(quality) = self.quality {
+            params.push(format!("q={quality}"));
+        }
+        write!(f, "{}", params.join("&"))
+    }
+}
+
+impl FromStr for ImageTransform {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut transform = Self::default();
+        for param in s.split('&').filter(|param| !param.is_empty()) {
+            let (key, value) = param
+                .split_once('=')
+                .with_context(|| invalid_transform(format!("Invalid parameter {param:?}")))?;
+            match key {
+                "crop" => {
+                    let parts = value
+                        .split(',')
+                        .map(|part| part.parse())
+                        .collect::<Result<Vec<u32>, _>>()?;
+                    let [x, y, width, height] = parts[..] else {
+                        anyhow::bail!(invalid_transform(format!("Invalid crop {value:?}")));
+                    };
+                    transform.crop = Some(CropRect {
+                        x,
+                        y,
+                        width,
+                        height,
+                    });
+                },
+                "w" => transform.width = Some(value.parse()?),
+                "h" => transform.height = Some(value.parse()?),
+                "fit" => transform.fit = value.parse()?,
+                "format" => transform.format = Some(value.parse()?),
+                "q" => transform.quality = Some(value.parse()?),
+                _ => anyhow::bail!(invalid_transform(format!("Unknown parameter {key:?}"))),
+            }
+        }
+        Ok(transform)
+    }
+}
+
+impl FromStr for ImageFit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "cover" => Ok(Self::Cover),
+            "contain" => Ok(Self::Contain),
+            "fill" => Ok(Self::Fill),
+            _ => anyhow::bail!(invalid_transform(format!(
+                "Invalid fit {s:?}, expected one of cover, contain or fill"
+            ))),
+        }
+    }
+}
+
+impl FromStr for ImageOutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "webp" => Ok(Self::Webp),
+            "avif" => Ok(Self::Avif),
+            "png" => Ok(Self::Png),
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            _ => anyhow::bail!(invalid_transform(format!(
+                "Invalid format {s:?}, expected one of webp, avif, png or jpeg"
+            ))),
+        }
+    }
+}
+
+impl<RT: Runtime> TransactionalFileStorage<RT> {
+    /// Returns a URL serving `transform` applied to the image, or `None` if the
+    /// file doesn't exist. The image is only transformed when the URL is first
+    /// requested.
+    pub async fn get_image_url(
+        &self,
+        tx: &mut Transaction<RT>,
+        key_broker: &KeyBroker,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        transform: ImageTransform,
+    ) -> anyhow::Result<Option<String>> {
+        ensure_image_transforms_enabled()?;
+        transform.validate()?;
+        let Some(entry) = self
+            .get_file_entry(tx, component.into(), storage_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if entry.encryption.is_some() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "EncryptedImage",
+                "Files uploaded with encryption metadata can't be transformed",
+            ));
+        }
+        if let Some(reason) = &entry.quarantine_reason {
+            anyhow::bail!(quarantined_error(&entry.storage_id, reason));
+        }
+        let token = key_broker.issue_image_transform_url(ImageTransformUrl {
+            component,
+            storage_id: entry.storage_id,
+            params: transform.to_string(),
+        });
+        let origin = &self.convex_origin;
+        Ok(Some(format!("{origin}/api/storage/image/{token}")))
+    }
+}
+
+impl<RT: Runtime> FileStorage<RT> {
+    /// Serves the transformed image for a URL from `get_image_url`,
+    /// transforming and caching it if this is the first request for it.
+    pub async fn get_transformed_image(
+        &self,
+        url: ImageTransformUrl,
+        usage_tracker: impl StorageUsageTracker + Clone + 'static,
+    ) -> anyhow::Result<FileStream> {
+        ensure_image_transforms_enabled()?;
+        let ImageTransformUrl {
+            component,
+            storage_id,
+            params,
+        } = url;
+        let transform: ImageTransform = params.parse()?;
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let Some(source) = self
+            .transactional_file_storage
+            .get_file_entry(
+                &mut tx,
+                component.into(),
+                FileStorageId::LegacyStorageId(storage_id.clone()),
+            )
+            .await?
+        else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "FileNotFound",
+                format!("File {storage_id} not found"),
+            ));
+        };
+        if let Some(reason) = &source.quarantine_reason {
+            anyhow::bail!(quarantined_error(&source.storage_id, reason));
+        }
+        let Some(component_path) = tx.get_component_path(component) else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "FileNotFound",
+                format!("Component {component:?} not found"),
+            ));
+        };
+        let cached = ImageTransformCacheModel::new(&mut tx)
+            .get(&source.storage_id, &params)
+            .await?;
+        log_image_transform_cache(cached.is_some());
+        let transformed = match cached {
+            Some(cached) => cached,
+            None => self.transform_and_cache(&source, transform, params).await?,
+        };
+        self.transactional_file_storage
+            .get_file_stream(
+                component_path,
+                FileStorageEntry {
+                    storage_id: source.storage_id,
+                    storage_key: transformed.storage_key,
+                    sha256: transformed.sha256,
+                    size: transformed.size,
+                    content_type: Some(transformed.content_type),
+                    encryption: None,
+                    quarantine_reason: None,
+                },
+                usage_tracker,
+            )
+            .await
+    }
+
+    async fn transform_and_cache(
+        &self,
+        source: &FileStorageEntry,
+        transform: ImageTransform,
+        params: String,
+    ) -> anyhow::Result<ImageTransformCacheEntry> {
+        let max_size = *IMAGE_TRANSFORM_MAX_SOURCE_SIZE_BYTES;
+        if source.size as u64 > max_size {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ImageTooLarge",
+                format!("Images larger than {max_size} bytes can't be transformed"),
+            ));
+        }
+        let storage = &self.transactional_file_storage.storage;
+        let source_bytes = storage
+            .get(&source.storage_key)
+            .await?
+            .with_context(|| format!("object {:?} not found", source.storage_key))?
+            .collect_as_bytes()
+            .await?;
+
+        let timer = image_transform_timer();
+        let TransformedImage {
+            bytes,
+            content_type,
+        } = tokio::task::spawn_blocking(move || transform.apply(&source_bytes)).await??;
+        timer.finish();
+
+        let uploaded = self
+            .transactional_file_storage
+            .upload_file(
+                Some(ContentLength(bytes.len() as u64)),
+                Some(content_type.parse()?),
+                stream::iter([anyhow::Ok(bytes)]),
+                None,
+            )
+            .await?;
+        let entry = ImageTransformCacheEntry {
+            source_storage_id: source.storage_id.clone(),
+            params,
+            storage_key: uploaded.storage_key,
+            sha256: uploaded.sha256,
+            size: uploaded.size,
+            content_type: content_type.to_string(),
+        };
+        let mut tx = self.database.begin(Identity::system()).await?;
+        match ImageTransformCacheModel::new(&mut tx)
+            .insert(entry.clone())
+            .await?
+        {
+            None => {
+                self.database
+                    .commit_with_write_source(tx, "file_storage_cache_transformed_image")
+                    .await?;
+                Ok(entry)
+            },
+            // Another request transformed the same image first, so serve its
+            // copy and drop ours.
+            Some(existing) => {
+                storage.delete_object(&entry.storage_key).await?;
+                Ok(existing)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{
+        DynamicImage,
+        ImageFormat,
+        ImageReader,
+        RgbaImage,
+    };
+
+    use super::{
+        CropRect,
+        ImageFit,
+        ImageOutputFormat,
+        ImageTransform,
+    };
+
+    fn test_png(width: u32, height: u32) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = vec![];
+        DynamicImage::ImageRgba8(RgbaImage::new(width, height))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> anyhow::Result<(ImageFormat, u32, u32)> {
+        let reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+        let format = reader.format().unwrap();
+        let image = reader.decode()?;
+        Ok((format, image.width(), image.height()))
+    }
+
+    #[test]
+    fn test_params_roundtrip() -> anyhow::Result<()> {
+        let transform = ImageTransform {
+            crop: Some(CropRect {
+                x: 10,
+                y: 20,
+                width: 300,
+                height: 200,
+            }),
+            width: Some(150),
+            height: None,
+            fit: ImageFit::Contain,
+            format: Some(ImageOutputFormat::Webp),
+            quality: Some(70),
+        };
+        let params = transform.to_string();
+        assert_eq!(
+            params,
+            "crop=10,20,300,200&w=150&fit=contain&format=webp&q=70"
+        );
+        assert_eq!(params.parse::<ImageTransform>()?, transform);
+        assert!("w=10&rotate=90".parse::<ImageTransform>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ImageTransform::default().validate().is_err());
+        let transform = ImageTransform {
+            width: Some(100_000),
+            ..Default::default()
+        };
+        assert!(transform.validate().is_err());
+        let transform = ImageTransform {
+            width: Some(100),
+            quality: Some(0),
+            ..Default::default()
+        };
+        assert!(transform.validate().is_err());
+    }
+
+    #[test]
+    fn test_apply() -> anyhow::Result<()> {
+        let source = test_png(400, 200)?;
+
+        // Cover crops to exactly the requested size.
+        let transform = ImageTransform {
+            width: Some(100),
+            height: Some(100),
+            ..Default::default()
+        };
+        let output = transform.apply(&source)?;
+        assert_eq!(output.content_type, "image/png");
+        assert_eq!(decode(&output.bytes)?, (ImageFormat::Png, 100, 100));
+
+        // Contain preserves the aspect ratio.
+        let transform = ImageTransform {
+            width: Some(100),
+            height: Some(100),
+            fit: ImageFit::Contain,
+            format: Some(ImageOutputFormat::Jpeg),
+            ..Default::default()
+        };
+        let output = transform.apply(&source)?;
+        assert_eq!(output.content_type, "image/jpeg");
+        assert_eq!(decode(&output.bytes)?, (ImageFormat::Jpeg, 100, 50));
+
+        // A single dimension scales the other one.
+        let transform = ImageTransform {
+            crop: Some(CropRect {
+                x: 0,
+                y: 0,
+                width: 200,
+                height: 200,
+            }),
+            height: Some(50),
+            format: Some(ImageOutputFormat::Webp),
+            ..Default::default()
+        };
+        let output = transform.apply(&source)?;
+        assert_eq!(decode(&output.bytes)?, (ImageFormat::WebP, 50, 50));
+
+        let transform = ImageTransform {
+            crop: Some(CropRect {
+                x: 300,
+                y: 0,
+                width: 200,
+                height: 200,
+            }),
+            ..Default::default()
+        };
+        assert!(transform.apply(&source).is_err());
+        assert!(transform.apply(b"not an image").is_err());
+        Ok(())
+    }
+}
@@ -1,7 +1,12 @@
 #![feature(let_chains)]
-use std::sync::Arc;
+use std::{
+    net::IpAddr,
+    sync::Arc,
+    time::Duration,
+};
 
 use common::{
+    document::CreationTime,
     runtime::Runtime,
     sha256::Sha256Digest,
     types::ConvexOrigin,
@@ -13,10 +18,30 @@ use headers::{
     ContentRange,
     ContentType,
 };
+use model::file_storage::types::{
+    FileEncryptionMetadata,
+    FileStorageEntry,
+};
 use storage::Storage;
 
+pub use crate::{
+    image_transform::{
+        CropRect,
+        ImageFit,
+        ImageOutputFormat,
+        ImageTransform,
+    },
+    scan::{
+        FileScanner,
+        HttpFileScanner,
+        ScanVerdict,
+    },
+};
+
 mod core;
+mod image_transform;
 mod metrics;
+mod scan;
 #[cfg(test)]
 mod tests;
 
@@ -24,6 +49,7 @@ pub struct FileStream {
     pub sha256: Sha256Digest,
     pub content_length: ContentLength,
     pub content_type: Option<ContentType>,
+    pub encryption: Option<FileEncryptionMetadata>,
     pub stream: BoxStream<'static, futures::io::Result<bytes::Bytes>>,
 }
 
@@ -33,9 +59,28 @@ pub struct FileRangeStream {
     /// 0-size files https://datatracker.ietf.org/doc/html/rfc7233#section-4.2
     pub content_range: Option<ContentRange>,
     pub content_type: Option<ContentType>,
+    pub encryption: Option<FileEncryptionMetadata>,
     pub stream: BoxStream<'static, futures::io::Result<bytes::Bytes>>,
 }
 
+/// A file's storage entry and upload time, which are enough to answer
+/// conditional and range requests before fetching the file's contents.
+#[derive(Clone, Debug)]
+pub struct StoredFile {
+    pub entry: FileStorageEntry,
+    pub creation_time: CreationTime,
+}
+
+/// Options for [`TransactionalFileStorage::get_signed_url`].
+#[derive(Clone, Debug)]
+pub struct SignedUrlOptions {
+    pub expires_in: Duration,
+    /// Serve the file as an attachment with this filename.
+    pub filename: Option<String>,
+    /// Only serve the file to clients connecting from this address.
+    pub ip: Option<IpAddr>,
+}
+
 #[derive(Clone)]
 pub struct FileStorage<RT: Runtime> {
     pub database: Database<RT>,
@@ -47,4 +92,5 @@ pub struct TransactionalFileStorage<RT: Runtime> {
     rt: RT,
     storage: Arc<dyn Storage>,
     convex_origin: ConvexOrigin,
+    scanner: Option<Arc<dyn FileScanner>>,
 }
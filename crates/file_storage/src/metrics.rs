@@ -1,6 +1,9 @@
 use metrics::{
+    log_counter_with_labels,
     log_distribution_with_labels,
+    register_convex_counter,
     register_convex_histogram,
+    IntoLabel,
     StaticMetricLabel,
     StatusTimer,
     STATUS_LABEL,
@@ -46,3 +49,47 @@ pub fn log_get_file_chunk_size(size_bytes: u64, get_file_type: GetFileType) {
         vec![get_file_type.tag()],
     );
 }
+
+register_convex_histogram!(
+    SCAN_FILE_SECONDS,
+    "Duration of scanning an uploaded file for malware",
+    &STATUS_LABEL
+);
+pub fn scan_file_timer() -> StatusTimer {
+    StatusTimer::new(&SCAN_FILE_SECONDS)
+}
+
+register_convex_counter!(
+    SCAN_FILE_TOTAL,
+    "Number of uploaded files scanned for malware",
+    &["infected"],
+);
+pub fn log_file_scan(infected: bool) {
+    log_counter_with_labels(
+        &SCAN_FILE_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("infected", infected.as_label())],
+    );
+}
+
+register_convex_histogram!(
+    IMAGE_TRANSFORM_SECONDS,
+    "Duration of decoding, transforming and encoding an image",
+    &STATUS_LABEL
+);
+pub fn image_transform_timer() -> StatusTimer {
+    StatusTimer::new(&IMAGE_TRANSFORM_SECONDS)
+}
+
+register_convex_counter!(
+    IMAGE_TRANSFORM_CACHE_HIT_TOTAL,
+    "Count of transformed image requests, labeled with whether the result was already cached",
+    &["hit"]
+);
+pub fn log_image_transform_cache(hit: bool) {
+    log_counter_with_labels(
+        &IMAGE_TRANSFORM_CACHE_HIT_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("hit", hit.as_label())],
+    );
+}
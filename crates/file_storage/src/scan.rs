@@ -0,0 +1,129 @@
+//! Malware scanning for uploaded files. When a [`FileScanner`] is configured,
+//! every upload is scanned after it's written to storage and before its
+//! `_storage` document is committed. Files the scanner flags are still
+//! recorded, with a quarantine reason, so admins can inspect them, but they're
+//! never served.
+
+use std::{
+    pin::Pin,
+    sync::Mutex,
+    task::{
+        Context,
+        Poll,
+    },
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use common::knobs::FILE_SCAN_TIMEOUT;
+use futures::{
+    stream::BoxStream,
+    Stream,
+    StreamExt,
+};
+use model::file_storage::types::FileStorageEntry;
+use serde::Deserialize;
+use storage::StorageGetStream;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    Infected { reason: String },
+}
+
+#[async_trait]
+pub trait FileScanner: Send + Sync {
+    /// Scan an uploaded file's contents. Errors fail the upload rather than
+    /// letting an unscanned file through.
+    async fn scan(
+        &self,
+        entry: &FileStorageEntry,
+        contents: StorageGetStream,
+    ) -> anyhow::Result<ScanVerdict>;
+}
+
+/// Sends each file to an HTTP scanning service, which can be a scanner's REST
+/// API or an HTTP action that forwards the file to one. The file is POSTed as
+/// the request body and the service responds with
+/// `{"infected": boolean, "reason"?: string}`.
+pub struct HttpFileScanner {
+    client: reqwest::Client,
+    url: String,
+    auth_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ScanResponse {
+    infected: bool,
+    reason: Option<String>,
+}
+
+impl HttpFileScanner {
+    /// Configured from `FILE_SCANNER_URL` and optionally
+    /// `FILE_SCANNER_AUTH_TOKEN`, which is sent as a bearer token.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            client: reqwest::Client::builder()
+                .timeout(*FILE_SCAN_TIMEOUT)
+                .build()
+                .ok()?,
+            url: std::env::var("FILE_SCANNER_URL").ok()?,
+            auth_token: std::env::var("FILE_SCANNER_AUTH_TOKEN").ok(),
+        })
+    }
+}
+
+#[async_trait]
+impl FileScanner for HttpFileScanner {
+    async fn scan(
+        &self,
+        entry: &FileStorageEntry,
+        contents: StorageGetStream,
+    ) -> anyhow::Result<ScanVerdict> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_LENGTH, contents.content_length)
+            .header("Convex-Sha256", entry.sha256.as_hex())
+            .body(reqwest::Body::wrap_stream(SyncStream(Mutex::new(
+                contents.stream,
+            ))));
+        if let Some(content_type) = &entry.content_type {
+            request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("File scanner returned {status}: {text}");
+        }
+        let response: ScanResponse = response.json().await?;
+        Ok(if response.infected {
+            ScanVerdict::Infected {
+                reason: response
+                    .reason
+                    .unwrap_or_else(|| "Flagged by malware scan".to_string()),
+            }
+        } else {
+            ScanVerdict::Clean
+        })
+    }
+}
+
+/// Request bodies must be `Sync`, which storage streams aren't. The stream is
+/// only ever polled through `&mut`, so the lock is never contended.
+struct SyncStream(Mutex<BoxStream<'static, futures::io::Result<Bytes>>>);
+
+impl Stream for SyncStream {
+    type Item = futures::io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.get_mut().0.get_mut() {
+            Ok(stream) => stream.poll_next_unpin(cx),
+            Err(poisoned) => poisoned.into_inner().poll_next_unpin(cx),
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use common::{
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
     runtime::Runtime,
     sha256::Sha256,
 };
@@ -16,19 +21,39 @@ use errors::{
     ErrorMetadata,
 };
 use events::usage::NoOpUsageEventLogger;
-use futures::stream;
+use futures::{
+    stream,
+    TryStreamExt,
+};
 use keybroker::Identity;
 use model::{
-    file_storage::FileStorageId,
+    component_limits::{
+        types::ComponentLimits,
+        ComponentLimitsModel,
+    },
+    file_storage::{
+        types::{
+            FileEncryptionMetadata,
+            FileStorageEntry,
+        },
+        FileStorageId,
+    },
     test_helpers::DbFixturesWithModel,
 };
 use runtime::testing::TestRuntime;
-use storage::LocalDirStorage;
+use storage::{
+    LocalDirStorage,
+    StorageGetStream,
+};
 use usage_tracking::UsageCounter;
 use value::TableNamespace;
 
 use super::FileStorage;
-use crate::TransactionalFileStorage;
+use crate::{
+    FileScanner,
+    ScanVerdict,
+    TransactionalFileStorage,
+};
 
 fn setup_file_storage(
     rt: TestRuntime,
@@ -74,6 +99,7 @@ async fn test_store_file_sha_mismatch(rt: TestRuntime) -> anyhow::Result<()> {
             None,
             stream::iter([Ok(big_file)]),
             Some(wrong.clone()),
+            None,
             &UsageCounter::new(Arc::new(NoOpUsageEventLogger)),
         )
         .await
@@ -84,3 +110,163 @@ async fn test_store_file_sha_mismatch(rt: TestRuntime) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_store_file_with_encryption_metadata(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = DbFixtures::new_with_model(&rt).await?.db;
+    let file_storage = setup_file_storage(rt, &database)?;
+
+    let encryption = FileEncryptionMetadata {
+        key_id: "key-1".to_string(),
+        iv: "AAECAwQFBgcICQoL".to_string(),
+        algorithm: "AES-GCM".to_string(),
+    };
+    let storage_id = file_storage
+        .store_file(
+            TableNamespace::test_user(),
+            None,
+            None,
+            stream::iter([Ok(vec![1, 2, 3])]),
+            None,
+            Some(encryption.clone()),
+            &UsageCounter::new(Arc::new(NoOpUsageEventLogger)),
+        )
+        .await?;
+    let mut tx = database.begin(Identity::system()).await?;
+    let entry = file_storage
+        .transactional_file_storage
+        .get_file_entry(
+            &mut tx,
+            TableNamespace::test_user(),
+            FileStorageId::DocumentId(storage_id),
+        )
+        .await?
+        .expect("file should exist");
+    assert_eq!(entry.encryption, Some(encryption));
+
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_store_file_storage_quota(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = DbFixtures::new_with_model(&rt).await?.db;
+    let file_storage = setup_file_storage(rt, &database)?;
+    let usage_tracker = UsageCounter::new(Arc::new(NoOpUsageEventLogger));
+
+    let mut tx = database.begin(Identity::system()).await?;
+    ComponentLimitsModel::new(&mut tx)
+        .set(ComponentLimits {
+            component: ComponentId::test_user(),
+            max_documents_read: None,
+            function_timeout: None,
+            max_scheduled_functions: None,
+            max_heap_size: None,
+            max_storage_bytes: Some(5),
+            max_storage_files: Some(2),
+        })
+        .await?;
+    database.commit(tx).await?;
+
+    let store = |contents: Vec<u8>| {
+        file_storage.store_file(
+            TableNamespace::test_user(),
+            None,
+            None,
+            stream::iter([Ok(contents)]),
+            None,
+            None,
+            &usage_tracker,
+        )
+    };
+    store(vec![1, 2, 3]).await?;
+    let err: ErrorMetadata = store(vec![4, 5, 6]).await.unwrap_err().downcast()?;
+    assert_eq!(err.code, ErrorCode::BadRequest);
+    assert_eq!(err.short_msg, "StorageQuotaExceeded");
+    // Files that fit in the remaining bytes are still accepted.
+    store(vec![4, 5]).await?;
+    let err: ErrorMetadata = store(vec![]).await.unwrap_err().downcast()?;
+    assert_eq!(err.short_msg, "StorageQuotaExceeded");
+    assert!(err.msg.contains("quota of 2 files"));
+
+    Ok(())
+}
+
+/// Flags any file containing the bytes "EICAR".
+struct FakeScanner;
+
+#[async_trait]
+impl FileScanner for FakeScanner {
+    async fn scan(
+        &self,
+        _entry: &FileStorageEntry,
+        contents: StorageGetStream,
+    ) -> anyhow::Result<ScanVerdict> {
+        let contents: Vec<_> = contents.stream.try_concat().await?.to_vec();
+        Ok(if contents.windows(5).any(|window| window == b"EICAR") {
+            ScanVerdict::Infected {
+                reason: "Eicar-Test-Signature".to_string(),
+            }
+        } else {
+            ScanVerdict::Clean
+        })
+    }
+}
+
+#[convex_macro::test_runtime]
+async fn test_store_file_quarantined_by_scanner(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = DbFixtures::new_with_model(&rt).await?.db;
+    let mut file_storage = setup_file_storage(rt, &database)?;
+    file_storage.transactional_file_storage = file_storage
+        .transactional_file_storage
+        .with_scanner(Arc::new(FakeScanner));
+    let usage_tracker = UsageCounter::new(Arc::new(NoOpUsageEventLogger));
+
+    let mut entries = vec![];
+    for contents in [b"hello".to_vec(), b"X5O!EICAR-TEST".to_vec()] {
+        let storage_id = file_storage
+            .store_file(
+                TableNamespace::test_user(),
+                None,
+                None,
+                stream::iter([Ok(contents)]),
+                None,
+                None,
+                &usage_tracker,
+            )
+            .await?;
+        let mut tx = database.begin(Identity::system()).await?;
+        let entry = file_storage
+            .transactional_file_storage
+            .get_file_entry(
+                &mut tx,
+                TableNamespace::test_user(),
+                FileStorageId::DocumentId(storage_id),
+            )
+            .await?
+            .expect("file should exist");
+        entries.push(entry);
+    }
+    let [clean, infected] = entries.try_into().unwrap();
+    assert_eq!(clean.quarantine_reason, None);
+    assert_eq!(
+        infected.quarantine_reason.as_deref(),
+        Some("Eicar-Test-Signature")
+    );
+
+    file_storage
+        .transactional_file_storage
+        .get_file_stream(ComponentPath::root(), clean, usage_tracker.clone())
+        .await?;
+    let Err(err) = file_storage
+        .transactional_file_storage
+        .get_file_stream(ComponentPath::root(), infected, usage_tracker)
+        .await
+    else {
+        panic!("Quarantined file was served");
+    };
+    let err: ErrorMetadata = err.downcast()?;
+    assert_eq!(err.code, ErrorCode::Forbidden);
+    assert_eq!(err.short_msg, "FileQuarantined");
+
+    Ok(())
+}
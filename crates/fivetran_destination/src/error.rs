@@ -386,6 +386,10 @@ mod tests {
                 "name" => FieldValidator::required_field_type(Validator::String),
                 "email" => FieldValidator::required_field_type(Validator::String),
             )])),
+            triggers: vec![],
+            on_commit: vec![],
+            cascade_delete_files: false,
+            file_text: None,
             search_indexes: Default::default(),
             vector_indexes: Default::default(),
         };
@@ -209,6 +209,10 @@ impl FivetranTableSchema {
         Ok(TableDefinition {
             table_name,
             document_type,
+            triggers: vec![],
+            on_commit: vec![],
+            cascade_delete_files: false,
+            file_text: None,
             indexes,
             search_indexes: Default::default(),
             vector_indexes: Default::default(),
@@ -1007,6 +1011,10 @@ mod tests {
                     })
                     .collect(),
             )])),
+            triggers: vec![],
+            on_commit: vec![],
+            cascade_delete_files: false,
+            file_text: None,
             indexes: convex_indexes(indexes),
         }
     }
@@ -1884,6 +1892,10 @@ mod tests {
                         ))),
                     ))),
                 )])),
+                triggers: vec![],
+                on_commit: vec![],
+                cascade_delete_files: false,
+                file_text: None,
                 search_indexes: Default::default(),
                 vector_indexes: Default::default(),
             },
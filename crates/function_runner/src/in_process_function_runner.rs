@@ -29,6 +29,7 @@ use common::{
         UnixTimestamp,
     },
     schemas::DatabaseSchema,
+    secrets::SecretsResolver,
     types::{
         ConvexOrigin,
         IndexId,
@@ -99,6 +100,7 @@ pub struct InProcessFunctionRunner<RT: Runtime> {
     // and ApplicationFunctionRunner.
     action_callbacks: Arc<RwLock<Option<Weak<dyn ActionCallbacks>>>>,
     fetch_client: Arc<dyn FetchClient>,
+    secrets: SecretsResolver,
 }
 
 impl<RT: Runtime> InProcessFunctionRunner<RT> {
@@ -111,6 +113,7 @@ impl<RT: Runtime> InProcessFunctionRunner<RT> {
         storage: InstanceStorage,
         database: Database<RT>,
         fetch_client: Arc<dyn FetchClient>,
+        secrets: SecretsResolver,
     ) -> anyhow::Result<Self> {
         // InProcessFunrun is single tenant and thus can use the full capacity.
         let max_percent_per_client = 100;
@@ -124,6 +127,7 @@ impl<RT: Runtime> InProcessFunctionRunner<RT> {
             database,
             action_callbacks: Arc::new(RwLock::new(None)),
             fetch_client,
+            secrets,
         })
     }
 }
@@ -177,6 +181,7 @@ impl<RT: Runtime> FunctionRunner<RT> for InProcessFunctionRunner<RT> {
             text_index_snapshot,
             action_callbacks,
             fetch_client: self.fetch_client.clone(),
+            secrets: self.secrets.clone(),
             log_line_sender,
             udf_type,
             identity,
@@ -83,6 +83,7 @@ pub mod in_process_function_runner;
 mod metrics;
 mod module_cache;
 pub mod server;
+pub mod worker_pool;
 
 #[async_trait]
 pub trait FunctionRunner<RT: Runtime>: Send + Sync + 'static {
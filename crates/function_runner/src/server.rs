@@ -23,6 +23,7 @@ use common::{
         fetch::FetchClient,
         RoutedHttpPath,
     },
+    knobs::UDF_DETERMINISM_CHECK,
     log_lines::LogLine,
     persistence::{
         NoopRetentionValidator,
@@ -35,6 +36,7 @@ use common::{
         UnixTimestamp,
     },
     schemas::DatabaseSchema,
+    secrets::SecretsResolver,
     types::{
         ConvexOrigin,
         IndexId,
@@ -123,6 +125,7 @@ pub struct RunRequestArgs {
     pub text_index_snapshot: Arc<dyn TransactionTextSnapshot>,
     pub action_callbacks: Arc<dyn ActionCallbacks>,
     pub fetch_client: Arc<dyn FetchClient>,
+    pub secrets: SecretsResolver,
     pub log_line_sender: Option<mpsc::UnboundedSender<LogLine>>,
     pub udf_type: UdfType,
     pub identity: Identity,
@@ -323,6 +326,7 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
             text_index_snapshot,
             action_callbacks,
             fetch_client,
+            secrets,
             log_line_sender,
             udf_type,
             identity,
@@ -354,6 +358,31 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
                 .await?,
             ),
         };
+        // Begin a second transaction at the same snapshot to check that
+        // queries and mutations are deterministic. It doesn't count towards
+        // usage.
+        let second_transaction =
+            if *UDF_DETERMINISM_CHECK && matches!(udf_type, UdfType::Query | UdfType::Mutation) {
+                Some(
+                    self.index_cache
+                        .begin_tx(
+                            identity.clone(),
+                            ts,
+                            existing_writes.clone(),
+                            reader.clone(),
+                            instance_name.clone(),
+                            in_memory_index_last_modified.clone(),
+                            bootstrap_metadata.clone(),
+                            table_count_snapshot.clone(),
+                            text_index_snapshot.clone(),
+                            FunctionUsageTracker::new(),
+                            Arc::new(NoopRetentionValidator {}),
+                        )
+                        .await?,
+                )
+            } else {
+                None
+            };
         let mut transaction = self
             .index_cache
             .begin_tx(
@@ -390,6 +419,7 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
                 cache: self.module_cache.clone(),
                 modules_storage,
             }),
+            secrets,
         };
 
         match udf_type {
@@ -398,19 +428,36 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
                     path_and_args,
                     journal,
                 } = function_metadata.context("Missing function metadata for query or mutation")?;
-                let (tx, outcome) = self
-                    .isolate_client
-                    .execute_udf(
-                        udf_type,
-                        path_and_args,
-                        transaction,
-                        journal,
-                        context,
-                        environment_data,
-                        0,
-                        instance_name,
-                    )
-                    .await?;
+                let (tx, outcome) = match second_transaction {
+                    Some(second_transaction) => {
+                        self.isolate_client
+                            .execute_udf_checking_determinism(
+                                udf_type,
+                                path_and_args,
+                                transaction,
+                                second_transaction,
+                                journal,
+                                context,
+                                environment_data,
+                                instance_name,
+                            )
+                            .await?
+                    },
+                    None => {
+                        self.isolate_client
+                            .execute_udf(
+                                udf_type,
+                                path_and_args,
+                                transaction,
+                                journal,
+                                context,
+                                environment_data,
+                                0,
+                                instance_name,
+                            )
+                            .await?
+                    },
+                };
                 Ok((
                     Some(tx.try_into()?),
                     outcome,
@@ -0,0 +1,667 @@
+//! Dispatches function runner calls to a pool of workers, so function
+//! execution can scale independently of the backend.
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    hash::{
+        DefaultHasher,
+        Hash,
+        Hasher,
+    },
+    sync::{
+        Arc,
+        Weak,
+    },
+};
+
+use async_trait::async_trait;
+use common::{
+    auth::AuthConfig,
+    bootstrap_model::components::definition::ComponentDefinitionMetadata,
+    components::{
+        ComponentDefinitionPath,
+        ComponentId,
+        ComponentName,
+        Resource,
+    },
+    errors::JsError,
+    execution_context::ExecutionContext,
+    knobs::{
+        FUNCTION_RUNNER_WORKER_HEARTBEAT_TIMEOUT,
+        FUNCTION_RUNNER_WORKER_MAX_FAILURES,
+    },
+    log_lines::LogLine,
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    schemas::DatabaseSchema,
+    types::{
+        IndexId,
+        RepeatableTimestamp,
+        UdfType,
+    },
+};
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
+use isolate::ActionCallbacks;
+use keybroker::Identity;
+use model::{
+    config::types::ModuleConfig,
+    environment_variables::types::{
+        EnvVarName,
+        EnvVarValue,
+    },
+    modules::module_versions::{
+        AnalyzedModule,
+        ModuleSource,
+        SourceMap,
+    },
+    udf_config::types::UdfConfig,
+};
+use parking_lot::{
+    Mutex,
+    RwLock,
+};
+use sync_types::{
+    CanonicalizedModulePath,
+    Timestamp,
+};
+use tokio::sync::mpsc;
+use udf::{
+    EvaluateAppDefinitionsResult,
+    FunctionOutcome,
+};
+use usage_tracking::FunctionUsageStats;
+use value::identifier::Identifier;
+
+use super::FunctionRunner;
+use crate::{
+    server::{
+        FunctionMetadata,
+        HttpActionMetadata,
+    },
+    FunctionFinalTransaction,
+    FunctionWrites,
+};
+
+/// Calls for the same module version go to the same worker, so each worker
+/// only loads and caches the modules it's routed.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+struct WorkerRoutingKey {
+    component: ComponentId,
+    module_path: CanonicalizedModulePath,
+    /// The pinned version of a component with a traffic split.
+    traffic_split_version: Option<u64>,
+}
+
+impl WorkerRoutingKey {
+    fn new(
+        function_metadata: &Option<FunctionMetadata>,
+        http_action_metadata: &Option<HttpActionMetadata>,
+    ) -> Option<Self> {
+        if let Some(function_metadata) = function_metadata {
+            let path_and_args = &function_metadata.path_and_args;
+            let path = path_and_args.path();
+            return Some(Self {
+                component: path.component,
+                module_path: path.udf_path.module().clone(),
+                traffic_split_version: path_and_args.module_version(),
+            });
+        }
+        let path = http_action_metadata.as_ref()?.http_module_path.path();
+        Some(Self {
+            component: path.component,
+            module_path: path.udf_path.module().clone(),
+            traffic_split_version: None,
+        })
+    }
+
+    /// Rendezvous hashing: each worker's score only depends on the key and
+    /// the worker, so a worker joining or leaving only moves the keys that
+    /// pick it.
+    fn score(&self, worker_id: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        worker_id.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+struct Worker<RT: Runtime> {
+    runner: Arc<dyn FunctionRunner<RT>>,
+    last_heartbeat: tokio::time::Instant,
+    consecutive_failures: u32,
+}
+
+/// A `FunctionRunner` that sends each call to one of a pool of registered
+/// workers, e.g. clients for function runners in other processes.
+///
+/// Workers register with `register_worker` and stay in the pool as long as
+/// they call `heartbeat` within `FUNCTION_RUNNER_WORKER_HEARTBEAT_TIMEOUT`.
+/// A worker that fails `FUNCTION_RUNNER_WORKER_MAX_FAILURES` calls in a row
+/// is removed, and its next heartbeat fails so it knows to register again.
+/// Calls aren't retried on another worker, since an action may have had side
+/// effects before failing.
+pub struct FunctionRunnerWorkerPool<RT: Runtime> {
+    rt: RT,
+    workers: Mutex<BTreeMap<String, Worker<RT>>>,
+    // Use Weak reference to avoid reference cycle between the pool and
+    // ApplicationFunctionRunner, like InProcessFunctionRunner.
+    action_callbacks: RwLock<Option<Weak<dyn ActionCallbacks>>>,
+}
+
+impl<RT: Runtime> FunctionRunnerWorkerPool<RT> {
+    pub fn new(rt: RT) -> Self {
+        Self {
+            rt,
+            workers: Mutex::new(BTreeMap::new()),
+            action_callbacks: RwLock::new(None),
+        }
+    }
+
+    /// Add a worker to the pool, replacing any worker with the same id.
+    pub fn register_worker(&self, worker_id: String, runner: Arc<dyn FunctionRunner<RT>>) {
+        if let Some(action_callbacks) = self
+            .action_callbacks
+            .read()
+            .as_ref()
+            .and_then(|callbacks| callbacks.upgrade())
+        {
+            runner.set_action_callbacks(action_callbacks);
+        }
+        tracing::info!("Function runner worker {worker_id} registered");
+        self.workers.lock().insert(
+            worker_id,
+            Worker {
+                runner,
+                last_heartbeat: self.rt.monotonic_now(),
+                consecutive_failures: 0,
+            },
+        );
+    }
+
+    /// Keep a registered worker in the pool. Fails if the worker isn't in the
+    /// pool, in which case it should register again.
+    pub fn heartbeat(&self, worker_id: &str) -> anyhow::Result<()> {
+        let mut workers = self.workers.lock();
+        let Some(worker) = workers.get_mut(worker_id) else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "FunctionRunnerWorkerNotRegistered",
+                format!("Function runner worker {worker_id} isn't registered"),
+            ));
+        };
+        worker.last_heartbeat = self.rt.monotonic_now();
+        Ok(())
+    }
+
+    pub fn deregister_worker(&self, worker_id: &str) {
+        if self.workers.lock().remove(worker_id).is_some() {
+            tracing::info!("Function runner worker {worker_id} deregistered");
+        }
+    }
+
+    /// Pick a healthy worker for `key`, or any healthy worker without a key.
+    fn pick_worker(
+        &self,
+        key: Option<&WorkerRoutingKey>,
+    ) -> anyhow::Result<(String, Arc<dyn FunctionRunner<RT>>)> {
+        let now = self.rt.monotonic_now();
+        let mut workers = self.workers.lock();
+        workers.retain(|worker_id, worker| {
+            let alive = now.duration_since(worker.last_heartbeat)
+                < *FUNCTION_RUNNER_WORKER_HEARTBEAT_TIMEOUT;
+            if !alive {
+                tracing::warn!("Function runner worker {worker_id} stopped sending heartbeats");
+            }
+            alive
+        });
+        let picked = match key {
+            Some(key) => workers
+                .iter()
+                .max_by_key(|(worker_id, _)| key.score(worker_id)),
+            None => workers.iter().next(),
+        };
+        let Some((worker_id, worker)) = picked else {
+            anyhow::bail!(ErrorMetadata::overloaded(
+                "NoFunctionRunnerWorkers",
+                "No function runner workers are available. Please try again later.",
+            ));
+        };
+        Ok((worker_id.clone(), worker.runner.clone()))
+    }
+
+    fn record_result<T>(&self, worker_id: &str, result: &anyhow::Result<T>) {
+        let mut workers = self.workers.lock();
+        let Some(worker) = workers.get_mut(worker_id) else {
+            return;
+        };
+        match result {
+            Ok(_) => {
+                worker.consecutive_failures = 0;
+                return;
+            },
+            // The worker handled the request fine, the developer's code or
+            // request was at fault.
+            Err(e) if e.is_deterministic_user_error() => return,
+            Err(_) => (),
+        }
+        worker.consecutive_failures += 1;
+        if worker.consecutive_failures >= *FUNCTION_RUNNER_WORKER_MAX_FAILURES {
+            tracing::warn!(
+                "Function runner worker {worker_id} failed {} calls in a row, removing it",
+                worker.consecutive_failures
+            );
+            workers.remove(worker_id);
+        }
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> FunctionRunner<RT> for FunctionRunnerWorkerPool<RT> {
+    #[fastrace::trace]
+    async fn run_function(
+        &self,
+        udf_type: UdfType,
+        identity: Identity,
+        ts: RepeatableTimestamp,
+        existing_writes: FunctionWrites,
+        log_line_sender: Option<mpsc::UnboundedSender<LogLine>>,
+        function_metadata: Option<FunctionMetadata>,
+        http_action_metadata: Option<HttpActionMetadata>,
+        system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        in_memory_index_last_modified: BTreeMap<IndexId, Timestamp>,
+        context: ExecutionContext,
+    ) -> anyhow::Result<(
+        Option<FunctionFinalTransaction>,
+        FunctionOutcome,
+        FunctionUsageStats,
+    )> {
+        let key = WorkerRoutingKey::new(&function_metadata, &http_action_metadata);
+        let (worker_id, runner) = self.pick_worker(key.as_ref())?;
+        let result = runner
+            .run_function(
+                udf_type,
+                identity,
+                ts,
+                existing_writes,
+                log_line_sender,
+                function_metadata,
+                http_action_metadata,
+                system_env_vars,
+                in_memory_index_last_modified,
+                context,
+            )
+            .await;
+        self.record_result(&worker_id, &result);
+        result
+    }
+
+    #[fastrace::trace]
+    async fn analyze(
+        &self,
+        udf_config: UdfConfig,
+        modules: BTreeMap<CanonicalizedModulePath, ModuleConfig>,
+        environment_variables: BTreeMap<EnvVarName, EnvVarValue>,
+    ) -> anyhow::Result<Result<BTreeMap<CanonicalizedModulePath, AnalyzedModule>, JsError>> {
+        let (worker_id, runner) = self.pick_worker(None)?;
+        let result = runner
+            .analyze(udf_config, modules, environment_variables)
+            .await;
+        self.record_result(&worker_id, &result);
+        result
+    }
+
+    #[fastrace::trace]
+    async fn evaluate_app_definitions(
+        &self,
+        app_definition: ModuleConfig,
+        component_definitions: BTreeMap<ComponentDefinitionPath, ModuleConfig>,
+        dependency_graph: BTreeSet<(ComponentDefinitionPath, ComponentDefinitionPath)>,
+        environment_variables: BTreeMap<EnvVarName, EnvVarValue>,
+        system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+    ) -> anyhow::Result<EvaluateAppDefinitionsResult> {
+        let (worker_id, runner) = self.pick_worker(None)?;
+        let result = runner
+            .evaluate_app_definitions(
+                app_definition,
+                component_definitions,
+                dependency_graph,
+                environment_variables,
+                system_env_vars,
+            )
+            .await;
+        self.record_result(&worker_id, &result);
+        result
+    }
+
+    #[fastrace::trace]
+    async fn evaluate_component_initializer(
+        &self,
+        evaluated_definitions: BTreeMap<ComponentDefinitionPath, ComponentDefinitionMetadata>,
+        path: ComponentDefinitionPath,
+        definition: ModuleConfig,
+        args: BTreeMap<Identifier, Resource>,
+        name: ComponentName,
+    ) -> anyhow::Result<BTreeMap<Identifier, Resource>> {
+        let (worker_id, runner) = self.pick_worker(None)?;
+        let result = runner
+            .evaluate_component_initializer(evaluated_definitions, path, definition, args, name)
+            .await;
+        self.record_result(&worker_id, &result);
+        result
+    }
+
+    #[fastrace::trace]
+    async fn evaluate_schema(
+        &self,
+        schema_bundle: ModuleSource,
+        source_map: Option<SourceMap>,
+        rng_seed: [u8; 32],
+        unix_timestamp: UnixTimestamp,
+    ) -> anyhow::Result<DatabaseSchema> {
+        let (worker_id, runner) = self.pick_worker(None)?;
+        let result = runner
+            .evaluate_schema(schema_bundle, source_map, rng_seed, unix_timestamp)
+            .await;
+        self.record_result(&worker_id, &result);
+        result
+    }
+
+    #[fastrace::trace]
+    async fn evaluate_auth_config(
+        &self,
+        auth_config_bundle: ModuleSource,
+        source_map: Option<SourceMap>,
+        environment_variables: BTreeMap<EnvVarName, EnvVarValue>,
+        explanation: &str,
+    ) -> anyhow::Result<AuthConfig> {
+        let (worker_id, runner) = self.pick_worker(None)?;
+        let result = runner
+            .evaluate_auth_config(
+                auth_config_bundle,
+                source_map,
+                environment_variables,
+                explanation,
+            )
+            .await;
+        self.record_result(&worker_id, &result);
+        result
+    }
+
+    /// Passed on to every worker, including ones that register later.
+    fn set_action_callbacks(&self, action_callbacks: Arc<dyn ActionCallbacks>) {
+        *self.action_callbacks.write() = Some(Arc::downgrade(&action_callbacks));
+        for worker in self.workers.lock().values() {
+            worker.runner.set_action_callbacks(action_callbacks.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{
+            BTreeMap,
+            BTreeSet,
+        },
+        sync::{
+            atomic::{
+                AtomicBool,
+                AtomicUsize,
+                Ordering,
+            },
+            Arc,
+        },
+    };
+
+    use async_trait::async_trait;
+    use common::{
+        auth::AuthConfig,
+        bootstrap_model::components::definition::ComponentDefinitionMetadata,
+        components::{
+            ComponentDefinitionPath,
+            ComponentId,
+            ComponentName,
+            Resource,
+        },
+        errors::JsError,
+        execution_context::ExecutionContext,
+        knobs::{
+            FUNCTION_RUNNER_WORKER_HEARTBEAT_TIMEOUT,
+            FUNCTION_RUNNER_WORKER_MAX_FAILURES,
+        },
+        log_lines::LogLine,
+        runtime::UnixTimestamp,
+        schemas::DatabaseSchema,
+        types::{
+            IndexId,
+            RepeatableTimestamp,
+            UdfType,
+        },
+    };
+    use errors::ErrorMetadataAnyhowExt;
+    use isolate::ActionCallbacks;
+    use keybroker::Identity;
+    use model::{
+        config::types::ModuleConfig,
+        environment_variables::types::{
+            EnvVarName,
+            EnvVarValue,
+        },
+        modules::module_versions::{
+            AnalyzedModule,
+            ModuleSource,
+            SourceMap,
+        },
+        udf_config::types::UdfConfig,
+    };
+    use runtime::testing::TestRuntime;
+    use sync_types::{
+        CanonicalizedModulePath,
+        Timestamp,
+    };
+    use tokio::sync::mpsc;
+    use udf::{
+        EvaluateAppDefinitionsResult,
+        FunctionOutcome,
+    };
+    use usage_tracking::FunctionUsageStats;
+    use value::identifier::Identifier;
+
+    use super::{
+        FunctionRunnerWorkerPool,
+        WorkerRoutingKey,
+    };
+    use crate::{
+        server::{
+            FunctionMetadata,
+            HttpActionMetadata,
+        },
+        FunctionFinalTransaction,
+        FunctionRunner,
+        FunctionWrites,
+    };
+
+    /// Only supports `analyze`, which counts its calls and fails while
+    /// `failing` is set.
+    #[derive(Default)]
+    struct FakeRunner {
+        calls: AtomicUsize,
+        failing: AtomicBool,
+    }
+
+    #[async_trait]
+    impl FunctionRunner<TestRuntime> for FakeRunner {
+        async fn run_function(
+            &self,
+            _udf_type: UdfType,
+            _identity: Identity,
+            _ts: RepeatableTimestamp,
+            _existing_writes: FunctionWrites,
+            _log_line_sender: Option<mpsc::UnboundedSender<LogLine>>,
+            _function_metadata: Option<FunctionMetadata>,
+            _http_action_metadata: Option<HttpActionMetadata>,
+            _system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+            _in_memory_index_last_modified: BTreeMap<IndexId, Timestamp>,
+            _context: ExecutionContext,
+        ) -> anyhow::Result<(
+            Option<FunctionFinalTransaction>,
+            FunctionOutcome,
+            FunctionUsageStats,
+        )> {
+            unimplemented!()
+        }
+
+        async fn analyze(
+            &self,
+            _udf_config: UdfConfig,
+            _modules: BTreeMap<CanonicalizedModulePath, ModuleConfig>,
+            _environment_variables: BTreeMap<EnvVarName, EnvVarValue>,
+        ) -> anyhow::Result<Result<BTreeMap<CanonicalizedModulePath, AnalyzedModule>, JsError>>
+        {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            anyhow::ensure!(!self.failing.load(Ordering::SeqCst), "worker failed");
+            Ok(Ok(BTreeMap::new()))
+        }
+
+        async fn evaluate_app_definitions(
+            &self,
+            _app_definition: ModuleConfig,
+            _component_definitions: BTreeMap<ComponentDefinitionPath, ModuleConfig>,
+            _dependency_graph: BTreeSet<(ComponentDefinitionPath, ComponentDefinitionPath)>,
+            _environment_variables: BTreeMap<EnvVarName, EnvVarValue>,
+            _system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        ) -> anyhow::Result<EvaluateAppDefinitionsResult> {
+            unimplemented!()
+        }
+
+        async fn evaluate_component_initializer(
+            &self,
+            _evaluated_definitions: BTreeMap<ComponentDefinitionPath, ComponentDefinitionMetadata>,
+            _path: ComponentDefinitionPath,
+            _definition: ModuleConfig,
+            _args: BTreeMap<Identifier, Resource>,
+            _name: ComponentName,
+        ) -> anyhow::Result<BTreeMap<Identifier, Resource>> {
+            unimplemented!()
+        }
+
+        async fn evaluate_schema(
+            &self,
+            _schema_bundle: ModuleSource,
+            _source_map: Option<SourceMap>,
+            _rng_seed: [u8; 32],
+            _unix_timestamp: UnixTimestamp,
+        ) -> anyhow::Result<DatabaseSchema> {
+            unimplemented!()
+        }
+
+        async fn evaluate_auth_config(
+            &self,
+            _auth_config_bundle: ModuleSource,
+            _source_map: Option<SourceMap>,
+            _environment_variables: BTreeMap<EnvVarName, EnvVarValue>,
+            _explanation: &str,
+        ) -> anyhow::Result<AuthConfig> {
+            unimplemented!()
+        }
+
+        fn set_action_callbacks(&self, _action_callbacks: Arc<dyn ActionCallbacks>) {}
+    }
+
+    fn routing_key(module_path: &str) -> anyhow::Result<WorkerRoutingKey> {
+        Ok(WorkerRoutingKey {
+            component: ComponentId::Root,
+            module_path: module_path.parse()?,
+            traffic_split_version: None,
+        })
+    }
+
+    async fn analyze(pool: &FunctionRunnerWorkerPool<TestRuntime>) -> anyhow::Result<()> {
+        let udf_config = UdfConfig::new_for_test(&pool.rt, "1.0.0".parse()?);
+        pool.analyze(udf_config, BTreeMap::new(), BTreeMap::new())
+            .await?
+            .map_err(|e| anyhow::anyhow!(e.message))?;
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_sticky_routing(rt: TestRuntime) -> anyhow::Result<()> {
+        let pool = FunctionRunnerWorkerPool::new(rt);
+        for i in 0..4 {
+            pool.register_worker(format!("worker{i}"), Arc::new(FakeRunner::default()));
+        }
+        let key = routing_key("a.js")?;
+        let (worker_id, _) = pool.pick_worker(Some(&key))?;
+        for _ in 0..10 {
+            assert_eq!(pool.pick_worker(Some(&key))?.0, worker_id);
+        }
+
+        // Once the worker leaves, the module moves to another worker and stays
+        // there.
+        pool.deregister_worker(&worker_id);
+        let (new_worker_id, _) = pool.pick_worker(Some(&key))?;
+        assert_ne!(new_worker_id, worker_id);
+        assert_eq!(pool.pick_worker(Some(&key))?.0, new_worker_id);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_heartbeat_timeout(rt: TestRuntime) -> anyhow::Result<()> {
+        let pool = FunctionRunnerWorkerPool::new(rt.clone());
+        pool.register_worker("live".to_string(), Arc::new(FakeRunner::default()));
+        pool.register_worker("dead".to_string(), Arc::new(FakeRunner::default()));
+
+        rt.advance_time(*FUNCTION_RUNNER_WORKER_HEARTBEAT_TIMEOUT / 2)
+            .await;
+        pool.heartbeat("live")?;
+        rt.advance_time(*FUNCTION_RUNNER_WORKER_HEARTBEAT_TIMEOUT / 2)
+            .await;
+        pool.heartbeat("live")?;
+
+        for module_path in ["a.js", "b.js", "c.js", "d.js"] {
+            let key = routing_key(module_path)?;
+            assert_eq!(pool.pick_worker(Some(&key))?.0, "live");
+        }
+        // The expired worker has to register again.
+        assert!(pool.heartbeat("dead").is_err());
+
+        rt.advance_time(*FUNCTION_RUNNER_WORKER_HEARTBEAT_TIMEOUT)
+            .await;
+        let err = pool.pick_worker(None).err().unwrap();
+        assert!(err.is_overloaded());
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_failing_worker_is_removed(rt: TestRuntime) -> anyhow::Result<()> {
+        let pool = FunctionRunnerWorkerPool::new(rt);
+        let runner = Arc::new(FakeRunner::default());
+        pool.register_worker("worker".to_string(), runner.clone());
+
+        // A success resets the failure count.
+        runner.failing.store(true, Ordering::SeqCst);
+        for _ in 1..*FUNCTION_RUNNER_WORKER_MAX_FAILURES {
+            assert!(analyze(&pool).await.is_err());
+        }
+        runner.failing.store(false, Ordering::SeqCst);
+        analyze(&pool).await?;
+
+        runner.failing.store(true, Ordering::SeqCst);
+        for _ in 0..*FUNCTION_RUNNER_WORKER_MAX_FAILURES {
+            assert!(analyze(&pool).await.is_err());
+        }
+        let err = analyze(&pool).await.err().unwrap();
+        assert!(err.is_overloaded());
+        assert_eq!(
+            runner.calls.load(Ordering::SeqCst),
+            2 * *FUNCTION_RUNNER_WORKER_MAX_FAILURES as usize
+        );
+        Ok(())
+    }
+}
@@ -13,6 +13,7 @@ use std::{
 };
 
 use ::metrics::Timer;
+use anyhow::Context;
 use async_trait::async_trait;
 use common::{
     auth::AuthConfig,
@@ -58,6 +59,7 @@ use common::{
         V8_THREADS,
     },
     log_lines::LogLine,
+    query::Query,
     query_journal::QueryJournal,
     runtime::{
         shutdown_and_join,
@@ -66,6 +68,7 @@ use common::{
         UnixTimestamp,
     },
     schemas::DatabaseSchema,
+    secrets::SecretsResolver,
     static_span,
     types::{
         ModuleEnvironment,
@@ -75,6 +78,8 @@ use common::{
 };
 use database::{
     shutdown_error,
+    BulkWriteOperation,
+    BulkWriteProgress,
     Transaction,
 };
 use deno_core::{
@@ -89,7 +94,11 @@ use fastrace::{
     func_path,
     future::FutureExt as _,
 };
-use file_storage::TransactionalFileStorage;
+use file_storage::{
+    ImageTransform,
+    SignedUrlOptions,
+    TransactionalFileStorage,
+};
 use futures::{
     select,
     select_biased,
@@ -121,10 +130,13 @@ use model::{
         ModuleSource,
         SourceMap,
     },
+    outbound_network_policy::types::NetworkPolicyViolation,
+    scheduled_jobs::types::ScheduleOptions,
     udf_config::types::UdfConfig,
 };
 use parking_lot::Mutex;
 use prometheus::VMHistogram;
+use rand::Rng;
 use serde_json::Value as JsonValue;
 use sync_types::CanonicalizedModulePath;
 use tokio::sync::{
@@ -147,11 +159,20 @@ use usage_tracking::FunctionUsageStats;
 use value::{
     id_v6::DeveloperDocumentId,
     identifier::Identifier,
+    ConvexValue,
 };
 use vector::PublicVectorSearchQueryResult;
 
 use crate::{
     concurrency_limiter::ConcurrencyLimiter,
+    environment::udf::{
+        determinism::nondeterminism_warning,
+        replay::{
+            ExecutionRecorder,
+            ExecutionRecording,
+            RecordingMode,
+        },
+    },
     isolate::{
         Isolate,
         IsolateHeapStats,
@@ -255,6 +276,29 @@ pub trait ActionCallbacks: Send + Sync {
         storage_id: FileStorageId,
     ) -> anyhow::Result<Option<String>>;
 
+    async fn storage_get_signed_url(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        options: SignedUrlOptions,
+    ) -> anyhow::Result<Option<String>>;
+
+    async fn storage_revoke_signed_url(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        url: String,
+    ) -> anyhow::Result<()>;
+
+    async fn storage_get_image_url(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        transform: ImageTransform,
+    ) -> anyhow::Result<Option<String>>;
+
     async fn storage_delete(
         &self,
         identity: Identity,
@@ -286,6 +330,7 @@ pub trait ActionCallbacks: Send + Sync {
         scheduled_path: CanonicalizedComponentFunctionPath,
         udf_args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<DeveloperDocumentId>;
 
@@ -302,6 +347,24 @@ pub trait ActionCallbacks: Send + Sync {
         query: JsonValue,
     ) -> anyhow::Result<(Vec<PublicVectorSearchQueryResult>, FunctionUsageStats)>;
 
+    // Streaming queries
+    async fn stream_query_batch(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        query: Query,
+        cursor: Option<String>,
+        batch_size: usize,
+    ) -> anyhow::Result<QueryStreamBatch>;
+
+    // Bulk writes
+    async fn bulk_write(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        operations: Vec<BulkWriteOperation>,
+    ) -> anyhow::Result<(BulkWriteProgress, FunctionUsageStats)>;
+
     // Components
     async fn lookup_function_handle(
         &self,
@@ -313,6 +376,31 @@ pub trait ActionCallbacks: Send + Sync {
         identity: Identity,
         path: CanonicalizedComponentFunctionPath,
     ) -> anyhow::Result<FunctionHandle>;
+
+    // Outbound network policy
+    async fn record_network_policy_violation(
+        &self,
+        identity: Identity,
+        violation: NetworkPolicyViolation,
+        environment: ModuleEnvironment,
+    ) -> anyhow::Result<()>;
+
+    // HTTP action response cache. Returns how many responses were dropped.
+    async fn invalidate_http_action_cache(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        path_prefix: Option<String>,
+    ) -> anyhow::Result<usize>;
+}
+
+/// A batch of results from an action's streaming query. Each batch is read in
+/// its own transaction; see `Database::query_stream_batch`.
+pub struct QueryStreamBatch {
+    pub page: Vec<ConvexValue>,
+    pub continue_cursor: String,
+    pub is_done: bool,
+    pub usage_stats: FunctionUsageStats,
 }
 
 pub struct UdfRequest<RT: Runtime> {
@@ -322,6 +410,7 @@ pub struct UdfRequest<RT: Runtime> {
     pub transaction: Transaction<RT>,
     pub journal: QueryJournal,
     pub context: ExecutionContext,
+    pub recording: Option<RecordingMode>,
 }
 
 pub struct HttpActionRequest<RT: Runtime> {
@@ -352,6 +441,9 @@ pub struct EnvironmentData<RT: Runtime> {
     pub system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
     pub file_storage: TransactionalFileStorage<RT>,
     pub module_loader: Arc<dyn ModuleLoader<RT>>,
+    /// Resolves secret references in user environment variables. Only used by
+    /// actions.
+    pub secrets: SecretsResolver,
 }
 
 pub struct Request<RT: Runtime> {
@@ -671,6 +763,164 @@ impl<RT: Runtime> IsolateClient<RT> {
         environment_data: EnvironmentData<RT>,
         reactor_depth: usize,
         instance_name: String,
+    ) -> anyhow::Result<(Transaction<RT>, FunctionOutcome)> {
+        self.execute_udf_inner(
+            udf_type,
+            path_and_args,
+            transaction,
+            journal,
+            context,
+            environment_data,
+            reactor_depth,
+            instance_name,
+            None,
+        )
+        .await
+    }
+
+    /// Runs a query or mutation like `execute_udf`, also returning a recording
+    /// of everything it observed that `replay_udf` can re-execute.
+    #[fastrace::trace]
+    pub async fn record_udf(
+        &self,
+        udf_type: UdfType,
+        path_and_args: ValidatedPathAndArgs,
+        transaction: Transaction<RT>,
+        journal: QueryJournal,
+        context: ExecutionContext,
+        environment_data: EnvironmentData<RT>,
+        instance_name: String,
+    ) -> anyhow::Result<(Transaction<RT>, FunctionOutcome, ExecutionRecording)> {
+        let recorder = ExecutionRecorder::new();
+        let (tx, outcome) = self
+            .execute_udf_inner(
+                udf_type,
+                path_and_args,
+                transaction,
+                journal,
+                context,
+                environment_data,
+                0,
+                instance_name,
+                Some(RecordingMode::Record(recorder.clone())),
+            )
+            .await?;
+        let recording = recorder
+            .take()
+            .context("Execution finished without a recording")?;
+        Ok((tx, outcome, recording))
+    }
+
+    /// Re-executes a recorded query or mutation, answering its syscalls from
+    /// the recording rather than running them. `transaction` is only used to
+    /// load modules and environment variables, so it should begin at the
+    /// recording's timestamp, and the returned transaction has no writes.
+    /// Fails with a system error if the execution diverges from the recording.
+    #[fastrace::trace]
+    pub async fn replay_udf(
+        &self,
+        recording: ExecutionRecording,
+        transaction: Transaction<RT>,
+        context: ExecutionContext,
+        environment_data: EnvironmentData<RT>,
+        instance_name: String,
+    ) -> anyhow::Result<(Transaction<RT>, FunctionOutcome)> {
+        self.execute_udf_inner(
+            recording.udf_type,
+            recording.path_and_args.clone(),
+            transaction,
+            QueryJournal::new(),
+            context,
+            environment_data,
+            0,
+            instance_name,
+            Some(RecordingMode::Replay(recording)),
+        )
+        .await
+    }
+
+    /// Runs a query or mutation like `execute_udf`, then runs it again on
+    /// `second_transaction`, which must be at the same snapshot, with different
+    /// seeds for `Math.random` and `Date.now`. If the executions differ, adds a
+    /// warning to the first one's log lines. Only the first execution's
+    /// transaction and outcome are returned.
+    #[fastrace::trace]
+    pub async fn execute_udf_checking_determinism(
+        &self,
+        udf_type: UdfType,
+        path_and_args: ValidatedPathAndArgs,
+        transaction: Transaction<RT>,
+        mut second_transaction: Transaction<RT>,
+        journal: QueryJournal,
+        context: ExecutionContext,
+        environment_data: EnvironmentData<RT>,
+        instance_name: String,
+    ) -> anyhow::Result<(Transaction<RT>, FunctionOutcome)> {
+        second_transaction.copy_generated_values_from(&transaction);
+        let (tx, mut outcome, recording) = self
+            .record_udf(
+                udf_type,
+                path_and_args.clone(),
+                transaction,
+                journal.clone(),
+                context.clone(),
+                environment_data.clone(),
+                instance_name.clone(),
+            )
+            .await?;
+        let recorder = ExecutionRecorder::with_seeds(
+            self.rt.rng().gen(),
+            recording.unix_timestamp + Duration::from_secs(1),
+        );
+        let second = self
+            .execute_udf_inner(
+                udf_type,
+                path_and_args,
+                second_transaction,
+                journal,
+                context,
+                environment_data,
+                0,
+                instance_name,
+                Some(RecordingMode::Record(recorder.clone())),
+            )
+            .await;
+        let (second_outcome, second_recording) = match (second, recorder.take()) {
+            (Ok((_, second_outcome)), Some(second_recording)) => (second_outcome, second_recording),
+            (Err(e), _) => {
+                tracing::warn!("Determinism check failed to rerun {udf_type}: {e:?}");
+                return Ok((tx, outcome));
+            },
+            (Ok(_), None) => anyhow::bail!("Execution finished without a recording"),
+        };
+        if let (
+            FunctionOutcome::Query(first) | FunctionOutcome::Mutation(first),
+            FunctionOutcome::Query(second) | FunctionOutcome::Mutation(second),
+        ) = (&mut outcome, &second_outcome)
+            && let Some(warning) =
+                nondeterminism_warning((first, &recording), (second, &second_recording))
+        {
+            first.log_lines.push(LogLine::new_system_log_line(
+                warning.level,
+                warning.messages,
+                self.rt.unix_timestamp(),
+                warning.system_log_metadata,
+            ));
+        }
+        Ok((tx, outcome))
+    }
+
+    async fn execute_udf_inner(
+        &self,
+        udf_type: UdfType,
+        path_and_args: ValidatedPathAndArgs,
+        transaction: Transaction<RT>,
+        journal: QueryJournal,
+        context: ExecutionContext,
+        environment_data: EnvironmentData<RT>,
+        reactor_depth: usize,
+        instance_name: String,
+        recording: Option<RecordingMode>,
     ) -> anyhow::Result<(Transaction<RT>, FunctionOutcome)> {
         let (tx, rx) = oneshot::channel();
         let request = RequestType::Udf {
@@ -681,6 +931,7 @@ impl<RT: Runtime> IsolateClient<RT> {
                 transaction,
                 journal,
                 context,
+                recording,
             },
             environment_data,
             response: tx,
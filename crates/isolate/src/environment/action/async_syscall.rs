@@ -7,11 +7,17 @@ use common::{
         ComponentId,
         Reference,
     },
+    knobs::ACTION_QUERY_STREAM_MAX_BATCH_SIZE,
+    query::Query,
     runtime::{
         Runtime,
         UnixTimestamp,
     },
 };
+use database::{
+    BulkWriteFailure,
+    BulkWriteOperation,
+};
 use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
@@ -21,7 +27,14 @@ use model::{
         auth::propagate_component_auth,
         handles::function_handle_not_found,
     },
-    file_storage::FileStorageId,
+    file_storage::{
+        types::FileEncryptionMetadata,
+        FileStorageId,
+    },
+    scheduled_jobs::types::{
+        ScheduleOptions,
+        ScheduleOptionsJson,
+    },
 };
 use serde::{
     Deserialize,
@@ -39,7 +52,17 @@ use vector::{
 
 use super::task_executor::TaskExecutor;
 use crate::{
+    client::QueryStreamBatch,
     environment::helpers::{
+        image_urls::parse_get_image_url_args,
+        service_tokens::{
+            self,
+            MintServiceTokenArgs,
+        },
+        signed_urls::{
+            parse_get_signed_url_args,
+            parse_revoke_signed_url_args,
+        },
         with_argument_error,
         ArgName,
     },
@@ -64,14 +87,27 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 "1.0/actions/schedule" => self.async_syscall_schedule(args).await?,
                 "1.0/actions/cancel_job" => self.async_syscall_cancel_job(args).await?,
                 "1.0/actions/vectorSearch" => self.async_syscall_vectorSearch(args).await?,
+                "1.0/actions/queryStreamBatch" => self.async_syscall_queryStreamBatch(args).await?,
+                "1.0/actions/bulkWrite" => self.async_syscall_bulkWrite(args).await?,
                 "1.0/getUserIdentity" => self.async_syscall_getUserIdentity(args).await?,
+                "1.0/getRequestContext" => self.async_syscall_getRequestContext(args).await?,
                 "1.0/storageDelete" => self.async_syscall_storageDelete(args).await?,
                 "1.0/storageGetMetadata" => self.async_syscall_storageGetMetadata(args).await?,
                 "1.0/storageGenerateUploadUrl" => {
                     self.async_syscall_storageGenerateUploadUrl(args).await?
                 },
                 "1.0/storageGetUrl" => self.async_syscall_storageGetUrl(args).await?,
+                "1.0/storageGetSignedUrl" => self.async_syscall_storageGetSignedUrl(args).await?,
+                "1.0/storageRevokeSignedUrl" => {
+                    self.async_syscall_storageRevokeSignedUrl(args).await?
+                },
+                "1.0/storageGetImageUrl" => self.async_syscall_storageGetImageUrl(args).await?,
                 "1.0/createFunctionHandle" => self.async_syscall_createFunctionHandle(args).await?,
+                "1.0/mintServiceToken" => self.async_syscall_mintServiceToken(args).await?,
+                "1.0/verifyServiceToken" => self.async_syscall_verifyServiceToken(args).await?,
+                "1.0/invalidateHttpActionCache" => {
+                    self.async_syscall_invalidateHttpActionCache(args).await?
+                },
                 _ => {
                     anyhow::bail!(ErrorMetadata::bad_request(
                         "UnknownAsyncOperation",
@@ -241,6 +277,8 @@ impl<RT: Runtime> TaskExecutor<RT> {
             function_handle: Option<String>,
             ts: f64,
             args: UdfArgsJson,
+            #[serde(flatten)]
+            options: ScheduleOptionsJson,
         }
 
         let ScheduleArgs {
@@ -249,7 +287,9 @@ impl<RT: Runtime> TaskExecutor<RT> {
             function_handle,
             ts,
             args,
+            options,
         }: ScheduleArgs = with_argument_error("scheduler", || Ok(serde_json::from_value(args)?))?;
+        let options = ScheduleOptions::try_from(options)?;
         let path = match function_handle {
             Some(h) => {
                 let handle: FunctionHandle = with_argument_error("scheduler", || h.parse())?;
@@ -271,6 +311,7 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 path,
                 args.into_arg_vec(),
                 scheduled_ts,
+                options,
                 self.context.clone(),
             )
             .await?;
@@ -316,11 +357,179 @@ impl<RT: Runtime> TaskExecutor<RT> {
         Ok(json!({ "results": results }))
     }
 
+    #[convex_macro::instrument_future]
+    async fn async_syscall_queryStreamBatch(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct QueryStreamBatchArgs {
+            query: JsonValue,
+            cursor: Option<String>,
+            batch_size: usize,
+        }
+        let (query, cursor, batch_size) = with_argument_error("queryStreamBatch", || {
+            let args: QueryStreamBatchArgs = serde_json::from_value(args)?;
+            let query = Query::try_from(args.query).context(ArgName("query"))?;
+            anyhow::ensure!(
+                args.batch_size > 0 && args.batch_size <= *ACTION_QUERY_STREAM_MAX_BATCH_SIZE,
+                "batchSize must be between 1 and {}",
+                *ACTION_QUERY_STREAM_MAX_BATCH_SIZE
+            );
+            Ok((query, args.cursor, args.batch_size))
+        })?;
+        let QueryStreamBatch {
+            page,
+            continue_cursor,
+            is_done,
+            usage_stats,
+        } = self
+            .action_callbacks
+            .stream_query_batch(
+                self.identity.clone(),
+                self.component_id(),
+                query,
+                cursor,
+                batch_size,
+            )
+            .await?;
+        self.usage_tracker.add(usage_stats);
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct QueryStreamBatchResult {
+            page: Vec<JsonValue>,
+            continue_cursor: String,
+            is_done: bool,
+        }
+        Ok(serde_json::to_value(QueryStreamBatchResult {
+            page: page.into_iter().map(JsonValue::from).collect(),
+            continue_cursor,
+            is_done,
+        })?)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn async_syscall_bulkWrite(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct BulkWriteArgs {
+            operations: Vec<JsonValue>,
+        }
+        let operations = with_argument_error("bulkWrite", || {
+            let args: BulkWriteArgs = serde_json::from_value(args)?;
+            args.operations
+                .into_iter()
+                .map(BulkWriteOperation::try_from)
+                .collect::<anyhow::Result<Vec<_>>>()
+                .context(ArgName("operations"))
+        })?;
+        let result = self
+            .action_callbacks
+            .bulk_write(self.identity.clone(), self.component_id(), operations)
+            .await;
+        // A developer error partway through leaves earlier chunks committed, so
+        // report what was committed along with the error rather than throwing.
+        let (progress, error) = match result {
+            Ok((progress, usage_stats)) => {
+                self.usage_tracker.add(usage_stats);
+                (progress, None)
+            },
+            Err(e) if e.is_deterministic_user_error() => {
+                let Some(failure) = e.downcast_ref::<BulkWriteFailure>() else {
+                    return Err(e);
+                };
+                (failure.progress.clone(), Some(e.user_facing_message()))
+            },
+            Err(e) => return Err(e),
+        };
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct BulkWriteResult {
+            chunks_committed: usize,
+            operations_committed: usize,
+            inserted_ids: Vec<Option<String>>,
+            error: Option<String>,
+        }
+        Ok(serde_json::to_value(BulkWriteResult {
+            chunks_committed: progress.chunks_committed,
+            operations_committed: progress.operations_committed,
+            inserted_ids: progress
+                .inserted_ids
+                .into_iter()
+                .map(|id| id.map(|id| id.encode()))
+                .collect(),
+            error,
+        })?)
+    }
+
     #[convex_macro::instrument_future]
     async fn async_syscall_getUserIdentity(&self, _args: JsonValue) -> anyhow::Result<JsonValue> {
         self.user_identity()
     }
 
+    #[convex_macro::instrument_future]
+    async fn async_syscall_getRequestContext(&self, _args: JsonValue) -> anyhow::Result<JsonValue> {
+        Ok(JsonValue::from(self.context.request_context.clone()))
+    }
+
+    async fn async_syscall_mintServiceToken(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let args = MintServiceTokenArgs::parse(args)?;
+        let mut storage_uuids = vec![];
+        for storage_id in args.storage_ids()? {
+            let (_, entry) = self
+                .action_callbacks
+                .storage_get_file_entry(
+                    self.identity.clone(),
+                    self.component_id(),
+                    storage_id.clone(),
+                )
+                .await?
+                .context(ErrorMetadata::bad_request(
+                    "FileNotFound",
+                    format!("File {storage_id} not found"),
+                ))?;
+            storage_uuids.push(entry.storage_id);
+        }
+        let token = args.into_service_token(
+            self.user_identity_attributes(),
+            self.component_id(),
+            self.rt.unix_timestamp(),
+            storage_uuids,
+        )?;
+        Ok(JsonValue::String(
+            self.key_broker.issue_service_token(token),
+        ))
+    }
+
+    async fn async_syscall_verifyServiceToken(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        service_tokens::verify_service_token(
+            &self.key_broker,
+            self.component_id(),
+            self.rt.unix_timestamp(),
+            args,
+        )
+    }
+
+    async fn async_syscall_invalidateHttpActionCache(
+        &self,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct InvalidateHttpActionCacheArgs {
+            path_prefix: Option<String>,
+        }
+        let path_prefix = with_argument_error("invalidateHttpActionCache", || {
+            let InvalidateHttpActionCacheArgs { path_prefix } = serde_json::from_value(args)?;
+            Ok(path_prefix)
+        })?;
+        let dropped = self
+            .action_callbacks
+            .invalidate_http_action_cache(self.identity.clone(), self.component_id(), path_prefix)
+            .await?;
+        Ok(dropped.into())
+    }
+
     async fn async_syscall_storageGenerateUploadUrl(
         &self,
         _args: JsonValue,
@@ -351,6 +560,51 @@ impl<RT: Runtime> TaskExecutor<RT> {
         Ok(url.into())
     }
 
+    #[convex_macro::instrument_future]
+    async fn async_syscall_storageGetSignedUrl(
+        &self,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        let (storage_id, options) = parse_get_signed_url_args(args)?;
+        let url = self
+            .action_callbacks
+            .storage_get_signed_url(
+                self.identity.clone(),
+                self.component_id(),
+                storage_id,
+                options,
+            )
+            .await?;
+        Ok(url.into())
+    }
+
+    #[convex_macro::instrument_future]
+    async fn async_syscall_storageRevokeSignedUrl(
+        &self,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        let url = parse_revoke_signed_url_args(args)?;
+        self.action_callbacks
+            .storage_revoke_signed_url(self.identity.clone(), self.component_id(), url)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn async_syscall_storageGetImageUrl(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let (storage_id, transform) = parse_get_image_url_args(args)?;
+        let url = self
+            .action_callbacks
+            .storage_get_image_url(
+                self.identity.clone(),
+                self.component_id(),
+                storage_id,
+                transform,
+            )
+            .await?;
+        Ok(url.into())
+    }
+
     #[convex_macro::instrument_future]
     async fn async_syscall_storageDelete(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
         #[derive(Deserialize)]
@@ -389,6 +643,8 @@ impl<RT: Runtime> TaskExecutor<RT> {
             sha256: String,
             size: i64,
             content_type: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            encryption: Option<FileEncryptionMetadata>,
         }
         let file_metadata = self
             .action_callbacks
@@ -402,6 +658,7 @@ impl<RT: Runtime> TaskExecutor<RT> {
                     sha256: entry.sha256.as_hex(),
                     size: entry.size,
                     content_type: entry.content_type,
+                    encryption: entry.encryption,
                 }
             });
         Ok(serde_json::to_value(file_metadata)?)
@@ -7,8 +7,15 @@ use common::{
         HttpResponseStream,
     },
     runtime::Runtime,
+    types::ModuleEnvironment,
 };
 use errors::ErrorMetadata;
+use fastrace::local::LocalSpan;
+use model::outbound_network_policy::types::NetworkPolicyViolation;
+use url::{
+    Host,
+    Url,
+};
 
 use super::task_executor::TaskExecutor;
 use crate::{
@@ -22,6 +29,7 @@ use crate::{
 };
 
 impl<RT: Runtime> TaskExecutor<RT> {
+    #[fastrace::trace]
     #[convex_macro::instrument_future]
     pub async fn run_fetch(
         &self,
@@ -32,6 +40,12 @@ impl<RT: Runtime> TaskExecutor<RT> {
         let t = metrics::udf_fetch_timer();
         // Only log origin because query params might contain some PII.
         let origin = request.url.origin().unicode_serialization();
+        LocalSpan::add_properties(|| {
+            [
+                ("http.request.method", request.method.to_string()),
+                ("server.origin", origin.clone()),
+            ]
+        });
         let result = self.run_fetch_inner(request).await;
         let initial_response_time = t.elapsed();
         let (body, response) = match result
@@ -66,9 +80,51 @@ impl<RT: Runtime> TaskExecutor<RT> {
         &self,
         request: HttpRequestStream,
     ) -> anyhow::Result<HttpResponseStream> {
+        if let Err(violation) = self.check_network_policy(&request.url).await {
+            let message = violation.to_string();
+            if let Err(e) = self
+                .action_callbacks
+                .record_network_policy_violation(
+                    self.identity.clone(),
+                    violation,
+                    ModuleEnvironment::Isolate,
+                )
+                .await
+            {
+                tracing::error!("Failed to record outbound network policy violation: {e:#}");
+            }
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "OutboundNetworkPolicyViolation",
+                message
+            ));
+        }
         self.fetch_client.fetch(request).await
     }
 
+    /// Checks the request's host against the deployment's outbound network
+    /// policy. Unless private IPs are allowed, also resolves the host and
+    /// checks each of its addresses, so a public hostname can't be used to
+    /// reach an internal one.
+    async fn check_network_policy(&self, url: &Url) -> Result<(), NetworkPolicyViolation> {
+        let Some(host) = url.host_str() else {
+            return Ok(());
+        };
+        let policy = self.network_policy.lock().clone();
+        policy.check_host(host)?;
+        if policy.allow_private_ips || url.host().is_some_and(|h| !matches!(h, Host::Domain(_))) {
+            return Ok(());
+        }
+        let port = url.port_or_known_default().unwrap_or(443);
+        // If the lookup fails, let the fetch client report the error.
+        let Ok(addrs) = tokio::net::lookup_host((host, port)).await else {
+            return Ok(());
+        };
+        for addr in addrs {
+            policy.check_ip(host, addr.ip())?;
+        }
+        Ok(())
+    }
+
     fn log_fetch_request(
         t: StatusTimer,
         origin: String,
@@ -25,6 +25,7 @@ use common::{
         RoutedHttpPath,
     },
     knobs::{
+        ACTION_CPU_TIMEOUT,
         ACTION_USER_TIMEOUT,
         FUNCTION_MAX_ARGS_SIZE,
         FUNCTION_MAX_RESULT_SIZE,
@@ -74,6 +75,7 @@ use model::{
         module_versions::FullModuleSource,
         user_error::FunctionNotFoundError,
     },
+    outbound_network_policy::types::OutboundNetworkPolicy,
 };
 use parking_lot::Mutex;
 use rand_chacha::ChaCha12Rng;
@@ -97,6 +99,7 @@ use udf::{
     SyscallTrace,
     HTTP_ACTION_BODY_LIMIT,
 };
+use usage_tracking::FunctionUsageTracker;
 use value::{
     heap_size::HeapSize,
     ConvexArray,
@@ -223,6 +226,7 @@ pub struct ActionEnvironment<RT: Runtime> {
     phase: ActionPhase<RT>,
     syscall_trace: Arc<Mutex<SyscallTrace>>,
     heap_stats: SharedIsolateHeapStats,
+    usage_tracker: FunctionUsageTracker,
 }
 
 impl<RT: Runtime> ActionEnvironment<RT> {
@@ -234,6 +238,7 @@ impl<RT: Runtime> ActionEnvironment<RT> {
             system_env_vars,
             file_storage,
             module_loader,
+            secrets,
         }: EnvironmentData<RT>,
         identity: Identity,
         transaction: Transaction<RT>,
@@ -248,6 +253,8 @@ impl<RT: Runtime> ActionEnvironment<RT> {
         let (task_retval_sender, task_responses) = mpsc::unbounded_channel();
         let resources = Arc::new(Mutex::new(BTreeMap::new()));
         let function_handles = Arc::new(Mutex::new(BTreeMap::new()));
+        let network_policy = Arc::new(Mutex::new(OutboundNetworkPolicy::default()));
+        let usage_tracker = transaction.usage_tracker.clone();
         let task_executor = TaskExecutor {
             rt: rt.clone(),
             identity: identity.clone(),
@@ -259,11 +266,12 @@ impl<RT: Runtime> ActionEnvironment<RT> {
             key_broker,
             task_order: Default::default(),
             task_retval_sender,
-            usage_tracker: transaction.usage_tracker.clone(),
+            usage_tracker: usage_tracker.clone(),
             context,
             resources: resources.clone(),
             component_id: component,
             function_handles: function_handles.clone(),
+            network_policy: network_policy.clone(),
         };
         let (pending_task_sender, pending_task_receiver) = spsc::unbounded_channel();
         let running_tasks = rt.spawn("task_executor", task_executor.go(pending_task_receiver));
@@ -285,11 +293,14 @@ impl<RT: Runtime> ActionEnvironment<RT> {
                 transaction,
                 module_loader,
                 system_env_vars,
+                secrets,
                 resources,
                 function_handles,
+                network_policy,
             ),
             syscall_trace,
             heap_stats,
+            usage_tracker,
         }
     }
 
@@ -349,6 +360,9 @@ impl<RT: Runtime> ActionEnvironment<RT> {
 
         let execution_time;
         (self, execution_time) = isolate_context.take_environment();
+        if let Some(cpu_time) = execution_time.cpu_time {
+            self.usage_tracker.track_cpu_time(cpu_time);
+        }
         let http_response_streamer = self
             .http_response_streamer
             .as_ref()
@@ -658,6 +672,9 @@ impl<RT: Runtime> ActionEnvironment<RT> {
         }
         let execution_time;
         (self, execution_time) = isolate_context.take_environment();
+        if let Some(cpu_time) = execution_time.cpu_time {
+            self.usage_tracker.track_cpu_time(cpu_time);
+        }
         let (path, arguments, udf_server_version) = request_params.path_and_args.consume();
         self.add_warnings_to_log_lines_action(
             execution_time,
@@ -1368,7 +1385,7 @@ impl<RT: Runtime> IsolateEnvironment<RT> for ActionEnvironment<RT> {
         self.start_task(TaskRequestEnum::AsyncOp(request), resolver)
     }
 
-    fn record_heap_stats(&self, mut isolate_stats: IsolateHeapStats) {
+    fn record_heap_stats(&mut self, mut isolate_stats: IsolateHeapStats) {
         // Add the memory allocated by the environment itself.
         isolate_stats.environment_heap_size = self.syscall_trace.lock().heap_size();
         self.heap_stats.store(isolate_stats);
@@ -1381,4 +1398,8 @@ impl<RT: Runtime> IsolateEnvironment<RT> for ActionEnvironment<RT> {
     fn system_timeout(&self) -> std::time::Duration {
         *V8_ACTION_SYSTEM_TIMEOUT
     }
+
+    fn cpu_timeout(&self) -> Option<std::time::Duration> {
+        Some(*ACTION_CPU_TIMEOUT)
+    }
 }
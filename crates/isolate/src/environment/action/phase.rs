@@ -17,6 +17,7 @@ use common::{
         Runtime,
         UnixTimestamp,
     },
+    secrets::SecretsResolver,
     types::ModuleEnvironment,
 };
 use database::{
@@ -42,6 +43,10 @@ use model::{
         types::ModuleMetadata,
         ModuleModel,
     },
+    outbound_network_policy::{
+        types::OutboundNetworkPolicy,
+        OutboundNetworkPolicyModel,
+    },
     source_packages::SourcePackageModel,
     udf_config::UdfConfigModel,
 };
@@ -91,8 +96,10 @@ enum ActionPreloaded<RT: Runtime> {
         tx: Transaction<RT>,
         module_loader: Arc<dyn ModuleLoader<RT>>,
         system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        secrets: SecretsResolver,
         resources: Arc<Mutex<BTreeMap<Reference, Resource>>>,
         function_handles: Arc<Mutex<BTreeMap<CanonicalizedComponentFunctionPath, FunctionHandle>>>,
+        network_policy: Arc<Mutex<OutboundNetworkPolicy>>,
     },
     Preloading,
     Ready {
@@ -111,8 +118,10 @@ impl<RT: Runtime> ActionPhase<RT> {
         tx: Transaction<RT>,
         module_loader: Arc<dyn ModuleLoader<RT>>,
         system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        secrets: SecretsResolver,
         resources: Arc<Mutex<BTreeMap<Reference, Resource>>>,
         function_handles: Arc<Mutex<BTreeMap<CanonicalizedComponentFunctionPath, FunctionHandle>>>,
+        network_policy: Arc<Mutex<OutboundNetworkPolicy>>,
     ) -> Self {
         Self {
             component,
@@ -122,8 +131,10 @@ impl<RT: Runtime> ActionPhase<RT> {
                 tx,
                 module_loader,
                 system_env_vars,
+                secrets,
                 resources,
                 function_handles,
+                network_policy,
             },
         }
     }
@@ -141,8 +152,10 @@ impl<RT: Runtime> ActionPhase<RT> {
             mut tx,
             module_loader,
             system_env_vars,
+            secrets,
             resources,
             function_handles,
+            network_policy,
         } = preloaded
         else {
             anyhow::bail!("ActionPhase initialized twice");
@@ -202,11 +215,10 @@ impl<RT: Runtime> ActionPhase<RT> {
         // Environment variables are not accessible in component functions.
         let env_vars = if self.component.is_root() {
             let mut env_vars = system_env_vars;
-            let user_env_vars = with_release_permit(
-                timeout,
-                permit_slot,
-                EnvironmentVariablesModel::new(&mut tx).get_all(),
-            )
+            let user_env_vars = with_release_permit(timeout, permit_slot, async {
+                let user_env_vars = EnvironmentVariablesModel::new(&mut tx).get_all().await?;
+                secrets.resolve_env_vars(user_env_vars).await
+            })
             .await?;
             env_vars.extend(user_env_vars);
             env_vars
@@ -237,6 +249,16 @@ impl<RT: Runtime> ActionPhase<RT> {
             *function_handles.lock() = handles;
         }
 
+        {
+            let policy = with_release_permit(
+                timeout,
+                permit_slot,
+                OutboundNetworkPolicyModel::new(&mut tx).get(),
+            )
+            .await?;
+            *network_policy.lock() = policy;
+        }
+
         self.preloaded = ActionPreloaded::Ready {
             modules,
             env_vars,
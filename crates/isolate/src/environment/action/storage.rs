@@ -19,7 +19,10 @@ use headers::{
     Header,
     HeaderValue,
 };
-use model::file_storage::FileStorageId;
+use model::file_storage::{
+    types::FileEncryptionMetadata,
+    FileStorageId,
+};
 use usage_tracking::StorageUsageTracker;
 use value::id_v6::DeveloperDocumentId;
 
@@ -45,6 +48,7 @@ impl<RT: Runtime> TaskExecutor<RT> {
         content_type: Option<String>,
         content_length: Option<String>,
         digest: Option<String>,
+        encryption: Option<FileEncryptionMetadata>,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let content_length = content_length
             .map(|c| -> anyhow::Result<headers::ContentLength> {
@@ -66,8 +70,11 @@ impl<RT: Runtime> TaskExecutor<RT> {
             })
             .transpose()
             .map_err(|e| ErrorMetadata::bad_request("InvalidDigestHeader", e.to_string()))?;
+        if let Some(encryption) = &encryption {
+            encryption.validate()?;
+        }
 
-        let entry = self
+        let mut entry = self
             .file_storage
             .upload_file(
                 content_length,
@@ -76,6 +83,7 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 digest,
             )
             .await?;
+        entry.encryption = encryption;
         let storage_id = entry.storage_id.clone();
         let size = entry.size;
         let sha256 = entry.sha256.clone();
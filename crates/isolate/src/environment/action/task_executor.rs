@@ -34,8 +34,12 @@ use futures::{
 use keybroker::{
     Identity,
     KeyBroker,
+    UserIdentityAttributes,
+};
+use model::{
+    config::module_loader::ModuleLoader,
+    outbound_network_policy::types::OutboundNetworkPolicy,
 };
-use model::config::module_loader::ModuleLoader;
 use parking_lot::Mutex;
 use serde_json::Value as JsonValue;
 use tokio::sync::mpsc;
@@ -79,6 +83,7 @@ pub struct TaskExecutor<RT: Runtime> {
     pub resources: Arc<Mutex<BTreeMap<Reference, Resource>>>,
     pub component_id: ComponentId,
     pub function_handles: Arc<Mutex<BTreeMap<CanonicalizedComponentFunctionPath, FunctionHandle>>>,
+    pub network_policy: Arc<Mutex<OutboundNetworkPolicy>>,
 }
 
 impl<RT: Runtime> TaskExecutor<RT> {
@@ -152,8 +157,15 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 content_type,
                 content_length,
                 digest,
+                encryption,
             }) => self
-                .run_storage_store(body_stream, content_type, content_length, digest)
+                .run_storage_store(
+                    body_stream,
+                    content_type,
+                    content_length,
+                    digest,
+                    encryption,
+                )
                 .await
                 .map(TaskResponseEnum::StorageStore),
             TaskRequestEnum::AsyncOp(AsyncOpRequest::StorageGet {
@@ -183,13 +195,16 @@ impl<RT: Runtime> TaskExecutor<RT> {
         Ok(until)
     }
 
-    pub fn user_identity(&self) -> anyhow::Result<JsonValue> {
-        let user_identity = match self.identity.clone() {
+    pub fn user_identity_attributes(&self) -> Option<UserIdentityAttributes> {
+        match self.identity.clone() {
             Identity::User(identity) => Some(identity.attributes),
             Identity::ActingUser(_, identity) => Some(identity),
             _ => None,
-        };
-        if let Some(user_identity) = user_identity {
+        }
+    }
+
+    pub fn user_identity(&self) -> anyhow::Result<JsonValue> {
+        if let Some(user_identity) = self.user_identity_attributes() {
             return user_identity.try_into();
         }
         Ok(JsonValue::Null)
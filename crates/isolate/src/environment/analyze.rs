@@ -52,6 +52,7 @@ use model::{
     modules::{
         function_validators::{
             ArgsValidator,
+            HttpBodyValidator,
             ReturnsValidator,
         },
         module_versions::{
@@ -62,6 +63,7 @@ use model::{
             AnalyzedModule,
             AnalyzedSourcePosition,
             FullModuleSource,
+            QueryCacheKey,
             Visibility,
         },
         user_error::{
@@ -368,6 +370,7 @@ impl AnalyzeEnvironment {
                                 ),
                                 custom_data: None,
                                 frames: e.frames,
+                                context: e.context,
                             }))
                         },
                         Err(e) => return Err(e),
@@ -506,6 +509,57 @@ fn parse_args_validator<'s, RT: Runtime>(
     Ok(Ok(args))
 }
 
+/// HTTP actions declare a body validator with `exportBody`, which is
+/// undefined for actions that don't validate their body.
+fn parse_http_body_validator<RT: Runtime>(
+    scope: &mut ExecutionScope<RT, AnalyzeEnvironment>,
+    function: v8::Local<v8::Object>,
+    route_for_error: String,
+) -> anyhow::Result<Result<HttpBodyValidator, JsError>> {
+    let export_body = strings::exportBody.create(scope)?;
+    let body = match function.get(scope, export_body.into()) {
+        Some(export_body_value) if export_body_value.is_function() => {
+            let export_body_function: v8::Local<v8::Function> = export_body_value.try_into()?;
+            let result_v8 = scope
+                .with_try_catch(|s| export_body_function.call(s, function.into(), &[]))??
+                .context("Missing return value from successful function call")?;
+            let Ok(result_v8_str) = v8::Local::<v8::String>::try_from(result_v8) else {
+                let message = format!(
+                    "Invalid exportBody return value: the HTTP action for {route_for_error} \
+                     didn't return a string from exportBody()."
+                );
+                return Ok(Err(JsError::from_message(message)));
+            };
+            let result_str = helpers::to_rust_string(scope, &result_v8_str)?;
+            let parsed = serde_json::from_str::<JsonValue>(&result_str)
+                .map_err(anyhow::Error::from)
+                .and_then(HttpBodyValidator::try_from);
+            match parsed {
+                Ok(validator) => validator,
+                Err(parse_error) => {
+                    let message = format!(
+                        "Invalid body validator for the HTTP action for {route_for_error}: \
+                         {parse_error}"
+                    );
+                    return Ok(Err(JsError::from_message(message)));
+                },
+            }
+        },
+        Some(export_body_value) if export_body_value.is_undefined() => {
+            HttpBodyValidator::Unvalidated
+        },
+        Some(_) => {
+            let message = format!(
+                "The HTTP action for {route_for_error} has an exportBody that is not a function \
+                 or `undefined`."
+            );
+            return Ok(Err(JsError::from_message(message)));
+        },
+        None => HttpBodyValidator::Unvalidated,
+    };
+    Ok(Ok(body))
+}
+
 #[fastrace::trace]
 fn parse_returns_validator<'s, RT: Runtime>(
     scope: &mut ExecutionScope<RT, AnalyzeEnvironment>,
@@ -568,6 +622,57 @@ fn parse_returns_validator<'s, RT: Runtime>(
     };
     Ok(Ok(returns))
 }
+
+/// Call `exportCacheKey`, if a query defines it, to get the identity claims its
+/// cached results can be shared on.
+#[fastrace::trace]
+fn parse_cache_key<RT: Runtime>(
+    scope: &mut ExecutionScope<RT, AnalyzeEnvironment>,
+    function: v8::Local<v8::Object>,
+    function_identifier_for_error: String,
+) -> anyhow::Result<Result<Option<QueryCacheKey>, JsError>> {
+    let export_cache_key = strings::exportCacheKey.create(scope)?;
+    let cache_key = match function.get(scope, export_cache_key.into()) {
+        Some(export_cache_key_value) if export_cache_key_value.is_function() => {
+            let export_cache_key_function: v8::Local<v8::Function> =
+                export_cache_key_value.try_into()?;
+            let result_v8 = scope
+                .with_try_catch(|s| export_cache_key_function.call(s, function.into(), &[]))??
+                .context("Missing return value from successful function call")?;
+            let Ok(result_v8_str) = v8::Local::<v8::String>::try_from(result_v8) else {
+                let message = format!(
+                    "Invalid exportCacheKey return value: \
+                     {function_identifier_for_error}.exportCacheKey() didn't return a string."
+                );
+                return Ok(Err(JsError::from_message(message)));
+            };
+            let result_str = helpers::to_rust_string(scope, &result_v8_str)?;
+            let cache_key = serde_json::from_str::<JsonValue>(&result_str)
+                .map_err(anyhow::Error::from)
+                .and_then(QueryCacheKey::try_from);
+            match cache_key {
+                Ok(cache_key) => Some(cache_key),
+                Err(parse_error) => {
+                    let message = format!(
+                        "Invalid JSON returned from \
+                         {function_identifier_for_error}.exportCacheKey(): {parse_error}"
+                    );
+                    return Ok(Err(JsError::from_message(message)));
+                },
+            }
+        },
+        Some(export_cache_key_value) if export_cache_key_value.is_undefined() => None,
+        Some(_) => {
+            let message = format!(
+                "{function_identifier_for_error}.exportCacheKey is not a function or `undefined`."
+            );
+            return Ok(Err(JsError::from_message(message)));
+        },
+        None => None,
+    };
+    Ok(Ok(cache_key))
+}
+
 #[fastrace::trace]
 fn udf_analyze<RT: Runtime>(
     scope: &mut ExecutionScope<RT, AnalyzeEnvironment>,
@@ -634,6 +739,13 @@ fn udf_analyze<RT: Runtime>(
         let returns =
             parse_returns_validator(scope, function, format!("{module_path:?}:{property_name}"))??;
 
+        let cache_key = match udf_type {
+            UdfType::Query => {
+                parse_cache_key(scope, function, format!("{module_path:?}:{property_name}"))??
+            },
+            UdfType::Mutation | UdfType::Action | UdfType::HttpAction => None,
+        };
+
         let visibility = match (is_public, is_internal) {
             (true, false) => Some(Visibility::Public),
             (false, true) => Some(Visibility::Internal),
@@ -700,28 +812,34 @@ fn udf_analyze<RT: Runtime>(
             && fn_canon_path.as_str() == module_path.as_str()
         {
             // Source map is valid; proceed with mapping in original source map
-            functions.push(AnalyzedFunction::new(
-                canonicalized_name.clone(),
-                Some(AnalyzedSourcePosition {
-                    path: fn_canon_path,
-                    start_lineno: token.get_src_line(),
-                    start_col: token.get_src_col(),
-                }),
-                udf_type,
-                visibility.clone(),
-                args.clone(),
-                returns.clone(),
-            )?);
+            functions.push(
+                AnalyzedFunction::new(
+                    canonicalized_name.clone(),
+                    Some(AnalyzedSourcePosition {
+                        path: fn_canon_path,
+                        start_lineno: token.get_src_line(),
+                        start_col: token.get_src_col(),
+                    }),
+                    udf_type,
+                    visibility.clone(),
+                    args.clone(),
+                    returns.clone(),
+                )?
+                .with_cache_key(cache_key.clone()),
+            );
         } else {
             // If there is no valid source map, push a function without a position
-            functions.push(AnalyzedFunction::new(
-                canonicalized_name.clone(),
-                None,
-                udf_type,
-                visibility.clone(),
-                args.clone(),
-                returns.clone(),
-            )?);
+            functions.push(
+                AnalyzedFunction::new(
+                    canonicalized_name.clone(),
+                    None,
+                    udf_type,
+                    visibility.clone(),
+                    args.clone(),
+                    returns.clone(),
+                )?
+                .with_cache_key(cache_key.clone()),
+            );
 
             // Log reason for fallback
             if fn_canon_path.as_str() != module_path.as_str() {
@@ -955,12 +1073,20 @@ fn http_analyze<RT: Runtime>(
         if source_pos.is_none() {
             tracing::warn!("Failed to resolve {module_path:?}:{path}");
         }
+        let body = match parse_http_body_validator(scope, function, format!("{method} {path}"))? {
+            Ok(body) => body,
+            Err(e) => return Ok(Err(e)),
+        };
         http_routes.push(AnalyzedHttpRoute {
             route: HttpActionRoute {
                 path: path.clone(),
                 method,
             },
             pos: source_pos,
+            body_str: match body {
+                HttpBodyValidator::Unvalidated => None,
+                body => Some(serde_json::to_string(&JsonValue::try_from(body)?)?),
+            },
         });
     }
 
@@ -6,6 +6,7 @@ use common::{
     sync::spsc,
 };
 use futures::stream::BoxStream;
+use model::file_storage::types::FileEncryptionMetadata;
 
 pub enum AsyncOpRequest {
     Fetch {
@@ -25,6 +26,7 @@ pub enum AsyncOpRequest {
         content_type: Option<String>,
         content_length: Option<String>,
         digest: Option<String>,
+        encryption: Option<FileEncryptionMetadata>,
     },
     StorageGet {
         storage_id: String,
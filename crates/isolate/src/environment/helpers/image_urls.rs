@@ -0,0 +1,81 @@
+//! Argument handling for `storage.getImageUrl`, shared by queries, mutations,
+//! V8 actions and Node actions.
+
+use anyhow::Context;
+use file_storage::{
+    CropRect,
+    ImageTransform,
+};
+use model::file_storage::FileStorageId;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+use super::{
+    with_argument_error,
+    ArgName,
+};
+
+#[derive(Deserialize)]
+struct CropArgs {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetImageUrlArgs {
+    storage_id: String,
+    crop: Option<CropArgs>,
+    width: Option<u32>,
+    height: Option<u32>,
+    fit: Option<String>,
+    format: Option<String>,
+    quality: Option<u8>,
+}
+
+pub fn parse_get_image_url_args(
+    args: JsonValue,
+) -> anyhow::Result<(FileStorageId, ImageTransform)> {
+    with_argument_error("storage.getImageUrl", || {
+        let GetImageUrlArgs {
+            storage_id,
+            crop,
+            width,
+            height,
+            fit,
+            format,
+            quality,
+        } = serde_json::from_value(args)?;
+        let storage_id = storage_id.parse().context(ArgName("storageId"))?;
+        let transform = ImageTransform {
+            crop: crop.map(
+                |CropArgs {
+                     x,
+                     y,
+                     width,
+                     height,
+                 }| CropRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                },
+            ),
+            width,
+            height,
+            fit: fit
+                .map(|fit| fit.parse())
+                .transpose()
+                .context(ArgName("fit"))?
+                .unwrap_or_default(),
+            format: format
+                .map(|format| format.parse())
+                .transpose()
+                .context(ArgName("format"))?,
+            quality,
+        };
+        Ok((storage_id, transform))
+    })
+}
@@ -1,6 +1,9 @@
+pub mod image_urls;
 pub mod module_loader;
 pub mod permit;
 mod promise;
+pub mod service_tokens;
+pub mod signed_urls;
 pub mod syscall_error;
 mod version;
 
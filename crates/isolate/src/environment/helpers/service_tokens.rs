@@ -0,0 +1,145 @@
+//! Argument handling for `ctx.auth.mintServiceToken` and
+//! `ctx.auth.verifyServiceToken`, shared by mutations, V8 actions and Node
+//! actions.
+
+use anyhow::Context;
+use common::{
+    components::ComponentId,
+    knobs::SERVICE_TOKEN_MAX_TTL,
+    runtime::UnixTimestamp,
+    types::StorageUuid,
+};
+use errors::ErrorMetadata;
+use keybroker::{
+    KeyBroker,
+    ServiceToken,
+    UserIdentityAttributes,
+};
+use model::file_storage::FileStorageId;
+use serde::Deserialize;
+use serde_json::{
+    json,
+    Map as JsonMap,
+    Value as JsonValue,
+};
+
+use super::{
+    with_argument_error,
+    ArgName,
+};
+
+/// Custom claims are carried in the token, so keep them small enough for the
+/// token to fit in a URL.
+const MAX_CLAIMS_SIZE: usize = 4096;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintServiceTokenArgs {
+    expires_in_seconds: f64,
+    #[serde(default)]
+    identity_fields: Vec<String>,
+    #[serde(default)]
+    claims: JsonMap<String, JsonValue>,
+    #[serde(default)]
+    storage_ids: Vec<String>,
+}
+
+impl MintServiceTokenArgs {
+    pub fn parse(args: JsonValue) -> anyhow::Result<Self> {
+        with_argument_error("auth.mintServiceToken", || {
+            let args: Self = serde_json::from_value(args)?;
+            let max_ttl = SERVICE_TOKEN_MAX_TTL.as_secs_f64();
+            if !(1.0..=max_ttl).contains(&args.expires_in_seconds) {
+                return Err(anyhow::anyhow!("must be between 1 and {max_ttl} seconds")
+                    .context(ArgName("expiresInSeconds")));
+            }
+            if serde_json::to_string(&args.claims)?.len() > MAX_CLAIMS_SIZE {
+                return Err(anyhow::anyhow!(
+                    "must be at most {MAX_CLAIMS_SIZE} bytes when serialized"
+                )
+                .context(ArgName("claims")));
+            }
+            Ok(args)
+        })
+    }
+
+    /// The files the token should grant read access to. The caller resolves
+    /// these to their storage UUIDs, which is what file URLs contain.
+    pub fn storage_ids(&self) -> anyhow::Result<Vec<FileStorageId>> {
+        with_argument_error("auth.mintServiceToken", || {
+            self.storage_ids
+                .iter()
+                .map(|id| id.parse().context(ArgName("storageIds")))
+                .collect()
+        })
+    }
+
+    pub fn into_service_token(
+        self,
+        identity: Option<UserIdentityAttributes>,
+        component: ComponentId,
+        now: UnixTimestamp,
+        storage_uuids: Vec<StorageUuid>,
+    ) -> anyhow::Result<ServiceToken> {
+        let identity = if self.identity_fields.is_empty() {
+            None
+        } else {
+            let identity = identity.context(ErrorMetadata::bad_request(
+                "NoIdentityForServiceToken",
+                "Can't include identity fields in a service token minted by an unauthenticated \
+                 caller",
+            ))?;
+            let JsonValue::Object(mut identity) = JsonValue::try_from(identity)? else {
+                anyhow::bail!("User identity isn't a JSON object");
+            };
+            let included: JsonMap<_, _> = self
+                .identity_fields
+                .into_iter()
+                .filter_map(|field| identity.remove(&field).map(|value| (field, value)))
+                .collect();
+            Some(JsonValue::Object(included).to_string())
+        };
+        Ok(ServiceToken {
+            component,
+            issued: now,
+            expires: now + std::time::Duration::from_secs_f64(self.expires_in_seconds),
+            identity,
+            claims: JsonValue::Object(self.claims).to_string(),
+            storage_ids: storage_uuids.iter().map(|uuid| uuid.to_string()).collect(),
+        })
+    }
+}
+
+/// Verifies the token passed to `ctx.auth.verifyServiceToken`, returning its
+/// contents or `null` if it's invalid, expired or was minted by another
+/// component.
+pub fn verify_service_token(
+    key_broker: &KeyBroker,
+    component: ComponentId,
+    now: UnixTimestamp,
+    args: JsonValue,
+) -> anyhow::Result<JsonValue> {
+    #[derive(Deserialize)]
+    struct VerifyServiceTokenArgs {
+        token: String,
+    }
+    let VerifyServiceTokenArgs { token } = with_argument_error("auth.verifyServiceToken", || {
+        Ok(serde_json::from_value(args)?)
+    })?;
+    let token = match key_broker.check_service_token(&token, now) {
+        Ok(token) if token.component == component => token,
+        _ => return Ok(JsonValue::Null),
+    };
+    let identity: Option<JsonValue> = token
+        .identity
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()?;
+    let claims: JsonValue = serde_json::from_str(&token.claims)?;
+    Ok(json!({
+        "identity": identity,
+        "claims": claims,
+        "issuedAt": token.issued.as_ms_since_epoch()?,
+        "expiresAt": token.expires.as_ms_since_epoch()?,
+    }))
+}
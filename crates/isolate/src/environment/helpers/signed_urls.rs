@@ -0,0 +1,79 @@
+//! Argument handling for `storage.getSignedUrl` and `storage.revokeSignedUrl`,
+//! shared by mutations, V8 actions and Node actions.
+
+use std::{
+    net::IpAddr,
+    time::Duration,
+};
+
+use anyhow::Context;
+use common::knobs::SIGNED_FILE_URL_MAX_TTL;
+use file_storage::SignedUrlOptions;
+use model::file_storage::FileStorageId;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+use super::{
+    with_argument_error,
+    ArgName,
+};
+
+const MAX_FILENAME_LENGTH: usize = 255;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetSignedUrlArgs {
+    storage_id: String,
+    expires_in_seconds: f64,
+    filename: Option<String>,
+    ip: Option<String>,
+}
+
+pub fn parse_get_signed_url_args(
+    args: JsonValue,
+) -> anyhow::Result<(FileStorageId, SignedUrlOptions)> {
+    with_argument_error("storage.getSignedUrl", || {
+        let GetSignedUrlArgs {
+            storage_id,
+            expires_in_seconds,
+            filename,
+            ip,
+        } = serde_json::from_value(args)?;
+        let storage_id = storage_id.parse().context(ArgName("storageId"))?;
+        let max_ttl = SIGNED_FILE_URL_MAX_TTL.as_secs_f64();
+        if !(1.0..=max_ttl).contains(&expires_in_seconds) {
+            return Err(anyhow::anyhow!("must be between 1 and {max_ttl} seconds")
+                .context(ArgName("expiresInSeconds")));
+        }
+        if let Some(filename) = &filename
+            && (filename.is_empty()
+                || filename.len() > MAX_FILENAME_LENGTH
+                || filename.chars().any(char::is_control))
+        {
+            return Err(anyhow::anyhow!(
+                "must be between 1 and {MAX_FILENAME_LENGTH} bytes without control characters"
+            )
+            .context(ArgName("filename")));
+        }
+        let ip: Option<IpAddr> = ip.map(|ip| ip.parse()).transpose().context(ArgName("ip"))?;
+        Ok((
+            storage_id,
+            SignedUrlOptions {
+                expires_in: Duration::from_secs_f64(expires_in_seconds),
+                filename,
+                ip,
+            },
+        ))
+    })
+}
+
+pub fn parse_revoke_signed_url_args(args: JsonValue) -> anyhow::Result<String> {
+    #[derive(Deserialize)]
+    struct RevokeSignedUrlArgs {
+        url: String,
+    }
+    with_argument_error("storage.revokeSignedUrl", || {
+        let RevokeSignedUrlArgs { url } = serde_json::from_value(args)?;
+        Ok(url)
+    })
+}
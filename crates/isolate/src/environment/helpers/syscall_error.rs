@@ -1,4 +1,13 @@
-use errors::ErrorMetadata;
+use common::errors::{
+    JsError,
+    JS_ERROR_CONTEXT_DOCUMENT_ID,
+    JS_ERROR_CONTEXT_SYSCALL,
+};
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
+use serde_json::Value as JsonValue;
 
 pub fn syscall_name_for_error(name: &str) -> &'static str {
     match name {
@@ -23,3 +32,47 @@ pub fn clone_error_for_batch(e: &anyhow::Error) -> anyhow::Error {
         None => anyhow::anyhow!("{e}"),
     }
 }
+
+/// The document a syscall operates on, for syscalls like `get` and `replace`
+/// that take one.
+pub fn syscall_document_id(args: &JsonValue) -> Option<String> {
+    args.get("id")
+        .and_then(|id| id.as_str())
+        .map(|id| id.to_string())
+}
+
+/// A syscall that failed with an error thrown into JS, kept so that if the
+/// function fails with that error we can say where it came from.
+pub struct SyscallErrorContext {
+    name: String,
+    document_id: Option<String>,
+    message: String,
+}
+
+impl SyscallErrorContext {
+    /// Returns `None` for system errors, which aren't thrown into JS.
+    pub fn new(name: &str, document_id: Option<String>, error: &anyhow::Error) -> Option<Self> {
+        if !error.is_deterministic_user_error() {
+            return None;
+        }
+        Some(Self {
+            name: name.to_string(),
+            document_id,
+            message: error.user_facing_message(),
+        })
+    }
+
+    /// Add the syscall to `error`'s context if `error` looks like it was
+    /// caused by it, i.e. the function didn't catch it and throw something
+    /// unrelated.
+    pub fn add_to(self, error: JsError) -> JsError {
+        if self.message.is_empty() || !error.message.contains(&self.message) {
+            return error;
+        }
+        let error = error.with_context(JS_ERROR_CONTEXT_SYSCALL, self.name);
+        match self.document_id {
+            Some(document_id) => error.with_context(JS_ERROR_CONTEXT_DOCUMENT_ID, document_id),
+            None => error,
+        }
+    }
+}
@@ -15,6 +15,7 @@ pub mod component_definitions;
 pub mod helpers;
 pub mod schema;
 pub mod udf;
+pub mod unindexed_queries;
 pub mod warnings;
 
 use common::{
@@ -91,10 +92,16 @@ pub trait IsolateEnvironment<RT: Runtime>: 'static {
         resolver: v8::Global<v8::PromiseResolver>,
     ) -> anyhow::Result<()>;
 
-    fn record_heap_stats(&self, _heap_size: IsolateHeapStats) {}
+    fn record_heap_stats(&mut self, _heap_size: IsolateHeapStats) {}
 
     fn user_timeout(&self) -> Duration;
     fn system_timeout(&self) -> Duration;
+
+    /// Maximum CPU time, excluding syscalls, that a request may use. Only
+    /// enforced when it's lower than the user timeout.
+    fn cpu_timeout(&self) -> Option<Duration> {
+        None
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -18,22 +18,33 @@ use common::{
     },
     document::DeveloperDocument,
     execution_context::ExecutionContext,
+    geospatial::{
+        GeoArea,
+        Point,
+    },
     knobs::{
+        FUNCTION_MAX_ARGS_SIZE,
         MAX_REACTOR_CALL_DEPTH,
         MAX_SYSCALL_BATCH_SIZE,
     },
     query::{
         Cursor,
         CursorPosition,
+        Order,
         Query,
     },
-    query_journal::QueryJournal,
+    query_journal::{
+        PageSplit,
+        QueryJournal,
+    },
     runtime::{
         Runtime,
         UnixTimestamp,
     },
+    secrets::SecretsResolver,
     types::{
         AllowedVisibility,
+        IndexName,
         PersistenceVersion,
         UdfType,
     },
@@ -41,7 +52,9 @@ use common::{
     version::Version,
 };
 use database::{
+    parse_document_version,
     query::{
+        geospatial_search,
         query_batch_next,
         PaginationOptions,
         TableFilter,
@@ -59,19 +72,39 @@ use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
+use file_storage::{
+    ImageTransform,
+    SignedUrlOptions,
+};
 use itertools::Itertools;
-use keybroker::KeyBroker;
+use keybroker::{
+    KeyBroker,
+    ANONYMOUS_IDENTITY_ISSUER,
+};
 use model::{
+    anonymous_identities::AnonymousIdentitiesModel,
+    append_only_lists::AppendOnlyListModel,
     components::{
         handles::FunctionHandlesModel,
         ComponentsModel,
     },
     file_storage::{
-        types::FileStorageEntry,
+        types::{
+            FileEncryptionMetadata,
+            FileStorageEntry,
+        },
         BatchKey,
         FileStorageId,
     },
-    scheduled_jobs::VirtualSchedulerModel,
+    scheduled_jobs::{
+        types::{
+            ScheduleOptions,
+            ScheduleOptionsJson,
+            ScheduledJobArgs,
+        },
+        VirtualSchedulerModel,
+    },
+    sharded_counters::ShardedCounterModel,
     virtual_system_mapping,
 };
 use serde::{
@@ -104,7 +137,16 @@ use crate::{
     environment::{
         action::parse_name_or_reference,
         helpers::{
+            image_urls::parse_get_image_url_args,
             parse_version,
+            service_tokens::{
+                self,
+                MintServiceTokenArgs,
+            },
+            signed_urls::{
+                parse_get_signed_url_args,
+                parse_revoke_signed_url_args,
+            },
             syscall_error::clone_error_for_batch,
             with_argument_error,
             ArgName,
@@ -273,6 +315,7 @@ pub trait AsyncSyscallProvider<RT: Runtime> {
     fn rt(&self) -> &RT;
     fn tx(&mut self) -> anyhow::Result<&mut Transaction<RT>>;
     fn key_broker(&self) -> &KeyBroker;
+    fn udf_type(&self) -> UdfType;
     fn context(&self) -> &ExecutionContext;
 
     fn unix_timestamp(&self) -> anyhow::Result<UnixTimestamp>;
@@ -298,6 +341,10 @@ pub trait AsyncSyscallProvider<RT: Runtime> {
         args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
     ) -> anyhow::Result<(CanonicalizedComponentFunctionPath, ConvexArray)>;
+    async fn offload_schedule_args(
+        &mut self,
+        args: ConvexArray,
+    ) -> anyhow::Result<ScheduledJobArgs>;
 
     fn file_storage_generate_upload_url(&self) -> anyhow::Result<String>;
     async fn file_storage_get_url_batch(
@@ -309,6 +356,17 @@ pub trait AsyncSyscallProvider<RT: Runtime> {
         &mut self,
         storage_id: FileStorageId,
     ) -> anyhow::Result<Option<FileStorageEntry>>;
+    async fn file_storage_get_signed_url(
+        &mut self,
+        storage_id: FileStorageId,
+        options: SignedUrlOptions,
+    ) -> anyhow::Result<Option<String>>;
+    async fn file_storage_revoke_signed_url(&mut self, url: String) -> anyhow::Result<()>;
+    async fn file_storage_get_image_url(
+        &mut self,
+        storage_id: FileStorageId,
+        transform: ImageTransform,
+    ) -> anyhow::Result<Option<String>>;
 
     async fn run_udf(
         &mut self,
@@ -346,6 +404,10 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
         &self.key_broker
     }
 
+    fn udf_type(&self) -> UdfType {
+        self.udf_type
+    }
+
     fn context(&self) -> &ExecutionContext {
         &self.context
     }
@@ -417,6 +479,13 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
         .await
     }
 
+    async fn offload_schedule_args(
+        &mut self,
+        args: ConvexArray,
+    ) -> anyhow::Result<ScheduledJobArgs> {
+        self.file_storage.offload_scheduled_job_args(args).await
+    }
+
     fn file_storage_generate_upload_url(&self) -> anyhow::Result<String> {
         let issued_ts = self.phase.unix_timestamp()?;
         let component = self.component()?;
@@ -470,6 +539,47 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
             .await
     }
 
+    async fn file_storage_get_signed_url(
+        &mut self,
+        storage_id: FileStorageId,
+        options: SignedUrlOptions,
+    ) -> anyhow::Result<Option<String>> {
+        let component = self.component()?;
+        self.file_storage
+            .get_signed_url(
+                self.phase.tx()?,
+                &self.key_broker,
+                component,
+                storage_id,
+                options,
+            )
+            .await
+    }
+
+    async fn file_storage_revoke_signed_url(&mut self, url: String) -> anyhow::Result<()> {
+        let component = self.component()?;
+        self.file_storage
+            .revoke_signed_url(self.phase.tx()?, &self.key_broker, component, &url)
+            .await
+    }
+
+    async fn file_storage_get_image_url(
+        &mut self,
+        storage_id: FileStorageId,
+        transform: ImageTransform,
+    ) -> anyhow::Result<Option<String>> {
+        let component = self.component()?;
+        self.file_storage
+            .get_image_url(
+                self.phase.tx()?,
+                &self.key_broker,
+                component,
+                storage_id,
+                transform,
+            )
+            .await
+    }
+
     #[fastrace::trace]
     async fn run_udf(
         &mut self,
@@ -501,6 +611,7 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
             PublicFunctionPath::ResolvedComponent(path.clone()),
             ConvexArray::try_from(vec![args.into()])?,
             udf_type,
+            *FUNCTION_MAX_ARGS_SIZE,
         )
         .await?;
         let (path_and_args, returns_validator) = match path_and_args_result {
@@ -545,6 +656,7 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
                     system_env_vars: BTreeMap::new(),
                     file_storage: self.file_storage.clone(),
                     module_loader: self.phase.module_loader().clone(),
+                    secrets: SecretsResolver::default(),
                 },
                 tx,
                 query_journal,
@@ -655,15 +767,37 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                 let result = match &name[..] {
                     // Database
                     "1.0/count" => Box::pin(Self::count(provider, args)).await,
+                    "1.0/geospatialSearch" => {
+                        Box::pin(Self::geospatial_search(provider, args)).await
+                    },
+                    "1.0/incrementCounter" => {
+                        Box::pin(Self::increment_counter(provider, args)).await
+                    },
+                    "1.0/readCounter" => Box::pin(Self::read_counter(provider, args)).await,
+                    "1.0/appendToList" => Box::pin(Self::append_to_list(provider, args)).await,
+                    "1.0/readList" => Box::pin(Self::read_list(provider, args)).await,
                     "1.0/insert" => Box::pin(Self::insert(provider, args)).await,
                     "1.0/shallowMerge" => Box::pin(Self::shallow_merge(provider, args)).await,
                     "1.0/replace" => Box::pin(Self::replace(provider, args)).await,
+                    "1.0/getVersion" => Box::pin(Self::get_version(provider, args)).await,
                     "1.0/remove" => Box::pin(Self::remove(provider, args)).await,
                     "1.0/queryPage" => Box::pin(Self::query_page(provider, args)).await,
                     // Auth
                     "1.0/getUserIdentity" => {
                         Box::pin(Self::get_user_identity(provider, args)).await
                     },
+                    "1.0/mintServiceToken" => {
+                        Box::pin(Self::mint_service_token(provider, args)).await
+                    },
+                    "1.0/verifyServiceToken" => {
+                        Box::pin(Self::verify_service_token(provider, args)).await
+                    },
+                    "1.0/promoteAnonymousIdentity" => {
+                        Box::pin(Self::promote_anonymous_identity(provider, args)).await
+                    },
+                    "1.0/getRequestContext" => {
+                        Box::pin(Self::get_request_context(provider, args)).await
+                    },
                     // Storage
                     "1.0/storageDelete" => Box::pin(Self::storage_delete(provider, args)).await,
                     "1.0/storageGetMetadata" => {
@@ -672,6 +806,15 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                     "1.0/storageGenerateUploadUrl" => {
                         Box::pin(Self::storage_generate_upload_url(provider, args)).await
                     },
+                    "1.0/storageGetSignedUrl" => {
+                        Box::pin(Self::storage_get_signed_url(provider, args)).await
+                    },
+                    "1.0/storageRevokeSignedUrl" => {
+                        Box::pin(Self::storage_revoke_signed_url(provider, args)).await
+                    },
+                    "1.0/storageGetImageUrl" => {
+                        Box::pin(Self::storage_get_image_url(provider, args)).await
+                    },
                     // Scheduling
                     "1.0/schedule" => Box::pin(Self::schedule(provider, args)).await,
                     "1.0/cancel_job" => Box::pin(Self::cancel_job(provider, args)).await,
@@ -740,6 +883,176 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(ConvexValue::from(result).into())
     }
 
+    #[convex_macro::instrument_future]
+    async fn geospatial_search(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        struct PointJson {
+            latitude: f64,
+            longitude: f64,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GeospatialSearchArgs {
+            index_name: String,
+            center: Option<PointJson>,
+            radius_meters: Option<f64>,
+            southwest: Option<PointJson>,
+            northeast: Option<PointJson>,
+            limit: usize,
+            #[serde(default)]
+            version: Option<String>,
+        }
+        let (index_name, area, limit, version) =
+            with_argument_error("db.geospatialSearch", || {
+                let args: GeospatialSearchArgs = serde_json::from_value(args)?;
+                let index_name: IndexName =
+                    args.index_name.parse().context(ArgName("indexName"))?;
+                let point = |point: PointJson| Point::new(point.latitude, point.longitude);
+                let area = match (
+                    args.center,
+                    args.radius_meters,
+                    args.southwest,
+                    args.northeast,
+                ) {
+                    (Some(center), Some(meters), None, None) => {
+                        anyhow::ensure!(
+                            meters.is_finite() && meters >= 0.0,
+                            "radiusMeters must be a non-negative number"
+                        );
+                        GeoArea::Radius {
+                            center: point(center).context(ArgName("center"))?,
+                            meters,
+                        }
+                    },
+                    (None, None, Some(southwest), Some(northeast)) => {
+                        let southwest = point(southwest).context(ArgName("southwest"))?;
+                        let northeast = point(northeast).context(ArgName("northeast"))?;
+                        anyhow::ensure!(
+                            southwest.latitude <= northeast.latitude,
+                            "southwest must not be north of northeast"
+                        );
+                        GeoArea::BoundingBox {
+                            southwest,
+                            northeast,
+                        }
+                    },
+                    _ => anyhow::bail!(
+                        "Expected either center and radiusMeters or southwest and northeast"
+                    ),
+                };
+                Ok((index_name, area, args.limit, args.version))
+            })?;
+        let version = parse_version(version)?;
+        system_table_guard(index_name.table(), false)?;
+        let table_filter = provider.table_filter();
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let results = geospatial_search(
+            tx,
+            component.into(),
+            index_name,
+            area,
+            limit,
+            version,
+            table_filter,
+        )
+        .await?;
+        let results = results
+            .into_iter()
+            .map(|result| {
+                json!({
+                    "document": JsonValue::from(ConvexValue::from(result.document.into_value().0)),
+                    "distanceMeters": result.distance_meters,
+                })
+            })
+            .collect();
+        Ok(JsonValue::Array(results))
+    }
+
+    #[convex_macro::instrument_future]
+    async fn increment_counter(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct IncrementCounterArgs {
+            name: String,
+            delta: i64,
+        }
+        let args: IncrementCounterArgs =
+            with_argument_error("db.incrementCounter", || Ok(serde_json::from_value(args)?))?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        ShardedCounterModel::new(tx, component.into())
+            .increment(&args.name, args.delta)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn read_counter(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ReadCounterArgs {
+            name: String,
+        }
+        let args: ReadCounterArgs =
+            with_argument_error("db.readCounter", || Ok(serde_json::from_value(args)?))?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let value = ShardedCounterModel::new(tx, component.into())
+            .read(&args.name)
+            .await?;
+        Ok(JsonValue::from(value))
+    }
+
+    #[convex_macro::instrument_future]
+    async fn append_to_list(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct AppendToListArgs {
+            name: String,
+            value: JsonValue,
+        }
+        let (name, value) = with_argument_error("db.appendToList", || {
+            let args: AppendToListArgs = serde_json::from_value(args)?;
+            let value = ConvexValue::try_from(args.value).context(ArgName("value"))?;
+            Ok((args.name, value))
+        })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        AppendOnlyListModel::new(tx, component.into())
+            .append(&name, value)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn read_list(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ReadListArgs {
+            name: String,
+            order: String,
+            limit: usize,
+        }
+        let (name, order, limit) = with_argument_error("db.readList", || {
+            let args: ReadListArgs = serde_json::from_value(args)?;
+            let order = match &args.order[..] {
+                "asc" => Order::Asc,
+                "desc" => Order::Desc,
+                order => anyhow::bail!("order must be \"asc\" or \"desc\", got {order:?}"),
+            };
+            Ok((args.name, order, args.limit))
+        })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let values = AppendOnlyListModel::new(tx, component.into())
+            .read(&name, order, limit)
+            .await?;
+        Ok(JsonValue::Array(
+            values.into_iter().map(JsonValue::from).collect(),
+        ))
+    }
+
     #[convex_macro::instrument_future]
     async fn get_user_identity(provider: &mut P, _args: JsonValue) -> anyhow::Result<JsonValue> {
         provider.observe_identity()?;
@@ -753,6 +1066,118 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(JsonValue::Null)
     }
 
+    async fn get_request_context(provider: &mut P, _args: JsonValue) -> anyhow::Result<JsonValue> {
+        // Query results are cached and shared across requests, so they can't
+        // depend on any one request's context.
+        if provider.udf_type() != UdfType::Mutation {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "RequestContextInQuery",
+                "ctx.requestContext can only be used in mutations and actions"
+            ));
+        }
+        Ok(JsonValue::from(provider.context().request_context.clone()))
+    }
+
+    fn ensure_service_tokens_allowed(provider: &P, name: &str) -> anyhow::Result<()> {
+        if provider.udf_type() != UdfType::Mutation {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ServiceTokenInQuery",
+                format!(
+                    "ctx.auth.{name} can only be used in mutations and actions, since a token's \
+                     validity depends on the current time"
+                )
+            ));
+        }
+        Ok(())
+    }
+
+    #[convex_macro::instrument_future]
+    async fn mint_service_token(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        Self::ensure_service_tokens_allowed(provider, "mintServiceToken")?;
+        let args = MintServiceTokenArgs::parse(args)?;
+        let mut storage_uuids = vec![];
+        for storage_id in args.storage_ids()? {
+            let entry = provider
+                .file_storage_get_entry(storage_id.clone())
+                .await?
+                .context(ErrorMetadata::bad_request(
+                    "FileNotFound",
+                    format!("File {storage_id} not found"),
+                ))?;
+            storage_uuids.push(entry.storage_id);
+        }
+        provider.observe_identity()?;
+        let identity = provider.tx()?.user_identity();
+        let token = args.into_service_token(
+            identity,
+            provider.component()?,
+            provider.unix_timestamp()?,
+            storage_uuids,
+        )?;
+        Ok(JsonValue::String(
+            provider.key_broker().issue_service_token(token),
+        ))
+    }
+
+    #[convex_macro::instrument_future]
+    async fn verify_service_token(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        Self::ensure_service_tokens_allowed(provider, "verifyServiceToken")?;
+        service_tokens::verify_service_token(
+            provider.key_broker(),
+            provider.component()?,
+            provider.unix_timestamp()?,
+            args,
+        )
+    }
+
+    /// Promote the anonymous identity in `token` to the signed-in caller. The
+    /// mutation can then move the anonymous identity's data to the caller in
+    /// the same transaction, so the promotion and data move commit together.
+    #[convex_macro::instrument_future]
+    async fn promote_anonymous_identity(
+        provider: &mut P,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PromoteAnonymousIdentityArgs {
+            token: String,
+            device_id: String,
+        }
+        if provider.udf_type() != UdfType::Mutation {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "PromoteAnonymousIdentityOutsideMutation",
+                "ctx.auth.promoteAnonymousIdentity can only be used in mutations"
+            ));
+        }
+        let PromoteAnonymousIdentityArgs { token, device_id } =
+            with_argument_error("auth.promoteAnonymousIdentity", || {
+                Ok(serde_json::from_value(args)?)
+            })?;
+        provider.observe_identity()?;
+        let user = provider
+            .tx()?
+            .user_identity()
+            .filter(|user| user.issuer.as_deref() != Some(ANONYMOUS_IDENTITY_ISSUER))
+            .context(ErrorMetadata::bad_request(
+                "PromoteAnonymousIdentityUnauthenticated",
+                "ctx.auth.promoteAnonymousIdentity must be called by a signed-in, non-anonymous \
+                 user",
+            ))?;
+        let now = provider.unix_timestamp()?.as_system_time();
+        let anonymous = provider
+            .key_broker()
+            .check_anonymous_identity_token(&token, now)?;
+        AnonymousIdentitiesModel::new(provider.tx()?)
+            .promote(
+                &anonymous.subject,
+                &device_id,
+                user.token_identifier.0.clone(),
+            )
+            .await?;
+        Ok(JsonValue::from(anonymous.attributes.token_identifier.0))
+    }
+
     #[convex_macro::instrument_future]
     async fn storage_generate_upload_url(
         provider: &mut P,
@@ -799,6 +1224,49 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         results.into_values().collect()
     }
 
+    fn ensure_signed_urls_allowed(provider: &P, name: &str) -> anyhow::Result<()> {
+        if provider.udf_type() != UdfType::Mutation {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "SignedUrlInQuery",
+                format!("storage.{name} can only be used in mutations and actions")
+            ));
+        }
+        Ok(())
+    }
+
+    #[convex_macro::instrument_future]
+    async fn storage_get_signed_url(
+        provider: &mut P,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        Self::ensure_signed_urls_allowed(provider, "getSignedUrl")?;
+        let (storage_id, options) = parse_get_signed_url_args(args)?;
+        let url = provider
+            .file_storage_get_signed_url(storage_id, options)
+            .await?;
+        Ok(url.into())
+    }
+
+    #[convex_macro::instrument_future]
+    async fn storage_revoke_signed_url(
+        provider: &mut P,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        Self::ensure_signed_urls_allowed(provider, "revokeSignedUrl")?;
+        let url = parse_revoke_signed_url_args(args)?;
+        provider.file_storage_revoke_signed_url(url).await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn storage_get_image_url(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        let (storage_id, transform) = parse_get_image_url_args(args)?;
+        let url = provider
+            .file_storage_get_image_url(storage_id, transform)
+            .await?;
+        Ok(url.into())
+    }
+
     #[convex_macro::instrument_future]
     async fn storage_delete(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
         #[derive(Deserialize)]
@@ -836,6 +1304,8 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             sha256: String,
             size: i64,
             content_type: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            encryption: Option<FileEncryptionMetadata>,
         }
         let file_metadata = provider.file_storage_get_entry(storage_id).await?.map(
             |FileStorageEntry {
@@ -844,6 +1314,8 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                  sha256,
                  size,
                  content_type,
+                 encryption,
+                 quarantine_reason: _, // exposed through the `_storage` system table
              }| {
                 FileMetadataJson {
                     storage_id: storage_id.to_string(),
@@ -851,6 +1323,7 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                     sha256: sha256.as_hex(),
                     size,
                     content_type,
+                    encryption,
                 }
             },
         );
@@ -867,6 +1340,8 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             function_handle: Option<String>,
             ts: f64,
             args: UdfArgsJson,
+            #[serde(flatten)]
+            options: ScheduleOptionsJson,
         }
 
         let ScheduleArgs {
@@ -875,7 +1350,9 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             function_handle,
             ts,
             args,
+            options,
         }: ScheduleArgs = with_argument_error("scheduler", || Ok(serde_json::from_value(args)?))?;
+        let options = ScheduleOptions::try_from(options)?;
 
         let path = match function_handle {
             Some(h) => {
@@ -908,11 +1385,12 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         let (path, udf_args) = provider
             .validate_schedule_args(path, args.into_arg_vec(), scheduled_ts)
             .await?;
+        let udf_args = provider.offload_schedule_args(udf_args).await?;
 
         let context = provider.context().clone();
         let tx = provider.tx()?;
         let virtual_id = VirtualSchedulerModel::new(tx, scheduling_component.into())
-            .schedule(path, udf_args, scheduled_ts, context)
+            .schedule(path, udf_args, scheduled_ts, options, context)
             .await?;
 
         Ok(JsonValue::from(virtual_id))
@@ -979,11 +1457,13 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         struct UpdateArgs {
             id: String,
             value: JsonValue,
+            #[serde(default)]
+            expected_version: Option<String>,
         }
         let table_filter = provider.table_filter();
         let component = provider.component()?;
         let tx = provider.tx()?;
-        let (id, value, table_name) = with_argument_error("db.patch", || {
+        let (id, value, table_name, expected_version) = with_argument_error("db.patch", || {
             let args: UpdateArgs = serde_json::from_value(args)?;
 
             let id = DeveloperDocumentId::decode(&args.id).context(ArgName("id"))?;
@@ -992,14 +1472,22 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                 .context(ArgName("id"))?;
 
             let value = PatchValue::try_from(args.value).context(ArgName("value"))?;
-            Ok((id, value, table_name))
+            let expected_version = args
+                .expected_version
+                .as_deref()
+                .map(parse_document_version)
+                .transpose()
+                .context(ArgName("expectedVersion"))?;
+            Ok((id, value, table_name, expected_version))
         })?;
 
         system_table_guard(&table_name, false)?;
 
-        let document = UserFacingModel::new(tx, component.into())
-            .patch(id, value)
-            .await?;
+        let mut model = UserFacingModel::new(tx, component.into());
+        if let Some(expected_version) = expected_version {
+            model.check_document_version(id, expected_version).await?;
+        }
+        let document = model.patch(id, value).await?;
         Ok(document.into_value().0.into())
     }
 
@@ -1011,11 +1499,13 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         struct ReplaceArgs {
             id: String,
             value: JsonValue,
+            #[serde(default)]
+            expected_version: Option<String>,
         }
         let table_filter = provider.table_filter();
         let component = provider.component()?;
         let tx = provider.tx()?;
-        let (id, value, table_name) = with_argument_error("db.replace", || {
+        let (id, value, table_name, expected_version) = with_argument_error("db.replace", || {
             let args: ReplaceArgs = serde_json::from_value(args)?;
 
             let id = DeveloperDocumentId::decode(&args.id).context(ArgName("id"))?;
@@ -1024,17 +1514,62 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                 .context(ArgName("id"))?;
 
             let value = ConvexValue::try_from(args.value).context(ArgName("value"))?;
-            Ok((id, value.try_into().context(ArgName("value"))?, table_name))
+            let expected_version = args
+                .expected_version
+                .as_deref()
+                .map(parse_document_version)
+                .transpose()
+                .context(ArgName("expectedVersion"))?;
+            Ok((
+                id,
+                value.try_into().context(ArgName("value"))?,
+                table_name,
+                expected_version,
+            ))
         })?;
 
         system_table_guard(&table_name, false)?;
 
-        let document = UserFacingModel::new(tx, component.into())
-            .replace(id, value)
-            .await?;
+        let mut model = UserFacingModel::new(tx, component.into());
+        if let Some(expected_version) = expected_version {
+            model.check_document_version(id, expected_version).await?;
+        }
+        let document = model.replace(id, value).await?;
         Ok(document.into_value().0.into())
     }
 
+    /// Returns the document's version, for passing as the `expectedVersion`
+    /// of a later patch or replace. Versions are strings since they don't fit
+    /// in a JavaScript number.
+    #[fastrace::trace]
+    #[convex_macro::instrument_future]
+    async fn get_version(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GetVersionArgs {
+            id: String,
+        }
+        let table_filter = provider.table_filter();
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let (id, table_name) = with_argument_error("db.getVersion", || {
+            let args: GetVersionArgs = serde_json::from_value(args)?;
+
+            let id = DeveloperDocumentId::decode(&args.id).context(ArgName("id"))?;
+            let table_name = tx
+                .resolve_idv6(id, component.into(), table_filter)
+                .context(ArgName("id"))?;
+            Ok((id, table_name))
+        })?;
+
+        system_table_guard(&table_name, false)?;
+
+        let version = UserFacingModel::new(tx, component.into())
+            .document_version(id)
+            .await?;
+        Ok(json!({ "version": version.map(|ts| u64::from(ts).to_string()) }))
+    }
+
     #[fastrace::trace]
     #[convex_macro::instrument_future]
     async fn query_batch(
@@ -1532,6 +2067,15 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsShared<RT, P> {
             (page, metadata)
         };
 
+        // Report oversized pages to the sync layer too, so clients can split
+        // them without inspecting the function's return value.
+        let page_split = match (page_status, &split_cursor) {
+            (Some(status), Some(split_cursor)) => Some(PageSplit {
+                split_cursor: split_cursor.clone(),
+                required: matches!(status, QueryPageStatus::SplitRequired),
+            }),
+            _ => None,
+        };
         let page_status = page_status.map(|s| s.as_str());
 
         // Place split_cursor in the middle.
@@ -1562,6 +2106,7 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsShared<RT, P> {
             )
         );
         provider.next_journal().end_cursor = Some(cursor);
+        provider.next_journal().page_split = page_split;
 
         #[derive(Serialize)]
         #[serde(rename_all = "camelCase")]
@@ -0,0 +1,92 @@
+use common::log_lines::{
+    LogLevel,
+    SystemLogMetadata,
+};
+use udf::UdfOutcome;
+
+use super::replay::ExecutionRecording;
+use crate::{
+    environment::warnings::SystemWarning,
+    metrics::log_udf_nondeterminism,
+};
+
+/// Compares two executions of a query or mutation against the same snapshot,
+/// the second with different seeds for `Math.random` and `Date.now`, and
+/// returns a warning for the function log if they behaved differently.
+pub fn nondeterminism_warning(
+    (first, first_recording): (&UdfOutcome, &ExecutionRecording),
+    (second, second_recording): (&UdfOutcome, &ExecutionRecording),
+) -> Option<SystemWarning> {
+    let difference = syscall_difference(first_recording, second_recording)
+        .or_else(|| result_difference(first, second))?;
+    let observed_rng = first.observed_rng || second.observed_rng;
+    let observed_time = first.observed_time || second.observed_time;
+    let (source, explanation) = match (observed_rng, observed_time) {
+        (true, true) => (
+            "rng_and_time",
+            "It used Math.random() and Date.now(), which return different values in each \
+             execution.",
+        ),
+        (true, false) => (
+            "rng",
+            "It used Math.random(), which returns different values in each execution.",
+        ),
+        (false, true) => (
+            "time",
+            "It used Date.now(), which returns a different time in each execution.",
+        ),
+        (false, false) => (
+            "unknown",
+            "It didn't use Math.random() or Date.now(), so the difference came from elsewhere, \
+             like state kept in global variables between executions.",
+        ),
+    };
+    log_udf_nondeterminism(source);
+    Some(SystemWarning {
+        level: LogLevel::Warn,
+        messages: vec![format!(
+            "Function behaved differently when run twice against the same data: {difference}. \
+             {explanation}"
+        )],
+        system_log_metadata: SystemLogMetadata {
+            code: "warning:Nondeterminism".to_string(),
+        },
+    })
+}
+
+fn syscall_difference(first: &ExecutionRecording, second: &ExecutionRecording) -> Option<String> {
+    for (i, (a, b)) in first.syscalls.iter().zip(&second.syscalls).enumerate() {
+        if a.name != b.name {
+            return Some(format!(
+                "syscall {i} was {} in the first execution and {} in the second",
+                a.name, b.name
+            ));
+        }
+        if a.args != b.args {
+            return Some(format!("syscall {i} ({}) had different arguments", a.name));
+        }
+        if a.result != b.result {
+            return Some(format!(
+                "syscall {i} ({}) returned different results",
+                a.name
+            ));
+        }
+    }
+    if first.syscalls.len() != second.syscalls.len() {
+        return Some(format!(
+            "the first execution made {} syscalls and the second made {}",
+            first.syscalls.len(),
+            second.syscalls.len()
+        ));
+    }
+    None
+}
+
+fn result_difference(first: &UdfOutcome, second: &UdfOutcome) -> Option<String> {
+    let same = match (&first.result, &second.result) {
+        (Ok(a), Ok(b)) => a.as_str() == b.as_str(),
+        (Err(a), Err(b)) => a.message == b.message,
+        _ => false,
+    };
+    (!same).then(|| "it returned different results".to_string())
+}
@@ -12,6 +12,7 @@ use futures::{
     FutureExt,
 };
 use model::{
+    component_limits::EffectiveComponentLimits,
     environment_variables::types::{
         EnvVarName,
         EnvVarValue,
@@ -28,7 +29,9 @@ use udf::{
 };
 pub mod async_syscall;
 
+pub mod determinism;
 mod phase;
+pub mod replay;
 pub mod syscall;
 use std::{
     cmp::Ordering,
@@ -38,11 +41,14 @@ use std::{
 
 use anyhow::anyhow;
 use common::{
-    errors::JsError,
+    errors::{
+        JsError,
+        JS_ERROR_CONTEXT_COMPONENT_PATH,
+    },
     identity::InertIdentity,
     knobs::{
+        DATABASE_UDF_CPU_TIMEOUT,
         DATABASE_UDF_SYSTEM_TIMEOUT,
-        DATABASE_UDF_USER_TIMEOUT,
         FUNCTION_MAX_ARGS_SIZE,
         FUNCTION_MAX_RESULT_SIZE,
         TRANSACTION_MAX_NUM_SCHEDULED,
@@ -84,6 +90,10 @@ use deno_core::{
 };
 use errors::ErrorMetadata;
 use file_storage::TransactionalFileStorage;
+use humansize::{
+    FormatSize,
+    BINARY,
+};
 use keybroker::KeyBroker;
 use rand::Rng;
 use rand_chacha::ChaCha12Rng;
@@ -110,6 +120,10 @@ use self::{
         QueryManager,
     },
     phase::UdfPhase,
+    replay::{
+        RecordingMode,
+        RecordingState,
+    },
     syscall::syscall_impl,
 };
 use super::{
@@ -132,6 +146,10 @@ use crate::{
         helpers::{
             module_loader::module_specifier_from_path,
             resolve_promise,
+            syscall_error::{
+                syscall_document_id,
+                SyscallErrorContext,
+            },
             MAX_LOG_LINES,
         },
         udf::async_syscall::DatabaseSyscallsV1,
@@ -149,10 +167,14 @@ use crate::{
     metrics::{
         self,
         log_isolate_request_cancelled,
+        log_udf_peak_heap_used,
     },
     request_scope::RequestScope,
     strings,
-    termination::TerminationReason,
+    termination::{
+        HeapLimitExceededError,
+        TerminationReason,
+    },
     timeout::{
         FunctionExecutionTime,
         Timeout,
@@ -188,10 +210,25 @@ pub struct DatabaseUdfEnvironment<RT: Runtime> {
     pending_syscalls: WithHeapSize<VecDeque<PendingSyscall>>,
 
     syscall_trace: SyscallTrace,
+    /// The most recent syscall to throw an error into JS.
+    last_syscall_error: Option<SyscallErrorContext>,
+
+    /// Limits for the function's component, loaded before execution starts.
+    component_limits: EffectiveComponentLimits,
+    /// Documents the transaction had read before this function started, so
+    /// the component's read limit only counts this function's reads.
+    documents_read_at_start: usize,
 
     heap_stats: SharedIsolateHeapStats,
+    /// Heap the isolate was using before this function started, which the
+    /// heap samples below are measured from.
+    heap_used_at_start: usize,
+    /// The largest heap sample taken while the function ran.
+    peak_heap_used: usize,
 
     context: ExecutionContext,
+    /// Set when recording this execution or replaying a recording of it.
+    recording: Option<RecordingState>,
 
     reactor_depth: usize,
     udf_callback: Box<dyn UdfCallback<RT>>,
@@ -250,7 +287,30 @@ impl<RT: Runtime> IsolateEnvironment<RT> for DatabaseUdfEnvironment<RT> {
     }
 
     fn syscall(&mut self, name: &str, args: JsonValue) -> anyhow::Result<JsonValue> {
-        syscall_impl(self, name, args)
+        let document_id = syscall_document_id(&args);
+        let calls = match &self.recording {
+            Some(_) => vec![(name.to_string(), args.clone())],
+            None => vec![],
+        };
+        let replayed = match &mut self.recording {
+            Some(recording) => recording.replay(&calls)?,
+            None => None,
+        };
+        let result = match replayed {
+            Some(mut results) => results.remove(0),
+            None => {
+                syscall_impl(self, name, args).and_then(|v| self.check_documents_read().map(|()| v))
+            },
+        };
+        if let Some(recording) = &mut self.recording {
+            recording.record(calls, std::slice::from_ref(&result));
+        }
+        if let Err(ref e) = result
+            && let Some(context) = SyscallErrorContext::new(name, document_id, e)
+        {
+            self.last_syscall_error = Some(context);
+        }
+        result
     }
 
     fn start_async_syscall(
@@ -281,20 +341,27 @@ impl<RT: Runtime> IsolateEnvironment<RT> for DatabaseUdfEnvironment<RT> {
             ))
     }
 
-    fn record_heap_stats(&self, mut isolate_stats: IsolateHeapStats) {
+    fn record_heap_stats(&mut self, mut isolate_stats: IsolateHeapStats) {
         // Add the memory allocated by the environment itself.
         isolate_stats.environment_heap_size =
             self.pending_syscalls.heap_size() + self.syscall_trace.heap_size();
         self.heap_stats.store(isolate_stats);
+        let heap_used = (isolate_stats.v8_used_heap_size + isolate_stats.env_heap_size())
+            .saturating_sub(self.heap_used_at_start);
+        self.peak_heap_used = self.peak_heap_used.max(heap_used);
     }
 
     fn user_timeout(&self) -> std::time::Duration {
-        *DATABASE_UDF_USER_TIMEOUT
+        self.component_limits.function_timeout
     }
 
     fn system_timeout(&self) -> std::time::Duration {
         *DATABASE_UDF_SYSTEM_TIMEOUT
     }
+
+    fn cpu_timeout(&self) -> Option<std::time::Duration> {
+        Some(*DATABASE_UDF_CPU_TIMEOUT)
+    }
 }
 
 impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
@@ -306,6 +373,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
             system_env_vars,
             file_storage,
             module_loader,
+            secrets: _,
         }: EnvironmentData<RT>,
         heap_stats: SharedIsolateHeapStats,
         UdfRequest {
@@ -315,12 +383,16 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
             transaction,
             journal,
             context,
+            recording,
         }: UdfRequest<RT>,
         reactor_depth: usize,
         udf_callback: Box<dyn UdfCallback<RT>>,
         client_id: String,
     ) -> Self {
         let persistence_version = transaction.persistence_version();
+        let recording = recording
+            .map(|mode| RecordingState::new(mode, &path_and_args, *transaction.begin_timestamp()));
+        let module_version = path_and_args.module_version();
         let (path, arguments, udf_server_version) = path_and_args.consume();
         let component = path.component;
         Self {
@@ -337,6 +409,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
                 module_loader.clone(),
                 system_env_vars,
                 component,
+                module_version,
             ),
             file_storage,
 
@@ -350,8 +423,14 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
 
             pending_syscalls: WithHeapSize::default(),
             syscall_trace: SyscallTrace::new(),
+            last_syscall_error: None,
+            component_limits: EffectiveComponentLimits::default(),
+            documents_read_at_start: 0,
             heap_stats,
+            heap_used_at_start: 0,
+            peak_heap_used: 0,
             context,
+            recording,
 
             reactor_depth,
             udf_callback,
@@ -370,8 +449,12 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         // Initialize the UDF's RNG from some high-quality entropy. As with
         // `unix_timestamp` below, the UDF is only deterministic modulo this
         // system-generated input.
-        let rng_seed = self.rt.rng().gen();
-        let unix_timestamp = self.rt.unix_timestamp();
+        // Replays use the recorded inputs instead.
+        let (rng_seed, unix_timestamp) =
+            match self.recording.as_ref().and_then(RecordingState::seeds) {
+                Some(seeds) => seeds,
+                None => (self.rt.rng().gen(), self.rt.unix_timestamp()),
+            };
         let heap_stats = self.heap_stats.clone();
 
         // See Isolate::with_context for an explanation of this setup code. We can't use
@@ -379,6 +462,11 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         // generic async closure to `Isolate` is currently difficult.
         let client_id = Arc::new(client_id);
         let path = self.path.clone();
+        // Load the component's limits before starting the request since
+        // `start_request` reads the user timeout from the environment.
+        self.component_limits = self.phase.component_limits().await?;
+        self.documents_read_at_start = self.phase.execution_size()?.read_size.total_document_count;
+        self.heap_used_at_start = isolate.heap_stats().v8_used_heap_size;
         let (handle, state) = isolate.start_request(client_id, self).await?;
         let mut handle_scope = isolate.handle_scope();
         let v8_context = v8::Context::new(&mut handle_scope);
@@ -415,6 +503,16 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
 
         let execution_time;
         (self, execution_time) = isolate_context.take_environment();
+        let cpu_time = execution_time.cpu_time;
+        if let Some(recording) = self.recording.take() {
+            recording.finish(
+                self.udf_type,
+                rng_seed,
+                unix_timestamp,
+                self.phase.read_set()?,
+            )?;
+        }
+        let result = result.map_err(|e| self.add_error_context(e));
         let success_result_value = match result.as_ref() {
             Ok(v) => Some(v),
             _ => None,
@@ -478,7 +576,13 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
             }),
             _ => anyhow::bail!("UdfEnvironment should only run queries and mutations"),
         };
-        Ok((self.phase.into_transaction()?, outcome))
+        log_udf_peak_heap_used(self.peak_heap_used);
+        let tx = self.phase.into_transaction()?;
+        tx.usage_tracker.track_heap_used(self.peak_heap_used as u64);
+        if let Some(cpu_time) = cpu_time {
+            tx.usage_tracker.track_cpu_time(cpu_time);
+        }
+        Ok((tx, outcome))
     }
 
     #[convex_macro::instrument_future]
@@ -654,6 +758,9 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
             // queue.
             scope.perform_microtask_checkpoint();
             scope.record_heap_stats()?;
+            if let Some(e) = scope.state()?.environment.heap_limit_error() {
+                handle.terminate_and_throw(TerminationReason::UncatchableDeveloperError(e))?;
+            }
             handle.check_terminated()?;
 
             // Check for rejected promises still unhandled, if so terminate.
@@ -688,6 +795,12 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
                     // No syscalls or javascript to run, so we're done.
                     break;
                 };
+                let mut syscalls = vec![(p.name.clone(), syscall_document_id(&p.args))];
+                // The calls' arguments, kept only when recording or replaying.
+                let mut calls = vec![];
+                if state.environment.recording.is_some() {
+                    calls.push((p.name.clone(), p.args.clone()));
+                }
                 let mut batch = AsyncSyscallBatch::new(p.name, p.args);
                 let mut resolvers = vec![p.resolver];
                 while let Some(p) = state.environment.pending_syscalls.front()
@@ -698,6 +811,10 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
                         .pending_syscalls
                         .pop_front()
                         .expect("should have a syscall");
+                    syscalls.push((p.name.clone(), syscall_document_id(&p.args)));
+                    if state.environment.recording.is_some() {
+                        calls.push((p.name.clone(), p.args.clone()));
+                    }
                     batch.push(p.name, p.args)?;
                     resolvers.push(p.resolver);
                 }
@@ -712,19 +829,42 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
                 // Even though the future would be blocking on the database most of the
                 // time it still does some processing that might result in oversubscribing
                 // the CPU threads dedicated to v8.
-                let results = select_biased! {
-                    _ = cancellation => {
-                        log_isolate_request_cancelled();
-                        anyhow::bail!("Cancelled");
+                let replayed = match &mut state.environment.recording {
+                    Some(recording) => recording.replay(&calls)?,
+                    None => None,
+                };
+                let results = match replayed {
+                    Some(results) => results,
+                    None => select_biased! {
+                        _ = cancellation => {
+                            log_isolate_request_cancelled();
+                            anyhow::bail!("Cancelled");
+                        },
+                        results = with_release_permit(
+                            &mut state.timeout,
+                            &mut state.permit,
+                            DatabaseSyscallsV1::run_async_syscall_batch(
+                                &mut state.environment, batch,
+                            ).map(Ok),
+                        ).fuse() => results?,
                     },
-                    results = with_release_permit(
-                        &mut state.timeout,
-                        &mut state.permit,
-                        DatabaseSyscallsV1::run_async_syscall_batch(
-                            &mut state.environment, batch,
-                        ).map(Ok),
-                    ).fuse() => results?,
                 };
+                let results: Vec<_> = results
+                    .into_iter()
+                    .map(|result| {
+                        result.and_then(|v| state.environment.check_documents_read().map(|()| v))
+                    })
+                    .collect();
+                for ((name, document_id), result) in syscalls.into_iter().zip(&results) {
+                    if let Err(e) = result
+                        && let Some(context) = SyscallErrorContext::new(&name, document_id, e)
+                    {
+                        state.environment.last_syscall_error = Some(context);
+                    }
+                }
+                if let Some(recording) = &mut state.environment.recording {
+                    recording.record(calls, &results);
+                }
                 (resolvers, results)
             };
             // Every syscall must have a result (which could be an error or None).
@@ -768,6 +908,56 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         Ok(result)
     }
 
+    /// Fail the function once it has read more documents than its component
+    /// allows. The error is thrown into JS like any other syscall error.
+    fn check_documents_read(&self) -> anyhow::Result<()> {
+        let Some(limit) = self.component_limits.max_documents_read else {
+            return Ok(());
+        };
+        let documents_read = self
+            .phase
+            .execution_size()?
+            .read_size
+            .total_document_count
+            .saturating_sub(self.documents_read_at_start);
+        if documents_read as u64 > limit {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "TooManyDocumentsRead",
+                format!(
+                    "Too many documents read in a single function execution (limit: {limit} for \
+                     this component)"
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The error to terminate the function with once its heap sample exceeds
+    /// its component's limit. Sampling happens between microtasks, so a
+    /// function can briefly go over the limit before it's stopped.
+    fn heap_limit_error(&self) -> Option<JsError> {
+        let limit = self.component_limits.max_heap_size?;
+        if self.peak_heap_used as u64 <= limit {
+            return None;
+        }
+        Some(JsError::from_message(
+            HeapLimitExceededError(limit.format_size(BINARY)).to_string(),
+        ))
+    }
+
+    /// Record where an error the function failed with came from, for admins.
+    fn add_error_context(&mut self, mut error: JsError) -> JsError {
+        if let Some(component_path) = &self.path.component_path
+            && !component_path.is_root()
+        {
+            error = error.with_context(JS_ERROR_CONTEXT_COMPONENT_PATH, component_path.to_string());
+        }
+        match self.last_syscall_error.take() {
+            Some(syscall) => syscall.add_to(error),
+            None => error,
+        }
+    }
+
     pub fn emit_log_line(&mut self, log_line: LogLine) {
         // - 1 to reserve for the [ERROR] log line
         match self.log_lines.len().cmp(&(MAX_LOG_LINES - 1)) {
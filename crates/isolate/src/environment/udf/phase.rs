@@ -15,20 +15,28 @@ use common::{
         CanonicalizedComponentModulePath,
         ComponentId,
     },
+    document::ParsedDocument,
     runtime::{
         Runtime,
         UnixTimestamp,
     },
+    secrets::SECRET_REFERENCE_PREFIX,
     types::ModuleEnvironment,
 };
 use database::{
     BiggestDocumentWrites,
     BootstrapComponentsModel,
     FunctionExecutionSize,
+    ReadSet,
     Transaction,
 };
 use errors::ErrorMetadata;
 use model::{
+    component_limits::{
+        ComponentLimitsModel,
+        EffectiveComponentLimits,
+    },
+    component_versions::ComponentVersionsModel,
     config::module_loader::ModuleLoader,
     environment_variables::{
         types::{
@@ -85,6 +93,9 @@ pub struct UdfPhase<RT: Runtime> {
     system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
     preloaded: UdfPreloaded,
     component: ComponentId,
+    // Set when a traffic split pins this execution to a recorded version of
+    // the component instead of its latest modules.
+    module_version: Option<u64>,
 }
 
 enum UdfPreloaded {
@@ -108,6 +119,7 @@ impl<RT: Runtime> UdfPhase<RT> {
         module_loader: Arc<dyn ModuleLoader<RT>>,
         system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
         component: ComponentId,
+        module_version: Option<u64>,
     ) -> Self {
         Self {
             phase: Phase::Importing,
@@ -117,6 +129,7 @@ impl<RT: Runtime> UdfPhase<RT> {
             system_env_vars,
             preloaded: UdfPreloaded::Created,
             component,
+            module_version,
         }
     }
 
@@ -147,11 +160,20 @@ impl<RT: Runtime> UdfPhase<RT> {
         };
 
         // UdfConfig might not be defined for super old modules or system modules.
-        let udf_config = with_release_permit(
-            timeout,
-            permit_slot,
-            UdfConfigModel::new(self.tx_mut()?, component.into()).get(),
-        )
+        let module_version = self.module_version;
+        let udf_config = with_release_permit(timeout, permit_slot, async {
+            let udf_config = match module_version {
+                Some(version) => ComponentVersionsModel::new(self.tx_mut()?)
+                    .get(component, version)
+                    .await?
+                    .map(|component_version| component_version.into_value().udf_config),
+                None => UdfConfigModel::new(self.tx_mut()?, component.into())
+                    .get()
+                    .await?
+                    .map(ParsedDocument::into_value),
+            };
+            anyhow::Ok(udf_config)
+        })
         .await?;
         let rng = udf_config
             .as_ref()
@@ -184,6 +206,15 @@ impl<RT: Runtime> UdfPhase<RT> {
         Ok(())
     }
 
+    /// Load the execution limits for the component this function runs in.
+    pub async fn component_limits(&mut self) -> anyhow::Result<EffectiveComponentLimits> {
+        let component = self.component;
+        let limits = ComponentLimitsModel::new(self.tx_mut()?)
+            .get_limits(component)
+            .await?;
+        Ok(limits.into())
+    }
+
     pub fn component(&self) -> anyhow::Result<ComponentId> {
         let UdfPreloaded::Ready { component, .. } = &self.preloaded else {
             anyhow::bail!("Phase not initialized");
@@ -231,6 +262,7 @@ impl<RT: Runtime> UdfPhase<RT> {
             anyhow::bail!("Phase not initialized");
         };
         let component = *component;
+        let module_version = self.module_version;
         let path = CanonicalizedComponentModulePath {
             component,
             module_path: module_path.clone().canonicalize(),
@@ -238,7 +270,7 @@ impl<RT: Runtime> UdfPhase<RT> {
         let Some((module_metadata, source_package)) =
             with_release_permit(timeout, permit_slot, async {
                 match ModuleModel::new(self.tx_mut()?)
-                    .get_metadata(path.clone())
+                    .get_metadata_at_version(path.clone(), module_version)
                     .await?
                 {
                     None => anyhow::Ok(None),
@@ -321,6 +353,10 @@ impl<RT: Runtime> UdfPhase<RT> {
         Ok(self.tx_ref()?.execution_size())
     }
 
+    pub fn read_set(&self) -> anyhow::Result<&ReadSet> {
+        Ok(self.tx_ref()?.reads().read_set())
+    }
+
     pub fn begin_execution(
         &mut self,
         rng_seed: [u8; 32],
@@ -358,6 +394,16 @@ impl<RT: Runtime> UdfPhase<RT> {
             return Ok(None);
         };
         if let Some(var) = env_vars.get(tx, &name)? {
+            if var.as_ref().starts_with(SECRET_REFERENCE_PREFIX) {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "SecretInQueryOrMutation",
+                    format!(
+                        "Environment variable {name} references a secret, which can only be read \
+                         in actions. Queries and mutations must be deterministic, so they can't \
+                         fetch secrets from an external secrets manager."
+                    )
+                ));
+            }
             return Ok(Some(var.clone()));
         }
         Ok(self.system_env_vars.get(&name).cloned())
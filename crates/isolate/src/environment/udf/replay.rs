@@ -0,0 +1,402 @@
+//! Recording a query or mutation's execution and replaying it later.
+//!
+//! A recording holds everything the function's JavaScript observed while it
+//! ran: its arguments, the seeds for `Math.random` and `Date.now`, and the
+//! result of every syscall in the order JS received them. Replaying runs the
+//! same code again but answers each syscall from the recording instead of the
+//! database, so the execution is deterministic and fails loudly at the first
+//! syscall that differs from the recorded one.
+use std::{
+    collections::{
+        BTreeMap,
+        VecDeque,
+    },
+    sync::Arc,
+};
+
+use anyhow::Context;
+use common::{
+    components::ComponentPath,
+    errors::JsError,
+    runtime::UnixTimestamp,
+    types::{
+        Timestamp,
+        UdfType,
+    },
+};
+use database::ReadSet;
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
+use parking_lot::Mutex;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use udf::validation::ValidatedPathAndArgs;
+use value::ConvexValue;
+
+/// How to run a query or mutation with respect to recordings.
+pub enum RecordingMode {
+    /// Run normally, saving a recording of the execution into the recorder.
+    Record(ExecutionRecorder),
+    /// Re-execute a recording, answering syscalls from it.
+    Replay(ExecutionRecording),
+}
+
+/// A shared slot the isolate fills with a recording once the execution
+/// finishes.
+#[derive(Clone, Default)]
+pub struct ExecutionRecorder {
+    recording: Arc<Mutex<Option<ExecutionRecording>>>,
+    seeds: Option<([u8; 32], UnixTimestamp)>,
+}
+
+impl ExecutionRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an execution that uses the given seeds for `Math.random` and
+    /// `Date.now` instead of fresh ones.
+    pub fn with_seeds(rng_seed: [u8; 32], unix_timestamp: UnixTimestamp) -> Self {
+        Self {
+            recording: Arc::default(),
+            seeds: Some((rng_seed, unix_timestamp)),
+        }
+    }
+
+    pub(crate) fn finish(&self, recording: ExecutionRecording) {
+        *self.recording.lock() = Some(recording);
+    }
+
+    pub fn take(&self) -> Option<ExecutionRecording> {
+        self.recording.lock().take()
+    }
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(Debug))]
+pub struct ExecutionRecording {
+    pub udf_type: UdfType,
+    /// The function, its arguments and the component version it ran at.
+    pub path_and_args: ValidatedPathAndArgs,
+    /// The transaction's begin timestamp. Replays should load modules and
+    /// environment variables at this timestamp when it's still in retention.
+    pub begin_timestamp: Timestamp,
+    pub rng_seed: [u8; 32],
+    pub unix_timestamp: UnixTimestamp,
+    /// Syscalls in the order their results were returned to JS.
+    pub syscalls: Vec<RecordedSyscall>,
+    /// The index ranges the execution read, by index, for debugging OCC
+    /// conflicts. These aren't used by the replay itself.
+    pub reads: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedSyscall {
+    pub name: String,
+    pub args: JsonValue,
+    pub result: Result<JsonValue, RecordedSyscallError>,
+}
+
+/// An error a syscall threw into JS.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedSyscallError {
+    pub short_msg: String,
+    pub msg: String,
+    /// The `data` of a `ConvexError` thrown by a function this one called.
+    pub data: Option<JsonValue>,
+}
+
+impl RecordedSyscall {
+    fn new(name: String, args: JsonValue, result: &anyhow::Result<JsonValue>) -> Self {
+        let result = match result {
+            Ok(value) => Ok(value.clone()),
+            Err(e) => Err(RecordedSyscallError {
+                short_msg: e.short_msg().to_string(),
+                msg: e.user_facing_message(),
+                data: e
+                    .downcast_ref::<JsError>()
+                    .and_then(|js_error| js_error.custom_data.clone())
+                    .map(JsonValue::from),
+            }),
+        };
+        Self { name, args, result }
+    }
+}
+
+impl RecordedSyscallError {
+    fn into_error(self) -> anyhow::Result<anyhow::Error> {
+        let metadata = ErrorMetadata::bad_request(self.short_msg, self.msg.clone());
+        let error = match self.data {
+            Some(data) => anyhow::Error::new(JsError {
+                message: self.msg,
+                custom_data: Some(ConvexValue::try_from(data)?),
+                frames: None,
+                context: BTreeMap::new(),
+            })
+            .context(metadata),
+            None => anyhow::anyhow!(metadata),
+        };
+        Ok(error)
+    }
+}
+
+/// The syscall results left to return during a replay.
+struct SyscallReplay {
+    syscalls: VecDeque<RecordedSyscall>,
+    num_replayed: usize,
+}
+
+impl SyscallReplay {
+    fn new(syscalls: Vec<RecordedSyscall>) -> Self {
+        Self {
+            syscalls: syscalls.into(),
+            num_replayed: 0,
+        }
+    }
+
+    /// Returns the recorded result of the next syscall. The outer error is a
+    /// system error for when the execution diverged from the recording.
+    fn next(&mut self, name: &str, args: &JsonValue) -> anyhow::Result<anyhow::Result<JsonValue>> {
+        let index = self.num_replayed;
+        let recorded = self.syscalls.pop_front().with_context(|| {
+            format!("Replay diverged at syscall {index}: {name} wasn't in the recording")
+        })?;
+        anyhow::ensure!(
+            recorded.name == name && &recorded.args == args,
+            "Replay diverged at syscall {index}: expected {} with {}, got {name} with {args}",
+            recorded.name,
+            recorded.args,
+        );
+        self.num_replayed += 1;
+        Ok(match recorded.result {
+            Ok(value) => Ok(value),
+            Err(e) => Err(e.into_error()?),
+        })
+    }
+
+    /// Fails if the execution finished without making every recorded syscall.
+    fn finish(&self) -> anyhow::Result<()> {
+        if let Some(recorded) = self.syscalls.front() {
+            anyhow::bail!(
+                "Replay diverged at syscall {}: execution finished before calling {}",
+                self.num_replayed,
+                recorded.name,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// A function execution's part in recording or replaying.
+pub(crate) enum RecordingState {
+    Recording {
+        recorder: ExecutionRecorder,
+        path_and_args: ValidatedPathAndArgs,
+        begin_timestamp: Timestamp,
+        syscalls: Vec<RecordedSyscall>,
+    },
+    Replaying {
+        rng_seed: [u8; 32],
+        unix_timestamp: UnixTimestamp,
+        syscalls: SyscallReplay,
+    },
+}
+
+impl RecordingState {
+    pub(crate) fn new(
+        mode: RecordingMode,
+        path_and_args: &ValidatedPathAndArgs,
+        begin_timestamp: Timestamp,
+    ) -> Self {
+        match mode {
+            RecordingMode::Record(recorder) => Self::Recording {
+                recorder,
+                path_and_args: path_and_args.clone(),
+                begin_timestamp,
+                syscalls: vec![],
+            },
+            RecordingMode::Replay(recording) => Self::Replaying {
+                rng_seed: recording.rng_seed,
+                unix_timestamp: recording.unix_timestamp,
+                syscalls: SyscallReplay::new(recording.syscalls),
+            },
+        }
+    }
+
+    /// The seeds to run with, if they were chosen in advance.
+    pub(crate) fn seeds(&self) -> Option<([u8; 32], UnixTimestamp)> {
+        match self {
+            Self::Recording { recorder, .. } => recorder.seeds,
+            Self::Replaying {
+                rng_seed,
+                unix_timestamp,
+                ..
+            } => Some((*rng_seed, *unix_timestamp)),
+        }
+    }
+
+    /// The recorded results of a batch of syscalls, if replaying.
+    pub(crate) fn replay(
+        &mut self,
+        calls: &[(String, JsonValue)],
+    ) -> anyhow::Result<Option<Vec<anyhow::Result<JsonValue>>>> {
+        match self {
+            Self::Recording { .. } => Ok(None),
+            Self::Replaying { syscalls, .. } => Ok(Some(
+                calls
+                    .iter()
+                    .map(|(name, args)| syscalls.next(name, args))
+                    .collect::<anyhow::Result<_>>()?,
+            )),
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        calls: Vec<(String, JsonValue)>,
+        results: &[anyhow::Result<JsonValue>],
+    ) {
+        if let Self::Recording { syscalls, .. } = self {
+            for ((name, args), result) in calls.into_iter().zip(results) {
+                syscalls.push(RecordedSyscall::new(name, args, result));
+            }
+        }
+    }
+
+    /// Saves the recording, or checks that the replay used every recorded
+    /// syscall.
+    pub(crate) fn finish(
+        self,
+        udf_type: UdfType,
+        rng_seed: [u8; 32],
+        unix_timestamp: UnixTimestamp,
+        read_set: &ReadSet,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Recording {
+                recorder,
+                path_and_args,
+                begin_timestamp,
+                syscalls,
+            } => {
+                recorder.finish(ExecutionRecording {
+                    udf_type,
+                    path_and_args,
+                    begin_timestamp,
+                    rng_seed,
+                    unix_timestamp,
+                    syscalls,
+                    reads: summarize_reads(read_set),
+                });
+                Ok(())
+            },
+            Self::Replaying { syscalls, .. } => syscalls.finish(),
+        }
+    }
+}
+
+fn summarize_reads(read_set: &ReadSet) -> BTreeMap<String, Vec<String>> {
+    read_set
+        .iter_indexed()
+        .map(|(index_name, reads)| {
+            let intervals = reads
+                .intervals
+                .iter()
+                .map(|interval| format!("{interval:?}"))
+                .collect();
+            (index_name.to_string(), intervals)
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedExecutionRecording {
+    udf_type: String,
+    udf_path: String,
+    component_id: Option<String>,
+    component_path: Option<String>,
+    args: JsonValue,
+    npm_version: Option<String>,
+    module_version: Option<u64>,
+    is_candidate: Option<bool>,
+    begin_timestamp: u64,
+    rng_seed: [u8; 32],
+    unix_timestamp_nanos: u64,
+    syscalls: Vec<RecordedSyscall>,
+    reads: BTreeMap<String, Vec<String>>,
+}
+
+impl TryFrom<ExecutionRecording> for JsonValue {
+    type Error = anyhow::Error;
+
+    fn try_from(recording: ExecutionRecording) -> anyhow::Result<Self> {
+        let pb::common::ValidatedPathAndArgs {
+            path,
+            args,
+            npm_version,
+            component_path,
+            component_id,
+            module_version,
+            is_candidate,
+        } = recording.path_and_args.try_into()?;
+        let component_path = component_path
+            .map(ComponentPath::try_from)
+            .transpose()?
+            .map(|path| path.to_string());
+        let serialized = SerializedExecutionRecording {
+            udf_type: recording.udf_type.to_string(),
+            udf_path: path.context("Missing udf_path")?,
+            component_id,
+            component_path,
+            args: serde_json::from_slice(&args.context("Missing args")?)?,
+            npm_version,
+            module_version,
+            is_candidate,
+            begin_timestamp: recording.begin_timestamp.into(),
+            rng_seed: recording.rng_seed,
+            unix_timestamp_nanos: recording.unix_timestamp.as_nanos().try_into()?,
+            syscalls: recording.syscalls,
+            reads: recording.reads,
+        };
+        Ok(serde_json::to_value(serialized)?)
+    }
+}
+
+impl TryFrom<JsonValue> for ExecutionRecording {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonValue) -> anyhow::Result<Self> {
+        let serialized: SerializedExecutionRecording = serde_json::from_value(value)?;
+        let component_path = serialized
+            .component_path
+            .map(|path| path.parse::<ComponentPath>())
+            .transpose()?
+            .unwrap_or_else(ComponentPath::root);
+        let path_and_args = ValidatedPathAndArgs::from_proto(pb::common::ValidatedPathAndArgs {
+            path: Some(serialized.udf_path),
+            args: Some(serde_json::to_vec(&serialized.args)?),
+            npm_version: serialized.npm_version,
+            component_path: Some(component_path.into()),
+            component_id: serialized.component_id,
+            module_version: serialized.module_version,
+            is_candidate: serialized.is_candidate,
+        })?;
+        Ok(Self {
+            udf_type: serialized.udf_type.parse()?,
+            path_and_args,
+            begin_timestamp: serialized.begin_timestamp.try_into()?,
+            rng_seed: serialized.rng_seed,
+            unix_timestamp: UnixTimestamp::from_nanos(serialized.unix_timestamp_nanos),
+            syscalls: serialized.syscalls,
+            reads: serialized.reads,
+        })
+    }
+}
@@ -12,6 +12,8 @@ use common::{
 use database::{
     query::TableFilter,
     DeveloperQuery,
+    FunctionExecutionSize,
+    ResourceUsage,
 };
 use errors::ErrorMetadata;
 use model::virtual_system_mapping;
@@ -52,6 +54,8 @@ pub trait SyscallProvider<RT: Runtime> {
 
     fn start_query(&mut self, query: Query, version: Option<Version>) -> anyhow::Result<u32>;
     fn cleanup_query(&mut self, query_id: u32) -> bool;
+
+    fn execution_size(&self) -> anyhow::Result<FunctionExecutionSize>;
 }
 
 impl<RT: Runtime> SyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
@@ -103,6 +107,10 @@ impl<RT: Runtime> SyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
     fn cleanup_query(&mut self, query_id: u32) -> bool {
         self.query_manager.cleanup_developer(query_id)
     }
+
+    fn execution_size(&self) -> anyhow::Result<FunctionExecutionSize> {
+        self.phase.execution_size()
+    }
 }
 
 pub fn syscall_impl<RT: Runtime, P: SyscallProvider<RT>>(
@@ -115,6 +123,7 @@ pub fn syscall_impl<RT: Runtime, P: SyscallProvider<RT>>(
         "1.0/queryStream" => syscall_query_stream(provider, args),
         "1.0/db/normalizeId" => syscall_normalize_id(provider, args),
         "1.0/componentArgument" => syscall_component_argument(provider, args),
+        "1.0/transactionMetrics" => syscall_transaction_metrics(provider, args),
 
         #[cfg(any(test, feature = "testing"))]
         "throwSystemError" => anyhow::bail!("I can't go for that."),
@@ -209,6 +218,49 @@ fn syscall_component_argument<RT: Runtime, P: SyscallProvider<RT>>(
     Ok(result)
 }
 
+fn syscall_transaction_metrics<RT: Runtime, P: SyscallProvider<RT>>(
+    provider: &mut P,
+    _args: JsonValue,
+) -> anyhow::Result<JsonValue> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ResourceUsageJson {
+        used: usize,
+        limit: usize,
+        remaining: usize,
+    }
+    impl From<ResourceUsage> for ResourceUsageJson {
+        fn from(usage: ResourceUsage) -> Self {
+            Self {
+                used: usage.used,
+                limit: usage.limit,
+                remaining: usage.remaining(),
+            }
+        }
+    }
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct TransactionMetricsResult {
+        documents_read: ResourceUsageJson,
+        bytes_read: ResourceUsageJson,
+        read_set_intervals: ResourceUsageJson,
+        documents_written: ResourceUsageJson,
+        bytes_written: ResourceUsageJson,
+        functions_scheduled: ResourceUsageJson,
+        scheduled_function_argument_bytes: ResourceUsageJson,
+    }
+    let usage = provider.execution_size()?.resource_usage();
+    Ok(serde_json::to_value(TransactionMetricsResult {
+        documents_read: usage.documents_read.into(),
+        bytes_read: usage.bytes_read.into(),
+        read_set_intervals: usage.read_set_intervals.into(),
+        documents_written: usage.documents_written.into(),
+        bytes_written: usage.bytes_written.into(),
+        functions_scheduled: usage.functions_scheduled.into(),
+        scheduled_function_argument_bytes: usage.scheduled_function_argument_bytes.into(),
+    })?)
+}
+
 fn syscall_query_stream<RT: Runtime, P: SyscallProvider<RT>>(
     provider: &mut P,
     args: JsonValue,
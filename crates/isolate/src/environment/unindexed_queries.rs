@@ -0,0 +1,304 @@
+//! Best-effort static detection of queries that will scan a whole table.
+//!
+//! We look for `.query("table")` call chains in the bundled module source that
+//! call `.filter(...)` on fields but never `.withIndex(...)`, and report the
+//! ones where none of the filtered fields is the leading field of an index on
+//! that table. This can't see through helper functions or dynamic table names,
+//! so it may both miss table scans and flag queries on small tables that are
+//! fine in practice: it's only used for warnings at push time.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        CREATION_TIME_FIELD_PATH,
+        ID_FIELD_PATH,
+    },
+    schemas::DatabaseSchema,
+};
+use model::config::types::ModuleConfig;
+use regex::Regex;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    FieldPath,
+    TableName,
+};
+
+static QUERY_CALL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\.query\(\s*["'`]([A-Za-z][A-Za-z0-9_]*)["'`]\s*\)"#).unwrap());
+static FIELD_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\.field\(\s*["'`]([^"'`]+)["'`]\s*\)"#).unwrap());
+
+/// Methods on a query that use an index, so filters after them are fine.
+const INDEXED_QUERY_METHODS: &[&str] = &["withIndex", "withSearchIndex"];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnindexedQuery {
+    pub module_path: String,
+    /// `file:line` in the original source if the module has a source map,
+    /// otherwise the line in the bundled module.
+    pub location: String,
+    pub table_name: String,
+    pub fields: Vec<String>,
+    pub message: String,
+}
+
+pub fn find_unindexed_queries<'a>(
+    modules: impl IntoIterator<Item = &'a ModuleConfig>,
+    schema: Option<&DatabaseSchema>,
+) -> Vec<UnindexedQuery> {
+    let mut result = vec![];
+    for module in modules {
+        if module.path.clone().canonicalize().is_deps() {
+            continue;
+        }
+        let source = module.source.as_str();
+        // Only parse the source map if we find something to report.
+        let mut source_map = None;
+        for captures in QUERY_CALL.captures_iter(source) {
+            let (Some(query_call), Some(table_name)) = (captures.get(0), captures.get(1)) else {
+                continue;
+            };
+            let chain = method_chain(source, query_call.end());
+            if chain
+                .iter()
+                .any(|(method, _)| INDEXED_QUERY_METHODS.contains(method))
+            {
+                continue;
+            }
+            let fields: Vec<&str> = chain
+                .iter()
+                .filter(|(method, _)| *method == "filter")
+                .flat_map(|(_, args)| FIELD_REFERENCE.captures_iter(args))
+                .filter_map(|captures| captures.get(1).map(|m| m.as_str()))
+                .collect();
+            if fields.is_empty()
+                || fields
+                    .iter()
+                    .any(|field| is_index_prefix(schema, table_name.as_str(), field))
+            {
+                continue;
+            }
+            let parsed_source_map = source_map.get_or_insert_with(|| {
+                module
+                    .source_map
+                    .as_ref()
+                    .and_then(|m| sourcemap::SourceMap::from_slice(m.as_bytes()).ok())
+            });
+            let location = location(
+                module.path.as_str(),
+                source,
+                query_call.start(),
+                parsed_source_map.as_ref(),
+            );
+            let mut fields: Vec<String> = fields.into_iter().map(String::from).collect();
+            fields.sort();
+            fields.dedup();
+            let message = format!(
+                "{location}: query on table \"{}\" filters on {} without using an index, so it \
+                 will scan the whole table. Consider adding an index on {} and querying it with \
+                 `.withIndex`.",
+                table_name.as_str(),
+                fields.join(", "),
+                if fields.len() == 1 {
+                    "this field"
+                } else {
+                    "one of these fields"
+                },
+            );
+            result.push(UnindexedQuery {
+                module_path: module.path.as_str().to_string(),
+                location,
+                table_name: table_name.as_str().to_string(),
+                fields,
+                message,
+            });
+        }
+    }
+    result
+}
+
+/// Whether an equality filter on `field` can be served by an index on the
+/// table, i.e. whether some index starts with it.
+fn is_index_prefix(schema: Option<&DatabaseSchema>, table_name: &str, field: &str) -> bool {
+    let Ok(field) = field.parse::<FieldPath>() else {
+        return false;
+    };
+    if field == *CREATION_TIME_FIELD_PATH || field == *ID_FIELD_PATH {
+        return true;
+    }
+    let Some(table) = schema
+        .zip(table_name.parse::<TableName>().ok())
+        .and_then(|(schema, table_name)| schema.tables.get(&table_name))
+    else {
+        return false;
+    };
+    table
+        .indexes
+        .values()
+        .any(|index| index.fields.first() == Some(&field))
+}
+
+/// The methods called on the value ending at `pos`, with the source of their
+/// arguments, e.g. `[("filter", "(q) => ..."), ("collect", "")]`.
+fn method_chain(source: &str, mut pos: usize) -> Vec<(&str, &str)> {
+    let bytes = source.as_bytes();
+    let mut chain = vec![];
+    loop {
+        pos = skip_whitespace(bytes, pos);
+        if bytes.get(pos) != Some(&b'.') {
+            break;
+        }
+        pos = skip_whitespace(bytes, pos + 1);
+        let name_start = pos;
+        while pos < bytes.len()
+            && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_' || bytes[pos] == b'$')
+        {
+            pos += 1;
+        }
+        let name = &source[name_start..pos];
+        pos = skip_whitespace(bytes, pos);
+        if name.is_empty() || bytes.get(pos) != Some(&b'(') {
+            break;
+        }
+        let Some(args_end) = matching_paren(bytes, pos) else {
+            break;
+        };
+        chain.push((name, &source[pos + 1..args_end]));
+        pos = args_end + 1;
+    }
+    chain
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+/// The position of the `)` closing the `(` at `open`, skipping over string
+/// literals.
+fn matching_paren(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut pos = open;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            },
+            quote @ (b'"' | b'\'' | b'`') => {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos] != quote {
+                    if bytes[pos] == b'\\' {
+                        pos += 1;
+                    }
+                    pos += 1;
+                }
+            },
+            _ => {},
+        }
+        pos += 1;
+    }
+    None
+}
+
+fn location(
+    module_path: &str,
+    source: &str,
+    offset: usize,
+    source_map: Option<&sourcemap::SourceMap>,
+) -> String {
+    let line = source[..offset].matches('\n').count();
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let column = source[line_start..offset].chars().count();
+    if let Some(token) = source_map.and_then(|m| m.lookup_token(line as u32, column as u32))
+        && let Some(original) = token.get_source()
+    {
+        return format!("{original}:{}", token.get_src_line() + 1);
+    }
+    format!("{module_path}:{}", line + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        schemas::{
+            DatabaseSchema,
+            IndexSchema,
+            TableDefinition,
+        },
+        types::{
+            IndexDescriptor,
+            ModuleEnvironment,
+        },
+    };
+    use maplit::btreemap;
+    use model::config::types::ModuleConfig;
+
+    use super::find_unindexed_queries;
+
+    fn module(source: &str) -> anyhow::Result<ModuleConfig> {
+        Ok(ModuleConfig {
+            path: "messages.js".parse()?,
+            source: source.to_string(),
+            source_map: None,
+            environment: ModuleEnvironment::Isolate,
+        })
+    }
+
+    #[test]
+    fn test_find_unindexed_queries() -> anyhow::Result<()> {
+        let index_descriptor = IndexDescriptor::new("by_channel")?;
+        let schema = DatabaseSchema {
+            tables: btreemap! {
+                "messages".parse()? => TableDefinition {
+                    table_name: "messages".parse()?,
+                    indexes: btreemap! {
+                        index_descriptor.clone() => IndexSchema {
+                            index_descriptor,
+                            fields: vec!["channel".parse()?].try_into()?,
+                        },
+                    },
+                    search_indexes: Default::default(),
+                    vector_indexes: Default::default(),
+                    document_type: None,
+                    triggers: vec![],
+                    on_commit: vec![],
+                    cascade_delete_files: false,
+                    file_text: None,
+                },
+            },
+            schema_validation: true,
+        };
+        let source = r#"
+            const a = await ctx.db.query("messages")
+                .filter((q) => q.eq(q.field("author"), "ann"))
+                .collect();
+            const b = await ctx.db.query("messages")
+                .filter((q) => q.eq(q.field("channel"), "sports")).first();
+            const c = await ctx.db.query("messages")
+                .withIndex("by_channel", (q) => q.eq("channel", "sports"))
+                .filter((q) => q.eq(q.field("author"), "ann")).collect();
+            const d = await ctx.db.query("users").collect();
+        "#;
+        let warnings = find_unindexed_queries(&[module(source)?], Some(&schema));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].table_name, "messages");
+        assert_eq!(warnings[0].fields, vec!["author".to_string()]);
+        assert_eq!(warnings[0].location, "messages.js:2");
+
+        // Without a schema, there are no indexes to use.
+        let warnings = find_unindexed_queries(&[module(source)?], None);
+        assert_eq!(warnings.len(), 2);
+        Ok(())
+    }
+}
@@ -76,6 +76,8 @@ pub enum IsolateNotClean {
     UnhandledPromiseRejection,
     #[error("Isolate hit user timeout")]
     UserTimeout,
+    #[error("Isolate hit user CPU timeout")]
+    UserCpuTimeout,
     #[error("Isolate hit system timeout")]
     SystemTimeout,
     #[error("Isolate ran out of memory")]
@@ -96,6 +98,7 @@ impl IsolateNotClean {
             Self::UncatchableDeveloperError => "uncatchable_developer_error",
             Self::UnhandledPromiseRejection => "unhandled_promise_rejection",
             Self::UserTimeout => "user_timeout",
+            Self::UserCpuTimeout => "user_cpu_timeout",
             Self::SystemTimeout => "system_timeout",
             Self::OutOfMemory => "out_of_memory",
             Self::TooMuchMemoryCarryOver(..) => "memory_carry_over",
@@ -290,6 +293,7 @@ impl<RT: Runtime> Isolate<RT> {
             context_handle,
             Some(user_timeout),
             Some(environment.system_timeout()),
+            environment.cpu_timeout(),
         );
         let permit = timeout
             .with_timeout(self.limiter.acquire(client_id))
@@ -166,6 +166,7 @@ impl<RT: Runtime> IsolateThreadClient<RT> {
         Ok(FunctionExecutionTime {
             elapsed,
             limit: self.user_timeout,
+            cpu_time: None,
         })
     }
 
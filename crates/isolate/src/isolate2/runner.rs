@@ -42,9 +42,14 @@ use common::{
 use database::{
     query::TableFilter,
     DeveloperQuery,
+    FunctionExecutionSize,
     Transaction,
 };
 use errors::ErrorMetadata;
+use file_storage::{
+    ImageTransform,
+    SignedUrlOptions,
+};
 use keybroker::KeyBroker;
 use model::{
     config::module_loader::ModuleLoader,
@@ -58,6 +63,7 @@ use model::{
         FileStorageId,
     },
     modules::user_error::ModuleNotFoundError,
+    scheduled_jobs::types::ScheduledJobArgs,
     udf_config::UdfConfigModel,
     virtual_system_mapping,
 };
@@ -340,6 +346,10 @@ impl<RT: Runtime> SyscallProvider<RT> for UdfEnvironment<RT> {
     fn cleanup_query(&mut self, query_id: u32) -> bool {
         self.shared.cleanup_query(query_id)
     }
+
+    fn execution_size(&self) -> anyhow::Result<FunctionExecutionSize> {
+        todo!();
+    }
 }
 
 impl<RT: Runtime> Environment for UdfEnvironment<RT> {
@@ -575,6 +585,7 @@ async fn run_request<RT: Runtime>(
     let mut provider = Isolate2SyscallProvider::new(
         tx,
         rt.clone(),
+        udf_type,
         execution_time_seed.unix_timestamp,
         query_journal,
         udf_path.is_system(),
@@ -810,6 +821,7 @@ struct UdfSharedInner<RT: Runtime> {
 struct Isolate2SyscallProvider<'a, RT: Runtime> {
     tx: &'a mut Transaction<RT>,
     rt: RT,
+    udf_type: UdfType,
 
     shared: UdfShared<RT>,
 
@@ -830,6 +842,7 @@ impl<'a, RT: Runtime> Isolate2SyscallProvider<'a, RT> {
     fn new(
         tx: &'a mut Transaction<RT>,
         rt: RT,
+        udf_type: UdfType,
         unix_timestamp: UnixTimestamp,
         prev_journal: QueryJournal,
         is_system: bool,
@@ -840,6 +853,7 @@ impl<'a, RT: Runtime> Isolate2SyscallProvider<'a, RT> {
         Self {
             tx,
             rt,
+            udf_type,
             shared,
             unix_timestamp,
             prev_journal,
@@ -870,6 +884,10 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for Isolate2SyscallProvider<'_, RT> {
         &self.key_broker
     }
 
+    fn udf_type(&self) -> UdfType {
+        self.udf_type
+    }
+
     fn context(&self) -> &ExecutionContext {
         &self.context
     }
@@ -920,6 +938,14 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for Isolate2SyscallProvider<'_, RT> {
         validate_schedule_args(path, args, scheduled_ts, self.unix_timestamp, self.tx).await
     }
 
+    async fn offload_schedule_args(
+        &mut self,
+        args: ConvexArray,
+    ) -> anyhow::Result<ScheduledJobArgs> {
+        // There's no file storage here yet, so arguments stay in the job.
+        Ok(ScheduledJobArgs::Inline(args))
+    }
+
     fn file_storage_generate_upload_url(&self) -> anyhow::Result<String> {
         todo!()
     }
@@ -942,6 +968,26 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for Isolate2SyscallProvider<'_, RT> {
         todo!()
     }
 
+    async fn file_storage_get_signed_url(
+        &mut self,
+        _storage_id: FileStorageId,
+        _options: SignedUrlOptions,
+    ) -> anyhow::Result<Option<String>> {
+        todo!()
+    }
+
+    async fn file_storage_revoke_signed_url(&mut self, _url: String) -> anyhow::Result<()> {
+        todo!()
+    }
+
+    async fn file_storage_get_image_url(
+        &mut self,
+        _storage_id: FileStorageId,
+        _transform: ImageTransform,
+    ) -> anyhow::Result<Option<String>> {
+        todo!()
+    }
+
     fn insert_query(&mut self, query_id: QueryId, query: DeveloperQuery<RT>) {
         self.shared.insert_query(query_id, query)
     }
@@ -28,6 +28,7 @@ pub mod strings;
 mod termination;
 #[cfg(test)]
 mod tests;
+mod thread_cpu_time;
 mod timeout;
 
 #[cfg(any(test, feature = "testing"))]
@@ -39,6 +40,7 @@ pub use self::{
         ActionRequestParams,
         IsolateClient,
         IsolateConfig,
+        QueryStreamBatch,
         UdfCallback,
     },
     concurrency_limiter::{
@@ -250,6 +250,14 @@ pub fn log_result_length(result: &str) {
     log_distribution(&UDF_ISOLATE_RESULT_BYTES, result.len() as f64);
 }
 
+register_convex_histogram!(
+    UDF_PEAK_HEAP_USED_BYTES,
+    "Most heap a query or mutation used at once while it ran"
+);
+pub fn log_udf_peak_heap_used(bytes: usize) {
+    log_distribution(&UDF_PEAK_HEAP_USED_BYTES, bytes as f64);
+}
+
 register_convex_histogram!(UDF_OP_SECONDS, "Duration of UDF op", &["status", "op"]);
 pub fn op_timer(op_name: &str) -> StatusTimer {
     let mut t = StatusTimer::new(&UDF_OP_SECONDS);
@@ -334,6 +342,19 @@ pub fn log_function_limit_warning(
     log_counter_with_labels(&FUNCTION_LIMIT_WARNING_TOTAL, 1, labels);
 }
 
+register_convex_counter!(
+    UDF_NONDETERMINISM_TOTAL,
+    "Count of functions the determinism check found behaving differently when run twice",
+    &["source"]
+);
+pub fn log_udf_nondeterminism(source: &'static str) {
+    log_counter_with_labels(
+        &UDF_NONDETERMINISM_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("source", source)],
+    );
+}
+
 register_convex_counter!(
     UDF_SOURCE_MAP_FAILURE_TOTAL,
     "Number of source map failures"
@@ -348,6 +369,14 @@ pub fn log_user_timeout() {
     log_counter(&UDF_USER_TIMEOUT_TOTAL, 1);
 }
 
+register_convex_counter!(
+    UDF_USER_CPU_TIMEOUT_TOTAL,
+    "Number of UDF user CPU timeouts"
+);
+pub fn log_user_cpu_timeout() {
+    log_counter(&UDF_USER_CPU_TIMEOUT_TOTAL, 1);
+}
+
 register_convex_counter!(UDF_SYSTEM_TIMEOUT_TOTAL, "Number of UDF system timeouts");
 pub fn log_system_timeout() {
     log_counter(&UDF_SYSTEM_TIMEOUT_TOTAL, 1);
@@ -5,6 +5,7 @@ use deno_core::{
         self,
     },
 };
+use errors::ErrorMetadata;
 
 use super::OpProvider;
 use crate::{
@@ -29,6 +30,12 @@ pub fn async_op_storage_store<'b, P: OpProvider<'b>>(
     let content_type = content_type.filter(|ct| !ct.is_empty());
     let content_length = serde_v8::from_v8(provider.scope(), args.get(3))?;
     let digest = serde_v8::from_v8(provider.scope(), args.get(4))?;
+    let encryption = serde_v8::from_v8(provider.scope(), args.get(5)).map_err(|e| {
+        ErrorMetadata::bad_request(
+            "InvalidEncryptionMetadata",
+            format!("`encryption` must be an object with keyId, iv and algorithm: {e}"),
+        )
+    })?;
 
     provider.start_async_op(
         AsyncOpRequest::StorageStore {
@@ -36,6 +43,7 @@ pub fn async_op_storage_store<'b, P: OpProvider<'b>>(
             content_type,
             content_length,
             digest,
+            encryption,
         },
         resolver,
     )
@@ -66,6 +66,8 @@ declare_strings!(
     empty => "",
     export,
     exportArgs,
+    exportBody,
+    exportCacheKey,
     exportReturns,
     import_meta_unsupported => "import.meta unsupported",
     internal_error => "Convex encountered an internal error",
@@ -25,6 +25,7 @@ pub enum TerminationReason {
     UncatchableDeveloperError(JsError),
     UnhandledPromiseRejection(JsError),
     UserTimeout(Duration),
+    UserCpuTimeout(Duration),
     SystemTimeout(Duration),
     OutOfMemory,
 }
@@ -36,6 +37,7 @@ impl TerminationReason {
             Self::UncatchableDeveloperError(e) => Self::UncatchableDeveloperError(e.clone()),
             Self::UnhandledPromiseRejection(e) => Self::UnhandledPromiseRejection(e.clone()),
             Self::UserTimeout(d) => Self::UserTimeout(*d),
+            Self::UserCpuTimeout(d) => Self::UserCpuTimeout(*d),
             Self::SystemTimeout(d) => Self::SystemTimeout(*d),
             Self::OutOfMemory => Self::OutOfMemory,
         }
@@ -47,6 +49,7 @@ impl TerminationReason {
             Self::UncatchableDeveloperError(_) => IsolateNotClean::UncatchableDeveloperError,
             Self::UnhandledPromiseRejection(_) => IsolateNotClean::UnhandledPromiseRejection,
             Self::UserTimeout(_) => IsolateNotClean::UserTimeout,
+            Self::UserCpuTimeout(_) => IsolateNotClean::UserCpuTimeout,
             Self::SystemTimeout(_) => IsolateNotClean::SystemTimeout,
             Self::OutOfMemory => IsolateNotClean::OutOfMemory,
         }
@@ -151,6 +154,9 @@ impl IsolateHandle {
                     TerminationReason::UserTimeout(max_duration) => Ok(Err(JsError::from_message(
                         format!("{}", UserTimeoutError(max_duration)),
                     ))),
+                    TerminationReason::UserCpuTimeout(max_cpu_time) => Ok(Err(
+                        JsError::from_message(format!("{}", UserCpuTimeoutError(max_cpu_time))),
+                    )),
                     TerminationReason::OutOfMemory => {
                         log_isolate_out_of_memory();
                         // We report this error here because otherwise it is only surfaced to users
@@ -217,6 +223,15 @@ impl ContextHandle {
 #[error("JavaScript execution ran out of memory (maximum memory usage: 64 MB)")]
 pub struct OutOfMemoryError;
 
+/// The function used more heap than its component's limit allows.
+#[derive(Debug, Error)]
+#[error("JavaScript execution used too much memory (maximum for this function: {0})")]
+pub struct HeapLimitExceededError(pub String);
+
 #[derive(Error, Debug)]
 #[error("Function execution timed out (maximum duration: {0:?})")]
 pub struct UserTimeoutError(Duration);
+
+#[derive(Error, Debug)]
+#[error("Function execution used too much CPU time (maximum CPU time: {0:?})")]
+pub struct UserCpuTimeoutError(Duration);
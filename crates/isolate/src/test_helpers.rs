@@ -36,12 +36,17 @@ use common::{
     log_lines::LogLines,
     pause::HoldGuard,
     persistence::Persistence,
+    query::{
+        CursorPosition,
+        Query,
+    },
     query_journal::QueryJournal,
     runtime::{
         testing::TestRuntime,
         Runtime,
         UnixTimestamp,
     },
+    secrets::SecretsResolver,
     testing::TestPersistence,
     types::{
         AllowedVisibility,
@@ -58,13 +63,19 @@ use database::{
     },
     text_index_worker::flusher::backfill_text_indexes,
     vector_index_worker::flusher::backfill_vector_indexes,
+    BulkWriteOperation,
+    BulkWriteProgress,
     Database,
     FollowerRetentionManager,
     IndexModel,
     IndexWorker,
     Transaction,
 };
-use file_storage::TransactionalFileStorage;
+use file_storage::{
+    ImageTransform,
+    SignedUrlOptions,
+    TransactionalFileStorage,
+};
 use futures::{
     select,
     Future,
@@ -91,11 +102,22 @@ use model::{
         },
         ConfigModel,
     },
+    deployment_audit_log::{
+        types::DeploymentAuditLogEvent,
+        DeploymentAuditLogModel,
+    },
     file_storage::{
         types::FileStorageEntry,
         FileStorageId,
     },
-    scheduled_jobs::VirtualSchedulerModel,
+    outbound_network_policy::types::NetworkPolicyViolation,
+    scheduled_jobs::{
+        types::{
+            ScheduleOptions,
+            ScheduledJobArgs,
+        },
+        VirtualSchedulerModel,
+    },
     source_packages::{
         types::SourcePackage,
         upload_download::upload_package,
@@ -141,7 +163,10 @@ use udf::{
     HttpActionResult,
     UdfOutcome,
 };
-use usage_tracking::FunctionUsageStats;
+use usage_tracking::{
+    FunctionUsageStats,
+    FunctionUsageTracker,
+};
 use value::{
     id_v6::DeveloperDocumentId,
     ConvexArray,
@@ -160,6 +185,7 @@ use crate::{
         initialize_v8,
         EnvironmentData,
         IsolateWorker,
+        QueryStreamBatch,
         Request,
         RequestType,
         SharedIsolateHeapStats,
@@ -167,6 +193,7 @@ use crate::{
         UdfRequest,
     },
     concurrency_limiter::ConcurrencyLimiter,
+    environment::udf::replay::ExecutionRecording,
     isolate2::runner::{
         run_isolate_v2_udf,
         SeedData,
@@ -237,6 +264,7 @@ pub fn test_environment_data<RT: Runtime>(rt: RT) -> anyhow::Result<EnvironmentD
         system_env_vars,
         file_storage,
         module_loader,
+        secrets: SecretsResolver::default(),
     })
 }
 
@@ -312,6 +340,7 @@ impl<RT: Runtime, P: Persistence> UdfTest<RT, P> {
             },
             file_storage: file_storage.clone(),
             module_loader: module_loader.clone(),
+            secrets: SecretsResolver::default(),
         };
 
         let isolate = IsolateClient::new(
@@ -739,6 +768,104 @@ impl<RT: Runtime, P: Persistence> UdfTest<RT, P> {
         }
     }
 
+    /// Run a query in the v1 isolate, recording its execution.
+    pub async fn record_query(
+        &self,
+        udf_path: &str,
+        args: ConvexObject,
+    ) -> anyhow::Result<(UdfOutcome, ExecutionRecording)> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let path = ComponentFunctionPath {
+            component: ComponentPath::test_user(),
+            udf_path: udf_path.parse()?,
+        };
+        let path_and_args = ValidatedPathAndArgs::new(
+            AllowedVisibility::PublicOnly,
+            &mut tx,
+            PublicFunctionPath::Component(path.canonicalize()),
+            ConvexArray::try_from(vec![ConvexValue::Object(args)])?,
+            UdfType::Query,
+        )
+        .await?
+        .map_err(|e| anyhow::anyhow!("Invalid query arguments: {e:?}"))?;
+        let (_, outcome, recording) = self
+            .isolate
+            .record_udf(
+                UdfType::Query,
+                path_and_args,
+                tx,
+                QueryJournal::new(),
+                ExecutionContext::new_for_test(),
+                self.environment_data.clone(),
+                DEV_INSTANCE_NAME.to_string(),
+            )
+            .await?;
+        let FunctionOutcome::Query(outcome) = outcome else {
+            anyhow::bail!("Called record_query on a non-query");
+        };
+        Ok((outcome, recording))
+    }
+
+    /// Run a query in the v1 isolate twice against the same snapshot, as with
+    /// `UDF_DETERMINISM_CHECK`.
+    pub async fn query_checking_determinism(
+        &self,
+        udf_path: &str,
+        args: ConvexObject,
+    ) -> anyhow::Result<UdfOutcome> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let second_tx = self.database.begin(Identity::system()).await?;
+        let path = ComponentFunctionPath {
+            component: ComponentPath::test_user(),
+            udf_path: udf_path.parse()?,
+        };
+        let path_and_args = ValidatedPathAndArgs::new(
+            AllowedVisibility::PublicOnly,
+            &mut tx,
+            PublicFunctionPath::Component(path.canonicalize()),
+            ConvexArray::try_from(vec![ConvexValue::Object(args)])?,
+            UdfType::Query,
+        )
+        .await?
+        .map_err(|e| anyhow::anyhow!("Invalid query arguments: {e:?}"))?;
+        let (_, outcome) = self
+            .isolate
+            .execute_udf_checking_determinism(
+                UdfType::Query,
+                path_and_args,
+                tx,
+                second_tx,
+                QueryJournal::new(),
+                ExecutionContext::new_for_test(),
+                self.environment_data.clone(),
+                DEV_INSTANCE_NAME.to_string(),
+            )
+            .await?;
+        let FunctionOutcome::Query(outcome) = outcome else {
+            anyhow::bail!("Called query_checking_determinism on a non-query");
+        };
+        Ok(outcome)
+    }
+
+    /// Replay a recorded query or mutation in the v1 isolate.
+    pub async fn replay(&self, recording: ExecutionRecording) -> anyhow::Result<UdfOutcome> {
+        let tx = self.database.begin(Identity::system()).await?;
+        let (_, outcome) = self
+            .isolate
+            .replay_udf(
+                recording,
+                tx,
+                ExecutionContext::new_for_test(),
+                self.environment_data.clone(),
+                DEV_INSTANCE_NAME.to_string(),
+            )
+            .await?;
+        match outcome {
+            FunctionOutcome::Query(outcome) | FunctionOutcome::Mutation(outcome) => Ok(outcome),
+            _ => anyhow::bail!("Replayed a function that isn't a query or mutation"),
+        }
+    }
+
     /// Run a query, bypassing the validation done in `ValidatedUdfPathAndArgs`,
     /// and retrieve the JS error it produces.
     ///
@@ -1322,6 +1449,46 @@ impl<RT: Runtime, P: Persistence> ActionCallbacks for UdfTest<RT, P> {
             .await
     }
 
+    async fn storage_get_signed_url(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        options: SignedUrlOptions,
+    ) -> anyhow::Result<Option<String>> {
+        let mut tx = self.database.begin(identity).await?;
+        self.file_storage
+            .get_signed_url(&mut tx, &self.key_broker, component, storage_id, options)
+            .await
+    }
+
+    async fn storage_revoke_signed_url(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        url: String,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(identity).await?;
+        self.file_storage
+            .revoke_signed_url(&mut tx, &self.key_broker, component, &url)
+            .await?;
+        self.database.commit(tx).await?;
+        Ok(())
+    }
+
+    async fn storage_get_image_url(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        transform: ImageTransform,
+    ) -> anyhow::Result<Option<String>> {
+        let mut tx = self.database.begin(identity).await?;
+        self.file_storage
+            .get_image_url(&mut tx, &self.key_broker, component, storage_id, transform)
+            .await
+    }
+
     async fn storage_get_file_entry(
         &self,
         identity: Identity,
@@ -1372,6 +1539,7 @@ impl<RT: Runtime, P: Persistence> ActionCallbacks for UdfTest<RT, P> {
         scheduled_path: CanonicalizedComponentFunctionPath,
         udf_args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let mut tx: database::Transaction<RT> = self.database.begin(identity).await?;
@@ -1387,7 +1555,13 @@ impl<RT: Runtime, P: Persistence> ActionCallbacks for UdfTest<RT, P> {
         .await?;
 
         let virtual_id = VirtualSchedulerModel::new(&mut tx, scheduling_component.into())
-            .schedule(scheduled_path, udf_args, scheduled_ts, context)
+            .schedule(
+                scheduled_path,
+                ScheduledJobArgs::Inline(udf_args),
+                scheduled_ts,
+                options,
+                context,
+            )
             .await?;
         self.database.commit(tx).await?;
 
@@ -1416,6 +1590,59 @@ impl<RT: Runtime, P: Persistence> ActionCallbacks for UdfTest<RT, P> {
         self.database.vector_search(identity, query).await
     }
 
+    async fn stream_query_batch(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        query: Query,
+        cursor: Option<String>,
+        batch_size: usize,
+    ) -> anyhow::Result<QueryStreamBatch> {
+        let persistence_version = self.database.persistence_version();
+        let start_cursor = cursor
+            .map(|c| self.key_broker.decrypt_cursor(c, persistence_version))
+            .transpose()?;
+        let (page, cursor, usage_stats) = self
+            .database
+            .query_stream_batch(identity, component.into(), query, start_cursor, batch_size)
+            .await?;
+        Ok(QueryStreamBatch {
+            page: page
+                .into_iter()
+                .map(|doc| ConvexValue::from(doc.into_value().0))
+                .collect(),
+            is_done: matches!(cursor.position, CursorPosition::End),
+            continue_cursor: self.key_broker.encrypt_cursor(&cursor, persistence_version),
+            usage_stats,
+        })
+    }
+
+    async fn bulk_write(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        operations: Vec<BulkWriteOperation>,
+    ) -> anyhow::Result<(BulkWriteProgress, FunctionUsageStats)> {
+        let usage = FunctionUsageTracker::new();
+        let progress = self
+            .database
+            .bulk_write(
+                identity,
+                component.into(),
+                operations,
+                usage.clone(),
+                |progress| {
+                    tracing::debug!(
+                        "Bulk write committed {} operations in {} chunks",
+                        progress.operations_committed,
+                        progress.chunks_committed
+                    )
+                },
+            )
+            .await?;
+        Ok((progress, usage.gather_user_stats()))
+    }
+
     async fn lookup_function_handle(
         &self,
         identity: Identity,
@@ -1435,6 +1662,35 @@ impl<RT: Runtime, P: Persistence> ActionCallbacks for UdfTest<RT, P> {
             .get_with_component_path(path)
             .await
     }
+
+    async fn record_network_policy_violation(
+        &self,
+        _identity: Identity,
+        violation: NetworkPolicyViolation,
+        environment: ModuleEnvironment,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        DeploymentAuditLogModel::new(&mut tx)
+            .insert(vec![
+                DeploymentAuditLogEvent::OutboundNetworkPolicyViolation {
+                    host: violation.host,
+                    reason: violation.reason,
+                    environment,
+                },
+            ])
+            .await?;
+        self.database.commit(tx).await?;
+        Ok(())
+    }
+
+    async fn invalidate_http_action_cache(
+        &self,
+        _identity: Identity,
+        _component: ComponentId,
+        _path_prefix: Option<String>,
+    ) -> anyhow::Result<usize> {
+        Ok(0)
+    }
 }
 
 /// Create a bogus UDF request for testing. Should only be used for tests
@@ -1456,6 +1712,7 @@ pub async fn bogus_udf_request<RT: Runtime>(
         transaction: tx,
         journal: QueryJournal::new(),
         context: ExecutionContext::new_for_test(),
+        recording: None,
     };
     let inner = RequestType::Udf {
         request,
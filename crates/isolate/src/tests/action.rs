@@ -11,6 +11,7 @@ use must_let::must_let;
 use runtime::testing::TestRuntime;
 use value::{
     assert_obj,
+    assert_val,
     ConvexValue,
 };
 
@@ -161,3 +162,24 @@ async fn test_action_fails_with_uncatcatchable_developer_error(
     assert_contains(&e, "Unknown JS syscall: idonotexistandicannotlie");
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_query_stream_batches(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = action_udf_test(rt).await?;
+    let result = t
+        .action(
+            "action:queryStreamBatches",
+            assert_obj!("count" => 5.0, "batchSize" => 2.0),
+        )
+        .await?;
+    // Each batch resumes from the previous batch's cursor, so every document
+    // is returned exactly once.
+    assert_eq!(
+        result,
+        assert_val!({
+            "batchSizes" => [2.0, 2.0, 1.0],
+            "values" => [0.0, 1.0, 2.0, 3.0, 4.0],
+        })
+    );
+    Ok(())
+}
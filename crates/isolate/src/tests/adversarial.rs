@@ -11,6 +11,7 @@ use common::{
     },
     components::{
         CanonicalizedComponentFunctionPath,
+        ComponentId,
         ComponentPath,
         PublicFunctionPath,
     },
@@ -28,7 +29,13 @@ use common::{
 };
 use database::SystemMetadataModel;
 use keybroker::Identity;
-use model::udf_config::UdfConfigModel;
+use model::{
+    component_limits::{
+        types::ComponentLimits,
+        ComponentLimitsModel,
+    },
+    udf_config::UdfConfigModel,
+};
 use must_let::must_let;
 use runtime::{
     prod::ProdRuntime,
@@ -839,6 +846,32 @@ async fn test_big_memory_usage(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_component_heap_limit(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    let mut tx = t.database.begin(Identity::system()).await?;
+    ComponentLimitsModel::new(&mut tx)
+        .set(ComponentLimits {
+            component: ComponentId::test_user(),
+            max_documents_read: None,
+            function_timeout: None,
+            max_scheduled_functions: None,
+            max_heap_size: Some(1 << 20),
+            max_storage_bytes: None,
+            max_storage_files: None,
+        })
+        .await?;
+    t.database.commit(tx).await?;
+    let e = t
+        .query_js_error("adversarial:bigMemoryUsage", assert_obj!())
+        .await?;
+    assert_contains(
+        &e,
+        "JavaScript execution used too much memory (maximum for this function: 1 MiB)",
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_not_implemented_builtin(rt: TestRuntime) -> anyhow::Result<()> {
     let t = UdfTest::default(rt).await?;
@@ -0,0 +1,54 @@
+use common::{
+    assert_obj,
+    testing::assert_contains,
+};
+use runtime::testing::TestRuntime;
+
+use crate::test_helpers::UdfTest;
+
+#[convex_macro::test_runtime]
+async fn test_determinism_check_reports_time(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    let mut outcome = t
+        .query_checking_determinism("basic:readTimeMs", assert_obj!())
+        .await?;
+    assert_contains(
+        &outcome
+            .log_lines
+            .pop()
+            .unwrap()
+            .to_pretty_string_test_only(),
+        "[WARN] Function behaved differently when run twice against the same data: it returned \
+         different results. It used Date.now()",
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_determinism_check_reports_rng(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    let mut outcome = t
+        .query_checking_determinism("directory/udfs:pseudoRandom", assert_obj!())
+        .await?;
+    assert_contains(
+        &outcome
+            .log_lines
+            .pop()
+            .unwrap()
+            .to_pretty_string_test_only(),
+        "It used Math.random()",
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_determinism_check_passes_deterministic_query(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    t.mutation("basic:insertObject", assert_obj!("field" => "a"))
+        .await?;
+    let outcome = t
+        .query_checking_determinism("basic:listAllObjects", assert_obj!())
+        .await?;
+    assert!(outcome.log_lines.is_empty());
+    Ok(())
+}
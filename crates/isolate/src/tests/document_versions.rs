@@ -0,0 +1,88 @@
+use common::{
+    assert_obj,
+    testing::assert_contains,
+    value::ConvexValue,
+};
+use must_let::must_let;
+use runtime::testing::TestRuntime;
+
+use crate::test_helpers::{
+    UdfTest,
+    UdfTestType,
+};
+
+#[convex_macro::test_runtime]
+async fn test_document_versions(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        must_let!(let ConvexValue::String(id) = t
+            .mutation("documentVersions:createAccount", assert_obj!("balance" => 10.0))
+            .await?);
+        must_let!(let ConvexValue::String(version) = t
+            .query("documentVersions:getVersion", assert_obj!("id" => id.clone()))
+            .await?);
+
+        // Writing with the current version succeeds and changes the version.
+        t.mutation(
+            "documentVersions:setBalance",
+            assert_obj!(
+                "id" => id.clone(),
+                "balance" => 20.0,
+                "expectedVersion" => version.clone(),
+            ),
+        )
+        .await?;
+        must_let!(let ConvexValue::String(new_version) = t
+            .query("documentVersions:getVersion", assert_obj!("id" => id.clone()))
+            .await?);
+        assert_ne!(new_version, version);
+
+        // Writing with the old version fails for both patch and replace.
+        for udf_path in [
+            "documentVersions:setBalance",
+            "documentVersions:replaceBalance",
+        ] {
+            let err = t
+                .mutation_js_error(
+                    udf_path,
+                    assert_obj!(
+                        "id" => id.clone(),
+                        "balance" => 30.0,
+                        "expectedVersion" => version.clone(),
+                    ),
+                )
+                .await?;
+            assert_contains(&err, "has changed: expected version");
+        }
+        t.mutation(
+            "documentVersions:replaceBalance",
+            assert_obj!(
+                "id" => id.clone(),
+                "balance" => 30.0,
+                "expectedVersion" => new_version,
+            ),
+        )
+        .await?;
+
+        let err = t
+            .mutation_js_error(
+                "documentVersions:setBalance",
+                assert_obj!(
+                    "id" => id.clone(),
+                    "balance" => 40.0,
+                    "expectedVersion" => "not a version",
+                ),
+            )
+            .await?;
+        assert_contains(&err, "Invalid argument `expectedVersion` for `db.patch`");
+
+        let err = t
+            .mutation_js_error(
+                "documentVersions:getVersionAfterWrite",
+                assert_obj!("id" => id),
+            )
+            .await?;
+        assert_contains(&err, "doesn't have a version yet");
+        Ok(())
+    })
+    .await
+}
@@ -93,3 +93,34 @@ async fn test_system_environment_variables(rt: TestRuntime) -> anyhow::Result<()
     assert_eq!(v, ConvexValue::try_from("https://carnitas.convex.site")?);
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_secret_reference(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    let mut tx = t.database.begin(Identity::system()).await?;
+    let environment_variable = EnvironmentVariable::new(
+        "TEST_NAME".parse()?,
+        "secret://vault/secret/stripe#apiKey".parse()?,
+    );
+    EnvironmentVariablesModel::new(&mut tx)
+        .create(environment_variable, &HashSet::new())
+        .await?;
+    t.database.commit(tx).await?;
+
+    // Queries and mutations can't read secrets.
+    let err = t
+        .query_js_error("environmentVariables:getEnvironmentVariable", assert_obj!())
+        .await?;
+    assert!(err.message.contains("can only be read in actions"), "{err}");
+
+    // Actions resolve the secret, which fails since no provider is configured.
+    let err = t
+        .action(
+            "environmentVariables:actionGetEnvironmentVariable",
+            assert_obj!(),
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("isn't configured"), "{err:#}");
+    Ok(())
+}
@@ -1,6 +1,12 @@
-use std::time::Duration;
+use std::time::{
+    Duration,
+    UNIX_EPOCH,
+};
 
-use common::assert_obj;
+use common::{
+    assert_obj,
+    runtime::Runtime,
+};
 use runtime::testing::TestRuntime;
 use value::{
     numeric::is_integral,
@@ -77,6 +83,34 @@ async fn test_rand(rt: TestRuntime) -> anyhow::Result<()> {
     .await
 }
 
+#[convex_macro::test_runtime]
+async fn test_controlled_time_and_rng(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        // Functions see the runtime's clock in `Date.now()`.
+        let target = t.rt.system_time() + Duration::from_secs(30 * 24 * 60 * 60);
+        t.rt.advance_time_to(target).await;
+        let ConvexValue::Float64(now) = t.query("globals:getDateNow", assert_obj!()).await? else {
+            panic!("Expected Float64 from getDateNow");
+        };
+        let target_ms = target.duration_since(UNIX_EPOCH)?.as_millis() as f64;
+        assert!(now >= target_ms);
+        assert!(now < target_ms + 60_000.0);
+
+        // Reseeding the runtime makes `Math.random()` and
+        // `crypto.getRandomValues()` repeat.
+        t.rt.reseed_rng(7);
+        let rand1 = t.query("globals:getRandom", assert_obj!()).await?;
+        let bytes1 = t.query("globals:getRandomValues", assert_obj!()).await?;
+        t.rt.reseed_rng(7);
+        let rand2 = t.query("globals:getRandom", assert_obj!()).await?;
+        let bytes2 = t.query("globals:getRandomValues", assert_obj!()).await?;
+        assert_eq!(rand1, rand2);
+        assert_eq!(bytes1, bytes2);
+        Ok(())
+    })
+    .await
+}
+
 #[convex_macro::test_runtime]
 async fn test_finalization_registry(rt: TestRuntime) -> anyhow::Result<()> {
     UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
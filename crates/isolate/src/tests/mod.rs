@@ -8,6 +8,8 @@ mod backend_state;
 mod basic;
 mod creation_time;
 mod custom_errors;
+mod determinism;
+mod document_versions;
 mod environment_variables;
 mod fetch;
 mod globals;
@@ -20,6 +22,7 @@ mod js_builtins;
 mod logging;
 mod module_loader;
 mod query;
+mod replay;
 mod scheduler;
 mod schema;
 mod search;
@@ -550,6 +550,46 @@ async fn test_pagination_max_bytes_read(rt: TestRuntime) -> anyhow::Result<()> {
     }).await
 }
 
+#[convex_macro::test_runtime]
+async fn test_pagination_reports_page_split(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        for number in 0..5 {
+            t.mutation("query:insert", assert_obj!("number" => number))
+                .await?;
+        }
+
+        // A small page doesn't need splitting.
+        let (_, outcome) = t
+            .query_outcome(
+                "query:paginateTableScan",
+                pagination_opts(ConvexValue::Null),
+                Identity::system(),
+            )
+            .await?;
+        assert!(outcome.journal.page_split.is_none());
+
+        // A page that reads close to its row limit is split in the middle, and
+        // the split shows up in the journal for the sync worker to report.
+        let args = assert_obj!("paginationOpts" => {
+            "cursor" => ConvexValue::Null,
+            "numItems" => 100.0,
+            "maximumRowsRead" => 4.0,
+        });
+        let (value, outcome) = t
+            .query_outcome("query:paginateWithOpts", args, Identity::system())
+            .await?;
+        must_let!(let Some(page_split) = outcome.journal.page_split);
+        must_let!(let ConvexValue::Object(result) = value);
+        must_let!(let Some(ConvexValue::String(split_cursor)) = result.get("splitCursor"));
+        let split_cursor = t
+            .key_broker
+            .decrypt_cursor(split_cursor.to_string(), PersistenceVersion::default())?;
+        assert_eq!(page_split.split_cursor, split_cursor);
+        Ok(())
+    })
+    .await
+}
+
 #[convex_macro::test_runtime]
 async fn test_invalid_cursor_error(rt: TestRuntime) -> anyhow::Result<()> {
     UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use common::assert_obj;
+use runtime::testing::TestRuntime;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+
+use crate::{
+    environment::udf::replay::ExecutionRecording,
+    test_helpers::UdfTest,
+};
+
+#[convex_macro::test_runtime]
+async fn test_replay_returns_recorded_reads(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    t.mutation("basic:insertObject", assert_obj!("field" => "a"))
+        .await?;
+    let (outcome, recording) = t
+        .record_query("basic:listAllObjects", assert_obj!())
+        .await?;
+    assert!(!recording.syscalls.is_empty());
+    assert!(!recording.reads.is_empty());
+
+    // The replay doesn't see documents written after the recording.
+    t.mutation("basic:insertObject", assert_obj!("field" => "b"))
+        .await?;
+    let replayed = t.replay(recording).await?;
+    assert_eq!(replayed.result, outcome.result);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_replay_uses_recorded_time(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    let (outcome, recording) = t.record_query("basic:readTimeMs", assert_obj!()).await?;
+    t.rt.advance_time(Duration::from_secs(10)).await;
+
+    // Recordings survive being saved as JSON.
+    let recording = ExecutionRecording::try_from(JsonValue::try_from(recording)?)?;
+    let replayed = t.replay(recording).await?;
+    assert_eq!(replayed.result, outcome.result);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_replay_fails_on_divergence(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    t.mutation("basic:insertObject", assert_obj!("field" => "a"))
+        .await?;
+    let (_, mut recording) = t
+        .record_query("basic:listAllObjects", assert_obj!())
+        .await?;
+    recording.syscalls[0].args = json!({ "unexpected": true });
+    let err = t.replay(recording).await.unwrap_err();
+    assert!(
+        format!("{err:?}").contains("Replay diverged at syscall 0"),
+        "{err:?}"
+    );
+    Ok(())
+}
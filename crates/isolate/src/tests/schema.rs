@@ -270,7 +270,11 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                     "union" => FieldValidator::required_field_type(Validator::Union(vec![Validator::String, Validator::Float64])),
                     "object" => FieldValidator::required_field_type(Validator::Object(object_validator!("a" => FieldValidator::optional_field_type(Validator::Any))))
                   )
-                ]))
+                ])),
+                triggers: vec![],
+                on_commit: vec![],
+                cascade_delete_files: false,
+                file_text: None,
             },
             name2.clone() => TableDefinition {
                 table_name: name2,
@@ -287,6 +291,10 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                 search_indexes: btreemap!(),
                 vector_indexes: btreemap!(),
                 document_type: None,
+                triggers: vec![],
+                on_commit: vec![],
+                cascade_delete_files: false,
+                file_text: None,
             },
             name3.clone() => TableDefinition {
               table_name: name3,
@@ -300,6 +308,10 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                },
                vector_indexes: btreemap!(),
                document_type: None,
+               triggers: vec![],
+               on_commit: vec![],
+               cascade_delete_files: false,
+               file_text: None,
           }
         ),
         schema_validation: true,
@@ -1,6 +1,7 @@
 #![allow(clippy::float_cmp)]
 
 use common::{
+    knobs::TRANSACTION_MAX_NUM_USER_WRITES,
     testing::assert_contains,
     value::ConvexValue,
 };
@@ -68,3 +69,22 @@ async fn test_argument_throws_nesting_error(rt: TestRuntime) -> anyhow::Result<(
     assert_contains(&argument_error, "Value is too nested");
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_transaction_metrics(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    let metrics = t
+        .mutation("size_errors:transactionMetrics", assert_obj!())
+        .await?;
+    assert_eq!(
+        metrics,
+        assert_val!({
+            "readBefore" => 0.,
+            "writtenBefore" => 0.,
+            "read" => 3.,
+            "written" => 3.,
+            "writesRemaining" => (*TRANSACTION_MAX_NUM_USER_WRITES - 3) as f64,
+        })
+    );
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! CPU time accounting for isolate threads. A request's JavaScript runs on its
+//! isolate worker's thread, so that thread's CPU clock measures the CPU time
+//! the request used, excluding time spent waiting on I/O.
+
+use std::time::Duration;
+
+/// The CPU clock of the thread that created it. The clock can be read from any
+/// thread, which lets the timeout task check the isolate thread's CPU time.
+#[derive(Clone, Copy, Debug)]
+pub struct ThreadCpuClock {
+    #[cfg(target_os = "linux")]
+    clock_id: libc::clockid_t,
+}
+
+impl ThreadCpuClock {
+    /// The current thread's CPU clock, or `None` if the platform doesn't
+    /// expose per-thread CPU clocks.
+    #[cfg(target_os = "linux")]
+    pub fn current() -> Option<Self> {
+        let mut clock_id: libc::clockid_t = 0;
+        // SAFETY: `pthread_self` is always a valid thread handle, and
+        // `clock_id` is a valid pointer for the call to write to.
+        let r = unsafe { libc::pthread_getcpuclockid(libc::pthread_self(), &mut clock_id) };
+        (r == 0).then_some(Self { clock_id })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn current() -> Option<Self> {
+        None
+    }
+
+    /// Total CPU time the thread has used since it started.
+    #[cfg(target_os = "linux")]
+    pub fn elapsed(&self) -> Duration {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `ts` is a valid pointer for the call to write to. If the
+        // thread has exited, the call fails and we report no time.
+        let r = unsafe { libc::clock_gettime(self.clock_id, &mut ts) };
+        if r != 0 {
+            return Duration::ZERO;
+        }
+        Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
@@ -23,6 +23,7 @@ use crate::{
         ContextHandle,
         TerminationReason,
     },
+    thread_cpu_time::ThreadCpuClock,
 };
 
 /// A `Timeout` is an asynchronous background job that terminates an
@@ -32,6 +33,10 @@ use crate::{
 ///
 /// If the higher level operation succeeds, call `Timeout::finish` to cancel the
 /// background job and prevent it from terminating the isolate.
+///
+/// Alongside wall clock time, a `Timeout` tracks the CPU time used by the
+/// thread that created it, which is the isolate's thread. CPU time spent while
+/// paused is exempt, just like wall clock time.
 pub struct Timeout<RT: Runtime> {
     handle: Box<dyn SpawnHandle>,
     inner: Arc<Mutex<TimeoutInner<RT>>>,
@@ -48,10 +53,33 @@ struct TimeoutInner<RT: Runtime> {
     pause_elapsed: Duration,
     max_time_paused: Option<Duration>,
 
+    // CPU time of the isolate thread, if the platform can measure it.
+    cpu_clock: Option<ThreadCpuClock>,
+    cpu_timeout: Option<Duration>,
+    cpu_start: Duration,
+    // How much CPU time was used while paused?
+    cpu_paused: Duration,
+
     state: TimeoutState,
 }
 
 impl<RT: Runtime> TimeoutInner<RT> {
+    fn cpu_time(&self) -> Option<Duration> {
+        let cpu_clock = self.cpu_clock.as_ref()?;
+        let cpu_paused = match self.state {
+            TimeoutState::Paused {
+                cpu_pause_start, ..
+            } => self.cpu_paused + cpu_clock.elapsed().saturating_sub(cpu_pause_start),
+            _ => self.cpu_paused,
+        };
+        Some(
+            cpu_clock
+                .elapsed()
+                .saturating_sub(self.cpu_start)
+                .saturating_sub(cpu_paused),
+        )
+    }
+
     fn termination_reason_or_wait(
         &mut self,
         timeout: Duration,
@@ -66,14 +94,25 @@ impl<RT: Runtime> TimeoutInner<RT> {
                     metrics::log_user_timeout();
                     return Ok(Some(TerminationReason::UserTimeout(timeout)));
                 }
+                let mut wait = deadline - now;
+                if let (Some(cpu_timeout), Some(cpu_time)) = (self.cpu_timeout, self.cpu_time()) {
+                    if cpu_time >= cpu_timeout {
+                        metrics::log_user_cpu_timeout();
+                        return Ok(Some(TerminationReason::UserCpuTimeout(cpu_timeout)));
+                    }
+                    // CPU time can't advance faster than wall clock time, so
+                    // this is the earliest the CPU timeout could be hit.
+                    wait = wait.min(cpu_timeout - cpu_time);
+                }
                 // Wait on our current deadline to pass.
                 // TODO: Cancel the timer on `Timeout::finish` so we don't keep an
                 // `IsolateHandle` alive for the wait duration.
-                Err(Either::Left(self.rt.wait(deadline - now)))
+                Err(Either::Left(self.rt.wait(wait)))
             },
             TimeoutState::Paused {
                 ref mut pause_done,
                 ref pause_start,
+                ..
             } => {
                 let expired = if let Some(max_time_paused) = self.max_time_paused {
                     let total_pause_elapsed = self.pause_elapsed + pause_start.elapsed();
@@ -100,6 +139,7 @@ enum TimeoutState {
     Running,
     Paused {
         pause_start: tokio::time::Instant,
+        cpu_pause_start: Duration,
         pause_done: async_broadcast::Receiver<()>,
     },
     Finished,
@@ -114,19 +154,35 @@ impl<RT: Runtime> Drop for Timeout<RT> {
 // We default to counting everything as user time but we exempt async syscalls
 // from the user timeout and count them as system time instead.
 impl<RT: Runtime> Timeout<RT> {
+    /// Must be called on the isolate's thread. The CPU timeout is only
+    /// enforced if it's lower than the wall clock `timeout`.
     pub fn new(
         rt: RT,
         handle: ContextHandle,
         timeout: Option<Duration>,
         max_time_paused: Option<Duration>,
+        cpu_timeout: Option<Duration>,
     ) -> Self {
         let start = rt.monotonic_now();
+        let cpu_clock = ThreadCpuClock::current();
+        let cpu_start = cpu_clock
+            .as_ref()
+            .map(ThreadCpuClock::elapsed)
+            .unwrap_or_default();
+        let cpu_timeout = cpu_timeout.filter(|cpu_timeout| match timeout {
+            Some(timeout) => *cpu_timeout < timeout,
+            None => false,
+        });
         let inner = TimeoutInner {
             rt: rt.clone(),
             start,
             timeout,
             pause_elapsed: Duration::ZERO,
             max_time_paused,
+            cpu_clock,
+            cpu_timeout,
+            cpu_start,
+            cpu_paused: Duration::ZERO,
             state: TimeoutState::Running,
         };
         let inner = Arc::new(Mutex::new(inner));
@@ -177,9 +233,15 @@ impl<RT: Runtime> Timeout<RT> {
                 panic!("Overlapping calls to timeout.pause()");
             };
             let pause_start = inner.rt.monotonic_now();
+            let cpu_pause_start = inner
+                .cpu_clock
+                .as_ref()
+                .map(ThreadCpuClock::elapsed)
+                .unwrap_or_default();
             inner.state = TimeoutState::Paused {
                 pause_done: rx,
                 pause_start,
+                cpu_pause_start,
             };
             pause_start
         };
@@ -227,13 +289,19 @@ impl<RT: Runtime> Timeout<RT> {
         let inner = self.inner.lock();
         let elapsed = inner.rt.monotonic_now() - inner.start - inner.pause_elapsed;
         let limit = inner.timeout.unwrap_or(Duration::ZERO);
-        FunctionExecutionTime { elapsed, limit }
+        FunctionExecutionTime {
+            elapsed,
+            limit,
+            cpu_time: inner.cpu_time(),
+        }
     }
 }
 
 pub struct FunctionExecutionTime {
     pub elapsed: Duration,
     pub limit: Duration,
+    /// CPU time used outside of syscalls, if the platform can measure it.
+    pub cpu_time: Option<Duration>,
 }
 
 pub struct PauseGuard<'a, RT: Runtime> {
@@ -255,8 +323,15 @@ impl<RT: Runtime> Drop for PauseGuard<'_, RT> {
         };
         {
             let mut inner = self.timeout.inner.lock();
-            assert!(matches!(inner.state, TimeoutState::Paused { .. }));
-
+            let TimeoutState::Paused {
+                cpu_pause_start, ..
+            } = inner.state
+            else {
+                panic!("Timeout resumed while not paused");
+            };
+            if let Some(cpu_now) = inner.cpu_clock.as_ref().map(ThreadCpuClock::elapsed) {
+                inner.cpu_paused += cpu_now.saturating_sub(cpu_pause_start);
+            }
             inner.pause_elapsed += self.pause_start.elapsed();
             inner.state = TimeoutState::Running;
         }
@@ -2,6 +2,7 @@ use core::panic;
 use std::{
     collections::BTreeMap,
     fmt,
+    net::IpAddr,
     time::{
         Duration,
         SystemTime,
@@ -9,6 +10,10 @@ use std::{
 };
 
 use anyhow::Context;
+use chrono::{
+    DateTime,
+    Utc,
+};
 pub use common::types::SystemKey;
 use common::{
     components::ComponentId,
@@ -35,6 +40,7 @@ use common::{
         AdminKey,
         MemberId,
         PersistenceVersion,
+        StorageUuid,
         TeamId,
         UdfType,
     },
@@ -42,10 +48,24 @@ use common::{
 use errors::ErrorMetadata;
 use openidconnect::{
     core::{
+        CoreGenderClaim,
+        CoreHmacKey,
         CoreIdToken,
+        CoreIdTokenClaims,
         CoreIdTokenVerifier,
+        CoreJsonWebKeySet,
+        CoreJwsSigningAlgorithm,
     },
+    Audience,
+    ClaimsVerificationError,
+    ClientId,
+    ClientSecret,
+    EmptyAdditionalClaims,
+    EndUserEmail,
+    IssuerUrl,
     Nonce,
+    StandardClaims,
+    SubjectIdentifier,
 };
 use pb::{
     convex_actions::ActionCallbackToken as ActionCallbackTokenProto,
@@ -65,6 +85,9 @@ use pb::{
             StoreFile as StoreFileProto,
         },
         AdminKey as AdminKeyProto,
+        ImageTransformUrl as ImageTransformUrlProto,
+        ServiceToken as ServiceTokenProto,
+        SignedFileUrl as SignedFileUrlProto,
         StorageToken as StorageTokenProto,
     },
     convex_query_journal::InstanceQueryJournal as InstanceQueryJournalProto,
@@ -91,6 +114,8 @@ use crate::{
     encryptor::Encryptor,
     metrics::{
         log_actions_token_expired,
+        log_service_token_expired,
+        log_signed_file_url_expired,
         log_store_file_auth_expired,
     },
     secret::InstanceSecret,
@@ -101,6 +126,59 @@ const ADMIN_KEY_VERSION: u8 = 1;
 const CURSOR_VERSION: u8 = 7;
 const STORE_FILE_AUTHZ_VERSION: u8 = 1;
 const QUERY_JOURNAL_VERSION: u8 = 7;
+const SERVICE_TOKEN_VERSION: u8 = 3;
+const SIGNED_FILE_URL_VERSION: u8 = 4;
+const IMAGE_TRANSFORM_URL_VERSION: u8 = 5;
+
+/// Issuer of the ID tokens the backend signs for anonymous identities.
+/// Deployments opt in by adding an auth provider with this domain and
+/// [`ANONYMOUS_IDENTITY_AUDIENCE`] as its application ID.
+pub const ANONYMOUS_IDENTITY_ISSUER: &str = "https://anonymous.convex.dev";
+pub const ANONYMOUS_IDENTITY_AUDIENCE: &str = "anonymous";
+/// Issuer of the ID tokens for users of the built-in auth subsystem, which is
+/// enabled the same way as anonymous identities.
+pub const BUILTIN_AUTH_ISSUER: &str = "https://builtin-auth.convex.dev";
+pub const BUILTIN_AUTH_AUDIENCE: &str = "builtin";
+
+/// ID tokens the backend signs itself rather than an external auth provider.
+/// They're verified with a key derived from the instance secret, so there's
+/// no provider metadata to discover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelfIssuedTokenKind {
+    Anonymous,
+    BuiltinAuth,
+}
+
+impl SelfIssuedTokenKind {
+    pub fn from_issuer(issuer: &str) -> Option<Self> {
+        match issuer.trim_end_matches('/') {
+            ANONYMOUS_IDENTITY_ISSUER => Some(Self::Anonymous),
+            BUILTIN_AUTH_ISSUER => Some(Self::BuiltinAuth),
+            _ => None,
+        }
+    }
+
+    pub fn issuer(&self) -> &'static str {
+        match self {
+            Self::Anonymous => ANONYMOUS_IDENTITY_ISSUER,
+            Self::BuiltinAuth => BUILTIN_AUTH_ISSUER,
+        }
+    }
+
+    pub fn audience(&self) -> &'static str {
+        match self {
+            Self::Anonymous => ANONYMOUS_IDENTITY_AUDIENCE,
+            Self::BuiltinAuth => BUILTIN_AUTH_AUDIENCE,
+        }
+    }
+
+    fn key_purpose(&self) -> &'static str {
+        match self {
+            Self::Anonymous => "anonymous-identity-token",
+            Self::BuiltinAuth => "builtin-auth-token",
+        }
+    }
+}
 
 // Max delay from transaction start time -> key being issued that is tolerable.
 const MAX_TS_DELAY: Duration = Duration::from_secs(15);
@@ -681,6 +759,46 @@ pub struct StoreFileAuthorization(String);
 #[derive(Debug, derive_more::Display)]
 pub struct GetFileAuthorization(String);
 
+/// A short-lived token minted by a mutation or action. It carries a subset of
+/// the minting caller's identity and custom claims, and can be verified by
+/// functions in the same component or by the storage serving path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceToken {
+    pub component: ComponentId,
+    pub issued: UnixTimestamp,
+    pub expires: UnixTimestamp,
+    /// JSON object with the included fields of the minting caller's identity.
+    pub identity: Option<String>,
+    /// JSON object with the token's custom claims.
+    pub claims: String,
+    /// Storage IDs of the files the token grants read access to.
+    pub storage_ids: Vec<String>,
+}
+
+/// A URL for downloading a single file. It expires, can optionally be bound to
+/// the client's IP address, and can be revoked early by recording its nonce.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedFileUrl {
+    pub component: ComponentId,
+    pub storage_id: StorageUuid,
+    pub expires: UnixTimestamp,
+    /// Serve the file as an attachment with this filename.
+    pub filename: Option<String>,
+    /// Only serve the file to clients connecting from this address.
+    pub ip: Option<IpAddr>,
+    pub nonce: String,
+}
+
+/// A URL for a transformed version of an image. Signing the transform
+/// parameters means clients can only request transforms that a function
+/// generated a URL for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageTransformUrl {
+    pub component: ComponentId,
+    pub storage_id: StorageUuid,
+    pub params: String,
+}
+
 pub fn cursor_parse_error() -> ErrorMetadata {
     ErrorMetadata::bad_request("InvalidCursor", "Failed to parse cursor")
 }
@@ -864,6 +982,304 @@ impl KeyBroker {
         Ok(component)
     }
 
+    pub fn issue_service_token(&self, token: ServiceToken) -> String {
+        let ServiceToken {
+            component,
+            issued,
+            expires,
+            identity,
+            claims,
+            storage_ids,
+        } = token;
+        self.encryptor.encode_proto(
+            SERVICE_TOKEN_VERSION,
+            ServiceTokenProto {
+                instance_name: self.instance_name.clone(),
+                issued_s: issued.as_secs(),
+                expires_s: expires.as_secs(),
+                component_id: component.serialize_to_string(),
+                identity,
+                claims,
+                storage_ids,
+            },
+        )
+    }
+
+    /// Checks that `token` was issued by this instance and hasn't expired as
+    /// of `now`.
+    pub fn check_service_token(
+        &self,
+        token: &str,
+        now: UnixTimestamp,
+    ) -> anyhow::Result<ServiceToken> {
+        let invalid = || ErrorMetadata::unauthenticated("InvalidServiceToken", "Invalid token");
+        let ServiceTokenProto {
+            instance_name,
+            issued_s,
+            expires_s,
+            component_id,
+            identity,
+            claims,
+            storage_ids,
+        } = self
+            .encryptor
+            .decode_proto(SERVICE_TOKEN_VERSION, token)
+            .context(invalid())?;
+        if instance_name != self.instance_name || issued_s == 0 || expires_s == 0 {
+            anyhow::bail!(invalid());
+        }
+        if expires_s <= now.as_secs() {
+            log_service_token_expired();
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "ServiceTokenExpired",
+                "Token expired"
+            ));
+        }
+        let component =
+            ComponentId::deserialize_from_string(component_id.as_deref()).context(invalid())?;
+        Ok(ServiceToken {
+            component,
+            issued: UnixTimestamp::from_millis(issued_s * 1000),
+            expires: UnixTimestamp::from_millis(expires_s * 1000),
+            identity,
+            claims,
+            storage_ids,
+        })
+    }
+
+    pub fn issue_signed_file_url(&self, url: SignedFileUrl) -> String {
+        let SignedFileUrl {
+            component,
+            storage_id,
+            expires,
+            filename,
+            ip,
+            nonce,
+        } = url;
+        self.encryptor.encode_proto(
+            SIGNED_FILE_URL_VERSION,
+            SignedFileUrlProto {
+                instance_name: self.instance_name.clone(),
+                component_id: component.serialize_to_string(),
+                storage_id: storage_id.to_string(),
+                expires_s: expires.as_secs(),
+                filename,
+                ip: ip.map(|ip| ip.to_string()),
+                nonce,
+            },
+        )
+    }
+
+    /// Decodes a signed file URL token. Checks that it was issued by this
+    /// instance and hasn't expired as of `now`, but not whether it has been
+    /// revoked or is bound to another IP address.
+    pub fn check_signed_file_url(
+        &self,
+        token: &str,
+        now: UnixTimestamp,
+    ) -> anyhow::Result<SignedFileUrl> {
+        let invalid = || ErrorMetadata::unauthenticated("InvalidSignedUrl", "Invalid signed URL");
+        let SignedFileUrlProto {
+            instance_name,
+            component_id,
+            storage_id,
+            expires_s,
+            filename,
+            ip,
+            nonce,
+        } = self
+            .encryptor
+            .decode_proto(SIGNED_FILE_URL_VERSION, token)
+            .context(invalid())?;
+        if instance_name != self.instance_name || expires_s == 0 || nonce.is_empty() {
+            anyhow::bail!(invalid());
+        }
+        if expires_s <= now.as_secs() {
+            log_signed_file_url_expired();
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "SignedUrlExpired",
+                "Signed URL expired"
+            ));
+        }
+        let component =
+            ComponentId::deserialize_from_string(component_id.as_deref()).context(invalid())?;
+        Ok(SignedFileUrl {
+            component,
+            storage_id: storage_id.parse().context(invalid())?,
+            expires: UnixTimestamp::from_millis(expires_s * 1000),
+            filename,
+            ip: ip.map(|ip| ip.parse()).transpose().context(invalid())?,
+            nonce,
+        })
+    }
+
+    pub fn issue_image_transform_url(&self, url: ImageTransformUrl) -> String {
+        let ImageTransformUrl {
+            component,
+            storage_id,
+            params,
+        } = url;
+        self.encryptor.encode_proto(
+            IMAGE_TRANSFORM_URL_VERSION,
+            ImageTransformUrlProto {
+                instance_name: self.instance_name.clone(),
+                component_id: component.serialize_to_string(),
+                storage_id: storage_id.to_string(),
+                params,
+            },
+        )
+    }
+
+    pub fn check_image_transform_url(&self, token: &str) -> anyhow::Result<ImageTransformUrl> {
+        let invalid = || ErrorMetadata::unauthenticated("InvalidImageUrl", "Invalid image URL");
+        let ImageTransformUrlProto {
+            instance_name,
+            component_id,
+            storage_id,
+            params,
+        } = self
+            .encryptor
+            .decode_proto(IMAGE_TRANSFORM_URL_VERSION, token)
+            .context(invalid())?;
+        if instance_name != self.instance_name {
+            anyhow::bail!(invalid());
+        }
+        let component =
+            ComponentId::deserialize_from_string(component_id.as_deref()).context(invalid())?;
+        Ok(ImageTransformUrl {
+            component,
+            storage_id: storage_id.parse().context(invalid())?,
+            params,
+        })
+    }
+
+    /// Self-issued tokens are signed with a key derived from the instance
+    /// secret and name. It's hex encoded since the verifier takes it as an
+    /// OAuth client secret string.
+    fn self_issued_token_key(&self, kind: SelfIssuedTokenKind) -> String {
+        let purpose = format!("{}:{}", kind.key_purpose(), self.instance_name);
+        hex::encode(self.encryptor.derive_key(&purpose))
+    }
+
+    fn issue_self_issued_token(
+        &self,
+        kind: SelfIssuedTokenKind,
+        claims: StandardClaims<CoreGenderClaim>,
+        issued: SystemTime,
+        expires: SystemTime,
+    ) -> anyhow::Result<String> {
+        let claims = CoreIdTokenClaims::new(
+            IssuerUrl::new(kind.issuer().to_owned())?,
+            vec![Audience::new(kind.audience().to_owned())],
+            DateTime::<Utc>::from(expires),
+            DateTime::<Utc>::from(issued),
+            claims,
+            EmptyAdditionalClaims {},
+        );
+        let token = CoreIdToken::new(
+            claims,
+            &CoreHmacKey::new(self.self_issued_token_key(kind)),
+            CoreJwsSigningAlgorithm::HmacSha256,
+            None,
+            None,
+        )?;
+        Ok(token.to_string())
+    }
+
+    /// Checks that `token` is a token of the given kind issued by this
+    /// instance that hasn't expired as of `now`.
+    pub fn check_self_issued_token(
+        &self,
+        kind: SelfIssuedTokenKind,
+        token: &str,
+        now: SystemTime,
+    ) -> anyhow::Result<UserIdentity> {
+        let (invalid_code, expired_code, name) = match kind {
+            SelfIssuedTokenKind::Anonymous => (
+                "InvalidAnonymousIdentityToken",
+                "AnonymousIdentityTokenExpired",
+                "Anonymous identity token",
+            ),
+            SelfIssuedTokenKind::BuiltinAuth => (
+                "InvalidBuiltinAuthToken",
+                "BuiltinAuthTokenExpired",
+                "Built-in auth token",
+            ),
+        };
+        let invalid = || {
+            ErrorMetadata::unauthenticated(invalid_code, format!("Invalid {}", name.to_lowercase()))
+        };
+        let token: CoreIdToken = token.parse().context(invalid())?;
+        let verifier = CoreIdTokenVerifier::new_confidential_client(
+            ClientId::new(kind.audience().to_owned()),
+            ClientSecret::new(self.self_issued_token_key(kind)),
+            IssuerUrl::new(kind.issuer().to_owned())?,
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+        .set_time_fn(move || DateTime::<Utc>::from(now));
+        match UserIdentity::from_token(token, verifier) {
+            Ok(identity) => Ok(identity),
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<ClaimsVerificationError>(),
+                    Some(ClaimsVerificationError::Expired(_))
+                ) =>
+            {
+                Err(e.context(ErrorMetadata::unauthenticated(
+                    expired_code,
+                    format!("{name} expired"),
+                )))
+            },
+            Err(e) => Err(e.context(invalid())),
+        }
+    }
+
+    /// Issue an OpenID Connect ID token for the anonymous identity
+    /// `anonymous_id`, so clients can authenticate with it like with any other
+    /// provider's token.
+    pub fn issue_anonymous_identity_token(
+        &self,
+        anonymous_id: String,
+        issued: SystemTime,
+        expires: SystemTime,
+    ) -> anyhow::Result<String> {
+        self.issue_self_issued_token(
+            SelfIssuedTokenKind::Anonymous,
+            StandardClaims::new(SubjectIdentifier::new(anonymous_id)),
+            issued,
+            expires,
+        )
+    }
+
+    pub fn check_anonymous_identity_token(
+        &self,
+        token: &str,
+        now: SystemTime,
+    ) -> anyhow::Result<UserIdentity> {
+        self.check_self_issued_token(SelfIssuedTokenKind::Anonymous, token, now)
+    }
+
+    /// Issue an OpenID Connect ID token for a user of the built-in auth
+    /// subsystem.
+    pub fn issue_builtin_auth_token(
+        &self,
+        user_id: String,
+        email: String,
+        email_verified: bool,
+        issued: SystemTime,
+        expires: SystemTime,
+    ) -> anyhow::Result<String> {
+        self.issue_self_issued_token(
+            SelfIssuedTokenKind::BuiltinAuth,
+            StandardClaims::new(SubjectIdentifier::new(user_id))
+                .set_email(Some(EndUserEmail::new(email)))
+                .set_email_verified(Some(email_verified)),
+            issued,
+            expires,
+        )
+    }
+
     fn cursor_to_proto(&self, cursor: &Cursor) -> InstanceCursorProto {
         let position = match cursor.position {
             CursorPosition::End => PositionProto::End(()),
@@ -875,6 +1291,7 @@ impl KeyBroker {
             instance_name: self.instance_name.clone(),
             position: Some(position),
             query_fingerprint: cursor.query_fingerprint.clone(),
+            index_fingerprint: cursor.index_fingerprint.clone(),
         }
     }
 
@@ -899,6 +1316,7 @@ impl KeyBroker {
         Ok(Cursor {
             position: cursor_position,
             query_fingerprint: proto.query_fingerprint,
+            index_fingerprint: proto.index_fingerprint,
         })
     }
 
@@ -959,7 +1377,10 @@ impl KeyBroker {
                     Some(cursor) => Some(self.proto_to_cursor(cursor)?),
                     None => None,
                 };
-                Ok(QueryJournal { end_cursor })
+                Ok(QueryJournal {
+                    end_cursor,
+                    page_split: None,
+                })
             },
         }
     }
@@ -1028,13 +1449,17 @@ mod tests {
         components::ComponentId,
         index::IndexKey,
         query::{
+            index_fingerprint,
             Cursor,
             CursorPosition,
             Order,
             Query,
         },
         query_journal::QueryJournal,
-        runtime::Runtime,
+        runtime::{
+            Runtime,
+            UnixTimestamp,
+        },
         types::{
             MemberId,
             PersistenceVersion,
@@ -1052,8 +1477,13 @@ mod tests {
 
     use super::{
         AdminKey,
+        ImageTransformUrl,
         KeyBroker,
+        SelfIssuedTokenKind,
+        ServiceToken,
+        SignedFileUrl,
         ADMIN_KEY_VERSION,
+        ANONYMOUS_IDENTITY_ISSUER,
     };
     use crate::{
         AdminIdentity,
@@ -1146,6 +1576,7 @@ mod tests {
         let cursor = Cursor {
             position: CursorPosition::End,
             query_fingerprint: vec![],
+            index_fingerprint: Some(vec![1, 2, 3]),
         };
         let encrypted = kb.encrypt_cursor(&cursor, PersistenceVersion::default());
         let echoed = kb.decrypt_cursor(encrypted, PersistenceVersion::default())?;
@@ -1184,10 +1615,11 @@ mod tests {
                 IndexKey::new(vec![100.into()], DeveloperDocumentId::MIN).into_bytes(),
             ),
             query_fingerprint: query.fingerprint(&IndexedFields::creation_time())?,
+            index_fingerprint: Some(index_fingerprint(&IndexedFields::creation_time())),
         });
         let serialized_journal_with_cursor =
             kb.encrypt_query_journal(&journal_with_cursor, PersistenceVersion::default());
-        assert_eq!(serialized_journal_with_cursor.unwrap().len(), 252);
+        assert_eq!(serialized_journal_with_cursor.unwrap().len(), 272);
         Ok(())
     }
 
@@ -1220,6 +1652,149 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_service_token() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        let td = TestDriver::new();
+        let now = td.rt().unix_timestamp();
+        let token = ServiceToken {
+            component: ComponentId::test_user(),
+            issued: now,
+            expires: now + Duration::from_secs(60),
+            identity: Some(r#"{"subject":"user1"}"#.to_string()),
+            claims: r#"{"scope":"read"}"#.to_string(),
+            storage_ids: vec!["8b4e9c3a-4f3b-4b5e-9a0d-5f1d2c3b4a5e".to_string()],
+        };
+        let encoded = kb.issue_service_token(token.clone());
+        let mut checked = kb.check_service_token(&encoded, now)?;
+        // Timestamps are rounded down to the second.
+        checked.issued = token.issued;
+        checked.expires = token.expires;
+        assert_eq!(checked, token);
+
+        let err = kb
+            .check_service_token(&encoded, now + Duration::from_secs(120))
+            .unwrap_err();
+        assert!(format!("{err}").contains("Token expired"));
+        kb.check_service_token("invalid-token", now).unwrap_err();
+        KeyBroker::local_dev("other-instance")
+            .check_service_token(&encoded, now)
+            .unwrap_err();
+        // Other kinds of keys aren't service tokens.
+        let action_token = kb.issue_action_token(ComponentId::test_user());
+        kb.check_service_token(&action_token, now).unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_anonymous_identity_token() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        let now = SystemTime::now();
+        let token = kb.issue_anonymous_identity_token(
+            "anon1".to_string(),
+            now,
+            now + Duration::from_secs(60),
+        )?;
+        let identity = kb.check_anonymous_identity_token(&token, now)?;
+        assert_eq!(identity.subject, "anon1");
+        assert_eq!(identity.issuer, ANONYMOUS_IDENTITY_ISSUER);
+
+        let err = kb
+            .check_anonymous_identity_token(&token, now + Duration::from_secs(120))
+            .unwrap_err();
+        assert!(format!("{err}").contains("Anonymous identity token expired"));
+        kb.check_anonymous_identity_token("invalid-token", now)
+            .unwrap_err();
+        KeyBroker::local_dev("other-instance")
+            .check_anonymous_identity_token(&token, now)
+            .unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_builtin_auth_token() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        let now = SystemTime::now();
+        let token = kb.issue_builtin_auth_token(
+            "user1".to_string(),
+            "user@example.com".to_string(),
+            true,
+            now,
+            now + Duration::from_secs(60),
+        )?;
+        let identity = kb.check_self_issued_token(SelfIssuedTokenKind::BuiltinAuth, &token, now)?;
+        assert_eq!(identity.subject, "user1");
+        assert_eq!(
+            identity.attributes.email.as_deref(),
+            Some("user@example.com")
+        );
+        assert_eq!(identity.attributes.email_verified, Some(true));
+        // Tokens of one kind aren't accepted as another.
+        kb.check_anonymous_identity_token(&token, now).unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_file_url() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        let td = TestDriver::new();
+        let now = td.rt().unix_timestamp();
+        let url = SignedFileUrl {
+            component: ComponentId::test_user(),
+            storage_id: "8b4e9c3a-4f3b-4b5e-9a0d-5f1d2c3b4a5e".parse()?,
+            expires: UnixTimestamp::from_millis((now.as_secs() + 60) * 1000),
+            filename: Some("report.pdf".to_string()),
+            ip: Some("203.0.113.7".parse()?),
+            nonce: "nonce".to_string(),
+        };
+        let encoded = kb.issue_signed_file_url(url.clone());
+        assert_eq!(kb.check_signed_file_url(&encoded, now)?, url);
+
+        let err = kb
+            .check_signed_file_url(&encoded, now + Duration::from_secs(120))
+            .unwrap_err();
+        assert!(format!("{err}").contains("Signed URL expired"));
+        KeyBroker::local_dev("other-instance")
+            .check_signed_file_url(&encoded, now)
+            .unwrap_err();
+        // Service tokens aren't signed URLs.
+        let service_token = kb.issue_service_token(ServiceToken {
+            component: ComponentId::test_user(),
+            issued: now,
+            expires: now + Duration::from_secs(60),
+            identity: None,
+            claims: "{}".to_string(),
+            storage_ids: vec![],
+        });
+        kb.check_signed_file_url(&service_token, now).unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_image_transform_url() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        let url = ImageTransformUrl {
+            component: ComponentId::test_user(),
+            storage_id: "8b4e9c3a-4f3b-4b5e-9a0d-5f1d2c3b4a5e".parse()?,
+            params: "w=200&h=100&fit=cover&format=webp".to_string(),
+        };
+        let encoded = kb.issue_image_transform_url(url.clone());
+        assert_eq!(kb.check_image_transform_url(&encoded)?, url);
+        KeyBroker::local_dev("other-instance")
+            .check_image_transform_url(&encoded)
+            .unwrap_err();
+        let signed_url = kb.issue_signed_file_url(SignedFileUrl {
+            component: ComponentId::test_user(),
+            storage_id: url.storage_id,
+            expires: UnixTimestamp::from_millis(1000),
+            filename: None,
+            ip: None,
+            nonce: "nonce".to_string(),
+        });
+        kb.check_image_transform_url(&signed_url).unwrap_err();
+        Ok(())
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig { cases: 64 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, .. ProptestConfig::default() })]
 
@@ -1239,7 +1814,9 @@ mod tests {
                 encrypted,
                 PersistenceVersion::default(),
             ).unwrap();
-            assert_eq!(journal, decrypted);
+            // Page splits are reported to the client separately, not encrypted
+            // into the journal.
+            assert_eq!(QueryJournal { page_split: None, ..journal }, decrypted);
         }
 
         #[test]
@@ -2,7 +2,10 @@ use std::io::Read;
 
 use byteorder::ReadBytesExt;
 use prost::Message;
-use sodiumoxide::crypto::secretbox;
+use sodiumoxide::crypto::{
+    auth::hmacsha256,
+    secretbox,
+};
 
 use crate::secret::Secret;
 
@@ -18,6 +21,13 @@ impl Encryptor {
         })
     }
 
+    /// Derive a key for `purpose` from the secret, for signing schemes that
+    /// can't use the secretbox key directly.
+    pub fn derive_key(&self, purpose: &str) -> [u8; 32] {
+        let key = hmacsha256::Key(self.secret.0);
+        hmacsha256::authenticate(purpose.as_bytes(), &key).0
+    }
+
     pub fn encode_proto(&self, version: u8, message: impl Message) -> String {
         let nonce = secretbox::gen_nonce();
         let plaintext = message.encode_to_vec();
@@ -5,9 +5,11 @@
 mod broker;
 mod encryptor;
 mod metrics;
+mod password;
 mod secret;
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
+mod webauthn;
 
 pub use sync_types::UserIdentityAttributes;
 
@@ -17,16 +19,36 @@ pub use self::{
         AdminIdentityPrincipal,
         GetFileAuthorization,
         Identity,
+        ImageTransformUrl,
         KeyBroker,
+        SelfIssuedTokenKind,
+        ServiceToken,
+        SignedFileUrl,
         StoreFileAuthorization,
         SystemKey,
         UserIdentity,
+        ANONYMOUS_IDENTITY_AUDIENCE,
+        ANONYMOUS_IDENTITY_ISSUER,
+        BUILTIN_AUTH_AUDIENCE,
+        BUILTIN_AUTH_ISSUER,
     },
     encryptor::Encryptor,
+    password::{
+        hash_password,
+        verify_password,
+    },
     secret::{
         InstanceSecret,
         Secret,
     },
+    webauthn::{
+        client_data_challenge,
+        verify_passkey_assertion,
+        verify_passkey_registration,
+        RegisteredPasskey,
+        RelyingParty,
+        COSE_ALG_ES256,
+    },
 };
 
 pub const DEV_INSTANCE_NAME: &str = include_str!("../dev/instance_name.txt");
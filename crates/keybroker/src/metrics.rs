@@ -18,3 +18,19 @@ register_convex_counter!(
 pub fn log_actions_token_expired() {
     log_counter(&KEYBROKER_ACTIONS_TOKEN_EXPIRED_TOTAL, 1);
 }
+
+register_convex_counter!(
+    KEYBROKER_SERVICE_TOKEN_EXPIRED_TOTAL,
+    "Number of times a service token was rejected because it was expired"
+);
+pub fn log_service_token_expired() {
+    log_counter(&KEYBROKER_SERVICE_TOKEN_EXPIRED_TOTAL, 1);
+}
+
+register_convex_counter!(
+    KEYBROKER_SIGNED_FILE_URL_EXPIRED_TOTAL,
+    "Number of times a signed file URL was rejected because it was expired"
+);
+pub fn log_signed_file_url_expired() {
+    log_counter(&KEYBROKER_SIGNED_FILE_URL_EXPIRED_TOTAL, 1);
+}
@@ -0,0 +1,48 @@
+//! Password hashing for the built-in auth subsystem.
+
+use sodiumoxide::crypto::pwhash::argon2id13;
+
+/// Hash `password` with Argon2id. The encoded hash includes the salt and cost
+/// parameters, so it's all that needs to be stored. Hashing is deliberately
+/// expensive, so callers shouldn't run it directly on an async executor.
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let hash = argon2id13::pwhash(
+        password.as_bytes(),
+        argon2id13::OPSLIMIT_INTERACTIVE,
+        argon2id13::MEMLIMIT_INTERACTIVE,
+    )
+    .map_err(|()| anyhow::anyhow!("Failed to hash password"))?;
+    // The encoded hash is a NUL-padded ASCII string.
+    let len = hash.0.iter().position(|b| *b == 0).unwrap_or(hash.0.len());
+    Ok(String::from_utf8(hash.0[..len].to_vec())?)
+}
+
+/// Whether `password` matches a hash from [`hash_password`].
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    let mut bytes = [0u8; argon2id13::HASHEDPASSWORDBYTES];
+    if hash.len() >= bytes.len() {
+        return false;
+    }
+    bytes[..hash.len()].copy_from_slice(hash.as_bytes());
+    argon2id13::pwhash_verify(&argon2id13::HashedPassword(bytes), password.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        hash_password,
+        verify_password,
+    };
+
+    #[test]
+    fn test_password_hash() -> anyhow::Result<()> {
+        let hash = hash_password("hunter2")?;
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password(&hash, "hunter2"));
+        assert!(!verify_password(&hash, "hunter3"));
+        assert!(!verify_password("not a hash", "hunter2"));
+        // Hashes are salted.
+        assert_ne!(hash_password("hunter2")?, hash);
+        Ok(())
+    }
+}
@@ -0,0 +1,455 @@
+//! Verification of WebAuthn registration and assertion responses for passkey
+//! sign-in in the built-in auth subsystem.
+//!
+//! Only ES256 credentials are supported, which every passkey provider offers.
+//! We ask for `"none"` attestation since we don't restrict which
+//! authenticators can be used, so the only attestation statements accepted
+//! are `"none"` and packed self attestation.
+
+use ciborium::value::Value as CborValue;
+use common::value::{
+    base64::decode_urlsafe,
+    sha256::Sha256,
+};
+use errors::ErrorMetadata;
+use ring::signature::{
+    UnparsedPublicKey,
+    ECDSA_P256_SHA256_ASN1,
+};
+use serde::Deserialize;
+
+/// COSE algorithm identifier for ECDSA with SHA-256 on P-256.
+pub const COSE_ALG_ES256: i64 = -7;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// The site passkeys are scoped to.
+pub struct RelyingParty<'a> {
+    /// Usually the origin's host.
+    pub id: &'a str,
+    /// The origin the WebAuthn ceremonies must run on, e.g.
+    /// `"https://example.com"`.
+    pub origin: &'a str,
+}
+
+/// A passkey from a verified registration, to be stored with the user.
+#[derive(Debug)]
+pub struct RegisteredPasskey {
+    pub credential_id: Vec<u8>,
+    /// Uncompressed SEC1 encoding of the P-256 public key.
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClientData {
+    #[serde(rename = "type")]
+    ceremony: String,
+    challenge: String,
+    origin: String,
+}
+
+struct AuthenticatorData<'a> {
+    rp_id_hash: &'a [u8],
+    flags: u8,
+    sign_count: u32,
+    attested_credential_data: &'a [u8],
+}
+
+fn invalid_registration(msg: impl Into<String>) -> anyhow::Error {
+    ErrorMetadata::bad_request("InvalidPasskeyRegistration", msg.into()).into()
+}
+
+fn invalid_assertion(msg: impl Into<String>) -> anyhow::Error {
+    ErrorMetadata::unauthenticated("InvalidPasskeyAssertion", msg.into()).into()
+}
+
+/// Decode the CBOR item at the start of `bytes`, returning it along with the
+/// bytes after it.
+fn decode_cbor_prefix(bytes: &[u8]) -> Result<(CborValue, &[u8]), String> {
+    let mut rest = bytes;
+    let value = ciborium::de::from_reader(&mut rest).map_err(|e| e.to_string())?;
+    Ok((value, rest))
+}
+
+/// Decode `bytes`, which must hold exactly one CBOR item.
+fn decode_cbor(bytes: &[u8]) -> Result<CborValue, String> {
+    let (value, rest) = decode_cbor_prefix(bytes)?;
+    if !rest.is_empty() {
+        return Err("Trailing bytes after CBOR item".to_string());
+    }
+    Ok(value)
+}
+
+fn cbor_get<'a>(map: &'a CborValue, key: impl Into<CborValue>) -> Option<&'a CborValue> {
+    let CborValue::Map(entries) = map else {
+        return None;
+    };
+    let key = key.into();
+    entries.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+}
+
+/// The challenge the client signed, so the caller can look up the ceremony
+/// it belongs to before verifying the response.
+pub fn client_data_challenge(client_data_json: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let invalid = |msg| ErrorMetadata::bad_request("InvalidPasskeyResponse", msg);
+    let client_data: ClientData = serde_json::from_slice(client_data_json)
+        .map_err(|e| invalid(format!("Invalid client data: {e}")))?;
+    Ok(decode_urlsafe(&client_data.challenge)
+        .map_err(|e| invalid(format!("Invalid challenge: {e}")))?)
+}
+
+fn check_client_data(
+    rp: &RelyingParty,
+    ceremony: &str,
+    challenge: &[u8],
+    client_data_json: &[u8],
+) -> Result<(), String> {
+    let client_data: ClientData = serde_json::from_slice(client_data_json)
+        .map_err(|e| format!("Invalid client data: {e}"))?;
+    if client_data.ceremony != ceremony {
+        return Err(format!(
+            "Expected a {ceremony} response but got {}",
+            client_data.ceremony
+        ));
+    }
+    if decode_urlsafe(&client_data.challenge).ok().as_deref() != Some(challenge) {
+        return Err("Response is for a different challenge".to_string());
+    }
+    if client_data.origin != rp.origin {
+        return Err(format!(
+            "Response is from {} instead of {}",
+            client_data.origin, rp.origin
+        ));
+    }
+    Ok(())
+}
+
+fn parse_authenticator_data<'a>(
+    rp: &RelyingParty,
+    bytes: &'a [u8],
+) -> Result<AuthenticatorData<'a>, String> {
+    if bytes.len() < 37 {
+        return Err("Authenticator data is too short".to_string());
+    }
+    let data = AuthenticatorData {
+        rp_id_hash: &bytes[..32],
+        flags: bytes[32],
+        sign_count: u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]),
+        attested_credential_data: &bytes[37..],
+    };
+    if data.rp_id_hash != &Sha256::hash(rp.id.as_bytes())[..] {
+        return Err(format!("Credential isn't scoped to {}", rp.id));
+    }
+    // Passkeys stand in for a password, so the authenticator must have
+    // verified the user, e.g. with a biometric or PIN.
+    if data.flags & FLAG_USER_PRESENT == 0 || data.flags & FLAG_USER_VERIFIED == 0 {
+        return Err("Authenticator didn't verify the user".to_string());
+    }
+    Ok(data)
+}
+
+/// Convert a COSE EC2 key to an uncompressed SEC1 public key.
+fn parse_cose_key(key: &CborValue) -> Result<Vec<u8>, String> {
+    let int = |label: i64| match cbor_get(key, label) {
+        Some(CborValue::Integer(i)) => Some(i128::from(*i)),
+        _ => None,
+    };
+    let bytes = |label: i64| match cbor_get(key, label) {
+        Some(CborValue::Bytes(b)) if b.len() == 32 => Some(b.as_slice()),
+        _ => None,
+    };
+    // kty 2 is EC2 and crv 1 is P-256.
+    if int(1) != Some(2) || int(3) != Some(COSE_ALG_ES256 as i128) || int(-1) != Some(1) {
+        return Err("Only ES256 passkeys are supported".to_string());
+    }
+    let (Some(x), Some(y)) = (bytes(-2), bytes(-3)) else {
+        return Err("Invalid credential public key".to_string());
+    };
+    Ok([&[0x04], x, y].concat())
+}
+
+fn verify_signature(
+    public_key: &[u8],
+    authenticator_data: &[u8],
+    client_data_json: &[u8],
+    signature: &[u8],
+) -> bool {
+    let message = [authenticator_data, &Sha256::hash(client_data_json)[..]].concat();
+    UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key)
+        .verify(&message, signature)
+        .is_ok()
+}
+
+/// Verify the response to a `navigator.credentials.create()` call for
+/// `challenge`, returning the new passkey.
+pub fn verify_passkey_registration(
+    rp: &RelyingParty,
+    challenge: &[u8],
+    client_data_json: &[u8],
+    attestation_object: &[u8],
+) -> anyhow::Result<RegisteredPasskey> {
+    check_client_data(rp, "webauthn.create", challenge, client_data_json)
+        .map_err(invalid_registration)?;
+    let attestation = decode_cbor(attestation_object)
+        .map_err(|e| invalid_registration(format!("Invalid attestation object: {e}")))?;
+    let (
+        Some(CborValue::Text(fmt)),
+        Some(statement @ CborValue::Map(_)),
+        Some(CborValue::Bytes(auth_data_bytes)),
+    ) = (
+        cbor_get(&attestation, "fmt"),
+        cbor_get(&attestation, "attStmt"),
+        cbor_get(&attestation, "authData"),
+    )
+    else {
+        return Err(invalid_registration("Invalid attestation object"));
+    };
+    let auth_data = parse_authenticator_data(rp, auth_data_bytes).map_err(invalid_registration)?;
+    if auth_data.flags & FLAG_ATTESTED_CREDENTIAL_DATA == 0 {
+        return Err(invalid_registration(
+            "Response doesn't include a credential",
+        ));
+    }
+    // The attested credential data is a 16 byte AAGUID, then the length
+    // prefixed credential ID, then the COSE public key.
+    let credential_data = auth_data.attested_credential_data;
+    if credential_data.len() < 18 {
+        return Err(invalid_registration("Invalid attested credential data"));
+    }
+    let id_len = u16::from_be_bytes([credential_data[16], credential_data[17]]) as usize;
+    let Some(credential_id) = credential_data.get(18..18 + id_len) else {
+        return Err(invalid_registration("Invalid attested credential data"));
+    };
+    let (cose_key, _) = decode_cbor_prefix(&credential_data[18 + id_len..])
+        .map_err(|e| invalid_registration(format!("Invalid credential public key: {e}")))?;
+    let public_key = parse_cose_key(&cose_key).map_err(invalid_registration)?;
+
+    match &fmt[..] {
+        "none" => {
+            if statement != &CborValue::Map(vec![]) {
+                return Err(invalid_registration("Unexpected attestation statement"));
+            }
+        },
+        "packed" if cbor_get(statement, "x5c").is_none() => {
+            // Self attestation is signed with the credential's own key.
+            let (Some(CborValue::Integer(alg)), Some(CborValue::Bytes(sig))) =
+                (cbor_get(statement, "alg"), cbor_get(statement, "sig"))
+            else {
+                return Err(invalid_registration("Invalid packed attestation"));
+            };
+            if i128::from(*alg) != COSE_ALG_ES256 as i128
+                || !verify_signature(&public_key, auth_data_bytes, client_data_json, sig)
+            {
+                return Err(invalid_registration("Invalid packed attestation"));
+            }
+        },
+        fmt => {
+            return Err(invalid_registration(format!(
+                "Unsupported attestation format {fmt:?}. Request \"none\" attestation."
+            )));
+        },
+    }
+    Ok(RegisteredPasskey {
+        credential_id: credential_id.to_vec(),
+        public_key,
+        sign_count: auth_data.sign_count,
+    })
+}
+
+/// Verify the response to a `navigator.credentials.get()` call for
+/// `challenge`, signed by the passkey with `public_key`. Returns the
+/// authenticator's new signature count.
+pub fn verify_passkey_assertion(
+    rp: &RelyingParty,
+    challenge: &[u8],
+    client_data_json: &[u8],
+    authenticator_data: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+    sign_count: u32,
+) -> anyhow::Result<u32> {
+    check_client_data(rp, "webauthn.get", challenge, client_data_json)
+        .map_err(invalid_assertion)?;
+    let auth_data = parse_authenticator_data(rp, authenticator_data).map_err(invalid_assertion)?;
+    if !verify_signature(public_key, authenticator_data, client_data_json, signature) {
+        return Err(invalid_assertion("Invalid passkey signature"));
+    }
+    // Authenticators that keep a counter increment it on every assertion, so
+    // a counter that went backwards means the credential was cloned. Synced
+    // passkeys always report zero.
+    if (auth_data.sign_count != 0 || sign_count != 0) && auth_data.sign_count <= sign_count {
+        return Err(invalid_assertion(
+            "Passkey signature counter went backwards",
+        ));
+    }
+    Ok(auth_data.sign_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use common::value::{
+        base64::encode_urlsafe,
+        sha256::Sha256,
+    };
+    use errors::ErrorMetadataAnyhowExt;
+    use ring::{
+        rand::SystemRandom,
+        signature::{
+            EcdsaKeyPair,
+            KeyPair,
+            ECDSA_P256_SHA256_ASN1_SIGNING,
+        },
+    };
+    use serde_json::json;
+
+    use super::{
+        verify_passkey_assertion,
+        verify_passkey_registration,
+        CborValue,
+        RelyingParty,
+        COSE_ALG_ES256,
+    };
+
+    const RP: RelyingParty = RelyingParty {
+        id: "example.com",
+        origin: "https://example.com",
+    };
+
+    fn client_data(ceremony: &str, challenge: &[u8]) -> Vec<u8> {
+        serde_json::to_vec(&json!({
+            "type": ceremony,
+            "challenge": encode_urlsafe(challenge),
+            "origin": RP.origin,
+        }))
+        .unwrap()
+    }
+
+    fn auth_data(sign_count: u32, attested_credential_data: &[u8]) -> Vec<u8> {
+        let flags = if attested_credential_data.is_empty() {
+            0x05
+        } else {
+            0x45
+        };
+        [
+            &Sha256::hash(RP.id.as_bytes())[..],
+            &[flags],
+            &sign_count.to_be_bytes(),
+            attested_credential_data,
+        ]
+        .concat()
+    }
+
+    fn to_cbor(value: CborValue) -> Vec<u8> {
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(&value, &mut bytes).unwrap();
+        bytes
+    }
+
+    /// An attestation object with "none" attestation for an ES256 key.
+    fn attestation_object(credential_id: &[u8], public_key: &[u8]) -> Vec<u8> {
+        let cose_key = to_cbor(CborValue::Map(vec![
+            (1.into(), 2.into()),
+            (3.into(), COSE_ALG_ES256.into()),
+            ((-1).into(), 1.into()),
+            ((-2).into(), public_key[1..33].to_vec().into()),
+            ((-3).into(), public_key[33..].to_vec().into()),
+        ]));
+        let credential_data = [
+            &[0; 16][..],
+            &(credential_id.len() as u16).to_be_bytes(),
+            credential_id,
+            &cose_key,
+        ]
+        .concat();
+        to_cbor(CborValue::Map(vec![
+            ("fmt".into(), "none".into()),
+            ("attStmt".into(), CborValue::Map(vec![])),
+            ("authData".into(), auth_data(0, &credential_data).into()),
+        ]))
+    }
+
+    #[test]
+    fn test_passkey_ceremonies() -> anyhow::Result<()> {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let key_pair =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+                .unwrap();
+        let public_key = key_pair.public_key().as_ref().to_vec();
+
+        let attestation = attestation_object(b"credential", &public_key);
+        let passkey = verify_passkey_registration(
+            &RP,
+            b"challenge",
+            &client_data("webauthn.create", b"challenge"),
+            &attestation,
+        )?;
+        assert_eq!(passkey.credential_id, b"credential");
+        assert_eq!(passkey.public_key, public_key);
+        let err = verify_passkey_registration(
+            &RP,
+            b"other challenge",
+            &client_data("webauthn.create", b"challenge"),
+            &attestation,
+        )
+        .unwrap_err();
+        assert_eq!(err.short_msg(), "InvalidPasskeyRegistration");
+
+        let sign = |sign_count: u32, challenge: &[u8]| {
+            let authenticator_data = auth_data(sign_count, &[]);
+            let client_data_json = client_data("webauthn.get", challenge);
+            let message = [
+                &authenticator_data[..],
+                &Sha256::hash(&client_data_json)[..],
+            ]
+            .concat();
+            let signature = key_pair.sign(&rng, &message).unwrap();
+            (
+                authenticator_data,
+                client_data_json,
+                signature.as_ref().to_vec(),
+            )
+        };
+        let (authenticator_data, client_data_json, signature) = sign(1, b"challenge");
+        let sign_count = verify_passkey_assertion(
+            &RP,
+            b"challenge",
+            &client_data_json,
+            &authenticator_data,
+            &signature,
+            &passkey.public_key,
+            passkey.sign_count,
+        )?;
+        assert_eq!(sign_count, 1);
+
+        // Replayed and tampered assertions are rejected.
+        let err = verify_passkey_assertion(
+            &RP,
+            b"challenge",
+            &client_data_json,
+            &authenticator_data,
+            &signature,
+            &passkey.public_key,
+            sign_count,
+        )
+        .unwrap_err();
+        assert_eq!(err.short_msg(), "InvalidPasskeyAssertion");
+        let (mut authenticator_data, client_data_json, signature) = sign(2, b"challenge");
+        authenticator_data[36] = 3;
+        assert!(verify_passkey_assertion(
+            &RP,
+            b"challenge",
+            &client_data_json,
+            &authenticator_data,
+            &signature,
+            &passkey.public_key,
+            sign_count,
+        )
+        .is_err());
+        Ok(())
+    }
+}
@@ -19,6 +19,7 @@ use common::{
         UdfType,
     },
 };
+use http::header::CONTENT_TYPE;
 use serde::Deserialize;
 use sync_types::UdfPath;
 
@@ -27,6 +28,8 @@ use crate::{
     LocalAppState,
 };
 
+const PROMETHEUS_TEXT_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct UdfRateQueryArgs {
@@ -61,6 +64,17 @@ pub(crate) async fn udf_rate(
     Ok(Json(timeseries.clone()))
 }
 
+/// Per-function execution counts and latencies, OCC retries and per-table
+/// bytes read and written, for scraping by Prometheus. Scrapers authenticate
+/// with the admin key as `Authorization: Convex <admin key>`.
+pub(crate) async fn prometheus_metrics(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let metrics = st.application.prometheus_metrics(identity).await?;
+    Ok(([(CONTENT_TYPE, PROMETHEUS_TEXT_CONTENT_TYPE)], metrics))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct TopKQueryArgs {
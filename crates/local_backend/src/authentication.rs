@@ -33,6 +33,7 @@ use sync_types::{
 };
 
 use crate::{
+    ip_access::ExtractClientIp,
     LocalAppState,
     RouterState,
 };
@@ -119,11 +120,12 @@ where
     ) -> Result<Self, Self::Rejection> {
         let token: AuthenticationToken =
             parts.extract::<ExtractAuthenticationToken>().await?.into();
+        let Ok(ExtractClientIp(client_ip)) = parts.extract::<ExtractClientIp>().await;
         let st = LocalAppState::from_ref(st);
 
         Ok(Self(
             st.application
-                .authenticate(token, st.application.runtime().system_time())
+                .authenticate_from(token, st.application.runtime().system_time(), client_ip)
                 .await?,
         ))
     }
@@ -156,7 +158,12 @@ impl FromRequestParts<RouterState> for TryExtractIdentity {
             Ok(id) => id,
             Err(e) => return Ok(Self(Err(e.into()))),
         };
-        Ok(Self(st.api.authenticate(&host, request_id.0, token).await))
+        let Ok(ExtractClientIp(client_ip)) = parts.extract::<ExtractClientIp>().await;
+        Ok(Self(
+            st.api
+                .authenticate_from(&host, request_id.0, token, client_ip)
+                .await,
+        ))
     }
 }
 
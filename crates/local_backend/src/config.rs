@@ -75,6 +75,11 @@ pub struct LocalConfig {
     /// tests.
     #[clap(long)]
     pub do_not_require_ssl: bool,
+
+    /// Path to a JSON file listing log sinks to stream function logs to, e.g.
+    /// `[{"name": "dd", "type": "datadog", "apiKey": "..."}]`.
+    #[clap(long)]
+    pub log_sinks_config: Option<PathBuf>,
 }
 
 impl fmt::Debug for LocalConfig {
@@ -0,0 +1,129 @@
+use std::num::NonZeroUsize;
+
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    pool_stats::{
+        PoolWorkload,
+        WorkloadPoolStats,
+    },
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadPoolStatsJson {
+    workload: String,
+    /// Absent if the workload is only limited by the pool size.
+    limit: Option<usize>,
+    in_use: usize,
+    waiting: usize,
+    acquired_total: u64,
+    timeouts_total: u64,
+    mean_wait_secs: f64,
+    max_wait_secs: f64,
+}
+
+impl From<WorkloadPoolStats> for WorkloadPoolStatsJson {
+    fn from(stats: WorkloadPoolStats) -> Self {
+        Self {
+            workload: stats.workload.to_string(),
+            limit: stats.limit.map(NonZeroUsize::get),
+            in_use: stats.in_use,
+            waiting: stats.waiting,
+            acquired_total: stats.acquired_total,
+            timeouts_total: stats.timeouts_total,
+            mean_wait_secs: stats.mean_wait.as_secs_f64(),
+            max_wait_secs: stats.max_wait.as_secs_f64(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionPoolStatsResponse {
+    max_size: usize,
+    /// Absent if the database driver doesn't report idle connections.
+    idle: Option<usize>,
+    workloads: Vec<WorkloadPoolStatsJson>,
+}
+
+/// Usage of the persistence connection pool by commits, reads and retention.
+pub async fn connection_pool_stats(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let Some(snapshot) = st.application.connection_pool_stats(&identity)? else {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "ConnectionPoolNotSupported",
+            "This deployment's persistence doesn't pool connections",
+        ))
+        .into());
+    };
+    Ok(Json(ConnectionPoolStatsResponse {
+        max_size: snapshot.max_size,
+        idle: snapshot.idle,
+        workloads: snapshot.workloads.into_iter().map(Into::into).collect(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetConnectionPoolLimitArgs {
+    /// `read` or `background`.
+    workload: String,
+    /// Omit to only limit the workload by the pool size.
+    limit: Option<usize>,
+}
+
+/// Caps how many pooled connections a workload may hold at once, until the
+/// backend restarts.
+#[debug_handler]
+pub async fn set_connection_pool_limit(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetConnectionPoolLimitArgs { workload, limit }): Json<SetConnectionPoolLimitArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let workload: PoolWorkload = workload.parse().map_err(|_| {
+        anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidConnectionPoolWorkload",
+            format!("Unknown connection pool workload {workload}"),
+        ))
+    })?;
+    let limit = match limit {
+        Some(limit) => Some(NonZeroUsize::new(limit).ok_or_else(|| {
+            anyhow::anyhow!(ErrorMetadata::bad_request(
+                "InvalidConnectionPoolLimit",
+                "A workload's connection limit must be at least 1",
+            ))
+        })?),
+        None => None,
+    };
+    st.application
+        .set_connection_pool_limit(identity, workload, limit)
+        .await?;
+    Ok(StatusCode::OK)
+}
@@ -1,6 +1,12 @@
+use std::{
+    collections::BTreeMap,
+    time::Duration,
+};
+
 use anyhow::Context;
 use application::{
     deploy_config::ModuleJson,
+    seed::SeedFixture,
     valid_identifier::ValidIdentifier,
 };
 use axum::{
@@ -9,7 +15,11 @@ use axum::{
     response::IntoResponse,
 };
 use common::{
-    components::ComponentId,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentId,
+        ComponentPath,
+    },
     http::{
         extract::{
             Json,
@@ -30,14 +40,23 @@ use errors::ErrorMetadata;
 use http::StatusCode;
 use isolate::UdfArgsJson;
 use model::{
+    component_limits::types::ComponentLimits,
     config::types::ModuleConfig,
+    outbound_network_policy::types::OutboundNetworkPolicy,
+    traffic_splits::types::TrafficSplit,
     virtual_system_mapping,
 };
 use serde::{
     Deserialize,
     Serialize,
 };
+use serde_json::Value as JsonValue;
+use sync_types::{
+    UdfPath,
+    UserIdentityAttributes,
+};
 use value::{
+    export::ValueFormat,
     TableName,
     TableNamespace,
 };
@@ -49,6 +68,7 @@ use crate::{
         must_be_admin_member_with_write_access,
     },
     authentication::ExtractIdentity,
+    parse::parse_udf_path,
     public_api::{
         export_value,
         UdfResponse,
@@ -64,10 +84,54 @@ pub struct DeleteTableArgs {
     component_id: Option<String>,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneTableArgs {
+    source_table_name: String,
+    target_table_name: String,
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CloneTableResponse {
+    documents_copied: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedArgs {
+    fixture: JsonValue,
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SeedResponse {
+    num_documents: u64,
+    ids: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillSchemaDefaultsArgs {
+    table_name: String,
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BackfillSchemaDefaultsResponse {
+    documents_updated: u64,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeleteComponentArgs {
     component_id: Option<String>,
+    /// Also delete the component's files from storage.
+    #[serde(default)]
+    cascade_delete_files: bool,
 }
 
 #[derive(Deserialize)]
@@ -109,6 +173,43 @@ pub async fn shapes2(
     Ok(Json(out))
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecomputeTableSummariesArgs {
+    /// Every table in the component if not set.
+    table_names: Option<Vec<String>>,
+    component_id: Option<String>,
+}
+
+/// Rescan tables to rebuild the summaries `shapes2` reports, for when the
+/// shapes carried forward from every write have drifted from the data.
+#[debug_handler]
+pub async fn recompute_table_summaries(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RecomputeTableSummariesArgs {
+        table_names,
+        component_id,
+    }): Json<RecomputeTableSummariesArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let table_names = table_names
+        .map(|table_names| {
+            table_names
+                .into_iter()
+                .map(|t| Ok(t.parse::<ValidIdentifier<TableName>>()?.0))
+                .collect::<anyhow::Result<_>>()
+        })
+        .transpose()?;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    st.application
+        .recompute_table_summaries(&identity, table_namespace, table_names)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
 #[debug_handler]
 pub async fn delete_tables(
     State(st): State<LocalAppState>,
@@ -132,20 +233,566 @@ pub async fn delete_tables(
     Ok(StatusCode::OK)
 }
 
+#[debug_handler]
+pub async fn clone_table(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CloneTableArgs {
+        source_table_name,
+        target_table_name,
+        component_id,
+    }): Json<CloneTableArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let source = source_table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let target = target_table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let documents_copied = st
+        .application
+        .clone_table(&identity, table_namespace, source, target)
+        .await?;
+    Ok(Json(CloneTableResponse { documents_copied }))
+}
+
+/// Load a fixture of documents for local development, returning the IDs of
+/// the documents given a `$name`.
+#[debug_handler]
+pub async fn seed(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SeedArgs {
+        fixture,
+        component_id,
+    }): Json<SeedArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let fixture = SeedFixture::try_from(fixture)?;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let result = st
+        .application
+        .seed(&identity, table_namespace, fixture)
+        .await?;
+    Ok(Json(SeedResponse {
+        num_documents: result.num_documents,
+        ids: result
+            .ids
+            .into_iter()
+            .map(|(name, id)| (name, id.encode()))
+            .collect(),
+    }))
+}
+
+/// Fill in the active schema's field defaults on existing documents.
+#[debug_handler]
+pub async fn backfill_schema_defaults(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(BackfillSchemaDefaultsArgs {
+        table_name,
+        component_id,
+    }): Json<BackfillSchemaDefaultsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let table_name = table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let documents_updated = st
+        .application
+        .backfill_schema_defaults(&identity, table_namespace, table_name)
+        .await?;
+    Ok(Json(BackfillSchemaDefaultsResponse { documents_updated }))
+}
+
 #[debug_handler]
 pub async fn delete_component(
     State(st): State<LocalAppState>,
     ExtractIdentity(identity): ExtractIdentity,
-    Json(DeleteComponentArgs { component_id }): Json<DeleteComponentArgs>,
+    Json(DeleteComponentArgs {
+        component_id,
+        cascade_delete_files,
+    }): Json<DeleteComponentArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    st.application
+        .delete_component(&identity, component_id, cascade_delete_files)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListComponentVersionsArgs {
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComponentVersionJson {
+    version: u64,
+    restored_from: Option<u64>,
+    /// Milliseconds since the epoch.
+    timestamp: Option<f64>,
+    server_version: String,
+    modules: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListComponentVersionsResponse {
+    versions: Vec<ComponentVersionJson>,
+}
+
+#[debug_handler]
+pub async fn list_component_versions(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListComponentVersionsArgs { component_id }): Query<ListComponentVersionsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let versions = st
+        .application
+        .list_component_versions(identity, component_id)
+        .await?
+        .into_iter()
+        .map(|doc| {
+            let timestamp = doc.creation_time().map(f64::from);
+            let version = doc.into_value();
+            ComponentVersionJson {
+                version: version.version,
+                restored_from: version.restored_from,
+                timestamp,
+                server_version: version.udf_config.server_version.to_string(),
+                modules: version
+                    .modules
+                    .into_iter()
+                    .map(|module| String::from(module.path))
+                    .collect(),
+            }
+        })
+        .collect();
+    Ok(Json(ListComponentVersionsResponse { versions }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSizeReportArgs {
+    component_id: Option<String>,
+    version: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModuleSizeJson {
+    path: String,
+    environment: String,
+    source_size: Option<u64>,
+    source_map_size: Option<u64>,
+    has_source_map: Option<bool>,
+    external_dependencies: Option<Vec<String>>,
+    source_size_delta: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleSizeReportJson {
+    version: u64,
+    previous_version: Option<u64>,
+    total_source_size: u64,
+    total_source_map_size: u64,
+    total_source_size_delta: Option<i64>,
+    modules: Vec<ModuleSizeJson>,
+    removed_modules: Vec<String>,
+    added_dependencies: Vec<String>,
+    removed_dependencies: Vec<String>,
+}
+
+#[debug_handler]
+pub async fn bundle_size_report(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(BundleSizeReportArgs {
+        component_id,
+        version,
+    }): Query<BundleSizeReportArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let report = st
+        .application
+        .bundle_size_report(identity, component_id, version)
+        .await?
+        .map(|report| BundleSizeReportJson {
+            version: report.version,
+            previous_version: report.previous_version,
+            total_source_size: report.total_source_size,
+            total_source_map_size: report.total_source_map_size,
+            total_source_size_delta: report.total_source_size_delta,
+            modules: report
+                .modules
+                .into_iter()
+                .map(|module| {
+                    let stats = module.bundle_stats;
+                    ModuleSizeJson {
+                        path: String::from(module.path),
+                        environment: module.environment.to_string(),
+                        source_size: stats.as_ref().map(|s| s.source_size),
+                        source_map_size: stats.as_ref().and_then(|s| s.source_map_size),
+                        has_source_map: stats.as_ref().map(|s| s.source_map_size.is_some()),
+                        external_dependencies: stats
+                            .map(|s| s.external_dependencies.into_iter().collect()),
+                        source_size_delta: module.source_size_delta,
+                    }
+                })
+                .collect(),
+            removed_modules: report
+                .removed_modules
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            added_dependencies: report.added_dependencies.into_iter().collect(),
+            removed_dependencies: report.removed_dependencies.into_iter().collect(),
+        });
+    Ok(Json(report))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackComponentArgs {
+    component_id: Option<String>,
+    version: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RollbackComponentResponse {
+    version: u64,
+}
+
+#[debug_handler]
+pub async fn rollback_component(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RollbackComponentArgs {
+        component_id,
+        version,
+    }): Json<RollbackComponentArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let version = st
+        .application
+        .rollback_component(identity, component_id, version)
+        .await?;
+    Ok(Json(RollbackComponentResponse { version }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetComponentLimitsArgs {
+    component_id: Option<String>,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ComponentLimitsJson {
+    max_documents_read: Option<u64>,
+    function_timeout_ms: Option<u64>,
+    max_scheduled_functions: Option<u64>,
+    max_heap_size_bytes: Option<u64>,
+    max_storage_bytes: Option<u64>,
+    max_storage_files: Option<u64>,
+}
+
+#[debug_handler]
+pub async fn get_component_limits(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(GetComponentLimitsArgs { component_id }): Query<GetComponentLimitsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let limits = st
+        .application
+        .get_component_limits(identity, component_id)
+        .await?
+        .map(|limits| ComponentLimitsJson {
+            max_documents_read: limits.max_documents_read,
+            function_timeout_ms: limits
+                .function_timeout
+                .map(|timeout| timeout.as_millis() as u64),
+            max_scheduled_functions: limits.max_scheduled_functions,
+            max_heap_size_bytes: limits.max_heap_size,
+            max_storage_bytes: limits.max_storage_bytes,
+            max_storage_files: limits.max_storage_files,
+        })
+        .unwrap_or_default();
+    Ok(Json(limits))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetComponentLimitsArgs {
+    component_id: Option<String>,
+    /// Unset limits fall back to the deployment-wide defaults.
+    max_documents_read: Option<u64>,
+    function_timeout_ms: Option<u64>,
+    max_scheduled_functions: Option<u64>,
+    max_heap_size_bytes: Option<u64>,
+    max_storage_bytes: Option<u64>,
+    max_storage_files: Option<u64>,
+}
+
+#[debug_handler]
+pub async fn set_component_limits(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetComponentLimitsArgs {
+        component_id,
+        max_documents_read,
+        function_timeout_ms,
+        max_scheduled_functions,
+        max_heap_size_bytes,
+        max_storage_bytes,
+        max_storage_files,
+    }): Json<SetComponentLimitsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    st.application
+        .set_component_limits(
+            identity,
+            ComponentLimits {
+                component,
+                max_documents_read,
+                function_timeout: function_timeout_ms.map(Duration::from_millis),
+                max_scheduled_functions,
+                max_heap_size: max_heap_size_bytes,
+                max_storage_bytes,
+                max_storage_files,
+            },
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboundNetworkPolicyJson {
+    allowed_hosts: Vec<String>,
+    denied_hosts: Vec<String>,
+    allow_private_ips: bool,
+}
+
+#[debug_handler]
+pub async fn get_outbound_network_policy(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let policy = st.application.get_outbound_network_policy(identity).await?;
+    Ok(Json(OutboundNetworkPolicyJson {
+        allowed_hosts: policy.allowed_hosts,
+        denied_hosts: policy.denied_hosts,
+        allow_private_ips: policy.allow_private_ips,
+    }))
+}
+
+#[debug_handler]
+pub async fn set_outbound_network_policy(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(OutboundNetworkPolicyJson {
+        allowed_hosts,
+        denied_hosts,
+        allow_private_ips,
+    }): Json<OutboundNetworkPolicyJson>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     must_be_admin_member_with_write_access(&identity)?;
+    st.application
+        .set_outbound_network_policy(
+            identity,
+            OutboundNetworkPolicy {
+                allowed_hosts,
+                denied_hosts,
+                allow_private_ips,
+            },
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTrafficSplitArgs {
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrafficSplitJson {
+    stable_version: u64,
+    candidate_version: u64,
+    default_percent: u8,
+    function_percents: BTreeMap<String, u8>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrafficSplitVariantStatsJson {
+    executions: u64,
+    errors: u64,
+    error_rate: f64,
+    mean_duration_ms: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetTrafficSplitResponse {
+    split: Option<TrafficSplitJson>,
+    /// Keyed by function path, then by "stable" or "candidate".
+    stats: BTreeMap<String, BTreeMap<String, TrafficSplitVariantStatsJson>>,
+}
+
+#[debug_handler]
+pub async fn get_traffic_split(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(GetTrafficSplitArgs { component_id }): Query<GetTrafficSplitArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
     let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let split = st
+        .application
+        .get_traffic_split(identity.clone(), component_id)
+        .await?
+        .map(|split| TrafficSplitJson {
+            stable_version: split.stable_version,
+            candidate_version: split.candidate_version,
+            default_percent: split.default_percent,
+            function_percents: split
+                .function_percents
+                .into_iter()
+                .map(|(udf_path, percent)| (udf_path.to_string(), percent))
+                .collect(),
+        });
+    let stats = st
+        .application
+        .traffic_split_stats(identity, component_id)?
+        .into_iter()
+        .map(|(udf_path, variants)| {
+            let variants = variants
+                .into_iter()
+                .map(|(variant, stats)| {
+                    let stats_json = TrafficSplitVariantStatsJson {
+                        executions: stats.executions,
+                        errors: stats.errors,
+                        error_rate: stats.error_rate(),
+                        mean_duration_ms: stats.mean_duration().as_secs_f64() * 1000.0,
+                    };
+                    (variant.to_string(), stats_json)
+                })
+                .collect();
+            (udf_path.to_string(), variants)
+        })
+        .collect();
+    Ok(Json(GetTrafficSplitResponse { split, stats }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTrafficSplitArgs {
+    component_id: Option<String>,
+    stable_version: u64,
+    candidate_version: u64,
+    default_percent: u8,
+    /// Overrides `default_percent` for individual functions, keyed by path
+    /// (e.g. "messages:list").
+    #[serde(default)]
+    function_percents: BTreeMap<String, u8>,
+}
+
+#[debug_handler]
+pub async fn set_traffic_split(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetTrafficSplitArgs {
+        component_id,
+        stable_version,
+        candidate_version,
+        default_percent,
+        function_percents,
+    }): Json<SetTrafficSplitArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let function_percents = function_percents
+        .into_iter()
+        .map(|(udf_path, percent)| {
+            let udf_path: UdfPath = udf_path.parse().context(ErrorMetadata::bad_request(
+                "InvalidTrafficSplit",
+                format!("Invalid function path {udf_path}"),
+            ))?;
+            anyhow::Ok((udf_path.canonicalize(), percent))
+        })
+        .collect::<anyhow::Result<_>>()?;
     st.application
-        .delete_component(&identity, component_id)
+        .set_traffic_split(
+            identity,
+            TrafficSplit {
+                component,
+                stable_version,
+                candidate_version,
+                default_percent,
+                function_percents,
+            },
+        )
         .await?;
     Ok(StatusCode::OK)
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinishTrafficSplitArgs {
+    component_id: Option<String>,
+}
+
+#[debug_handler]
+pub async fn promote_traffic_split(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(FinishTrafficSplitArgs { component_id }): Json<FinishTrafficSplitArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let version = st
+        .application
+        .promote_traffic_split(identity, component_id)
+        .await?;
+    Ok(Json(RollbackComponentResponse { version }))
+}
+
+#[debug_handler]
+pub async fn rollback_traffic_split(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(FinishTrafficSplitArgs { component_id }): Json<FinishTrafficSplitArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let version = st
+        .application
+        .rollback_traffic_split(identity, component_id)
+        .await?;
+    Ok(Json(RollbackComponentResponse { version }))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetIndexesArgs {
@@ -178,6 +825,109 @@ pub async fn get_indexes(
     }))
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditComponentArgs {
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditComponentResponse {
+    path: String,
+    /// Number of documents in each of the component's tables.
+    tables: BTreeMap<String, u64>,
+    indexes: Vec<IndexMetadataResponse>,
+    num_stored_files: u64,
+    num_scheduled_functions: u64,
+    crons: Vec<String>,
+    environment_variables: Vec<String>,
+}
+
+#[debug_handler]
+pub async fn audit_component(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(AuditComponentArgs { component_id }): Query<AuditComponentArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let audit = st
+        .application
+        .audit_component(identity, component_id)
+        .await?;
+    Ok(Json(AuditComponentResponse {
+        path: String::from(audit.path),
+        tables: audit
+            .tables
+            .into_iter()
+            .map(|(table_name, count)| (table_name.to_string(), count))
+            .collect(),
+        indexes: audit
+            .indexes
+            .into_iter()
+            .map(IndexMetadataResponse::try_from)
+            .collect::<anyhow::Result<_>>()?,
+        num_stored_files: audit.num_stored_files,
+        num_scheduled_functions: audit.num_scheduled_jobs,
+        crons: audit.crons.iter().map(ToString::to_string).collect(),
+        environment_variables: audit
+            .environment_variables
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaTypesArgs {
+    component_id: Option<String>,
+    format: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaTypesResponse {
+    typescript: String,
+    json_schema: JsonValue,
+}
+
+/// The active schema as TypeScript types and JSON Schema, for generating
+/// bindings outside the Convex CLI. The JSON Schema describes values in
+/// `format`, which defaults to clean JSON.
+#[debug_handler]
+pub async fn schema_types(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(SchemaTypesArgs {
+        component_id,
+        format,
+    }): Query<SchemaTypesArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let value_format = format
+        .map(|f| f.parse())
+        .transpose()?
+        .unwrap_or(ValueFormat::ConvexCleanJSON);
+    let Some(definitions) = st
+        .application
+        .schema_type_definitions(identity, component_id, value_format)
+        .await?
+    else {
+        return Err(anyhow::anyhow!(ErrorMetadata::not_found(
+            "NoActiveSchema",
+            "This deployment has no active schema",
+        ))
+        .into());
+    };
+    Ok(Json(SchemaTypesResponse {
+        typescript: definitions.typescript,
+        json_schema: definitions.json_schema,
+    }))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetSourceCodeArgs {
@@ -253,3 +1003,57 @@ pub async fn run_test_function(
     };
     Ok(Json(response))
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunAsUserArgs {
+    path: String,
+    args: UdfArgsJson,
+    component_path: Option<String>,
+    /// Claims for the synthesized identity, in the same shape that
+    /// `ctx.auth.getUserIdentity()` returns them.
+    identity: JsonValue,
+    format: Option<String>,
+}
+
+/// Run a query or mutation as a user defined by explicit claims. Meant for
+/// reproducing permission bugs; every call is recorded in the audit log.
+#[debug_handler]
+pub async fn run_as_user(
+    State(st): State<LocalAppState>,
+    ExtractRequestId(request_id): ExtractRequestId,
+    ExtractIdentity(identity): ExtractIdentity,
+    ExtractClientVersion(client_version): ExtractClientVersion,
+    Json(req): Json<RunAsUserArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let acting_as = UserIdentityAttributes::try_from(req.identity).context(
+        ErrorMetadata::bad_request("InvalidIdentity", "Invalid identity claims"),
+    )?;
+    let path = CanonicalizedComponentFunctionPath {
+        component: ComponentPath::deserialize(req.component_path.as_deref())?,
+        udf_path: parse_udf_path(&req.path)?,
+    };
+    let udf_result = st
+        .application
+        .run_as_user(
+            request_id,
+            path,
+            req.args.into_arg_vec(),
+            identity,
+            acting_as,
+            FunctionCaller::HttpApi(client_version.clone()),
+        )
+        .await?;
+    let value_format = req.format.as_ref().map(|f| f.parse()).transpose()?;
+    let response = match udf_result {
+        Ok(result) => UdfResponse::Success {
+            value: export_value(result.value, value_format, client_version)?,
+            log_lines: result.log_lines,
+        },
+        Err(error) => {
+            UdfResponse::error(error.error, error.log_lines, value_format, client_version)?
+        },
+    };
+    Ok(Json(response))
+}
@@ -0,0 +1,174 @@
+use anyhow::Context;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::data_migrations::types::{
+    DataMigration,
+    DataMigrationState,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin_member,
+        must_be_admin_member_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterDataMigrationRequest {
+    pub name: String,
+    pub version: i64,
+    /// Canonicalized path of the mutation that runs one batch.
+    pub udf_path: String,
+    pub component_path: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[debug_handler]
+pub async fn register_data_migration(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RegisterDataMigrationRequest {
+        name,
+        version,
+        udf_path,
+        component_path,
+        dry_run,
+    }): Json<RegisterDataMigrationRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let udf_path = udf_path.parse().context(ErrorMetadata::bad_request(
+        "InvalidUdfPath",
+        "Data migrations require a canonicalized UdfPath",
+    ))?;
+    let path = CanonicalizedComponentFunctionPath {
+        component: ComponentPath::deserialize(component_path.as_deref())?,
+        udf_path,
+    };
+    st.application
+        .register_data_migration(&identity, DataMigration::new(name, version, path, dry_run))
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataMigrationStatus {
+    name: String,
+    version: i64,
+    udf_path: String,
+    component_path: Option<String>,
+    dry_run: bool,
+    /// One of "pending", "inProgress", "completed", "dryRunCompleted" or
+    /// "failed".
+    state: &'static str,
+    num_processed: u64,
+    cursor: Option<String>,
+    error: Option<String>,
+}
+
+impl From<DataMigration> for DataMigrationStatus {
+    fn from(migration: DataMigration) -> Self {
+        let state = match &migration.state {
+            DataMigrationState::Pending => "pending",
+            DataMigrationState::InProgress { .. } => "inProgress",
+            DataMigrationState::Completed { .. } => "completed",
+            DataMigrationState::DryRunCompleted { .. } => "dryRunCompleted",
+            DataMigrationState::Failed { .. } => "failed",
+        };
+        let error = match &migration.state {
+            DataMigrationState::Failed { error, .. } => Some(error.clone()),
+            _ => None,
+        };
+        Self {
+            name: migration.name,
+            version: migration.version,
+            udf_path: String::from(migration.path.udf_path),
+            component_path: migration.path.component.serialize(),
+            dry_run: migration.dry_run,
+            state,
+            num_processed: migration.state.num_processed(),
+            cursor: migration.state.cursor().map(String::from),
+            error,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDataMigrationsResponse {
+    migrations: Vec<DataMigrationStatus>,
+}
+
+#[debug_handler]
+pub async fn list_data_migrations(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let migrations = st.application.list_data_migrations(&identity).await?;
+    Ok(Json(ListDataMigrationsResponse {
+        migrations: migrations.into_iter().map(Into::into).collect(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryDataMigrationRequest {
+    pub name: String,
+}
+
+#[debug_handler]
+pub async fn retry_data_migration(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RetryDataMigrationRequest { name }): Json<RetryDataMigrationRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    st.application
+        .retry_data_migration(&identity, &name)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunDataMigrationRequest {
+    pub name: String,
+}
+
+/// Starts the real run of a data migration whose dry run has completed.
+#[debug_handler]
+pub async fn run_data_migration(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RunDataMigrationRequest { name }): Json<RunDataMigrationRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    st.application
+        .start_data_migration_real_run(&identity, &name)
+        .await?;
+    Ok(StatusCode::OK)
+}
@@ -35,6 +35,7 @@ use fastrace::{
         TraceId,
     },
 };
+use isolate::environment::unindexed_queries::UnindexedQuery;
 use model::{
     auth::types::AuthDiff,
     components::{
@@ -49,6 +50,7 @@ use model::{
     external_packages::types::ExternalDepsPackageId,
     modules::module_versions::SerializedAnalyzedModule,
     source_packages::types::SourcePackage,
+    static_assets::types::StaticAsset,
 };
 use serde::{
     Deserialize,
@@ -95,6 +97,20 @@ impl TryFrom<StartPushResponse> for SerializedStartPushResponse {
                 .collect::<anyhow::Result<_>>()?,
             app: value.app.try_into()?,
             schema_change: value.schema_change.try_into()?,
+            unindexed_queries: value
+                .unindexed_queries
+                .into_iter()
+                .map(|(k, v)| (String::from(k), v))
+                .collect(),
+            static_assets: value
+                .static_assets
+                .map(|assets| {
+                    assets
+                        .into_iter()
+                        .map(|asset| Ok(JsonValue::from(ConvexObject::try_from(asset)?)))
+                        .collect::<anyhow::Result<_>>()
+                })
+                .transpose()?,
         })
     }
 }
@@ -135,6 +151,20 @@ impl TryFrom<SerializedStartPushResponse> for StartPushResponse {
                 .collect::<anyhow::Result<_>>()?,
             app: value.app.try_into()?,
             schema_change: value.schema_change.try_into()?,
+            unindexed_queries: value
+                .unindexed_queries
+                .into_iter()
+                .map(|(k, v)| Ok((k.parse()?, v)))
+                .collect::<anyhow::Result<_>>()?,
+            static_assets: value
+                .static_assets
+                .map(|assets| {
+                    assets
+                        .into_iter()
+                        .map(|asset| StaticAsset::try_from(ConvexObject::try_from(asset)?))
+                        .collect::<anyhow::Result<_>>()
+                })
+                .transpose()?,
         })
     }
 }
@@ -157,6 +187,14 @@ pub struct SerializedStartPushResponse {
 
     // Schema changes.
     schema_change: SerializedSchemaChange,
+
+    // Push-time warnings, keyed by component definition path.
+    #[serde(default)]
+    unindexed_queries: BTreeMap<String, Vec<UnindexedQuery>>,
+
+    // Uploaded static assets, if the push included any.
+    #[serde(default)]
+    static_assets: Option<Vec<JsonValue>>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -0,0 +1,594 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use application::{
+    document_batch::{
+        DocumentBatchOperation,
+        DocumentBatchOperationResult,
+        DocumentBatchResult,
+        DocumentBatchTarget,
+    },
+    document_query::{
+        DocumentFilter,
+        DocumentFilterOp,
+        DocumentQuery,
+    },
+    valid_identifier::ValidIdentifier,
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentId,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+    paths::FieldPath,
+    query::Order,
+    types::IndexDescriptor,
+};
+use database::{
+    document_history::{
+        DocumentRevision,
+        RevisionOperation,
+    },
+    parse_document_version,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use keybroker::Identity;
+use model::http_table_access::types::{
+    HttpTableAccess,
+    HttpTableAccessLevel,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use value::{
+    ConvexValue,
+    DeveloperDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentBatchRequest {
+    operations: Vec<DocumentBatchOperationJson>,
+    component_id: Option<String>,
+    /// Write to a preview deployment's tables instead of the component's.
+    preview: Option<String>,
+}
+
+/// An operation refers to its document either by `id` or by the `tempId` of
+/// an insert earlier in the batch.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+enum DocumentBatchOperationJson {
+    #[serde(rename_all = "camelCase")]
+    Insert {
+        table: String,
+        temp_id: Option<String>,
+        value: JsonValue,
+    },
+    #[serde(rename_all = "camelCase")]
+    Patch {
+        id: Option<String>,
+        temp_id: Option<String>,
+        value: JsonValue,
+        expected_version: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Replace {
+        id: Option<String>,
+        temp_id: Option<String>,
+        value: JsonValue,
+        expected_version: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Delete {
+        id: Option<String>,
+        temp_id: Option<String>,
+    },
+}
+
+impl TryFrom<DocumentBatchOperationJson> for DocumentBatchOperation {
+    type Error = anyhow::Error;
+
+    fn try_from(op: DocumentBatchOperationJson) -> anyhow::Result<Self> {
+        let op = match op {
+            DocumentBatchOperationJson::Insert {
+                table,
+                temp_id,
+                value,
+            } => DocumentBatchOperation::Insert {
+                table_name: table.parse::<ValidIdentifier<TableName>>()?.0,
+                temp_id,
+                value,
+            },
+            DocumentBatchOperationJson::Patch {
+                id,
+                temp_id,
+                value,
+                expected_version,
+            } => DocumentBatchOperation::Patch {
+                target: parse_target(id, temp_id)?,
+                value,
+                expected_version: expected_version
+                    .as_deref()
+                    .map(parse_document_version)
+                    .transpose()?,
+            },
+            DocumentBatchOperationJson::Replace {
+                id,
+                temp_id,
+                value,
+                expected_version,
+            } => DocumentBatchOperation::Replace {
+                target: parse_target(id, temp_id)?,
+                value,
+                expected_version: expected_version
+                    .as_deref()
+                    .map(parse_document_version)
+                    .transpose()?,
+            },
+            DocumentBatchOperationJson::Delete { id, temp_id } => DocumentBatchOperation::Delete {
+                target: parse_target(id, temp_id)?,
+            },
+        };
+        Ok(op)
+    }
+}
+
+fn parse_target(
+    id: Option<String>,
+    temp_id: Option<String>,
+) -> anyhow::Result<DocumentBatchTarget> {
+    match (id, temp_id) {
+        (Some(id), None) => Ok(DocumentBatchTarget::Id(
+            DeveloperDocumentId::decode(&id).context(ErrorMetadata::bad_request(
+                "InvalidId",
+                format!("Invalid document id {id:?}"),
+            ))?,
+        )),
+        (None, Some(temp_id)) => Ok(DocumentBatchTarget::TempId(temp_id)),
+        _ => anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidDocumentBatchOperation",
+            "Operations must have exactly one of `id` and `tempId`",
+        )),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentBatchResponse {
+    /// Timestamp the batch committed at, which is also the version of every
+    /// document it wrote.
+    ts: String,
+    results: Vec<DocumentBatchOperationResultJson>,
+    temp_ids: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "result", rename_all = "camelCase")]
+enum DocumentBatchOperationResultJson {
+    Inserted { id: String },
+    Updated { document: JsonValue },
+    Deleted { id: String },
+}
+
+impl From<DocumentBatchResult> for DocumentBatchResponse {
+    fn from(
+        DocumentBatchResult {
+            ts,
+            results,
+            temp_ids,
+        }: DocumentBatchResult,
+    ) -> Self {
+        Self {
+            ts: u64::from(ts).to_string(),
+            results: results
+                .into_iter()
+                .map(|result| match result {
+                    DocumentBatchOperationResult::Inserted { id } => {
+                        DocumentBatchOperationResultJson::Inserted { id: id.encode() }
+                    },
+                    DocumentBatchOperationResult::Updated { document } => {
+                        DocumentBatchOperationResultJson::Updated {
+                            document: document.into(),
+                        }
+                    },
+                    DocumentBatchOperationResult::Deleted { id } => {
+                        DocumentBatchOperationResultJson::Deleted { id: id.encode() }
+                    },
+                })
+                .collect(),
+            temp_ids: temp_ids
+                .into_iter()
+                .map(|(temp_id, id)| (temp_id, id.encode()))
+                .collect(),
+        }
+    }
+}
+
+/// The namespace a request's tables are in: the named preview deployment's if
+/// there is one, otherwise the component's. Only admins can use previews.
+async fn request_namespace(
+    st: &LocalAppState,
+    identity: &Identity,
+    component_id: Option<String>,
+    preview: Option<String>,
+) -> anyhow::Result<TableNamespace> {
+    let Some(preview) = preview else {
+        return Ok(TableNamespace::from(ComponentId::deserialize_from_string(
+            component_id.as_deref(),
+        )?));
+    };
+    anyhow::ensure!(
+        component_id.is_none(),
+        ErrorMetadata::bad_request(
+            "PreviewWithComponentId",
+            "A request can't set both `preview` and `componentId`"
+        )
+    );
+    st.application
+        .preview_deployment_namespace(identity.clone(), &preview)
+        .await
+}
+
+/// Apply an ordered list of document writes atomically. Inserts can be given
+/// a `tempId` that later operations use to refer to the new document, either
+/// as their target or inside values as `{"$tempId": "..."}`. Non-admin
+/// identities can only write to tables whose HTTP access allows them to.
+#[debug_handler]
+pub async fn document_batch(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DocumentBatchRequest {
+        operations,
+        component_id,
+        preview,
+    }): Json<DocumentBatchRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    if identity.is_admin() {
+        must_be_admin_with_write_access(&identity)?;
+    }
+    let table_namespace = request_namespace(&st, &identity, component_id, preview).await?;
+    let operations = operations
+        .into_iter()
+        .map(DocumentBatchOperation::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let result = st
+        .application
+        .execute_document_batch(identity, table_namespace, operations)
+        .await?;
+    Ok(Json(DocumentBatchResponse::from(result)))
+}
+
+/// Number of documents returned by a document query that doesn't set a limit.
+const DEFAULT_QUERY_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryDocumentsRequest {
+    table: String,
+    index: Option<String>,
+    #[serde(default)]
+    filter: Vec<DocumentFilterJson>,
+    #[serde(default)]
+    order: OrderJson,
+    limit: Option<usize>,
+    component_id: Option<String>,
+    /// Read a preview deployment's tables instead of the component's.
+    preview: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DocumentFilterJson {
+    field: String,
+    op: DocumentFilterOpJson,
+    value: JsonValue,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum DocumentFilterOpJson {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum OrderJson {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl TryFrom<DocumentFilterJson> for DocumentFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        DocumentFilterJson { field, op, value }: DocumentFilterJson,
+    ) -> anyhow::Result<Self> {
+        let field: FieldPath = field.parse().context(ErrorMetadata::bad_request(
+            "InvalidFilter",
+            format!("Invalid field path {field:?}"),
+        ))?;
+        let op = match op {
+            DocumentFilterOpJson::Eq => DocumentFilterOp::Eq,
+            DocumentFilterOpJson::Gt => DocumentFilterOp::Gt,
+            DocumentFilterOpJson::Gte => DocumentFilterOp::Gte,
+            DocumentFilterOpJson::Lt => DocumentFilterOp::Lt,
+            DocumentFilterOpJson::Lte => DocumentFilterOp::Lte,
+        };
+        let value = ConvexValue::try_from(value).context(ErrorMetadata::bad_request(
+            "InvalidFilter",
+            format!("Invalid value in filter on {field}"),
+        ))?;
+        Ok(Self { field, op, value })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryDocumentsResponse {
+    /// The index that served the query.
+    index: String,
+    documents: Vec<JsonValue>,
+}
+
+/// Read documents with a filter/order/limit instead of a query function.
+/// Filters are translated into an index range, and filters no index can serve
+/// are rejected rather than falling back to a table scan. Non-admin identities
+/// can only read tables whose HTTP access allows them to.
+#[debug_handler]
+pub async fn query_documents(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(QueryDocumentsRequest {
+        table,
+        index,
+        filter,
+        order,
+        limit,
+        component_id,
+        preview,
+    }): Json<QueryDocumentsRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let table_namespace = request_namespace(&st, &identity, component_id, preview).await?;
+    let index = index.map(IndexDescriptor::new).transpose()?;
+    let query = DocumentQuery {
+        table_name: table.parse::<ValidIdentifier<TableName>>()?.0,
+        index,
+        filters: filter
+            .into_iter()
+            .map(DocumentFilter::try_from)
+            .collect::<anyhow::Result<_>>()?,
+        order: match order {
+            OrderJson::Asc => Order::Asc,
+            OrderJson::Desc => Order::Desc,
+        },
+        limit: limit.unwrap_or(DEFAULT_QUERY_LIMIT),
+    };
+    let result = st
+        .application
+        .query_documents(identity, table_namespace, query)
+        .await?;
+    Ok(Json(QueryDocumentsResponse {
+        index: result.index_name.descriptor().to_string(),
+        documents: result.documents.into_iter().map(JsonValue::from).collect(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListHttpTableAccessArgs {
+    component_id: Option<String>,
+}
+
+/// Access levels are `"admin"`, `"authenticated"` or `"public"`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpTableAccessJson {
+    table: String,
+    read: String,
+    write: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListHttpTableAccessResponse {
+    /// Tables that aren't listed are admin-only.
+    tables: Vec<HttpTableAccessJson>,
+}
+
+#[debug_handler]
+pub async fn list_http_table_access(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListHttpTableAccessArgs { component_id }): Query<ListHttpTableAccessArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let tables = st
+        .application
+        .list_http_table_access(identity, component_id)
+        .await?
+        .into_iter()
+        .map(|access| HttpTableAccessJson {
+            table: access.table_name.to_string(),
+            read: access.read.as_str().to_string(),
+            write: access.write.as_str().to_string(),
+        })
+        .collect();
+    Ok(Json(ListHttpTableAccessResponse { tables }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetHttpTableAccessArgs {
+    component_id: Option<String>,
+    #[serde(flatten)]
+    access: HttpTableAccessJson,
+}
+
+/// Set who may read and write a table through `/document_batch` and
+/// `/query_documents`. Setting both levels to `"admin"` restores the default.
+#[debug_handler]
+pub async fn set_http_table_access(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetHttpTableAccessArgs {
+        component_id,
+        access: HttpTableAccessJson { table, read, write },
+    }): Json<SetHttpTableAccessArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let component = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let parse_level = |level: String| {
+        HttpTableAccessLevel::try_from(level.clone()).context(ErrorMetadata::bad_request(
+            "InvalidHttpTableAccess",
+            format!(
+                "Invalid access level {level:?}. Expected \"admin\", \"authenticated\" or \
+                 \"public\""
+            ),
+        ))
+    };
+    st.application
+        .set_http_table_access(
+            identity,
+            HttpTableAccess {
+                component,
+                table_name: table.parse::<ValidIdentifier<TableName>>()?.0,
+                read: parse_level(read)?,
+                write: parse_level(write)?,
+            },
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentHistoryArgs {
+    id: String,
+    component_id: Option<String>,
+    /// Only return revisions older than this timestamp, to page through the
+    /// history with a previous response's `cursor`.
+    before: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentHistoryResponse {
+    revisions: Vec<DocumentRevisionJson>,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentRevisionJson {
+    ts: String,
+    operation: &'static str,
+    document: Option<JsonValue>,
+    /// `None` when the previous revision is out of retention.
+    changes: Option<Vec<FieldChangeJson>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FieldChangeJson {
+    field: String,
+    old_value: Option<JsonValue>,
+    new_value: Option<JsonValue>,
+}
+
+impl From<DocumentRevision> for DocumentRevisionJson {
+    fn from(revision: DocumentRevision) -> Self {
+        Self {
+            ts: u64::from(revision.ts).to_string(),
+            operation: match revision.operation {
+                RevisionOperation::Insert => "insert",
+                RevisionOperation::Update => "update",
+                RevisionOperation::Delete => "delete",
+            },
+            document: revision
+                .document
+                .map(|document| JsonValue::from(document.into_value().0)),
+            changes: revision.changes.map(|changes| {
+                changes
+                    .into_iter()
+                    .map(|change| FieldChangeJson {
+                        field: change.field.to_string(),
+                        old_value: change.old_value.map(JsonValue::from),
+                        new_value: change.new_value.map(JsonValue::from),
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
+
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+const MAX_HISTORY_LIMIT: usize = 200;
+
+/// The revisions of a single document within the retention window, newest
+/// first, with the fields each one changed.
+#[debug_handler]
+pub async fn document_history(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(DocumentHistoryArgs {
+        id,
+        component_id,
+        before,
+        limit,
+    }): Query<DocumentHistoryArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    let id = DeveloperDocumentId::decode(&id).context(ErrorMetadata::bad_request(
+        "InvalidId",
+        format!("Invalid document id {id:?}"),
+    ))?;
+    let before = before.as_deref().map(parse_document_version).transpose()?;
+    let limit = limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .clamp(1, MAX_HISTORY_LIMIT);
+    let history = st
+        .application
+        .document_history(identity, table_namespace, id, before, limit)
+        .await?;
+    Ok(Json(DocumentHistoryResponse {
+        revisions: history
+            .revisions
+            .into_iter()
+            .map(DocumentRevisionJson::from)
+            .collect(),
+        cursor: history.cursor.map(|ts| u64::from(ts).to_string()),
+    }))
+}
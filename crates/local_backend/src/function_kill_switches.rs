@@ -0,0 +1,189 @@
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentId,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::function_kill_switches::types::{
+    FunctionKillSwitch,
+    KillSwitchTarget,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_member_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    parse::parse_udf_path,
+    LocalAppState,
+};
+
+/// A function is identified by exactly one of `udfPath`, like
+/// `messages:send`, or `pathPrefix`, like `admin/`, which matches every
+/// function whose canonicalized path (e.g. `admin/users.js:purge`) starts
+/// with it.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillSwitchTargetJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    udf_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path_prefix: Option<String>,
+}
+
+impl TryFrom<KillSwitchTargetJson> for KillSwitchTarget {
+    type Error = anyhow::Error;
+
+    fn try_from(value: KillSwitchTargetJson) -> anyhow::Result<Self> {
+        match (value.udf_path, value.path_prefix) {
+            (Some(udf_path), None) => Ok(KillSwitchTarget::Function(parse_udf_path(&udf_path)?)),
+            (None, Some(prefix)) if !prefix.is_empty() => Ok(KillSwitchTarget::Prefix(prefix)),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidKillSwitchTarget",
+                "Expected exactly one of udfPath and a non-empty pathPrefix",
+            )),
+        }
+    }
+}
+
+impl From<KillSwitchTarget> for KillSwitchTargetJson {
+    fn from(value: KillSwitchTarget) -> Self {
+        match value {
+            KillSwitchTarget::Function(udf_path) => Self {
+                udf_path: Some(udf_path.to_string()),
+                path_prefix: None,
+            },
+            KillSwitchTarget::Prefix(prefix) => Self {
+                udf_path: None,
+                path_prefix: Some(prefix),
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFunctionKillSwitchesArgs {
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FunctionKillSwitchJson {
+    #[serde(flatten)]
+    target: KillSwitchTargetJson,
+    reason: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListFunctionKillSwitchesResponse {
+    kill_switches: Vec<FunctionKillSwitchJson>,
+}
+
+#[debug_handler]
+pub async fn list_function_kill_switches(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListFunctionKillSwitchesArgs { component_id }): Query<ListFunctionKillSwitchesArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let kill_switches = st
+        .application
+        .list_function_kill_switches(identity, component_id)
+        .await?
+        .into_iter()
+        .map(|kill_switch| FunctionKillSwitchJson {
+            target: kill_switch.target.into(),
+            reason: kill_switch.reason,
+        })
+        .collect();
+    Ok(Json(ListFunctionKillSwitchesResponse { kill_switches }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisableFunctionArgs {
+    component_id: Option<String>,
+    #[serde(flatten)]
+    target: KillSwitchTargetJson,
+    /// Shown to callers along with the error.
+    reason: Option<String>,
+}
+
+/// Make calls to the function fail, and leave scheduled jobs that target it
+/// pending, until it's enabled again.
+#[debug_handler]
+pub async fn disable_function(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DisableFunctionArgs {
+        component_id,
+        target,
+        reason,
+    }): Json<DisableFunctionArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    st.application
+        .disable_function(
+            identity,
+            FunctionKillSwitch {
+                component,
+                target: target.try_into()?,
+                reason,
+            },
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnableFunctionArgs {
+    component_id: Option<String>,
+    #[serde(flatten)]
+    target: KillSwitchTargetJson,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnableFunctionResponse {
+    /// False if the function wasn't disabled.
+    enabled: bool,
+}
+
+#[debug_handler]
+pub async fn enable_function(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(EnableFunctionArgs {
+        component_id,
+        target,
+    }): Json<EnableFunctionArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let enabled = st
+        .application
+        .enable_function(identity, component_id, target.try_into()?)
+        .await?;
+    Ok(Json(EnableFunctionResponse { enabled }))
+}
@@ -0,0 +1,89 @@
+use application::health::HealthReport;
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::extract::Json;
+use http::StatusCode;
+use serde::Serialize;
+
+use crate::LocalAppState;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyCheckJson {
+    name: &'static str,
+    status: &'static str,
+    latency_ms: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthResponse {
+    status: &'static str,
+    checks: Vec<DependencyCheckJson>,
+}
+
+/// Responds with 200 if every check passed and 503 otherwise. These endpoints
+/// aren't authenticated, so errors are logged rather than returned.
+fn health_response(report: HealthReport) -> impl IntoResponse {
+    let is_healthy = report.is_healthy();
+    let checks = report
+        .checks
+        .into_iter()
+        .map(|check| {
+            if let Some(e) = check.error {
+                tracing::warn!("Health check {} failed: {e:#}", check.name);
+            }
+            DependencyCheckJson {
+                name: check.name,
+                status: check.status.as_str(),
+                latency_ms: check.latency.as_millis() as u64,
+            }
+        })
+        .collect();
+    let (status_code, status) = if is_healthy {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+    (status_code, Json(HealthResponse { status, checks }))
+}
+
+/// Liveness: whether the background workers are still running. Restart the
+/// backend if this fails.
+pub async fn healthz(State(st): State<LocalAppState>) -> impl IntoResponse {
+    health_response(st.application.check_liveness())
+}
+
+/// Readiness: whether the backend can reach persistence, storage and search,
+/// and its workers are running. Don't route requests to the backend until this
+/// succeeds.
+pub async fn readyz(State(st): State<LocalAppState>) -> impl IntoResponse {
+    health_response(st.application.check_readiness().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Request;
+    use runtime::prod::ProdRuntime;
+    use serde_json::Value as JsonValue;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_healthz(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/healthz")
+            .method("GET")
+            .body(axum::body::Body::empty())?;
+        let response: JsonValue = backend.expect_success(req).await?;
+        assert_eq!(response["status"], "ok");
+        let checks = response["checks"].as_array().unwrap();
+        assert!(checks
+            .iter()
+            .any(|check| check["name"] == "index_worker" && check["status"] == "ok"));
+        Ok(())
+    }
+}
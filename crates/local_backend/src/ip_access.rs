@@ -0,0 +1,317 @@
+use std::{
+    convert::Infallible,
+    net::{
+        IpAddr,
+        SocketAddr,
+    },
+    sync::LazyLock,
+};
+
+use async_trait::async_trait;
+use axum::{
+    debug_handler,
+    extract::{
+        ConnectInfo,
+        FromRequestParts,
+        State,
+    },
+    middleware::Next,
+    response::{
+        IntoResponse,
+        Response,
+    },
+    RequestPartsExt,
+};
+use common::{
+    http::{
+        extract::Json,
+        ExtractRequestId,
+        ExtractResolvedHostname,
+        HttpResponseError,
+    },
+    knobs::TRUSTED_PROXIES,
+};
+use http::{
+    HeaderMap,
+    HeaderName,
+    StatusCode,
+};
+use model::ip_access_policy::types::{
+    IpAccessPolicy,
+    IpAccessRules,
+    IpAccessSurface,
+    IpNetwork,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin_member,
+        must_be_admin_member_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+    RouterState,
+};
+
+static X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+static TRUSTED_PROXY_NETWORKS: LazyLock<Vec<IpNetwork>> = LazyLock::new(|| {
+    TRUSTED_PROXIES
+        .split(',')
+        .map(str::trim)
+        .filter(|network| !network.is_empty())
+        .filter_map(|network| match network.parse() {
+            Ok(network) => Some(network),
+            Err(e) => {
+                tracing::error!("Ignoring invalid trusted proxy {network:?}: {e:#}");
+                None
+            },
+        })
+        .collect()
+});
+
+/// The address a request came from. Requests from a trusted proxy are
+/// attributed to the address it reports in `X-Forwarded-For`, skipping over
+/// any other trusted proxies along the way. `None` for requests without a
+/// peer address, like those made within the process.
+pub struct ExtractClientIp(pub Option<IpAddr>);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ExtractClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Ok(remote_addr) = parts.extract::<Option<ConnectInfo<SocketAddr>>>().await;
+        Ok(Self(remote_addr.map(|ConnectInfo(addr)| {
+            forwarded_client_ip(addr.ip(), &parts.headers, &TRUSTED_PROXY_NETWORKS)
+        })))
+    }
+}
+
+fn forwarded_client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpNetwork]) -> IpAddr {
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|network| network.contains(ip));
+    let forwarded: Vec<_> = headers
+        .get_all(&X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+    // Each proxy appends the address it received the request from, so walk
+    // back from the nearest one. Entries before the first untrusted address
+    // come from the client and can't be believed.
+    let mut client_ip = peer;
+    for entry in forwarded.into_iter().rev() {
+        if !is_trusted(client_ip) {
+            break;
+        }
+        let Ok(ip) = entry.parse() else {
+            break;
+        };
+        client_ip = ip;
+    }
+    client_ip
+}
+
+/// Rejects requests to the CLI and dashboard APIs from addresses the IP
+/// access policy doesn't allow.
+pub async fn admin_ip_access_middleware(
+    State(st): State<LocalAppState>,
+    ExtractClientIp(client_ip): ExtractClientIp,
+    req: axum::extract::Request,
+    next: Next,
+) -> Result<Response, HttpResponseError> {
+    st.application
+        .check_ip_access(IpAccessSurface::Admin, client_ip)
+        .await?;
+    Ok(next.run(req).await)
+}
+
+/// Rejects sync WebSocket connections and public API requests from addresses
+/// the IP access policy doesn't allow.
+pub async fn client_ip_access_middleware(
+    State(st): State<RouterState>,
+    ExtractClientIp(client_ip): ExtractClientIp,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    req: axum::extract::Request,
+    next: Next,
+) -> Result<Response, HttpResponseError> {
+    st.api
+        .check_ip_access(&host, request_id, IpAccessSurface::Client, client_ip)
+        .await?;
+    Ok(next.run(req).await)
+}
+
+/// Rejects HTTP action requests from addresses the IP access policy doesn't
+/// allow.
+pub async fn http_action_ip_access_middleware(
+    State(st): State<RouterState>,
+    ExtractClientIp(client_ip): ExtractClientIp,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    req: axum::extract::Request,
+    next: Next,
+) -> Result<Response, HttpResponseError> {
+    st.api
+        .check_ip_access(&host, request_id, IpAccessSurface::HttpActions, client_ip)
+        .await?;
+    Ok(next.run(req).await)
+}
+
+/// Networks are CIDR ranges like `10.0.0.0/8` or single addresses like
+/// `203.0.113.7`.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct IpAccessRulesJson {
+    #[serde(default)]
+    allowed: Vec<String>,
+    #[serde(default)]
+    denied: Vec<String>,
+}
+
+impl From<IpAccessRules> for IpAccessRulesJson {
+    fn from(value: IpAccessRules) -> Self {
+        Self {
+            allowed: value.allowed.iter().map(ToString::to_string).collect(),
+            denied: value.denied.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+impl TryFrom<IpAccessRulesJson> for IpAccessRules {
+    type Error = anyhow::Error;
+
+    fn try_from(value: IpAccessRulesJson) -> anyhow::Result<Self> {
+        Ok(Self {
+            allowed: value.allowed.iter().map(|s| s.parse()).try_collect()?,
+            denied: value.denied.iter().map(|s| s.parse()).try_collect()?,
+        })
+    }
+}
+
+/// Rules for one identity, written as `member:<member ID>` for an admin team
+/// member or `user:<token identifier>` for a user.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityIpAccessRulesJson {
+    identity: String,
+    #[serde(flatten)]
+    rules: IpAccessRulesJson,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpAccessPolicyJson {
+    /// Applies to every request, along with the rules for its surface.
+    #[serde(default)]
+    global: IpAccessRulesJson,
+    #[serde(default)]
+    admin: IpAccessRulesJson,
+    #[serde(default)]
+    client: IpAccessRulesJson,
+    #[serde(default)]
+    http_actions: IpAccessRulesJson,
+    /// Applies whenever the identity authenticates, on every surface.
+    #[serde(default)]
+    identities: Vec<IdentityIpAccessRulesJson>,
+}
+
+#[debug_handler]
+pub async fn get_ip_access_policy(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let policy = st.application.get_ip_access_policy(identity).await?;
+    Ok(Json(IpAccessPolicyJson {
+        global: policy.global.into(),
+        admin: policy.admin.into(),
+        client: policy.client.into(),
+        http_actions: policy.http_actions.into(),
+        identities: policy
+            .identities
+            .into_iter()
+            .map(|(identity, rules)| IdentityIpAccessRulesJson {
+                identity: identity.to_string(),
+                rules: rules.into(),
+            })
+            .collect(),
+    }))
+}
+
+#[debug_handler]
+pub async fn set_ip_access_policy(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    ExtractClientIp(client_ip): ExtractClientIp,
+    Json(IpAccessPolicyJson {
+        global,
+        admin,
+        client,
+        http_actions,
+        identities,
+    }): Json<IpAccessPolicyJson>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let policy = IpAccessPolicy {
+        global: global.try_into()?,
+        admin: admin.try_into()?,
+        client: client.try_into()?,
+        http_actions: http_actions.try_into()?,
+        identities: identities
+            .into_iter()
+            .map(|IdentityIpAccessRulesJson { identity, rules }| {
+                anyhow::Ok((identity.parse()?, rules.try_into()?))
+            })
+            .try_collect()?,
+    };
+    st.application
+        .set_ip_access_policy(identity, policy, client_ip)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use http::{
+        HeaderMap,
+        HeaderValue,
+    };
+
+    use super::{
+        forwarded_client_ip,
+        IpNetwork,
+        X_FORWARDED_FOR,
+    };
+
+    #[test]
+    fn test_forwarded_client_ip() -> anyhow::Result<()> {
+        let trusted: Vec<IpNetwork> = vec!["10.0.0.0/8".parse()?];
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_FORWARDED_FOR.clone(),
+            HeaderValue::from_static("192.0.2.1, 203.0.113.7, 10.0.0.2"),
+        );
+
+        // Requests from untrusted peers can't set their own address.
+        let peer: IpAddr = "198.51.100.1".parse()?;
+        assert_eq!(forwarded_client_ip(peer, &headers, &trusted), peer);
+
+        // Trusted proxies are skipped up to the first untrusted address.
+        assert_eq!(
+            forwarded_client_ip("10.0.0.1".parse()?, &headers, &trusted),
+            "203.0.113.7".parse::<IpAddr>()?
+        );
+        Ok(())
+    }
+}
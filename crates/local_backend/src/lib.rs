@@ -17,9 +17,15 @@ use ::storage::{
     LocalDirStorage,
     StorageUseCase,
 };
+use anyhow::Context;
 use application::{
     api::ApplicationApi,
+    log_sinks::{
+        parse_log_sinks_config,
+        LogManager,
+    },
     log_visibility::AllowLogging,
+    usage_export::UsageAggregator,
     Application,
     QueryCache,
 };
@@ -30,11 +36,20 @@ use common::{
     },
     knobs::{
         ACTION_USER_TIMEOUT,
+        ENABLE_LOG_STREAMING,
+        SHUTDOWN_DRAIN_TIMEOUT,
         UDF_CACHE_MAX_SIZE,
     },
-    log_streaming::NoopLogSender,
+    log_streaming::{
+        LogSender,
+        NoopLogSender,
+    },
     persistence::Persistence,
-    runtime::Runtime,
+    runtime::{
+        Runtime,
+        WithTimeout,
+    },
+    secrets::SecretsResolver,
     shutdown::ShutdownSignal,
     types::{
         ConvexOrigin,
@@ -46,6 +61,7 @@ use database::Database;
 use events::usage::NoOpUsageEventLogger;
 use file_storage::{
     FileStorage,
+    HttpFileScanner,
     TransactionalFileStorage,
 };
 use function_runner::{
@@ -69,33 +85,47 @@ use search::{
 };
 use serde::Serialize;
 
+use crate::subs::SyncSocketTracker;
+
 pub mod admin;
 mod app_metrics;
 mod args_structs;
 pub mod authentication;
 pub mod beacon;
 pub mod config;
+pub mod connection_pool;
 pub mod custom_headers;
 pub mod dashboard;
+pub mod data_migrations;
 pub mod deploy_config;
 pub mod deploy_config2;
+pub mod documents;
 pub mod environment_variables;
+pub mod function_kill_switches;
+pub mod health;
 pub mod http_actions;
+pub mod ip_access;
 pub mod logs;
+pub mod maintenance_windows;
 pub mod node_action_callbacks;
 pub mod parse;
 pub mod persistence;
+pub mod preview_deployments;
 pub mod proxy;
 pub mod public_api;
+pub mod retention;
 pub mod router;
 pub mod scheduling;
 pub mod schema;
+pub mod slow_queries;
 pub mod snapshot_export;
 pub mod snapshot_import;
 pub mod storage;
 pub mod subs;
+pub mod surrogate_keys;
 #[cfg(test)]
 mod test_helpers;
+pub mod usage;
 
 pub const MAX_CONCURRENT_REQUESTS: usize = 128;
 
@@ -109,10 +139,30 @@ pub struct LocalAppState {
     pub instance_name: String,
     pub application: Application<ProdRuntime>,
     pub zombify_rx: async_broadcast::Receiver<()>,
+    pub sync_sockets: SyncSocketTracker,
 }
 
 impl LocalAppState {
     pub async fn shutdown(self) -> anyhow::Result<()> {
+        // Websocket sessions stop when `zombify_rx` fires. Give them a chance to
+        // finish their in-flight mutations and actions before the function
+        // runners go away. The sessions enforce the drain timeout themselves, so
+        // this timeout only guards against one getting stuck closing.
+        let runtime = self.application.runtime();
+        let drain = runtime.with_timeout(
+            "drain_sync_sockets",
+            *SHUTDOWN_DRAIN_TIMEOUT + Duration::from_secs(5),
+            async {
+                self.sync_sockets.wait_for_drain().await;
+                Ok(())
+            },
+        );
+        if let Err(e) = drain.await {
+            tracing::warn!(
+                "{} sync sockets still open at shutdown: {e:#}",
+                self.sync_sockets.num_open()
+            );
+        }
         self.application.shutdown().await?;
 
         Ok(())
@@ -126,6 +176,8 @@ impl LocalAppState {
 pub struct RouterState {
     pub api: Arc<dyn ApplicationApi>,
     pub runtime: ProdRuntime,
+    pub shutdown_rx: async_broadcast::Receiver<()>,
+    pub sync_sockets: SyncSocketTracker,
 }
 
 #[derive(Serialize)]
@@ -144,13 +196,18 @@ pub async fn make_app(
     // TODO(CX-6572) Separate `SegmentMetadataFetcher` from `SearcherImpl`
     let segment_metadata_fetcher: Arc<dyn SegmentTermMetadataFetcher> =
         Arc::new(in_process_searcher);
+    let usage_aggregator = Arc::new(UsageAggregator::new(
+        config.name(),
+        runtime.unix_timestamp(),
+        Arc::new(NoOpUsageEventLogger),
+    ));
     let database = Database::load(
         persistence.clone(),
         runtime.clone(),
         searcher.clone(),
         preempt_tx,
         virtual_system_mapping(),
-        Arc::new(NoOpUsageEventLogger),
+        usage_aggregator.clone(),
     )
     .await?;
     initialize_application_system_tables(&database).await?;
@@ -182,12 +239,16 @@ pub async fn make_app(
         StorageUseCase::SnapshotImports,
     )?);
 
+    let mut transactional_file_storage = TransactionalFileStorage::new(
+        runtime.clone(),
+        files_storage.clone(),
+        config.convex_origin_url(),
+    );
+    if let Some(scanner) = HttpFileScanner::from_env() {
+        transactional_file_storage = transactional_file_storage.with_scanner(Arc::new(scanner));
+    }
     let file_storage = FileStorage {
-        transactional_file_storage: TransactionalFileStorage::new(
-            runtime.clone(),
-            files_storage.clone(),
-            config.convex_origin_url(),
-        ),
+        transactional_file_storage,
         database: database.clone(),
     };
 
@@ -210,6 +271,20 @@ pub async fn make_app(
         config.convex_http_proxy.clone(),
         config.name(),
     ));
+    let log_sender: Arc<dyn LogSender> = match &config.log_sinks_config {
+        Some(path) if *ENABLE_LOG_STREAMING => {
+            let sinks_config = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read log sinks config {}", path.display()))?;
+            Arc::new(LogManager::start(
+                runtime.clone(),
+                fetch_client.clone(),
+                parse_log_sinks_config(&sinks_config)?,
+                config.name(),
+            )?)
+        },
+        _ => Arc::new(NoopLogSender),
+    };
+    let secrets = SecretsResolver::from_env();
     let function_runner: Arc<dyn FunctionRunner<ProdRuntime>> = Arc::new(
         InProcessFunctionRunner::new(
             config.name().clone(),
@@ -223,6 +298,7 @@ pub async fn make_app(
             },
             database.clone(),
             fetch_client,
+            secrets.clone(),
         )
         .await?,
     );
@@ -236,6 +312,7 @@ pub async fn make_app(
         exports_storage.clone(),
         snapshot_imports_storage.clone(),
         database.usage_counter(),
+        usage_aggregator,
         key_broker.clone(),
         config.name(),
         function_runner,
@@ -245,7 +322,8 @@ pub async fn make_app(
         segment_metadata_fetcher.clone(),
         persistence,
         actions,
-        Arc::new(NoopLogSender),
+        secrets,
+        log_sender,
         Arc::new(AllowLogging),
         Arc::new(ApplicationAuth::new(
             key_broker.clone(),
@@ -273,6 +351,7 @@ pub async fn make_app(
         instance_name,
         application,
         zombify_rx,
+        sync_sockets: SyncSocketTracker::default(),
     };
 
     Ok(app_state)
@@ -11,6 +11,7 @@ use axum::{
     response::IntoResponse,
 };
 use common::{
+    document::CreationTime,
     http::{
         extract::{
             Json,
@@ -24,6 +25,10 @@ use common::{
 };
 use errors::ErrorMetadata;
 use futures::FutureExt;
+use model::function_execution_records::{
+    types::FunctionExecutionStatus,
+    FunctionExecutionRecordFilter,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -279,3 +284,140 @@ fn execution_to_json(
     };
     Ok(json)
 }
+
+/// Number of records returned by `function_execution_records` if the request
+/// doesn't set a limit.
+const DEFAULT_FUNCTION_EXECUTION_RECORDS_LIMIT: usize = 100;
+const MAX_FUNCTION_EXECUTION_RECORDS_LIMIT: usize = 1000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionExecutionRecordsQueryArgs {
+    function_path: Option<String>,
+    status: Option<String>,
+    /// Only return records persisted at or after this time, in milliseconds
+    /// since the epoch.
+    start_ms: Option<f64>,
+    /// Only return records persisted before this time.
+    end_ms: Option<f64>,
+    cursor: Option<f64>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionExecutionRecordJson {
+    /// When the record was persisted, in milliseconds since the epoch.
+    creation_time: f64,
+    component_path: Option<String>,
+    function_path: String,
+    udf_type: String,
+    status: &'static str,
+    error: Option<String>,
+    request_id: String,
+    execution_id: String,
+    timestamp: f64,
+    execution_time_ms: u64,
+    cached_result: bool,
+    rows_read: u64,
+    rows_written: u64,
+    database_read_bytes: u64,
+    database_write_bytes: u64,
+    log_lines: Vec<String>,
+    log_lines_truncated: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionExecutionRecordsResponse {
+    records: Vec<FunctionExecutionRecordJson>,
+    /// Pass as `cursor` to fetch the next page. `null` once there are no more
+    /// records.
+    cursor: Option<f64>,
+}
+
+/// List persisted function executions newest first. Unlike
+/// `stream_function_logs`, this covers executions from the whole retention
+/// period rather than just the ones still in memory.
+pub async fn function_execution_records(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(query_args): Query<FunctionExecutionRecordsQueryArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let limit = query_args
+        .limit
+        .unwrap_or(DEFAULT_FUNCTION_EXECUTION_RECORDS_LIMIT);
+    if limit > MAX_FUNCTION_EXECUTION_RECORDS_LIMIT {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidLimit",
+            format!(
+                "Limit {limit} is greater than the maximum of \
+                 {MAX_FUNCTION_EXECUTION_RECORDS_LIMIT}"
+            ),
+        ))
+        .into());
+    }
+    let parse_time = |ms: Option<f64>| -> anyhow::Result<Option<CreationTime>> {
+        ms.map(|ms| {
+            CreationTime::try_from(ms).context(ErrorMetadata::bad_request(
+                "InvalidTimestamp",
+                format!("Invalid timestamp {ms}"),
+            ))
+        })
+        .transpose()
+    };
+    let status = query_args
+        .status
+        .map(|status| {
+            status
+                .parse::<FunctionExecutionStatus>()
+                .context(ErrorMetadata::bad_request(
+                    "InvalidStatus",
+                    format!("Invalid status {status:?}, expected \"success\" or \"failure\""),
+                ))
+        })
+        .transpose()?;
+    let filter = FunctionExecutionRecordFilter {
+        function_path: query_args.function_path,
+        status,
+        start: parse_time(query_args.start_ms)?,
+        end: parse_time(query_args.end_ms)?,
+    };
+    let page = st
+        .application
+        .list_function_execution_records(identity, filter, parse_time(query_args.cursor)?, limit)
+        .await?;
+    let records = page
+        .records
+        .into_iter()
+        .map(|record| {
+            let creation_time = record
+                .creation_time()
+                .context("Function execution record missing creation time")?;
+            let record = record.into_value();
+            Ok(FunctionExecutionRecordJson {
+                creation_time: creation_time.into(),
+                component_path: record.component_path.serialize(),
+                function_path: record.function_path,
+                udf_type: record.udf_type.to_string(),
+                status: record.status.as_str(),
+                error: record.error,
+                request_id: record.request_id,
+                execution_id: record.execution_id,
+                timestamp: record.unix_timestamp_ms as f64 / 1000.0,
+                execution_time_ms: record.execution_time_ms,
+                cached_result: record.cached_result,
+                rows_read: record.rows_read,
+                rows_written: record.rows_written,
+                database_read_bytes: record.database_read_bytes,
+                database_write_bytes: record.database_write_bytes,
+                log_lines: record.log_lines,
+                log_lines_truncated: record.log_lines_truncated,
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Json(FunctionExecutionRecordsResponse {
+        records,
+        cursor: page.cursor.map(f64::from),
+    }))
+}
@@ -6,8 +6,13 @@ use anyhow::anyhow;
 use clap::Parser;
 use cmd_util::env::config_service;
 use common::{
-    errors::MainError,
+    errors::{
+        LeaseLostError,
+        MainError,
+    },
+    fastrace_helpers::config_trace_export,
     http::ConvexHttpService,
+    knobs::SHUTDOWN_DRAIN_TIMEOUT,
     runtime::Runtime,
     shutdown::ShutdownSignal,
     version::SERVER_VERSION_STR,
@@ -36,6 +41,7 @@ use tokio::signal::{
 fn main() -> Result<(), MainError> {
     tracing::info!("Starting a local backend");
     let _guard = config_service();
+    let _trace_guard = config_trace_export()?;
     let config = LocalConfig::parse();
     tracing::info!("Starting with config {:?}", config);
     let sentry = sentry::init(sentry::ClientOptions {
@@ -90,6 +96,21 @@ async fn run_server(runtime: ProdRuntime, config: LocalConfig) -> anyhow::Result
     Ok(())
 }
 
+/// Resolves with the signal's name on Ctrl-C or, on Unix, SIGTERM.
+async fn shutdown_signal() -> anyhow::Result<&'static str> {
+    #[cfg(unix)]
+    {
+        let mut sigterm = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+        futures::select! {
+            r = signal::ctrl_c().fuse() => r?,
+            _ = sigterm.recv().fuse() => return Ok("SIGTERM"),
+        }
+    }
+    #[cfg(not(unix))]
+    signal::ctrl_c().await?;
+    Ok("Ctrl-C")
+}
+
 async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::Result<()> {
     // Used to receive fatal errors from the database or /preempt endpoint.
     let (preempt_tx, mut preempt_rx) = async_broadcast::broadcast(1);
@@ -139,32 +160,51 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
     futures::pin_mut!(preempt_future);
 
     // Start shutdown when we get a manual shutdown signal or with the first
-    // ctrl-c.
+    // ctrl-c or SIGTERM.
     let mut force_exit_duration = None;
     futures::select! {
         r = serve_future => {
             r?;
             panic!("Serve future stopped unexpectedly!")
         },
-        _err = preempt_future => {
-            // If we fail with a fatal error, we want to exit immediately.
-            tracing::info!("Received a fatal error. Shutting down immediately");
-            force_exit_duration = Some(Duration::from_secs(0));
+        msg = preempt_future => {
+            let lease_lost = msg
+                .as_ref()
+                .is_ok_and(|msg| msg.error.downcast_ref::<LeaseLostError>().is_some());
+            if lease_lost {
+                // Another backend process acquired the lease, so our commits are
+                // fenced off. Drain gracefully so clients reconnect to it.
+                tracing::info!("Another backend took over the lease. Shutting down");
+            } else {
+                // If we fail with a fatal error, we want to exit immediately.
+                tracing::info!("Received a fatal error. Shutting down immediately");
+                force_exit_duration = Some(Duration::from_secs(0));
+            }
             let _: Result<_, _> = shutdown_tx.broadcast(()).await;
         }
-        r = signal::ctrl_c().fuse() => {
-            tracing::info!("Received Ctrl-C signal!");
-            r?;
+        r = shutdown_signal().fuse() => {
+            tracing::info!("Received {} signal!", r?);
             let _: Result<_, _> = shutdown_tx.broadcast(()).await;
         },
     }
 
+    let runtime_ = runtime.clone();
     let shutdown = async move {
-        // First, drain all in-progress requests;
+        // First, drain all in-progress requests. The http service stops
+        // accepting connections and websocket sessions start draining as soon
+        // as `shutdown_tx` fires.
         tracing::info!("Shutdown initiated, draining existing requests...");
-        serve_future.await?;
+        futures::select! {
+            r = serve_future => {
+                r?;
+            },
+            _ = runtime_.wait(*SHUTDOWN_DRAIN_TIMEOUT) => {
+                tracing::warn!("Timed out draining requests");
+            },
+        }
 
-        // Next, shutdown all of our asynchronous workers.
+        // Next, wait for websocket sessions to close, checkpoint, and shutdown
+        // all of our asynchronous workers.
         tracing::info!("Shutting down application...");
         st.shutdown().await?;
 
@@ -195,8 +235,8 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
                 tracing::info!("Cool down expired. Shutting down");
                 break;
             }
-            // Forcibly shutdown with second ctrl-c.
-            r = signal::ctrl_c().fuse() => {
+            // Forcibly shutdown with second ctrl-c or SIGTERM.
+            r = shutdown_signal().fuse() => {
                 r?;
                 tracing::warn!("Forcibly shutting down!");
                 break;
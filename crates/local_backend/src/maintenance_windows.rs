@@ -0,0 +1,138 @@
+use anyhow::Context;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    runtime::UnixTimestamp,
+};
+use http::StatusCode;
+use model::maintenance_windows::{
+    types::MaintenanceWindowState,
+    MAINTENANCE_WINDOWS_TABLE,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::id_v6::DeveloperDocumentId;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_member_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    parse::invalid_id_error,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleMaintenanceWindowArgs {
+    start_ms: u64,
+    /// Windows without an end last until they're cancelled.
+    end_ms: Option<u64>,
+    /// Shown to clients instead of the default paused message.
+    message: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScheduleMaintenanceWindowResponse {
+    id: String,
+}
+
+/// Schedule the deployment to be paused from `startMs` until `endMs`.
+#[debug_handler]
+pub async fn schedule_maintenance_window(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ScheduleMaintenanceWindowArgs {
+        start_ms,
+        end_ms,
+        message,
+    }): Json<ScheduleMaintenanceWindowArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let id = st
+        .application
+        .schedule_maintenance_window(
+            identity,
+            UnixTimestamp::from_millis(start_ms),
+            end_ms.map(UnixTimestamp::from_millis),
+            message,
+        )
+        .await?;
+    Ok(Json(ScheduleMaintenanceWindowResponse { id: id.encode() }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MaintenanceWindowJson {
+    id: String,
+    start_ms: u64,
+    end_ms: Option<u64>,
+    message: Option<String>,
+    active: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListMaintenanceWindowsResponse {
+    windows: Vec<MaintenanceWindowJson>,
+}
+
+#[debug_handler]
+pub async fn list_maintenance_windows(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let windows = st
+        .application
+        .list_maintenance_windows(identity)
+        .await?
+        .into_iter()
+        .map(|window| {
+            let id = window.developer_id().encode();
+            let window = window.into_value();
+            anyhow::Ok(MaintenanceWindowJson {
+                id,
+                start_ms: window.start.as_ms_since_epoch()?,
+                end_ms: window.end.map(|end| end.as_ms_since_epoch()).transpose()?,
+                message: window.message,
+                active: matches!(window.state, MaintenanceWindowState::Active { .. }),
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Json(ListMaintenanceWindowsResponse { windows }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelMaintenanceWindowArgs {
+    id: String,
+}
+
+/// Cancel a maintenance window. Cancelling an active window resumes the
+/// deployment if the window paused it.
+#[debug_handler]
+pub async fn cancel_maintenance_window(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CancelMaintenanceWindowArgs { id }): Json<CancelMaintenanceWindowArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let id =
+        DeveloperDocumentId::decode(&id).context(invalid_id_error(&MAINTENANCE_WINDOWS_TABLE))?;
+    st.application
+        .cancel_maintenance_window(identity, id)
+        .await?;
+    Ok(StatusCode::OK)
+}
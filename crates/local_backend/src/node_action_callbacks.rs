@@ -31,13 +31,18 @@ use common::{
     },
     http::{
         extract::Json,
+        request_context_from_headers,
         ExtractClientVersion,
         HttpResponseError,
     },
     knobs::ACTION_USER_TIMEOUT,
-    runtime::UnixTimestamp,
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
     types::{
         FunctionCaller,
+        ModuleEnvironment,
         UdfIdentifier,
     },
     RequestId,
@@ -46,10 +51,29 @@ use errors::ErrorMetadata;
 use fastrace::future::FutureExt;
 use http::HeaderMap;
 use isolate::{
+    environment::helpers::{
+        image_urls::parse_get_image_url_args,
+        service_tokens::{
+            self,
+            MintServiceTokenArgs,
+        },
+        signed_urls::{
+            parse_get_signed_url_args,
+            parse_revoke_signed_url_args,
+        },
+    },
     ActionCallbacks,
     UdfArgsJson,
 };
 use keybroker::Identity;
+use model::{
+    file_storage::types::FileEncryptionMetadata,
+    outbound_network_policy::types::NetworkPolicyViolation,
+    scheduled_jobs::types::{
+        ScheduleOptions,
+        ScheduleOptionsJson,
+    },
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -183,6 +207,7 @@ pub async fn internal_mutation_post(
             FunctionCaller::Action {
                 parent_scheduled_job: context.parent_scheduled_job,
             },
+            context.request_context,
         )
         .await?;
     if req.format.is_some() {
@@ -240,6 +265,7 @@ pub async fn internal_action_post(
             FunctionCaller::Action {
                 parent_scheduled_job: context.parent_scheduled_job,
             },
+            context.request_context,
         )
         .await?;
     if req.format.is_some() {
@@ -269,6 +295,8 @@ pub struct ScheduleJobRequest {
     udf_path: Option<String>,
     udf_args: UdfArgsJson,
     scheduled_ts: f64,
+    #[serde(flatten)]
+    options: ScheduleOptionsJson,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -303,6 +331,7 @@ pub async fn schedule_job(
             anyhow::anyhow!(ErrorMetadata::bad_request("InvalidUdfPath", e.to_string()))
         })?;
     let udf_args = req.udf_args.into_arg_vec();
+    let options = ScheduleOptions::try_from(req.options)?;
     let job_id = st
         .application
         .runner()
@@ -312,6 +341,7 @@ pub async fn schedule_job(
             path,
             udf_args,
             scheduled_ts,
+            options,
             context,
         )
         .await?;
@@ -391,6 +421,115 @@ pub async fn create_function_handle(
     }))
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPolicyViolationRequest {
+    host: String,
+    reason: String,
+}
+
+/// Records a request that the Node runtime blocked because of the
+/// deployment's outbound network policy.
+#[debug_handler]
+pub async fn record_network_policy_violation(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity { identity, .. }: ExtractActionIdentity,
+    Json(req): Json<NetworkPolicyViolationRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let reason = req.reason.parse().map_err(|_| {
+        anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidNetworkPolicyViolation",
+            format!("Unknown violation reason {:?}", req.reason),
+        ))
+    })?;
+    let violation = NetworkPolicyViolation {
+        host: req.host,
+        reason,
+    };
+    st.application
+        .runner()
+        .record_network_policy_violation(identity, violation, ModuleEnvironment::Node)
+        .await?;
+    Ok(Json(json!(null)))
+}
+
+/// Mints a service token for `ctx.auth.mintServiceToken` in Node actions.
+#[debug_handler]
+pub async fn mint_service_token(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity {
+        identity,
+        component_id,
+    }: ExtractActionIdentity,
+    Json(req): Json<JsonValue>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let args = MintServiceTokenArgs::parse(req)?;
+    let mut storage_uuids = vec![];
+    for storage_id in args.storage_ids()? {
+        let (_, entry) = st
+            .application
+            .runner()
+            .storage_get_file_entry(identity.clone(), component_id, storage_id.clone())
+            .await?
+            .context(ErrorMetadata::bad_request(
+                "FileNotFound",
+                format!("File {storage_id} not found"),
+            ))?;
+        storage_uuids.push(entry.storage_id);
+    }
+    let user_identity = match identity {
+        Identity::User(identity) => Some(identity.attributes),
+        Identity::ActingUser(_, identity) => Some(identity),
+        _ => None,
+    };
+    let token = args.into_service_token(
+        user_identity,
+        component_id,
+        st.application.runtime().unix_timestamp(),
+        storage_uuids,
+    )?;
+    let token = st.application.key_broker().issue_service_token(token);
+    Ok(Json(json!({ "token": token })))
+}
+
+#[debug_handler]
+pub async fn verify_service_token(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity { component_id, .. }: ExtractActionIdentity,
+    Json(req): Json<JsonValue>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let result = service_tokens::verify_service_token(
+        st.application.key_broker(),
+        component_id,
+        st.application.runtime().unix_timestamp(),
+        req,
+    )?;
+    Ok(Json(result))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidateHttpActionCacheRequest {
+    path_prefix: Option<String>,
+}
+
+#[debug_handler]
+pub async fn invalidate_http_action_cache(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity {
+        identity,
+        component_id,
+    }: ExtractActionIdentity,
+    Json(req): Json<InvalidateHttpActionCacheRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let dropped = st
+        .application
+        .runner()
+        .invalidate_http_action_cache(identity, component_id, req.path_prefix)
+        .await?;
+    Ok(Json(json!({ "dropped": dropped })))
+}
+
 #[debug_handler]
 pub async fn vector_search(
     State(st): State<LocalAppState>,
@@ -486,6 +625,59 @@ pub async fn storage_get_url(
     Ok(Json(json!({ "url": url })))
 }
 
+#[debug_handler]
+pub async fn storage_get_signed_url(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity {
+        identity,
+        component_id,
+    }: ExtractActionIdentity,
+    Json(req): Json<JsonValue>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let (storage_id, options) = parse_get_signed_url_args(req)?;
+    let url = st
+        .application
+        .runner()
+        .storage_get_signed_url(identity, component_id, storage_id, options)
+        .await?;
+    Ok(Json(json!({ "url": url })))
+}
+
+#[debug_handler]
+pub async fn storage_revoke_signed_url(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity {
+        identity,
+        component_id,
+    }: ExtractActionIdentity,
+    Json(req): Json<JsonValue>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let url = parse_revoke_signed_url_args(req)?;
+    st.application
+        .runner()
+        .storage_revoke_signed_url(identity, component_id, url)
+        .await?;
+    Ok(Json(json!(null)))
+}
+
+#[debug_handler]
+pub async fn storage_get_image_url(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity {
+        identity,
+        component_id,
+    }: ExtractActionIdentity,
+    Json(req): Json<JsonValue>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let (storage_id, transform) = parse_get_image_url_args(req)?;
+    let url = st
+        .application
+        .runner()
+        .storage_get_image_url(identity, component_id, storage_id, transform)
+        .await?;
+    Ok(Json(json!({ "url": url })))
+}
+
 #[debug_handler]
 pub async fn storage_get_metadata(
     State(st): State<LocalAppState>,
@@ -503,6 +695,8 @@ pub async fn storage_get_metadata(
         sha256: String,
         size: i64,
         content_type: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        encryption: Option<FileEncryptionMetadata>,
     }
 
     let file_metadata = st
@@ -518,6 +712,7 @@ pub async fn storage_get_metadata(
                 sha256: entry.sha256.as_hex(),
                 size: entry.size,
                 content_type: entry.content_type,
+                encryption: entry.encryption,
             }
         });
     Ok(Json(file_metadata))
@@ -700,12 +895,14 @@ impl<T> FromRequestParts<T> for ExtractExecutionContext {
             .map(|s| s.parse())
             .transpose()
             .context("Invalid scheduled job id")?;
+        let request_context = request_context_from_headers(&parts.headers)?;
 
         Ok(Self(ExecutionContext::new_from_parts(
             request_id,
             execution_id,
             parent_job_id,
             is_root,
+            request_context,
         )))
     }
 }
@@ -43,7 +43,7 @@ pub async fn connect_persistence(
                 db,
                 require_ssl,
             )?;
-            Arc::new(PostgresPersistence::new(args.url.as_str(), options).await?)
+            Arc::new(PostgresPersistence::new(args.url.as_str(), options, shutdown_signal).await?)
         },
         DbDriverTag::MySql(version) | DbDriverTag::MySqlAwsIam(version) => {
             let options = MySqlOptions {
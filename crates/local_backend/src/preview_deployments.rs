@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use http::StatusCode;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_member_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatePreviewDeploymentArgs {
+    name: String,
+    /// Defaults to `PREVIEW_DEPLOYMENT_DEFAULT_TTL`.
+    ttl_ms: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatePreviewDeploymentResponse {
+    expires_at_ms: u64,
+}
+
+/// Create a preview deployment called `name` that expires after `ttlMs`. If it
+/// already exists, its data is kept and its expiry pushed back, so CI can call
+/// this on every run. Use its name as `preview` in `/document_batch` and
+/// `/query_documents` to write and read its tables.
+#[debug_handler]
+pub async fn create_preview_deployment(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CreatePreviewDeploymentArgs { name, ttl_ms }): Json<CreatePreviewDeploymentArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let expires_at = st
+        .application
+        .create_preview_deployment(identity, name, ttl_ms.map(Duration::from_millis))
+        .await?;
+    Ok(Json(CreatePreviewDeploymentResponse {
+        expires_at_ms: expires_at.as_ms_since_epoch()?,
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PreviewDeploymentJson {
+    name: String,
+    expires_at_ms: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListPreviewDeploymentsResponse {
+    previews: Vec<PreviewDeploymentJson>,
+}
+
+#[debug_handler]
+pub async fn list_preview_deployments(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let previews = st
+        .application
+        .list_preview_deployments(identity)
+        .await?
+        .into_iter()
+        .map(|preview| {
+            let preview = preview.into_value();
+            anyhow::Ok(PreviewDeploymentJson {
+                name: preview.name,
+                expires_at_ms: preview.expires_at.as_ms_since_epoch()?,
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Json(ListPreviewDeploymentsResponse { previews }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletePreviewDeploymentArgs {
+    name: String,
+}
+
+/// Delete a preview deployment and its tables without waiting for it to
+/// expire.
+#[debug_handler]
+pub async fn delete_preview_deployment(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeletePreviewDeploymentArgs { name }): Json<DeletePreviewDeploymentArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    st.application
+        .delete_preview_deployment(identity, &name)
+        .await?;
+    Ok(StatusCode::OK)
+}
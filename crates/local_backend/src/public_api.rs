@@ -1,12 +1,17 @@
 use application::{
     api::ExecuteQueryTimestamp,
+    builtin_auth::BuiltinAuthTokens,
     redaction::{
         RedactedJsError,
         RedactedLogLines,
     },
 };
+use async_trait::async_trait;
 use axum::{
-    extract::State,
+    extract::{
+        FromRequestParts,
+        State,
+    },
     response::IntoResponse,
 };
 use common::{
@@ -21,6 +26,7 @@ use common::{
             Query,
         },
         ExtractClientVersion,
+        ExtractRequestContext,
         ExtractRequestId,
         ExtractResolvedHostname,
         HttpResponseError,
@@ -29,6 +35,10 @@ use common::{
     version::ClientVersion,
 };
 use errors::ErrorMetadata;
+use http::{
+    HeaderName,
+    StatusCode,
+};
 use isolate::UdfArgsJson;
 use serde::{
     Deserialize,
@@ -44,6 +54,7 @@ use value::{
 use crate::{
     args_structs::UdfPostRequestWithComponent,
     authentication::ExtractAuthenticationToken,
+    ip_access::ExtractClientIp,
     parse::{
         parse_export_path,
         parse_udf_path,
@@ -99,6 +110,60 @@ impl TryFrom<SerializedTs> for Timestamp {
     }
 }
 
+/// Carries the latest timestamp a client has observed through the HTTP API,
+/// encoded like `SerializedTs`. Query, mutation and action responses set it,
+/// and queries sent with it execute at or after that timestamp, so clients
+/// that echo it back always read their own writes.
+#[allow(clippy::declare_interior_mutable_const)]
+pub const CONVEX_SESSION_TOKEN_HEADER: HeaderName = HeaderName::from_static("convex-session-token");
+
+pub struct ExtractSessionToken(pub Option<Timestamp>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ExtractSessionToken
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpResponseError;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(header) = parts.headers.get(CONVEX_SESSION_TOKEN_HEADER) else {
+            return Ok(Self(None));
+        };
+        let ts = header
+            .to_str()
+            .ok()
+            .and_then(|h| Timestamp::try_from(SerializedTs(h.to_string())).ok())
+            .ok_or_else(|| {
+                anyhow::anyhow!(ErrorMetadata::bad_request(
+                    "InvalidSessionToken",
+                    format!("Invalid {CONVEX_SESSION_TOKEN_HEADER} header"),
+                ))
+            })?;
+        Ok(Self(Some(ts)))
+    }
+}
+
+/// Builds the session token response header for a client that has observed
+/// `observed_ts`, never moving backwards from the token it sent.
+fn session_token_header(
+    session_token: Option<Timestamp>,
+    observed_ts: Timestamp,
+) -> [(HeaderName, String); 1] {
+    let ts = session_token.map_or(observed_ts, |ts| ts.max(observed_ts));
+    [(CONVEX_SESSION_TOKEN_HEADER, SerializedTs::from(ts).0)]
+}
+
+fn query_timestamp(session_token: Option<Timestamp>) -> ExecuteQueryTimestamp {
+    match session_token {
+        Some(min_ts) => ExecuteQueryTimestamp::AtLeast(min_ts),
+        None => ExecuteQueryTimestamp::Latest,
+    }
+}
+
 #[derive(Deserialize)]
 pub struct UdfArgsQuery {
     pub path: String,
@@ -333,6 +398,7 @@ pub async fn public_query_get(
     ExtractRequestId(request_id): ExtractRequestId,
     ExtractAuthenticationToken(auth_token): ExtractAuthenticationToken,
     ExtractClientVersion(client_version): ExtractClientVersion,
+    ExtractSessionToken(session_token): ExtractSessionToken,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let export_path = parse_export_path(&req.path)?;
     let args = req.args.into_arg_vec();
@@ -354,10 +420,11 @@ pub async fn public_query_get(
             export_path,
             args,
             FunctionCaller::HttpApi(client_version.clone()),
-            ExecuteQueryTimestamp::Latest,
+            query_timestamp(session_token),
             journal,
         )
         .await?;
+    let observed_ts = query_result.token.ts();
     let value_format = req.format.as_ref().map(|f| f.parse()).transpose()?;
     let log_lines = query_result.log_lines;
     let response = match query_result.result {
@@ -367,7 +434,10 @@ pub async fn public_query_get(
         },
         Err(error) => UdfResponse::error(error, log_lines, value_format, client_version)?,
     };
-    Ok(Json(response))
+    Ok((
+        session_token_header(session_token, observed_ts),
+        Json(response),
+    ))
 }
 
 #[fastrace::trace(properties = { "udf_type": "query"})]
@@ -377,6 +447,7 @@ pub async fn public_query_post(
     ExtractRequestId(request_id): ExtractRequestId,
     ExtractAuthenticationToken(auth_token): ExtractAuthenticationToken,
     ExtractClientVersion(client_version): ExtractClientVersion,
+    ExtractSessionToken(session_token): ExtractSessionToken,
     Json(req): Json<UdfPostRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let udf_path = parse_export_path(&req.path)?;
@@ -398,10 +469,11 @@ pub async fn public_query_post(
             udf_path,
             req.args.into_arg_vec(),
             FunctionCaller::HttpApi(client_version.clone()),
-            ExecuteQueryTimestamp::Latest,
+            query_timestamp(session_token),
             journal,
         )
         .await?;
+    let observed_ts = query_return.token.ts();
     let value_format = req.format.as_ref().map(|f| f.parse()).transpose()?;
     let response = match query_return.result {
         Ok(value) => UdfResponse::Success {
@@ -412,7 +484,10 @@ pub async fn public_query_post(
             UdfResponse::error(error, query_return.log_lines, value_format, client_version)?
         },
     };
-    Ok(Json(response))
+    Ok((
+        session_token_header(session_token, observed_ts),
+        Json(response),
+    ))
 }
 
 pub async fn public_get_query_ts(
@@ -486,11 +561,19 @@ pub async fn public_query_batch_post(
     ExtractRequestId(request_id): ExtractRequestId,
     ExtractAuthenticationToken(auth_token): ExtractAuthenticationToken,
     ExtractClientVersion(client_version): ExtractClientVersion,
+    ExtractSessionToken(session_token): ExtractSessionToken,
     Json(req_batch): Json<QueryBatchArgs>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let mut results = vec![];
-    // All queries execute at the same timestamp.
-    let ts = st.api.latest_timestamp(&host, request_id.clone()).await?;
+    // All queries execute at the same timestamp. With a session token, the
+    // first query picks a timestamp at or after it and the rest reuse it.
+    let mut ts = match session_token {
+        Some(min_ts) => ExecuteQueryTimestamp::AtLeast(min_ts),
+        None => {
+            ExecuteQueryTimestamp::At(*st.api.latest_timestamp(&host, request_id.clone()).await?)
+        },
+    };
+    let mut observed_ts = session_token;
     let identity = st
         .api
         .authenticate(&host, request_id.clone(), auth_token)
@@ -507,10 +590,12 @@ pub async fn public_query_batch_post(
                 export_path,
                 req.args.into_arg_vec(),
                 FunctionCaller::HttpApi(client_version.clone()),
-                ExecuteQueryTimestamp::At(*ts),
+                ts,
                 None,
             )
             .await?;
+        ts = ExecuteQueryTimestamp::At(udf_return.token.ts());
+        observed_ts = Some(udf_return.token.ts());
         let response = match udf_return.result {
             Ok(value) => UdfResponse::Success {
                 value: export_value(value, value_format, client_version.clone())?,
@@ -525,7 +610,8 @@ pub async fn public_query_batch_post(
         };
         results.push(response);
     }
-    Ok(Json(QueryBatchResponse { results }))
+    let headers = observed_ts.map(|ts| session_token_header(session_token, ts));
+    Ok((headers, Json(QueryBatchResponse { results })))
 }
 
 #[fastrace::trace(properties = { "udf_type": "mutation"})]
@@ -535,6 +621,8 @@ pub async fn public_mutation_post(
     ExtractRequestId(request_id): ExtractRequestId,
     ExtractAuthenticationToken(auth_token): ExtractAuthenticationToken,
     ExtractClientVersion(client_version): ExtractClientVersion,
+    ExtractRequestContext(request_context): ExtractRequestContext,
+    ExtractSessionToken(session_token): ExtractSessionToken,
     Json(req): Json<UdfPostRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let export_path = parse_export_path(&req.path)?;
@@ -556,22 +644,32 @@ pub async fn public_mutation_post(
             req.args.into_arg_vec(),
             FunctionCaller::HttpApi(client_version.clone()),
             None,
+            request_context,
         )
         .await?;
     let value_format = req.format.as_ref().map(|f| f.parse()).transpose()?;
-    let response = match udf_result {
-        Ok(write_return) => UdfResponse::Success {
-            value: export_value(write_return.value, value_format, client_version)?,
-            log_lines: write_return.log_lines,
-        },
-        Err(write_error) => UdfResponse::error(
-            write_error.error,
-            write_error.log_lines,
-            value_format,
-            client_version,
-        )?,
+    let (response, observed_ts) = match udf_result {
+        Ok(write_return) => (
+            UdfResponse::Success {
+                value: export_value(write_return.value, value_format, client_version)?,
+                log_lines: write_return.log_lines,
+            },
+            Some(write_return.ts),
+        ),
+        Err(write_error) => (
+            UdfResponse::error(
+                write_error.error,
+                write_error.log_lines,
+                value_format,
+                client_version,
+            )?,
+            None,
+        ),
     };
-    Ok(Json(response))
+    let headers = observed_ts
+        .or(session_token)
+        .map(|ts| session_token_header(session_token, ts));
+    Ok((headers, Json(response)))
 }
 
 #[fastrace::trace(properties = { "udf_type": "action"})]
@@ -581,6 +679,8 @@ pub async fn public_action_post(
     ExtractRequestId(request_id): ExtractRequestId,
     ExtractAuthenticationToken(auth_token): ExtractAuthenticationToken,
     ExtractClientVersion(client_version): ExtractClientVersion,
+    ExtractRequestContext(request_context): ExtractRequestContext,
+    ExtractSessionToken(session_token): ExtractSessionToken,
     Json(req): Json<UdfPostRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let export_path = parse_export_path(&req.path)?;
@@ -597,13 +697,17 @@ pub async fn public_action_post(
         .api
         .execute_public_action(
             &host,
-            request_id,
+            request_id.clone(),
             identity,
             export_path,
             req.args.into_arg_vec(),
             FunctionCaller::HttpApi(client_version.clone()),
+            request_context,
         )
         .await?;
+    // Any mutations the action ran have committed by now, so the latest
+    // timestamp covers them.
+    let observed_ts = *st.api.latest_timestamp(&host, request_id).await?;
     let value_format = req.format.as_ref().map(|f| f.parse()).transpose()?;
     let response = match action_result {
         Ok(action_return) => UdfResponse::Success {
@@ -617,7 +721,280 @@ pub async fn public_action_post(
             client_version,
         )?,
     };
-    Ok(Json(response))
+    Ok((
+        session_token_header(session_token, observed_ts),
+        Json(response),
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnonymousTokenRequest {
+    /// A random ID the client generates once and keeps in local storage. It
+    /// binds the anonymous identity to the device.
+    device_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnonymousTokenResponse {
+    token: String,
+}
+
+/// Issues an ID token for the device's anonymous identity, which clients pass
+/// to `setAuth` like any other provider's token.
+pub async fn public_anonymous_token_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    Json(req): Json<AnonymousTokenRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let token = st
+        .api
+        .issue_anonymous_identity_token(&host, request_id, req.device_id)
+        .await?;
+    Ok(Json(AnonymousTokenResponse { token }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuiltinAuthPasswordRequest {
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuiltinAuthMagicLinkRequest {
+    email: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuiltinAuthVerifyMagicLinkRequest {
+    token: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuiltinAuthRefreshRequest {
+    refresh_token: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuiltinAuthTokensResponse {
+    /// ID token for the user, which clients pass to `setAuth`.
+    token: String,
+    refresh_token: String,
+}
+
+impl From<BuiltinAuthTokens> for BuiltinAuthTokensResponse {
+    fn from(tokens: BuiltinAuthTokens) -> Self {
+        Self {
+            token: tokens.token,
+            refresh_token: tokens.refresh_token,
+        }
+    }
+}
+
+/// Creates a user with the email and password, who then signs in. Succeeds
+/// whether or not a user with the email already exists.
+pub async fn public_builtin_auth_sign_up_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    Json(req): Json<BuiltinAuthPasswordRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    st.api
+        .builtin_auth_sign_up(&host, request_id, req.email, req.password)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+pub async fn public_builtin_auth_sign_in_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    ExtractClientIp(client_ip): ExtractClientIp,
+    Json(req): Json<BuiltinAuthPasswordRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let tokens = st
+        .api
+        .builtin_auth_sign_in(&host, request_id, req.email, req.password, client_ip)
+        .await?;
+    Ok(Json(BuiltinAuthTokensResponse::from(tokens)))
+}
+
+/// Schedules the deployment's magic link action to send a sign-in link to
+/// the email. Succeeds whether or not a user with the email already exists.
+pub async fn public_builtin_auth_magic_link_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    ExtractClientVersion(client_version): ExtractClientVersion,
+    ExtractClientIp(client_ip): ExtractClientIp,
+    Json(req): Json<BuiltinAuthMagicLinkRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    st.api
+        .builtin_auth_send_magic_link(&host, request_id, req.email, client_version, client_ip)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+pub async fn public_builtin_auth_verify_magic_link_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    Json(req): Json<BuiltinAuthVerifyMagicLinkRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let tokens = st
+        .api
+        .builtin_auth_verify_magic_link(&host, request_id, req.token)
+        .await?;
+    Ok(Json(BuiltinAuthTokensResponse::from(tokens)))
+}
+
+pub async fn public_builtin_auth_refresh_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    Json(req): Json<BuiltinAuthRefreshRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let tokens = st
+        .api
+        .builtin_auth_refresh(&host, request_id, req.refresh_token)
+        .await?;
+    Ok(Json(BuiltinAuthTokensResponse::from(tokens)))
+}
+
+pub async fn public_builtin_auth_sign_out_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    Json(req): Json<BuiltinAuthRefreshRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    st.api
+        .builtin_auth_sign_out(&host, request_id, req.refresh_token)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+/// The JSON encoding of a `PublicKeyCredential` from
+/// `navigator.credentials.create()`, as returned by its `toJSON()`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyRegistrationRequest {
+    response: PasskeyAttestationResponse,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PasskeyAttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    client_data_json: String,
+    attestation_object: String,
+}
+
+/// The JSON encoding of a `PublicKeyCredential` from
+/// `navigator.credentials.get()`, as returned by its `toJSON()`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeySignInRequest {
+    id: String,
+    response: PasskeyAssertionResponse,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PasskeyAssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    client_data_json: String,
+    authenticator_data: String,
+    signature: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyRegistrationResponse {
+    credential_id: String,
+}
+
+/// Returns options for `navigator.credentials.create()` to register a passkey
+/// for the user signed in with built-in auth.
+pub async fn public_builtin_auth_passkey_register_options_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    ExtractAuthenticationToken(auth_token): ExtractAuthenticationToken,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let identity = st
+        .api
+        .authenticate(&host, request_id.clone(), auth_token)
+        .await?;
+    let options = st
+        .api
+        .builtin_auth_passkey_registration_options(&host, request_id, identity)
+        .await?;
+    Ok(Json(options))
+}
+
+pub async fn public_builtin_auth_passkey_register_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    ExtractAuthenticationToken(auth_token): ExtractAuthenticationToken,
+    Json(req): Json<PasskeyRegistrationRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let identity = st
+        .api
+        .authenticate(&host, request_id.clone(), auth_token)
+        .await?;
+    let credential_id = st
+        .api
+        .builtin_auth_register_passkey(
+            &host,
+            request_id,
+            identity,
+            req.response.client_data_json,
+            req.response.attestation_object,
+        )
+        .await?;
+    Ok(Json(PasskeyRegistrationResponse { credential_id }))
+}
+
+/// Returns options for `navigator.credentials.get()` to sign in with a
+/// passkey.
+pub async fn public_builtin_auth_passkey_sign_in_options_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let options = st
+        .api
+        .builtin_auth_passkey_sign_in_options(&host, request_id)
+        .await?;
+    Ok(Json(options))
+}
+
+pub async fn public_builtin_auth_passkey_sign_in_post(
+    State(st): State<RouterState>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+    Json(req): Json<PasskeySignInRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let tokens = st
+        .api
+        .builtin_auth_passkey_sign_in(
+            &host,
+            request_id,
+            req.id,
+            req.response.client_data_json,
+            req.response.authenticator_data,
+            req.response.signature,
+        )
+        .await?;
+    Ok(Json(BuiltinAuthTokensResponse::from(tokens)))
 }
 
 #[cfg(test)]
@@ -633,7 +1010,12 @@ mod tests {
         json,
         Value as JsonValue,
     };
+    use sync_types::Timestamp;
 
+    use super::{
+        SerializedTs,
+        CONVEX_SESSION_TOKEN_HEADER,
+    };
     use crate::test_helpers::setup_backend_for_test;
 
     async fn http_format_tester(
@@ -786,4 +1168,64 @@ mod tests {
         )
         .await
     }
+
+    fn session_request(
+        uri: &str,
+        udf: &str,
+        session_token: Option<&str>,
+    ) -> anyhow::Result<Request<Body>> {
+        let body = Body::from(serde_json::to_vec(&json!({
+            "path": udf,
+            "args": {},
+        }))?);
+        let mut req = Request::builder()
+            .uri(uri)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Host", "localhost");
+        if let Some(session_token) = session_token {
+            req = req.header(CONVEX_SESSION_TOKEN_HEADER, session_token);
+        }
+        Ok(req.body(body)?)
+    }
+
+    fn parse_session_token(headers: &http::HeaderMap) -> anyhow::Result<Timestamp> {
+        let header = headers
+            .get(CONVEX_SESSION_TOKEN_HEADER)
+            .ok_or_else(|| anyhow::anyhow!("Missing session token"))?;
+        Timestamp::try_from(SerializedTs(header.to_str()?.to_string()))
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_http_session_token(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+
+        let req = session_request("/api/mutation", "values:intMutation", None)?;
+        let (_, headers): (JsonValue, _) = backend.expect_success_with_headers(req).await?;
+        let mutation_ts = parse_session_token(&headers)?;
+        let token = headers
+            .get(CONVEX_SESSION_TOKEN_HEADER)
+            .unwrap()
+            .to_str()?
+            .to_string();
+
+        // A query pinned by the token reads at or after the mutation.
+        let req = session_request("/api/query", "values:intQuery", Some(&token))?;
+        let (_, headers): (JsonValue, _) = backend.expect_success_with_headers(req).await?;
+        assert!(parse_session_token(&headers)? >= mutation_ts);
+
+        // Tokens ahead of the deployment are rejected rather than served stale.
+        let future_token = SerializedTs::from(Timestamp::MAX).0;
+        let req = session_request("/api/query", "values:intQuery", Some(&future_token))?;
+        backend
+            .expect_error(req, StatusCode::BAD_REQUEST, "InvalidSessionToken")
+            .await?;
+
+        let req = session_request("/api/query", "values:intQuery", Some("garbage"))?;
+        backend
+            .expect_error(req, StatusCode::BAD_REQUEST, "InvalidSessionToken")
+            .await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,141 @@
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentId,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+};
+use database::retention_backlog::{
+    RetentionProgress,
+    TableRetentionBacklog,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionProgressJson {
+    min_snapshot_ts: String,
+    checkpoint: String,
+    lag_secs: f64,
+}
+
+impl From<RetentionProgress> for RetentionProgressJson {
+    fn from(progress: RetentionProgress) -> Self {
+        Self {
+            min_snapshot_ts: u64::from(progress.min_snapshot_ts).to_string(),
+            checkpoint: u64::from(progress.checkpoint).to_string(),
+            lag_secs: progress.lag_secs,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRetentionBacklogJson {
+    component_id: Option<String>,
+    table_name: String,
+    pending_revisions: u64,
+    pending_tombstones: u64,
+    prioritized: bool,
+}
+
+impl From<TableRetentionBacklog> for TableRetentionBacklogJson {
+    fn from(backlog: TableRetentionBacklog) -> Self {
+        Self {
+            component_id: ComponentId::from(backlog.namespace).serialize_to_string(),
+            table_name: backlog.table_name.to_string(),
+            pending_revisions: backlog.pending_revisions,
+            pending_tombstones: backlog.pending_tombstones,
+            prioritized: backlog.prioritized,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionBacklogResponse {
+    index_retention: RetentionProgressJson,
+    document_retention: RetentionProgressJson,
+    tables: Vec<TableRetentionBacklogJson>,
+    /// False if the backlog was too large to count in full, so the counts are
+    /// lower bounds.
+    complete: bool,
+}
+
+/// How far retention is behind deleting old revisions and tombstones, per
+/// table.
+pub async fn retention_backlog(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let backlog = st.application.retention_backlog(identity).await?;
+    Ok(Json(RetentionBacklogResponse {
+        index_retention: backlog.index_retention.into(),
+        document_retention: backlog.document_retention.into(),
+        tables: backlog.tables.into_iter().map(Into::into).collect(),
+        complete: backlog.complete,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrioritizeRetentionArgs {
+    table_names: Vec<String>,
+    component_id: Option<String>,
+}
+
+/// Have document retention clean up the tables on its next pass, ahead of the
+/// rest of its backlog.
+#[debug_handler]
+pub async fn prioritize_retention(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(PrioritizeRetentionArgs {
+        table_names,
+        component_id,
+    }): Json<PrioritizeRetentionArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let table_names = table_names
+        .into_iter()
+        .map(|table_name| {
+            table_name.parse::<TableName>().map_err(|e| {
+                ErrorMetadata::bad_request(
+                    "InvalidTableName",
+                    format!("Invalid table name {table_name}: {e}"),
+                )
+                .into()
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let table_namespace = TableNamespace::from(ComponentId::deserialize_from_string(
+        component_id.as_deref(),
+    )?);
+    st.application
+        .prioritize_retention(identity, table_namespace, table_names)?;
+    Ok(StatusCode::OK)
+}
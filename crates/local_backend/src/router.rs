@@ -59,30 +59,88 @@ use crate::{
         cache_hit_percentage_top_k,
         failure_percentage_top_k,
         latency_percentiles,
+        prometheus_metrics,
         scheduled_job_lag,
         table_rate,
         udf_rate,
     },
+    connection_pool::{
+        connection_pool_stats,
+        set_connection_pool_limit,
+    },
     dashboard::{
+        audit_component,
+        backfill_schema_defaults,
+        bundle_size_report,
+        clone_table,
         delete_component,
         delete_tables,
+        get_component_limits,
         get_indexes,
+        get_outbound_network_policy,
         get_source_code,
+        get_traffic_split,
+        list_component_versions,
+        promote_traffic_split,
+        recompute_table_summaries,
+        rollback_component,
+        rollback_traffic_split,
+        run_as_user,
         run_test_function,
+        schema_types,
+        seed,
+        set_component_limits,
+        set_outbound_network_policy,
+        set_traffic_split,
         shapes2,
     },
+    data_migrations::{
+        list_data_migrations,
+        register_data_migration,
+        retry_data_migration,
+        run_data_migration,
+    },
     deploy_config::{
         get_config,
         get_config_hashes,
         push_config,
     },
     deploy_config2,
+    documents::{
+        document_batch,
+        document_history,
+        list_http_table_access,
+        query_documents,
+        set_http_table_access,
+    },
     environment_variables::update_environment_variables,
+    function_kill_switches::{
+        disable_function,
+        enable_function,
+        list_function_kill_switches,
+    },
+    health::{
+        healthz,
+        readyz,
+    },
     http_actions::http_action_handler,
+    ip_access::{
+        admin_ip_access_middleware,
+        client_ip_access_middleware,
+        get_ip_access_policy,
+        http_action_ip_access_middleware,
+        set_ip_access_policy,
+    },
     logs::{
+        function_execution_records,
         stream_function_logs,
         stream_udf_execution,
     },
+    maintenance_windows::{
+        cancel_maintenance_window,
+        list_maintenance_windows,
+        schedule_maintenance_window,
+    },
     node_action_callbacks::{
         action_callbacks_middleware,
         cancel_developer_job,
@@ -90,15 +148,38 @@ use crate::{
         internal_action_post,
         internal_mutation_post,
         internal_query_post,
+        invalidate_http_action_cache,
+        mint_service_token,
+        record_network_policy_violation,
         schedule_job,
         storage_delete,
         storage_generate_upload_url,
+        storage_get_image_url,
         storage_get_metadata,
+        storage_get_signed_url,
         storage_get_url,
+        storage_revoke_signed_url,
         vector_search,
+        verify_service_token,
+    },
+    preview_deployments::{
+        create_preview_deployment,
+        delete_preview_deployment,
+        list_preview_deployments,
     },
     public_api::{
         public_action_post,
+        public_anonymous_token_post,
+        public_builtin_auth_magic_link_post,
+        public_builtin_auth_passkey_register_options_post,
+        public_builtin_auth_passkey_register_post,
+        public_builtin_auth_passkey_sign_in_options_post,
+        public_builtin_auth_passkey_sign_in_post,
+        public_builtin_auth_refresh_post,
+        public_builtin_auth_sign_in_post,
+        public_builtin_auth_sign_out_post,
+        public_builtin_auth_sign_up_post,
+        public_builtin_auth_verify_magic_link_post,
         public_function_post,
         public_function_post_with_path,
         public_get_query_ts,
@@ -108,31 +189,58 @@ use crate::{
         public_query_get,
         public_query_post,
     },
+    retention::{
+        prioritize_retention,
+        retention_backlog,
+    },
     scheduling::{
         cancel_all_jobs,
         cancel_job,
+        scheduler_backlog,
     },
     schema::{
         prepare_schema,
         schema_state,
+        schema_validation_report,
+        validate_schema,
+    },
+    slow_queries::{
+        index_recommendations,
+        list_slow_queries,
     },
     snapshot_export::{
         get_zip_export,
+        request_parquet_export,
         request_zip_export,
     },
     snapshot_import::{
         cancel_import,
         import,
+        import_check_clone,
         import_finish_upload,
         import_start_upload,
         import_upload_part,
+        import_validate,
+        pause_import,
         perform_import,
+        resume_import,
     },
     storage::{
         storage_get,
+        storage_get_image,
+        storage_get_signed,
         storage_upload,
+        ENCRYPTION_ALGORITHM_HEADER,
+        ENCRYPTION_IV_HEADER,
+        ENCRYPTION_KEY_ID_HEADER,
     },
     subs::sync,
+    surrogate_keys::purge_surrogate_keys,
+    usage::{
+        current_usage,
+        list_usage_exports,
+        table_stats,
+    },
     LocalAppState,
     RouterState,
 };
@@ -158,6 +266,21 @@ pub fn router(st: LocalAppState) -> Router {
         // Scheduled jobs routes
         .route("/cancel_all_jobs", post(cancel_all_jobs))
         .route("/cancel_job", post(cancel_job))
+        .route("/scheduler_backlog", get(scheduler_backlog))
+        // Data migration routes
+        .route("/data_migrations", get(list_data_migrations))
+        .route("/register_data_migration", post(register_data_migration))
+        .route("/retry_data_migration", post(retry_data_migration))
+        .route("/run_data_migration", post(run_data_migration))
+        // Retention routes
+        .route("/retention_backlog", get(retention_backlog))
+        .route("/prioritize_retention", post(prioritize_retention))
+        // Persistence connection pool routes
+        .route("/connection_pool_stats", get(connection_pool_stats))
+        .route("/set_connection_pool_limit", post(set_connection_pool_limit))
+        // Validate-only schema routes
+        .route("/validate_schema", post(validate_schema))
+        .route("/schema_validation_report", get(schema_validation_report))
         // Environment variable routes
         .route("/update_environment_variables", post(update_environment_variables))
         // Administrative routes for the dashboard
@@ -195,36 +318,56 @@ pub fn router(st: LocalAppState) -> Router {
 
     let snapshot_export_routes = Router::new()
         .route("/request/zip", post(request_zip_export))
+        .route("/request/parquet", post(request_parquet_export))
         .route("/zip/:id", get(get_zip_export));
 
     let api_routes = Router::new()
         .merge(cli_routes)
         .merge(dashboard_routes)
+        .nest("/export", snapshot_export_routes)
+        .layer(axum::middleware::from_fn_with_state(
+            st.clone(),
+            admin_ip_access_middleware,
+        ))
+        // Action callbacks come from the function runners, not from clients,
+        // so the IP access policy doesn't apply to them.
         .nest(
             "/actions",
             action_callback_routes().layer(axum::middleware::map_request_with_state(
                 st.clone(),
                 add_extension::<LocalAppState, _>,
             )),
-        )
-        .nest("/export", snapshot_export_routes);
+        );
 
+    let router_state = RouterState {
+        api: Arc::new(st.application.clone()),
+        runtime: st.application.runtime().clone(),
+        shutdown_rx: st.zombify_rx.clone(),
+        sync_sockets: st.sync_sockets.clone(),
+    };
     // Endpoints migrated to use the RouterState trait instead of application.
     let migrated_api_routes = Router::new()
         .merge(browser_routes)
         .merge(public_api_routes())
-        .nest("/storage", storage_api_routes());
+        .nest("/storage", storage_api_routes())
+        .layer(axum::middleware::from_fn_with_state(
+            router_state.clone(),
+            client_ip_access_middleware,
+        ));
     let migrated = Router::new()
         .nest("/api", migrated_api_routes)
         .layer(cors())
         // Order matters. Layers only apply to routes above them.
         // Notably, any layers added here won't apply to common routes
         // added inside `serve_http`
-        .nest("/http/", http_action_routes())
-        .with_state(RouterState {
-            api: Arc::new(st.application.clone()),
-            runtime: st.application.runtime().clone(),
-        });
+        .nest(
+            "/http/",
+            http_action_routes().layer(axum::middleware::from_fn_with_state(
+                router_state.clone(),
+                http_action_ip_access_middleware,
+            )),
+        )
+        .with_state(router_state);
 
     let instance_name = st.instance_name.clone();
     let version = SERVER_VERSION_STR.to_string();
@@ -234,6 +377,8 @@ pub fn router(st: LocalAppState) -> Router {
         // /instance_name is used by the CLI and dashboard to check connectivity!
         .route("/instance_name", get(|| async move { instance_name }))
         .route("/instance_version", get(|| async move { version }))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
         .layer(cors())
         .with_state(st)
         .merge(migrated)
@@ -251,6 +396,35 @@ pub fn public_api_routes() -> Router<RouterState> {
         .route("/action", post(public_action_post))
         .route("/function", post(public_function_post))
         .route("/run/*rest", post(public_function_post_with_path))
+        .route("/anonymous_token", post(public_anonymous_token_post))
+        .route("/auth/sign_up", post(public_builtin_auth_sign_up_post))
+        .route("/auth/sign_in", post(public_builtin_auth_sign_in_post))
+        .route(
+            "/auth/magic_link",
+            post(public_builtin_auth_magic_link_post),
+        )
+        .route(
+            "/auth/magic_link/verify",
+            post(public_builtin_auth_verify_magic_link_post),
+        )
+        .route("/auth/refresh", post(public_builtin_auth_refresh_post))
+        .route("/auth/sign_out", post(public_builtin_auth_sign_out_post))
+        .route(
+            "/auth/passkey/register/options",
+            post(public_builtin_auth_passkey_register_options_post),
+        )
+        .route(
+            "/auth/passkey/register",
+            post(public_builtin_auth_passkey_register_post),
+        )
+        .route(
+            "/auth/passkey/sign_in/options",
+            post(public_builtin_auth_passkey_sign_in_options_post),
+        )
+        .route(
+            "/auth/passkey/sign_in",
+            post(public_builtin_auth_passkey_sign_in_post),
+        )
         .layer(DefaultBodyLimit::max(*MAX_BACKEND_PUBLIC_API_REQUEST_SIZE))
 }
 
@@ -258,6 +432,8 @@ pub fn storage_api_routes() -> Router<RouterState> {
     Router::new()
         .route("/upload", post(storage_upload))
         .route("/:storage_id", get(storage_get))
+        .route("/signed/:token", get(storage_get_signed))
+        .route("/image/:token", get(storage_get_image))
 }
 
 // IMPORTANT NOTE: Those routes are proxied by Usher. Any changes to the router,
@@ -276,10 +452,20 @@ where
         .route("/vector_search", post(vector_search))
         .route("/cancel_job", post(cancel_developer_job))
         .route("/create_function_handle", post(create_function_handle))
+        .route(
+            "/record_network_policy_violation",
+            post(record_network_policy_violation),
+        )
+        .route("/mint_service_token", post(mint_service_token))
+        .route("/verify_service_token", post(verify_service_token))
+        .route("/invalidate_http_action_cache", post(invalidate_http_action_cache))
         // file storage endpoints
         .route("/storage_generate_upload_url", post(storage_generate_upload_url))
         .route("/storage_get_url", post(storage_get_url))
         .route("/storage_get_metadata", post(storage_get_metadata))
+        .route("/storage_get_signed_url", post(storage_get_signed_url))
+        .route("/storage_get_image_url", post(storage_get_image_url))
+        .route("/storage_revoke_signed_url", post(storage_revoke_signed_url))
         .route("/storage_delete", post(storage_delete))
         // All routes above this line get the increased limit
         .layer(DefaultBodyLimit::max(*MAX_BACKEND_RPC_REQUEST_SIZE))
@@ -296,7 +482,11 @@ where
         .route("/import/start_upload", post(import_start_upload))
         .route("/import/upload_part", post(import_upload_part))
         .route("/import/finish_upload", post(import_finish_upload))
+        .route("/import/validate", post(import_validate))
+        .route("/import/check_clone", post(import_check_clone))
         .route("/perform_import", post(perform_import))
+        .route("/pause_import", post(pause_import))
+        .route("/resume_import", post(resume_import))
         .route("/cancel_import", post(cancel_import))
 }
 
@@ -325,6 +515,16 @@ where
         .route("/table_rate", get(table_rate))
         .route("/latency_percentiles", get(latency_percentiles))
         .route("/scheduled_job_lag", get(scheduled_job_lag))
+        .route("/prometheus", get(prometheus_metrics))
+        .route("/usage", get(current_usage))
+        .route("/usage_exports", get(list_usage_exports))
+        .route("/table_stats", get(table_stats))
+        .route("/slow_queries", get(list_slow_queries))
+        .route("/index_recommendations", get(index_recommendations))
+        .route(
+            "/function_execution_records",
+            get(function_execution_records),
+        )
 }
 
 // Routes with the same handlers for the local backend + closed source backend
@@ -335,10 +535,57 @@ where
 {
     Router::new()
         .route("/shapes2", get(shapes2))
+        .route("/recompute_table_summaries", post(recompute_table_summaries))
         .route("/get_indexes", get(get_indexes))
         .route("/delete_tables", post(delete_tables))
+        .route("/clone_table", post(clone_table))
+        .route("/backfill_schema_defaults", post(backfill_schema_defaults))
+        .route("/seed", post(seed))
+        .route("/document_batch", post(document_batch))
+        .route("/query_documents", post(query_documents))
+        .route("/document_history", get(document_history))
+        .route("/list_http_table_access", get(list_http_table_access))
+        .route("/set_http_table_access", post(set_http_table_access))
+        .route("/purge_surrogate_keys", post(purge_surrogate_keys))
+        .route(
+            "/schedule_maintenance_window",
+            post(schedule_maintenance_window),
+        )
+        .route("/list_maintenance_windows", get(list_maintenance_windows))
+        .route("/cancel_maintenance_window", post(cancel_maintenance_window))
+        .route("/create_preview_deployment", post(create_preview_deployment))
+        .route("/list_preview_deployments", get(list_preview_deployments))
+        .route("/delete_preview_deployment", post(delete_preview_deployment))
+        .route(
+            "/list_function_kill_switches",
+            get(list_function_kill_switches),
+        )
+        .route("/disable_function", post(disable_function))
+        .route("/enable_function", post(enable_function))
         .route("/delete_component", post(delete_component))
+        .route("/list_component_versions", get(list_component_versions))
+        .route("/rollback_component", post(rollback_component))
+        .route("/bundle_size_report", get(bundle_size_report))
+        .route("/audit_component", get(audit_component))
+        .route("/schema_types", get(schema_types))
+        .route("/get_component_limits", get(get_component_limits))
+        .route("/set_component_limits", post(set_component_limits))
+        .route(
+            "/get_outbound_network_policy",
+            get(get_outbound_network_policy),
+        )
+        .route(
+            "/set_outbound_network_policy",
+            post(set_outbound_network_policy),
+        )
+        .route("/get_ip_access_policy", get(get_ip_access_policy))
+        .route("/set_ip_access_policy", post(set_ip_access_policy))
+        .route("/get_traffic_split", get(get_traffic_split))
+        .route("/set_traffic_split", post(set_traffic_split))
+        .route("/promote_traffic_split", post(promote_traffic_split))
+        .route("/rollback_traffic_split", post(rollback_traffic_split))
         .route("/get_source_code", get(get_source_code))
+        .route("/run_as_user", post(run_as_user))
         // Metrics routes
         .nest("/app_metrics", app_metrics_routes())
 }
@@ -355,6 +602,15 @@ pub fn cors() -> CorsLayer {
            CONVEX_CLIENT_HEADER,
            REFERER,
            USER_AGENT,
+           ENCRYPTION_KEY_ID_HEADER,
+           ENCRYPTION_IV_HEADER,
+           ENCRYPTION_ALGORITHM_HEADER,
+        ])
+        // Let browsers read the encryption metadata returned with stored files.
+        .expose_headers(vec![
+            ENCRYPTION_KEY_ID_HEADER,
+            ENCRYPTION_IV_HEADER,
+            ENCRYPTION_ALGORITHM_HEADER,
         ])
         .allow_credentials(true)
         .allow_methods(vec![
@@ -14,6 +14,8 @@ use common::{
         extract::Json,
         HttpResponseError,
     },
+    knobs::SCHEDULER_BACKLOG_THROUGHPUT_WINDOW,
+    runtime::Runtime,
 };
 use errors::ErrorMetadata;
 use http::StatusCode;
@@ -28,7 +30,10 @@ use serde::{
 use value::TableNamespace;
 
 use crate::{
-    admin::must_be_admin_member_with_write_access,
+    admin::{
+        must_be_admin,
+        must_be_admin_member_with_write_access,
+    },
     authentication::ExtractIdentity,
     parse::parse_document_id,
     LocalAppState,
@@ -116,3 +121,64 @@ pub async fn cancel_job(
 
     Ok(StatusCode::OK)
 }
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionBacklogJson {
+    component_path: Option<String>,
+    udf_path: String,
+    due_jobs: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulerBacklogResponse {
+    due_jobs: usize,
+    in_progress_jobs: usize,
+    oldest_due_ts: Option<String>,
+    /// How long the oldest due job has been waiting to run.
+    lag_secs: f64,
+    /// Due jobs per function, most backlogged first.
+    functions: Vec<FunctionBacklogJson>,
+    completed_jobs: usize,
+    completed_per_sec: f64,
+    window_secs: f64,
+    /// False if the backlog was too large to count in full, so the counts are
+    /// lower bounds.
+    complete: bool,
+}
+
+/// How far behind the scheduler is running due jobs, and how quickly it's
+/// finishing them.
+pub async fn scheduler_backlog(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let backlog = st.application.scheduler_backlog(&identity).await?;
+    let now = st.application.runtime().generate_timestamp()?;
+    let mut functions: Vec<_> = backlog
+        .due_jobs_by_function
+        .into_iter()
+        .map(|(path, due_jobs)| FunctionBacklogJson {
+            component_path: path.component.serialize(),
+            udf_path: path.udf_path.to_string(),
+            due_jobs,
+        })
+        .collect();
+    functions.sort_by(|a, b| b.due_jobs.cmp(&a.due_jobs));
+    let window_secs = SCHEDULER_BACKLOG_THROUGHPUT_WINDOW.as_secs_f64();
+    Ok(Json(SchedulerBacklogResponse {
+        due_jobs: backlog.due_jobs,
+        in_progress_jobs: backlog.in_progress_jobs,
+        oldest_due_ts: backlog.oldest_due_ts.map(|ts| u64::from(ts).to_string()),
+        lag_secs: backlog
+            .oldest_due_ts
+            .map_or(0.0, |ts| now.secs_since_f64(ts).max(0.0)),
+        functions,
+        completed_jobs: backlog.completed_jobs,
+        completed_per_sec: backlog.completed_jobs as f64 / window_secs,
+        window_secs,
+        complete: backlog.complete,
+    }))
+}
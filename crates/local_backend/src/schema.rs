@@ -30,13 +30,16 @@ use common::{
             SchemaState,
         },
     },
+    components::ComponentId,
     http::{
         extract::{
             Json,
             Path,
+            Query,
         },
         HttpResponseError,
     },
+    schemas::DatabaseSchema,
 };
 use database::{
     IndexModel,
@@ -44,6 +47,11 @@ use database::{
     SchemaModel,
 };
 use errors::ErrorMetadata;
+use http::StatusCode;
+use model::schema_validation_reports::types::{
+    SchemaValidationReport,
+    SchemaValidationReportState,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -63,6 +71,8 @@ use crate::{
     admin::{
         must_be_admin,
         must_be_admin_from_key_with_write_access,
+        must_be_admin_member,
+        must_be_admin_member_with_write_access,
     },
     authentication::ExtractIdentity,
     LocalAppState,
@@ -384,3 +394,114 @@ pub async fn schema_state(
         schema_state: state.into(),
     }))
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateSchemaArgs {
+    /// JSON serialized `DatabaseSchema`, in the format stored in `_schemas`.
+    schema: JsonValue,
+    component_id: Option<String>,
+}
+
+/// Check the existing documents against a schema without enforcing it. The
+/// schema worker produces the report in the background.
+#[debug_handler]
+pub async fn validate_schema(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ValidateSchemaArgs {
+        schema,
+        component_id,
+    }): Json<ValidateSchemaArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let schema = DatabaseSchema::try_from(schema).context(ErrorMetadata::bad_request(
+        "InvalidSchema",
+        "Schema to validate is not a valid schema",
+    ))?;
+    st.application
+        .request_schema_validation_report(&identity, component_id, schema)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaValidationReportArgs {
+    component_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(tag = "state")]
+enum SchemaValidationReportJson {
+    None,
+    ValidateOnly,
+    #[serde(rename_all = "camelCase")]
+    Completed {
+        num_documents_checked: u64,
+        violations: Vec<SchemaViolationJson>,
+        truncated: bool,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaViolationJson {
+    table_name: String,
+    document_id: String,
+    field_path: Option<String>,
+    reason: String,
+}
+
+impl From<Option<SchemaValidationReport>> for SchemaValidationReportJson {
+    fn from(report: Option<SchemaValidationReport>) -> Self {
+        let Some(report) = report else {
+            return SchemaValidationReportJson::None;
+        };
+        match report.state {
+            SchemaValidationReportState::ValidateOnly => SchemaValidationReportJson::ValidateOnly,
+            SchemaValidationReportState::Completed {
+                num_documents_checked,
+                violations,
+                truncated,
+                ..
+            } => SchemaValidationReportJson::Completed {
+                num_documents_checked,
+                violations: violations
+                    .into_iter()
+                    .map(|violation| SchemaViolationJson {
+                        table_name: violation.table_name.to_string(),
+                        document_id: violation.document_id.encode(),
+                        field_path: violation.field_path,
+                        reason: violation.reason,
+                    })
+                    .collect(),
+                truncated,
+            },
+            SchemaValidationReportState::Failed { error } => {
+                SchemaValidationReportJson::Failed { error }
+            },
+        }
+    }
+}
+
+/// Gets the latest validate-only report for a component.
+#[debug_handler]
+pub async fn schema_validation_report(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(SchemaValidationReportArgs { component_id }): Query<SchemaValidationReportArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let component_id = ComponentId::deserialize_from_string(component_id.as_deref())?;
+    let report = st
+        .application
+        .get_schema_validation_report(&identity, component_id)
+        .await?;
+    Ok(Json(SchemaValidationReportJson::from(report)))
+}
@@ -0,0 +1,174 @@
+use anyhow::Context;
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    document::CreationTime,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+    query::Order,
+};
+use errors::ErrorMetadata;
+use model::slow_queries::types::IndexRecommendation;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+/// Number of slow queries returned by `list_slow_queries` if the request
+/// doesn't set a limit.
+const DEFAULT_SLOW_QUERIES_LIMIT: usize = 100;
+const MAX_SLOW_QUERIES_LIMIT: usize = 1000;
+
+/// Number of recent slow queries index recommendations are computed from.
+const INDEX_RECOMMENDATION_MAX_QUERIES: usize = 1000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListSlowQueriesQueryArgs {
+    cursor: Option<f64>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQueryJson {
+    /// When the query was recorded, in milliseconds since the epoch.
+    timestamp: Option<f64>,
+    component_path: Option<String>,
+    table_name: String,
+    index_name: String,
+    range: String,
+    filter: Option<String>,
+    order: &'static str,
+    documents_scanned: u64,
+    bytes_scanned: u64,
+    documents_returned: u64,
+    execution_time_ms: u64,
+    suggested_index: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListSlowQueriesResponse {
+    queries: Vec<SlowQueryJson>,
+    /// Pass as `cursor` to fetch the next page. `null` once there are no more
+    /// slow queries.
+    cursor: Option<f64>,
+}
+
+/// List queries that exceeded the slow query thresholds, newest first.
+pub async fn list_slow_queries(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(query_args): Query<ListSlowQueriesQueryArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let limit = query_args.limit.unwrap_or(DEFAULT_SLOW_QUERIES_LIMIT);
+    if limit > MAX_SLOW_QUERIES_LIMIT {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidLimit",
+            format!("Limit {limit} is greater than the maximum of {MAX_SLOW_QUERIES_LIMIT}"),
+        ))
+        .into());
+    }
+    let cursor = query_args
+        .cursor
+        .map(|cursor| {
+            CreationTime::try_from(cursor).context(ErrorMetadata::bad_request(
+                "InvalidCursor",
+                format!("Invalid cursor {cursor}"),
+            ))
+        })
+        .transpose()?;
+    let page = st
+        .application
+        .list_slow_queries(identity, cursor, limit)
+        .await?;
+    let queries = page
+        .queries
+        .into_iter()
+        .map(|doc| {
+            let timestamp = doc.creation_time().map(f64::from);
+            let query = doc.into_value();
+            SlowQueryJson {
+                timestamp,
+                component_path: query.component_path.serialize(),
+                table_name: query.table_name,
+                index_name: query.index_name,
+                range: query.range,
+                filter: query.filter,
+                order: match query.order {
+                    Order::Asc => "asc",
+                    Order::Desc => "desc",
+                },
+                documents_scanned: query.documents_scanned,
+                bytes_scanned: query.bytes_scanned,
+                documents_returned: query.documents_returned,
+                execution_time_ms: query.execution_time_ms,
+                suggested_index: query.suggested_index,
+            }
+        })
+        .collect();
+    Ok(Json(ListSlowQueriesResponse {
+        queries,
+        cursor: page.cursor.map(f64::from),
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexRecommendationJson {
+    component_path: Option<String>,
+    table_name: String,
+    fields: Vec<String>,
+    num_queries: u64,
+    documents_scanned: u64,
+    documents_returned: u64,
+    query_shapes: Vec<String>,
+}
+
+impl From<IndexRecommendation> for IndexRecommendationJson {
+    fn from(recommendation: IndexRecommendation) -> Self {
+        Self {
+            component_path: recommendation.component_path.serialize(),
+            table_name: recommendation.table_name,
+            fields: recommendation.fields,
+            num_queries: recommendation.num_queries,
+            documents_scanned: recommendation.documents_scanned,
+            documents_returned: recommendation.documents_returned,
+            query_shapes: recommendation.query_shapes,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexRecommendationsResponse {
+    recommendations: Vec<IndexRecommendationJson>,
+}
+
+/// Indexes that would have served recent slow queries, the ones that would
+/// have saved the most documents from being scanned first.
+pub async fn index_recommendations(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let recommendations = st
+        .application
+        .index_recommendations(identity, INDEX_RECOMMENDATION_MAX_QUERIES)
+        .await?;
+    Ok(Json(IndexRecommendationsResponse {
+        recommendations: recommendations.into_iter().map(Into::into).collect(),
+    }))
+}
@@ -77,6 +77,34 @@ pub async fn request_zip_export(
     Ok(StatusCode::OK)
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestParquetExport {
+    pub component: Option<String>,
+}
+
+/// Requests a snapshot export of the deployment's tables as Parquet files,
+/// downloaded like zip exports.
+#[fastrace::trace]
+pub async fn request_parquet_export(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(RequestParquetExport { component }): Query<RequestParquetExport>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let component = ComponentId::deserialize_from_string(component.as_deref())?;
+    st.application
+        .request_export(
+            identity,
+            ExportFormat::Parquet,
+            component,
+            ExportRequestor::SnapshotExport,
+            None,
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
 #[derive(Deserialize)]
 pub struct ZipExportRequest {
     // The ID of the snapshot
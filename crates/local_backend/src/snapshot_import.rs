@@ -1,9 +1,13 @@
-use std::str::FromStr;
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+};
 
 use anyhow::Context;
 use application::snapshot_import::{
     self,
     do_import,
+    validate_import,
 };
 use axum::{
     body::Body,
@@ -27,6 +31,9 @@ use futures::{
     TryStreamExt,
 };
 use model::snapshot_imports::types::{
+    FieldConversion,
+    FieldMappingRule,
+    ImportFieldMapping,
     ImportFormat,
     ImportMode,
 };
@@ -40,7 +47,9 @@ use storage::{
 };
 use value::{
     id_v6::DeveloperDocumentId,
+    FieldName,
     TableName,
+    TableNumber,
 };
 
 use crate::{
@@ -57,6 +66,28 @@ pub struct ImportQueryArgs {
     format: ImportFormatArg,
     #[serde(default)]
     mode: ImportMode,
+    /// Field matching imported documents to existing ones in `upsert` mode.
+    upsert_key: Option<String>,
+    /// JSON-encoded `FieldMappingArg` for Firestore and MongoDB imports.
+    field_mapping: Option<String>,
+}
+
+/// How to map the top-level fields of Firestore and MongoDB documents, e.g.
+/// `{"idField": "legacyId", "fields": {"createdAt": {"convert": "string"}}}`.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct FieldMappingArg {
+    id_field: Option<String>,
+    #[serde(default)]
+    fields: BTreeMap<String, FieldMappingRuleArg>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FieldMappingRuleArg {
+    rename: Option<String>,
+    /// One of "skip", "string", "float64" or "int64".
+    convert: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -82,6 +113,9 @@ enum ImportFormatArg {
     JsonLines,
     JsonArray,
     Zip,
+    Firestore,
+    MongoJson,
+    MongoBson,
 }
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -89,9 +123,57 @@ struct ImportResponse {
     num_written: u64,
 }
 
+fn parse_field_mapping(field_mapping: Option<String>) -> anyhow::Result<ImportFieldMapping> {
+    let invalid_mapping =
+        |message: String| ErrorMetadata::bad_request("InvalidFieldMapping", message);
+    let FieldMappingArg { id_field, fields } = match field_mapping {
+        Some(field_mapping) => serde_json::from_str(&field_mapping)
+            .map_err(|e| invalid_mapping(format!("Invalid field mapping: {e}")))?,
+        None => FieldMappingArg::default(),
+    };
+    let parse_field_name = |field: String| {
+        field
+            .parse::<FieldName>()
+            .map_err(|e| invalid_mapping(format!("Invalid field name {field:?}: {e}")))
+    };
+    let id_field = id_field.map(parse_field_name).transpose()?;
+    let fields = fields
+        .into_iter()
+        .map(|(field, FieldMappingRuleArg { rename, convert })| {
+            let rule = FieldMappingRule {
+                rename: rename.map(parse_field_name).transpose()?,
+                convert: convert
+                    .map(|convert| {
+                        convert.parse::<FieldConversion>().map_err(|_| {
+                            invalid_mapping(format!("Invalid conversion {convert:?} for {field}"))
+                        })
+                    })
+                    .transpose()?,
+            };
+            anyhow::Ok((field, rule))
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(ImportFieldMapping { id_field, fields })
+}
+
+fn parse_upsert_key_arg(upsert_key: Option<String>) -> anyhow::Result<Option<FieldName>> {
+    let upsert_key = upsert_key
+        .map(|upsert_key| {
+            FieldName::from_str(&upsert_key).map_err(|e| {
+                ErrorMetadata::bad_request(
+                    "InvalidUpsertKey",
+                    format!("invalid upsert key {upsert_key}: {e}"),
+                )
+            })
+        })
+        .transpose()?;
+    Ok(upsert_key)
+}
+
 fn parse_format_arg(
     table_name: Option<String>,
     format: ImportFormatArg,
+    field_mapping: Option<String>,
 ) -> anyhow::Result<ImportFormat> {
     let table_name = table_name
         .map(|table_name| {
@@ -122,6 +204,27 @@ fn parse_format_arg(
         ImportFormatArg::JsonLines => ImportFormat::JsonLines(table_name.context(
             ErrorMetadata::bad_request("InvalidName", "JSONL import requires table name"),
         )?),
+        ImportFormatArg::Firestore => ImportFormat::Firestore(
+            table_name.context(ErrorMetadata::bad_request(
+                "InvalidName",
+                "Firestore import requires table name",
+            ))?,
+            parse_field_mapping(field_mapping)?,
+        ),
+        ImportFormatArg::MongoJson => ImportFormat::MongoExtendedJson(
+            table_name.context(ErrorMetadata::bad_request(
+                "InvalidName",
+                "MongoDB import requires table name",
+            ))?,
+            parse_field_mapping(field_mapping)?,
+        ),
+        ImportFormatArg::MongoBson => ImportFormat::MongoBson(
+            table_name.context(ErrorMetadata::bad_request(
+                "InvalidName",
+                "MongoDB import requires table name",
+            ))?,
+            parse_field_mapping(field_mapping)?,
+        ),
     };
     Ok(inner_format)
 }
@@ -134,11 +237,14 @@ pub async fn import(
         component_path,
         format,
         mode,
+        upsert_key,
+        field_mapping,
     }): Query<ImportQueryArgs>,
     stream: Body,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     must_be_admin_with_write_access(&identity)?;
-    let format = parse_format_arg(table_name, format)?;
+    let format = parse_format_arg(table_name, format, field_mapping)?;
+    let upsert_key = parse_upsert_key_arg(upsert_key)?;
     let component_path = ComponentPath::deserialize(component_path.as_deref())?;
     let body_stream = stream
         .into_data_stream()
@@ -149,6 +255,7 @@ pub async fn import(
         identity,
         format,
         mode,
+        upsert_key,
         component_path,
         body_stream,
     )
@@ -156,6 +263,57 @@ pub async fn import(
     Ok(Json(ImportResponse { num_written }))
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateImportResponse {
+    num_documents: u64,
+    num_invalid_documents: u64,
+    /// Source field name -> source type -> number of documents.
+    field_types: BTreeMap<String, BTreeMap<String, u64>>,
+    errors: Vec<String>,
+    complete: bool,
+}
+
+/// Dry run of a Firestore or MongoDB import: reports the types found in each
+/// field and the documents that wouldn't import, without writing anything.
+pub async fn import_validate(
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ImportQueryArgs {
+        table_name,
+        component_path: _,
+        format,
+        mode: _,
+        upsert_key: _,
+        field_mapping,
+    }): Query<ImportQueryArgs>,
+    stream: Body,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let format = parse_format_arg(table_name, format, field_mapping)?;
+    let body_stream = stream
+        .into_data_stream()
+        .map_err(anyhow::Error::from)
+        .boxed();
+    let report = validate_import(identity, format, body_stream).await?;
+    Ok(Json(ValidateImportResponse {
+        num_documents: report.num_documents,
+        num_invalid_documents: report.num_invalid_documents,
+        field_types: report
+            .field_types
+            .into_iter()
+            .map(|(field, types)| {
+                let types = types
+                    .into_iter()
+                    .map(|(type_name, count)| (type_name.to_string(), count))
+                    .collect();
+                (field, types)
+            })
+            .collect(),
+        errors: report.errors,
+        complete: report.complete,
+    }))
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StartUploadResponse {
@@ -224,13 +382,16 @@ pub async fn import_finish_upload(
                 component_path,
                 format,
                 mode,
+                upsert_key,
+                field_mapping,
             },
         upload_token,
         part_tokens,
     }): Json<ImportFinishUploadArgs>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     must_be_admin_with_write_access(&identity)?;
-    let format = parse_format_arg(table_name, format)?;
+    let format = parse_format_arg(table_name, format, field_mapping)?;
+    let upsert_key = parse_upsert_key_arg(upsert_key)?;
     let component_path = ComponentPath::deserialize(component_path.as_deref())?;
     let import_id = st
         .application
@@ -238,6 +399,7 @@ pub async fn import_finish_upload(
             identity,
             format,
             mode,
+            upsert_key,
             component_path,
             ClientDrivenUploadToken(upload_token),
             part_tokens
@@ -271,6 +433,48 @@ pub async fn perform_import(
     Ok(())
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PauseImportArgs {
+    pub import_id: String,
+}
+
+/// Pauses an in-progress import. It continues from where it stopped when
+/// resumed.
+pub async fn pause_import(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(PauseImportArgs { import_id }): Json<PauseImportArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let import_id = DeveloperDocumentId::decode(&import_id).context(ErrorMetadata::bad_request(
+        "InvalidImport",
+        format!("invalid import id {import_id}"),
+    ))?;
+    snapshot_import::pause_import(&st.application, identity, import_id).await?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeImportArgs {
+    pub import_id: String,
+}
+
+pub async fn resume_import(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ResumeImportArgs { import_id }): Json<ResumeImportArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let import_id = DeveloperDocumentId::decode(&import_id).context(ErrorMetadata::bad_request(
+        "InvalidImport",
+        format!("invalid import id {import_id}"),
+    ))?;
+    snapshot_import::resume_import(&st.application, identity, import_id).await?;
+    Ok(())
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CancelImportArgs {
@@ -289,3 +493,83 @@ pub async fn cancel_import(
     snapshot_import::cancel_import(&st.application, identity, import_id).await?;
     Ok(())
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckCloneArgs {
+    /// The `_tables` entries of the deployment being cloned.
+    tables: Vec<CloneTableArg>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CloneTableArg {
+    component_path: Option<String>,
+    name: String,
+    id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckCloneResponse {
+    conflicts: Vec<CloneTableConflictResponse>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CloneTableConflictResponse {
+    #[serde(flatten)]
+    table: CloneTableArg,
+    existing_table: String,
+}
+
+/// Checks whether another deployment's tables can be cloned into this one
+/// with an import in `clone` mode, keeping all of their document IDs.
+pub async fn import_check_clone(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CheckCloneArgs { tables }): Json<CheckCloneArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut source_tables: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for CloneTableArg {
+        component_path,
+        name,
+        id,
+    } in tables
+    {
+        let component_path = ComponentPath::deserialize(component_path.as_deref())?;
+        let table_name = TableName::from_str(&name).map_err(|e| {
+            ErrorMetadata::bad_request(
+                "ImportInvalidName",
+                format!("invalid table name {name}: {e}"),
+            )
+        })?;
+        let table_number = TableNumber::try_from(id).map_err(|e| {
+            ErrorMetadata::bad_request(
+                "InvalidTableNumber",
+                format!("invalid table number {id}: {e}"),
+            )
+        })?;
+        source_tables
+            .entry(component_path)
+            .or_default()
+            .push((table_name, table_number));
+    }
+    let conflicts =
+        snapshot_import::check_clone_table_numbers(&st.application, identity, source_tables)
+            .await?;
+    Ok(Json(CheckCloneResponse {
+        conflicts: conflicts
+            .into_iter()
+            .map(|conflict| CloneTableConflictResponse {
+                table: CloneTableArg {
+                    component_path: Some(String::from(conflict.component_path)),
+                    name: conflict.table_name.to_string(),
+                    id: conflict.table_number.into(),
+                },
+                existing_table: conflict.existing_table.to_string(),
+            })
+            .collect(),
+    }))
+}
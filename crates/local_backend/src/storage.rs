@@ -1,9 +1,16 @@
 use std::{
     ops::Bound,
-    time::Duration,
+    time::{
+        Duration,
+        SystemTime,
+    },
 };
 
 use anyhow::Context;
+use application::surrogate_keys::{
+    file_key,
+    SURROGATE_KEY,
+};
 use axum::{
     body::Body,
     debug_handler,
@@ -21,8 +28,15 @@ use axum_extra::{
         AcceptRanges,
         CacheControl,
         ContentLength,
+        ContentRange,
         ContentType,
+        ETag,
         Header,
+        HeaderMapExt,
+        IfModifiedSince,
+        IfNoneMatch,
+        IfRange,
+        LastModified,
         Range,
     },
     typed_header::{
@@ -42,29 +56,58 @@ use common::{
         ExtractRequestId,
         ExtractResolvedHostname,
         HttpResponseError,
+        ResolvedHostname,
     },
+    runtime::Runtime,
     sha256::DigestHeader,
+    types::ConvexOrigin,
+    RequestId,
 };
 use errors::ErrorMetadata;
 use file_storage::{
     FileRangeStream,
     FileStream,
+    StoredFile,
 };
 use futures::StreamExt;
-use http::StatusCode;
-use model::file_storage::FileStorageId;
+use http::{
+    header,
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    StatusCode,
+};
+use keybroker::SignedFileUrl;
+use model::file_storage::{
+    types::FileEncryptionMetadata,
+    FileStorageId,
+};
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::RouterState;
+use crate::{
+    ip_access::ExtractClientIp,
+    RouterState,
+};
 
 // Storage GETs are immutable. Browser can cache for a long time.
 const MAX_CACHE_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
 
 const STORE_FILE_AUTHORIZATION_VALIDITY: Duration = Duration::from_secs(60 * 60);
 
+// Opaque client-side encryption metadata, accepted on upload and returned
+// when the file is served.
+#[allow(clippy::declare_interior_mutable_const)]
+pub const ENCRYPTION_KEY_ID_HEADER: HeaderName =
+    HeaderName::from_static("convex-encryption-key-id");
+#[allow(clippy::declare_interior_mutable_const)]
+pub const ENCRYPTION_IV_HEADER: HeaderName = HeaderName::from_static("convex-encryption-iv");
+#[allow(clippy::declare_interior_mutable_const)]
+pub const ENCRYPTION_ALGORITHM_HEADER: HeaderName =
+    HeaderName::from_static("convex-encryption-algorithm");
+
 fn map_header_err<T: Header>(
     r: Result<TypedHeader<T>, TypedHeaderRejection>,
 ) -> anyhow::Result<Option<T>> {
@@ -78,6 +121,66 @@ fn map_header_err<T: Header>(
     })
 }
 
+/// Reads encryption metadata from an upload. The headers must be sent
+/// together, or not at all.
+fn encryption_metadata_from_headers(
+    headers: &HeaderMap,
+) -> anyhow::Result<Option<FileEncryptionMetadata>> {
+    let get = |name: HeaderName| -> anyhow::Result<Option<String>> {
+        headers
+            .get(&name)
+            .map(|value| {
+                value
+                    .to_str()
+                    .map(str::to_string)
+                    .context(ErrorMetadata::bad_request(
+                        "InvalidEncryptionMetadata",
+                        format!("The {name} header must be printable ASCII"),
+                    ))
+            })
+            .transpose()
+    };
+    let encryption = match (
+        get(ENCRYPTION_KEY_ID_HEADER)?,
+        get(ENCRYPTION_IV_HEADER)?,
+        get(ENCRYPTION_ALGORITHM_HEADER)?,
+    ) {
+        (None, None, None) => return Ok(None),
+        (Some(key_id), Some(iv), Some(algorithm)) => FileEncryptionMetadata {
+            key_id,
+            iv,
+            algorithm,
+        },
+        _ => anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidEncryptionMetadata",
+            format!(
+                "Encryption metadata requires all of the {ENCRYPTION_KEY_ID_HEADER}, \
+                 {ENCRYPTION_IV_HEADER} and {ENCRYPTION_ALGORITHM_HEADER} headers"
+            ),
+        )),
+    };
+    encryption.validate()?;
+    Ok(Some(encryption))
+}
+
+/// The headers returning a file's encryption metadata when it's served.
+fn encryption_metadata_headers(
+    encryption: Option<FileEncryptionMetadata>,
+) -> anyhow::Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    if let Some(FileEncryptionMetadata {
+        key_id,
+        iv,
+        algorithm,
+    }) = encryption
+    {
+        headers.insert(ENCRYPTION_KEY_ID_HEADER, key_id.try_into()?);
+        headers.insert(ENCRYPTION_IV_HEADER, iv.try_into()?);
+        headers.insert(ENCRYPTION_ALGORITHM_HEADER, algorithm.try_into()?);
+    }
+    Ok(headers)
+}
+
 #[derive(Deserialize)]
 pub struct QueryParams {
     token: String,
@@ -90,6 +193,7 @@ pub async fn storage_upload(
     content_type: Result<TypedHeader<ContentType>, TypedHeaderRejection>,
     content_length: Result<TypedHeader<ContentLength>, TypedHeaderRejection>,
     sha256: Result<TypedHeader<DigestHeader>, TypedHeaderRejection>,
+    headers: HeaderMap,
     ExtractResolvedHostname(host): ExtractResolvedHostname,
     Host(original_host): Host,
     ExtractRequestId(request_id): ExtractRequestId,
@@ -107,6 +211,7 @@ pub async fn storage_upload(
     let content_length = map_header_err(content_length)?;
     let content_type = map_header_err(content_type)?;
     let sha256 = map_header_err(sha256)?.map(|dh| dh.0);
+    let encryption = encryption_metadata_from_headers(&headers)?;
     let body = body
         .into_data_stream()
         .map(|r| r.context("Error parsing body"))
@@ -122,6 +227,7 @@ pub async fn storage_upload(
             content_length,
             content_type,
             sha256,
+            encryption,
             body,
         )
         .await?;
@@ -139,14 +245,17 @@ pub async fn storage_upload(
 #[derive(Deserialize)]
 pub struct GetQueryParams {
     component: Option<String>,
+    /// A service token minted with `ctx.auth.mintServiceToken`. If present, it
+    /// must grant access to the requested file.
+    token: Option<String>,
 }
 
 #[debug_handler]
 pub async fn storage_get(
     State(st): State<RouterState>,
     Path(uuid): Path<String>,
-    Query(GetQueryParams { component }): Query<GetQueryParams>,
-    range: Result<TypedHeader<Range>, TypedHeaderRejection>,
+    Query(GetQueryParams { component, token }): Query<GetQueryParams>,
+    headers: HeaderMap,
     ExtractResolvedHostname(host): ExtractResolvedHostname,
     Host(original_host): Host,
     ExtractRequestId(request_id): ExtractRequestId,
@@ -155,82 +264,290 @@ pub async fn storage_get(
         "InvalidStoragePath",
         format!("Invalid storage path: \"{uuid}\". Please use `storage.getUrl()` to generate a valid URL to retrieve files. See https://docs.convex.dev/file-storage/serve-files for more details"),
     ))?;
-    let file_storage_id = FileStorageId::LegacyStorageId(storage_uuid);
     let component = ComponentId::deserialize_from_string(component.as_deref())?;
-    let origin = original_host.into();
+    if let Some(token) = token {
+        let token = st
+            .api
+            .check_service_token(&host, request_id.clone(), &token)
+            .await?;
+        if token.component != component || !token.storage_ids.contains(&storage_uuid.to_string()) {
+            return Err(anyhow::anyhow!(ErrorMetadata::forbidden(
+                "ServiceTokenForbidden",
+                "This service token doesn't grant access to this file",
+            ))
+            .into());
+        }
+    }
+    let file_storage_id = FileStorageId::LegacyStorageId(storage_uuid);
+    serve_file(
+        &st,
+        &host,
+        request_id,
+        original_host.into(),
+        component,
+        file_storage_id,
+        &headers,
+        CacheControl::new()
+            .with_private()
+            .with_max_age(MAX_CACHE_AGE),
+        None,
+    )
+    .await
+}
 
-    // TODO(CX-3065) figure out deterministic repeatable tokens
+/// Serves a file from a URL returned by `storage.getSignedUrl()`. The token
+/// in the path identifies the file, so the URL doesn't reveal its storage ID.
+#[debug_handler]
+pub async fn storage_get_signed(
+    State(st): State<RouterState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    ExtractClientIp(client_ip): ExtractClientIp,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    Host(original_host): Host,
+    ExtractRequestId(request_id): ExtractRequestId,
+) -> Result<Response, HttpResponseError> {
+    let SignedFileUrl {
+        component,
+        storage_id,
+        expires,
+        filename,
+        ..
+    } = st
+        .api
+        .check_signed_file_url(&host, request_id.clone(), &token, client_ip)
+        .await?;
+    // Don't let browsers keep serving the file after the URL expires.
+    let max_age = expires
+        .as_system_time()
+        .duration_since(st.runtime.system_time())
+        .unwrap_or_default()
+        .min(MAX_CACHE_AGE);
+    let content_disposition = filename
+        .map(|filename| attachment_content_disposition(&filename))
+        .transpose()?;
+    serve_file(
+        &st,
+        &host,
+        request_id,
+        original_host.into(),
+        component,
+        FileStorageId::LegacyStorageId(storage_id),
+        &headers,
+        CacheControl::new().with_private().with_max_age(max_age),
+        content_disposition,
+    )
+    .await
+}
 
-    if let Ok(range_header) = range {
-        let ranges: Vec<(Bound<u64>, Bound<u64>)> = range_header
-            .satisfiable_ranges(
-                u64::MAX, /* technically, we should pass in the length of the file to protect
-                           * against inputs that are too large, but it's a
-                           * bit tricky to get at this point */
-            )
-            .collect();
-        // Convex only supports a single range because underlying AWS S3 only supports
-        // a single range
-        if ranges.len() != 1 {
-            // It's always allowable to return the whole file
-            // so we could do that here too.
-            return Ok(StatusCode::RANGE_NOT_SATISFIABLE.into_response());
-        }
-        let range = ranges[0];
+/// Serves a transformed image from a URL returned by `storage.getImageUrl()`.
+/// The transform is signed into the token, and the result is immutable like
+/// the source file.
+#[debug_handler]
+pub async fn storage_get_image(
+    State(st): State<RouterState>,
+    Path(token): Path<String>,
+    ExtractResolvedHostname(host): ExtractResolvedHostname,
+    ExtractRequestId(request_id): ExtractRequestId,
+) -> Result<Response, HttpResponseError> {
+    let FileStream {
+        sha256,
+        content_type,
+        content_length,
+        stream,
+        ..
+    } = st
+        .api
+        .get_transformed_image(&host, request_id, &token)
+        .await?;
+    Ok((
+        TypedHeader(DigestHeader(sha256)),
+        content_type.map(TypedHeader),
+        TypedHeader(content_length),
+        TypedHeader(
+            CacheControl::new()
+                .with_private()
+                .with_max_age(MAX_CACHE_AGE),
+        ),
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
 
-        let FileRangeStream {
-            content_length,
-            content_range,
-            content_type,
-            stream,
-        } = st
-            .api
-            .get_file_range(&host, request_id, origin, component, file_storage_id, range)
-            .await?;
+/// `Content-Disposition: attachment` with `filename`, using the RFC 6266
+/// `filename*` parameter for names that aren't plain ASCII.
+fn attachment_content_disposition(filename: &str) -> anyhow::Result<HeaderValue> {
+    let ascii_filename: String = filename
+        .chars()
+        .map(|c| {
+            if c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Ok(HeaderValue::from_str(&format!(
+        "attachment; filename=\"{ascii_filename}\"; filename*=UTF-8''{}",
+        urlencoding::encode(filename)
+    ))?)
+}
 
-        let (status, content_range) = match content_range {
-            Some(content_range) => (
-                StatusCode::PARTIAL_CONTENT,
-                Some(TypedHeader(content_range)),
-            ),
-            None => (StatusCode::OK, None),
-        };
+/// Resolves a byte range from a `Range` header against a file of `len` bytes,
+/// clamping the end to the end of the file. Returns `None` if the range starts
+/// past the end of the file.
+fn resolve_range(
+    (start, end): (Bound<u64>, Bound<u64>),
+    len: u64,
+) -> Option<(Bound<u64>, Bound<u64>)> {
+    let last = len.checked_sub(1)?;
+    let start = match start {
+        Bound::Included(start) => start,
+        Bound::Excluded(start) => start.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match end {
+        Bound::Included(end) => end.min(last),
+        Bound::Excluded(end) => end.checked_sub(1)?.min(last),
+        Bound::Unbounded => last,
+    };
+    (start <= end).then_some((Bound::Included(start), Bound::Included(end)))
+}
 
+/// Serves a file, answering conditional requests with `304 Not Modified` and
+/// `Range` requests with only the requested bytes. Stored files never change,
+/// so the sha256 of the contents is a strong ETag and the upload time is the
+/// last modified time.
+async fn serve_file(
+    st: &RouterState,
+    host: &ResolvedHostname,
+    request_id: RequestId,
+    origin: ConvexOrigin,
+    component: ComponentId,
+    file_storage_id: FileStorageId,
+    headers: &HeaderMap,
+    cache_control: CacheControl,
+    content_disposition: Option<HeaderValue>,
+) -> Result<Response, HttpResponseError> {
+    let StoredFile {
+        entry,
+        creation_time,
+    } = st
+        .api
+        .get_stored_file(host, request_id.clone(), component, file_storage_id.clone())
+        .await?;
+    let etag: ETag = format!("\"{}\"", entry.sha256.as_hex())
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid ETag for {file_storage_id}"))?;
+    let last_modified = LastModified::from(
+        SystemTime::UNIX_EPOCH + Duration::from_millis(f64::from(creation_time) as u64),
+    );
+    // Lets a CDN in front of the deployment purge the file once it's deleted.
+    let surrogate_key = [(SURROGATE_KEY, file_key(&entry.storage_id.to_string()))];
+
+    // If-None-Match takes precedence over If-Modified-Since (RFC 9110 13.2.2).
+    let not_modified = match headers.typed_get::<IfNoneMatch>() {
+        Some(if_none_match) => !if_none_match.precondition_passes(&etag),
+        None => headers
+            .typed_get::<IfModifiedSince>()
+            .is_some_and(|since| !since.is_modified(last_modified.into())),
+    };
+    if not_modified {
         return Ok((
-            status,
-            content_type.map(TypedHeader),
-            content_range,
-            TypedHeader(content_length),
-            TypedHeader(
-                CacheControl::new()
-                    .with_private()
-                    .with_max_age(MAX_CACHE_AGE),
-            ),
-            TypedHeader(AcceptRanges::bytes()),
-            Body::from_stream(stream),
+            StatusCode::NOT_MODIFIED,
+            TypedHeader(etag),
+            TypedHeader(last_modified),
+            TypedHeader(cache_control),
+            surrogate_key,
         )
             .into_response());
     }
 
+    let content_disposition =
+        content_disposition.map(|value| [(header::CONTENT_DISPOSITION, value)]);
+    let len = entry.size as u64;
+    // Only send part of the file if the client's partial copy is still current.
+    let range = headers.typed_get::<Range>().filter(|_| {
+        headers
+            .typed_get::<IfRange>()
+            .is_none_or(|if_range| !if_range.is_modified(Some(&etag), Some(&last_modified)))
+    });
+    if let Some(range) = range {
+        let ranges: Vec<(Bound<u64>, Bound<u64>)> = range.satisfiable_ranges(len).collect();
+        // Convex only supports a single range because underlying AWS S3 only supports
+        // a single range. It's always allowable to return the whole file, so we do
+        // that for multi-range requests.
+        if ranges.len() <= 1 {
+            let Some(range) = ranges
+                .into_iter()
+                .next()
+                .and_then(|range| resolve_range(range, len))
+            else {
+                return Ok((
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    TypedHeader(ContentRange::unsatisfied_bytes(len)),
+                )
+                    .into_response());
+            };
+
+            let FileRangeStream {
+                content_length,
+                content_range,
+                content_type,
+                encryption,
+                stream,
+            } = st
+                .api
+                .get_file_range(host, request_id, origin, component, file_storage_id, range)
+                .await?;
+
+            let (status, content_range) = match content_range {
+                Some(content_range) => (
+                    StatusCode::PARTIAL_CONTENT,
+                    Some(TypedHeader(content_range)),
+                ),
+                None => (StatusCode::OK, None),
+            };
+
+            return Ok((
+                status,
+                content_type.map(TypedHeader),
+                content_range,
+                TypedHeader(content_length),
+                TypedHeader(etag),
+                TypedHeader(last_modified),
+                TypedHeader(cache_control),
+                TypedHeader(AcceptRanges::bytes()),
+                surrogate_key,
+                content_disposition,
+                encryption_metadata_headers(encryption)?,
+                Body::from_stream(stream),
+            )
+                .into_response());
+        }
+    }
+
     let FileStream {
         sha256,
         content_type,
         content_length,
+        encryption,
         stream,
     } = st
         .api
-        .get_file(&host, request_id, origin, component, file_storage_id)
+        .get_file(host, request_id, origin, component, file_storage_id)
         .await?;
     Ok((
         TypedHeader(DigestHeader(sha256)),
         content_type.map(TypedHeader),
         TypedHeader(content_length),
-        TypedHeader(
-            CacheControl::new()
-                .with_private()
-                .with_max_age(MAX_CACHE_AGE),
-        ),
+        TypedHeader(etag),
+        TypedHeader(last_modified),
+        TypedHeader(cache_control),
         TypedHeader(AcceptRanges::bytes()),
+        surrogate_key,
+        content_disposition,
+        encryption_metadata_headers(encryption)?,
         Body::from_stream(stream),
     )
         .into_response())
@@ -1,6 +1,10 @@
-use std::time::{
-    Duration,
-    Instant,
+use std::{
+    net::IpAddr,
+    sync::Arc,
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 use ::errors::{
@@ -36,6 +40,7 @@ use common::{
     ws::is_connection_closed_error,
 };
 use futures::{
+    future,
     select_biased,
     try_join,
     FutureExt,
@@ -56,7 +61,10 @@ use sync_types::{
     IdentityVersion,
     SessionId,
 };
-use tokio::sync::mpsc;
+use tokio::sync::{
+    mpsc,
+    watch,
+};
 
 mod metrics;
 
@@ -75,26 +83,61 @@ use metrics::{
     websocket_upgrade_timer,
 };
 
-use crate::RouterState;
+use crate::{
+    ip_access::ExtractClientIp,
+    RouterState,
+};
 
 /// How often heartbeat pings are sent.
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 /// How long before lack of client response causes a timeout.
 const CLIENT_TIMEOUT: Duration = Duration::from_secs(120);
 
-struct SyncSocketDropToken {}
+/// Counts open sync sockets so shutdown can wait for them to close.
+#[derive(Clone)]
+pub struct SyncSocketTracker {
+    open: Arc<watch::Sender<usize>>,
+}
+
+impl Default for SyncSocketTracker {
+    fn default() -> Self {
+        let (open, _) = watch::channel(0);
+        Self {
+            open: Arc::new(open),
+        }
+    }
+}
+
+impl SyncSocketTracker {
+    pub fn num_open(&self) -> usize {
+        *self.open.borrow()
+    }
+
+    /// Wait until there are no open sync sockets.
+    pub async fn wait_for_drain(&self) {
+        let mut open_rx = self.open.subscribe();
+        // `self` holds the sender, so this can't fail.
+        let _ = open_rx.wait_for(|open| *open == 0).await;
+    }
+}
+
+struct SyncSocketDropToken {
+    tracker: SyncSocketTracker,
+}
 
 /// Tracker that exists for the lifetime of a run_sync_socket.
 impl SyncSocketDropToken {
-    fn new() -> Self {
+    fn new(tracker: SyncSocketTracker) -> Self {
         log_sync_protocol_websockets_total(1);
-        SyncSocketDropToken {}
+        tracker.open.send_modify(|open| *open += 1);
+        SyncSocketDropToken { tracker }
     }
 }
 
 impl Drop for SyncSocketDropToken {
     fn drop(&mut self) {
         log_sync_protocol_websockets_total(-1);
+        self.tracker.open.send_modify(|open| *open -= 1);
     }
 }
 
@@ -129,6 +172,11 @@ impl Drop for DebugSyncSocketDropToken {
 // on a close frame and close the WebSocket. They can also signal clean shutdown
 // by returning `Ok(())`, and once all of them have cleanly exited, we'll
 // gracefully close the socket.
+//
+// When the server starts shutting down, the sync worker stops starting new
+// mutations and actions and exits once the in-flight ones have responded. The
+// `send_messages` loop then flushes the responses and fails with an overloaded
+// error, whose close frame tells the client to reconnect.
 async fn run_sync_socket(
     st: RouterState,
     host: ResolvedHostname,
@@ -137,12 +185,13 @@ async fn run_sync_socket(
     sentry_scope: sentry::Scope,
     on_connect: Box<dyn FnOnce(SessionId) + Send>,
 ) {
-    let _drop_token = SyncSocketDropToken::new();
+    let _drop_token = SyncSocketDropToken::new(st.sync_sockets.clone());
 
     let (mut tx, mut rx) = socket.split();
 
     let last_received = Mutex::new(Instant::now());
     let last_ping_sent = Mutex::new(Instant::now());
+    let shutting_down = Mutex::new(false);
 
     let (client_tx, client_rx) = mpsc::unbounded_channel();
     let receive_messages = async {
@@ -226,6 +275,12 @@ async fn run_sync_socket(
                 },
             }
         }
+        if *shutting_down.lock() {
+            return Err(anyhow::anyhow!(ErrorMetadata::overloaded(
+                "ServerShuttingDown",
+                "The server is shutting down. Reconnect to continue.",
+            )));
+        }
         Ok(())
     };
     let mut identity_version: Option<IdentityVersion> = None;
@@ -240,8 +295,16 @@ async fn run_sync_socket(
             server_tx,
             on_connect,
         );
+        let mut shutdown_rx = st.shutdown_rx.clone();
+        sync_worker.drain_on_shutdown(async move {
+            // The sender is only dropped without broadcasting in tests.
+            if shutdown_rx.recv().await.is_err() {
+                future::pending::<()>().await;
+            }
+        });
         let r = sync_worker.go().await;
         identity_version = Some(sync_worker.identity_version());
+        *shutting_down.lock() = sync_worker.is_draining();
         // Explicit drop for emphasis: dropping triggers send_messages to complete.
         drop(sync_worker);
         r
@@ -329,18 +392,25 @@ async fn run_sync_socket(
     log_websocket_closed();
 }
 
-fn new_sync_worker_config(client_version: ClientVersion) -> anyhow::Result<SyncWorkerConfig> {
-    Ok(SyncWorkerConfig { client_version })
+fn new_sync_worker_config(
+    client_version: ClientVersion,
+    client_ip: Option<IpAddr>,
+) -> anyhow::Result<SyncWorkerConfig> {
+    Ok(SyncWorkerConfig {
+        client_version,
+        client_ip,
+    })
 }
 
 pub async fn sync_handler(
     st: RouterState,
     host: ResolvedHostname,
     client_version: ClientVersion,
+    client_ip: Option<IpAddr>,
     ws: WebSocketUpgrade,
     on_connect: Box<dyn FnOnce(SessionId) + Send>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    let config = new_sync_worker_config(client_version)?;
+    let config = new_sync_worker_config(client_version, client_ip)?;
     // Make a copy of the Sentry scope, which contains the request metadata.
     let sentry_scope = sentry::configure_scope(move |s| s.clone());
 
@@ -359,9 +429,18 @@ pub async fn sync(
     State(st): State<RouterState>,
     ExtractResolvedHostname(host): ExtractResolvedHostname,
     ExtractClientVersion(client_version): ExtractClientVersion,
+    ExtractClientIp(client_ip): ExtractClientIp,
     ws: WebSocketUpgrade,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    sync_handler(st, host, client_version, ws, Box::new(|_session_id| ())).await
+    sync_handler(
+        st,
+        host,
+        client_version,
+        client_ip,
+        ws,
+        Box::new(|_session_id| ()),
+    )
+    .await
 }
 
 #[cfg(test)]
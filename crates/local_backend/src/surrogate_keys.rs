@@ -0,0 +1,40 @@
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use http::StatusCode;
+use serde::Deserialize;
+
+use crate::{
+    admin::must_be_admin_with_write_access,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeSurrogateKeysArgs {
+    surrogate_keys: Vec<String>,
+}
+
+/// Purge responses tagged with any of the keys from the CDN configured with
+/// `CDN_PURGE_URL`. Files are tagged `file:<storageId>`, and HTTP action
+/// responses `table:<tabletId>` for each table their queries read, on top of
+/// any keys the action set itself.
+#[debug_handler]
+pub async fn purge_surrogate_keys(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(PurgeSurrogateKeysArgs { surrogate_keys }): Json<PurgeSurrogateKeysArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    st.application
+        .purge_surrogate_keys(identity, surrogate_keys)
+        .await?;
+    Ok(StatusCode::OK)
+}
@@ -16,6 +16,7 @@ use common::{
     types::MemberId,
 };
 use http::{
+    HeaderMap,
     Request,
     StatusCode,
 };
@@ -78,6 +79,14 @@ impl TestLocalBackend {
         &self,
         req: Request<axum::body::Body>,
     ) -> anyhow::Result<T> {
+        let (result, _) = self.expect_success_with_headers(req).await?;
+        Ok(result)
+    }
+
+    pub async fn expect_success_with_headers<T: DeserializeOwned>(
+        &self,
+        req: Request<axum::body::Body>,
+    ) -> anyhow::Result<(T, HeaderMap)> {
         tracing::info!("Sending req {req:?}");
         let (parts, body) = self.app.router().clone().oneshot(req).await?.into_parts();
         let bytes = body
@@ -88,8 +97,9 @@ impl TestLocalBackend {
         let msg = format!("Got response: {}", String::from_utf8_lossy(&bytes));
         tracing::info!("{msg}");
         assert_eq!(parts.status, StatusCode::OK, "{msg}");
-        serde_json::from_slice(if bytes.is_empty() { b"null" } else { &bytes })
-            .context(format!("Couldn't deserialize as json: {bytes:?}"))
+        let result = serde_json::from_slice(if bytes.is_empty() { b"null" } else { &bytes })
+            .context(format!("Couldn't deserialize as json: {bytes:?}"))?;
+        Ok((result, parts.headers))
     }
 
     pub async fn expect_error(
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use application::table_stats::TableStats;
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    document::CreationTime,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+/// Number of exports returned by `list_usage_exports` if the request doesn't
+/// set a limit.
+const DEFAULT_USAGE_EXPORTS_LIMIT: usize = 100;
+const MAX_USAGE_EXPORTS_LIMIT: usize = 1000;
+
+/// Usage per component, function and table since the last usage export. The
+/// response has the same format as the exported reports.
+pub async fn current_usage(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let report = st.application.current_usage_report(identity).await?;
+    Ok(Json(report))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListUsageExportsQueryArgs {
+    cursor: Option<f64>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageExportJson {
+    period_start_ms: u64,
+    period_end_ms: u64,
+    /// The report's key in the exports storage bucket.
+    object_key: String,
+    size_bytes: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListUsageExportsResponse {
+    exports: Vec<UsageExportJson>,
+    /// Pass as `cursor` to fetch the next page. `null` once there are no more
+    /// exports.
+    cursor: Option<f64>,
+}
+
+/// List usage reports uploaded to the exports storage bucket, newest first.
+pub async fn list_usage_exports(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(query_args): Query<ListUsageExportsQueryArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let limit = query_args.limit.unwrap_or(DEFAULT_USAGE_EXPORTS_LIMIT);
+    if limit > MAX_USAGE_EXPORTS_LIMIT {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidLimit",
+            format!("Limit {limit} is greater than the maximum of {MAX_USAGE_EXPORTS_LIMIT}"),
+        ))
+        .into());
+    }
+    let cursor = query_args
+        .cursor
+        .map(|cursor| {
+            CreationTime::try_from(cursor).context(ErrorMetadata::bad_request(
+                "InvalidCursor",
+                format!("Invalid cursor {cursor}"),
+            ))
+        })
+        .transpose()?;
+    let page = st
+        .application
+        .list_usage_exports(identity, cursor, limit)
+        .await?;
+    let exports = page
+        .exports
+        .into_iter()
+        .map(|export| {
+            let export = export.into_value();
+            UsageExportJson {
+                period_start_ms: export.period_start_ms,
+                period_end_ms: export.period_end_ms,
+                object_key: export.object_key.into(),
+                size_bytes: export.size_bytes,
+            }
+        })
+        .collect();
+    Ok(Json(ListUsageExportsResponse {
+        exports,
+        cursor: page.cursor.map(f64::from),
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStatsJson {
+    component_path: Option<String>,
+    table_name: String,
+    document_count: u64,
+    document_bytes: u64,
+    index_bytes: BTreeMap<String, u64>,
+    search_segment_bytes: u64,
+    historical_revision_bytes: u64,
+}
+
+impl From<TableStats> for TableStatsJson {
+    fn from(stats: TableStats) -> Self {
+        Self {
+            component_path: stats.component_path.serialize(),
+            table_name: stats.table_name.to_string(),
+            document_count: stats.document_count,
+            document_bytes: stats.document_bytes,
+            index_bytes: stats
+                .index_bytes
+                .into_iter()
+                .map(|(index, bytes)| (index.to_string(), bytes))
+                .collect(),
+            search_segment_bytes: stats.search_segment_bytes,
+            historical_revision_bytes: stats.historical_revision_bytes,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStatsResponse {
+    tables: Vec<TableStatsJson>,
+    /// The database timestamp the stats reflect.
+    snapshot_ts: Option<String>,
+    /// When the stats were computed, in milliseconds since the epoch. `null`
+    /// until the table stats worker has computed them.
+    computed_at_ms: Option<u64>,
+}
+
+/// Per-table document counts and storage, as of the table stats worker's last
+/// run.
+pub async fn table_stats(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let response = match st.application.table_stats(&identity)? {
+        Some(snapshot) => TableStatsResponse {
+            tables: snapshot.tables.into_iter().map(Into::into).collect(),
+            snapshot_ts: Some(u64::from(snapshot.snapshot_ts).to_string()),
+            computed_at_ms: Some(snapshot.computed_at.as_ms_since_epoch()?),
+        },
+        None => TableStatsResponse {
+            tables: vec![],
+            snapshot_ts: None,
+            computed_at_ms: None,
+        },
+    };
+    Ok(Json(response))
+}
@@ -0,0 +1,210 @@
+//! Anonymous identities the backend issues ID tokens for, so apps can let
+//! users try them out before signing up. Each identity is bound to the device
+//! it was issued to, and is promoted to a signed-in user at most once.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    sha256::Sha256,
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::AnonymousIdentity;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static ANONYMOUS_IDENTITIES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_anonymous_identities"
+        .parse()
+        .expect("Invalid built-in anonymous identities table")
+});
+
+static ANONYMOUS_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "anonymousId".parse().expect("Invalid built-in field"));
+static DEVICE_HASH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "deviceHash".parse().expect("Invalid built-in field"));
+
+pub static ANONYMOUS_IDENTITIES_INDEX_BY_ANONYMOUS_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&ANONYMOUS_IDENTITIES_TABLE, "by_anonymous_id"));
+pub static ANONYMOUS_IDENTITIES_INDEX_BY_DEVICE_HASH: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&ANONYMOUS_IDENTITIES_TABLE, "by_device_hash"));
+
+pub struct AnonymousIdentitiesTable;
+impl SystemTable for AnonymousIdentitiesTable {
+    fn table_name(&self) -> &'static TableName {
+        &ANONYMOUS_IDENTITIES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: ANONYMOUS_IDENTITIES_INDEX_BY_ANONYMOUS_ID.clone(),
+                fields: vec![ANONYMOUS_ID_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+            SystemIndex {
+                name: ANONYMOUS_IDENTITIES_INDEX_BY_DEVICE_HASH.clone(),
+                fields: vec![DEVICE_HASH_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<AnonymousIdentity>::try_from(document).map(|_| ())
+    }
+}
+
+/// Device IDs are only stored hashed, since they're long-lived credentials
+/// for the identity.
+fn hash_device_id(device_id: &str) -> String {
+    Sha256::hash(device_id.as_bytes()).as_hex()
+}
+
+pub struct AnonymousIdentitiesModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> AnonymousIdentitiesModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn index_query(
+        index_name: &IndexName,
+        field: &FieldPath,
+        value: &str,
+    ) -> anyhow::Result<Query> {
+        Ok(Query::index_range(IndexRange {
+            index_name: index_name.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                field.clone(),
+                ConvexValue::try_from(value)?.into(),
+            )],
+            order: Order::Asc,
+        }))
+    }
+
+    async fn get(
+        &mut self,
+        anonymous_id: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<AnonymousIdentity>>> {
+        let query = Self::index_query(
+            &ANONYMOUS_IDENTITIES_INDEX_BY_ANONYMOUS_ID,
+            &ANONYMOUS_ID_FIELD,
+            anonymous_id,
+        )?;
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// The anonymous ID for `device_id`, creating a new identity if the device
+    /// doesn't have one or its identity has been promoted.
+    pub async fn get_or_create(&mut self, device_id: &str) -> anyhow::Result<String> {
+        let device_hash = hash_device_id(device_id);
+        let query = Self::index_query(
+            &ANONYMOUS_IDENTITIES_INDEX_BY_DEVICE_HASH,
+            &DEVICE_HASH_FIELD,
+            &device_hash,
+        )?;
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            let identity: ParsedDocument<AnonymousIdentity> = document.try_into()?;
+            if identity.promoted_to.is_none() {
+                return Ok(identity.into_value().anonymous_id);
+            }
+        }
+        let anonymous_id = self.tx.runtime().new_uuid_v4().to_string();
+        SystemMetadataModel::new_global(self.tx)
+            .insert(
+                &ANONYMOUS_IDENTITIES_TABLE,
+                AnonymousIdentity {
+                    anonymous_id: anonymous_id.clone(),
+                    device_hash,
+                    promoted_to: None,
+                }
+                .try_into()?,
+            )
+            .await?;
+        Ok(anonymous_id)
+    }
+
+    /// Whether `anonymous_id` has been promoted to a signed-in user. Tokens
+    /// for promoted identities are no longer accepted.
+    pub async fn is_promoted(&mut self, anonymous_id: &str) -> anyhow::Result<bool> {
+        Ok(self
+            .get(anonymous_id)
+            .await?
+            .is_some_and(|identity| identity.promoted_to.is_some()))
+    }
+
+    /// Record that the anonymous identity was promoted to the user with
+    /// `token_identifier`. The caller must present the device ID the identity
+    /// was issued to, and an identity can only be promoted once.
+    pub async fn promote(
+        &mut self,
+        anonymous_id: &str,
+        device_id: &str,
+        token_identifier: String,
+    ) -> anyhow::Result<()> {
+        let not_found = || {
+            ErrorMetadata::bad_request(
+                "AnonymousIdentityNotFound",
+                "Anonymous identity not found for this device",
+            )
+        };
+        let Some(identity) = self.get(anonymous_id).await? else {
+            anyhow::bail!(not_found());
+        };
+        if identity.device_hash != hash_device_id(device_id) {
+            anyhow::bail!(not_found());
+        }
+        if identity.promoted_to.is_some() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "AnonymousIdentityAlreadyPromoted",
+                "Anonymous identity was already promoted to a user"
+            ));
+        }
+        let (id, mut identity) = identity.into_id_and_value();
+        identity.promoted_to = Some(token_identifier);
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, identity.try_into()?)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// An anonymous identity issued to a device. Its ID tokens have the anonymous
+/// ID as their subject.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct AnonymousIdentity {
+    pub anonymous_id: String,
+    /// Hex encoded SHA-256 of the device ID the identity was issued to.
+    pub device_hash: String,
+    /// Token identifier of the user the identity was promoted to. Promoted
+    /// identities can't be used to mint new tokens or be promoted again.
+    pub promoted_to: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedAnonymousIdentity {
+    anonymous_id: String,
+    device_hash: String,
+    promoted_to: Option<String>,
+}
+
+impl From<AnonymousIdentity> for SerializedAnonymousIdentity {
+    fn from(value: AnonymousIdentity) -> Self {
+        Self {
+            anonymous_id: value.anonymous_id,
+            device_hash: value.device_hash,
+            promoted_to: value.promoted_to,
+        }
+    }
+}
+
+impl From<SerializedAnonymousIdentity> for AnonymousIdentity {
+    fn from(value: SerializedAnonymousIdentity) -> Self {
+        Self {
+            anonymous_id: value.anonymous_id,
+            device_hash: value.device_hash,
+            promoted_to: value.promoted_to,
+        }
+    }
+}
+
+codegen_convex_serialization!(AnonymousIdentity, SerializedAnonymousIdentity);
@@ -0,0 +1,127 @@
+//! Lists that mutations only ever append to, like an activity feed on a hot
+//! document. Each value is its own document, so appending reads nothing and
+//! concurrent appends to the same list never conflict. Reading a list scans
+//! its entries in creation time order.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::ListEntry;
+use crate::{
+    sharded_counters::{
+        name_value,
+        validate_name,
+    },
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static LIST_ENTRIES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_list_entries"
+        .parse()
+        .expect("Invalid built-in list entries table")
+});
+
+pub static LIST_ENTRIES_BY_NAME: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&LIST_ENTRIES_TABLE, "by_name"));
+
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("invalid name field"));
+
+pub struct ListEntriesTable;
+impl SystemTable for ListEntriesTable {
+    fn table_name(&self) -> &'static TableName {
+        &LIST_ENTRIES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: LIST_ENTRIES_BY_NAME.clone(),
+            fields: vec![NAME_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ListEntry>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct AppendOnlyListModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> AppendOnlyListModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    pub async fn append(&mut self, name: &str, value: ConvexValue) -> anyhow::Result<()> {
+        validate_name(name)?;
+        let entry = ListEntry::new(name.to_string(), value)?;
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .insert(&LIST_ENTRIES_TABLE, entry.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Returns up to `limit` values from the list, oldest first for
+    /// [`Order::Asc`] and newest first for [`Order::Desc`].
+    pub async fn read(
+        &mut self,
+        name: &str,
+        order: Order,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ConvexValue>> {
+        validate_name(name)?;
+        let query = Query::index_range(IndexRange {
+            index_name: LIST_ENTRIES_BY_NAME.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                NAME_FIELD.clone(),
+                name_value(name)?.into(),
+            )],
+            order,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut values = vec![];
+        while let Some(document) = query_stream.next(self.tx, Some(limit)).await? {
+            let entry: ParsedDocument<ListEntry> = document.try_into()?;
+            values.push(entry.value()?);
+            if values.len() >= limit {
+                break;
+            }
+        }
+        Ok(values)
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(any(test, feature = "testing"))]
+use proptest::prelude::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_bytes::ByteBuf;
+use serde_json::Value as JsonValue;
+use value::{
+    codegen_convex_serialization,
+    ConvexValue,
+};
+
+/// One value appended to a named list. Entries are never modified, so
+/// concurrent appends to the same list don't conflict with each other.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ListEntry {
+    pub name: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::vec(any::<u8>(), 0..64).prop_map(ByteBuf::from)"
+        )
+    )]
+    value_bytes: ByteBuf,
+}
+
+impl ListEntry {
+    pub fn new(name: String, value: ConvexValue) -> anyhow::Result<Self> {
+        let value_bytes = serde_json::to_vec(&JsonValue::from(value))?;
+        Ok(Self {
+            name,
+            value_bytes: ByteBuf::from(value_bytes),
+        })
+    }
+
+    pub fn value(&self) -> anyhow::Result<ConvexValue> {
+        let value_json: JsonValue = serde_json::from_slice(&self.value_bytes)?;
+        value_json.try_into()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedListEntry {
+    name: String,
+    // Serialize the value as binary since we restrict what field names can
+    // be used in a `Document`'s top-level object.
+    value: ByteBuf,
+}
+
+impl From<ListEntry> for SerializedListEntry {
+    fn from(entry: ListEntry) -> Self {
+        Self {
+            name: entry.name,
+            value: entry.value_bytes,
+        }
+    }
+}
+
+impl From<SerializedListEntry> for ListEntry {
+    fn from(value: SerializedListEntry) -> Self {
+        Self {
+            name: value.name,
+            value_bytes: value.value,
+        }
+    }
+}
+
+codegen_convex_serialization!(ListEntry, SerializedListEntry);
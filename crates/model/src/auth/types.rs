@@ -35,9 +35,27 @@ impl TryFrom<ConvexObject> for AuthInfoPersisted {
             Some(ConvexValue::String(s)) => IssuerUrl::new(s.into())?,
             _ => anyhow::bail!("Missing or invalid domain field for AuthInfo"),
         };
+        let jwks_cache_ttl_seconds = match fields.remove("jwksCacheTtlSeconds") {
+            Some(ConvexValue::Int64(ttl)) => Some(u64::try_from(ttl)?),
+            None => None,
+            _ => anyhow::bail!("Invalid jwksCacheTtlSeconds field for AuthInfo"),
+        };
+        let magic_link_action = match fields.remove("magicLinkAction") {
+            Some(ConvexValue::String(s)) => Some(s.into()),
+            None => None,
+            _ => anyhow::bail!("Invalid magicLinkAction field for AuthInfo"),
+        };
+        let passkey_origin = match fields.remove("passkeyOrigin") {
+            Some(ConvexValue::String(s)) => Some(s.into()),
+            None => None,
+            _ => anyhow::bail!("Invalid passkeyOrigin field for AuthInfo"),
+        };
         Ok(Self(AuthInfo {
             application_id,
             domain,
+            jwks_cache_ttl_seconds,
+            magic_link_action,
+            passkey_origin,
         }))
     }
 }
@@ -46,10 +64,20 @@ impl TryFrom<AuthInfoPersisted> for ConvexObject {
     type Error = anyhow::Error;
 
     fn try_from(info: AuthInfoPersisted) -> Result<Self, Self::Error> {
-        obj!(
+        let mut object = obj!(
             "applicationID" => info.0.application_id,
             "domain" => info.0.domain.to_string(),
-        )
+        )?;
+        if let Some(ttl) = info.0.jwks_cache_ttl_seconds {
+            object = object.shallow_merge(obj!("jwksCacheTtlSeconds" => i64::try_from(ttl)?)?)?;
+        }
+        if let Some(magic_link_action) = info.0.magic_link_action {
+            object = object.shallow_merge(obj!("magicLinkAction" => magic_link_action)?)?;
+        }
+        if let Some(passkey_origin) = info.0.passkey_origin {
+            object = object.shallow_merge(obj!("passkeyOrigin" => passkey_origin)?)?;
+        }
+        Ok(object)
     }
 }
 
@@ -24,6 +24,7 @@ use value::{
 };
 
 use crate::{
+    maintenance_windows::MaintenanceWindowsModel,
     SystemIndex,
     SystemTable,
 };
@@ -117,7 +118,11 @@ impl<'a, RT: Runtime> BackendStateModel<'a, RT> {
         match backend_state {
             BackendState::Running => {},
             BackendState::Paused => {
-                return Ok(Err(JsError::from_message(PAUSED_ERROR_MESSAGE.to_string())));
+                let message = MaintenanceWindowsModel::new(self.tx)
+                    .active_message()
+                    .await?
+                    .unwrap_or_else(|| PAUSED_ERROR_MESSAGE.to_string());
+                return Ok(Err(JsError::from_message(message)));
             },
             BackendState::Disabled => {
                 return Ok(Err(JsError::from_message(
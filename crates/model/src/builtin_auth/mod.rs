@@ -0,0 +1,629 @@
+//! Users, credentials, passkeys and sessions for the built-in auth subsystem,
+//! which lets deployments sign users in with an email and password, a magic
+//! link or a passkey without a third-party auth provider.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use rand::RngCore;
+use value::{
+    base64::encode_urlsafe,
+    sha256::Sha256,
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    AuthCredential,
+    AuthCredentialKind,
+    AuthCredentialOwner,
+    AuthPasskey,
+    AuthPasskeyChallenge,
+    AuthSession,
+    AuthUser,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static BUILTIN_AUTH_USERS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_builtin_auth_users"
+        .parse()
+        .expect("Invalid built-in auth users table")
+});
+pub static BUILTIN_AUTH_CREDENTIALS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_builtin_auth_credentials"
+        .parse()
+        .expect("Invalid built-in auth credentials table")
+});
+pub static BUILTIN_AUTH_SESSIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_builtin_auth_sessions"
+        .parse()
+        .expect("Invalid built-in auth sessions table")
+});
+pub static BUILTIN_AUTH_PASSKEYS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_builtin_auth_passkeys"
+        .parse()
+        .expect("Invalid built-in auth passkeys table")
+});
+pub static BUILTIN_AUTH_PASSKEY_CHALLENGES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_builtin_auth_passkey_challenges"
+        .parse()
+        .expect("Invalid built-in auth passkey challenges table")
+});
+
+static USER_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "userId".parse().expect("Invalid built-in field"));
+static EMAIL_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "email".parse().expect("Invalid built-in field"));
+static KIND_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "kind".parse().expect("Invalid built-in field"));
+static SECRET_HASH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "secretHash".parse().expect("Invalid built-in field"));
+static REFRESH_TOKEN_HASH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "refreshTokenHash".parse().expect("Invalid built-in field"));
+static CREDENTIAL_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "credentialId".parse().expect("Invalid built-in field"));
+static CHALLENGE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "challenge".parse().expect("Invalid built-in field"));
+
+pub static BUILTIN_AUTH_USERS_INDEX_BY_USER_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&BUILTIN_AUTH_USERS_TABLE, "by_user_id"));
+pub static BUILTIN_AUTH_USERS_INDEX_BY_EMAIL: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&BUILTIN_AUTH_USERS_TABLE, "by_email"));
+pub static BUILTIN_AUTH_CREDENTIALS_INDEX_BY_USER_ID_AND_KIND: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&BUILTIN_AUTH_CREDENTIALS_TABLE, "by_user_id_and_kind"));
+pub static BUILTIN_AUTH_CREDENTIALS_INDEX_BY_SECRET_HASH: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&BUILTIN_AUTH_CREDENTIALS_TABLE, "by_secret_hash"));
+pub static BUILTIN_AUTH_SESSIONS_INDEX_BY_REFRESH_TOKEN_HASH: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&BUILTIN_AUTH_SESSIONS_TABLE, "by_refresh_token_hash"));
+pub static BUILTIN_AUTH_PASSKEYS_INDEX_BY_CREDENTIAL_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&BUILTIN_AUTH_PASSKEYS_TABLE, "by_credential_id"));
+pub static BUILTIN_AUTH_PASSKEYS_INDEX_BY_USER_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&BUILTIN_AUTH_PASSKEYS_TABLE, "by_user_id"));
+pub static BUILTIN_AUTH_PASSKEY_CHALLENGES_INDEX_BY_CHALLENGE: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&BUILTIN_AUTH_PASSKEY_CHALLENGES_TABLE, "by_challenge"));
+
+pub struct BuiltinAuthUsersTable;
+impl SystemTable for BuiltinAuthUsersTable {
+    fn table_name(&self) -> &'static TableName {
+        &BUILTIN_AUTH_USERS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: BUILTIN_AUTH_USERS_INDEX_BY_USER_ID.clone(),
+                fields: vec![USER_ID_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+            SystemIndex {
+                name: BUILTIN_AUTH_USERS_INDEX_BY_EMAIL.clone(),
+                fields: vec![EMAIL_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<AuthUser>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct BuiltinAuthCredentialsTable;
+impl SystemTable for BuiltinAuthCredentialsTable {
+    fn table_name(&self) -> &'static TableName {
+        &BUILTIN_AUTH_CREDENTIALS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: BUILTIN_AUTH_CREDENTIALS_INDEX_BY_USER_ID_AND_KIND.clone(),
+                fields: vec![
+                    USER_ID_FIELD.clone(),
+                    KIND_FIELD.clone(),
+                    CREATION_TIME_FIELD_PATH.clone(),
+                ]
+                .try_into()
+                .unwrap(),
+            },
+            SystemIndex {
+                name: BUILTIN_AUTH_CREDENTIALS_INDEX_BY_SECRET_HASH.clone(),
+                fields: vec![SECRET_HASH_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<AuthCredential>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct BuiltinAuthSessionsTable;
+impl SystemTable for BuiltinAuthSessionsTable {
+    fn table_name(&self) -> &'static TableName {
+        &BUILTIN_AUTH_SESSIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: BUILTIN_AUTH_SESSIONS_INDEX_BY_REFRESH_TOKEN_HASH.clone(),
+            fields: vec![
+                REFRESH_TOKEN_HASH_FIELD.clone(),
+                CREATION_TIME_FIELD_PATH.clone(),
+            ]
+            .try_into()
+            .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<AuthSession>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct BuiltinAuthPasskeysTable;
+impl SystemTable for BuiltinAuthPasskeysTable {
+    fn table_name(&self) -> &'static TableName {
+        &BUILTIN_AUTH_PASSKEYS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: BUILTIN_AUTH_PASSKEYS_INDEX_BY_CREDENTIAL_ID.clone(),
+                fields: vec![
+                    CREDENTIAL_ID_FIELD.clone(),
+                    CREATION_TIME_FIELD_PATH.clone(),
+                ]
+                .try_into()
+                .unwrap(),
+            },
+            SystemIndex {
+                name: BUILTIN_AUTH_PASSKEYS_INDEX_BY_USER_ID.clone(),
+                fields: vec![USER_ID_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<AuthPasskey>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct BuiltinAuthPasskeyChallengesTable;
+impl SystemTable for BuiltinAuthPasskeyChallengesTable {
+    fn table_name(&self) -> &'static TableName {
+        &BUILTIN_AUTH_PASSKEY_CHALLENGES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: BUILTIN_AUTH_PASSKEY_CHALLENGES_INDEX_BY_CHALLENGE.clone(),
+            fields: vec![CHALLENGE_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<AuthPasskeyChallenge>::try_from(document).map(|_| ())
+    }
+}
+
+/// Emails are case insensitive in practice, so users are looked up by the
+/// lowercased address.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Random tokens are only stored hashed. Unlike passwords, they have enough
+/// entropy that a fast hash is fine, and it lets us look them up by hash.
+fn hash_token(token: &str) -> String {
+    Sha256::hash(token.as_bytes()).as_hex()
+}
+
+pub struct BuiltinAuthModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> BuiltinAuthModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn random_bytes(&mut self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        self.tx.runtime().rng().fill_bytes(&mut bytes);
+        bytes
+    }
+
+    fn new_token(&mut self) -> String {
+        self.random_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    async fn query_one<T>(
+        &mut self,
+        index_name: &IndexName,
+        range: Vec<(&FieldPath, &str)>,
+    ) -> anyhow::Result<Option<ParsedDocument<T>>>
+    where
+        ParsedDocument<T>: TryFrom<ResolvedDocument, Error = anyhow::Error>,
+    {
+        let range = range
+            .into_iter()
+            .map(|(field, value)| {
+                anyhow::Ok(IndexRangeExpression::Eq(
+                    field.clone(),
+                    ConvexValue::try_from(value)?.into(),
+                ))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let query = Query::index_range(IndexRange {
+            index_name: index_name.clone(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    pub async fn get_user(
+        &mut self,
+        user_id: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<AuthUser>>> {
+        self.query_one(
+            &BUILTIN_AUTH_USERS_INDEX_BY_USER_ID,
+            vec![(&USER_ID_FIELD, user_id)],
+        )
+        .await
+    }
+
+    pub async fn get_user_by_email(
+        &mut self,
+        email: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<AuthUser>>> {
+        self.query_one(
+            &BUILTIN_AUTH_USERS_INDEX_BY_EMAIL,
+            vec![(&EMAIL_FIELD, &normalize_email(email))],
+        )
+        .await
+    }
+
+    pub async fn create_user(
+        &mut self,
+        email: &str,
+        email_verified: bool,
+    ) -> anyhow::Result<AuthUser> {
+        let user = AuthUser {
+            user_id: self.tx.runtime().new_uuid_v4().to_string(),
+            email: normalize_email(email),
+            email_verified,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&BUILTIN_AUTH_USERS_TABLE, user.clone().try_into()?)
+            .await?;
+        Ok(user)
+    }
+
+    pub async fn mark_email_verified(
+        &mut self,
+        user: ParsedDocument<AuthUser>,
+    ) -> anyhow::Result<AuthUser> {
+        let (id, mut user) = user.into_id_and_value();
+        if !user.email_verified {
+            user.email_verified = true;
+            SystemMetadataModel::new_global(self.tx)
+                .replace(id, user.clone().try_into()?)
+                .await?;
+        }
+        Ok(user)
+    }
+
+    pub async fn get_password_hash(&mut self, user_id: &str) -> anyhow::Result<Option<String>> {
+        let credential: Option<ParsedDocument<AuthCredential>> = self
+            .query_one(
+                &BUILTIN_AUTH_CREDENTIALS_INDEX_BY_USER_ID_AND_KIND,
+                vec![
+                    (&USER_ID_FIELD, user_id),
+                    (&KIND_FIELD, AuthCredentialKind::Password.as_str()),
+                ],
+            )
+            .await?;
+        Ok(credential.map(|credential| credential.into_value().secret_hash))
+    }
+
+    /// Set the user's password to the one with `password_hash`, replacing
+    /// any previous password.
+    pub async fn set_password_hash(
+        &mut self,
+        user_id: &str,
+        password_hash: String,
+    ) -> anyhow::Result<()> {
+        let existing: Option<ParsedDocument<AuthCredential>> = self
+            .query_one(
+                &BUILTIN_AUTH_CREDENTIALS_INDEX_BY_USER_ID_AND_KIND,
+                vec![
+                    (&USER_ID_FIELD, user_id),
+                    (&KIND_FIELD, AuthCredentialKind::Password.as_str()),
+                ],
+            )
+            .await?;
+        let credential = AuthCredential {
+            owner: AuthCredentialOwner::User(user_id.to_string()),
+            kind: AuthCredentialKind::Password,
+            secret_hash: password_hash,
+            expires: None,
+        };
+        match existing {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), credential.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&BUILTIN_AUTH_CREDENTIALS_TABLE, credential.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Create a single-use magic link token for the email, which needn't
+    /// belong to a user yet.
+    pub async fn create_magic_link(
+        &mut self,
+        email: &str,
+        expires: UnixTimestamp,
+    ) -> anyhow::Result<String> {
+        let token = self.new_token();
+        let credential = AuthCredential {
+            owner: AuthCredentialOwner::Email(normalize_email(email)),
+            kind: AuthCredentialKind::MagicLink,
+            secret_hash: hash_token(&token),
+            expires: Some(expires),
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&BUILTIN_AUTH_CREDENTIALS_TABLE, credential.try_into()?)
+            .await?;
+        Ok(token)
+    }
+
+    /// Use up the magic link `token`, returning who it was issued to if it's
+    /// valid and hasn't expired.
+    pub async fn consume_magic_link(
+        &mut self,
+        token: &str,
+        now: UnixTimestamp,
+    ) -> anyhow::Result<Option<AuthCredentialOwner>> {
+        let credential: Option<ParsedDocument<AuthCredential>> = self
+            .query_one(
+                &BUILTIN_AUTH_CREDENTIALS_INDEX_BY_SECRET_HASH,
+                vec![(&SECRET_HASH_FIELD, &hash_token(token))],
+            )
+            .await?;
+        let Some(credential) = credential else {
+            return Ok(None);
+        };
+        if credential.kind != AuthCredentialKind::MagicLink {
+            return Ok(None);
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .delete(credential.id())
+            .await?;
+        let credential = credential.into_value();
+        if credential.expires.is_some_and(|expires| expires <= now) {
+            return Ok(None);
+        }
+        Ok(Some(credential.owner))
+    }
+
+    /// Start a session for the user, returning its refresh token.
+    pub async fn create_session(
+        &mut self,
+        user_id: &str,
+        expires: UnixTimestamp,
+    ) -> anyhow::Result<String> {
+        let refresh_token = self.new_token();
+        let session = AuthSession {
+            user_id: user_id.to_string(),
+            refresh_token_hash: hash_token(&refresh_token),
+            expires,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&BUILTIN_AUTH_SESSIONS_TABLE, session.try_into()?)
+            .await?;
+        Ok(refresh_token)
+    }
+
+    async fn get_session(
+        &mut self,
+        refresh_token: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<AuthSession>>> {
+        self.query_one(
+            &BUILTIN_AUTH_SESSIONS_INDEX_BY_REFRESH_TOKEN_HASH,
+            vec![(&REFRESH_TOKEN_HASH_FIELD, &hash_token(refresh_token))],
+        )
+        .await
+    }
+
+    /// Exchange a session's refresh token for a new one, extending the session
+    /// to `expires`. Returns the user ID and new refresh token, or `None` if
+    /// the session doesn't exist or has expired.
+    pub async fn refresh_session(
+        &mut self,
+        refresh_token: &str,
+        now: UnixTimestamp,
+        expires: UnixTimestamp,
+    ) -> anyhow::Result<Option<(String, String)>> {
+        let Some(session) = self.get_session(refresh_token).await? else {
+            return Ok(None);
+        };
+        let (id, mut session) = session.into_id_and_value();
+        if session.expires <= now {
+            SystemMetadataModel::new_global(self.tx).delete(id).await?;
+            return Ok(None);
+        }
+        let refresh_token = self.new_token();
+        session.refresh_token_hash = hash_token(&refresh_token);
+        session.expires = expires;
+        let user_id = session.user_id.clone();
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, session.try_into()?)
+            .await?;
+        Ok(Some((user_id, refresh_token)))
+    }
+
+    /// End the session with `refresh_token`. Ending a session twice is a
+    /// no-op.
+    pub async fn delete_session(&mut self, refresh_token: &str) -> anyhow::Result<()> {
+        if let Some(session) = self.get_session(refresh_token).await? {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(session.id())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Start a WebAuthn ceremony, returning its random challenge. Pass the
+    /// user for registrations and `None` for sign-ins.
+    pub async fn create_passkey_challenge(
+        &mut self,
+        user_id: Option<&str>,
+        expires: UnixTimestamp,
+    ) -> anyhow::Result<Vec<u8>> {
+        let challenge = self.random_bytes().to_vec();
+        let document = AuthPasskeyChallenge {
+            challenge: encode_urlsafe(&challenge),
+            user_id: user_id.map(String::from),
+            expires,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&BUILTIN_AUTH_PASSKEY_CHALLENGES_TABLE, document.try_into()?)
+            .await?;
+        Ok(challenge)
+    }
+
+    /// Use up `challenge`, returning it if it exists and hasn't expired.
+    pub async fn consume_passkey_challenge(
+        &mut self,
+        challenge: &[u8],
+        now: UnixTimestamp,
+    ) -> anyhow::Result<Option<AuthPasskeyChallenge>> {
+        let document: Option<ParsedDocument<AuthPasskeyChallenge>> = self
+            .query_one(
+                &BUILTIN_AUTH_PASSKEY_CHALLENGES_INDEX_BY_CHALLENGE,
+                vec![(&CHALLENGE_FIELD, &encode_urlsafe(challenge))],
+            )
+            .await?;
+        let Some(document) = document else {
+            return Ok(None);
+        };
+        let (id, challenge) = document.into_id_and_value();
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        if challenge.expires <= now {
+            return Ok(None);
+        }
+        Ok(Some(challenge))
+    }
+
+    pub async fn get_passkey(
+        &mut self,
+        credential_id: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<AuthPasskey>>> {
+        self.query_one(
+            &BUILTIN_AUTH_PASSKEYS_INDEX_BY_CREDENTIAL_ID,
+            vec![(&CREDENTIAL_ID_FIELD, credential_id)],
+        )
+        .await
+    }
+
+    pub async fn list_passkeys(&mut self, user_id: &str) -> anyhow::Result<Vec<AuthPasskey>> {
+        let query = Query::index_range(IndexRange {
+            index_name: BUILTIN_AUTH_PASSKEYS_INDEX_BY_USER_ID.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                USER_ID_FIELD.clone(),
+                ConvexValue::try_from(user_id)?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut passkeys = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            passkeys.push(ParsedDocument::<AuthPasskey>::try_from(document)?.into_value());
+        }
+        Ok(passkeys)
+    }
+
+    pub async fn insert_passkey(&mut self, passkey: AuthPasskey) -> anyhow::Result<()> {
+        if self.get_passkey(&passkey.credential_id).await?.is_some() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "PasskeyAlreadyRegistered",
+                "This passkey is already registered"
+            ));
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&BUILTIN_AUTH_PASSKEYS_TABLE, passkey.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_passkey_sign_count(
+        &mut self,
+        passkey: ParsedDocument<AuthPasskey>,
+        sign_count: u32,
+    ) -> anyhow::Result<()> {
+        let (id, mut passkey) = passkey.into_id_and_value();
+        if passkey.sign_count != sign_count {
+            passkey.sign_count = sign_count;
+            SystemMetadataModel::new_global(self.tx)
+                .replace(id, passkey.try_into()?)
+                .await?;
+        }
+        Ok(())
+    }
+}
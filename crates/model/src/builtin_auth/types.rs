@@ -0,0 +1,325 @@
+use common::runtime::UnixTimestamp;
+#[cfg(any(test, feature = "testing"))]
+use proptest::prelude::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A user of the built-in auth subsystem. Their ID tokens have the user ID as
+/// their subject.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct AuthUser {
+    pub user_id: String,
+    /// Normalized to lowercase.
+    pub email: String,
+    /// Whether the user has signed in with a magic link sent to their email.
+    pub email_verified: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedAuthUser {
+    user_id: String,
+    email: String,
+    email_verified: bool,
+}
+
+impl From<AuthUser> for SerializedAuthUser {
+    fn from(value: AuthUser) -> Self {
+        Self {
+            user_id: value.user_id,
+            email: value.email,
+            email_verified: value.email_verified,
+        }
+    }
+}
+
+impl From<SerializedAuthUser> for AuthUser {
+    fn from(value: SerializedAuthUser) -> Self {
+        Self {
+            user_id: value.user_id,
+            email: value.email,
+            email_verified: value.email_verified,
+        }
+    }
+}
+
+codegen_convex_serialization!(AuthUser, SerializedAuthUser);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum AuthCredentialKind {
+    Password,
+    MagicLink,
+}
+
+impl AuthCredentialKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthCredentialKind::Password => "password",
+            AuthCredentialKind::MagicLink => "magicLink",
+        }
+    }
+}
+
+impl TryFrom<String> for AuthCredentialKind {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> anyhow::Result<Self> {
+        match &value[..] {
+            "password" => Ok(AuthCredentialKind::Password),
+            "magicLink" => Ok(AuthCredentialKind::MagicLink),
+            _ => anyhow::bail!("Invalid auth credential kind {value}"),
+        }
+    }
+}
+
+/// Who can sign in with a credential.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum AuthCredentialOwner {
+    User(String),
+    /// Magic links are sent to an email, and the user is only created once
+    /// one is redeemed.
+    Email(String),
+}
+
+/// A secret a user can sign in with: their password, or a pending magic link.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct AuthCredential {
+    pub owner: AuthCredentialOwner,
+    pub kind: AuthCredentialKind,
+    /// An Argon2id hash for passwords, and a SHA-256 hash for magic link
+    /// tokens, which are random and only need to be looked up.
+    pub secret_hash: String,
+    /// Magic links expire, passwords don't.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::option::of((0..1u64 << 42).prop_map(UnixTimestamp::from_millis))"
+        )
+    )]
+    pub expires: Option<UnixTimestamp>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedAuthCredential {
+    user_id: Option<String>,
+    email: Option<String>,
+    kind: String,
+    secret_hash: String,
+    expires_ms: Option<i64>,
+}
+
+impl TryFrom<AuthCredential> for SerializedAuthCredential {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AuthCredential) -> anyhow::Result<Self> {
+        let (user_id, email) = match value.owner {
+            AuthCredentialOwner::User(user_id) => (Some(user_id), None),
+            AuthCredentialOwner::Email(email) => (None, Some(email)),
+        };
+        Ok(Self {
+            user_id,
+            email,
+            kind: value.kind.as_str().to_string(),
+            secret_hash: value.secret_hash,
+            expires_ms: value
+                .expires
+                .map(|expires| anyhow::Ok(expires.as_ms_since_epoch()?.try_into()?))
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedAuthCredential> for AuthCredential {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedAuthCredential) -> anyhow::Result<Self> {
+        let owner = match (value.user_id, value.email) {
+            (Some(user_id), None) => AuthCredentialOwner::User(user_id),
+            (None, Some(email)) => AuthCredentialOwner::Email(email),
+            _ => anyhow::bail!("Credential must have exactly one of userId and email"),
+        };
+        Ok(Self {
+            owner,
+            kind: value.kind.try_into()?,
+            secret_hash: value.secret_hash,
+            expires: value
+                .expires_ms
+                .map(|expires_ms| anyhow::Ok(UnixTimestamp::from_millis(expires_ms.try_into()?)))
+                .transpose()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(AuthCredential, SerializedAuthCredential);
+
+/// A signed-in session. Clients exchange its refresh token for new ID tokens,
+/// and each exchange rotates the refresh token.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct AuthSession {
+    pub user_id: String,
+    /// SHA-256 hash of the current refresh token.
+    pub refresh_token_hash: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "(0..1u64 << 42).prop_map(UnixTimestamp::from_millis)")
+    )]
+    pub expires: UnixTimestamp,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedAuthSession {
+    user_id: String,
+    refresh_token_hash: String,
+    expires_ms: i64,
+}
+
+impl TryFrom<AuthSession> for SerializedAuthSession {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AuthSession) -> anyhow::Result<Self> {
+        Ok(Self {
+            user_id: value.user_id,
+            refresh_token_hash: value.refresh_token_hash,
+            expires_ms: value.expires.as_ms_since_epoch()?.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedAuthSession> for AuthSession {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedAuthSession) -> anyhow::Result<Self> {
+        Ok(Self {
+            user_id: value.user_id,
+            refresh_token_hash: value.refresh_token_hash,
+            expires: UnixTimestamp::from_millis(value.expires_ms.try_into()?),
+        })
+    }
+}
+
+codegen_convex_serialization!(AuthSession, SerializedAuthSession);
+
+/// A WebAuthn credential a user registered to sign in with.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct AuthPasskey {
+    pub user_id: String,
+    /// Base64url encoded, as clients send it.
+    pub credential_id: String,
+    /// Base64url encoded uncompressed P-256 public key.
+    pub public_key: String,
+    /// The authenticator's signature counter as of the last sign-in, used to
+    /// detect cloned credentials.
+    pub sign_count: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedAuthPasskey {
+    user_id: String,
+    credential_id: String,
+    public_key: String,
+    sign_count: i64,
+}
+
+impl From<AuthPasskey> for SerializedAuthPasskey {
+    fn from(value: AuthPasskey) -> Self {
+        Self {
+            user_id: value.user_id,
+            credential_id: value.credential_id,
+            public_key: value.public_key,
+            sign_count: value.sign_count.into(),
+        }
+    }
+}
+
+impl TryFrom<SerializedAuthPasskey> for AuthPasskey {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedAuthPasskey) -> anyhow::Result<Self> {
+        Ok(Self {
+            user_id: value.user_id,
+            credential_id: value.credential_id,
+            public_key: value.public_key,
+            sign_count: value.sign_count.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(AuthPasskey, SerializedAuthPasskey);
+
+/// A challenge for a pending WebAuthn ceremony. Each challenge can only be
+/// answered once.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct AuthPasskeyChallenge {
+    /// Base64url encoded random bytes.
+    pub challenge: String,
+    /// The user registering a passkey, or `None` for a sign-in, where we
+    /// don't know who the user is until they've answered.
+    pub user_id: Option<String>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "(0..1u64 << 42).prop_map(UnixTimestamp::from_millis)")
+    )]
+    pub expires: UnixTimestamp,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedAuthPasskeyChallenge {
+    challenge: String,
+    user_id: Option<String>,
+    expires_ms: i64,
+}
+
+impl TryFrom<AuthPasskeyChallenge> for SerializedAuthPasskeyChallenge {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AuthPasskeyChallenge) -> anyhow::Result<Self> {
+        Ok(Self {
+            challenge: value.challenge,
+            user_id: value.user_id,
+            expires_ms: value.expires.as_ms_since_epoch()?.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedAuthPasskeyChallenge> for AuthPasskeyChallenge {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedAuthPasskeyChallenge) -> anyhow::Result<Self> {
+        Ok(Self {
+            challenge: value.challenge,
+            user_id: value.user_id,
+            expires: UnixTimestamp::from_millis(value.expires_ms.try_into()?),
+        })
+    }
+}
+
+codegen_convex_serialization!(AuthPasskeyChallenge, SerializedAuthPasskeyChallenge);
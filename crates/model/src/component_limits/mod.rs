@@ -0,0 +1,255 @@
+//! Per-component overrides for execution limits. Components share the
+//! deployment-wide knobs by default; an instance admin can tighten the
+//! documents read, user timeout, heap and scheduling limits for a single
+//! component, and cap how much file storage it uses.
+
+use std::{
+    sync::LazyLock,
+    time::Duration,
+};
+
+use common::{
+    components::ComponentId,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    knobs::{
+        DATABASE_UDF_USER_TIMEOUT,
+        ISOLATE_MAX_USER_HEAP_SIZE,
+        TRANSACTION_MAX_NUM_SCHEDULED,
+        TRANSACTION_MAX_READ_SIZE_ROWS,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::ComponentLimits;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static COMPONENT_LIMITS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_component_limits"
+        .parse()
+        .expect("Invalid built-in component limits table")
+});
+
+pub static COMPONENT_LIMITS_BY_COMPONENT: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&COMPONENT_LIMITS_TABLE, "by_component"));
+
+/// Heap limits below this would fail functions while the runtime is still
+/// setting up.
+const MIN_HEAP_SIZE: u64 = 1 << 20;
+
+static COMPONENT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "component".parse().expect("invalid component field"));
+
+pub struct ComponentLimitsTable;
+impl SystemTable for ComponentLimitsTable {
+    fn table_name(&self) -> &'static TableName {
+        &COMPONENT_LIMITS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: COMPONENT_LIMITS_BY_COMPONENT.clone(),
+            fields: vec![COMPONENT_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ComponentLimits>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct ComponentLimitsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ComponentLimitsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn get(
+        &mut self,
+        component: ComponentId,
+    ) -> anyhow::Result<Option<ParsedDocument<ComponentLimits>>> {
+        let serialized_component = match component.serialize_to_string() {
+            Some(s) => ConvexValue::String(s.try_into()?),
+            None => ConvexValue::Null,
+        };
+        let query = Query::index_range(IndexRange {
+            index_name: COMPONENT_LIMITS_BY_COMPONENT.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                COMPONENT_FIELD.clone(),
+                serialized_component.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// The limits in effect for the component, if it has any overrides.
+    pub async fn get_limits(
+        &mut self,
+        component: ComponentId,
+    ) -> anyhow::Result<Option<ComponentLimits>> {
+        Ok(self.get(component).await?.map(ParsedDocument::into_value))
+    }
+
+    /// Replace the component's overrides. Setting limits with no fields
+    /// populated removes the overrides entirely.
+    pub async fn set(&mut self, limits: ComponentLimits) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("set_component_limits"));
+        }
+        Self::validate(&limits)?;
+        if limits.is_empty() {
+            self.clear(limits.component).await?;
+            return Ok(());
+        }
+        match self.get(limits.component).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), limits.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&COMPONENT_LIMITS_TABLE, limits.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Remove the component's overrides, returning the removed limits.
+    pub async fn clear(
+        &mut self,
+        component: ComponentId,
+    ) -> anyhow::Result<Option<ComponentLimits>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("clear_component_limits"));
+        }
+        let Some(existing) = self.get(component).await? else {
+            return Ok(None);
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(Some(existing.into_value()))
+    }
+
+    fn validate(limits: &ComponentLimits) -> anyhow::Result<()> {
+        let max_documents_read = *TRANSACTION_MAX_READ_SIZE_ROWS as u64;
+        if let Some(limit) = limits.max_documents_read
+            && !(1..=max_documents_read).contains(&limit)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidComponentLimits",
+                format!("maxDocumentsRead must be between 1 and {max_documents_read}, got {limit}"),
+            ));
+        }
+        if let Some(timeout) = limits.function_timeout
+            && (timeout.is_zero() || timeout > *DATABASE_UDF_USER_TIMEOUT)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidComponentLimits",
+                format!(
+                    "functionTimeoutMs must be between 1 and {}, got {}",
+                    DATABASE_UDF_USER_TIMEOUT.as_millis(),
+                    timeout.as_millis(),
+                ),
+            ));
+        }
+        let max_scheduled = *TRANSACTION_MAX_NUM_SCHEDULED as u64;
+        if let Some(limit) = limits.max_scheduled_functions
+            && limit > max_scheduled
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidComponentLimits",
+                format!("maxScheduledFunctions must be at most {max_scheduled}, got {limit}"),
+            ));
+        }
+        let max_heap_size = *ISOLATE_MAX_USER_HEAP_SIZE as u64;
+        if let Some(limit) = limits.max_heap_size
+            && !(MIN_HEAP_SIZE..=max_heap_size).contains(&limit)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidComponentLimits",
+                format!(
+                    "maxHeapSizeBytes must be between {MIN_HEAP_SIZE} and {max_heap_size}, got \
+                     {limit}"
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Limits resolved for a single function execution: the component's
+/// overrides where set, and the global knobs otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EffectiveComponentLimits {
+    pub max_documents_read: Option<u64>,
+    pub function_timeout: Duration,
+    pub max_heap_size: Option<u64>,
+}
+
+impl Default for EffectiveComponentLimits {
+    fn default() -> Self {
+        Self {
+            max_documents_read: None,
+            function_timeout: *DATABASE_UDF_USER_TIMEOUT,
+            max_heap_size: None,
+        }
+    }
+}
+
+impl From<Option<ComponentLimits>> for EffectiveComponentLimits {
+    fn from(limits: Option<ComponentLimits>) -> Self {
+        let Some(limits) = limits else {
+            return Self::default();
+        };
+        Self {
+            max_documents_read: limits.max_documents_read,
+            function_timeout: limits
+                .function_timeout
+                .unwrap_or(*DATABASE_UDF_USER_TIMEOUT),
+            max_heap_size: limits.max_heap_size,
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use common::components::ComponentId;
+#[cfg(any(test, feature = "testing"))]
+use proptest::prelude::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Execution limits for a single component that override the deployment-wide
+/// knobs. Unset fields fall back to the global limit, and overrides can only
+/// tighten a limit, never raise it. Storage quotas have no deployment-wide
+/// equivalent, so unset quotas leave the component's storage unlimited.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct ComponentLimits {
+    pub component: ComponentId,
+    /// Maximum number of documents a single query or mutation may read.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..1_000_000u64)")
+    )]
+    pub max_documents_read: Option<u64>,
+    /// User time allowed for a single query or mutation.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::option::of((0..1_000_000u64).prop_map(Duration::from_millis))"
+        )
+    )]
+    pub function_timeout: Option<Duration>,
+    /// Maximum number of functions a single mutation may schedule.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..1_000_000u64)")
+    )]
+    pub max_scheduled_functions: Option<u64>,
+    /// Maximum JavaScript heap, in bytes, a single query or mutation may use.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..1_000_000_000u64)")
+    )]
+    pub max_heap_size: Option<u64>,
+    /// Maximum total size, in bytes, of the component's stored files.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..1_000_000_000u64)")
+    )]
+    pub max_storage_bytes: Option<u64>,
+    /// Maximum number of files the component may store.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..1_000_000u64)")
+    )]
+    pub max_storage_files: Option<u64>,
+}
+
+impl ComponentLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_documents_read.is_none()
+            && self.function_timeout.is_none()
+            && self.max_scheduled_functions.is_none()
+            && self.max_heap_size.is_none()
+            && self.max_storage_bytes.is_none()
+            && self.max_storage_files.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedComponentLimits {
+    component: Option<String>,
+    max_documents_read: Option<i64>,
+    function_timeout_ms: Option<i64>,
+    max_scheduled_functions: Option<i64>,
+    max_heap_size_bytes: Option<i64>,
+    max_storage_bytes: Option<i64>,
+    max_storage_files: Option<i64>,
+}
+
+impl TryFrom<ComponentLimits> for SerializedComponentLimits {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ComponentLimits) -> anyhow::Result<Self> {
+        Ok(Self {
+            component: value.component.serialize_to_string(),
+            max_documents_read: value.max_documents_read.map(i64::try_from).transpose()?,
+            function_timeout_ms: value
+                .function_timeout
+                .map(|timeout| i64::try_from(timeout.as_millis()))
+                .transpose()?,
+            max_scheduled_functions: value
+                .max_scheduled_functions
+                .map(i64::try_from)
+                .transpose()?,
+            max_heap_size_bytes: value.max_heap_size.map(i64::try_from).transpose()?,
+            max_storage_bytes: value.max_storage_bytes.map(i64::try_from).transpose()?,
+            max_storage_files: value.max_storage_files.map(i64::try_from).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedComponentLimits> for ComponentLimits {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedComponentLimits) -> anyhow::Result<Self> {
+        Ok(Self {
+            component: ComponentId::deserialize_from_string(value.component.as_deref())?,
+            max_documents_read: value.max_documents_read.map(u64::try_from).transpose()?,
+            function_timeout: value
+                .function_timeout_ms
+                .map(|ms| anyhow::Ok(Duration::from_millis(ms.try_into()?)))
+                .transpose()?,
+            max_scheduled_functions: value
+                .max_scheduled_functions
+                .map(u64::try_from)
+                .transpose()?,
+            max_heap_size: value.max_heap_size_bytes.map(u64::try_from).transpose()?,
+            max_storage_bytes: value.max_storage_bytes.map(u64::try_from).transpose()?,
+            max_storage_files: value.max_storage_files.map(u64::try_from).transpose()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(ComponentLimits, SerializedComponentLimits);
@@ -0,0 +1,214 @@
+//! Past versions of each component's modules and UDF config, recorded on every
+//! push so a component can be rolled back without pushing its old source
+//! again.
+
+use std::sync::LazyLock;
+
+use common::{
+    components::ComponentId,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    knobs::COMPONENT_VERSIONS_TO_KEEP,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::{
+    size_report::BundleSizeReport,
+    types::ComponentVersion,
+};
+use crate::{
+    modules::ModuleModel,
+    source_packages::types::SourcePackageId,
+    udf_config::types::UdfConfig,
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod size_report;
+pub mod types;
+
+pub static COMPONENT_VERSIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_component_versions"
+        .parse()
+        .expect("Invalid built-in component versions table")
+});
+
+pub static COMPONENT_VERSIONS_BY_COMPONENT_AND_VERSION: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&COMPONENT_VERSIONS_TABLE, "by_component_and_version"));
+
+static COMPONENT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "component".parse().expect("invalid component field"));
+static VERSION_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "version".parse().expect("invalid version field"));
+
+pub struct ComponentVersionsTable;
+impl SystemTable for ComponentVersionsTable {
+    fn table_name(&self) -> &'static TableName {
+        &COMPONENT_VERSIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: COMPONENT_VERSIONS_BY_COMPONENT_AND_VERSION.clone(),
+            fields: vec![
+                COMPONENT_FIELD.clone(),
+                VERSION_FIELD.clone(),
+                CREATION_TIME_FIELD_PATH.clone(),
+            ]
+            .try_into()
+            .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ComponentVersion>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct ComponentVersionsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ComponentVersionsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Record the component's current modules along with the source package
+    /// and UDF config they were pushed with, and delete versions beyond
+    /// `COMPONENT_VERSIONS_TO_KEEP`. Returns the new version number.
+    pub async fn record(
+        &mut self,
+        component: ComponentId,
+        source_package_id: SourcePackageId,
+        udf_config: UdfConfig,
+        restored_from: Option<u64>,
+    ) -> anyhow::Result<u64> {
+        let modules = ModuleModel::new(self.tx)
+            .get_application_metadata(component)
+            .await?
+            .into_iter()
+            .map(ParsedDocument::into_value)
+            .collect();
+        let mut versions = self.list(component).await?;
+        let version = versions.first().map_or(1, |latest| latest.version + 1);
+        SystemMetadataModel::new_global(self.tx)
+            .insert(
+                &COMPONENT_VERSIONS_TABLE,
+                ComponentVersion {
+                    component,
+                    version,
+                    source_package_id,
+                    modules,
+                    udf_config,
+                    restored_from,
+                }
+                .try_into()?,
+            )
+            .await?;
+
+        // `versions` doesn't include the one we just inserted.
+        let num_to_keep = COMPONENT_VERSIONS_TO_KEEP.saturating_sub(1);
+        if versions.len() > num_to_keep {
+            for expired in versions.split_off(num_to_keep) {
+                SystemMetadataModel::new_global(self.tx)
+                    .delete(expired.id())
+                    .await?;
+            }
+        }
+        Ok(version)
+    }
+
+    /// All recorded versions of the component, newest first.
+    pub async fn list(
+        &mut self,
+        component: ComponentId,
+    ) -> anyhow::Result<Vec<ParsedDocument<ComponentVersion>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: COMPONENT_VERSIONS_BY_COMPONENT_AND_VERSION.clone(),
+            range: vec![component_range(component)?],
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut versions = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            versions.push(doc.try_into()?);
+        }
+        Ok(versions)
+    }
+
+    pub async fn get(
+        &mut self,
+        component: ComponentId,
+        version: u64,
+    ) -> anyhow::Result<Option<ParsedDocument<ComponentVersion>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: COMPONENT_VERSIONS_BY_COMPONENT_AND_VERSION.clone(),
+            range: vec![
+                component_range(component)?,
+                IndexRangeExpression::Eq(
+                    VERSION_FIELD.clone(),
+                    ConvexValue::Int64(version.try_into()?).into(),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// Bundle sizes for a version of the component, or its latest version if
+    /// `version` is `None`, with deltas from the version before it.
+    pub async fn bundle_size_report(
+        &mut self,
+        component: ComponentId,
+        version: Option<u64>,
+    ) -> anyhow::Result<Option<BundleSizeReport>> {
+        let versions = self.list(component).await?;
+        let Some(index) = versions
+            .iter()
+            .position(|v| version.is_none_or(|version| v.version == version))
+        else {
+            return Ok(None);
+        };
+        let previous = versions.get(index + 1).map(|v| &**v);
+        Ok(Some(BundleSizeReport::new(&versions[index], previous)?))
+    }
+}
+
+fn component_range(component: ComponentId) -> anyhow::Result<IndexRangeExpression> {
+    let serialized_component = match component.serialize_to_string() {
+        Some(s) => ConvexValue::String(s.try_into()?),
+        None => ConvexValue::Null,
+    };
+    Ok(IndexRangeExpression::Eq(
+        COMPONENT_FIELD.clone(),
+        serialized_component.into(),
+    ))
+}
@@ -0,0 +1,123 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+use common::types::ModuleEnvironment;
+use sync_types::CanonicalizedModulePath;
+
+use super::types::ComponentVersion;
+use crate::modules::types::ModuleBundleStats;
+
+/// Bundle sizes and dependencies of a component version's modules, compared
+/// with the version before it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BundleSizeReport {
+    pub version: u64,
+    /// `None` if this is the component's first version or the previous one
+    /// has been pruned.
+    pub previous_version: Option<u64>,
+    pub total_source_size: u64,
+    pub total_source_map_size: u64,
+    pub total_source_size_delta: Option<i64>,
+    pub modules: Vec<ModuleSizeReport>,
+    pub removed_modules: Vec<CanonicalizedModulePath>,
+    pub added_dependencies: BTreeSet<String>,
+    pub removed_dependencies: BTreeSet<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModuleSizeReport {
+    pub path: CanonicalizedModulePath,
+    pub environment: ModuleEnvironment,
+    /// `None` for modules pushed before we recorded bundle stats.
+    pub bundle_stats: Option<ModuleBundleStats>,
+    /// `None` if the module is new in this version or either version is
+    /// missing its stats.
+    pub source_size_delta: Option<i64>,
+}
+
+impl BundleSizeReport {
+    pub fn new(
+        current: &ComponentVersion,
+        previous: Option<&ComponentVersion>,
+    ) -> anyhow::Result<Self> {
+        let previous_stats: BTreeMap<_, _> = previous
+            .into_iter()
+            .flat_map(|previous| &previous.modules)
+            .map(|module| (&module.path, module.bundle_stats.as_ref()))
+            .collect();
+        let mut modules = vec![];
+        for module in &current.modules {
+            let source_size_delta = match (
+                &module.bundle_stats,
+                previous_stats.get(&module.path).copied().flatten(),
+            ) {
+                (Some(current), Some(previous)) => {
+                    Some(i64::try_from(current.source_size)? - i64::try_from(previous.source_size)?)
+                },
+                _ => None,
+            };
+            modules.push(ModuleSizeReport {
+                path: module.path.clone(),
+                environment: module.environment,
+                bundle_stats: module.bundle_stats.clone(),
+                source_size_delta,
+            });
+        }
+        let current_paths: BTreeSet<_> = current.modules.iter().map(|m| &m.path).collect();
+        let removed_modules = previous_stats
+            .keys()
+            .filter(|path| !current_paths.contains(*path))
+            .map(|path| (*path).clone())
+            .collect();
+
+        let total_source_size = total_size(current, |stats| Some(stats.source_size));
+        let total_source_map_size = total_size(current, |stats| stats.source_map_size);
+        let total_source_size_delta = previous
+            .map(|previous| {
+                anyhow::Ok(
+                    i64::try_from(total_source_size)?
+                        - i64::try_from(total_size(previous, |stats| Some(stats.source_size)))?,
+                )
+            })
+            .transpose()?;
+
+        let current_dependencies = dependencies(Some(current));
+        let previous_dependencies = dependencies(previous);
+        Ok(Self {
+            version: current.version,
+            previous_version: previous.map(|previous| previous.version),
+            total_source_size,
+            total_source_map_size,
+            total_source_size_delta,
+            modules,
+            removed_modules,
+            added_dependencies: current_dependencies
+                .difference(&previous_dependencies)
+                .cloned()
+                .collect(),
+            removed_dependencies: previous_dependencies
+                .difference(&current_dependencies)
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+fn total_size(version: &ComponentVersion, size: impl Fn(&ModuleBundleStats) -> Option<u64>) -> u64 {
+    version
+        .modules
+        .iter()
+        .filter_map(|module| module.bundle_stats.as_ref().and_then(&size))
+        .sum()
+}
+
+fn dependencies(version: Option<&ComponentVersion>) -> BTreeSet<String> {
+    version
+        .into_iter()
+        .flat_map(|version| &version.modules)
+        .filter_map(|module| module.bundle_stats.as_ref())
+        .flat_map(|stats| stats.external_dependencies.iter().cloned())
+        .collect()
+}
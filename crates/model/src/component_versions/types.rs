@@ -0,0 +1,145 @@
+use common::{
+    components::ComponentId,
+    runtime::UnixTimestamp,
+};
+#[cfg(any(test, feature = "testing"))]
+use proptest::prelude::*;
+use semver::Version;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    DeveloperDocumentId,
+};
+
+use crate::{
+    modules::types::{
+        ModuleMetadata,
+        SerializedModuleMetadata,
+    },
+    source_packages::types::SourcePackageId,
+    udf_config::types::UdfConfig,
+};
+
+/// A component's modules and UDF config as of a push. Module sources live in
+/// the source package, so restoring the module metadata is enough to restore
+/// the code.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct ComponentVersion {
+    pub component: ComponentId,
+    /// Increases by one on every push or rollback of the component.
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0..1_000_000u64"))]
+    pub version: u64,
+    pub source_package_id: SourcePackageId,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "prop::collection::vec(any::<ModuleMetadata>(), 0..4)")
+    )]
+    pub modules: Vec<ModuleMetadata>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "arbitrary_udf_config()")
+    )]
+    pub udf_config: UdfConfig,
+    /// Set if this version was created by rolling back to an earlier one.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "prop::option::of(0..1_000_000u64)")
+    )]
+    pub restored_from: Option<u64>,
+}
+
+/// `UdfConfig`'s own strategy generates timestamps that don't fit in an `i64`
+/// of nanoseconds.
+#[cfg(any(test, feature = "testing"))]
+fn arbitrary_udf_config() -> impl Strategy<Value = UdfConfig> {
+    any::<([u8; 32], i64)>().prop_map(|(import_phase_rng_seed, nanos)| UdfConfig {
+        server_version: Version::new(1, 0, 0),
+        import_phase_rng_seed,
+        import_phase_unix_timestamp: UnixTimestamp::from_nanos(nanos.unsigned_abs()),
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedUdfConfig {
+    server_version: String,
+    #[serde(with = "serde_bytes")]
+    import_phase_rng_seed: Vec<u8>,
+    import_phase_unix_timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedComponentVersion {
+    component: Option<String>,
+    version: i64,
+    source_package_id: String,
+    modules: Vec<SerializedModuleMetadata>,
+    udf_config: SerializedUdfConfig,
+    restored_from: Option<i64>,
+}
+
+impl TryFrom<ComponentVersion> for SerializedComponentVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ComponentVersion) -> anyhow::Result<Self> {
+        let udf_config = value.udf_config;
+        Ok(Self {
+            component: value.component.serialize_to_string(),
+            version: value.version.try_into()?,
+            source_package_id: DeveloperDocumentId::from(value.source_package_id).to_string(),
+            modules: value
+                .modules
+                .into_iter()
+                .map(SerializedModuleMetadata::try_from)
+                .collect::<anyhow::Result<_>>()?,
+            udf_config: SerializedUdfConfig {
+                server_version: udf_config.server_version.to_string(),
+                import_phase_rng_seed: udf_config.import_phase_rng_seed.to_vec(),
+                import_phase_unix_timestamp: udf_config
+                    .import_phase_unix_timestamp
+                    .as_nanos()
+                    .try_into()?,
+            },
+            restored_from: value.restored_from.map(i64::try_from).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedComponentVersion> for ComponentVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedComponentVersion) -> anyhow::Result<Self> {
+        let udf_config = value.udf_config;
+        let Ok(import_phase_rng_seed) = udf_config.import_phase_rng_seed.try_into() else {
+            anyhow::bail!("importPhaseRngSeed must be 32 bytes");
+        };
+        Ok(Self {
+            component: ComponentId::deserialize_from_string(value.component.as_deref())?,
+            version: value.version.try_into()?,
+            source_package_id: DeveloperDocumentId::decode(&value.source_package_id)?.into(),
+            modules: value
+                .modules
+                .into_iter()
+                .map(ModuleMetadata::try_from)
+                .collect::<anyhow::Result<_>>()?,
+            udf_config: UdfConfig {
+                server_version: Version::parse(&udf_config.server_version)?,
+                import_phase_rng_seed,
+                import_phase_unix_timestamp: UnixTimestamp::from_nanos(
+                    udf_config.import_phase_unix_timestamp.try_into()?,
+                ),
+            },
+            restored_from: value.restored_from.map(u64::try_from).transpose()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(ComponentVersion, SerializedComponentVersion);
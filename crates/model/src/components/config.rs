@@ -7,6 +7,7 @@ use common::{
             definition::{
                 ComponentDefinitionMetadata,
                 ComponentDefinitionType,
+                ComponentLifecycle,
             },
             ComponentMetadata,
             ComponentState,
@@ -58,6 +59,7 @@ use super::{
 };
 use crate::{
     component_system_tables,
+    component_versions::ComponentVersionsModel,
     config::types::{
         CronDiff,
         ModuleConfig,
@@ -434,12 +436,14 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                     let component_type = match parent_and_name {
                         None => {
                             anyhow::ensure!(new_node.args.is_empty());
+                            anyhow::ensure!(new_node.grants.is_empty());
                             ComponentType::App
                         },
                         Some((parent, name)) => ComponentType::ChildComponent {
                             parent,
                             name,
                             args: new_node.args.clone(),
+                            grants: new_node.grants.clone(),
                         },
                     };
                     Ok(ComponentMetadata {
@@ -541,6 +545,9 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                 modules.analyze_results.clone(),
             )
             .await?;
+        ComponentVersionsModel::new(self.tx)
+            .record(component_id, source_package_id, udf_config.clone(), None)
+            .await?;
         let cron_diff = CronModel::new(self.tx, component_id)
             .apply(&modules.analyze_results)
             .await?;
@@ -608,6 +615,9 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                 modules.analyze_results.clone(),
             )
             .await?;
+        ComponentVersionsModel::new(self.tx)
+            .record(component_id, source_package_id, udf_config.clone(), None)
+            .await?;
         let cron_diff = CronModel::new(self.tx, component_id)
             .apply(&modules.analyze_results)
             .await?;
@@ -644,6 +654,90 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
         ))
     }
 
+    /// Restore the modules and UDF config the component had at `version`,
+    /// recording the result as a new version. The component's schema, indexes
+    /// and children are left as they are.
+    #[fastrace::trace]
+    pub async fn rollback_component(
+        &mut self,
+        component_id: ComponentId,
+        version: u64,
+    ) -> anyhow::Result<(u64, ComponentDiff)> {
+        // Deployments that haven't pushed with components yet don't have a root
+        // component document, but can still roll back the root's modules.
+        match BootstrapComponentsModel::new(self.tx)
+            .load_component(component_id)
+            .await?
+        {
+            None if !component_id.is_root() => {
+                anyhow::bail!(ErrorMetadata::not_found(
+                    "ComponentNotFound",
+                    format!("Component with ID {:?} not found", component_id)
+                ));
+            },
+            Some(existing) if existing.state == ComponentState::Unmounted => {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "UnmountedComponent",
+                    "Unmounted components can't be rolled back"
+                ));
+            },
+            _ => {},
+        }
+        let Some(component_version) = ComponentVersionsModel::new(self.tx)
+            .get(component_id, version)
+            .await?
+        else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "ComponentVersionNotFound",
+                format!(
+                    "Version {version} of component {:?} not found",
+                    component_id
+                )
+            ));
+        };
+        let component_version = component_version.into_value();
+        let analyze_results: BTreeMap<_, _> = component_version
+            .modules
+            .iter()
+            .filter_map(|module| {
+                let analyze_result = module.analyze_result.clone()?;
+                Some((module.path.clone(), analyze_result))
+            })
+            .collect();
+
+        let udf_config_diff = UdfConfigModel::new(self.tx, component_id.into())
+            .set(component_version.udf_config.clone())
+            .await?;
+        let module_diff = ModuleModel::new(self.tx)
+            .restore(component_id, component_version.modules)
+            .await?;
+        let cron_diff = CronModel::new(self.tx, component_id)
+            .apply(&analyze_results)
+            .await?;
+        FunctionHandlesModel::new(self.tx)
+            .apply_config_diff(component_id, Some(&analyze_results))
+            .await?;
+        let new_version = ComponentVersionsModel::new(self.tx)
+            .record(
+                component_id,
+                component_version.source_package_id,
+                component_version.udf_config,
+                Some(version),
+            )
+            .await?;
+        Ok((
+            new_version,
+            ComponentDiff {
+                diff_type: ComponentDiffType::Modify,
+                module_diff,
+                udf_config_diff,
+                cron_diff,
+                index_diff: AuditLogIndexDiff::default(),
+                schema_diff: None,
+            },
+        ))
+    }
+
     #[fastrace::trace]
     async fn unmount_component(
         &mut self,
@@ -681,8 +775,13 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
         ))
     }
 
+    /// Deletes an unmounted component, its descendants and all of their
+    /// tables, returning the ids of the deleted components.
     #[fastrace::trace]
-    pub async fn delete_component(&mut self, component_id: ComponentId) -> anyhow::Result<()> {
+    pub async fn delete_component(
+        &mut self,
+        component_id: ComponentId,
+    ) -> anyhow::Result<Vec<ComponentId>> {
         if component_id.is_root() {
             anyhow::bail!("Cannot delete root component");
         }
@@ -714,6 +813,7 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
         }
 
         // Delete the components we found.
+        let mut deleted = vec![];
         for component_id in all_ids {
             SystemMetadataModel::new_global(self.tx)
                 .delete(component_id)
@@ -734,9 +834,10 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                     .delete_table(namespace, table_name.clone())
                     .await?;
             }
+            deleted.push(ComponentId::from(namespace));
         }
 
-        Ok(())
+        Ok(deleted)
     }
 
     pub async fn disable_components(&mut self) -> anyhow::Result<()> {
@@ -767,6 +868,7 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                             child_components: Vec::new(),
                             http_mounts: BTreeMap::new(),
                             exports: BTreeMap::new(),
+                            lifecycle: ComponentLifecycle::default(),
                         },
                     )
                     .await?;
@@ -1,6 +1,12 @@
-use std::collections::{
-    BTreeMap,
-    BTreeSet,
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    fmt::{
+        self,
+        Display,
+    },
 };
 
 use async_recursion::async_recursion;
@@ -12,6 +18,7 @@ use common::{
         ComponentDefinitionType,
         ComponentExport,
         HttpMountPath,
+        TableAccess,
     },
     components::{
         CanonicalizedComponentFunctionPath,
@@ -21,13 +28,17 @@ use common::{
         Reference,
         Resource,
     },
-    types::HttpActionRoute,
+    types::{
+        HttpActionRoute,
+        UdfType,
+    },
 };
 use errors::ErrorMetadata;
 use sync_types::path::PathComponent;
 use value::{
     identifier::Identifier,
     TableMapping,
+    TableName,
     TableNamespace,
 };
 
@@ -46,11 +57,29 @@ pub struct CheckedComponent {
     pub component_path: ComponentPath,
 
     pub args: BTreeMap<Identifier, Resource>,
+    /// Tables of the parent component this component may access.
+    pub grants: BTreeMap<TableName, TableAccess>,
     pub child_components: BTreeMap<ComponentName, CheckedComponent>,
     pub http_routes: CheckedHttpRoutes,
     pub exports: BTreeMap<PathComponent, ResourceTree>,
 }
 
+impl CheckedComponent {
+    /// Conflicting HTTP routes in this component and all of its descendants.
+    pub fn http_route_conflicts(&self) -> Vec<(ComponentPath, HttpRouteConflict)> {
+        let mut conflicts: Vec<_> = self
+            .http_routes
+            .conflicts()
+            .into_iter()
+            .map(|conflict| (self.component_path.clone(), conflict))
+            .collect();
+        for child in self.child_components.values() {
+            conflicts.extend(child.http_route_conflicts());
+        }
+        conflicts
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ResourceTree {
     Branch(BTreeMap<PathComponent, ResourceTree>),
@@ -88,8 +117,32 @@ impl<'a> TypecheckContext<'a> {
         let definition_path = ComponentDefinitionPath::root();
         let component_path = ComponentPath::root();
         let args = BTreeMap::new();
-        self.instantiate(definition_path, component_path, args)
-            .await
+        let app = self
+            .instantiate(definition_path, component_path, args, BTreeMap::new())
+            .await?;
+
+        // Report every conflicting HTTP route across the app and its
+        // components at once rather than failing on the first one.
+        let conflicts = app.http_route_conflicts();
+        if !conflicts.is_empty() {
+            let report = conflicts
+                .iter()
+                .map(|(component_path, conflict)| {
+                    let component = if component_path.is_root() {
+                        "app".to_string()
+                    } else {
+                        format!("component {component_path:?}")
+                    };
+                    format!("  - In {component}: {conflict}")
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "HttpRouteConflict",
+                format!("Found conflicting HTTP routes:\n{report}"),
+            ));
+        }
+        Ok(app)
     }
 
     #[async_recursion]
@@ -98,6 +151,7 @@ impl<'a> TypecheckContext<'a> {
         definition_path: ComponentDefinitionPath,
         component_path: ComponentPath,
         args: BTreeMap<Identifier, Resource>,
+        grants: BTreeMap<TableName, TableAccess>,
     ) -> anyhow::Result<CheckedComponent> {
         let evaluated = self
             .evaluated_definitions
@@ -114,6 +168,7 @@ impl<'a> TypecheckContext<'a> {
             &component_path,
             evaluated,
             args,
+            grants,
         )?;
 
         // Instantiate our children in order, since we'd like to support one
@@ -137,12 +192,23 @@ impl<'a> TypecheckContext<'a> {
                         .await?
                 },
             };
+            if let Some(table) = instantiation.grants.keys().find(|table| table.is_system()) {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidTableGrant",
+                    format!(
+                        "Component {component_path:?} can't grant {:?} access to system table \
+                         {table}",
+                        instantiation.name
+                    ),
+                ));
+            }
             let child_component_path = component_path.push(instantiation.name.clone());
             let child_component = self
                 .instantiate(
                     instantiation.path.clone(),
                     child_component_path,
                     resolved_args,
+                    instantiation.grants.clone(),
                 )
                 .await?;
             builder.insert_child_component(instantiation.name.clone(), child_component)?;
@@ -164,16 +230,145 @@ pub fn validate_component_args(
     component_path: &ComponentPath,
     arg_validators: &BTreeMap<Identifier, ComponentArgumentValidator>,
     args: &BTreeMap<Identifier, Resource>,
+) -> anyhow::Result<()> {
+    check_component_args(
+        &format!("Component {component_path:?}"),
+        arg_validators,
+        args,
+    )
+}
+
+/// Check the declared argument validators and literal instantiation arguments
+/// of every definition in a push, before any component is instantiated.
+/// Arguments computed by an initializer are checked at instantiation time.
+pub fn validate_component_definitions(
+    definitions: &BTreeMap<ComponentDefinitionPath, EvaluatedComponentDefinition>,
+) -> anyhow::Result<()> {
+    for (definition_path, evaluated) in definitions {
+        if let ComponentDefinitionType::ChildComponent {
+            args: arg_validators,
+            ..
+        } = &evaluated.definition.definition_type
+        {
+            for (arg_name, ComponentArgumentValidator::Value { validator, .. }) in arg_validators {
+                // Component arguments are checked without a table mapping, so
+                // IDs can never pass validation.
+                if let Some(table_name) = validator.foreign_keys().next() {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "InvalidComponentArgumentValidator",
+                        format!(
+                            "Component definition {definition_path:?} declares argument \
+                             {arg_name:?} with an ID validator for table {table_name:?}, which \
+                             isn't supported for component arguments"
+                        ),
+                    ));
+                }
+            }
+        }
+        check_lifecycle_hooks(definition_path, evaluated)?;
+        for instantiation in &evaluated.definition.child_components {
+            let Some(ref args) = instantiation.args else {
+                continue;
+            };
+            let child = definitions.get(&instantiation.path).ok_or_else(|| {
+                ErrorMetadata::bad_request(
+                    "TypecheckError",
+                    format!("Component definition not found: {:?}", instantiation.path),
+                )
+            })?;
+            let ComponentDefinitionType::ChildComponent {
+                args: arg_validators,
+                ..
+            } = &child.definition.definition_type
+            else {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "TypecheckError",
+                    format!(
+                        "Can't instantiate the app as component {:?}",
+                        instantiation.name
+                    ),
+                ));
+            };
+            let args = args
+                .iter()
+                .map(|(name, ComponentArgument::Value(value))| {
+                    (name.clone(), Resource::Value(value.clone()))
+                })
+                .collect();
+            check_component_args(
+                &format!(
+                    "Component {:?} instantiated by {definition_path:?}",
+                    instantiation.name
+                ),
+                arg_validators,
+                &args,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn check_lifecycle_hooks(
+    definition_path: &ComponentDefinitionPath,
+    evaluated: &EvaluatedComponentDefinition,
+) -> anyhow::Result<()> {
+    let lifecycle = &evaluated.definition.lifecycle;
+    if evaluated.definition.is_app() && !lifecycle.is_empty() {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidLifecycleHook",
+            "The app can't declare lifecycle hooks since it's never mounted or unmounted",
+        ));
+    }
+    let hooks = [
+        ("onMount", &lifecycle.on_mount),
+        ("onUnmount", &lifecycle.on_unmount),
+    ];
+    for (hook, path) in hooks {
+        let Some(path) = path else {
+            continue;
+        };
+        let function = evaluated.functions.get(path.module()).and_then(|module| {
+            module
+                .functions
+                .iter()
+                .find(|function| &function.name == path.function_name())
+        });
+        match function {
+            Some(function) if function.udf_type == UdfType::Mutation => {},
+            Some(function) => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidLifecycleHook",
+                format!(
+                    "Component definition {definition_path:?} declares {hook} hook {path:?}, \
+                     which is a {}, but lifecycle hooks must be mutations",
+                    function.udf_type
+                ),
+            )),
+            None => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidLifecycleHook",
+                format!(
+                    "Component definition {definition_path:?} declares {hook} hook {path:?}, \
+                     which isn't a function in the component"
+                ),
+            )),
+        }
+    }
+    Ok(())
+}
+
+fn check_component_args(
+    component: &str,
+    arg_validators: &BTreeMap<Identifier, ComponentArgumentValidator>,
+    args: &BTreeMap<Identifier, Resource>,
 ) -> anyhow::Result<()> {
     for (arg_name, arg_value) in args {
         let validator = arg_validators.get(arg_name).ok_or_else(|| {
             ErrorMetadata::bad_request(
-                "TypecheckError",
-                format!("Component {component_path:?} has no argument named {arg_name:?}"),
+                "UnknownComponentArgument",
+                format!("{component} has no argument named {arg_name:?}"),
             )
         })?;
         match (arg_value, validator) {
-            (Resource::Value(ref value), ComponentArgumentValidator::Value(ref validator)) => {
+            (Resource::Value(ref value), ComponentArgumentValidator::Value { validator, .. }) => {
                 // TODO(CX-6540): Remove hack where we pass in empty mappings.
                 let table_mapping =
                     TableMapping::new().namespace(TableNamespace::by_component_TODO());
@@ -182,10 +377,10 @@ pub fn validate_component_args(
                     .check_value(value, &table_mapping, &virtual_system_mapping)
                     .map_err(|validator_error| {
                         ErrorMetadata::bad_request(
-                            "TypecheckError",
+                            "InvalidComponentArgument",
                             format!(
-                                "Component {component_path:?} has an invalid value for argument \
-                                 {arg_name:?}: {validator_error:?}"
+                                "{component} has an invalid value for argument {arg_name:?}: \
+                                 {validator_error}"
                             ),
                         )
                     })?;
@@ -193,11 +388,22 @@ pub fn validate_component_args(
             (Resource::Function { .. } | Resource::ResolvedSystemUdf { .. }, _) => {
                 anyhow::bail!(ErrorMetadata::bad_request(
                     "TypecheckError",
-                    "Function references are not supported"
+                    format!(
+                        "{component} was passed a function reference for argument {arg_name:?}, \
+                         but function references are not supported"
+                    )
                 ));
             },
         }
     }
+    for (arg_name, ComponentArgumentValidator::Value { optional, .. }) in arg_validators {
+        if !optional && !args.contains_key(arg_name) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "MissingComponentArgument",
+                format!("{component} is missing required argument {arg_name:?}"),
+            ));
+        }
+    }
     Ok(())
 }
 
@@ -208,6 +414,7 @@ struct CheckedComponentBuilder<'a> {
 
     // Phase 1: Arguments are checked immediately at construction time.
     args: BTreeMap<Identifier, Resource>,
+    grants: BTreeMap<TableName, TableAccess>,
 
     // Phase 2: The layer above adds in child components one at a time, and instantiating a child
     // component may depend on arguments or previous child components.
@@ -226,6 +433,7 @@ impl<'a> CheckedComponentBuilder<'a> {
         component_path: &'a ComponentPath,
         evaluated: &'a EvaluatedComponentDefinition,
         args: BTreeMap<Identifier, Resource>,
+        grants: BTreeMap<TableName, TableAccess>,
     ) -> anyhow::Result<Self> {
         match &evaluated.definition.definition_type {
             ComponentDefinitionType::App => {
@@ -249,6 +457,7 @@ impl<'a> CheckedComponentBuilder<'a> {
             evaluated,
 
             args,
+            grants,
             child_components: BTreeMap::new(),
             http_routes: CheckedHttpRoutes::new(evaluated),
         })
@@ -333,6 +542,7 @@ impl<'a> CheckedComponentBuilder<'a> {
             definition_path: self.definition_path.clone(),
             component_path: self.component_path.clone(),
             args: self.args,
+            grants: self.grants,
             http_routes: self.http_routes,
             child_components: self.child_components,
             exports,
@@ -470,21 +680,8 @@ impl CheckedHttpRoutes {
     }
 
     pub fn mount(&mut self, mount_path: HttpMountPath) -> anyhow::Result<()> {
-        // Check that the mount path does not overlap with any prefix route from our
-        // `http.js` or previously mounted route.
-        if let Some(http_module_routes) = &self.http_module_routes {
-            if http_module_routes
-                .iter()
-                .any(|route| route.overlaps_with_mount(&mount_path))
-            {
-                anyhow::bail!(ErrorMetadata::bad_request(
-                    "TypecheckError",
-                    format!(
-                        "HTTP mount {mount_path:?} is invalid: Overlap with existing prefix route"
-                    ),
-                ));
-            }
-        }
+        // Overlaps with prefix routes from our `http.js` are reported by
+        // `conflicts` once all of the mounts are in.
         if self.mounts.contains(&mount_path) {
             anyhow::bail!(ErrorMetadata::bad_request(
                 "TypecheckError",
@@ -497,6 +694,31 @@ impl CheckedHttpRoutes {
         Ok(())
     }
 
+    /// Routes from `http.js` that would never be routed to, either because
+    /// they're defined more than once or because a mounted component receives
+    /// all of their requests.
+    pub fn conflicts(&self) -> Vec<HttpRouteConflict> {
+        let Some(http_module_routes) = &self.http_module_routes else {
+            return vec![];
+        };
+        let mut conflicts = vec![];
+        let mut seen = BTreeSet::new();
+        for route in http_module_routes {
+            if !seen.insert(route) {
+                conflicts.push(HttpRouteConflict::DuplicateRoute(route.clone()));
+            }
+            for mount_path in &self.mounts {
+                if route.overlaps_with_mount(mount_path) {
+                    conflicts.push(HttpRouteConflict::ShadowedByMount {
+                        route: route.clone(),
+                        mount_path: mount_path.clone(),
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+
     pub fn is_empty(&self) -> bool {
         self.http_module_routes
             .as_ref()
@@ -506,6 +728,35 @@ impl CheckedHttpRoutes {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HttpRouteConflict {
+    /// The same method and path are defined more than once in `http.js`.
+    DuplicateRoute(HttpActionRoute),
+    /// A prefix route in `http.js` has the same prefix as a mounted component,
+    /// so the component receives all of its requests.
+    ShadowedByMount {
+        route: HttpActionRoute,
+        mount_path: HttpMountPath,
+    },
+}
+
+impl Display for HttpRouteConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpRouteConflict::DuplicateRoute(route) => {
+                write!(f, "Route {route} is defined more than once")
+            },
+            HttpRouteConflict::ShadowedByMount { route, mount_path } => {
+                write!(
+                    f,
+                    "Route {route} is shadowed by the component mounted at {:?}",
+                    &mount_path[..]
+                )
+            },
+        }
+    }
+}
+
 mod json {
     use std::collections::BTreeMap;
 
@@ -531,6 +782,7 @@ mod json {
         component_path: String,
 
         args: BTreeMap<String, SerializedResource>,
+        grants: Option<BTreeMap<String, String>>,
         child_components: BTreeMap<String, SerializedCheckedComponent>,
         http_routes: SerializedCheckedHttpRoutes,
         exports: BTreeMap<String, SerializedResourceTree>,
@@ -548,6 +800,13 @@ mod json {
                     .into_iter()
                     .map(|(k, v)| Ok((String::from(k), v.try_into()?)))
                     .collect::<anyhow::Result<_>>()?,
+                grants: Some(
+                    value
+                        .grants
+                        .into_iter()
+                        .map(|(table, access)| (table.to_string(), access.to_string()))
+                        .collect(),
+                ),
                 child_components: value
                     .child_components
                     .into_iter()
@@ -575,6 +834,12 @@ mod json {
                     .into_iter()
                     .map(|(k, v)| Ok((k.parse()?, v.try_into()?)))
                     .collect::<anyhow::Result<_>>()?,
+                grants: value
+                    .grants
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(table, access)| Ok((table.parse()?, access.parse()?)))
+                    .collect::<anyhow::Result<_>>()?,
                 child_components: value
                     .child_components
                     .into_iter()
@@ -685,3 +950,107 @@ pub use self::json::{
     SerializedCheckedComponent,
     SerializedResourceTree,
 };
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{
+        BTreeMap,
+        BTreeSet,
+    };
+
+    use common::{
+        bootstrap_model::components::definition::ComponentArgumentValidator,
+        components::{
+            ComponentPath,
+            Resource,
+        },
+        schemas::validator::Validator,
+    };
+    use errors::ErrorMetadataAnyhowExt;
+    use value::ConvexValue;
+
+    use super::{
+        validate_component_args,
+        CheckedHttpRoutes,
+        HttpRouteConflict,
+    };
+
+    #[test]
+    fn test_validate_component_args() -> anyhow::Result<()> {
+        let component_path = ComponentPath::deserialize(Some("child"))?;
+        let validators = BTreeMap::from([
+            (
+                "name".parse()?,
+                ComponentArgumentValidator::Value {
+                    validator: Validator::String,
+                    optional: false,
+                },
+            ),
+            (
+                "limit".parse()?,
+                ComponentArgumentValidator::Value {
+                    validator: Validator::Float64,
+                    optional: true,
+                },
+            ),
+        ]);
+        let name = Resource::Value(ConvexValue::String("convex".try_into()?));
+
+        let args = BTreeMap::from([("name".parse()?, name.clone())]);
+        validate_component_args(&component_path, &validators, &args)?;
+
+        let err =
+            validate_component_args(&component_path, &validators, &BTreeMap::new()).unwrap_err();
+        assert_eq!(err.short_msg(), "MissingComponentArgument");
+        assert!(err.msg().contains("\"name\""), "{err}");
+
+        let args = BTreeMap::from([
+            ("name".parse()?, name.clone()),
+            ("limit".parse()?, Resource::Value(ConvexValue::Null)),
+        ]);
+        let err = validate_component_args(&component_path, &validators, &args).unwrap_err();
+        assert_eq!(err.short_msg(), "InvalidComponentArgument");
+        assert!(err.msg().contains("\"limit\""), "{err}");
+
+        let args = BTreeMap::from([
+            ("name".parse()?, name),
+            ("other".parse()?, Resource::Value(ConvexValue::Null)),
+        ]);
+        let err = validate_component_args(&component_path, &validators, &args).unwrap_err();
+        assert_eq!(err.short_msg(), "UnknownComponentArgument");
+        Ok(())
+    }
+    #[test]
+    fn test_http_route_conflicts() -> anyhow::Result<()> {
+        let mut http_routes = CheckedHttpRoutes {
+            http_module_routes: Some(vec![
+                "GET /api/*".parse()?,
+                "POST /api/*".parse()?,
+                "GET /hello".parse()?,
+                "GET /hello".parse()?,
+                "GET /other/*".parse()?,
+            ]),
+            mounts: BTreeSet::new(),
+        };
+        http_routes.mount("/api/".parse()?)?;
+        http_routes.mount("/other/nested/".parse()?)?;
+        assert_eq!(
+            http_routes.conflicts(),
+            vec![
+                HttpRouteConflict::ShadowedByMount {
+                    route: "GET /api/*".parse()?,
+                    mount_path: "/api/".parse()?,
+                },
+                HttpRouteConflict::ShadowedByMount {
+                    route: "POST /api/*".parse()?,
+                    mount_path: "/api/".parse()?,
+                },
+                HttpRouteConflict::DuplicateRoute("GET /hello".parse()?),
+            ]
+        );
+
+        let err = http_routes.mount("/api/".parse()?).unwrap_err();
+        assert_eq!(err.short_msg(), "TypecheckError");
+        Ok(())
+    }
+}
@@ -30,6 +30,7 @@ use crate::{
         AnalyzedModule,
         SerializedAnalyzedModule,
     },
+    static_assets::types::StaticAssetsConfig,
     udf_config::types::UdfConfig,
 };
 
@@ -42,6 +43,9 @@ pub struct ProjectConfig {
 
     // TODO(CX-6483): Add support for components to declare their own external dependencies.
     pub node_dependencies: Vec<NodeDependency>,
+    // Files to serve from the HTTP actions host. Pushes without this leave the
+    // deployment's static assets as they are.
+    pub static_assets: Option<StaticAssetsConfig>,
 }
 
 #[derive(Debug)]
@@ -103,6 +103,10 @@ macro_rules! db_schema_with_indexes {
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
                         document_type: None,
+                        triggers: vec![],
+                        on_commit: vec![],
+                        cascade_delete_files: false,
+                        file_text: None,
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -33,6 +33,7 @@ use value::ResolvedDocumentId;
 use self::module_loader::ModuleLoader;
 use crate::{
     auth::AuthInfoModel,
+    component_versions::ComponentVersionsModel,
     config::types::{
         ConfigDiff,
         ConfigMetadata,
@@ -107,6 +108,11 @@ impl<'a, RT: Runtime> ConfigModel<'a, RT> {
 
         // Update auth info.
         let auth_diff = AuthInfoModel::new(self.tx).put(config.auth_info).await?;
+        if let Some(source_package_id) = source_package_id {
+            ComponentVersionsModel::new(self.tx)
+                .record(self.component, source_package_id, new_config.clone(), None)
+                .await?;
+        }
         let udf_server_version_diff = UdfConfigModel::new(self.tx, self.component.into())
             .set(new_config)
             .await?;
@@ -197,10 +197,12 @@ impl<'a, RT: Runtime> CronModel<'a, RT> {
         cron_spec: CronSpec,
     ) -> anyhow::Result<()> {
         let now = self.runtime().generate_timestamp()?;
+        let component = self.tx.must_component_path(self.component)?;
         let cron = CronJob {
             name,
             next_ts: compute_next_ts(&cron_spec, None, now)?,
             cron_spec,
+            component: Some(component),
             state: CronJobState::Pending,
             prev_ts: None,
         };
@@ -221,6 +223,7 @@ impl<'a, RT: Runtime> CronModel<'a, RT> {
             cron_job.next_ts = compute_next_ts(&new_cron_spec, cron_job.prev_ts, now)?;
         }
         cron_job.cron_spec = new_cron_spec;
+        cron_job.component = Some(self.tx.must_component_path(self.component)?);
         self.update_job_state(job_id, cron_job).await?;
         Ok(())
     }
@@ -11,6 +11,7 @@ use anyhow::{
     Context,
 };
 use common::{
+    components::ComponentPath,
     log_lines::RawLogLines,
     types::Timestamp,
 };
@@ -54,6 +55,10 @@ pub struct CronJob {
     // Cron-related metadata specified by the user and updated on pushes
     pub cron_spec: CronSpec,
 
+    // The component whose crons.js defines this cron. Unset for crons created
+    // before crons recorded their component.
+    pub component: Option<ComponentPath>,
+
     // Internally tracked metadata to execute the current run of the cron
     pub state: CronJobState,
     pub prev_ts: Option<Timestamp>,
@@ -65,6 +70,7 @@ pub struct CronJob {
 struct SerializedCronJob {
     name: String,
     cron_spec: SerializedCronSpec,
+    component: Option<String>,
     state: CronJobState,
     prev_ts: Option<i64>,
     next_ts: i64,
@@ -77,6 +83,7 @@ impl TryFrom<CronJob> for SerializedCronJob {
         Ok(Self {
             name: job.name.to_string(),
             cron_spec: job.cron_spec.try_into()?,
+            component: job.component.map(String::from),
             state: job.state,
             prev_ts: job.prev_ts.map(|ts| ts.into()),
             next_ts: job.next_ts.into(),
@@ -91,6 +98,7 @@ impl TryFrom<SerializedCronJob> for CronJob {
         Ok(Self {
             name: value.name.parse()?,
             cron_spec: value.cron_spec.try_into()?,
+            component: value.component.map(|p| p.parse()).transpose()?,
             state: value.state,
             prev_ts: value.prev_ts.map(|ts| ts.try_into()).transpose()?,
             next_ts: value.next_ts.try_into()?,
@@ -0,0 +1,349 @@
+//! User-defined data migrations. Migrations are registered by name with a
+//! version and the mutation that performs them, and a worker in the
+//! application crate runs them in version order, checkpointing after every
+//! batch.
+
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    DataMigration,
+    DataMigrationState,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static DATA_MIGRATIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_data_migrations"
+        .parse()
+        .expect("Invalid built-in data migrations table")
+});
+
+pub static DATA_MIGRATIONS_INDEX_BY_NAME: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&DATA_MIGRATIONS_TABLE, "by_name"));
+
+pub static DATA_MIGRATIONS_INDEX_BY_VERSION: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&DATA_MIGRATIONS_TABLE, "by_version"));
+
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("Invalid built-in field"));
+
+static VERSION_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "version".parse().expect("Invalid built-in field"));
+
+pub struct DataMigrationsTable;
+impl SystemTable for DataMigrationsTable {
+    fn table_name(&self) -> &'static TableName {
+        &DATA_MIGRATIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: DATA_MIGRATIONS_INDEX_BY_NAME.clone(),
+                fields: vec![NAME_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+            SystemIndex {
+                name: DATA_MIGRATIONS_INDEX_BY_VERSION.clone(),
+                fields: vec![VERSION_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<DataMigration>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct DataMigrationsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> DataMigrationsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn register(
+        &mut self,
+        migration: DataMigration,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        anyhow::ensure!(
+            self.get_by_name(&migration.name).await?.is_none(),
+            ErrorMetadata::bad_request(
+                "DataMigrationAlreadyExists",
+                format!("Data migration {} already exists", migration.name),
+            )
+        );
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&DATA_MIGRATIONS_TABLE, migration.try_into()?)
+            .await
+    }
+
+    /// All registered migrations, in the order they run.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<DataMigration>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: DATA_MIGRATIONS_INDEX_BY_VERSION.clone(),
+            range: vec![],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut result = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            result.push(doc.try_into()?);
+        }
+        Ok(result)
+    }
+
+    pub async fn get_by_name(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<DataMigration>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: DATA_MIGRATIONS_INDEX_BY_NAME.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                NAME_FIELD.clone(),
+                ConvexValue::try_from(name.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| doc.try_into())
+            .transpose()
+    }
+
+    /// The migration the runner should work on next: the lowest version that
+    /// hasn't completed. A failed migration blocks all later ones until it is
+    /// retried, and a completed dry run blocks them until its real run is
+    /// started, so this returns `None` in those cases.
+    pub async fn next_runnable(&mut self) -> anyhow::Result<Option<ParsedDocument<DataMigration>>> {
+        let next = self
+            .list()
+            .await?
+            .into_iter()
+            .find(|migration| !matches!(migration.state, DataMigrationState::Completed { .. }));
+        Ok(next.filter(|migration| {
+            !matches!(
+                migration.state,
+                DataMigrationState::Failed { .. } | DataMigrationState::DryRunCompleted { .. }
+            )
+        }))
+    }
+
+    pub async fn update_state(
+        &mut self,
+        id: ResolvedDocumentId,
+        state: DataMigrationState,
+    ) -> anyhow::Result<()> {
+        let mut migration = self.get(id).await?;
+        migration.state = state;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, migration.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    async fn get(&mut self, id: ResolvedDocumentId) -> anyhow::Result<DataMigration> {
+        self.tx
+            .get(id)
+            .await?
+            .context("Data migration not found")?
+            .into_value()
+            .0
+            .try_into()
+    }
+
+    async fn must_get_by_name(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<ParsedDocument<DataMigration>> {
+        self.get_by_name(name)
+            .await?
+            .context(ErrorMetadata::not_found(
+                "DataMigrationNotFound",
+                format!("Data migration {name} not found"),
+            ))
+    }
+
+    /// Resume a failed migration from its last checkpoint.
+    pub async fn retry(&mut self, name: &str) -> anyhow::Result<()> {
+        let migration = self.must_get_by_name(name).await?;
+        let DataMigrationState::Failed {
+            cursor,
+            num_processed,
+            ..
+        } = &migration.state
+        else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "DataMigrationNotFailed",
+                format!("Data migration {name} has not failed, so it can't be retried"),
+            ));
+        };
+        let state = DataMigrationState::InProgress {
+            cursor: cursor.clone(),
+            num_processed: *num_processed,
+        };
+        self.update_state(migration.id(), state).await
+    }
+
+    /// Run a migration for real after its dry run completed. The real run
+    /// starts again from the beginning.
+    pub async fn start_real_run(&mut self, name: &str) -> anyhow::Result<()> {
+        let (id, mut migration) = self.must_get_by_name(name).await?.into_id_and_value();
+        anyhow::ensure!(
+            matches!(migration.state, DataMigrationState::DryRunCompleted { .. }),
+            ErrorMetadata::bad_request(
+                "DataMigrationDryRunNotCompleted",
+                format!("Data migration {name} hasn't completed a dry run"),
+            )
+        );
+        migration.dry_run = false;
+        migration.state = DataMigrationState::Pending;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, migration.try_into()?)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        components::{
+            CanonicalizedComponentFunctionPath,
+            ComponentPath,
+        },
+        types::Timestamp,
+    };
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        data_migrations::{
+            types::{
+                DataMigration,
+                DataMigrationState,
+            },
+            DataMigrationsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn migration(name: &str, version: i64) -> anyhow::Result<DataMigration> {
+        Ok(DataMigration::new(
+            name.to_string(),
+            version,
+            CanonicalizedComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: "migrations.js:run".parse()?,
+            },
+            false,
+        ))
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_data_migrations_run_in_version_order(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = DataMigrationsModel::new(&mut tx);
+        model.register(migration("second", 2)?).await?;
+        let first_id = model.register(migration("first", 1)?).await?;
+        assert!(model.register(migration("first", 3)?).await.is_err());
+
+        let next = model.next_runnable().await?.unwrap();
+        assert_eq!(next.name, "first");
+
+        // A failed migration blocks the ones after it until it's retried.
+        model
+            .update_state(
+                first_id,
+                DataMigrationState::Failed {
+                    cursor: Some("cursor".to_string()),
+                    num_processed: 10,
+                    error: "boom".to_string(),
+                },
+            )
+            .await?;
+        assert!(model.next_runnable().await?.is_none());
+        model.retry("first").await?;
+        let next = model.next_runnable().await?.unwrap();
+        assert_eq!(next.name, "first");
+        assert_eq!(next.state.cursor(), Some("cursor"));
+        assert_eq!(next.state.num_processed(), 10);
+        assert!(model.retry("first").await.is_err());
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_dry_run_blocks_later_migrations(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = DataMigrationsModel::new(&mut tx);
+        let mut dry_run = migration("first", 1)?;
+        dry_run.dry_run = true;
+        let first_id = model.register(dry_run).await?;
+        model.register(migration("second", 2)?).await?;
+        assert!(model.start_real_run("first").await.is_err());
+
+        // None of the dry run's writes were applied, so later migrations
+        // wait for the real run.
+        model
+            .update_state(
+                first_id,
+                DataMigrationState::DryRunCompleted {
+                    num_processed: 10,
+                    completed_ts: Timestamp::MIN,
+                },
+            )
+            .await?;
+        assert!(model.next_runnable().await?.is_none());
+
+        model.start_real_run("first").await?;
+        let next = model.next_runnable().await?.unwrap();
+        assert_eq!(next.name, "first");
+        assert!(!next.dry_run);
+        assert_eq!(next.state, DataMigrationState::Pending);
+        Ok(())
+    }
+}
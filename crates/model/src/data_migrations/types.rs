@@ -0,0 +1,264 @@
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    types::Timestamp,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A user-defined data migration. Each migration is a mutation that processes
+/// one batch of documents per call, and migrations run one at a time in
+/// ascending `version` order.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct DataMigration {
+    /// Unique, user-provided name for the migration.
+    pub name: String,
+    pub version: i64,
+    /// The mutation that runs a single batch of the migration.
+    pub path: CanonicalizedComponentFunctionPath,
+    /// Dry runs pass `dryRun: true` to the mutation, which is expected to
+    /// report what it would change without writing.
+    pub dry_run: bool,
+    pub state: DataMigrationState,
+}
+
+impl DataMigration {
+    pub fn new(
+        name: String,
+        version: i64,
+        path: CanonicalizedComponentFunctionPath,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            name,
+            version,
+            path,
+            dry_run,
+            state: DataMigrationState::Pending,
+        }
+    }
+}
+
+/// The data migration state machine. A new migration starts as `Pending` and
+/// the valid transitions are:
+///
+/// - Pending -> InProgress: the runner committed the first batch
+/// - InProgress -> InProgress: the runner committed another batch and
+///   checkpointed its cursor
+/// - Pending,InProgress -> Completed: the mutation reported it is done
+/// - Pending,InProgress -> DryRunCompleted: a dry run's mutation reported it is
+///   done
+/// - Pending,InProgress -> Failed: the mutation threw an error
+/// - Failed -> InProgress: an admin retried the migration, which resumes from
+///   the last checkpoint
+/// - DryRunCompleted -> Pending: an admin started the real run, which starts
+///   again from the beginning with `dry_run` cleared
+///
+/// Completed is a terminal state. Like Failed, DryRunCompleted blocks later
+/// migrations, since none of the dry run's writes were applied.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum DataMigrationState {
+    Pending,
+    InProgress {
+        /// Opaque cursor returned by the last committed batch.
+        cursor: Option<String>,
+        num_processed: u64,
+    },
+    Completed {
+        num_processed: u64,
+        completed_ts: Timestamp,
+    },
+    DryRunCompleted {
+        num_processed: u64,
+        completed_ts: Timestamp,
+    },
+    Failed {
+        /// Cursor of the last committed batch, so a retry can resume.
+        cursor: Option<String>,
+        num_processed: u64,
+        error: String,
+    },
+}
+
+impl DataMigrationState {
+    pub fn cursor(&self) -> Option<&str> {
+        match self {
+            DataMigrationState::InProgress { cursor, .. }
+            | DataMigrationState::Failed { cursor, .. } => cursor.as_deref(),
+            DataMigrationState::Pending
+            | DataMigrationState::Completed { .. }
+            | DataMigrationState::DryRunCompleted { .. } => None,
+        }
+    }
+
+    pub fn num_processed(&self) -> u64 {
+        match self {
+            DataMigrationState::Pending => 0,
+            DataMigrationState::InProgress { num_processed, .. }
+            | DataMigrationState::Completed { num_processed, .. }
+            | DataMigrationState::DryRunCompleted { num_processed, .. }
+            | DataMigrationState::Failed { num_processed, .. } => *num_processed,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedDataMigration {
+    name: String,
+    version: i64,
+    component: Option<String>,
+    udf_path: String,
+    dry_run: bool,
+    state: SerializedDataMigrationState,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+enum SerializedDataMigrationState {
+    Pending,
+    #[serde(rename_all = "camelCase")]
+    InProgress {
+        cursor: Option<String>,
+        num_processed: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Completed {
+        num_processed: i64,
+        completed_ts: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    DryRunCompleted {
+        num_processed: i64,
+        completed_ts: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Failed {
+        cursor: Option<String>,
+        num_processed: i64,
+        error: String,
+    },
+}
+
+impl From<DataMigration> for SerializedDataMigration {
+    fn from(migration: DataMigration) -> Self {
+        SerializedDataMigration {
+            name: migration.name,
+            version: migration.version,
+            component: Some(String::from(migration.path.component)),
+            udf_path: String::from(migration.path.udf_path),
+            dry_run: migration.dry_run,
+            state: migration.state.into(),
+        }
+    }
+}
+
+impl TryFrom<SerializedDataMigration> for DataMigration {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedDataMigration) -> anyhow::Result<Self> {
+        let component = value
+            .component
+            .map(|p| p.parse())
+            .transpose()?
+            .unwrap_or_else(ComponentPath::root);
+        Ok(DataMigration {
+            name: value.name,
+            version: value.version,
+            path: CanonicalizedComponentFunctionPath {
+                component,
+                udf_path: value.udf_path.parse()?,
+            },
+            dry_run: value.dry_run,
+            state: value.state.try_into()?,
+        })
+    }
+}
+
+impl From<DataMigrationState> for SerializedDataMigrationState {
+    fn from(state: DataMigrationState) -> Self {
+        match state {
+            DataMigrationState::Pending => SerializedDataMigrationState::Pending,
+            DataMigrationState::InProgress {
+                cursor,
+                num_processed,
+            } => SerializedDataMigrationState::InProgress {
+                cursor,
+                num_processed: num_processed as i64,
+            },
+            DataMigrationState::Completed {
+                num_processed,
+                completed_ts,
+            } => SerializedDataMigrationState::Completed {
+                num_processed: num_processed as i64,
+                completed_ts: completed_ts.into(),
+            },
+            DataMigrationState::DryRunCompleted {
+                num_processed,
+                completed_ts,
+            } => SerializedDataMigrationState::DryRunCompleted {
+                num_processed: num_processed as i64,
+                completed_ts: completed_ts.into(),
+            },
+            DataMigrationState::Failed {
+                cursor,
+                num_processed,
+                error,
+            } => SerializedDataMigrationState::Failed {
+                cursor,
+                num_processed: num_processed as i64,
+                error,
+            },
+        }
+    }
+}
+
+impl TryFrom<SerializedDataMigrationState> for DataMigrationState {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedDataMigrationState) -> anyhow::Result<Self> {
+        let state = match value {
+            SerializedDataMigrationState::Pending => DataMigrationState::Pending,
+            SerializedDataMigrationState::InProgress {
+                cursor,
+                num_processed,
+            } => DataMigrationState::InProgress {
+                cursor,
+                num_processed: num_processed as u64,
+            },
+            SerializedDataMigrationState::Completed {
+                num_processed,
+                completed_ts,
+            } => DataMigrationState::Completed {
+                num_processed: num_processed as u64,
+                completed_ts: completed_ts.try_into()?,
+            },
+            SerializedDataMigrationState::DryRunCompleted {
+                num_processed,
+                completed_ts,
+            } => DataMigrationState::DryRunCompleted {
+                num_processed: num_processed as u64,
+                completed_ts: completed_ts.try_into()?,
+            },
+            SerializedDataMigrationState::Failed {
+                cursor,
+                num_processed,
+                error,
+            } => DataMigrationState::Failed {
+                cursor,
+                num_processed: num_processed as u64,
+                error,
+            },
+        };
+        Ok(state)
+    }
+}
+
+codegen_convex_serialization!(DataMigration, SerializedDataMigration);
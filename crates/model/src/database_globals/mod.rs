@@ -38,7 +38,7 @@ pub mod types;
 // migrations unless explicitly dropping support.
 // Add a user name next to the version when you make a change to highlight merge
 // conflicts.
-pub const DATABASE_VERSION: DatabaseVersion = 115; // nipunn
+pub const DATABASE_VERSION: DatabaseVersion = 117; // sosloan
 
 pub static DATABASE_GLOBALS_TABLE: LazyLock<TableName> =
     LazyLock::new(|| "_db".parse().expect("invalid built-in db table"));
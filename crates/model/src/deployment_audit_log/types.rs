@@ -1,5 +1,6 @@
 use std::{
     collections::BTreeMap,
+    net::IpAddr,
     str::FromStr,
     sync::LazyLock,
 };
@@ -16,11 +17,14 @@ use common::{
         LogEvent,
         StructuredLogEvent,
     },
+    pool_stats::PoolWorkload,
     runtime::UnixTimestamp,
     types::{
         GenericIndexName,
         IndexDiff,
         IndexName,
+        ModuleEnvironment,
+        UdfType,
     },
 };
 use database::LegacyIndexDiff;
@@ -35,6 +39,7 @@ use value::{
     codegen_convex_serialization,
     obj,
     remove_int64,
+    remove_nullable_int64,
     remove_nullable_string,
     remove_object,
     remove_string,
@@ -55,6 +60,16 @@ use crate::{
     },
     config::types::ConfigDiff,
     environment_variables::types::EnvVarName,
+    ip_access_policy::types::{
+        IpAccessDenialReason,
+        IpAccessIdentity,
+        IpAccessPolicy,
+        IpAccessSurface,
+    },
+    outbound_network_policy::types::{
+        NetworkPolicyViolationReason,
+        OutboundNetworkPolicy,
+    },
     snapshot_imports::types::{
         ImportFormat,
         ImportMode,
@@ -161,6 +176,55 @@ pub enum DeploymentAuditLogEvent {
         table_names_deleted: BTreeMap<ComponentPath, Vec<TableName>>,
         table_count_deleted: u64,
     },
+    UpdateOutboundNetworkPolicy {
+        policy: OutboundNetworkPolicy,
+    },
+    /// An action tried to send a request that the outbound network policy
+    /// blocked.
+    OutboundNetworkPolicyViolation {
+        host: String,
+        reason: NetworkPolicyViolationReason,
+        environment: ModuleEnvironment,
+    },
+    UpdateIpAccessPolicy {
+        policy: IpAccessPolicy,
+    },
+    /// A request was rejected by the IP access policy.
+    IpAccessDenied {
+        ip: IpAddr,
+        surface: IpAccessSurface,
+        reason: IpAccessDenialReason,
+    },
+    /// An identity authenticated from an address its IP access rules don't
+    /// allow.
+    IdentityIpAccessDenied {
+        ip: IpAddr,
+        identity: IpAccessIdentity,
+        reason: IpAccessDenialReason,
+    },
+    /// Too many requests with an invalid admin key came from `ip`, or used the
+    /// key identified by `key_fingerprint`, and further admin key requests
+    /// from the address or with the key are rejected for a while.
+    AdminKeyLockout {
+        ip: Option<IpAddr>,
+        key_fingerprint: String,
+        failed_attempts: u64,
+        duration_secs: u64,
+    },
+    /// An admin ran a function as a synthesized user identity.
+    ImpersonateUser {
+        component: ComponentPath,
+        udf_path: String,
+        udf_type: UdfType,
+        subject: Option<String>,
+        issuer: Option<String>,
+    },
+    /// An admin capped how many persistence pool connections `workload` may
+    /// hold at once, or uncapped it if `limit` is `None`.
+    SetConnectionPoolLimit {
+        workload: PoolWorkload,
+        limit: Option<u64>,
+    },
 }
 
 impl From<LegacyIndexDiff> for DeploymentAuditLogEvent {
@@ -212,6 +276,18 @@ impl DeploymentAuditLogEvent {
             DeploymentAuditLogEvent::ChangeDeploymentState { .. } => "change_deployment_state",
             DeploymentAuditLogEvent::SnapshotImport { .. } => "snapshot_import",
             DeploymentAuditLogEvent::ClearTables => "clear_tables",
+            DeploymentAuditLogEvent::UpdateOutboundNetworkPolicy { .. } => {
+                "update_outbound_network_policy"
+            },
+            DeploymentAuditLogEvent::OutboundNetworkPolicyViolation { .. } => {
+                "outbound_network_policy_violation"
+            },
+            DeploymentAuditLogEvent::UpdateIpAccessPolicy { .. } => "update_ip_access_policy",
+            DeploymentAuditLogEvent::IpAccessDenied { .. } => "ip_access_denied",
+            DeploymentAuditLogEvent::IdentityIpAccessDenied { .. } => "identity_ip_access_denied",
+            DeploymentAuditLogEvent::AdminKeyLockout { .. } => "admin_key_lockout",
+            DeploymentAuditLogEvent::ImpersonateUser { .. } => "impersonate_user",
+            DeploymentAuditLogEvent::SetConnectionPoolLimit { .. } => "set_connection_pool_limit",
         }
     }
 
@@ -329,6 +405,75 @@ impl DeploymentAuditLogEvent {
                 )
             },
             DeploymentAuditLogEvent::ClearTables => obj!(),
+            DeploymentAuditLogEvent::UpdateOutboundNetworkPolicy { policy } => policy.try_into(),
+            DeploymentAuditLogEvent::OutboundNetworkPolicyViolation {
+                host,
+                reason,
+                environment,
+            } => {
+                obj!(
+                    "host" => host,
+                    "reason" => reason.to_string(),
+                    "environment" => environment.to_string(),
+                )
+            },
+            DeploymentAuditLogEvent::UpdateIpAccessPolicy { policy } => policy.try_into(),
+            DeploymentAuditLogEvent::IpAccessDenied {
+                ip,
+                surface,
+                reason,
+            } => {
+                obj!(
+                    "ip" => ip.to_string(),
+                    "surface" => surface.to_string(),
+                    "reason" => reason.to_string(),
+                )
+            },
+            DeploymentAuditLogEvent::IdentityIpAccessDenied {
+                ip,
+                identity,
+                reason,
+            } => {
+                obj!(
+                    "ip" => ip.to_string(),
+                    "identity" => identity.to_string(),
+                    "reason" => reason.to_string(),
+                )
+            },
+            DeploymentAuditLogEvent::AdminKeyLockout {
+                ip,
+                key_fingerprint,
+                failed_attempts,
+                duration_secs,
+            } => {
+                obj!(
+                    "ip" => ip.map(|ip| ip.to_string()),
+                    "key_fingerprint" => key_fingerprint,
+                    "failed_attempts" => failed_attempts as i64,
+                    "duration_secs" => duration_secs as i64,
+                )
+            },
+            DeploymentAuditLogEvent::ImpersonateUser {
+                component,
+                udf_path,
+                udf_type,
+                subject,
+                issuer,
+            } => {
+                obj!(
+                    "component" => component.serialize(),
+                    "udf_path" => udf_path,
+                    "udf_type" => udf_type.to_lowercase_string(),
+                    "subject" => subject,
+                    "issuer" => issuer,
+                )
+            },
+            DeploymentAuditLogEvent::SetConnectionPoolLimit { workload, limit } => {
+                obj!(
+                    "workload" => workload.to_string(),
+                    "limit" => limit.map(|limit| limit as i64),
+                )
+            },
         }
     }
 
@@ -416,6 +561,52 @@ impl TryFrom<ConvexObject> for DeploymentAuditLogEvent {
                 new_state: remove_string(&mut fields, "new_state")?.parse()?,
             },
             "clear_tables" => DeploymentAuditLogEvent::ClearTables,
+            "update_outbound_network_policy" => {
+                DeploymentAuditLogEvent::UpdateOutboundNetworkPolicy {
+                    policy: ConvexObject::try_from(fields)?.try_into()?,
+                }
+            },
+            "outbound_network_policy_violation" => {
+                DeploymentAuditLogEvent::OutboundNetworkPolicyViolation {
+                    host: remove_string(&mut fields, "host")?,
+                    reason: remove_string(&mut fields, "reason")?.parse()?,
+                    environment: remove_string(&mut fields, "environment")?.parse()?,
+                }
+            },
+            "update_ip_access_policy" => DeploymentAuditLogEvent::UpdateIpAccessPolicy {
+                policy: ConvexObject::try_from(fields)?.try_into()?,
+            },
+            "ip_access_denied" => DeploymentAuditLogEvent::IpAccessDenied {
+                ip: remove_string(&mut fields, "ip")?.parse()?,
+                surface: remove_string(&mut fields, "surface")?.parse()?,
+                reason: remove_string(&mut fields, "reason")?.parse()?,
+            },
+            "identity_ip_access_denied" => DeploymentAuditLogEvent::IdentityIpAccessDenied {
+                ip: remove_string(&mut fields, "ip")?.parse()?,
+                identity: remove_string(&mut fields, "identity")?.parse()?,
+                reason: remove_string(&mut fields, "reason")?.parse()?,
+            },
+            "admin_key_lockout" => DeploymentAuditLogEvent::AdminKeyLockout {
+                ip: remove_nullable_string(&mut fields, "ip")?
+                    .map(|ip| ip.parse())
+                    .transpose()?,
+                key_fingerprint: remove_string(&mut fields, "key_fingerprint")?,
+                failed_attempts: remove_int64(&mut fields, "failed_attempts")? as u64,
+                duration_secs: remove_int64(&mut fields, "duration_secs")? as u64,
+            },
+            "impersonate_user" => DeploymentAuditLogEvent::ImpersonateUser {
+                component: ComponentPath::deserialize(
+                    remove_nullable_string(&mut fields, "component")?.as_deref(),
+                )?,
+                udf_path: remove_string(&mut fields, "udf_path")?,
+                udf_type: remove_string(&mut fields, "udf_type")?.parse()?,
+                subject: remove_nullable_string(&mut fields, "subject")?,
+                issuer: remove_nullable_string(&mut fields, "issuer")?,
+            },
+            "set_connection_pool_limit" => DeploymentAuditLogEvent::SetConnectionPoolLimit {
+                workload: remove_string(&mut fields, "workload")?.parse()?,
+                limit: remove_nullable_int64(&mut fields, "limit")?.map(|limit| limit as u64),
+            },
             "snapshot_import" => {
                 let table_names: BTreeMap<_, _> = remove_vec(&mut fields, "table_names")?
                     .into_iter()
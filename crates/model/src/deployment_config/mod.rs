@@ -0,0 +1,192 @@
+//! A read-only `_deployment_config` virtual table that gathers the
+//! deployment's backend state, auth providers, environment variable names,
+//! knob overrides and IP access policy into one document, so the dashboard and
+//! CLI can show them without an endpoint each. The application keeps the
+//! backing document in sync as the configuration changes, and only admins can
+//! read it.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        LazyLock,
+    },
+};
+
+use cmd_util::env::env_config_overrides;
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        GenericIndexName,
+        IndexName,
+    },
+    virtual_system_mapping::VirtualSystemDocMapper,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use maplit::btreemap;
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use self::{
+    types::{
+        AuthProviderConfig,
+        DeploymentConfig,
+        KnobOverride,
+    },
+    virtual_table::DeploymentConfigDocMapper,
+};
+use crate::{
+    auth::AuthInfoModel,
+    backend_state::BackendStateModel,
+    environment_variables::EnvironmentVariablesModel,
+    ip_access_policy::IpAccessPolicyModel,
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+mod virtual_table;
+
+pub static DEPLOYMENT_CONFIG_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_deployment_config_snapshot"
+        .parse()
+        .expect("Invalid built-in deployment config table")
+});
+pub static DEPLOYMENT_CONFIG_VIRTUAL_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_deployment_config"
+        .parse()
+        .expect("_deployment_config is not a valid virtual table name")
+});
+
+static DEPLOYMENT_CONFIG_INDEX_BY_ID: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_id(DEPLOYMENT_CONFIG_TABLE.clone()));
+static DEPLOYMENT_CONFIG_INDEX_BY_CREATION_TIME: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_creation_time(DEPLOYMENT_CONFIG_TABLE.clone()));
+static DEPLOYMENT_CONFIG_VIRTUAL_INDEX_BY_ID: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_id(DEPLOYMENT_CONFIG_VIRTUAL_TABLE.clone()));
+static DEPLOYMENT_CONFIG_VIRTUAL_INDEX_BY_CREATION_TIME: LazyLock<IndexName> =
+    LazyLock::new(|| GenericIndexName::by_creation_time(DEPLOYMENT_CONFIG_VIRTUAL_TABLE.clone()));
+
+pub struct DeploymentConfigTable;
+impl SystemTable for DeploymentConfigTable {
+    fn table_name(&self) -> &'static TableName {
+        &DEPLOYMENT_CONFIG_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn virtual_table(
+        &self,
+    ) -> Option<(
+        &'static TableName,
+        BTreeMap<IndexName, IndexName>,
+        Arc<dyn VirtualSystemDocMapper>,
+    )> {
+        Some((
+            &DEPLOYMENT_CONFIG_VIRTUAL_TABLE,
+            btreemap! {
+                DEPLOYMENT_CONFIG_VIRTUAL_INDEX_BY_CREATION_TIME.clone() =>
+                    DEPLOYMENT_CONFIG_INDEX_BY_CREATION_TIME.clone(),
+                DEPLOYMENT_CONFIG_VIRTUAL_INDEX_BY_ID.clone() =>
+                    DEPLOYMENT_CONFIG_INDEX_BY_ID.clone(),
+            },
+            Arc::new(DeploymentConfigDocMapper),
+        ))
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<DeploymentConfig>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct DeploymentConfigModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> DeploymentConfigModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    async fn get(&mut self) -> anyhow::Result<Option<ParsedDocument<DeploymentConfig>>> {
+        let query = Query::full_table_scan(DEPLOYMENT_CONFIG_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// Gather the configuration in effect from where it's stored.
+    pub async fn collect(&mut self) -> anyhow::Result<DeploymentConfig> {
+        let backend_state = BackendStateModel::new(self.tx).get_backend_state().await?;
+        let auth_providers = AuthInfoModel::new(self.tx)
+            .get()
+            .await?
+            .into_iter()
+            .map(|info| AuthProviderConfig {
+                domain: info.domain.to_string(),
+                application_id: info.application_id.clone(),
+            })
+            .collect();
+        let environment_variable_names = EnvironmentVariablesModel::new(self.tx)
+            .get_all()
+            .await?
+            .into_keys()
+            .map(String::from)
+            .collect();
+        let knob_overrides = env_config_overrides()
+            .into_iter()
+            .map(|(name, value)| KnobOverride { name, value })
+            .collect();
+        let ip_access_policy = IpAccessPolicyModel::new(self.tx).get().await?;
+        Ok(DeploymentConfig {
+            backend_state,
+            auth_providers,
+            environment_variable_names,
+            knob_overrides,
+            ip_access_policy,
+        })
+    }
+
+    /// Store the configuration the `_deployment_config` virtual table shows,
+    /// returning whether it changed.
+    pub async fn set(&mut self, config: DeploymentConfig) -> anyhow::Result<bool> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("set_deployment_config"));
+        }
+        match self.get().await? {
+            Some(existing) if *existing == config => Ok(false),
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), config.try_into()?)
+                    .await?;
+                Ok(true)
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&DEPLOYMENT_CONFIG_TABLE, config.try_into()?)
+                    .await?;
+                Ok(true)
+            },
+        }
+    }
+}
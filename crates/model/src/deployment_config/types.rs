@@ -0,0 +1,124 @@
+use common::types::BackendState;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+use crate::ip_access_policy::types::{
+    IpAccessPolicy,
+    SerializedIpAccessPolicy,
+};
+
+/// The deployment's active configuration, gathered from the tables and
+/// environment it's otherwise spread across. Environment variables are listed
+/// by name only, so their values never show up here.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct DeploymentConfig {
+    pub backend_state: BackendState,
+    pub auth_providers: Vec<AuthProviderConfig>,
+    pub environment_variable_names: Vec<String>,
+    /// Knobs the backend process was started with non-default values for.
+    pub knob_overrides: Vec<KnobOverride>,
+    pub ip_access_policy: IpAccessPolicy,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct AuthProviderConfig {
+    pub domain: String,
+    pub application_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct KnobOverride {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedDeploymentConfig {
+    backend_state: String,
+    auth_providers: Vec<SerializedAuthProviderConfig>,
+    environment_variable_names: Vec<String>,
+    knob_overrides: Vec<SerializedKnobOverride>,
+    // Missing from documents written before IP access policies existed.
+    ip_access_policy: Option<SerializedIpAccessPolicy>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedAuthProviderConfig {
+    domain: String,
+    application_id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedKnobOverride {
+    name: String,
+    value: String,
+}
+
+impl From<DeploymentConfig> for SerializedDeploymentConfig {
+    fn from(value: DeploymentConfig) -> Self {
+        Self {
+            backend_state: value.backend_state.to_string(),
+            auth_providers: value
+                .auth_providers
+                .into_iter()
+                .map(|provider| SerializedAuthProviderConfig {
+                    domain: provider.domain,
+                    application_id: provider.application_id,
+                })
+                .collect(),
+            environment_variable_names: value.environment_variable_names,
+            knob_overrides: value
+                .knob_overrides
+                .into_iter()
+                .map(|knob| SerializedKnobOverride {
+                    name: knob.name,
+                    value: knob.value,
+                })
+                .collect(),
+            ip_access_policy: Some(value.ip_access_policy.into()),
+        }
+    }
+}
+
+impl TryFrom<SerializedDeploymentConfig> for DeploymentConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedDeploymentConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            backend_state: value.backend_state.parse()?,
+            auth_providers: value
+                .auth_providers
+                .into_iter()
+                .map(|provider| AuthProviderConfig {
+                    domain: provider.domain,
+                    application_id: provider.application_id,
+                })
+                .collect(),
+            environment_variable_names: value.environment_variable_names,
+            knob_overrides: value
+                .knob_overrides
+                .into_iter()
+                .map(|knob| KnobOverride {
+                    name: knob.name,
+                    value: knob.value,
+                })
+                .collect(),
+            ip_access_policy: value
+                .ip_access_policy
+                .map(IpAccessPolicy::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+codegen_convex_serialization!(DeploymentConfig, SerializedDeploymentConfig);
@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use common::{
+    document::{
+        DeveloperDocument,
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD,
+        ID_FIELD,
+    },
+    virtual_system_mapping::{
+        VirtualSystemDocMapper,
+        VirtualSystemMapping,
+    },
+};
+use semver::Version;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    TableMapping,
+};
+
+use super::types::DeploymentConfig;
+
+pub struct DeploymentConfigDocMapper;
+
+impl VirtualSystemDocMapper for DeploymentConfigDocMapper {
+    fn system_to_virtual_doc(
+        &self,
+        virtual_system_mapping: &VirtualSystemMapping,
+        doc: ResolvedDocument,
+        _table_mapping: &TableMapping,
+        _version: Version,
+    ) -> anyhow::Result<DeveloperDocument> {
+        let config: ParsedDocument<DeploymentConfig> = doc.clone().try_into()?;
+        let virtual_developer_id =
+            virtual_system_mapping.system_resolved_id_to_virtual_developer_id(doc.id())?;
+
+        let mut fields: BTreeMap<_, _> = ConvexObject::try_from(config.into_value())?.into();
+        fields.insert(ID_FIELD.to_owned().into(), virtual_developer_id.into());
+        if let Some(t) = doc.creation_time() {
+            fields.insert(
+                CREATION_TIME_FIELD.to_owned().into(),
+                ConvexValue::from(f64::from(t)),
+            );
+        }
+        Ok(DeveloperDocument::new(
+            virtual_developer_id,
+            doc.creation_time(),
+            fields.try_into()?,
+        ))
+    }
+}
@@ -322,6 +322,9 @@ impl Export {
 pub enum ExportFormat {
     /// zip file containing a CleanJsonl for each table, and sidecar type info.
     Zip { include_storage: bool },
+    /// zip file containing Parquet files for each table and a manifest
+    /// describing them, for loading into data warehouses.
+    Parquet,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -330,19 +333,28 @@ pub enum ExportFormat {
 #[serde(rename_all = "snake_case")]
 enum SerializedExportFormat {
     Zip { include_storage: bool },
+    Parquet,
 }
 
 impl From<ExportFormat> for SerializedExportFormat {
     fn from(value: ExportFormat) -> Self {
-        let ExportFormat::Zip { include_storage } = value;
-        SerializedExportFormat::Zip { include_storage }
+        match value {
+            ExportFormat::Zip { include_storage } => {
+                SerializedExportFormat::Zip { include_storage }
+            },
+            ExportFormat::Parquet => SerializedExportFormat::Parquet,
+        }
     }
 }
 
 impl From<SerializedExportFormat> for ExportFormat {
     fn from(value: SerializedExportFormat) -> Self {
-        let SerializedExportFormat::Zip { include_storage } = value;
-        ExportFormat::Zip { include_storage }
+        match value {
+            SerializedExportFormat::Zip { include_storage } => {
+                ExportFormat::Zip { include_storage }
+            },
+            SerializedExportFormat::Parquet => ExportFormat::Parquet,
+        }
     }
 }
 
@@ -0,0 +1,462 @@
+//! Back-references from documents to the files they attach. A document
+//! attaches a file by holding its id in a field the schema types as
+//! `v.id("_storage")`. Mutations record each document's attachments here as
+//! they write it, so a file's referencing documents can be found without
+//! scanning every table.
+//!
+//! When a document is deleted from a table whose schema sets
+//! `cascadeDeleteFiles`, its attachments are marked pending instead of being
+//! removed, and a background worker deletes the files that no other document
+//! references.
+
+use std::{
+    collections::BTreeSet,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        DocumentUpdate,
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    schemas::TableDefinition,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    query::TableFilter,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use keybroker::Identity;
+use value::{
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::FileAttachment;
+use crate::{
+    file_storage::{
+        FileStorageId,
+        FileStorageModel,
+        FILE_STORAGE_VIRTUAL_TABLE,
+    },
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static FILE_ATTACHMENTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_file_attachments"
+        .parse()
+        .expect("Invalid built-in file attachments table")
+});
+
+static STORAGE_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "storageId".parse().expect("Invalid built-in field"));
+static DOCUMENT_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "documentId".parse().expect("Invalid built-in field"));
+static CASCADE_PENDING_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "cascadePending".parse().expect("Invalid built-in field"));
+
+pub static FILE_ATTACHMENTS_INDEX_BY_STORAGE_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FILE_ATTACHMENTS_TABLE, "by_storage_id"));
+pub static FILE_ATTACHMENTS_INDEX_BY_DOCUMENT_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FILE_ATTACHMENTS_TABLE, "by_document_id"));
+pub static FILE_ATTACHMENTS_INDEX_BY_CASCADE_PENDING: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FILE_ATTACHMENTS_TABLE, "by_cascade_pending"));
+
+pub struct FileAttachmentsTable;
+impl SystemTable for FileAttachmentsTable {
+    fn table_name(&self) -> &'static TableName {
+        &FILE_ATTACHMENTS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: FILE_ATTACHMENTS_INDEX_BY_STORAGE_ID.clone(),
+                fields: vec![
+                    STORAGE_ID_FIELD.clone(),
+                    CASCADE_PENDING_FIELD.clone(),
+                    CREATION_TIME_FIELD_PATH.clone(),
+                ]
+                .try_into()
+                .unwrap(),
+            },
+            SystemIndex {
+                name: FILE_ATTACHMENTS_INDEX_BY_DOCUMENT_ID.clone(),
+                fields: vec![DOCUMENT_ID_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+            SystemIndex {
+                name: FILE_ATTACHMENTS_INDEX_BY_CASCADE_PENDING.clone(),
+                fields: vec![
+                    CASCADE_PENDING_FIELD.clone(),
+                    CREATION_TIME_FIELD_PATH.clone(),
+                ]
+                .try_into()
+                .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FileAttachment>::try_from(document).map(|_| ())
+    }
+}
+
+/// Whether documents in `table` can attach files, i.e. whether its schema has
+/// any `v.id("_storage")` fields.
+pub fn has_attachment_fields(table: &TableDefinition) -> bool {
+    table.document_type.as_ref().is_some_and(|document_type| {
+        document_type
+            .foreign_keys()
+            .any(|table_name| *table_name == *FILE_STORAGE_VIRTUAL_TABLE)
+    })
+}
+
+pub struct FileAttachmentsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> FileAttachmentsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// Components created before attachments were tracked don't have the
+    /// table.
+    fn table_exists(&mut self) -> bool {
+        self.tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .name_exists(&FILE_ATTACHMENTS_TABLE)
+    }
+
+    /// Brings the attachments recorded for a document in `table` up to date
+    /// with its new contents. If the document was deleted and the table
+    /// cascades file deletes, its attachments are left pending for the
+    /// cascade worker.
+    pub async fn record_write(
+        &mut self,
+        table: &TableDefinition,
+        update: &DocumentUpdate,
+    ) -> anyhow::Result<()> {
+        if !self.table_exists() {
+            return Ok(());
+        }
+        let document_id = update.id.developer_id;
+        let mut storage_ids = BTreeSet::new();
+        if let (Some(document), Some(document_type)) = (&update.new_document, &table.document_type)
+        {
+            // Without schema validation the fields may hold ids in other
+            // tables, which aren't attachments.
+            for id in document_type.referenced_ids(&FILE_STORAGE_VIRTUAL_TABLE, document.value()) {
+                let table_name = self.tx.resolve_idv6(
+                    id,
+                    self.namespace,
+                    TableFilter::ExcludePrivateSystemTables,
+                );
+                if table_name.is_ok_and(|table_name| table_name == *FILE_STORAGE_VIRTUAL_TABLE) {
+                    storage_ids.insert(id);
+                }
+            }
+        }
+        let cascade = update.new_document.is_none() && table.cascade_delete_files;
+
+        for attachment in self.for_document(document_id).await? {
+            if attachment.cascade_pending || storage_ids.remove(&attachment.storage_id) {
+                continue;
+            }
+            if cascade {
+                let mut pending = attachment.clone().into_value();
+                pending.cascade_pending = true;
+                SystemMetadataModel::new(self.tx, self.namespace)
+                    .replace(attachment.id(), pending.try_into()?)
+                    .await?;
+            } else {
+                SystemMetadataModel::new(self.tx, self.namespace)
+                    .delete(attachment.id())
+                    .await?;
+            }
+        }
+        for storage_id in storage_ids {
+            let attachment = FileAttachment {
+                storage_id,
+                document_id,
+                cascade_pending: false,
+            };
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .insert(&FILE_ATTACHMENTS_TABLE, attachment.try_into()?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// The documents that currently attach the file.
+    pub async fn attached_to(
+        &mut self,
+        storage_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Vec<DeveloperDocumentId>> {
+        let query = Query::index_range(IndexRange {
+            index_name: FILE_ATTACHMENTS_INDEX_BY_STORAGE_ID.clone(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    STORAGE_ID_FIELD.clone(),
+                    ConvexValue::try_from(storage_id.to_string())?.into(),
+                ),
+                IndexRangeExpression::Eq(
+                    CASCADE_PENDING_FIELD.clone(),
+                    ConvexValue::from(false).into(),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut documents = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            let attachment: ParsedDocument<FileAttachment> = document.try_into()?;
+            documents.push(attachment.document_id);
+        }
+        Ok(documents)
+    }
+
+    /// Up to `limit` attachments of deleted documents whose files are waiting
+    /// to be cascade-deleted, oldest first.
+    pub async fn pending_cascades(
+        &mut self,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<FileAttachment>>> {
+        if !self.table_exists() {
+            return Ok(vec![]);
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: FILE_ATTACHMENTS_INDEX_BY_CASCADE_PENDING.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                CASCADE_PENDING_FIELD.clone(),
+                ConvexValue::from(true).into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut attachments = vec![];
+        while let Some(document) = query_stream.next(self.tx, Some(limit)).await? {
+            attachments.push(document.try_into()?);
+            if attachments.len() >= limit {
+                break;
+            }
+        }
+        Ok(attachments)
+    }
+
+    /// Removes a pending attachment and deletes its file if no document
+    /// attaches it anymore. Returns whether the file was deleted.
+    pub async fn complete_cascade(
+        &mut self,
+        attachment: ParsedDocument<FileAttachment>,
+    ) -> anyhow::Result<bool> {
+        anyhow::ensure!(
+            attachment.cascade_pending,
+            "Attachment {} isn't pending a cascade",
+            attachment.id()
+        );
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .delete(attachment.id())
+            .await?;
+        if !self.attached_to(attachment.storage_id).await?.is_empty() {
+            return Ok(false);
+        }
+        let deleted = FileStorageModel::new(self.tx, self.namespace)
+            .delete_file(
+                FileStorageId::DocumentId(attachment.storage_id),
+                Identity::system(),
+            )
+            .await?;
+        Ok(deleted.is_some())
+    }
+
+    async fn for_document(
+        &mut self,
+        document_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Vec<ParsedDocument<FileAttachment>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: FILE_ATTACHMENTS_INDEX_BY_DOCUMENT_ID.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                DOCUMENT_ID_FIELD.clone(),
+                ConvexValue::try_from(document_id.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut attachments = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            attachments.push(document.try_into()?);
+        }
+        Ok(attachments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        document::DocumentUpdate,
+        runtime::Runtime,
+        schemas::DatabaseSchema,
+        types::StorageUuid,
+    };
+    use database::{
+        test_helpers::DbFixtures,
+        TestFacingModel,
+        Transaction,
+    };
+    use runtime::testing::TestRuntime;
+    use serde_json::json;
+    use value::{
+        assert_obj,
+        sha256::Sha256,
+        ConvexValue,
+        DeveloperDocumentId,
+        TableNamespace,
+    };
+
+    use crate::{
+        file_attachments::FileAttachmentsModel,
+        file_storage::{
+            types::FileStorageEntry,
+            FileStorageId,
+            FileStorageModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    async fn store_file(
+        rt: &TestRuntime,
+        tx: &mut Transaction<TestRuntime>,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        let storage_id = StorageUuid::from(rt.new_uuid_v4());
+        let entry = FileStorageEntry {
+            storage_key: storage_id.to_string().try_into()?,
+            storage_id,
+            sha256: Sha256::hash(b"contents"),
+            size: 8,
+            content_type: None,
+            encryption: None,
+            quarantine_reason: None,
+        };
+        let id = FileStorageModel::new(tx, TableNamespace::test_user())
+            .store_file(entry)
+            .await?;
+        Ok(id.developer_id)
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_cascade_delete_files(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let schema = DatabaseSchema::try_from(json!({
+            "tables": [
+                {
+                    "tableName": "messages",
+                    "documentType": {
+                        "type": "object",
+                        "value": {
+                            "files": {
+                                "fieldType": {
+                                    "type": "array",
+                                    "value": { "type": "id", "tableName": "_storage" }
+                                },
+                                "optional": false
+                            },
+                        }
+                    },
+                    "indexes": [],
+                    "searchIndexes": [],
+                    "cascadeDeleteFiles": true,
+                },
+            ],
+            "schemaValidation": true
+        }))?;
+        let table = &schema.tables[&"messages".parse()?];
+
+        let shared = store_file(&rt, &mut tx).await?;
+        let only_first = store_file(&rt, &mut tx).await?;
+        let files = |ids: Vec<DeveloperDocumentId>| -> anyhow::Result<ConvexValue> {
+            Ok(ConvexValue::Array(
+                ids.into_iter()
+                    .map(ConvexValue::from)
+                    .collect::<Vec<_>>()
+                    .try_into()?,
+            ))
+        };
+        let mut documents = vec![];
+        for ids in [vec![shared, only_first], vec![shared]] {
+            let document = TestFacingModel::new(&mut tx)
+                .insert_and_get("messages".parse()?, assert_obj!("files" => files(ids)?))
+                .await?;
+            let update = DocumentUpdate {
+                id: document.id(),
+                old_document: None,
+                new_document: Some(document.clone()),
+            };
+            FileAttachmentsModel::new(&mut tx, TableNamespace::test_user())
+                .record_write(table, &update)
+                .await?;
+            documents.push(document);
+        }
+        let mut model = FileAttachmentsModel::new(&mut tx, TableNamespace::test_user());
+        assert_eq!(model.attached_to(shared).await?.len(), 2);
+        assert!(model.pending_cascades(10).await?.is_empty());
+
+        // Deleting the first document leaves both of its attachments pending,
+        // but only the file nothing else attaches is deleted.
+        let update = DocumentUpdate {
+            id: documents[0].id(),
+            old_document: Some(documents[0].clone()),
+            new_document: None,
+        };
+        model.record_write(table, &update).await?;
+        assert_eq!(
+            model.attached_to(shared).await?,
+            vec![documents[1].id().developer_id]
+        );
+        let pending = model.pending_cascades(10).await?;
+        assert_eq!(pending.len(), 2);
+        let mut deleted = vec![];
+        for attachment in pending {
+            let storage_id = attachment.storage_id;
+            if model.complete_cascade(attachment).await? {
+                deleted.push(storage_id);
+            }
+        }
+        assert_eq!(deleted, vec![only_first]);
+        assert!(model.pending_cascades(10).await?.is_empty());
+
+        let mut file_storage = FileStorageModel::new(&mut tx, TableNamespace::test_user());
+        assert!(file_storage
+            .get_file(FileStorageId::DocumentId(only_first))
+            .await?
+            .is_none());
+        assert!(file_storage
+            .get_file(FileStorageId::DocumentId(shared))
+            .await?
+            .is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    DeveloperDocumentId,
+};
+
+/// A reference from a document to a file it holds in a `v.id("_storage")`
+/// field.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct FileAttachment {
+    /// The `_storage` id of the referenced file.
+    pub storage_id: DeveloperDocumentId,
+    /// The document that references the file.
+    pub document_id: DeveloperDocumentId,
+    /// Set once the document is deleted from a table that cascades file
+    /// deletes. The file is deleted in the background unless another document
+    /// references it by then.
+    pub cascade_pending: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFileAttachment {
+    storage_id: String,
+    document_id: String,
+    cascade_pending: bool,
+}
+
+impl TryFrom<FileAttachment> for SerializedFileAttachment {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FileAttachment) -> anyhow::Result<Self> {
+        Ok(Self {
+            storage_id: value.storage_id.to_string(),
+            document_id: value.document_id.to_string(),
+            cascade_pending: value.cascade_pending,
+        })
+    }
+}
+
+impl TryFrom<SerializedFileAttachment> for FileAttachment {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedFileAttachment) -> anyhow::Result<Self> {
+        Ok(Self {
+            storage_id: DeveloperDocumentId::decode(&value.storage_id)?,
+            document_id: DeveloperDocumentId::decode(&value.document_id)?,
+            cascade_pending: value.cascade_pending,
+        })
+    }
+}
+
+codegen_convex_serialization!(FileAttachment, SerializedFileAttachment);
@@ -9,6 +9,7 @@ use std::{
 
 use anyhow::Context;
 use common::{
+    components::ComponentId,
     document::{
         ParsedDocument,
         ResolvedDocument,
@@ -57,6 +58,7 @@ use value::{
 
 use self::virtual_table::FileStorageDocMapper;
 use crate::{
+    component_limits::ComponentLimitsModel,
     file_storage::types::FileStorageEntry,
     SystemIndex,
     SystemTable,
@@ -203,6 +205,7 @@ impl<'a, RT: Runtime> FileStorageModel<'a, RT> {
         &mut self,
         entry: FileStorageEntry,
     ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_storage_quota(entry.size as u64).await?;
         // Call insert_metadata rather than insert because we already
         // did access check on `identity` rather than `self.identity`
         SystemMetadataModel::new(self.tx, self.namespace)
@@ -338,7 +341,48 @@ impl<'a, RT: Runtime> FileStorageModel<'a, RT> {
         if !self.tx.identity().is_system() {
             anyhow::bail!(unauthorized_error("get_total_storage_size"))
         }
+        self.sum_storage_size().await
+    }
+
+    /// Fails with `StorageQuotaExceeded` if storing another file of
+    /// `additional_bytes` would take the component over its storage quotas.
+    /// Usage is only computed for components with a quota, since summing the
+    /// size scans every file the component has stored.
+    pub async fn check_storage_quota(&mut self, additional_bytes: u64) -> anyhow::Result<()> {
+        let Some(limits) = ComponentLimitsModel::new(self.tx)
+            .get_limits(ComponentId::from(self.namespace))
+            .await?
+        else {
+            return Ok(());
+        };
+        if let Some(max_files) = limits.max_storage_files {
+            let files = self.get_total_storage_count().await?;
+            if files + 1 > max_files {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "StorageQuotaExceeded",
+                    format!(
+                        "Storing this file would exceed the component's quota of {max_files} \
+                         files ({files} files stored)"
+                    ),
+                ));
+            }
+        }
+        if let Some(max_bytes) = limits.max_storage_bytes {
+            let bytes = self.sum_storage_size().await?;
+            if bytes.saturating_add(additional_bytes) > max_bytes {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "StorageQuotaExceeded",
+                    format!(
+                        "Storing this file ({additional_bytes} bytes) would exceed the \
+                         component's storage quota of {max_bytes} bytes ({bytes} bytes stored)"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
 
+    async fn sum_storage_size(&mut self) -> anyhow::Result<u64> {
         let query = Query::full_table_scan(FILE_STORAGE_TABLE.to_owned(), Order::Asc);
         let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
         let mut total_size = 0;
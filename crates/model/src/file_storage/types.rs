@@ -8,7 +8,15 @@ use common::{
         StorageUuid,
     },
 };
-use pb::storage::FileStorageEntry as FileStorageEntryProto;
+use errors::ErrorMetadata;
+use pb::storage::{
+    FileEncryptionMetadata as FileEncryptionMetadataProto,
+    FileStorageEntry as FileStorageEntryProto,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use value::{
     sha256::Sha256Digest,
     ConvexObject,
@@ -24,6 +32,111 @@ pub struct FileStorageEntry {
     pub sha256: Sha256Digest,   // Sha256 of contents
     pub size: i64,              // Size of file in storage
     pub content_type: Option<String>, // Optional ContentType header saved with file
+    pub encryption: Option<FileEncryptionMetadata>, // Optional client-side encryption metadata
+    /// Set if a malware scan flagged the file when it was uploaded.
+    /// Quarantined files are kept for inspection but never served.
+    pub quarantine_reason: Option<String>,
+}
+
+/// Limit on each field of [`FileEncryptionMetadata`], which is returned in
+/// response headers when the file is served.
+pub const MAX_ENCRYPTION_METADATA_FIELD_LEN: usize = 1024;
+
+/// How a client encrypted a file before uploading it. Convex treats these
+/// values as opaque: they're stored with the file and returned whenever the
+/// file's metadata or contents are read.
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEncryptionMetadata {
+    pub key_id: String,
+    pub iv: String,
+    pub algorithm: String,
+}
+
+impl FileEncryptionMetadata {
+    /// Checks values supplied by a client. Each field must be non-empty
+    /// printable ASCII so it can be sent back in a header.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, value) in [
+            ("keyId", &self.key_id),
+            ("iv", &self.iv),
+            ("algorithm", &self.algorithm),
+        ] {
+            if value.is_empty()
+                || value.len() > MAX_ENCRYPTION_METADATA_FIELD_LEN
+                || !value.bytes().all(|b| b.is_ascii_graphic() || b == b' ')
+            {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidEncryptionMetadata",
+                    format!(
+                        "Encryption metadata field `{name}` must be 1 to \
+                         {MAX_ENCRYPTION_METADATA_FIELD_LEN} printable ASCII characters"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<FileEncryptionMetadata> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FileEncryptionMetadata {
+            key_id,
+            iv,
+            algorithm,
+        }: FileEncryptionMetadata,
+    ) -> Result<Self, Self::Error> {
+        obj!(
+            "keyId" => key_id,
+            "iv" => iv,
+            "algorithm" => algorithm,
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for FileEncryptionMetadata {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ConvexObject) -> Result<Self, Self::Error> {
+        let mut object_fields: BTreeMap<_, _> = value.into();
+        let mut field = |name: &str| -> anyhow::Result<String> {
+            match object_fields.remove(name) {
+                Some(ConvexValue::String(s)) => Ok(String::from(s)),
+                _ => anyhow::bail!("Missing '{name}' in encryption metadata"),
+            }
+        };
+        Ok(Self {
+            key_id: field("keyId")?,
+            iv: field("iv")?,
+            algorithm: field("algorithm")?,
+        })
+    }
+}
+
+impl TryFrom<FileEncryptionMetadataProto> for FileEncryptionMetadata {
+    type Error = anyhow::Error;
+
+    fn try_from(metadata: FileEncryptionMetadataProto) -> anyhow::Result<Self> {
+        Ok(Self {
+            key_id: metadata.key_id.context("Missing `key_id` field")?,
+            iv: metadata.iv.context("Missing `iv` field")?,
+            algorithm: metadata.algorithm.context("Missing `algorithm` field")?,
+        })
+    }
+}
+
+impl From<FileEncryptionMetadata> for FileEncryptionMetadataProto {
+    fn from(metadata: FileEncryptionMetadata) -> Self {
+        Self {
+            key_id: Some(metadata.key_id),
+            iv: Some(metadata.iv),
+            algorithm: Some(metadata.algorithm),
+        }
+    }
 }
 
 impl TryFrom<FileStorageEntry> for ConvexObject {
@@ -36,10 +149,12 @@ impl TryFrom<FileStorageEntry> for ConvexObject {
             sha256,
             size,
             content_type,
+            encryption,
+            quarantine_reason,
         }: FileStorageEntry,
     ) -> Result<Self, Self::Error> {
         let storage_key: String = storage_key.into();
-        obj!(
+        let object = obj!(
             "storageId" => storage_id.to_string(),
             "storageKey" => storage_key,
             "sha256" => sha256,
@@ -48,7 +163,23 @@ impl TryFrom<FileStorageEntry> for ConvexObject {
                 None => ConvexValue::Null,
                 Some(ct) => ct.try_into()?,
             },
-        )
+        )?;
+        // Only files uploaded with encryption metadata or flagged by a scan
+        // have these fields, so existing documents are unchanged.
+        if encryption.is_none() && quarantine_reason.is_none() {
+            return Ok(object);
+        }
+        let mut fields: BTreeMap<_, _> = object.into();
+        if let Some(encryption) = encryption {
+            fields.insert(
+                "encryption".parse()?,
+                ConvexObject::try_from(encryption)?.into(),
+            );
+        }
+        if let Some(reason) = quarantine_reason {
+            fields.insert("quarantineReason".parse()?, reason.try_into()?);
+        }
+        fields.try_into()
     }
 }
 
@@ -78,12 +209,24 @@ impl TryFrom<ConvexObject> for FileStorageEntry {
             Some(ConvexValue::String(ct)) => Some(String::from(ct)),
             _ => anyhow::bail!("Invalid 'content_type' in {object_fields:?}"),
         };
+        let encryption = match object_fields.remove("encryption") {
+            None => None,
+            Some(ConvexValue::Object(encryption)) => Some(encryption.try_into()?),
+            _ => anyhow::bail!("Invalid 'encryption' in {object_fields:?}"),
+        };
+        let quarantine_reason = match object_fields.remove("quarantineReason") {
+            None => None,
+            Some(ConvexValue::String(reason)) => Some(String::from(reason)),
+            _ => anyhow::bail!("Invalid 'quarantineReason' in {object_fields:?}"),
+        };
         Ok(Self {
             storage_id,
             storage_key,
             sha256,
             size,
             content_type,
+            encryption,
+            quarantine_reason,
         })
     }
 }
@@ -108,6 +251,8 @@ impl TryFrom<FileStorageEntryProto> for FileStorageEntry {
             sha256,
             size,
             content_type: entry.content_type,
+            encryption: entry.encryption.map(TryInto::try_into).transpose()?,
+            quarantine_reason: entry.quarantine_reason,
         })
     }
 }
@@ -120,6 +265,8 @@ impl From<FileStorageEntry> for FileStorageEntryProto {
             sha256: Some(entry.sha256.to_vec()),
             size: Some(entry.size),
             content_type: entry.content_type,
+            encryption: entry.encryption.map(Into::into),
+            quarantine_reason: entry.quarantine_reason,
         }
     }
 }
@@ -132,7 +279,10 @@ mod tests {
     use proptest::prelude::*;
     use value::ConvexObject;
 
-    use super::FileStorageEntry;
+    use super::{
+        FileEncryptionMetadata,
+        FileStorageEntry,
+    };
 
     proptest! {
         #![proptest_config(
@@ -150,4 +300,21 @@ mod tests {
         }
 
     }
+
+    #[test]
+    fn test_validate_encryption_metadata() {
+        let metadata = FileEncryptionMetadata {
+            key_id: "key-1".to_string(),
+            iv: "AAECAwQFBgcICQoL".to_string(),
+            algorithm: "AES-GCM".to_string(),
+        };
+        assert!(metadata.validate().is_ok());
+        for invalid in ["", "line\nbreak", "ünicode"] {
+            let metadata = FileEncryptionMetadata {
+                iv: invalid.to_string(),
+                ..metadata.clone()
+            };
+            assert!(metadata.validate().is_err());
+        }
+    }
 }
@@ -26,7 +26,10 @@ use value::{
 };
 
 use super::{
-    types::FileStorageEntry,
+    types::{
+        FileEncryptionMetadata,
+        FileStorageEntry,
+    },
     FILE_STORAGE_TABLE,
 };
 
@@ -67,6 +70,8 @@ impl VirtualSystemDocMapper for FileStorageDocMapper {
             sha256,
             size: metadata.size as f64,
             content_type: metadata.content_type,
+            encryption: metadata.encryption,
+            quarantine_reason: metadata.quarantine_reason,
         };
         let mut public_metadata_resolved: ConvexObject = public_metadata.try_into()?;
 
@@ -98,6 +103,10 @@ struct PublicFileMetadata {
     sha256: String,               // Hex-encoded Sha256 of contents
     size: f64,                    // Size of file in storage
     content_type: Option<String>, // Optional ContentType header saved with file
+    // Client-side encryption metadata, omitted if none was provided on upload
+    encryption: Option<FileEncryptionMetadata>,
+    // Why a malware scan quarantined the file, omitted for clean files
+    quarantine_reason: Option<String>,
 }
 
 impl TryFrom<PublicFileMetadata> for ConvexObject {
@@ -108,6 +117,8 @@ impl TryFrom<PublicFileMetadata> for ConvexObject {
             sha256,
             size,
             content_type,
+            encryption,
+            quarantine_reason,
         }: PublicFileMetadata,
     ) -> Result<Self, Self::Error> {
         let mut obj: BTreeMap<FieldName, ConvexValue> = BTreeMap::new();
@@ -120,6 +131,15 @@ impl TryFrom<PublicFileMetadata> for ConvexObject {
                 Some(ct) => val!(ct),
             },
         );
+        if let Some(encryption) = encryption {
+            obj.insert(
+                "encryption".parse()?,
+                ConvexObject::try_from(encryption)?.into(),
+            );
+        }
+        if let Some(reason) = quarantine_reason {
+            obj.insert("quarantineReason".parse()?, reason.try_into()?);
+        }
         ConvexObject::try_from(obj)
     }
 }
@@ -0,0 +1,447 @@
+//! Text extraction for files referenced from documents. A table whose schema
+//! configures `extractFileText` names a `v.id("_storage")` field and a text
+//! field that one of its search indexes searches. Whenever a mutation writes a
+//! document with a new file in that field, the document is queued here, and a
+//! background worker extracts the file's text and writes it into the text
+//! field, where the search index picks it up.
+
+use std::sync::LazyLock;
+
+use common::{
+    bootstrap_model::schema::SchemaState,
+    document::{
+        DocumentUpdate,
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    schemas::{
+        FileTextSchema,
+        TableDefinition,
+    },
+    types::{
+        IndexName,
+        MaybeValue,
+    },
+};
+use database::{
+    defaults::system_index,
+    query::TableFilter,
+    PatchValue,
+    ResolvedQuery,
+    SchemaModel,
+    SystemMetadataModel,
+    Transaction,
+    UserFacingModel,
+};
+use maplit::btreemap;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldName,
+    FieldPath,
+    IdentifierFieldName,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::FileTextExtraction;
+use crate::{
+    file_storage::FILE_STORAGE_VIRTUAL_TABLE,
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static FILE_TEXT_EXTRACTIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_file_text_extractions"
+        .parse()
+        .expect("Invalid built-in file text extractions table")
+});
+
+static DOCUMENT_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "documentId".parse().expect("Invalid built-in field"));
+
+pub static FILE_TEXT_EXTRACTIONS_INDEX_BY_DOCUMENT_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FILE_TEXT_EXTRACTIONS_TABLE, "by_document_id"));
+
+pub struct FileTextExtractionsTable;
+impl SystemTable for FileTextExtractionsTable {
+    fn table_name(&self) -> &'static TableName {
+        &FILE_TEXT_EXTRACTIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: FILE_TEXT_EXTRACTIONS_INDEX_BY_DOCUMENT_ID.clone(),
+            fields: vec![DOCUMENT_ID_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FileTextExtraction>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct FileTextModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> FileTextModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// Components created before file text extraction existed don't have the
+    /// table.
+    fn table_exists(&mut self) -> bool {
+        self.tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .name_exists(&FILE_TEXT_EXTRACTIONS_TABLE)
+    }
+
+    /// Queues a document in `table` for extraction if the write put a new file
+    /// in its storage field, replacing any extraction still queued for the
+    /// file it held before.
+    pub async fn record_write(
+        &mut self,
+        table: &TableDefinition,
+        update: &DocumentUpdate,
+    ) -> anyhow::Result<()> {
+        let Some(file_text) = &table.file_text else {
+            return Ok(());
+        };
+        if !self.table_exists() {
+            return Ok(());
+        }
+        let old_storage_id = match &update.old_document {
+            Some(document) => self.storage_id(document.value(), &file_text.storage_field),
+            None => None,
+        };
+        let new_storage_id = match &update.new_document {
+            Some(document) => self.storage_id(document.value(), &file_text.storage_field),
+            None => None,
+        };
+        if update.new_document.is_some() && new_storage_id == old_storage_id {
+            return Ok(());
+        }
+        let document_id = update.id.developer_id;
+        for extraction in self.for_document(document_id).await? {
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .delete(extraction.id())
+                .await?;
+        }
+        if let Some(storage_id) = new_storage_id {
+            let extraction = FileTextExtraction {
+                document_id,
+                storage_id,
+            };
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .insert(&FILE_TEXT_EXTRACTIONS_TABLE, extraction.try_into()?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// The oldest queued extraction, if any.
+    pub async fn next_pending(
+        &mut self,
+    ) -> anyhow::Result<Option<ParsedDocument<FileTextExtraction>>> {
+        if !self.table_exists() {
+            return Ok(None);
+        }
+        let query = Query::full_table_scan(FILE_TEXT_EXTRACTIONS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        query_stream
+            .next(self.tx, Some(1))
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// How the document's table extracts file text, if its active schema still
+    /// configures it.
+    pub async fn file_text_schema(
+        &mut self,
+        document_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<FileTextSchema>> {
+        let Some(table_name) = self
+            .tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .name_by_number_if_exists(document_id.table())
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        let schema = SchemaModel::new(self.tx, self.namespace)
+            .get_by_state(SchemaState::Active)
+            .await?;
+        Ok(schema.and_then(|(_, mut schema)| {
+            schema
+                .tables
+                .remove(&table_name)
+                .and_then(|table| table.file_text)
+        }))
+    }
+
+    /// Dequeues an extraction and writes `text` into its document, or clears
+    /// the text field if there's no text. Nothing is written if the extraction
+    /// was dequeued in the meantime or the document no longer holds the file.
+    /// Returns whether the document was updated.
+    pub async fn complete(
+        &mut self,
+        extraction: ParsedDocument<FileTextExtraction>,
+        text: Option<String>,
+    ) -> anyhow::Result<bool> {
+        if !self.dequeue(&extraction).await? {
+            return Ok(false);
+        }
+        let Some(file_text) = self.file_text_schema(extraction.document_id).await? else {
+            return Ok(false);
+        };
+        let id = extraction.document_id.to_resolved(
+            self.tx
+                .table_mapping()
+                .namespace(self.namespace)
+                .number_to_tablet(),
+        )?;
+        let Some(document) = self.tx.get(id).await? else {
+            return Ok(false);
+        };
+        if self.storage_id(document.value(), &file_text.storage_field)
+            != Some(extraction.storage_id)
+        {
+            return Ok(false);
+        }
+        let text = text.map(ConvexValue::try_from).transpose()?;
+        let patch = PatchValue::from(btreemap! {
+            FieldName::from(file_text.text_field) => MaybeValue(text),
+        });
+        UserFacingModel::new(self.tx, self.namespace)
+            .patch(extraction.document_id, patch)
+            .await?;
+        Ok(true)
+    }
+
+    /// Removes an extraction from the queue without writing any text. Returns
+    /// false if it was already dequeued.
+    pub async fn dequeue(
+        &mut self,
+        extraction: &ParsedDocument<FileTextExtraction>,
+    ) -> anyhow::Result<bool> {
+        if self.tx.get(extraction.id()).await?.is_none() {
+            return Ok(false);
+        }
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .delete(extraction.id())
+            .await?;
+        Ok(true)
+    }
+
+    /// The file in a document's storage field. Without schema validation the
+    /// field may hold something else, which isn't extracted.
+    fn storage_id(
+        &mut self,
+        document: &ConvexObject,
+        storage_field: &IdentifierFieldName,
+    ) -> Option<DeveloperDocumentId> {
+        let Some(ConvexValue::String(s)) = document.get(&**storage_field) else {
+            return None;
+        };
+        let id = DeveloperDocumentId::decode(s).ok()?;
+        let table_name = self
+            .tx
+            .resolve_idv6(id, self.namespace, TableFilter::ExcludePrivateSystemTables)
+            .ok()?;
+        (table_name == *FILE_STORAGE_VIRTUAL_TABLE).then_some(id)
+    }
+
+    async fn for_document(
+        &mut self,
+        document_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Vec<ParsedDocument<FileTextExtraction>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: FILE_TEXT_EXTRACTIONS_INDEX_BY_DOCUMENT_ID.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                DOCUMENT_ID_FIELD.clone(),
+                ConvexValue::try_from(document_id.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut extractions = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            extractions.push(document.try_into()?);
+        }
+        Ok(extractions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        document::DocumentUpdate,
+        runtime::Runtime,
+        schemas::DatabaseSchema,
+        types::StorageUuid,
+    };
+    use database::{
+        test_helpers::DbFixtures,
+        PatchValue,
+        SchemaModel,
+        TestFacingModel,
+        Transaction,
+        UserFacingModel,
+    };
+    use runtime::testing::TestRuntime;
+    use serde_json::json;
+    use value::{
+        assert_obj,
+        sha256::Sha256,
+        ConvexValue,
+        DeveloperDocumentId,
+        TableNamespace,
+    };
+
+    use crate::{
+        file_storage::{
+            types::FileStorageEntry,
+            FileStorageModel,
+        },
+        file_text::FileTextModel,
+        test_helpers::DbFixturesWithModel,
+    };
+
+    async fn store_file(
+        rt: &TestRuntime,
+        tx: &mut Transaction<TestRuntime>,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        let storage_id = StorageUuid::from(rt.new_uuid_v4());
+        let entry = FileStorageEntry {
+            storage_key: storage_id.to_string().try_into()?,
+            storage_id,
+            sha256: Sha256::hash(b"contents"),
+            size: 8,
+            content_type: Some("text/plain".to_string()),
+            encryption: None,
+            quarantine_reason: None,
+        };
+        let id = FileStorageModel::new(tx, TableNamespace::test_user())
+            .store_file(entry)
+            .await?;
+        Ok(id.developer_id)
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_extraction_queue(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let namespace = TableNamespace::test_user();
+        let schema = DatabaseSchema::try_from(json!({
+            "tables": [
+                {
+                    "tableName": "documents",
+                    "documentType": {
+                        "type": "object",
+                        "value": {
+                            "file": {
+                                "fieldType": { "type": "id", "tableName": "_storage" },
+                                "optional": false
+                            },
+                            "text": {
+                                "fieldType": { "type": "string" },
+                                "optional": true
+                            },
+                        }
+                    },
+                    "indexes": [],
+                    "searchIndexes": [
+                        {
+                            "indexDescriptor": "search_text",
+                            "searchField": "text",
+                            "filterFields": [],
+                        },
+                    ],
+                    "fileText": { "storageField": "file", "textField": "text" },
+                },
+            ],
+            "schemaValidation": true
+        }))?;
+        let table = schema.tables[&"documents".parse()?].clone();
+        let mut schema_model = SchemaModel::new(&mut tx, namespace);
+        let (schema_id, _) = schema_model.submit_pending(schema).await?;
+        schema_model.mark_validated(schema_id).await?;
+        schema_model.mark_active(schema_id).await?;
+
+        // Inserting a document with a file queues it.
+        let file = store_file(&rt, &mut tx).await?;
+        let document = TestFacingModel::new(&mut tx)
+            .insert_and_get("documents".parse()?, assert_obj!("file" => file))
+            .await?;
+        let update = DocumentUpdate {
+            id: document.id(),
+            old_document: None,
+            new_document: Some(document.clone()),
+        };
+        FileTextModel::new(&mut tx, namespace)
+            .record_write(&table, &update)
+            .await?;
+        let extraction = FileTextModel::new(&mut tx, namespace)
+            .next_pending()
+            .await?
+            .unwrap();
+        assert_eq!(extraction.storage_id, file);
+
+        // Replacing the file before the text is written requeues the document
+        // and the stale extraction is dropped.
+        let new_file = store_file(&rt, &mut tx).await?;
+        UserFacingModel::new(&mut tx, namespace)
+            .patch(
+                document.id().developer_id,
+                PatchValue::from(assert_obj!("file" => new_file)),
+            )
+            .await?;
+        let new_document = tx.get(document.id()).await?.unwrap();
+        let update = DocumentUpdate {
+            id: document.id(),
+            old_document: Some(document),
+            new_document: Some(new_document.clone()),
+        };
+        let mut model = FileTextModel::new(&mut tx, namespace);
+        model.record_write(&table, &update).await?;
+        assert!(
+            !model
+                .complete(extraction, Some("stale".to_string()))
+                .await?
+        );
+        let extraction = model.next_pending().await?.unwrap();
+        assert_eq!(extraction.storage_id, new_file);
+
+        assert!(
+            model
+                .complete(extraction, Some("hello".to_string()))
+                .await?
+        );
+        assert!(model.next_pending().await?.is_none());
+        let document = tx.get(new_document.id()).await?.unwrap();
+        assert_eq!(
+            document.value().get("text"),
+            Some(&ConvexValue::try_from("hello")?)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    DeveloperDocumentId,
+};
+
+/// A document whose file's text is waiting to be extracted.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct FileTextExtraction {
+    /// The document the text is written to.
+    pub document_id: DeveloperDocumentId,
+    /// The `_storage` id of the file the document held when it was written.
+    /// The text is only written if the document still holds it.
+    pub storage_id: DeveloperDocumentId,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFileTextExtraction {
+    document_id: String,
+    storage_id: String,
+}
+
+impl TryFrom<FileTextExtraction> for SerializedFileTextExtraction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FileTextExtraction) -> anyhow::Result<Self> {
+        Ok(Self {
+            document_id: value.document_id.to_string(),
+            storage_id: value.storage_id.to_string(),
+        })
+    }
+}
+
+impl TryFrom<SerializedFileTextExtraction> for FileTextExtraction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedFileTextExtraction) -> anyhow::Result<Self> {
+        Ok(Self {
+            document_id: DeveloperDocumentId::decode(&value.document_id)?,
+            storage_id: DeveloperDocumentId::decode(&value.storage_id)?,
+        })
+    }
+}
+
+codegen_convex_serialization!(FileTextExtraction, SerializedFileTextExtraction);
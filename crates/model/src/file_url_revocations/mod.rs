@@ -0,0 +1,124 @@
+//! Signed file URLs that were revoked before they expired. Each signed URL
+//! carries a random nonce, and the storage serving path refuses URLs whose
+//! nonce appears in this table.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::FileUrlRevocation;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static FILE_URL_REVOCATIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_file_url_revocations"
+        .parse()
+        .expect("Invalid built-in file URL revocations table")
+});
+
+static NONCE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "nonce".parse().expect("Invalid built-in field"));
+
+pub static FILE_URL_REVOCATIONS_INDEX_BY_NONCE: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FILE_URL_REVOCATIONS_TABLE, "by_nonce"));
+
+pub struct FileUrlRevocationsTable;
+impl SystemTable for FileUrlRevocationsTable {
+    fn table_name(&self) -> &'static TableName {
+        &FILE_URL_REVOCATIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: FILE_URL_REVOCATIONS_INDEX_BY_NONCE.clone(),
+            fields: vec![NONCE_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FileUrlRevocation>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct FileUrlRevocationsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FileUrlRevocationsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    async fn get(
+        &mut self,
+        nonce: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<FileUrlRevocation>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: FILE_URL_REVOCATIONS_INDEX_BY_NONCE.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                NONCE_FIELD.clone(),
+                ConvexValue::try_from(nonce)?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    pub async fn is_revoked(&mut self, nonce: &str) -> anyhow::Result<bool> {
+        Ok(self.get(nonce).await?.is_some())
+    }
+
+    /// Revoke the signed URL with `nonce`. Revoking a URL twice is a no-op.
+    pub async fn revoke(&mut self, nonce: String, expires: UnixTimestamp) -> anyhow::Result<()> {
+        if self.get(&nonce).await?.is_some() {
+            return Ok(());
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert(
+                &FILE_URL_REVOCATIONS_TABLE,
+                FileUrlRevocation { nonce, expires }.try_into()?,
+            )
+            .await?;
+        Ok(())
+    }
+}
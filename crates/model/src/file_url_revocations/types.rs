@@ -0,0 +1,57 @@
+use common::runtime::UnixTimestamp;
+#[cfg(any(test, feature = "testing"))]
+use proptest::prelude::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A signed file URL that was revoked before it expired.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct FileUrlRevocation {
+    /// The nonce embedded in the revoked URL.
+    pub nonce: String,
+    /// When the revoked URL would have expired. The revocation can be deleted
+    /// after this.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "(0..1u64 << 42).prop_map(UnixTimestamp::from_millis)")
+    )]
+    pub expires: UnixTimestamp,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFileUrlRevocation {
+    nonce: String,
+    expires_ms: i64,
+}
+
+impl TryFrom<FileUrlRevocation> for SerializedFileUrlRevocation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FileUrlRevocation) -> anyhow::Result<Self> {
+        Ok(Self {
+            nonce: value.nonce,
+            expires_ms: value.expires.as_ms_since_epoch()?.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedFileUrlRevocation> for FileUrlRevocation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedFileUrlRevocation) -> anyhow::Result<Self> {
+        Ok(Self {
+            nonce: value.nonce,
+            expires: UnixTimestamp::from_millis(value.expires_ms.try_into()?),
+        })
+    }
+}
+
+codegen_convex_serialization!(FileUrlRevocation, SerializedFileUrlRevocation);
@@ -0,0 +1,263 @@
+//! Persisted function execution records. The in-memory function log only
+//! keeps the most recent executions, so a worker in the application crate
+//! copies completed executions into this table, where they can be queried
+//! until the system table cleanup worker deletes them.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        CreationTime,
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    FunctionExecutionRecord,
+    FunctionExecutionStatus,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static FUNCTION_EXECUTION_RECORDS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_function_execution_records"
+        .parse()
+        .expect("Invalid built-in function execution records table")
+});
+
+pub static FUNCTION_EXECUTION_RECORDS_INDEX_BY_FUNCTION_PATH: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FUNCTION_EXECUTION_RECORDS_TABLE, "by_function_path"));
+
+static FUNCTION_PATH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "functionPath".parse().expect("Invalid built-in field"));
+
+pub struct FunctionExecutionRecordsTable;
+impl SystemTable for FunctionExecutionRecordsTable {
+    fn table_name(&self) -> &'static TableName {
+        &FUNCTION_EXECUTION_RECORDS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: FUNCTION_EXECUTION_RECORDS_INDEX_BY_FUNCTION_PATH.clone(),
+            fields: vec![
+                FUNCTION_PATH_FIELD.clone(),
+                CREATION_TIME_FIELD_PATH.clone(),
+            ]
+            .try_into()
+            .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FunctionExecutionRecord>::try_from(document).map(|_| ())
+    }
+}
+
+/// Which records to return from `FunctionExecutionRecordModel::list`. Records
+/// are returned newest first, and time bounds apply to when the record was
+/// persisted.
+#[derive(Clone, Debug, Default)]
+pub struct FunctionExecutionRecordFilter {
+    pub function_path: Option<String>,
+    pub status: Option<FunctionExecutionStatus>,
+    /// Only return records created at or after this time.
+    pub start: Option<CreationTime>,
+    /// Only return records created before this time.
+    pub end: Option<CreationTime>,
+}
+
+pub struct FunctionExecutionRecordPage {
+    pub records: Vec<ParsedDocument<FunctionExecutionRecord>>,
+    /// Pass as the `cursor` of the next call to continue after this page, or
+    /// `None` if there are no more records.
+    pub cursor: Option<CreationTime>,
+}
+
+pub struct FunctionExecutionRecordModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FunctionExecutionRecordModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn insert(&mut self, records: Vec<FunctionExecutionRecord>) -> anyhow::Result<()> {
+        for record in records {
+            SystemMetadataModel::new_global(self.tx)
+                .insert(&FUNCTION_EXECUTION_RECORDS_TABLE, record.try_into()?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Returns up to `limit` records matching `filter` created before
+    /// `cursor`. The page can be short if the status filter skips many
+    /// records, so callers should keep paginating until the cursor is `None`.
+    pub async fn list(
+        &mut self,
+        filter: FunctionExecutionRecordFilter,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<FunctionExecutionRecordPage> {
+        let mut range = vec![];
+        let index_name = match filter.function_path {
+            Some(function_path) => {
+                range.push(IndexRangeExpression::Eq(
+                    FUNCTION_PATH_FIELD.clone(),
+                    ConvexValue::try_from(function_path)?.into(),
+                ));
+                FUNCTION_EXECUTION_RECORDS_INDEX_BY_FUNCTION_PATH.clone()
+            },
+            None => IndexName::by_creation_time(FUNCTION_EXECUTION_RECORDS_TABLE.clone()),
+        };
+        if let Some(start) = filter.start {
+            range.push(IndexRangeExpression::Gte(
+                CREATION_TIME_FIELD_PATH.clone(),
+                f64::from(start).into(),
+            ));
+        }
+        let end = match (filter.end, cursor) {
+            (Some(end), Some(cursor)) => Some(end.min(cursor)),
+            (end, cursor) => end.or(cursor),
+        };
+        if let Some(end) = end {
+            range.push(IndexRangeExpression::Lt(
+                CREATION_TIME_FIELD_PATH.clone(),
+                f64::from(end).into(),
+            ));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name,
+            range,
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut records = vec![];
+        let mut last_creation_time = None;
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            last_creation_time = doc.creation_time();
+            let record: ParsedDocument<FunctionExecutionRecord> = doc.try_into()?;
+            if filter.status.is_none_or(|status| record.status == status) {
+                records.push(record);
+            }
+            if records.len() >= limit || query_stream.is_approaching_data_limit() {
+                return Ok(FunctionExecutionRecordPage {
+                    records,
+                    cursor: last_creation_time,
+                });
+            }
+        }
+        Ok(FunctionExecutionRecordPage {
+            records,
+            cursor: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        components::ComponentPath,
+        types::UdfType,
+    };
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        function_execution_records::{
+            types::{
+                FunctionExecutionRecord,
+                FunctionExecutionStatus,
+            },
+            FunctionExecutionRecordFilter,
+            FunctionExecutionRecordModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn record(function_path: &str, status: FunctionExecutionStatus) -> FunctionExecutionRecord {
+        FunctionExecutionRecord {
+            component_path: ComponentPath::root(),
+            function_path: function_path.to_string(),
+            udf_type: UdfType::Mutation,
+            status,
+            error: None,
+            request_id: "request".to_string(),
+            execution_id: "execution".to_string(),
+            unix_timestamp_ms: 0,
+            execution_time_ms: 1,
+            cached_result: false,
+            rows_read: 0,
+            rows_written: 1,
+            database_read_bytes: 0,
+            database_write_bytes: 100,
+            log_lines: vec![],
+            log_lines_truncated: false,
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_list_function_execution_records(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionExecutionRecordModel::new(&mut tx);
+        model
+            .insert(vec![
+                record("a.js:run", FunctionExecutionStatus::Success),
+                record("b.js:run", FunctionExecutionStatus::Failure),
+                record("a.js:run", FunctionExecutionStatus::Failure),
+            ])
+            .await?;
+
+        let page = model
+            .list(FunctionExecutionRecordFilter::default(), None, 2)
+            .await?;
+        assert_eq!(page.records.len(), 2);
+        // Newest first.
+        assert_eq!(page.records[0].function_path, "a.js:run");
+        assert_eq!(page.records[1].function_path, "b.js:run");
+        let page = model
+            .list(FunctionExecutionRecordFilter::default(), page.cursor, 2)
+            .await?;
+        assert_eq!(page.records.len(), 1);
+        assert!(page.cursor.is_none());
+
+        let filter = FunctionExecutionRecordFilter {
+            function_path: Some("a.js:run".to_string()),
+            status: Some(FunctionExecutionStatus::Failure),
+            ..Default::default()
+        };
+        let page = model.list(filter, None, 10).await?;
+        assert_eq!(page.records.len(), 1);
+        assert_eq!(page.records[0].status, FunctionExecutionStatus::Failure);
+        Ok(())
+    }
+}
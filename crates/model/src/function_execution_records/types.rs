@@ -0,0 +1,136 @@
+use common::{
+    components::ComponentPath,
+    types::UdfType,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A persisted summary of a single function execution. Records are copied
+/// from the in-memory function log so executions can be queried after they
+/// fall out of it, and are deleted once they're older than the retention
+/// period.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FunctionExecutionRecord {
+    pub component_path: ComponentPath,
+    /// The function's path, or the route for HTTP actions.
+    pub function_path: String,
+    pub udf_type: UdfType,
+    pub status: FunctionExecutionStatus,
+    pub error: Option<String>,
+    pub request_id: String,
+    pub execution_id: String,
+    /// When the execution was logged, in milliseconds since the epoch.
+    pub unix_timestamp_ms: u64,
+    pub execution_time_ms: u64,
+    pub cached_result: bool,
+    pub rows_read: u64,
+    pub rows_written: u64,
+    pub database_read_bytes: u64,
+    pub database_write_bytes: u64,
+    pub log_lines: Vec<String>,
+    /// Set if the function logged more lines than we keep in a record.
+    pub log_lines_truncated: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum FunctionExecutionStatus {
+    Success,
+    Failure,
+}
+
+impl FunctionExecutionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FunctionExecutionStatus::Success => "success",
+            FunctionExecutionStatus::Failure => "failure",
+        }
+    }
+}
+
+impl std::str::FromStr for FunctionExecutionStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "success" => Ok(FunctionExecutionStatus::Success),
+            "failure" => Ok(FunctionExecutionStatus::Failure),
+            _ => anyhow::bail!("Invalid function execution status {s:?}"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFunctionExecutionRecord {
+    component_path: Option<String>,
+    function_path: String,
+    udf_type: String,
+    status: String,
+    error: Option<String>,
+    request_id: String,
+    execution_id: String,
+    unix_timestamp_ms: i64,
+    execution_time_ms: i64,
+    cached_result: bool,
+    rows_read: i64,
+    rows_written: i64,
+    database_read_bytes: i64,
+    database_write_bytes: i64,
+    log_lines: Vec<String>,
+    log_lines_truncated: bool,
+}
+
+impl From<FunctionExecutionRecord> for SerializedFunctionExecutionRecord {
+    fn from(record: FunctionExecutionRecord) -> Self {
+        Self {
+            component_path: record.component_path.serialize(),
+            function_path: record.function_path,
+            udf_type: record.udf_type.to_string(),
+            status: record.status.as_str().to_string(),
+            error: record.error,
+            request_id: record.request_id,
+            execution_id: record.execution_id,
+            unix_timestamp_ms: record.unix_timestamp_ms as i64,
+            execution_time_ms: record.execution_time_ms as i64,
+            cached_result: record.cached_result,
+            rows_read: record.rows_read as i64,
+            rows_written: record.rows_written as i64,
+            database_read_bytes: record.database_read_bytes as i64,
+            database_write_bytes: record.database_write_bytes as i64,
+            log_lines: record.log_lines,
+            log_lines_truncated: record.log_lines_truncated,
+        }
+    }
+}
+
+impl TryFrom<SerializedFunctionExecutionRecord> for FunctionExecutionRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedFunctionExecutionRecord) -> anyhow::Result<Self> {
+        Ok(Self {
+            component_path: ComponentPath::deserialize(value.component_path.as_deref())?,
+            function_path: value.function_path,
+            udf_type: value.udf_type.parse()?,
+            status: value.status.parse()?,
+            error: value.error,
+            request_id: value.request_id,
+            execution_id: value.execution_id,
+            unix_timestamp_ms: value.unix_timestamp_ms as u64,
+            execution_time_ms: value.execution_time_ms as u64,
+            cached_result: value.cached_result,
+            rows_read: value.rows_read as u64,
+            rows_written: value.rows_written as u64,
+            database_read_bytes: value.database_read_bytes as u64,
+            database_write_bytes: value.database_write_bytes as u64,
+            log_lines: value.log_lines,
+            log_lines_truncated: value.log_lines_truncated,
+        })
+    }
+}
+
+codegen_convex_serialization!(FunctionExecutionRecord, SerializedFunctionExecutionRecord);
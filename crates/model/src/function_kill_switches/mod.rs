@@ -0,0 +1,304 @@
+//! Kill switches that disable functions at runtime, without a push. Calls to a
+//! disabled function fail with an error, and the scheduler leaves jobs that
+//! target it pending until it's enabled again.
+
+use std::sync::LazyLock;
+
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentId,
+        ComponentPath,
+    },
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    BootstrapComponentsModel,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use sync_types::CanonicalizedUdfPath;
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    FunctionKillSwitch,
+    KillSwitchTarget,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static FUNCTION_KILL_SWITCHES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_function_kill_switches"
+        .parse()
+        .expect("Invalid built-in function kill switches table")
+});
+
+pub static FUNCTION_KILL_SWITCHES_BY_COMPONENT: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FUNCTION_KILL_SWITCHES_TABLE, "by_component"));
+
+static COMPONENT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "component".parse().expect("invalid component field"));
+
+pub struct FunctionKillSwitchesTable;
+impl SystemTable for FunctionKillSwitchesTable {
+    fn table_name(&self) -> &'static TableName {
+        &FUNCTION_KILL_SWITCHES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: FUNCTION_KILL_SWITCHES_BY_COMPONENT.clone(),
+            fields: vec![COMPONENT_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FunctionKillSwitch>::try_from(document).map(|_| ())
+    }
+}
+
+/// The message callers of a disabled function get.
+pub fn function_disabled_message(
+    udf_path: &CanonicalizedUdfPath,
+    kill_switch: &FunctionKillSwitch,
+) -> String {
+    let mut message = format!("Function {udf_path} has been disabled by an administrator.");
+    if let Some(reason) = &kill_switch.reason {
+        message.push_str(&format!(" Reason: {reason}"));
+    }
+    message
+}
+
+/// Every kill switch, with the path of the component it's in, for callers
+/// that identify functions by component path.
+pub struct DisabledFunctions(Vec<(ComponentPath, FunctionKillSwitch)>);
+
+impl DisabledFunctions {
+    pub fn get(&self, path: &CanonicalizedComponentFunctionPath) -> Option<&FunctionKillSwitch> {
+        self.0
+            .iter()
+            .find(|(component, kill_switch)| {
+                *component == path.component && kill_switch.target.matches(&path.udf_path)
+            })
+            .map(|(_, kill_switch)| kill_switch)
+    }
+}
+
+pub struct FunctionKillSwitchesModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FunctionKillSwitchesModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn list(
+        &mut self,
+        component: ComponentId,
+    ) -> anyhow::Result<Vec<ParsedDocument<FunctionKillSwitch>>> {
+        let serialized_component = match component.serialize_to_string() {
+            Some(s) => ConvexValue::String(s.try_into()?),
+            None => ConvexValue::Null,
+        };
+        let query = Query::index_range(IndexRange {
+            index_name: FUNCTION_KILL_SWITCHES_BY_COMPONENT.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                COMPONENT_FIELD.clone(),
+                serialized_component.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut kill_switches = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            kill_switches.push(doc.try_into()?);
+        }
+        Ok(kill_switches)
+    }
+
+    /// The kill switch that disables the function, if any.
+    pub async fn disabled_by(
+        &mut self,
+        component: ComponentId,
+        udf_path: &CanonicalizedUdfPath,
+    ) -> anyhow::Result<Option<FunctionKillSwitch>> {
+        Ok(self
+            .list(component)
+            .await?
+            .into_iter()
+            .map(ParsedDocument::into_value)
+            .find(|kill_switch| kill_switch.target.matches(udf_path)))
+    }
+
+    /// Load every kill switch at once, so checking many functions doesn't
+    /// take a query each. Skips kill switches in components that have been
+    /// deleted.
+    pub async fn disabled_functions(&mut self) -> anyhow::Result<DisabledFunctions> {
+        let query = Query::full_table_scan(FUNCTION_KILL_SWITCHES_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut disabled = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            let kill_switch: ParsedDocument<FunctionKillSwitch> = doc.try_into()?;
+            let kill_switch = kill_switch.into_value();
+            if let Some(component) =
+                BootstrapComponentsModel::new(self.tx).get_component_path(kill_switch.component)
+            {
+                disabled.push((component, kill_switch));
+            }
+        }
+        Ok(DisabledFunctions(disabled))
+    }
+
+    /// Disable the function or prefix, replacing the reason if it's already
+    /// disabled.
+    pub async fn disable(&mut self, kill_switch: FunctionKillSwitch) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("disable_function"));
+        }
+        match self.get(kill_switch.component, &kill_switch.target).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), kill_switch.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&FUNCTION_KILL_SWITCHES_TABLE, kill_switch.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Remove the kill switch for the function or prefix, returning whether
+    /// there was one.
+    pub async fn enable(
+        &mut self,
+        component: ComponentId,
+        target: &KillSwitchTarget,
+    ) -> anyhow::Result<bool> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("enable_function"));
+        }
+        let Some(existing) = self.get(component, target).await? else {
+            return Ok(false);
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(true)
+    }
+
+    async fn get(
+        &mut self,
+        component: ComponentId,
+        target: &KillSwitchTarget,
+    ) -> anyhow::Result<Option<ParsedDocument<FunctionKillSwitch>>> {
+        Ok(self
+            .list(component)
+            .await?
+            .into_iter()
+            .find(|kill_switch| kill_switch.target == *target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::components::ComponentId;
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+    use value::DeveloperDocumentId;
+
+    use crate::{
+        function_kill_switches::{
+            types::{
+                FunctionKillSwitch,
+                KillSwitchTarget,
+            },
+            FunctionKillSwitchesModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_kill_switch_prefixes(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionKillSwitchesModel::new(&mut tx);
+        model
+            .disable(FunctionKillSwitch {
+                component: ComponentId::Root,
+                target: KillSwitchTarget::Prefix("admin/".to_string()),
+                reason: None,
+            })
+            .await?;
+        model
+            .disable(FunctionKillSwitch {
+                component: ComponentId::Root,
+                target: KillSwitchTarget::Function("messages:send".parse()?),
+                reason: Some("Spam".to_string()),
+            })
+            .await?;
+
+        let send = "messages:send".parse()?;
+        let list = "messages:list".parse()?;
+        let purge = "admin/users:purge".parse()?;
+        assert_eq!(
+            model
+                .disabled_by(ComponentId::Root, &send)
+                .await?
+                .and_then(|kill_switch| kill_switch.reason),
+            Some("Spam".to_string())
+        );
+        assert!(model.disabled_by(ComponentId::Root, &list).await?.is_none());
+        assert!(model
+            .disabled_by(ComponentId::Root, &purge)
+            .await?
+            .is_some());
+        assert!(model
+            .disabled_by(ComponentId::Child(DeveloperDocumentId::MIN), &purge)
+            .await?
+            .is_none());
+
+        assert!(
+            model
+                .enable(
+                    ComponentId::Root,
+                    &KillSwitchTarget::Prefix("admin/".to_string())
+                )
+                .await?
+        );
+        assert!(model
+            .disabled_by(ComponentId::Root, &purge)
+            .await?
+            .is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use common::components::ComponentId;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sync_types::CanonicalizedUdfPath;
+use value::codegen_convex_serialization;
+
+/// Stops a function, or every function under a path prefix, from running
+/// until an admin enables it again.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct FunctionKillSwitch {
+    pub component: ComponentId,
+    pub target: KillSwitchTarget,
+    /// Shown to callers along with the error.
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum KillSwitchTarget {
+    Function(CanonicalizedUdfPath),
+    /// Matches canonicalized paths like `messages.js:send` that start with
+    /// the prefix, e.g. `admin/` for every function in the `admin` directory.
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "\"[a-z/]{1,8}\""))]
+    Prefix(String),
+}
+
+impl KillSwitchTarget {
+    pub fn matches(&self, udf_path: &CanonicalizedUdfPath) -> bool {
+        match self {
+            KillSwitchTarget::Function(path) => path == udf_path,
+            KillSwitchTarget::Prefix(prefix) => udf_path.to_string().starts_with(prefix.as_str()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFunctionKillSwitch {
+    component: Option<String>,
+    udf_path: Option<String>,
+    path_prefix: Option<String>,
+    reason: Option<String>,
+}
+
+impl TryFrom<FunctionKillSwitch> for SerializedFunctionKillSwitch {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FunctionKillSwitch) -> anyhow::Result<Self> {
+        let (udf_path, path_prefix) = match value.target {
+            KillSwitchTarget::Function(path) => (Some(path.to_string()), None),
+            KillSwitchTarget::Prefix(prefix) => (None, Some(prefix)),
+        };
+        Ok(Self {
+            component: value.component.serialize_to_string(),
+            udf_path,
+            path_prefix,
+            reason: value.reason,
+        })
+    }
+}
+
+impl TryFrom<SerializedFunctionKillSwitch> for FunctionKillSwitch {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedFunctionKillSwitch) -> anyhow::Result<Self> {
+        let target = match (value.udf_path, value.path_prefix) {
+            (Some(path), None) => KillSwitchTarget::Function(path.parse()?),
+            (None, Some(prefix)) => KillSwitchTarget::Prefix(prefix),
+            _ => anyhow::bail!("Kill switch must have exactly one of udfPath and pathPrefix"),
+        };
+        Ok(Self {
+            component: ComponentId::deserialize_from_string(value.component.as_deref())?,
+            target,
+            reason: value.reason,
+        })
+    }
+}
+
+codegen_convex_serialization!(FunctionKillSwitch, SerializedFunctionKillSwitch);
@@ -0,0 +1,239 @@
+//! Which tables the HTTP document API exposes, and to whom. Every table is
+//! admin-only by default, so turning on the document API for user tokens
+//! doesn't expose anything until an admin opens up a table's reads or
+//! writes.
+
+use std::sync::LazyLock;
+
+use common::{
+    components::ComponentId,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    HttpTableAccess,
+    HttpTableAccessLevel,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static HTTP_TABLE_ACCESS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_http_table_access"
+        .parse()
+        .expect("Invalid built-in HTTP table access table")
+});
+
+pub static HTTP_TABLE_ACCESS_BY_COMPONENT_AND_TABLE: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&HTTP_TABLE_ACCESS_TABLE, "by_component_and_table"));
+
+static COMPONENT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "component".parse().expect("invalid component field"));
+static TABLE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "table".parse().expect("invalid table field"));
+
+pub struct HttpTableAccessTable;
+impl SystemTable for HttpTableAccessTable {
+    fn table_name(&self) -> &'static TableName {
+        &HTTP_TABLE_ACCESS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: HTTP_TABLE_ACCESS_BY_COMPONENT_AND_TABLE.clone(),
+            fields: vec![
+                COMPONENT_FIELD.clone(),
+                TABLE_FIELD.clone(),
+                CREATION_TIME_FIELD_PATH.clone(),
+            ]
+            .try_into()
+            .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<HttpTableAccess>::try_from(document).map(|_| ())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpTableOperation {
+    Read,
+    Write,
+}
+
+pub struct HttpTableAccessModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> HttpTableAccessModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn serialized_component(component: ComponentId) -> anyhow::Result<ConvexValue> {
+        Ok(match component.serialize_to_string() {
+            Some(s) => ConvexValue::String(s.try_into()?),
+            None => ConvexValue::Null,
+        })
+    }
+
+    async fn get(
+        &mut self,
+        component: ComponentId,
+        table_name: &TableName,
+    ) -> anyhow::Result<Option<ParsedDocument<HttpTableAccess>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: HTTP_TABLE_ACCESS_BY_COMPONENT_AND_TABLE.clone(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    COMPONENT_FIELD.clone(),
+                    Self::serialized_component(component)?.into(),
+                ),
+                IndexRangeExpression::Eq(
+                    TABLE_FIELD.clone(),
+                    ConvexValue::String(table_name.to_string().try_into()?).into(),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// The access levels in effect for the table, which are admin-only if
+    /// none were set.
+    pub async fn get_access(
+        &mut self,
+        component: ComponentId,
+        table_name: &TableName,
+    ) -> anyhow::Result<HttpTableAccess> {
+        Ok(self
+            .get(component, table_name)
+            .await?
+            .map(ParsedDocument::into_value)
+            .unwrap_or_else(|| HttpTableAccess::admin_only(component, table_name.clone())))
+    }
+
+    /// The component's tables that aren't admin-only.
+    pub async fn list(&mut self, component: ComponentId) -> anyhow::Result<Vec<HttpTableAccess>> {
+        let query = Query::index_range(IndexRange {
+            index_name: HTTP_TABLE_ACCESS_BY_COMPONENT_AND_TABLE.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                COMPONENT_FIELD.clone(),
+                Self::serialized_component(component)?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut access = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            access.push(ParsedDocument::<HttpTableAccess>::try_from(document)?.into_value());
+        }
+        Ok(access)
+    }
+
+    /// Replace the table's access levels. Making a table admin-only again
+    /// removes its entry.
+    pub async fn set(&mut self, access: HttpTableAccess) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("set_http_table_access"));
+        }
+        anyhow::ensure!(
+            !access.table_name.is_system(),
+            ErrorMetadata::bad_request(
+                "InvalidTableName",
+                format!(
+                    "System table {} can't be exposed through the HTTP document API",
+                    access.table_name
+                ),
+            )
+        );
+        let existing = self.get(access.component, &access.table_name).await?;
+        match (existing, access.is_admin_only()) {
+            (Some(existing), true) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .delete(existing.id())
+                    .await?;
+            },
+            (Some(existing), false) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), access.try_into()?)
+                    .await?;
+            },
+            (None, true) => {},
+            (None, false) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&HTTP_TABLE_ACCESS_TABLE, access.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Fail unless the transaction's identity may perform `operation` on the
+    /// table through the HTTP document API.
+    pub async fn check(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+        operation: HttpTableOperation,
+    ) -> anyhow::Result<()> {
+        let identity = self.tx.identity().clone();
+        if identity.is_admin() || identity.is_system() {
+            return Ok(());
+        }
+        let access = self
+            .get_access(ComponentId::from(namespace), table_name)
+            .await?;
+        let (level, verb) = match operation {
+            HttpTableOperation::Read => (access.read, "read"),
+            HttpTableOperation::Write => (access.write, "written"),
+        };
+        if level.allows(&identity) {
+            return Ok(());
+        }
+        let msg = format!("Table {table_name} can't be {verb} through the HTTP document API");
+        if level == HttpTableAccessLevel::Authenticated && matches!(identity, Identity::Unknown) {
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "TableAccessDenied",
+                format!("{msg} without signing in"),
+            ));
+        }
+        anyhow::bail!(ErrorMetadata::forbidden("TableAccessDenied", msg));
+    }
+}
@@ -0,0 +1,120 @@
+use common::components::ComponentId;
+use keybroker::Identity;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    TableName,
+};
+
+/// Who may use a table through the HTTP document API.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum HttpTableAccessLevel {
+    /// Only admin keys.
+    #[default]
+    Admin,
+    /// Any request with a valid user token, as well as admin keys.
+    Authenticated,
+    /// Every request, including ones without credentials.
+    Public,
+}
+
+impl HttpTableAccessLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpTableAccessLevel::Admin => "admin",
+            HttpTableAccessLevel::Authenticated => "authenticated",
+            HttpTableAccessLevel::Public => "public",
+        }
+    }
+
+    pub fn allows(&self, identity: &Identity) -> bool {
+        if identity.is_admin() || identity.is_system() {
+            return true;
+        }
+        match self {
+            HttpTableAccessLevel::Admin => false,
+            HttpTableAccessLevel::Authenticated => {
+                matches!(identity, Identity::User(_) | Identity::ActingUser(..))
+            },
+            HttpTableAccessLevel::Public => true,
+        }
+    }
+}
+
+impl TryFrom<String> for HttpTableAccessLevel {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> anyhow::Result<Self> {
+        match &value[..] {
+            "admin" => Ok(HttpTableAccessLevel::Admin),
+            "authenticated" => Ok(HttpTableAccessLevel::Authenticated),
+            "public" => Ok(HttpTableAccessLevel::Public),
+            _ => anyhow::bail!("Invalid HTTP table access level {value}"),
+        }
+    }
+}
+
+/// Which identities may read and write a table through the HTTP document
+/// API. Tables without an entry are admin-only in both directions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct HttpTableAccess {
+    pub component: ComponentId,
+    pub table_name: TableName,
+    pub read: HttpTableAccessLevel,
+    pub write: HttpTableAccessLevel,
+}
+
+impl HttpTableAccess {
+    pub fn admin_only(component: ComponentId, table_name: TableName) -> Self {
+        Self {
+            component,
+            table_name,
+            read: HttpTableAccessLevel::Admin,
+            write: HttpTableAccessLevel::Admin,
+        }
+    }
+
+    pub fn is_admin_only(&self) -> bool {
+        self.read == HttpTableAccessLevel::Admin && self.write == HttpTableAccessLevel::Admin
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedHttpTableAccess {
+    component: Option<String>,
+    table: String,
+    read: String,
+    write: String,
+}
+
+impl From<HttpTableAccess> for SerializedHttpTableAccess {
+    fn from(value: HttpTableAccess) -> Self {
+        Self {
+            component: value.component.serialize_to_string(),
+            table: value.table_name.to_string(),
+            read: value.read.as_str().to_string(),
+            write: value.write.as_str().to_string(),
+        }
+    }
+}
+
+impl TryFrom<SerializedHttpTableAccess> for HttpTableAccess {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedHttpTableAccess) -> anyhow::Result<Self> {
+        Ok(Self {
+            component: ComponentId::deserialize_from_string(value.component.as_deref())?,
+            table_name: value.table.parse()?,
+            read: value.read.try_into()?,
+            write: value.write.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(HttpTableAccess, SerializedHttpTableAccess);
@@ -0,0 +1,135 @@
+//! Transformed images generated when serving image URLs. The transformed
+//! bytes live in object storage, and this table maps each source file and
+//! transform to the object holding the result, so each transform only has to
+//! be computed once.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexName,
+        StorageUuid,
+    },
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::ImageTransformCacheEntry;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static IMAGE_TRANSFORM_CACHE_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_image_transform_cache"
+        .parse()
+        .expect("Invalid built-in image transform cache table")
+});
+
+static SOURCE_STORAGE_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "sourceStorageId".parse().expect("Invalid built-in field"));
+static PARAMS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "params".parse().expect("Invalid built-in field"));
+
+pub static IMAGE_TRANSFORM_CACHE_INDEX_BY_SOURCE_AND_PARAMS: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&IMAGE_TRANSFORM_CACHE_TABLE, "by_source_and_params"));
+
+pub struct ImageTransformCacheTable;
+impl SystemTable for ImageTransformCacheTable {
+    fn table_name(&self) -> &'static TableName {
+        &IMAGE_TRANSFORM_CACHE_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: IMAGE_TRANSFORM_CACHE_INDEX_BY_SOURCE_AND_PARAMS.clone(),
+            fields: vec![
+                SOURCE_STORAGE_ID_FIELD.clone(),
+                PARAMS_FIELD.clone(),
+                CREATION_TIME_FIELD_PATH.clone(),
+            ]
+            .try_into()
+            .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ImageTransformCacheEntry>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct ImageTransformCacheModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ImageTransformCacheModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn get(
+        &mut self,
+        source_storage_id: &StorageUuid,
+        params: &str,
+    ) -> anyhow::Result<Option<ImageTransformCacheEntry>> {
+        let query = Query::index_range(IndexRange {
+            index_name: IMAGE_TRANSFORM_CACHE_INDEX_BY_SOURCE_AND_PARAMS.clone(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    SOURCE_STORAGE_ID_FIELD.clone(),
+                    ConvexValue::try_from(source_storage_id.to_string())?.into(),
+                ),
+                IndexRangeExpression::Eq(
+                    PARAMS_FIELD.clone(),
+                    ConvexValue::try_from(params)?.into(),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| Ok(ParsedDocument::<ImageTransformCacheEntry>::try_from(doc)?.into_value()))
+            .transpose()
+    }
+
+    /// Record a transformed image. If another request already cached the same
+    /// transform, returns its entry instead of inserting a new one.
+    pub async fn insert(
+        &mut self,
+        entry: ImageTransformCacheEntry,
+    ) -> anyhow::Result<Option<ImageTransformCacheEntry>> {
+        if let Some(existing) = self.get(&entry.source_storage_id, &entry.params).await? {
+            return Ok(Some(existing));
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&IMAGE_TRANSFORM_CACHE_TABLE, entry.try_into()?)
+            .await?;
+        Ok(None)
+    }
+}
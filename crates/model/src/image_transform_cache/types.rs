@@ -0,0 +1,72 @@
+use common::types::{
+    ObjectKey,
+    StorageUuid,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    sha256::Sha256Digest,
+};
+
+/// A transformed image stored in object storage, keyed by the file it was
+/// generated from and the transform that was applied.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct ImageTransformCacheEntry {
+    pub source_storage_id: StorageUuid,
+    /// The canonical form of the transform parameters.
+    pub params: String,
+    pub storage_key: ObjectKey,
+    pub sha256: Sha256Digest,
+    pub size: i64,
+    pub content_type: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedImageTransformCacheEntry {
+    source_storage_id: String,
+    params: String,
+    storage_key: String,
+    sha256: String,
+    size: i64,
+    content_type: String,
+}
+
+impl TryFrom<ImageTransformCacheEntry> for SerializedImageTransformCacheEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ImageTransformCacheEntry) -> anyhow::Result<Self> {
+        Ok(Self {
+            source_storage_id: value.source_storage_id.to_string(),
+            params: value.params,
+            storage_key: value.storage_key.into(),
+            sha256: value.sha256.as_base64(),
+            size: value.size,
+            content_type: value.content_type,
+        })
+    }
+}
+
+impl TryFrom<SerializedImageTransformCacheEntry> for ImageTransformCacheEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedImageTransformCacheEntry) -> anyhow::Result<Self> {
+        Ok(Self {
+            source_storage_id: value.source_storage_id.parse()?,
+            params: value.params,
+            storage_key: value.storage_key.try_into()?,
+            sha256: Sha256Digest::from_base64(&value.sha256)?,
+            size: value.size,
+            content_type: value.content_type,
+        })
+    }
+}
+
+codegen_convex_serialization!(ImageTransformCacheEntry, SerializedImageTransformCacheEntry);
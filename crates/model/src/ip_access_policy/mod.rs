@@ -0,0 +1,109 @@
+//! The deployment's IP access policy, which restricts the client addresses
+//! that may call the admin APIs, the client APIs and HTTP actions, and that
+//! particular identities may authenticate from. A deployment without a policy
+//! document allows every address.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use self::types::IpAccessPolicy;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static IP_ACCESS_POLICY_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_ip_access_policy"
+        .parse()
+        .expect("Invalid built-in IP access policy table")
+});
+
+pub struct IpAccessPolicyTable;
+impl SystemTable for IpAccessPolicyTable {
+    fn table_name(&self) -> &'static TableName {
+        &IP_ACCESS_POLICY_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<IpAccessPolicy>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct IpAccessPolicyModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> IpAccessPolicyModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    async fn get_document(&mut self) -> anyhow::Result<Option<ParsedDocument<IpAccessPolicy>>> {
+        let query = Query::full_table_scan(IP_ACCESS_POLICY_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// The policy in effect, falling back to allowing every address if none
+    /// is set.
+    pub async fn get(&mut self) -> anyhow::Result<IpAccessPolicy> {
+        Ok(self
+            .get_document()
+            .await?
+            .map(ParsedDocument::into_value)
+            .unwrap_or_default())
+    }
+
+    /// Replace the deployment's policy, returning the previous one.
+    pub async fn set(&mut self, policy: IpAccessPolicy) -> anyhow::Result<IpAccessPolicy> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("set_ip_access_policy"));
+        }
+        match self.get_document().await? {
+            Some(existing) => {
+                let (id, previous) = existing.into_id_and_value();
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(id, policy.try_into()?)
+                    .await?;
+                Ok(previous)
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&IP_ACCESS_POLICY_TABLE, policy.try_into()?)
+                    .await?;
+                Ok(IpAccessPolicy::default())
+            },
+        }
+    }
+}
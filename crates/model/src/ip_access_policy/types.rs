@@ -0,0 +1,505 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+        Ipv6Addr,
+    },
+    str::FromStr,
+};
+
+use common::types::MemberId;
+use errors::ErrorMetadata;
+use keybroker::{
+    AdminIdentityPrincipal,
+    Identity,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Which client IP addresses may reach the deployment. The global rules apply
+/// to every request, and each surface has its own rules on top. Identities
+/// with their own rules are also held to them whenever they authenticate.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct IpAccessPolicy {
+    pub global: IpAccessRules,
+    pub admin: IpAccessRules,
+    pub client: IpAccessRules,
+    pub http_actions: IpAccessRules,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::btree_map(proptest::prelude::any::<IpAccessIdentity>(), \
+                        proptest::prelude::any::<IpAccessRules>(), 0..4)"
+        )
+    )]
+    pub identities: BTreeMap<IpAccessIdentity, IpAccessRules>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct IpAccessRules {
+    /// If non-empty, only addresses in one of these networks are allowed.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::vec(proptest::prelude::any::<IpNetwork>(), 0..4)"
+        )
+    )]
+    pub allowed: Vec<IpNetwork>,
+    /// Addresses in one of these networks are always denied.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::vec(proptest::prelude::any::<IpNetwork>(), 0..4)"
+        )
+    )]
+    pub denied: Vec<IpNetwork>,
+}
+
+/// The entry points that have their own rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum IpAccessSurface {
+    /// The CLI and dashboard APIs, authenticated with admin keys.
+    Admin,
+    /// The sync WebSocket and the public function and storage APIs.
+    Client,
+    HttpActions,
+}
+
+/// Who per-identity rules apply to: an admin team member, or a user signed in
+/// through an auth provider, by their `tokenIdentifier`. Written as
+/// `member:<member ID>` or `user:<token identifier>`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum IpAccessIdentity {
+    Member(MemberId),
+    User(
+        #[cfg_attr(any(test, feature = "testing"), proptest(regex = "[a-z0-9:/.|]{1,32}"))] String,
+    ),
+}
+
+impl IpAccessIdentity {
+    /// The identity whose rules apply to a request made as `identity`, if it
+    /// can have any. Admins acting as a user are held to their own rules.
+    pub fn from_identity(identity: &Identity) -> Option<Self> {
+        match identity {
+            Identity::InstanceAdmin(admin) | Identity::ActingUser(admin, _) => {
+                match admin.principal() {
+                    AdminIdentityPrincipal::Member(member_id) => Some(Self::Member(*member_id)),
+                    AdminIdentityPrincipal::Team(_) => None,
+                }
+            },
+            Identity::User(user) => Some(Self::User(user.attributes.token_identifier.0.clone())),
+            Identity::System(_) | Identity::Unknown => None,
+        }
+    }
+}
+
+impl fmt::Display for IpAccessIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Member(member_id) => write!(f, "member:{member_id}"),
+            Self::User(token_identifier) => write!(f, "user:{token_identifier}"),
+        }
+    }
+}
+
+impl FromStr for IpAccessIdentity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let identity = match s.split_once(':') {
+            Some(("member", member_id)) => member_id.parse().ok().map(Self::Member),
+            Some(("user", token_identifier)) if !token_identifier.is_empty() => {
+                Some(Self::User(token_identifier.to_string()))
+            },
+            _ => None,
+        };
+        identity.ok_or_else(|| {
+            ErrorMetadata::bad_request(
+                "InvalidIpAccessIdentity",
+                format!(
+                    "Invalid identity {s:?}. Expected member:<member ID> or user:<token \
+                     identifier>"
+                ),
+            )
+            .into()
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum IpAccessDenialReason {
+    Denied,
+    NotAllowed,
+}
+
+impl IpAccessPolicy {
+    pub fn rules(&self, surface: IpAccessSurface) -> &IpAccessRules {
+        match surface {
+            IpAccessSurface::Admin => &self.admin,
+            IpAccessSurface::Client => &self.client,
+            IpAccessSurface::HttpActions => &self.http_actions,
+        }
+    }
+
+    /// Checks a request from `ip` against the global rules and the surface's
+    /// rules. Deny rules take precedence over allow rules, and an address
+    /// must be in both allowlists if both are set.
+    pub fn check(&self, surface: IpAccessSurface, ip: IpAddr) -> Result<(), IpAccessDenialReason> {
+        check_rules(&[&self.global, self.rules(surface)], ip)
+    }
+
+    /// Checks a request from `ip` made as `identity` against that identity's
+    /// rules, if it has any.
+    pub fn check_identity(
+        &self,
+        identity: &IpAccessIdentity,
+        ip: IpAddr,
+    ) -> Result<(), IpAccessDenialReason> {
+        match self.identities.get(identity) {
+            Some(rules) => check_rules(&[rules], ip),
+            None => Ok(()),
+        }
+    }
+}
+
+fn check_rules(rules: &[&IpAccessRules], ip: IpAddr) -> Result<(), IpAccessDenialReason> {
+    let ip = ip.to_canonical();
+    if rules
+        .iter()
+        .any(|rules| rules.denied.iter().any(|network| network.contains(ip)))
+    {
+        return Err(IpAccessDenialReason::Denied);
+    }
+    if rules.iter().any(|rules| {
+        !rules.allowed.is_empty() && !rules.allowed.iter().any(|network| network.contains(ip))
+    }) {
+        return Err(IpAccessDenialReason::NotAllowed);
+    }
+    Ok(())
+}
+
+/// An IPv4 or IPv6 network in CIDR notation, like `10.0.0.0/8`. A bare
+/// address is a network with only that address in it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    /// Clears the bits of `addr` past the prefix, so `10.1.2.3/8` is the same
+    /// network as `10.0.0.0/8`.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> anyhow::Result<Self> {
+        let addr = addr.to_canonical();
+        let addr = match addr {
+            IpAddr::V4(addr) => {
+                anyhow::ensure!(prefix_len <= 32, "IPv4 prefix length must be at most 32");
+                IpAddr::V4(Ipv4Addr::from(u32::from(addr) & v4_mask(prefix_len)))
+            },
+            IpAddr::V6(addr) => {
+                anyhow::ensure!(prefix_len <= 128, "IPv6 prefix length must be at most 128");
+                IpAddr::V6(Ipv6Addr::from(u128::from(addr) & v6_mask(prefix_len)))
+            },
+        };
+        Ok(Self { addr, prefix_len })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                u32::from(ip) & v4_mask(self.prefix_len) == u32::from(network)
+            },
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                u128::from(ip) & v6_mask(self.prefix_len) == u128::from(network)
+            },
+            _ => false,
+        }
+    }
+}
+
+fn v4_mask(prefix_len: u8) -> u32 {
+    u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0)
+}
+
+fn v6_mask(prefix_len: u8) -> u128 {
+    u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0)
+}
+
+impl FromStr for IpNetwork {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let invalid = || {
+            ErrorMetadata::bad_request(
+                "InvalidIpNetwork",
+                format!(
+                    "Invalid IP network {s:?}. Expected an address like 203.0.113.7 or a CIDR \
+                     range like 10.0.0.0/8"
+                ),
+            )
+        };
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (
+                addr.parse::<IpAddr>().map_err(|_| invalid())?,
+                Some(prefix_len.parse::<u8>().map_err(|_| invalid())?),
+            ),
+            None => (s.parse::<IpAddr>().map_err(|_| invalid())?, None),
+        };
+        let prefix_len = prefix_len.unwrap_or(match addr.to_canonical() {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        });
+        Self::new(addr, prefix_len).map_err(|_| invalid().into())
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl proptest::arbitrary::Arbitrary for IpNetwork {
+    type Parameters = ();
+
+    type Strategy = impl proptest::strategy::Strategy<Value = IpNetwork>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+        let v4 = (any::<Ipv4Addr>(), 0..=32u8)
+            .prop_map(|(addr, prefix_len)| (IpAddr::from(addr), prefix_len));
+        let v6 = (any::<Ipv6Addr>(), 0..=128u8)
+            .prop_filter("IPv4-mapped address", |(addr, _)| {
+                addr.to_ipv4_mapped().is_none()
+            })
+            .prop_map(|(addr, prefix_len)| (IpAddr::from(addr), prefix_len));
+        prop_oneof![v4, v6].prop_map(|(addr, prefix_len)| IpNetwork::new(addr, prefix_len).unwrap())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedIpAccessPolicy {
+    global: SerializedIpAccessRules,
+    admin: SerializedIpAccessRules,
+    client: SerializedIpAccessRules,
+    http_actions: SerializedIpAccessRules,
+    // Missing from documents written before per-identity rules existed.
+    identities: Option<Vec<SerializedIdentityIpAccessRules>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedIpAccessRules {
+    allowed: Vec<String>,
+    denied: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedIdentityIpAccessRules {
+    identity: String,
+    rules: SerializedIpAccessRules,
+}
+
+impl From<IpAccessRules> for SerializedIpAccessRules {
+    fn from(value: IpAccessRules) -> Self {
+        Self {
+            allowed: value.allowed.iter().map(IpNetwork::to_string).collect(),
+            denied: value.denied.iter().map(IpNetwork::to_string).collect(),
+        }
+    }
+}
+
+impl TryFrom<SerializedIpAccessRules> for IpAccessRules {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedIpAccessRules) -> anyhow::Result<Self> {
+        Ok(Self {
+            allowed: value.allowed.iter().map(|s| s.parse()).try_collect()?,
+            denied: value.denied.iter().map(|s| s.parse()).try_collect()?,
+        })
+    }
+}
+
+impl From<IpAccessPolicy> for SerializedIpAccessPolicy {
+    fn from(value: IpAccessPolicy) -> Self {
+        Self {
+            global: value.global.into(),
+            admin: value.admin.into(),
+            client: value.client.into(),
+            http_actions: value.http_actions.into(),
+            identities: Some(
+                value
+                    .identities
+                    .into_iter()
+                    .map(|(identity, rules)| SerializedIdentityIpAccessRules {
+                        identity: identity.to_string(),
+                        rules: rules.into(),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl TryFrom<SerializedIpAccessPolicy> for IpAccessPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedIpAccessPolicy) -> anyhow::Result<Self> {
+        Ok(Self {
+            global: value.global.try_into()?,
+            admin: value.admin.try_into()?,
+            client: value.client.try_into()?,
+            http_actions: value.http_actions.try_into()?,
+            identities: value
+                .identities
+                .unwrap_or_default()
+                .into_iter()
+                .map(|SerializedIdentityIpAccessRules { identity, rules }| {
+                    anyhow::Ok((identity.parse()?, rules.try_into()?))
+                })
+                .try_collect()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(IpAccessPolicy, SerializedIpAccessPolicy);
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use common::types::MemberId;
+    use maplit::btreemap;
+
+    use super::{
+        IpAccessDenialReason,
+        IpAccessIdentity,
+        IpAccessPolicy,
+        IpAccessRules,
+        IpAccessSurface,
+        IpNetwork,
+    };
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn networks(networks: &[&str]) -> Vec<IpNetwork> {
+        networks.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_networks() -> anyhow::Result<()> {
+        assert_eq!("10.1.2.3/8".parse::<IpNetwork>()?.to_string(), "10.0.0.0/8");
+        assert_eq!(
+            "203.0.113.7".parse::<IpNetwork>()?.to_string(),
+            "203.0.113.7/32"
+        );
+        assert_eq!(
+            "2001:db8::1/32".parse::<IpNetwork>()?.to_string(),
+            "2001:db8::/32"
+        );
+        assert_eq!(
+            "::ffff:10.0.0.1".parse::<IpNetwork>()?.to_string(),
+            "10.0.0.1/32"
+        );
+        assert_eq!("0.0.0.0/0".parse::<IpNetwork>()?.to_string(), "0.0.0.0/0");
+        for invalid in ["10.0.0.0/33", "::/129", "10.0.0.0/", "example.com", ""] {
+            assert!(invalid.parse::<IpNetwork>().is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_contains() -> anyhow::Result<()> {
+        let network: IpNetwork = "192.168.0.0/16".parse()?;
+        assert!(network.contains(ip("192.168.4.1")));
+        assert!(network.contains(ip("::ffff:192.168.4.1")));
+        assert!(!network.contains(ip("192.169.0.1")));
+        assert!(!network.contains(ip("::1")));
+        assert!("0.0.0.0/0".parse::<IpNetwork>()?.contains(ip("8.8.8.8")));
+        assert!("fd00::/8".parse::<IpNetwork>()?.contains(ip("fd12::1")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_rules() {
+        let policy = IpAccessPolicy {
+            global: IpAccessRules {
+                allowed: vec![],
+                denied: networks(&["198.51.100.0/24"]),
+            },
+            admin: IpAccessRules {
+                allowed: networks(&["10.0.0.0/8"]),
+                denied: networks(&["10.0.0.13"]),
+            },
+            ..Default::default()
+        };
+        assert!(policy.check(IpAccessSurface::Client, ip("8.8.8.8")).is_ok());
+        assert!(policy.check(IpAccessSurface::Admin, ip("10.1.2.3")).is_ok());
+        assert_eq!(
+            policy.check(IpAccessSurface::Admin, ip("8.8.8.8")),
+            Err(IpAccessDenialReason::NotAllowed)
+        );
+        assert_eq!(
+            policy.check(IpAccessSurface::Admin, ip("10.0.0.13")),
+            Err(IpAccessDenialReason::Denied)
+        );
+        assert_eq!(
+            policy.check(IpAccessSurface::HttpActions, ip("198.51.100.9")),
+            Err(IpAccessDenialReason::Denied)
+        );
+        assert!(IpAccessPolicy::default()
+            .check(IpAccessSurface::Admin, ip("::1"))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_identity_rules() -> anyhow::Result<()> {
+        let member: IpAccessIdentity = "member:7".parse()?;
+        assert_eq!(member, IpAccessIdentity::Member(MemberId(7)));
+        let user: IpAccessIdentity = "user:https://auth.example.com|ada".parse()?;
+        assert_eq!(
+            user,
+            IpAccessIdentity::User("https://auth.example.com|ada".to_string())
+        );
+        assert_eq!(user.to_string(), "user:https://auth.example.com|ada");
+        for invalid in ["member:ada", "user:", "team:1", "ada"] {
+            assert!(invalid.parse::<IpAccessIdentity>().is_err(), "{invalid}");
+        }
+
+        let policy = IpAccessPolicy {
+            identities: btreemap! {
+                member.clone() => IpAccessRules {
+                    allowed: networks(&["10.0.0.0/8"]),
+                    denied: vec![],
+                },
+            },
+            ..Default::default()
+        };
+        assert!(policy.check_identity(&member, ip("10.1.2.3")).is_ok());
+        assert_eq!(
+            policy.check_identity(&member, ip("8.8.8.8")),
+            Err(IpAccessDenialReason::NotAllowed)
+        );
+        // Identities without rules, and the surfaces, aren't restricted.
+        assert!(policy.check_identity(&user, ip("8.8.8.8")).is_ok());
+        assert!(policy.check(IpAccessSurface::Admin, ip("8.8.8.8")).is_ok());
+        Ok(())
+    }
+}
@@ -66,6 +66,7 @@ pub use database::defaults::{
     SystemTable,
 };
 use database::{
+    defaults::bootstrap_system_tables,
     ComponentDefinitionsTable,
     ComponentsTable,
     Database,
@@ -106,44 +107,101 @@ use value::{
 };
 
 use crate::{
+    anonymous_identities::AnonymousIdentitiesTable,
+    append_only_lists::ListEntriesTable,
     auth::AuthTable,
     backend_state::BackendStateModel,
+    builtin_auth::{
+        BuiltinAuthCredentialsTable,
+        BuiltinAuthPasskeyChallengesTable,
+        BuiltinAuthPasskeysTable,
+        BuiltinAuthSessionsTable,
+        BuiltinAuthUsersTable,
+    },
+    component_limits::ComponentLimitsTable,
+    component_versions::ComponentVersionsTable,
     cron_jobs::{
         CronJobLogsTable,
         CronJobsTable,
     },
+    data_migrations::DataMigrationsTable,
     deployment_audit_log::DeploymentAuditLogsTable,
+    deployment_config::{
+        DeploymentConfigTable,
+        DEPLOYMENT_CONFIG_VIRTUAL_TABLE,
+    },
     environment_variables::EnvironmentVariablesTable,
     exports::ExportsTable,
     external_packages::ExternalPackagesTable,
+    file_attachments::FileAttachmentsTable,
     file_storage::FileStorageTable,
+    file_text::FileTextExtractionsTable,
+    file_url_revocations::FileUrlRevocationsTable,
+    function_execution_records::FunctionExecutionRecordsTable,
+    function_kill_switches::FunctionKillSwitchesTable,
+    http_table_access::HttpTableAccessTable,
+    image_transform_cache::ImageTransformCacheTable,
+    ip_access_policy::IpAccessPolicyTable,
+    maintenance_windows::MaintenanceWindowsTable,
     modules::ModulesTable,
+    outbound_network_policy::OutboundNetworkPolicyTable,
+    preview_deployments::PreviewDeploymentsTable,
     scheduled_jobs::ScheduledJobsTable,
+    schema_validation_reports::SchemaValidationReportsTable,
     session_requests::SessionRequestsTable,
+    sharded_counters::CounterShardsTable,
+    slow_queries::SlowQueriesTable,
     snapshot_imports::SnapshotImportsTable,
     source_packages::SourcePackagesTable,
+    static_assets::StaticAssetsTable,
+    traffic_splits::TrafficSplitsTable,
     udf_config::UdfConfigTable,
+    usage_exports::UsageExportsTable,
 };
 
+pub mod anonymous_identities;
+pub mod append_only_lists;
 pub mod auth;
 pub mod backend_state;
+pub mod builtin_auth;
+pub mod component_limits;
+pub mod component_versions;
 pub mod components;
 pub mod config;
 pub mod cron_jobs;
+pub mod data_migrations;
 pub mod database_globals;
 pub mod deployment_audit_log;
+pub mod deployment_config;
 pub mod environment_variables;
 pub mod exports;
 pub mod external_packages;
+pub mod file_attachments;
 pub mod file_storage;
+pub mod file_text;
+pub mod file_url_revocations;
+pub mod function_execution_records;
+pub mod function_kill_switches;
+pub mod http_table_access;
+pub mod image_transform_cache;
+pub mod ip_access_policy;
+pub mod maintenance_windows;
 mod metrics;
 pub mod migrations;
 pub mod modules;
+pub mod outbound_network_policy;
+pub mod preview_deployments;
 pub mod scheduled_jobs;
+pub mod schema_validation_reports;
 pub mod session_requests;
+pub mod sharded_counters;
+pub mod slow_queries;
 pub mod snapshot_imports;
 pub mod source_packages;
+pub mod static_assets;
+pub mod traffic_splits;
 pub mod udf_config;
+pub mod usage_exports;
 
 #[cfg(any(test, feature = "testing"))]
 pub mod test_helpers;
@@ -177,9 +235,37 @@ enum DefaultTableNumber {
     ComponentDefinitionsTable = 31,
     ComponentsTable = 32,
     FunctionHandlesTable = 33,
+    DataMigrations = 34,
+    SchemaValidationReports = 35,
+    FunctionExecutionRecords = 36,
+    UsageExports = 37,
+    SlowQueries = 38,
+    ComponentVersions = 39,
+    TrafficSplits = 40,
+    ComponentLimits = 41,
+    CounterShards = 42,
+    ListEntries = 43,
+    OutboundNetworkPolicy = 44,
+    FileUrlRevocations = 45,
+    ImageTransformCache = 46,
+    FileAttachments = 47,
+    FileTextExtractions = 48,
+    AnonymousIdentities = 49,
+    BuiltinAuthUsers = 50,
+    BuiltinAuthCredentials = 51,
+    BuiltinAuthSessions = 52,
+    BuiltinAuthPasskeys = 53,
+    BuiltinAuthPasskeyChallenges = 54,
+    HttpTableAccess = 55,
+    StaticAssets = 56,
+    DeploymentConfig = 57,
+    MaintenanceWindows = 58,
+    FunctionKillSwitches = 59,
+    IpAccessPolicy = 60,
+    PreviewDeployments = 61,
     // Keep this number and your user name up to date. The number makes it easy to know
     // what to use next. The username on the same line detects merge conflicts
-    // Next Number - 34 - sujayakar
+    // Next Number - 62 - sosloan
 }
 
 impl From<DefaultTableNumber> for TableNumber {
@@ -216,6 +302,34 @@ impl From<DefaultTableNumber> for &'static dyn SystemTable {
             DefaultTableNumber::ComponentDefinitionsTable => &ComponentDefinitionsTable,
             DefaultTableNumber::ComponentsTable => &ComponentsTable,
             DefaultTableNumber::FunctionHandlesTable => &FunctionHandlesTable,
+            DefaultTableNumber::DataMigrations => &DataMigrationsTable,
+            DefaultTableNumber::SchemaValidationReports => &SchemaValidationReportsTable,
+            DefaultTableNumber::FunctionExecutionRecords => &FunctionExecutionRecordsTable,
+            DefaultTableNumber::UsageExports => &UsageExportsTable,
+            DefaultTableNumber::SlowQueries => &SlowQueriesTable,
+            DefaultTableNumber::ComponentVersions => &ComponentVersionsTable,
+            DefaultTableNumber::TrafficSplits => &TrafficSplitsTable,
+            DefaultTableNumber::ComponentLimits => &ComponentLimitsTable,
+            DefaultTableNumber::CounterShards => &CounterShardsTable,
+            DefaultTableNumber::ListEntries => &ListEntriesTable,
+            DefaultTableNumber::OutboundNetworkPolicy => &OutboundNetworkPolicyTable,
+            DefaultTableNumber::FileUrlRevocations => &FileUrlRevocationsTable,
+            DefaultTableNumber::ImageTransformCache => &ImageTransformCacheTable,
+            DefaultTableNumber::FileAttachments => &FileAttachmentsTable,
+            DefaultTableNumber::FileTextExtractions => &FileTextExtractionsTable,
+            DefaultTableNumber::AnonymousIdentities => &AnonymousIdentitiesTable,
+            DefaultTableNumber::BuiltinAuthUsers => &BuiltinAuthUsersTable,
+            DefaultTableNumber::BuiltinAuthCredentials => &BuiltinAuthCredentialsTable,
+            DefaultTableNumber::BuiltinAuthSessions => &BuiltinAuthSessionsTable,
+            DefaultTableNumber::BuiltinAuthPasskeys => &BuiltinAuthPasskeysTable,
+            DefaultTableNumber::BuiltinAuthPasskeyChallenges => &BuiltinAuthPasskeyChallengesTable,
+            DefaultTableNumber::HttpTableAccess => &HttpTableAccessTable,
+            DefaultTableNumber::StaticAssets => &StaticAssetsTable,
+            DefaultTableNumber::DeploymentConfig => &DeploymentConfigTable,
+            DefaultTableNumber::MaintenanceWindows => &MaintenanceWindowsTable,
+            DefaultTableNumber::FunctionKillSwitches => &FunctionKillSwitchesTable,
+            DefaultTableNumber::IpAccessPolicy => &IpAccessPolicyTable,
+            DefaultTableNumber::PreviewDeployments => &PreviewDeploymentsTable,
         }
     }
 }
@@ -407,6 +521,29 @@ pub fn app_system_tables() -> Vec<&'static dyn SystemTable> {
         &ExportsTable,
         &SnapshotImportsTable,
         &FunctionHandlesTable,
+        &DataMigrationsTable,
+        &FunctionExecutionRecordsTable,
+        &UsageExportsTable,
+        &SlowQueriesTable,
+        &ComponentVersionsTable,
+        &TrafficSplitsTable,
+        &ComponentLimitsTable,
+        &OutboundNetworkPolicyTable,
+        &FileUrlRevocationsTable,
+        &ImageTransformCacheTable,
+        &AnonymousIdentitiesTable,
+        &BuiltinAuthUsersTable,
+        &BuiltinAuthCredentialsTable,
+        &BuiltinAuthSessionsTable,
+        &BuiltinAuthPasskeysTable,
+        &BuiltinAuthPasskeyChallengesTable,
+        &HttpTableAccessTable,
+        &StaticAssetsTable,
+        &DeploymentConfigTable,
+        &MaintenanceWindowsTable,
+        &FunctionKillSwitchesTable,
+        &IpAccessPolicyTable,
+        &PreviewDeploymentsTable,
     ];
     system_tables.extend(component_system_tables());
     system_tables
@@ -423,12 +560,20 @@ pub fn component_system_tables() -> Vec<&'static dyn SystemTable> {
         &ModulesTable,
         &UdfConfigTable,
         &SourcePackagesTable,
+        &SchemaValidationReportsTable,
+        &CounterShardsTable,
+        &ListEntriesTable,
+        &FileAttachmentsTable,
+        &FileTextExtractionsTable,
     ]
 }
 
 pub fn virtual_system_mapping() -> VirtualSystemMapping {
     let mut mapping = VirtualSystemMapping::default();
-    for table in app_system_tables() {
+    for table in app_system_tables()
+        .into_iter()
+        .chain(bootstrap_system_tables())
+    {
         if let Some((virtual_table_name, virtual_indexes, mapper)) = table.virtual_table() {
             mapping.add_table(
                 virtual_table_name,
@@ -438,6 +583,7 @@ pub fn virtual_system_mapping() -> VirtualSystemMapping {
             )
         }
     }
+    mapping.set_admin_only(&DEPLOYMENT_CONFIG_VIRTUAL_TABLE);
     mapping
 }
 
@@ -445,7 +591,10 @@ pub fn virtual_system_mapping() -> VirtualSystemMapping {
 mod test_default_table_numbers {
     use std::sync::Arc;
 
-    use common::testing::TestPersistence;
+    use common::{
+        document::CREATION_TIME_FIELD_PATH,
+        testing::TestPersistence,
+    };
     use database::{
         defaults::DEFAULT_BOOTSTRAP_TABLE_NUMBERS,
         test_helpers::{
@@ -460,6 +609,7 @@ mod test_default_table_numbers {
         test_helpers::DbFixturesWithModel,
         virtual_system_mapping,
         DEFAULT_TABLE_NUMBERS,
+        SYSTEM_INDEXES_WITHOUT_CREATION_TIME,
     };
 
     #[test]
@@ -489,6 +639,25 @@ mod test_default_table_numbers {
         }
     }
 
+    #[test]
+    fn test_system_indexes_end_with_creation_time() {
+        // Initializing an existing table fails on indexes without the
+        // tiebreak, so catch them before they break restarts.
+        for table in app_system_tables() {
+            for index in table.indexes() {
+                if SYSTEM_INDEXES_WITHOUT_CREATION_TIME.contains(&index.name) {
+                    continue;
+                }
+                assert_eq!(
+                    index.fields.last(),
+                    Some(&*CREATION_TIME_FIELD_PATH),
+                    "System index {} should end with _creationTime",
+                    index.name
+                );
+            }
+        }
+    }
+
     #[convex_macro::test_runtime]
     async fn test_initialize_model(rt: TestRuntime) -> anyhow::Result<()> {
         let args = DbFixturesArgs {
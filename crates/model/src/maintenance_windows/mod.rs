@@ -0,0 +1,324 @@
+//! Scheduled maintenance windows. The application's maintenance window worker
+//! pauses the deployment when a window starts and resumes it when the window
+//! ends, and while a window is active clients get its message instead of
+//! `PAUSED_ERROR_MESSAGE`.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        Order,
+        Query,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    types::{
+        BackendState,
+        IndexName,
+    },
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    DeveloperDocumentId,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    MaintenanceWindow,
+    MaintenanceWindowState,
+};
+use crate::{
+    backend_state::BackendStateModel,
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static MAINTENANCE_WINDOWS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_maintenance_windows"
+        .parse()
+        .expect("Invalid built-in maintenance windows table")
+});
+
+static START_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "startMs".parse().expect("Invalid built-in field"));
+
+pub static MAINTENANCE_WINDOWS_INDEX_BY_START: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&MAINTENANCE_WINDOWS_TABLE, "by_start"));
+
+pub struct MaintenanceWindowsTable;
+impl SystemTable for MaintenanceWindowsTable {
+    fn table_name(&self) -> &'static TableName {
+        &MAINTENANCE_WINDOWS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: MAINTENANCE_WINDOWS_INDEX_BY_START.clone(),
+            fields: vec![START_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<MaintenanceWindow>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct MaintenanceWindowsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> MaintenanceWindowsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// All scheduled and active windows, in the order they start.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<MaintenanceWindow>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: MAINTENANCE_WINDOWS_INDEX_BY_START.clone(),
+            range: vec![],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut windows = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            windows.push(doc.try_into()?);
+        }
+        Ok(windows)
+    }
+
+    /// Schedule the deployment to be paused from `start` until `end`, or until
+    /// the window is cancelled if there's no `end`.
+    pub async fn schedule(
+        &mut self,
+        start: UnixTimestamp,
+        end: Option<UnixTimestamp>,
+        message: Option<String>,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("schedule_maintenance_window"));
+        }
+        if let Some(end) = end {
+            anyhow::ensure!(
+                end > start,
+                ErrorMetadata::bad_request(
+                    "InvalidMaintenanceWindow",
+                    "A maintenance window must end after it starts"
+                )
+            );
+        }
+        for existing in self.list().await? {
+            let overlaps = existing.end.is_none_or(|existing_end| start < existing_end)
+                && end.is_none_or(|end| existing.start < end);
+            anyhow::ensure!(
+                !overlaps,
+                ErrorMetadata::bad_request(
+                    "OverlappingMaintenanceWindow",
+                    format!(
+                        "This maintenance window overlaps with {}",
+                        existing.developer_id().encode()
+                    )
+                )
+            );
+        }
+        let window = MaintenanceWindow {
+            start,
+            end,
+            message,
+            state: MaintenanceWindowState::Scheduled,
+        };
+        let id = SystemMetadataModel::new_global(self.tx)
+            .insert(&MAINTENANCE_WINDOWS_TABLE, window.try_into()?)
+            .await?;
+        Ok(id.into())
+    }
+
+    /// Cancel a window. Cancelling an active window ends it right away.
+    pub async fn cancel(&mut self, id: DeveloperDocumentId) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("cancel_maintenance_window"));
+        }
+        let window = self
+            .list()
+            .await?
+            .into_iter()
+            .find(|window| window.developer_id() == id)
+            .ok_or_else(|| {
+                ErrorMetadata::not_found(
+                    "MaintenanceWindowNotFound",
+                    format!("Maintenance window {} not found", id.encode()),
+                )
+            })?;
+        self.end(window).await
+    }
+
+    /// The message of the active window, if any, for clients to see instead of
+    /// the default paused message.
+    pub async fn active_message(&mut self) -> anyhow::Result<Option<String>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|window| matches!(window.state, MaintenanceWindowState::Active { .. }))
+            .and_then(|window| window.into_value().message))
+    }
+
+    /// Start and end the windows that are due at `now`, returning when the
+    /// next one is due.
+    pub async fn advance(&mut self, now: UnixTimestamp) -> anyhow::Result<Option<UnixTimestamp>> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("advance_maintenance_windows"));
+        }
+        let mut next_due = None;
+        for window in self.list().await? {
+            let due = match window.state {
+                MaintenanceWindowState::Scheduled if window.start > now => Some(window.start),
+                MaintenanceWindowState::Scheduled => {
+                    if window.end.is_some_and(|end| end <= now) {
+                        // The window passed while nothing was running to
+                        // start it, so there's nothing left to do.
+                        SystemMetadataModel::new_global(self.tx)
+                            .delete(window.id())
+                            .await?;
+                        continue;
+                    }
+                    let mut backend_state = BackendStateModel::new(self.tx);
+                    let paused_deployment =
+                        backend_state.get_backend_state().await? == BackendState::Running;
+                    if paused_deployment {
+                        backend_state
+                            .toggle_backend_state(BackendState::Paused)
+                            .await?;
+                    }
+                    let (id, mut window) = window.into_id_and_value();
+                    window.state = MaintenanceWindowState::Active { paused_deployment };
+                    let end = window.end;
+                    SystemMetadataModel::new_global(self.tx)
+                        .replace(id, window.try_into()?)
+                        .await?;
+                    end
+                },
+                MaintenanceWindowState::Active { .. } => match window.end {
+                    Some(end) if end <= now => {
+                        self.end(window).await?;
+                        continue;
+                    },
+                    end => end,
+                },
+            };
+            if let Some(due) = due {
+                next_due = Some(next_due.map_or(due, |next_due: UnixTimestamp| next_due.min(due)));
+            }
+        }
+        Ok(next_due)
+    }
+
+    /// Delete the window, resuming the deployment if the window paused it and
+    /// nobody has changed its state since.
+    async fn end(&mut self, window: ParsedDocument<MaintenanceWindow>) -> anyhow::Result<()> {
+        if let MaintenanceWindowState::Active {
+            paused_deployment: true,
+        } = window.state
+        {
+            let mut backend_state = BackendStateModel::new(self.tx);
+            if backend_state.get_backend_state().await? == BackendState::Paused {
+                backend_state
+                    .toggle_backend_state(BackendState::Running)
+                    .await?;
+            }
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .delete(window.id())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::{
+        runtime::UnixTimestamp,
+        types::BackendState,
+    };
+    use database::test_helpers::DbFixtures;
+    use errors::ErrorMetadataAnyhowExt;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        backend_state::BackendStateModel,
+        maintenance_windows::MaintenanceWindowsModel,
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_maintenance_window_pauses_and_resumes(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let start = UnixTimestamp::from_millis(1_000_000);
+        let end = start + Duration::from_secs(3600);
+        MaintenanceWindowsModel::new(&mut tx)
+            .schedule(start, Some(end), Some("Back in an hour".to_string()))
+            .await?;
+
+        // Overlapping windows are rejected.
+        let err = MaintenanceWindowsModel::new(&mut tx)
+            .schedule(end - Duration::from_secs(1), None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "OverlappingMaintenanceWindow");
+
+        let next_due = MaintenanceWindowsModel::new(&mut tx)
+            .advance(start - Duration::from_secs(1))
+            .await?;
+        assert_eq!(next_due, Some(start));
+        assert!(BackendStateModel::new(&mut tx)
+            .fail_while_not_running()
+            .await?
+            .is_ok());
+
+        let next_due = MaintenanceWindowsModel::new(&mut tx).advance(start).await?;
+        assert_eq!(next_due, Some(end));
+        let mut backend_state = BackendStateModel::new(&mut tx);
+        assert_eq!(
+            backend_state.get_backend_state().await?,
+            BackendState::Paused
+        );
+        let err = backend_state.fail_while_not_running().await?.unwrap_err();
+        assert_eq!(err.message, "Back in an hour");
+
+        let next_due = MaintenanceWindowsModel::new(&mut tx).advance(end).await?;
+        assert_eq!(next_due, None);
+        assert_eq!(
+            BackendStateModel::new(&mut tx).get_backend_state().await?,
+            BackendState::Running
+        );
+        assert!(MaintenanceWindowsModel::new(&mut tx)
+            .list()
+            .await?
+            .is_empty());
+        Ok(())
+    }
+}
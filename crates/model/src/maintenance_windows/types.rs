@@ -0,0 +1,109 @@
+use common::runtime::UnixTimestamp;
+#[cfg(any(test, feature = "testing"))]
+use proptest::prelude::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A period during which the deployment is paused, so clients get `message`
+/// instead of running functions.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct MaintenanceWindow {
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "(0..1u64 << 42).prop_map(UnixTimestamp::from_millis)")
+    )]
+    pub start: UnixTimestamp,
+    /// Windows without an end stay active until they're cancelled.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::option::of((0..1u64 << 42).prop_map(UnixTimestamp::from_millis))"
+        )
+    )]
+    pub end: Option<UnixTimestamp>,
+    /// Returned to clients instead of the default paused message.
+    pub message: Option<String>,
+    pub state: MaintenanceWindowState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum MaintenanceWindowState {
+    Scheduled,
+    /// The window has started. `paused_deployment` is false if the deployment
+    /// was already paused or disabled when it started, in which case ending
+    /// the window leaves the deployment as it was.
+    Active {
+        paused_deployment: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedMaintenanceWindow {
+    start_ms: i64,
+    end_ms: Option<i64>,
+    message: Option<String>,
+    state: SerializedMaintenanceWindowState,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedMaintenanceWindowState {
+    Scheduled,
+    #[serde(rename_all = "camelCase")]
+    Active {
+        paused_deployment: bool,
+    },
+}
+
+impl TryFrom<MaintenanceWindow> for SerializedMaintenanceWindow {
+    type Error = anyhow::Error;
+
+    fn try_from(value: MaintenanceWindow) -> anyhow::Result<Self> {
+        Ok(Self {
+            start_ms: value.start.as_ms_since_epoch()?.try_into()?,
+            end_ms: value
+                .end
+                .map(|end| anyhow::Ok(end.as_ms_since_epoch()?.try_into()?))
+                .transpose()?,
+            message: value.message,
+            state: match value.state {
+                MaintenanceWindowState::Scheduled => SerializedMaintenanceWindowState::Scheduled,
+                MaintenanceWindowState::Active { paused_deployment } => {
+                    SerializedMaintenanceWindowState::Active { paused_deployment }
+                },
+            },
+        })
+    }
+}
+
+impl TryFrom<SerializedMaintenanceWindow> for MaintenanceWindow {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedMaintenanceWindow) -> anyhow::Result<Self> {
+        Ok(Self {
+            start: UnixTimestamp::from_millis(value.start_ms.try_into()?),
+            end: value
+                .end_ms
+                .map(|end_ms| anyhow::Ok(UnixTimestamp::from_millis(end_ms.try_into()?)))
+                .transpose()?,
+            message: value.message,
+            state: match value.state {
+                SerializedMaintenanceWindowState::Scheduled => MaintenanceWindowState::Scheduled,
+                SerializedMaintenanceWindowState::Active { paused_deployment } => {
+                    MaintenanceWindowState::Active { paused_deployment }
+                },
+            },
+        })
+    }
+}
+
+codegen_convex_serialization!(MaintenanceWindow, SerializedMaintenanceWindow);
@@ -56,7 +56,7 @@ const MAX_BACKOFF: Duration = Duration::from_secs(3600);
 // migrations unless explicitly dropping support.
 // Add a user name next to the version when you make a change to highlight merge
 // conflicts.
-pub const DATABASE_VERSION: DatabaseVersion = 115; // nipunn
+pub const DATABASE_VERSION: DatabaseVersion = 117; // sosloan
 
 pub struct MigrationWorker<RT: Runtime> {
     rt: RT,
@@ -337,6 +337,14 @@ impl<RT: Runtime> MigrationWorker<RT> {
                         .await?;
                 }
             },
+            116 => {
+                // Empty migration corresponding to
+                // _scheduled_jobs.by_component_and_creation_time creation
+            },
+            117 => {
+                // Empty migration corresponding to
+                // _scheduled_jobs.by_priority_and_next_ts creation
+            },
             // NOTE: Make sure to increase DATABASE_VERSION when adding new migrations.
             _ => anyhow::bail!("Version did not define a migration! {}", to_version),
         };
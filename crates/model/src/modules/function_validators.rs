@@ -179,18 +179,170 @@ impl TryFrom<ReturnsValidator> for JsonValue {
     }
 }
 
+/**
+ * A validator for the JSON body of a request to an HTTP action.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum HttpBodyValidator {
+    Unvalidated,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "prop::collection::btree_set(any::<value::TableName>(), \
+                             1..8).prop_flat_map(any_with::<Validator>).\
+                             prop_map(HttpBodyValidator::Validated)")
+    )]
+    Validated(Validator),
+}
+
+impl HttpBodyValidator {
+    /// Check a request body, which must be JSON in the same format as function
+    /// arguments.
+    pub fn check_body(
+        &self,
+        body: &[u8],
+        table_mapping: &NamespacedTableMapping,
+        virtual_system_mapping: &VirtualSystemMapping,
+    ) -> Result<(), HttpBodyValidationError> {
+        let HttpBodyValidator::Validated(validator) = self else {
+            return Ok(());
+        };
+        let json: JsonValue = serde_json::from_slice(body)
+            .map_err(|e| HttpBodyValidationError::InvalidJson(e.to_string()))?;
+        let value = ConvexValue::try_from(json)
+            .map_err(|e| HttpBodyValidationError::InvalidJson(e.to_string()))?;
+        validator
+            .check_value(&value, table_mapping, virtual_system_mapping)
+            .map_err(|e| HttpBodyValidationError::DoesNotMatch {
+                message: e.to_string(),
+                path: e.field_path(),
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpBodyValidationError {
+    TooLarge {
+        limit: usize,
+    },
+    InvalidJson(String),
+    DoesNotMatch {
+        message: String,
+        /// Where in the body the mismatch is, e.g. `.items[2].price`.
+        path: Option<String>,
+    },
+}
+
+impl HttpBodyValidationError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            HttpBodyValidationError::TooLarge { .. } => "RequestBodyTooLarge",
+            HttpBodyValidationError::InvalidJson(_) => "InvalidRequestBody",
+            HttpBodyValidationError::DoesNotMatch { .. } => "RequestBodyValidationError",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            HttpBodyValidationError::TooLarge { limit } => {
+                format!("Request bodies checked by a validator can be at most {limit} bytes")
+            },
+            HttpBodyValidationError::InvalidJson(msg) => {
+                format!("Request body is not valid JSON: {msg}")
+            },
+            HttpBodyValidationError::DoesNotMatch { message, .. } => message.clone(),
+        }
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            HttpBodyValidationError::DoesNotMatch { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<JsonValue> for HttpBodyValidator {
+    type Error = anyhow::Error;
+
+    fn try_from(json: JsonValue) -> Result<Self, Self::Error> {
+        Ok(match json {
+            JsonValue::Null => HttpBodyValidator::Unvalidated,
+            json => HttpBodyValidator::Validated(Validator::try_from(json).map_err(|e| {
+                e.wrap_error_message(|msg| format!("Error in HTTP action body validator: {msg}"))
+            })?),
+        })
+    }
+}
+
+impl TryFrom<HttpBodyValidator> for JsonValue {
+    type Error = anyhow::Error;
+
+    fn try_from(body: HttpBodyValidator) -> Result<Self, Self::Error> {
+        match body {
+            HttpBodyValidator::Unvalidated => Ok(JsonValue::Null),
+            HttpBodyValidator::Validated(validator) => JsonValue::try_from(validator),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use cmd_util::env::env_config;
+    use common::virtual_system_mapping::VirtualSystemMapping;
     use proptest::prelude::*;
-    use serde_json::Value as JsonValue;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
     use sync_types::testing::assert_roundtrips;
+    use value::{
+        TableMapping,
+        TableNamespace,
+    };
 
     use crate::modules::function_validators::{
         ArgsValidator,
+        HttpBodyValidationError,
+        HttpBodyValidator,
         ReturnsValidator,
     };
 
+    #[test]
+    fn test_http_body_validator() -> anyhow::Result<()> {
+        let validator = HttpBodyValidator::try_from(json!({
+            "type": "object",
+            "value": {
+                "name": {"fieldType": {"type": "string"}, "optional": false},
+            },
+        }))?;
+        let table_mapping = TableMapping::new().namespace(TableNamespace::Global);
+        let check = |body: &str| {
+            validator.check_body(
+                body.as_bytes(),
+                &table_mapping,
+                &VirtualSystemMapping::default(),
+            )
+        };
+        assert_eq!(check(r#"{"name": "convex"}"#), Ok(()));
+        assert!(matches!(
+            check("{"),
+            Err(HttpBodyValidationError::InvalidJson(_))
+        ));
+        let err = check(r#"{"name": 1}"#).unwrap_err();
+        assert_eq!(err.code(), "RequestBodyValidationError");
+        assert_eq!(err.path(), Some(".name"));
+        assert_eq!(
+            HttpBodyValidator::Unvalidated.check_body(
+                b"not json",
+                &table_mapping,
+                &VirtualSystemMapping::default()
+            ),
+            Ok(())
+        );
+        Ok(())
+    }
+
     proptest! {
         #![proptest_config(
             ProptestConfig { cases: 256 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, ..ProptestConfig::default() }
@@ -204,5 +356,10 @@ mod tests {
         fn test_returns_roundtrips(v in any::<ReturnsValidator>()) {
             assert_roundtrips::<ReturnsValidator, JsonValue>(v);
         }
+
+        #[test]
+        fn test_http_body_roundtrips(v in any::<HttpBodyValidator>()) {
+            assert_roundtrips::<HttpBodyValidator, JsonValue>(v);
+        }
     }
 }
@@ -44,6 +44,7 @@ use database::{
 };
 use errors::ErrorMetadata;
 use metrics::get_module_metadata_timer;
+use regex::Regex;
 use sync_types::CanonicalizedModulePath;
 use value::{
     sha256::{
@@ -61,13 +62,17 @@ use self::{
         ModuleSource,
         SourceMap,
     },
-    types::ModuleMetadata,
+    types::{
+        ModuleBundleStats,
+        ModuleMetadata,
+    },
     user_error::{
         FunctionNotFoundError,
         ModuleNotFoundError,
     },
 };
 use crate::{
+    component_versions::ComponentVersionsModel,
     config::{
         module_loader::ModuleLoader,
         types::{
@@ -204,6 +209,55 @@ impl<'a, RT: Runtime> ModuleModel<'a, RT> {
         ModuleDiff::new(added_modules, removed_modules)
     }
 
+    /// Replace the component's modules with metadata recorded by an earlier
+    /// push. The sources are still in that push's source package, so they
+    /// don't need to be uploaded again.
+    #[fastrace::trace]
+    pub async fn restore(
+        &mut self,
+        component: ComponentId,
+        modules: Vec<ModuleMetadata>,
+    ) -> anyhow::Result<ModuleDiff> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("restore_modules"));
+        }
+        let mut added_modules = BTreeSet::new();
+        let mut remaining_modules: BTreeSet<_> = self
+            .get_application_metadata(component)
+            .await?
+            .into_iter()
+            .map(|module| module.into_value().path)
+            .collect();
+        for module in modules {
+            if !remaining_modules.remove(&module.path) {
+                added_modules.insert(module.path.clone());
+            }
+            self.put_module_metadata(
+                CanonicalizedComponentModulePath {
+                    component,
+                    module_path: module.path,
+                },
+                module.source_package_id,
+                module.analyze_result,
+                module.environment,
+                module.sha256,
+                module.bundle_stats,
+            )
+            .await?;
+        }
+
+        let mut removed_modules = BTreeSet::new();
+        for path in remaining_modules {
+            removed_modules.insert(path.clone());
+            self.delete(CanonicalizedComponentModulePath {
+                component,
+                module_path: path,
+            })
+            .await?;
+        }
+        ModuleDiff::new(added_modules, removed_modules)
+    }
+
     /// Returns the registered modules metadata, including system modules.
     #[fastrace::trace]
     pub async fn get_all_metadata(
@@ -309,6 +363,42 @@ impl<'a, RT: Runtime> ModuleModel<'a, RT> {
         Ok(Some(module_metadata))
     }
 
+    /// Get a module as of a recorded version of its component, or at the
+    /// latest version if `version` is `None`.
+    pub async fn get_metadata_at_version(
+        &mut self,
+        path: CanonicalizedComponentModulePath,
+        version: Option<u64>,
+    ) -> anyhow::Result<Option<ParsedDocument<ModuleMetadata>>> {
+        let Some(version) = version else {
+            return self.get_metadata(path).await;
+        };
+        let component_version = ComponentVersionsModel::new(self.tx)
+            .get(path.component, version)
+            .await?
+            .with_context(|| {
+                format!(
+                    "Version {version} of component {:?} not found",
+                    path.component
+                )
+            })?;
+        if !component_version
+            .modules
+            .iter()
+            .any(|module| module.path == path.module_path)
+        {
+            return Ok(None);
+        }
+        let module = component_version.map(|component_version| {
+            component_version
+                .modules
+                .into_iter()
+                .find(|module| module.path == path.module_path)
+                .context("Module disappeared from component version")
+        })?;
+        Ok(Some(module))
+    }
+
     /// Put a module's source at a given path.
     pub async fn put(
         &mut self,
@@ -330,8 +420,16 @@ impl<'a, RT: Runtime> ModuleModel<'a, RT> {
             "AnalyzedModule is required for non-dependency modules"
         );
         let sha256 = hash_module_source(&source, source_map.as_ref());
-        self.put_module_metadata(path, source_package_id, analyze_result, environment, sha256)
-            .await?;
+        let bundle_stats = module_bundle_stats(&source, source_map.as_ref(), environment);
+        self.put_module_metadata(
+            path,
+            source_package_id,
+            analyze_result,
+            environment,
+            sha256,
+            Some(bundle_stats),
+        )
+        .await?;
         Ok(())
     }
 
@@ -342,6 +440,7 @@ impl<'a, RT: Runtime> ModuleModel<'a, RT> {
         analyze_result: Option<AnalyzedModule>,
         environment: ModuleEnvironment,
         sha256: Sha256Digest,
+        bundle_stats: Option<ModuleBundleStats>,
     ) -> anyhow::Result<ResolvedDocumentId> {
         let module_id = match self.module_metadata(path.clone()).await? {
             Some(module_metadata) => {
@@ -351,6 +450,7 @@ impl<'a, RT: Runtime> ModuleModel<'a, RT> {
                     environment,
                     analyze_result: analyze_result.clone(),
                     sha256,
+                    bundle_stats: bundle_stats.clone(),
                 };
                 SystemMetadataModel::new(self.tx, path.component.into())
                     .replace(module_metadata.id(), new_metadata.try_into()?)
@@ -365,6 +465,7 @@ impl<'a, RT: Runtime> ModuleModel<'a, RT> {
                     environment,
                     analyze_result: analyze_result.clone(),
                     sha256,
+                    bundle_stats: bundle_stats.clone(),
                 };
 
                 SystemMetadataModel::new(self.tx, path.component.into())
@@ -460,9 +561,23 @@ impl<'a, RT: Runtime> ModuleModel<'a, RT> {
     pub async fn get_analyzed_function_by_id(
         &mut self,
         path: &ResolvedComponentFunctionPath,
+    ) -> anyhow::Result<anyhow::Result<AnalyzedFunction>> {
+        self.get_analyzed_function_at_version(path, None).await
+    }
+
+    /// Like `get_analyzed_function_by_id`, but looks the function up in a
+    /// recorded version of the component if `version` is set.
+    pub async fn get_analyzed_function_at_version(
+        &mut self,
+        path: &ResolvedComponentFunctionPath,
+        version: Option<u64>,
     ) -> anyhow::Result<anyhow::Result<AnalyzedFunction>> {
         let udf_path = &path.udf_path;
-        let Some(module) = self.get_metadata_for_function_by_id(path).await? else {
+        let module_path = CanonicalizedComponentModulePath {
+            component: path.component,
+            module_path: udf_path.module().clone(),
+        };
+        let Some(module) = self.get_metadata_at_version(module_path, version).await? else {
             let err = ModuleNotFoundError::new(udf_path.module().as_str());
             return Ok(Err(ErrorMetadata::bad_request(
                 "ModuleNotFound",
@@ -521,3 +636,86 @@ pub fn hash_module_source(source: &ModuleSource, source_map: Option<&SourceMap>)
     }
     hasher.finalize()
 }
+
+static IMPORT_SPECIFIER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:\bfrom\s*|\bimport\s*\(?\s*|\brequire\(\s*)["']([^"'./][^"']*)["']"#).unwrap()
+});
+
+/// Node's built-in modules, which can be imported without the `node:` prefix
+/// but aren't external dependencies.
+const NODE_BUILTIN_MODULES: &[&str] = &[
+    "assert",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "console",
+    "constants",
+    "crypto",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "events",
+    "fs",
+    "http",
+    "http2",
+    "https",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "punycode",
+    "querystring",
+    "readline",
+    "repl",
+    "stream",
+    "string_decoder",
+    "timers",
+    "tls",
+    "tty",
+    "url",
+    "util",
+    "v8",
+    "vm",
+    "worker_threads",
+    "zlib",
+];
+
+/// Compute a module's bundle stats from its source. Only Node modules can
+/// import external packages: isolate modules are fully bundled.
+pub fn module_bundle_stats(
+    source: &ModuleSource,
+    source_map: Option<&SourceMap>,
+    environment: ModuleEnvironment,
+) -> ModuleBundleStats {
+    let mut external_dependencies = BTreeSet::new();
+    if environment == ModuleEnvironment::Node {
+        for captures in IMPORT_SPECIFIER.captures_iter(source) {
+            let Some(specifier) = captures.get(1).map(|m| m.as_str()) else {
+                continue;
+            };
+            if specifier.starts_with("node:") {
+                continue;
+            }
+            // Strip subpaths, keeping the scope of scoped packages.
+            let mut parts = specifier.split('/');
+            let package = match (parts.next(), parts.next()) {
+                (Some(scope), Some(name)) if scope.starts_with('@') => format!("{scope}/{name}"),
+                (Some(name), _) => name.to_string(),
+                (None, _) => continue,
+            };
+            if NODE_BUILTIN_MODULES.contains(&package.as_str()) {
+                continue;
+            }
+            external_dependencies.insert(package);
+        }
+    }
+    ModuleBundleStats {
+        source_size: source.len() as u64,
+        source_map_size: source_map.map(|m| m.len() as u64),
+        external_dependencies,
+    }
+}
@@ -33,6 +33,7 @@ use value::heap_size::{
 
 use super::function_validators::{
     ArgsValidator,
+    HttpBodyValidator,
     ReturnsValidator,
 };
 use crate::cron_jobs::types::{
@@ -263,6 +264,36 @@ pub struct AnalyzedFunction {
     pub args_str: Option<String>,
     // JSON-serialized ReturnsValidator
     pub returns_str: Option<String>,
+
+    // Only set for queries that opt in to sharing cached results.
+    pub cache_key: Option<QueryCacheKey>,
+}
+
+/// Declared by a query to share its cached results between callers: if the
+/// query reads `ctx.auth`, its results are keyed by the values of these
+/// identity claims (e.g. `"subject"`) instead of the caller's whole identity.
+/// The query must not depend on any other part of the identity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct QueryCacheKey {
+    pub identity_claims: Vec<String>,
+}
+
+impl TryFrom<JsonValue> for QueryCacheKey {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonValue) -> anyhow::Result<Self> {
+        let cache_key: QueryCacheKey = serde_json::from_value(value)?;
+        anyhow::ensure!(
+            cache_key
+                .identity_claims
+                .iter()
+                .all(|claim| !claim.is_empty()),
+            "Identity claim names must be nonempty"
+        );
+        Ok(cache_key)
+    }
 }
 
 impl AnalyzedFunction {
@@ -283,9 +314,15 @@ impl AnalyzedFunction {
             visibility,
             args_str: Some(serde_json::to_string(&args_json)?),
             returns_str: Some(serde_json::to_string(&returns_json)?),
+            cache_key: None,
         })
     }
 
+    pub fn with_cache_key(mut self, cache_key: Option<QueryCacheKey>) -> Self {
+        self.cache_key = cache_key;
+        self
+    }
+
     pub fn args(&self) -> anyhow::Result<ArgsValidator> {
         match &self.args_str {
             Some(args) => {
@@ -314,6 +351,13 @@ impl HeapSize for AnalyzedFunction {
             + mem::size_of::<UdfType>()
             + mem::size_of::<Visibility>()
             + mem::size_of::<ArgsValidator>()
+            + self.cache_key.as_ref().map_or(0, |cache_key| {
+                cache_key
+                    .identity_claims
+                    .iter()
+                    .map(|claim| claim.heap_size())
+                    .sum()
+            })
     }
 }
 
@@ -326,6 +370,7 @@ struct SerializedAnalyzedFunction {
     visibility: Option<Visibility>,
     args: Option<String>,
     returns: Option<String>,
+    cache_key: Option<QueryCacheKey>,
 }
 
 impl TryFrom<AnalyzedFunction> for SerializedAnalyzedFunction {
@@ -339,6 +384,7 @@ impl TryFrom<AnalyzedFunction> for SerializedAnalyzedFunction {
             visibility: f.visibility,
             args: f.args_str,
             returns: f.returns_str,
+            cache_key: f.cache_key,
         })
     }
 }
@@ -354,6 +400,7 @@ impl TryFrom<SerializedAnalyzedFunction> for AnalyzedFunction {
             visibility: f.visibility,
             args_str: f.args,
             returns_str: f.returns,
+            cache_key: f.cache_key,
         })
     }
 }
@@ -403,6 +450,20 @@ impl TryFrom<SerializedHttpActionRoute> for HttpActionRoute {
 pub struct AnalyzedHttpRoute {
     pub route: HttpActionRoute,
     pub pos: Option<AnalyzedSourcePosition>,
+    // JSON-serialized HttpBodyValidator, left unparsed like function args.
+    pub body_str: Option<String>,
+}
+
+impl AnalyzedHttpRoute {
+    pub fn body(&self) -> anyhow::Result<HttpBodyValidator> {
+        match &self.body_str {
+            Some(body) => {
+                let deserialized_value: JsonValue = serde_json::from_str(body)?;
+                HttpBodyValidator::try_from(deserialized_value)
+            },
+            None => Ok(HttpBodyValidator::Unvalidated),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -410,11 +471,12 @@ pub struct AnalyzedHttpRoute {
 struct SerializedAnalyzedHttpRoute {
     route: SerializedHttpActionRoute,
     pos: Option<SerializedAnalyzedSourcePosition>,
+    body: Option<String>,
 }
 
 impl HeapSize for AnalyzedHttpRoute {
     fn heap_size(&self) -> usize {
-        self.route.heap_size() + self.pos.heap_size()
+        self.route.heap_size() + self.pos.heap_size() + self.body_str.heap_size()
     }
 }
 
@@ -425,6 +487,7 @@ impl TryFrom<AnalyzedHttpRoute> for SerializedAnalyzedHttpRoute {
         Ok(Self {
             route: SerializedHttpActionRoute::try_from(r.route)?,
             pos: r.pos.map(TryFrom::try_from).transpose()?,
+            body: r.body_str,
         })
     }
 }
@@ -436,6 +499,7 @@ impl TryFrom<SerializedAnalyzedHttpRoute> for AnalyzedHttpRoute {
         Ok(Self {
             route: HttpActionRoute::try_from(r.route)?,
             pos: r.pos.map(AnalyzedSourcePosition::try_from).transpose()?,
+            body_str: r.body,
         })
     }
 }
@@ -460,8 +524,8 @@ impl AnalyzedHttpRoutes {
         }
     }
 
-    pub fn route_exact(&self, path: &str, method: RoutableMethod) -> bool {
-        self.routes.iter().any(|AnalyzedHttpRoute { route, .. }| {
+    pub fn route_exact(&self, path: &str, method: RoutableMethod) -> Option<&AnalyzedHttpRoute> {
+        self.routes.iter().find(|AnalyzedHttpRoute { route, .. }| {
             if route.path.ends_with('*') {
                 return false;
             }
@@ -473,9 +537,10 @@ impl AnalyzedHttpRoutes {
         &self,
         path: &RoutedHttpPath,
         method: RoutableMethod,
-    ) -> Option<RoutedHttpPath> {
-        let mut longest_match: Option<RoutedHttpPath> = None;
-        for AnalyzedHttpRoute { route, .. } in &self.routes {
+    ) -> Option<(RoutedHttpPath, &AnalyzedHttpRoute)> {
+        let mut longest_match: Option<(RoutedHttpPath, &AnalyzedHttpRoute)> = None;
+        for analyzed_route in &self.routes {
+            let route = &analyzed_route.route;
             if route.method != method {
                 continue;
             }
@@ -489,14 +554,14 @@ impl AnalyzedHttpRoutes {
                 continue;
             };
             let new_match = RoutedHttpPath(format!("/{match_suffix}"));
-            if let Some(ref existing_suffix) = longest_match {
+            if let Some((ref existing_suffix, _)) = longest_match {
                 // If the existing longest match has a shorter suffix, then it
                 // matches a longer prefix.
                 if existing_suffix.len() < match_suffix.len() {
                     continue;
                 }
             }
-            longest_match = Some(new_match);
+            longest_match = Some((new_match, analyzed_route));
         }
         longest_match
     }
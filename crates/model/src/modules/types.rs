@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 use common::types::ModuleEnvironment;
 use serde::{
     Deserialize,
@@ -31,6 +33,26 @@ pub struct ModuleMetadata {
     pub analyze_result: Option<AnalyzedModule>,
     // This is a hash of source + source_map.
     pub sha256: Sha256Digest,
+    /// Not set for modules pushed before we started recording it.
+    pub bundle_stats: Option<ModuleBundleStats>,
+}
+
+/// Size and dependency information about a module's bundled source, recorded
+/// when it's pushed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ModuleBundleStats {
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0..1u64 << 40"))]
+    pub source_size: u64,
+    /// `None` if the module was pushed without a source map.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..1u64 << 40)")
+    )]
+    pub source_map_size: Option<u64>,
+    /// Packages the module imports from outside its bundle, which must be
+    /// installed as external dependencies. Always empty for isolate modules.
+    pub external_dependencies: BTreeSet<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -41,6 +63,39 @@ pub struct SerializedModuleMetadata {
     pub environment: String,
     pub analyze_result: Option<SerializedAnalyzedModule>,
     pub sha256: String,
+    pub bundle_stats: Option<SerializedModuleBundleStats>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedModuleBundleStats {
+    source_size: i64,
+    source_map_size: Option<i64>,
+    external_dependencies: Vec<String>,
+}
+
+impl TryFrom<SerializedModuleBundleStats> for ModuleBundleStats {
+    type Error = anyhow::Error;
+
+    fn try_from(s: SerializedModuleBundleStats) -> anyhow::Result<Self> {
+        Ok(Self {
+            source_size: s.source_size.try_into()?,
+            source_map_size: s.source_map_size.map(u64::try_from).transpose()?,
+            external_dependencies: s.external_dependencies.into_iter().collect(),
+        })
+    }
+}
+
+impl TryFrom<ModuleBundleStats> for SerializedModuleBundleStats {
+    type Error = anyhow::Error;
+
+    fn try_from(s: ModuleBundleStats) -> anyhow::Result<Self> {
+        Ok(Self {
+            source_size: s.source_size.try_into()?,
+            source_map_size: s.source_map_size.map(i64::try_from).transpose()?,
+            external_dependencies: s.external_dependencies.into_iter().collect(),
+        })
+    }
 }
 
 impl TryFrom<SerializedModuleMetadata> for ModuleMetadata {
@@ -56,6 +111,7 @@ impl TryFrom<SerializedModuleMetadata> for ModuleMetadata {
             environment: m.environment.parse()?,
             analyze_result: m.analyze_result.map(|s| s.try_into()).transpose()?,
             sha256: Sha256Digest::from_base64(&m.sha256)?,
+            bundle_stats: m.bundle_stats.map(|s| s.try_into()).transpose()?,
         })
     }
 }
@@ -70,6 +126,7 @@ impl TryFrom<ModuleMetadata> for SerializedModuleMetadata {
             environment: m.environment.to_string(),
             analyze_result: m.analyze_result.map(|s| s.try_into()).transpose()?,
             sha256: m.sha256.as_base64(),
+            bundle_stats: m.bundle_stats.map(|s| s.try_into()).transpose()?,
         })
     }
 }
@@ -0,0 +1,117 @@
+//! The deployment's outbound network policy, which restricts the hosts that
+//! actions can send requests to. A deployment without a policy document uses
+//! the default policy, which allows all public hosts and blocks private IP
+//! ranges.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use self::types::OutboundNetworkPolicy;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static OUTBOUND_NETWORK_POLICY_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_outbound_network_policy"
+        .parse()
+        .expect("Invalid built-in outbound network policy table")
+});
+
+pub struct OutboundNetworkPolicyTable;
+impl SystemTable for OutboundNetworkPolicyTable {
+    fn table_name(&self) -> &'static TableName {
+        &OUTBOUND_NETWORK_POLICY_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<OutboundNetworkPolicy>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct OutboundNetworkPolicyModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> OutboundNetworkPolicyModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    async fn get_document(
+        &mut self,
+    ) -> anyhow::Result<Option<ParsedDocument<OutboundNetworkPolicy>>> {
+        let query = Query::full_table_scan(OUTBOUND_NETWORK_POLICY_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// The policy in effect, falling back to the default if none is set.
+    pub async fn get(&mut self) -> anyhow::Result<OutboundNetworkPolicy> {
+        Ok(self
+            .get_document()
+            .await?
+            .map(ParsedDocument::into_value)
+            .unwrap_or_default())
+    }
+
+    /// Replace the deployment's policy, returning the previous one.
+    pub async fn set(
+        &mut self,
+        policy: OutboundNetworkPolicy,
+    ) -> anyhow::Result<OutboundNetworkPolicy> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("set_outbound_network_policy"));
+        }
+        policy.validate().map_err(|e| {
+            ErrorMetadata::bad_request("InvalidOutboundNetworkPolicy", e.to_string())
+        })?;
+        match self.get_document().await? {
+            Some(existing) => {
+                let (id, previous) = existing.into_id_and_value();
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(id, policy.try_into()?)
+                    .await?;
+                Ok(previous)
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&OUTBOUND_NETWORK_POLICY_TABLE, policy.try_into()?)
+                    .await?;
+                Ok(OutboundNetworkPolicy::default())
+            },
+        }
+    }
+}
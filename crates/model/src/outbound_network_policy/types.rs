@@ -0,0 +1,283 @@
+use std::{
+    fmt,
+    net::IpAddr,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Which hosts a deployment's actions may send requests to. Hosts are matched
+/// against patterns that are either an exact hostname, `*.` followed by a
+/// domain to match all of its subdomains, or `*` to match every host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct OutboundNetworkPolicy {
+    /// If non-empty, only hosts matching one of these patterns are allowed.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::collection::vec(\"[a-z]{1,8}\\\\.com\", 0..4)")
+    )]
+    pub allowed_hosts: Vec<String>,
+    /// Hosts matching one of these patterns are always blocked.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::collection::vec(\"\\\\*\\\\.[a-z]{1,8}\\\\.com\", 0..4)")
+    )]
+    pub denied_hosts: Vec<String>,
+    /// Whether requests may go to loopback, private, link-local and other
+    /// non-public addresses.
+    pub allow_private_ips: bool,
+}
+
+impl Default for OutboundNetworkPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: vec![],
+            denied_hosts: vec![],
+            allow_private_ips: false,
+        }
+    }
+}
+
+impl OutboundNetworkPolicy {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for pattern in self.allowed_hosts.iter().chain(&self.denied_hosts) {
+            let domain = pattern.strip_prefix("*.").unwrap_or(pattern);
+            let valid = pattern == "*"
+                || (!domain.is_empty()
+                    && domain.split('.').all(|label| {
+                        !label.is_empty()
+                            && label
+                                .chars()
+                                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                    }));
+            anyhow::ensure!(
+                valid,
+                "Invalid host pattern {pattern:?}. Patterns must be a lowercase hostname, \"*.\" \
+                 followed by a domain, or \"*\""
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks a request's host against the allow and deny lists. If the host
+    /// is an IP address, also checks whether it's a private address.
+    pub fn check_host(&self, host: &str) -> Result<(), NetworkPolicyViolation> {
+        let host = host.to_ascii_lowercase();
+        let violation = |reason| NetworkPolicyViolation {
+            host: host.clone(),
+            reason,
+        };
+        if self.denied_hosts.iter().any(|p| host_matches(p, &host)) {
+            return Err(violation(NetworkPolicyViolationReason::Denied));
+        }
+        if !self.allowed_hosts.is_empty()
+            && !self.allowed_hosts.iter().any(|p| host_matches(p, &host))
+        {
+            return Err(violation(NetworkPolicyViolationReason::NotAllowed));
+        }
+        let ip_literal = host.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = ip_literal.parse::<IpAddr>() {
+            self.check_ip(&host, ip)?;
+        }
+        Ok(())
+    }
+
+    /// Checks an address that `host` resolved to.
+    pub fn check_ip(&self, host: &str, ip: IpAddr) -> Result<(), NetworkPolicyViolation> {
+        if !self.allow_private_ips && is_private_ip(ip) {
+            return Err(NetworkPolicyViolation {
+                host: host.to_string(),
+                reason: NetworkPolicyViolationReason::PrivateIp,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.')),
+        None => pattern == host,
+    }
+}
+
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10.
+                || (a == 100 && (b & 0xc0) == 64)
+        },
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_private_ip(IpAddr::V4(ip));
+            }
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                // Unique local, fc00::/7.
+                || (first & 0xfe00) == 0xfc00
+                // Link-local, fe80::/10.
+                || (first & 0xffc0) == 0xfe80
+        },
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkPolicyViolation {
+    pub host: String,
+    pub reason: NetworkPolicyViolationReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum NetworkPolicyViolationReason {
+    Denied,
+    NotAllowed,
+    PrivateIp,
+}
+
+impl fmt::Display for NetworkPolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            NetworkPolicyViolationReason::Denied => write!(
+                f,
+                "Request to {} blocked: the host is denied by this deployment's outbound network \
+                 policy",
+                self.host
+            ),
+            NetworkPolicyViolationReason::NotAllowed => write!(
+                f,
+                "Request to {} blocked: the host isn't in this deployment's outbound network \
+                 policy allowlist",
+                self.host
+            ),
+            NetworkPolicyViolationReason::PrivateIp => write!(
+                f,
+                "Request to {} blocked: requests to private IP addresses aren't allowed",
+                self.host
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedOutboundNetworkPolicy {
+    allowed_hosts: Vec<String>,
+    denied_hosts: Vec<String>,
+    allow_private_ips: bool,
+}
+
+impl From<OutboundNetworkPolicy> for SerializedOutboundNetworkPolicy {
+    fn from(value: OutboundNetworkPolicy) -> Self {
+        Self {
+            allowed_hosts: value.allowed_hosts,
+            denied_hosts: value.denied_hosts,
+            allow_private_ips: value.allow_private_ips,
+        }
+    }
+}
+
+impl From<SerializedOutboundNetworkPolicy> for OutboundNetworkPolicy {
+    fn from(value: SerializedOutboundNetworkPolicy) -> Self {
+        Self {
+            allowed_hosts: value.allowed_hosts,
+            denied_hosts: value.denied_hosts,
+            allow_private_ips: value.allow_private_ips,
+        }
+    }
+}
+
+codegen_convex_serialization!(OutboundNetworkPolicy, SerializedOutboundNetworkPolicy);
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        NetworkPolicyViolationReason,
+        OutboundNetworkPolicy,
+    };
+
+    #[test]
+    fn test_host_patterns() {
+        let policy = OutboundNetworkPolicy {
+            allowed_hosts: vec!["api.example.com".to_string(), "*.trusted.dev".to_string()],
+            denied_hosts: vec!["blocked.trusted.dev".to_string()],
+            allow_private_ips: false,
+        };
+        assert!(policy.check_host("api.example.com").is_ok());
+        assert!(policy.check_host("a.b.trusted.dev").is_ok());
+        let reason = |host| policy.check_host(host).unwrap_err().reason;
+        assert_eq!(
+            reason("trusted.dev"),
+            NetworkPolicyViolationReason::NotAllowed
+        );
+        assert_eq!(
+            reason("eviltrusted.dev"),
+            NetworkPolicyViolationReason::NotAllowed
+        );
+        assert_eq!(
+            reason("blocked.trusted.dev"),
+            NetworkPolicyViolationReason::Denied
+        );
+    }
+
+    #[test]
+    fn test_private_ips_blocked_by_default() {
+        let policy = OutboundNetworkPolicy::default();
+        assert!(policy.check_host("example.com").is_ok());
+        assert!(policy.check_host("8.8.8.8").is_ok());
+        for host in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "192.168.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "[::1]",
+            "[fd00::1]",
+            "[::ffff:10.0.0.1]",
+        ] {
+            assert_eq!(
+                policy.check_host(host).unwrap_err().reason,
+                NetworkPolicyViolationReason::PrivateIp,
+                "{host}"
+            );
+        }
+        let policy = OutboundNetworkPolicy {
+            allow_private_ips: true,
+            ..Default::default()
+        };
+        assert!(policy.check_host("127.0.0.1").is_ok());
+    }
+
+    #[test]
+    fn test_validate_patterns() {
+        let policy = |pattern: &str| OutboundNetworkPolicy {
+            allowed_hosts: vec![pattern.to_string()],
+            ..Default::default()
+        };
+        assert!(policy("*").validate().is_ok());
+        assert!(policy("*.example.com").validate().is_ok());
+        assert!(policy("example.com").validate().is_ok());
+        assert!(policy("Example.com").validate().is_err());
+        assert!(policy("ex*.com").validate().is_err());
+        assert!(policy("https://example.com").validate().is_err());
+        assert!(policy("").validate().is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! Preview deployments, e.g. one per pull request in CI. Each preview gets
+//! its own table namespace, so its data is kept apart from the deployment's
+//! while living in the same backend and persistence. The application's
+//! preview deployment worker deletes a preview's tables once it expires.
+
+use std::{
+    collections::BTreeSet,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    TableModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::PreviewDeployment;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static PREVIEW_DEPLOYMENTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_preview_deployments"
+        .parse()
+        .expect("Invalid built-in preview deployments table")
+});
+
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("Invalid built-in field"));
+
+pub static PREVIEW_DEPLOYMENTS_INDEX_BY_NAME: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&PREVIEW_DEPLOYMENTS_TABLE, "by_name"));
+
+const MAX_NAME_LENGTH: usize = 64;
+
+pub struct PreviewDeploymentsTable;
+impl SystemTable for PreviewDeploymentsTable {
+    fn table_name(&self) -> &'static TableName {
+        &PREVIEW_DEPLOYMENTS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: PREVIEW_DEPLOYMENTS_INDEX_BY_NAME.clone(),
+            fields: vec![NAME_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<PreviewDeployment>::try_from(document).map(|_| ())
+    }
+}
+
+/// The namespace holding a preview's tables. It's keyed by the preview's
+/// document ID, which can't collide with a component's.
+pub fn preview_namespace(preview: &ParsedDocument<PreviewDeployment>) -> TableNamespace {
+    TableNamespace::ByComponent(preview.developer_id())
+}
+
+fn validate_name(name: &str) -> anyhow::Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    anyhow::ensure!(
+        valid,
+        ErrorMetadata::bad_request(
+            "InvalidPreviewDeploymentName",
+            format!(
+                "Preview deployment names must be 1 to {MAX_NAME_LENGTH} lowercase letters, \
+                 digits and dashes, got \"{name}\""
+            )
+        )
+    );
+    Ok(())
+}
+
+fn not_found_error(name: &str) -> ErrorMetadata {
+    ErrorMetadata::not_found(
+        "PreviewDeploymentNotFound",
+        format!("Preview deployment \"{name}\" not found"),
+    )
+}
+
+pub struct PreviewDeploymentsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> PreviewDeploymentsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// All previews, ordered by name.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<PreviewDeployment>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: PREVIEW_DEPLOYMENTS_INDEX_BY_NAME.clone(),
+            range: vec![],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut previews = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            previews.push(doc.try_into()?);
+        }
+        Ok(previews)
+    }
+
+    async fn get(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<PreviewDeployment>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: PREVIEW_DEPLOYMENTS_INDEX_BY_NAME.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                NAME_FIELD.clone(),
+                ConvexValue::String(name.try_into()?).into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// The namespace holding the tables of the preview called `name`.
+    pub async fn namespace(&mut self, name: &str) -> anyhow::Result<TableNamespace> {
+        let preview = self.get(name).await?.ok_or_else(|| not_found_error(name))?;
+        Ok(preview_namespace(&preview))
+    }
+
+    /// The namespaces of every preview, for callers that work on the whole
+    /// deployment's tables and should leave previews alone.
+    pub async fn namespaces(&mut self) -> anyhow::Result<BTreeSet<TableNamespace>> {
+        Ok(self.list().await?.iter().map(preview_namespace).collect())
+    }
+
+    /// Create a preview that expires at `expires_at`, or push back the expiry
+    /// of an existing one, keeping its tables.
+    pub async fn create_or_renew(
+        &mut self,
+        name: String,
+        expires_at: UnixTimestamp,
+    ) -> anyhow::Result<TableNamespace> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("create_preview_deployment"));
+        }
+        validate_name(&name)?;
+        let preview = PreviewDeployment {
+            name: name.clone(),
+            expires_at,
+        };
+        match self.get(&name).await? {
+            Some(existing) => {
+                let namespace = preview_namespace(&existing);
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), preview.try_into()?)
+                    .await?;
+                Ok(namespace)
+            },
+            None => {
+                let id = SystemMetadataModel::new_global(self.tx)
+                    .insert(&PREVIEW_DEPLOYMENTS_TABLE, preview.try_into()?)
+                    .await?;
+                Ok(TableNamespace::ByComponent(id.into()))
+            },
+        }
+    }
+
+    /// Delete the preview called `name` and its tables.
+    pub async fn delete(&mut self, name: &str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("delete_preview_deployment"));
+        }
+        let preview = self.get(name).await?.ok_or_else(|| not_found_error(name))?;
+        self.delete_preview(preview).await
+    }
+
+    /// Delete the previews that have expired at `now`, returning when the next
+    /// one expires.
+    pub async fn delete_expired(
+        &mut self,
+        now: UnixTimestamp,
+    ) -> anyhow::Result<Option<UnixTimestamp>> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("delete_expired_preview_deployments"));
+        }
+        let mut next_expiry: Option<UnixTimestamp> = None;
+        for preview in self.list().await? {
+            if preview.expires_at <= now {
+                self.delete_preview(preview).await?;
+            } else {
+                next_expiry = Some(next_expiry.map_or(preview.expires_at, |next_expiry| {
+                    next_expiry.min(preview.expires_at)
+                }));
+            }
+        }
+        Ok(next_expiry)
+    }
+
+    async fn delete_preview(
+        &mut self,
+        preview: ParsedDocument<PreviewDeployment>,
+    ) -> anyhow::Result<()> {
+        let namespace = preview_namespace(&preview);
+        let table_names: Vec<TableName> = self
+            .tx
+            .table_mapping()
+            .namespace(namespace)
+            .iter_active_user_tables()
+            .map(|(_, _, name)| name.clone())
+            .collect();
+        for table_name in table_names {
+            TableModel::new(self.tx)
+                .delete_table(namespace, table_name)
+                .await?;
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .delete(preview.id())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::runtime::UnixTimestamp;
+    use database::{
+        test_helpers::DbFixtures,
+        TableModel,
+        UserFacingModel,
+    };
+    use errors::ErrorMetadataAnyhowExt;
+    use runtime::testing::TestRuntime;
+    use value::{
+        assert_obj,
+        TableName,
+        TableNamespace,
+    };
+
+    use crate::{
+        preview_deployments::PreviewDeploymentsModel,
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_preview_deployment_tables_expire(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let table_name: TableName = "messages".parse()?;
+        let start = UnixTimestamp::from_millis(1_000_000);
+        let expires_at = start + Duration::from_secs(3600);
+
+        let mut tx = db.begin_system().await?;
+        let err = PreviewDeploymentsModel::new(&mut tx)
+            .create_or_renew("PR 1".to_string(), expires_at)
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "InvalidPreviewDeploymentName");
+
+        let namespace = PreviewDeploymentsModel::new(&mut tx)
+            .create_or_renew("pr-1".to_string(), expires_at)
+            .await?;
+        assert_ne!(namespace, TableNamespace::Global);
+        UserFacingModel::new(&mut tx, namespace)
+            .insert(table_name.clone(), assert_obj!("body" => "preview"))
+            .await?;
+        db.commit(tx).await?;
+
+        // The preview's table is separate from the deployment's.
+        let mut tx = db.begin_system().await?;
+        assert!(TableModel::new(&mut tx).table_exists(namespace, &table_name));
+        assert!(!TableModel::new(&mut tx).table_exists(TableNamespace::Global, &table_name));
+
+        // Renewing keeps the namespace.
+        let renewed_expires_at = expires_at + Duration::from_secs(3600);
+        assert_eq!(
+            PreviewDeploymentsModel::new(&mut tx)
+                .create_or_renew("pr-1".to_string(), renewed_expires_at)
+                .await?,
+            namespace
+        );
+        let next_expiry = PreviewDeploymentsModel::new(&mut tx)
+            .delete_expired(expires_at)
+            .await?;
+        assert_eq!(next_expiry, Some(renewed_expires_at));
+        assert!(TableModel::new(&mut tx).table_exists(namespace, &table_name));
+
+        let next_expiry = PreviewDeploymentsModel::new(&mut tx)
+            .delete_expired(renewed_expires_at)
+            .await?;
+        assert_eq!(next_expiry, None);
+        assert!(!TableModel::new(&mut tx).table_exists(namespace, &table_name));
+        let err = PreviewDeploymentsModel::new(&mut tx)
+            .namespace("pr-1")
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "PreviewDeploymentNotFound");
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use common::runtime::UnixTimestamp;
+#[cfg(any(test, feature = "testing"))]
+use proptest::prelude::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A named set of tables, separate from the deployment's own, that's deleted
+/// once it expires.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct PreviewDeployment {
+    #[cfg_attr(any(test, feature = "testing"), proptest(regex = "[a-z0-9-]{1,64}"))]
+    pub name: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "(0..1u64 << 42).prop_map(UnixTimestamp::from_millis)")
+    )]
+    pub expires_at: UnixTimestamp,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedPreviewDeployment {
+    name: String,
+    expires_at_ms: i64,
+}
+
+impl TryFrom<PreviewDeployment> for SerializedPreviewDeployment {
+    type Error = anyhow::Error;
+
+    fn try_from(value: PreviewDeployment) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: value.name,
+            expires_at_ms: value.expires_at.as_ms_since_epoch()?.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedPreviewDeployment> for PreviewDeployment {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedPreviewDeployment) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: value.name,
+            expires_at: UnixTimestamp::from_millis(value.expires_at_ms.try_into()?),
+        })
+    }
+}
+
+codegen_convex_serialization!(PreviewDeployment, SerializedPreviewDeployment);
@@ -0,0 +1,80 @@
+//! Arguments of scheduled jobs that are too large to store in the job itself.
+//! They're uploaded to file storage when the job is scheduled, read back when
+//! it runs, and deleted along with the job.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use bytes::Bytes;
+use common::knobs::{
+    FUNCTION_MAX_ARGS_SIZE,
+    SCHEDULED_JOB_MAX_ARGS_SIZE,
+};
+use errors::ErrorMetadata;
+use futures::TryStreamExt;
+use serde_json::Value as JsonValue;
+use storage::{
+    Storage,
+    StorageExt,
+    Upload,
+};
+use value::{
+    ConvexArray,
+    Size,
+};
+
+use super::types::{
+    ScheduledJob,
+    ScheduledJobArgs,
+};
+
+/// Uploads `args` to `storage` if they're larger than `FUNCTION_MAX_ARGS_SIZE`.
+/// Nothing references the object until the job is committed, so objects from
+/// transactions that fail are left for storage GC.
+pub async fn offload_args(
+    storage: &Arc<dyn Storage>,
+    args: ConvexArray,
+) -> anyhow::Result<ScheduledJobArgs> {
+    let size = args.size();
+    if size <= *FUNCTION_MAX_ARGS_SIZE {
+        return Ok(ScheduledJobArgs::Inline(args));
+    }
+    anyhow::ensure!(
+        size <= *SCHEDULED_JOB_MAX_ARGS_SIZE,
+        ErrorMetadata::bad_request(
+            "ScheduledFunctionArgumentsTooLarge",
+            format!(
+                "Arguments of the scheduled function are too large (actual: {size} bytes, limit: \
+                 {} bytes)",
+                *SCHEDULED_JOB_MAX_ARGS_SIZE,
+            )
+        )
+    );
+    let args_bytes = serde_json::to_vec(&JsonValue::from(args))?;
+    let mut upload = storage.start_upload().await?;
+    upload.write(Bytes::from(args_bytes)).await?;
+    let storage_key = upload.complete().await?;
+    Ok(ScheduledJobArgs::Offloaded(storage_key))
+}
+
+/// The arguments of `job`, reading them from `storage` if they were
+/// offloaded.
+pub async fn load_args(
+    storage: &Arc<dyn Storage>,
+    job: &ScheduledJob,
+) -> anyhow::Result<ConvexArray> {
+    let Some(storage_key) = &job.args_storage_key else {
+        return job.udf_args();
+    };
+    let mut stream = storage
+        .get(storage_key)
+        .await?
+        .with_context(|| format!("Scheduled job arguments {storage_key:?} not found"))?
+        .stream;
+    let mut args_bytes = vec![];
+    while let Some(chunk) = stream.try_next().await? {
+        args_bytes.extend_from_slice(&chunk);
+    }
+    let args_json: JsonValue = serde_json::from_slice(&args_bytes)?;
+    args_json.try_into()
+}
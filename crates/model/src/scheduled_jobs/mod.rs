@@ -7,10 +7,14 @@ use std::{
 };
 
 use common::{
-    components::CanonicalizedComponentFunctionPath,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentId,
+    },
     document::{
         ParsedDocument,
         ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
     },
     execution_context::ExecutionContext,
     knobs::{
@@ -32,6 +36,7 @@ use common::{
     types::{
         GenericIndexName,
         IndexName,
+        MaybeValue,
     },
     virtual_system_mapping::VirtualSystemDocMapper,
 };
@@ -47,28 +52,31 @@ use maplit::btreemap;
 use sync_types::Timestamp;
 use value::{
     id_v6::DeveloperDocumentId,
-    ConvexArray,
     ConvexValue,
     FieldPath,
     ResolvedDocumentId,
-    Size,
     TableName,
     TableNamespace,
 };
 
 use self::{
     types::{
+        ScheduleOptions,
+        SchedulePriority,
         ScheduledJob,
+        ScheduledJobArgs,
         ScheduledJobAttempts,
         ScheduledJobState,
     },
     virtual_table::ScheduledJobsDocMapper,
 };
 use crate::{
+    component_limits::ComponentLimitsModel,
     SystemIndex,
     SystemTable,
 };
 
+pub mod args;
 pub mod types;
 pub mod virtual_table;
 
@@ -100,6 +108,12 @@ pub static SCHEDULED_JOBS_INDEX_BY_UDF_PATH: LazyLock<IndexName> =
     LazyLock::new(|| system_index(&SCHEDULED_JOBS_TABLE, "by_udf_path_and_next_event_ts"));
 pub static SCHEDULED_JOBS_INDEX_BY_COMPLETED_TS: LazyLock<IndexName> =
     LazyLock::new(|| system_index(&SCHEDULED_JOBS_TABLE, "by_completed_ts"));
+pub static SCHEDULED_JOBS_INDEX_BY_PRIORITY: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&SCHEDULED_JOBS_TABLE, "by_priority_and_next_ts"));
+pub static SCHEDULED_JOBS_INDEX_BY_COMPONENT: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&SCHEDULED_JOBS_TABLE, "by_component_and_creation_time"));
+static SCHEDULED_JOBS_VIRTUAL_INDEX_BY_COMPONENT: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&SCHEDULED_JOBS_VIRTUAL_TABLE, "by_component"));
 pub static NEXT_TS_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "nextTs".parse().expect("invalid nextTs field"));
 pub static COMPLETED_TS_FIELD: LazyLock<FieldPath> =
@@ -108,6 +122,8 @@ static UDF_PATH_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "udfPath".parse().expect("invalid udfPath field"));
 static COMPONENT_PATH_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "component".parse().expect("invalid component field"));
+static PRIORITY_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "priority".parse().expect("invalid priority field"));
 
 pub struct ScheduledJobsTable;
 impl SystemTable for ScheduledJobsTable {
@@ -127,6 +143,17 @@ impl SystemTable for ScheduledJobsTable {
                 name: SCHEDULED_JOBS_INDEX.clone(),
                 fields: vec![NEXT_TS_FIELD.clone()].try_into().unwrap(),
             },
+            // By priority and next ts. Used to start higher priority jobs first.
+            SystemIndex {
+                name: SCHEDULED_JOBS_INDEX_BY_PRIORITY.clone(),
+                fields: vec![
+                    PRIORITY_FIELD.clone(),
+                    NEXT_TS_FIELD.clone(),
+                    CREATION_TIME_FIELD_PATH.clone(),
+                ]
+                .try_into()
+                .unwrap(),
+            },
             // By udf path and next ts. Used by the dashboard to group scheduled jobs by udf
             // function.
             SystemIndex {
@@ -135,6 +162,17 @@ impl SystemTable for ScheduledJobsTable {
                     .try_into()
                     .unwrap(),
             },
+            // By the component of the scheduled function. Exposed on the virtual
+            // table so the dashboard can filter scheduled functions by component.
+            SystemIndex {
+                name: SCHEDULED_JOBS_INDEX_BY_COMPONENT.clone(),
+                fields: vec![
+                    COMPONENT_PATH_FIELD.clone(),
+                    CREATION_TIME_FIELD_PATH.clone(),
+                ]
+                .try_into()
+                .unwrap(),
+            },
         ]
     }
 
@@ -152,6 +190,8 @@ impl SystemTable for ScheduledJobsTable {
                     SCHEDULED_JOBS_INDEX_BY_CREATION_TIME.clone(),
                 SCHEDULED_JOBS_VIRTUAL_INDEX_BY_ID.clone() =>
                     SCHEDULED_JOBS_INDEX_BY_ID.clone(),
+                SCHEDULED_JOBS_VIRTUAL_INDEX_BY_COMPONENT.clone() =>
+                    SCHEDULED_JOBS_INDEX_BY_COMPONENT.clone(),
             },
             Arc::new(ScheduledJobsDocMapper),
         ))
@@ -162,6 +202,19 @@ impl SystemTable for ScheduledJobsTable {
     }
 }
 
+/// Unfinished jobs with the given priority, in the order they're due.
+pub fn unfinished_jobs_query(priority: SchedulePriority) -> anyhow::Result<Query> {
+    let priority = priority.as_str().map(ConvexValue::try_from).transpose()?;
+    Ok(Query::index_range(IndexRange {
+        index_name: SCHEDULED_JOBS_INDEX_BY_PRIORITY.clone(),
+        range: vec![
+            IndexRangeExpression::Eq(PRIORITY_FIELD.clone(), MaybeValue(priority)),
+            IndexRangeExpression::Gt(NEXT_TS_FIELD.clone(), ConvexValue::Null),
+        ],
+        order: Order::Asc,
+    }))
+}
+
 // Maintains state for scheduling asynchronous functions (scheduled jobs).
 pub struct SchedulerModel<'a, RT: Runtime> {
     tx: &'a mut Transaction<RT>,
@@ -173,21 +226,26 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         Self { tx, namespace }
     }
 
-    fn check_scheduling_limits(&mut self, args: &ConvexArray) -> anyhow::Result<()> {
-        // Limit how much you can schedule from a single transaction.
+    async fn check_scheduling_limits(&mut self, args: &ScheduledJobArgs) -> anyhow::Result<()> {
+        // Limit how much you can schedule from a single transaction. Components
+        // may have a tighter limit than the deployment.
+        let max_scheduled = ComponentLimitsModel::new(self.tx)
+            .get_limits(ComponentId::from(self.namespace))
+            .await?
+            .and_then(|limits| limits.max_scheduled_functions)
+            .map_or(Ok(*TRANSACTION_MAX_NUM_SCHEDULED), usize::try_from)?;
         anyhow::ensure!(
-            self.tx.scheduled_size.num_writes < *TRANSACTION_MAX_NUM_SCHEDULED,
+            self.tx.scheduled_size.num_writes < max_scheduled,
             ErrorMetadata::bad_request(
                 "TooManyFunctionsScheduled",
-                format!(
-                    "Too many functions scheduled by this mutation (limit: {})",
-                    *TRANSACTION_MAX_NUM_SCHEDULED,
-                )
+                format!("Too many functions scheduled by this mutation (limit: {max_scheduled})")
             )
         );
         self.tx.scheduled_size.num_writes += 1;
+        // Offloaded arguments don't take up space in the transaction.
+        let args_size = args.inline_size();
         anyhow::ensure!(
-            self.tx.scheduled_size.size + args.size()
+            self.tx.scheduled_size.size + args_size
                 <= *TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
             ErrorMetadata::bad_request(
                 "ScheduledFunctionsArgumentsTooLarge",
@@ -198,15 +256,16 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
                 )
             ),
         );
-        self.tx.scheduled_size.size += args.size();
+        self.tx.scheduled_size.size += args_size;
         Ok(())
     }
 
     pub async fn schedule(
         &mut self,
         path: CanonicalizedComponentFunctionPath,
-        args: ConvexArray,
+        args: ScheduledJobArgs,
         ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<ResolvedDocumentId> {
         if path.udf_path.is_system()
@@ -215,12 +274,17 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
             anyhow::bail!(unauthorized_error("schedule"))
         }
 
-        self.check_scheduling_limits(&args)?;
+        options.validate()?;
+        self.check_scheduling_limits(&args).await?;
 
         let now: Timestamp = self.tx.runtime().generate_timestamp()?;
         let original_scheduled_ts: Timestamp = ts.as_system_time().try_into()?;
+        let scheduled_by = self
+            .tx
+            .must_component_path(ComponentId::from(self.namespace))?;
         let scheduled_job = ScheduledJob::new(
             path.clone(),
+            scheduled_by.clone(),
             args.clone(),
             ScheduledJobState::Pending,
             // Don't set next_ts in the past to avoid scheduler incorrectly logging
@@ -230,8 +294,9 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
             None,
             original_scheduled_ts,
             ScheduledJobAttempts::default(),
+            options,
         )?;
-        let job = if let Some(parent_scheduled_job) = context.parent_scheduled_job {
+        let mut job = if let Some(parent_scheduled_job) = context.parent_scheduled_job {
             let table_mapping = self.tx.table_mapping();
             let parent_scheduled_job = parent_scheduled_job
                 .to_resolved(table_mapping.namespace(self.namespace).number_to_tablet())?;
@@ -247,12 +312,14 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
                         let scheduled_ts = self.tx.begin_timestamp();
                         ScheduledJob::new(
                             path,
+                            scheduled_by,
                             args,
                             ScheduledJobState::Canceled,
                             None,
                             Some(*scheduled_ts),
                             *scheduled_ts,
                             ScheduledJobAttempts::default(),
+                            options,
                         )?
                     },
                 }
@@ -262,6 +329,7 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         } else {
             scheduled_job
         };
+        job.request_context = context.request_context;
         let id = SystemMetadataModel::new(self.tx, self.namespace)
             .insert_metadata(&SCHEDULED_JOBS_TABLE, job.try_into()?)
             .await?;
@@ -430,6 +498,73 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         Ok(scheduled_jobs)
     }
 
+    /// How far behind the component's jobs are as of `now`: the pending jobs
+    /// whose scheduled time has passed, and the jobs that finished since
+    /// `completed_since`. Reads at most `limit` jobs of each kind.
+    pub async fn backlog(
+        &mut self,
+        now: Timestamp,
+        completed_since: Timestamp,
+        limit: usize,
+    ) -> anyhow::Result<SchedulerBacklog> {
+        let mut backlog = SchedulerBacklog {
+            complete: true,
+            ..Default::default()
+        };
+        let due_query = Query::index_range(IndexRange {
+            index_name: SCHEDULED_JOBS_INDEX.clone(),
+            range: vec![
+                IndexRangeExpression::Gt(NEXT_TS_FIELD.clone(), ConvexValue::Null),
+                IndexRangeExpression::Lte(NEXT_TS_FIELD.clone(), i64::from(now).into()),
+            ],
+            order: Order::Asc,
+        })
+        .limit(limit + 1);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, due_query)?;
+        let mut scanned = 0;
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            if scanned == limit {
+                backlog.complete = false;
+                break;
+            }
+            scanned += 1;
+            let job: ParsedDocument<ScheduledJob> = doc.try_into()?;
+            match job.state {
+                ScheduledJobState::Pending => {
+                    backlog.due_jobs += 1;
+                    if backlog.oldest_due_ts.is_none() {
+                        backlog.oldest_due_ts = job.next_ts;
+                    }
+                    *backlog
+                        .due_jobs_by_function
+                        .entry(job.path.clone())
+                        .or_default() += 1;
+                },
+                ScheduledJobState::InProgress => backlog.in_progress_jobs += 1,
+                _ => anyhow::bail!("Unfinished scheduled job {} has the wrong state", job.id()),
+            }
+        }
+
+        let completed_query = Query::index_range(IndexRange {
+            index_name: SCHEDULED_JOBS_INDEX_BY_COMPLETED_TS.clone(),
+            range: vec![IndexRangeExpression::Gte(
+                COMPLETED_TS_FIELD.clone(),
+                i64::from(completed_since).into(),
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit + 1);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, completed_query)?;
+        while query_stream.next(self.tx, None).await?.is_some() {
+            if backlog.completed_jobs == limit {
+                backlog.complete = false;
+                break;
+            }
+            backlog.completed_jobs += 1;
+        }
+        Ok(backlog)
+    }
+
     /// Checks the status of the scheduled job. If it has been garbage collected
     /// and the scheduled job is no longer in the table, it returns None.
     pub async fn check_status(
@@ -447,6 +582,41 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
     }
 }
 
+/// The scheduled jobs that are due to run but haven't, and how many jobs
+/// finished recently.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchedulerBacklog {
+    /// Pending jobs whose scheduled time has passed.
+    pub due_jobs: usize,
+    /// Actions that have started but not finished.
+    pub in_progress_jobs: usize,
+    /// The scheduled time of the longest-waiting due job.
+    pub oldest_due_ts: Option<Timestamp>,
+    pub due_jobs_by_function: BTreeMap<CanonicalizedComponentFunctionPath, usize>,
+    /// Jobs that succeeded, failed or were canceled in the window.
+    pub completed_jobs: usize,
+    /// False if there were too many jobs to count in full, so the counts are
+    /// lower bounds.
+    pub complete: bool,
+}
+
+impl SchedulerBacklog {
+    /// Adds another component's backlog to this one.
+    pub fn merge(&mut self, other: SchedulerBacklog) {
+        self.due_jobs += other.due_jobs;
+        self.in_progress_jobs += other.in_progress_jobs;
+        self.oldest_due_ts = match (self.oldest_due_ts, other.oldest_due_ts) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        for (path, count) in other.due_jobs_by_function {
+            *self.due_jobs_by_function.entry(path).or_default() += count;
+        }
+        self.completed_jobs += other.completed_jobs;
+        self.complete &= other.complete;
+    }
+}
+
 /// Same as SchedulerModel but works with the respective virtual table instead
 /// of the underlying system table.
 pub struct VirtualSchedulerModel<'a, RT: Runtime> {
@@ -462,12 +632,13 @@ impl<'a, RT: Runtime> VirtualSchedulerModel<'a, RT> {
     pub async fn schedule(
         &mut self,
         path: CanonicalizedComponentFunctionPath,
-        args: ConvexArray,
+        args: ScheduledJobArgs,
         ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let system_id = SchedulerModel::new(self.tx, self.namespace)
-            .schedule(path, args, ts, context)
+            .schedule(path, args, ts, options, context)
             .await?;
         self.tx
             .virtual_system_mapping()
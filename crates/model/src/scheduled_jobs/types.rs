@@ -1,10 +1,17 @@
+use std::str::FromStr;
+
 use common::{
     components::{
         CanonicalizedComponentFunctionPath,
         ComponentPath,
     },
-    types::Timestamp,
+    execution_context::RequestContext,
+    types::{
+        ObjectKey,
+        Timestamp,
+    },
 };
+use errors::ErrorMetadata;
 #[cfg(any(test, feature = "testing"))]
 use proptest::prelude::*;
 use serde::{
@@ -16,6 +23,7 @@ use serde_json::Value as JsonValue;
 use value::{
     codegen_convex_serialization,
     ConvexArray,
+    Size,
 };
 
 #[derive(Clone, Debug, PartialEq)]
@@ -25,6 +33,10 @@ pub struct ScheduledJob {
     /// component that scheduled it. But it can run jobs in a different
     /// component.
     pub path: CanonicalizedComponentFunctionPath,
+    /// The component that scheduled this job, which may differ from the
+    /// component of the function it runs. `None` for jobs scheduled before
+    /// jobs recorded their origin.
+    pub scheduled_by: Option<ComponentPath>,
     #[cfg_attr(
         any(test, feature = "testing"),
         proptest(
@@ -33,6 +45,9 @@ pub struct ScheduledJob {
         )
     )]
     pub udf_args_bytes: ByteBuf,
+    /// The object in file storage holding the arguments, if they were too
+    /// large to store in the job. `udf_args_bytes` is then an empty array.
+    pub args_storage_key: Option<ObjectKey>,
 
     pub state: ScheduledJobState,
 
@@ -48,6 +63,12 @@ pub struct ScheduledJob {
     pub original_scheduled_ts: Timestamp,
 
     pub attempts: ScheduledJobAttempts,
+
+    pub options: ScheduleOptions,
+
+    /// The request context of the function that scheduled this job, which the
+    /// job runs with.
+    pub request_context: RequestContext,
 }
 
 fn args_to_bytes(args: ConvexArray) -> anyhow::Result<ByteBuf> {
@@ -56,27 +77,58 @@ fn args_to_bytes(args: ConvexArray) -> anyhow::Result<ByteBuf> {
     Ok(ByteBuf::from(args_bytes))
 }
 
+/// The arguments of a new scheduled job.
+#[derive(Clone, Debug)]
+pub enum ScheduledJobArgs {
+    Inline(ConvexArray),
+    /// Arguments larger than `FUNCTION_MAX_ARGS_SIZE`, uploaded to file
+    /// storage as JSON.
+    Offloaded(ObjectKey),
+}
+
+impl ScheduledJobArgs {
+    /// The size the arguments take up in the job.
+    pub fn inline_size(&self) -> usize {
+        match self {
+            Self::Inline(args) => args.size(),
+            Self::Offloaded(_) => 0,
+        }
+    }
+}
+
 impl ScheduledJob {
     pub fn new(
         path: CanonicalizedComponentFunctionPath,
-        udf_args: ConvexArray,
+        scheduled_by: ComponentPath,
+        udf_args: ScheduledJobArgs,
         state: ScheduledJobState,
         next_ts: Option<Timestamp>,
         completed_ts: Option<Timestamp>,
         original_scheduled_ts: Timestamp,
         attempts: ScheduledJobAttempts,
+        options: ScheduleOptions,
     ) -> anyhow::Result<Self> {
+        let (udf_args, args_storage_key) = match udf_args {
+            ScheduledJobArgs::Inline(args) => (args, None),
+            ScheduledJobArgs::Offloaded(storage_key) => (ConvexArray::empty(), Some(storage_key)),
+        };
         Ok(Self {
             path,
+            scheduled_by: Some(scheduled_by),
             udf_args_bytes: args_to_bytes(udf_args)?,
+            args_storage_key,
             state,
             next_ts,
             completed_ts,
             original_scheduled_ts,
             attempts,
+            options,
+            request_context: RequestContext::default(),
         })
     }
 
+    /// The arguments stored in the job, which are empty if they were
+    /// offloaded to file storage. Use `load_args` to read those.
     pub fn udf_args(&self) -> anyhow::Result<ConvexArray> {
         let args_json: JsonValue = serde_json::from_slice(&self.udf_args_bytes)?;
         let args = args_json.try_into()?;
@@ -89,14 +141,25 @@ impl ScheduledJob {
 struct SerializedScheduledJob {
     component: Option<String>,
     udf_path: String,
+    scheduled_by: Option<String>,
     // Serialize the udf arguments as binary since we restrict what
     // field names can be used in a `Document`'s top-level object.
     udf_args: ByteBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args_storage_key: Option<String>,
     state: SerializedScheduledJobState,
     next_ts: Option<i64>,
     completed_ts: Option<i64>,
     original_scheduled_ts: Option<i64>,
     attempts: Option<ScheduledJobAttempts>,
+    // Normal priority jobs leave this unset, so they're in the same range of the
+    // priority index as jobs scheduled before priorities existed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    max_concurrency: Option<i64>,
+    // Stored as JSON since its keys aren't necessarily valid field names.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_context: Option<String>,
 }
 
 impl TryFrom<ScheduledJob> for SerializedScheduledJob {
@@ -106,12 +169,21 @@ impl TryFrom<ScheduledJob> for SerializedScheduledJob {
         Ok(SerializedScheduledJob {
             component: Some(String::from(job.path.component)),
             udf_path: String::from(job.path.udf_path),
+            scheduled_by: job.scheduled_by.map(String::from),
             udf_args: job.udf_args_bytes,
+            args_storage_key: job.args_storage_key.map(String::from),
             state: job.state.try_into()?,
             next_ts: job.next_ts.map(|ts| ts.into()),
             completed_ts: job.completed_ts.map(|ts| ts.into()),
             original_scheduled_ts: Some(job.original_scheduled_ts.into()),
             attempts: Some(job.attempts),
+            priority: job.options.priority.as_str().map(String::from),
+            max_concurrency: job.options.max_concurrency.map(i64::from),
+            request_context: if job.request_context.is_empty() {
+                None
+            } else {
+                Some(JsonValue::from(job.request_context).to_string())
+            },
         })
     }
 }
@@ -126,7 +198,12 @@ impl TryFrom<SerializedScheduledJob> for ScheduledJob {
             .transpose()?
             .unwrap_or_else(ComponentPath::root);
         let udf_path = value.udf_path.parse()?;
+        let scheduled_by = value.scheduled_by.map(|p| p.parse()).transpose()?;
         let udf_args_bytes = value.udf_args;
+        let args_storage_key = value
+            .args_storage_key
+            .map(ObjectKey::try_from)
+            .transpose()?;
         let state = value.state.try_into()?;
         let next_ts = value.next_ts.map(|ts| ts.try_into()).transpose()?;
         let completed_ts = value.completed_ts.map(|ts| ts.try_into()).transpose()?;
@@ -148,12 +225,27 @@ impl TryFrom<SerializedScheduledJob> for ScheduledJob {
                 component,
                 udf_path,
             },
+            scheduled_by,
             udf_args_bytes,
+            args_storage_key,
             state,
             next_ts,
             completed_ts,
             original_scheduled_ts,
             attempts: value.attempts.unwrap_or_default(),
+            options: ScheduleOptions {
+                priority: value
+                    .priority
+                    .map(|p| p.parse())
+                    .transpose()?
+                    .unwrap_or_default(),
+                max_concurrency: value.max_concurrency.map(u32::try_from).transpose()?,
+            },
+            request_context: value
+                .request_context
+                .map(|c| c.parse())
+                .transpose()?
+                .unwrap_or_default(),
         })
     }
 }
@@ -172,6 +264,95 @@ impl ScheduledJobAttempts {
     }
 }
 
+/// How soon a job starts once it's due. When more jobs are due than the
+/// scheduler can run at once, higher priority jobs start first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum SchedulePriority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl SchedulePriority {
+    /// Every priority, highest first.
+    pub const ALL: [SchedulePriority; 3] = [Self::High, Self::Normal, Self::Low];
+
+    /// The stored value, which is unset for normal priority.
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            Self::High => Some("high"),
+            Self::Normal => None,
+            Self::Low => Some("low"),
+        }
+    }
+}
+
+impl FromStr for SchedulePriority {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "high" => Ok(Self::High),
+            "normal" => Ok(Self::Normal),
+            "low" => Ok(Self::Low),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSchedulePriority",
+                format!("Invalid priority {s:?}, expected \"high\", \"normal\" or \"low\""),
+            )),
+        }
+    }
+}
+
+/// Options set when a job is scheduled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ScheduleOptions {
+    pub priority: SchedulePriority,
+    /// The job won't start while this many jobs for the same function are
+    /// running.
+    pub max_concurrency: Option<u32>,
+}
+
+impl ScheduleOptions {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.max_concurrency != Some(0),
+            ErrorMetadata::bad_request(
+                "InvalidScheduleOptions",
+                "maxConcurrency must be at least 1",
+            )
+        );
+        Ok(())
+    }
+}
+
+/// `ScheduleOptions` as passed to the scheduling syscalls.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleOptionsJson {
+    pub priority: Option<String>,
+    pub max_concurrency: Option<u32>,
+}
+
+impl TryFrom<ScheduleOptionsJson> for ScheduleOptions {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ScheduleOptionsJson) -> anyhow::Result<Self> {
+        let options = Self {
+            priority: value
+                .priority
+                .map(|p| p.parse())
+                .transpose()?
+                .unwrap_or_default(),
+            max_concurrency: value.max_concurrency,
+        };
+        options.validate()?;
+        Ok(options)
+    }
+}
+
 /// The state machine for scheduled jobs. Note that only actions go through the
 /// InProgress state. Mutations jump straight from Pending to one of the
 /// completion states.
@@ -5,6 +5,7 @@ use std::{
 };
 
 use common::{
+    components::ComponentPath,
     document::{
         timestamp_to_ms,
         DeveloperDocument,
@@ -62,8 +63,9 @@ impl VirtualSystemDocMapper for ScheduledJobsDocMapper {
         let job: ScheduledJob = job.into_value();
         let udf_args = job.udf_args()?;
         let public_job = PublicScheduledJob {
-            // TODO(ENG-6920) include component (job.path.component) in virtual table.
             name: job.path.udf_path,
+            component: job.path.component,
+            scheduled_by: job.scheduled_by,
             args: udf_args,
             state: job.state,
             scheduled_time: timestamp_to_ms(job.original_scheduled_ts)?,
@@ -100,6 +102,10 @@ impl VirtualSystemDocMapper for ScheduledJobsDocMapper {
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub struct PublicScheduledJob {
     pub name: CanonicalizedUdfPath,
+    /// Component of the scheduled function. Serialized the same way as the
+    /// system document so `by_component` index ranges match.
+    pub component: ComponentPath,
+    pub scheduled_by: Option<ComponentPath>,
     pub args: ConvexArray,
     pub state: ScheduledJobState,
     pub scheduled_time: f64,
@@ -115,6 +121,16 @@ impl TryFrom<PublicScheduledJob> for ConvexObject {
             "name".parse()?,
             ConvexValue::try_from(String::from(job.name))?,
         );
+        obj.insert(
+            "component".parse()?,
+            ConvexValue::try_from(String::from(job.component))?,
+        );
+        if let Some(scheduled_by) = job.scheduled_by {
+            obj.insert(
+                "scheduledBy".parse()?,
+                ConvexValue::try_from(String::from(scheduled_by))?,
+            );
+        }
         obj.insert("args".parse()?, ConvexValue::Array(job.args));
 
         // Rename `type` -> `kind` in the scheduled job state
@@ -152,6 +168,19 @@ impl TryFrom<ConvexObject> for PublicScheduledJob {
                 anyhow::bail!("Missing or invalid `name` field for PublicScheduledJob: {name:?}")
             },
         };
+        let component = match fields.remove("component") {
+            Some(ConvexValue::String(component)) => String::from(component).parse()?,
+            component => anyhow::bail!(
+                "Missing or invalid `component` field for PublicScheduledJob: {component:?}"
+            ),
+        };
+        let scheduled_by = match fields.remove("scheduledBy") {
+            None => None,
+            Some(ConvexValue::String(scheduled_by)) => Some(String::from(scheduled_by).parse()?),
+            scheduled_by => anyhow::bail!(
+                "Invalid `scheduledBy` field for PublicScheduledJob: {scheduled_by:?}"
+            ),
+        };
         let args = match fields.remove("args") {
             Some(ConvexValue::Array(args)) => args,
             args => {
@@ -188,6 +217,8 @@ impl TryFrom<ConvexObject> for PublicScheduledJob {
         };
         Ok(PublicScheduledJob {
             name,
+            component,
+            scheduled_by,
             args,
             state,
             scheduled_time,
@@ -0,0 +1,216 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    schemas::DatabaseSchema,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use serde_json::Value as JsonValue;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    SchemaValidationReport,
+    SchemaValidationReportState,
+};
+use crate::{
+    initialize_application_system_table,
+    SystemIndex,
+    SystemTable,
+    DEFAULT_TABLE_NUMBERS,
+};
+
+pub mod types;
+
+pub static SCHEMA_VALIDATION_REPORTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_schema_validation_reports"
+        .parse()
+        .expect("Invalid built-in schema validation reports table")
+});
+
+pub struct SchemaValidationReportsTable;
+impl SystemTable for SchemaValidationReportsTable {
+    fn table_name(&self) -> &'static TableName {
+        &SCHEMA_VALIDATION_REPORTS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<SchemaValidationReport>::try_from(document).map(|_| ())
+    }
+}
+
+/// Each component keeps at most one report: requesting a new one replaces
+/// the previous report.
+pub struct SchemaValidationReportModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> SchemaValidationReportModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// Request a validate-only pass of `schema` over the existing documents.
+    pub async fn request(&mut self, schema: DatabaseSchema) -> anyhow::Result<ResolvedDocumentId> {
+        // Components created before this table existed don't have it yet.
+        initialize_application_system_table(
+            self.tx,
+            &SchemaValidationReportsTable,
+            self.namespace,
+            &DEFAULT_TABLE_NUMBERS,
+        )
+        .await?;
+        if let Some(existing) = self.get().await? {
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .delete(existing.id())
+                .await?;
+        }
+        let json_schema: JsonValue = schema.try_into()?;
+        let report = SchemaValidationReport {
+            raw_schema: serde_json::to_string(&json_schema)?,
+            state: SchemaValidationReportState::ValidateOnly,
+        };
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .insert(&SCHEMA_VALIDATION_REPORTS_TABLE, report.try_into()?)
+            .await
+    }
+
+    pub async fn get(&mut self) -> anyhow::Result<Option<ParsedDocument<SchemaValidationReport>>> {
+        if !self
+            .tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .name_exists(&SCHEMA_VALIDATION_REPORTS_TABLE)
+        {
+            return Ok(None);
+        }
+        let query = Query::full_table_scan(SCHEMA_VALIDATION_REPORTS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| doc.try_into())
+            .transpose()
+    }
+
+    /// The report waiting to be produced by the schema worker, if any.
+    pub async fn pending(
+        &mut self,
+    ) -> anyhow::Result<Option<(ResolvedDocumentId, DatabaseSchema)>> {
+        let Some(report) = self.get().await? else {
+            return Ok(None);
+        };
+        if report.state != SchemaValidationReportState::ValidateOnly {
+            return Ok(None);
+        }
+        let json_schema: JsonValue = serde_json::from_str(&report.raw_schema)?;
+        Ok(Some((report.id(), DatabaseSchema::try_from(json_schema)?)))
+    }
+
+    /// Record the result of a validate-only pass. Does nothing if the report
+    /// was replaced while the worker was scanning.
+    pub async fn finish(
+        &mut self,
+        id: ResolvedDocumentId,
+        state: SchemaValidationReportState,
+    ) -> anyhow::Result<()> {
+        let Some(doc) = self.tx.get(id).await? else {
+            return Ok(());
+        };
+        let mut report: SchemaValidationReport =
+            ParsedDocument::<SchemaValidationReport>::try_from(doc)?.into_value();
+        anyhow::ensure!(
+            report.state == SchemaValidationReportState::ValidateOnly,
+            "Schema validation report {id} has already finished"
+        );
+        report.state = state;
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .replace(id, report.try_into()?)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        db_schema,
+        schemas::DatabaseSchema,
+        types::Timestamp,
+    };
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+    use value::TableNamespace;
+
+    use crate::{
+        schema_validation_reports::{
+            types::SchemaValidationReportState,
+            SchemaValidationReportModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_request_replaces_report(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = SchemaValidationReportModel::new(&mut tx, TableNamespace::Global);
+        assert!(model.get().await?.is_none());
+
+        let schema: DatabaseSchema = db_schema!();
+        let first_id = model.request(schema.clone()).await?;
+        let (pending_id, pending_schema) = model.pending().await?.unwrap();
+        assert_eq!(pending_id, first_id);
+        assert_eq!(pending_schema, schema);
+
+        let second_id = model.request(schema).await?;
+        model
+            .finish(
+                second_id,
+                SchemaValidationReportState::Completed {
+                    completed_ts: Timestamp::MIN,
+                    num_documents_checked: 0,
+                    violations: vec![],
+                    truncated: false,
+                },
+            )
+            .await?;
+        // Finishing a replaced report is a no-op.
+        model
+            .finish(
+                first_id,
+                SchemaValidationReportState::Failed {
+                    error: "replaced".to_string(),
+                },
+            )
+            .await?;
+        assert!(model.pending().await?.is_none());
+        let report = model.get().await?.unwrap();
+        assert_eq!(report.id(), second_id);
+        assert!(matches!(
+            report.state,
+            SchemaValidationReportState::Completed { .. }
+        ));
+        Ok(())
+    }
+}
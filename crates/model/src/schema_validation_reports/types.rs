@@ -0,0 +1,174 @@
+use common::types::Timestamp;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    id_v6::DeveloperDocumentId,
+    TableName,
+};
+
+/// Result of validating the documents in a component against a schema
+/// without enforcing it. Reports are produced by the schema worker so the
+/// dashboard can show what would fail before a schema is pushed with
+/// validation turned on.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SchemaValidationReport {
+    /// JSON serialized `DatabaseSchema` being validated.
+    pub raw_schema: String,
+    pub state: SchemaValidationReportState,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum SchemaValidationReportState {
+    /// Waiting for the schema worker to scan the tables.
+    ValidateOnly,
+    Completed {
+        completed_ts: Timestamp,
+        num_documents_checked: u64,
+        violations: Vec<SchemaViolation>,
+        /// Set if there were more violations than we keep in a report.
+        truncated: bool,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// A single document that does not match the schema.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SchemaViolation {
+    pub table_name: TableName,
+    pub document_id: DeveloperDocumentId,
+    /// Path within the document of the mismatching value, if any.
+    pub field_path: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSchemaValidationReport {
+    schema: String,
+    state: SerializedSchemaValidationReportState,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedSchemaValidationReportState {
+    ValidateOnly,
+    #[serde(rename_all = "camelCase")]
+    Completed {
+        completed_ts: i64,
+        num_documents_checked: i64,
+        violations: Vec<SerializedSchemaViolation>,
+        truncated: bool,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSchemaViolation {
+    table_name: String,
+    document_id: String,
+    field_path: Option<String>,
+    reason: String,
+}
+
+impl From<SchemaValidationReport> for SerializedSchemaValidationReport {
+    fn from(report: SchemaValidationReport) -> Self {
+        Self {
+            schema: report.raw_schema,
+            state: report.state.into(),
+        }
+    }
+}
+
+impl TryFrom<SerializedSchemaValidationReport> for SchemaValidationReport {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedSchemaValidationReport) -> anyhow::Result<Self> {
+        Ok(Self {
+            raw_schema: value.schema,
+            state: value.state.try_into()?,
+        })
+    }
+}
+
+impl From<SchemaValidationReportState> for SerializedSchemaValidationReportState {
+    fn from(state: SchemaValidationReportState) -> Self {
+        match state {
+            SchemaValidationReportState::ValidateOnly => Self::ValidateOnly,
+            SchemaValidationReportState::Completed {
+                completed_ts,
+                num_documents_checked,
+                violations,
+                truncated,
+            } => Self::Completed {
+                completed_ts: completed_ts.into(),
+                num_documents_checked: num_documents_checked as i64,
+                violations: violations.into_iter().map(Into::into).collect(),
+                truncated,
+            },
+            SchemaValidationReportState::Failed { error } => Self::Failed { error },
+        }
+    }
+}
+
+impl TryFrom<SerializedSchemaValidationReportState> for SchemaValidationReportState {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedSchemaValidationReportState) -> anyhow::Result<Self> {
+        let state = match value {
+            SerializedSchemaValidationReportState::ValidateOnly => Self::ValidateOnly,
+            SerializedSchemaValidationReportState::Completed {
+                completed_ts,
+                num_documents_checked,
+                violations,
+                truncated,
+            } => Self::Completed {
+                completed_ts: completed_ts.try_into()?,
+                num_documents_checked: num_documents_checked as u64,
+                violations: violations
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .try_collect()?,
+                truncated,
+            },
+            SerializedSchemaValidationReportState::Failed { error } => Self::Failed { error },
+        };
+        Ok(state)
+    }
+}
+
+impl From<SchemaViolation> for SerializedSchemaViolation {
+    fn from(violation: SchemaViolation) -> Self {
+        Self {
+            table_name: violation.table_name.to_string(),
+            document_id: violation.document_id.encode(),
+            field_path: violation.field_path,
+            reason: violation.reason,
+        }
+    }
+}
+
+impl TryFrom<SerializedSchemaViolation> for SchemaViolation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedSchemaViolation) -> anyhow::Result<Self> {
+        Ok(Self {
+            table_name: value.table_name.parse()?,
+            document_id: DeveloperDocumentId::decode(&value.document_id)?,
+            field_path: value.field_path,
+            reason: value.reason,
+        })
+    }
+}
+
+codegen_convex_serialization!(SchemaValidationReport, SerializedSchemaValidationReport);
@@ -0,0 +1,253 @@
+//! Counters for hot values that many mutations update at once. A counter
+//! stored in a single document makes every concurrent increment conflict, so
+//! each increment here updates one of [`SHARDED_COUNTER_NUM_SHARDS`] shard
+//! documents picked at random, and reading the counter sums the shards.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    knobs::SHARDED_COUNTER_NUM_SHARDS,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use rand::Rng;
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::CounterShard;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static COUNTER_SHARDS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_counter_shards"
+        .parse()
+        .expect("Invalid built-in counter shards table")
+});
+
+pub static COUNTER_SHARDS_BY_NAME_AND_SHARD: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&COUNTER_SHARDS_TABLE, "by_name_and_shard"));
+
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("invalid name field"));
+static SHARD_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "shard".parse().expect("invalid shard field"));
+
+/// Longest counter or list name, in bytes.
+pub const MAX_NAME_LENGTH: usize = 256;
+
+pub struct CounterShardsTable;
+impl SystemTable for CounterShardsTable {
+    fn table_name(&self) -> &'static TableName {
+        &COUNTER_SHARDS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: COUNTER_SHARDS_BY_NAME_AND_SHARD.clone(),
+            fields: vec![
+                NAME_FIELD.clone(),
+                SHARD_FIELD.clone(),
+                CREATION_TIME_FIELD_PATH.clone(),
+            ]
+            .try_into()
+            .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<CounterShard>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct ShardedCounterModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> ShardedCounterModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// Add `delta` to the counter. Only reads one shard, so it conflicts
+    /// with concurrent increments to the same shard and nothing else.
+    pub async fn increment(&mut self, name: &str, delta: i64) -> anyhow::Result<()> {
+        validate_name(name)?;
+        let shard = self
+            .tx
+            .runtime()
+            .rng()
+            .gen_range(0..*SHARDED_COUNTER_NUM_SHARDS);
+        let range = vec![
+            IndexRangeExpression::Eq(NAME_FIELD.clone(), name_value(name)?.into()),
+            IndexRangeExpression::Eq(
+                SHARD_FIELD.clone(),
+                ConvexValue::from(i64::from(shard)).into(),
+            ),
+        ];
+        let query = Query::index_range(IndexRange {
+            index_name: COUNTER_SHARDS_BY_NAME_AND_SHARD.clone(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let existing: Option<ParsedDocument<CounterShard>> = query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()?;
+        match existing {
+            Some(existing) => {
+                let id = existing.id();
+                let mut counter_shard = existing.into_value();
+                counter_shard.value = checked_add(name, counter_shard.value, delta)?;
+                SystemMetadataModel::new(self.tx, self.namespace)
+                    .replace(id, counter_shard.try_into()?)
+                    .await?;
+            },
+            None => {
+                let counter_shard = CounterShard {
+                    name: name.to_string(),
+                    shard,
+                    value: delta,
+                };
+                SystemMetadataModel::new(self.tx, self.namespace)
+                    .insert(&COUNTER_SHARDS_TABLE, counter_shard.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// The counter's value, zero if it has never been incremented. Reads
+    /// every shard, so a mutation that reads a counter conflicts with all
+    /// concurrent increments to it.
+    pub async fn read(&mut self, name: &str) -> anyhow::Result<i64> {
+        validate_name(name)?;
+        let query = Query::index_range(IndexRange {
+            index_name: COUNTER_SHARDS_BY_NAME_AND_SHARD.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                NAME_FIELD.clone(),
+                name_value(name)?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut total = 0i64;
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            let counter_shard: ParsedDocument<CounterShard> = document.try_into()?;
+            total = checked_add(name, total, counter_shard.value)?;
+        }
+        Ok(total)
+    }
+}
+
+pub(crate) fn validate_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidName",
+            format!("Names must be between 1 and {MAX_NAME_LENGTH} bytes long"),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn name_value(name: &str) -> anyhow::Result<ConvexValue> {
+    ConvexValue::try_from(name.to_string())
+}
+
+fn checked_add(name: &str, value: i64, delta: i64) -> anyhow::Result<i64> {
+    value.checked_add(delta).ok_or_else(|| {
+        ErrorMetadata::bad_request(
+            "CounterOverflow",
+            format!("Counter \"{name}\" would overflow a 64-bit integer"),
+        )
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use common::query::Order;
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+    use value::{
+        ConvexValue,
+        TableNamespace,
+    };
+
+    use crate::{
+        append_only_lists::AppendOnlyListModel,
+        sharded_counters::ShardedCounterModel,
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_counter_sums_shards(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = ShardedCounterModel::new(&mut tx, TableNamespace::Global);
+        assert_eq!(model.read("likes").await?, 0);
+        for _ in 0..50 {
+            model.increment("likes", 2).await?;
+        }
+        model.increment("likes", -10).await?;
+        model.increment("views", 1).await?;
+        assert_eq!(model.read("likes").await?, 90);
+        assert_eq!(model.read("views").await?, 1);
+
+        // The second increment either overflows its shard or the sum does.
+        model.increment("big", i64::MAX).await?;
+        let overflowed =
+            model.increment("big", 1).await.is_err() || model.read("big").await.is_err();
+        assert!(overflowed);
+        assert!(model.increment("", 1).await.is_err());
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_list_reads_in_order(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = AppendOnlyListModel::new(&mut tx, TableNamespace::Global);
+        for i in 0..5i64 {
+            model.append("feed", ConvexValue::from(i)).await?;
+        }
+        model.append("other", ConvexValue::Null).await?;
+        assert_eq!(
+            model.read("feed", Order::Asc, 3).await?,
+            vec![0i64.into(), 1i64.into(), 2i64.into()]
+        );
+        assert_eq!(
+            model.read("feed", Order::Desc, 10).await?,
+            (0..5i64).rev().map(ConvexValue::from).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// One shard of a named counter. The counter's value is the sum of the
+/// values of all of its shards.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct CounterShard {
+    pub name: String,
+    pub shard: u32,
+    pub value: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedCounterShard {
+    name: String,
+    shard: i64,
+    value: i64,
+}
+
+impl From<CounterShard> for SerializedCounterShard {
+    fn from(shard: CounterShard) -> Self {
+        Self {
+            name: shard.name,
+            shard: shard.shard.into(),
+            value: shard.value,
+        }
+    }
+}
+
+impl TryFrom<SerializedCounterShard> for CounterShard {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedCounterShard) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: value.name,
+            shard: value.shard.try_into()?,
+            value: value.value,
+        })
+    }
+}
+
+codegen_convex_serialization!(CounterShard, SerializedCounterShard);
@@ -0,0 +1,255 @@
+//! Queries on user tables that scanned too many documents or bytes, or took
+//! too long, along with the index that would have served each one. Slow
+//! queries are buffered in memory as they run and persisted here by the
+//! application.
+
+use std::{
+    collections::BTreeMap,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        CreationTime,
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    IndexRecommendation,
+    SlowQueryRecord,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static SLOW_QUERIES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_slow_queries"
+        .parse()
+        .expect("Invalid built-in slow queries table")
+});
+
+pub struct SlowQueriesTable;
+impl SystemTable for SlowQueriesTable {
+    fn table_name(&self) -> &'static TableName {
+        &SLOW_QUERIES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<SlowQueryRecord>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct SlowQueryPage {
+    pub queries: Vec<ParsedDocument<SlowQueryRecord>>,
+    /// Pass as the `cursor` of the next call to continue after this page, or
+    /// `None` if there are no more slow queries.
+    pub cursor: Option<CreationTime>,
+}
+
+pub struct SlowQueryModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> SlowQueryModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn insert(&mut self, records: Vec<SlowQueryRecord>) -> anyhow::Result<()> {
+        for record in records {
+            SystemMetadataModel::new_global(self.tx)
+                .insert(&SLOW_QUERIES_TABLE, record.try_into()?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Returns up to `limit` slow queries recorded before `cursor`, newest
+    /// first.
+    pub async fn list(
+        &mut self,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<SlowQueryPage> {
+        let range = cursor
+            .map(|cursor| {
+                IndexRangeExpression::Lt(CREATION_TIME_FIELD_PATH.clone(), f64::from(cursor).into())
+            })
+            .into_iter()
+            .collect();
+        let query = Query::index_range(IndexRange {
+            index_name: IndexName::by_creation_time(SLOW_QUERIES_TABLE.clone()),
+            range,
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut queries = vec![];
+        while let Some(doc) = query_stream.next(self.tx, Some(limit)).await? {
+            let creation_time = doc.creation_time();
+            queries.push(doc.try_into()?);
+            if queries.len() >= limit {
+                return Ok(SlowQueryPage {
+                    queries,
+                    cursor: creation_time,
+                });
+            }
+        }
+        Ok(SlowQueryPage {
+            queries,
+            cursor: None,
+        })
+    }
+
+    /// Indexes suggested by the most recent `max_queries` slow queries, the
+    /// ones that would have saved the most documents from being scanned first.
+    pub async fn index_recommendations(
+        &mut self,
+        max_queries: usize,
+    ) -> anyhow::Result<Vec<IndexRecommendation>> {
+        let page = self.list(None, max_queries).await?;
+        Ok(recommend_indexes(
+            page.queries.into_iter().map(ParsedDocument::into_value),
+        ))
+    }
+}
+
+fn recommend_indexes(queries: impl Iterator<Item = SlowQueryRecord>) -> Vec<IndexRecommendation> {
+    let mut recommendations = BTreeMap::new();
+    for query in queries {
+        let Some(fields) = query.suggested_index else {
+            continue;
+        };
+        let key = (
+            query.component_path.clone(),
+            query.table_name.clone(),
+            fields.clone(),
+        );
+        let recommendation = recommendations
+            .entry(key)
+            .or_insert_with(|| IndexRecommendation {
+                component_path: query.component_path,
+                table_name: query.table_name,
+                fields,
+                num_queries: 0,
+                documents_scanned: 0,
+                documents_returned: 0,
+                query_shapes: vec![],
+            });
+        recommendation.num_queries += 1;
+        recommendation.documents_scanned += query.documents_scanned;
+        recommendation.documents_returned += query.documents_returned;
+        let shape = match query.filter {
+            Some(filter) => format!("{}({}).filter({filter})", query.index_name, query.range),
+            None => format!("{}({})", query.index_name, query.range),
+        };
+        if !recommendation.query_shapes.contains(&shape) {
+            recommendation.query_shapes.push(shape);
+        }
+    }
+    let mut recommendations: Vec<_> = recommendations.into_values().collect();
+    recommendations.sort_by_key(|r| {
+        std::cmp::Reverse(r.documents_scanned.saturating_sub(r.documents_returned))
+    });
+    recommendations
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        components::ComponentPath,
+        query::Order,
+    };
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        slow_queries::{
+            types::SlowQueryRecord,
+            SlowQueryModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn record(
+        filter: &str,
+        suggested_index: Option<Vec<&str>>,
+        documents_scanned: u64,
+    ) -> SlowQueryRecord {
+        SlowQueryRecord {
+            component_path: ComponentPath::root(),
+            table_name: "messages".to_string(),
+            index_name: "by_creation_time".to_string(),
+            range: String::new(),
+            filter: Some(filter.to_string()),
+            order: Order::Asc,
+            documents_scanned,
+            bytes_scanned: documents_scanned * 100,
+            documents_returned: 1,
+            execution_time_ms: 10,
+            suggested_index: suggested_index
+                .map(|fields| fields.into_iter().map(String::from).collect()),
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_index_recommendations(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = SlowQueryModel::new(&mut tx);
+        model
+            .insert(vec![
+                record("author == ?", Some(vec!["author"]), 2000),
+                record("author == ?", Some(vec!["author"]), 3000),
+                record(
+                    "channel == ? && likes > ?",
+                    Some(vec!["channel", "likes"]),
+                    10000,
+                ),
+                record("(author == ?) || (channel == ?)", None, 5000),
+            ])
+            .await?;
+
+        let page = model.list(None, 3).await?;
+        assert_eq!(page.queries.len(), 3);
+        assert!(model.list(page.cursor, 3).await?.cursor.is_none());
+
+        let recommendations = model.index_recommendations(100).await?;
+        assert_eq!(recommendations.len(), 2);
+        assert_eq!(recommendations[0].fields, vec!["channel", "likes"]);
+        assert_eq!(recommendations[1].fields, vec!["author"]);
+        assert_eq!(recommendations[1].num_queries, 2);
+        assert_eq!(recommendations[1].documents_scanned, 5000);
+        assert_eq!(
+            recommendations[1].query_shapes,
+            vec!["by_creation_time().filter(author == ?)"]
+        );
+        Ok(())
+    }
+}
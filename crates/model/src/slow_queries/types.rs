@@ -0,0 +1,111 @@
+use common::{
+    components::ComponentPath,
+    query::Order,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A query on a user table that exceeded the slow query thresholds. Values in
+/// the query's range and filter are replaced with `?`, so records of the same
+/// query with different arguments have the same shape.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SlowQueryRecord {
+    pub component_path: ComponentPath,
+    pub table_name: String,
+    /// The index scanned, without the table name. Full table scans use
+    /// `by_creation_time`.
+    pub index_name: String,
+    pub range: String,
+    pub filter: Option<String>,
+    pub order: Order,
+    pub documents_scanned: u64,
+    pub bytes_scanned: u64,
+    pub documents_returned: u64,
+    pub execution_time_ms: u64,
+    /// Fields of an index that would serve the query without the filter
+    /// discarding documents, if the query's index doesn't already.
+    pub suggested_index: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSlowQueryRecord {
+    component_path: Option<String>,
+    table_name: String,
+    index_name: String,
+    range: String,
+    filter: Option<String>,
+    order: String,
+    documents_scanned: i64,
+    bytes_scanned: i64,
+    documents_returned: i64,
+    execution_time_ms: i64,
+    suggested_index: Option<Vec<String>>,
+}
+
+impl From<SlowQueryRecord> for SerializedSlowQueryRecord {
+    fn from(record: SlowQueryRecord) -> Self {
+        Self {
+            component_path: record.component_path.serialize(),
+            table_name: record.table_name,
+            index_name: record.index_name,
+            range: record.range,
+            filter: record.filter,
+            order: match record.order {
+                Order::Asc => "asc",
+                Order::Desc => "desc",
+            }
+            .to_string(),
+            documents_scanned: record.documents_scanned as i64,
+            bytes_scanned: record.bytes_scanned as i64,
+            documents_returned: record.documents_returned as i64,
+            execution_time_ms: record.execution_time_ms as i64,
+            suggested_index: record.suggested_index,
+        }
+    }
+}
+
+impl TryFrom<SerializedSlowQueryRecord> for SlowQueryRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedSlowQueryRecord) -> anyhow::Result<Self> {
+        Ok(Self {
+            component_path: ComponentPath::deserialize(value.component_path.as_deref())?,
+            table_name: value.table_name,
+            index_name: value.index_name,
+            range: value.range,
+            filter: value.filter,
+            order: match &value.order[..] {
+                "asc" => Order::Asc,
+                "desc" => Order::Desc,
+                order => anyhow::bail!("Invalid order {order:?}"),
+            },
+            documents_scanned: value.documents_scanned as u64,
+            bytes_scanned: value.bytes_scanned as u64,
+            documents_returned: value.documents_returned as u64,
+            execution_time_ms: value.execution_time_ms as u64,
+            suggested_index: value.suggested_index,
+        })
+    }
+}
+
+codegen_convex_serialization!(SlowQueryRecord, SerializedSlowQueryRecord);
+
+/// An index that would have served one or more slow queries, aggregated over
+/// the queries in the slow query log.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexRecommendation {
+    pub component_path: ComponentPath,
+    pub table_name: String,
+    pub fields: Vec<String>,
+    pub num_queries: u64,
+    pub documents_scanned: u64,
+    pub documents_returned: u64,
+    /// The distinct shapes of the queries the index would serve, formatted as
+    /// `index(range).filter(filter)`.
+    pub query_shapes: Vec<String>,
+}
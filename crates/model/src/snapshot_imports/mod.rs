@@ -14,7 +14,10 @@ use common::{
         Query,
     },
     runtime::Runtime,
-    types::ObjectKey,
+    types::{
+        FieldName,
+        ObjectKey,
+    },
 };
 use database::{
     patch_value,
@@ -39,6 +42,7 @@ use self::types::{
     ImportMode,
     ImportState,
     ImportTableCheckpoint,
+    ImportUpsertStats,
     SnapshotImport,
 };
 use crate::{
@@ -111,6 +115,7 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
         component_path: ComponentPath,
         object_key: ObjectKey,
         requestor: ImportRequestor,
+        upsert_key: Option<FieldName>,
     ) -> anyhow::Result<ResolvedDocumentId> {
         let snapshot_import = SnapshotImport {
             state: ImportState::Uploaded,
@@ -121,6 +126,7 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
             member_id: self.tx.identity().member_id(),
             checkpoints: None,
             requestor,
+            upsert_key,
         };
         let id = SystemMetadataModel::new_global(self.tx)
             .insert(
@@ -158,7 +164,10 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
             | (ImportState::WaitingForConfirmation { .. }, ImportState::Failed { .. })
             | (ImportState::InProgress { .. }, ImportState::InProgress { .. })
             | (ImportState::InProgress { .. }, ImportState::Completed { .. })
-            | (ImportState::InProgress { .. }, ImportState::Failed(..)) => {},
+            | (ImportState::InProgress { .. }, ImportState::Failed(..))
+            | (ImportState::InProgress { .. }, ImportState::Paused { .. })
+            | (ImportState::Paused { .. }, ImportState::InProgress { .. })
+            | (ImportState::Paused { .. }, ImportState::Failed(..)) => {},
             (..) => {
                 anyhow::bail!("invalid import state transition {current_state:?} -> {new_state:?}")
             },
@@ -222,14 +231,65 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
         Ok(())
     }
 
+    /// Stops the import worker from writing more documents until the import
+    /// is resumed.
+    pub async fn pause_import(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let current_state = self.must_get_state(id).await?;
+        match current_state {
+            ImportState::InProgress {
+                checkpoint_messages,
+                ..
+            } => {
+                self.update_state(id, move |_| ImportState::Paused {
+                    progress_message: "Paused".to_string(),
+                    checkpoint_messages,
+                })
+                .await?
+            },
+            // Pausing twice is a no-op.
+            ImportState::Paused { .. } => {},
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "CannotPauseImport",
+                "Only imports in progress can be paused"
+            )),
+        }
+        Ok(())
+    }
+
+    pub async fn resume_import(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let current_state = self.must_get_state(id).await?;
+        match current_state {
+            ImportState::Paused {
+                checkpoint_messages,
+                ..
+            } => {
+                self.update_state(id, move |_| ImportState::InProgress {
+                    progress_message: "Resuming import".to_string(),
+                    checkpoint_messages,
+                })
+                .await?
+            },
+            // Resuming an import that isn't paused is a no-op.
+            ImportState::InProgress { .. } => {},
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "CannotResumeImport",
+                "Only paused imports can be resumed"
+            )),
+        }
+        Ok(())
+    }
+
     pub async fn cancel_import(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
         let current_state = self.must_get_state(id).await?;
         match current_state {
-            ImportState::Uploaded | ImportState::WaitingForConfirmation { .. } => {
+            // The import worker notices in-progress imports are canceled between
+            // batches of writes, and the tables it was writing are never activated.
+            ImportState::Uploaded
+            | ImportState::WaitingForConfirmation { .. }
+            | ImportState::InProgress { .. }
+            | ImportState::Paused { .. } => {
                 self.fail_import(id, "Import canceled".to_string()).await?
             },
-            // TODO: support cancelling imports in progress
-            ImportState::InProgress { .. } => anyhow::bail!("Cannot cancel an import in progress"),
             ImportState::Completed { .. } => anyhow::bail!(ErrorMetadata::bad_request(
                 "CannotCancelImport",
                 "Cannot cancel an import that has completed"
@@ -281,6 +341,27 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
         .await
     }
 
+    /// Adds a batch of upsert writes to the table's checkpoint. Called in the
+    /// transaction that makes the writes, so the import can resume after them.
+    pub async fn checkpoint_upsert_batch(
+        &mut self,
+        id: ResolvedDocumentId,
+        component_path: &ComponentPath,
+        display_table_name: &TableName,
+        batch_stats: ImportUpsertStats,
+    ) -> anyhow::Result<()> {
+        self.update_checkpoints(id, move |checkpoints| {
+            if let Some(checkpoint) = checkpoints.iter_mut().find(|c| {
+                c.component_path == *component_path && c.display_table_name == *display_table_name
+            }) {
+                let stats = checkpoint.upsert_stats.get_or_insert_default();
+                *stats += batch_stats;
+                checkpoint.num_rows_written = stats.num_rows();
+            }
+        })
+        .await
+    }
+
     pub async fn get_table_checkpoint(
         &mut self,
         id: ResolvedDocumentId,
@@ -323,6 +404,10 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
             }
         })
         .await?;
+        // Don't let the worker resume an import that was paused or canceled.
+        if !self.must_get_state(id).await?.is_in_progress() {
+            return Ok(());
+        }
         self.update_state(id, move |state| {
             let (progress_message, mut checkpoint_messages) = match state {
                 ImportState::InProgress {
@@ -371,7 +456,7 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
             }
         })
         .await?;
-        if noop {
+        if noop || !self.must_get_state(id).await?.is_in_progress() {
             return Ok(());
         }
         self.update_state(id, move |state| {
@@ -427,6 +512,7 @@ mod tests {
             types::{
                 ImportFormat,
                 ImportMode,
+                ImportState,
             },
             SnapshotImportModel,
         },
@@ -446,10 +532,51 @@ mod tests {
                 ComponentPath::root(),
                 "objectkey".try_into()?,
                 ImportRequestor::SnapshotImport,
+                None,
             )
             .await?;
         let doc = imports_model.get(id).await?.context("Doc missing?")?;
         assert_eq!(imports_model.list().await?, vec![doc]);
         Ok(())
     }
+
+    #[convex_macro::test_runtime]
+    async fn test_pause_resume_cancel(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures { db, .. } = DbFixtures::new_with_model(&rt).await?;
+        let mut tx = db.begin_system().await?;
+        let mut imports_model = SnapshotImportModel::new(&mut tx);
+
+        let id = imports_model
+            .start_import(
+                ImportFormat::Zip,
+                ImportMode::Replace,
+                ComponentPath::root(),
+                "objectkey".try_into()?,
+                ImportRequestor::SnapshotImport,
+                None,
+            )
+            .await?;
+        // Only imports in progress can be paused.
+        assert!(imports_model.pause_import(id).await.is_err());
+        imports_model
+            .mark_waiting_for_confirmation(id, "info".to_string(), false, vec![])
+            .await?;
+        imports_model.confirm_import(id).await?;
+
+        imports_model.pause_import(id).await?;
+        assert!(matches!(
+            imports_model.must_get_state(id).await?,
+            ImportState::Paused { .. }
+        ));
+        imports_model.resume_import(id).await?;
+        assert!(imports_model.must_get_state(id).await?.is_in_progress());
+
+        imports_model.cancel_import(id).await?;
+        assert_eq!(
+            imports_model.must_get_state(id).await?,
+            ImportState::Failed("Import canceled".to_string())
+        );
+        assert!(imports_model.resume_import(id).await.is_err());
+        Ok(())
+    }
 }
@@ -1,6 +1,12 @@
+use std::{
+    collections::BTreeMap,
+    ops::AddAssign,
+};
+
 use common::{
     components::ComponentPath,
     types::{
+        FieldName,
         MemberId,
         ObjectKey,
         TableName,
@@ -27,6 +33,8 @@ pub struct SnapshotImport {
     pub member_id: Option<MemberId>,
     pub checkpoints: Option<Vec<ImportTableCheckpoint>>,
     pub requestor: ImportRequestor,
+    /// Field that identifies existing documents in [`ImportMode::Upsert`].
+    pub upsert_key: Option<FieldName>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -39,6 +47,7 @@ struct SerializedSnapshotImport {
     member_id: Option<i64>,
     checkpoints: Option<Vec<SerializedImportTableCheckpoint>>,
     requestor: SerializedImportRequestor,
+    upsert_key: Option<String>,
 }
 
 impl From<SnapshotImport> for SerializedSnapshotImport {
@@ -54,6 +63,7 @@ impl From<SnapshotImport> for SerializedSnapshotImport {
                 .checkpoints
                 .map(|checkpoints| checkpoints.into_iter().map(Into::into).collect()),
             requestor: import.requestor.into(),
+            upsert_key: import.upsert_key.map(|field| field.to_string()),
         }
     }
 }
@@ -74,6 +84,7 @@ impl TryFrom<SerializedSnapshotImport> for SnapshotImport {
                 .map(|checkpoints| checkpoints.into_iter().map(TryInto::try_into).try_collect())
                 .transpose()?,
             requestor: import.requestor.into(),
+            upsert_key: import.upsert_key.map(|field| field.parse()).transpose()?,
         })
     }
 }
@@ -87,6 +98,96 @@ pub enum ImportFormat {
     JsonLines(TableName),
     JsonArray(TableName),
     Zip,
+    /// Firestore documents, either as a bundle or as one REST API `Document`
+    /// per line.
+    Firestore(TableName, ImportFieldMapping),
+    /// Documents in MongoDB extended JSON, as written by `mongoexport`.
+    MongoExtendedJson(TableName, ImportFieldMapping),
+    /// Documents in BSON, as written by `mongodump`.
+    MongoBson(TableName, ImportFieldMapping),
+}
+
+/// How to map the top-level fields of documents exported from another
+/// database onto Convex documents. Fields without a rule keep their name and
+/// use the default conversion for their type.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ImportFieldMapping {
+    /// Field to store the source document's id (a Firestore document name or a
+    /// MongoDB `_id`) in. The source id is dropped if this is unset.
+    pub id_field: Option<FieldName>,
+    /// Rules keyed by source field name.
+    pub fields: BTreeMap<String, FieldMappingRule>,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FieldMappingRule {
+    pub rename: Option<FieldName>,
+    pub convert: Option<FieldConversion>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, strum::EnumString, strum::Display)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "camelCase")]
+pub enum FieldConversion {
+    /// Leave the field out of the imported document.
+    Skip,
+    String,
+    Float64,
+    Int64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedImportFieldMapping {
+    id_field: Option<String>,
+    fields: Vec<SerializedFieldMappingRule>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SerializedFieldMappingRule {
+    field: String,
+    rename: Option<String>,
+    convert: Option<String>,
+}
+
+impl From<ImportFieldMapping> for SerializedImportFieldMapping {
+    fn from(mapping: ImportFieldMapping) -> Self {
+        SerializedImportFieldMapping {
+            id_field: mapping.id_field.map(|field| field.to_string()),
+            fields: mapping
+                .fields
+                .into_iter()
+                .map(|(field, rule)| SerializedFieldMappingRule {
+                    field,
+                    rename: rule.rename.map(|rename| rename.to_string()),
+                    convert: rule.convert.map(|convert| convert.to_string()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<SerializedImportFieldMapping> for ImportFieldMapping {
+    type Error = anyhow::Error;
+
+    fn try_from(mapping: SerializedImportFieldMapping) -> anyhow::Result<Self> {
+        Ok(ImportFieldMapping {
+            id_field: mapping.id_field.map(|field| field.parse()).transpose()?,
+            fields: mapping
+                .fields
+                .into_iter()
+                .map(|rule| {
+                    let mapping_rule = FieldMappingRule {
+                        rename: rule.rename.map(|rename| rename.parse()).transpose()?,
+                        convert: rule.convert.map(|convert| convert.parse()).transpose()?,
+                    };
+                    anyhow::Ok((rule.field, mapping_rule))
+                })
+                .try_collect()?,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -100,6 +201,21 @@ pub enum SerializedImportFormat {
     JsonArray { table: String },
     #[serde(rename = "zip")]
     Zip,
+    #[serde(rename = "firestore")]
+    Firestore {
+        table: String,
+        mapping: SerializedImportFieldMapping,
+    },
+    #[serde(rename = "mongo_json")]
+    MongoExtendedJson {
+        table: String,
+        mapping: SerializedImportFieldMapping,
+    },
+    #[serde(rename = "mongo_bson")]
+    MongoBson {
+        table: String,
+        mapping: SerializedImportFieldMapping,
+    },
 }
 
 impl From<ImportFormat> for SerializedImportFormat {
@@ -115,6 +231,20 @@ impl From<ImportFormat> for SerializedImportFormat {
                 table: table.to_string(),
             },
             ImportFormat::Zip => SerializedImportFormat::Zip,
+            ImportFormat::Firestore(table, mapping) => SerializedImportFormat::Firestore {
+                table: table.to_string(),
+                mapping: mapping.into(),
+            },
+            ImportFormat::MongoExtendedJson(table, mapping) => {
+                SerializedImportFormat::MongoExtendedJson {
+                    table: table.to_string(),
+                    mapping: mapping.into(),
+                }
+            },
+            ImportFormat::MongoBson(table, mapping) => SerializedImportFormat::MongoBson {
+                table: table.to_string(),
+                mapping: mapping.into(),
+            },
         }
     }
 }
@@ -132,6 +262,15 @@ impl TryFrom<SerializedImportFormat> for ImportFormat {
                 Ok(ImportFormat::JsonArray(table.parse()?))
             },
             SerializedImportFormat::Zip => Ok(ImportFormat::Zip),
+            SerializedImportFormat::Firestore { table, mapping } => {
+                Ok(ImportFormat::Firestore(table.parse()?, mapping.try_into()?))
+            },
+            SerializedImportFormat::MongoExtendedJson { table, mapping } => Ok(
+                ImportFormat::MongoExtendedJson(table.parse()?, mapping.try_into()?),
+            ),
+            SerializedImportFormat::MongoBson { table, mapping } => {
+                Ok(ImportFormat::MongoBson(table.parse()?, mapping.try_into()?))
+            },
         }
     }
 }
@@ -164,16 +303,17 @@ Import│Worker parses
       │                     │
 CLI requests confirmation   │
       │                     │
-┌─────▼──────┐              │
-│ InProgress │              │
-└─────┬──────┘              │
-      │                     │
-Import│Worker imports       │
-      ├─────────────────┐   │
-      │                 │   │
-┌─────▼──────┐      ┌───▼───▼─┐
-│ Completed  │      │ Failed  │
-└────────────┘      └─────────┘
+┌─────▼──────┐   pause      │   ┌────────┐
+│ InProgress ├──────────────┼──►│ Paused │
+│            │◄─────────────┼───┤        │
+└─────┬──────┘   resume     │   └───┬────┘
+      │                     │       │
+Import│Worker imports       │       │cancel
+      ├─────────────────┐   │       │
+      │                 │   │       │
+┌─────▼──────┐      ┌───▼───▼───────▼┐
+│ Completed  │      │     Failed     │
+└────────────┘      └────────────────┘
  */
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
@@ -187,6 +327,12 @@ pub enum ImportState {
         progress_message: String,
         checkpoint_messages: Vec<String>,
     },
+    /// An in-progress import that was paused. The worker stops writing until
+    /// it is resumed, and then continues from its checkpoints.
+    Paused {
+        progress_message: String,
+        checkpoint_messages: Vec<String>,
+    },
     Completed {
         ts: Timestamp,
         num_rows_written: i64,
@@ -206,6 +352,10 @@ enum SerializedImportState {
         progress_message: Option<String>,
         checkpoint_messages: Vec<String>,
     },
+    Paused {
+        progress_message: String,
+        checkpoint_messages: Vec<String>,
+    },
     Completed {
         timestamp: i64,
         num_rows_written: i64,
@@ -233,6 +383,13 @@ impl From<ImportState> for SerializedImportState {
                 progress_message: Some(progress_message),
                 checkpoint_messages,
             },
+            ImportState::Paused {
+                progress_message,
+                checkpoint_messages,
+            } => SerializedImportState::Paused {
+                progress_message,
+                checkpoint_messages,
+            },
             ImportState::Completed {
                 ts,
                 num_rows_written,
@@ -267,6 +424,13 @@ impl TryFrom<SerializedImportState> for ImportState {
                 progress_message: progress_message.unwrap_or_else(|| "Importing".to_string()),
                 checkpoint_messages,
             }),
+            SerializedImportState::Paused {
+                progress_message,
+                checkpoint_messages,
+            } => Ok(ImportState::Paused {
+                progress_message,
+                checkpoint_messages,
+            }),
             SerializedImportState::Completed {
                 timestamp,
                 num_rows_written,
@@ -281,6 +445,12 @@ impl TryFrom<SerializedImportState> for ImportState {
     }
 }
 
+impl ImportState {
+    pub fn is_in_progress(&self) -> bool {
+        matches!(self, ImportState::InProgress { .. })
+    }
+}
+
 mod import_state_serde {
     use value::codegen_convex_serialization;
 
@@ -313,6 +483,39 @@ pub struct ImportTableCheckpoint {
     // been imported by a previous attempt, which means we have to start over
     // on any transient errors.
     pub is_missing_id_field: bool,
+
+    // How the rows written so far were applied, for upsert imports. This is
+    // updated in the same transaction as the writes, so `num_rows_written` is
+    // exact for these tables.
+    pub upsert_stats: Option<ImportUpsertStats>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ImportUpsertStats {
+    /// Rows that didn't match an existing document.
+    pub num_inserted: i64,
+    /// Rows that matched an existing document and changed it.
+    pub num_updated: i64,
+    /// Rows that matched an existing document that already had their values.
+    pub num_unchanged: i64,
+    /// Rows that matched more than one existing document, which were skipped.
+    pub num_conflicts: i64,
+}
+
+impl ImportUpsertStats {
+    pub fn num_rows(&self) -> i64 {
+        self.num_inserted + self.num_updated + self.num_unchanged + self.num_conflicts
+    }
+}
+
+impl AddAssign for ImportUpsertStats {
+    fn add_assign(&mut self, other: Self) {
+        self.num_inserted += other.num_inserted;
+        self.num_updated += other.num_updated;
+        self.num_unchanged += other.num_unchanged;
+        self.num_conflicts += other.num_conflicts;
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -325,6 +528,7 @@ pub struct SerializedImportTableCheckpoint {
     pub existing_rows_in_table: i64,
     pub existing_rows_to_delete: i64,
     pub is_missing_id_field: bool,
+    pub upsert_stats: Option<ImportUpsertStats>,
 }
 
 impl From<ImportTableCheckpoint> for SerializedImportTableCheckpoint {
@@ -338,6 +542,7 @@ impl From<ImportTableCheckpoint> for SerializedImportTableCheckpoint {
             existing_rows_in_table: checkpoint.existing_rows_in_table,
             existing_rows_to_delete: checkpoint.existing_rows_to_delete,
             is_missing_id_field: checkpoint.is_missing_id_field,
+            upsert_stats: checkpoint.upsert_stats,
         }
     }
 }
@@ -358,6 +563,7 @@ impl TryFrom<SerializedImportTableCheckpoint> for ImportTableCheckpoint {
             existing_rows_in_table: checkpoint.existing_rows_in_table,
             existing_rows_to_delete: checkpoint.existing_rows_to_delete,
             is_missing_id_field: checkpoint.is_missing_id_field,
+            upsert_stats: checkpoint.upsert_stats,
         })
     }
 }
@@ -373,6 +579,13 @@ pub enum ImportMode {
     ReplaceAll,
     #[default]
     RequireEmpty,
+    /// Patch the existing document whose upsert key matches each imported
+    /// document, and insert the documents that don't match any.
+    Upsert,
+    /// Like `ReplaceAll`, but fails before writing anything unless every
+    /// document keeps its `_id`, so references between documents survive
+    /// cloning a deployment.
+    Clone,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
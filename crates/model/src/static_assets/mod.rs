@@ -0,0 +1,233 @@
+//! Static assets pushed along with the app's functions, like a small frontend
+//! build. Each file is stored in file storage and served from the HTTP
+//! actions host, with storage GC cleaning up the objects of files that later
+//! pushes replace.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        LazyLock,
+    },
+};
+
+use anyhow::Context;
+use bytes::Bytes;
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexName,
+        ObjectKey,
+    },
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use storage::{
+    Storage,
+    StorageExt,
+    StorageGetStream,
+    Upload,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::StaticAsset;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static STATIC_ASSETS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_static_assets"
+        .parse()
+        .expect("Invalid built-in static assets table")
+});
+
+pub static STATIC_ASSETS_BY_PATH: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&STATIC_ASSETS_TABLE, "by_path"));
+pub static STATIC_ASSETS_BY_SPA_FALLBACK: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&STATIC_ASSETS_TABLE, "by_spa_fallback"));
+
+static PATH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "path".parse().expect("invalid path field"));
+static IS_SPA_FALLBACK_FIELD: LazyLock<FieldPath> = LazyLock::new(|| {
+    "isSpaFallback"
+        .parse()
+        .expect("invalid isSpaFallback field")
+});
+
+pub struct StaticAssetsTable;
+impl SystemTable for StaticAssetsTable {
+    fn table_name(&self) -> &'static TableName {
+        &STATIC_ASSETS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: STATIC_ASSETS_BY_PATH.clone(),
+                fields: vec![PATH_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+            SystemIndex {
+                name: STATIC_ASSETS_BY_SPA_FALLBACK.clone(),
+                fields: vec![
+                    IS_SPA_FALLBACK_FIELD.clone(),
+                    CREATION_TIME_FIELD_PATH.clone(),
+                ]
+                .try_into()
+                .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<StaticAsset>::try_from(document).map(|_| ())
+    }
+}
+
+/// Upload a static asset's contents. Nothing references the object until
+/// the push commits, so objects from failed pushes are left for storage GC.
+pub async fn upload_static_asset(
+    storage: &Arc<dyn Storage>,
+    content: Bytes,
+) -> anyhow::Result<ObjectKey> {
+    let mut upload = storage.start_upload().await?;
+    upload.write(content).await?;
+    upload.complete().await
+}
+
+pub async fn download_static_asset(
+    storage: &Arc<dyn Storage>,
+    asset: &StaticAsset,
+) -> anyhow::Result<StorageGetStream> {
+    storage
+        .get(&asset.storage_key)
+        .await?
+        .with_context(|| format!("Static asset {} not found in storage", asset.path))
+}
+
+pub struct StaticAssetsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> StaticAssetsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    async fn query(
+        &mut self,
+        index_name: &IndexName,
+        range: Vec<IndexRangeExpression>,
+    ) -> anyhow::Result<Vec<ParsedDocument<StaticAsset>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: index_name.clone(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut assets = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            assets.push(ParsedDocument::try_from(document)?);
+        }
+        Ok(assets)
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<StaticAsset>>> {
+        self.query(&STATIC_ASSETS_BY_PATH, vec![]).await
+    }
+
+    pub async fn get(&mut self, path: &str) -> anyhow::Result<Option<StaticAsset>> {
+        let range = vec![IndexRangeExpression::Eq(
+            PATH_FIELD.clone(),
+            ConvexValue::String(path.to_string().try_into()?).into(),
+        )];
+        Ok(self
+            .query(&STATIC_ASSETS_BY_PATH, range)
+            .await?
+            .into_iter()
+            .next()
+            .map(ParsedDocument::into_value))
+    }
+
+    /// The asset to serve for a request path: the file at that path, or the
+    /// `index.html` in the directory it names.
+    pub async fn resolve(&mut self, request_path: &str) -> anyhow::Result<Option<StaticAsset>> {
+        if let Some(asset) = self.get(request_path).await? {
+            return Ok(Some(asset));
+        }
+        let index_path = format!("{}/index.html", request_path.trim_end_matches('/'));
+        self.get(&index_path).await
+    }
+
+    pub async fn spa_fallback(&mut self) -> anyhow::Result<Option<StaticAsset>> {
+        let range = vec![IndexRangeExpression::Eq(
+            IS_SPA_FALLBACK_FIELD.clone(),
+            ConvexValue::Boolean(true).into(),
+        )];
+        Ok(self
+            .query(&STATIC_ASSETS_BY_SPA_FALLBACK, range)
+            .await?
+            .into_iter()
+            .next()
+            .map(ParsedDocument::into_value))
+    }
+
+    /// Replace the deployment's static assets with the ones from a push.
+    pub async fn replace_all(&mut self, assets: Vec<StaticAsset>) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("replace_static_assets"));
+        }
+        let mut existing: BTreeMap<_, _> = self
+            .list()
+            .await?
+            .into_iter()
+            .map(|document| (document.path.clone(), document))
+            .collect();
+        for asset in assets {
+            match existing.remove(&asset.path) {
+                Some(document) if *document == asset => {},
+                Some(document) => {
+                    SystemMetadataModel::new_global(self.tx)
+                        .replace(document.id(), asset.try_into()?)
+                        .await?;
+                },
+                None => {
+                    SystemMetadataModel::new_global(self.tx)
+                        .insert(&STATIC_ASSETS_TABLE, asset.try_into()?)
+                        .await?;
+                },
+            }
+        }
+        for document in existing.into_values() {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(document.id())
+                .await?;
+        }
+        Ok(())
+    }
+}
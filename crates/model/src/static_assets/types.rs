@@ -0,0 +1,208 @@
+use bytes::Bytes;
+use common::types::ObjectKey;
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    sha256::Sha256Digest,
+};
+
+/// Longest path a static asset may be served from.
+pub const MAX_STATIC_ASSET_PATH_LEN: usize = 1024;
+
+/// A file from the app's static assets directory, as stored by a push.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct StaticAsset {
+    /// The URL path the asset is served from, like `/assets/index.js`.
+    pub path: String,
+    pub storage_key: ObjectKey,
+    pub sha256: Sha256Digest,
+    pub size: i64,
+    pub content_type: String,
+    pub cache_control: String,
+    /// Served for navigation requests that don't match an asset or an HTTP
+    /// action route, so single page apps can route on the client.
+    pub is_spa_fallback: bool,
+}
+
+impl StaticAsset {
+    /// A strong ETag for the asset's contents.
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.sha256.as_hex())
+    }
+}
+
+/// The static assets included in a push.
+#[derive(Clone, Debug, Default)]
+pub struct StaticAssetsConfig {
+    pub files: Vec<StaticAssetFile>,
+    /// Path of the file to serve when nothing else matches a navigation
+    /// request.
+    pub spa_fallback: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct StaticAssetFile {
+    pub path: String,
+    pub content: Bytes,
+    /// Guessed from the path's extension if not set.
+    pub content_type: Option<String>,
+    /// Defaults to revalidating HTML on every request and caching everything
+    /// else for an hour.
+    pub cache_control: Option<String>,
+}
+
+impl StaticAssetFile {
+    pub fn content_type(&self) -> String {
+        self.content_type
+            .clone()
+            .unwrap_or_else(|| content_type_for_path(&self.path).to_string())
+    }
+
+    pub fn cache_control(&self) -> String {
+        self.cache_control.clone().unwrap_or_else(|| {
+            if self.content_type().starts_with("text/html") {
+                "public, max-age=0, must-revalidate".to_string()
+            } else {
+                "public, max-age=3600".to_string()
+            }
+        })
+    }
+}
+
+/// Check that a static asset path is absolute, normalized and names a file.
+pub fn validate_static_asset_path(path: &str) -> anyhow::Result<()> {
+    let valid = path.len() <= MAX_STATIC_ASSET_PATH_LEN
+        && path.starts_with('/')
+        && !path.ends_with('/')
+        && !path.contains(['?', '#', '\\'])
+        && path[1..]
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+    anyhow::ensure!(
+        valid,
+        ErrorMetadata::bad_request(
+            "InvalidStaticAssetPath",
+            format!(
+                "Static asset path {path:?} must start with `/`, name a file and be at most \
+                 {MAX_STATIC_ASSET_PATH_LEN} characters"
+            ),
+        )
+    );
+    Ok(())
+}
+
+/// The `Content-Type` for a file, going by its extension.
+pub fn content_type_for_path(path: &str) -> &'static str {
+    let extension = path
+        .rsplit_once('.')
+        .filter(|(_, extension)| !extension.contains('/'))
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("html" | "htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js" | "mjs") => "text/javascript; charset=utf-8",
+        Some("json" | "map") => "application/json",
+        Some("webmanifest") => "application/manifest+json",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("xml") => "application/xml",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("wasm") => "application/wasm",
+        Some("pdf") => "application/pdf",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mp3") => "audio/mpeg",
+        _ => "application/octet-stream",
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedStaticAsset {
+    path: String,
+    storage_key: String,
+    sha256: String,
+    size: i64,
+    content_type: String,
+    cache_control: String,
+    is_spa_fallback: bool,
+}
+
+impl From<StaticAsset> for SerializedStaticAsset {
+    fn from(value: StaticAsset) -> Self {
+        Self {
+            path: value.path,
+            storage_key: value.storage_key.into(),
+            sha256: value.sha256.as_base64(),
+            size: value.size,
+            content_type: value.content_type,
+            cache_control: value.cache_control,
+            is_spa_fallback: value.is_spa_fallback,
+        }
+    }
+}
+
+impl TryFrom<SerializedStaticAsset> for StaticAsset {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedStaticAsset) -> anyhow::Result<Self> {
+        Ok(Self {
+            path: value.path,
+            storage_key: value.storage_key.try_into()?,
+            sha256: Sha256Digest::from_base64(&value.sha256)?,
+            size: value.size,
+            content_type: value.content_type,
+            cache_control: value.cache_control,
+            is_spa_fallback: value.is_spa_fallback,
+        })
+    }
+}
+
+codegen_convex_serialization!(StaticAsset, SerializedStaticAsset);
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        content_type_for_path,
+        validate_static_asset_path,
+    };
+
+    #[test]
+    fn test_validate_static_asset_path() {
+        assert!(validate_static_asset_path("/index.html").is_ok());
+        assert!(validate_static_asset_path("/assets/index-4f2a.js").is_ok());
+        assert!(validate_static_asset_path("index.html").is_err());
+        assert!(validate_static_asset_path("/").is_err());
+        assert!(validate_static_asset_path("/assets/").is_err());
+        assert!(validate_static_asset_path("/assets//index.js").is_err());
+        assert!(validate_static_asset_path("/assets/../secret").is_err());
+        assert!(validate_static_asset_path("/index.html?v=1").is_err());
+    }
+
+    #[test]
+    fn test_content_type_for_path() {
+        assert_eq!(
+            content_type_for_path("/index.HTML"),
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(content_type_for_path("/logo.svg"), "image/svg+xml");
+        assert_eq!(
+            content_type_for_path("/v1.2/LICENSE"),
+            "application/octet-stream"
+        );
+    }
+}
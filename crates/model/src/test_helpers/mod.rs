@@ -1,11 +1,23 @@
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
-use common::runtime::Runtime;
-use database::test_helpers::{
-    DbFixtures,
-    DbFixturesArgs,
+use common::{
+    bootstrap_model::components::definition::ComponentExport,
+    components::ComponentId,
+    runtime::Runtime,
+};
+use database::{
+    test_helpers::{
+        DbFixtures,
+        DbFixturesArgs,
+    },
+    ComponentExportsLoader,
+    Transaction,
 };
+use sync_types::path::PathComponent;
 
 use crate::{
+    components::ComponentsModel,
     initialize_application_system_tables,
     virtual_system_mapping,
 };
@@ -35,3 +47,19 @@ impl<RT: Runtime> DbFixturesWithModel<RT> for DbFixtures<RT> {
         Ok(fixture)
     }
 }
+
+/// Loads a component's exports from the public functions in its modules.
+pub struct FileBasedExportsLoader;
+
+#[async_trait]
+impl<RT: Runtime> ComponentExportsLoader<RT> for FileBasedExportsLoader {
+    async fn load_exports(
+        &self,
+        tx: &mut Transaction<RT>,
+        component: ComponentId,
+    ) -> anyhow::Result<BTreeMap<PathComponent, ComponentExport>> {
+        ComponentsModel::new(tx)
+            .load_component_exports(component)
+            .await
+    }
+}
@@ -0,0 +1,217 @@
+//! Blue/green deploys: while a component has a traffic split, each query and
+//! mutation call is pinned to either its stable or its candidate version.
+
+use std::sync::LazyLock;
+
+use common::{
+    components::{
+        ComponentId,
+        ResolvedComponentFunctionPath,
+    },
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use serde_json::Value as JsonValue;
+use value::{
+    sha256::Sha256,
+    ConvexArray,
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    TrafficSplit,
+    TrafficSplitAssignment,
+    TrafficSplitVariant,
+};
+use crate::{
+    component_versions::ComponentVersionsModel,
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static TRAFFIC_SPLITS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_traffic_splits"
+        .parse()
+        .expect("Invalid built-in traffic splits table")
+});
+
+pub static TRAFFIC_SPLITS_BY_COMPONENT: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&TRAFFIC_SPLITS_TABLE, "by_component"));
+
+static COMPONENT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "component".parse().expect("invalid component field"));
+
+pub struct TrafficSplitsTable;
+impl SystemTable for TrafficSplitsTable {
+    fn table_name(&self) -> &'static TableName {
+        &TRAFFIC_SPLITS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: TRAFFIC_SPLITS_BY_COMPONENT.clone(),
+            fields: vec![COMPONENT_FIELD.clone(), CREATION_TIME_FIELD_PATH.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<TrafficSplit>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct TrafficSplitModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> TrafficSplitModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn get(
+        &mut self,
+        component: ComponentId,
+    ) -> anyhow::Result<Option<ParsedDocument<TrafficSplit>>> {
+        let serialized_component = match component.serialize_to_string() {
+            Some(s) => ConvexValue::String(s.try_into()?),
+            None => ConvexValue::Null,
+        };
+        let query = Query::index_range(IndexRange {
+            index_name: TRAFFIC_SPLITS_BY_COMPONENT.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                COMPONENT_FIELD.clone(),
+                serialized_component.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// Start routing traffic between two recorded versions of a component,
+    /// replacing any existing split for it.
+    pub async fn set(&mut self, split: TrafficSplit) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("set_traffic_split"));
+        }
+        if split.stable_version == split.candidate_version {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTrafficSplit",
+                "The stable and candidate versions must be different",
+            ));
+        }
+        if let Some(percent) = std::iter::once(&split.default_percent)
+            .chain(split.function_percents.values())
+            .find(|percent| **percent > 100)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTrafficSplit",
+                format!("Traffic percentages must be between 0 and 100, got {percent}"),
+            ));
+        }
+        for version in [split.stable_version, split.candidate_version] {
+            if ComponentVersionsModel::new(self.tx)
+                .get(split.component, version)
+                .await?
+                .is_none()
+            {
+                anyhow::bail!(ErrorMetadata::not_found(
+                    "ComponentVersionNotFound",
+                    format!(
+                        "Version {version} of component {:?} not found",
+                        split.component
+                    ),
+                ));
+            }
+        }
+        match self.get(split.component).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), split.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&TRAFFIC_SPLITS_TABLE, split.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Stop splitting traffic for the component, returning the removed split.
+    pub async fn clear(&mut self, component: ComponentId) -> anyhow::Result<Option<TrafficSplit>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("clear_traffic_split"));
+        }
+        let Some(existing) = self.get(component).await? else {
+            return Ok(None);
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(Some(existing.into_value()))
+    }
+
+    /// Pick the version a call should run against. Calls are bucketed by a
+    /// hash of their path and arguments rather than at random, so a query
+    /// with the same arguments always sees the same version and its cached
+    /// results and subscriptions stay consistent.
+    pub async fn assign(
+        &mut self,
+        path: &ResolvedComponentFunctionPath,
+        args: &ConvexArray,
+    ) -> anyhow::Result<Option<TrafficSplitAssignment>> {
+        let Some(split) = self.get(path.component).await? else {
+            return Ok(None);
+        };
+        let percent = split.candidate_percent(&path.udf_path);
+        let mut hasher = Sha256::new();
+        hasher.update(path.udf_path.to_string().as_bytes());
+        hasher.update(&serde_json::to_vec(&JsonValue::from(args.clone()))?);
+        let digest = hasher.finalize();
+        let bucket = u64::from_le_bytes(digest[..8].try_into()?) % 100;
+        let assignment = if bucket < u64::from(percent) {
+            TrafficSplitAssignment {
+                version: split.candidate_version,
+                variant: TrafficSplitVariant::Candidate,
+            }
+        } else {
+            TrafficSplitAssignment {
+                version: split.stable_version,
+                variant: TrafficSplitVariant::Stable,
+            }
+        };
+        Ok(Some(assignment))
+    }
+}
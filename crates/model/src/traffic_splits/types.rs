@@ -0,0 +1,139 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+};
+
+use common::components::ComponentId;
+#[cfg(any(test, feature = "testing"))]
+use proptest::prelude::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sync_types::CanonicalizedUdfPath;
+use value::codegen_convex_serialization;
+
+/// Routes a percentage of a component's query and mutation traffic to a
+/// candidate version while the rest keeps running the stable version. Both are
+/// versions recorded in `_component_versions`.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    any(test, feature = "testing"),
+    derive(proptest_derive::Arbitrary, PartialEq)
+)]
+pub struct TrafficSplit {
+    pub component: ComponentId,
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0..1_000_000u64"))]
+    pub stable_version: u64,
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0..1_000_000u64"))]
+    pub candidate_version: u64,
+    /// Percentage of calls routed to the candidate for functions without an
+    /// entry in `function_percents`.
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0..=100u8"))]
+    pub default_percent: u8,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "prop::collection::btree_map(any::<CanonicalizedUdfPath>(), 0..=100u8, \
+                        0..4)"
+        )
+    )]
+    pub function_percents: BTreeMap<CanonicalizedUdfPath, u8>,
+}
+
+impl TrafficSplit {
+    pub fn candidate_percent(&self, udf_path: &CanonicalizedUdfPath) -> u8 {
+        self.function_percents
+            .get(udf_path)
+            .copied()
+            .unwrap_or(self.default_percent)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum TrafficSplitVariant {
+    Stable,
+    Candidate,
+}
+
+impl TrafficSplitVariant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrafficSplitVariant::Stable => "stable",
+            TrafficSplitVariant::Candidate => "candidate",
+        }
+    }
+}
+
+impl fmt::Display for TrafficSplitVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The component version a single function call was routed to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TrafficSplitAssignment {
+    pub version: u64,
+    pub variant: TrafficSplitVariant,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFunctionPercent {
+    udf_path: String,
+    percent: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedTrafficSplit {
+    component: Option<String>,
+    stable_version: i64,
+    candidate_version: i64,
+    default_percent: i64,
+    function_percents: Vec<SerializedFunctionPercent>,
+}
+
+impl TryFrom<TrafficSplit> for SerializedTrafficSplit {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TrafficSplit) -> anyhow::Result<Self> {
+        Ok(Self {
+            component: value.component.serialize_to_string(),
+            stable_version: value.stable_version.try_into()?,
+            candidate_version: value.candidate_version.try_into()?,
+            default_percent: value.default_percent.into(),
+            function_percents: value
+                .function_percents
+                .into_iter()
+                .map(|(udf_path, percent)| SerializedFunctionPercent {
+                    udf_path: udf_path.to_string(),
+                    percent: percent.into(),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<SerializedTrafficSplit> for TrafficSplit {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedTrafficSplit) -> anyhow::Result<Self> {
+        Ok(Self {
+            component: ComponentId::deserialize_from_string(value.component.as_deref())?,
+            stable_version: value.stable_version.try_into()?,
+            candidate_version: value.candidate_version.try_into()?,
+            default_percent: value.default_percent.try_into()?,
+            function_percents: value
+                .function_percents
+                .into_iter()
+                .map(|p| anyhow::Ok((p.udf_path.parse()?, p.percent.try_into()?)))
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(TrafficSplit, SerializedTrafficSplit);
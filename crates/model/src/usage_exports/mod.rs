@@ -0,0 +1,169 @@
+//! Records of usage reports exported to object storage. The reports
+//! themselves live in the exports storage bucket, and this table is how
+//! self-hosters find them.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        CreationTime,
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+use self::types::UsageExport;
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static USAGE_EXPORTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_usage_exports"
+        .parse()
+        .expect("Invalid built-in usage exports table")
+});
+
+pub struct UsageExportsTable;
+impl SystemTable for UsageExportsTable {
+    fn table_name(&self) -> &'static TableName {
+        &USAGE_EXPORTS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<UsageExport>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct UsageExportPage {
+    pub exports: Vec<ParsedDocument<UsageExport>>,
+    /// Pass as the `cursor` of the next call to continue after this page, or
+    /// `None` if there are no more exports.
+    pub cursor: Option<CreationTime>,
+}
+
+pub struct UsageExportModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> UsageExportModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn insert(&mut self, export: UsageExport) -> anyhow::Result<()> {
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&USAGE_EXPORTS_TABLE, export.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn latest(&mut self) -> anyhow::Result<Option<ParsedDocument<UsageExport>>> {
+        Ok(self.list(None, 1).await?.exports.pop())
+    }
+
+    /// Returns up to `limit` exports created before `cursor`, newest first.
+    pub async fn list(
+        &mut self,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<UsageExportPage> {
+        let range = cursor
+            .map(|cursor| {
+                IndexRangeExpression::Lt(CREATION_TIME_FIELD_PATH.clone(), f64::from(cursor).into())
+            })
+            .into_iter()
+            .collect();
+        let query = Query::index_range(IndexRange {
+            index_name: IndexName::by_creation_time(USAGE_EXPORTS_TABLE.clone()),
+            range,
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut exports = vec![];
+        while let Some(doc) = query_stream.next(self.tx, Some(limit)).await? {
+            let creation_time = doc.creation_time();
+            exports.push(doc.try_into()?);
+            if exports.len() >= limit {
+                return Ok(UsageExportPage {
+                    exports,
+                    cursor: creation_time,
+                });
+            }
+        }
+        Ok(UsageExportPage {
+            exports,
+            cursor: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        test_helpers::DbFixturesWithModel,
+        usage_exports::{
+            types::UsageExport,
+            UsageExportModel,
+        },
+    };
+
+    fn export(period_start_ms: u64) -> anyhow::Result<UsageExport> {
+        Ok(UsageExport {
+            period_start_ms,
+            period_end_ms: period_start_ms + 1000,
+            object_key: format!("usage-{period_start_ms}").try_into()?,
+            size_bytes: 10,
+        })
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_list_usage_exports(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = UsageExportModel::new(&mut tx);
+        assert!(model.latest().await?.is_none());
+        for period_start_ms in [0, 1000, 2000] {
+            model.insert(export(period_start_ms)?).await?;
+        }
+        assert_eq!(
+            model.latest().await?.map(|e| e.into_value()),
+            Some(export(2000)?)
+        );
+        let page = model.list(None, 2).await?;
+        assert_eq!(page.exports.len(), 2);
+        assert_eq!(page.exports[1].period_start_ms, 1000);
+        let page = model.list(page.cursor, 2).await?;
+        assert_eq!(page.exports.len(), 1);
+        assert_eq!(page.exports[0].period_start_ms, 0);
+        assert!(page.cursor.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use common::types::ObjectKey;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A usage report uploaded to the exports storage bucket. The report covers
+/// usage from `period_start_ms` (inclusive) to `period_end_ms` (exclusive).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct UsageExport {
+    pub period_start_ms: u64,
+    pub period_end_ms: u64,
+    pub object_key: ObjectKey,
+    pub size_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedUsageExport {
+    period_start_ms: i64,
+    period_end_ms: i64,
+    object_key: String,
+    size_bytes: i64,
+}
+
+impl From<UsageExport> for SerializedUsageExport {
+    fn from(export: UsageExport) -> Self {
+        Self {
+            period_start_ms: export.period_start_ms as i64,
+            period_end_ms: export.period_end_ms as i64,
+            object_key: export.object_key.to_string(),
+            size_bytes: export.size_bytes as i64,
+        }
+    }
+}
+
+impl TryFrom<SerializedUsageExport> for UsageExport {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SerializedUsageExport) -> anyhow::Result<Self> {
+        Ok(Self {
+            period_start_ms: value.period_start_ms as u64,
+            period_end_ms: value.period_end_ms as u64,
+            object_key: value.object_key.try_into()?,
+            size_bytes: value.size_bytes as u64,
+        })
+    }
+}
+
+codegen_convex_serialization!(UsageExport, SerializedUsageExport);
@@ -1,5 +1,7 @@
 use std::{
+    num::NonZeroUsize,
     str::FromStr,
+    sync::Arc,
     time::Duration,
 };
 
@@ -12,8 +14,12 @@ use common::{
         MYSQL_TIMEOUT,
     },
     pool_stats::{
+        ConnectionPoolPartitions,
+        ConnectionPoolSnapshot,
         ConnectionPoolStats,
         ConnectionTracker,
+        PartitionPermit,
+        PoolWorkload,
     },
     runtime::Runtime,
 };
@@ -228,6 +234,9 @@ pub(crate) struct MySqlConnection<'a> {
     labels: Vec<StaticMetricLabel>,
     use_prepared_statements: bool,
     db_name: &'a str,
+    // Dropped after `conn`, so the connection is back in the pool by the time
+    // the workload's share is released.
+    _permit: PartitionPermit,
     _tracker: ConnectionTracker,
     _timer: Timer<VMHistogramVec>,
 }
@@ -435,6 +444,7 @@ pub struct ConvexMySqlPool<RT: Runtime> {
     use_prepared_statements: bool,
     runtime: Option<RT>,
     stats: ConnectionPoolStats,
+    partitions: Arc<ConnectionPoolPartitions>,
     // Used for metrics
     cluster_name: String,
 }
@@ -480,6 +490,7 @@ impl<RT: Runtime> ConvexMySqlPool<RT> {
             use_prepared_statements,
             runtime,
             stats: new_connection_pool_stats(cluster_name.as_str()),
+            partitions: ConnectionPoolPartitions::new(),
             cluster_name,
         })
     }
@@ -488,19 +499,29 @@ impl<RT: Runtime> ConvexMySqlPool<RT> {
     pub(crate) async fn acquire<'a>(
         &self,
         name: &'static str,
+        workload: PoolWorkload,
         db_name: &'a str,
     ) -> anyhow::Result<MySqlConnection<'a>> {
         let pool_get_timer = get_connection_timer(&self.cluster_name);
-        let conn = with_timeout(self.pool.get_conn()).await;
-        pool_get_timer.finish(conn.is_ok());
+        let result = self
+            .partitions
+            .acquire(
+                workload,
+                Duration::from_secs(*MYSQL_TIMEOUT),
+                with_timeout(self.pool.get_conn()),
+            )
+            .await;
+        pool_get_timer.finish(result.is_ok());
+        let (conn, permit) = result?;
         Ok(MySqlConnection {
-            conn: conn?,
+            conn,
             labels: vec![
                 StaticMetricLabel::new("name", name),
                 StaticMetricLabel::new("cluster_name", self.cluster_name.clone()),
             ],
             use_prepared_statements: self.use_prepared_statements,
             db_name,
+            _permit: permit,
             _tracker: ConnectionTracker::new(&self.stats),
             _timer: connection_lifetime_timer(name, &self.cluster_name),
         })
@@ -510,6 +531,19 @@ impl<RT: Runtime> ConvexMySqlPool<RT> {
         &self.cluster_name
     }
 
+    pub(crate) fn connection_pool_stats(&self) -> ConnectionPoolSnapshot {
+        // mysql_async doesn't report how many connections are idle.
+        self.partitions.snapshot(*MYSQL_MAX_CONNECTIONS, None)
+    }
+
+    pub(crate) fn set_connection_pool_limit(
+        &self,
+        workload: PoolWorkload,
+        limit: Option<NonZeroUsize>,
+    ) -> anyhow::Result<()> {
+        self.partitions.set_limit(workload, limit)
+    }
+
     pub async fn shutdown(&self) -> anyhow::Result<()> {
         tracing::info!("Shutting down ConvexMySqlPool");
         Ok(self.pool.clone().disconnect().await?)
@@ -20,6 +20,7 @@ use std::{
     },
     fmt::Write,
     future::Future,
+    num::NonZeroUsize,
     ops::Bound,
     pin::Pin,
     sync::{
@@ -78,6 +79,10 @@ use common::{
         RetentionValidator,
         TimestampRange,
     },
+    pool_stats::{
+        ConnectionPoolSnapshot,
+        PoolWorkload,
+    },
     query::Order,
     runtime::Runtime,
     sha256::Sha256,
@@ -165,7 +170,9 @@ impl<RT: Runtime> MySqlPersistence<RT> {
         lease_lost_shutdown: ShutdownSignal,
     ) -> Result<Self, ConnectError> {
         let newly_created = {
-            let mut client = pool.acquire("init_sql", &db_name).await?;
+            let mut client = pool
+                .acquire("init_sql", PoolWorkload::Commit, &db_name)
+                .await?;
             let table_count: usize = client
                 .query_optional(GET_TABLE_COUNT, vec![])
                 .await
@@ -187,7 +194,9 @@ impl<RT: Runtime> MySqlPersistence<RT> {
             }
             Self::check_newly_created(&mut client).await?
         };
-        let mut client = pool.acquire("read_only", &db_name).await?;
+        let mut client = pool
+            .acquire("read_only", PoolWorkload::Commit, &db_name)
+            .await?;
         if !options.allow_read_only && Self::is_read_only(&mut client).await? {
             return Err(ConnectError::ReadOnly);
         }
@@ -233,7 +242,7 @@ impl<RT: Runtime> MySqlPersistence<RT> {
     pub(crate) async fn get_table_count(&self) -> anyhow::Result<usize> {
         let mut client = self
             .read_pool
-            .acquire("get_table_count", &self.db_name)
+            .acquire("get_table_count", PoolWorkload::Read, &self.db_name)
             .await?;
         client
             .query_optional(GET_TABLE_COUNT, vec![])
@@ -297,7 +306,7 @@ impl<RT: Runtime> Persistence for MySqlPersistence<RT> {
         self.newly_created.store(false, SeqCst);
         let cluster_name = self.read_pool.cluster_name().to_owned();
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Commit, move |tx| {
                 async move {
                     {
                         // First, process all of the full document chunks.
@@ -403,7 +412,7 @@ impl<RT: Runtime> Persistence for MySqlPersistence<RT> {
 
     async fn set_read_only(&self, read_only: bool) -> anyhow::Result<()> {
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Commit, move |tx| {
                 async move {
                     let statement = if read_only {
                         SET_READ_ONLY
@@ -425,7 +434,7 @@ impl<RT: Runtime> Persistence for MySqlPersistence<RT> {
     ) -> anyhow::Result<()> {
         let timer = write_persistence_global_timer(self.read_pool.cluster_name());
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Commit, move |tx| {
                 async move {
                     let stmt = WRITE_PERSISTENCE_GLOBAL;
                     let params = vec![String::from(key).into(), value.into()];
@@ -446,7 +455,7 @@ impl<RT: Runtime> Persistence for MySqlPersistence<RT> {
     ) -> anyhow::Result<Vec<IndexEntry>> {
         let mut client = self
             .read_pool
-            .acquire("load_index_chunk", &self.db_name)
+            .acquire("load_index_chunk", PoolWorkload::Background, &self.db_name)
             .await?;
         let stmt = LOAD_INDEXES_PAGE;
         let mut params = MySqlReader::<RT>::_index_cursor_params(cursor.as_ref());
@@ -462,7 +471,7 @@ impl<RT: Runtime> Persistence for MySqlPersistence<RT> {
         expired_entries: Vec<IndexEntry>,
     ) -> anyhow::Result<usize> {
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Background, move |tx| {
                 async move {
                     let mut deleted_count = 0;
                     for chunk in smart_chunks(&expired_entries) {
@@ -486,7 +495,7 @@ impl<RT: Runtime> Persistence for MySqlPersistence<RT> {
         documents: Vec<(Timestamp, InternalDocumentId)>,
     ) -> anyhow::Result<usize> {
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Background, move |tx| {
                 async move {
                     let mut deleted_count = 0;
                     for chunk in smart_chunks(&documents) {
@@ -592,7 +601,7 @@ impl<RT: Runtime> MySqlReader<RT> {
         let timer = metrics::load_documents_timer(self.read_pool.cluster_name());
         let mut client = self
             .read_pool
-            .acquire("load_documents", &self.db_name)
+            .acquire("load_documents", PoolWorkload::Read, &self.db_name)
             .await?;
         let mut num_returned = 0;
         let mut num_skipped_by_table = 0;
@@ -733,7 +742,10 @@ impl<RT: Runtime> MySqlReader<RT> {
                 let mut to_yield = vec![];
                 // Avoid holding connections across yield points, to limit lifetime
                 // and improve fairness.
-                let mut client = self.read_pool.acquire("index_scan", &self.db_name).await?;
+                let mut client = self
+                    .read_pool
+                    .acquire("index_scan", PoolWorkload::Read, &self.db_name)
+                    .await?;
                 stats.sql_statements += 1;
                 let (query, params) = index_query(
                     index_id,
@@ -983,7 +995,7 @@ impl<RT: Runtime> PersistenceReader for MySqlReader<RT> {
 
         let mut client = self
             .read_pool
-            .acquire("previous_revisions", &self.db_name)
+            .acquire("previous_revisions", PoolWorkload::Read, &self.db_name)
             .await?;
         let ids: Vec<_> = ids.into_iter().collect();
 
@@ -1062,7 +1074,7 @@ impl<RT: Runtime> PersistenceReader for MySqlReader<RT> {
     ) -> anyhow::Result<Option<JsonValue>> {
         let mut client = self
             .read_pool
-            .acquire("get_persistence_global", &self.db_name)
+            .acquire("get_persistence_global", PoolWorkload::Read, &self.db_name)
             .await?;
         let params = vec![String::from(key).into()];
         let row_stream = client
@@ -1085,7 +1097,7 @@ impl<RT: Runtime> PersistenceReader for MySqlReader<RT> {
     async fn table_size_stats(&self) -> anyhow::Result<Vec<PersistenceTableSize>> {
         let mut client = self
             .read_pool
-            .acquire("table_size_stats", &self.db_name)
+            .acquire("table_size_stats", PoolWorkload::Read, &self.db_name)
             .await?;
         let stats = client
             .query_stream(TABLE_SIZE_QUERY, vec![self.db_name.clone().into()], 5)
@@ -1103,6 +1115,18 @@ impl<RT: Runtime> PersistenceReader for MySqlReader<RT> {
             .await?;
         Ok(stats)
     }
+
+    fn connection_pool_stats(&self) -> Option<ConnectionPoolSnapshot> {
+        Some(self.read_pool.connection_pool_stats())
+    }
+
+    fn set_connection_pool_limit(
+        &self,
+        workload: PoolWorkload,
+        limit: Option<NonZeroUsize>,
+    ) -> anyhow::Result<()> {
+        self.read_pool.set_connection_pool_limit(workload, limit)
+    }
 }
 
 /// A `Lease` is unique for an instance across all of the processes in the
@@ -1133,7 +1157,9 @@ impl<RT: Runtime> Lease<RT> {
         lease_lost_shutdown: ShutdownSignal,
     ) -> anyhow::Result<Self> {
         let timer = metrics::lease_acquire_timer(pool.cluster_name());
-        let mut client = pool.acquire("lease_acquire", &db_name).await?;
+        let mut client = pool
+            .acquire("lease_acquire", PoolWorkload::Commit, &db_name)
+            .await?;
         let ts = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("before 1970")
@@ -1166,14 +1192,19 @@ impl<RT: Runtime> Lease<RT> {
     /// and any in-memory state then resynced because of any changes that
     /// might've been made to the database state while the lease was not
     /// held.
+    ///
+    /// The connection counts against `workload`'s share of the pool.
     #[fastrace::trace]
-    async fn transact<F, T>(&self, f: F) -> anyhow::Result<T>
+    async fn transact<F, T>(&self, workload: PoolWorkload, f: F) -> anyhow::Result<T>
     where
         F: for<'b> FnOnce(
             &'b mut MySqlTransaction<'_>,
         ) -> Pin<Box<dyn Future<Output = anyhow::Result<T>> + Send + 'b>>,
     {
-        let mut client = self.pool.acquire("transact", &self.db_name).await?;
+        let mut client = self
+            .pool
+            .acquire("transact", workload, &self.db_name)
+            .await?;
         let mut tx = client.transaction(&self.db_name).await?;
 
         let timer = metrics::lease_precond_timer(self.pool.cluster_name());
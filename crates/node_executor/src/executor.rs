@@ -60,6 +60,7 @@ use model::{
             Visibility,
         },
     },
+    outbound_network_policy::types::OutboundNetworkPolicy,
     source_packages::types::{
         PackageSize,
         SourcePackageId,
@@ -570,6 +571,9 @@ impl TryFrom<ExecutorRequest> for JsonValue {
                     "authHeader": r.auth_header,
                     "userIdentity": r.user_identity.map(JsonValue::try_from).transpose()?,
                     "environmentVariables": JsonValue::Array(environment_variables),
+                    "outboundNetworkPolicy": JsonValue::from(
+                        ConvexObject::try_from(r.outbound_network_policy)?
+                    ),
                     "npmVersion": npm_version.map(|v| v.to_string()),
                     "executionContext": JsonValue::from(r.context),
                     "encodedParentTrace": JsonValue::from(r.encoded_parent_trace),
@@ -666,6 +670,7 @@ pub struct ExecuteRequest {
     pub user_identity: Option<UserIdentityAttributes>,
     pub auth_header: Option<String>,
     pub environment_variables: BTreeMap<EnvVarName, EnvVarValue>,
+    pub outbound_network_policy: OutboundNetworkPolicy,
 
     pub callback_token: ActionCallbackToken,
     pub context: ExecutionContext,
@@ -303,6 +303,7 @@ mod tests {
             user_identity: None,
             auth_header: None,
             environment_variables: btreemap! {},
+            outbound_network_policy: Default::default(),
             callback_token: "".to_owned(),
             context: ExecutionContext::new_for_test(),
             encoded_parent_trace: EncodedSpan::from_parent().0,
@@ -418,6 +419,7 @@ mod tests {
                 user_identity: Some(identity.attributes.clone()),
                 auth_header: None,
                 environment_variables: btreemap! {},
+                outbound_network_policy: Default::default(),
                 callback_token: "".to_owned(),
                 context: ExecutionContext::new_for_test(),
                 encoded_parent_trace: None,
@@ -642,6 +644,7 @@ mod tests {
                 user_identity: None,
                 auth_header: None,
                 environment_variables,
+                outbound_network_policy: Default::default(),
                 callback_token: "".to_owned(),
                 context: ExecutionContext::new_for_test(),
                 encoded_parent_trace: None,
@@ -21,6 +21,7 @@ impl From<ErrorCode> for ErrorCodeProto {
             ErrorCode::Unauthenticated => ErrorCodeProto::Unauthenticated,
             ErrorCode::Forbidden => ErrorCodeProto::Forbidden,
             ErrorCode::NotFound => ErrorCodeProto::TransientNotFound,
+            ErrorCode::Conflict => ErrorCodeProto::Conflict,
             ErrorCode::ClientDisconnect => ErrorCodeProto::ClientDisconnect,
             ErrorCode::RateLimited => ErrorCodeProto::RateLimited,
             ErrorCode::Overloaded => ErrorCodeProto::Overloaded,
@@ -43,6 +44,7 @@ impl ErrorCodeProto {
             ErrorCodeProto::Unauthenticated => ErrorCode::Unauthenticated,
             ErrorCodeProto::Forbidden => ErrorCode::Forbidden,
             ErrorCodeProto::TransientNotFound => ErrorCode::NotFound,
+            ErrorCodeProto::Conflict => ErrorCode::Conflict,
             ErrorCodeProto::ClientDisconnect => ErrorCode::ClientDisconnect,
             ErrorCodeProto::RateLimited => ErrorCode::RateLimited,
             ErrorCodeProto::Overloaded => ErrorCode::Overloaded,
@@ -1,13 +1,24 @@
 use std::{
-    sync::LazyLock,
+    num::NonZeroUsize,
+    sync::{
+        Arc,
+        LazyLock,
+    },
     time::Duration,
 };
 
 use ::metrics::StaticMetricLabel;
 use cmd_util::env::env_config;
-use common::pool_stats::{
-    ConnectionPoolStats,
-    ConnectionTracker,
+use common::{
+    metrics::log_persistence_pool_idle,
+    pool_stats::{
+        ConnectionPoolPartitions,
+        ConnectionPoolSnapshot,
+        ConnectionPoolStats,
+        ConnectionTracker,
+        PartitionPermit,
+        PoolWorkload,
+    },
 };
 use deadpool_postgres::{
     Status,
@@ -76,6 +87,9 @@ where
 pub(crate) struct PostgresConnection {
     conn: deadpool_postgres::Object,
     labels: Vec<StaticMetricLabel>,
+    // Dropped after `conn`, so the connection is back in the pool by the time
+    // the workload's share is released.
+    _permit: PartitionPermit,
     _tracker: ConnectionTracker,
     _timer: Timer<VMHistogramVec>,
 }
@@ -205,6 +219,7 @@ impl PostgresTransaction<'_> {
 pub struct ConvexPgPool {
     inner: deadpool_postgres::Pool,
     stats: ConnectionPoolStats,
+    partitions: Arc<ConnectionPoolPartitions>,
 }
 
 impl ConvexPgPool {
@@ -212,19 +227,29 @@ impl ConvexPgPool {
         ConvexPgPool {
             inner: pool,
             stats: new_connection_pool_stats(""),
+            partitions: ConnectionPoolPartitions::new(),
         }
     }
 
     pub(crate) async fn get_connection(
         &self,
         name: &'static str,
+        workload: PoolWorkload,
     ) -> anyhow::Result<PostgresConnection> {
         let pool_get_timer = get_connection_timer();
-        let conn = with_timeout(self.inner.get()).await;
-        pool_get_timer.finish(conn.is_ok());
+        let result = self
+            .partitions
+            .acquire(workload, Duration::from_secs(*POSTGRES_TIMEOUT), async {
+                Ok(self.inner.get().await?)
+            })
+            .await;
+        pool_get_timer.finish(result.is_ok());
+        log_persistence_pool_idle(self.inner.status().available);
+        let (conn, permit) = result?;
         Ok(PostgresConnection {
-            conn: conn?,
+            conn,
             labels: vec![StaticMetricLabel::new("name", name)],
+            _permit: permit,
             _tracker: ConnectionTracker::new(&self.stats),
             _timer: connection_lifetime_timer(name),
         })
@@ -233,4 +258,18 @@ impl ConvexPgPool {
     pub(crate) fn status(&self) -> Status {
         self.inner.status()
     }
+
+    pub(crate) fn connection_pool_stats(&self) -> ConnectionPoolSnapshot {
+        let status = self.inner.status();
+        self.partitions
+            .snapshot(status.max_size, Some(status.available))
+    }
+
+    pub(crate) fn set_connection_pool_limit(
+        &self,
+        workload: PoolWorkload,
+        limit: Option<NonZeroUsize>,
+    ) -> anyhow::Result<()> {
+        self.partitions.set_limit(workload, limit)
+    }
 }
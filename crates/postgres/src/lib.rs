@@ -17,6 +17,7 @@ use std::{
     error::Error,
     fmt::Write,
     future::Future,
+    num::NonZeroUsize,
     ops::Bound,
     pin::Pin,
     str::FromStr,
@@ -29,6 +30,7 @@ use std::{
         LazyLock,
     },
     time::{
+        Duration,
         SystemTime,
         UNIX_EPOCH,
     },
@@ -43,7 +45,7 @@ use common::{
         InternalId,
         ResolvedDocument,
     },
-    errors::LeaseLostError,
+    errors::lease_lost_error,
     index::{
         IndexEntry,
         IndexKeyBytes,
@@ -67,8 +69,13 @@ use common::{
         RetentionValidator,
         TimestampRange,
     },
+    pool_stats::{
+        ConnectionPoolSnapshot,
+        PoolWorkload,
+    },
     query::Order,
     sha256::Sha256,
+    shutdown::ShutdownSignal,
     types::{
         DatabaseIndexUpdate,
         DatabaseIndexValue,
@@ -153,17 +160,25 @@ pub struct PostgresReaderOptions {
 }
 
 impl PostgresPersistence {
-    pub async fn new(url: &str, options: PostgresOptions) -> Result<Self, ConnectError> {
+    pub async fn new(
+        url: &str,
+        options: PostgresOptions,
+        lease_lost_shutdown: ShutdownSignal,
+    ) -> Result<Self, ConnectError> {
         let pool = Self::create_pool(url)?;
         let newly_created = {
-            let client = pool.get_connection("init_sql").await?;
+            let client = pool
+                .get_connection("init_sql", PoolWorkload::Commit)
+                .await?;
             client
                 .batch_execute(INIT_SQL)
                 .await
                 .map_err(Into::<anyhow::Error>::into)?;
             Self::check_newly_created(&client).await?
         };
-        let client = pool.get_connection("read_only").await?;
+        let client = pool
+            .get_connection("read_only", PoolWorkload::Commit)
+            .await?;
         if !options.allow_read_only && Self::is_read_only(&client).await? {
             return Err(ConnectError::ReadOnly);
         }
@@ -172,7 +187,7 @@ impl PostgresPersistence {
             pool.status().max_size
         );
 
-        let lease = Lease::acquire(pool.clone()).await?;
+        let lease = Lease::acquire(pool.clone(), lease_lost_shutdown).await?;
         Ok(Self {
             newly_created: newly_created.into(),
             lease,
@@ -240,7 +255,7 @@ impl Persistence for PostgresPersistence {
         // True, the below might end up failing and not changing anything.
         self.newly_created.store(false, SeqCst);
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Commit, move |tx| {
                 async move {
                     let (insert_document, insert_document_chunk, insert_index, insert_index_chunk) =
                         try_join!(
@@ -358,7 +373,7 @@ impl Persistence for PostgresPersistence {
 
     async fn set_read_only(&self, read_only: bool) -> anyhow::Result<()> {
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Commit, move |tx| {
                 async move {
                     let statement = if read_only {
                         SET_READ_ONLY
@@ -379,7 +394,7 @@ impl Persistence for PostgresPersistence {
         value: JsonValue,
     ) -> anyhow::Result<()> {
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Commit, move |tx| {
                 async move {
                     let stmt = tx.prepare_cached(WRITE_PERSISTENCE_GLOBAL).await?;
                     let params = [Param::PersistenceGlobalKey(key), Param::JsonValue(value)];
@@ -397,7 +412,10 @@ impl Persistence for PostgresPersistence {
         cursor: Option<IndexEntry>,
         chunk_size: usize,
     ) -> anyhow::Result<Vec<IndexEntry>> {
-        let client = self.read_pool.get_connection("load_index_chunk").await?;
+        let client = self
+            .read_pool
+            .get_connection("load_index_chunk", PoolWorkload::Background)
+            .await?;
         let stmt = client.prepare_cached(LOAD_INDEXES_PAGE).await?;
         let cursor_params = PostgresReader::_index_cursor_params(cursor.as_ref())?;
         let limit = chunk_size as i64;
@@ -417,7 +435,7 @@ impl Persistence for PostgresPersistence {
         expired_entries: Vec<IndexEntry>,
     ) -> anyhow::Result<usize> {
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Background, move |tx| {
                 async move {
                     let mut deleted_count = 0;
                     let mut expired_chunks = expired_entries.chunks_exact(CHUNK_SIZE);
@@ -449,7 +467,7 @@ impl Persistence for PostgresPersistence {
         documents: Vec<(Timestamp, InternalDocumentId)>,
     ) -> anyhow::Result<usize> {
         self.lease
-            .transact(move |tx| {
+            .transact(PoolWorkload::Background, move |tx| {
                 async move {
                     let mut deleted_count = 0;
                     let mut expired_chunks = documents.chunks_exact(CHUNK_SIZE);
@@ -555,7 +573,10 @@ impl PostgresReader {
         retention_validator: Arc<dyn RetentionValidator>,
     ) {
         let timer = metrics::load_documents_timer();
-        let client = self.read_pool.get_connection("load_documents").await?;
+        let client = self
+            .read_pool
+            .get_connection("load_documents", PoolWorkload::Read)
+            .await?;
         let mut num_returned = 0;
         let mut last_ts = match order {
             Order::Asc => Timestamp::MIN,
@@ -691,7 +712,10 @@ impl PostgresReader {
         let _timer = metrics::query_index_timer();
         let (mut lower, mut upper) = to_sql_bounds(interval.clone());
 
-        let client = self.read_pool.get_connection("index_scan").await?;
+        let client = self
+            .read_pool
+            .get_connection("index_scan", PoolWorkload::Read)
+            .await?;
         let mut stats = QueryIndexStats::new();
 
         // We use the size_hint to determine the batch size. This means in the
@@ -912,7 +936,10 @@ impl PersistenceReader for PostgresReader {
     ) -> anyhow::Result<BTreeMap<(InternalDocumentId, Timestamp), DocumentLogEntry>> {
         let timer = metrics::prev_revisions_timer();
 
-        let client = self.read_pool.get_connection("previous_revisions").await?;
+        let client = self
+            .read_pool
+            .get_connection("previous_revisions", PoolWorkload::Read)
+            .await?;
         let (prev_rev_chunk, prev_rev) = try_join!(
             client.prepare_cached(PREV_REV_CHUNK),
             client.prepare_cached(PREV_REV)
@@ -1004,7 +1031,7 @@ impl PersistenceReader for PostgresReader {
     ) -> anyhow::Result<Option<JsonValue>> {
         let client = self
             .read_pool
-            .get_connection("get_persistence_global")
+            .get_connection("get_persistence_global", PoolWorkload::Read)
             .await?;
         let stmt = client.prepare_cached(GET_PERSISTENCE_GLOBAL).await?;
         let params = vec![Param::PersistenceGlobalKey(key)];
@@ -1024,6 +1051,18 @@ impl PersistenceReader for PostgresReader {
     fn version(&self) -> PersistenceVersion {
         self.version
     }
+
+    fn connection_pool_stats(&self) -> Option<ConnectionPoolSnapshot> {
+        Some(self.read_pool.connection_pool_stats())
+    }
+
+    fn set_connection_pool_limit(
+        &self,
+        workload: PoolWorkload,
+        limit: Option<NonZeroUsize>,
+    ) -> anyhow::Result<()> {
+        self.read_pool.set_connection_pool_limit(workload, limit)
+    }
 }
 
 /// A `Lease` is unique for an instance across all of the processes in the
@@ -1041,14 +1080,27 @@ impl PersistenceReader for PostgresReader {
 struct Lease {
     pool: ConvexPgPool,
     lease_ts: i64,
+    lease_lost_shutdown: ShutdownSignal,
+    lease_checker: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        self.lease_checker.abort();
+    }
 }
 
 impl Lease {
-    /// Acquire a lease. Blocks as long as there is another lease holder.
-    /// Returns any transient errors encountered.
-    async fn acquire(pool: ConvexPgPool) -> anyhow::Result<Self> {
+    /// Acquire a lease. Makes other lease-holders get `LeaseLostError` when
+    /// they commit. Returns any transient errors encountered.
+    async fn acquire(
+        pool: ConvexPgPool,
+        lease_lost_shutdown: ShutdownSignal,
+    ) -> anyhow::Result<Self> {
         let timer = metrics::lease_acquire_timer();
-        let client = pool.get_connection("lease_acquire").await?;
+        let client = pool
+            .get_connection("lease_acquire", PoolWorkload::Commit)
+            .await?;
         let ts = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("before 1970")
@@ -1064,7 +1116,46 @@ impl Lease {
         tracing::info!("lease acquired with ts {}", ts);
 
         timer.finish();
-        Ok(Self { pool, lease_ts: ts })
+        let lease_checker = tokio::spawn(Self::check_periodically(
+            pool.clone(),
+            ts,
+            lease_lost_shutdown.clone(),
+        ));
+        Ok(Self {
+            pool,
+            lease_ts: ts,
+            lease_lost_shutdown,
+            lease_checker,
+        })
+    }
+
+    /// Signal `lease_lost_shutdown` once another process takes the lease, so
+    /// we stop serving reads even if we never try to commit again.
+    async fn check_periodically(
+        pool: ConvexPgPool,
+        lease_ts: i64,
+        lease_lost_shutdown: ShutdownSignal,
+    ) {
+        loop {
+            tokio::time::sleep(*LEASE_CHECK_INTERVAL).await;
+            match Self::is_held(&pool, lease_ts).await {
+                Ok(true) => {},
+                Ok(false) => {
+                    tracing::error!("lease with ts {lease_ts} was taken by another process");
+                    lease_lost_shutdown.signal(lease_lost_error());
+                    return;
+                },
+                Err(e) => tracing::warn!("failed to check lease: {e:#}"),
+            }
+        }
+    }
+
+    async fn is_held(pool: &ConvexPgPool, lease_ts: i64) -> anyhow::Result<bool> {
+        let client = pool
+            .get_connection("lease_check", PoolWorkload::Background)
+            .await?;
+        let stmt = client.prepare_cached(LEASE_CHECK).await?;
+        Ok(client.query_opt(&stmt, &[&lease_ts]).await?.is_some())
     }
 
     /// Execute the transaction function f atomically ensuring that the lease is
@@ -1075,13 +1166,15 @@ impl Lease {
     /// and any in-memory state then resynced because of any changes that
     /// might've been made to the database state while the lease was not
     /// held.
-    async fn transact<F, T>(&self, f: F) -> anyhow::Result<T>
+    ///
+    /// The connection counts against `workload`'s share of the pool.
+    async fn transact<F, T>(&self, workload: PoolWorkload, f: F) -> anyhow::Result<T>
     where
         F: for<'b> FnOnce(
             &'b PostgresTransaction,
         ) -> Pin<Box<dyn Future<Output = anyhow::Result<T>> + Send + 'b>>,
     {
-        let mut client = self.pool.get_connection("transact").await?;
+        let mut client = self.pool.get_connection("transact", workload).await?;
         let tx = client.transaction().await?;
 
         let timer = metrics::lease_precond_timer();
@@ -1089,7 +1182,8 @@ impl Lease {
         let lease_ts = self.lease_ts;
         let rows = tx.query(&stmt, &[&lease_ts]).await?;
         if rows.len() != 1 {
-            return Err(LeaseLostError {}.into());
+            self.lease_lost_shutdown.signal(lease_lost_error());
+            anyhow::bail!(lease_lost_error());
         }
         timer.finish();
 
@@ -1484,6 +1578,9 @@ const NUM_DOCUMENT_PARAMS: usize = 6;
 const NUM_INDEX_PARAMS: usize = 8;
 const MAX_INSERT_SIZE: usize = 16384;
 static PIPELINE_QUERIES: LazyLock<usize> = LazyLock::new(|| env_config("PIPELINE_QUERIES", 16));
+// How often the lease holder checks that no other process has taken the lease.
+static LEASE_CHECK_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("POSTGRES_LEASE_CHECK_INTERVAL_SECS", 5)));
 
 // Gross: after initialization, the first thing database does is insert metadata
 // documents.
@@ -1499,6 +1596,9 @@ const UNSET_READ_ONLY: &str = "DELETE FROM read_only WHERE id = 1";
 // until the end of the transaction.
 const LEASE_PRECOND: &str = "SELECT 1 FROM leases WHERE id=1 AND ts=$1 FOR SHARE";
 
+// Returns a result iff the lease is still held, without locking it.
+const LEASE_CHECK: &str = "SELECT 1 FROM leases WHERE id=1 AND ts=$1";
+
 // Acquire the lease unless acquire by someone with a higher timestamp.
 const LEASE_ACQUIRE: &str = "UPDATE leases SET ts=$1 WHERE id=1 AND ts<$1";
 
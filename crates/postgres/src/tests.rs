@@ -3,7 +3,10 @@ use std::{
     collections::BTreeSet,
     env,
     sync::Arc,
-    time::Instant,
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 use common::{
@@ -11,6 +14,7 @@ use common::{
         CreationTime,
         ResolvedDocument,
     },
+    errors::LeaseLostError,
     obj,
     persistence::{
         ConflictStrategy,
@@ -18,6 +22,7 @@ use common::{
         Persistence,
     },
     run_persistence_test_suite,
+    shutdown::ShutdownSignal,
     testing::{
         self,
         persistence_test_suite,
@@ -49,7 +54,8 @@ run_persistence_test_suite!(
         PostgresOptions {
             allow_read_only: false,
             version: PersistenceVersion::V5,
-        }
+        },
+        ShutdownSignal::no_op(),
     )
     .await?,
     PostgresPersistence::new(
@@ -57,7 +63,8 @@ run_persistence_test_suite!(
         PostgresOptions {
             allow_read_only: true,
             version: PersistenceVersion::V5,
-        }
+        },
+        ShutdownSignal::no_op(),
     )
     .await?
 );
@@ -68,8 +75,12 @@ async fn test_loading_locally() -> anyhow::Result<()> {
         allow_read_only: false,
         version: PersistenceVersion::V5,
     };
-    let persistence =
-        PostgresPersistence::new(&crate::itest::new_db_opts().await?, options).await?; // need coverage on false too.
+    let persistence = PostgresPersistence::new(
+        &crate::itest::new_db_opts().await?,
+        options,
+        ShutdownSignal::no_op(),
+    )
+    .await?; // need coverage on false too.
 
     let start = Instant::now();
     let reader = persistence.reader();
@@ -105,8 +116,12 @@ async fn test_writing_locally() -> anyhow::Result<()> {
         allow_read_only: false,
         version: PersistenceVersion::V5,
     };
-    let persistence =
-        PostgresPersistence::new(&crate::itest::new_db_opts().await?, options).await?;
+    let persistence = PostgresPersistence::new(
+        &crate::itest::new_db_opts().await?,
+        options,
+        ShutdownSignal::no_op(),
+    )
+    .await?;
 
     let mut max_ts = None;
     {
@@ -157,7 +172,7 @@ async fn test_lease_preempt() -> anyhow::Result<()> {
         allow_read_only: false,
         version: PersistenceVersion::default(),
     };
-    let p1 = Arc::new(PostgresPersistence::new(&url, options).await?);
+    let p1 = Arc::new(PostgresPersistence::new(&url, options, ShutdownSignal::no_op()).await?);
 
     let mut id_generator = TestIdGenerator::new();
     let table: TableName = str::parse("table")?;
@@ -186,7 +201,7 @@ async fn test_lease_preempt() -> anyhow::Result<()> {
         allow_read_only: false,
         version: PersistenceVersion::V5,
     };
-    let p2 = PostgresPersistence::new(&url, options).await?;
+    let p2 = PostgresPersistence::new(&url, options, ShutdownSignal::no_op()).await?;
 
     // New Persistence can write.
     p2.write(
@@ -227,3 +242,22 @@ async fn test_lease_preempt() -> anyhow::Result<()> {
     assert!(result.is_err());
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_lease_lost_signals_shutdown() -> anyhow::Result<()> {
+    let url = crate::itest::new_db_opts().await?;
+    let options = PostgresOptions {
+        allow_read_only: false,
+        version: PersistenceVersion::default(),
+    };
+    let (lease_lost_shutdown, mut shutdown_rx) = ShutdownSignal::new_for_test();
+    let _p1 = PostgresPersistence::new(&url, options, lease_lost_shutdown).await?;
+
+    // Acquire the lease from another Persistence.
+    let _p2 = PostgresPersistence::new(&url, options, ShutdownSignal::no_op()).await?;
+
+    // The old Persistence notices without attempting a write.
+    let message = tokio::time::timeout(Duration::from_secs(30), shutdown_rx.recv()).await??;
+    assert!(message.error.downcast_ref::<LeaseLostError>().is_some());
+    Ok(())
+}
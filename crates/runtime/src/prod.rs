@@ -71,6 +71,12 @@ impl SpawnHandle for FutureHandle {
         }
         .boxed()
     }
+
+    fn is_finished(&self) -> bool {
+        self.handle
+            .as_ref()
+            .is_none_or(|handle| handle.is_finished())
+    }
 }
 
 pub struct ThreadHandle {
@@ -107,6 +113,12 @@ impl SpawnHandle for ThreadHandle {
         };
         future.boxed()
     }
+
+    fn is_finished(&self) -> bool {
+        self.handle
+            .as_ref()
+            .is_none_or(|handle| handle.is_finished())
+    }
 }
 
 impl ThreadHandle {
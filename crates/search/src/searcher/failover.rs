@@ -0,0 +1,610 @@
+//! A `Searcher` that spreads requests over several searchers, so search keeps
+//! working while one of them restarts.
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use common::{
+    bootstrap_model::index::{
+        text_index::FragmentedTextSegment,
+        vector_index::FragmentedVectorSegment,
+    },
+    knobs::{
+        SEARCHER_CIRCUIT_BREAKER_COOLDOWN,
+        SEARCHER_CIRCUIT_BREAKER_FAILURES,
+        SEARCHER_HEDGE_DELAY,
+    },
+    runtime::Runtime,
+};
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
+use futures::{
+    future::{
+        self,
+        BoxFuture,
+        FusedFuture,
+    },
+    stream::FuturesUnordered,
+    FutureExt,
+    StreamExt,
+};
+use parking_lot::Mutex;
+use pb::searchlight::FragmentedVectorSegmentPaths;
+use storage::Storage;
+use tantivy::Term;
+use vector::{
+    CompiledVectorSearch,
+    QdrantSchema,
+    VectorSearchQueryResult,
+    VectorSearcher,
+};
+
+use super::{
+    metrics::{
+        log_searcher_circuit_opened,
+        log_searcher_failover,
+        log_searcher_hedged_request,
+    },
+    searcher::{
+        Bm25Stats,
+        PostingListMatch,
+        PostingListQuery,
+        TokenMatch,
+        TokenQuery,
+    },
+    FragmentedTextStorageKeys,
+};
+use crate::Searcher;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FailoverSearcherConfig {
+    /// Consecutive failures after which a searcher's circuit opens and it
+    /// stops receiving requests.
+    pub circuit_breaker_failures: u32,
+    /// How long an open circuit stays open before a request is let through to
+    /// check whether the searcher has recovered.
+    pub circuit_breaker_cooldown: Duration,
+    /// How long to wait on a slow query before also sending it to the next
+    /// searcher. `None` disables hedging.
+    pub hedge_delay: Option<Duration>,
+}
+
+impl Default for FailoverSearcherConfig {
+    fn default() -> Self {
+        Self {
+            circuit_breaker_failures: *SEARCHER_CIRCUIT_BREAKER_FAILURES,
+            circuit_breaker_cooldown: *SEARCHER_CIRCUIT_BREAKER_COOLDOWN,
+            hedge_delay: Some(*SEARCHER_HEDGE_DELAY).filter(|delay| !delay.is_zero()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct SearcherHealth {
+    consecutive_failures: u32,
+    open_until: Option<tokio::time::Instant>,
+}
+
+struct SearcherEndpoint {
+    searcher: Arc<dyn Searcher>,
+    health: Mutex<SearcherHealth>,
+}
+
+/// Sends each request to the first searcher whose circuit is closed, and
+/// fails over to the next one when a searcher returns a system error.
+/// Developer errors, like an invalid query, are returned as is since another
+/// searcher would fail the same way.
+///
+/// A searcher that fails `circuit_breaker_failures` times in a row is skipped
+/// for `circuit_breaker_cooldown`, after which the next request to it decides
+/// whether it is healthy again. When every searcher has failed or is skipped,
+/// requests fail with a `SearchTemporarilyUnavailable` error.
+#[derive(Clone)]
+pub struct FailoverSearcher<RT: Runtime> {
+    rt: RT,
+    endpoints: Arc<Vec<SearcherEndpoint>>,
+    config: FailoverSearcherConfig,
+}
+
+impl<RT: Runtime> FailoverSearcher<RT> {
+    pub fn new(
+        rt: RT,
+        searchers: Vec<Arc<dyn Searcher>>,
+        config: FailoverSearcherConfig,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(!searchers.is_empty(), "FailoverSearcher needs a searcher");
+        let endpoints = searchers
+            .into_iter()
+            .map(|searcher| SearcherEndpoint {
+                searcher,
+                health: Mutex::new(SearcherHealth::default()),
+            })
+            .collect();
+        Ok(Self {
+            rt,
+            endpoints: Arc::new(endpoints),
+            config,
+        })
+    }
+
+    /// The searchers to try, in order.
+    fn available_endpoints(&self) -> Vec<usize> {
+        let now = self.rt.monotonic_now();
+        (0..self.endpoints.len())
+            .filter(|&i| {
+                let health = self.endpoints[i].health.lock();
+                health.open_until.is_none_or(|open_until| open_until <= now)
+            })
+            .collect()
+    }
+
+    fn record_result<T>(&self, i: usize, result: &anyhow::Result<T>) {
+        let mut health = self.endpoints[i].health.lock();
+        match result {
+            Err(e) if !e.is_deterministic_user_error() => {
+                health.consecutive_failures += 1;
+                if health.consecutive_failures >= self.config.circuit_breaker_failures {
+                    if health.open_until.is_none() {
+                        tracing::warn!(
+                            "Searcher {i} failed {} times in a row, skipping it for {:?}",
+                            health.consecutive_failures,
+                            self.config.circuit_breaker_cooldown,
+                        );
+                        log_searcher_circuit_opened();
+                    }
+                    health.open_until =
+                        Some(self.rt.monotonic_now() + self.config.circuit_breaker_cooldown);
+                }
+            },
+            _ => *health = SearcherHealth::default(),
+        }
+    }
+
+    fn hedge_timer(
+        &self,
+        hedge: bool,
+        has_next: bool,
+    ) -> Pin<Box<dyn FusedFuture<Output = ()> + Send + 'static>> {
+        match self.config.hedge_delay {
+            Some(delay) if hedge && has_next => self.rt.wait(delay),
+            _ => Box::pin(future::pending()),
+        }
+    }
+
+    async fn call<T, F, Fut>(&self, hedge: bool, f: F) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+        F: Fn(Arc<dyn Searcher>) -> Fut + Send + Sync,
+        Fut: Future<Output = anyhow::Result<T>> + Send + 'static,
+    {
+        let mut remaining = self.available_endpoints().into_iter().peekable();
+        let start = |i: usize| -> BoxFuture<'static, (usize, anyhow::Result<T>)> {
+            let request = f(self.endpoints[i].searcher.clone());
+            async move { (i, request.await) }.boxed()
+        };
+        let mut in_flight = FuturesUnordered::new();
+        let mut last_error = None;
+        if let Some(i) = remaining.next() {
+            in_flight.push(start(i));
+        }
+        let mut hedge_timer = self.hedge_timer(hedge, remaining.peek().is_some());
+        while !in_flight.is_empty() {
+            tokio::select! {
+                Some((i, result)) = in_flight.next() => {
+                    self.record_result(i, &result);
+                    match result {
+                        Ok(value) => return Ok(value),
+                        Err(e) if e.is_deterministic_user_error() => return Err(e),
+                        Err(e) => {
+                            tracing::warn!("Searcher {i} failed: {e:#}");
+                            last_error = Some(e);
+                        },
+                    }
+                    if in_flight.is_empty() && let Some(next) = remaining.next() {
+                        log_searcher_failover();
+                        in_flight.push(start(next));
+                        hedge_timer = self.hedge_timer(hedge, remaining.peek().is_some());
+                    }
+                },
+                _ = &mut hedge_timer => {
+                    if let Some(next) = remaining.next() {
+                        log_searcher_hedged_request();
+                        in_flight.push(start(next));
+                    }
+                    hedge_timer = self.hedge_timer(hedge, remaining.peek().is_some());
+                },
+            }
+        }
+        let error = last_error.unwrap_or_else(|| anyhow::anyhow!("Every searcher is unhealthy"));
+        Err(error.context(ErrorMetadata::overloaded(
+            "SearchTemporarilyUnavailable",
+            "Search is temporarily unavailable. Please try again later.",
+        )))
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> Searcher for FailoverSearcher<RT> {
+    async fn query_tokens(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_keys: FragmentedTextStorageKeys,
+        queries: Vec<TokenQuery>,
+        max_results: usize,
+    ) -> anyhow::Result<Vec<TokenMatch>> {
+        self.call(true, |searcher| {
+            let (search_storage, storage_keys, queries) = (
+                search_storage.clone(),
+                storage_keys.clone(),
+                queries.clone(),
+            );
+            async move {
+                searcher
+                    .query_tokens(search_storage, storage_keys, queries, max_results)
+                    .await
+            }
+        })
+        .await
+    }
+
+    async fn query_bm25_stats(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_keys: FragmentedTextStorageKeys,
+        terms: Vec<Term>,
+    ) -> anyhow::Result<Bm25Stats> {
+        self.call(true, |searcher| {
+            let (search_storage, storage_keys, terms) =
+                (search_storage.clone(), storage_keys.clone(), terms.clone());
+            async move {
+                searcher
+                    .query_bm25_stats(search_storage, storage_keys, terms)
+                    .await
+            }
+        })
+        .await
+    }
+
+    async fn query_posting_lists(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_keys: FragmentedTextStorageKeys,
+        query: PostingListQuery,
+    ) -> anyhow::Result<Vec<PostingListMatch>> {
+        self.call(true, |searcher| {
+            let (search_storage, storage_keys, query) =
+                (search_storage.clone(), storage_keys.clone(), query.clone());
+            async move {
+                searcher
+                    .query_posting_lists(search_storage, storage_keys, query)
+                    .await
+            }
+        })
+        .await
+    }
+
+    async fn execute_text_compaction(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<FragmentedTextSegment> {
+        self.call(false, |searcher| {
+            let (search_storage, segments) = (search_storage.clone(), segments.clone());
+            async move {
+                searcher
+                    .execute_text_compaction(search_storage, segments)
+                    .await
+            }
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> VectorSearcher for FailoverSearcher<RT> {
+    async fn execute_multi_segment_vector_query(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+        schema: QdrantSchema,
+        search: CompiledVectorSearch,
+        overfetch_delta: u32,
+    ) -> anyhow::Result<Vec<VectorSearchQueryResult>> {
+        self.call(true, |searcher| {
+            let (search_storage, segments, schema, search) = (
+                search_storage.clone(),
+                segments.clone(),
+                schema.clone(),
+                search.clone(),
+            );
+            async move {
+                searcher
+                    .execute_multi_segment_vector_query(
+                        search_storage,
+                        segments,
+                        schema,
+                        search,
+                        overfetch_delta,
+                    )
+                    .await
+            }
+        })
+        .await
+    }
+
+    async fn execute_vector_compaction(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+        dimension: usize,
+    ) -> anyhow::Result<FragmentedVectorSegment> {
+        self.call(false, |searcher| {
+            let (search_storage, segments) = (search_storage.clone(), segments.clone());
+            async move {
+                searcher
+                    .execute_vector_compaction(search_storage, segments, dimension)
+                    .await
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{
+                AtomicBool,
+                AtomicUsize,
+                Ordering,
+            },
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use common::{
+        bootstrap_model::index::{
+            text_index::FragmentedTextSegment,
+            vector_index::FragmentedVectorSegment,
+        },
+        runtime::Runtime,
+        types::ObjectKey,
+    };
+    use errors::ErrorMetadataAnyhowExt;
+    use pb::searchlight::FragmentedVectorSegmentPaths;
+    use runtime::testing::TestRuntime;
+    use storage::{
+        LocalDirStorage,
+        Storage,
+    };
+    use tantivy::Term;
+    use vector::{
+        CompiledVectorSearch,
+        QdrantSchema,
+        VectorSearchQueryResult,
+        VectorSearcher,
+    };
+
+    use super::{
+        FailoverSearcher,
+        FailoverSearcherConfig,
+    };
+    use crate::searcher::{
+        Bm25Stats,
+        FragmentedTextStorageKeys,
+        PostingListMatch,
+        PostingListQuery,
+        Searcher,
+        TokenMatch,
+        TokenQuery,
+    };
+
+    /// Answers `query_bm25_stats` with `num_documents` set to its own id, so
+    /// tests can tell which searcher answered.
+    struct FakeSearcher {
+        rt: TestRuntime,
+        id: u64,
+        latency: Duration,
+        healthy: AtomicBool,
+        requests: AtomicUsize,
+    }
+
+    impl FakeSearcher {
+        fn new(rt: &TestRuntime, id: u64, latency: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                rt: rt.clone(),
+                id,
+                latency,
+                healthy: AtomicBool::new(true),
+                requests: AtomicUsize::new(0),
+            })
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+
+        fn set_healthy(&self, healthy: bool) {
+            self.healthy.store(healthy, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl Searcher for FakeSearcher {
+        async fn query_tokens(
+            &self,
+            _search_storage: Arc<dyn Storage>,
+            _storage_keys: FragmentedTextStorageKeys,
+            _queries: Vec<TokenQuery>,
+            _max_results: usize,
+        ) -> anyhow::Result<Vec<TokenMatch>> {
+            unimplemented!()
+        }
+
+        async fn query_bm25_stats(
+            &self,
+            _search_storage: Arc<dyn Storage>,
+            _storage_keys: FragmentedTextStorageKeys,
+            _terms: Vec<Term>,
+        ) -> anyhow::Result<Bm25Stats> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            self.rt.wait(self.latency).await;
+            anyhow::ensure!(
+                self.healthy.load(Ordering::SeqCst),
+                "Searcher {} is restarting",
+                self.id
+            );
+            Ok(Bm25Stats {
+                num_documents: self.id,
+                ..Bm25Stats::empty()
+            })
+        }
+
+        async fn query_posting_lists(
+            &self,
+            _search_storage: Arc<dyn Storage>,
+            _storage_keys: FragmentedTextStorageKeys,
+            _query: PostingListQuery,
+        ) -> anyhow::Result<Vec<PostingListMatch>> {
+            unimplemented!()
+        }
+
+        async fn execute_text_compaction(
+            &self,
+            _search_storage: Arc<dyn Storage>,
+            _segments: Vec<FragmentedTextStorageKeys>,
+        ) -> anyhow::Result<FragmentedTextSegment> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait]
+    impl VectorSearcher for FakeSearcher {
+        async fn execute_multi_segment_vector_query(
+            &self,
+            _search_storage: Arc<dyn Storage>,
+            _segments: Vec<FragmentedVectorSegmentPaths>,
+            _schema: QdrantSchema,
+            _search: CompiledVectorSearch,
+            _overfetch_delta: u32,
+        ) -> anyhow::Result<Vec<VectorSearchQueryResult>> {
+            unimplemented!()
+        }
+
+        async fn execute_vector_compaction(
+            &self,
+            _search_storage: Arc<dyn Storage>,
+            _segments: Vec<FragmentedVectorSegmentPaths>,
+            _dimension: usize,
+        ) -> anyhow::Result<FragmentedVectorSegment> {
+            unimplemented!()
+        }
+    }
+
+    async fn query(
+        rt: &TestRuntime,
+        searcher: &FailoverSearcher<TestRuntime>,
+    ) -> anyhow::Result<u64> {
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let key = ObjectKey::try_from("segment".to_string())?;
+        let storage_keys = FragmentedTextStorageKeys {
+            segment: key.clone(),
+            id_tracker: key.clone(),
+            deleted_terms_table: key.clone(),
+            alive_bitset: key,
+        };
+        let stats = searcher
+            .query_bm25_stats(storage, storage_keys, vec![])
+            .await?;
+        Ok(stats.num_documents)
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_failover_opens_circuit(rt: TestRuntime) -> anyhow::Result<()> {
+        let primary = FakeSearcher::new(&rt, 1, Duration::ZERO);
+        let secondary = FakeSearcher::new(&rt, 2, Duration::ZERO);
+        let searcher = FailoverSearcher::new(
+            rt.clone(),
+            vec![primary.clone() as Arc<dyn Searcher>, secondary.clone()],
+            FailoverSearcherConfig {
+                circuit_breaker_failures: 2,
+                circuit_breaker_cooldown: Duration::from_secs(10),
+                hedge_delay: None,
+            },
+        )?;
+        assert_eq!(query(&rt, &searcher).await?, 1);
+
+        primary.set_healthy(false);
+        assert_eq!(query(&rt, &searcher).await?, 2);
+        assert_eq!(query(&rt, &searcher).await?, 2);
+        assert_eq!(primary.requests(), 3);
+
+        // The primary failed twice in a row, so it's skipped until the
+        // cooldown passes.
+        assert_eq!(query(&rt, &searcher).await?, 2);
+        assert_eq!(primary.requests(), 3);
+
+        primary.set_healthy(true);
+        rt.advance_time(Duration::from_secs(10)).await;
+        assert_eq!(query(&rt, &searcher).await?, 1);
+        assert_eq!(primary.requests(), 4);
+        assert_eq!(secondary.requests(), 3);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_search_temporarily_unavailable(rt: TestRuntime) -> anyhow::Result<()> {
+        let primary = FakeSearcher::new(&rt, 1, Duration::ZERO);
+        let secondary = FakeSearcher::new(&rt, 2, Duration::ZERO);
+        let searcher = FailoverSearcher::new(
+            rt.clone(),
+            vec![primary.clone() as Arc<dyn Searcher>, secondary.clone()],
+            FailoverSearcherConfig {
+                circuit_breaker_failures: 1,
+                circuit_breaker_cooldown: Duration::from_secs(10),
+                hedge_delay: None,
+            },
+        )?;
+        primary.set_healthy(false);
+        secondary.set_healthy(false);
+        let err = query(&rt, &searcher).await.unwrap_err();
+        assert_eq!(err.short_msg(), "SearchTemporarilyUnavailable");
+        assert!(err.is_overloaded());
+
+        // Both circuits are open, so requests fail without reaching either.
+        let err = query(&rt, &searcher).await.unwrap_err();
+        assert_eq!(err.short_msg(), "SearchTemporarilyUnavailable");
+        assert_eq!(primary.requests() + secondary.requests(), 2);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_hedged_request(rt: TestRuntime) -> anyhow::Result<()> {
+        let slow = FakeSearcher::new(&rt, 1, Duration::from_secs(5));
+        let fast = FakeSearcher::new(&rt, 2, Duration::ZERO);
+        let searcher = FailoverSearcher::new(
+            rt.clone(),
+            vec![slow.clone() as Arc<dyn Searcher>, fast.clone()],
+            FailoverSearcherConfig {
+                circuit_breaker_failures: 2,
+                circuit_breaker_cooldown: Duration::from_secs(10),
+                hedge_delay: Some(Duration::from_secs(1)),
+            },
+        )?;
+        let start = rt.monotonic_now();
+        assert_eq!(query(&rt, &searcher).await?, 2);
+        assert_eq!(rt.monotonic_now() - start, Duration::from_secs(1));
+        assert_eq!(slow.requests(), 1);
+        assert_eq!(fast.requests(), 1);
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 use metrics::{
+    log_counter,
+    register_convex_counter,
     register_convex_histogram,
     CancelableTimer,
     StatusTimer,
@@ -86,3 +88,27 @@ pub fn vector_query_timer(vector_index_type: VectorIndexType) -> CancelableTimer
     timer.add_label(vector_index_type_label(vector_index_type));
     timer
 }
+
+register_convex_counter!(
+    SEARCHER_FAILOVER_TOTAL,
+    "Number of search requests retried on another searcher after one failed"
+);
+pub(crate) fn log_searcher_failover() {
+    log_counter(&SEARCHER_FAILOVER_TOTAL, 1);
+}
+
+register_convex_counter!(
+    SEARCHER_HEDGED_REQUEST_TOTAL,
+    "Number of search queries also sent to another searcher because the first was slow"
+);
+pub(crate) fn log_searcher_hedged_request() {
+    log_counter(&SEARCHER_HEDGED_REQUEST_TOTAL, 1);
+}
+
+register_convex_counter!(
+    SEARCHER_CIRCUIT_OPENED_TOTAL,
+    "Number of times a searcher failed enough in a row to stop receiving requests"
+);
+pub(crate) fn log_searcher_circuit_opened() {
+    log_counter(&SEARCHER_CIRCUIT_OPENED_TOTAL, 1);
+}
@@ -1,3 +1,4 @@
+mod failover;
 mod in_process;
 mod metrics;
 #[allow(clippy::module_inception)]
@@ -5,6 +6,10 @@ mod searcher;
 mod searchlight_knobs;
 mod segment_cache;
 
+pub use failover::{
+    FailoverSearcher,
+    FailoverSearcherConfig,
+};
 pub use in_process::{
     InProcessSearcher,
     SearcherStub,
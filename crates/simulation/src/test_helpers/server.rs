@@ -9,6 +9,7 @@ use application::{
     RedactedMutationReturn,
 };
 use common::{
+    execution_context::RequestContext,
     http::{
         RequestDestination,
         ResolvedHostname,
@@ -212,6 +213,7 @@ impl ServerThread {
                                 vec![args.into()],
                                 FunctionCaller::Test,
                                 None,
+                                RequestContext::default(),
                             ).await?;
                             let _ = result.send(res);
                         },
@@ -30,7 +30,10 @@ use std::{
         Context,
         Poll,
     },
-    time::Duration,
+    time::{
+        Duration,
+        SystemTime,
+    },
 };
 
 use anyhow::Context as _;
@@ -186,12 +189,21 @@ pub trait Storage: Send + Sync + Debug {
     ) -> anyhow::Result<ObjectKey>;
     /// Delete the given object.
     async fn delete_object(&self, key: &ObjectKey) -> anyhow::Result<()>;
+    /// List every object in storage, in no particular order.
+    fn list_objects(&self) -> BoxStream<'_, anyhow::Result<ListedObject>>;
 }
 
 pub struct ObjectAttributes {
     pub size: u64,
 }
 
+pub struct ListedObject {
+    pub key: ObjectKey,
+    pub size: u64,
+    /// When the object was last written, according to the storage backend.
+    pub last_modified: SystemTime,
+}
+
 pub struct SizeAndHash {
     sha256: Sha256,
     size: usize,
@@ -1116,6 +1128,39 @@ impl<RT: Runtime> Storage for LocalDirStorage<RT> {
         fs::remove_file(path)?;
         Ok(())
     }
+
+    fn list_objects(&self) -> BoxStream<'_, anyhow::Result<ListedObject>> {
+        let list = || -> anyhow::Result<Vec<ListedObject>> {
+            let mut objects = vec![];
+            let mut dirs = vec![self.dir.clone()];
+            while let Some(dir) = dirs.pop() {
+                for entry in fs::read_dir(dir)? {
+                    let entry = entry?;
+                    let metadata = entry.metadata()?;
+                    let path = entry.path();
+                    if metadata.is_dir() {
+                        dirs.push(path);
+                        continue;
+                    }
+                    // Keys may contain "/", which we store as subdirectories.
+                    let relative = path.strip_prefix(&self.dir)?.to_string_lossy();
+                    let Some(key) = relative.strip_suffix(".blob") else {
+                        continue;
+                    };
+                    objects.push(ListedObject {
+                        key: key.replace('\\', "/").try_into()?,
+                        size: metadata.len(),
+                        last_modified: metadata.modified()?,
+                    });
+                }
+            }
+            Ok(objects)
+        };
+        match list() {
+            Ok(objects) => stream::iter(objects.into_iter().map(Ok)).boxed(),
+            Err(e) => stream::once(async move { Err(e) }).boxed(),
+        }
+    }
 }
 
 pub struct LocalDirUpload {
@@ -1491,4 +1536,21 @@ mod local_storage_tests {
         assert!(storage.get(&object_key).await?.is_none());
         Ok(())
     }
+
+    #[convex_macro::test_runtime]
+    async fn test_storage_list_objects(rt: TestRuntime) -> anyhow::Result<()> {
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt)?);
+        let mut test_upload = storage.start_upload().await?;
+        test_upload.write(vec![1, 2, 3].into()).await?;
+        let object_key = test_upload.complete().await?;
+        let objects: Vec<_> = storage.list_objects().try_collect().await?;
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].key, object_key);
+        assert_eq!(objects[0].size, 3);
+
+        storage.delete_object(&object_key).await?;
+        let objects: Vec<_> = storage.list_objects().try_collect().await?;
+        assert!(objects.is_empty());
+        Ok(())
+    }
 }
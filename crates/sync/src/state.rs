@@ -35,6 +35,7 @@ use futures::{
 use keybroker::Identity;
 use sync_types::{
     IdentityVersion,
+    PageSplitMessage,
     Query,
     QueryId,
     QuerySetModification,
@@ -380,6 +381,7 @@ impl SyncState {
         result: Result<ConvexValue, RedactedJsError>,
         log_lines: RedactedLogLines,
         journal: SerializedQueryJournal,
+        page_split: Option<PageSplitMessage>,
         subscription: Box<dyn SubscriptionTrait>,
     ) -> anyhow::Result<Option<StateModification<ConvexValue>>> {
         if let Some(query) = self.in_progress_queries.remove(&query_id) {
@@ -430,6 +432,7 @@ impl SyncState {
                     value,
                     log_lines: log_lines.into(),
                     journal,
+                    page_split,
                 },
                 Err(error) => {
                     metrics::log_query_failed();
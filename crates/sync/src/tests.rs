@@ -40,6 +40,10 @@ use common::{
     RequestId,
 };
 use errors::ErrorMetadataAnyhowExt;
+use futures::{
+    future,
+    Future,
+};
 use isolate::test_helpers::TEST_SOURCE_ISOLATE_ONLY;
 use keybroker::{
     testing::TestUserIdentity,
@@ -66,7 +70,10 @@ use sync_types::{
     StateModification,
     UserIdentityAttributes,
 };
-use tokio::sync::mpsc;
+use tokio::sync::{
+    mpsc,
+    oneshot,
+};
 
 use crate::{
     worker::{
@@ -128,13 +135,14 @@ impl<RT: Runtime> SyncTest<RT> {
 
     fn new_worker(&self) -> anyhow::Result<TestSyncWorker<RT>> {
         let config = SyncWorkerConfig::default();
-        self.new_worker_with_config(config, None)
+        self.new_worker_with_config(config, None, future::pending())
     }
 
     fn new_worker_with_config(
         &self,
         config: SyncWorkerConfig,
         max_observed_timestamp: Option<Timestamp>,
+        shutdown: impl Future<Output = ()> + Send + 'static,
     ) -> anyhow::Result<TestSyncWorker<RT>> {
         let worker_failed = Arc::new(Mutex::new(None));
         let (client_tx, client_rx) = mpsc::unbounded_channel();
@@ -146,7 +154,7 @@ impl<RT: Runtime> SyncTest<RT> {
         let future = async move {
             // TODO(CX-597): The panic in this future currently gets swallowed by
             // `futures::RemoteHandle`.
-            if let Err(e) = SyncWorker::new(
+            let mut worker = SyncWorker::new(
                 api,
                 rt,
                 ResolvedHostname {
@@ -157,10 +165,9 @@ impl<RT: Runtime> SyncTest<RT> {
                 client_rx,
                 server_tx,
                 Box::new(|_session_id| ()),
-            )
-            .go()
-            .await
-            {
+            );
+            worker.drain_on_shutdown(shutdown);
+            if let Err(e) = worker.go().await {
                 worker_failed_.lock().replace(e);
             }
         };
@@ -228,6 +235,7 @@ impl<RT: Runtime> TestSyncWorker<RT> {
             udf_path: path.parse()?,
             args: vec![args.into()],
             component_path: None,
+            request_context: None,
         })?;
 
         must_let!(let ServerMessage::MutationResponse {
@@ -898,7 +906,8 @@ async fn test_max_observed_timestamp(rt: TestRuntime) -> anyhow::Result<()> {
     let test = SyncTest::new(rt).await?;
 
     let config = SyncWorkerConfig::default();
-    let mut sync_worker = test.new_worker_with_config(config, Some(Timestamp::MAX))?;
+    let mut sync_worker =
+        test.new_worker_with_config(config, Some(Timestamp::MAX), future::pending())?;
     must_let!(let Err(err) = sync_worker.receive().await);
     assert!(
         format!("{err}")
@@ -908,3 +917,40 @@ async fn test_max_observed_timestamp(rt: TestRuntime) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_drain_on_shutdown(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt).await?;
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    let mut sync_worker =
+        test.new_worker_with_config(SyncWorkerConfig::default(), None, async move {
+            let _ = shutdown_rx.await;
+        })?;
+    sync_worker
+        .mutation(
+            "sync:initialize",
+            assert_obj!("name" => "orinoco", "balance" => 100.0),
+            0,
+        )
+        .await?;
+    must_let!(let ServerMessage::Transition { .. } = sync_worker.receive().await?);
+
+    // Mutations sent once the server is shutting down aren't run, and the
+    // worker exits cleanly since nothing is in flight.
+    let _ = shutdown_tx.send(());
+    sync_worker.send(ClientMessage::Mutation {
+        request_id: 1,
+        udf_path: "sync:initialize".parse()?,
+        args: vec![assert_obj!("name" => "tizoncito", "balance" => 50.0).into()],
+        component_path: None,
+        request_context: None,
+    })?;
+    while let Some((message, _)) = sync_worker.rx.next().await {
+        assert!(
+            !matches!(message, ServerMessage::MutationResponse { .. }),
+            "{message:?}"
+        );
+    }
+    sync_worker.shutdown().await?;
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use std::{
     collections::BTreeMap,
+    net::IpAddr,
+    pin::Pin,
     sync::{
         atomic::{
             AtomicUsize,
@@ -33,9 +35,13 @@ use common::{
         ComponentPath,
         ExportPath,
     },
+    execution_context::RequestContext,
     fastrace_helpers::get_sampled_span,
     http::ResolvedHostname,
-    knobs::SYNC_MAX_SEND_TRANSITION_COUNT,
+    knobs::{
+        SHUTDOWN_DRAIN_TIMEOUT,
+        SYNC_MAX_SEND_TRANSITION_COUNT,
+    },
     runtime::{
         Runtime,
         WithTimeout,
@@ -55,6 +61,7 @@ use futures::{
         self,
         BoxFuture,
         Fuse,
+        FusedFuture,
     },
     select_biased,
     stream::{
@@ -72,6 +79,7 @@ use model::session_requests::types::SessionRequestIdentifier;
 use sync_types::{
     ClientMessage,
     IdentityVersion,
+    PageSplitMessage,
     QueryId,
     QuerySetModification,
     SerializedQueryJournal,
@@ -114,12 +122,16 @@ const SYNC_WORKER_PROCESS_TIMEOUT: Duration = Duration::from_secs(60);
 #[derive(Clone, Debug)]
 pub struct SyncWorkerConfig {
     pub client_version: ClientVersion,
+    /// The address the websocket connected from, which invalid admin keys
+    /// sent over it are counted against.
+    pub client_ip: Option<IpAddr>,
 }
 
 impl Default for SyncWorkerConfig {
     fn default() -> Self {
         Self {
             client_version: ClientVersion::unknown(),
+            client_ip: None,
         }
     }
 }
@@ -227,6 +239,8 @@ pub struct SyncWorker<RT: Runtime> {
     // a single one since this is less error prone model for the developer.
     mutation_futures: Buffered<ReceiverStream<BoxFuture<'static, anyhow::Result<ServerMessage>>>>,
     mutation_sender: mpsc::Sender<BoxFuture<'static, anyhow::Result<ServerMessage>>>,
+    // Number of mutations sent to `mutation_futures` that haven't responded yet.
+    mutations_in_flight: usize,
 
     action_futures: FuturesUnordered<BoxFuture<'static, anyhow::Result<ServerMessage>>>,
 
@@ -236,6 +250,11 @@ pub struct SyncWorker<RT: Runtime> {
     update_scheduled: bool,
 
     on_connect: Option<(StatusTimer, Box<dyn FnOnce(SessionId) + Send>)>,
+
+    // Resolves when the server starts shutting down. After that, new mutations
+    // and actions are ignored and `go` exits once the in-flight ones finish.
+    shutdown: Fuse<BoxFuture<'static, ()>>,
+    draining: bool,
 }
 
 enum QueryResult {
@@ -243,6 +262,7 @@ enum QueryResult {
         result: Result<ConvexValue, RedactedJsError>,
         log_lines: RedactedLogLines,
         journal: SerializedQueryJournal,
+        page_split: Option<PageSplitMessage>,
     },
     Refresh,
 }
@@ -277,13 +297,29 @@ impl<RT: Runtime> SyncWorker<RT> {
             tx,
             mutation_futures,
             mutation_sender,
+            mutations_in_flight: 0,
             action_futures: FuturesUnordered::new(),
             transition_future: None,
             update_scheduled: false,
             on_connect: Some((connect_timer(), on_connect)),
+            shutdown: future::pending().boxed().fuse(),
+            draining: false,
         }
     }
 
+    /// Drain the worker when `shutdown` resolves: stop starting mutations and
+    /// actions, and have `go` exit cleanly once the in-flight ones respond or
+    /// `SHUTDOWN_DRAIN_TIMEOUT` passes. Clients retry requests they didn't get
+    /// a response for when they reconnect.
+    pub fn drain_on_shutdown(&mut self, shutdown: impl Future<Output = ()> + Send + 'static) {
+        self.shutdown = shutdown.boxed().fuse();
+    }
+
+    /// Whether `go` exited because the server is shutting down.
+    pub fn is_draining(&self) -> bool {
+        self.draining
+    }
+
     fn schedule_update(&mut self) {
         self.update_scheduled = true;
     }
@@ -294,6 +330,8 @@ impl<RT: Runtime> SyncWorker<RT> {
     pub async fn go(&mut self) -> anyhow::Result<()> {
         let mut ping_timeout = self.rt.wait(HEARTBEAT_INTERVAL);
         let mut pending = future::pending().boxed().fuse();
+        let mut drain_timeout: Pin<Box<dyn FusedFuture<Output = ()> + Send>> =
+            Box::pin(future::pending());
 
         // Create a new subscription client for every sync socket. Thus we don't require
         // the subscription client to auto-recover on connection failures.
@@ -303,14 +341,38 @@ impl<RT: Runtime> SyncWorker<RT> {
         // Starts off as a future that is never ready, as there's no identity that may
         // expire.
         'top: loop {
+            if self.draining && self.mutations_in_flight == 0 && self.action_futures.is_empty() {
+                break 'top;
+            }
             let rt = self.rt.clone();
             self.state.validate()?;
             let maybe_response = select_biased! {
+                _ = &mut self.shutdown => {
+                    self.draining = true;
+                    drain_timeout = self.rt.wait(*SHUTDOWN_DRAIN_TIMEOUT);
+                    None
+                },
+                _ = drain_timeout => {
+                    tracing::warn!(
+                        "Shutting down sync worker with {} mutations and {} actions in flight",
+                        self.mutations_in_flight,
+                        self.action_futures.len(),
+                    );
+                    break 'top;
+                },
                 message = self.rx.recv().fuse() => {
                     let (message, received_time) = match message {
                         Some(m) => m,
                         None => break 'top,
                     };
+                    if self.draining
+                        && matches!(
+                            message,
+                            ClientMessage::Mutation { .. } | ClientMessage::Action { .. }
+                        )
+                    {
+                        continue 'top;
+                    }
                     self.handle_message(message).await?;
                     let delay = self.rt.monotonic_now() - received_time;
                     metrics::log_process_client_message_delay(delay);
@@ -326,6 +388,7 @@ impl<RT: Runtime> SyncWorker<RT> {
                         Some(m) => m?,
                         None => panic!("mutation_futures sender dropped prematurely"),
                     };
+                    self.mutations_in_flight -= 1;
                     self.schedule_update();
                     Some(message)
                 },
@@ -470,8 +533,10 @@ impl<RT: Runtime> SyncWorker<RT> {
                 udf_path,
                 args,
                 component_path,
+                request_context,
             } => {
                 let identity = self.state.identity(self.rt.system_time())?;
+                let request_context = RequestContext::new(request_context.unwrap_or_default())?;
                 let mutation_identifier =
                     self.state.session_id().map(|id| SessionRequestIdentifier {
                         session_id: id,
@@ -510,6 +575,7 @@ impl<RT: Runtime> SyncWorker<RT> {
                                     args,
                                     caller,
                                     mutation_identifier,
+                                    request_context,
                                 )
                                 .in_span(root)
                                 .await?
@@ -525,6 +591,7 @@ impl<RT: Runtime> SyncWorker<RT> {
                                     args,
                                     caller,
                                     mutation_identifier,
+                                    request_context,
                                 )
                                 .in_span(root)
                                 .await?
@@ -565,14 +632,17 @@ impl<RT: Runtime> SyncWorker<RT> {
                         anyhow::anyhow!("Failed to send to mutation channel: {err}")
                     }
                 })?;
+                self.mutations_in_flight += 1;
             },
             ClientMessage::Action {
                 request_id,
                 udf_path,
                 args,
                 component_path,
+                request_context,
             } => {
                 let identity = self.state.identity(self.rt.system_time())?;
+                let request_context = RequestContext::new(request_context.unwrap_or_default())?;
 
                 let api = self.api.clone();
                 let host = self.host.clone();
@@ -601,6 +671,7 @@ impl<RT: Runtime> SyncWorker<RT> {
                                 ExportPath::from(udf_path.canonicalize()),
                                 args,
                                 caller,
+                                request_context,
                             )
                             .in_span(root)
                             .await?
@@ -614,6 +685,7 @@ impl<RT: Runtime> SyncWorker<RT> {
                                 path,
                                 args,
                                 caller,
+                                request_context,
                             )
                             .in_span(root)
                             .await?
@@ -648,7 +720,12 @@ impl<RT: Runtime> SyncWorker<RT> {
             } => {
                 let identity = self
                     .api
-                    .authenticate(&self.host, RequestId::new(), auth_token)
+                    .authenticate_from(
+                        &self.host,
+                        RequestId::new(),
+                        auth_token,
+                        self.config.client_ip,
+                    )
                     .await?;
                 self.state.modify_identity(identity, base_version)?;
                 self.schedule_update();
@@ -811,6 +888,7 @@ impl<RT: Runtime> SyncWorker<RT> {
                                 result: udf_return.result,
                                 log_lines: udf_return.log_lines,
                                 journal: udf_return.journal,
+                                page_split: udf_return.page_split,
                             },
                             subscription,
                         )
@@ -859,12 +937,14 @@ impl<RT: Runtime> SyncWorker<RT> {
                     result,
                     log_lines,
                     journal,
+                    page_split,
                 } => {
                     let modification = self.state.complete_fetch(
                         query_id,
                         result,
                         log_lines,
                         journal,
+                        page_split,
                         subscription,
                     )?;
                     let Some(modification) = modification else {
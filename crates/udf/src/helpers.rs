@@ -1,6 +1,10 @@
 use common::{
     errors::JsError,
-    knobs::FUNCTION_MAX_ARGS_SIZE,
+    execution_context::ExecutionContext,
+    knobs::{
+        FUNCTION_MAX_ARGS_SIZE,
+        SCHEDULED_JOB_MAX_ARGS_SIZE,
+    },
 };
 use humansize::{
     FormatSize,
@@ -35,16 +39,28 @@ pub fn parse_udf_args(
         })
 }
 
+/// The maximum size of the arguments of a function run in `context`. The
+/// scheduler runs jobs with arguments up to `SCHEDULED_JOB_MAX_ARGS_SIZE`, but
+/// the functions those jobs call are held to `FUNCTION_MAX_ARGS_SIZE`.
+pub fn max_udf_args_size(context: &ExecutionContext) -> usize {
+    if context.is_root() && context.parent_scheduled_job.is_some() {
+        *SCHEDULED_JOB_MAX_ARGS_SIZE
+    } else {
+        *FUNCTION_MAX_ARGS_SIZE
+    }
+}
+
 pub fn validate_udf_args_size(
     path: &CanonicalizedUdfPath,
     args: &ConvexArray,
+    max_size: usize,
 ) -> Result<(), JsError> {
-    if args.size() > *FUNCTION_MAX_ARGS_SIZE {
+    if args.size() > max_size {
         return Err(JsError::from_message(format!(
             "Arguments for {} are too large (actual: {}, limit: {})",
             path.clone(),
             args.size().format_size(BINARY),
-            (*FUNCTION_MAX_ARGS_SIZE).format_size(BINARY),
+            max_size.format_size(BINARY),
         )));
     }
 
@@ -1,5 +1,6 @@
 use anyhow::Context;
 use common::{
+    bootstrap_model::components::ComponentState,
     components::{
         CanonicalizedComponentFunctionPath,
         ComponentId,
@@ -7,8 +8,10 @@ use common::{
         PublicFunctionPath,
         ResolvedComponentFunctionPath,
     },
+    document::ParsedDocument,
     errors::JsError,
     identity::InertIdentity,
+    knobs::FUNCTION_MAX_ARGS_SIZE,
     log_lines::LogLines,
     query_journal::QueryJournal,
     runtime::{
@@ -33,7 +36,12 @@ use errors::ErrorMetadata;
 use keybroker::Identity;
 use model::{
     backend_state::BackendStateModel,
+    component_versions::ComponentVersionsModel,
     components::ComponentsModel,
+    function_kill_switches::{
+        function_disabled_message,
+        FunctionKillSwitchesModel,
+    },
     modules::{
         function_validators::ReturnsValidator,
         module_versions::{
@@ -42,6 +50,13 @@ use model::{
         },
         ModuleModel,
     },
+    traffic_splits::{
+        types::{
+            TrafficSplitAssignment,
+            TrafficSplitVariant,
+        },
+        TrafficSplitModel,
+    },
     udf_config::UdfConfigModel,
     virtual_system_mapping,
 };
@@ -96,24 +111,55 @@ pub async fn validate_schedule_args<RT: Runtime>(
     // We do serialize the arguments, so this is likely our fault.
     let udf_args = parse_udf_args(&path.udf_path, udf_args)?;
 
+    // Resolve the component the function lives in, which may not be the one
+    // scheduling it, so the checks below look at that component's modules.
+    let Some((_, component_id)) =
+        BootstrapComponentsModel::new(tx).component_path_to_ids(&path.component)?
+    else {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidScheduledFunction",
+            format!(
+                "Attempted to schedule function {} in nonexistent component '{}'",
+                String::from(path.udf_path.clone().strip()),
+                String::from(path.component.clone()),
+            ),
+        ));
+    };
+    let component = BootstrapComponentsModel::new(tx)
+        .load_component(component_id)
+        .await?;
+    if component.is_some_and(|component| component.state == ComponentState::Unmounted) {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "UnmountedComponent",
+            format!(
+                "Attempted to schedule function {} in unmounted component '{}'. Make sure the \
+                 component is installed in your `convex.config.ts` file.",
+                String::from(path.udf_path.clone().strip()),
+                String::from(path.component.clone()),
+            ),
+        ));
+    }
+
     // Even though we might use different version of modules when executing,
     // we do validate that the scheduled function exists at time of scheduling.
     // We do it here instead of within transaction in order to leverage the module
     // cache.
-    let canonicalized = path.clone();
+    let resolved_path = ResolvedComponentFunctionPath {
+        component: component_id,
+        udf_path: path.udf_path.clone(),
+        component_path: Some(path.component.clone()),
+    };
     let module = ModuleModel::new(tx)
-        .get_metadata_for_function(canonicalized.clone())
+        .get_metadata_for_function_by_id(&resolved_path)
         .await?
         .with_context(|| {
-            let p = String::from(path.udf_path.module().clone());
-            let component = if path.component.is_root() {
-                "".to_string()
-            } else {
-                format!("{} ", String::from(path.clone().component))
-            };
             ErrorMetadata::bad_request(
                 "InvalidScheduledFunction",
-                format!("Attempted to schedule function at nonexistent path: {component}{p}",),
+                format!(
+                    "Attempted to schedule function at nonexistent path: {}{}",
+                    String::from(path.udf_path.module().clone()),
+                    path.component.in_component_str(),
+                ),
             )
         })?;
 
@@ -121,7 +167,7 @@ pub async fn validate_schedule_args<RT: Runtime>(
     // that scheduling was added after we started persisting the result
     // of analyze, we should always validate in practice. We will tighten
     // the interface and make AnalyzedResult non-optional in the future.
-    let function_name = canonicalized.udf_path.function_name();
+    let function_name = path.udf_path.function_name();
     if let Some(analyze_result) = &module.analyze_result {
         let found = analyze_result
             .functions
@@ -156,9 +202,19 @@ fn missing_or_internal_error(path: PublicFunctionPath) -> anyhow::Result<String>
 
 async fn udf_version<RT: Runtime>(
     path: &ResolvedComponentFunctionPath,
+    module_version: Option<u64>,
     tx: &mut Transaction<RT>,
 ) -> anyhow::Result<Result<Version, JsError>> {
-    let udf_config = UdfConfigModel::new(tx, path.component.into()).get().await?;
+    let udf_config = match module_version {
+        Some(version) => ComponentVersionsModel::new(tx)
+            .get(path.component, version)
+            .await?
+            .map(|component_version| component_version.into_value().udf_config),
+        None => UdfConfigModel::new(tx, path.component.into())
+            .get()
+            .await?
+            .map(ParsedDocument::into_value),
+    };
 
     let udf_version = match udf_config {
         Some(udf_config) if udf_config.server_version > DEPRECATION_THRESHOLD.npm.unsupported => {
@@ -209,6 +265,9 @@ pub struct ValidatedPathAndArgs {
     args: ConvexArray,
     // Not set for system modules.
     npm_version: Option<Version>,
+    // Set if the component has a traffic split, pinning this call to one of
+    // its recorded versions.
+    traffic_split: Option<TrafficSplitAssignment>,
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -235,6 +294,7 @@ impl Arbitrary for ValidatedPathAndArgs {
                 },
                 args,
                 npm_version: None,
+                traffic_split: None,
             }
         })
     }
@@ -247,6 +307,7 @@ impl ValidatedPathAndArgs {
     /// We want to use the same error message for "this function exists, but
     /// with the wrong visibility" and "this function does not exist" so we
     /// don't leak which non-public functions exist.
+    #[fastrace::trace]
     pub async fn new<RT: Runtime>(
         allowed_visibility: AllowedVisibility,
         tx: &mut Transaction<RT>,
@@ -254,13 +315,21 @@ impl ValidatedPathAndArgs {
         args: ConvexArray,
         expected_udf_type: UdfType,
     ) -> anyhow::Result<Result<ValidatedPathAndArgs, JsError>> {
-        Self::new_with_returns_validator(allowed_visibility, tx, path, args, expected_udf_type)
-            .await
-            .map(|r| r.map(|(path_and_args, _)| path_and_args))
+        Self::new_with_returns_validator(
+            allowed_visibility,
+            tx,
+            path,
+            args,
+            expected_udf_type,
+            *FUNCTION_MAX_ARGS_SIZE,
+        )
+        .await
+        .map(|r| r.map(|(path_and_args, _)| path_and_args))
     }
 
     /// Do argument validation and get returns validator without retrieving
-    /// the analyze result twice.
+    /// the analyze result twice. The arguments may be at most
+    /// `max_args_size` bytes.
 
     #[fastrace::trace]
     pub async fn new_with_returns_validator<RT: Runtime>(
@@ -269,6 +338,7 @@ impl ValidatedPathAndArgs {
         public_path: PublicFunctionPath,
         args: ConvexArray,
         expected_udf_type: UdfType,
+        max_args_size: usize,
     ) -> anyhow::Result<Result<(ValidatedPathAndArgs, ReturnsValidator), JsError>> {
         if public_path.is_system() {
             let path = match public_path {
@@ -296,6 +366,7 @@ impl ValidatedPathAndArgs {
                         path,
                         args,
                         npm_version: None,
+                        traffic_split: None,
                     },
                     ReturnsValidator::Unvalidated,
                 ))
@@ -340,7 +411,27 @@ impl ValidatedPathAndArgs {
             PublicFunctionPath::ResolvedComponent(path) => path,
         };
 
-        let udf_version = match udf_version(&path, tx).await? {
+        if let Some(kill_switch) = FunctionKillSwitchesModel::new(tx)
+            .disabled_by(path.component, &path.udf_path)
+            .await?
+        {
+            return Ok(Err(JsError::from_message(function_disabled_message(
+                &path.udf_path,
+                &kill_switch,
+            ))));
+        }
+
+        // Only queries and mutations are split. Actions, which may run in
+        // Node, always run the latest version.
+        let traffic_split = match expected_udf_type {
+            UdfType::Query | UdfType::Mutation => {
+                TrafficSplitModel::new(tx).assign(&path, &args).await?
+            },
+            UdfType::Action | UdfType::HttpAction => None,
+        };
+        let module_version = traffic_split.map(|assignment| assignment.version);
+
+        let udf_version = match udf_version(&path, module_version, tx).await? {
             Ok(udf_version) => udf_version,
             Err(e) => return Ok(Err(e)),
         };
@@ -349,7 +440,7 @@ impl ValidatedPathAndArgs {
         //
         //
         let Ok(analyzed_function) = ModuleModel::new(tx)
-            .get_analyzed_function_by_id(&path)
+            .get_analyzed_function_at_version(&path, module_version)
             .await?
         else {
             return Ok(Err(JsError::from_message(missing_or_internal_error(
@@ -371,6 +462,8 @@ impl ValidatedPathAndArgs {
             expected_udf_type,
             analyzed_function,
             udf_version,
+            traffic_split,
+            max_args_size,
         )? {
             Ok(validated_udf_path_and_args) => {
                 Ok(Ok((validated_udf_path_and_args, returns_validator)))
@@ -387,6 +480,8 @@ impl ValidatedPathAndArgs {
         expected_udf_type: UdfType,
         analyzed_function: AnalyzedFunction,
         version: Version,
+        traffic_split: Option<TrafficSplitAssignment>,
+        max_args_size: usize,
     ) -> anyhow::Result<Result<ValidatedPathAndArgs, JsError>> {
         let identity = tx.identity();
         match identity {
@@ -421,7 +516,7 @@ impl ValidatedPathAndArgs {
             ))));
         }
 
-        match validate_udf_args_size(&path.udf_path, &args) {
+        match validate_udf_args_size(&path.udf_path, &args, max_args_size) {
             Ok(()) => (),
             Err(err) => return Ok(Err(err)),
         }
@@ -445,6 +540,7 @@ impl ValidatedPathAndArgs {
             path,
             args,
             npm_version: Some(version),
+            traffic_split,
         }))
     }
 
@@ -478,6 +574,7 @@ impl ValidatedPathAndArgs {
             },
             args,
             npm_version,
+            traffic_split: None,
         }
     }
 
@@ -493,6 +590,16 @@ impl ValidatedPathAndArgs {
         &self.npm_version
     }
 
+    pub fn traffic_split(&self) -> Option<TrafficSplitAssignment> {
+        self.traffic_split
+    }
+
+    /// The recorded component version to load modules from, or `None` to use
+    /// the latest.
+    pub fn module_version(&self) -> Option<u64> {
+        self.traffic_split.map(|assignment| assignment.version)
+    }
+
     pub fn from_proto(
         pb::common::ValidatedPathAndArgs {
             path,
@@ -500,6 +607,8 @@ impl ValidatedPathAndArgs {
             npm_version,
             component_path,
             component_id,
+            module_version,
+            is_candidate,
         }: pb::common::ValidatedPathAndArgs,
     ) -> anyhow::Result<Self> {
         let args_json: JsonValue =
@@ -518,6 +627,14 @@ impl ValidatedPathAndArgs {
             },
             args,
             npm_version: npm_version.map(|v| Version::parse(&v)).transpose()?,
+            traffic_split: module_version.map(|version| TrafficSplitAssignment {
+                version,
+                variant: if is_candidate.unwrap_or(false) {
+                    TrafficSplitVariant::Candidate
+                } else {
+                    TrafficSplitVariant::Stable
+                },
+            }),
         })
     }
 }
@@ -530,6 +647,7 @@ impl TryFrom<ValidatedPathAndArgs> for pb::common::ValidatedPathAndArgs {
             path,
             args,
             npm_version,
+            traffic_split,
         }: ValidatedPathAndArgs,
     ) -> anyhow::Result<Self> {
         let args_json = JsonValue::from(args);
@@ -543,6 +661,9 @@ impl TryFrom<ValidatedPathAndArgs> for pb::common::ValidatedPathAndArgs {
             npm_version: npm_version.map(|v| v.to_string()),
             component_path,
             component_id: path.component.serialize_to_string(),
+            module_version: traffic_split.map(|assignment| assignment.version),
+            is_candidate: traffic_split
+                .map(|assignment| assignment.variant == TrafficSplitVariant::Candidate),
         })
     }
 }
@@ -631,7 +752,16 @@ impl ValidatedHttpPath {
             udf_path: path.udf_path,
             component_path: Some(path.component),
         };
-        let udf_version = match udf_version(&path, tx).await? {
+        if let Some(kill_switch) = FunctionKillSwitchesModel::new(tx)
+            .disabled_by(path.component, &path.udf_path)
+            .await?
+        {
+            return Ok(Err(JsError::from_message(function_disabled_message(
+                &path.udf_path,
+                &kill_switch,
+            ))));
+        }
+        let udf_version = match udf_version(&path, None, tx).await? {
             Ok(udf_version) => udf_version,
             Err(e) => return Ok(Err(e)),
         };
@@ -174,6 +174,10 @@ impl CallType {
         }
     }
 
+    fn tracks_heap(&self) -> bool {
+        matches!(self, CallType::UncachedQuery | CallType::Mutation { .. })
+    }
+
     fn duration_millis(&self) -> u64 {
         match self {
             CallType::Action { duration, .. } | CallType::HttpAction { duration, .. } => {
@@ -236,6 +240,10 @@ impl UsageCounter {
                 tag: call_type.tag().to_string(),
                 memory_megabytes: call_type.memory_megabytes(),
                 duration_millis: call_type.duration_millis(),
+                peak_heap_used_bytes: call_type
+                    .tracks_heap()
+                    .then_some(stats.peak_heap_used_bytes),
+                cpu_time_micros: stats.cpu_time_micros,
                 environment: call_type.environment(),
                 is_tracked: should_track_calls,
                 response_sha256: call_type.response_sha256(),
@@ -643,6 +651,20 @@ impl FunctionUsageTracker {
             .vector_egress_size
             .mutate_entry_or_default(key, |count| *count += egress_size);
     }
+
+    /// Records a sample of the JavaScript heap the function is using, keeping
+    /// the largest one seen.
+    pub fn track_heap_used(&self, heap_used_bytes: u64) {
+        let mut state = self.state.lock();
+        state.peak_heap_used_bytes = state.peak_heap_used_bytes.max(heap_used_bytes);
+    }
+
+    /// Records CPU time the function spent running JavaScript.
+    pub fn track_cpu_time(&self, cpu_time: Duration) {
+        let cpu_time_micros = u64::try_from(cpu_time.as_micros()).unwrap_or(u64::MAX);
+        let mut state = self.state.lock();
+        state.cpu_time_micros = state.cpu_time_micros.saturating_add(cpu_time_micros);
+    }
 }
 
 // For UDFs, we track storage at the per UDF level, no finer. So we can just
@@ -714,6 +736,12 @@ pub struct FunctionUsageStats {
     pub database_egress_rows: WithHeapSize<BTreeMap<(ComponentPath, TableName), u64>>,
     pub vector_ingress_size: WithHeapSize<BTreeMap<(ComponentPath, TableName), u64>>,
     pub vector_egress_size: WithHeapSize<BTreeMap<(ComponentPath, TableName), u64>>,
+    /// The most JavaScript heap the function used at once, sampled while it
+    /// ran. Only tracked for queries and mutations.
+    pub peak_heap_used_bytes: u64,
+    /// CPU time spent running JavaScript, excluding time spent in syscalls.
+    /// Zero if the platform can't measure it.
+    pub cpu_time_micros: u64,
 }
 
 impl FunctionUsageStats {
@@ -725,6 +753,8 @@ impl FunctionUsageStats {
             storage_write_bytes: self.storage_ingress_size.values().sum(),
             vector_index_read_bytes: self.vector_egress_size.values().sum(),
             vector_index_write_bytes: self.vector_ingress_size.values().sum(),
+            peak_heap_used_bytes: self.peak_heap_used_bytes,
+            cpu_time_micros: self.cpu_time_micros,
         }
     }
 
@@ -764,6 +794,8 @@ impl FunctionUsageStats {
             self.vector_egress_size
                 .mutate_entry_or_default(key.clone(), |count| *count += egress_size);
         }
+        self.peak_heap_used_bytes = self.peak_heap_used_bytes.max(other.peak_heap_used_bytes);
+        self.cpu_time_micros = self.cpu_time_micros.saturating_add(other.cpu_time_micros);
     }
 }
 
@@ -825,6 +857,8 @@ mod usage_arbitrary {
                     0..=4,
                 )
                 .prop_map(WithHeapSize::from),
+                0..=1u64 << 30,
+                0..=1u64 << 30,
             );
             strategies
                 .prop_map(
@@ -837,6 +871,8 @@ mod usage_arbitrary {
                         database_egress_rows,
                         vector_ingress_size,
                         vector_egress_size,
+                        peak_heap_used_bytes,
+                        cpu_time_micros,
                     )| FunctionUsageStats {
                         storage_calls,
                         storage_ingress_size,
@@ -846,6 +882,8 @@ mod usage_arbitrary {
                         database_egress_rows,
                         vector_ingress_size,
                         vector_egress_size,
+                        peak_heap_used_bytes,
+                        cpu_time_micros,
                     },
                 )
                 .boxed()
@@ -922,6 +960,8 @@ impl From<FunctionUsageStats> for FunctionUsageStatsProto {
             database_egress_rows: to_by_tag_count(stats.database_egress_rows.into_iter()),
             vector_ingress_size: to_by_tag_count(stats.vector_ingress_size.into_iter()),
             vector_egress_size: to_by_tag_count(stats.vector_egress_size.into_iter()),
+            peak_heap_used_bytes: Some(stats.peak_heap_used_bytes),
+            cpu_time_micros: Some(stats.cpu_time_micros),
         }
     }
 }
@@ -950,6 +990,8 @@ impl TryFrom<FunctionUsageStatsProto> for FunctionUsageStats {
             database_egress_size,
             vector_ingress_size,
             vector_egress_size,
+            peak_heap_used_bytes: stats.peak_heap_used_bytes.unwrap_or_default(),
+            cpu_time_micros: stats.cpu_time_micros.unwrap_or_default(),
         })
     }
 }
@@ -964,6 +1006,8 @@ pub struct AggregatedFunctionUsageStats {
     pub storage_write_bytes: u64,
     pub vector_index_read_bytes: u64,
     pub vector_index_write_bytes: u64,
+    pub peak_heap_used_bytes: u64,
+    pub cpu_time_micros: u64,
 }
 
 #[cfg(test)]
@@ -41,6 +41,7 @@ use sync_types::{
     ErrorPayload,
     FunctionName,
     LogLinesMessage,
+    PageSplitMessage,
     ServerMessage,
     SessionId,
     StateModification,
@@ -883,6 +884,12 @@ impl HeapSize for LogLinesMessage {
     }
 }
 
+impl HeapSize for PageSplitMessage {
+    fn heap_size(&self) -> usize {
+        self.split_cursor.heap_size()
+    }
+}
+
 impl<V: HeapSize> HeapSize for ServerMessage<V> {
     fn heap_size(&self) -> usize {
         match self {
@@ -926,7 +933,13 @@ impl<V: HeapSize> HeapSize for StateModification<V> {
                 value,
                 log_lines,
                 journal,
-            } => value.heap_size() + log_lines.heap_size() + journal.heap_size(),
+                page_split,
+            } => {
+                value.heap_size()
+                    + log_lines.heap_size()
+                    + journal.heap_size()
+                    + page_split.heap_size()
+            },
             StateModification::QueryFailed {
                 query_id: _,
                 error_message,
@@ -44,6 +44,7 @@ export class ConvexHttpClient {
   private auth?: string;
   private adminAuth?: string;
   private encodedTsPromise?: Promise<string>;
+  private sessionToken?: string;
   private debug: boolean;
   private fetchOptions?: FetchOptions;
   private logger: Logger;
@@ -253,6 +254,9 @@ export class ConvexHttpClient {
     const endpoint = timestamp
       ? `${this.address}/api/query_at_ts`
       : `${this.address}/api/query`;
+    if (!timestamp && this.sessionToken) {
+      headers["Convex-Session-Token"] = this.sessionToken;
+    }
 
     const response = await localFetch(endpoint, {
       ...this.fetchOptions,
@@ -263,6 +267,8 @@ export class ConvexHttpClient {
     if (!response.ok && response.status !== STATUS_CODE_UDF_FAILED) {
       throw new Error(await response.text());
     }
+    this.sessionToken =
+      response.headers.get("Convex-Session-Token") ?? this.sessionToken;
     const respJSON = await response.json();
 
     if (this.debug) {
@@ -314,6 +320,9 @@ export class ConvexHttpClient {
     } else if (this.auth) {
       headers["Authorization"] = `Bearer ${this.auth}`;
     }
+    if (this.sessionToken) {
+      headers["Convex-Session-Token"] = this.sessionToken;
+    }
     const localFetch = specifiedFetch || fetch;
     const response = await localFetch(`${this.address}/api/mutation`, {
       ...this.fetchOptions,
@@ -324,6 +333,8 @@ export class ConvexHttpClient {
     if (!response.ok && response.status !== STATUS_CODE_UDF_FAILED) {
       throw new Error(await response.text());
     }
+    this.sessionToken =
+      response.headers.get("Convex-Session-Token") ?? this.sessionToken;
     const respJSON = await response.json();
     if (this.debug) {
       for (const line of respJSON.logLines ?? []) {
@@ -374,6 +385,9 @@ export class ConvexHttpClient {
     } else if (this.auth) {
       headers["Authorization"] = `Bearer ${this.auth}`;
     }
+    if (this.sessionToken) {
+      headers["Convex-Session-Token"] = this.sessionToken;
+    }
     const localFetch = specifiedFetch || fetch;
     const response = await localFetch(`${this.address}/api/action`, {
       ...this.fetchOptions,
@@ -384,6 +398,8 @@ export class ConvexHttpClient {
     if (!response.ok && response.status !== STATUS_CODE_UDF_FAILED) {
       throw new Error(await response.text());
     }
+    this.sessionToken =
+      response.headers.get("Convex-Session-Token") ?? this.sessionToken;
     const respJSON = await response.json();
     if (this.debug) {
       for (const line of respJSON.logLines ?? []) {
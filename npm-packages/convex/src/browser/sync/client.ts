@@ -196,6 +196,7 @@ export class BaseConvexClient {
   private readonly debug: boolean;
   private readonly logger: Logger;
   private maxObservedTimestamp: TS | undefined;
+  private requestContext: Record<string, string> | undefined;
 
   /**
    * @param address - The url of your Convex deployment, often provided
@@ -556,6 +557,17 @@ export class BaseConvexClient {
     this.webSocketManager.sendMessage(message);
   }
 
+  /**
+   * Set context, such as trace ids or a locale, to attach to subsequent
+   * mutations and actions. Functions read it with `ctx.getRequestContext()`.
+   *
+   * @param requestContext - String values to attach, or `undefined` to stop
+   * attaching context.
+   */
+  setRequestContext(requestContext: Record<string, string> | undefined) {
+    this.requestContext = requestContext;
+  }
+
   /**
    * Subscribe to a query function.
    *
@@ -786,6 +798,7 @@ export class BaseConvexClient {
       udfPath,
       componentPath,
       args: [convexToJson(mutationArgs)],
+      requestContext: this.requestContext,
     };
     const mightBeSent = this.webSocketManager.sendMessage(message);
     const mutationPromise = this.requestManager.request(message, mightBeSent);
@@ -836,6 +849,7 @@ export class BaseConvexClient {
       udfPath,
       componentPath,
       args: [convexToJson(actionArgs)],
+      requestContext: this.requestContext,
     };
 
     const mightBeSent = this.webSocketManager.sendMessage(message);
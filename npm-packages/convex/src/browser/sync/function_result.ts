@@ -13,6 +13,14 @@ export type FunctionSuccess = {
   success: true;
   value: Value;
   logLines: string[];
+  /**
+   * Where the server suggests splitting the page of a paginated query, if
+   * the page grew too large.
+   */
+  pageSplit?: {
+    splitCursor: string;
+    pageStatus: "SplitRecommended" | "SplitRequired";
+  };
 };
 export type FunctionFailure = {
   success: false;
@@ -155,6 +155,8 @@ export type MutationRequest = {
   // Execute the mutation on a specific component.
   // Only admin auth is allowed to run mutations on non-root components.
   componentPath?: string;
+  // Opaque context readable by the function and anything it calls or schedules.
+  requestContext?: Record<string, string>;
 };
 
 export type ActionRequest = {
@@ -165,6 +167,8 @@ export type ActionRequest = {
   // Execute the action on a specific component.
   // Only admin auth is allowed to run actions on non-root components.
   componentPath?: string;
+  // Opaque context readable by the function and anything it calls or schedules.
+  requestContext?: Record<string, string>;
 };
 
 export type AdminAuthentication = {
@@ -228,6 +232,16 @@ export type StateVersion = {
 };
 type EncodedStateVersion = Omit<StateVersion, "ts"> & { ts: EncodedTS };
 
+/**
+ * Sent with a query result when the paginated page the query read grew too
+ * large. The page can be replaced by two pages split at `splitCursor`, so
+ * only that page needs to re-execute.
+ */
+export type PageSplit = {
+  splitCursor: string;
+  pageStatus: "SplitRecommended" | "SplitRequired";
+};
+
 type StateModification =
   | {
       type: "QueryUpdated";
@@ -236,6 +250,7 @@ type StateModification =
       logLines: LogLines;
       // Optional because old backend versions don't send this.
       journal?: QueryJournal;
+      pageSplit?: PageSplit;
     }
   | {
       type: "QueryFailed";
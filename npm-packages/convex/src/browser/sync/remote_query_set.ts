@@ -46,6 +46,9 @@ export class RemoteQuerySet {
             success: true,
             value,
             logLines: modification.logLines,
+            ...(modification.pageSplit
+              ? { pageSplit: modification.pageSplit }
+              : {}),
           });
           break;
         }
@@ -40,6 +40,7 @@ import {
 import { typeCheckFunctionsInMode, TypeCheckMode } from "./typecheck.js";
 import { withTmpDir } from "../../bundler/fs.js";
 import { handleDebugBundlePath } from "./debugBundlePath.js";
+import { collectStaticAssets } from "./staticAssets.js";
 import chalk from "chalk";
 import { StartPushRequest, StartPushResponse } from "./deployApi/startPush.js";
 import {
@@ -284,6 +285,7 @@ async function startComponentsPushAndCodegen(
     appDefinition,
     componentDefinitions,
     nodeDependencies: appImplementation.externalNodeDependencies,
+    staticAssets: await collectStaticAssets(ctx, configPath, projectConfig),
   };
   if (options.writePushRequest) {
     const pushRequestPath = path.resolve(options.writePushRequest);
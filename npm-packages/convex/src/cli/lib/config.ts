@@ -51,6 +51,12 @@ export interface AuthInfo {
   applicationID: string;
   // Domain used for authentication. Corresponds to the `iss` field in an OIDC token.
   domain: string;
+  // How long the deployment caches the provider's metadata and JWKS.
+  jwksCacheTtlSeconds?: number;
+  // For the built-in auth provider, the function that emails magic links.
+  magicLinkAction?: string;
+  // For the built-in auth provider, the origin of the site that offers passkey sign-in.
+  passkeyOrigin?: string;
 }
 
 /** Type representing Convex project configuration. */
@@ -60,6 +66,13 @@ export interface ProjectConfig {
     externalPackages: string[];
   };
   generateCommonJSApi: boolean;
+  // Directory of files to serve from the deployment's HTTP actions URL,
+  // relative to `convex.json`.
+  staticAssets?: {
+    dir: string;
+    // File to serve for page navigations that don't match anything else.
+    spaFallback?: string;
+  };
   // deprecated
   project?: string;
   // deprecated
@@ -155,6 +168,23 @@ export async function parseProjectConfig(
     });
   }
 
+  if (obj.staticAssets !== undefined) {
+    if (
+      typeof obj.staticAssets !== "object" ||
+      obj.staticAssets === null ||
+      typeof obj.staticAssets.dir !== "string" ||
+      (obj.staticAssets.spaFallback !== undefined &&
+        typeof obj.staticAssets.spaFallback !== "string")
+    ) {
+      return await ctx.crash({
+        exitCode: 1,
+        errorType: "invalid filesystem data",
+        printedMessage:
+          "Expected `staticAssets` in `convex.json` to be an object with a `dir` string and an optional `spaFallback` string",
+      });
+    }
+  }
+
   // Allow the `authInfo` key to be omitted, treating it as an empty list of providers.
   if (obj.authInfo !== undefined) {
     if (!isAuthInfos(obj.authInfo)) {
@@ -8,6 +8,7 @@ export const componentArgumentValidator = looseObject({
   type: z.literal("value"),
   // Validator serialized to JSON.
   value: z.string(),
+  optional: z.optional(z.boolean()),
 });
 
 export const componentDefinitionType = z.union([
@@ -29,6 +30,9 @@ export const componentInstantiation = looseObject({
   name: identifier,
   path: componentDefinitionPath,
   args: z.nullable(z.array(z.tuple([identifier, componentArgument]))),
+  grants: z.optional(
+    z.array(z.tuple([z.string(), z.enum(["read", "write"])])),
+  ),
 });
 
 export type ComponentExports =
@@ -57,6 +61,12 @@ export const componentDefinitionMetadata = looseObject({
     type: z.literal("branch"),
     branch: z.array(z.tuple([identifier, componentExports])),
   }),
+  lifecycle: z.optional(
+    looseObject({
+      onMount: z.optional(z.string()),
+      onUnmount: z.optional(z.string()),
+    }),
+  ),
 });
 
 export const evaluatedComponentDefinition = looseObject({
@@ -10,6 +10,20 @@ import {
 import { authInfo } from "./types.js";
 import { looseObject } from "./utils.js";
 
+export const staticAssets = looseObject({
+  files: z.array(
+    looseObject({
+      path: z.string(),
+      // Base64 encoded.
+      content: z.string(),
+      contentType: z.optional(z.string()),
+      cacheControl: z.optional(z.string()),
+    }),
+  ),
+  spaFallback: z.optional(z.string()),
+});
+export type StaticAssets = z.infer<typeof staticAssets>;
+
 export const startPushRequest = looseObject({
   adminKey: z.string(),
   dryRun: z.boolean(),
@@ -20,6 +34,8 @@ export const startPushRequest = looseObject({
   componentDefinitions: z.array(componentDefinitionConfig),
 
   nodeDependencies: z.array(nodeDependency),
+
+  staticAssets: z.optional(staticAssets),
 });
 export type StartPushRequest = z.infer<typeof startPushRequest>;
 
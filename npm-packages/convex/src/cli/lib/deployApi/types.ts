@@ -7,6 +7,9 @@ export type Reference = z.infer<typeof reference>;
 export const authInfo = looseObject({
   applicationID: z.string(),
   domain: z.string(),
+  jwksCacheTtlSeconds: z.optional(z.number()),
+  magicLinkAction: z.optional(z.string()),
+  passkeyOrigin: z.optional(z.string()),
 });
 export type AuthInfo = z.infer<typeof authInfo>;
 
@@ -0,0 +1,57 @@
+import path from "path";
+import { Context } from "../../bundler/context.js";
+import { ProjectConfig } from "./config.js";
+import { StaticAssets } from "./deployApi/startPush.js";
+
+// Collect the files under the static assets directory from `convex.json`,
+// keyed by the URL path they're served from.
+export async function collectStaticAssets(
+  ctx: Context,
+  configPath: string,
+  projectConfig: ProjectConfig,
+): Promise<StaticAssets | undefined> {
+  const config = projectConfig.staticAssets;
+  if (!config) {
+    return undefined;
+  }
+  const dir = path.resolve(path.dirname(configPath), config.dir);
+  if (!ctx.fs.exists(dir) || !ctx.fs.stat(dir).isDirectory()) {
+    return await ctx.crash({
+      exitCode: 1,
+      errorType: "invalid filesystem data",
+      printedMessage: `Static assets directory ${config.dir} doesn't exist`,
+    });
+  }
+  const files: StaticAssets["files"] = [];
+  const walk = async (current: string) => {
+    for (const entry of ctx.fs.listDir(current)) {
+      const entryPath = path.join(current, entry.name);
+      if (entry.isDirectory()) {
+        await walk(entryPath);
+      } else if (entry.isFile()) {
+        const chunks: Buffer[] = [];
+        for await (const chunk of ctx.fs.createReadStream(entryPath, {})) {
+          chunks.push(chunk as Buffer);
+        }
+        const relative = path.relative(dir, entryPath).split(path.sep);
+        files.push({
+          path: "/" + relative.join("/"),
+          content: Buffer.concat(chunks).toString("base64"),
+        });
+      }
+    }
+  };
+  await walk(dir);
+  const spaFallback =
+    config.spaFallback === undefined
+      ? undefined
+      : "/" + config.spaFallback.replace(/^\/+/, "");
+  if (spaFallback && !files.some((file) => file.path === spaFallback)) {
+    return await ctx.crash({
+      exitCode: 1,
+      errorType: "invalid filesystem data",
+      printedMessage: `SPA fallback ${config.spaFallback} isn't in ${config.dir}`,
+    });
+  }
+  return { files, spaFallback };
+}
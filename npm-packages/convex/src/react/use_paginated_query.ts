@@ -143,8 +143,8 @@ const completeSplitQuery =
  *
  * If the query reference or arguments change, the pagination state will be reset
  * to the first page. Similarly, if any of the pages result in an InvalidCursor
- * error or an error associated with too much data, the pagination state will also
- * reset to the first page.
+ * or RestartPagination error or an error associated with too much data, the
+ * pagination state will also reset to the first page.
  *
  * To learn more about pagination, see [Paginated Queries](https://docs.convex.dev/database/pagination).
  *
@@ -249,15 +249,19 @@ export function usePaginatedQuery<Query extends PaginatedQueryReference>(
       if (currResult instanceof Error) {
         if (
           currResult.message.includes("InvalidCursor") ||
+          currResult.message.includes("RestartPagination") ||
           (currResult instanceof ConvexError &&
             typeof currResult.data === "object" &&
             currResult.data?.isConvexSystemError === true &&
-            currResult.data?.paginationError === "InvalidCursor")
+            (currResult.data?.paginationError === "InvalidCursor" ||
+              currResult.data?.paginationError === "RestartPagination"))
         ) {
           // - InvalidCursor: If the cursor is invalid, probably the paginated
           // database query was data-dependent and changed underneath us. The
           // cursor in the params or journal no longer matches the current
           // database query.
+          // - RestartPagination: The index the query reads from was redefined
+          // since the cursor was created, so its position is meaningless.
 
           // In all cases, we want to restart pagination to throw away all our
           // existing cursors.
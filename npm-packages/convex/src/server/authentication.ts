@@ -164,3 +164,123 @@ export interface Auth {
    */
   getUserIdentity(): Promise<UserIdentity | null>;
 }
+
+/**
+ * Options for {@link AuthWithServiceTokens.mintServiceToken}.
+ *
+ * @public
+ */
+export interface MintServiceTokenOptions {
+  /**
+   * How long the token is valid for, in seconds. Tokens can be valid for at
+   * most 24 hours.
+   */
+  expiresInSeconds: number;
+  /**
+   * Fields of the caller's {@link UserIdentity} to include in the token, e.g.
+   * `["subject", "email"]`. The caller must be authenticated if any are given.
+   */
+  identityFields?: (keyof UserIdentity)[];
+  /**
+   * Custom claims to include in the token. At most 4KB when serialized.
+   */
+  claims?: Record<string, JSONValue>;
+  /**
+   * Files the token grants read access to. Passing the token as the `token`
+   * query parameter of a file's URL lets anyone holding it download the file.
+   */
+  storageIds?: string[];
+}
+
+/**
+ * The contents of a valid service token, returned by
+ * {@link AuthWithServiceTokens.verifyServiceToken}.
+ *
+ * @public
+ */
+export interface ServiceTokenContents {
+  /**
+   * The identity fields included when the token was minted, or `null` if none
+   * were.
+   */
+  identity: Partial<UserIdentity> | null;
+  /**
+   * The custom claims included when the token was minted.
+   */
+  claims: Record<string, JSONValue>;
+  /**
+   * When the token was minted, in milliseconds since the Unix epoch.
+   */
+  issuedAt: number;
+  /**
+   * When the token expires, in milliseconds since the Unix epoch.
+   */
+  expiresAt: number;
+}
+
+/**
+ * {@link Auth} for mutations and actions, which can also mint and verify
+ * short-lived signed tokens to hand to other services or clients.
+ *
+ * @public
+ */
+export interface AuthWithServiceTokens extends Auth {
+  /**
+   * Mint a short-lived token signed by this deployment, carrying a subset of
+   * the caller's identity and custom claims.
+   *
+   * @returns A promise that resolves to the opaque token string.
+   */
+  mintServiceToken(options: MintServiceTokenOptions): Promise<string>;
+
+  /**
+   * Verify a token minted by {@link AuthWithServiceTokens.mintServiceToken} in
+   * this component, e.g. one passed to an HTTP action.
+   *
+   * @returns A promise that resolves to the token's contents, or `null` if the
+   * token is invalid or has expired.
+   */
+  verifyServiceToken(token: string): Promise<ServiceTokenContents | null>;
+}
+
+/**
+ * Options for {@link MutationAuth.promoteAnonymousIdentity}.
+ *
+ * @public
+ */
+export interface PromoteAnonymousIdentityOptions {
+  /**
+   * A valid ID token for the anonymous identity, from the
+   * `/api/anonymous_token` endpoint.
+   */
+  token: string;
+  /**
+   * The device ID the anonymous identity was issued to.
+   */
+  deviceId: string;
+}
+
+/**
+ * {@link Auth} for mutations, which can also promote an anonymous identity to
+ * the signed-in user.
+ *
+ * @public
+ */
+export interface MutationAuth extends AuthWithServiceTokens {
+  /**
+   * Promote an anonymous identity to the signed-in user calling this
+   * mutation. Move the anonymous identity's data to the user in the same
+   * mutation, so both happen atomically. An anonymous identity can only be
+   * promoted once, and can't sign in again afterwards.
+   *
+   * Anonymous identities are enabled by adding a provider with domain
+   * `"https://anonymous.convex.dev"` and applicationID `"anonymous"` to
+   * `auth.config.js`.
+   *
+   * @returns A promise that resolves to the anonymous identity's
+   * {@link UserIdentity.tokenIdentifier}.
+   */
+  promoteAnonymousIdentity(
+    options: PromoteAnonymousIdentityOptions,
+  ): Promise<string>;
+}
@@ -17,7 +17,7 @@ export type ComponentDefinitionExport = {
 export type ComponentDefinitionType = {
   type: "childComponent";
   name: string;
-  args: [string, { type: "value"; value: string }][];
+  args: [string, { type: "value"; value: string; optional: boolean }][];
 };
 export type AppDefinitionType = { type: "app" };
 
@@ -42,6 +42,7 @@ export type ComponentDefinitionAnalysis = {
   childComponents: ComponentInstantiation[];
   httpMounts: Record<string, HttpMount>;
   exports: ComponentExport;
+  lifecycle?: { onMount?: string; onUnmount?: string };
 };
 export type AppDefinitionAnalysis = {
   definitionType: AppDefinitionType;
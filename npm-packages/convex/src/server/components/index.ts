@@ -85,7 +85,9 @@ export type ComponentDefinition<Exports extends ComponentExports = any> = {
   /**
    * Install a component with the given definition in this component definition.
    *
-   * Takes a component definition and an optional name.
+   * Takes a component definition, an optional name, and optional grants
+   * giving the component read or write access to documents in this
+   * component's tables by ID.
    *
    * For editor tooling this method expects a {@link ComponentDefinition}
    * but at runtime the object that is imported will be a {@link ImportedComponentDefinition}
@@ -94,6 +96,7 @@ export type ComponentDefinition<Exports extends ComponentExports = any> = {
     definition: Definition,
     options?: {
       name?: string;
+      grants?: Record<string, TableAccess>;
     },
   ): InstalledComponent<Definition>;
 
@@ -119,7 +122,9 @@ export type AppDefinition = {
   /**
    * Install a component with the given definition in this component definition.
    *
-   * Takes a component definition and an optional name.
+   * Takes a component definition, an optional name, and optional grants
+   * giving the component read or write access to documents in this
+   * component's tables by ID.
    *
    * For editor tooling this method expects a {@link ComponentDefinition}
    * but at runtime the object that is imported will be a {@link ImportedComponentDefinition}
@@ -128,10 +133,19 @@ export type AppDefinition = {
     definition: Definition,
     options?: {
       name?: string;
+      grants?: Record<string, TableAccess>;
     },
   ): InstalledComponent<Definition>;
 };
 
+/**
+ * Access a parent component grants a child component to one of its tables.
+ *
+ * This is a feature of components, which are in beta.
+ * This API is unstable and may change in subsequent releases.
+ */
+export type TableAccess = "read" | "write";
+
 interface ExportTree {
   // Tree with serialized `Reference`s as leaves.
   [key: string]: string | ExportTree;
@@ -143,6 +157,7 @@ type CommonDefinitionData = {
     string,
     ImportedComponentDefinition,
     Record<string, any> | null,
+    Record<string, TableAccess>,
   ][];
   _exportTree: ExportTree;
 };
@@ -151,6 +166,29 @@ type ComponentDefinitionData = CommonDefinitionData & {
   _args: PropertyValidators;
   _name: string;
   _onInitCallbacks: Record<string, (argsStr: string) => string>;
+  _lifecycle: ComponentLifecycleHooks;
+};
+
+/**
+ * Mutations a component runs when it's mounted or unmounted.
+ *
+ * Each hook is the path of a mutation in the component, like
+ * `"lifecycle:seed"`. Hooks run in the same transaction as the push that mounts
+ * or unmounts the component, so if a hook throws the push fails.
+ *
+ * This is a feature of components, which are in beta.
+ * This API is unstable and may change in subsequent releases.
+ */
+export type ComponentLifecycleHooks = {
+  /**
+   * Runs once, when the component is first installed.
+   */
+  onMount?: string;
+  /**
+   * Runs when the component is removed from its parent, before its
+   * functions are deleted.
+   */
+  onUnmount?: string;
 };
 type AppDefinitionData = CommonDefinitionData;
 
@@ -204,6 +242,7 @@ function use<Definition extends ComponentDefinition<any>>(
   definition: Definition,
   options?: {
     name?: string;
+    grants?: Record<string, TableAccess>;
   },
 ): InstalledComponent<Definition> {
   // At runtime an imported component will have this shape.
@@ -220,7 +259,12 @@ function use<Definition extends ComponentDefinition<any>>(
     importedComponentDefinition.defaultName ||
     // can be removed once backend is out
     importedComponentDefinition.componentDefinitionPath.split("/").pop()!;
-  this._childComponents.push([name, importedComponentDefinition, {}]);
+  this._childComponents.push([
+    name,
+    importedComponentDefinition,
+    {},
+    options?.grants ?? {},
+  ]);
   return new InstalledComponent(definition, name);
 }
 
@@ -270,13 +314,15 @@ function serializeChildComponents(
     string,
     ImportedComponentDefinition,
     Record<string, any> | null,
+    Record<string, TableAccess>,
   ][],
 ): {
   name: string;
   path: string;
   args: [string, { type: "value"; value: string }][] | null;
+  grants: [string, TableAccess][];
 }[] {
-  return childComponents.map(([name, definition, p]) => {
+  return childComponents.map(([name, definition, p, grants]) => {
     let args: [string, { type: "value"; value: string }][] | null = null;
     if (p !== null) {
       args = [];
@@ -301,6 +347,7 @@ function serializeChildComponents(
       name: name!,
       path: path!,
       args,
+      grants: Object.entries(grants),
     };
   });
 }
@@ -308,15 +355,15 @@ function serializeChildComponents(
 function exportComponentForAnalysis(
   this: ComponentDefinition<any> & ComponentDefinitionData,
 ): ComponentDefinitionAnalysis {
-  const args: [string, { type: "value"; value: string }][] = Object.entries(
-    this._args,
-  ).map(([name, validator]) => [
-    name,
-    {
-      type: "value",
-      value: JSON.stringify(validator.json),
-    },
-  ]);
+  const args: [string, { type: "value"; value: string; optional: boolean }][] =
+    Object.entries(this._args).map(([name, validator]) => [
+      name,
+      {
+        type: "value",
+        value: JSON.stringify(validator.json),
+        optional: validator.isOptional === "optional",
+      },
+    ]);
   const definitionType: ComponentDefinitionType = {
     type: "childComponent" as const,
     name: this._name,
@@ -329,6 +376,7 @@ function exportComponentForAnalysis(
     childComponents: childComponents as any,
     httpMounts: {},
     exports: serializeExportTree(this._exportTree),
+    lifecycle: this._lifecycle,
   };
 }
 
@@ -352,12 +400,15 @@ type RuntimeAppDefinition = AppDefinition &
  *
  * @param name Name must be alphanumeric plus underscores. Typically these are
  * lowercase with underscores like `"onboarding_flow_tracker"`.
+ * @param options Optional {@link ComponentLifecycleHooks} to run when the
+ * component is mounted or unmounted.
  *
  * This is a feature of components, which are in beta.
  * This API is unstable and may change in subsequent releases.
  */
 export function defineComponent<Exports extends ComponentExports = any>(
   name: string,
+  options?: ComponentLifecycleHooks,
 ): ComponentDefinition<Exports> {
   const ret: RuntimeComponentDefinition = {
     _isRoot: false,
@@ -366,6 +417,10 @@ export function defineComponent<Exports extends ComponentExports = any>(
     _childComponents: [],
     _exportTree: {},
     _onInitCallbacks: {},
+    _lifecycle: {
+      onMount: options?.onMount,
+      onUnmount: options?.onUnmount,
+    },
 
     export: exportComponentForAnalysis,
     use,
@@ -1,7 +1,11 @@
 import { GenericId } from "../values/index.js";
 import { test } from "vitest";
 import { assert, assertFalse, Equals } from "../test/type_testing.js";
-import { GenericDatabaseWriter } from "./database.js";
+import {
+  BaseTableWriter,
+  DocumentWriteOptions,
+  GenericDatabaseWriter,
+} from "./database.js";
 import { SystemTableNames } from "./schema.js";
 
 type CreateDataModel<Document> = {
@@ -34,6 +38,7 @@ test("DatabaseWriter has the right types for a simple data model", () => {
       _id?: GenericId<"tableName">;
       _creationTime?: number;
     },
+    options?: DocumentWriteOptions,
   ];
   assert<Equals<PatchType, ExpectedPatchType>>();
 
@@ -45,6 +50,7 @@ test("DatabaseWriter has the right types for a simple data model", () => {
       _id?: GenericId<"tableName">;
       _creationTime?: number;
     },
+    options?: DocumentWriteOptions,
   ];
   assert<Equals<ReplaceType, ExpectedReplaceType>>();
 });
@@ -89,6 +95,7 @@ test("DatabaseWriter has the right types for a union", () => {
           _id?: GenericId<"tableName">;
           _creationTime?: number;
         },
+    options?: DocumentWriteOptions,
   ];
   assert<Equals<PatchType, ExpectedPatchType>>();
 
@@ -103,6 +110,7 @@ test("DatabaseWriter has the right types for a union", () => {
           _creationTime?: number;
         }
       | { type: "giphy"; _id?: GenericId<"tableName">; _creationTime?: number },
+    options?: DocumentWriteOptions,
   ];
   assert<Equals<ReplaceType, ExpectedReplaceType>>();
 });
@@ -134,6 +142,7 @@ test("DatabaseWriter has the right types with loose data model", () => {
       _creationTime?: number;
       [propertyName: string]: any;
     },
+    options?: DocumentWriteOptions,
   ];
   assert<Equals<PatchType, ExpectedPatchType>>();
 
@@ -146,10 +155,39 @@ test("DatabaseWriter has the right types with loose data model", () => {
       _creationTime?: number;
       [propertyName: string]: any;
     },
+    options?: DocumentWriteOptions,
   ];
   assert<Equals<ReplaceType, ExpectedReplaceType>>();
 });
 
+test("DatabaseWriter has the right types for document versions", () => {
+  type Document = {
+    body: string;
+    _id: GenericId<"tableName">;
+    _creationTime: number;
+  };
+  type DB = GenericDatabaseWriter<CreateDataModel<Document>>;
+
+  type GetVersionType = Parameters<DB["getVersion"]>;
+  type ExpectedGetVersionType = [id: GenericId<"tableName">];
+  assert<Equals<GetVersionType, ExpectedGetVersionType>>();
+  type GetVersionReturnType = ReturnType<DB["getVersion"]>;
+  assert<Equals<GetVersionReturnType, Promise<string | null>>>();
+
+  type TableDB = BaseTableWriter<CreateDataModel<Document>, "tableName">;
+  type TablePatchType = Parameters<TableDB["patch"]>;
+  type ExpectedTablePatchType = [
+    id: GenericId<"tableName">,
+    value: {
+      body?: string;
+      _id?: GenericId<"tableName">;
+      _creationTime?: number;
+    },
+    options?: DocumentWriteOptions,
+  ];
+  assert<Equals<TablePatchType, ExpectedTablePatchType>>();
+});
+
 test("DatabaseWriter can only access system tables through system", () => {
   // Even with this system Document is in the data model, it is inaccessible from SystemDB
 
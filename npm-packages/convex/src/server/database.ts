@@ -1,4 +1,4 @@
-import { GenericId } from "../values/index.js";
+import { GenericId, Value } from "../values/index.js";
 import {
   DocumentByName,
   GenericDataModel,
@@ -114,8 +114,117 @@ export interface GenericDatabaseReader<DataModel extends GenericDataModel>
    * @public
    */
   system: BaseDatabaseReader<SystemDataModel>;
+
+  /**
+   * Find the documents whose point lies within an area, using a geospatial
+   * index defined with {@link TableDefinition.geospatialIndex}.
+   *
+   * Results of a radius search are ordered closest first.
+   *
+   * @param tableName - The name of the table to search.
+   * @param indexName - The name of the geospatial index.
+   * @param area - A circle (`center` and `radiusMeters`) or a bounding box
+   * (`southwest` and `northeast` corners).
+   * @param options - `limit` caps the number of results, 100 by default.
+   * @returns - The matching documents with their distance from the center of
+   * a radius search.
+   */
+  geospatialSearch<TableName extends TableNamesInDataModel<DataModel>>(
+    tableName: TableName,
+    indexName: string,
+    area: GeospatialArea,
+    options?: { limit?: number },
+  ): Promise<GeospatialSearchResult<DocumentByName<DataModel, TableName>>[]>;
+
+  /**
+   * Read the value of a counter updated with
+   * {@link GenericDatabaseWriter.incrementCounter}.
+   *
+   * Reading a counter sums all of its shards, so a mutation that reads a
+   * counter conflicts with every concurrent increment of it.
+   *
+   * @param name - The name of the counter.
+   * @returns - The counter's value, or 0 if it has never been incremented.
+   */
+  readCounter(name: string): Promise<number>;
+
+  /**
+   * Read the values appended to a list with
+   * {@link GenericDatabaseWriter.appendToList}.
+   *
+   * @param name - The name of the list.
+   * @param options - `order` is `"asc"` (oldest first, the default) or
+   * `"desc"`, and `limit` caps the number of values returned, 100 by default.
+   * @returns - The list's values.
+   */
+  readList(
+    name: string,
+    options?: { order?: "asc" | "desc"; limit?: number },
+  ): Promise<Value[]>;
+
+  /**
+   * Fetch the version of a document, which changes every time the document is
+   * written.
+   *
+   * Pass it as the `expectedVersion` of a later
+   * {@link GenericDatabaseWriter.patch} or
+   * {@link GenericDatabaseWriter.replace}, possibly in another mutation, to
+   * only write the document if nothing else has written it in between.
+   *
+   * @param id - The {@link values.GenericId} of the document.
+   * @returns - The document's version, or `null` if it doesn't exist.
+   */
+  getVersion<TableName extends TableNamesInDataModel<DataModel>>(
+    id: GenericId<TableName>,
+  ): Promise<string | null>;
 }
 
+/**
+ * Options for {@link GenericDatabaseWriter.patch} and
+ * {@link GenericDatabaseWriter.replace}.
+ *
+ * @public
+ */
+export type DocumentWriteOptions = {
+  /**
+   * Only write the document if its version, as returned by
+   * {@link GenericDatabaseReader.getVersion}, is still this one. Otherwise
+   * the write throws a `DocumentVersionConflict` error.
+   */
+  expectedVersion?: string;
+};
+
+/**
+ * A point indexed by a geospatial index, stored in documents as
+ * `{ latitude, longitude }` in degrees.
+ *
+ * @public
+ */
+export type GeospatialPoint = { latitude: number; longitude: number };
+
+/**
+ * The area to search with {@link GenericDatabaseReader.geospatialSearch}.
+ *
+ * @public
+ */
+export type GeospatialArea =
+  | { center: GeospatialPoint; radiusMeters: number }
+  | { southwest: GeospatialPoint; northeast: GeospatialPoint };
+
+/**
+ * A document returned by {@link GenericDatabaseReader.geospatialSearch}.
+ *
+ * @public
+ */
+export type GeospatialSearchResult<Document> = {
+  document: Document;
+  /**
+   * Distance in meters from the center of a radius search, or `null` for a
+   * bounding box search.
+   */
+  distanceMeters: number | null;
+};
+
 export interface GenericDatabaseReaderWithTable<
   DataModel extends GenericDataModel,
 > extends BaseDatabaseReaderWithTable<DataModel> {
@@ -170,10 +279,13 @@ export interface GenericDatabaseWriter<DataModel extends GenericDataModel>
    * @param id - The {@link values.GenericId} of the document to patch.
    * @param value - The partial {@link GenericDocument} to merge into the specified document. If this new value
    * specifies system fields like `_id`, they must match the document's existing field values.
+   * @param options - `expectedVersion` makes the write fail if the document
+   * has changed since that version.
    */
   patch<TableName extends TableNamesInDataModel<DataModel>>(
     id: GenericId<TableName>,
     value: Partial<DocumentByName<DataModel, TableName>>,
+    options?: DocumentWriteOptions,
   ): Promise<void>;
 
   /**
@@ -182,10 +294,13 @@ export interface GenericDatabaseWriter<DataModel extends GenericDataModel>
    * @param id - The {@link values.GenericId} of the document to replace.
    * @param value - The new {@link GenericDocument} for the document. This value can omit the system fields,
    * and the database will fill them in.
+   * @param options - `expectedVersion` makes the write fail if the document
+   * has changed since that version.
    */
   replace<TableName extends TableNamesInDataModel<DataModel>>(
     id: GenericId<TableName>,
     value: WithOptionalSystemFields<DocumentByName<DataModel, TableName>>,
+    options?: DocumentWriteOptions,
   ): Promise<void>;
 
   /**
@@ -194,6 +309,27 @@ export interface GenericDatabaseWriter<DataModel extends GenericDataModel>
    * @param id - The {@link values.GenericId} of the document to remove.
    */
   delete(id: GenericId<TableNamesInDataModel<DataModel>>): Promise<void>;
+
+  /**
+   * Add to a counter, creating it if it doesn't exist.
+   *
+   * Increments are spread over several shards, so unlike a counter stored in
+   * a document, concurrent increments rarely conflict.
+   *
+   * @param name - The name of the counter.
+   * @param delta - The integer to add to the counter, 1 by default.
+   */
+  incrementCounter(name: string, delta?: number): Promise<void>;
+
+  /**
+   * Append a value to a list, creating it if it doesn't exist.
+   *
+   * Appending doesn't read the list, so concurrent appends never conflict.
+   *
+   * @param name - The name of the list.
+   * @param value - The {@link values.Value} to append.
+   */
+  appendToList(name: string, value: Value): Promise<void>;
 }
 
 /**
@@ -245,10 +381,13 @@ export interface BaseTableWriter<
    * @param id - The {@link values.GenericId} of the document to patch.
    * @param value - The partial {@link GenericDocument} to merge into the specified document. If this new value
    * specifies system fields like `_id`, they must match the document's existing field values.
+   * @param options - `expectedVersion` makes the write fail if the document
+   * has changed since that version.
    */
   patch(
     id: GenericId<TableName>,
     value: Partial<DocumentByName<DataModel, TableName>>,
+    options?: DocumentWriteOptions,
   ): Promise<void>;
 
   /**
@@ -257,10 +396,13 @@ export interface BaseTableWriter<
    * @param id - The {@link values.GenericId} of the document to replace.
    * @param value - The new {@link GenericDocument} for the document. This value can omit the system fields,
    * and the database will fill them in.
+   * @param options - `expectedVersion` makes the write fail if the document
+   * has changed since that version.
    */
   replace(
     id: GenericId<TableName>,
     value: WithOptionalSystemFields<DocumentByName<DataModel, TableName>>,
+    options?: DocumentWriteOptions,
   ): Promise<void>;
 
   /**
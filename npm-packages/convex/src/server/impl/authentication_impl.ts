@@ -1,4 +1,11 @@
-import { Auth } from "../authentication.js";
+import {
+  Auth,
+  AuthWithServiceTokens,
+  MintServiceTokenOptions,
+  MutationAuth,
+  PromoteAnonymousIdentityOptions,
+} from "../authentication.js";
+import { version } from "../../index.js";
 import { performAsyncSyscall } from "./syscall.js";
 
 export function setupAuth(requestId: string): Auth {
@@ -10,3 +17,39 @@ export function setupAuth(requestId: string): Auth {
     },
   };
 }
+
+export function setupAuthWithServiceTokens(
+  requestId: string,
+): AuthWithServiceTokens {
+  return {
+    ...setupAuth(requestId),
+    mintServiceToken: async (options: MintServiceTokenOptions) => {
+      return await performAsyncSyscall("1.0/mintServiceToken", {
+        requestId,
+        version,
+        ...options,
+      });
+    },
+    verifyServiceToken: async (token: string) => {
+      return await performAsyncSyscall("1.0/verifyServiceToken", {
+        requestId,
+        version,
+        token,
+      });
+    },
+  };
+}
+
+export function setupMutationAuth(requestId: string): MutationAuth {
+  return {
+    ...setupAuthWithServiceTokens(requestId),
+    promoteAnonymousIdentity: async (
+      options: PromoteAnonymousIdentityOptions,
+    ) => {
+      return await performAsyncSyscall("1.0/promoteAnonymousIdentity", {
+        requestId,
+        ...options,
+      });
+    },
+  };
+}
@@ -10,6 +10,8 @@ import {
   GenericDatabaseReaderWithTable,
   GenericDatabaseWriter,
   GenericDatabaseWriterWithTable,
+  DocumentWriteOptions,
+  GeospatialArea,
 } from "../database.js";
 import { QueryInitializerImpl } from "./query_impl.js";
 import { GenericDataModel, GenericDocument } from "../data_model.js";
@@ -36,6 +38,58 @@ async function get(id: GenericId<string>, isSystem: boolean) {
   return jsonToConvex(syscallJSON) as GenericDocument;
 }
 
+const DEFAULT_GEOSPATIAL_SEARCH_LIMIT = 100;
+
+async function geospatialSearch(
+  tableName: string,
+  indexName: string,
+  area: GeospatialArea,
+  options?: { limit?: number },
+) {
+  validateArg(tableName, 1, "geospatialSearch", "tableName");
+  validateArg(indexName, 2, "geospatialSearch", "indexName");
+  validateArg(area, 3, "geospatialSearch", "area");
+  const syscallJSON = await performAsyncSyscall("1.0/geospatialSearch", {
+    indexName: tableName + "." + indexName,
+    ...area,
+    limit: options?.limit ?? DEFAULT_GEOSPATIAL_SEARCH_LIMIT,
+    version,
+  });
+  return (syscallJSON as any[]).map(({ document, distanceMeters }) => ({
+    document: jsonToConvex(document) as GenericDocument,
+    distanceMeters: distanceMeters as number | null,
+  }));
+}
+
+async function readCounter(name: string) {
+  validateArg(name, 1, "readCounter", "name");
+  const syscallJSON = await performAsyncSyscall("1.0/readCounter", { name });
+  return syscallJSON as number;
+}
+
+const DEFAULT_READ_LIST_LIMIT = 100;
+
+async function readList(
+  name: string,
+  options?: { order?: "asc" | "desc"; limit?: number },
+) {
+  validateArg(name, 1, "readList", "name");
+  const syscallJSON = await performAsyncSyscall("1.0/readList", {
+    name,
+    order: options?.order ?? "asc",
+    limit: options?.limit ?? DEFAULT_READ_LIST_LIMIT,
+  });
+  return (syscallJSON as any[]).map((value) => jsonToConvex(value));
+}
+
+async function getVersion(id: any) {
+  validateArg(id, 1, "getVersion", "id");
+  const syscallJSON = await performAsyncSyscall("1.0/getVersion", {
+    id: convexToJson(id),
+  });
+  return syscallJSON.version as string | null;
+}
+
 export function setupReader(): GenericDatabaseReader<GenericDataModel> {
   const reader = (
     isSystem = false,
@@ -48,6 +102,18 @@ export function setupReader(): GenericDatabaseReader<GenericDataModel> {
       query: (tableName: string) => {
         return new TableReader(tableName, isSystem).query();
       },
+      geospatialSearch: async (tableName, indexName, area, options) => {
+        return await geospatialSearch(tableName, indexName, area, options);
+      },
+      readCounter: async (name) => {
+        return await readCounter(name);
+      },
+      readList: async (name, options) => {
+        return await readList(name, options);
+      },
+      getVersion: async (id) => {
+        return await getVersion(id);
+      },
       normalizeId: <TableName extends string>(
         tableName: TableName,
         id: string,
@@ -98,21 +164,23 @@ async function insert(tableName: string, value: any) {
   return syscallResult._id;
 }
 
-async function patch(id: any, value: any) {
+async function patch(id: any, value: any, options?: DocumentWriteOptions) {
   validateArg(id, 1, "patch", "id");
   validateArg(value, 2, "patch", "value");
   await performAsyncSyscall("1.0/shallowMerge", {
     id: convexToJson(id),
     value: patchValueToJson(value as Value),
+    expectedVersion: options?.expectedVersion,
   });
 }
 
-async function replace(id: any, value: any) {
+async function replace(id: any, value: any, options?: DocumentWriteOptions) {
   validateArg(id, 1, "replace", "id");
   validateArg(value, 2, "replace", "value");
   await performAsyncSyscall("1.0/replace", {
     id: convexToJson(id),
     value: convexToJson(value),
+    expectedVersion: options?.expectedVersion,
   });
 }
 
@@ -121,26 +189,55 @@ async function delete_(id: any) {
   await performAsyncSyscall("1.0/remove", { id: convexToJson(id) });
 }
 
+async function incrementCounter(name: string, delta: number = 1) {
+  validateArg(name, 1, "incrementCounter", "name");
+  if (!Number.isInteger(delta)) {
+    throw new Error(
+      `Invalid argument \`delta\` for \`db.incrementCounter\`, expected an integer but got ${delta}`,
+    );
+  }
+  await performAsyncSyscall("1.0/incrementCounter", { name, delta });
+}
+
+async function appendToList(name: string, value: Value) {
+  validateArg(name, 1, "appendToList", "name");
+  validateArg(value, 2, "appendToList", "value");
+  await performAsyncSyscall("1.0/appendToList", {
+    name,
+    value: convexToJson(value),
+  });
+}
+
 export function setupWriter(): GenericDatabaseWriter<GenericDataModel> &
   GenericDatabaseWriterWithTable<GenericDataModel> {
   const reader = setupReader();
   return {
     get: reader.get,
     query: reader.query,
+    geospatialSearch: reader.geospatialSearch,
+    readCounter: reader.readCounter,
+    readList: reader.readList,
+    getVersion: reader.getVersion,
     normalizeId: reader.normalizeId,
     system: reader.system as any,
     insert: async (table, value) => {
       return await insert(table, value);
     },
-    patch: async (id, value) => {
-      return await patch(id, value);
+    patch: async (id, value, options) => {
+      return await patch(id, value, options);
     },
-    replace: async (id, value) => {
-      return await replace(id, value);
+    replace: async (id, value, options) => {
+      return await replace(id, value, options);
     },
     delete: async (id) => {
       return await delete_(id);
     },
+    incrementCounter: async (name, delta) => {
+      return await incrementCounter(name, delta);
+    },
+    appendToList: async (name, value) => {
+      return await appendToList(name, value);
+    },
     table: (tableName) => {
       return new TableWriter(tableName, false);
     },
@@ -176,11 +273,11 @@ class TableWriter extends TableReader {
   async insert(value: any) {
     return insert(this.tableName, value);
   }
-  async patch(id: any, value: any) {
-    return patch(id, value);
+  async patch(id: any, value: any, options?: DocumentWriteOptions) {
+    return patch(id, value, options);
   }
-  async replace(id: any, value: any) {
-    return replace(id, value);
+  async replace(id: any, value: any, options?: DocumentWriteOptions) {
+    return replace(id, value, options);
   }
   async delete(id: any) {
     return delete_(id);
@@ -23,7 +23,11 @@ import {
 } from "../registration.js";
 import { setupActionCalls } from "./actions_impl.js";
 import { setupActionVectorSearch } from "./vector_search_impl.js";
-import { setupAuth } from "./authentication_impl.js";
+import {
+  setupAuth,
+  setupAuthWithServiceTokens,
+  setupMutationAuth,
+} from "./authentication_impl.js";
 import { setupReader, setupWriter } from "./database_impl.js";
 import { QueryImpl, QueryInitializerImpl } from "./query_impl.js";
 import {
@@ -49,9 +53,11 @@ async function invokeMutation<
   const args = jsonToConvex(JSON.parse(argsStr));
   const mutationCtx = {
     db: setupWriter(),
-    auth: setupAuth(requestId),
+    auth: setupMutationAuth(requestId),
     storage: setupStorageWriter(requestId),
     scheduler: setupMutationScheduler(),
+    getRequestContext: () =>
+      performAsyncSyscall("1.0/getRequestContext", { requestId }),
 
     runQuery: (reference: any, args?: any) => runUdf("query", reference, args),
     runMutation: (reference: any, args?: any) =>
@@ -344,10 +350,16 @@ async function invokeAction<
   const calls = setupActionCalls(requestId);
   const ctx = {
     ...calls,
-    auth: setupAuth(requestId),
+    auth: setupAuthWithServiceTokens(requestId),
     scheduler: setupActionScheduler(requestId),
     storage: setupStorageActionWriter(requestId),
     vectorSearch: setupActionVectorSearch(requestId) as any,
+    getRequestContext: () =>
+      performAsyncSyscall("1.0/getRequestContext", { requestId }),
+    invalidateHttpActionCache: (options?: { pathPrefix?: string }) =>
+      performAsyncSyscall("1.0/invalidateHttpActionCache", {
+        pathPrefix: options?.pathPrefix,
+      }),
   };
   const result = await invokeFunction(func, ctx, args as any);
   return JSON.stringify(convexToJson(result === undefined ? null : result));
@@ -434,10 +446,16 @@ async function invokeHttpAction<
   const calls = setupActionCalls(requestId);
   const ctx = {
     ...calls,
-    auth: setupAuth(requestId),
+    auth: setupAuthWithServiceTokens(requestId),
     storage: setupStorageActionWriter(requestId),
     scheduler: setupActionScheduler(requestId),
     vectorSearch: setupActionVectorSearch(requestId) as any,
+    getRequestContext: () =>
+      performAsyncSyscall("1.0/getRequestContext", { requestId }),
+    invalidateHttpActionCache: (options?: { pathPrefix?: string }) =>
+      performAsyncSyscall("1.0/invalidateHttpActionCache", {
+        pathPrefix: options?.pathPrefix,
+      }),
   };
   return await invokeFunction(func, ctx, [request]);
 }
@@ -446,22 +464,37 @@ async function invokeHttpAction<
  * Define a Convex HTTP action.
  *
  * @param func - The function. It receives an {@link GenericActionCtx} as its first argument, and a `Request` object
- * as its second.
+ * as its second. Pass `{ body, handler }` instead to have Convex check that
+ * the request's body is JSON matching the `body` validator before running the
+ * handler; requests that don't match get a 400 response.
  * @returns The wrapped function. Route a URL path to this function in `convex/http.js`.
  *
  * @public
  */
 export const httpActionGeneric = (
-  func: (
-    ctx: GenericActionCtx<GenericDataModel>,
-    request: Request,
-  ) => Promise<Response>,
+  func:
+    | ((
+        ctx: GenericActionCtx<GenericDataModel>,
+        request: Request,
+      ) => Promise<Response>)
+    | {
+        body?: GenericValidator | Record<string, GenericValidator>;
+        handler: (
+          ctx: GenericActionCtx<GenericDataModel>,
+          request: Request,
+        ) => Promise<Response>;
+      },
 ): PublicHttpAction => {
-  const q = dontCallDirectly("httpAction", func) as PublicHttpAction;
+  const handler = typeof func === "function" ? func : func.handler;
+  const q = dontCallDirectly("httpAction", handler) as PublicHttpAction;
   assertNotBrowser();
   q.isHttp = true;
-  q.invokeHttpAction = (request) => invokeHttpAction(func as any, request);
-  q._handler = func;
+  q.invokeHttpAction = (request) => invokeHttpAction(handler as any, request);
+  q.exportBody = () => {
+    const body = typeof func === "function" ? undefined : func.body;
+    return JSON.stringify(body ? asObjectValidator(body).json : null);
+  };
+  q._handler = handler;
   return q;
 };
 
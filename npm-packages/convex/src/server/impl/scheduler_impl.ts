@@ -2,19 +2,28 @@ import { convexToJson, Value } from "../../values/index.js";
 import { version } from "../../index.js";
 import { performAsyncSyscall } from "./syscall.js";
 import { parseArgs } from "../../common/index.js";
-import { SchedulableFunctionReference, Scheduler } from "../scheduler.js";
+import {
+  SchedulableFunctionReference,
+  ScheduleOptions,
+  Scheduler,
+} from "../scheduler.js";
 import { Id } from "../../values/value.js";
 import { validateArg } from "./validate.js";
 import { getFunctionAddress } from "../components/paths.js";
 
-export function setupMutationScheduler(): Scheduler {
+export function setupMutationScheduler(
+  options: ScheduleOptions = {},
+): Scheduler {
   return {
     runAfter: async (
       delayMs: number,
       functionReference: SchedulableFunctionReference,
       args?: Record<string, Value>,
     ) => {
-      const syscallArgs = runAfterSyscallArgs(delayMs, functionReference, args);
+      const syscallArgs = {
+        ...runAfterSyscallArgs(delayMs, functionReference, args),
+        ...options,
+      };
       return await performAsyncSyscall("1.0/schedule", syscallArgs);
     },
     runAt: async (
@@ -22,11 +31,10 @@ export function setupMutationScheduler(): Scheduler {
       functionReference: SchedulableFunctionReference,
       args?: Record<string, Value>,
     ) => {
-      const syscallArgs = runAtSyscallArgs(
-        ms_since_epoch_or_date,
-        functionReference,
-        args,
-      );
+      const syscallArgs = {
+        ...runAtSyscallArgs(ms_since_epoch_or_date, functionReference, args),
+        ...options,
+      };
       return await performAsyncSyscall("1.0/schedule", syscallArgs);
     },
     cancel: async (id: Id<"_scheduled_functions">) => {
@@ -34,10 +42,18 @@ export function setupMutationScheduler(): Scheduler {
       const args = { id: convexToJson(id) };
       await performAsyncSyscall("1.0/cancel_job", args);
     },
+    withOptions: (newOptions: ScheduleOptions) =>
+      setupMutationScheduler({
+        ...options,
+        ...validateScheduleOptions(newOptions),
+      }),
   };
 }
 
-export function setupActionScheduler(requestId: string): Scheduler {
+export function setupActionScheduler(
+  requestId: string,
+  options: ScheduleOptions = {},
+): Scheduler {
   return {
     runAfter: async (
       delayMs: number,
@@ -47,6 +63,7 @@ export function setupActionScheduler(requestId: string): Scheduler {
       const syscallArgs = {
         requestId,
         ...runAfterSyscallArgs(delayMs, functionReference, args),
+        ...options,
       };
       return await performAsyncSyscall("1.0/actions/schedule", syscallArgs);
     },
@@ -58,6 +75,7 @@ export function setupActionScheduler(requestId: string): Scheduler {
       const syscallArgs = {
         requestId,
         ...runAtSyscallArgs(ms_since_epoch_or_date, functionReference, args),
+        ...options,
       };
       return await performAsyncSyscall("1.0/actions/schedule", syscallArgs);
     },
@@ -66,6 +84,33 @@ export function setupActionScheduler(requestId: string): Scheduler {
       const syscallArgs = { id: convexToJson(id) };
       return await performAsyncSyscall("1.0/actions/cancel_job", syscallArgs);
     },
+    withOptions: (newOptions: ScheduleOptions) =>
+      setupActionScheduler(requestId, {
+        ...options,
+        ...validateScheduleOptions(newOptions),
+      }),
+  };
+}
+
+function validateScheduleOptions(options: ScheduleOptions): ScheduleOptions {
+  const { priority, maxConcurrency } = options;
+  if (
+    priority !== undefined &&
+    priority !== "high" &&
+    priority !== "normal" &&
+    priority !== "low"
+  ) {
+    throw new Error('`priority` must be "high", "normal" or "low"');
+  }
+  if (
+    maxConcurrency !== undefined &&
+    (!Number.isInteger(maxConcurrency) || maxConcurrency < 1)
+  ) {
+    throw new Error("`maxConcurrency` must be a positive integer");
+  }
+  return {
+    ...(priority !== undefined ? { priority } : {}),
+    ...(maxConcurrency !== undefined ? { maxConcurrency } : {}),
   };
 }
 
@@ -1,7 +1,10 @@
 import {
+  FileEncryptionMetadata,
   FileMetadata,
   StorageActionWriter,
   FileStorageId,
+  ImageTransformOptions,
+  SignedUrlOptions,
   StorageReader,
   StorageWriter,
 } from "../storage.js";
@@ -26,6 +29,18 @@ export function setupStorageReader(requestId: string): StorageReader {
         storageId,
       });
     },
+    getImageUrl: async (
+      storageId: FileStorageId,
+      options: ImageTransformOptions,
+    ) => {
+      validateArg(storageId, 1, "getImageUrl", "storageId");
+      return await performAsyncSyscall("1.0/storageGetImageUrl", {
+        requestId,
+        version,
+        storageId,
+        ...options,
+      });
+    },
   };
 }
 
@@ -45,8 +60,28 @@ export function setupStorageWriter(requestId: string): StorageWriter {
         storageId,
       });
     },
+    getSignedUrl: async (
+      storageId: FileStorageId,
+      options: SignedUrlOptions,
+    ) => {
+      validateArg(storageId, 1, "getSignedUrl", "storageId");
+      return await performAsyncSyscall("1.0/storageGetSignedUrl", {
+        requestId,
+        version,
+        storageId,
+        ...options,
+      });
+    },
+    revokeSignedUrl: async (url: string) => {
+      await performAsyncSyscall("1.0/storageRevokeSignedUrl", {
+        requestId,
+        version,
+        url,
+      });
+    },
     getUrl: reader.getUrl,
     getMetadata: reader.getMetadata,
+    getImageUrl: reader.getImageUrl,
   };
 }
 
@@ -56,7 +91,10 @@ export function setupStorageActionWriter(
   const writer = setupStorageWriter(requestId);
   return {
     ...writer,
-    store: async (blob: Blob, options?: { sha256?: string }) => {
+    store: async (
+      blob: Blob,
+      options?: { sha256?: string; encryption?: FileEncryptionMetadata },
+    ) => {
       return await performJsSyscall("storage/storeBlob", {
         requestId,
         version,
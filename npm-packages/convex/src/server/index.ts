@@ -49,6 +49,11 @@
 
 export type {
   Auth,
+  AuthWithServiceTokens,
+  MintServiceTokenOptions,
+  MutationAuth,
+  PromoteAnonymousIdentityOptions,
+  ServiceTokenContents,
   UserIdentity,
   UserIdentityAttributes,
 } from "./authentication.js";
@@ -128,7 +133,11 @@ export type {
 } from "./registration.js";
 export * from "./search_filter_builder.js";
 export * from "./storage.js";
-export type { Scheduler, SchedulableFunctionReference } from "./scheduler.js";
+export type {
+  Scheduler,
+  SchedulableFunctionReference,
+  ScheduleOptions,
+} from "./scheduler.js";
 export { cronJobs } from "./cron.js";
 export type { CronJob, Crons } from "./cron.js";
 export type {
@@ -179,6 +188,8 @@ export type {
   ComponentDefinition,
   AnyComponents,
   FunctionHandle,
+  TableAccess,
+  ComponentLifecycleHooks,
 } from "./components/index.js";
 
 /**
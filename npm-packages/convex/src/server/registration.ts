@@ -1,9 +1,11 @@
 import {
   Auth,
+  AuthWithServiceTokens,
   GenericDatabaseReader,
   GenericDatabaseReaderWithTable,
   GenericDatabaseWriter,
   GenericDatabaseWriterWithTable,
+  MutationAuth,
   StorageActionWriter,
   StorageReader,
   StorageWriter,
@@ -52,7 +54,7 @@ export interface GenericMutationCtx<DataModel extends GenericDataModel> {
   /**
    * Information about the currently authenticated user.
    */
-  auth: Auth;
+  auth: MutationAuth;
 
   /**
    * A utility for reading and writing files in storage.
@@ -64,6 +66,13 @@ export interface GenericMutationCtx<DataModel extends GenericDataModel> {
    */
   scheduler: Scheduler;
 
+  /**
+   * Read the context the client attached to the request that started this
+   * function, such as trace ids or a locale. Functions this one calls or
+   * schedules see the same context.
+   */
+  getRequestContext(): Promise<Record<string, string>>;
+
   /**
    * Call a query function within the same transaction.
    *
@@ -238,13 +247,34 @@ export interface GenericActionCtx<DataModel extends GenericDataModel> {
   /**
    * Information about the currently authenticated user.
    */
-  auth: Auth;
+  auth: AuthWithServiceTokens;
 
   /**
    * A utility for reading and writing files in storage.
    */
   storage: StorageActionWriter;
 
+  /**
+   * Read the context the client attached to the request that started this
+   * function, such as trace ids or a locale. Functions this one calls or
+   * schedules see the same context.
+   */
+  getRequestContext(): Promise<Record<string, string>>;
+
+  /**
+   * Drop this component's cached HTTP action responses, so the next GET
+   * request reruns the action.
+   *
+   * Responses are only cached when the deployment enables the HTTP action
+   * cache and the response's `Cache-Control` header allows shared caching.
+   *
+   * @param options.pathPrefix - Only drop responses to requests whose path
+   * starts with this prefix. Drops all of the component's responses if
+   * omitted.
+   * @returns The number of cached responses dropped.
+   */
+  invalidateHttpActionCache(options?: { pathPrefix?: string }): Promise<number>;
+
   /**
    * Run a vector search on the given table and index.
    *
@@ -444,6 +474,8 @@ export type PublicHttpAction = {
   /** @internal */
   invokeHttpAction(request: Request): Promise<Response>;
   /** @internal */
+  exportBody(): string;
+  /** @internal */
   _handler: (ctx: GenericActionCtx<any>, request: Request) => Promise<Response>;
 };
 
@@ -1068,5 +1100,17 @@ export type ActionBuilder<
  * @public
  */
 export type HttpActionBuilder = (
-  func: (ctx: GenericActionCtx<any>, request: Request) => Promise<Response>,
+  func:
+    | ((ctx: GenericActionCtx<any>, request: Request) => Promise<Response>)
+    | {
+        /**
+         * Validator for the request's JSON body, checked before the handler
+         * runs.
+         */
+        body?: GenericValidator | Record<string, GenericValidator>;
+        handler: (
+          ctx: GenericActionCtx<any>,
+          request: Request,
+        ) => Promise<Response>;
+      },
 ) => PublicHttpAction;
@@ -13,6 +13,25 @@ export type SchedulableFunctionReference = FunctionReference<
   "public" | "internal"
 >;
 
+/**
+ * Options for functions scheduled with {@link Scheduler.withOptions}.
+ *
+ * @public
+ */
+export type ScheduleOptions = {
+  /**
+   * When more scheduled functions are due than can run at once, higher
+   * priority functions start first. Use `"low"` for bulk work like backfills
+   * so it doesn't delay latency-sensitive functions. Defaults to `"normal"`.
+   */
+  priority?: "high" | "normal" | "low";
+  /**
+   * The scheduled function won't start while this many runs of the same
+   * function are in progress. Must be a positive integer.
+   */
+  maxConcurrency?: number;
+};
+
 /**
  * An interface to schedule Convex functions.
  *
@@ -69,4 +88,12 @@ export interface Scheduler {
    * @param id
    */
   cancel(id: Id<"_scheduled_functions">): Promise<void>;
+
+  /**
+   * A scheduler that schedules functions with the given options, for example
+   * `ctx.scheduler.withOptions({ priority: "low" }).runAfter(0, ...)`.
+   *
+   * @param options - {@link ScheduleOptions} for the functions it schedules.
+   */
+  withOptions(options: ScheduleOptions): Scheduler;
 }
@@ -49,6 +49,7 @@ import {
   v,
 } from "../values/validator.js";
 import { VObject, Validator } from "../values/validators.js";
+import { FunctionReference, getFunctionName } from "./api.js";
 
 /**
  * Extract all of the index field paths within a {@link Validator}.
@@ -147,6 +148,23 @@ export type SearchIndex = {
   searchField: string;
   filterFields: string[];
 };
+
+/**
+ * @internal
+ */
+export type GeospatialIndex = {
+  indexDescriptor: string;
+  locationField: string;
+};
+
+/**
+ * @internal
+ */
+export type FileTextConfig = {
+  storageField: string;
+  textField: string;
+  extractor?: string;
+};
 /**
  * The definition of a table within a schema.
  *
@@ -162,6 +180,11 @@ export class TableDefinition<
   private indexes: Index[];
   private searchIndexes: SearchIndex[];
   private vectorIndexes: VectorIndex[];
+  private geospatialIndexes: GeospatialIndex[];
+  private triggers: string[];
+  private onCommitHandlers: string[];
+  private cascadeDeleteFilesEnabled: boolean;
+  private fileText: FileTextConfig | undefined;
   // The type of documents stored in this table.
   validator: DocumentType;
 
@@ -172,6 +195,11 @@ export class TableDefinition<
     this.indexes = [];
     this.searchIndexes = [];
     this.vectorIndexes = [];
+    this.geospatialIndexes = [];
+    this.triggers = [];
+    this.onCommitHandlers = [];
+    this.cascadeDeleteFilesEnabled = false;
+    this.fileText = undefined;
     this.validator = documentType;
   }
 
@@ -292,6 +320,113 @@ export class TableDefinition<
     return this;
   }
 
+  /**
+   * Define a geospatial index on this table.
+   *
+   * The indexed field holds a point `{ latitude: number, longitude: number }`.
+   * Search the index with `ctx.db.geospatialSearch`. Documents whose field
+   * isn't a valid point are left out of the index.
+   *
+   * @param name - The name of the index.
+   * @param indexConfig - The geospatial index configuration object.
+   * @returns A {@link TableDefinition} with this geospatial index included.
+   */
+  geospatialIndex<IndexName extends string>(
+    name: IndexName,
+    indexConfig: { locationField: ExtractFieldPaths<DocumentType> },
+  ): TableDefinition<DocumentType, Indexes, SearchIndexes, VectorIndexes> {
+    this.geospatialIndexes.push({
+      indexDescriptor: name,
+      locationField: indexConfig.locationField,
+    });
+    return this;
+  }
+
+  /**
+   * Run a mutation whenever a document in this table is inserted, updated or
+   * deleted.
+   *
+   * The trigger runs in the same transaction as the write and receives
+   * `{ table, operation, id, oldDoc, newDoc }`. If it throws, the writing
+   * mutation fails too.
+   *
+   * @param handler - An internal mutation, e.g. `internal.audit.onWrite`.
+   * @returns A {@link TableDefinition} with this trigger included.
+   */
+  trigger(
+    handler: FunctionReference<"mutation", "internal">,
+  ): TableDefinition<DocumentType, Indexes, SearchIndexes, VectorIndexes> {
+    this.triggers.push(getFunctionName(handler));
+    return this;
+  }
+
+  /**
+   * Schedule an action whenever a document in this table is inserted, updated
+   * or deleted.
+   *
+   * The action is enqueued in the same transaction as the write, so it runs if
+   * and only if the write commits. It receives the same
+   * `{ table, operation, id, oldDoc, newDoc }` argument as a trigger.
+   *
+   * @param handler - An internal action, e.g. `internal.emails.send`.
+   * @returns A {@link TableDefinition} with this commit hook included.
+   */
+  onCommit(
+    handler: FunctionReference<"action", "internal">,
+  ): TableDefinition<DocumentType, Indexes, SearchIndexes, VectorIndexes> {
+    this.onCommitHandlers.push(getFunctionName(handler));
+    return this;
+  }
+
+  /**
+   * Delete the files a document references when the document is deleted.
+   *
+   * Files are referenced by fields typed `v.id("_storage")`. After a document
+   * is deleted, each file it referenced is deleted in the background unless
+   * another document still references it.
+   *
+   * @returns A {@link TableDefinition} that cascades file deletes.
+   */
+  cascadeDeleteFiles(): TableDefinition<
+    DocumentType,
+    Indexes,
+    SearchIndexes,
+    VectorIndexes
+  > {
+    this.cascadeDeleteFilesEnabled = true;
+    return this;
+  }
+
+  /**
+   * Extract the text of the file each document references, so that a search
+   * index can find documents by the contents of their files.
+   *
+   * Whenever a document is written with a new file in `storageField`, the
+   * file's text is extracted in the background and written to `textField`,
+   * which one of this table's search indexes must search. Plain text files
+   * are read directly. Other files, such as PDFs, are passed to `extractor`,
+   * which receives `{ storageId, contentType }` and returns the text or
+   * `null`.
+   *
+   * @param config - The `v.id("_storage")` field, the text field and the
+   * optional extractor, e.g. `internal.files.extractText`.
+   * @returns A {@link TableDefinition} that extracts file text.
+   */
+  extractFileText(config: {
+    storageField: ExtractFieldPaths<DocumentType>;
+    textField: ExtractFieldPaths<DocumentType>;
+    extractor?: FunctionReference<"action", "internal">;
+  }): TableDefinition<DocumentType, Indexes, SearchIndexes, VectorIndexes> {
+    this.fileText = {
+      storageField: config.storageField,
+      textField: config.textField,
+      ...(config.extractor
+        ? { extractor: getFunctionName(config.extractor) }
+        : {}),
+    };
+    return this;
+  }
+
   /**
    * Work around for https://github.com/microsoft/TypeScript/issues/57035
    */
@@ -314,6 +449,11 @@ export class TableDefinition<
       indexes: this.indexes,
       searchIndexes: this.searchIndexes,
       vectorIndexes: this.vectorIndexes,
+      geospatialIndexes: this.geospatialIndexes,
+      triggers: this.triggers,
+      onCommit: this.onCommitHandlers,
+      cascadeDeleteFiles: this.cascadeDeleteFilesEnabled,
+      fileText: this.fileText,
       documentType: this.validator.json,
     };
   }
@@ -431,13 +571,27 @@ export class SchemaDefinition<
   export(): string {
     return JSON.stringify({
       tables: Object.entries(this.tables).map(([tableName, definition]) => {
-        const { indexes, searchIndexes, vectorIndexes, documentType } =
-          definition.export();
+        const {
+          indexes,
+          searchIndexes,
+          vectorIndexes,
+          geospatialIndexes,
+          triggers,
+          onCommit,
+          cascadeDeleteFiles,
+          fileText,
+          documentType,
+        } = definition.export();
         return {
           tableName,
           indexes,
           searchIndexes,
           vectorIndexes,
+          ...(geospatialIndexes.length > 0 ? { geospatialIndexes } : {}),
+          ...(triggers.length > 0 ? { triggers } : {}),
+          ...(onCommit.length > 0 ? { onCommit } : {}),
+          ...(cascadeDeleteFiles ? { cascadeDeleteFiles } : {}),
+          ...(fileText ? { fileText } : {}),
           documentType,
         };
       }),
@@ -564,6 +718,8 @@ type MaybeMakeLooseDataModel<
 const _systemSchema = defineSchema({
   _scheduled_functions: defineTable({
     name: v.string(),
+    component: v.string(),
+    scheduledBy: v.optional(v.string()),
     args: v.array(v.any()),
     scheduledTime: v.float64(),
     completedTime: v.optional(v.float64()),
@@ -574,11 +730,33 @@ const _systemSchema = defineSchema({
       v.object({ kind: v.literal("failed"), error: v.string() }),
       v.object({ kind: v.literal("canceled") }),
     ),
-  }),
+  }).index("by_component", ["component"]),
   _storage: defineTable({
     sha256: v.string(),
     size: v.float64(),
     contentType: v.optional(v.string()),
+    encryption: v.optional(
+      v.object({ keyId: v.string(), iv: v.string(), algorithm: v.string() }),
+    ),
+    quarantineReason: v.optional(v.string()),
+  }),
+  _component_tree: defineTable({
+    path: v.string(),
+    definitionPath: v.string(),
+    state: v.union(v.literal("active"), v.literal("unmounted")),
+  }),
+  _deployment_config: defineTable({
+    backendState: v.union(
+      v.literal("disabled"),
+      v.literal("paused"),
+      v.literal("running"),
+      v.literal("suspended"),
+    ),
+    authProviders: v.array(
+      v.object({ domain: v.string(), applicationId: v.string() }),
+    ),
+    environmentVariableNames: v.array(v.string()),
+    knobOverrides: v.array(v.object({ name: v.string(), value: v.string() })),
   }),
 });
 
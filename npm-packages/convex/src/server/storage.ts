@@ -32,6 +32,102 @@ export type FileMetadata = {
    * ContentType of the file if it was provided on upload
    */
   contentType: string | null;
+  /**
+   * Encryption metadata for the file if it was provided on upload
+   */
+  encryption?: FileEncryptionMetadata;
+};
+
+/**
+ * Metadata describing how a file was encrypted before it was uploaded.
+ *
+ * Convex doesn't interpret these values. They're stored with the file and
+ * returned by {@link StorageReader.getMetadata | storage.getMetadata},
+ * `db.system.get` and in the `Convex-Encryption-Key-Id`,
+ * `Convex-Encryption-Iv` and `Convex-Encryption-Algorithm` headers when the
+ * file is served. Uploads to a URL from
+ * {@link StorageWriter.generateUploadUrl | storage.generateUploadUrl} can set
+ * them with the same headers.
+ *
+ * @public
+ */
+export type FileEncryptionMetadata = {
+  /**
+   * Identifies the key the file was encrypted with.
+   */
+  keyId: string;
+  /**
+   * The initialization vector, typically base64 encoded.
+   */
+  iv: string;
+  /**
+   * The encryption algorithm, e.g. `"AES-GCM"`.
+   */
+  algorithm: string;
+};
+
+/**
+ * Options for {@link StorageWriter.getSignedUrl | storage.getSignedUrl}.
+ *
+ * @public
+ */
+export type SignedUrlOptions = {
+  /**
+   * How long the URL is valid for, in seconds. URLs can be valid for at most
+   * 7 days.
+   */
+  expiresInSeconds: number;
+  /**
+   * If set, the file is downloaded as an attachment with this filename
+   * instead of being displayed in the browser.
+   */
+  filename?: string;
+  /**
+   * If set, only clients connecting from this IP address can use the URL.
+   */
+  ip?: string;
+};
+
+/**
+ * Options for {@link StorageReader.getImageUrl | storage.getImageUrl}.
+ *
+ * At least one of `crop`, `width`, `height` or `format` must be set.
+ *
+ * @public
+ */
+export type ImageTransformOptions = {
+  /**
+   * A rectangle of the original image to keep, in pixels. Cropping happens
+   * before resizing.
+   */
+  crop?: { x: number; y: number; width: number; height: number };
+  /**
+   * The width of the transformed image in pixels. If only one of `width` and
+   * `height` is set, the other is scaled to preserve the aspect ratio.
+   */
+  width?: number;
+  /**
+   * The height of the transformed image in pixels.
+   */
+  height?: number;
+  /**
+   * How to fit the image into `width` and `height` when both are set:
+   * - `"cover"` (the default) scales the image to cover the size and crops
+   *   the overflow.
+   * - `"contain"` scales the image to fit within the size.
+   * - `"fill"` stretches the image to exactly the size.
+   */
+  fit?: "cover" | "contain" | "fill";
+  /**
+   * The format of the transformed image. Defaults to the original image's
+   * format.
+   */
+  format?: "webp" | "avif" | "png" | "jpeg";
+  /**
+   * Encoder quality from 1 to 100 for JPEG and AVIF images. Defaults to 80.
+   * WebP images are always lossless.
+   */
+  quality?: number;
 };
 
 /**
@@ -85,6 +181,23 @@ export interface StorageReader {
   getMetadata<T extends StorageId>(
     storageId: T extends { __tableName: any } ? never : T,
   ): Promise<FileMetadata | null>;
+
+  /**
+   * Get a URL for a resized, cropped or converted version of an image in
+   * storage.
+   *
+   * The image is transformed when the URL is first fetched, and the result is
+   * cached for later requests. The transform is signed into the URL, so
+   * clients can't change it.
+   *
+   * @param storageId - The `Id<"_storage">` of the image.
+   * @param options - The transform to apply, see {@link ImageTransformOptions}.
+   * @returns - A url which fetches the transformed image via an HTTP GET, or `null` if the file no longer exists.
+   */
+  getImageUrl(
+    storageId: GenericId<"_storage">,
+    options: ImageTransformOptions,
+  ): Promise<string | null>;
 }
 
 /**
@@ -124,6 +237,30 @@ export interface StorageWriter extends StorageReader {
   delete<T extends StorageId>(
     storageId: T extends { __tableName: any } ? never : T,
   ): Promise<void>;
+
+  /**
+   * Get a URL for a file in storage that expires.
+   *
+   * Unlike the URLs from {@link StorageReader.getUrl}, signed URLs don't reveal
+   * the file's `Id<"_storage">` and stop working after they expire or are
+   * revoked with {@link StorageWriter.revokeSignedUrl}.
+   *
+   * @param storageId - The `Id<"_storage">` of the file to fetch from Convex storage.
+   * @param options - The URL's expiry and optional download filename and client IP address.
+   * @returns - A url which fetches the file via an HTTP GET, or `null` if it no longer exists.
+   */
+  getSignedUrl(
+    storageId: GenericId<"_storage">,
+    options: SignedUrlOptions,
+  ): Promise<string | null>;
+
+  /**
+   * Revoke a URL returned by {@link StorageWriter.getSignedUrl} before it
+   * expires.
+   *
+   * @param url - The signed URL to revoke.
+   */
+  revokeSignedUrl(url: string): Promise<void>;
 }
 
 /**
@@ -149,9 +286,10 @@ export interface StorageActionWriter extends StorageWriter {
    * Store the file contained in the Blob.
    *
    * If provided, this will verify the sha256 checksum matches the contents of the file.
+   * `encryption` is stored with the file, see {@link FileEncryptionMetadata}.
    */
   store(
     blob: Blob,
-    options?: { sha256?: string },
+    options?: { sha256?: string; encryption?: FileEncryptionMetadata },
   ): Promise<GenericId<"_storage">>;
 }
@@ -28,6 +28,11 @@ import { SourcePackage, maybeDownloadAndLinkPackages } from "./source_package";
 import { buildDeps, BuildDepsRequest } from "./build_deps";
 import { ConvexError, JSONValue } from "convex/values";
 import { logDebug, logDurationMs } from "./log";
+import {
+  OutboundNetworkPolicy,
+  clearOutboundNetworkPolicy,
+  setOutboundNetworkPolicy,
+} from "./network_policy";
 
 // When we bundle commonJS modules as ESM with esbuild, the bundled code might still use
 // `require`, exports, module, __dirname or __filename despite being in ESM.
@@ -115,6 +120,8 @@ export type ExecuteRequest = {
   authHeader: string | null;
   userIdentity: UserIdentity | null;
   environmentVariables: EnvironmentVariable[];
+  // Not sent by older backends.
+  outboundNetworkPolicy?: OutboundNetworkPolicy;
   timeoutSecs: number;
   npmVersion: string | null;
   executionContext: ExecutionContext;
@@ -126,6 +133,7 @@ export type ExecutionContext = {
   executionId: string | undefined;
   isRoot: boolean | undefined;
   parentScheduledJob: string | null;
+  requestContext?: Record<string, string>;
 };
 
 export type ExecuteResponseInner =
@@ -187,6 +195,20 @@ export async function execute(
     request.encodedParentTrace,
   );
 
+  if (request.outboundNetworkPolicy !== undefined) {
+    setOutboundNetworkPolicy({
+      policy: request.outboundNetworkPolicy,
+      exemptHost: new URL(request.backendAddress).hostname,
+      onViolation: (host, reason) => {
+        syscalls
+          .reportNetworkPolicyViolation(host, reason, request.npmVersion)
+          .catch((e) =>
+            logDebug(`Failed to report network policy violation: ${e}`),
+          );
+      },
+    });
+  }
+
   let innerResult: ExecuteResponseInner;
   try {
     if (!local.modules.has(request.udfPath.canonicalizedPath)) {
@@ -212,6 +234,8 @@ export async function execute(
       // Log lines should be streamed, but send an empty array for backwards compatibility
       logLines: [],
     };
+  } finally {
+    clearOutboundNetworkPolicy();
   }
 
   const totalExecutorTimeMs = logDurationMs("totalExecutorTime", start);
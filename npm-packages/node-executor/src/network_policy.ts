@@ -0,0 +1,209 @@
+import net from "node:net";
+import dns from "node:dns";
+
+// Must match `OutboundNetworkPolicy` in Rust.
+export type OutboundNetworkPolicy = {
+  allowedHosts: string[];
+  deniedHosts: string[];
+  allowPrivateIps: boolean;
+};
+
+export type NetworkPolicyViolationReason =
+  | "denied"
+  | "not_allowed"
+  | "private_ip";
+
+export class NetworkPolicyViolationError extends Error {
+  constructor(host: string, reason: NetworkPolicyViolationReason) {
+    super(violationMessage(host, reason));
+    this.name = "NetworkPolicyViolationError";
+  }
+}
+
+function violationMessage(
+  host: string,
+  reason: NetworkPolicyViolationReason,
+): string {
+  switch (reason) {
+    case "denied":
+      return `Request to ${host} blocked: the host is denied by this deployment's outbound network policy`;
+    case "not_allowed":
+      return `Request to ${host} blocked: the host isn't in this deployment's outbound network policy allowlist`;
+    case "private_ip":
+      return `Request to ${host} blocked: requests to private IP addresses aren't allowed`;
+  }
+}
+
+const privateAddresses = new net.BlockList();
+privateAddresses.addSubnet("0.0.0.0", 8, "ipv4");
+privateAddresses.addSubnet("10.0.0.0", 8, "ipv4");
+// Carrier-grade NAT.
+privateAddresses.addSubnet("100.64.0.0", 10, "ipv4");
+privateAddresses.addSubnet("127.0.0.0", 8, "ipv4");
+privateAddresses.addSubnet("169.254.0.0", 16, "ipv4");
+privateAddresses.addSubnet("172.16.0.0", 12, "ipv4");
+privateAddresses.addSubnet("192.168.0.0", 16, "ipv4");
+privateAddresses.addAddress("255.255.255.255", "ipv4");
+privateAddresses.addAddress("::", "ipv6");
+privateAddresses.addAddress("::1", "ipv6");
+// Unique local addresses.
+privateAddresses.addSubnet("fc00::", 7, "ipv6");
+privateAddresses.addSubnet("fe80::", 10, "ipv6");
+
+export function isPrivateIp(address: string): boolean {
+  const family = net.isIP(address);
+  if (family === 0) {
+    return false;
+  }
+  // `BlockList` also matches IPv4-mapped IPv6 addresses against IPv4 rules.
+  return privateAddresses.check(address, family === 4 ? "ipv4" : "ipv6");
+}
+
+function hostMatches(pattern: string, host: string): boolean {
+  if (pattern === "*") {
+    return true;
+  }
+  if (pattern.startsWith("*.")) {
+    return host.endsWith(pattern.slice(1));
+  }
+  return pattern === host;
+}
+
+/**
+ * Checks a host against the policy's allow and deny lists, and if it's an IP
+ * address, whether it's private. Returns `null` if the host is allowed.
+ */
+export function checkHost(
+  policy: OutboundNetworkPolicy,
+  host: string,
+): NetworkPolicyViolationReason | null {
+  host = host.toLowerCase();
+  if (policy.deniedHosts.some((p) => hostMatches(p, host))) {
+    return "denied";
+  }
+  if (
+    policy.allowedHosts.length > 0 &&
+    !policy.allowedHosts.some((p) => hostMatches(p, host))
+  ) {
+    return "not_allowed";
+  }
+  const ipLiteral = host.replace(/^\[/, "").replace(/\]$/, "");
+  if (!policy.allowPrivateIps && isPrivateIp(ipLiteral)) {
+    return "private_ip";
+  }
+  return null;
+}
+
+type ActivePolicy = {
+  policy: OutboundNetworkPolicy;
+  // The backend's own host, which the executor calls back into for syscalls.
+  exemptHost: string;
+  onViolation: (host: string, reason: NetworkPolicyViolationReason) => void;
+};
+
+let activePolicy: ActivePolicy | null = null;
+let installed = false;
+
+/**
+ * Enforce `policy` on every TCP connection the process opens until
+ * `clearOutboundNetworkPolicy` is called. This covers `fetch`, `http`,
+ * `https`, and any library built on `net`.
+ */
+export function setOutboundNetworkPolicy(policy: ActivePolicy) {
+  installConnectHook();
+  activePolicy = policy;
+}
+
+export function clearOutboundNetworkPolicy() {
+  activePolicy = null;
+}
+
+function installConnectHook() {
+  if (installed) {
+    return;
+  }
+  installed = true;
+  const originalConnect = net.Socket.prototype.connect as (
+    this: net.Socket,
+    ...args: any[]
+  ) => net.Socket;
+  net.Socket.prototype.connect = function (
+    this: net.Socket,
+    ...args: any[]
+  ): net.Socket {
+    const active = activePolicy;
+    if (active === null) {
+      return originalConnect.apply(this, args);
+    }
+    const [options, callback] = normalizeConnectArgs(args);
+    if (options === null || options.path !== undefined) {
+      // IPC connections don't leave the machine.
+      return originalConnect.apply(this, args);
+    }
+    const host: string = options.host ?? "localhost";
+    if (host === active.exemptHost) {
+      return originalConnect.apply(this, args);
+    }
+    const violation = (reason: NetworkPolicyViolationReason) => {
+      active.onViolation(host, reason);
+      return new NetworkPolicyViolationError(host, reason);
+    };
+    const reason = checkHost(active.policy, host);
+    if (reason !== null) {
+      const error = violation(reason);
+      process.nextTick(() => this.destroy(error));
+      return this;
+    }
+    let checkedOptions = options;
+    if (!active.policy.allowPrivateIps) {
+      // Check the addresses the host resolves to, so a public hostname can't
+      // be used to reach a private address.
+      const lookup = options.lookup ?? dns.lookup;
+      checkedOptions = {
+        ...options,
+        lookup: (hostname: string, lookupOptions: any, cb: any) => {
+          lookup(
+            hostname,
+            lookupOptions,
+            (err: any, address: any, family: any) => {
+              if (err) {
+                cb(err, address, family);
+                return;
+              }
+              const addresses: string[] = Array.isArray(address)
+                ? address.map((a) => a.address)
+                : [address];
+              if (addresses.some(isPrivateIp)) {
+                cb(violation("private_ip"));
+                return;
+              }
+              cb(err, address, family);
+            },
+          );
+        },
+      };
+    }
+    return callback === undefined
+      ? originalConnect.call(this, checkedOptions)
+      : originalConnect.call(this, checkedOptions, callback);
+  } as typeof net.Socket.prototype.connect;
+}
+
+// Mirrors how `net` interprets the overloaded arguments to `connect`. Returns
+// `null` options for argument forms we don't recognize.
+function normalizeConnectArgs(args: any[]): [any, any] {
+  const last = args[args.length - 1];
+  const callback = typeof last === "function" ? last : undefined;
+  const first = args[0];
+  if (typeof first === "object" && first !== null) {
+    return [first, callback];
+  }
+  if (typeof first === "string" && isNaN(Number(first))) {
+    return [{ path: first }, callback];
+  }
+  if (first === undefined) {
+    return [null, callback];
+  }
+  const host = typeof args[1] === "string" ? args[1] : undefined;
+  return [{ port: first, host }, callback];
+}
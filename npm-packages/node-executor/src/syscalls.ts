@@ -4,6 +4,7 @@ import { UserIdentity } from "convex/server";
 import { ExecutionContext, SyscallStats } from "./executor";
 import { ConvexError, JSONValue } from "convex/values";
 import { UdfPath } from "./convex";
+import { NetworkPolicyViolationReason } from "./network_policy";
 import dns from "node:dns";
 
 const MAX_PENDING_SYSCALLS = 1000;
@@ -46,6 +47,8 @@ const scheduleSchema = z.object({
   functionHandle: z.optional(z.string()),
   ts: z.number(),
   args: z.any(),
+  priority: z.optional(z.enum(["high", "normal", "low"])),
+  maxConcurrency: z.optional(z.number()),
   version: z.string(),
 });
 
@@ -170,6 +173,10 @@ export class SyscallsImpl {
     if (this.executionContext.isRoot !== undefined) {
       headers["Convex-Root-Request"] = this.executionContext.isRoot.toString();
     }
+    const requestContext = this.executionContext.requestContext ?? {};
+    if (Object.keys(requestContext).length > 0) {
+      headers["Convex-Request-Context"] = JSON.stringify(requestContext);
+    }
     if (this.authHeader !== null) {
       headers["Authorization"] = this.authHeader;
     }
@@ -309,6 +316,8 @@ export class SyscallsImpl {
           return JSON.stringify(await this.syscallCancelJob(jsonArgs));
         case "1.0/getUserIdentity":
           return JSON.stringify(this.syscallGetUserIdentity(jsonArgs));
+        case "1.0/getRequestContext":
+          return JSON.stringify(this.executionContext.requestContext ?? {});
         case "1.0/storageGenerateUploadUrl": {
           return JSON.stringify(
             await this.syscallStorageGenerateUploadUrl(jsonArgs),
@@ -320,10 +329,28 @@ export class SyscallsImpl {
           return JSON.stringify(await this.syscallStorageGetMetadata(jsonArgs));
         case "1.0/storageDelete":
           return JSON.stringify(await this.syscallStorageDelete(jsonArgs));
+        case "1.0/storageGetSignedUrl":
+          return JSON.stringify(
+            await this.syscallStorageGetSignedUrl(jsonArgs),
+          );
+        case "1.0/storageRevokeSignedUrl":
+          return JSON.stringify(
+            await this.syscallStorageRevokeSignedUrl(jsonArgs),
+          );
+        case "1.0/storageGetImageUrl":
+          return JSON.stringify(await this.syscallStorageGetImageUrl(jsonArgs));
         case "1.0/createFunctionHandle":
           return JSON.stringify(
             await this.syscallCreateFunctionHandle(jsonArgs),
           );
+        case "1.0/mintServiceToken":
+          return JSON.stringify(await this.syscallMintServiceToken(jsonArgs));
+        case "1.0/verifyServiceToken":
+          return JSON.stringify(await this.syscallVerifyServiceToken(jsonArgs));
+        case "1.0/invalidateHttpActionCache":
+          return JSON.stringify(
+            await this.syscallInvalidateHttpActionCache(jsonArgs),
+          );
         default:
           throw new Error(`Unknown operation ${op}`);
       }
@@ -526,6 +553,8 @@ export class SyscallsImpl {
         udfPath: scheduleArgs.name,
         udfArgs: scheduleArgs.args,
         scheduledTs: scheduleArgs.ts,
+        priority: scheduleArgs.priority,
+        maxConcurrency: scheduleArgs.maxConcurrency,
       },
       path: "/api/actions/schedule_job",
       operationName,
@@ -660,6 +689,11 @@ export class SyscallsImpl {
     if (options?.sha256 !== undefined) {
       headers["Digest"] = `sha-256=${options.sha256}`;
     }
+    if (options?.encryption !== undefined) {
+      headers["Convex-Encryption-Key-Id"] = options.encryption.keyId;
+      headers["Convex-Encryption-Iv"] = options.encryption.iv;
+      headers["Convex-Encryption-Algorithm"] = options.encryption.algorithm;
+    }
 
     const uploadUrl = await this._storageGenerateUploadUrl(args["version"]);
     const response = await fetch(uploadUrl, {
@@ -702,6 +736,20 @@ export class SyscallsImpl {
     return await getResult.blob();
   }
 
+  async reportNetworkPolicyViolation(
+    host: string,
+    reason: NetworkPolicyViolationReason,
+    version: string | null,
+  ): Promise<void> {
+    await this.actionCallback({
+      version: version ?? "unknown",
+      body: { host, reason },
+      path: "/api/actions/record_network_policy_violation",
+      operationName: "record network policy violation",
+      responseValidator: z.any(),
+    });
+  }
+
   async syscallCreateFunctionHandle(rawArgs: string): Promise<JSONValue> {
     const createFunctionHandleArgs = z.object({
       name: z.optional(z.string()),
@@ -727,6 +775,136 @@ export class SyscallsImpl {
     });
     return handle;
   }
+
+  async syscallMintServiceToken(rawArgs: string): Promise<JSONValue> {
+    const mintServiceTokenArgs = z
+      .object({
+        version: z.string(),
+      })
+      .passthrough();
+    const operationName = "mint service token";
+    const { version, ...body } = this.validateArgs(
+      rawArgs,
+      mintServiceTokenArgs,
+      operationName,
+    );
+    const { token } = await this.actionCallback({
+      version,
+      body,
+      path: "/api/actions/mint_service_token",
+      operationName,
+      responseValidator: z.object({ token: z.string() }),
+    });
+    return token;
+  }
+
+  async syscallVerifyServiceToken(rawArgs: string): Promise<JSONValue> {
+    const verifyServiceTokenArgs = z.object({
+      token: z.string(),
+      version: z.string(),
+    });
+    const operationName = "verify service token";
+    const args = this.validateArgs(
+      rawArgs,
+      verifyServiceTokenArgs,
+      operationName,
+    );
+    return this.actionCallback({
+      version: args.version,
+      body: { token: args.token },
+      path: "/api/actions/verify_service_token",
+      operationName,
+      responseValidator: z.any(),
+    });
+  }
+
+  async syscallInvalidateHttpActionCache(rawArgs: string): Promise<JSONValue> {
+    const invalidateHttpActionCacheArgs = z.object({
+      pathPrefix: z.optional(z.string()),
+      version: z.string(),
+    });
+    const operationName = "invalidate http action cache";
+    const args = this.validateArgs(
+      rawArgs,
+      invalidateHttpActionCacheArgs,
+      operationName,
+    );
+    const { dropped } = await this.actionCallback({
+      version: args.version,
+      body: { pathPrefix: args.pathPrefix },
+      path: "/api/actions/invalidate_http_action_cache",
+      operationName,
+      responseValidator: z.object({ dropped: z.number() }),
+    });
+    return dropped;
+  }
+
+  async syscallStorageGetSignedUrl(rawArgs: string): Promise<JSONValue> {
+    const storageGetSignedUrlArgs = z
+      .object({
+        storageId: z.string(),
+        version: z.string(),
+      })
+      .passthrough();
+    const operationName = "storage get signed url";
+    const { version, ...body } = this.validateArgs(
+      rawArgs,
+      storageGetSignedUrlArgs,
+      operationName,
+    );
+    const { url } = await this.actionCallback({
+      version,
+      body,
+      path: "/api/actions/storage_get_signed_url",
+      operationName,
+      responseValidator: z.object({ url: z.string().nullable() }),
+    });
+    return url;
+  }
+
+  async syscallStorageRevokeSignedUrl(rawArgs: string): Promise<JSONValue> {
+    const storageRevokeSignedUrlArgs = z.object({
+      url: z.string(),
+      version: z.string(),
+    });
+    const operationName = "storage revoke signed url";
+    const args = this.validateArgs(
+      rawArgs,
+      storageRevokeSignedUrlArgs,
+      operationName,
+    );
+    await this.actionCallback({
+      version: args.version,
+      body: { url: args.url },
+      path: "/api/actions/storage_revoke_signed_url",
+      operationName,
+      responseValidator: z.any(),
+    });
+    return null;
+  }
+
+  async syscallStorageGetImageUrl(rawArgs: string): Promise<JSONValue> {
+    const storageGetImageUrlArgs = z
+      .object({
+        storageId: z.string(),
+        version: z.string(),
+      })
+      .passthrough();
+    const operationName = "storage get image url";
+    const { version, ...body } = this.validateArgs(
+      rawArgs,
+      storageGetImageUrlArgs,
+      operationName,
+    );
+    const { url } = await this.actionCallback({
+      version,
+      body,
+      path: "/api/actions/storage_get_image_url",
+      operationName,
+      responseValidator: z.object({ url: z.string().nullable() }),
+    });
+    return url;
+  }
 }
 
 function forwardErrorData(errorData: JSONValue, error: ConvexError<string>) {
@@ -0,0 +1,37 @@
+import { checkHost } from "../src/network_policy";
+import { describe, test, expect } from "vitest";
+
+describe("checkHost", () => {
+  test("allow and deny patterns", () => {
+    const policy = {
+      allowedHosts: ["api.example.com", "*.trusted.dev"],
+      deniedHosts: ["blocked.trusted.dev"],
+      allowPrivateIps: false,
+    };
+    expect(checkHost(policy, "api.example.com")).toBeNull();
+    expect(checkHost(policy, "a.b.trusted.dev")).toBeNull();
+    expect(checkHost(policy, "trusted.dev")).toEqual("not_allowed");
+    expect(checkHost(policy, "eviltrusted.dev")).toEqual("not_allowed");
+    expect(checkHost(policy, "blocked.trusted.dev")).toEqual("denied");
+  });
+
+  test("private IPs blocked by default", () => {
+    const policy = { allowedHosts: [], deniedHosts: [], allowPrivateIps: false };
+    expect(checkHost(policy, "example.com")).toBeNull();
+    expect(checkHost(policy, "8.8.8.8")).toBeNull();
+    for (const host of [
+      "127.0.0.1",
+      "10.1.2.3",
+      "169.254.169.254",
+      "100.64.0.1",
+      "[::1]",
+      "fd00::1",
+      "::ffff:10.0.0.1",
+    ]) {
+      expect(checkHost(policy, host)).toEqual("private_ip");
+    }
+    expect(
+      checkHost({ ...policy, allowPrivateIps: true }, "127.0.0.1"),
+    ).toBeNull();
+  });
+});
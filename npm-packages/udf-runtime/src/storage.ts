@@ -9,7 +9,10 @@ export const storeBlob = async ({
   options,
 }: {
   blob: Blob;
-  options?: { sha256?: string };
+  options?: {
+    sha256?: string;
+    encryption?: { keyId: string; iv: string; algorithm: string };
+  };
 }) => {
   if (!(blob instanceof Blob)) {
     throw new Error(
@@ -27,6 +30,7 @@ export const storeBlob = async ({
     blob.type,
     blob.size.toString(),
     digestHeader,
+    options?.encryption,
   );
   return storageId;
 };
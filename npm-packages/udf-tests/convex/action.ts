@@ -94,3 +94,33 @@ export const uncatchableDeveloperErrorAction = action({
     }
   },
 });
+
+export const queryStreamBatches = action({
+  args: { count: v.number(), batchSize: v.number() },
+  handler: async ({ runMutation }, { count, batchSize }) => {
+    for (let i = 0; i < count; i++) {
+      await runMutation(api.basic.insertObject, { i });
+    }
+    const query = {
+      source: { type: "FullTableScan", tableName: "objects", order: "asc" },
+      operators: [],
+    };
+    const batchSizes = [];
+    const values = [];
+    let cursor = null;
+    let isDone = false;
+    while (!isDone) {
+      const batch = JSON.parse(
+        await Convex.asyncSyscall(
+          "1.0/actions/queryStreamBatch",
+          JSON.stringify({ query, cursor, batchSize }),
+        ),
+      );
+      batchSizes.push(batch.page.length);
+      values.push(...batch.page.map((doc: any) => doc.i));
+      cursor = batch.continueCursor;
+      isDone = batch.isDone;
+    }
+    return { batchSizes, values };
+  },
+});
@@ -1,4 +1,5 @@
-import { query } from "./_generated/server";
+import { v } from "convex/values";
+import { mutation, query } from "./_generated/server";
 
 export const getName = query(async function ({ auth }) {
   const user = await auth.getUserIdentity();
@@ -28,3 +29,10 @@ export const conditionallyCheckAuth = query(async function (ctx) {
   const user = await ctx.auth.getUserIdentity();
   return user?.tokenIdentifier ?? "No user";
 });
+
+export const promoteAnonymous = mutation({
+  args: { token: v.string(), deviceId: v.string() },
+  handler: async (ctx, args) => {
+    return await ctx.auth.promoteAnonymousIdentity(args);
+  },
+});
@@ -106,3 +106,25 @@ export const simpleMutation = mutation(async () => {
 export const simpleAction = action(async () => {
   return 2;
 });
+
+// A data migration that marks every object as migrated, one page per batch.
+export const migrateObjects = mutation(
+  async (
+    { db },
+    {
+      cursor,
+      batchSize,
+      dryRun,
+    }: { cursor: string | null; batchSize: number; dryRun: boolean },
+  ) => {
+    const { page, continueCursor, isDone } = await db
+      .query("objects")
+      .paginate({ cursor, numItems: batchSize });
+    if (!dryRun) {
+      for (const doc of page) {
+        await db.patch(doc._id, { migrated: true });
+      }
+    }
+    return { cursor: continueCursor, isDone, numProcessed: page.length };
+  },
+);
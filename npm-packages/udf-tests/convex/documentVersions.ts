@@ -0,0 +1,48 @@
+import { Id } from "./_generated/dataModel";
+import { mutation, query } from "./_generated/server";
+
+export const createAccount = mutation(
+  async ({ db }, { balance }: { balance: number }) => {
+    return await db.insert("accounts", { name: "checking", balance });
+  },
+);
+
+export const getVersion = query(
+  async ({ db }, { id }: { id: Id<"accounts"> }) => {
+    return await db.getVersion(id);
+  },
+);
+
+export const setBalance = mutation(
+  async (
+    { db },
+    {
+      id,
+      balance,
+      expectedVersion,
+    }: { id: Id<"accounts">; balance: number; expectedVersion?: string },
+  ) => {
+    await db.patch(id, { balance }, { expectedVersion });
+  },
+);
+
+export const replaceBalance = mutation(
+  async (
+    { db },
+    {
+      id,
+      balance,
+      expectedVersion,
+    }: { id: Id<"accounts">; balance: number; expectedVersion?: string },
+  ) => {
+    await db.replace(id, { name: "checking", balance }, { expectedVersion });
+  },
+);
+
+// A document written earlier in the mutation doesn't have a version yet.
+export const getVersionAfterWrite = mutation(
+  async ({ db }, { id }: { id: Id<"accounts"> }) => {
+    await db.patch(id, { balance: 0 });
+    return await db.getVersion(id);
+  },
+);
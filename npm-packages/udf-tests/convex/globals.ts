@@ -41,6 +41,10 @@ export const getGlobalRandom = query(async () => {
   return globalRand;
 });
 
+export const getRandomValues = query(async () => {
+  return Array.from(crypto.getRandomValues(new Uint8Array(8)));
+});
+
 export const createFinalizationRegistry = query(async () => {
   const registry = new FinalizationRegistry((value) => {
     // This callback will never actually be called.
@@ -29,3 +29,25 @@ export const actionThrowsArgumentNestingError = action(async (ctx) => {
   }
   await ctx.runMutation(api.size_errors.writeToNowhere, { x: nested });
 });
+
+declare const Convex: {
+  syscall: (op: string, jsonArgs: string) => string;
+};
+
+export const transactionMetrics = mutation(async ({ db }) => {
+  const metrics = () =>
+    JSON.parse(Convex.syscall("1.0/transactionMetrics", "{}"));
+  const before = metrics();
+  for (let i = 0; i < 3; i++) {
+    await db.insert("objects", { i });
+  }
+  await db.query("objects").collect();
+  const after = metrics();
+  return {
+    readBefore: before.documentsRead.used,
+    writtenBefore: before.documentsWritten.used,
+    read: after.documentsRead.used,
+    written: after.documentsWritten.used,
+    writesRemaining: after.documentsWritten.remaining,
+  };
+});